@@ -0,0 +1,157 @@
+//! Backtest engine throughput on synthetic 1m candles
+//!
+//! Run with `cargo bench -p ea-okx-backtest`.
+
+use async_trait::async_trait;
+use chrono::{Duration, TimeZone, Utc};
+use criterion::{BatchSize, BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use ea_okx_backtest::{BacktestConfig, BacktestEngine, Candle, MockDataSource, PositionSizing};
+use ea_okx_core::Symbol;
+use ea_okx_core::models::Order;
+use ea_okx_strategy::metrics::PerformanceMetrics;
+use ea_okx_strategy::signal::{Signal, SignalType};
+use ea_okx_strategy::traits::{MarketDataEvent, Strategy, StrategyConfig};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+/// Alternates between long and flat every `period` candles
+struct FlipStrategy {
+    period: u64,
+    seen: u64,
+}
+
+#[async_trait]
+impl Strategy for FlipStrategy {
+    async fn initialize(&mut self, _config: StrategyConfig) -> ea_okx_strategy::Result<()> {
+        Ok(())
+    }
+
+    async fn on_market_data(&mut self, event: MarketDataEvent) -> ea_okx_strategy::Result<()> {
+        if let MarketDataEvent::Candle { .. } = event {
+            self.seen += 1;
+        }
+        Ok(())
+    }
+
+    async fn generate_signal(&self) -> ea_okx_strategy::Result<Signal> {
+        Ok(match self.seen % (2 * self.period) {
+            1 => Signal::buy(1.0),
+            n if n == self.period + 1 => Signal {
+                signal_type: SignalType::CloseLong,
+                ..Signal::hold()
+            },
+            _ => Signal::hold(),
+        })
+    }
+
+    async fn on_order_fill(&mut self, _order: &Order) -> ea_okx_strategy::Result<()> {
+        Ok(())
+    }
+
+    async fn on_order_reject(
+        &mut self,
+        _order: &Order,
+        _reason: &str,
+    ) -> ea_okx_strategy::Result<()> {
+        Ok(())
+    }
+
+    fn get_metrics(&self) -> PerformanceMetrics {
+        PerformanceMetrics::default()
+    }
+
+    fn serialize_state(&self) -> ea_okx_strategy::Result<serde_json::Value> {
+        Ok(serde_json::json!({}))
+    }
+
+    fn deserialize_state(&mut self, _state: serde_json::Value) -> ea_okx_strategy::Result<()> {
+        Ok(())
+    }
+
+    async fn shutdown(&mut self) -> ea_okx_strategy::Result<()> {
+        Ok(())
+    }
+}
+
+/// Random-walk 1m candles
+fn candles(symbol: &Symbol, count: i64) -> Vec<Candle> {
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let mut price = dec!(40000);
+    (0..count)
+        .map(|i| {
+            let step = Decimal::from((i * 7919) % 41 - 20);
+            let open = price;
+            price += step;
+            Candle {
+                symbol: symbol.clone(),
+                timestamp: start + Duration::minutes(i),
+                open,
+                high: open.max(price) + dec!(5),
+                low: open.min(price) - dec!(5),
+                close: price,
+                volume: dec!(10),
+            }
+        })
+        .collect()
+}
+
+fn bench_engine(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let symbols = [
+        Symbol::new("BTC-USDT").unwrap(),
+        Symbol::new("ETH-USDT").unwrap(),
+    ];
+
+    let mut group = c.benchmark_group("backtest_engine");
+    group.sample_size(10);
+
+    for days in [7i64, 30] {
+        let per_symbol = days * 24 * 60;
+        let data: Vec<_> = symbols.iter().map(|s| candles(s, per_symbol)).collect();
+        group.throughput(Throughput::Elements(
+            (per_symbol as u64) * symbols.len() as u64,
+        ));
+
+        let config = BacktestConfig {
+            start_time: data[0][0].timestamp,
+            end_time: data[0][data[0].len() - 1].timestamp,
+            symbols: symbols.to_vec(),
+            position_sizing: PositionSizing::Fixed(dec!(1000)),
+            ..Default::default()
+        };
+
+        group.bench_with_input(BenchmarkId::new("1m_candles", days), &data, |b, data| {
+            b.iter_batched(
+                || {
+                    let mut source = MockDataSource::new();
+                    for (symbol, candles) in symbols.iter().zip(data) {
+                        source.add_candles(symbol.clone(), candles.clone());
+                    }
+                    source
+                },
+                |source| {
+                    runtime.block_on(async {
+                        let strategy = FlipStrategy {
+                            period: 240,
+                            seen: 0,
+                        };
+                        let mut engine = BacktestEngine::new(
+                            config.clone(),
+                            Box::new(strategy),
+                            Box::new(source),
+                        )
+                        .await
+                        .unwrap();
+                        engine.run().await.unwrap()
+                    })
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_engine);
+criterion_main!(benches);
@@ -0,0 +1,216 @@
+//! Funding Rate Carry Strategy
+//!
+//! Demonstrates a multi-instrument strategy driven by perpetual funding rates:
+//! - Go long spot and short the perpetual when annualized funding exceeds a threshold
+//! - Unwind both legs when funding falls back below the exit threshold
+//!
+//! The backtester consumes funding history alongside spot candles and credits
+//! funding on the short perpetual hedge at every settlement.
+//!
+//! **Usage:**
+//! ```bash
+//! cargo run -p ea-okx-backtest --example funding_carry
+//! ```
+
+use async_trait::async_trait;
+use chrono::{Duration, TimeZone, Utc};
+use ea_okx_backtest::{
+    BacktestConfig, BacktestEngine, Candle, CostModel, FundingRate, GapPolicy, IntrabarPath,
+    LimitFillModel, MarginConfig, MockDataSource, PositionSizing,
+};
+use ea_okx_core::Interval;
+use ea_okx_core::models::{Order, OrderSide};
+use ea_okx_core::types::Symbol;
+use ea_okx_strategy::error::Result;
+use ea_okx_strategy::metrics::PerformanceMetrics;
+use ea_okx_strategy::signal::{Signal, SignalType};
+use ea_okx_strategy::traits::{MarketDataEvent, Strategy, StrategyConfig};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+/// Funding settlements per year (OKX settles every 8 hours)
+const FUNDING_PERIODS_PER_YEAR: i64 = 3 * 365;
+
+/// Long spot / short perpetual carry strategy
+pub struct FundingCarryStrategy {
+    symbol: Symbol,
+
+    /// Annualized funding required to open the carry
+    entry_threshold: Decimal,
+
+    /// Annualized funding below which the carry is closed
+    exit_threshold: Decimal,
+
+    last_annualized: Option<Decimal>,
+    in_position: bool,
+    metrics: PerformanceMetrics,
+}
+
+impl FundingCarryStrategy {
+    pub fn new(symbol: Symbol, entry_threshold: Decimal, exit_threshold: Decimal) -> Self {
+        Self {
+            symbol,
+            entry_threshold,
+            exit_threshold,
+            last_annualized: None,
+            in_position: false,
+            metrics: PerformanceMetrics::default(),
+        }
+    }
+}
+
+#[async_trait]
+impl Strategy for FundingCarryStrategy {
+    async fn initialize(&mut self, _config: StrategyConfig) -> Result<()> {
+        Ok(())
+    }
+
+    async fn on_market_data(&mut self, event: MarketDataEvent) -> Result<()> {
+        if let MarketDataEvent::FundingRate {
+            symbol,
+            funding_rate,
+            ..
+        } = event
+            && symbol == self.symbol
+        {
+            self.last_annualized = Some(funding_rate * Decimal::from(FUNDING_PERIODS_PER_YEAR));
+        }
+        Ok(())
+    }
+
+    async fn generate_signal(&self) -> Result<Signal> {
+        let Some(annualized) = self.last_annualized else {
+            return Ok(Signal::hold());
+        };
+
+        if !self.in_position && annualized >= self.entry_threshold {
+            let mut signal = Signal::buy(1.0);
+            signal.metadata = serde_json::json!({
+                "symbol": self.symbol.as_str(),
+                "hedge": "perp",
+                "annualized_funding": annualized.to_string(),
+            });
+            return Ok(signal);
+        }
+
+        if self.in_position && annualized < self.exit_threshold {
+            let mut signal = Signal::hold();
+            signal.signal_type = SignalType::CloseLong;
+            signal.metadata = serde_json::json!({ "symbol": self.symbol.as_str() });
+            return Ok(signal);
+        }
+
+        Ok(Signal::hold())
+    }
+
+    async fn on_order_fill(&mut self, order: &Order) -> Result<()> {
+        self.in_position = order.side == OrderSide::Buy;
+        self.metrics.total_trades += 1;
+        Ok(())
+    }
+
+    async fn on_order_reject(&mut self, _order: &Order, _reason: &str) -> Result<()> {
+        Ok(())
+    }
+
+    fn get_metrics(&self) -> PerformanceMetrics {
+        self.metrics.clone()
+    }
+
+    fn serialize_state(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::json!({ "in_position": self.in_position }))
+    }
+
+    fn deserialize_state(&mut self, state: serde_json::Value) -> Result<()> {
+        self.in_position = state["in_position"].as_bool().unwrap_or(false);
+        Ok(())
+    }
+
+    async fn shutdown(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Synthetic hourly spot candles and 8-hourly funding with a high/low/high regime
+fn synthetic_data(symbol: &Symbol, days: i64) -> (Vec<Candle>, Vec<FundingRate>) {
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let mut candles = Vec::new();
+    let mut funding = Vec::new();
+
+    for hour in 0..days * 24 {
+        let timestamp = start + Duration::hours(hour);
+        let wave = Decimal::from((hour % 48) - 24) * dec!(10);
+        let close = dec!(40000) + wave;
+
+        candles.push(Candle {
+            symbol: symbol.clone(),
+            timestamp,
+            open: close - dec!(5),
+            high: close + dec!(20),
+            low: close - dec!(20),
+            close,
+            volume: dec!(100),
+        });
+
+        if hour % 8 == 0 {
+            let day = hour / 24;
+            let rate = if day < days / 3 || day >= 2 * days / 3 {
+                dec!(0.0003) // ~33% annualized
+            } else {
+                dec!(0.00002) // ~2% annualized
+            };
+            funding.push(FundingRate {
+                symbol: symbol.clone(),
+                timestamp,
+                rate,
+            });
+        }
+    }
+
+    (candles, funding)
+}
+
+#[tokio::main]
+async fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt().with_env_filter("warn").init();
+
+    let symbol = Symbol::new("BTC-USDT")?;
+    let days = 30;
+    let (candles, funding) = synthetic_data(&symbol, days);
+
+    let start_time = candles
+        .first()
+        .map(|c| c.timestamp)
+        .unwrap_or_else(Utc::now);
+    let end_time = candles.last().map(|c| c.timestamp).unwrap_or_else(Utc::now);
+
+    let mut data = MockDataSource::new();
+    data.add_candles(symbol.clone(), candles);
+    data.add_funding_rates(symbol.clone(), funding);
+
+    let config = BacktestConfig {
+        initial_capital: dec!(100000),
+        start_time,
+        end_time,
+        symbols: vec![symbol.clone()],
+        interval: Interval::OneHour,
+        higher_timeframes: Vec::new(),
+        cost_model: CostModel::okx_spot_conservative(),
+        verbose: false,
+        max_positions: 1,
+        position_sizing: PositionSizing::PercentOfEquity(dec!(0.5)),
+        intrabar_path: IntrabarPath::default(),
+        margin: MarginConfig::default(),
+        limit_fill: LimitFillModel::default(),
+        gap_policy: GapPolicy::default(),
+        streaming: None,
+        maintenance: Vec::new(),
+    };
+
+    let strategy = FundingCarryStrategy::new(symbol, dec!(0.10), dec!(0.03));
+    let mut engine = BacktestEngine::new(config, Box::new(strategy), Box::new(data)).await?;
+    let result = engine.run().await?;
+
+    println!("{}", result.summary());
+    Ok(())
+}
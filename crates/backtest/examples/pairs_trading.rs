@@ -0,0 +1,353 @@
+//! Pairs Trading Strategy
+//!
+//! Demonstrates a two-symbol statistical arbitrage strategy:
+//! - Fit a rolling hedge ratio between the two closes by least squares
+//! - Trade the spread only while a Dickey-Fuller check finds it mean reverting
+//! - Short the spread (sell Y, buy X) when its z-score is stretched high and
+//!   buy it when stretched low, sizing the X leg by the hedge ratio
+//! - Close both legs together when the spread reverts or blows through a stop
+//!
+//! Both legs are emitted as one batch from `generate_signals`, so the
+//! backtester opens and closes them on the same bar.
+//!
+//! **Usage:**
+//! ```bash
+//! cargo run -p ea-okx-backtest --example pairs_trading
+//! ```
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use ea_okx_backtest::{
+    BacktestConfig, BacktestEngine, Candle, CostModel, GapPolicy, IntrabarPath, LimitFillModel,
+    MarginConfig, MockDataSource, PositionSizing,
+};
+use ea_okx_core::Interval;
+use ea_okx_core::models::Order;
+use ea_okx_core::types::{Quantity, Symbol};
+use ea_okx_strategy::error::Result;
+use ea_okx_strategy::metrics::PerformanceMetrics;
+use ea_okx_strategy::signal::{Signal, SignalType};
+use ea_okx_strategy::stats::{self, OlsFit, Stationarity};
+use ea_okx_strategy::traits::{MarketDataEvent, Strategy, StrategyConfig};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal_macros::dec;
+use std::collections::VecDeque;
+
+/// Which way the pair is held
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SpreadPosition {
+    Flat,
+    /// Long Y, short X
+    Long,
+    /// Short Y, long X
+    Short,
+}
+
+/// Trades `y - beta * x` back to its mean
+pub struct PairsTradingStrategy {
+    y: Symbol,
+    x: Symbol,
+
+    /// Paired closes used for the hedge ratio and z-score
+    lookback: usize,
+
+    /// |z| at which a position is opened
+    entry_z: f64,
+
+    /// |z| at which an open position is closed as reverted
+    exit_z: f64,
+
+    /// |z| at which an open position is closed as broken
+    stop_z: f64,
+
+    /// Quote currency committed to the Y leg of each trade
+    leg_notional: f64,
+
+    /// Latest close of each leg and when it was seen
+    last_y: Option<(DateTime<Utc>, f64)>,
+    last_x: Option<(DateTime<Utc>, f64)>,
+
+    /// Closes of both legs at the same timestamps, oldest first
+    history: VecDeque<(f64, f64)>,
+
+    position: SpreadPosition,
+
+    /// Leg signals for the latest event
+    pending: Vec<Signal>,
+    metrics: PerformanceMetrics,
+}
+
+impl PairsTradingStrategy {
+    pub fn new(y: Symbol, x: Symbol, lookback: usize) -> Self {
+        Self {
+            y,
+            x,
+            lookback,
+            entry_z: 2.0,
+            exit_z: 0.5,
+            stop_z: 4.0,
+            leg_notional: 20_000.0,
+            last_y: None,
+            last_x: None,
+            history: VecDeque::new(),
+            position: SpreadPosition::Flat,
+            pending: Vec::new(),
+            metrics: PerformanceMetrics::default(),
+        }
+    }
+
+    /// Entry, exit and stop z-scores
+    pub fn with_thresholds(mut self, entry_z: f64, exit_z: f64, stop_z: f64) -> Self {
+        self.entry_z = entry_z;
+        self.exit_z = exit_z;
+        self.stop_z = stop_z;
+        self
+    }
+
+    pub fn with_leg_notional(mut self, leg_notional: f64) -> Self {
+        self.leg_notional = leg_notional;
+        self
+    }
+
+    /// Hedge ratio, spread z-score and stationarity over the lookback
+    fn evaluate(&self) -> Option<(OlsFit, f64, Stationarity)> {
+        let (ys, xs): (Vec<f64>, Vec<f64>) = self.history.iter().copied().unzip();
+        let fit = stats::ols(&ys, &xs)?;
+        let spread = stats::spread(&ys, &xs, &fit);
+        Some((fit, stats::zscore(&spread)?, stats::stationarity(&spread)?))
+    }
+
+    fn leg(&self, signal_type: SignalType, symbol: &Symbol, quantity: Option<f64>) -> Signal {
+        Signal {
+            signal_type,
+            suggested_quantity: quantity
+                .and_then(|q| Decimal::from_f64(q).map(|q| q.round_dp(4)))
+                .and_then(|q| Quantity::new(q).ok()),
+            metadata: serde_json::json!({ "symbol": symbol.as_str() }),
+            ..Signal::hold()
+        }
+    }
+
+    /// Both legs of an entry, the X leg sized by the hedge ratio
+    fn open(&mut self, position: SpreadPosition, beta: f64, y_close: f64) {
+        let y_quantity = self.leg_notional / y_close;
+        let (y_side, x_side) = match position {
+            SpreadPosition::Long => (SignalType::Buy, SignalType::Sell),
+            SpreadPosition::Short => (SignalType::Sell, SignalType::Buy),
+            SpreadPosition::Flat => return,
+        };
+        self.pending = vec![
+            self.leg(y_side, &self.y, Some(y_quantity)),
+            self.leg(x_side, &self.x, Some(beta * y_quantity)),
+        ];
+        self.position = position;
+    }
+
+    /// Both legs of an exit, each closing the whole leg
+    fn close(&mut self) {
+        let (y_close, x_close) = match self.position {
+            SpreadPosition::Long => (SignalType::CloseLong, SignalType::CloseShort),
+            SpreadPosition::Short => (SignalType::CloseShort, SignalType::CloseLong),
+            SpreadPosition::Flat => return,
+        };
+        self.pending = vec![
+            self.leg(y_close, &self.y, None),
+            self.leg(x_close, &self.x, None),
+        ];
+        self.position = SpreadPosition::Flat;
+    }
+}
+
+#[async_trait]
+impl Strategy for PairsTradingStrategy {
+    async fn initialize(&mut self, _config: StrategyConfig) -> Result<()> {
+        Ok(())
+    }
+
+    async fn on_market_data(&mut self, event: MarketDataEvent) -> Result<()> {
+        self.pending.clear();
+        let MarketDataEvent::Candle {
+            symbol,
+            close,
+            timestamp,
+            ..
+        } = event
+        else {
+            return Ok(());
+        };
+        let close = close.to_f64().unwrap_or_default();
+        if symbol == self.y {
+            self.last_y = Some((timestamp, close));
+        } else if symbol == self.x {
+            self.last_x = Some((timestamp, close));
+        } else {
+            return Ok(());
+        }
+
+        // Decide once per bar, when the second leg's close arrives
+        let (Some((y_time, y_close)), Some((x_time, x_close))) = (self.last_y, self.last_x) else {
+            return Ok(());
+        };
+        if y_time != x_time || y_time != timestamp {
+            return Ok(());
+        }
+        self.history.push_back((y_close, x_close));
+        if self.history.len() > self.lookback {
+            self.history.pop_front();
+        }
+        if self.history.len() < self.lookback {
+            return Ok(());
+        }
+
+        let Some((fit, z, stationarity)) = self.evaluate() else {
+            return Ok(());
+        };
+        match self.position {
+            SpreadPosition::Flat if stationarity.stationary && fit.beta > 0.0 => {
+                if z >= self.entry_z {
+                    self.open(SpreadPosition::Short, fit.beta, y_close);
+                } else if z <= -self.entry_z {
+                    self.open(SpreadPosition::Long, fit.beta, y_close);
+                }
+            }
+            SpreadPosition::Flat => {}
+            _ if z.abs() <= self.exit_z || z.abs() >= self.stop_z => self.close(),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn generate_signal(&self) -> Result<Signal> {
+        Ok(self.pending.first().cloned().unwrap_or_else(Signal::hold))
+    }
+
+    async fn generate_signals(&self) -> Result<Vec<Signal>> {
+        Ok(self.pending.clone())
+    }
+
+    async fn on_order_fill(&mut self, _order: &Order) -> Result<()> {
+        self.metrics.total_trades += 1;
+        Ok(())
+    }
+
+    async fn on_order_reject(&mut self, _order: &Order, _reason: &str) -> Result<()> {
+        // One leg alone is not a hedge: unwind whatever did fill
+        self.close();
+        Ok(())
+    }
+
+    fn get_metrics(&self) -> PerformanceMetrics {
+        self.metrics.clone()
+    }
+
+    fn serialize_state(&self) -> Result<serde_json::Value> {
+        let position = match self.position {
+            SpreadPosition::Flat => "flat",
+            SpreadPosition::Long => "long",
+            SpreadPosition::Short => "short",
+        };
+        Ok(serde_json::json!({ "position": position }))
+    }
+
+    fn deserialize_state(&mut self, state: serde_json::Value) -> Result<()> {
+        self.position = match state["position"].as_str() {
+            Some("long") => SpreadPosition::Long,
+            Some("short") => SpreadPosition::Short,
+            _ => SpreadPosition::Flat,
+        };
+        Ok(())
+    }
+
+    async fn shutdown(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Deterministic noise in [-0.5, 0.5)
+fn noise(state: &mut u64) -> f64 {
+    *state = state
+        .wrapping_mul(6364136223846793005)
+        .wrapping_add(1442695040888963407);
+    (*state >> 11) as f64 / (1u64 << 53) as f64 - 0.5
+}
+
+/// Synthetic hourly closes: X wanders, Y tracks 1.5 X plus a mean-reverting gap
+fn synthetic_data(y: &Symbol, x: &Symbol, hours: i64) -> (Vec<Candle>, Vec<Candle>) {
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let candle = |symbol: &Symbol, hour: i64, close: f64| {
+        let close = Decimal::from_f64(close).unwrap_or_default().round_dp(2);
+        Candle {
+            symbol: symbol.clone(),
+            timestamp: start + Duration::hours(hour),
+            open: close,
+            high: close * dec!(1.001),
+            low: close * dec!(0.999),
+            close,
+            volume: dec!(1000),
+        }
+    };
+
+    let mut state = 42;
+    let (mut x_close, mut gap) = (100.0, 0.0);
+    let (mut y_candles, mut x_candles) = (Vec::new(), Vec::new());
+    for hour in 0..hours {
+        x_close += noise(&mut state);
+        gap = gap * 0.9 + noise(&mut state) * 2.0;
+        x_candles.push(candle(x, hour, x_close));
+        y_candles.push(candle(y, hour, 10.0 + 1.5 * x_close + gap));
+    }
+    (y_candles, x_candles)
+}
+
+#[tokio::main]
+async fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt().with_env_filter("warn").init();
+
+    let y = Symbol::new("ETH-USDT")?;
+    let x = Symbol::new("LTC-USDT")?;
+    let (y_candles, x_candles) = synthetic_data(&y, &x, 30 * 24);
+
+    let start_time = x_candles
+        .first()
+        .map(|c| c.timestamp)
+        .unwrap_or_else(Utc::now);
+    let end_time = x_candles
+        .last()
+        .map(|c| c.timestamp)
+        .unwrap_or_else(Utc::now);
+
+    let mut data = MockDataSource::new();
+    data.add_candles(y.clone(), y_candles);
+    data.add_candles(x.clone(), x_candles);
+
+    let config = BacktestConfig {
+        initial_capital: dec!(100000),
+        start_time,
+        end_time,
+        symbols: vec![y.clone(), x.clone()],
+        interval: Interval::OneHour,
+        higher_timeframes: Vec::new(),
+        cost_model: CostModel::okx_spot_conservative(),
+        verbose: false,
+        max_positions: 2,
+        // Only used for signals without a suggested quantity
+        position_sizing: PositionSizing::PercentOfEquity(dec!(0.2)),
+        intrabar_path: IntrabarPath::default(),
+        margin: MarginConfig {
+            allow_short: true,
+            ..Default::default()
+        },
+        limit_fill: LimitFillModel::default(),
+        gap_policy: GapPolicy::default(),
+        streaming: None,
+        maintenance: Vec::new(),
+    };
+
+    let strategy = PairsTradingStrategy::new(y, x, 96);
+    let mut engine = BacktestEngine::new(config, Box::new(strategy), Box::new(data)).await?;
+    let result = engine.run().await?;
+
+    println!("{}", result.summary());
+    Ok(())
+}
@@ -105,7 +105,7 @@ impl SlippageModel {
     /// Calculate slippage for a market order
     pub fn calculate_market(
         &self,
-        side: OrderSide,
+        _side: OrderSide,
         price: Decimal,
         quantity: Decimal,
         avg_volume: Decimal,
@@ -150,21 +150,12 @@ impl SlippageModel {
 }
 
 /// Combined cost model including commission and slippage
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CostModel {
     pub commission: CommissionModel,
     pub slippage: SlippageModel,
 }
 
-impl Default for CostModel {
-    fn default() -> Self {
-        Self {
-            commission: CommissionModel::default(),
-            slippage: SlippageModel::default(),
-        }
-    }
-}
-
 impl CostModel {
     pub fn okx_spot_conservative() -> Self {
         Self {
@@ -180,6 +171,23 @@ impl CostModel {
         }
     }
 
+    /// The same model with commission and slippage scaled by the given
+    /// multipliers, minimums included
+    pub fn scaled(&self, commission: Decimal, slippage: Decimal) -> Self {
+        Self {
+            commission: CommissionModel {
+                maker_rate: self.commission.maker_rate * commission,
+                taker_rate: self.commission.taker_rate * commission,
+                min_commission: self.commission.min_commission * commission,
+            },
+            slippage: SlippageModel {
+                fixed_bps: self.slippage.fixed_bps * slippage,
+                impact_coefficient: self.slippage.impact_coefficient * slippage,
+                min_slippage: self.slippage.min_slippage * slippage,
+            },
+        }
+    }
+
     /// Calculate total cost for a trade
     pub fn calculate_total_cost(
         &self,
@@ -0,0 +1,250 @@
+//! Chart-friendly equity and drawdown curves
+//!
+//! A long backtest records one curve point per fill and per mark, which can
+//! run into millions of points. [`CurveQuery`] reduces a curve for display:
+//! resampling to one point per hour or day (the last value in each bucket),
+//! Douglas-Peucker simplification that drops points within a tolerance of
+//! the line through their neighbours, and offset/limit pagination.
+
+use chrono::{DateTime, DurationRound, TimeDelta, Utc};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+
+/// Point on an equity or drawdown curve
+pub type CurvePoint = (DateTime<Utc>, Decimal);
+
+/// Bucket size for resampling
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CurveResolution {
+    /// Every recorded point
+    #[default]
+    Raw,
+    Minute,
+    Hour,
+    Day,
+}
+
+impl CurveResolution {
+    fn bucket(&self) -> Option<TimeDelta> {
+        match self {
+            CurveResolution::Raw => None,
+            CurveResolution::Minute => Some(TimeDelta::minutes(1)),
+            CurveResolution::Hour => Some(TimeDelta::hours(1)),
+            CurveResolution::Day => Some(TimeDelta::days(1)),
+        }
+    }
+}
+
+/// How to reduce and page a curve
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CurveQuery {
+    #[serde(default)]
+    pub resolution: CurveResolution,
+
+    /// Drop points closer than this to the simplified line, in curve units
+    #[serde(default)]
+    pub simplify_tolerance: Option<Decimal>,
+
+    /// Points to skip after resampling and simplification
+    #[serde(default)]
+    pub offset: usize,
+
+    /// Maximum points returned
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+impl CurveQuery {
+    pub fn resolution(resolution: CurveResolution) -> Self {
+        Self {
+            resolution,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_simplify_tolerance(mut self, tolerance: Decimal) -> Self {
+        self.simplify_tolerance = Some(tolerance);
+        self
+    }
+
+    pub fn with_page(mut self, offset: usize, limit: usize) -> Self {
+        self.offset = offset;
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Resample, simplify and page `points`
+    pub fn apply(&self, points: &[CurvePoint]) -> CurvePage {
+        let mut reduced = resample(points, self.resolution);
+        if let Some(tolerance) = self.simplify_tolerance {
+            reduced = simplify(&reduced, tolerance);
+        }
+
+        let total = reduced.len();
+        let offset = self.offset.min(total);
+        let end = self
+            .limit
+            .map_or(total, |limit| offset.saturating_add(limit).min(total));
+        CurvePage {
+            points: reduced[offset..end].to_vec(),
+            offset,
+            total,
+            has_more: end < total,
+        }
+    }
+}
+
+/// One page of a reduced curve
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CurvePage {
+    pub points: Vec<CurvePoint>,
+    pub offset: usize,
+
+    /// Points in the reduced curve across all pages
+    pub total: usize,
+    pub has_more: bool,
+}
+
+/// Last point of each `resolution` bucket, stamped with the bucket start
+///
+/// `points` must be in time order.
+pub fn resample(points: &[CurvePoint], resolution: CurveResolution) -> Vec<CurvePoint> {
+    let Some(bucket) = resolution.bucket() else {
+        return points.to_vec();
+    };
+
+    let mut resampled: Vec<CurvePoint> = Vec::new();
+    for (timestamp, value) in points {
+        let start = timestamp.duration_trunc(bucket).unwrap_or(*timestamp);
+        match resampled.last_mut() {
+            Some(last) if last.0 == start => last.1 = *value,
+            _ => resampled.push((start, *value)),
+        }
+    }
+    resampled
+}
+
+/// Douglas-Peucker simplification keeping the first and last points
+///
+/// Distance is measured vertically, in curve units, from the straight line
+/// between the kept neighbours.
+pub fn simplify(points: &[CurvePoint], tolerance: Decimal) -> Vec<CurvePoint> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+
+    let x = |i: usize| points[i].0.timestamp_millis() as f64;
+    let y = |i: usize| points[i].1.to_f64().unwrap_or(0.0);
+    let tolerance = tolerance.to_f64().unwrap_or(0.0);
+
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+
+    let mut stack = vec![(0, points.len() - 1)];
+    while let Some((first, last)) = stack.pop() {
+        if last <= first + 1 {
+            continue;
+        }
+        let (x0, y0, x1, y1) = (x(first), y(first), x(last), y(last));
+        let slope = if x1 > x0 { (y1 - y0) / (x1 - x0) } else { 0.0 };
+
+        let (farthest, distance) = (first + 1..last)
+            .map(|i| (i, (y(i) - (y0 + slope * (x(i) - x0))).abs()))
+            .fold((first, -1.0), |best, candidate| {
+                if candidate.1 > best.1 {
+                    candidate
+                } else {
+                    best
+                }
+            });
+        if distance > tolerance {
+            keep[farthest] = true;
+            stack.push((first, farthest));
+            stack.push((farthest, last));
+        }
+    }
+
+    points
+        .iter()
+        .zip(keep)
+        .filter_map(|(point, kept)| kept.then_some(*point))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+
+    fn at(hour: i64, minute: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+            + TimeDelta::hours(hour)
+            + TimeDelta::minutes(minute)
+    }
+
+    #[test]
+    fn test_resample_keeps_last_value_per_bucket() {
+        let points = vec![
+            (at(0, 5), dec!(100)),
+            (at(0, 40), dec!(101)),
+            (at(1, 10), dec!(99)),
+            (at(3, 0), dec!(105)),
+        ];
+        assert_eq!(
+            resample(&points, CurveResolution::Hour),
+            vec![
+                (at(0, 0), dec!(101)),
+                (at(1, 0), dec!(99)),
+                (at(3, 0), dec!(105))
+            ]
+        );
+        assert_eq!(
+            resample(&points, CurveResolution::Day),
+            vec![(at(0, 0), dec!(105))]
+        );
+        assert_eq!(resample(&points, CurveResolution::Raw), points);
+    }
+
+    #[test]
+    fn test_simplify_drops_points_on_a_line_but_keeps_turns() {
+        // Straight rise to a peak at 02:00, then a straight fall
+        let points: Vec<CurvePoint> = (0..5)
+            .map(|m| (at(0, m * 30), Decimal::from(100 + m * 10)))
+            .chain((1..5).map(|m| (at(2, m * 30), Decimal::from(140 - m * 10))))
+            .collect();
+
+        let simplified = simplify(&points, dec!(0.5));
+        assert_eq!(
+            simplified,
+            vec![
+                (at(0, 0), dec!(100)),
+                (at(2, 0), dec!(140)),
+                (at(4, 0), dec!(100))
+            ]
+        );
+        assert_eq!(simplify(&points, dec!(-1)), points);
+    }
+
+    #[test]
+    fn test_query_pages_reduced_curve() {
+        let points: Vec<CurvePoint> = (0..24)
+            .flat_map(|h| [(at(h, 0), Decimal::from(h)), (at(h, 30), Decimal::from(h))])
+            .collect();
+        let query = CurveQuery::resolution(CurveResolution::Hour).with_page(20, 10);
+
+        let page = query.apply(&points);
+        assert_eq!(page.total, 24);
+        assert_eq!(page.offset, 20);
+        assert_eq!(page.points.len(), 4);
+        assert!(!page.has_more);
+        assert_eq!(page.points[0], (at(20, 0), dec!(20)));
+
+        let first = CurveQuery::default().with_page(0, 10).apply(&points);
+        assert_eq!(first.total, 48);
+        assert!(first.has_more);
+    }
+}
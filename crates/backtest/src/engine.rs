@@ -1,11 +1,21 @@
 use crate::cost_model::CostModel;
 use crate::error::{Error, Result};
 use crate::events::{ExecutionEvent, Fill, MarketEvent, Trade};
-use crate::portfolio::Portfolio;
+use crate::fills::{BarRange, LimitFillModel, LimitFillTracker};
+use crate::gaps::{GapPolicy, GapTracker, find_gaps};
+use crate::intrabar::{ExitLevels, ExitTrigger, IntrabarPath};
+use crate::lookahead::LookAheadGuard;
+use crate::maintenance::{MaintenanceTracker, MaintenanceWindow, RestingOrders};
+use crate::observers::{BacktestObserver, ObserverContext};
+use crate::portfolio::{MarginConfig, Portfolio};
 use crate::results::BacktestResult;
-use chrono::{DateTime, Utc};
+use crate::series::{EventRef, Timeline};
+use crate::stream::{CandleChunks, CandleMerge, StreamingConfig};
+use chrono::{DateTime, NaiveDate, Utc};
+use ea_okx_core::math::{safe_div, safe_mul};
 use ea_okx_core::models::{Order, OrderSide, OrderType, PositionSide};
-use ea_okx_core::{Price, Quantity, Symbol};
+use ea_okx_core::{Interval, Price, Quantity, Symbol};
+use sha2::{Digest, Sha256};
 
 // Candle structure for backtesting (duplicated from ea_okx_data to avoid sqlx dependency)
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -18,43 +28,182 @@ pub struct Candle {
     pub close: Decimal,
     pub volume: Decimal,
 }
+/// Funding rate settlement for backtesting (keyed by the underlying spot pair)
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct FundingRate {
+    pub symbol: Symbol,
+    pub timestamp: DateTime<Utc>,
+    pub rate: Decimal,
+}
+
+/// Positioning statistic for the base currency of `symbol`, stamped with the
+/// time it became known
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PositioningSample {
+    pub symbol: Symbol,
+    pub timestamp: DateTime<Utc>,
+    pub value: PositioningValue,
+}
+
+/// Value of a [`PositioningSample`]
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PositioningValue {
+    OpenInterest {
+        open_interest: Decimal,
+        volume: Decimal,
+    },
+    TakerVolume {
+        buy_volume: Decimal,
+        sell_volume: Decimal,
+    },
+    LongShortRatio {
+        ratio: Decimal,
+    },
+}
+
+impl PositioningSample {
+    /// Event handed to the strategy
+    pub fn into_market_data(self) -> MarketDataEvent {
+        let (symbol, timestamp) = (self.symbol, self.timestamp);
+        match self.value {
+            PositioningValue::OpenInterest {
+                open_interest,
+                volume,
+            } => MarketDataEvent::OpenInterest {
+                symbol,
+                open_interest,
+                volume,
+                timestamp,
+            },
+            PositioningValue::TakerVolume {
+                buy_volume,
+                sell_volume,
+            } => MarketDataEvent::TakerVolume {
+                symbol,
+                buy_volume,
+                sell_volume,
+                timestamp,
+            },
+            PositioningValue::LongShortRatio { ratio } => MarketDataEvent::LongShortRatio {
+                symbol,
+                ratio,
+                timestamp,
+            },
+        }
+    }
+}
 // use ea_okx_data::storage::TimescaleStorage;  // Disabled due to sqlx compile-time requirements
 use async_trait::async_trait;
 use ea_okx_strategy::signal::{Signal, SignalType};
-use ea_okx_strategy::traits::{RiskLimits, Strategy, StrategyConfig};
+use ea_okx_strategy::traits::{MarketDataEvent, RiskLimits, Strategy, StrategyConfig};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet};
 use tracing::{debug, info, warn};
 use uuid::Uuid;
 
-/// Mock storage interface for backtesting (until TimescaleDB is configured)
+/// Source of historical market data for backtests
 #[async_trait]
 pub trait HistoricalDataSource: Send + Sync {
     async fn query_candles(
         &self,
         symbol: &Symbol,
-        interval: &str,
+        interval: Interval,
         start: DateTime<Utc>,
         end: DateTime<Utc>,
     ) -> Result<Vec<Candle>>;
+
+    /// Funding rate history for the perpetual on `symbol` (none by default)
+    async fn query_funding_rates(
+        &self,
+        _symbol: &Symbol,
+        _start: DateTime<Utc>,
+        _end: DateTime<Utc>,
+    ) -> Result<Vec<FundingRate>> {
+        Ok(Vec::new())
+    }
+
+    /// Open interest, taker volume and long/short ratio history for the base
+    /// currency of `symbol` (none by default)
+    async fn query_positioning(
+        &self,
+        _symbol: &Symbol,
+        _start: DateTime<Utc>,
+        _end: DateTime<Utc>,
+    ) -> Result<Vec<PositioningSample>> {
+        Ok(Vec::new())
+    }
+
+    /// Candles for `symbol` delivered in chunks as the run consumes them
+    ///
+    /// The default loads everything through [`query_candles`] and hands it
+    /// out in `streaming.chunk_size` pieces; sources that can read
+    /// incrementally should override it.
+    ///
+    /// [`query_candles`]: HistoricalDataSource::query_candles
+    async fn stream_candles(
+        &self,
+        symbol: &Symbol,
+        interval: Interval,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        streaming: &StreamingConfig,
+    ) -> Result<CandleChunks> {
+        let candles = self.query_candles(symbol, interval, start, end).await?;
+        Ok(CandleChunks::from_candles(candles, streaming.chunk_size))
+    }
 }
 
 /// In-memory mock storage for testing
 pub struct MockDataSource {
     candles: HashMap<String, Vec<Candle>>,
+    interval_candles: HashMap<(String, Interval), Vec<Candle>>,
+    funding_rates: HashMap<String, Vec<FundingRate>>,
+    positioning: HashMap<String, Vec<PositioningSample>>,
 }
 
 impl MockDataSource {
     pub fn new() -> Self {
         Self {
             candles: HashMap::new(),
+            interval_candles: HashMap::new(),
+            funding_rates: HashMap::new(),
+            positioning: HashMap::new(),
         }
     }
 
+    /// Candles served for `symbol` at any interval without its own data
     pub fn add_candles(&mut self, symbol: Symbol, candles: Vec<Candle>) {
         self.candles.insert(symbol.as_str().to_string(), candles);
     }
+
+    /// Candles served for `symbol` at `interval` only
+    pub fn add_interval_candles(
+        &mut self,
+        symbol: Symbol,
+        interval: Interval,
+        candles: Vec<Candle>,
+    ) {
+        self.interval_candles
+            .insert((symbol.as_str().to_string(), interval), candles);
+    }
+
+    pub fn add_funding_rates(&mut self, symbol: Symbol, rates: Vec<FundingRate>) {
+        self.funding_rates
+            .insert(symbol.as_str().to_string(), rates);
+    }
+
+    pub fn add_positioning(&mut self, symbol: Symbol, samples: Vec<PositioningSample>) {
+        self.positioning
+            .insert(symbol.as_str().to_string(), samples);
+    }
+}
+
+impl Default for MockDataSource {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[async_trait]
@@ -62,12 +211,40 @@ impl HistoricalDataSource for MockDataSource {
     async fn query_candles(
         &self,
         symbol: &Symbol,
-        _interval: &str,
+        interval: Interval,
         _start: DateTime<Utc>,
         _end: DateTime<Utc>,
     ) -> Result<Vec<Candle>> {
+        let key = (symbol.as_str().to_string(), interval);
+        Ok(self
+            .interval_candles
+            .get(&key)
+            .or_else(|| self.candles.get(symbol.as_str()))
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    async fn query_funding_rates(
+        &self,
+        symbol: &Symbol,
+        _start: DateTime<Utc>,
+        _end: DateTime<Utc>,
+    ) -> Result<Vec<FundingRate>> {
+        Ok(self
+            .funding_rates
+            .get(symbol.as_str())
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    async fn query_positioning(
+        &self,
+        symbol: &Symbol,
+        _start: DateTime<Utc>,
+        _end: DateTime<Utc>,
+    ) -> Result<Vec<PositioningSample>> {
         Ok(self
-            .candles
+            .positioning
             .get(symbol.as_str())
             .cloned()
             .unwrap_or_default())
@@ -75,7 +252,7 @@ impl HistoricalDataSource for MockDataSource {
 }
 
 /// Configuration for backtest execution
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct BacktestConfig {
     /// Initial capital in quote currency
     pub initial_capital: Decimal,
@@ -90,7 +267,11 @@ pub struct BacktestConfig {
     pub symbols: Vec<Symbol>,
 
     /// Candle interval for data
-    pub interval: String,
+    pub interval: Interval,
+
+    /// Longer candle intervals also fed to the strategy, e.g. "4H" alongside
+    /// a "1H" base; each bar is delivered only once it has closed
+    pub higher_timeframes: Vec<Interval>,
 
     /// Cost model for realistic execution
     pub cost_model: CostModel,
@@ -103,9 +284,30 @@ pub struct BacktestConfig {
 
     /// Position sizing mode
     pub position_sizing: PositionSizing,
+
+    /// Price path assumed when evaluating stop-loss / take-profit inside a candle
+    pub intrabar_path: IntrabarPath,
+
+    /// Short-selling rules; the default trades plain long-only spot
+    pub margin: MarginConfig,
+
+    /// When resting limit orders (signals with a target price) fill
+    pub limit_fill: LimitFillModel,
+
+    /// What to do about missing base-interval candles
+    #[serde(default)]
+    pub gap_policy: GapPolicy,
+
+    /// Stream base-interval candles in chunks instead of preloading them;
+    /// for histories too large to hold in memory
+    pub streaming: Option<StreamingConfig>,
+
+    /// Exchange downtime to simulate
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub maintenance: Vec<MaintenanceWindow>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum PositionSizing {
     /// Fixed amount per trade
     Fixed(Decimal),
@@ -127,11 +329,46 @@ impl Default for BacktestConfig {
             start_time: Utc::now() - chrono::Duration::days(365),
             end_time: Utc::now(),
             symbols: vec![Symbol::new("BTC-USDT").unwrap()],
-            interval: "1H".to_string(),
+            interval: Interval::OneHour,
+            higher_timeframes: Vec::new(),
             cost_model: CostModel::default(),
             verbose: false,
             max_positions: 5,
             position_sizing: PositionSizing::PercentOfEquity(dec!(0.1)),
+            intrabar_path: IntrabarPath::default(),
+            margin: MarginConfig::default(),
+            limit_fill: LimitFillModel::default(),
+            gap_policy: GapPolicy::default(),
+            streaming: None,
+            maintenance: Vec::new(),
+        }
+    }
+}
+
+impl BacktestConfig {
+    /// SHA-256 over the settings that can change a run's outcome
+    ///
+    /// Logging and streaming only change how a run executes, not what it
+    /// computes, so they are left out: the same hash means the same result
+    /// for the same strategy and data.
+    pub fn config_hash(&self) -> Result<String> {
+        let mut canonical = self.clone();
+        canonical.verbose = false;
+        canonical.streaming = None;
+        let json = serde_json::to_vec(&canonical)?;
+        Ok(Sha256::digest(&json)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect())
+    }
+}
+
+/// Overwrite `map[symbol]`, cloning the key only the first time it is seen
+fn set_latest(map: &mut HashMap<Symbol, Decimal>, symbol: &Symbol, value: Decimal) {
+    match map.get_mut(symbol) {
+        Some(slot) => *slot = value,
+        None => {
+            map.insert(symbol.clone(), value);
         }
     }
 }
@@ -143,8 +380,11 @@ pub struct BacktestEngine {
     portfolio: Portfolio,
     storage: Box<dyn HistoricalDataSource>,
 
-    /// Event queue sorted by timestamp
-    events: VecDeque<MarketEvent>,
+    /// Preloaded market data in replay order
+    timeline: Timeline,
+
+    /// Base-interval candles read during the run when streaming
+    stream: Option<CandleMerge>,
 
     /// Pending orders
     pending_orders: HashMap<Uuid, Order>,
@@ -160,6 +400,48 @@ pub struct BacktestEngine {
 
     /// Average volumes for slippage calculation
     avg_volumes: HashMap<Symbol, Decimal>,
+
+    /// Short perpetual hedge size per underlying symbol
+    perp_hedges: HashMap<Symbol, Decimal>,
+
+    /// Orders whose fills should be hedged with a short perpetual
+    hedged_orders: HashSet<Uuid>,
+
+    /// Strategy ID recorded on trades
+    strategy_id: Uuid,
+
+    /// Parameters the strategy is initialized with
+    strategy_parameters: HashMap<String, serde_json::Value>,
+
+    /// Trades not yet closed, per symbol
+    open_trades: HashMap<Symbol, Trade>,
+
+    /// Protective levels attached to open positions
+    exit_levels: HashMap<Symbol, ExitLevels>,
+
+    /// Protective levels to attach once the entry order fills
+    pending_exit_levels: HashMap<Uuid, ExitLevels>,
+
+    /// Exit orders generated by a stop-loss or take-profit
+    exit_triggers: HashMap<Uuid, ExitTrigger>,
+
+    /// Simulation clock checking every event fed to the strategy
+    lookahead: LookAheadGuard,
+
+    /// Queue state of resting limit orders
+    limit_fills: LimitFillTracker,
+
+    /// Missing base candles and the synthetic ones replacing them
+    gaps: GapTracker,
+
+    /// Configured exchange downtime and what it did to the run
+    maintenance: MaintenanceTracker,
+
+    /// Custom analytics following the run
+    observers: Vec<Box<dyn BacktestObserver>>,
+
+    /// UTC day of the last processed event
+    current_day: Option<NaiveDate>,
 }
 
 impl BacktestEngine {
@@ -168,22 +450,59 @@ impl BacktestEngine {
         strategy: Box<dyn Strategy>,
         storage: Box<dyn HistoricalDataSource>,
     ) -> Result<Self> {
-        let portfolio = Portfolio::new(config.initial_capital);
+        let portfolio = Portfolio::new(config.initial_capital).with_margin(config.margin);
+        let lookahead = LookAheadGuard::new(config.interval);
+        let limit_fills = LimitFillTracker::new(config.limit_fill);
+        let gaps = GapTracker::new(config.gap_policy, config.interval)
+            .with_maintenance(&config.maintenance);
+        let maintenance = MaintenanceTracker::new(&config.maintenance)?;
 
         Ok(Self {
             config,
             strategy,
             portfolio,
             storage,
-            events: VecDeque::new(),
+            timeline: Timeline::new(),
+            stream: None,
             pending_orders: HashMap::new(),
             executions: Vec::new(),
             trades: Vec::new(),
             current_prices: HashMap::new(),
             avg_volumes: HashMap::new(),
+            perp_hedges: HashMap::new(),
+            hedged_orders: HashSet::new(),
+            strategy_id: Uuid::new_v4(),
+            strategy_parameters: HashMap::new(),
+            open_trades: HashMap::new(),
+            exit_levels: HashMap::new(),
+            pending_exit_levels: HashMap::new(),
+            exit_triggers: HashMap::new(),
+            lookahead,
+            limit_fills,
+            gaps,
+            maintenance,
+            observers: Vec::new(),
+            current_day: None,
         })
     }
 
+    /// Initialize the strategy with `parameters` instead of none, e.g. those
+    /// of the deployed strategy being tested
+    pub fn with_strategy_parameters(
+        mut self,
+        parameters: HashMap<String, serde_json::Value>,
+    ) -> Self {
+        self.strategy_parameters = parameters;
+        self
+    }
+
+    /// Register an observer; its report is added to
+    /// [`BacktestResult::analytics`] under its name
+    pub fn with_observer(mut self, observer: Box<dyn BacktestObserver>) -> Self {
+        self.observers.push(observer);
+        self
+    }
+
     /// Load historical market data
     async fn load_data(&mut self) -> Result<()> {
         info!(
@@ -191,41 +510,144 @@ impl BacktestEngine {
             self.config.start_time, self.config.end_time
         );
 
-        for symbol in &self.config.symbols {
-            let candles = self
-                .storage
-                .query_candles(
-                    symbol,
-                    &self.config.interval,
-                    self.config.start_time,
-                    self.config.end_time,
-                )
-                .await?;
+        let mut stream = self.config.streaming.map(|_| CandleMerge::new());
+        let mut gaps = Vec::new();
 
-            info!("Loaded {} candles for {}", candles.len(), symbol.as_str());
+        for symbol in &self.config.symbols {
+            let has_data = match (&mut stream, &self.config.streaming) {
+                (Some(merge), Some(streaming)) => {
+                    let chunks = self
+                        .storage
+                        .stream_candles(
+                            symbol,
+                            self.config.interval,
+                            self.config.start_time,
+                            self.config.end_time,
+                            streaming,
+                        )
+                        .await?;
+                    info!("Streaming candles for {}", symbol.as_str());
+                    merge.add(chunks).await?
+                }
+                _ => {
+                    let candles = self
+                        .storage
+                        .query_candles(
+                            symbol,
+                            self.config.interval,
+                            self.config.start_time,
+                            self.config.end_time,
+                        )
+                        .await?;
+
+                    info!("Loaded {} candles for {}", candles.len(), symbol.as_str());
+
+                    let has_data = !candles.is_empty();
+                    if self.config.gap_policy == GapPolicy::Abort
+                        && let Some(bar) = self.config.interval.duration()
+                    {
+                        gaps.extend(
+                            find_gaps(&candles, bar)
+                                .into_iter()
+                                .filter(|gap| !self.gaps.during_maintenance(gap)),
+                        );
+                    }
+                    self.timeline.add_candles(symbol.clone(), candles);
+                    has_data
+                }
+            };
 
-            if candles.is_empty() {
+            if !has_data {
                 return Err(Error::InsufficientData(format!(
                     "No data found for {} in the specified time range",
                     symbol.as_str()
                 )));
             }
 
-            // Convert candles to market events
-            for candle in candles {
-                self.events.push_back(MarketEvent::Candle(candle));
+            for &interval in &self.config.higher_timeframes {
+                let length = self.higher_timeframe_length(interval)?;
+                let mut candles = self
+                    .storage
+                    .query_candles(
+                        symbol,
+                        interval,
+                        self.config.start_time,
+                        self.config.end_time,
+                    )
+                    .await?;
+                // A bar still open at the end of the run never becomes visible
+                candles.retain(|c| c.timestamp + length <= self.config.end_time);
+
+                info!(
+                    "Loaded {} {} candles for {}",
+                    candles.len(),
+                    interval,
+                    symbol.as_str()
+                );
+                self.timeline
+                    .add_higher_timeframe(symbol.clone(), interval, candles, length);
+            }
+
+            let funding_rates = self
+                .storage
+                .query_funding_rates(symbol, self.config.start_time, self.config.end_time)
+                .await?;
+
+            if !funding_rates.is_empty() {
+                info!(
+                    "Loaded {} funding rates for {}",
+                    funding_rates.len(),
+                    symbol.as_str()
+                );
+            }
+
+            self.timeline.add_funding_rates(funding_rates);
+
+            let positioning = self
+                .storage
+                .query_positioning(symbol, self.config.start_time, self.config.end_time)
+                .await?;
+
+            if !positioning.is_empty() {
+                info!(
+                    "Loaded {} positioning samples for {}",
+                    positioning.len(),
+                    symbol.as_str()
+                );
             }
+
+            self.timeline.add_positioning(positioning);
+        }
+
+        // Report every gap up front rather than stopping at the first one
+        if !gaps.is_empty() {
+            let mut report = self.gaps.report();
+            report.gaps = gaps;
+            return Err(Error::DataGaps(report));
         }
 
-        // Sort events by timestamp
-        let mut events_vec: Vec<_> = self.events.drain(..).collect();
-        events_vec.sort_by_key(|e| e.timestamp());
-        self.events.extend(events_vec);
+        self.timeline.sort();
+        self.stream = stream;
 
-        info!("Total events loaded: {}", self.events.len());
+        info!("Total events loaded: {}", self.timeline.len());
         Ok(())
     }
 
+    /// Bar length of a configured higher timeframe, which must be a whole
+    /// multiple of the base interval
+    fn higher_timeframe_length(&self, interval: Interval) -> Result<chrono::Duration> {
+        let length = interval.duration().ok_or_else(|| {
+            Error::InvalidConfig(format!("Unsupported higher timeframe: {}", interval))
+        })?;
+        match self.config.interval.bars_per(interval) {
+            Some(bars) if bars > 1 => Ok(length),
+            _ => Err(Error::InvalidConfig(format!(
+                "Higher timeframe {} is not a multiple of the {} base interval",
+                interval, self.config.interval
+            ))),
+        }
+    }
+
     /// Run the backtest
     pub async fn run(&mut self) -> Result<BacktestResult> {
         info!("Starting backtest...");
@@ -235,7 +657,7 @@ impl BacktestEngine {
 
         // Initialize strategy
         let strategy_config = StrategyConfig {
-            strategy_id: Uuid::new_v4(),
+            strategy_id: self.strategy_id,
             name: "Backtest Strategy".to_string(),
             version: "1.0.0".to_string(),
             symbols: self
@@ -244,7 +666,7 @@ impl BacktestEngine {
                 .iter()
                 .map(|s| s.as_str().to_string())
                 .collect(),
-            parameters: HashMap::new(),
+            parameters: self.strategy_parameters.clone(),
             risk_limits: RiskLimits {
                 max_position_size: dec!(10000.0),
                 max_leverage: dec!(3.0),
@@ -255,25 +677,70 @@ impl BacktestEngine {
 
         self.strategy.initialize(strategy_config).await?;
 
-        let mut event_count = 0;
-        let total_events = self.events.len();
+        let total_events = self.timeline.len();
+        let mut index = 0;
+        let mut event_count = 0usize;
 
-        // Process events chronologically
-        while let Some(event) = self.events.pop_front() {
-            event_count += 1;
+        // Process events chronologically, merging streamed candles with the
+        // preloaded timeline
+        loop {
+            let streamed = match &mut self.stream {
+                Some(stream) => stream.peek_time().await?,
+                None => None,
+            };
+            let preloaded = self.timeline.replay_key(index);
+
+            // Streamed candles are base events, so they follow higher
+            // timeframes closing at the same time and precede funding
+            let from_timeline = match (preloaded, streamed) {
+                (Some(key), Some(ts)) => key < (ts, true),
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (None, None) => break,
+            };
 
-            if self.config.verbose && event_count % 1000 == 0 {
-                info!("Processing event {}/{}", event_count, total_events);
+            event_count += 1;
+            if self.config.verbose && event_count.is_multiple_of(1000) {
+                if self.stream.is_some() {
+                    info!("Processing event {}", event_count);
+                } else {
+                    info!("Processing event {}/{}", event_count, total_events);
+                }
             }
 
-            self.process_event(event).await?;
+            if from_timeline {
+                let (Some(clock), Some(event_ref)) =
+                    (self.timeline.time(index), self.timeline.get(index))
+                else {
+                    break;
+                };
+                index += 1;
+                self.fill_gaps(clock).await?;
+                self.lookahead.advance(clock)?;
+                self.replay_event(event_ref).await?;
+            } else if let Some(stream) = &mut self.stream
+                && let Some(candle) = stream.next_candle().await?
+            {
+                self.fill_gaps(candle.timestamp).await?;
+                self.lookahead.advance(candle.timestamp)?;
+                self.replay_candle(candle).await?;
+            }
         }
 
         // Close all open positions at end
         self.close_all_positions().await?;
+        if let Some(day) = self.current_day.take() {
+            self.observe(self.config.end_time, |observer, ctx| {
+                observer.on_day_close(day, ctx)
+            });
+        }
 
         // Finalize and generate results
-        let result = self.generate_results().await?;
+        let mut result = self.generate_results().await?;
+        for observer in &mut self.observers {
+            let report = observer.on_finish(&result);
+            result.analytics.insert(observer.name().to_string(), report);
+        }
 
         info!("Backtest completed. Final equity: {}", result.final_equity);
         info!("Total trades: {}", result.total_trades);
@@ -282,43 +749,128 @@ impl BacktestEngine {
         Ok(result)
     }
 
+    /// Replay one preloaded timeline event
+    async fn replay_event(&mut self, event_ref: EventRef) -> Result<()> {
+        let event = match event_ref {
+            EventRef::Candle { series, row } => {
+                let data = &self.timeline.series[series as usize];
+                let candle = data.candle(row as usize);
+                if let Some(interval) = data.interval {
+                    return self.deliver_higher_timeframe(candle, interval).await;
+                }
+                return self.replay_candle(candle).await;
+            }
+            EventRef::Funding { index } => {
+                let funding = &self.timeline.funding[index as usize];
+                MarketEvent::FundingRate {
+                    symbol: funding.symbol.clone(),
+                    rate: funding.rate,
+                    timestamp: funding.timestamp,
+                }
+            }
+            EventRef::Positioning { index } => {
+                MarketEvent::Positioning(self.timeline.positioning[index as usize].clone())
+            }
+        };
+
+        self.process_event(event).await
+    }
+
+    /// Replay a base-interval candle from the data source
+    ///
+    /// Under [`GapPolicy::Skip`] the strategy first learns how many bars
+    /// were missing before it. Candles opening during maintenance are
+    /// withheld, as a live feed would never have published them.
+    async fn replay_candle(&mut self, candle: Candle) -> Result<()> {
+        if self.maintenance.withhold(&candle) {
+            self.begin_maintenance(candle.timestamp).await?;
+            return Ok(());
+        }
+        if let Some(gap) = self.gaps.observe(&candle)?
+            && self.gaps.policy() == GapPolicy::Skip
+        {
+            self.feed_strategy(MarketDataEvent::Gap {
+                symbol: gap.symbol,
+                interval: self.config.interval,
+                missing_bars: gap.missing_bars,
+                timestamp: gap.resumed_at,
+            })
+            .await?;
+        }
+        self.process_event(MarketEvent::Candle(candle)).await
+    }
+
+    /// Replay synthetic candles for bars missing before an event at `now`
+    async fn fill_gaps(&mut self, now: DateTime<Utc>) -> Result<()> {
+        for candle in self.gaps.fill_before(now) {
+            self.lookahead.advance(candle.timestamp)?;
+            self.process_event(MarketEvent::Candle(candle)).await?;
+        }
+        Ok(())
+    }
+
     /// Process a single market event
     async fn process_event(&mut self, event: MarketEvent) -> Result<()> {
         let timestamp = event.timestamp();
+        self.close_days(timestamp);
+        self.begin_maintenance(timestamp).await?;
 
         // Update current market state
         match &event {
             MarketEvent::Candle(candle) => {
-                self.current_prices
-                    .insert(candle.symbol.clone(), candle.close);
-                self.avg_volumes
-                    .insert(candle.symbol.clone(), candle.volume);
+                set_latest(&mut self.current_prices, &candle.symbol, candle.close);
+                // A bar without trades, such as a forward-filled one, says
+                // nothing about liquidity
+                if !candle.volume.is_zero() {
+                    set_latest(&mut self.avg_volumes, &candle.symbol, candle.volume);
+                }
             }
             MarketEvent::Trade { symbol, price, .. } => {
-                self.current_prices.insert(symbol.clone(), *price);
+                set_latest(&mut self.current_prices, symbol, *price);
             }
             MarketEvent::OrderBook {
                 symbol, bids, asks, ..
             } => {
-                if let Some((best_bid, _)) = bids.first() {
-                    if let Some((best_ask, _)) = asks.first() {
-                        let mid_price = (*best_bid + *best_ask) / dec!(2.0);
-                        self.current_prices.insert(symbol.clone(), mid_price);
-                    }
+                if let Some((best_bid, _)) = bids.first()
+                    && let Some((best_ask, _)) = asks.first()
+                {
+                    let mid_price = (*best_bid + *best_ask) / dec!(2.0);
+                    set_latest(&mut self.current_prices, symbol, mid_price);
                 }
             }
+            MarketEvent::FundingRate {
+                symbol,
+                rate,
+                timestamp,
+            } => {
+                self.settle_funding(symbol, *rate, *timestamp);
+            }
+            MarketEvent::Positioning(_) => {}
         }
 
         // Update portfolio with current prices
         self.portfolio.update_prices(&self.current_prices);
+        self.portfolio.accrue_borrow_cost(timestamp);
 
         // Check pending orders for fills
-        self.check_pending_orders(timestamp).await?;
+        let bar = match &event {
+            MarketEvent::Candle(candle) => Some(candle),
+            _ => None,
+        };
+        self.check_pending_orders(timestamp, bar).await?;
+        self.observe(timestamp, |observer, ctx| observer.on_event(&event, ctx));
+
+        // Stops and targets see the whole bar, not just its close
+        if let MarketEvent::Candle(candle) = &event {
+            self.track_excursions(candle);
+            self.check_exit_levels(candle).await?;
+        }
 
         // Feed event to strategy
         let market_data = match event {
-            MarketEvent::Candle(candle) => ea_okx_strategy::traits::MarketDataEvent::Candle {
-                symbol: candle.symbol.clone(),
+            MarketEvent::Candle(candle) => MarketDataEvent::Candle {
+                symbol: candle.symbol,
+                interval: self.config.interval,
                 open: candle.open,
                 high: candle.high,
                 low: candle.low,
@@ -326,19 +878,36 @@ impl BacktestEngine {
                 volume: candle.volume,
                 timestamp: candle.timestamp,
             },
-            _ => return Ok(()), // Only process candles for now
+            MarketEvent::FundingRate {
+                symbol,
+                rate,
+                timestamp,
+            } => MarketDataEvent::FundingRate {
+                symbol,
+                funding_rate: rate,
+                timestamp,
+            },
+            MarketEvent::Positioning(sample) => sample.into_market_data(),
+            _ => return Ok(()), // Trades and order books are not replayed yet
         };
 
-        self.strategy.on_market_data(market_data).await?;
+        self.feed_strategy(market_data).await?;
 
         // Check if strategy generated a signal - strategies now don't have symbols in signals
-        // We'll process the first symbol in config for now
+        // We route to the first configured symbol unless the signal names one in its metadata
         if !self.config.symbols.is_empty() {
-            let symbol = self.config.symbols[0].clone(); // Clone to avoid borrow issues
-            match self.strategy.generate_signal().await {
-                Ok(signal) => {
-                    if signal.signal_type != SignalType::Hold {
-                        self.execute_signal(signal, &symbol, timestamp).await?;
+            match self.strategy.generate_signals().await {
+                Ok(signals) => {
+                    for signal in signals {
+                        let symbol = signal
+                            .metadata
+                            .get("symbol")
+                            .and_then(|s| s.as_str())
+                            .and_then(|s| Symbol::new(s).ok())
+                            .unwrap_or_else(|| self.config.symbols[0].clone());
+                        if signal.signal_type != SignalType::Hold {
+                            self.execute_signal(signal, &symbol, timestamp).await?;
+                        }
                     }
                 }
                 Err(e) => {
@@ -350,21 +919,98 @@ impl BacktestEngine {
         Ok(())
     }
 
+    /// Tell observers the previous UTC day ended when `now` falls on a later one
+    fn close_days(&mut self, now: DateTime<Utc>) {
+        let day = now.date_naive();
+        if let Some(previous) = self.current_day.replace(day)
+            && previous < day
+        {
+            let midnight = day.and_hms_opt(0, 0, 0).map_or(now, |t| t.and_utc());
+            self.observe(midnight, |observer, ctx| {
+                observer.on_day_close(previous, ctx)
+            });
+        }
+    }
+
+    /// Run a hook on every observer
+    fn observe(
+        &mut self,
+        timestamp: DateTime<Utc>,
+        hook: impl Fn(&mut dyn BacktestObserver, &ObserverContext<'_>),
+    ) {
+        let ctx = ObserverContext {
+            timestamp,
+            portfolio: &self.portfolio,
+            prices: &self.current_prices,
+        };
+        for observer in &mut self.observers {
+            hook(observer.as_mut(), &ctx);
+        }
+    }
+
+    /// Hand a closed higher-timeframe bar to the strategy
+    ///
+    /// Prices, fills and exits follow the base interval, and signals are
+    /// evaluated on the base candle that follows at the same timestamp.
+    async fn deliver_higher_timeframe(&mut self, candle: Candle, interval: Interval) -> Result<()> {
+        self.feed_strategy(MarketDataEvent::Candle {
+            symbol: candle.symbol,
+            interval,
+            open: candle.open,
+            high: candle.high,
+            low: candle.low,
+            close: candle.close,
+            volume: candle.volume,
+            timestamp: candle.timestamp,
+        })
+        .await
+    }
+
+    /// Deliver market data to the strategy, failing on look-ahead bias
+    async fn feed_strategy(&mut self, event: MarketDataEvent) -> Result<()> {
+        self.lookahead.check(&event)?;
+        self.strategy.on_market_data(event).await?;
+        Ok(())
+    }
+
     /// Check pending orders for execution
-    async fn check_pending_orders(&mut self, timestamp: DateTime<Utc>) -> Result<()> {
+    ///
+    /// Limit orders on the symbol of `bar` are checked against its whole
+    /// range; others only against their symbol's latest price.
+    async fn check_pending_orders(
+        &mut self,
+        timestamp: DateTime<Utc>,
+        bar: Option<&Candle>,
+    ) -> Result<()> {
+        if self.pending_orders.is_empty() {
+            return Ok(());
+        }
+
         let mut to_fill = Vec::new();
 
         for (order_id, order) in &self.pending_orders {
+            // Nothing fills while the exchange is down
+            if self.maintenance.is_down(&order.symbol, timestamp) {
+                continue;
+            }
             if let Some(current_price) = self.current_prices.get(&order.symbol) {
-                // Simple fill logic based on order type
                 let should_fill = match order.order_type {
                     OrderType::Market => true,
                     OrderType::Limit => {
                         if let Some(order_price) = order.price {
-                            match order.side {
-                                OrderSide::Buy => current_price <= &order_price.as_decimal(),
-                                OrderSide::Sell => current_price >= &order_price.as_decimal(),
-                            }
+                            let range = match bar {
+                                Some(candle) if candle.symbol == order.symbol => {
+                                    BarRange::from(candle)
+                                }
+                                _ => BarRange::point(*current_price),
+                            };
+                            self.limit_fills.check(
+                                order.id,
+                                order.side,
+                                order_price.as_decimal(),
+                                order.quantity.as_decimal(),
+                                &range,
+                            )
                         } else {
                             false
                         }
@@ -388,6 +1034,67 @@ impl BacktestEngine {
         Ok(())
     }
 
+    /// Update MAE/MFE of the open trade from the candle's range
+    fn track_excursions(&mut self, candle: &Candle) {
+        let Some(trade) = self.open_trades.get_mut(&candle.symbol) else {
+            return;
+        };
+
+        let (favorable, adverse) = match trade.side {
+            OrderSide::Buy => (
+                candle.high - trade.entry_price,
+                trade.entry_price - candle.low,
+            ),
+            OrderSide::Sell => (
+                trade.entry_price - candle.low,
+                candle.high - trade.entry_price,
+            ),
+        };
+        trade.max_favorable_excursion = trade
+            .max_favorable_excursion
+            .max(favorable * trade.quantity);
+        trade.max_adverse_excursion = trade.max_adverse_excursion.max(adverse * trade.quantity);
+    }
+
+    /// Close the position if the candle touched its stop-loss or take-profit
+    async fn check_exit_levels(&mut self, candle: &Candle) -> Result<()> {
+        let symbol = &candle.symbol;
+        // Stops cannot execute while the exchange is down
+        if self.maintenance.is_down(symbol, candle.timestamp) {
+            return Ok(());
+        }
+        let Some(levels) = self.exit_levels.get(symbol).copied() else {
+            return Ok(());
+        };
+        let Some(position) = self.portfolio.get_position(symbol) else {
+            self.exit_levels.remove(symbol);
+            return Ok(());
+        };
+
+        let entry_side = match position.side {
+            PositionSide::Short => OrderSide::Sell,
+            PositionSide::Long | PositionSide::Net => OrderSide::Buy,
+        };
+
+        if let Some((trigger, price)) = self
+            .config
+            .intrabar_path
+            .resolve(candle, entry_side, &levels)
+        {
+            debug!(
+                "{:?} hit on {} at {} ({:?} path)",
+                trigger,
+                symbol.as_str(),
+                price,
+                self.config.intrabar_path
+            );
+            self.close_position_at(symbol, price, Some(trigger), candle.timestamp)
+                .await?;
+        }
+
+        Ok(())
+    }
+
     /// Execute a signal from the strategy
     async fn execute_signal(
         &mut self,
@@ -397,51 +1104,167 @@ impl BacktestEngine {
     ) -> Result<()> {
         debug!("Executing signal: {:?}", signal);
 
+        let side = match signal.signal_type {
+            SignalType::Buy => OrderSide::Buy,
+            SignalType::Sell => OrderSide::Sell,
+            SignalType::Hold => return Ok(()),
+            SignalType::CloseLong | SignalType::CloseShort => {
+                if self.maintenance.is_down(symbol, timestamp) {
+                    if let Some(&price) = self.current_prices.get(symbol)
+                        && let Some(order) = self.closing_order(symbol, price)?
+                    {
+                        self.reject_for_maintenance(order, timestamp).await?;
+                    }
+                    return Ok(());
+                }
+                // Close existing position; exits are never held back by the limit
+                self.close_position(symbol, timestamp).await?;
+                return Ok(());
+            }
+        };
+
         // Check position limits
         if self.portfolio.position_count() >= self.config.max_positions {
             debug!("Max positions reached, skipping signal");
             return Ok(());
         }
 
-        // Calculate position size
-        let size = self.calculate_position_size(symbol)?;
+        // Calculate position size, unless the strategy sized the order itself
+        let size = match signal.suggested_quantity {
+            Some(quantity) => quantity.as_decimal(),
+            None => self.calculate_position_size(symbol)?,
+        };
 
         if size <= Decimal::ZERO {
             debug!("Position size is zero, skipping signal");
             return Ok(());
         }
 
-        // Create order
-        let side = match signal.signal_type {
-            SignalType::Buy => OrderSide::Buy,
-            SignalType::Sell => OrderSide::Sell,
-            SignalType::Hold => return Ok(()),
-            SignalType::CloseLong | SignalType::CloseShort => {
-                // Close existing position
-                self.close_position(symbol, timestamp).await?;
-                return Ok(());
-            }
-        };
-
         let price = self
             .current_prices
             .get(symbol)
             .copied()
             .ok_or_else(|| Error::ExecutionError("No price available".to_string()))?;
 
-        let order = Order::new(
-            Uuid::new_v4(),
-            symbol.clone(),
-            side,
-            OrderType::Market,
-            Quantity::new(size)?,
-            Some(Price::new(price)?),
-        );
+        // A target price rests as a limit order; otherwise take the market
+        let order = match signal.target_price {
+            Some(limit) => Order::new(
+                Uuid::new_v4(),
+                symbol.clone(),
+                side,
+                OrderType::Limit,
+                Quantity::new(size)?,
+                Some(limit),
+            ),
+            None => Order::new(
+                Uuid::new_v4(),
+                symbol.clone(),
+                side,
+                OrderType::Market,
+                Quantity::new(size)?,
+                Some(Price::new(price)?),
+            ),
+        };
 
-        // Add to pending orders
-        self.pending_orders.insert(order.id, order);
+        if self.maintenance.is_down(symbol, timestamp) {
+            return self.reject_for_maintenance(order, timestamp).await;
+        }
+        if order.order_type == OrderType::Limit {
+            self.limit_fills.place(order.id);
+        }
 
-        Ok(())
+        // Hedge spot buys with a short perpetual when the signal asks for it
+        if side == OrderSide::Buy
+            && signal.metadata.get("hedge").and_then(|h| h.as_str()) == Some("perp")
+        {
+            self.hedged_orders.insert(order.id);
+        }
+
+        // Attach protective levels to entries (long or short), not to exits
+        let levels = ExitLevels {
+            stop_loss: signal.stop_loss.map(|p| p.as_decimal()),
+            take_profit: signal.take_profit.map(|p| p.as_decimal()),
+        };
+        let is_entry = match side {
+            OrderSide::Buy => self
+                .portfolio
+                .get_position(symbol)
+                .is_none_or(|p| p.side != PositionSide::Short),
+            OrderSide::Sell => {
+                self.config.margin.allow_short && self.portfolio.get_position(symbol).is_none()
+            }
+        };
+        if is_entry && !levels.is_empty() {
+            self.pending_exit_levels.insert(order.id, levels);
+        }
+
+        // Add to pending orders
+        self.pending_orders.insert(order.id, order);
+
+        Ok(())
+    }
+
+    /// Refuse an order sent while its symbol is under maintenance
+    async fn reject_for_maintenance(
+        &mut self,
+        order: Order,
+        timestamp: DateTime<Utc>,
+    ) -> Result<()> {
+        let reason = "Exchange under maintenance".to_string();
+        debug!(
+            "Order rejected: {:?} {} {}: {}",
+            order.side,
+            order.quantity.as_decimal(),
+            order.symbol.as_str(),
+            reason
+        );
+        self.maintenance.reject(&order.symbol, timestamp);
+        self.executions.push(ExecutionEvent::OrderRejected {
+            order_id: order.id,
+            reason: reason.clone(),
+            timestamp,
+        });
+        self.strategy.on_order_reject(&order, &reason).await?;
+        Ok(())
+    }
+
+    /// Apply maintenance windows beginning by `now`, cancelling resting
+    /// orders on the symbols they take down if the exchange would
+    async fn begin_maintenance(&mut self, now: DateTime<Utc>) -> Result<()> {
+        for window in self.maintenance.begin(now) {
+            info!(
+                "Exchange maintenance from {} to {}",
+                window.start, window.end
+            );
+            if window.resting_orders != RestingOrders::Cancel {
+                continue;
+            }
+
+            let mut cancelled: Vec<_> = self
+                .pending_orders
+                .values()
+                .filter(|order| window.affects(&order.symbol))
+                .map(|order| order.id)
+                .collect();
+            cancelled.sort();
+            self.maintenance.cancelled(&window, cancelled.len());
+
+            let reason = "Cancelled for exchange maintenance";
+            for order_id in cancelled {
+                let Some(order) = self.pending_orders.remove(&order_id) else {
+                    continue;
+                };
+                self.pending_exit_levels.remove(&order.id);
+                self.hedged_orders.remove(&order.id);
+                self.exit_triggers.remove(&order.id);
+                self.executions.push(ExecutionEvent::OrderCancelled {
+                    order_id: order.id,
+                    timestamp: window.start,
+                });
+                self.strategy.on_order_reject(&order, reason).await?;
+            }
+        }
+        Ok(())
     }
 
     /// Fill an order
@@ -455,8 +1278,9 @@ impl BacktestEngine {
             order.side,
             order
                 .price
-                .unwrap_or(Price::new(dec!(0.0)).unwrap())
-                .as_decimal(),
+                .map(|p| p.as_decimal())
+                .or_else(|| self.current_prices.get(symbol).copied())
+                .unwrap_or(Decimal::ZERO),
             order.quantity.as_decimal(),
             avg_volume,
         );
@@ -471,8 +1295,37 @@ impl BacktestEngine {
             slippage,
         };
 
-        // Update portfolio
-        self.portfolio.apply_fill(&order, &fill)?;
+        // Update portfolio; fills the account cannot cover are rejected like
+        // the exchange would, without aborting the run
+        if let Err(e) = self.portfolio.apply_fill(&order, &fill) {
+            let Error::ExecutionError(reason) = e else {
+                return Err(e);
+            };
+            warn!(
+                "Order rejected: {:?} {} {}: {}",
+                order.side,
+                fill.quantity,
+                symbol.as_str(),
+                reason
+            );
+            self.pending_exit_levels.remove(&order.id);
+            self.hedged_orders.remove(&order.id);
+            self.exit_triggers.remove(&order.id);
+            self.strategy.on_order_reject(&order, &reason).await?;
+            return Ok(());
+        }
+
+        // Track the perpetual hedge alongside the spot leg
+        if self.hedged_orders.remove(&order.id) {
+            *self.perp_hedges.entry(symbol.clone()).or_default() += fill.quantity;
+        } else if order.side == OrderSide::Sell
+            && let Some(hedge) = self.perp_hedges.get_mut(symbol)
+        {
+            *hedge = (*hedge - fill.quantity).max(Decimal::ZERO);
+            if hedge.is_zero() {
+                self.perp_hedges.remove(symbol);
+            }
+        }
 
         // Record execution
         let execution = ExecutionEvent::OrderFilled {
@@ -487,6 +1340,11 @@ impl BacktestEngine {
 
         self.executions.push(execution);
 
+        self.record_trade(&order, &fill)?;
+        self.observe(timestamp, |observer, ctx| {
+            observer.on_fill(&order, &fill, ctx)
+        });
+
         // Notify strategy
         self.strategy.on_order_fill(&order).await?;
 
@@ -502,6 +1360,130 @@ impl BacktestEngine {
         Ok(())
     }
 
+    /// Open, extend or close the trade record for a fill
+    ///
+    /// A fill against the open trade's side closes it (partially, or fully with
+    /// any remainder opening a trade the other way); otherwise it opens or
+    /// extends a trade on the order's side.
+    fn record_trade(&mut self, order: &Order, fill: &Fill) -> Result<()> {
+        let symbol = &order.symbol;
+        let trigger = self.exit_triggers.remove(&order.id);
+
+        let mut remaining = fill.quantity;
+        if let Some(open) = self.open_trades.get_mut(symbol)
+            && open.side != order.side
+        {
+            let quantity = fill.quantity.min(open.quantity);
+            let share = safe_div(quantity, fill.quantity)?;
+            let (commission, slippage) = (fill.commission * share, fill.slippage * share);
+
+            let mut closed = if quantity >= open.quantity {
+                self.exit_levels.remove(symbol);
+                self.open_trades
+                    .remove(symbol)
+                    .expect("open trade checked above")
+            } else {
+                // Partial exit: split off the closed portion with its share of entry costs
+                let share = safe_div(quantity, open.quantity)?;
+                let mut part = open.clone();
+                part.id = Uuid::new_v4();
+                part.quantity = quantity;
+                part.commission = open.commission * share;
+                part.slippage = open.slippage * share;
+                part.max_adverse_excursion = open.max_adverse_excursion * share;
+                part.max_favorable_excursion = open.max_favorable_excursion * share;
+
+                open.quantity -= quantity;
+                open.commission -= part.commission;
+                open.slippage -= part.slippage;
+                open.max_adverse_excursion -= part.max_adverse_excursion;
+                open.max_favorable_excursion -= part.max_favorable_excursion;
+                part
+            };
+
+            closed.close(fill.timestamp, fill.price, commission, slippage);
+            closed.exit_trigger = trigger;
+            self.trades.push(closed);
+            remaining -= quantity;
+        }
+
+        if remaining <= Decimal::ZERO {
+            return Ok(());
+        }
+
+        // Shorts are only opened when the portfolio allows them; a plain spot
+        // sell with no open trade has nothing to record
+        if order.side == OrderSide::Sell
+            && self
+                .portfolio
+                .get_position(symbol)
+                .is_none_or(|p| p.side != PositionSide::Short)
+        {
+            return Ok(());
+        }
+
+        let share = safe_div(remaining, fill.quantity)?;
+        let (commission, slippage) = (fill.commission * share, fill.slippage * share);
+        if let Some(levels) = self.pending_exit_levels.remove(&order.id) {
+            self.exit_levels.insert(symbol.clone(), levels);
+        }
+
+        match self.open_trades.get_mut(symbol) {
+            Some(trade) => {
+                let quantity = trade.quantity + remaining;
+                trade.entry_price = safe_div(
+                    safe_mul(trade.entry_price, trade.quantity)? + safe_mul(fill.price, remaining)?,
+                    quantity,
+                )?;
+                trade.quantity = quantity;
+                trade.commission += commission;
+                trade.slippage += slippage;
+            }
+            None => {
+                self.open_trades.insert(
+                    symbol.clone(),
+                    Trade::new(
+                        self.strategy_id,
+                        symbol.clone(),
+                        order.side,
+                        fill.timestamp,
+                        fill.price,
+                        remaining,
+                        commission,
+                        slippage,
+                    ),
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Credit (or debit) funding on short perpetual hedges.
+    ///
+    /// Hedges are modeled by notional only: the perpetual is assumed to track
+    /// the spot price, so basis moves are ignored and only funding is booked.
+    fn settle_funding(&mut self, symbol: &Symbol, rate: Decimal, timestamp: DateTime<Utc>) {
+        let Some(hedge_qty) = self.perp_hedges.get(symbol).copied() else {
+            return;
+        };
+        let Some(price) = self.current_prices.get(symbol).copied() else {
+            return;
+        };
+
+        // Shorts receive funding when the rate is positive
+        let payment = hedge_qty * price * rate;
+        self.portfolio.apply_funding(payment, timestamp);
+
+        debug!(
+            "Funding settled on {} hedge of {}: rate {}, payment {}",
+            symbol.as_str(),
+            hedge_qty,
+            rate,
+            payment
+        );
+    }
+
     /// Calculate position size based on configuration
     fn calculate_position_size(&self, symbol: &Symbol) -> Result<Decimal> {
         let price = self
@@ -513,52 +1495,76 @@ impl BacktestEngine {
         let equity = self.portfolio.total_equity();
 
         let size = match &self.config.position_sizing {
-            PositionSizing::Fixed(amount) => amount / price,
-            PositionSizing::PercentOfEquity(pct) => (equity * pct) / price,
+            PositionSizing::Fixed(amount) => safe_div(*amount, price)?,
+            PositionSizing::PercentOfEquity(pct) => safe_div(safe_mul(equity, *pct)?, price)?,
             PositionSizing::Kelly {
                 win_rate,
                 win_loss_ratio,
             } => {
-                let kelly = (win_rate * (win_loss_ratio + dec!(1.0)) - dec!(1.0)) / win_loss_ratio;
+                let kelly = safe_div(
+                    win_rate * (win_loss_ratio + dec!(1.0)) - dec!(1.0),
+                    *win_loss_ratio,
+                )?;
                 let kelly_fraction = kelly.max(Decimal::ZERO).min(dec!(0.25)); // Cap at 25%
-                (equity * kelly_fraction) / price
+                safe_div(safe_mul(equity, kelly_fraction)?, price)?
             }
         };
 
         Ok(size)
     }
 
-    /// Close a specific position
+    /// Close a specific position at the current price
     async fn close_position(&mut self, symbol: &Symbol, timestamp: DateTime<Utc>) -> Result<()> {
-        if let Some(position) = self.portfolio.get_position(symbol) {
-            let quantity = position.quantity.as_decimal().abs();
-            let price = self
-                .current_prices
-                .get(symbol)
-                .copied()
-                .ok_or_else(|| Error::ExecutionError("No price available".to_string()))?;
-
-            let side = match position.side {
-                PositionSide::Long => OrderSide::Sell,
-                PositionSide::Short => OrderSide::Buy,
-                PositionSide::Net => OrderSide::Sell,
-            };
+        let price = self
+            .current_prices
+            .get(symbol)
+            .copied()
+            .ok_or_else(|| Error::ExecutionError("No price available".to_string()))?;
 
-            let order = Order::new(
-                Uuid::new_v4(),
-                symbol.clone(),
-                side,
-                OrderType::Market,
-                Quantity::new(quantity)?,
-                Some(Price::new(price)?),
-            );
+        self.close_position_at(symbol, price, None, timestamp).await
+    }
 
+    /// Close a specific position at `price`, recording what triggered the exit
+    async fn close_position_at(
+        &mut self,
+        symbol: &Symbol,
+        price: Decimal,
+        trigger: Option<ExitTrigger>,
+        timestamp: DateTime<Utc>,
+    ) -> Result<()> {
+        if let Some(order) = self.closing_order(symbol, price)? {
+            if let Some(trigger) = trigger {
+                self.exit_triggers.insert(order.id, trigger);
+            }
             self.fill_order(order, timestamp).await?;
         }
 
         Ok(())
     }
 
+    /// Market order flattening the position in `symbol` at `price`, if any
+    fn closing_order(&self, symbol: &Symbol, price: Decimal) -> Result<Option<Order>> {
+        let Some(position) = self.portfolio.get_position(symbol) else {
+            return Ok(None);
+        };
+        let quantity = position.quantity.as_decimal().abs();
+
+        let side = match position.side {
+            PositionSide::Long => OrderSide::Sell,
+            PositionSide::Short => OrderSide::Buy,
+            PositionSide::Net => OrderSide::Sell,
+        };
+
+        Ok(Some(Order::new(
+            Uuid::new_v4(),
+            symbol.clone(),
+            side,
+            OrderType::Market,
+            Quantity::new(quantity)?,
+            Some(Price::new(price)?),
+        )))
+    }
+
     /// Close all open positions
     async fn close_all_positions(&mut self) -> Result<()> {
         let symbols: Vec<_> = self.portfolio.positions.keys().cloned().collect();
@@ -573,12 +1579,932 @@ impl BacktestEngine {
 
     /// Generate backtest results
     async fn generate_results(&self) -> Result<BacktestResult> {
-        BacktestResult::from_portfolio_and_trades(
+        let mut result = BacktestResult::from_portfolio_and_trades(
             &self.portfolio,
             &self.trades,
             self.config.initial_capital,
             self.config.start_time,
             self.config.end_time,
+        )?;
+        result.limit_fills = self.limit_fills.report();
+        result.gaps = self.gaps.report();
+        result.maintenance = self.maintenance.report();
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost_model::{CommissionModel, SlippageModel};
+    use crate::observers::{ExcursionObserver, ExcursionReport};
+    use chrono::{Duration, TimeZone};
+    use ea_okx_strategy::metrics::PerformanceMetrics;
+    use ea_okx_strategy::traits::MarketDataEvent;
+
+    fn zero_cost() -> CostModel {
+        CostModel {
+            commission: CommissionModel {
+                maker_rate: Decimal::ZERO,
+                taker_rate: Decimal::ZERO,
+                min_commission: Decimal::ZERO,
+            },
+            slippage: SlippageModel {
+                fixed_bps: Decimal::ZERO,
+                impact_coefficient: Decimal::ZERO,
+                min_slippage: Decimal::ZERO,
+            },
+        }
+    }
+
+    /// Enters once (long, or short when `short`) with fixed protective levels
+    struct BracketStrategy {
+        entered: bool,
+        short: bool,
+    }
+
+    #[async_trait]
+    impl Strategy for BracketStrategy {
+        async fn initialize(&mut self, _config: StrategyConfig) -> ea_okx_strategy::Result<()> {
+            Ok(())
+        }
+
+        async fn on_market_data(&mut self, _event: MarketDataEvent) -> ea_okx_strategy::Result<()> {
+            Ok(())
+        }
+
+        async fn generate_signal(&self) -> ea_okx_strategy::Result<Signal> {
+            if self.entered {
+                return Ok(Signal::hold());
+            }
+            let (mut signal, stop, target) = if self.short {
+                (Signal::sell(1.0), dec!(115), dec!(95))
+            } else {
+                (Signal::buy(1.0), dec!(95), dec!(110))
+            };
+            signal.stop_loss = Some(Price::new(stop).unwrap());
+            signal.take_profit = Some(Price::new(target).unwrap());
+            Ok(signal)
+        }
+
+        async fn on_order_fill(&mut self, _order: &Order) -> ea_okx_strategy::Result<()> {
+            self.entered = true;
+            Ok(())
+        }
+
+        async fn on_order_reject(
+            &mut self,
+            _order: &Order,
+            _reason: &str,
+        ) -> ea_okx_strategy::Result<()> {
+            Ok(())
+        }
+
+        fn get_metrics(&self) -> PerformanceMetrics {
+            PerformanceMetrics::default()
+        }
+
+        fn serialize_state(&self) -> ea_okx_strategy::Result<serde_json::Value> {
+            Ok(serde_json::json!({}))
+        }
+
+        fn deserialize_state(&mut self, _state: serde_json::Value) -> ea_okx_strategy::Result<()> {
+            Ok(())
+        }
+
+        async fn shutdown(&mut self) -> ea_okx_strategy::Result<()> {
+            Ok(())
+        }
+    }
+
+    async fn run_bracket(path: IntrabarPath) -> BacktestResult {
+        run_bracket_with(path, false, MarginConfig::default()).await
+    }
+
+    async fn run_bracket_with(
+        path: IntrabarPath,
+        short: bool,
+        margin: MarginConfig,
+    ) -> BacktestResult {
+        bracket_engine(path, short, margin, PositionSizing::Fixed(dec!(1000)))
+            .await
+            .run()
+            .await
+            .unwrap()
+    }
+
+    async fn bracket_engine(
+        path: IntrabarPath,
+        short: bool,
+        margin: MarginConfig,
+        position_sizing: PositionSizing,
+    ) -> BacktestEngine {
+        let symbol = Symbol::new("BTC-USDT").unwrap();
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let bar = |hour: i64, open, high, low, close| Candle {
+            symbol: symbol.clone(),
+            timestamp: start + Duration::hours(hour),
+            open,
+            high,
+            low,
+            close,
+            volume: dec!(1000),
+        };
+
+        // Entry signal, fill, then a bar spanning both the stop and the target
+        let candles = vec![
+            bar(0, dec!(100), dec!(101), dec!(99), dec!(100)),
+            bar(1, dec!(100), dec!(101), dec!(99), dec!(100)),
+            bar(2, dec!(100), dec!(112), dec!(93), dec!(100)),
+            bar(3, dec!(100), dec!(101), dec!(99), dec!(100)),
+        ];
+
+        let mut data = MockDataSource::new();
+        data.add_candles(symbol.clone(), candles);
+
+        let config = BacktestConfig {
+            start_time: start,
+            end_time: start + Duration::hours(3),
+            symbols: vec![symbol],
+            cost_model: zero_cost(),
+            position_sizing,
+            intrabar_path: path,
+            margin,
+            ..Default::default()
+        };
+
+        BacktestEngine::new(
+            config,
+            Box::new(BracketStrategy {
+                entered: false,
+                short,
+            }),
+            Box::new(data),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_intrabar_path_decides_exit() {
+        let conservative = run_bracket(IntrabarPath::Conservative).await;
+        assert_eq!(conservative.total_trades, 1);
+        assert_eq!(conservative.stop_loss_exits, 1);
+        assert_eq!(conservative.total_pnl, dec!(-50));
+
+        let optimistic = run_bracket(IntrabarPath::Optimistic).await;
+        assert_eq!(optimistic.take_profit_exits, 1);
+        assert_eq!(optimistic.total_pnl, dec!(100));
+    }
+
+    #[tokio::test]
+    async fn test_close_only_ignores_wicks() {
+        let result = run_bracket(IntrabarPath::CloseOnly).await;
+        assert_eq!(result.total_trades, 1);
+        assert_eq!(result.stop_loss_exits + result.take_profit_exits, 0);
+        assert_eq!(result.total_pnl, Decimal::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_short_entry_hits_target() {
+        let margin = MarginConfig {
+            allow_short: true,
+            ..Default::default()
+        };
+        let result = run_bracket_with(IntrabarPath::Conservative, true, margin).await;
+
+        assert_eq!(result.total_trades, 1);
+        assert_eq!(result.take_profit_exits, 1);
+        // 10 short at 100 covered at 95, less one hour of borrow interest
+        assert!(result.total_pnl < dec!(50));
+        assert!(result.total_pnl > dec!(49.9));
+    }
+
+    /// Counts the hooks it sees
+    #[derive(Default)]
+    struct HookCounter {
+        events: usize,
+        fills: usize,
+        days: Vec<NaiveDate>,
+    }
+
+    impl BacktestObserver for HookCounter {
+        fn name(&self) -> &str {
+            "hooks"
+        }
+
+        fn on_event(&mut self, _event: &MarketEvent, _ctx: &ObserverContext<'_>) {
+            self.events += 1;
+        }
+
+        fn on_fill(&mut self, _order: &Order, _fill: &Fill, ctx: &ObserverContext<'_>) {
+            // The entry is already in the portfolio, the exit already out
+            let held = ctx.portfolio.position_count() > 0;
+            assert_eq!(held, self.fills == 0);
+            self.fills += 1;
+        }
+
+        fn on_day_close(&mut self, day: NaiveDate, _ctx: &ObserverContext<'_>) {
+            self.days.push(day);
+        }
+
+        fn on_finish(&mut self, result: &BacktestResult) -> serde_json::Value {
+            serde_json::json!({
+                "events": self.events,
+                "fills": self.fills,
+                "days": self.days,
+                "trades": result.total_trades,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_observers_report_into_results() {
+        let result = bracket_engine(
+            IntrabarPath::CloseOnly,
+            false,
+            MarginConfig::default(),
+            PositionSizing::Fixed(dec!(1000)),
         )
+        .await
+        .with_observer(Box::new(HookCounter::default()))
+        .with_observer(Box::new(ExcursionObserver::new()))
+        .run()
+        .await
+        .unwrap();
+
+        assert_eq!(
+            result.analytics["hooks"],
+            serde_json::json!({ "events": 4, "fills": 2, "days": ["2024-01-01"], "trades": 1 })
+        );
+
+        // Entered at 100 on the second bar, then a bar from 93 to 112
+        let excursions = result
+            .observer_report::<ExcursionReport>(ExcursionObserver::NAME)
+            .unwrap();
+        assert_eq!(excursions.trades.len(), 1);
+        assert_eq!(excursions.avg_mae_pct, dec!(0.07));
+        assert_eq!(excursions.avg_mfe_pct, dec!(0.12));
+        assert_eq!(excursions.trades[0].return_pct, Decimal::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_short_rejected_on_spot() {
+        let result =
+            run_bracket_with(IntrabarPath::Conservative, true, MarginConfig::default()).await;
+        assert_eq!(result.total_trades, 0);
+        assert_eq!(result.total_pnl, Decimal::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_degenerate_kelly_sizing_fails_instead_of_panicking() {
+        let sizing = PositionSizing::Kelly {
+            win_rate: dec!(0.6),
+            win_loss_ratio: Decimal::ZERO,
+        };
+        let mut engine = bracket_engine(
+            IntrabarPath::Conservative,
+            false,
+            MarginConfig::default(),
+            sizing,
+        )
+        .await;
+
+        let err = engine.run().await.unwrap_err();
+        assert!(matches!(
+            err,
+            Error::CoreError(ea_okx_core::Error::DivisionByZero(_))
+        ));
+    }
+
+    /// Rests a single limit buy at `limit` after bar number `entry_bar`
+    struct LimitEntryStrategy {
+        limit: Decimal,
+        entry_bar: usize,
+        bars: usize,
+    }
+
+    #[async_trait]
+    impl Strategy for LimitEntryStrategy {
+        async fn initialize(&mut self, _config: StrategyConfig) -> ea_okx_strategy::Result<()> {
+            Ok(())
+        }
+
+        async fn on_market_data(&mut self, _event: MarketDataEvent) -> ea_okx_strategy::Result<()> {
+            self.bars += 1;
+            Ok(())
+        }
+
+        async fn generate_signal(&self) -> ea_okx_strategy::Result<Signal> {
+            if self.bars != self.entry_bar {
+                return Ok(Signal::hold());
+            }
+            let mut signal = Signal::buy(1.0);
+            signal.target_price = Some(Price::new(self.limit).unwrap());
+            Ok(signal)
+        }
+
+        async fn on_order_fill(&mut self, _order: &Order) -> ea_okx_strategy::Result<()> {
+            Ok(())
+        }
+
+        async fn on_order_reject(
+            &mut self,
+            _order: &Order,
+            _reason: &str,
+        ) -> ea_okx_strategy::Result<()> {
+            Ok(())
+        }
+
+        fn get_metrics(&self) -> PerformanceMetrics {
+            PerformanceMetrics::default()
+        }
+
+        fn serialize_state(&self) -> ea_okx_strategy::Result<serde_json::Value> {
+            Ok(serde_json::json!({}))
+        }
+
+        fn deserialize_state(&mut self, _state: serde_json::Value) -> ea_okx_strategy::Result<()> {
+            Ok(())
+        }
+
+        async fn shutdown(&mut self) -> ea_okx_strategy::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Limit buy at 99: touched in hour 1, traded through in hour 2
+    async fn run_limit_entry(model: LimitFillModel) -> (BacktestResult, Vec<ExecutionEvent>) {
+        run_limit_entry_with(model, Vec::new(), false).await
+    }
+
+    /// [`run_limit_entry`] under `maintenance`, optionally with ETH bars
+    /// alongside BTC's, in which case the order is sent on the third bar seen
+    async fn run_limit_entry_with(
+        model: LimitFillModel,
+        maintenance: Vec<MaintenanceWindow>,
+        with_eth: bool,
+    ) -> (BacktestResult, Vec<ExecutionEvent>) {
+        let symbol = Symbol::new("BTC-USDT").unwrap();
+        let eth = Symbol::new("ETH-USDT").unwrap();
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let bar = |symbol: &Symbol, hour: i64, low| Candle {
+            symbol: symbol.clone(),
+            timestamp: start + Duration::hours(hour),
+            open: dec!(100),
+            high: dec!(101),
+            low,
+            close: dec!(100),
+            volume: dec!(1000),
+        };
+
+        let mut data = MockDataSource::new();
+        data.add_candles(
+            symbol.clone(),
+            vec![
+                bar(&symbol, 0, dec!(100)),
+                bar(&symbol, 1, dec!(99)),
+                bar(&symbol, 2, dec!(97)),
+                bar(&symbol, 3, dec!(100)),
+            ],
+        );
+        let mut symbols = vec![symbol];
+        if with_eth {
+            data.add_candles(
+                eth.clone(),
+                (0..4).map(|hour| bar(&eth, hour, dec!(99))).collect(),
+            );
+            symbols.push(eth);
+        }
+
+        let config = BacktestConfig {
+            start_time: start,
+            end_time: start + Duration::hours(3),
+            symbols,
+            cost_model: zero_cost(),
+            position_sizing: PositionSizing::Fixed(dec!(990)),
+            limit_fill: model,
+            maintenance,
+            ..Default::default()
+        };
+
+        let mut engine = BacktestEngine::new(
+            config,
+            Box::new(LimitEntryStrategy {
+                limit: dec!(99),
+                entry_bar: if with_eth { 3 } else { 1 },
+                bars: 0,
+            }),
+            Box::new(data),
+        )
+        .await
+        .unwrap();
+        let result = engine.run().await.unwrap();
+        (result, engine.executions)
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_withholds_candles_and_fills() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let hour = |h: i64| start + Duration::hours(h);
+
+        // The order rests through the window and fills on the next bar
+        let window = MaintenanceWindow::new(hour(1), hour(2));
+        let (result, executions) =
+            run_limit_entry_with(LimitFillModel::Touch, vec![window.clone()], false).await;
+        assert_eq!(first_fill_time(&executions), Some(hour(2)));
+        assert_eq!(result.maintenance.withheld_candles(), 1);
+        assert!(result.summary().contains("Exchange Maintenance"));
+
+        // Cancelled as the window begins, it never fills
+        let (result, executions) = run_limit_entry_with(
+            LimitFillModel::Touch,
+            vec![window.cancelling_orders()],
+            false,
+        )
+        .await;
+        assert_eq!(first_fill_time(&executions), None);
+        assert_eq!(result.maintenance.cancelled_orders(), 1);
+        assert!(executions.iter().any(|e| matches!(
+            e,
+            ExecutionEvent::OrderCancelled { timestamp, .. } if *timestamp == hour(1)
+        )));
+
+        // ETH trades on while BTC is down, so the BTC order is refused
+        let btc_down = MaintenanceWindow::new(hour(1), hour(3))
+            .for_symbols(vec![Symbol::new("BTC-USDT").unwrap()]);
+        let (result, executions) =
+            run_limit_entry_with(LimitFillModel::Touch, vec![btc_down], true).await;
+        assert_eq!(result.total_trades, 0);
+        assert_eq!(result.maintenance.rejected_orders(), 1);
+        assert!(matches!(
+            &executions[..],
+            [ExecutionEvent::OrderRejected { reason, .. }] if reason == "Exchange under maintenance"
+        ));
+    }
+
+    fn first_fill_time(executions: &[ExecutionEvent]) -> Option<DateTime<Utc>> {
+        executions.iter().find_map(|e| match e {
+            ExecutionEvent::OrderFilled { timestamp, .. } => Some(*timestamp),
+            _ => None,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_queue_model_fills_later_than_touch() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+
+        let (touch, executions) = run_limit_entry(LimitFillModel::Touch).await;
+        assert_eq!(
+            first_fill_time(&executions),
+            Some(start + Duration::hours(1))
+        );
+        assert_eq!(touch.limit_fills.filled, 1);
+
+        let (queued, executions) = run_limit_entry(LimitFillModel::Queue {
+            trade_through: dec!(0.01),
+            level_volume_share: Decimal::ZERO,
+        })
+        .await;
+        assert_eq!(
+            first_fill_time(&executions),
+            Some(start + Duration::hours(2))
+        );
+        assert_eq!(queued.limit_fills.orders, 1);
+        assert_eq!(queued.limit_fills.touch_fills, 1);
+        assert_eq!(queued.limit_fills.filled, 1);
+        assert_eq!(queued.total_trades, 1);
+
+        let (missed, _) = run_limit_entry(LimitFillModel::Queue {
+            trade_through: dec!(0.05),
+            level_volume_share: Decimal::ZERO,
+        })
+        .await;
+        assert_eq!(missed.total_trades, 0);
+        assert_eq!(missed.limit_fills.missed_fills(), 1);
+        assert!(missed.summary().contains("Missed vs Touch: 1"));
+    }
+
+    type SeenCandles = Vec<(String, DateTime<Utc>)>;
+
+    /// Records the interval and timestamp of every candle it is fed,
+    /// long/short ratios as "ratio" and gaps as "gap N"
+    struct RecordingStrategy {
+        seen: std::sync::Arc<std::sync::Mutex<SeenCandles>>,
+    }
+
+    #[async_trait]
+    impl Strategy for RecordingStrategy {
+        async fn initialize(&mut self, _config: StrategyConfig) -> ea_okx_strategy::Result<()> {
+            Ok(())
+        }
+
+        async fn on_market_data(&mut self, event: MarketDataEvent) -> ea_okx_strategy::Result<()> {
+            let entry = match event {
+                MarketDataEvent::Candle {
+                    interval,
+                    timestamp,
+                    ..
+                } => (interval.to_string(), timestamp),
+                MarketDataEvent::LongShortRatio { timestamp, .. } => {
+                    ("ratio".to_string(), timestamp)
+                }
+                MarketDataEvent::Gap {
+                    missing_bars,
+                    timestamp,
+                    ..
+                } => (format!("gap {}", missing_bars), timestamp),
+                _ => return Ok(()),
+            };
+            self.seen.lock().unwrap().push(entry);
+            Ok(())
+        }
+
+        async fn generate_signal(&self) -> ea_okx_strategy::Result<Signal> {
+            Ok(Signal::hold())
+        }
+
+        async fn on_order_fill(&mut self, _order: &Order) -> ea_okx_strategy::Result<()> {
+            Ok(())
+        }
+
+        async fn on_order_reject(
+            &mut self,
+            _order: &Order,
+            _reason: &str,
+        ) -> ea_okx_strategy::Result<()> {
+            Ok(())
+        }
+
+        fn get_metrics(&self) -> PerformanceMetrics {
+            PerformanceMetrics::default()
+        }
+
+        fn serialize_state(&self) -> ea_okx_strategy::Result<serde_json::Value> {
+            Ok(serde_json::json!({}))
+        }
+
+        fn deserialize_state(&mut self, _state: serde_json::Value) -> ea_okx_strategy::Result<()> {
+            Ok(())
+        }
+
+        async fn shutdown(&mut self) -> ea_okx_strategy::Result<()> {
+            Ok(())
+        }
+    }
+
+    async fn run_multi_timeframe(
+        higher_timeframes: &[Interval],
+        streaming: Option<StreamingConfig>,
+    ) -> (Result<BacktestResult>, SeenCandles) {
+        let symbol = Symbol::new("BTC-USDT").unwrap();
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let bars = |count: i64, hours: i64| -> Vec<Candle> {
+            (0..count)
+                .map(|i| Candle {
+                    symbol: symbol.clone(),
+                    timestamp: start + Duration::hours(i * hours),
+                    open: dec!(100),
+                    high: dec!(101),
+                    low: dec!(99),
+                    close: dec!(100),
+                    volume: dec!(1000),
+                })
+                .collect()
+        };
+
+        let mut data = MockDataSource::new();
+        data.add_candles(symbol.clone(), bars(10, 1));
+        data.add_interval_candles(symbol.clone(), Interval::FourHours, bars(3, 4));
+        data.add_positioning(
+            symbol.clone(),
+            vec![PositioningSample {
+                symbol: symbol.clone(),
+                timestamp: start + Duration::minutes(150),
+                value: PositioningValue::LongShortRatio { ratio: dec!(1.8) },
+            }],
+        );
+
+        let config = BacktestConfig {
+            start_time: start,
+            end_time: start + Duration::hours(10),
+            symbols: vec![symbol],
+            higher_timeframes: higher_timeframes.to_vec(),
+            cost_model: zero_cost(),
+            streaming,
+            ..Default::default()
+        };
+
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let strategy = RecordingStrategy { seen: seen.clone() };
+        let mut engine = BacktestEngine::new(config, Box::new(strategy), Box::new(data))
+            .await
+            .unwrap();
+        let result = engine.run().await;
+        let seen = seen.lock().unwrap().clone();
+        (result, seen)
+    }
+
+    #[tokio::test]
+    async fn test_higher_timeframe_delivered_after_close() {
+        let (result, seen) = run_multi_timeframe(&[Interval::FourHours], None).await;
+        result.unwrap();
+
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let position = |interval: &str, hour: i64| {
+            seen.iter()
+                .position(|(i, ts)| i == interval && *ts == start + Duration::hours(hour))
+                .unwrap()
+        };
+
+        // 00:00 4H bar: after the 03:00 hourly bar, before the 04:00 one
+        assert!(position("4H", 0) > position("1H", 3));
+        assert!(position("4H", 0) < position("1H", 4));
+        assert!(position("4H", 4) < position("1H", 8));
+        // The 08:00 bar closes at 12:00, after the last hourly bar
+        assert!(
+            !seen
+                .iter()
+                .any(|(i, ts)| i == "4H" && *ts == start + Duration::hours(8))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_positioning_replayed_in_time_order() {
+        let (result, seen) = run_multi_timeframe(&[], None).await;
+        result.unwrap();
+
+        let names: Vec<&str> = seen.iter().map(|(i, _)| i.as_str()).collect();
+        assert_eq!(names.iter().filter(|i| **i == "ratio").count(), 1);
+        // Known at 02:30, between the 02:00 and 03:00 bars
+        assert_eq!(names[3], "ratio");
+    }
+
+    #[tokio::test]
+    async fn test_misaligned_higher_timeframe_is_rejected() {
+        let (result, seen) = run_multi_timeframe(&[Interval::ThirtyMinutes], None).await;
+        assert!(matches!(result, Err(Error::InvalidConfig(_))));
+        assert!(seen.is_empty());
+
+        let (result, _) = run_multi_timeframe(&[Interval::OneMonth], None).await;
+        assert!(matches!(result, Err(Error::InvalidConfig(_))));
+    }
+
+    #[tokio::test]
+    async fn test_streaming_replays_like_preloading() {
+        let (preloaded, expected) = run_multi_timeframe(&[Interval::FourHours], None).await;
+        let streaming = StreamingConfig {
+            chunk_size: 3,
+            prefetch_chunks: 1,
+        };
+        let (streamed, seen) = run_multi_timeframe(&[Interval::FourHours], Some(streaming)).await;
+
+        assert_eq!(seen, expected);
+        assert_eq!(
+            streamed.unwrap().final_equity,
+            preloaded.unwrap().final_equity
+        );
+    }
+
+    /// Replays hourly BTC bars at 00-01 and 04-05 and ETH bars at 00-05,
+    /// returning the candles and gaps the strategy saw as (symbol or gap, hour)
+    async fn run_with_gaps(
+        policy: GapPolicy,
+        streaming: Option<StreamingConfig>,
+    ) -> (Result<BacktestResult>, Vec<(String, i64)>) {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let bars = |symbol: &Symbol, hours: &[i64]| -> Vec<Candle> {
+            hours
+                .iter()
+                .map(|hour| Candle {
+                    symbol: symbol.clone(),
+                    timestamp: start + Duration::hours(*hour),
+                    open: dec!(100),
+                    high: dec!(101),
+                    low: dec!(99),
+                    close: dec!(100) + Decimal::from(*hour),
+                    volume: dec!(1000),
+                })
+                .collect()
+        };
+        let btc = Symbol::new("BTC-USDT").unwrap();
+        let eth = Symbol::new("ETH-USDT").unwrap();
+
+        let mut data = MockDataSource::new();
+        data.add_candles(btc.clone(), bars(&btc, &[0, 1, 4, 5]));
+        data.add_candles(eth.clone(), bars(&eth, &[0, 1, 2, 3, 4, 5]));
+
+        let config = BacktestConfig {
+            start_time: start,
+            end_time: start + Duration::hours(6),
+            symbols: vec![btc, eth],
+            cost_model: zero_cost(),
+            gap_policy: policy,
+            streaming,
+            ..Default::default()
+        };
+
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let strategy = RecordingStrategy { seen: seen.clone() };
+        let mut engine = BacktestEngine::new(config, Box::new(strategy), Box::new(data))
+            .await
+            .unwrap();
+        let result = engine.run().await;
+        let seen = seen
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, ts)| (name.clone(), (*ts - start).num_hours()))
+            .collect();
+        (result, seen)
+    }
+
+    #[tokio::test]
+    async fn test_gap_policies() {
+        let hours = |seen: &[(String, i64)], name: &str| -> Vec<i64> {
+            seen.iter()
+                .filter(|(n, _)| n == name)
+                .map(|(_, hour)| *hour)
+                .collect()
+        };
+
+        // Skip: the strategy is told about the two missing bars before 04:00
+        let (result, seen) = run_with_gaps(GapPolicy::Skip, None).await;
+        let result = result.unwrap();
+        assert_eq!(seen.iter().filter(|(n, _)| n == "1H").count(), 10);
+        assert_eq!(hours(&seen, "gap 2"), [4]);
+        assert_eq!(result.gaps.policy, GapPolicy::Skip);
+        assert_eq!(result.gaps.missing_bars(), 2);
+        assert_eq!(result.gaps.filled_bars, 0);
+
+        // Forward fill: flat bars at 02:00 and 03:00 keep the series regular,
+        // each replayed before the next hour's events
+        let (result, seen) = run_with_gaps(GapPolicy::ForwardFill, None).await;
+        let result = result.unwrap();
+        assert_eq!(seen.iter().filter(|(n, _)| n == "1H").count(), 12);
+        assert!(seen.iter().all(|(n, _)| !n.starts_with("gap")));
+        assert_eq!(hours(&seen, "1H"), [0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5]);
+        assert_eq!(result.gaps.filled_bars, 2);
+        assert_eq!(result.gaps.gaps.len(), 1);
+        assert!(result.summary().contains("Filled Bars: 2"));
+
+        let streaming = StreamingConfig {
+            chunk_size: 2,
+            prefetch_chunks: 1,
+        };
+        let (_, streamed) = run_with_gaps(GapPolicy::ForwardFill, Some(streaming)).await;
+        assert_eq!(streamed, seen);
+
+        // Abort: nothing is replayed
+        let (result, seen) = run_with_gaps(GapPolicy::Abort, None).await;
+        assert!(matches!(result, Err(Error::DataGaps(report)) if report.missing_bars() == 2));
+        assert!(seen.is_empty());
+    }
+
+    /// Opens a sized long BTC / short ETH pair once both have a price and
+    /// closes both legs a bar later
+    struct PairLegsStrategy {
+        bars: usize,
+    }
+
+    #[async_trait]
+    impl Strategy for PairLegsStrategy {
+        async fn initialize(&mut self, _config: StrategyConfig) -> ea_okx_strategy::Result<()> {
+            Ok(())
+        }
+
+        async fn on_market_data(&mut self, _event: MarketDataEvent) -> ea_okx_strategy::Result<()> {
+            self.bars += 1;
+            Ok(())
+        }
+
+        async fn generate_signal(&self) -> ea_okx_strategy::Result<Signal> {
+            Ok(Signal::hold())
+        }
+
+        async fn generate_signals(&self) -> ea_okx_strategy::Result<Vec<Signal>> {
+            let leg = |mut signal: Signal, symbol: &str, quantity: Option<Decimal>| {
+                signal.suggested_quantity = quantity.map(|q| Quantity::new(q).unwrap());
+                signal.metadata = serde_json::json!({ "symbol": symbol });
+                signal
+            };
+            let close = |signal_type| Signal {
+                signal_type,
+                ..Signal::hold()
+            };
+            Ok(match self.bars {
+                2 => vec![
+                    leg(Signal::buy(1.0), "BTC-USDT", Some(dec!(0.5))),
+                    leg(Signal::sell(1.0), "ETH-USDT", Some(dec!(2))),
+                ],
+                6 => vec![
+                    leg(close(SignalType::CloseLong), "BTC-USDT", None),
+                    leg(close(SignalType::CloseShort), "ETH-USDT", None),
+                ],
+                _ => Vec::new(),
+            })
+        }
+
+        async fn on_order_fill(&mut self, _order: &Order) -> ea_okx_strategy::Result<()> {
+            Ok(())
+        }
+
+        async fn on_order_reject(
+            &mut self,
+            _order: &Order,
+            _reason: &str,
+        ) -> ea_okx_strategy::Result<()> {
+            Ok(())
+        }
+
+        fn get_metrics(&self) -> PerformanceMetrics {
+            PerformanceMetrics::default()
+        }
+
+        fn serialize_state(&self) -> ea_okx_strategy::Result<serde_json::Value> {
+            Ok(serde_json::json!({}))
+        }
+
+        fn deserialize_state(&mut self, _state: serde_json::Value) -> ea_okx_strategy::Result<()> {
+            Ok(())
+        }
+
+        async fn shutdown(&mut self) -> ea_okx_strategy::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_multi_leg_signals_use_suggested_quantities_and_exit_together() {
+        let btc = Symbol::new("BTC-USDT").unwrap();
+        let eth = Symbol::new("ETH-USDT").unwrap();
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let bars = |symbol: &Symbol, close| {
+            (0..4)
+                .map(|hour| Candle {
+                    symbol: symbol.clone(),
+                    timestamp: start + Duration::hours(hour),
+                    open: close,
+                    high: close,
+                    low: close,
+                    close,
+                    volume: dec!(1000),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut data = MockDataSource::new();
+        data.add_candles(btc.clone(), bars(&btc, dec!(100)));
+        data.add_candles(eth.clone(), bars(&eth, dec!(10)));
+
+        let config = BacktestConfig {
+            start_time: start,
+            end_time: start + Duration::hours(3),
+            symbols: vec![btc.clone(), eth.clone()],
+            cost_model: zero_cost(),
+            max_positions: 2,
+            position_sizing: PositionSizing::Fixed(dec!(5000)),
+            margin: MarginConfig {
+                allow_short: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut engine = BacktestEngine::new(
+            config,
+            Box::new(PairLegsStrategy { bars: 0 }),
+            Box::new(data),
+        )
+        .await
+        .unwrap();
+        engine.run().await.unwrap();
+
+        let mut fills: Vec<(DateTime<Utc>, Symbol, OrderSide, Decimal)> = engine
+            .executions
+            .iter()
+            .filter_map(|e| match e {
+                ExecutionEvent::OrderFilled {
+                    symbol,
+                    side,
+                    filled_quantity,
+                    timestamp,
+                    ..
+                } => Some((*timestamp, symbol.clone(), *side, *filled_quantity)),
+                _ => None,
+            })
+            .collect();
+        // Pending orders of one bar fill in no particular order
+        fills.sort_by(|a, b| (a.0, a.1.as_str()).cmp(&(b.0, b.1.as_str())));
+        let at = |hour| start + Duration::hours(hour);
+        // Both legs close at the position limit, before the end of the run
+        assert_eq!(
+            fills,
+            vec![
+                (at(1), btc.clone(), OrderSide::Buy, dec!(0.5)),
+                (at(1), eth.clone(), OrderSide::Sell, dec!(2)),
+                (at(2), btc, OrderSide::Sell, dec!(0.5)),
+                (at(2), eth, OrderSide::Buy, dec!(2)),
+            ]
+        );
     }
 }
@@ -1,3 +1,4 @@
+use crate::gaps::GapReport;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -25,8 +26,20 @@ pub enum Error {
     #[error("Serialization error: {0}")]
     SerializationError(#[from] serde_json::Error),
 
+    #[error("Look-ahead bias: {0}")]
+    LookAheadBias(String),
+
     #[error("Invalid state transition: {0}")]
     InvalidStateTransition(String),
+
+    #[error("Backtest run not found: {0}")]
+    RunNotFound(String),
+
+    #[error("Backtest registry error: {0}")]
+    RegistryError(String),
+
+    #[error("Candle data has gaps: {}", .0.summary())]
+    DataGaps(GapReport),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
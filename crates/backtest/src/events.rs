@@ -6,7 +6,8 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 // Import Candle from engine module
-use crate::engine::Candle;
+use crate::engine::{Candle, PositioningSample};
+use crate::intrabar::ExitTrigger;
 
 /// Market event types that can occur during backtesting
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +31,16 @@ pub enum MarketEvent {
         asks: Vec<(Decimal, Decimal)>,
         timestamp: DateTime<Utc>,
     },
+
+    /// Perpetual funding settlement for the underlying symbol
+    FundingRate {
+        symbol: Symbol,
+        rate: Decimal,
+        timestamp: DateTime<Utc>,
+    },
+
+    /// Open interest, taker volume or long/short ratio became known
+    Positioning(PositioningSample),
 }
 
 impl MarketEvent {
@@ -38,6 +49,8 @@ impl MarketEvent {
             MarketEvent::Candle(candle) => candle.timestamp,
             MarketEvent::Trade { timestamp, .. } => *timestamp,
             MarketEvent::OrderBook { timestamp, .. } => *timestamp,
+            MarketEvent::FundingRate { timestamp, .. } => *timestamp,
+            MarketEvent::Positioning(sample) => sample.timestamp,
         }
     }
 
@@ -46,6 +59,8 @@ impl MarketEvent {
             MarketEvent::Candle(candle) => &candle.symbol,
             MarketEvent::Trade { symbol, .. } => symbol,
             MarketEvent::OrderBook { symbol, .. } => symbol,
+            MarketEvent::FundingRate { symbol, .. } => symbol,
+            MarketEvent::Positioning(sample) => &sample.symbol,
         }
     }
 }
@@ -124,9 +139,14 @@ pub struct Trade {
     pub slippage: Decimal,
     pub max_adverse_excursion: Decimal,   // MAE
     pub max_favorable_excursion: Decimal, // MFE
+
+    /// Stop-loss or take-profit that closed the trade, if any
+    #[serde(default)]
+    pub exit_trigger: Option<ExitTrigger>,
 }
 
 impl Trade {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         strategy_id: Uuid,
         symbol: Symbol,
@@ -152,6 +172,7 @@ impl Trade {
             slippage,
             max_adverse_excursion: Decimal::ZERO,
             max_favorable_excursion: Decimal::ZERO,
+            exit_trigger: None,
         }
     }
 
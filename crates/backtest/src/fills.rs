@@ -0,0 +1,261 @@
+//! Resting limit order fills
+//!
+//! A candle only says that a price was traded, not how much traded there or
+//! where a new order would have joined the queue. The naive assumption fills
+//! a limit order as soon as a bar touches its price, which overstates fills
+//! for passive strategies: at the touch the order is usually still behind
+//! the resting queue. [`LimitFillModel::Queue`] only fills once the price
+//! trades through the level, or once enough volume has traded at the level
+//! to work through the order.
+//!
+//! Whatever model is configured, [`LimitFillTracker`] also records which
+//! orders the touch model would have filled, so results show how many fills
+//! the queue assumption took away.
+
+use crate::engine::Candle;
+use ea_okx_core::models::OrderSide;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// Assumption deciding when a resting limit order fills
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitFillModel {
+    /// Fill as soon as a bar touches the limit price
+    #[default]
+    Touch,
+
+    /// Fill only when the price trades through the limit by more than
+    /// `trade_through` (a fraction of the limit), or once the volume traded
+    /// at the level exceeds the order size
+    Queue {
+        trade_through: Decimal,
+
+        /// Fraction of a touching bar's volume assumed to trade at the level
+        level_volume_share: Decimal,
+    },
+}
+
+/// Price range and volume a pending order is checked against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BarRange {
+    pub low: Decimal,
+    pub high: Decimal,
+    pub volume: Decimal,
+}
+
+impl BarRange {
+    /// A single traded price with no volume information
+    pub fn point(price: Decimal) -> Self {
+        Self {
+            low: price,
+            high: price,
+            volume: Decimal::ZERO,
+        }
+    }
+}
+
+impl From<&Candle> for BarRange {
+    fn from(candle: &Candle) -> Self {
+        Self {
+            low: candle.low,
+            high: candle.high,
+            volume: candle.volume,
+        }
+    }
+}
+
+/// Whether `bar` reached a `side` limit at `limit`
+fn touches(side: OrderSide, limit: Decimal, bar: &BarRange) -> bool {
+    match side {
+        OrderSide::Buy => bar.low <= limit,
+        OrderSide::Sell => bar.high >= limit,
+    }
+}
+
+/// Whether `bar` traded beyond a `side` limit by more than `epsilon`
+fn trades_through(side: OrderSide, limit: Decimal, epsilon: Decimal, bar: &BarRange) -> bool {
+    match side {
+        OrderSide::Buy => bar.low < limit * (Decimal::ONE - epsilon),
+        OrderSide::Sell => bar.high > limit * (Decimal::ONE + epsilon),
+    }
+}
+
+/// Limit fills under the naive touch model and the configured one
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LimitFillReport {
+    pub model: LimitFillModel,
+
+    /// Limit orders placed
+    pub orders: usize,
+
+    /// Orders the touch model would have filled
+    pub touch_fills: usize,
+
+    /// Orders filled under the configured model
+    pub filled: usize,
+}
+
+impl LimitFillReport {
+    /// Touched orders the configured model left unfilled
+    pub fn missed_fills(&self) -> usize {
+        self.touch_fills.saturating_sub(self.filled)
+    }
+}
+
+/// Per-order queue state for resting limit orders
+#[derive(Debug, Default)]
+pub struct LimitFillTracker {
+    model: LimitFillModel,
+    orders: usize,
+    touched: HashSet<Uuid>,
+    filled: usize,
+
+    /// Volume assumed traded at each order's level since it was placed
+    level_volume: HashMap<Uuid, Decimal>,
+}
+
+impl LimitFillTracker {
+    pub fn new(model: LimitFillModel) -> Self {
+        Self {
+            model,
+            ..Default::default()
+        }
+    }
+
+    /// Start tracking a newly placed limit order
+    pub fn place(&mut self, order_id: Uuid) {
+        self.orders += 1;
+        self.level_volume.insert(order_id, Decimal::ZERO);
+    }
+
+    /// Check a resting order against `bar`; true when it fills
+    pub fn check(
+        &mut self,
+        order_id: Uuid,
+        side: OrderSide,
+        limit: Decimal,
+        quantity: Decimal,
+        bar: &BarRange,
+    ) -> bool {
+        if !touches(side, limit, bar) {
+            return false;
+        }
+        self.touched.insert(order_id);
+
+        let fills = match self.model {
+            LimitFillModel::Touch => true,
+            LimitFillModel::Queue {
+                trade_through,
+                level_volume_share,
+            } => {
+                let traded = self.level_volume.entry(order_id).or_default();
+                *traded += bar.volume * level_volume_share;
+                trades_through(side, limit, trade_through, bar) || *traded > quantity
+            }
+        };
+
+        if fills {
+            self.filled += 1;
+            self.level_volume.remove(&order_id);
+        }
+        fills
+    }
+
+    pub fn report(&self) -> LimitFillReport {
+        LimitFillReport {
+            model: self.model,
+            orders: self.orders,
+            touch_fills: self.touched.len(),
+            filled: self.filled,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn bar(low: Decimal, high: Decimal, volume: Decimal) -> BarRange {
+        BarRange { low, high, volume }
+    }
+
+    fn queue() -> LimitFillTracker {
+        LimitFillTracker::new(LimitFillModel::Queue {
+            trade_through: dec!(0.001),
+            level_volume_share: dec!(0.1),
+        })
+    }
+
+    #[test]
+    fn test_touch_fills_at_the_level() {
+        let mut tracker = LimitFillTracker::new(LimitFillModel::Touch);
+        let order = Uuid::new_v4();
+        tracker.place(order);
+
+        let limit = dec!(100);
+        assert!(!tracker.check(
+            order,
+            OrderSide::Buy,
+            limit,
+            dec!(1),
+            &bar(dec!(101), dec!(102), dec!(5))
+        ));
+        assert!(tracker.check(
+            order,
+            OrderSide::Buy,
+            limit,
+            dec!(1),
+            &bar(dec!(100), dec!(102), dec!(5))
+        ));
+        assert_eq!(tracker.report().filled, 1);
+        assert_eq!(tracker.report().missed_fills(), 0);
+    }
+
+    #[test]
+    fn test_queue_needs_trade_through_or_volume() {
+        let mut tracker = queue();
+        let (touched, through) = (Uuid::new_v4(), Uuid::new_v4());
+        tracker.place(touched);
+        tracker.place(through);
+
+        // A sell at 100: touched without trading through 100.1, 0.5 of 1
+        // traded at the level
+        let touch = bar(dec!(99), dec!(100.1), dec!(5));
+        assert!(!tracker.check(touched, OrderSide::Sell, dec!(100), dec!(1), &touch));
+        // Another 0.5 only reaches the order size
+        assert!(!tracker.check(touched, OrderSide::Sell, dec!(100), dec!(1), &touch));
+        assert!(tracker.check(touched, OrderSide::Sell, dec!(100), dec!(1), &touch));
+
+        let spike = bar(dec!(99), dec!(100.2), Decimal::ZERO);
+        assert!(tracker.check(through, OrderSide::Sell, dec!(100), dec!(1), &spike));
+
+        let report = tracker.report();
+        assert_eq!(report.orders, 2);
+        assert_eq!(report.touch_fills, 2);
+        assert_eq!(report.filled, 2);
+    }
+
+    #[test]
+    fn test_report_counts_fills_the_touch_model_would_have_made() {
+        let mut tracker = queue();
+        let order = Uuid::new_v4();
+        tracker.place(order);
+
+        assert!(!tracker.check(
+            order,
+            OrderSide::Buy,
+            dec!(100),
+            dec!(10),
+            &bar(dec!(100), dec!(101), dec!(5))
+        ));
+
+        let report = tracker.report();
+        assert_eq!(report.touch_fills, 1);
+        assert_eq!(report.filled, 0);
+        assert_eq!(report.missed_fills(), 1);
+    }
+}
@@ -0,0 +1,362 @@
+//! Missing candles in the base series
+//!
+//! Exchanges and collectors drop bars: during outages, on illiquid pairs, or
+//! wherever a download was interrupted. Replaying the data as it is means the
+//! strategy simply sees fewer events, so an indicator counting bars quietly
+//! stretches over more time than it was configured for. [`GapPolicy`]
+//! decides what a run does instead:
+//!
+//! - [`GapPolicy::Skip`] replays only the candles present, but hands the
+//!   strategy a [`MarketDataEvent::Gap`] before the candle that ends a gap so
+//!   time-based indicators can account for the bars they missed
+//! - [`GapPolicy::ForwardFill`] replays a synthetic zero-volume candle at the
+//!   previous close for every missing bar
+//! - [`GapPolicy::Abort`] refuses to run, reporting every gap found, except
+//!   gaps lying inside a configured [`MaintenanceWindow`], which are expected
+//!
+//! Gaps are measured between a symbol's own candles on the base interval;
+//! bars missing before a symbol's first candle or after its last are not
+//! gaps, and monthly bars, having no fixed length, are never checked. Whatever the policy, the gaps a run saw are reported in
+//! [`BacktestResult::gaps`].
+//!
+//! [`MarketDataEvent::Gap`]: ea_okx_strategy::traits::MarketDataEvent::Gap
+//! [`BacktestResult::gaps`]: crate::results::BacktestResult::gaps
+//! [`MaintenanceWindow`]: crate::maintenance::MaintenanceWindow
+
+use crate::engine::Candle;
+use crate::error::{Error, Result};
+use crate::maintenance::MaintenanceWindow;
+use chrono::{DateTime, Duration, Utc};
+use ea_okx_core::{Interval, Symbol};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// How a run treats missing base-interval candles
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GapPolicy {
+    /// Replay the candles present and tell the strategy how many bars were
+    /// missed before each one that follows a gap
+    #[default]
+    Skip,
+
+    /// Replay a flat, zero-volume candle at the previous close for every
+    /// missing bar
+    ForwardFill,
+
+    /// Fail the run, listing the gaps
+    Abort,
+}
+
+/// A run of missing bars in one symbol's candles
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CandleGap {
+    pub symbol: Symbol,
+
+    /// Open time of the last candle before the gap
+    pub last_before: DateTime<Utc>,
+
+    /// Open time of the candle ending the gap
+    pub resumed_at: DateTime<Utc>,
+
+    /// Bars missing in between
+    pub missing_bars: u32,
+}
+
+/// Gaps seen during a run and how they were handled
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GapReport {
+    pub policy: GapPolicy,
+
+    pub gaps: Vec<CandleGap>,
+
+    /// Synthetic candles replayed under [`GapPolicy::ForwardFill`]; includes
+    /// bars filled after a symbol's data ran out while others continued
+    pub filled_bars: usize,
+}
+
+impl GapReport {
+    /// Bars missing across all gaps
+    pub fn missing_bars(&self) -> u64 {
+        self.gaps.iter().map(|g| u64::from(g.missing_bars)).sum()
+    }
+
+    /// One-line description naming the first few gaps
+    pub fn summary(&self) -> String {
+        const LISTED: usize = 5;
+
+        let mut summary = format!(
+            "{} gap(s), {} bar(s) missing",
+            self.gaps.len(),
+            self.missing_bars()
+        );
+        for (i, gap) in self.gaps.iter().take(LISTED).enumerate() {
+            summary.push_str(if i == 0 { ": " } else { "; " });
+            summary.push_str(&format!(
+                "{} {} bar(s) after {}",
+                gap.symbol.as_str(),
+                gap.missing_bars,
+                gap.last_before
+            ));
+        }
+        if self.gaps.len() > LISTED {
+            summary.push_str(&format!("; and {} more", self.gaps.len() - LISTED));
+        }
+        summary
+    }
+}
+
+/// Gaps in one symbol's timestamp-ordered candles
+pub fn find_gaps(candles: &[Candle], bar: Duration) -> Vec<CandleGap> {
+    candles
+        .windows(2)
+        .filter_map(|pair| gap_between(&pair[0].symbol, pair[0].timestamp, pair[1].timestamp, bar))
+        .collect()
+}
+
+fn gap_between(
+    symbol: &Symbol,
+    last_before: DateTime<Utc>,
+    resumed_at: DateTime<Utc>,
+    bar: Duration,
+) -> Option<CandleGap> {
+    let bars = (resumed_at - last_before).num_seconds() / bar.num_seconds().max(1);
+    (bars > 1).then(|| CandleGap {
+        symbol: symbol.clone(),
+        last_before,
+        resumed_at,
+        missing_bars: u32::try_from(bars - 1).unwrap_or(u32::MAX),
+    })
+}
+
+/// Follows each symbol's base candles through a run
+#[derive(Debug)]
+pub struct GapTracker {
+    /// Base bar length; `None` when it varies
+    bar: Option<Duration>,
+
+    /// Open time of each symbol's last real candle
+    last_real: HashMap<Symbol, DateTime<Utc>>,
+
+    /// Last candle replayed per symbol, synthetic or not
+    last_replayed: HashMap<Symbol, Candle>,
+
+    report: GapReport,
+
+    /// Downtime explaining gaps that should not abort a run
+    maintenance: Vec<MaintenanceWindow>,
+}
+
+impl GapTracker {
+    pub fn new(policy: GapPolicy, interval: Interval) -> Self {
+        Self {
+            bar: interval.duration(),
+            last_real: HashMap::new(),
+            last_replayed: HashMap::new(),
+            report: GapReport {
+                policy,
+                ..Default::default()
+            },
+            maintenance: Vec::new(),
+        }
+    }
+
+    /// Expect gaps inside `windows`
+    pub fn with_maintenance(mut self, windows: &[MaintenanceWindow]) -> Self {
+        self.maintenance = windows.to_vec();
+        self
+    }
+
+    pub fn policy(&self) -> GapPolicy {
+        self.report.policy
+    }
+
+    /// Synthetic candles due before an event at `now`, in time order
+    ///
+    /// A bar that should have opened before `now` and has not been replayed
+    /// is missing, since events arrive in time order. Empty unless the policy
+    /// is [`GapPolicy::ForwardFill`].
+    pub fn fill_before(&mut self, now: DateTime<Utc>) -> Vec<Candle> {
+        let Some(bar) = self
+            .bar
+            .filter(|_| self.report.policy == GapPolicy::ForwardFill)
+        else {
+            return Vec::new();
+        };
+
+        let mut filled = Vec::new();
+        for last in self.last_replayed.values_mut() {
+            while last.timestamp + bar < now {
+                *last = Candle {
+                    symbol: last.symbol.clone(),
+                    timestamp: last.timestamp + bar,
+                    open: last.close,
+                    high: last.close,
+                    low: last.close,
+                    close: last.close,
+                    volume: Decimal::ZERO,
+                };
+                filled.push(last.clone());
+            }
+        }
+        filled.sort_by(|a, b| {
+            a.timestamp
+                .cmp(&b.timestamp)
+                .then_with(|| a.symbol.as_str().cmp(b.symbol.as_str()))
+        });
+        self.report.filled_bars += filled.len();
+        filled
+    }
+
+    /// Record a real base candle, returning the gap it ends
+    ///
+    /// Fails under [`GapPolicy::Abort`] when the candle ends a gap not
+    /// explained by maintenance.
+    pub fn observe(&mut self, candle: &Candle) -> Result<Option<CandleGap>> {
+        let gap = self
+            .last_real
+            .insert(candle.symbol.clone(), candle.timestamp)
+            .zip(self.bar)
+            .and_then(|(last, bar)| gap_between(&candle.symbol, last, candle.timestamp, bar));
+        self.last_replayed
+            .insert(candle.symbol.clone(), candle.clone());
+
+        if let Some(gap) = &gap {
+            self.report.gaps.push(gap.clone());
+            if self.report.policy == GapPolicy::Abort && !self.during_maintenance(gap) {
+                return Err(Error::DataGaps(self.report.clone()));
+            }
+        }
+        Ok(gap)
+    }
+
+    /// Whether every bar missing in `gap` fell inside a maintenance window
+    pub fn during_maintenance(&self, gap: &CandleGap) -> bool {
+        self.bar.is_some_and(|bar| {
+            self.maintenance
+                .iter()
+                .any(|window| window.explains(gap, bar))
+        })
+    }
+
+    pub fn report(&self) -> GapReport {
+        self.report.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+
+    fn candle(symbol: &str, hour: i64, close: Decimal) -> Candle {
+        Candle {
+            symbol: Symbol::new(symbol).unwrap(),
+            timestamp: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::hours(hour),
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: dec!(10),
+        }
+    }
+
+    #[test]
+    fn test_find_gaps() {
+        let candles = [
+            candle("BTC-USDT", 0, dec!(100)),
+            candle("BTC-USDT", 1, dec!(101)),
+            candle("BTC-USDT", 4, dec!(102)),
+            candle("BTC-USDT", 6, dec!(103)),
+        ];
+        let gaps = find_gaps(&candles, Duration::hours(1));
+        assert_eq!(gaps.len(), 2);
+        assert_eq!(gaps[0].missing_bars, 2);
+        assert_eq!(gaps[0].last_before, candles[1].timestamp);
+        assert_eq!(gaps[0].resumed_at, candles[2].timestamp);
+        assert_eq!(gaps[1].missing_bars, 1);
+
+        let report = GapReport {
+            policy: GapPolicy::Abort,
+            gaps,
+            filled_bars: 0,
+        };
+        assert_eq!(report.missing_bars(), 3);
+        assert!(
+            report
+                .summary()
+                .starts_with("2 gap(s), 3 bar(s) missing: BTC-USDT 2 bar(s)")
+        );
+    }
+
+    #[test]
+    fn test_tracker_fills_and_aborts() {
+        let mut tracker = GapTracker::new(GapPolicy::ForwardFill, Interval::OneHour);
+        assert!(
+            tracker
+                .observe(&candle("BTC-USDT", 0, dec!(100)))
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            tracker
+                .observe(&candle("ETH-USDT", 0, dec!(10)))
+                .unwrap()
+                .is_none()
+        );
+
+        // ETH has its bar at 1 but BTC only resumes at 3
+        let eth = candle("ETH-USDT", 1, dec!(11));
+        assert!(tracker.fill_before(eth.timestamp).is_empty());
+        tracker.observe(&eth).unwrap();
+
+        let btc = candle("BTC-USDT", 3, dec!(104));
+        let filled = tracker.fill_before(btc.timestamp);
+        let replayed: Vec<_> = filled
+            .iter()
+            .map(|c| (c.symbol.base(), c.timestamp.format("%H").to_string()))
+            .collect();
+        assert_eq!(
+            replayed,
+            [
+                ("BTC", "01".to_string()),
+                ("BTC", "02".to_string()),
+                ("ETH", "02".to_string())
+            ]
+        );
+        assert!(filled.iter().all(|c| c.volume.is_zero()));
+        assert_eq!(filled[0].close, dec!(100));
+
+        let gap = tracker.observe(&btc).unwrap().unwrap();
+        assert_eq!(gap.missing_bars, 2);
+        let report = tracker.report();
+        assert_eq!(report.filled_bars, 3);
+        assert_eq!(report.gaps, [gap]);
+
+        let mut tracker = GapTracker::new(GapPolicy::Abort, Interval::OneHour);
+        tracker.observe(&candle("BTC-USDT", 0, dec!(100))).unwrap();
+        assert!(tracker.fill_before(Utc::now()).is_empty());
+        assert!(matches!(
+            tracker.observe(&candle("BTC-USDT", 2, dec!(100))),
+            Err(Error::DataGaps(report)) if report.missing_bars() == 1
+        ));
+
+        // A gap inside maintenance is reported but does not abort
+        let window = MaintenanceWindow::new(
+            candle("BTC-USDT", 1, dec!(100)).timestamp,
+            candle("BTC-USDT", 3, dec!(100)).timestamp,
+        );
+        let mut tracker =
+            GapTracker::new(GapPolicy::Abort, Interval::OneHour).with_maintenance(&[window]);
+        tracker.observe(&candle("BTC-USDT", 0, dec!(100))).unwrap();
+        assert!(
+            tracker
+                .observe(&candle("BTC-USDT", 3, dec!(100)))
+                .unwrap()
+                .is_some()
+        );
+        assert!(tracker.observe(&candle("BTC-USDT", 5, dec!(100))).is_err());
+    }
+}
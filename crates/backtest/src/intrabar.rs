@@ -0,0 +1,240 @@
+//! Intrabar stop-loss / take-profit evaluation
+//!
+//! A candle only records four prices, so when both a stop and a target sit
+//! inside the bar's range the order in which they were touched is unknown.
+//! [`IntrabarPath`] selects the assumption used to resolve that ambiguity.
+//! Gaps are always honored: a level already breached at the open exits at
+//! the open price rather than at the level.
+
+use crate::engine::Candle;
+use ea_okx_core::models::OrderSide;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// Assumed price path inside a candle
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntrabarPath {
+    /// Only check levels against the close, exiting at the close
+    CloseOnly,
+
+    /// Stop is assumed to trigger before the target
+    #[default]
+    Conservative,
+
+    /// Target is assumed to trigger before the stop
+    Optimistic,
+
+    /// Open -> nearer extreme -> farther extreme -> close
+    OhlcOrdered,
+}
+
+/// Which protective level closed the position
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitTrigger {
+    StopLoss,
+    TakeProfit,
+}
+
+/// Stop-loss and take-profit prices attached to an open position
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExitLevels {
+    pub stop_loss: Option<Decimal>,
+    pub take_profit: Option<Decimal>,
+}
+
+impl ExitLevels {
+    pub fn is_empty(&self) -> bool {
+        self.stop_loss.is_none() && self.take_profit.is_none()
+    }
+}
+
+impl IntrabarPath {
+    /// Resolve whether `candle` hits a level for a position entered with `side`
+    ///
+    /// Returns the trigger and its fill price.
+    pub fn resolve(
+        &self,
+        candle: &Candle,
+        side: OrderSide,
+        levels: &ExitLevels,
+    ) -> Option<(ExitTrigger, Decimal)> {
+        if let IntrabarPath::CloseOnly = self {
+            return Self::resolve_on_close(candle, side, levels);
+        }
+
+        // Long stops and short targets sit below the open; the others above
+        let (below, above) = match side {
+            OrderSide::Buy => (
+                levels.stop_loss.map(|p| (ExitTrigger::StopLoss, p)),
+                levels.take_profit.map(|p| (ExitTrigger::TakeProfit, p)),
+            ),
+            OrderSide::Sell => (
+                levels.take_profit.map(|p| (ExitTrigger::TakeProfit, p)),
+                levels.stop_loss.map(|p| (ExitTrigger::StopLoss, p)),
+            ),
+        };
+
+        // Gapped through at the open
+        if let Some((trigger, level)) = below
+            && candle.open <= level
+        {
+            return Some((trigger, candle.open));
+        }
+        if let Some((trigger, level)) = above
+            && candle.open >= level
+        {
+            return Some((trigger, candle.open));
+        }
+
+        let below_hit = below.filter(|(_, level)| candle.low <= *level);
+        let above_hit = above.filter(|(_, level)| candle.high >= *level);
+
+        match (below_hit, above_hit) {
+            (Some(hit), None) | (None, Some(hit)) => Some(hit),
+            (None, None) => None,
+            (Some(low_hit), Some(high_hit)) => Some(match self {
+                IntrabarPath::Conservative => {
+                    if low_hit.0 == ExitTrigger::StopLoss {
+                        low_hit
+                    } else {
+                        high_hit
+                    }
+                }
+                IntrabarPath::Optimistic => {
+                    if low_hit.0 == ExitTrigger::TakeProfit {
+                        low_hit
+                    } else {
+                        high_hit
+                    }
+                }
+                IntrabarPath::OhlcOrdered | IntrabarPath::CloseOnly => {
+                    if candle.high - candle.open < candle.open - candle.low {
+                        high_hit
+                    } else {
+                        low_hit
+                    }
+                }
+            }),
+        }
+    }
+
+    fn resolve_on_close(
+        candle: &Candle,
+        side: OrderSide,
+        levels: &ExitLevels,
+    ) -> Option<(ExitTrigger, Decimal)> {
+        let (stopped, target_hit) = match side {
+            OrderSide::Buy => (
+                levels.stop_loss.is_some_and(|p| candle.close <= p),
+                levels.take_profit.is_some_and(|p| candle.close >= p),
+            ),
+            OrderSide::Sell => (
+                levels.stop_loss.is_some_and(|p| candle.close >= p),
+                levels.take_profit.is_some_and(|p| candle.close <= p),
+            ),
+        };
+
+        if stopped {
+            Some((ExitTrigger::StopLoss, candle.close))
+        } else if target_hit {
+            Some((ExitTrigger::TakeProfit, candle.close))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use ea_okx_core::Symbol;
+    use rust_decimal_macros::dec;
+
+    fn candle(open: Decimal, high: Decimal, low: Decimal, close: Decimal) -> Candle {
+        Candle {
+            symbol: Symbol::new("BTC-USDT").unwrap(),
+            timestamp: Utc::now(),
+            open,
+            high,
+            low,
+            close,
+            volume: dec!(1),
+        }
+    }
+
+    fn levels(stop: Decimal, take: Decimal) -> ExitLevels {
+        ExitLevels {
+            stop_loss: Some(stop),
+            take_profit: Some(take),
+        }
+    }
+
+    #[test]
+    fn test_both_levels_inside_bar() {
+        let bar = candle(dec!(100), dec!(111), dec!(89), dec!(100));
+        let long = levels(dec!(90), dec!(110));
+
+        assert_eq!(
+            IntrabarPath::Conservative.resolve(&bar, OrderSide::Buy, &long),
+            Some((ExitTrigger::StopLoss, dec!(90)))
+        );
+        assert_eq!(
+            IntrabarPath::Optimistic.resolve(&bar, OrderSide::Buy, &long),
+            Some((ExitTrigger::TakeProfit, dec!(110)))
+        );
+        // Close-only never sees either level
+        assert_eq!(
+            IntrabarPath::CloseOnly.resolve(&bar, OrderSide::Buy, &long),
+            None
+        );
+    }
+
+    #[test]
+    fn test_ohlc_ordered_visits_nearer_extreme_first() {
+        let long = levels(dec!(90), dec!(110));
+
+        // Open near the high: high is touched first
+        let up_first = candle(dec!(108), dec!(111), dec!(89), dec!(95));
+        assert_eq!(
+            IntrabarPath::OhlcOrdered.resolve(&up_first, OrderSide::Buy, &long),
+            Some((ExitTrigger::TakeProfit, dec!(110)))
+        );
+
+        // Open near the low: low is touched first
+        let down_first = candle(dec!(92), dec!(111), dec!(89), dec!(105));
+        assert_eq!(
+            IntrabarPath::OhlcOrdered.resolve(&down_first, OrderSide::Buy, &long),
+            Some((ExitTrigger::StopLoss, dec!(90)))
+        );
+    }
+
+    #[test]
+    fn test_gap_through_stop_fills_at_open() {
+        let bar = candle(dec!(85), dec!(95), dec!(80), dec!(92));
+        assert_eq!(
+            IntrabarPath::Optimistic.resolve(&bar, OrderSide::Buy, &levels(dec!(90), dec!(110))),
+            Some((ExitTrigger::StopLoss, dec!(85)))
+        );
+    }
+
+    #[test]
+    fn test_short_levels_are_mirrored() {
+        let bar = candle(dec!(100), dec!(106), dec!(98), dec!(101));
+        let short = levels(dec!(105), dec!(90));
+        assert_eq!(
+            IntrabarPath::Conservative.resolve(&bar, OrderSide::Sell, &short),
+            Some((ExitTrigger::StopLoss, dec!(105)))
+        );
+        assert_eq!(
+            IntrabarPath::Conservative.resolve(
+                &candle(dec!(100), dec!(101), dec!(95), dec!(99)),
+                OrderSide::Sell,
+                &short
+            ),
+            None
+        );
+    }
+}
@@ -1,15 +1,52 @@
 pub mod cost_model;
+pub mod curve;
 pub mod engine;
 pub mod error;
 pub mod events;
+pub mod fills;
+pub mod gaps;
+pub mod intrabar;
+pub mod lookahead;
+pub mod maintenance;
+pub mod observers;
 pub mod portfolio;
+pub mod registry;
 pub mod results;
+pub mod sensitivity;
+pub mod series;
+pub mod stream;
+pub mod synthetic;
+pub mod timescale;
 
 pub use cost_model::{CommissionModel, CostModel, SlippageModel};
+pub use curve::{CurvePage, CurvePoint, CurveQuery, CurveResolution};
 pub use engine::{
-    BacktestConfig, BacktestEngine, HistoricalDataSource, MockDataSource, PositionSizing,
+    BacktestConfig, BacktestEngine, Candle, FundingRate, HistoricalDataSource, MockDataSource,
+    PositionSizing, PositioningSample, PositioningValue,
 };
 pub use error::{Error, Result};
 pub use events::{ExecutionEvent, Fill, MarketEvent, Trade};
-pub use portfolio::Portfolio;
+pub use fills::{LimitFillModel, LimitFillReport};
+pub use gaps::{CandleGap, GapPolicy, GapReport, GapTracker};
+pub use intrabar::{ExitLevels, ExitTrigger, IntrabarPath};
+pub use lookahead::LookAheadGuard;
+pub use maintenance::{
+    MaintenanceOutcome, MaintenanceReport, MaintenanceTracker, MaintenanceWindow, RestingOrders,
+};
+pub use observers::{
+    BacktestObserver, ExcursionObserver, ExcursionReport, ObserverContext, TradeExcursion,
+};
+pub use portfolio::{MarginConfig, Portfolio};
+pub use registry::{
+    BacktestComparison, BacktestFilter, BacktestRegistry, BacktestRun, BacktestRunStore,
+    EquityOverlay, FileBacktestRunStore, InMemoryBacktestRunStore, MetricRow, OverlaySeries,
+    RunMetrics, RunProvenance,
+};
 pub use results::BacktestResult;
+pub use sensitivity::{
+    CostVerdict, SensitivityGrid, SensitivityPoint, SensitivityReport, sweep_costs,
+};
+pub use series::{CandleSeries, EventRef, Timeline};
+pub use stream::{CandleChunks, CandleMerge, StreamingConfig};
+pub use synthetic::{SyntheticConfig, SyntheticDataSource, TrendSegment, VolatilityRegime};
+pub use timescale::TimescaleDataSource;
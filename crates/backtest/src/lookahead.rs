@@ -0,0 +1,216 @@
+//! Look-ahead bias detection
+//!
+//! The engine steps its simulation clock over the [`Timeline`](crate::series::Timeline)
+//! and every event handed to the strategy passes through a [`LookAheadGuard`].
+//! An event may only be delivered once it could have been known at the
+//! current clock:
+//!
+//! - candles of the base interval drive the clock and are known at their own
+//!   timestamp, as are funding rates and other point-in-time events;
+//! - candles of any other interval are labelled with their open time and only
+//!   become known once the bar has closed, at open + bar length.
+//!
+//! Anything earlier is reported as [`Error::LookAheadBias`], failing the run
+//! instead of producing results that silently used future data.
+
+use crate::error::{Error, Result};
+use chrono::{DateTime, Utc};
+use ea_okx_core::Interval;
+use ea_okx_strategy::traits::MarketDataEvent;
+
+/// Simulation clock that checks every event delivered to the strategy
+#[derive(Debug, Clone)]
+pub struct LookAheadGuard {
+    base_interval: Interval,
+    clock: Option<DateTime<Utc>>,
+}
+
+impl LookAheadGuard {
+    pub fn new(base_interval: Interval) -> Self {
+        Self {
+            base_interval,
+            clock: None,
+        }
+    }
+
+    /// Current simulation time, once the first event has been replayed
+    pub fn clock(&self) -> Option<DateTime<Utc>> {
+        self.clock
+    }
+
+    /// Move the clock to `now`; replay must never go back in time
+    pub fn advance(&mut self, now: DateTime<Utc>) -> Result<()> {
+        if let Some(clock) = self.clock
+            && now < clock
+        {
+            return Err(Error::LookAheadBias(format!(
+                "Simulation clock moved backwards from {} to {}",
+                clock, now
+            )));
+        }
+        self.clock = Some(now);
+        Ok(())
+    }
+
+    /// Earliest time `event` could have been known
+    pub fn available_at(&self, event: &MarketDataEvent) -> Result<DateTime<Utc>> {
+        match event {
+            MarketDataEvent::Candle {
+                interval,
+                timestamp,
+                ..
+            } if *interval != self.base_interval => {
+                let length = interval.duration().ok_or_else(|| {
+                    Error::InvalidConfig(format!("Unsupported candle interval: {}", interval))
+                })?;
+                Ok(*timestamp + length)
+            }
+            MarketDataEvent::Ticker { timestamp, .. }
+            | MarketDataEvent::Candle { timestamp, .. }
+            | MarketDataEvent::Trade { timestamp, .. }
+            | MarketDataEvent::FundingRate { timestamp, .. }
+            | MarketDataEvent::OpenInterest { timestamp, .. }
+            | MarketDataEvent::TakerVolume { timestamp, .. }
+            | MarketDataEvent::LongShortRatio { timestamp, .. }
+            | MarketDataEvent::Gap { timestamp, .. } => Ok(*timestamp),
+        }
+    }
+
+    /// Fail if `event` would reveal data from after the current clock
+    pub fn check(&self, event: &MarketDataEvent) -> Result<()> {
+        let Some(clock) = self.clock else {
+            return Err(Error::LookAheadBias(
+                "Event delivered before the simulation clock started".to_string(),
+            ));
+        };
+        let available_at = self.available_at(event)?;
+        if available_at > clock {
+            return Err(Error::LookAheadBias(format!(
+                "{} known at {} delivered at {}",
+                describe(event),
+                available_at,
+                clock
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn describe(event: &MarketDataEvent) -> String {
+    match event {
+        MarketDataEvent::Candle {
+            symbol,
+            interval,
+            timestamp,
+            ..
+        } => format!(
+            "{} {} candle opened {}",
+            symbol.as_str(),
+            interval,
+            timestamp
+        ),
+        MarketDataEvent::Ticker { symbol, .. } => format!("{} ticker", symbol.as_str()),
+        MarketDataEvent::Trade { symbol, .. } => format!("{} trade", symbol.as_str()),
+        MarketDataEvent::FundingRate { symbol, .. } => {
+            format!("{} funding rate", symbol.as_str())
+        }
+        MarketDataEvent::OpenInterest { symbol, .. } => {
+            format!("{} open interest", symbol.as_str())
+        }
+        MarketDataEvent::TakerVolume { symbol, .. } => {
+            format!("{} taker volume", symbol.as_str())
+        }
+        MarketDataEvent::LongShortRatio { symbol, .. } => {
+            format!("{} long/short ratio", symbol.as_str())
+        }
+        MarketDataEvent::Gap {
+            symbol, timestamp, ..
+        } => format!("{} gap ending {}", symbol.as_str(), timestamp),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use ea_okx_core::Symbol;
+    use rust_decimal_macros::dec;
+
+    fn candle(interval: Interval, timestamp: DateTime<Utc>) -> MarketDataEvent {
+        MarketDataEvent::Candle {
+            symbol: Symbol::new("BTC-USDT").unwrap(),
+            interval,
+            open: dec!(100),
+            high: dec!(100),
+            low: dec!(100),
+            close: dec!(100),
+            volume: dec!(1),
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn test_available_at_adds_the_bar_length() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let guard = LookAheadGuard::new(Interval::OneSecond);
+
+        for (interval, length) in [
+            (Interval::OneSecond, Duration::zero()),
+            (Interval::FifteenMinutes, Duration::minutes(15)),
+            (Interval::FourHours, Duration::hours(4)),
+            (Interval::OneDayUtc, Duration::days(1)),
+            (Interval::OneWeek, Duration::weeks(1)),
+        ] {
+            assert_eq!(
+                guard.available_at(&candle(interval, start)).unwrap(),
+                start + length
+            );
+        }
+        assert!(matches!(
+            guard.available_at(&candle(Interval::OneMonth, start)),
+            Err(Error::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn test_higher_timeframe_visible_only_after_close() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let mut guard = LookAheadGuard::new(Interval::OneHour);
+        let four_hour = candle(Interval::FourHours, start);
+
+        guard.advance(start + Duration::hours(3)).unwrap();
+        assert!(
+            guard
+                .check(&candle(Interval::OneHour, start + Duration::hours(3)))
+                .is_ok()
+        );
+        assert!(matches!(
+            guard.check(&four_hour),
+            Err(Error::LookAheadBias(_))
+        ));
+
+        guard.advance(start + Duration::hours(4)).unwrap();
+        assert!(guard.check(&four_hour).is_ok());
+    }
+
+    #[test]
+    fn test_future_events_and_rewinds_are_rejected() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let mut guard = LookAheadGuard::new(Interval::OneHour);
+        assert!(guard.check(&candle(Interval::OneHour, start)).is_err());
+
+        guard.advance(start).unwrap();
+        assert!(matches!(
+            guard.check(&candle(Interval::OneHour, start + Duration::hours(1))),
+            Err(Error::LookAheadBias(_))
+        ));
+        assert!(matches!(
+            guard.check(&candle(Interval::OneMonth, start)),
+            Err(Error::InvalidConfig(_))
+        ));
+        assert!(matches!(
+            guard.advance(start - Duration::minutes(1)),
+            Err(Error::LookAheadBias(_))
+        ));
+    }
+}
@@ -0,0 +1,330 @@
+//! Exchange maintenance windows
+//!
+//! OKX takes trading offline for scheduled upgrades, typically once a month,
+//! and a live strategy cannot trade around them. [`MaintenanceWindow`]s
+//! inject that downtime into a run:
+//!
+//! - candles opening inside a window are withheld, leaving the data gap a
+//!   live feed would have; the run's [`GapPolicy`] treats it like any other
+//!   gap, except that gaps a window explains never abort the run
+//! - nothing fills: pending orders wait until the window ends
+//! - orders the strategy sends while the exchange is down are rejected,
+//!   closes and protective exits included
+//! - resting orders are left alone, or cancelled when the window begins if
+//!   the exchange announced it would cancel them
+//!
+//! A window naming no symbols takes the whole exchange down.
+//!
+//! [`GapPolicy`]: crate::gaps::GapPolicy
+
+use crate::engine::Candle;
+use crate::error::{Error, Result};
+use crate::gaps::CandleGap;
+use chrono::{DateTime, Datelike, Duration, Months, TimeZone, Utc};
+use ea_okx_core::Symbol;
+use serde::{Deserialize, Serialize};
+
+/// What happens to resting orders when a window begins
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RestingOrders {
+    /// Orders stay on the book and may fill once trading resumes
+    #[default]
+    Keep,
+
+    /// The exchange cancels every open order on the affected symbols
+    Cancel,
+}
+
+/// A period the exchange is down
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaintenanceWindow {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+
+    /// Symbols taken down; empty for the whole exchange
+    #[serde(default)]
+    pub symbols: Vec<Symbol>,
+
+    #[serde(default)]
+    pub resting_orders: RestingOrders,
+}
+
+impl MaintenanceWindow {
+    /// Exchange-wide downtime keeping resting orders
+    pub fn new(start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        Self {
+            start,
+            end,
+            symbols: Vec::new(),
+            resting_orders: RestingOrders::Keep,
+        }
+    }
+
+    /// Limit the downtime to `symbols`
+    pub fn for_symbols(mut self, symbols: Vec<Symbol>) -> Self {
+        self.symbols = symbols;
+        self
+    }
+
+    /// Cancel resting orders when the window begins
+    pub fn cancelling_orders(mut self) -> Self {
+        self.resting_orders = RestingOrders::Cancel;
+        self
+    }
+
+    /// Windows of `duration` starting on `day` of every month at `hour` UTC,
+    /// for every start falling in `[from, to)`
+    ///
+    /// Months without `day` (e.g., the 31st in April) are skipped.
+    pub fn monthly(
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        day: u32,
+        hour: u32,
+        duration: Duration,
+    ) -> Vec<Self> {
+        let mut windows = Vec::new();
+        let mut month = from.date_naive().with_day(1);
+        while let Some(first) = month
+            && first.and_hms_opt(0, 0, 0).is_some_and(|t| t.and_utc() < to)
+        {
+            if let Some(start) = first
+                .with_day(day)
+                .and_then(|date| date.and_hms_opt(hour, 0, 0))
+                .map(|t| Utc.from_utc_datetime(&t))
+                .filter(|start| *start >= from && *start < to)
+            {
+                windows.push(Self::new(start, start + duration));
+            }
+            month = first.checked_add_months(Months::new(1));
+        }
+        windows
+    }
+
+    /// Whether the window takes `symbol` down
+    pub fn affects(&self, symbol: &Symbol) -> bool {
+        self.symbols.is_empty() || self.symbols.contains(symbol)
+    }
+
+    /// Whether `symbol` is down at `at`
+    pub fn covers(&self, symbol: &Symbol, at: DateTime<Utc>) -> bool {
+        self.start <= at && at < self.end && self.affects(symbol)
+    }
+
+    /// Whether every bar missing in `gap` would have opened inside the window
+    pub fn explains(&self, gap: &CandleGap, bar: Duration) -> bool {
+        self.affects(&gap.symbol)
+            && gap.last_before + bar >= self.start
+            && gap.resumed_at - bar < self.end
+    }
+}
+
+/// What one window did to a run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaintenanceOutcome {
+    pub window: MaintenanceWindow,
+
+    /// Base candles the strategy never saw
+    pub withheld_candles: usize,
+
+    /// Resting orders cancelled when the window began
+    pub cancelled_orders: usize,
+
+    /// Orders sent while the exchange was down
+    pub rejected_orders: usize,
+}
+
+/// Downtime injected into a run
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaintenanceReport {
+    pub windows: Vec<MaintenanceOutcome>,
+}
+
+impl MaintenanceReport {
+    /// Time the exchange was down, overlapping windows counted once each
+    pub fn downtime(&self) -> Duration {
+        self.windows
+            .iter()
+            .map(|o| o.window.end - o.window.start)
+            .fold(Duration::zero(), |total, d| total + d)
+    }
+
+    pub fn withheld_candles(&self) -> usize {
+        self.windows.iter().map(|o| o.withheld_candles).sum()
+    }
+
+    pub fn cancelled_orders(&self) -> usize {
+        self.windows.iter().map(|o| o.cancelled_orders).sum()
+    }
+
+    pub fn rejected_orders(&self) -> usize {
+        self.windows.iter().map(|o| o.rejected_orders).sum()
+    }
+}
+
+/// Follows the configured windows through a run
+#[derive(Debug)]
+pub struct MaintenanceTracker {
+    /// Outcomes in window start order
+    outcomes: Vec<MaintenanceOutcome>,
+
+    /// Windows before this index have begun
+    begun: usize,
+}
+
+impl MaintenanceTracker {
+    pub fn new(windows: &[MaintenanceWindow]) -> Result<Self> {
+        if let Some(window) = windows.iter().find(|w| w.start >= w.end) {
+            return Err(Error::InvalidConfig(format!(
+                "Maintenance window starting {} does not end after it starts",
+                window.start
+            )));
+        }
+
+        let mut outcomes: Vec<_> = windows
+            .iter()
+            .map(|window| MaintenanceOutcome {
+                window: window.clone(),
+                withheld_candles: 0,
+                cancelled_orders: 0,
+                rejected_orders: 0,
+            })
+            .collect();
+        outcomes.sort_by_key(|o| o.window.start);
+
+        Ok(Self { outcomes, begun: 0 })
+    }
+
+    /// Windows beginning by `now` that had not begun yet, in start order
+    pub fn begin(&mut self, now: DateTime<Utc>) -> Vec<MaintenanceWindow> {
+        let begun = self.begun;
+        while self
+            .outcomes
+            .get(self.begun)
+            .is_some_and(|o| o.window.start <= now)
+        {
+            self.begun += 1;
+        }
+        self.outcomes[begun..self.begun]
+            .iter()
+            .map(|o| o.window.clone())
+            .collect()
+    }
+
+    /// Whether `symbol` is down at `at`
+    pub fn is_down(&self, symbol: &Symbol, at: DateTime<Utc>) -> bool {
+        self.outcomes.iter().any(|o| o.window.covers(symbol, at))
+    }
+
+    /// Whether `candle` falls in a window, counting it as withheld if so
+    pub fn withhold(&mut self, candle: &Candle) -> bool {
+        self.count(&candle.symbol, candle.timestamp, |o| {
+            o.withheld_candles += 1
+        })
+    }
+
+    /// Count an order on `symbol` refused at `at`
+    pub fn reject(&mut self, symbol: &Symbol, at: DateTime<Utc>) {
+        self.count(symbol, at, |o| o.rejected_orders += 1);
+    }
+
+    /// Count `orders` cancelled as `window` began
+    pub fn cancelled(&mut self, window: &MaintenanceWindow, orders: usize) {
+        if let Some(outcome) = self.outcomes.iter_mut().find(|o| o.window == *window) {
+            outcome.cancelled_orders += orders;
+        }
+    }
+
+    pub fn report(&self) -> MaintenanceReport {
+        MaintenanceReport {
+            windows: self.outcomes.clone(),
+        }
+    }
+
+    /// Apply `update` to the first window covering `symbol` at `at`
+    fn count(
+        &mut self,
+        symbol: &Symbol,
+        at: DateTime<Utc>,
+        update: impl FnOnce(&mut MaintenanceOutcome),
+    ) -> bool {
+        match self
+            .outcomes
+            .iter_mut()
+            .find(|o| o.window.covers(symbol, at))
+        {
+            Some(outcome) => {
+                update(outcome);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, day, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_monthly_windows() {
+        let from = Utc.with_ymd_and_hms(2024, 1, 20, 0, 0, 0).unwrap();
+        let to = Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap();
+        let starts: Vec<_> = MaintenanceWindow::monthly(from, to, 30, 6, Duration::hours(1))
+            .iter()
+            .map(|w| w.start.format("%m-%d %H").to_string())
+            .collect();
+        // No 30th in February
+        assert_eq!(starts, ["01-30 06", "03-30 06", "04-30 06", "05-30 06"]);
+    }
+
+    #[test]
+    fn test_window_covers_its_symbols() {
+        let btc = Symbol::new("BTC-USDT").unwrap();
+        let eth = Symbol::new("ETH-USDT").unwrap();
+        let window = MaintenanceWindow::new(at(1, 2), at(1, 4)).for_symbols(vec![btc.clone()]);
+
+        assert!(window.covers(&btc, at(1, 2)));
+        assert!(window.covers(&btc, at(1, 3)));
+        assert!(!window.covers(&btc, at(1, 4)));
+        assert!(!window.covers(&eth, at(1, 3)));
+
+        // Bars at 02:00 and 03:00 are missing between 01:00 and 04:00
+        let gap = |last: u32, resumed: u32| CandleGap {
+            symbol: btc.clone(),
+            last_before: at(1, last),
+            resumed_at: at(1, resumed),
+            missing_bars: resumed - last - 1,
+        };
+        assert!(window.explains(&gap(1, 4), Duration::hours(1)));
+        assert!(!window.explains(&gap(0, 4), Duration::hours(1)));
+        assert!(!window.explains(&gap(1, 5), Duration::hours(1)));
+    }
+
+    #[test]
+    fn test_tracker_begins_windows_once() {
+        let late = MaintenanceWindow::new(at(3, 0), at(3, 1));
+        let early = MaintenanceWindow::new(at(2, 0), at(2, 1)).cancelling_orders();
+        let mut tracker = MaintenanceTracker::new(&[late.clone(), early.clone()]).unwrap();
+
+        assert!(tracker.begin(at(1, 0)).is_empty());
+        assert_eq!(tracker.begin(at(3, 0)), [early.clone(), late]);
+        assert!(tracker.begin(at(4, 0)).is_empty());
+
+        let btc = Symbol::new("BTC-USDT").unwrap();
+        tracker.reject(&btc, at(2, 0));
+        tracker.cancelled(&early, 2);
+        let report = tracker.report();
+        assert_eq!(report.rejected_orders(), 1);
+        assert_eq!(report.cancelled_orders(), 2);
+        assert_eq!(report.downtime(), Duration::hours(2));
+
+        let backwards = MaintenanceWindow::new(at(2, 0), at(1, 0));
+        assert!(MaintenanceTracker::new(&[backwards]).is_err());
+    }
+}
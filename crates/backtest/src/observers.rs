@@ -0,0 +1,330 @@
+//! Custom analytics computed alongside a run
+//!
+//! A [`BacktestObserver`] registered with
+//! [`BacktestEngine::with_observer`](crate::engine::BacktestEngine::with_observer)
+//! is called as the run progresses: after every market event once pending
+//! orders have been checked, after every fill, at each UTC day boundary, and
+//! once the final results are ready. Observers only read the engine's state,
+//! so they can never change the outcome of a run; what they report on
+//! finishing lands in [`BacktestResult::analytics`] under their name.
+//!
+//! [`ExcursionObserver`] is the built-in one, reporting how far each trade
+//! ran for and against its entry.
+
+use crate::events::{Fill, MarketEvent};
+use crate::portfolio::Portfolio;
+use crate::results::BacktestResult;
+use chrono::{DateTime, NaiveDate, Utc};
+use ea_okx_core::Symbol;
+use ea_okx_core::math::div_or;
+use ea_okx_core::models::{Order, OrderSide, PositionSide};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Engine state visible to observers
+#[derive(Debug, Clone, Copy)]
+pub struct ObserverContext<'a> {
+    /// Simulation time of the hook
+    pub timestamp: DateTime<Utc>,
+
+    pub portfolio: &'a Portfolio,
+
+    /// Latest price per symbol
+    pub prices: &'a HashMap<Symbol, Decimal>,
+}
+
+/// Lifecycle hooks for computing custom analytics during a run
+///
+/// Every hook but [`on_finish`](Self::on_finish) defaults to doing nothing.
+pub trait BacktestObserver: Send + Sync {
+    /// Key of the observer's report in [`BacktestResult::analytics`]
+    fn name(&self) -> &str;
+
+    /// A market event was replayed and pending orders checked against it
+    fn on_event(&mut self, _event: &MarketEvent, _ctx: &ObserverContext<'_>) {}
+
+    /// An order filled; the portfolio already reflects it
+    fn on_fill(&mut self, _order: &Order, _fill: &Fill, _ctx: &ObserverContext<'_>) {}
+
+    /// The UTC day `day` ended; the context shows the state at its close
+    fn on_day_close(&mut self, _day: NaiveDate, _ctx: &ObserverContext<'_>) {}
+
+    /// The run finished, with open positions closed; returns the report
+    fn on_finish(&mut self, result: &BacktestResult) -> serde_json::Value;
+}
+
+/// How far one trade moved for and against its entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TradeExcursion {
+    pub symbol: Symbol,
+    pub side: OrderSide,
+    pub entry_time: DateTime<Utc>,
+    pub exit_time: DateTime<Utc>,
+    pub entry_price: Decimal,
+    pub exit_price: Decimal,
+
+    /// Worst price reached against the position, as a fraction of entry
+    pub mae_pct: Decimal,
+
+    /// Best price reached in the position's favour, as a fraction of entry
+    pub mfe_pct: Decimal,
+
+    /// Move captured at exit, as a fraction of entry; negative on a loss
+    pub return_pct: Decimal,
+}
+
+impl TradeExcursion {
+    /// Share of the best move the exit kept; zero when there was none
+    pub fn capture_ratio(&self) -> Decimal {
+        ratio(self.return_pct, self.mfe_pct)
+    }
+}
+
+/// Report of [`ExcursionObserver`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExcursionReport {
+    pub trades: Vec<TradeExcursion>,
+    pub avg_mae_pct: Decimal,
+    pub avg_mfe_pct: Decimal,
+
+    /// Average MFE over average MAE; above one, entries tend to move the
+    /// right way first
+    pub edge_ratio: Decimal,
+}
+
+impl ExcursionReport {
+    fn new(trades: Vec<TradeExcursion>) -> Self {
+        let count = Decimal::from(trades.len());
+        let avg_mae_pct = ratio(trades.iter().map(|t| t.mae_pct).sum(), count);
+        let avg_mfe_pct = ratio(trades.iter().map(|t| t.mfe_pct).sum(), count);
+        Self {
+            trades,
+            avg_mae_pct,
+            avg_mfe_pct,
+            edge_ratio: ratio(avg_mfe_pct, avg_mae_pct),
+        }
+    }
+}
+
+/// `numerator / denominator`, or zero when undefined
+fn ratio(numerator: Decimal, denominator: Decimal) -> Decimal {
+    div_or(numerator, denominator, Decimal::ZERO).unwrap_or(Decimal::ZERO)
+}
+
+/// A position the observer follows, from its first fill until it is flat
+#[derive(Debug, Clone)]
+struct OpenExcursion {
+    side: OrderSide,
+    entry_time: DateTime<Utc>,
+    entry_price: Decimal,
+    quantity: Decimal,
+    highest: Decimal,
+    lowest: Decimal,
+}
+
+impl OpenExcursion {
+    fn close(
+        self,
+        symbol: Symbol,
+        exit_time: DateTime<Utc>,
+        exit_price: Decimal,
+    ) -> TradeExcursion {
+        let pct = |distance: Decimal| ratio(distance, self.entry_price);
+        let (adverse, favorable, captured) = match self.side {
+            OrderSide::Buy => (
+                self.entry_price - self.lowest,
+                self.highest - self.entry_price,
+                exit_price - self.entry_price,
+            ),
+            OrderSide::Sell => (
+                self.highest - self.entry_price,
+                self.entry_price - self.lowest,
+                self.entry_price - exit_price,
+            ),
+        };
+        TradeExcursion {
+            symbol,
+            side: self.side,
+            entry_time: self.entry_time,
+            exit_time,
+            entry_price: self.entry_price,
+            exit_price,
+            mae_pct: pct(adverse.max(Decimal::ZERO)),
+            mfe_pct: pct(favorable.max(Decimal::ZERO)),
+            return_pct: pct(captured),
+        }
+    }
+}
+
+/// Built-in observer reporting maximum adverse and favorable excursion per
+/// trade as a fraction of the entry price
+///
+/// A trade runs from the fill opening a position until the fill that
+/// flattens it; adding to it averages the entry price. Candles seen while it
+/// is open, including the one it filled on, widen its range.
+#[derive(Debug, Default)]
+pub struct ExcursionObserver {
+    open: HashMap<Symbol, OpenExcursion>,
+    closed: Vec<TradeExcursion>,
+}
+
+impl ExcursionObserver {
+    pub const NAME: &'static str = "excursions";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn open(&mut self, symbol: &Symbol, side: OrderSide, fill: &Fill, quantity: Decimal) {
+        match self.open.get_mut(symbol) {
+            Some(open) => {
+                let total = open.quantity + quantity;
+                open.entry_price = div_or(
+                    open.entry_price * open.quantity + fill.price * quantity,
+                    total,
+                    fill.price,
+                )
+                .unwrap_or(fill.price);
+                open.quantity = total;
+            }
+            None => {
+                self.open.insert(
+                    symbol.clone(),
+                    OpenExcursion {
+                        side,
+                        entry_time: fill.timestamp,
+                        entry_price: fill.price,
+                        quantity,
+                        highest: fill.price,
+                        lowest: fill.price,
+                    },
+                );
+            }
+        }
+    }
+}
+
+impl BacktestObserver for ExcursionObserver {
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+
+    fn on_event(&mut self, event: &MarketEvent, _ctx: &ObserverContext<'_>) {
+        if let MarketEvent::Candle(candle) = event
+            && let Some(open) = self.open.get_mut(&candle.symbol)
+        {
+            open.highest = open.highest.max(candle.high);
+            open.lowest = open.lowest.min(candle.low);
+        }
+    }
+
+    fn on_fill(&mut self, order: &Order, fill: &Fill, ctx: &ObserverContext<'_>) {
+        let symbol = &order.symbol;
+        let mut remaining = fill.quantity;
+
+        if let Some(open) = self.open.get_mut(symbol)
+            && open.side != order.side
+        {
+            let reduced = remaining.min(open.quantity);
+            open.quantity -= reduced;
+            remaining -= reduced;
+            if open.quantity <= Decimal::ZERO {
+                let open = self.open.remove(symbol).expect("open trade checked above");
+                self.closed
+                    .push(open.close(symbol.clone(), fill.timestamp, fill.price));
+            }
+        }
+
+        // A spot sell with nothing open leaves no short to follow
+        let opens_short = ctx
+            .portfolio
+            .get_position(symbol)
+            .is_some_and(|p| p.side == PositionSide::Short);
+        if remaining > Decimal::ZERO && (order.side == OrderSide::Buy || opens_short) {
+            self.open(symbol, order.side, fill, remaining);
+        }
+    }
+
+    fn on_finish(&mut self, _result: &BacktestResult) -> serde_json::Value {
+        let report = ExcursionReport::new(std::mem::take(&mut self.closed));
+        serde_json::to_value(report).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Candle;
+    use chrono::TimeZone;
+    use ea_okx_core::models::OrderType;
+    use ea_okx_core::{Price, Quantity};
+    use rust_decimal_macros::dec;
+    use uuid::Uuid;
+
+    fn order(side: OrderSide, quantity: Decimal) -> Order {
+        Order::new(
+            Uuid::new_v4(),
+            Symbol::new("BTC-USDT").unwrap(),
+            side,
+            OrderType::Market,
+            Quantity::new(quantity).unwrap(),
+            Some(Price::new(dec!(100)).unwrap()),
+        )
+    }
+
+    fn fill(order: &Order, hour: i64, price: Decimal) -> Fill {
+        Fill {
+            order_id: order.id,
+            price,
+            quantity: order.quantity.as_decimal(),
+            commission: Decimal::ZERO,
+            timestamp: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+                + chrono::Duration::hours(hour),
+            slippage: Decimal::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_excursions_follow_trade_until_flat() {
+        let portfolio = Portfolio::new(dec!(10000));
+        let prices = HashMap::new();
+        let ctx = ObserverContext {
+            timestamp: Utc::now(),
+            portfolio: &portfolio,
+            prices: &prices,
+        };
+        let mut observer = ExcursionObserver::new();
+
+        let buy = order(OrderSide::Buy, dec!(2));
+        observer.on_fill(&buy, &fill(&buy, 0, dec!(100)), &ctx);
+        let bar = Candle {
+            symbol: buy.symbol.clone(),
+            timestamp: Utc.with_ymd_and_hms(2024, 1, 1, 1, 0, 0).unwrap(),
+            open: dec!(100),
+            high: dec!(110),
+            low: dec!(96),
+            close: dec!(105),
+            volume: dec!(1),
+        };
+        observer.on_event(&MarketEvent::Candle(bar), &ctx);
+
+        // Half the position is still open after the first exit
+        let sell = order(OrderSide::Sell, dec!(1));
+        observer.on_fill(&sell, &fill(&sell, 2, dec!(105)), &ctx);
+        assert!(observer.closed.is_empty());
+        observer.on_fill(&sell, &fill(&sell, 3, dec!(105)), &ctx);
+
+        // Without a short position, a further sell opens nothing
+        observer.on_fill(&sell, &fill(&sell, 4, dec!(105)), &ctx);
+        assert!(observer.open.is_empty());
+
+        let report = ExcursionReport::new(observer.closed.clone());
+        let trade = &report.trades[0];
+        assert_eq!(trade.mae_pct, dec!(0.04));
+        assert_eq!(trade.mfe_pct, dec!(0.1));
+        assert_eq!(trade.return_pct, dec!(0.05));
+        assert_eq!(trade.capture_ratio(), dec!(0.5));
+        assert_eq!(report.edge_ratio, dec!(2.5));
+    }
+}
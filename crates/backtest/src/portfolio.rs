@@ -1,20 +1,49 @@
 use crate::error::{Error, Result};
 use crate::events::Fill;
+use ea_okx_core::math::{div_or, safe_div, safe_mul};
 use ea_okx_core::models::{Order, OrderSide, Position, PositionSide};
 use ea_okx_core::{Price, Quantity, Symbol};
 use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+const SECONDS_PER_YEAR: i64 = 365 * 24 * 60 * 60;
+
+/// Short-selling rules for margin and perpetual instruments
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MarginConfig {
+    /// Allow sells beyond the held quantity, opening a short position
+    pub allow_short: bool,
+
+    /// Annual interest charged on the notional of open shorts (0.1 = 10%)
+    pub borrow_rate: Decimal,
+
+    /// Equity required per unit of short notional when opening (0.5 = 2x)
+    pub initial_margin: Decimal,
+}
+
+impl Default for MarginConfig {
+    /// Plain spot: no shorting
+    fn default() -> Self {
+        Self {
+            allow_short: false,
+            borrow_rate: dec!(0.1),
+            initial_margin: dec!(0.5),
+        }
+    }
+}
+
 /// Portfolio tracking for backtesting
 #[derive(Debug, Clone)]
 pub struct Portfolio {
     /// Initial capital
     pub initial_capital: Decimal,
 
-    /// Current cash balance
+    /// Current cash balance (includes proceeds of short sales)
     pub cash: Decimal,
 
-    /// Open positions
+    /// Open positions; shorts hold a positive quantity with `PositionSide::Short`
     pub positions: HashMap<Symbol, Position>,
 
     /// Realized P&L
@@ -26,9 +55,21 @@ pub struct Portfolio {
     /// Total slippage incurred
     pub total_slippage: Decimal,
 
+    /// Net funding received (negative when paid)
+    pub funding_pnl: Decimal,
+
+    /// Interest paid on borrowed inventory for shorts
+    pub borrow_cost: Decimal,
+
     /// Equity curve (timestamp, equity)
     pub equity_curve: Vec<(chrono::DateTime<chrono::Utc>, Decimal)>,
 
+    /// Short-selling rules
+    margin: MarginConfig,
+
+    /// Time borrow interest was last accrued up to
+    borrow_accrued_at: Option<chrono::DateTime<chrono::Utc>>,
+
     /// Current market prices for positions
     current_prices: HashMap<Symbol, Decimal>,
 }
@@ -42,86 +83,91 @@ impl Portfolio {
             realized_pnl: Decimal::ZERO,
             total_commission: Decimal::ZERO,
             total_slippage: Decimal::ZERO,
+            funding_pnl: Decimal::ZERO,
+            borrow_cost: Decimal::ZERO,
             equity_curve: Vec::new(),
+            margin: MarginConfig::default(),
+            borrow_accrued_at: None,
             current_prices: HashMap::new(),
         }
     }
 
+    /// Use `margin` rules for short sales
+    pub fn with_margin(mut self, margin: MarginConfig) -> Self {
+        self.margin = margin;
+        self
+    }
+
     /// Apply a fill to the portfolio
+    ///
+    /// Buys first cover any short and sells first reduce any long; the rest of
+    /// the fill opens or extends a position on the order's side. Opening a
+    /// short requires `allow_short` and enough equity for the initial margin.
     pub fn apply_fill(&mut self, order: &Order, fill: &Fill) -> Result<()> {
-        let cost = fill.price * fill.quantity;
+        let cost = safe_mul(fill.price, fill.quantity)?;
+        let costs = fill.commission + fill.slippage;
+
+        let held = self
+            .positions
+            .get(&order.symbol)
+            .map(|p| (p.side, p.quantity.as_decimal()));
+        let reducing = match (order.side, held) {
+            (OrderSide::Buy, Some((PositionSide::Short, qty)))
+            | (OrderSide::Sell, Some((PositionSide::Long | PositionSide::Net, qty))) => {
+                fill.quantity.min(qty)
+            }
+            _ => Decimal::ZERO,
+        };
+        let opening = fill.quantity - reducing;
 
         match order.side {
             OrderSide::Buy => {
                 // Check if we have enough cash
-                let total_cost = cost + fill.commission + fill.slippage;
-                if self.cash < total_cost {
+                if self.cash < cost + costs {
                     return Err(Error::ExecutionError(
                         "Insufficient cash for buy order".to_string(),
                     ));
                 }
-
-                // Deduct cash
-                self.cash -= total_cost;
-
-                // Update or create position
-                let position = self
-                    .positions
-                    .entry(order.symbol.clone())
-                    .or_insert_with(|| {
-                        Position::new(
-                            uuid::Uuid::new_v4(), // strategy_id
-                            order.symbol.clone(),
-                            PositionSide::Long,
-                            Quantity::new(Decimal::ZERO).unwrap(),
-                            Price::new(Decimal::ZERO).unwrap(),
-                        )
-                    });
-
-                // Update position quantity and average price
-                let old_quantity = position.quantity.as_decimal();
-                let old_cost = old_quantity * position.avg_entry_price.as_decimal();
-                let new_quantity = old_quantity + fill.quantity;
-                let new_avg_price = (old_cost + cost) / new_quantity;
-
-                position.quantity = Quantity::new(new_quantity)?;
-                position.avg_entry_price = Price::new(new_avg_price)?;
             }
+            OrderSide::Sell if opening > Decimal::ZERO => {
+                if !self.margin.allow_short {
+                    let message = match held {
+                        Some(_) => "Insufficient position for sell order",
+                        None => "No position to sell",
+                    };
+                    return Err(Error::ExecutionError(message.to_string()));
+                }
 
-            OrderSide::Sell => {
-                // Check if we have the position to sell
-                if let Some(position) = self.positions.get_mut(&order.symbol) {
-                    let position_qty = position.quantity.as_decimal();
-
-                    if position_qty < fill.quantity {
-                        return Err(Error::ExecutionError(
-                            "Insufficient position for sell order".to_string(),
-                        ));
-                    }
-
-                    // Calculate realized PnL
-                    let entry_cost = fill.quantity * position.avg_entry_price.as_decimal();
-                    let exit_proceeds = cost;
-                    let gross_pnl = exit_proceeds - entry_cost;
-                    let net_pnl = gross_pnl - fill.commission - fill.slippage;
-
-                    self.realized_pnl += net_pnl;
-                    self.cash += exit_proceeds - fill.commission - fill.slippage;
-
-                    // Update position
-                    let new_qty = position_qty - fill.quantity;
-
-                    if new_qty <= Decimal::ZERO {
-                        // Close position completely
-                        self.positions.remove(&order.symbol);
-                    } else {
-                        // Reduce position
-                        position.quantity = Quantity::new(new_qty)?;
-                    }
-                } else {
-                    return Err(Error::ExecutionError("No position to sell".to_string()));
+                let short_notional = self.short_exposure() + opening * fill.price;
+                let required = short_notional * self.margin.initial_margin;
+                if self.total_equity() - costs < required {
+                    return Err(Error::ExecutionError(format!(
+                        "Insufficient margin for short sale: requires {}, equity {}",
+                        required,
+                        self.total_equity() - costs
+                    )));
                 }
             }
+            OrderSide::Sell => {}
+        }
+
+        // Cash moves by the full fill; costs are split between the closing and
+        // opening legs so realized PnL only carries the closing leg's share
+        match order.side {
+            OrderSide::Buy => self.cash -= cost + costs,
+            OrderSide::Sell => self.cash += cost - costs,
+        }
+
+        if reducing > Decimal::ZERO {
+            let share = safe_div(safe_mul(costs, reducing)?, fill.quantity)?;
+            self.reduce_position(&order.symbol, reducing, fill.price, share)?;
+        }
+        if opening > Decimal::ZERO {
+            let side = match order.side {
+                OrderSide::Buy => PositionSide::Long,
+                OrderSide::Sell => PositionSide::Short,
+            };
+            self.extend_position(&order.symbol, side, opening, fill.price)?;
         }
 
         // Track costs
@@ -135,16 +181,124 @@ impl Portfolio {
         Ok(())
     }
 
+    /// Add `quantity` at `price` to the position on `side`, averaging the entry
+    fn extend_position(
+        &mut self,
+        symbol: &Symbol,
+        side: PositionSide,
+        quantity: Decimal,
+        price: Decimal,
+    ) -> Result<()> {
+        let fill_price = Price::new(price)?;
+        let position = self.positions.entry(symbol.clone()).or_insert_with(|| {
+            Position::new(
+                uuid::Uuid::new_v4(), // strategy_id
+                symbol.clone(),
+                side,
+                Quantity::new(Decimal::ZERO).unwrap(),
+                fill_price,
+            )
+        });
+
+        // Update position quantity and average price
+        let old_quantity = position.quantity.as_decimal();
+        let old_cost = safe_mul(old_quantity, position.avg_entry_price.as_decimal())?;
+        let new_quantity = old_quantity + quantity;
+        let new_avg_price = safe_div(old_cost + safe_mul(price, quantity)?, new_quantity)?;
+
+        position.quantity = Quantity::new(new_quantity)?;
+        position.avg_entry_price = Price::new(new_avg_price)?;
+        Ok(())
+    }
+
+    /// Close `quantity` of the position at `price`, booking realized PnL net of `costs`
+    fn reduce_position(
+        &mut self,
+        symbol: &Symbol,
+        quantity: Decimal,
+        price: Decimal,
+        costs: Decimal,
+    ) -> Result<()> {
+        let Some(position) = self.positions.get_mut(symbol) else {
+            return Ok(());
+        };
+
+        let entry_price = position.avg_entry_price.as_decimal();
+        let gross_pnl = match position.side {
+            PositionSide::Short => safe_mul(entry_price - price, quantity)?,
+            PositionSide::Long | PositionSide::Net => safe_mul(price - entry_price, quantity)?,
+        };
+        self.realized_pnl += gross_pnl - costs;
+
+        let new_qty = position.quantity.as_decimal() - quantity;
+        if new_qty <= Decimal::ZERO {
+            // Close position completely
+            self.positions.remove(symbol);
+        } else {
+            // Reduce position
+            position.quantity = Quantity::new(new_qty)?;
+        }
+        Ok(())
+    }
+
+    /// Charge borrow interest on open shorts for the time since the last accrual
+    pub fn accrue_borrow_cost(&mut self, timestamp: chrono::DateTime<chrono::Utc>) {
+        let last = self.borrow_accrued_at.replace(timestamp);
+        let Some(last) = last else {
+            return;
+        };
+        let seconds = (timestamp - last).num_seconds();
+        if seconds <= 0 {
+            return;
+        }
+
+        let exposure = self.short_exposure();
+        if exposure.is_zero() {
+            return;
+        }
+
+        let interest = exposure * self.margin.borrow_rate * Decimal::from(seconds)
+            / Decimal::from(SECONDS_PER_YEAR);
+        self.cash -= interest;
+        self.borrow_cost += interest;
+        self.realized_pnl -= interest;
+    }
+
+    /// Market value of open short positions
+    pub fn short_exposure(&self) -> Decimal {
+        self.positions
+            .values()
+            .filter(|p| p.side == PositionSide::Short)
+            .map(|p| p.quantity.as_decimal() * p.current_price.as_decimal())
+            .sum()
+    }
+
+    /// Book a funding payment (positive = received)
+    pub fn apply_funding(&mut self, amount: Decimal, timestamp: chrono::DateTime<chrono::Utc>) {
+        self.cash += amount;
+        self.funding_pnl += amount;
+        self.realized_pnl += amount;
+
+        let equity = self.total_equity();
+        self.equity_curve.push((timestamp, equity));
+    }
+
     /// Update current market prices
     pub fn update_prices(&mut self, prices: &HashMap<Symbol, Decimal>) {
         for (symbol, price) in prices {
-            self.current_prices.insert(symbol.clone(), *price);
+            // Called once per event: only allocate a key the first time a symbol is seen
+            match self.current_prices.get_mut(symbol) {
+                Some(current) => *current = *price,
+                None => {
+                    self.current_prices.insert(symbol.clone(), *price);
+                }
+            }
 
             // Update unrealized PnL for positions
-            if let Some(position) = self.positions.get_mut(symbol) {
-                if let Ok(price_obj) = Price::new(*price) {
-                    position.update_price(price_obj);
-                }
+            if let Some(position) = self.positions.get_mut(symbol)
+                && let Ok(price_obj) = Price::new(*price)
+            {
+                position.update_price(price_obj);
             }
         }
     }
@@ -154,7 +308,7 @@ impl Portfolio {
         self.positions.get(symbol)
     }
 
-    /// Get total equity (cash + unrealized PnL)
+    /// Get total equity (cash + long market value - short market value)
     pub fn total_equity(&self) -> Decimal {
         let positions_value: Decimal = self
             .positions
@@ -162,7 +316,10 @@ impl Portfolio {
             .map(|p| {
                 let qty = p.quantity.as_decimal();
                 let price = p.current_price.as_decimal();
-                qty * price
+                match p.side {
+                    PositionSide::Short => -qty * price,
+                    PositionSide::Long | PositionSide::Net => qty * price,
+                }
             })
             .sum();
 
@@ -186,11 +343,12 @@ impl Portfolio {
 
     /// Get return percentage
     pub fn return_pct(&self) -> Decimal {
-        if self.initial_capital == Decimal::ZERO {
-            return Decimal::ZERO;
-        }
-
-        (self.total_equity() - self.initial_capital) / self.initial_capital
+        div_or(
+            self.total_equity() - self.initial_capital,
+            self.initial_capital,
+            Decimal::ZERO,
+        )
+        .unwrap_or(Decimal::ZERO)
     }
 }
 
@@ -208,6 +366,34 @@ mod tests {
         assert_eq!(portfolio.total_equity(), dec!(10000.0));
     }
 
+    #[test]
+    fn test_new_position_opens_at_fill_price() {
+        let mut portfolio = Portfolio::new(dec!(10000.0));
+
+        let symbol = Symbol::new("ETH-USDT").unwrap();
+        let order = Order::new(
+            uuid::Uuid::new_v4(),
+            symbol.clone(),
+            OrderSide::Buy,
+            OrderType::Market,
+            ea_okx_core::Quantity::new(dec!(1.0)).unwrap(),
+            None,
+        );
+        let fill = Fill {
+            order_id: order.id,
+            price: dec!(3000.0),
+            quantity: dec!(1.0),
+            commission: dec!(3.0),
+            timestamp: chrono::Utc::now(),
+            slippage: Decimal::ZERO,
+        };
+        portfolio.apply_fill(&order, &fill).unwrap();
+
+        let position = portfolio.get_position(&symbol).unwrap();
+        assert_eq!(position.avg_entry_price.as_decimal(), dec!(3000.0));
+        assert_eq!(position.current_price.as_decimal(), dec!(3000.0));
+    }
+
     #[test]
     fn test_buy_order() {
         let mut portfolio = Portfolio::new(dec!(10000.0));
@@ -240,4 +426,130 @@ mod tests {
         let position = portfolio.get_position(&symbol).unwrap();
         assert_eq!(position.quantity.as_decimal(), dec!(0.1));
     }
+
+    #[test]
+    fn test_apply_funding() {
+        let mut portfolio = Portfolio::new(dec!(10000.0));
+
+        portfolio.apply_funding(dec!(1.5), chrono::Utc::now());
+        portfolio.apply_funding(dec!(-0.5), chrono::Utc::now());
+
+        assert_eq!(portfolio.cash, dec!(10001.0));
+        assert_eq!(portfolio.funding_pnl, dec!(1.0));
+        assert_eq!(portfolio.equity_curve.len(), 2);
+    }
+
+    fn fill_for(
+        portfolio: &mut Portfolio,
+        side: OrderSide,
+        qty: Decimal,
+        price: Decimal,
+    ) -> Result<()> {
+        let order = Order::new(
+            uuid::Uuid::new_v4(),
+            Symbol::new("BTC-USDT").unwrap(),
+            side,
+            OrderType::Market,
+            ea_okx_core::Quantity::new(qty).unwrap(),
+            None,
+        );
+        let fill = Fill {
+            order_id: order.id,
+            price,
+            quantity: qty,
+            commission: dec!(1.0),
+            timestamp: chrono::Utc::now(),
+            slippage: Decimal::ZERO,
+        };
+        portfolio.apply_fill(&order, &fill)
+    }
+
+    fn shorting() -> MarginConfig {
+        MarginConfig {
+            allow_short: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_overflowing_fill_is_rejected_without_side_effects() {
+        let mut portfolio = Portfolio::new(dec!(10000.0));
+
+        let err = fill_for(&mut portfolio, OrderSide::Buy, dec!(2), Decimal::MAX).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::CoreError(ea_okx_core::Error::ArithmeticOverflow(_))
+        ));
+        assert_eq!(portfolio.cash, dec!(10000.0));
+        assert!(portfolio.positions.is_empty());
+        assert!(portfolio.equity_curve.is_empty());
+
+        assert_eq!(Portfolio::new(Decimal::ZERO).return_pct(), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_short_open_and_cover() {
+        let mut portfolio = Portfolio::new(dec!(10000.0)).with_margin(shorting());
+        let symbol = Symbol::new("BTC-USDT").unwrap();
+
+        fill_for(&mut portfolio, OrderSide::Sell, dec!(0.1), dec!(50000.0)).unwrap();
+        let position = portfolio.get_position(&symbol).unwrap();
+        assert_eq!(position.side, PositionSide::Short);
+        assert_eq!(portfolio.cash, dec!(14999.0));
+        assert_eq!(portfolio.total_equity(), dec!(9999.0));
+
+        // Price drops: the short gains
+        portfolio.update_prices(&HashMap::from([(symbol.clone(), dec!(45000.0))]));
+        assert_eq!(portfolio.total_equity(), dec!(10499.0));
+
+        fill_for(&mut portfolio, OrderSide::Buy, dec!(0.1), dec!(45000.0)).unwrap();
+        assert!(portfolio.positions.is_empty());
+        assert_eq!(portfolio.realized_pnl, dec!(499.0));
+        assert_eq!(portfolio.cash, dec!(10498.0));
+    }
+
+    #[test]
+    fn test_short_requires_margin_and_permission() {
+        let mut spot = Portfolio::new(dec!(10000.0));
+        assert!(fill_for(&mut spot, OrderSide::Sell, dec!(0.1), dec!(50000.0)).is_err());
+        assert_eq!(spot.cash, dec!(10000.0));
+
+        // 0.5 initial margin allows at most ~20000 of short notional
+        let mut margin = Portfolio::new(dec!(10000.0)).with_margin(shorting());
+        assert!(fill_for(&mut margin, OrderSide::Sell, dec!(0.5), dec!(50000.0)).is_err());
+        assert!(margin.positions.is_empty());
+        fill_for(&mut margin, OrderSide::Sell, dec!(0.3), dec!(50000.0)).unwrap();
+    }
+
+    #[test]
+    fn test_sell_through_long_flips_short() {
+        let mut portfolio = Portfolio::new(dec!(10000.0)).with_margin(shorting());
+        let symbol = Symbol::new("BTC-USDT").unwrap();
+
+        fill_for(&mut portfolio, OrderSide::Buy, dec!(0.1), dec!(50000.0)).unwrap();
+        fill_for(&mut portfolio, OrderSide::Sell, dec!(0.3), dec!(51000.0)).unwrap();
+
+        let position = portfolio.get_position(&symbol).unwrap();
+        assert_eq!(position.side, PositionSide::Short);
+        assert_eq!(position.quantity.as_decimal(), dec!(0.2));
+        assert_eq!(position.avg_entry_price.as_decimal(), dec!(51000.0));
+        // Long leg: +100 gross less a third of the sell commission
+        assert_eq!(portfolio.realized_pnl.round_dp(4), dec!(99.6667));
+    }
+
+    #[test]
+    fn test_borrow_cost_accrues_on_shorts() {
+        let mut portfolio = Portfolio::new(dec!(10000.0)).with_margin(shorting());
+        let start = chrono::Utc::now();
+
+        portfolio.accrue_borrow_cost(start);
+        fill_for(&mut portfolio, OrderSide::Sell, dec!(0.1), dec!(50000.0)).unwrap();
+        let cash = portfolio.cash;
+
+        // 5000 notional at 10% a year for one day
+        portfolio.accrue_borrow_cost(start + chrono::Duration::days(1));
+        let expected = dec!(5000) * dec!(0.1) / dec!(365);
+        assert_eq!(portfolio.borrow_cost, expected);
+        assert_eq!(portfolio.cash, cash - expected);
+    }
 }
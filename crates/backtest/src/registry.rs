@@ -0,0 +1,694 @@
+//! Backtest run registry
+//!
+//! Every finished run is recorded with what it takes to reproduce and judge
+//! it: the hash of its configuration, the strategy and code versions it ran
+//! against, the dataset range, its headline metrics and the stored
+//! artifacts (the configuration itself and the full result). Runs can then be
+//! listed by strategy, symbol or configuration, and any set of them compared
+//! side by side: metrics as aligned rows, equity curves on one time axis.
+
+use crate::curve::{CurvePoint, CurveResolution, resample};
+use crate::engine::BacktestConfig;
+use crate::error::{Error, Result};
+use crate::results::BacktestResult;
+use chrono::{DateTime, Utc};
+use ea_okx_core::{Interval, Symbol};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use uuid::Uuid;
+
+/// Artifact holding the run's [`BacktestConfig`]
+pub const CONFIG_ARTIFACT: &str = "config.json";
+
+/// Artifact holding the run's full [`BacktestResult`]
+pub const RESULT_ARTIFACT: &str = "result.json";
+
+/// What produced a run, supplied by whoever started it
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RunProvenance {
+    pub strategy_id: Option<Uuid>,
+    pub strategy_name: String,
+
+    /// Strategy version or parameter revision
+    pub strategy_version: Option<String>,
+
+    /// Commit the engine and strategy code were built from
+    pub git_revision: Option<String>,
+}
+
+/// Headline metrics of a run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunMetrics {
+    pub initial_capital: Decimal,
+    pub final_equity: Decimal,
+    pub total_pnl: Decimal,
+    pub total_return_pct: Decimal,
+    pub sharpe_ratio: Decimal,
+    pub sortino_ratio: Decimal,
+    pub calmar_ratio: Decimal,
+    pub max_drawdown_pct: Decimal,
+    pub win_rate: Decimal,
+    pub profit_factor: Decimal,
+    pub total_trades: usize,
+    pub total_costs: Decimal,
+}
+
+/// Which way a metric improves, for picking the best run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Better {
+    Higher,
+    Lower,
+    Neither,
+}
+
+impl RunMetrics {
+    /// Metric names, values and which way each improves, in display order
+    fn rows(&self) -> Vec<(&'static str, Decimal, Better)> {
+        vec![
+            ("initial_capital", self.initial_capital, Better::Neither),
+            ("final_equity", self.final_equity, Better::Higher),
+            ("total_pnl", self.total_pnl, Better::Higher),
+            ("total_return_pct", self.total_return_pct, Better::Higher),
+            ("sharpe_ratio", self.sharpe_ratio, Better::Higher),
+            ("sortino_ratio", self.sortino_ratio, Better::Higher),
+            ("calmar_ratio", self.calmar_ratio, Better::Higher),
+            ("max_drawdown_pct", self.max_drawdown_pct, Better::Lower),
+            ("win_rate", self.win_rate, Better::Higher),
+            ("profit_factor", self.profit_factor, Better::Higher),
+            (
+                "total_trades",
+                Decimal::from(self.total_trades),
+                Better::Neither,
+            ),
+            ("total_costs", self.total_costs, Better::Lower),
+        ]
+    }
+}
+
+impl From<&BacktestResult> for RunMetrics {
+    fn from(result: &BacktestResult) -> Self {
+        Self {
+            initial_capital: result.initial_capital,
+            final_equity: result.final_equity,
+            total_pnl: result.total_pnl,
+            total_return_pct: result.total_return_pct,
+            sharpe_ratio: result.sharpe_ratio,
+            sortino_ratio: result.sortino_ratio,
+            calmar_ratio: result.calmar_ratio,
+            max_drawdown_pct: result.max_drawdown_pct,
+            win_rate: result.win_rate,
+            profit_factor: result.profit_factor,
+            total_trades: result.total_trades,
+            total_costs: result.total_costs,
+        }
+    }
+}
+
+/// One recorded backtest run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BacktestRun {
+    pub id: String,
+
+    #[serde(flatten)]
+    pub provenance: RunProvenance,
+
+    /// [`BacktestConfig::config_hash`] of the configuration it ran with
+    pub config_hash: String,
+    pub symbols: Vec<Symbol>,
+    pub interval: Interval,
+    pub dataset_start: DateTime<Utc>,
+    pub dataset_end: DateTime<Utc>,
+    pub metrics: RunMetrics,
+
+    /// Names of the artifacts stored with the run
+    pub artifacts: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Which runs [`BacktestRegistry::list`] returns
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BacktestFilter {
+    #[serde(default)]
+    pub strategy_id: Option<Uuid>,
+
+    /// Runs that traded this symbol, among others
+    #[serde(default)]
+    pub symbol: Option<Symbol>,
+
+    #[serde(default)]
+    pub config_hash: Option<String>,
+
+    /// Runs recorded at or after this time
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+
+    /// Runs recorded before this time
+    #[serde(default)]
+    pub until: Option<DateTime<Utc>>,
+
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+impl BacktestFilter {
+    pub fn matches(&self, run: &BacktestRun) -> bool {
+        self.strategy_id
+            .is_none_or(|id| run.provenance.strategy_id == Some(id))
+            && self
+                .symbol
+                .as_ref()
+                .is_none_or(|symbol| run.symbols.contains(symbol))
+            && self
+                .config_hash
+                .as_ref()
+                .is_none_or(|hash| &run.config_hash == hash)
+            && self.since.is_none_or(|since| run.created_at >= since)
+            && self.until.is_none_or(|until| run.created_at < until)
+    }
+}
+
+/// One metric across the compared runs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricRow {
+    pub metric: String,
+
+    /// One value per run, in the order the runs were requested
+    pub values: Vec<Decimal>,
+
+    /// Index of the best value, for metrics with a better direction
+    pub best: Option<usize>,
+}
+
+/// One run's equity on the shared time axis
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OverlaySeries {
+    pub run_id: String,
+
+    /// Equity at each shared timestamp, carried forward between the run's
+    /// own points; `None` before the run's first point
+    pub equity: Vec<Option<Decimal>>,
+
+    /// Equity over initial capital, so runs with different capital overlay
+    pub growth: Vec<Option<Decimal>>,
+}
+
+/// Equity curves of several runs aligned on one time axis
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EquityOverlay {
+    pub timestamps: Vec<DateTime<Utc>>,
+    pub series: Vec<OverlaySeries>,
+}
+
+/// Side-by-side view of several runs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BacktestComparison {
+    pub runs: Vec<BacktestRun>,
+    pub metrics: Vec<MetricRow>,
+    pub equity: EquityOverlay,
+}
+
+/// Storage backend for recorded runs and their artifacts
+pub trait BacktestRunStore: Send + Sync {
+    /// Insert or replace a run, with its configuration and result
+    fn save(
+        &self,
+        run: &BacktestRun,
+        config: &BacktestConfig,
+        result: &BacktestResult,
+    ) -> Result<()>;
+
+    /// Every recorded run, in no particular order
+    fn runs(&self) -> Result<Vec<BacktestRun>>;
+
+    fn run(&self, id: &str) -> Result<Option<BacktestRun>>;
+
+    /// Full result stored with a run
+    fn result(&self, id: &str) -> Result<Option<BacktestResult>>;
+}
+
+/// In-memory store, mainly for tests
+#[derive(Debug, Default)]
+pub struct InMemoryBacktestRunStore {
+    runs: RwLock<BTreeMap<String, (BacktestRun, BacktestResult)>>,
+}
+
+impl InMemoryBacktestRunStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl BacktestRunStore for InMemoryBacktestRunStore {
+    fn save(
+        &self,
+        run: &BacktestRun,
+        _config: &BacktestConfig,
+        result: &BacktestResult,
+    ) -> Result<()> {
+        self.runs
+            .write()
+            .map_err(|e| Error::RegistryError(e.to_string()))?
+            .insert(run.id.clone(), (run.clone(), result.clone()));
+        Ok(())
+    }
+
+    fn runs(&self) -> Result<Vec<BacktestRun>> {
+        let runs = self
+            .runs
+            .read()
+            .map_err(|e| Error::RegistryError(e.to_string()))?;
+        Ok(runs.values().map(|(run, _)| run.clone()).collect())
+    }
+
+    fn run(&self, id: &str) -> Result<Option<BacktestRun>> {
+        let runs = self
+            .runs
+            .read()
+            .map_err(|e| Error::RegistryError(e.to_string()))?;
+        Ok(runs.get(id).map(|(run, _)| run.clone()))
+    }
+
+    fn result(&self, id: &str) -> Result<Option<BacktestResult>> {
+        let runs = self
+            .runs
+            .read()
+            .map_err(|e| Error::RegistryError(e.to_string()))?;
+        Ok(runs.get(id).map(|(_, result)| result.clone()))
+    }
+}
+
+/// File-backed store keeping `run.json` and the artifacts in one directory
+/// per run
+#[derive(Debug)]
+pub struct FileBacktestRunStore {
+    dir: PathBuf,
+}
+
+impl FileBacktestRunStore {
+    /// Creates the store, creating the directory if needed
+    pub fn new(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).map_err(|e| {
+            Error::RegistryError(format!("Failed to create {}: {}", dir.display(), e))
+        })?;
+        Ok(Self { dir })
+    }
+
+    fn run_dir(&self, id: &str) -> Result<PathBuf> {
+        if id.is_empty() || id.contains(['/', '\\']) || id.starts_with('.') {
+            return Err(Error::RegistryError(format!("Invalid run ID: {}", id)));
+        }
+        Ok(self.dir.join(id))
+    }
+
+    fn read<T: serde::de::DeserializeOwned>(path: &Path) -> Result<Option<T>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let bytes = fs::read(path).map_err(|e| {
+            Error::RegistryError(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+        Ok(Some(serde_json::from_slice(&bytes)?))
+    }
+}
+
+impl BacktestRunStore for FileBacktestRunStore {
+    fn save(
+        &self,
+        run: &BacktestRun,
+        config: &BacktestConfig,
+        result: &BacktestResult,
+    ) -> Result<()> {
+        let dir = self.run_dir(&run.id)?;
+        fs::create_dir_all(&dir).map_err(|e| {
+            Error::RegistryError(format!("Failed to create {}: {}", dir.display(), e))
+        })?;
+
+        // The run record goes last, so a run is only listed once its
+        // artifacts are complete
+        for (name, contents) in [
+            (CONFIG_ARTIFACT, serde_json::to_vec_pretty(config)?),
+            (RESULT_ARTIFACT, serde_json::to_vec(result)?),
+            ("run.json", serde_json::to_vec_pretty(run)?),
+        ] {
+            let path = dir.join(name);
+            fs::write(&path, contents).map_err(|e| {
+                Error::RegistryError(format!("Failed to write {}: {}", path.display(), e))
+            })?;
+        }
+        Ok(())
+    }
+
+    fn runs(&self) -> Result<Vec<BacktestRun>> {
+        let entries = fs::read_dir(&self.dir).map_err(|e| {
+            Error::RegistryError(format!("Failed to read {}: {}", self.dir.display(), e))
+        })?;
+
+        let mut runs = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path().join("run.json");
+            match Self::read(&path) {
+                Ok(Some(run)) => runs.push(run),
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Skipping unreadable run {}: {}", path.display(), e),
+            }
+        }
+        Ok(runs)
+    }
+
+    fn run(&self, id: &str) -> Result<Option<BacktestRun>> {
+        Self::read(&self.run_dir(id)?.join("run.json"))
+    }
+
+    fn result(&self, id: &str) -> Result<Option<BacktestResult>> {
+        Self::read(&self.run_dir(id)?.join(RESULT_ARTIFACT))
+    }
+}
+
+/// Records runs and answers listing and comparison queries over them
+pub struct BacktestRegistry {
+    store: Arc<dyn BacktestRunStore>,
+}
+
+impl BacktestRegistry {
+    pub fn new(store: Arc<dyn BacktestRunStore>) -> Self {
+        Self { store }
+    }
+
+    pub fn store(&self) -> &Arc<dyn BacktestRunStore> {
+        &self.store
+    }
+
+    /// Record a finished run under a new `backtest_<uuid>` ID
+    pub fn record(
+        &self,
+        provenance: RunProvenance,
+        config: &BacktestConfig,
+        result: &BacktestResult,
+    ) -> Result<BacktestRun> {
+        let run = BacktestRun {
+            id: format!("backtest_{}", Uuid::new_v4()),
+            provenance,
+            config_hash: config.config_hash()?,
+            symbols: config.symbols.clone(),
+            interval: config.interval,
+            dataset_start: config.start_time,
+            dataset_end: config.end_time,
+            metrics: RunMetrics::from(result),
+            artifacts: vec![CONFIG_ARTIFACT.to_string(), RESULT_ARTIFACT.to_string()],
+            created_at: Utc::now(),
+        };
+        self.store.save(&run, config, result)?;
+        tracing::info!(
+            "Recorded backtest {} of {} (config {})",
+            run.id,
+            run.provenance.strategy_name,
+            &run.config_hash[..12]
+        );
+        Ok(run)
+    }
+
+    /// Runs matching `filter`, newest first
+    pub fn list(&self, filter: &BacktestFilter) -> Result<Vec<BacktestRun>> {
+        let mut runs: Vec<BacktestRun> = self
+            .store
+            .runs()?
+            .into_iter()
+            .filter(|run| filter.matches(run))
+            .collect();
+        runs.sort_by_key(|run| std::cmp::Reverse(run.created_at));
+        if let Some(limit) = filter.limit {
+            runs.truncate(limit);
+        }
+        Ok(runs)
+    }
+
+    pub fn run(&self, id: &str) -> Result<BacktestRun> {
+        self.store
+            .run(id)?
+            .ok_or_else(|| Error::RunNotFound(id.to_string()))
+    }
+
+    pub fn result(&self, id: &str) -> Result<BacktestResult> {
+        self.store
+            .result(id)?
+            .ok_or_else(|| Error::RunNotFound(id.to_string()))
+    }
+
+    /// Compare runs in the order given, resampling equity curves to
+    /// `resolution` before aligning them
+    pub fn compare(
+        &self,
+        ids: &[String],
+        resolution: CurveResolution,
+    ) -> Result<BacktestComparison> {
+        if ids.is_empty() {
+            return Err(Error::InvalidConfig(
+                "At least one backtest is needed to compare".to_string(),
+            ));
+        }
+
+        let mut runs = Vec::with_capacity(ids.len());
+        let mut curves = Vec::with_capacity(ids.len());
+        for id in ids {
+            runs.push(self.run(id)?);
+            curves.push(resample(&self.result(id)?.equity_curve, resolution));
+        }
+
+        Ok(BacktestComparison {
+            metrics: metric_rows(&runs),
+            equity: overlay(&runs, &curves),
+            runs,
+        })
+    }
+}
+
+fn metric_rows(runs: &[BacktestRun]) -> Vec<MetricRow> {
+    let per_run: Vec<_> = runs.iter().map(|run| run.metrics.rows()).collect();
+    let Some(first) = per_run.first() else {
+        return Vec::new();
+    };
+
+    first
+        .iter()
+        .enumerate()
+        .map(|(i, (metric, _, better))| {
+            let values: Vec<Decimal> = per_run.iter().map(|rows| rows[i].1).collect();
+            let indexed = values.iter().enumerate();
+            let best = match better {
+                Better::Higher => indexed.max_by(|a, b| a.1.cmp(b.1)).map(|(i, _)| i),
+                Better::Lower => indexed.min_by(|a, b| a.1.cmp(b.1)).map(|(i, _)| i),
+                Better::Neither => None,
+            };
+            MetricRow {
+                metric: metric.to_string(),
+                values,
+                best,
+            }
+        })
+        .collect()
+}
+
+/// Put every curve on the union of their timestamps
+fn overlay(runs: &[BacktestRun], curves: &[Vec<CurvePoint>]) -> EquityOverlay {
+    let mut timestamps: Vec<DateTime<Utc>> = curves.iter().flatten().map(|(t, _)| *t).collect();
+    timestamps.sort();
+    timestamps.dedup();
+
+    let series = runs
+        .iter()
+        .zip(curves)
+        .map(|(run, curve)| {
+            let capital = run.metrics.initial_capital;
+            let mut points = curve.iter().peekable();
+            let mut current = None;
+            let equity: Vec<Option<Decimal>> = timestamps
+                .iter()
+                .map(|timestamp| {
+                    while let Some((_, value)) = points.next_if(|(t, _)| t <= timestamp) {
+                        current = Some(*value);
+                    }
+                    current
+                })
+                .collect();
+            let growth = equity
+                .iter()
+                .map(|value| value.filter(|_| !capital.is_zero()).map(|v| v / capital))
+                .collect();
+            OverlaySeries {
+                run_id: run.id.clone(),
+                equity,
+                growth,
+            }
+        })
+        .collect();
+
+    EquityOverlay { timestamps, series }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::PositionSizing;
+    use crate::portfolio::Portfolio;
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 1, hour, 0, 0).unwrap()
+    }
+
+    fn result(capital: Decimal, curve: Vec<(DateTime<Utc>, Decimal)>) -> BacktestResult {
+        let final_equity = curve.last().map_or(capital, |(_, e)| *e);
+        BacktestResult::from_portfolio_and_trades(
+            &Portfolio::new(capital),
+            &[],
+            capital,
+            at(0),
+            at(23),
+        )
+        .map(|mut r| {
+            r.equity_curve = curve;
+            r.final_equity = final_equity;
+            r.total_pnl = final_equity - capital;
+            r.max_drawdown_pct = if final_equity < capital {
+                dec!(10)
+            } else {
+                dec!(2)
+            };
+            r
+        })
+        .unwrap()
+    }
+
+    fn provenance(strategy_id: Uuid) -> RunProvenance {
+        RunProvenance {
+            strategy_id: Some(strategy_id),
+            strategy_name: "ma_cross".to_string(),
+            strategy_version: Some("3".to_string()),
+            git_revision: Some("abc123".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_config_hash_ignores_execution_settings() {
+        let config = BacktestConfig::default();
+        let mut verbose = config.clone();
+        verbose.verbose = true;
+        assert_eq!(
+            config.config_hash().unwrap(),
+            verbose.config_hash().unwrap()
+        );
+
+        let mut sized = config.clone();
+        sized.position_sizing = PositionSizing::Fixed(dec!(1000));
+        assert_ne!(config.config_hash().unwrap(), sized.config_hash().unwrap());
+    }
+
+    #[test]
+    fn test_file_store_lists_and_filters_runs() {
+        let dir = std::env::temp_dir().join(format!("ea-okx-backtests-{}", Uuid::new_v4()));
+        let registry = BacktestRegistry::new(Arc::new(FileBacktestRunStore::new(&dir).unwrap()));
+        let strategy = Uuid::new_v4();
+
+        let config = BacktestConfig::default();
+        let first = registry
+            .record(
+                provenance(strategy),
+                &config,
+                &result(dec!(1000), vec![(at(1), dec!(1100))]),
+            )
+            .unwrap();
+        let mut eth = config.clone();
+        eth.symbols = vec![Symbol::new("ETH-USDT").unwrap()];
+        let second = registry
+            .record(
+                provenance(Uuid::new_v4()),
+                &eth,
+                &result(dec!(1000), vec![(at(1), dec!(900))]),
+            )
+            .unwrap();
+
+        let all = registry.list(&BacktestFilter::default()).unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].id, second.id);
+
+        let by_strategy = BacktestFilter {
+            strategy_id: Some(strategy),
+            ..Default::default()
+        };
+        assert_eq!(registry.list(&by_strategy).unwrap(), vec![first.clone()]);
+        let by_symbol = BacktestFilter {
+            symbol: Some(Symbol::new("ETH-USDT").unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(registry.list(&by_symbol).unwrap()[0].id, second.id);
+
+        assert_eq!(registry.result(&first.id).unwrap().final_equity, dec!(1100));
+        assert!(dir.join(&first.id).join(CONFIG_ARTIFACT).exists());
+        assert!(matches!(
+            registry.run("backtest_missing"),
+            Err(Error::RunNotFound(_))
+        ));
+        assert!(registry.run("../escape").is_err());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_compare_aligns_metrics_and_curves() {
+        let registry = BacktestRegistry::new(Arc::new(InMemoryBacktestRunStore::new()));
+        let config = BacktestConfig::default();
+        let winner = registry
+            .record(
+                provenance(Uuid::new_v4()),
+                &config,
+                &result(dec!(1000), vec![(at(0), dec!(1000)), (at(2), dec!(1200))]),
+            )
+            .unwrap();
+        let loser = registry
+            .record(
+                provenance(Uuid::new_v4()),
+                &config,
+                &result(dec!(2000), vec![(at(1), dec!(2000)), (at(3), dec!(1800))]),
+            )
+            .unwrap();
+
+        let comparison = registry
+            .compare(&[winner.id.clone(), loser.id.clone()], CurveResolution::Raw)
+            .unwrap();
+
+        let row = |name: &str| {
+            comparison
+                .metrics
+                .iter()
+                .find(|row| row.metric == name)
+                .unwrap()
+                .clone()
+        };
+        assert_eq!(row("total_pnl").values, vec![dec!(200), dec!(-200)]);
+        assert_eq!(row("total_pnl").best, Some(0));
+        assert_eq!(row("max_drawdown_pct").best, Some(0));
+        assert_eq!(row("initial_capital").best, None);
+
+        let equity = &comparison.equity;
+        assert_eq!(equity.timestamps, vec![at(0), at(1), at(2), at(3)]);
+        assert_eq!(
+            equity.series[0].equity,
+            vec![
+                Some(dec!(1000)),
+                Some(dec!(1000)),
+                Some(dec!(1200)),
+                Some(dec!(1200))
+            ]
+        );
+        assert_eq!(equity.series[1].equity[0], None);
+        assert_eq!(equity.series[1].growth[3], Some(dec!(0.9)));
+
+        assert!(registry.compare(&[], CurveResolution::Raw).is_err());
+    }
+}
@@ -1,10 +1,21 @@
+use crate::curve::{CurvePage, CurveQuery};
 use crate::error::Result;
 use crate::events::Trade;
+use crate::fills::LimitFillReport;
+use crate::gaps::GapReport;
+use crate::intrabar::ExitTrigger;
+use crate::maintenance::MaintenanceReport;
 use crate::portfolio::Portfolio;
 use chrono::{DateTime, Utc};
+use ea_okx_core::math::{div_or, safe_div, safe_mul, safe_sqrt};
+use ea_okx_strategy::metrics::{DEFAULT_ROLLING_WINDOWS, RollingMetrics};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const TRADING_DAYS_PER_YEAR: Decimal = dec!(252);
 
 /// Complete backtest results with performance metrics
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +36,10 @@ pub struct BacktestResult {
     pub losing_trades: usize,
     pub win_rate: Decimal,
 
+    /// Trades closed by a stop-loss / take-profit
+    pub stop_loss_exits: usize,
+    pub take_profit_exits: usize,
+
     /// P&L metrics
     pub gross_profit: Decimal,
     pub gross_loss: Decimal,
@@ -46,6 +61,9 @@ pub struct BacktestResult {
     pub total_slippage: Decimal,
     pub total_costs: Decimal,
 
+    /// Net funding received on perpetual hedges
+    pub total_funding: Decimal,
+
     /// Time metrics
     pub avg_trade_duration_hours: Decimal,
     pub max_trade_duration_hours: Decimal,
@@ -56,6 +74,24 @@ pub struct BacktestResult {
 
     /// Drawdown curve
     pub drawdown_curve: Vec<(DateTime<Utc>, Decimal)>,
+
+    /// Rolling 30/90/180-day Sharpe, Sortino, volatility and win rate
+    pub rolling_metrics: RollingMetrics,
+
+    /// Limit order fills against what the naive touch model would have filled
+    pub limit_fills: LimitFillReport,
+
+    /// Missing candles seen during the run and the policy applied to them
+    #[serde(default)]
+    pub gaps: GapReport,
+
+    /// Simulated exchange downtime and the orders and candles it cost
+    #[serde(default)]
+    pub maintenance: MaintenanceReport,
+
+    /// Reports of the run's observers, keyed by observer name
+    #[serde(default)]
+    pub analytics: BTreeMap<String, serde_json::Value>,
 }
 
 impl BacktestResult {
@@ -69,7 +105,7 @@ impl BacktestResult {
         let final_equity = portfolio.total_equity();
         let total_pnl = final_equity - initial_capital;
         let total_return_pct = if initial_capital > Decimal::ZERO {
-            total_pnl / initial_capital
+            safe_div(total_pnl, initial_capital)?
         } else {
             Decimal::ZERO
         };
@@ -78,11 +114,20 @@ impl BacktestResult {
         let total_trades = trades.len();
         let winning_trades = trades.iter().filter(|t| t.pnl > Decimal::ZERO).count();
         let losing_trades = trades.iter().filter(|t| t.pnl < Decimal::ZERO).count();
-        let win_rate = if total_trades > 0 {
-            Decimal::from(winning_trades) / Decimal::from(total_trades)
-        } else {
-            Decimal::ZERO
-        };
+        let win_rate = div_or(
+            Decimal::from(winning_trades),
+            Decimal::from(total_trades),
+            Decimal::ZERO,
+        )?;
+
+        let stop_loss_exits = trades
+            .iter()
+            .filter(|t| t.exit_trigger == Some(ExitTrigger::StopLoss))
+            .count();
+        let take_profit_exits = trades
+            .iter()
+            .filter(|t| t.exit_trigger == Some(ExitTrigger::TakeProfit))
+            .count();
 
         // Calculate P&L metrics
         let gross_profit: Decimal = trades
@@ -98,24 +143,16 @@ impl BacktestResult {
             .sum();
 
         let profit_factor = if gross_loss > Decimal::ZERO {
-            gross_profit / gross_loss
+            // A ratio too large to represent is as good as no losses at all
+            safe_div(gross_profit, gross_loss).unwrap_or(Decimal::MAX)
         } else if gross_profit > Decimal::ZERO {
             Decimal::MAX
         } else {
             Decimal::ZERO
         };
 
-        let average_win = if winning_trades > 0 {
-            gross_profit / Decimal::from(winning_trades)
-        } else {
-            Decimal::ZERO
-        };
-
-        let average_loss = if losing_trades > 0 {
-            gross_loss / Decimal::from(losing_trades)
-        } else {
-            Decimal::ZERO
-        };
+        let average_win = div_or(gross_profit, Decimal::from(winning_trades), Decimal::ZERO)?;
+        let average_loss = div_or(gross_loss, Decimal::from(losing_trades), Decimal::ZERO)?;
 
         let largest_win = trades.iter().map(|t| t.pnl).max().unwrap_or(Decimal::ZERO);
 
@@ -126,11 +163,11 @@ impl BacktestResult {
             Self::calculate_drawdown(&portfolio.equity_curve);
 
         // Calculate risk metrics
-        let sharpe_ratio = Self::calculate_sharpe_ratio(&portfolio.equity_curve);
-        let sortino_ratio = Self::calculate_sortino_ratio(&portfolio.equity_curve);
+        let sharpe_ratio = Self::calculate_sharpe_ratio(&portfolio.equity_curve)?;
+        let sortino_ratio = Self::calculate_sortino_ratio(&portfolio.equity_curve)?;
 
         let calmar_ratio = if max_drawdown_pct.abs() > dec!(0.0001) {
-            total_return_pct / max_drawdown_pct.abs()
+            safe_div(total_return_pct, max_drawdown_pct.abs())?
         } else {
             Decimal::ZERO
         };
@@ -142,16 +179,26 @@ impl BacktestResult {
             .map(|d| Decimal::from(d.num_hours()))
             .collect();
 
-        let avg_trade_duration_hours = if !durations.is_empty() {
-            durations.iter().sum::<Decimal>() / Decimal::from(durations.len())
-        } else {
-            Decimal::ZERO
-        };
+        let avg_trade_duration_hours = div_or(
+            durations.iter().sum::<Decimal>(),
+            Decimal::from(durations.len()),
+            Decimal::ZERO,
+        )?;
 
         let max_trade_duration_hours = durations.iter().copied().max().unwrap_or(Decimal::ZERO);
 
         let min_trade_duration_hours = durations.iter().copied().min().unwrap_or(Decimal::ZERO);
 
+        let closed_trades: Vec<(DateTime<Utc>, Decimal)> = trades
+            .iter()
+            .filter_map(|t| t.exit_time.map(|exit| (exit, t.pnl)))
+            .collect();
+        let rolling_metrics = RollingMetrics::calculate(
+            &portfolio.equity_curve,
+            &closed_trades,
+            &DEFAULT_ROLLING_WINDOWS,
+        );
+
         Ok(Self {
             start_time,
             end_time,
@@ -163,6 +210,8 @@ impl BacktestResult {
             winning_trades,
             losing_trades,
             win_rate,
+            stop_loss_exits,
+            take_profit_exits,
             gross_profit,
             gross_loss,
             profit_factor,
@@ -178,11 +227,17 @@ impl BacktestResult {
             total_commission: portfolio.total_commission,
             total_slippage: portfolio.total_slippage,
             total_costs: portfolio.total_commission + portfolio.total_slippage,
+            total_funding: portfolio.funding_pnl,
             avg_trade_duration_hours,
             max_trade_duration_hours,
             min_trade_duration_hours,
             equity_curve: portfolio.equity_curve.clone(),
             drawdown_curve,
+            rolling_metrics,
+            limit_fills: LimitFillReport::default(),
+            gaps: GapReport::default(),
+            maintenance: MaintenanceReport::default(),
+            analytics: BTreeMap::new(),
         })
     }
 
@@ -218,84 +273,60 @@ impl BacktestResult {
         (max_dd, max_dd_pct, dd_curve)
     }
 
-    /// Calculate Sharpe ratio (annualized)
-    fn calculate_sharpe_ratio(equity_curve: &[(DateTime<Utc>, Decimal)]) -> Decimal {
-        if equity_curve.len() < 2 {
-            return Decimal::ZERO;
-        }
-
-        // Calculate returns
-        let mut returns = Vec::new();
-        for i in 1..equity_curve.len() {
-            let prev_equity = equity_curve[i - 1].1;
-            let curr_equity = equity_curve[i].1;
+    /// Period-over-period returns, skipping periods that start at zero equity
+    fn period_returns(equity_curve: &[(DateTime<Utc>, Decimal)]) -> Result<Vec<Decimal>> {
+        equity_curve
+            .windows(2)
+            .filter(|pair| pair[0].1 > Decimal::ZERO)
+            .map(|pair| Ok(safe_div(pair[1].1 - pair[0].1, pair[0].1)?))
+            .collect()
+    }
 
-            if prev_equity > Decimal::ZERO {
-                let ret = (curr_equity - prev_equity) / prev_equity;
-                returns.push(ret);
-            }
+    /// Mean return annualized, divided by `deviation` annualized
+    fn annualized_ratio(mean: Decimal, deviation: Decimal) -> Result<Decimal> {
+        if deviation <= Decimal::ZERO {
+            return Ok(Decimal::ZERO);
         }
+        // Assume 252 trading days per year
+        let annualized_return = safe_mul(mean, TRADING_DAYS_PER_YEAR)?;
+        let annualized_deviation = safe_mul(deviation, safe_sqrt(TRADING_DAYS_PER_YEAR)?)?;
+        Ok(safe_div(annualized_return, annualized_deviation)?)
+    }
 
+    /// Calculate Sharpe ratio (annualized)
+    fn calculate_sharpe_ratio(equity_curve: &[(DateTime<Utc>, Decimal)]) -> Result<Decimal> {
+        let returns = Self::period_returns(equity_curve)?;
         if returns.is_empty() {
-            return Decimal::ZERO;
+            return Ok(Decimal::ZERO);
         }
 
-        // Calculate mean return
-        let mean: Decimal = returns.iter().sum::<Decimal>() / Decimal::from(returns.len());
-
-        // Calculate standard deviation
-        let variance: Decimal = returns
-            .iter()
-            .map(|r| {
-                let diff = r - mean;
-                diff * diff
-            })
-            .sum::<Decimal>()
-            / Decimal::from(returns.len());
-
-        let std_dev = if variance > Decimal::ZERO {
-            let variance_f64 = variance.to_string().parse::<f64>().unwrap_or(0.0);
-            Decimal::from_f64_retain(variance_f64.sqrt()).unwrap_or(Decimal::ZERO)
-        } else {
-            Decimal::ZERO
-        };
-
-        if std_dev > Decimal::ZERO {
-            // Assume 252 trading days per year
-            let annualized_return = mean * dec!(252.0);
-            let sqrt_252 = Decimal::from_f64_retain(252.0_f64.sqrt()).unwrap_or(Decimal::ONE);
-            let annualized_std = std_dev * sqrt_252;
-
-            annualized_return / annualized_std
-        } else {
-            Decimal::ZERO
-        }
+        let count = Decimal::from(returns.len());
+        let mean = safe_div(returns.iter().sum::<Decimal>(), count)?;
+        let variance = safe_div(
+            returns
+                .iter()
+                .map(|r| {
+                    let diff = r - mean;
+                    diff * diff
+                })
+                .sum::<Decimal>(),
+            count,
+        )?;
+
+        Self::annualized_ratio(mean, safe_sqrt(variance)?)
     }
 
     /// Calculate Sortino ratio (annualized, using downside deviation)
-    fn calculate_sortino_ratio(equity_curve: &[(DateTime<Utc>, Decimal)]) -> Decimal {
-        if equity_curve.len() < 2 {
-            return Decimal::ZERO;
-        }
-
-        // Calculate returns
-        let mut returns = Vec::new();
-        for i in 1..equity_curve.len() {
-            let prev_equity = equity_curve[i - 1].1;
-            let curr_equity = equity_curve[i].1;
-
-            if prev_equity > Decimal::ZERO {
-                let ret = (curr_equity - prev_equity) / prev_equity;
-                returns.push(ret);
-            }
-        }
-
+    fn calculate_sortino_ratio(equity_curve: &[(DateTime<Utc>, Decimal)]) -> Result<Decimal> {
+        let returns = Self::period_returns(equity_curve)?;
         if returns.is_empty() {
-            return Decimal::ZERO;
+            return Ok(Decimal::ZERO);
         }
 
-        // Calculate mean return
-        let mean: Decimal = returns.iter().sum::<Decimal>() / Decimal::from(returns.len());
+        let mean = safe_div(
+            returns.iter().sum::<Decimal>(),
+            Decimal::from(returns.len()),
+        )?;
 
         // Calculate downside deviation (only negative returns)
         let downside_returns: Vec<Decimal> = returns
@@ -305,34 +336,37 @@ impl BacktestResult {
             .collect();
 
         if downside_returns.is_empty() {
-            return Decimal::MAX;
+            return Ok(Decimal::MAX);
         }
 
-        let downside_variance: Decimal = downside_returns.iter().map(|r| r * r).sum::<Decimal>()
-            / Decimal::from(downside_returns.len());
+        let downside_variance = safe_div(
+            downside_returns.iter().map(|r| r * r).sum::<Decimal>(),
+            Decimal::from(downside_returns.len()),
+        )?;
 
-        let downside_dev = if downside_variance > Decimal::ZERO {
-            let variance_f64 = downside_variance.to_string().parse::<f64>().unwrap_or(0.0);
-            Decimal::from_f64_retain(variance_f64.sqrt()).unwrap_or(Decimal::ZERO)
-        } else {
-            Decimal::ZERO
-        };
+        Self::annualized_ratio(mean, safe_sqrt(downside_variance)?)
+    }
 
-        if downside_dev > Decimal::ZERO {
-            // Assume 252 trading days per year
-            let annualized_return = mean * dec!(252.0);
-            let sqrt_252 = Decimal::from_f64_retain(252.0_f64.sqrt()).unwrap_or(Decimal::ONE);
-            let annualized_dd = downside_dev * sqrt_252;
+    /// Equity curve reduced and paged for charting
+    pub fn equity_curve_page(&self, query: &CurveQuery) -> CurvePage {
+        query.apply(&self.equity_curve)
+    }
 
-            annualized_return / annualized_dd
-        } else {
-            Decimal::ZERO
-        }
+    /// Drawdown curve reduced and paged for charting
+    pub fn drawdown_curve_page(&self, query: &CurveQuery) -> CurvePage {
+        query.apply(&self.drawdown_curve)
+    }
+
+    /// Report of the observer registered under `name`, e.g.
+    /// `result.observer_report::<ExcursionReport>(ExcursionObserver::NAME)`
+    pub fn observer_report<T: DeserializeOwned>(&self, name: &str) -> Option<T> {
+        let report = self.analytics.get(name)?;
+        serde_json::from_value(report.clone()).ok()
     }
 
     /// Generate a summary report
     pub fn summary(&self) -> String {
-        format!(
+        let mut summary = format!(
             r#"
 === Backtest Results ===
 
@@ -370,6 +404,7 @@ Costs:
   Commission: ${:.2}
   Slippage: ${:.2}
   Total Costs: ${:.2}
+  Funding: ${:.2}
 
 Trade Duration:
   Average: {:.2} hours
@@ -404,9 +439,136 @@ Trade Duration:
             self.total_commission,
             self.total_slippage,
             self.total_costs,
+            self.total_funding,
             self.avg_trade_duration_hours,
             self.max_trade_duration_hours,
             self.min_trade_duration_hours,
+        );
+
+        let fills = &self.limit_fills;
+        if fills.orders > 0 {
+            summary.push_str(&format!(
+                r#"
+Limit Fills ({:?}):
+  Orders: {}
+  Touched: {}
+  Filled: {}
+  Missed vs Touch: {}
+"#,
+                fills.model,
+                fills.orders,
+                fills.touch_fills,
+                fills.filled,
+                fills.missed_fills(),
+            ));
+        }
+
+        let gaps = &self.gaps;
+        if !gaps.gaps.is_empty() || gaps.filled_bars > 0 {
+            summary.push_str(&format!(
+                r#"
+Data Gaps ({:?}):
+  Gaps: {}
+  Missing Bars: {}
+  Filled Bars: {}
+"#,
+                gaps.policy,
+                gaps.gaps.len(),
+                gaps.missing_bars(),
+                gaps.filled_bars,
+            ));
+        }
+
+        let maintenance = &self.maintenance;
+        if !maintenance.windows.is_empty() {
+            summary.push_str(&format!(
+                r#"
+Exchange Maintenance:
+  Windows: {}
+  Downtime: {} min
+  Withheld Candles: {}
+  Cancelled Orders: {}
+  Rejected Orders: {}
+"#,
+                maintenance.windows.len(),
+                maintenance.downtime().num_minutes(),
+                maintenance.withheld_candles(),
+                maintenance.cancelled_orders(),
+                maintenance.rejected_orders(),
+            ));
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn curve(equity: &[Decimal]) -> Vec<(DateTime<Utc>, Decimal)> {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        equity
+            .iter()
+            .enumerate()
+            .map(|(day, value)| (start + Duration::days(day as i64), *value))
+            .collect()
+    }
+
+    #[test]
+    fn test_sharpe_is_exact_in_decimal() {
+        // Returns +10% and -10%: mean 0 so Sharpe 0 exactly
+        let flat = curve(&[dec!(100), dec!(110), dec!(99)]);
+        assert_eq!(
+            BacktestResult::calculate_sharpe_ratio(&flat).unwrap(),
+            Decimal::ZERO
+        );
+
+        // Returns +2% and +1%: mean 1.5%, std 0.5% -> 3 * sqrt(252)
+        let rising = curve(&[dec!(100), dec!(102), dec!(103.02)]);
+        let sharpe = BacktestResult::calculate_sharpe_ratio(&rising).unwrap();
+        assert_eq!(sharpe.round_dp(10), dec!(47.6235235992));
+    }
+
+    #[test]
+    fn test_ratio_edge_cases() {
+        // Too little history, or constant returns: nothing to divide by
+        assert_eq!(
+            BacktestResult::calculate_sharpe_ratio(&curve(&[dec!(100)])).unwrap(),
+            Decimal::ZERO
+        );
+        let steady = curve(&[dec!(100), dec!(110), dec!(121)]);
+        assert_eq!(
+            BacktestResult::calculate_sharpe_ratio(&steady).unwrap(),
+            Decimal::ZERO
+        );
+        assert_eq!(
+            BacktestResult::calculate_sortino_ratio(&steady).unwrap(),
+            Decimal::MAX
+        );
+
+        // Periods starting from a wiped-out account are skipped, not divided by
+        let wiped = curve(&[dec!(100), Decimal::ZERO, dec!(50), dec!(40)]);
+        let sortino = BacktestResult::calculate_sortino_ratio(&wiped).unwrap();
+        assert!(sortino < Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_results_on_empty_and_zero_capital_portfolios() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let result = BacktestResult::from_portfolio_and_trades(
+            &Portfolio::new(Decimal::ZERO),
+            &[],
+            Decimal::ZERO,
+            start,
+            start + Duration::days(1),
         )
+        .unwrap();
+
+        assert_eq!(result.total_return_pct, Decimal::ZERO);
+        assert_eq!(result.win_rate, Decimal::ZERO);
+        assert_eq!(result.profit_factor, Decimal::ZERO);
+        assert_eq!(result.avg_trade_duration_hours, Decimal::ZERO);
+        assert_eq!(result.sharpe_ratio, Decimal::ZERO);
     }
 }
@@ -0,0 +1,398 @@
+//! Commission and slippage sensitivity
+//!
+//! A backtest result is only as good as its cost assumptions. The sweep
+//! reruns one backtest with commission and slippage scaled across a grid of
+//! multipliers, 0.5× to 3× by default, and reports how return, Sharpe and
+//! drawdown degrade against the run at the configured costs. A strategy that
+//! stops making money once costs double, or only makes money below them, is
+//! flagged before it goes live rather than after.
+
+use crate::engine::{BacktestConfig, BacktestEngine, HistoricalDataSource};
+use crate::error::{Error, Result};
+use crate::results::BacktestResult;
+use ea_okx_strategy::traits::Strategy;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+/// Multipliers applied to the configured commission and slippage
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SensitivityGrid {
+    pub commission_multipliers: Vec<Decimal>,
+    pub slippage_multipliers: Vec<Decimal>,
+}
+
+impl Default for SensitivityGrid {
+    fn default() -> Self {
+        let multipliers = vec![dec!(0.5), dec!(1), dec!(1.5), dec!(2), dec!(3)];
+        Self {
+            commission_multipliers: multipliers.clone(),
+            slippage_multipliers: multipliers,
+        }
+    }
+}
+
+impl SensitivityGrid {
+    /// Sorted, deduplicated multipliers for one axis, always including 1×
+    /// so the configured costs are the baseline
+    fn axis(multipliers: &[Decimal], name: &str) -> Result<Vec<Decimal>> {
+        if let Some(m) = multipliers.iter().find(|m| m.is_sign_negative()) {
+            return Err(Error::InvalidConfig(format!(
+                "{} multiplier must not be negative: {}",
+                name, m
+            )));
+        }
+        let mut axis: Vec<Decimal> = multipliers.iter().map(|m| m.normalize()).collect();
+        axis.push(Decimal::ONE);
+        axis.sort();
+        axis.dedup();
+        Ok(axis)
+    }
+
+    /// Number of backtests a sweep over this grid runs
+    pub fn len(&self) -> usize {
+        let commission = Self::axis(&self.commission_multipliers, "commission");
+        let slippage = Self::axis(&self.slippage_multipliers, "slippage");
+        match (commission, slippage) {
+            (Ok(c), Ok(s)) => c.len() * s.len(),
+            _ => 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Outcome of one run in the sweep
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SensitivityPoint {
+    pub commission_multiplier: Decimal,
+    pub slippage_multiplier: Decimal,
+    pub total_return_pct: Decimal,
+    pub sharpe_ratio: Decimal,
+    pub max_drawdown_pct: Decimal,
+    pub total_costs: Decimal,
+    pub total_trades: usize,
+
+    /// Return against the baseline, in percentage points
+    pub return_change_pct: Decimal,
+    pub sharpe_change: Decimal,
+
+    /// Drawdown against the baseline, in percentage points (positive is worse)
+    pub drawdown_change_pct: Decimal,
+}
+
+impl SensitivityPoint {
+    fn new(commission: Decimal, slippage: Decimal, result: &BacktestResult) -> Self {
+        Self {
+            commission_multiplier: commission,
+            slippage_multiplier: slippage,
+            total_return_pct: result.total_return_pct,
+            sharpe_ratio: result.sharpe_ratio,
+            max_drawdown_pct: result.max_drawdown_pct,
+            total_costs: result.total_costs,
+            total_trades: result.total_trades,
+            return_change_pct: Decimal::ZERO,
+            sharpe_change: Decimal::ZERO,
+            drawdown_change_pct: Decimal::ZERO,
+        }
+    }
+
+    pub fn is_baseline(&self) -> bool {
+        self.commission_multiplier == Decimal::ONE && self.slippage_multiplier == Decimal::ONE
+    }
+
+    pub fn is_profitable(&self) -> bool {
+        self.total_return_pct > Decimal::ZERO
+    }
+}
+
+/// How a strategy holds up as costs rise
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CostVerdict {
+    /// Profitable at every point of the grid
+    Robust,
+
+    /// Profitable at the configured costs but not at every point
+    Sensitive,
+
+    /// Not profitable at the configured costs
+    Unprofitable,
+}
+
+/// Every run of a sweep, compared with the run at the configured costs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SensitivityReport {
+    pub baseline: SensitivityPoint,
+
+    /// Ordered by commission multiplier, then slippage multiplier
+    pub points: Vec<SensitivityPoint>,
+    pub verdict: CostVerdict,
+
+    /// Smallest multiplier that, applied to both commission and slippage,
+    /// leaves the strategy without a profit
+    pub break_even_multiplier: Option<Decimal>,
+}
+
+impl SensitivityReport {
+    fn new(points: Vec<SensitivityPoint>) -> Result<Self> {
+        let baseline = points
+            .iter()
+            .find(|p| p.is_baseline())
+            .cloned()
+            .ok_or_else(|| Error::ExecutionError("Sweep has no baseline run".to_string()))?;
+
+        let points: Vec<SensitivityPoint> = points
+            .into_iter()
+            .map(|mut p| {
+                p.return_change_pct = p.total_return_pct - baseline.total_return_pct;
+                p.sharpe_change = p.sharpe_ratio - baseline.sharpe_ratio;
+                p.drawdown_change_pct = p.max_drawdown_pct - baseline.max_drawdown_pct;
+                p
+            })
+            .collect();
+
+        let verdict = if !baseline.is_profitable() {
+            CostVerdict::Unprofitable
+        } else if points.iter().all(SensitivityPoint::is_profitable) {
+            CostVerdict::Robust
+        } else {
+            CostVerdict::Sensitive
+        };
+
+        let break_even_multiplier = points
+            .iter()
+            .filter(|p| p.commission_multiplier == p.slippage_multiplier && !p.is_profitable())
+            .map(|p| p.commission_multiplier)
+            .min();
+
+        Ok(Self {
+            baseline,
+            points,
+            verdict,
+            break_even_multiplier,
+        })
+    }
+
+    pub fn point(&self, commission: Decimal, slippage: Decimal) -> Option<&SensitivityPoint> {
+        self.points.iter().find(|p| {
+            p.commission_multiplier == commission.normalize()
+                && p.slippage_multiplier == slippage.normalize()
+        })
+    }
+
+    /// The run with the lowest return
+    pub fn worst(&self) -> Option<&SensitivityPoint> {
+        self.points.iter().min_by_key(|p| p.total_return_pct)
+    }
+}
+
+/// Rerun a backtest once per point of the grid
+///
+/// The engine consumes its strategy and data source, so both are built fresh
+/// for every run; the factories must produce the same strategy and data each
+/// time for the comparison to mean anything.
+pub async fn sweep_costs<S, D>(
+    config: &BacktestConfig,
+    grid: &SensitivityGrid,
+    mut strategy: S,
+    mut data: D,
+) -> Result<SensitivityReport>
+where
+    S: FnMut() -> Box<dyn Strategy>,
+    D: FnMut() -> Box<dyn HistoricalDataSource>,
+{
+    let commission_axis = SensitivityGrid::axis(&grid.commission_multipliers, "commission")?;
+    let slippage_axis = SensitivityGrid::axis(&grid.slippage_multipliers, "slippage")?;
+
+    let mut points = Vec::with_capacity(commission_axis.len() * slippage_axis.len());
+    for &commission in &commission_axis {
+        for &slippage in &slippage_axis {
+            let mut run_config = config.clone();
+            run_config.cost_model = config.cost_model.scaled(commission, slippage);
+
+            let mut engine = BacktestEngine::new(run_config, strategy(), data()).await?;
+            let result = engine.run().await?;
+            let point = SensitivityPoint::new(commission, slippage, &result);
+            if config.verbose {
+                info!(
+                    "Costs {}x commission, {}x slippage: return {}%, sharpe {}",
+                    commission, slippage, point.total_return_pct, point.sharpe_ratio
+                );
+            }
+            points.push(point);
+        }
+    }
+
+    SensitivityReport::new(points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost_model::{CommissionModel, CostModel, SlippageModel};
+    use crate::engine::{Candle, MockDataSource, PositionSizing};
+    use async_trait::async_trait;
+    use chrono::{Duration, TimeZone, Utc};
+    use ea_okx_core::Symbol;
+    use ea_okx_core::models::Order;
+    use ea_okx_strategy::metrics::PerformanceMetrics;
+    use ea_okx_strategy::signal::{Signal, SignalType};
+    use ea_okx_strategy::traits::{MarketDataEvent, StrategyConfig};
+
+    /// Buys on one bar and closes two bars later, over and over
+    struct RoundTrips {
+        bars: usize,
+    }
+
+    #[async_trait]
+    impl Strategy for RoundTrips {
+        async fn initialize(&mut self, _config: StrategyConfig) -> ea_okx_strategy::Result<()> {
+            Ok(())
+        }
+
+        async fn on_market_data(&mut self, _event: MarketDataEvent) -> ea_okx_strategy::Result<()> {
+            self.bars += 1;
+            Ok(())
+        }
+
+        async fn generate_signal(&self) -> ea_okx_strategy::Result<Signal> {
+            Ok(match self.bars % 4 {
+                1 => Signal::buy(1.0),
+                3 => Signal {
+                    signal_type: SignalType::CloseLong,
+                    ..Signal::hold()
+                },
+                _ => Signal::hold(),
+            })
+        }
+
+        async fn on_order_fill(&mut self, _order: &Order) -> ea_okx_strategy::Result<()> {
+            Ok(())
+        }
+
+        async fn on_order_reject(
+            &mut self,
+            _order: &Order,
+            _reason: &str,
+        ) -> ea_okx_strategy::Result<()> {
+            Ok(())
+        }
+
+        fn get_metrics(&self) -> PerformanceMetrics {
+            PerformanceMetrics::default()
+        }
+
+        fn serialize_state(&self) -> ea_okx_strategy::Result<serde_json::Value> {
+            Ok(serde_json::json!({}))
+        }
+
+        fn deserialize_state(&mut self, _state: serde_json::Value) -> ea_okx_strategy::Result<()> {
+            Ok(())
+        }
+
+        async fn shutdown(&mut self) -> ea_okx_strategy::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn cost_model() -> CostModel {
+        CostModel {
+            commission: CommissionModel {
+                maker_rate: dec!(0.0002),
+                taker_rate: dec!(0.0005),
+                min_commission: Decimal::ZERO,
+            },
+            slippage: SlippageModel {
+                fixed_bps: dec!(5),
+                impact_coefficient: Decimal::ZERO,
+                min_slippage: Decimal::ZERO,
+            },
+        }
+    }
+
+    /// Steadily rising closes, so round trips earn a little before costs
+    async fn sweep(cost_model: CostModel, grid: &SensitivityGrid) -> SensitivityReport {
+        let symbol = Symbol::new("BTC-USDT").unwrap();
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let candles: Vec<Candle> = (0..40)
+            .map(|i| {
+                let close = dec!(100) * (Decimal::ONE + dec!(0.002) * Decimal::from(i));
+                Candle {
+                    symbol: symbol.clone(),
+                    timestamp: start + Duration::hours(i),
+                    open: close,
+                    high: close,
+                    low: close,
+                    close,
+                    volume: dec!(1000),
+                }
+            })
+            .collect();
+
+        let config = BacktestConfig {
+            start_time: start,
+            end_time: start + Duration::hours(39),
+            symbols: vec![symbol.clone()],
+            cost_model,
+            position_sizing: PositionSizing::PercentOfEquity(dec!(0.5)),
+            ..Default::default()
+        };
+
+        sweep_costs(
+            &config,
+            grid,
+            || Box::new(RoundTrips { bars: 0 }),
+            || {
+                let mut data = MockDataSource::new();
+                data.add_candles(symbol.clone(), candles.clone());
+                Box::new(data)
+            },
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_returns_degrade_as_costs_rise() {
+        let report = sweep(cost_model(), &SensitivityGrid::default()).await;
+
+        assert_eq!(report.points.len(), 25);
+        assert!(report.baseline.is_baseline());
+        assert!(report.baseline.total_trades > 0);
+        assert!(report.baseline.is_profitable());
+
+        let cheapest = report.point(dec!(0.5), dec!(0.5)).unwrap();
+        let dearest = report.point(dec!(3), dec!(3)).unwrap();
+        assert!(cheapest.return_change_pct > Decimal::ZERO);
+        assert!(dearest.return_change_pct < Decimal::ZERO);
+        assert!(dearest.total_costs > report.baseline.total_costs);
+        assert_eq!(report.worst(), Some(dearest));
+
+        assert_eq!(report.verdict, CostVerdict::Sensitive);
+        assert_eq!(report.break_even_multiplier, Some(dec!(2)));
+    }
+
+    #[tokio::test]
+    async fn test_grid_always_includes_the_configured_costs() {
+        let grid = SensitivityGrid {
+            commission_multipliers: vec![dec!(2)],
+            slippage_multipliers: vec![dec!(2.0), dec!(2)],
+        };
+        assert_eq!(grid.len(), 4);
+
+        let report = sweep(cost_model(), &grid).await;
+        assert_eq!(report.points.len(), 4);
+        assert_eq!(report.baseline, *report.point(dec!(1), dec!(1)).unwrap());
+        assert_eq!(report.baseline.return_change_pct, Decimal::ZERO);
+
+        let negative = SensitivityGrid {
+            commission_multipliers: vec![dec!(-1)],
+            ..Default::default()
+        };
+        assert!(negative.is_empty());
+    }
+}
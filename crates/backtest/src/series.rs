@@ -0,0 +1,300 @@
+//! Preloaded market data for the backtest hot loop
+//!
+//! Candles are stored column-wise per symbol so a multi-year 1m backtest
+//! holds one symbol string per series rather than one per candle. The replay
+//! order is a flat, timestamp-sorted [`Timeline`] of small `Copy` references
+//! into those columns; the engine materializes a [`Candle`] only for the event
+//! it is processing.
+
+use crate::engine::{Candle, FundingRate, PositioningSample};
+use chrono::{DateTime, Duration, Utc};
+use ea_okx_core::{Interval, Symbol};
+use rust_decimal::Decimal;
+
+/// Contiguous OHLCV columns for one symbol
+#[derive(Debug, Clone)]
+pub struct CandleSeries {
+    pub symbol: Symbol,
+    /// Higher-timeframe bar length; `None` for the base interval
+    pub interval: Option<Interval>,
+    pub timestamps: Vec<DateTime<Utc>>,
+    pub open: Vec<Decimal>,
+    pub high: Vec<Decimal>,
+    pub low: Vec<Decimal>,
+    pub close: Vec<Decimal>,
+    pub volume: Vec<Decimal>,
+}
+
+impl CandleSeries {
+    pub fn from_candles(symbol: Symbol, candles: Vec<Candle>) -> Self {
+        let len = candles.len();
+        let mut series = Self {
+            symbol,
+            interval: None,
+            timestamps: Vec::with_capacity(len),
+            open: Vec::with_capacity(len),
+            high: Vec::with_capacity(len),
+            low: Vec::with_capacity(len),
+            close: Vec::with_capacity(len),
+            volume: Vec::with_capacity(len),
+        };
+        for candle in candles {
+            series.timestamps.push(candle.timestamp);
+            series.open.push(candle.open);
+            series.high.push(candle.high);
+            series.low.push(candle.low);
+            series.close.push(candle.close);
+            series.volume.push(candle.volume);
+        }
+        series
+    }
+
+    pub fn len(&self) -> usize {
+        self.timestamps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.timestamps.is_empty()
+    }
+
+    /// Materialize the candle at `row`
+    pub fn candle(&self, row: usize) -> Candle {
+        Candle {
+            symbol: self.symbol.clone(),
+            timestamp: self.timestamps[row],
+            open: self.open[row],
+            high: self.high[row],
+            low: self.low[row],
+            close: self.close[row],
+            volume: self.volume[row],
+        }
+    }
+}
+
+/// Reference to one replayable event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventRef {
+    /// Row `row` of candle series `series`
+    Candle { series: u32, row: u32 },
+    /// Entry `index` of the funding rate list
+    Funding { index: u32 },
+    /// Entry `index` of the positioning sample list
+    Positioning { index: u32 },
+}
+
+/// All preloaded data in replay order
+#[derive(Debug, Clone, Default)]
+pub struct Timeline {
+    pub series: Vec<CandleSeries>,
+    pub funding: Vec<FundingRate>,
+    pub positioning: Vec<PositioningSample>,
+    events: Vec<(DateTime<Utc>, EventRef)>,
+}
+
+impl Timeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_candles(&mut self, symbol: Symbol, candles: Vec<Candle>) {
+        let series = self.series.len() as u32;
+        let data = CandleSeries::from_candles(symbol, candles);
+        self.events.reserve(data.len());
+        self.events
+            .extend(data.timestamps.iter().enumerate().map(|(row, ts)| {
+                (
+                    *ts,
+                    EventRef::Candle {
+                        series,
+                        row: row as u32,
+                    },
+                )
+            }));
+        self.series.push(data);
+    }
+
+    /// Add higher-timeframe candles, each replayed once its bar has closed
+    ///
+    /// Candles are labelled with their open time, so a bar becomes an event
+    /// at `timestamp + length`.
+    pub fn add_higher_timeframe(
+        &mut self,
+        symbol: Symbol,
+        interval: Interval,
+        candles: Vec<Candle>,
+        length: Duration,
+    ) {
+        let series = self.series.len() as u32;
+        let mut data = CandleSeries::from_candles(symbol, candles);
+        data.interval = Some(interval);
+        self.events.reserve(data.len());
+        self.events
+            .extend(data.timestamps.iter().enumerate().map(|(row, ts)| {
+                (
+                    *ts + length,
+                    EventRef::Candle {
+                        series,
+                        row: row as u32,
+                    },
+                )
+            }));
+        self.series.push(data);
+    }
+
+    pub fn add_funding_rates(&mut self, rates: Vec<FundingRate>) {
+        for rate in rates {
+            let index = self.funding.len() as u32;
+            self.events
+                .push((rate.timestamp, EventRef::Funding { index }));
+            self.funding.push(rate);
+        }
+    }
+
+    pub fn add_positioning(&mut self, samples: Vec<PositioningSample>) {
+        for sample in samples {
+            let index = self.positioning.len() as u32;
+            self.events
+                .push((sample.timestamp, EventRef::Positioning { index }));
+            self.positioning.push(sample);
+        }
+    }
+
+    /// Order events by timestamp; ties keep insertion order, except that a
+    /// higher-timeframe bar closing at a timestamp comes before the base
+    /// events opening there
+    pub fn sort(&mut self) {
+        let series = &self.series;
+        self.events
+            .sort_by_key(|(ts, event)| (*ts, is_base(series, event)));
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Event at position `index` in replay order
+    pub fn get(&self, index: usize) -> Option<EventRef> {
+        self.events.get(index).map(|(_, event)| *event)
+    }
+
+    /// Simulation time at which the event at `index` is replayed
+    pub fn time(&self, index: usize) -> Option<DateTime<Utc>> {
+        self.events.get(index).map(|(ts, _)| *ts)
+    }
+
+    /// Sort key of the event at `index`: its replay time, and whether it is
+    /// a base-interval event rather than a higher-timeframe close
+    pub fn replay_key(&self, index: usize) -> Option<(DateTime<Utc>, bool)> {
+        self.events
+            .get(index)
+            .map(|(ts, event)| (*ts, is_base(&self.series, event)))
+    }
+}
+
+fn is_base(series: &[CandleSeries], event: &EventRef) -> bool {
+    match event {
+        EventRef::Candle { series: index, .. } => series[*index as usize].interval.is_none(),
+        EventRef::Funding { .. } | EventRef::Positioning { .. } => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+
+    fn candles(symbol: &Symbol, start: DateTime<Utc>, count: i64, step: i64) -> Vec<Candle> {
+        (0..count)
+            .map(|i| Candle {
+                symbol: symbol.clone(),
+                timestamp: start + Duration::minutes(i * step),
+                open: Decimal::from(i),
+                high: Decimal::from(i + 1),
+                low: Decimal::from(i),
+                close: Decimal::from(i),
+                volume: dec!(1),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_series_round_trips_candles() {
+        let symbol = Symbol::new("BTC-USDT").unwrap();
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let source = candles(&symbol, start, 3, 1);
+        let series = CandleSeries::from_candles(symbol, source.clone());
+
+        assert_eq!(series.len(), 3);
+        let row = series.candle(2);
+        assert_eq!(row.timestamp, source[2].timestamp);
+        assert_eq!(row.high, source[2].high);
+    }
+
+    #[test]
+    fn test_timeline_interleaves_by_timestamp() {
+        let btc = Symbol::new("BTC-USDT").unwrap();
+        let eth = Symbol::new("ETH-USDT").unwrap();
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+
+        let mut timeline = Timeline::new();
+        timeline.add_candles(btc.clone(), candles(&btc, start, 3, 2));
+        timeline.add_candles(
+            eth.clone(),
+            candles(&eth, start + Duration::minutes(1), 2, 2),
+        );
+        timeline.add_funding_rates(vec![FundingRate {
+            symbol: btc,
+            timestamp: start + Duration::minutes(2),
+            rate: dec!(0.0001),
+        }]);
+        timeline.sort();
+
+        let order: Vec<_> = (0..timeline.len())
+            .map(|i| timeline.get(i).unwrap())
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                EventRef::Candle { series: 0, row: 0 },
+                EventRef::Candle { series: 1, row: 0 },
+                EventRef::Candle { series: 0, row: 1 },
+                EventRef::Funding { index: 0 },
+                EventRef::Candle { series: 1, row: 1 },
+                EventRef::Candle { series: 0, row: 2 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_higher_timeframe_replays_at_close() {
+        let btc = Symbol::new("BTC-USDT").unwrap();
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+
+        let mut timeline = Timeline::new();
+        timeline.add_candles(btc.clone(), candles(&btc, start, 6, 60));
+        timeline.add_higher_timeframe(
+            btc.clone(),
+            Interval::FourHours,
+            candles(&btc, start, 1, 240),
+            Duration::hours(4),
+        );
+        timeline.sort();
+
+        // The 00:00 4H bar closes at 04:00, ahead of the 04:00 base candle
+        assert_eq!(
+            timeline.get(4),
+            Some(EventRef::Candle { series: 1, row: 0 })
+        );
+        assert_eq!(timeline.time(4), Some(start + Duration::hours(4)));
+        assert_eq!(
+            timeline.get(5),
+            Some(EventRef::Candle { series: 0, row: 4 })
+        );
+        assert_eq!(timeline.series[1].interval, Some(Interval::FourHours));
+    }
+}
@@ -0,0 +1,228 @@
+//! Streaming replay for histories too large to preload
+//!
+//! By default the engine loads every candle into the [`Timeline`] before the
+//! run starts. With [`StreamingConfig`] set, base-interval candles are
+//! instead read in chunks through [`HistoricalDataSource::stream_candles`]
+//! and merged by timestamp while the run progresses, so memory stays bounded
+//! by `chunk_size × prefetch_chunks` per symbol. Sources backed by a database
+//! fetch the next chunks in the background while the engine works through
+//! the current one. Funding rates and higher-timeframe bars are small and are
+//! still preloaded.
+//!
+//! [`Timeline`]: crate::series::Timeline
+//! [`HistoricalDataSource::stream_candles`]: crate::engine::HistoricalDataSource::stream_candles
+
+use crate::engine::Candle;
+use crate::error::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use tokio::sync::mpsc;
+
+/// Chunking and read-ahead for streamed candles
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StreamingConfig {
+    /// Candles fetched per round trip
+    pub chunk_size: usize,
+
+    /// Chunks buffered ahead of the engine per symbol
+    pub prefetch_chunks: usize,
+}
+
+impl Default for StreamingConfig {
+    fn default() -> Self {
+        Self {
+            chunk_size: 10_000,
+            prefetch_chunks: 4,
+        }
+    }
+}
+
+/// Producer half of a [`CandleChunks`] stream
+pub type ChunkSender = mpsc::Sender<Result<Vec<Candle>>>;
+
+/// Timestamp-ordered candles arriving in chunks
+pub struct CandleChunks {
+    rx: mpsc::Receiver<Result<Vec<Candle>>>,
+}
+
+impl CandleChunks {
+    /// Stream filled by a producer task; sends wait once `prefetch` chunks
+    /// are buffered, and fail once the stream is dropped
+    pub fn channel(prefetch: usize) -> (ChunkSender, Self) {
+        let (tx, rx) = mpsc::channel(prefetch.max(1));
+        (tx, Self { rx })
+    }
+
+    /// Stream over candles already in memory
+    pub fn from_candles(candles: Vec<Candle>, chunk_size: usize) -> Self {
+        let chunk_size = chunk_size.max(1);
+        let (tx, chunks) = Self::channel(candles.len().div_ceil(chunk_size));
+        let mut candles = candles.into_iter().peekable();
+        while candles.peek().is_some() {
+            let chunk = candles.by_ref().take(chunk_size).collect();
+            // Capacity covers every chunk, so this never waits
+            let _ = tx.try_send(Ok(chunk));
+        }
+        chunks
+    }
+
+    /// Next chunk; `None` once the producer is finished
+    pub async fn next_chunk(&mut self) -> Option<Result<Vec<Candle>>> {
+        self.rx.recv().await
+    }
+}
+
+struct Cursor {
+    chunks: CandleChunks,
+    buffer: VecDeque<Candle>,
+}
+
+impl Cursor {
+    /// Refill the buffer if it ran dry; false once the stream is exhausted
+    async fn fill(&mut self) -> Result<bool> {
+        while self.buffer.is_empty() {
+            match self.chunks.next_chunk().await {
+                Some(chunk) => self.buffer.extend(chunk?),
+                None => return Ok(false),
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Merges per-symbol candle streams into one timestamp-ordered stream
+///
+/// Candles at the same timestamp come out in the order their streams were
+/// added.
+#[derive(Default)]
+pub struct CandleMerge {
+    cursors: Vec<Cursor>,
+}
+
+impl CandleMerge {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a stream; returns false if it holds no candles at all
+    pub async fn add(&mut self, chunks: CandleChunks) -> Result<bool> {
+        let mut cursor = Cursor {
+            chunks,
+            buffer: VecDeque::new(),
+        };
+        let has_data = cursor.fill().await?;
+        self.cursors.push(cursor);
+        Ok(has_data)
+    }
+
+    /// Index of the stream holding the earliest pending candle
+    async fn earliest(&mut self) -> Result<Option<usize>> {
+        let mut earliest: Option<(usize, DateTime<Utc>)> = None;
+        for (index, cursor) in self.cursors.iter_mut().enumerate() {
+            if !cursor.fill().await? {
+                continue;
+            }
+            let timestamp = cursor.buffer[0].timestamp;
+            if earliest.is_none_or(|(_, best)| timestamp < best) {
+                earliest = Some((index, timestamp));
+            }
+        }
+        Ok(earliest.map(|(index, _)| index))
+    }
+
+    /// Timestamp of the next candle, without consuming it
+    pub async fn peek_time(&mut self) -> Result<Option<DateTime<Utc>>> {
+        let Some(index) = self.earliest().await? else {
+            return Ok(None);
+        };
+        Ok(self.cursors[index].buffer.front().map(|c| c.timestamp))
+    }
+
+    /// Next candle across all streams
+    pub async fn next_candle(&mut self) -> Result<Option<Candle>> {
+        let Some(index) = self.earliest().await? else {
+            return Ok(None);
+        };
+        Ok(self.cursors[index].buffer.pop_front())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use chrono::{Duration, TimeZone};
+    use ea_okx_core::Symbol;
+    use rust_decimal::Decimal;
+
+    fn candles(symbol: &str, hours: &[i64]) -> Vec<Candle> {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        hours
+            .iter()
+            .map(|hour| Candle {
+                symbol: Symbol::new(symbol).unwrap(),
+                timestamp: start + Duration::hours(*hour),
+                open: Decimal::ONE,
+                high: Decimal::ONE,
+                low: Decimal::ONE,
+                close: Decimal::ONE,
+                volume: Decimal::ONE,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_merges_streams_in_time_order() {
+        let mut merge = CandleMerge::new();
+        let btc = CandleChunks::from_candles(candles("BTC-USDT", &[0, 1, 3, 4]), 2);
+        let eth = CandleChunks::from_candles(candles("ETH-USDT", &[1, 2]), 1);
+        assert!(merge.add(btc).await.unwrap());
+        assert!(merge.add(eth).await.unwrap());
+        assert!(
+            !merge
+                .add(CandleChunks::from_candles(Vec::new(), 2))
+                .await
+                .unwrap()
+        );
+
+        let mut replayed = Vec::new();
+        while let Some(candle) = merge.next_candle().await.unwrap() {
+            let hour =
+                (candle.timestamp - Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()).num_hours();
+            replayed.push((candle.symbol.base().to_string(), hour));
+        }
+        let expected = [
+            ("BTC", 0),
+            ("BTC", 1),
+            ("ETH", 1),
+            ("ETH", 2),
+            ("BTC", 3),
+            ("BTC", 4),
+        ];
+        let expected: Vec<_> = expected.iter().map(|(s, h)| (s.to_string(), *h)).collect();
+        assert_eq!(replayed, expected);
+        assert_eq!(merge.peek_time().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_producer_errors_surface_and_prefetch_is_bounded() {
+        let (tx, chunks) = CandleChunks::channel(1);
+        let mut merge = CandleMerge::new();
+
+        tx.send(Ok(candles("BTC-USDT", &[0]))).await.unwrap();
+        // The buffer is full until the engine reads
+        assert!(tx.try_send(Ok(candles("BTC-USDT", &[1]))).is_err());
+
+        assert!(merge.add(chunks).await.unwrap());
+        tx.send(Err(Error::InsufficientData("cursor closed".to_string())))
+            .await
+            .unwrap();
+
+        assert!(merge.next_candle().await.unwrap().is_some());
+        assert!(matches!(
+            merge.next_candle().await,
+            Err(Error::InsufficientData(_))
+        ));
+    }
+}
@@ -0,0 +1,346 @@
+//! Historical data read directly from TimescaleDB
+//!
+//! Reads the `market_ohlcv`, `funding_rates` and positioning hypertables
+//! written by the data collectors. Streamed candles come from a server-side
+//! cursor held open in a read-only transaction by a background task, which
+//! fetches the next chunks while the engine replays the current one. Each
+//! streamed symbol holds one pool connection for the length of the run.
+
+use crate::engine::{
+    Candle, FundingRate, HistoricalDataSource, PositioningSample, PositioningValue,
+};
+use crate::error::{Error, Result};
+use crate::stream::{CandleChunks, ChunkSender, StreamingConfig};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use ea_okx_core::{Interval, Symbol};
+use rust_decimal::Decimal;
+use sqlx::FromRow;
+use sqlx::postgres::{PgPool, PgPoolOptions};
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+const CANDLE_QUERY: &str = r#"
+    SELECT timestamp, open, high, low, close, volume
+    FROM market_ohlcv
+    WHERE symbol = $1 AND interval = $2
+      AND timestamp >= $3 AND timestamp < $4
+    ORDER BY timestamp ASC
+"#;
+
+#[derive(Debug, FromRow)]
+struct CandleRow {
+    timestamp: DateTime<Utc>,
+    open: Decimal,
+    high: Decimal,
+    low: Decimal,
+    close: Decimal,
+    volume: Decimal,
+}
+
+impl CandleRow {
+    fn into_candle(self, symbol: &Symbol) -> Candle {
+        Candle {
+            symbol: symbol.clone(),
+            timestamp: self.timestamp,
+            open: self.open,
+            high: self.high,
+            low: self.low,
+            close: self.close,
+            volume: self.volume,
+        }
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct FundingRow {
+    funding_time: DateTime<Utc>,
+    funding_rate: Decimal,
+}
+
+#[derive(Debug, FromRow)]
+struct OpenInterestRow {
+    timestamp: DateTime<Utc>,
+    open_interest: Decimal,
+    volume: Decimal,
+}
+
+#[derive(Debug, FromRow)]
+struct TakerVolumeRow {
+    timestamp: DateTime<Utc>,
+    buy_volume: Decimal,
+    sell_volume: Decimal,
+}
+
+#[derive(Debug, FromRow)]
+struct LongShortRatioRow {
+    timestamp: DateTime<Utc>,
+    ratio: Decimal,
+}
+
+/// Backtest data source over the TimescaleDB market data tables
+#[derive(Clone)]
+pub struct TimescaleDataSource {
+    pool: PgPool,
+    positioning_period: String,
+}
+
+impl TimescaleDataSource {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            positioning_period: "5m".to_string(),
+        }
+    }
+
+    /// Reporting period of the positioning statistics to replay (`5m` by default)
+    pub fn with_positioning_period(mut self, period: impl Into<String>) -> Self {
+        self.positioning_period = period.into();
+        self
+    }
+
+    /// Connect with room for `max_connections` concurrent symbol streams
+    pub async fn connect(database_url: &str, max_connections: u32) -> Result<Self> {
+        let pool = PgPoolOptions::new()
+            .max_connections(max_connections)
+            .connect(database_url)
+            .await?;
+        Ok(Self::new(pool))
+    }
+}
+
+/// Walk a cursor over the candle query, sending each chunk to `tx`
+async fn fetch_with_cursor(
+    pool: PgPool,
+    symbol: Symbol,
+    interval: Interval,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    chunk_size: usize,
+    tx: &ChunkSender,
+) -> Result<()> {
+    let mut transaction = pool.begin().await?;
+    sqlx::query("SET TRANSACTION READ ONLY")
+        .execute(&mut *transaction)
+        .await?;
+
+    let cursor = format!("backtest_candles_{}", Uuid::new_v4().simple());
+    sqlx::query(&format!(
+        "DECLARE {} NO SCROLL CURSOR FOR {}",
+        cursor, CANDLE_QUERY
+    ))
+    .bind(symbol.as_str())
+    .bind(interval.storage_label())
+    .bind(start)
+    .bind(end)
+    .execute(&mut *transaction)
+    .await?;
+
+    let fetch = format!("FETCH FORWARD {} FROM {}", chunk_size, cursor);
+    loop {
+        let rows: Vec<CandleRow> = sqlx::query_as(&fetch).fetch_all(&mut *transaction).await?;
+        let last = rows.len() < chunk_size;
+        if !rows.is_empty() {
+            let chunk = rows.into_iter().map(|r| r.into_candle(&symbol)).collect();
+            if tx.send(Ok(chunk)).await.is_err() {
+                debug!("Candle stream for {} dropped, closing cursor", symbol);
+                break;
+            }
+        }
+        if last {
+            break;
+        }
+    }
+
+    transaction.rollback().await?;
+    Ok(())
+}
+
+#[async_trait]
+impl HistoricalDataSource for TimescaleDataSource {
+    async fn query_candles(
+        &self,
+        symbol: &Symbol,
+        interval: Interval,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<Candle>> {
+        let rows: Vec<CandleRow> = sqlx::query_as(CANDLE_QUERY)
+            .bind(symbol.as_str())
+            .bind(interval.storage_label())
+            .bind(start)
+            .bind(end)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows.into_iter().map(|r| r.into_candle(symbol)).collect())
+    }
+
+    async fn query_funding_rates(
+        &self,
+        symbol: &Symbol,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<FundingRate>> {
+        let rows: Vec<FundingRow> = sqlx::query_as(
+            r#"
+            SELECT funding_time, funding_rate
+            FROM funding_rates
+            WHERE symbol = $1 AND funding_time >= $2 AND funding_time < $3
+            ORDER BY funding_time ASC
+            "#,
+        )
+        .bind(symbol.as_str())
+        .bind(start)
+        .bind(end)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| FundingRate {
+                symbol: symbol.clone(),
+                timestamp: row.funding_time,
+                rate: row.funding_rate,
+            })
+            .collect())
+    }
+
+    /// Statistics are stored by period start and replayed once the period
+    /// has closed
+    async fn query_positioning(
+        &self,
+        symbol: &Symbol,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<PositioningSample>> {
+        let period = self.positioning_period.as_str();
+        let length = period.parse::<Interval>().ok().and_then(|i| i.duration());
+        let length = length.ok_or_else(|| {
+            Error::InvalidConfig(format!("Unsupported positioning period: {}", period))
+        })?;
+        let currency = symbol.base();
+        // Rows whose period closes inside the run
+        let (from, to) = (start - length, end - length);
+
+        let open_interest: Vec<OpenInterestRow> = sqlx::query_as(
+            r#"
+            SELECT timestamp, open_interest, volume
+            FROM open_interest
+            WHERE currency = $1 AND period = $2
+              AND timestamp >= $3 AND timestamp < $4
+            "#,
+        )
+        .bind(currency)
+        .bind(period)
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let taker: Vec<TakerVolumeRow> = sqlx::query_as(
+            r#"
+            SELECT timestamp, buy_volume, sell_volume
+            FROM taker_volume
+            WHERE currency = $1 AND inst_type = 'CONTRACTS' AND period = $2
+              AND timestamp >= $3 AND timestamp < $4
+            "#,
+        )
+        .bind(currency)
+        .bind(period)
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let ratio: Vec<LongShortRatioRow> = sqlx::query_as(
+            r#"
+            SELECT timestamp, ratio
+            FROM long_short_ratio
+            WHERE currency = $1 AND period = $2
+              AND timestamp >= $3 AND timestamp < $4
+            "#,
+        )
+        .bind(currency)
+        .bind(period)
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let sample = |timestamp: DateTime<Utc>, value| PositioningSample {
+            symbol: symbol.clone(),
+            timestamp: timestamp + length,
+            value,
+        };
+        let mut samples: Vec<PositioningSample> = open_interest
+            .into_iter()
+            .map(|row| {
+                sample(
+                    row.timestamp,
+                    PositioningValue::OpenInterest {
+                        open_interest: row.open_interest,
+                        volume: row.volume,
+                    },
+                )
+            })
+            .chain(taker.into_iter().map(|row| {
+                sample(
+                    row.timestamp,
+                    PositioningValue::TakerVolume {
+                        buy_volume: row.buy_volume,
+                        sell_volume: row.sell_volume,
+                    },
+                )
+            }))
+            .chain(ratio.into_iter().map(|row| {
+                sample(
+                    row.timestamp,
+                    PositioningValue::LongShortRatio { ratio: row.ratio },
+                )
+            }))
+            .collect();
+        samples.sort_by_key(|s| s.timestamp);
+        Ok(samples)
+    }
+
+    async fn stream_candles(
+        &self,
+        symbol: &Symbol,
+        interval: Interval,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        streaming: &StreamingConfig,
+    ) -> Result<CandleChunks> {
+        let (tx, chunks) = CandleChunks::channel(streaming.prefetch_chunks);
+        let pool = self.pool.clone();
+        let symbol = symbol.clone();
+        let chunk_size = streaming.chunk_size.max(1);
+
+        tokio::spawn(async move {
+            let result =
+                fetch_with_cursor(pool, symbol.clone(), interval, start, end, chunk_size, &tx)
+                    .await;
+            if let Err(e) = result {
+                warn!("Candle stream for {} failed: {}", symbol, e);
+                let _ = tx.send(Err(e)).await;
+            }
+        });
+
+        Ok(chunks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_storage_labels_match_the_collector() {
+        let label = |bar: &str| bar.parse::<Interval>().unwrap().storage_label();
+        assert_eq!(label("1m"), "1m");
+        assert_eq!(label("1H"), "1h");
+        assert_eq!(label("4H"), "4h");
+        assert_eq!(label("1D"), "1d");
+        assert_eq!(label("1M"), "1M");
+    }
+}
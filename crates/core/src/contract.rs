@@ -0,0 +1,99 @@
+//! JSON contract for payloads sent to the frontend
+//!
+//! Everything the desktop app hands to its TypeScript frontend follows the
+//! same rules, whatever the payload:
+//!
+//! - Decimals (prices, quantities, PnL, fees) are strings, so no value is
+//!   rounded through a float on either side
+//! - Timestamps are RFC 3339 with the zone, in UTC: `2024-01-01T00:00:00Z`,
+//!   with as many fraction digits as the value needs
+//! - Enums are their snake_case variant names, e.g. `"post_only"`
+//! - Optional fields are always present, as `null` when unset
+//! - IDs are strings
+//!
+//! The models derive [`TS`](ts_rs::TS), and `cargo test` writes their
+//! TypeScript definitions to `src/types/bindings`, so a model change shows up
+//! as a diff in the generated types rather than as a frontend that quietly
+//! reads a field that no longer exists. Payloads built by hand with
+//! `serde_json::json!` format timestamps with [`format_timestamp`].
+
+use chrono::{DateTime, SecondsFormat, Utc};
+
+/// A timestamp as it appears in payloads, matching the serialized form of
+/// `DateTime<Utc>` fields
+pub fn format_timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Order, OrderSide, OrderType, Position, PositionSide, Trade};
+    use crate::types::{Price, Quantity, Symbol};
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+    use serde_json::{Value, json};
+    use uuid::Uuid;
+
+    fn order() -> Order {
+        let mut order = Order::new(
+            Uuid::nil(),
+            Symbol::new("BTC-USDT").unwrap(),
+            OrderSide::Buy,
+            OrderType::PostOnly,
+            Quantity::new(dec!(0.015)).unwrap(),
+            Some(Price::new(dec!(42000.10)).unwrap()),
+        );
+        order.created_at = Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+        order
+    }
+
+    #[test]
+    fn test_models_follow_the_contract() {
+        let order = serde_json::to_value(order()).unwrap();
+        assert_eq!(order["quantity"], json!("0.015"));
+        assert_eq!(order["price"], json!("42000.10"));
+        assert_eq!(order["order_type"], json!("post_only"));
+        assert_eq!(order["status"], json!("created"));
+        assert_eq!(order["created_at"], json!("2024-01-02T03:04:05Z"));
+        assert_eq!(order["avg_fill_price"], Value::Null);
+
+        let mut position = Position::new(
+            Uuid::nil(),
+            Symbol::new("BTC-USDT-SWAP").unwrap(),
+            PositionSide::Long,
+            Quantity::new(dec!(2)).unwrap(),
+            Price::new(dec!(100)).unwrap(),
+        );
+        position.update_price(Price::new(dec!(101.5)).unwrap());
+        let position = serde_json::to_value(position).unwrap();
+        assert_eq!(position["side"], json!("long"));
+        assert_eq!(position["unrealized_pnl"], json!("3.0"));
+        assert_eq!(position["leverage"], Value::Null);
+
+        let trade = Trade::new(
+            Uuid::nil(),
+            "client-1".to_string(),
+            Symbol::new("ETH-USDT").unwrap(),
+            OrderSide::Sell,
+            OrderType::Market,
+            Quantity::new(dec!(1)).unwrap(),
+            Price::new(dec!(3000)).unwrap(),
+            dec!(-0.25),
+        );
+        let trade = serde_json::to_value(trade).unwrap();
+        assert_eq!(trade["side"], json!("sell"));
+        assert_eq!(trade["commission"], json!("-0.25"));
+    }
+
+    #[test]
+    fn test_format_timestamp_matches_serialized_form() {
+        let at = Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap()
+            + chrono::Duration::milliseconds(250);
+        assert_eq!(format_timestamp(at), "2024-01-02T03:04:05.250Z");
+        assert_eq!(
+            json!(format_timestamp(at)),
+            serde_json::to_value(at).unwrap()
+        );
+    }
+}
@@ -26,18 +26,42 @@ pub enum Error {
     #[error("Invalid position side: {0}")]
     InvalidPositionSide(String),
 
+    #[error("Invalid candle interval: {0}")]
+    InvalidInterval(String),
+
     #[error("Serialization error: {0}")]
     SerializationError(#[from] serde_json::Error),
 
+    #[error("Schema version error: {0}")]
+    SchemaError(String),
+
     #[error("Decimal conversion error: {0}")]
     DecimalError(String),
 
+    #[error("Division by zero: {0}")]
+    DivisionByZero(String),
+
+    #[error("Arithmetic overflow: {0}")]
+    ArithmeticOverflow(String),
+
     #[error("Validation error: {0}")]
     ValidationError(String),
 
     #[error("Configuration error: {0}")]
     ConfigError(String),
 
+    #[error("Exchange error: {0}")]
+    ExchangeError(String),
+
+    #[error("Exchange rejected the request: {code} - {message}")]
+    ExchangeRejected { code: String, message: String },
+
+    #[error("Exchange rate limit exceeded: {0}")]
+    RateLimited(String),
+
+    #[error("Exchange unavailable: {0}")]
+    ExchangeUnavailable(String),
+
     #[error("Resource not found: {0}")]
     NotFound(String),
 
@@ -0,0 +1,181 @@
+//! Venue-neutral exchange interface
+//!
+//! The engine and the market data collector talk to an exchange through
+//! [`ExchangeAdapter`]: order placement, lookup and cancellation over REST,
+//! market and user data over a streaming connection, and instrument
+//! metadata. Adapters translate their venue's wire formats into the types
+//! here, so supporting another exchange means writing an adapter rather
+//! than touching the engine.
+
+use crate::error::Result;
+use crate::interval::Interval;
+use crate::models::{Order, OrderSide, OrderStatus, OrderType};
+use crate::types::{InstrumentKind, Symbol};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// Order as the exchange reports it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExchangeOrder {
+    pub exchange_order_id: String,
+    pub client_order_id: String,
+    pub symbol: Symbol,
+    pub side: OrderSide,
+    pub order_type: OrderType,
+    pub quantity: Decimal,
+    /// Limit price; `None` for market orders
+    pub price: Option<Decimal>,
+    pub filled_quantity: Decimal,
+    pub avg_fill_price: Option<Decimal>,
+    pub status: OrderStatus,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ExchangeOrder {
+    /// Whether the order can still trade
+    pub fn is_open(&self) -> bool {
+        matches!(self.status, OrderStatus::Submitted | OrderStatus::Partial)
+    }
+}
+
+/// Trading rules and state of one instrument
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstrumentInfo {
+    pub symbol: Symbol,
+    pub kind: InstrumentKind,
+    /// Price increment
+    pub tick_size: Decimal,
+    /// Size increment
+    pub lot_size: Decimal,
+    pub min_size: Decimal,
+    /// Whether orders are accepted right now
+    pub tradable: bool,
+}
+
+/// Market data stream of one symbol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarketStream {
+    Ticker,
+    Trades,
+    Candles(Interval),
+    FundingRate,
+    /// Updates to the account's own orders
+    Orders,
+}
+
+/// Stream subscription
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MarketSubscription {
+    pub symbol: Symbol,
+    pub stream: MarketStream,
+}
+
+impl MarketSubscription {
+    pub fn new(symbol: Symbol, stream: MarketStream) -> Self {
+        Self { symbol, stream }
+    }
+}
+
+/// Latest trade price of a symbol
+#[derive(Debug, Clone, PartialEq)]
+pub struct TickerUpdate {
+    pub symbol: Symbol,
+    pub last: Decimal,
+    pub bid: Option<Decimal>,
+    pub ask: Option<Decimal>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// One public trade
+#[derive(Debug, Clone, PartialEq)]
+pub struct TradeTick {
+    pub symbol: Symbol,
+    pub trade_id: String,
+    pub price: Decimal,
+    pub quantity: Decimal,
+    /// Taker side
+    pub side: OrderSide,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// OHLCV bar, pushed while forming and once more when closed
+#[derive(Debug, Clone, PartialEq)]
+pub struct CandleUpdate {
+    /// `None` when the venue does not name the symbol in candle pushes
+    pub symbol: Option<Symbol>,
+    pub interval: Interval,
+    pub open_time: DateTime<Utc>,
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+    pub volume: Decimal,
+    pub closed: bool,
+}
+
+/// Funding rate of a perpetual swap
+#[derive(Debug, Clone, PartialEq)]
+pub struct FundingUpdate {
+    pub symbol: Symbol,
+    pub rate: Decimal,
+    pub next_rate: Option<Decimal>,
+    pub funding_time: DateTime<Utc>,
+}
+
+/// Event from an exchange's streaming connection
+#[derive(Debug, Clone, PartialEq)]
+pub enum MarketEvent {
+    Ticker(TickerUpdate),
+    Trade(TradeTick),
+    Candle(CandleUpdate),
+    FundingRate(FundingUpdate),
+    /// Update to one of the account's orders
+    Order(Box<ExchangeOrder>),
+    /// Updates on `stream` were lost and are being resynced
+    Gap {
+        stream: String,
+        symbol: Option<Symbol>,
+    },
+}
+
+/// One exchange: order entry, market and user data, instrument metadata
+#[async_trait]
+pub trait ExchangeAdapter: Send + Sync {
+    /// Short venue name, e.g. `okx`
+    fn name(&self) -> &'static str;
+
+    /// Place `order` under its client order ID, returning the exchange
+    /// order ID
+    async fn place_order(&self, order: &Order) -> Result<String>;
+
+    /// Cancel the order placed with `client_order_id`
+    async fn cancel_order(&self, symbol: &Symbol, client_order_id: &str) -> Result<()>;
+
+    /// Change the size or limit price of the order placed with
+    /// `client_order_id`; `quantity` is the new total, filled part included
+    async fn amend_order(
+        &self,
+        symbol: &Symbol,
+        client_order_id: &str,
+        quantity: Option<Decimal>,
+        price: Option<Decimal>,
+    ) -> Result<()>;
+
+    /// Order placed with `client_order_id`, if the exchange has one
+    async fn order(&self, symbol: &Symbol, client_order_id: &str) -> Result<Option<ExchangeOrder>>;
+
+    /// Every instrument of `kind`
+    async fn instruments(&self, kind: InstrumentKind) -> Result<Vec<InstrumentInfo>>;
+
+    /// Start receiving `subscriptions` from [`ExchangeAdapter::next_event`]
+    async fn subscribe(&self, subscriptions: &[MarketSubscription]) -> Result<()>;
+
+    /// Next streamed event; `None` once the stream has ended
+    async fn next_event(&self) -> Result<Option<MarketEvent>>;
+
+    /// Close the streaming connection
+    async fn disconnect(&self) -> Result<()>;
+}
@@ -0,0 +1,86 @@
+//! Liveness heartbeats for long-running tasks
+//!
+//! A task holds a [`Heartbeat`] and beats it each time round its loop; a
+//! supervisor holding a clone reads how long the task has been silent. It is
+//! a shared timestamp and nothing more, so beating it costs one atomic store
+//! and crates that run background loops can accept one without depending on
+//! whatever watches them.
+//!
+//! # Examples
+//!
+//! ```
+//! use ea_okx_core::heartbeat::Heartbeat;
+//!
+//! let heartbeat = Heartbeat::new();
+//! let watched = heartbeat.clone();
+//!
+//! heartbeat.beat();
+//! assert!(watched.silent_for(chrono::Utc::now()).num_seconds() < 1);
+//! ```
+
+use chrono::{DateTime, Duration, Utc};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicI64, Ordering};
+
+/// Last time a task reported it was alive; clones share the timestamp
+#[derive(Debug, Clone)]
+pub struct Heartbeat {
+    last_beat_ms: Arc<AtomicI64>,
+}
+
+impl Heartbeat {
+    /// A heartbeat that last beat now
+    pub fn new() -> Self {
+        Self {
+            last_beat_ms: Arc::new(AtomicI64::new(Utc::now().timestamp_millis())),
+        }
+    }
+
+    pub fn beat(&self) {
+        self.beat_at(Utc::now());
+    }
+
+    pub fn beat_at(&self, at: DateTime<Utc>) {
+        self.last_beat_ms
+            .store(at.timestamp_millis(), Ordering::Relaxed);
+    }
+
+    pub fn last_beat(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp_millis(self.last_beat_ms.load(Ordering::Relaxed))
+            .unwrap_or_default()
+    }
+
+    /// Time since the last beat, zero if it is in the future of `now`
+    pub fn silent_for(&self, now: DateTime<Utc>) -> Duration {
+        (now - self.last_beat()).max(Duration::zero())
+    }
+}
+
+impl Default for Heartbeat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clones_share_the_last_beat() {
+        let heartbeat = Heartbeat::new();
+        let watched = heartbeat.clone();
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+
+        heartbeat.beat_at(start);
+        assert_eq!(watched.last_beat(), start);
+        assert_eq!(
+            watched.silent_for(start + Duration::seconds(30)),
+            Duration::seconds(30)
+        );
+        assert_eq!(
+            watched.silent_for(start - Duration::seconds(1)),
+            Duration::zero()
+        );
+    }
+}
@@ -0,0 +1,370 @@
+//! Candlestick intervals
+//!
+//! [`Interval`] covers every bar OKX publishes, from `1s` to `3M`, plus the
+//! 5s and 15s bars built locally from trades. Labels are OKX's own (`1m`,
+//! `1H`, `1D`, `6Hutc`); storage uses lowercase hour, day and week units
+//! (`1h`, `1d`, `1w`) and parsing accepts both.
+//!
+//! OKX anchors bars of six hours and longer to Hong Kong time (UTC+8) and
+//! offers a `utc` variant anchored to UTC midnight; shorter bars and weeks
+//! aside, [`Interval::bar_start`] follows the same convention. Weeks start on
+//! Monday. Months have no fixed length, so [`Interval::duration`] is `None`
+//! for them and bar boundaries come from the calendar.
+
+use crate::error::{Error, Result};
+use chrono::{DateTime, Datelike, Duration, Months, TimeZone, Utc};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// Offset of the Hong Kong time OKX anchors long bars to
+const HONG_KONG_OFFSET_HOURS: i64 = 8;
+
+/// Unix epoch fell on a Thursday; weeks are counted from the Monday after
+const FIRST_MONDAY_DAYS: i64 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unit {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+}
+
+/// Everything derived from an interval
+struct Spec {
+    label: &'static str,
+    storage: &'static str,
+    channel: &'static str,
+    count: i64,
+    unit: Unit,
+    utc: bool,
+}
+
+const fn spec(
+    label: &'static str,
+    storage: &'static str,
+    channel: &'static str,
+    count: i64,
+    unit: Unit,
+    utc: bool,
+) -> Spec {
+    Spec {
+        label,
+        storage,
+        channel,
+        count,
+        unit,
+        utc,
+    }
+}
+
+/// Candlestick bar length
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Interval {
+    OneSecond,
+    /// Built locally from trades; OKX has no such bar
+    FiveSeconds,
+    /// Built locally from trades; OKX has no such bar
+    FifteenSeconds,
+    OneMinute,
+    ThreeMinutes,
+    FiveMinutes,
+    FifteenMinutes,
+    ThirtyMinutes,
+    OneHour,
+    TwoHours,
+    FourHours,
+    SixHours,
+    TwelveHours,
+    OneDay,
+    TwoDays,
+    ThreeDays,
+    OneWeek,
+    OneMonth,
+    ThreeMonths,
+    SixHoursUtc,
+    TwelveHoursUtc,
+    OneDayUtc,
+    TwoDaysUtc,
+    ThreeDaysUtc,
+    OneWeekUtc,
+    OneMonthUtc,
+    ThreeMonthsUtc,
+}
+
+impl Interval {
+    /// Every interval, shortest first with each UTC variant after the others
+    pub const ALL: [Interval; 27] = [
+        Interval::OneSecond,
+        Interval::FiveSeconds,
+        Interval::FifteenSeconds,
+        Interval::OneMinute,
+        Interval::ThreeMinutes,
+        Interval::FiveMinutes,
+        Interval::FifteenMinutes,
+        Interval::ThirtyMinutes,
+        Interval::OneHour,
+        Interval::TwoHours,
+        Interval::FourHours,
+        Interval::SixHours,
+        Interval::TwelveHours,
+        Interval::OneDay,
+        Interval::TwoDays,
+        Interval::ThreeDays,
+        Interval::OneWeek,
+        Interval::OneMonth,
+        Interval::ThreeMonths,
+        Interval::SixHoursUtc,
+        Interval::TwelveHoursUtc,
+        Interval::OneDayUtc,
+        Interval::TwoDaysUtc,
+        Interval::ThreeDaysUtc,
+        Interval::OneWeekUtc,
+        Interval::OneMonthUtc,
+        Interval::ThreeMonthsUtc,
+    ];
+
+    fn spec(&self) -> Spec {
+        use Unit::*;
+        match self {
+            Self::OneSecond => spec("1s", "1s", "candle1s", 1, Second, false),
+            Self::FiveSeconds => spec("5s", "5s", "candle5s", 5, Second, false),
+            Self::FifteenSeconds => spec("15s", "15s", "candle15s", 15, Second, false),
+            Self::OneMinute => spec("1m", "1m", "candle1m", 1, Minute, false),
+            Self::ThreeMinutes => spec("3m", "3m", "candle3m", 3, Minute, false),
+            Self::FiveMinutes => spec("5m", "5m", "candle5m", 5, Minute, false),
+            Self::FifteenMinutes => spec("15m", "15m", "candle15m", 15, Minute, false),
+            Self::ThirtyMinutes => spec("30m", "30m", "candle30m", 30, Minute, false),
+            Self::OneHour => spec("1H", "1h", "candle1H", 1, Hour, false),
+            Self::TwoHours => spec("2H", "2h", "candle2H", 2, Hour, false),
+            Self::FourHours => spec("4H", "4h", "candle4H", 4, Hour, false),
+            Self::SixHours => spec("6H", "6h", "candle6H", 6, Hour, false),
+            Self::TwelveHours => spec("12H", "12h", "candle12H", 12, Hour, false),
+            Self::OneDay => spec("1D", "1d", "candle1D", 1, Day, false),
+            Self::TwoDays => spec("2D", "2d", "candle2D", 2, Day, false),
+            Self::ThreeDays => spec("3D", "3d", "candle3D", 3, Day, false),
+            Self::OneWeek => spec("1W", "1w", "candle1W", 1, Week, false),
+            Self::OneMonth => spec("1M", "1M", "candle1M", 1, Month, false),
+            Self::ThreeMonths => spec("3M", "3M", "candle3M", 3, Month, false),
+            Self::SixHoursUtc => spec("6Hutc", "6hutc", "candle6Hutc", 6, Hour, true),
+            Self::TwelveHoursUtc => spec("12Hutc", "12hutc", "candle12Hutc", 12, Hour, true),
+            Self::OneDayUtc => spec("1Dutc", "1dutc", "candle1Dutc", 1, Day, true),
+            Self::TwoDaysUtc => spec("2Dutc", "2dutc", "candle2Dutc", 2, Day, true),
+            Self::ThreeDaysUtc => spec("3Dutc", "3dutc", "candle3Dutc", 3, Day, true),
+            Self::OneWeekUtc => spec("1Wutc", "1wutc", "candle1Wutc", 1, Week, true),
+            Self::OneMonthUtc => spec("1Mutc", "1Mutc", "candle1Mutc", 1, Month, true),
+            Self::ThreeMonthsUtc => spec("3Mutc", "3Mutc", "candle3Mutc", 3, Month, true),
+        }
+    }
+
+    /// OKX bar label, e.g. `1m`, `4H` or `1Dutc`
+    pub fn as_str(&self) -> &'static str {
+        self.spec().label
+    }
+
+    /// Label stored in the `interval` column of candle tables
+    pub fn storage_label(&self) -> &'static str {
+        self.spec().storage
+    }
+
+    /// WebSocket channel carrying these candles, e.g. `candle1H`
+    pub fn candle_channel(&self) -> &'static str {
+        self.spec().channel
+    }
+
+    /// Whether OKX publishes this bar; the others are built from trades
+    pub fn is_exchange_native(&self) -> bool {
+        !matches!(self, Self::FiveSeconds | Self::FifteenSeconds)
+    }
+
+    /// Fixed bar length; `None` for months
+    pub fn duration(&self) -> Option<Duration> {
+        let Spec { count, unit, .. } = self.spec();
+        match unit {
+            Unit::Second => Some(Duration::seconds(count)),
+            Unit::Minute => Some(Duration::minutes(count)),
+            Unit::Hour => Some(Duration::hours(count)),
+            Unit::Day => Some(Duration::days(count)),
+            Unit::Week => Some(Duration::weeks(count)),
+            Unit::Month => None,
+        }
+    }
+
+    /// Whole bars of `self` in one bar of `longer`, if `longer` is a multiple
+    pub fn bars_per(&self, longer: Interval) -> Option<i64> {
+        let base = self.duration()?.num_milliseconds();
+        let length = longer.duration()?.num_milliseconds();
+        (length >= base && length % base == 0).then(|| length / base)
+    }
+
+    /// Offset bar boundaries are anchored to, east of UTC
+    fn anchor_offset(&self) -> Duration {
+        let Spec {
+            count, unit, utc, ..
+        } = self.spec();
+        let long = match unit {
+            Unit::Second | Unit::Minute => false,
+            Unit::Hour => count >= 6,
+            Unit::Day | Unit::Week | Unit::Month => true,
+        };
+        if long && !utc {
+            Duration::hours(HONG_KONG_OFFSET_HOURS)
+        } else {
+            Duration::zero()
+        }
+    }
+
+    /// Open time of the bar containing `timestamp`
+    pub fn bar_start(&self, timestamp: DateTime<Utc>) -> DateTime<Utc> {
+        let offset = self.anchor_offset();
+        let local = timestamp + offset;
+
+        let start = match self.duration() {
+            Some(length) => {
+                let origin = if self.spec().unit == Unit::Week {
+                    Duration::days(FIRST_MONDAY_DAYS).num_milliseconds()
+                } else {
+                    0
+                };
+                let length = length.num_milliseconds();
+                let millis = local.timestamp_millis() - origin;
+                DateTime::from_timestamp_millis(millis.div_euclid(length) * length + origin)
+            }
+            None => {
+                let months = self.spec().count as u32;
+                let month0 = (local.month0() / months) * months;
+                Utc.with_ymd_and_hms(local.year(), month0 + 1, 1, 0, 0, 0)
+                    .single()
+            }
+        };
+        start.map_or(timestamp, |start| start - offset)
+    }
+
+    /// Open time of the bar after the one containing `timestamp`
+    pub fn next_bar_start(&self, timestamp: DateTime<Utc>) -> DateTime<Utc> {
+        let start = self.bar_start(timestamp);
+        match self.duration() {
+            Some(length) => start + length,
+            None => {
+                let offset = self.anchor_offset();
+                ((start + offset) + Months::new(self.spec().count as u32)) - offset
+            }
+        }
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Interval {
+    type Err = Error;
+
+    /// Parses OKX labels and storage labels alike
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|interval| interval.as_str() == s || interval.storage_label() == s)
+            .ok_or_else(|| Error::InvalidInterval(s.to_string()))
+    }
+}
+
+impl Serialize for Interval {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Interval {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_labels_round_trip() {
+        for interval in Interval::ALL {
+            assert_eq!(interval.as_str().parse::<Interval>().unwrap(), interval);
+            assert_eq!(
+                interval.storage_label().parse::<Interval>().unwrap(),
+                interval
+            );
+        }
+        assert_eq!("1m".parse::<Interval>().unwrap(), Interval::OneMinute);
+        assert_eq!("1M".parse::<Interval>().unwrap(), Interval::OneMonth);
+        assert_eq!("4h".parse::<Interval>().unwrap(), Interval::FourHours);
+        assert!("2m".parse::<Interval>().is_err());
+
+        let json = serde_json::to_string(&Interval::OneHour).unwrap();
+        assert_eq!(json, "\"1H\"");
+        assert_eq!(
+            serde_json::from_str::<Interval>("\"1Dutc\"").unwrap(),
+            Interval::OneDayUtc
+        );
+    }
+
+    #[test]
+    fn test_durations() {
+        assert_eq!(
+            Interval::FifteenMinutes.duration(),
+            Some(Duration::minutes(15))
+        );
+        assert_eq!(Interval::OneDayUtc.duration(), Some(Duration::days(1)));
+        assert_eq!(Interval::ThreeMonths.duration(), None);
+        assert_eq!(Interval::OneHour.bars_per(Interval::FourHours), Some(4));
+        assert_eq!(Interval::FourHours.bars_per(Interval::SixHours), None);
+        assert_eq!(Interval::OneHour.bars_per(Interval::OneMonth), None);
+    }
+
+    #[test]
+    fn test_bar_boundaries_follow_okx_anchors() {
+        let ts = at("2024-03-14T05:17:42Z");
+        assert_eq!(
+            Interval::FiveMinutes.bar_start(ts),
+            at("2024-03-14T05:15:00Z")
+        );
+        assert_eq!(
+            Interval::FourHours.bar_start(ts),
+            at("2024-03-14T04:00:00Z")
+        );
+
+        // Hong Kong midnight is 16:00 UTC the day before
+        assert_eq!(Interval::OneDay.bar_start(ts), at("2024-03-13T16:00:00Z"));
+        assert_eq!(
+            Interval::OneDayUtc.bar_start(ts),
+            at("2024-03-14T00:00:00Z")
+        );
+
+        // 2024-03-11 was a Monday
+        assert_eq!(
+            Interval::OneWeekUtc.bar_start(ts),
+            at("2024-03-11T00:00:00Z")
+        );
+        assert_eq!(
+            Interval::ThreeMonthsUtc.bar_start(ts),
+            at("2024-01-01T00:00:00Z")
+        );
+        assert_eq!(
+            Interval::ThreeMonthsUtc.next_bar_start(ts),
+            at("2024-04-01T00:00:00Z")
+        );
+        assert_eq!(
+            Interval::OneMonth.next_bar_start(ts),
+            at("2024-03-31T16:00:00Z")
+        );
+    }
+}
@@ -1,10 +1,15 @@
 //! Core domain models and types for the EA OKX quantitative trading system.
 //!
 //! This crate provides fundamental types used across the entire system:
-//! - Symbol types for trading pairs
+//! - Symbol types for spot pairs and derivative instruments
 //! - Price and quantity types with precise decimal arithmetic
-//! - Order and position models
+//! - Candlestick intervals with OKX labels and bar boundary math
+//! - Checked arithmetic helpers for PnL and sizing math
+//! - Order and position models, serialized with schema versions
+//! - The venue-neutral exchange adapter interface
+//! - Liveness heartbeats shared between background tasks and their supervisor
 //! - Error types
+//! - The JSON contract for frontend payloads, with generated TypeScript types
 //!
 //! # Examples
 //!
@@ -16,10 +21,17 @@
 //! assert_eq!(symbol.quote(), "USDT");
 //! ```
 
+pub mod contract;
 pub mod error;
+pub mod exchange;
+pub mod heartbeat;
+pub mod interval;
+pub mod math;
 pub mod models;
 pub mod types;
 
 // Re-export common types for convenience
 pub use error::{Error, Result};
-pub use types::{Decimal, Price, Quantity, Symbol};
+pub use exchange::{ExchangeAdapter, MarketEvent, MarketStream, MarketSubscription};
+pub use interval::Interval;
+pub use types::{Decimal, InstrumentKind, OptionType, Price, Quantity, Symbol};
@@ -0,0 +1,105 @@
+//! Checked decimal arithmetic for money and position math
+//!
+//! `Decimal` operators panic on division by zero and on overflow. PnL,
+//! sizing and ratio code goes through these helpers instead so a bad input
+//! surfaces as an [`Error`] rather than aborting the process, and square roots
+//! are taken in `Decimal` without a lossy round trip through `f64`.
+//!
+//! # Examples
+//!
+//! ```
+//! use ea_okx_core::math::{safe_div, safe_mul};
+//! use rust_decimal_macros::dec;
+//!
+//! assert_eq!(safe_div(dec!(10), dec!(4)).unwrap(), dec!(2.5));
+//! assert!(safe_div(dec!(10), dec!(0)).is_err());
+//! assert_eq!(safe_mul(dec!(1.5), dec!(2)).unwrap(), dec!(3.0));
+//! ```
+
+use crate::error::{Error, Result};
+use rust_decimal::{Decimal, MathematicalOps};
+
+/// `numerator / denominator`, failing on a zero denominator or overflow
+pub fn safe_div(numerator: Decimal, denominator: Decimal) -> Result<Decimal> {
+    if denominator.is_zero() {
+        return Err(Error::DivisionByZero(format!("{} / 0", numerator)));
+    }
+    numerator
+        .checked_div(denominator)
+        .ok_or_else(|| Error::ArithmeticOverflow(format!("{} / {}", numerator, denominator)))
+}
+
+/// `lhs * rhs`, failing on overflow
+pub fn safe_mul(lhs: Decimal, rhs: Decimal) -> Result<Decimal> {
+    lhs.checked_mul(rhs)
+        .ok_or_else(|| Error::ArithmeticOverflow(format!("{} * {}", lhs, rhs)))
+}
+
+/// `numerator / denominator`, or `fallback` when the denominator is zero
+///
+/// For ratios with a natural value on an empty base, such as a return on zero
+/// capital. Overflow is still an error.
+pub fn div_or(numerator: Decimal, denominator: Decimal, fallback: Decimal) -> Result<Decimal> {
+    if denominator.is_zero() {
+        return Ok(fallback);
+    }
+    safe_div(numerator, denominator)
+}
+
+/// Square root, failing on a negative value
+pub fn safe_sqrt(value: Decimal) -> Result<Decimal> {
+    value
+        .sqrt()
+        .ok_or_else(|| Error::DecimalError(format!("Square root of negative value: {}", value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_division_by_zero_and_overflow_are_errors() {
+        assert!(matches!(
+            safe_div(dec!(1), Decimal::ZERO),
+            Err(Error::DivisionByZero(_))
+        ));
+        assert!(matches!(
+            safe_div(Decimal::MAX, dec!(0.1)),
+            Err(Error::ArithmeticOverflow(_))
+        ));
+        assert!(matches!(
+            safe_mul(Decimal::MAX, dec!(2)),
+            Err(Error::ArithmeticOverflow(_))
+        ));
+        assert_eq!(safe_mul(Decimal::MAX, Decimal::ONE).unwrap(), Decimal::MAX);
+
+        assert_eq!(div_or(dec!(5), Decimal::ZERO, dec!(-1)).unwrap(), dec!(-1));
+        assert!(div_or(Decimal::MAX, dec!(0.5), Decimal::ZERO).is_err());
+    }
+
+    #[test]
+    fn test_division_keeps_decimal_precision() {
+        // 1/3 carries 28 significant digits, three thirds round back to one
+        let third = safe_div(Decimal::ONE, dec!(3)).unwrap();
+        assert_eq!(third.to_string(), "0.3333333333333333333333333333");
+        assert_eq!(safe_mul(third, dec!(3)).unwrap().round_dp(27), Decimal::ONE);
+
+        // Tiny prices do not lose digits the way an f64 round trip does
+        assert_eq!(
+            safe_div(dec!(0.000000123456789), dec!(0.000000000001)).unwrap(),
+            dec!(123456.789)
+        );
+    }
+
+    #[test]
+    fn test_square_root() {
+        assert_eq!(safe_sqrt(dec!(2.25)).unwrap(), dec!(1.5));
+        assert_eq!(safe_sqrt(Decimal::ZERO).unwrap(), Decimal::ZERO);
+        assert_eq!(
+            safe_sqrt(dec!(252)).unwrap().round_dp(12),
+            dec!(15.874507866388)
+        );
+        assert!(safe_sqrt(dec!(-1)).is_err());
+    }
+}
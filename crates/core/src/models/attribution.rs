@@ -0,0 +1,139 @@
+//! Strategy attribution carried on exchange orders
+//!
+//! OKX echoes an order's `clOrdId` and `tag` on every fill and orders-channel
+//! update, so that is where the owning strategy and signal are recorded. The
+//! client order ID is `e`, the strategy ID as 22 base-62 digits and a 9-digit
+//! per-order nonce: 32 alphanumerics, the most OKX accepts. The tag holds the
+//! first 16 hex digits of the signal ID. Orders placed outside this system
+//! (the OKX app, another bot) carry neither and parse to `None`.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// First character of every client order ID this system generates
+pub const CLIENT_ORDER_ID_PREFIX: char = 'e';
+
+/// Maximum `clOrdId` length accepted by OKX
+pub const CLIENT_ORDER_ID_MAX_LEN: usize = 32;
+
+/// Maximum `tag` length accepted by OKX
+pub const TAG_MAX_LEN: usize = 16;
+
+const BASE62: &[u8; 62] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+const STRATEGY_DIGITS: usize = 22;
+const NONCE_DIGITS: usize = 9;
+
+fn encode_base62(mut value: u128, digits: usize) -> String {
+    let mut out = vec![b'0'; digits];
+    for slot in out.iter_mut().rev() {
+        *slot = BASE62[(value % 62) as usize];
+        value /= 62;
+    }
+    String::from_utf8(out).expect("base-62 digits are ASCII")
+}
+
+fn decode_base62(digits: &str) -> Option<u128> {
+    digits.bytes().try_fold(0u128, |acc, byte| {
+        let digit = BASE62.iter().position(|b| *b == byte)? as u128;
+        acc.checked_mul(62)?.checked_add(digit)
+    })
+}
+
+/// Client order ID for an order of `strategy_id`, unique per `order_id`
+pub fn client_order_id(strategy_id: Uuid, order_id: Uuid) -> String {
+    let nonce = order_id.as_u128() % 62u128.pow(NONCE_DIGITS as u32);
+    format!(
+        "{}{}{}",
+        CLIENT_ORDER_ID_PREFIX,
+        encode_base62(strategy_id.as_u128(), STRATEGY_DIGITS),
+        encode_base62(nonce, NONCE_DIGITS)
+    )
+}
+
+/// Order tag identifying the signal that produced an order
+pub fn signal_tag(signal_id: Uuid) -> String {
+    signal_id.simple().to_string()[..TAG_MAX_LEN].to_string()
+}
+
+/// Strategy and signal an exchange order belongs to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderAttribution {
+    pub strategy_id: Uuid,
+
+    /// Signal tag, see [`signal_tag`]
+    pub signal_tag: Option<String>,
+}
+
+impl OrderAttribution {
+    /// Attribution encoded in an order's `clOrdId` and `tag`
+    ///
+    /// Returns `None` for client order IDs this system did not generate.
+    pub fn parse(client_order_id: &str, tag: Option<&str>) -> Option<Self> {
+        let encoded = client_order_id.strip_prefix(CLIENT_ORDER_ID_PREFIX)?;
+        if !encoded.is_ascii() || encoded.len() != STRATEGY_DIGITS + NONCE_DIGITS {
+            return None;
+        }
+        let strategy_id = Uuid::from_u128(decode_base62(&encoded[..STRATEGY_DIGITS])?);
+        decode_base62(&encoded[STRATEGY_DIGITS..])?;
+
+        let signal_tag = tag
+            .filter(|t| t.len() == TAG_MAX_LEN && t.bytes().all(|b| b.is_ascii_hexdigit()))
+            .map(str::to_string);
+        Some(Self {
+            strategy_id,
+            signal_tag,
+        })
+    }
+
+    /// Whether the order was produced by `signal_id`
+    pub fn is_from_signal(&self, signal_id: Uuid) -> bool {
+        self.signal_tag.as_deref() == Some(signal_tag(signal_id).as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_order_id_fits_okx_and_round_trips() {
+        let strategy_id = Uuid::new_v4();
+        let signal_id = Uuid::new_v4();
+        let id = client_order_id(strategy_id, Uuid::new_v4());
+        let tag = signal_tag(signal_id);
+
+        assert_eq!(id.len(), CLIENT_ORDER_ID_MAX_LEN);
+        assert!(id.bytes().all(|b| b.is_ascii_alphanumeric()));
+        assert_eq!(tag.len(), TAG_MAX_LEN);
+
+        let attribution = OrderAttribution::parse(&id, Some(&tag)).unwrap();
+        assert_eq!(attribution.strategy_id, strategy_id);
+        assert!(attribution.is_from_signal(signal_id));
+        assert!(!attribution.is_from_signal(Uuid::new_v4()));
+
+        // Extreme IDs still encode in the fixed width
+        let max_id = Uuid::from_u128(u128::MAX);
+        let max = client_order_id(max_id, max_id);
+        assert_eq!(
+            OrderAttribution::parse(&max, None).unwrap().strategy_id,
+            max_id
+        );
+    }
+
+    #[test]
+    fn test_foreign_client_order_ids_are_not_attributed() {
+        let strategy_id = Uuid::new_v4();
+        assert!(OrderAttribution::parse("", None).is_none());
+        assert!(OrderAttribution::parse("manual123", None).is_none());
+        assert!(OrderAttribution::parse(&format!("ord_{}", strategy_id.simple()), None).is_none());
+
+        let id = client_order_id(strategy_id, Uuid::new_v4());
+        assert!(OrderAttribution::parse(&id.replacen('e', "x", 1), None).is_none());
+        assert!(OrderAttribution::parse(&id[..31], None).is_none());
+
+        // A tag set by someone else is ignored, the strategy still resolves
+        let attribution = OrderAttribution::parse(&id, Some("desk-7")).unwrap();
+        assert_eq!(attribution.strategy_id, strategy_id);
+        assert_eq!(attribution.signal_tag, None);
+    }
+}
@@ -1,11 +1,15 @@
 //! Domain models for trading entities
 
+pub mod attribution;
 pub mod order;
 pub mod position;
+pub mod schema;
 pub mod strategy;
 pub mod trade;
 
+pub use attribution::{OrderAttribution, client_order_id, signal_tag};
 pub use order::{Order, OrderSide, OrderStatus, OrderType};
 pub use position::{Position, PositionSide};
+pub use schema::{SCHEMA_VERSION_FIELD, Versioned};
 pub use strategy::{Strategy, StrategyConfig, StrategyStatus};
 pub use trade::Trade;
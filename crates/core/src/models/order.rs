@@ -1,15 +1,20 @@
 //! Order model and related types
 
 use crate::error::{Error, Result};
+use crate::models::attribution::{OrderAttribution, client_order_id, signal_tag};
+use crate::models::schema::{self, Versioned, add_field};
 use crate::types::{Price, Quantity, Symbol};
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
 use std::str::FromStr;
+use ts_rs::TS;
 use uuid::Uuid;
 
 /// Order side (buy or sell)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
 pub enum OrderSide {
     Buy,
     Sell,
@@ -28,8 +33,9 @@ impl FromStr for OrderSide {
 }
 
 /// Order type
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
 #[serde(rename_all = "snake_case")]
+#[ts(export)]
 pub enum OrderType {
     Market,
     Limit,
@@ -62,8 +68,9 @@ impl FromStr for OrderType {
 }
 
 /// Order status in lifecycle
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
 pub enum OrderStatus {
     Created,
     Submitted,
@@ -92,7 +99,9 @@ impl FromStr for OrderStatus {
 }
 
 /// Order entity
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(remote = "Self")]
+#[ts(export)]
 pub struct Order {
     /// Internal order ID
     pub id: Uuid,
@@ -100,9 +109,14 @@ pub struct Order {
     /// OKX order ID (after submission)
     pub okx_order_id: Option<String>,
 
-    /// Client-assigned order ID
+    /// Client-assigned order ID, encoding the strategy (see [`attribution`])
+    ///
+    /// [`attribution`]: crate::models::attribution
     pub client_order_id: String,
 
+    /// OKX order tag identifying the originating signal
+    pub tag: Option<String>,
+
     /// Strategy ID that created this order
     pub strategy_id: Uuid,
 
@@ -121,6 +135,12 @@ pub struct Order {
     /// Limit price (None for market orders)
     pub price: Option<Price>,
 
+    /// Only reduce an open position, never open or flip one
+    pub reduce_only: bool,
+
+    /// Cancel the order if it is still resting at this time (good-till-date)
+    pub expires_at: Option<DateTime<Utc>>,
+
     /// Average fill price
     pub avg_fill_price: Option<Price>,
 
@@ -146,9 +166,51 @@ pub struct Order {
     pub completed_at: Option<DateTime<Utc>>,
 
     /// Latency from submission to first fill (milliseconds)
+    #[ts(type = "number | null")]
     pub latency_ms: Option<i64>,
 }
 
+/// Versions: 1 is the original order, 2 added `tag`, 3 `reduce_only` and
+/// 4 `expires_at`
+impl Versioned for Order {
+    const NAME: &'static str = "Order";
+    const SCHEMA_VERSION: u32 = 4;
+
+    fn migrate(from: u32, fields: &mut Map<String, Value>) {
+        match from {
+            1 => add_field(fields, "tag", Value::Null),
+            2 => add_field(fields, "reduce_only", Value::Bool(false)),
+            3 => add_field(fields, "expires_at", Value::Null),
+            _ => {}
+        }
+    }
+
+    fn serialize_fields<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        Order::serialize(self, serializer)
+    }
+
+    fn deserialize_fields<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        Order::deserialize(deserializer)
+    }
+}
+
+impl Serialize for Order {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        schema::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Order {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        schema::deserialize(deserializer)
+    }
+}
+
 impl Order {
     /// Creates a new order
     ///
@@ -183,13 +245,16 @@ impl Order {
         Self {
             id,
             okx_order_id: None,
-            client_order_id: format!("ord_{}", id.simple()),
+            client_order_id: client_order_id(strategy_id, id),
+            tag: None,
             strategy_id,
             symbol,
             side,
             order_type,
             quantity,
             price,
+            reduce_only: false,
+            expires_at: None,
             avg_fill_price: None,
             filled_quantity: Quantity::new(crate::Decimal::ZERO).unwrap(),
             status: OrderStatus::Created,
@@ -202,6 +267,34 @@ impl Order {
         }
     }
 
+    /// Tags the order with the signal that produced it
+    pub fn with_signal(mut self, signal_id: Uuid) -> Self {
+        self.tag = Some(signal_tag(signal_id));
+        self
+    }
+
+    /// Marks the order reduce-only
+    pub fn with_reduce_only(mut self) -> Self {
+        self.reduce_only = true;
+        self
+    }
+
+    /// Expires the order at `expires_at` unless it completes first
+    pub fn with_expiry(mut self, expires_at: DateTime<Utc>) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    /// Checks if the order's good-till-date has passed
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|at| at <= now)
+    }
+
+    /// Strategy and signal recorded in the client order ID and tag
+    pub fn attribution(&self) -> Option<OrderAttribution> {
+        OrderAttribution::parse(&self.client_order_id, self.tag.as_deref())
+    }
+
     /// Checks if order is fully filled
     pub fn is_filled(&self) -> bool {
         self.status == OrderStatus::Filled
@@ -269,6 +362,25 @@ mod tests {
     use super::*;
     use rust_decimal_macros::dec;
 
+    #[test]
+    fn test_order_carries_strategy_and_signal_attribution() {
+        let strategy_id = Uuid::new_v4();
+        let signal_id = Uuid::new_v4();
+        let order = Order::new(
+            strategy_id,
+            Symbol::new("BTC-USDT").unwrap(),
+            OrderSide::Buy,
+            OrderType::Limit,
+            Quantity::new(dec!(0.01)).unwrap(),
+            Some(Price::new(dec!(40000)).unwrap()),
+        )
+        .with_signal(signal_id);
+
+        let attribution = order.attribution().unwrap();
+        assert_eq!(attribution.strategy_id, strategy_id);
+        assert!(attribution.is_from_signal(signal_id));
+    }
+
     #[test]
     fn test_order_side_from_str() {
         assert_eq!("buy".parse::<OrderSide>().unwrap(), OrderSide::Buy);
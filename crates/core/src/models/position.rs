@@ -1,15 +1,19 @@
 //! Position model and related types
 
 use crate::error::{Error, Result};
+use crate::models::schema::{self, Versioned};
 use crate::types::{Decimal, Price, Quantity, Symbol};
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
 use std::str::FromStr;
+use ts_rs::TS;
 use uuid::Uuid;
 
 /// Position side
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
 pub enum PositionSide {
     Long,
     Short,
@@ -30,7 +34,9 @@ impl FromStr for PositionSide {
 }
 
 /// Position entity
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(remote = "Self")]
+#[ts(export)]
 pub struct Position {
     /// Position ID
     pub id: Uuid,
@@ -54,15 +60,19 @@ pub struct Position {
     pub current_price: Price,
 
     /// Unrealized profit/loss
+    #[ts(type = "string")]
     pub unrealized_pnl: Decimal,
 
     /// Realized profit/loss
+    #[ts(type = "string")]
     pub realized_pnl: Decimal,
 
     /// Margin requirement
+    #[ts(type = "string | null")]
     pub margin: Option<Decimal>,
 
     /// Leverage ratio
+    #[ts(type = "string | null")]
     pub leverage: Option<Decimal>,
 
     /// Liquidation price
@@ -75,6 +85,39 @@ pub struct Position {
     pub last_updated: DateTime<Utc>,
 }
 
+/// Version 1 is the original position
+impl Versioned for Position {
+    const NAME: &'static str = "Position";
+    const SCHEMA_VERSION: u32 = 1;
+
+    fn migrate(_from: u32, _fields: &mut Map<String, Value>) {}
+
+    fn serialize_fields<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        Position::serialize(self, serializer)
+    }
+
+    fn deserialize_fields<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        Position::deserialize(deserializer)
+    }
+}
+
+impl Serialize for Position {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        schema::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Position {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        schema::deserialize(deserializer)
+    }
+}
+
 impl Position {
     /// Creates a new position
     pub fn new(
@@ -0,0 +1,207 @@
+//! Schema versions of persisted models
+//!
+//! Orders, positions and trades outlive the build that wrote them: they sit
+//! in state snapshots, the order intent journal and the strategy stores. Each
+//! is written with a `schema_version` tag, and reading one upgrades its JSON
+//! one version at a time until it has the current shape, so a field change
+//! comes with a migration instead of leaving older files unreadable.
+//!
+//! Data written before the tag existed has none and is read as version 1.
+//! Those builds already added some fields, so migrations only fill in
+//! fields that are missing and never overwrite what older data carries.
+//! Data tagged with a version newer than this build knows is refused rather
+//! than read with fields silently dropped.
+//!
+//! Changing a model's fields means bumping its [`Versioned::SCHEMA_VERSION`],
+//! adding the step from the previous version to [`Versioned::migrate`] and
+//! keeping a fixture of the previous shape under `fixtures/models`.
+
+use crate::error::{Error, Result};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+
+/// Field carrying the schema version in serialized models
+pub const SCHEMA_VERSION_FIELD: &str = "schema_version";
+
+/// A model serialized with a schema version and upgraded when read
+pub trait Versioned: Sized {
+    /// Model name used in errors
+    const NAME: &'static str;
+
+    /// Version this build writes
+    const SCHEMA_VERSION: u32;
+
+    /// Rewrite `fields` of version `from` into version `from + 1`
+    fn migrate(from: u32, fields: &mut Map<String, Value>);
+
+    /// Serialize the current shape, without the version tag
+    fn serialize_fields<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error>;
+
+    /// Deserialize the current shape, without the version tag
+    fn deserialize_fields<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error>;
+}
+
+/// Bring serialized `value` of model `T` to its current schema version,
+/// dropping the tag
+pub fn upgrade<T: Versioned>(mut value: Value) -> Result<Value> {
+    let Value::Object(fields) = &mut value else {
+        return Err(Error::SchemaError(format!(
+            "{} is not a JSON object",
+            T::NAME
+        )));
+    };
+
+    let version = match fields.remove(SCHEMA_VERSION_FIELD) {
+        None => 1,
+        Some(tag) => tag
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .filter(|v| *v >= 1)
+            .ok_or_else(|| {
+                Error::SchemaError(format!("{} has an invalid schema version {}", T::NAME, tag))
+            })?,
+    };
+    if version > T::SCHEMA_VERSION {
+        return Err(Error::SchemaError(format!(
+            "{} schema version {} is newer than version {} of this build",
+            T::NAME,
+            version,
+            T::SCHEMA_VERSION
+        )));
+    }
+
+    for from in version..T::SCHEMA_VERSION {
+        T::migrate(from, fields);
+    }
+    Ok(value)
+}
+
+/// Serialize `model` tagged with its schema version
+pub fn serialize<T: Versioned, S: Serializer>(
+    model: &T,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    Tagged {
+        schema_version: T::SCHEMA_VERSION,
+        fields: Fields(model),
+    }
+    .serialize(serializer)
+}
+
+/// Deserialize a model of any known schema version
+pub fn deserialize<'de, T: Versioned, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<T, D::Error> {
+    let value = upgrade::<T>(Value::deserialize(deserializer)?).map_err(D::Error::custom)?;
+    T::deserialize_fields(value).map_err(D::Error::custom)
+}
+
+/// Insert `value` under `key` unless the field is already there
+pub(crate) fn add_field(fields: &mut Map<String, Value>, key: &str, value: Value) {
+    fields.entry(key).or_insert(value);
+}
+
+#[derive(Serialize)]
+#[serde(bound = "")]
+struct Tagged<'a, T: Versioned> {
+    schema_version: u32,
+    #[serde(flatten)]
+    fields: Fields<'a, T>,
+}
+
+struct Fields<'a, T>(&'a T);
+
+impl<T: Versioned> Serialize for Fields<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        self.0.serialize_fields(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Order, OrderStatus, Position, PositionSide, Trade};
+    use crate::types::{Price, Quantity};
+    use rust_decimal_macros::dec;
+    use serde_json::json;
+
+    const ORDER_V1: &str = include_str!("../../fixtures/models/order_v1.json");
+    const ORDER_V2: &str = include_str!("../../fixtures/models/order_v2.json");
+    const ORDER_V3: &str = include_str!("../../fixtures/models/order_v3.json");
+    const ORDER_V4: &str = include_str!("../../fixtures/models/order_v4.json");
+    const POSITION_V1: &str = include_str!("../../fixtures/models/position_v1.json");
+    const TRADE_V1: &str = include_str!("../../fixtures/models/trade_v1.json");
+
+    /// Serialize, read back, and check the second pass is identical
+    fn round_trip<T: Versioned + Serialize + for<'de> Deserialize<'de>>(model: &T) -> Value {
+        let value = serde_json::to_value(model).unwrap();
+        assert_eq!(value[SCHEMA_VERSION_FIELD], json!(T::SCHEMA_VERSION));
+        let again: T = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&again).unwrap(), value);
+        value
+    }
+
+    #[test]
+    fn test_order_fixtures_of_every_version() {
+        for fixture in [ORDER_V1, ORDER_V2, ORDER_V3, ORDER_V4] {
+            let order: Order = serde_json::from_str(fixture).unwrap();
+            assert_eq!(order.client_order_id, "ea1a2b3c4d00000000000000000001");
+            assert_eq!(order.quantity, Quantity::new(dec!(0.5)).unwrap());
+            assert_eq!(order.price, Some(Price::new(dec!(42000.5)).unwrap()));
+            assert_eq!(order.status, OrderStatus::Partial);
+            round_trip(&order);
+        }
+
+        let v1: Order = serde_json::from_str(ORDER_V1).unwrap();
+        assert_eq!(v1.tag, None);
+        assert!(!v1.reduce_only);
+        assert_eq!(v1.expires_at, None);
+
+        // Untagged but written after `tag` and `reduce_only` were added
+        let v3: Order = serde_json::from_str(ORDER_V3).unwrap();
+        assert_eq!(v3.tag.as_deref(), Some("s1a2b3c4d"));
+        assert!(v3.reduce_only);
+        assert_eq!(v3.expires_at, None);
+
+        let v4: Order = serde_json::from_str(ORDER_V4).unwrap();
+        assert_eq!(v4.tag.as_deref(), Some("s1a2b3c4d"));
+        assert!(v4.reduce_only);
+        assert!(v4.expires_at.is_some());
+
+        // The current fixture is exactly what this build writes
+        let written = round_trip(&v4);
+        assert_eq!(written, serde_json::from_str::<Value>(ORDER_V4).unwrap());
+    }
+
+    #[test]
+    fn test_position_and_trade_fixtures() {
+        let position: Position = serde_json::from_str(POSITION_V1).unwrap();
+        assert_eq!(position.side, PositionSide::Long);
+        assert_eq!(position.unrealized_pnl, dec!(3.0));
+        round_trip(&position);
+
+        let trade: Trade = serde_json::from_str(TRADE_V1).unwrap();
+        assert_eq!(trade.commission, dec!(-0.25));
+        assert_eq!(trade.commission_asset, "USDT");
+        round_trip(&trade);
+    }
+
+    #[test]
+    fn test_rejects_unknown_versions() {
+        let mut newer: Value = serde_json::from_str(ORDER_V4).unwrap();
+        newer[SCHEMA_VERSION_FIELD] = json!(Order::SCHEMA_VERSION + 1);
+        let err = serde_json::from_value::<Order>(newer).unwrap_err();
+        assert!(err.to_string().contains("newer than version"));
+
+        let mut invalid: Value = serde_json::from_str(TRADE_V1).unwrap();
+        invalid[SCHEMA_VERSION_FIELD] = json!("one");
+        assert!(serde_json::from_value::<Trade>(invalid).is_err());
+        assert!(upgrade::<Order>(json!([1, 2])).is_err());
+    }
+}
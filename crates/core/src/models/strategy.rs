@@ -51,6 +51,10 @@ pub struct StrategyConfig {
     /// Strategy parameters (JSON)
     pub parameters: JsonValue,
 
+    /// JSON schema for `parameters`; null when none was declared
+    #[serde(default)]
+    pub parameter_schema: JsonValue,
+
     /// Risk limits
     pub risk_limits: JsonValue,
 
@@ -72,6 +76,7 @@ impl StrategyConfig {
     pub fn new(parameters: JsonValue, symbols: Vec<Symbol>, allocated_capital: Decimal) -> Self {
         Self {
             parameters,
+            parameter_schema: JsonValue::Null,
             risk_limits: serde_json::json!({}),
             symbols,
             allocated_capital,
@@ -1,13 +1,18 @@
 //! Trade record model
 
-use crate::models::{OrderSide, OrderType};
+use crate::models::schema::{self, Versioned};
+use crate::models::{OrderAttribution, OrderSide, OrderType};
 use crate::types::{Decimal, Price, Quantity, Symbol};
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+use ts_rs::TS;
 use uuid::Uuid;
 
 /// Trade record - represents a completed trade execution
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(remote = "Self")]
+#[ts(export)]
 pub struct Trade {
     /// Trade ID
     pub id: Uuid,
@@ -36,13 +41,15 @@ pub struct Trade {
     /// Execution price
     pub price: Price,
 
-    /// Trading commission
+    /// Trading commission; negative for a maker rebate
+    #[ts(type = "string")]
     pub commission: Decimal,
 
     /// Commission asset
     pub commission_asset: String,
 
     /// Realized profit/loss
+    #[ts(type = "string | null")]
     pub realized_pnl: Option<Decimal>,
 
     /// Slippage in basis points
@@ -52,11 +59,46 @@ pub struct Trade {
     pub executed_at: DateTime<Utc>,
 
     /// Latency from signal to execution (milliseconds)
+    #[ts(type = "number | null")]
     pub latency_ms: Option<i64>,
 }
 
+/// Version 1 is the original trade
+impl Versioned for Trade {
+    const NAME: &'static str = "Trade";
+    const SCHEMA_VERSION: u32 = 1;
+
+    fn migrate(_from: u32, _fields: &mut Map<String, Value>) {}
+
+    fn serialize_fields<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        Trade::serialize(self, serializer)
+    }
+
+    fn deserialize_fields<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        Trade::deserialize(deserializer)
+    }
+}
+
+impl Serialize for Trade {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        schema::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Trade {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        schema::deserialize(deserializer)
+    }
+}
+
 impl Trade {
     /// Creates a new trade record
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         strategy_id: Uuid,
         client_order_id: String,
@@ -86,6 +128,46 @@ impl Trade {
         }
     }
 
+    /// Strategy recorded in the client order ID
+    pub fn attribution(&self) -> Option<OrderAttribution> {
+        OrderAttribution::parse(&self.client_order_id, None)
+    }
+
+    /// Sets the commission and the asset it was charged in
+    pub fn with_commission(mut self, commission: Decimal, asset: impl Into<String>) -> Self {
+        self.commission = commission;
+        self.commission_asset = asset.into();
+        self
+    }
+
+    /// Whether the fill earned a rebate rather than paying a fee
+    pub fn is_rebate(&self) -> bool {
+        self.commission < Decimal::ZERO
+    }
+
+    /// Commission expressed in `currency`
+    ///
+    /// Converts between the symbol's base and quote at the fill price; other
+    /// pairs need an outside rate, so `None` is returned for them.
+    pub fn commission_in(&self, currency: &str) -> Option<Decimal> {
+        if self.commission_asset.eq_ignore_ascii_case(currency) {
+            return Some(self.commission);
+        }
+        let (base, quote) = (self.symbol.base(), self.symbol.quote());
+        let price = self.price.as_decimal();
+        if self.commission_asset.eq_ignore_ascii_case(base) && quote.eq_ignore_ascii_case(currency)
+        {
+            Some(self.commission * price)
+        } else if self.commission_asset.eq_ignore_ascii_case(quote)
+            && base.eq_ignore_ascii_case(currency)
+            && !price.is_zero()
+        {
+            Some(self.commission / price)
+        } else {
+            None
+        }
+    }
+
     /// Returns the trade value (quantity * price)
     pub fn trade_value(&self) -> Decimal {
         self.quantity.as_decimal() * self.price.as_decimal()
@@ -203,6 +285,30 @@ mod tests {
         assert_eq!(trade.effective_price(), dec!(42042));
     }
 
+    #[test]
+    fn test_commission_in_converts_at_fill_price() {
+        let trade = Trade::new(
+            Uuid::new_v4(),
+            "ord_123".to_string(),
+            Symbol::new("BTC-USDT").unwrap(),
+            OrderSide::Buy,
+            OrderType::Limit,
+            Quantity::new(dec!(0.1)).unwrap(),
+            Price::new(dec!(40000)).unwrap(),
+            dec!(0),
+        )
+        .with_commission(dec!(-0.00001), "BTC");
+
+        assert!(trade.is_rebate());
+        assert_eq!(trade.commission_in("BTC"), Some(dec!(-0.00001)));
+        assert_eq!(trade.commission_in("usdt"), Some(dec!(-0.4)));
+        assert_eq!(trade.commission_in("USDC"), None);
+
+        let trade = trade.with_commission(dec!(4), "USDT");
+        assert!(!trade.is_rebate());
+        assert_eq!(trade.commission_in("BTC"), Some(dec!(0.0001)));
+    }
+
     #[test]
     fn test_trade_serialization() {
         let trade = Trade::new(
@@ -1,16 +1,106 @@
 //! Common types used throughout the trading system
 
 use crate::error::{Error, Result};
+use chrono::NaiveDate;
 use rust_decimal::Decimal as RustDecimal;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::str::FromStr;
+use ts_rs::TS;
 
 /// Type alias for decimal precision
 pub type Decimal = RustDecimal;
 
-/// Trading pair symbol (e.g., BTC-USDT)
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// OKX instrument type, as used in `instType`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum InstrumentKind {
+    /// `BTC-USDT`
+    Spot,
+    /// Perpetual swap, `BTC-USDT-SWAP`
+    Swap,
+    /// Dated futures, `BTC-USD-240628`
+    Futures,
+    /// `BTC-USD-240628-60000-C`
+    Option,
+}
+
+impl InstrumentKind {
+    /// The `instType` value OKX uses for this kind
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Spot => "SPOT",
+            Self::Swap => "SWAP",
+            Self::Futures => "FUTURES",
+            Self::Option => "OPTION",
+        }
+    }
+}
+
+impl fmt::Display for InstrumentKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for InstrumentKind {
+    type Err = Error;
+
+    /// Parses `instType` values in any case, e.g. `swap`
+    fn from_str(s: &str) -> Result<Self> {
+        [Self::Spot, Self::Swap, Self::Futures, Self::Option]
+            .into_iter()
+            .find(|kind| kind.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| Error::ValidationError(format!("Unknown instrument kind: {}", s)))
+    }
+}
+
+/// Call or put
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OptionType {
+    Call,
+    Put,
+}
+
+impl OptionType {
+    fn suffix(&self) -> &'static str {
+        match self {
+            Self::Call => "C",
+            Self::Put => "P",
+        }
+    }
+}
+
+/// Expiry date format in OKX instrument IDs (`240628`)
+const EXPIRY_FORMAT: &str = "%y%m%d";
+
+fn parse_expiry(s: &str) -> Option<NaiveDate> {
+    if s.len() != 6 || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    NaiveDate::parse_from_str(s, EXPIRY_FORMAT).ok()
+}
+
+fn parse_strike(s: &str) -> Option<Decimal> {
+    Decimal::from_str(s)
+        .ok()
+        .filter(|strike| *strike > Decimal::ZERO)
+}
+
+fn parse_option_type(s: &str) -> Option<OptionType> {
+    match s {
+        "C" => Some(OptionType::Call),
+        "P" => Some(OptionType::Put),
+        _ => None,
+    }
+}
+
+/// OKX instrument ID: a spot pair (`BTC-USDT`), perpetual swap
+/// (`BTC-USDT-SWAP`), dated future (`BTC-USD-240628`) or option
+/// (`BTC-USD-240628-60000-C`)
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, TS)]
+#[ts(export)]
 pub struct Symbol(String);
 
 impl Symbol {
@@ -19,15 +109,19 @@ impl Symbol {
     /// # Examples
     ///
     /// ```
-    /// use ea_okx_core::types::Symbol;
+    /// use ea_okx_core::types::{InstrumentKind, Symbol};
     ///
     /// let symbol = Symbol::new("BTC-USDT").unwrap();
     /// assert_eq!(symbol.as_str(), "BTC-USDT");
+    ///
+    /// let swap = Symbol::new("btc-usdt-swap").unwrap();
+    /// assert_eq!(swap.kind(), InstrumentKind::Swap);
+    /// assert_eq!(swap.underlying(), symbol);
     /// ```
     pub fn new(s: impl Into<String>) -> Result<Self> {
-        let s = s.into();
+        let s = s.into().to_uppercase();
 
-        // Validate format: BASE-QUOTE
+        // Validate format: BASE-QUOTE[-SWAP | -YYMMDD[-STRIKE-C|P]]
         if !s.contains('-') {
             return Err(Error::InvalidSymbol(format!(
                 "Symbol must contain '-' separator: {}",
@@ -36,37 +130,133 @@ impl Symbol {
         }
 
         let parts: Vec<&str> = s.split('-').collect();
-        if parts.len() != 2 {
+        if parts[0].is_empty() || parts[1].is_empty() {
             return Err(Error::InvalidSymbol(format!(
-                "Symbol must have exactly one '-' separator: {}",
+                "Base and quote cannot be empty: {}",
                 s
             )));
         }
 
-        if parts[0].is_empty() || parts[1].is_empty() {
+        let valid = match parts[2..] {
+            [] => true,
+            [suffix] => suffix == "SWAP" || parse_expiry(suffix).is_some(),
+            [expiry, strike, option_type] => {
+                parse_expiry(expiry).is_some()
+                    && parse_strike(strike).is_some()
+                    && parse_option_type(option_type).is_some()
+            }
+            _ => false,
+        };
+        if !valid {
             return Err(Error::InvalidSymbol(format!(
-                "Base and quote cannot be empty: {}",
+                "Expected BASE-QUOTE, BASE-QUOTE-SWAP, BASE-QUOTE-YYMMDD or \
+                 BASE-QUOTE-YYMMDD-STRIKE-C/P: {}",
                 s
             )));
         }
 
-        Ok(Self(s.to_uppercase()))
+        Ok(Self(s))
+    }
+
+    /// Perpetual swap on `base`-`quote`
+    pub fn swap(base: &str, quote: &str) -> Result<Self> {
+        Self::new(format!("{}-{}-SWAP", base, quote))
+    }
+
+    /// Futures on `base`-`quote` expiring on `expiry`
+    pub fn futures(base: &str, quote: &str, expiry: NaiveDate) -> Result<Self> {
+        Self::new(format!(
+            "{}-{}-{}",
+            base,
+            quote,
+            expiry.format(EXPIRY_FORMAT)
+        ))
+    }
+
+    /// Option on `base`-`quote` expiring on `expiry`
+    pub fn option(
+        base: &str,
+        quote: &str,
+        expiry: NaiveDate,
+        strike: Decimal,
+        option_type: OptionType,
+    ) -> Result<Self> {
+        Self::new(format!(
+            "{}-{}-{}-{}-{}",
+            base,
+            quote,
+            expiry.format(EXPIRY_FORMAT),
+            strike.normalize(),
+            option_type.suffix()
+        ))
     }
 
     /// Returns the base currency
     pub fn base(&self) -> &str {
-        self.0.split('-').next().unwrap()
+        self.part(0).unwrap()
     }
 
     /// Returns the quote currency
     pub fn quote(&self) -> &str {
-        self.0.split('-').nth(1).unwrap()
+        self.part(1).unwrap()
+    }
+
+    /// Instrument type, read from the ID's suffix
+    pub fn kind(&self) -> InstrumentKind {
+        match (self.part(2), self.part(3)) {
+            (None, _) => InstrumentKind::Spot,
+            (Some("SWAP"), _) => InstrumentKind::Swap,
+            (Some(_), None) => InstrumentKind::Futures,
+            (Some(_), Some(_)) => InstrumentKind::Option,
+        }
+    }
+
+    /// Whether this is a swap, future or option
+    pub fn is_derivative(&self) -> bool {
+        self.kind() != InstrumentKind::Spot
+    }
+
+    /// Currency profit and loss settles in: the base currency for
+    /// coin-margined derivatives quoted in `USD`, the quote currency otherwise
+    pub fn settlement_currency(&self) -> &str {
+        if self.is_derivative() && self.quote() == "USD" {
+            self.base()
+        } else {
+            self.quote()
+        }
+    }
+
+    /// Spot pair the instrument is based on (`BTC-USD-240628` -> `BTC-USD`)
+    pub fn underlying(&self) -> Symbol {
+        Self(format!("{}-{}", self.base(), self.quote()))
+    }
+
+    /// Expiry date of futures and options
+    pub fn expiry(&self) -> Option<NaiveDate> {
+        match self.kind() {
+            InstrumentKind::Futures | InstrumentKind::Option => parse_expiry(self.part(2)?),
+            InstrumentKind::Spot | InstrumentKind::Swap => None,
+        }
+    }
+
+    /// Strike price of an option
+    pub fn strike(&self) -> Option<Decimal> {
+        parse_strike(self.part(3)?)
+    }
+
+    /// Whether an option is a call or a put
+    pub fn option_type(&self) -> Option<OptionType> {
+        parse_option_type(self.part(4)?)
     }
 
     /// Returns the symbol as a string slice
     pub fn as_str(&self) -> &str {
         &self.0
     }
+
+    fn part(&self, index: usize) -> Option<&str> {
+        self.0.split('-').nth(index)
+    }
 }
 
 impl fmt::Display for Symbol {
@@ -84,8 +274,9 @@ impl FromStr for Symbol {
 }
 
 /// Price with 8 decimal precision
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
-pub struct Price(Decimal);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct Price(#[ts(type = "string")] Decimal);
 
 impl Price {
     /// Creates a new Price
@@ -129,8 +320,9 @@ impl fmt::Display for Price {
 }
 
 /// Quantity with 8 decimal precision
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
-pub struct Quantity(Decimal);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct Quantity(#[ts(type = "string")] Decimal);
 
 impl Quantity {
     /// Creates a new Quantity
@@ -215,6 +407,58 @@ mod tests {
         assert!(Symbol::new("BTC-").is_err());
     }
 
+    #[test]
+    fn test_symbol_derivatives() {
+        let swap = Symbol::new("BTC-USDT-SWAP").unwrap();
+        assert_eq!(swap.kind(), InstrumentKind::Swap);
+        assert_eq!(swap.base(), "BTC");
+        assert_eq!(swap.quote(), "USDT");
+        assert_eq!(swap.underlying().as_str(), "BTC-USDT");
+        assert_eq!(swap.expiry(), None);
+        assert_eq!(swap.settlement_currency(), "USDT");
+
+        let expiry = NaiveDate::from_ymd_opt(2024, 6, 28).unwrap();
+        let future = Symbol::new("btc-usd-240628").unwrap();
+        assert_eq!(future.kind(), InstrumentKind::Futures);
+        assert_eq!(future.expiry(), Some(expiry));
+        assert_eq!(future.strike(), None);
+        assert_eq!(future.settlement_currency(), "BTC");
+        assert_eq!(future, Symbol::futures("BTC", "USD", expiry).unwrap());
+
+        let option = Symbol::new("BTC-USD-240628-60000-C").unwrap();
+        assert_eq!(option.kind(), InstrumentKind::Option);
+        assert_eq!(option.underlying().as_str(), "BTC-USD");
+        assert_eq!(option.expiry(), Some(expiry));
+        assert_eq!(option.strike(), Some(dec!(60000)));
+        assert_eq!(option.option_type(), Some(OptionType::Call));
+        assert_eq!(
+            option,
+            Symbol::option("BTC", "USD", expiry, dec!(60000.00), OptionType::Call).unwrap()
+        );
+
+        let spot = Symbol::new("ETH-USDT").unwrap();
+        assert_eq!(spot.kind(), InstrumentKind::Spot);
+        assert!(!spot.is_derivative());
+        assert_eq!(spot.settlement_currency(), "USDT");
+        assert_eq!(Symbol::swap("ETH", "USDT").unwrap().underlying(), spot);
+
+        assert_eq!(
+            "swap".parse::<InstrumentKind>().unwrap(),
+            InstrumentKind::Swap
+        );
+        assert!("perp".parse::<InstrumentKind>().is_err());
+    }
+
+    #[test]
+    fn test_symbol_invalid_derivatives() {
+        assert!(Symbol::new("BTC-USD-241340").is_err());
+        assert!(Symbol::new("BTC-USD-2406").is_err());
+        assert!(Symbol::new("BTC-USD-240628-60000").is_err());
+        assert!(Symbol::new("BTC-USD-240628-60000-X").is_err());
+        assert!(Symbol::new("BTC-USD-240628-0-P").is_err());
+        assert!(Symbol::new("BTC-USD-SWAP-60000-C").is_err());
+    }
+
     #[test]
     fn test_symbol_from_str() {
         let symbol: Symbol = "ETH-BTC".parse().unwrap();
@@ -0,0 +1,28 @@
+-- Strategy store schema
+-- Portable between SQLite and PostgreSQL: identifiers and timestamps are
+-- stored as text (UUID / RFC 3339) and documents as JSON text.
+
+CREATE TABLE IF NOT EXISTS strategy_records (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    strategy_type TEXT NOT NULL,
+    status TEXT NOT NULL,
+    config TEXT NOT NULL,
+    document TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_strategy_records_status ON strategy_records(status);
+
+CREATE TABLE IF NOT EXISTS strategy_status_history (
+    id TEXT PRIMARY KEY,
+    strategy_id TEXT NOT NULL,
+    from_status TEXT,
+    to_status TEXT NOT NULL,
+    reason TEXT,
+    changed_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_strategy_status_history_strategy
+    ON strategy_status_history(strategy_id, changed_at);
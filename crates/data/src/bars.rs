@@ -0,0 +1,259 @@
+//! Sub-minute candles built from the trades channel
+//!
+//! OKX's finest candles are one second, and only for some instruments,
+//! leaving strategies that react within seconds without 5s or 15s bars.
+//! [`BarAggregator`] folds trades into OHLCV bars of any [`Interval`] per
+//! symbol, on the same boundaries OKX uses, and hands each bar back once its
+//! interval has ended, either because a later trade arrived or because
+//! [`BarAggregator::flush`] was called with a later time. Intervals without
+//! trades produce no bar.
+
+use crate::error::Result;
+use crate::storage::Candle;
+use chrono::{DateTime, Duration, Utc};
+use ea_okx_core::Interval;
+use ea_okx_core::types::{Price, Quantity, Symbol};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Sub-minute bar configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BarConfig {
+    /// Bar lengths to build for every collected symbol
+    pub intervals: Vec<Interval>,
+
+    /// Store completed bars alongside the exchange candles
+    pub store: bool,
+
+    /// Stored sub-minute bars older than this are deleted
+    pub retention_secs: u64,
+}
+
+impl Default for BarConfig {
+    fn default() -> Self {
+        Self {
+            intervals: vec![Interval::OneSecond, Interval::FiveSeconds],
+            store: false,
+            retention_secs: 6 * 3600,
+        }
+    }
+}
+
+impl BarConfig {
+    pub fn retention(&self) -> Duration {
+        Duration::seconds(self.retention_secs as i64)
+    }
+}
+
+/// Bar still accumulating trades
+#[derive(Debug, Clone)]
+struct OpenBar {
+    start: DateTime<Utc>,
+    open: Price,
+    high: Price,
+    low: Price,
+    close: Price,
+    volume: Decimal,
+    quote_volume: Decimal,
+    trade_count: i32,
+}
+
+impl OpenBar {
+    fn new(start: DateTime<Utc>, price: Price) -> Self {
+        Self {
+            start,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume: Decimal::ZERO,
+            quote_volume: Decimal::ZERO,
+            trade_count: 0,
+        }
+    }
+
+    fn add(&mut self, price: Price, quantity: Quantity) {
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.close = price;
+        self.volume += quantity.as_decimal();
+        self.quote_volume += quantity.as_decimal() * price.as_decimal();
+        self.trade_count += 1;
+    }
+
+    fn into_candle(self, symbol: &Symbol, interval: Interval) -> Result<Candle> {
+        let vwap = (!self.volume.is_zero()).then(|| self.quote_volume / self.volume);
+        Ok(Candle {
+            symbol: symbol.clone(),
+            timestamp: self.start,
+            interval,
+            open: self.open,
+            high: self.high,
+            low: self.low,
+            close: self.close,
+            volume: Quantity::new(self.volume)?,
+            quote_volume: self.quote_volume,
+            trade_count: self.trade_count,
+            vwap,
+        })
+    }
+}
+
+/// Folds trades into sub-minute OHLCV bars
+pub struct BarAggregator {
+    config: BarConfig,
+    open: HashMap<(Symbol, Interval), OpenBar>,
+    late_trades: u64,
+}
+
+impl BarAggregator {
+    pub fn new(config: BarConfig) -> Self {
+        Self {
+            config,
+            open: HashMap::new(),
+            late_trades: 0,
+        }
+    }
+
+    pub fn config(&self) -> &BarConfig {
+        &self.config
+    }
+
+    /// Add a trade, returning the bars it closed
+    ///
+    /// A trade older than the symbol's open bar is counted and dropped: the
+    /// bar it belongs to has already been emitted.
+    pub fn on_trade(
+        &mut self,
+        symbol: &Symbol,
+        price: Price,
+        quantity: Quantity,
+        timestamp: DateTime<Utc>,
+    ) -> Result<Vec<Candle>> {
+        let mut closed = Vec::new();
+        for interval in self.config.intervals.clone() {
+            let start = interval.bar_start(timestamp);
+            let key = (symbol.clone(), interval);
+
+            match self.open.get_mut(&key) {
+                Some(bar) if bar.start == start => bar.add(price, quantity),
+                Some(bar) if bar.start > start => self.late_trades += 1,
+                _ => {
+                    let mut bar = OpenBar::new(start, price);
+                    bar.add(price, quantity);
+                    if let Some(previous) = self.open.insert(key, bar) {
+                        closed.push(previous.into_candle(symbol, interval)?);
+                    }
+                }
+            }
+        }
+        Ok(closed)
+    }
+
+    /// Close every bar whose interval ended at or before `now`
+    pub fn flush(&mut self, now: DateTime<Utc>) -> Result<Vec<Candle>> {
+        let ended: Vec<(Symbol, Interval)> = self
+            .open
+            .iter()
+            .filter(|((_, interval), bar)| interval.next_bar_start(bar.start) <= now)
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        let mut closed = Vec::with_capacity(ended.len());
+        for key in ended {
+            if let Some(bar) = self.open.remove(&key) {
+                closed.push(bar.into_candle(&key.0, key.1)?);
+            }
+        }
+        closed.sort_by_key(|c| c.timestamp);
+        Ok(closed)
+    }
+
+    /// Trades dropped for arriving after their bar was emitted
+    pub fn late_trades(&self) -> u64 {
+        self.late_trades
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn at(millis: i64) -> DateTime<Utc> {
+        // Minute-aligned, so every bar length starts at 0
+        DateTime::from_timestamp_millis(1_699_999_980_000 + millis).unwrap()
+    }
+
+    fn trade(
+        bars: &mut BarAggregator,
+        price: Decimal,
+        quantity: Decimal,
+        millis: i64,
+    ) -> Vec<Candle> {
+        bars.on_trade(
+            &Symbol::new("BTC-USDT").unwrap(),
+            Price::new(price).unwrap(),
+            Quantity::new(quantity).unwrap(),
+            at(millis),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_bar_start_aligns_to_interval() {
+        let ts = at(7_250);
+        assert_eq!(Interval::FiveSeconds.bar_start(ts), at(5_000));
+        assert_eq!(Interval::FifteenSeconds.bar_start(ts), at(0));
+        assert_eq!("15s".parse::<Interval>().unwrap(), Interval::FifteenSeconds);
+        assert!("2s".parse::<Interval>().is_err());
+    }
+
+    #[test]
+    fn test_trades_fold_into_ohlcv_bars() {
+        let mut bars = BarAggregator::new(BarConfig {
+            intervals: vec![Interval::OneSecond],
+            ..Default::default()
+        });
+
+        assert!(trade(&mut bars, dec!(100), dec!(1), 100).is_empty());
+        assert!(trade(&mut bars, dec!(103), dec!(1), 400).is_empty());
+        assert!(trade(&mut bars, dec!(99), dec!(2), 900).is_empty());
+
+        let closed = trade(&mut bars, dec!(101), dec!(1), 1_200);
+        assert_eq!(closed.len(), 1);
+        let bar = &closed[0];
+        assert_eq!(bar.interval, Interval::OneSecond);
+        assert_eq!(bar.timestamp, at(0));
+        assert_eq!(bar.open.as_decimal(), dec!(100));
+        assert_eq!(bar.high.as_decimal(), dec!(103));
+        assert_eq!(bar.low.as_decimal(), dec!(99));
+        assert_eq!(bar.close.as_decimal(), dec!(99));
+        assert_eq!(bar.volume.as_decimal(), dec!(4));
+        assert_eq!(bar.trade_count, 3);
+        assert_eq!(bar.vwap, Some(dec!(100.25)));
+
+        // A trade for the emitted bar is dropped
+        assert!(trade(&mut bars, dec!(100), dec!(1), 950).is_empty());
+        assert_eq!(bars.late_trades(), 1);
+    }
+
+    #[test]
+    fn test_flush_closes_quiet_bars_only_after_they_end() {
+        let mut bars = BarAggregator::new(BarConfig {
+            intervals: vec![Interval::OneSecond, Interval::FiveSeconds],
+            ..Default::default()
+        });
+        trade(&mut bars, dec!(100), dec!(1), 200);
+
+        let closed = bars.flush(at(1_000)).unwrap();
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].interval, Interval::OneSecond);
+
+        assert!(bars.flush(at(4_999)).unwrap().is_empty());
+        let closed = bars.flush(at(5_000)).unwrap();
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].interval, Interval::FiveSeconds);
+    }
+}
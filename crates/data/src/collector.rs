@@ -1,20 +1,33 @@
 //! Market data collector
 //!
-//! Collects real-time market data from OKX WebSocket streams,
-//! applies quality control, and stores to database/cache.
+//! Collects real-time market data from an exchange's streams through its
+//! [`ExchangeAdapter`], applies quality control, and stores to
+//! database/cache. [`MarketDataCollector::initialize`] connects to OKX;
+//! other venues plug in through [`MarketDataCollector::initialize_with`].
 
+use crate::bars::{BarAggregator, BarConfig};
 use crate::error::{Error, Result};
+use crate::integrity::day_start;
+use crate::price_cache::PriceCache;
 use crate::quality::{QualityConfig, QualityControl};
-use crate::storage::{Candle, RedisStorage, Tick, TimescaleStorage};
-use chrono::Utc;
-use ea_okx_client::models::{
-    CandleData, Channel, SubscriptionRequest, TickerData, TradeData, WebSocketEvent,
-};
+use crate::recorder::{RawFeedConfig, RawFeedRecorder, replay_capture};
+use crate::storage::{Candle, FundingRate, RedisStorage, Tick, TimescaleStorage};
+use chrono::{Duration, Utc};
+use ea_okx_client::adapter::market_event;
 use ea_okx_client::websocket::OkxWebSocketClient;
-use ea_okx_client::Credentials;
+use ea_okx_client::{Credentials, OkxAdapter, OkxRestClient};
+use ea_okx_core::Interval;
+use ea_okx_core::exchange::{
+    CandleUpdate, ExchangeAdapter, FundingUpdate, MarketEvent, MarketStream, MarketSubscription,
+    TickerUpdate, TradeTick,
+};
+use ea_okx_core::models::OrderSide;
 use ea_okx_core::types::{Price, Quantity, Symbol};
+use parking_lot::Mutex;
+use std::path::Path;
 use std::sync::Arc;
 use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
 use tracing::{error, info, warn};
 
 /// Market data collector configuration
@@ -23,8 +36,8 @@ pub struct CollectorConfig {
     /// Symbols to collect data for
     pub symbols: Vec<String>,
 
-    /// Channels to subscribe to
-    pub channels: Vec<Channel>,
+    /// Streams to subscribe to for every symbol
+    pub streams: Vec<MarketStream>,
 
     /// Quality control configuration
     pub quality_config: QualityConfig,
@@ -34,16 +47,28 @@ pub struct CollectorConfig {
 
     /// Enable Redis caching
     pub enable_redis: bool,
+
+    /// Archive every raw WebSocket frame to disk
+    pub record_raw_feed: Option<RawFeedConfig>,
+
+    /// Build sub-minute candles from the trades channel
+    pub sub_minute_bars: Option<BarConfig>,
 }
 
 impl Default for CollectorConfig {
     fn default() -> Self {
         Self {
             symbols: vec!["BTC-USDT".to_string()],
-            channels: vec![Channel::Tickers, Channel::Candle1m, Channel::Trades],
+            streams: vec![
+                MarketStream::Ticker,
+                MarketStream::Candles(Interval::OneMinute),
+                MarketStream::Trades,
+            ],
             quality_config: QualityConfig::default(),
             enable_timescale: false,
             enable_redis: false,
+            record_raw_feed: None,
+            sub_minute_bars: None,
         }
     }
 }
@@ -51,29 +76,68 @@ impl Default for CollectorConfig {
 /// Market data collector
 pub struct MarketDataCollector {
     config: CollectorConfig,
-    ws_client: Option<OkxWebSocketClient>,
+    exchange: Option<Arc<dyn ExchangeAdapter>>,
     quality_control: Arc<QualityControl>,
     timescale: Option<TimescaleStorage>,
     redis: Option<RedisStorage>,
+    price_cache: Option<Arc<dyn PriceCache>>,
+    recorder: Option<JoinHandle<Result<u64>>>,
     shutdown_tx: Option<mpsc::Sender<()>>,
+    bars: Option<Mutex<BarAggregator>>,
+    bar_tx: mpsc::UnboundedSender<Candle>,
+    bar_rx: Mutex<Option<mpsc::UnboundedReceiver<Candle>>>,
+}
+
+/// Outcome of replaying a raw capture through the collector
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReplaySummary {
+    /// Frames read from the capture
+    pub frames: usize,
+    /// Frames the parser accepted
+    pub parsed: usize,
+    /// Frames the parser rejected
+    pub parse_failures: usize,
+    /// Parsed events that failed processing
+    pub processing_failures: usize,
 }
 
 impl MarketDataCollector {
     /// Create a new market data collector
     pub fn new(config: CollectorConfig) -> Self {
         let quality_control = Arc::new(QualityControl::new(config.quality_config.clone()));
+        let bars = config
+            .sub_minute_bars
+            .clone()
+            .map(|bar_config| Mutex::new(BarAggregator::new(bar_config)));
+        let (bar_tx, bar_rx) = mpsc::unbounded_channel();
 
         Self {
             config,
-            ws_client: None,
+            exchange: None,
             quality_control,
             timescale: None,
             redis: None,
+            price_cache: None,
+            recorder: None,
             shutdown_tx: None,
+            bars,
+            bar_tx,
+            bar_rx: Mutex::new(Some(bar_rx)),
         }
     }
 
-    /// Initialize connections
+    /// Write validated ticker prices to `cache`
+    pub fn with_price_cache(mut self, cache: Arc<dyn PriceCache>) -> Self {
+        self.price_cache = Some(cache);
+        self
+    }
+
+    /// Get completed sub-minute candles (can only be called once)
+    pub fn subscribe_bars(&self) -> Option<mpsc::UnboundedReceiver<Candle>> {
+        self.bar_rx.lock().take()
+    }
+
+    /// Connect to OKX and initialize
     pub async fn initialize(
         &mut self,
         credentials: Credentials,
@@ -81,59 +145,84 @@ impl MarketDataCollector {
         timescale_url: Option<&str>,
         redis_url: Option<&str>,
     ) -> Result<()> {
-        // Initialize WebSocket client
+        let rest = OkxRestClient::new(credentials.clone(), is_testnet)?;
         let mut ws_client = OkxWebSocketClient::new(credentials, is_testnet);
-        ws_client
-            .connect()
+
+        // Tap raw frames before connecting so nothing is missed
+        if let Some(raw_config) = &self.config.record_raw_feed {
+            let recorder = RawFeedRecorder::new(raw_config.clone())?;
+            self.recorder = Some(recorder.spawn(ws_client.capture_raw_frames()));
+            info!("Recording raw feed to {}", raw_config.directory.display());
+        }
+
+        ws_client.connect().await.map_err(Error::WebSocketError)?;
+
+        let exchange = OkxAdapter::new(Arc::new(rest)).with_websocket(ws_client);
+        self.initialize_with(Arc::new(exchange), timescale_url, redis_url)
             .await
-            .map_err(|e| Error::WebSocketError(e))?;
+    }
 
-        // Subscribe to channels
+    /// Initialize on `exchange`, whose streaming connection must be open
+    pub async fn initialize_with(
+        &mut self,
+        exchange: Arc<dyn ExchangeAdapter>,
+        timescale_url: Option<&str>,
+        redis_url: Option<&str>,
+    ) -> Result<()> {
         let mut subscriptions = Vec::new();
         for symbol in &self.config.symbols {
-            for channel in &self.config.channels {
-                subscriptions.push(SubscriptionRequest::new(channel.clone(), symbol));
+            let symbol = Symbol::new(symbol)?;
+            for stream in &self.config.streams {
+                subscriptions.push(MarketSubscription::new(symbol.clone(), *stream));
             }
         }
 
-        ws_client
-            .subscribe(subscriptions)
-            .await
-            .map_err(|e| Error::WebSocketError(e))?;
-        self.ws_client = Some(ws_client);
+        exchange.subscribe(&subscriptions).await?;
+        self.exchange = Some(exchange);
 
         // Initialize storage backends
-        if self.config.enable_timescale {
-            if let Some(url) = timescale_url {
-                self.timescale = Some(TimescaleStorage::new(url).await?);
-                info!("TimescaleDB storage initialized");
-            }
+        if self.config.enable_timescale
+            && let Some(url) = timescale_url
+        {
+            self.timescale = Some(TimescaleStorage::new(url).await?);
+            info!("TimescaleDB storage initialized");
         }
 
-        if self.config.enable_redis {
-            if let Some(url) = redis_url {
-                self.redis = Some(RedisStorage::new(url)?);
-                info!("Redis cache initialized");
-            }
+        if self.config.enable_redis
+            && let Some(url) = redis_url
+        {
+            self.redis = Some(RedisStorage::new(url)?);
+            info!("Redis cache initialized");
         }
 
         info!(
-            "Market data collector initialized for {} symbols",
-            self.config.symbols.len()
+            "Market data collector initialized for {} symbols on {}",
+            self.config.symbols.len(),
+            self.exchange.as_ref().map(|e| e.name()).unwrap_or_default()
         );
         Ok(())
     }
 
     /// Start collecting data
     pub async fn start(&mut self) -> Result<()> {
-        let ws_client = self
-            .ws_client
-            .as_ref()
-            .ok_or_else(|| Error::ConfigError("WebSocket client not initialized".to_string()))?;
+        let exchange = self
+            .exchange
+            .clone()
+            .ok_or_else(|| Error::ConfigError("Exchange not initialized".to_string()))?;
 
         let (shutdown_tx, mut shutdown_rx) = mpsc::channel(1);
         self.shutdown_tx = Some(shutdown_tx);
 
+        // Quiet bars are closed on a timer rather than by the next trade
+        let mut bar_flush = tokio::time::interval(std::time::Duration::from_millis(250));
+        let mut bar_prune = tokio::time::interval(std::time::Duration::from_secs(60));
+        let store_bars = self.timescale.is_some()
+            && self
+                .config
+                .sub_minute_bars
+                .as_ref()
+                .is_some_and(|c| c.store);
+
         info!("Starting market data collection...");
 
         loop {
@@ -144,8 +233,20 @@ impl MarketDataCollector {
                     break;
                 }
 
-                // Process WebSocket messages
-                event = ws_client.next_message() => {
+                _ = bar_flush.tick(), if self.bars.is_some() => {
+                    if let Err(e) = self.flush_bars(Utc::now()).await {
+                        error!("Error flushing sub-minute bars: {}", e);
+                    }
+                }
+
+                _ = bar_prune.tick(), if store_bars => {
+                    if let Err(e) = self.prune_bars().await {
+                        warn!("Sub-minute bar retention failed: {}", e);
+                    }
+                }
+
+                // Process streamed events
+                event = exchange.next_event() => {
                     match event {
                         Ok(Some(evt)) => {
                             if let Err(e) = self.process_event(evt).await {
@@ -153,11 +254,11 @@ impl MarketDataCollector {
                             }
                         }
                         Ok(None) => {
-                            warn!("WebSocket stream ended");
+                            warn!("{} stream ended", exchange.name());
                             break;
                         }
                         Err(e) => {
-                            error!("WebSocket error: {}", e);
+                            error!("{} stream error: {}", exchange.name(), e);
                             break;
                         }
                     }
@@ -169,26 +270,29 @@ impl MarketDataCollector {
         Ok(())
     }
 
-    /// Process WebSocket event
-    async fn process_event(&self, event: WebSocketEvent) -> Result<()> {
+    /// Process a streamed event
+    async fn process_event(&self, event: MarketEvent) -> Result<()> {
         match event {
-            WebSocketEvent::Ticker(ticker) => {
+            MarketEvent::Ticker(ticker) => {
                 self.process_ticker(ticker).await?;
             }
-            WebSocketEvent::Candle(candle) => {
+            MarketEvent::Candle(candle) => {
                 self.process_candle(candle).await?;
             }
-            WebSocketEvent::Trade(trade) => {
+            MarketEvent::Trade(trade) => {
                 self.process_trade(trade).await?;
             }
-            WebSocketEvent::Subscribe(resp) => {
-                info!("Subscription confirmed: {:?}", resp.arg);
+            MarketEvent::FundingRate(funding) => {
+                self.process_funding_rate(funding).await?;
             }
-            WebSocketEvent::Error { code, msg } => {
-                error!("WebSocket error - Code: {}, Message: {}", code, msg);
+            MarketEvent::Gap { stream, symbol } => {
+                warn!("Gap on {} {:?}, resync requested", stream, symbol);
+                if let Some(symbol) = symbol {
+                    self.quality_control.record_gap(&symbol);
+                }
             }
-            _ => {
-                // Ignore other events
+            MarketEvent::Order(_) => {
+                // Order updates belong to the trading engine
             }
         }
 
@@ -196,15 +300,10 @@ impl MarketDataCollector {
     }
 
     /// Process ticker data
-    async fn process_ticker(&self, ticker: TickerData) -> Result<()> {
-        let symbol = Symbol::new(&ticker.inst_id)?;
-        let price = Price::new(
-            ticker
-                .last
-                .parse()
-                .map_err(|e| Error::ParseError(format!("{}", e)))?,
-        )?;
-        let timestamp = Utc::now(); // Use current time since ticker doesn't have exact timestamp
+    async fn process_ticker(&self, ticker: TickerUpdate) -> Result<()> {
+        let symbol = ticker.symbol;
+        let price = Price::new(ticker.last)?;
+        let timestamp = ticker.timestamp;
 
         // Quality control
         if let Err(e) = self
@@ -215,25 +314,30 @@ impl MarketDataCollector {
             return Ok(()); // Don't propagate QC errors
         }
 
-        info!("Ticker {} - Last: {}", ticker.inst_id, ticker.last);
+        if let Some(cache) = &self.price_cache
+            && let Err(e) = cache.put_price(&symbol, price).await
+        {
+            warn!("Failed to cache price of {}: {}", symbol.as_str(), e);
+        }
+
+        info!("Ticker {} - Last: {}", symbol.as_str(), ticker.last);
         Ok(())
     }
 
     /// Process candle data
-    async fn process_candle(&self, candle_data: CandleData) -> Result<()> {
-        let parsed = candle_data
-            .parse()
-            .map_err(|e| Error::ParseError(format!("{}", e)))?;
-
+    async fn process_candle(&self, parsed: CandleUpdate) -> Result<()> {
         // Skip unconfirmed candles
-        if !parsed.is_confirmed {
+        if !parsed.closed {
             return Ok(());
         }
 
         let price = Price::new(parsed.close)?;
-        let symbol = Symbol::new("UNKNOWN")?; // Need to track symbol from subscription
-        let timestamp = chrono::DateTime::from_timestamp_millis(parsed.timestamp)
-            .ok_or_else(|| Error::ParseError("Invalid timestamp".to_string()))?;
+        let symbol = match parsed.symbol {
+            Some(symbol) => symbol,
+            None => Symbol::new("UNKNOWN")?, // Need to track symbol from subscription
+        };
+        let timestamp = parsed.open_time;
+        let interval = parsed.interval;
 
         // Quality control
         if let Err(e) = self
@@ -249,7 +353,7 @@ impl MarketDataCollector {
             let candle = Candle {
                 symbol: symbol.clone(),
                 timestamp,
-                interval: "1m".to_string(),
+                interval,
                 open: Price::new(parsed.open)?,
                 high: Price::new(parsed.high)?,
                 low: Price::new(parsed.low)?,
@@ -260,6 +364,16 @@ impl MarketDataCollector {
                 vwap: None,
             };
             ts.store_candle(&candle).await?;
+
+            // The day's last candle completes it: checksum the day so later
+            // changes to it show up in integrity checks
+            if let Some(length) = interval.duration()
+                && (timestamp + length).date_naive() != timestamp.date_naive()
+            {
+                let day = day_start(timestamp.date_naive());
+                ts.record_candle_checksums(&symbol, interval, day, day + Duration::days(1))
+                    .await?;
+            }
         }
 
         info!(
@@ -270,21 +384,10 @@ impl MarketDataCollector {
     }
 
     /// Process trade data
-    async fn process_trade(&self, trade: TradeData) -> Result<()> {
-        let symbol = Symbol::new(&trade.inst_id)?;
-        let price = Price::new(
-            trade
-                .px
-                .parse()
-                .map_err(|e| Error::ParseError(format!("{}", e)))?,
-        )?;
-        let timestamp = chrono::DateTime::from_timestamp_millis(
-            trade
-                .ts
-                .parse()
-                .map_err(|e| Error::ParseError(format!("{}", e)))?,
-        )
-        .ok_or_else(|| Error::ParseError("Invalid timestamp".to_string()))?;
+    async fn process_trade(&self, trade: TradeTick) -> Result<()> {
+        let symbol = trade.symbol;
+        let price = Price::new(trade.price)?;
+        let timestamp = trade.timestamp;
 
         // Quality control
         if let Err(e) = self.quality_control.validate_market_data(
@@ -297,6 +400,8 @@ impl MarketDataCollector {
             return Ok(());
         }
 
+        let quantity = Quantity::new(trade.quantity)?;
+
         // Store tick
         if let Some(ts) = &self.timescale {
             let tick = Tick {
@@ -304,31 +409,150 @@ impl MarketDataCollector {
                 timestamp,
                 trade_id: trade.trade_id,
                 price,
-                quantity: Quantity::new(
-                    trade
-                        .sz
-                        .parse()
-                        .map_err(|e| Error::ParseError(format!("{}", e)))?,
-                )?,
-                side: trade.side,
+                quantity,
+                side: match trade.side {
+                    OrderSide::Buy => "buy",
+                    OrderSide::Sell => "sell",
+                }
+                .to_string(),
                 is_block_trade: false,
             };
             ts.store_tick(&tick).await?;
         }
 
+        if let Some(bars) = &self.bars {
+            let closed = bars.lock().on_trade(&symbol, price, quantity, timestamp)?;
+            self.emit_bars(closed).await?;
+        }
+
         Ok(())
     }
 
+    /// Close sub-minute bars whose interval ended by `now`
+    async fn flush_bars(&self, now: chrono::DateTime<Utc>) -> Result<()> {
+        if let Some(bars) = &self.bars {
+            let closed = bars.lock().flush(now)?;
+            self.emit_bars(closed).await?;
+        }
+        Ok(())
+    }
+
+    /// Publish completed sub-minute bars, storing them if configured
+    async fn emit_bars(&self, bars: Vec<Candle>) -> Result<()> {
+        let store = self
+            .config
+            .sub_minute_bars
+            .as_ref()
+            .is_some_and(|c| c.store);
+        for bar in bars {
+            if store && let Some(ts) = &self.timescale {
+                ts.store_candle(&bar).await?;
+            }
+            let _ = self.bar_tx.send(bar);
+        }
+        Ok(())
+    }
+
+    /// Delete stored sub-minute bars past their retention window
+    async fn prune_bars(&self) -> Result<()> {
+        let (Some(config), Some(ts)) = (&self.config.sub_minute_bars, &self.timescale) else {
+            return Ok(());
+        };
+        let before = Utc::now() - config.retention();
+        for interval in &config.intervals {
+            let removed = ts.prune_candles(*interval, before).await?;
+            if removed > 0 {
+                info!("Pruned {} stored {} bars", removed, interval);
+            }
+        }
+        Ok(())
+    }
+
+    /// Process funding rate data
+    async fn process_funding_rate(&self, funding: FundingUpdate) -> Result<()> {
+        let funding = FundingRate::new(
+            funding.symbol.as_str().to_string(),
+            funding.funding_time,
+            funding.rate,
+            funding.next_rate,
+        )?;
+
+        if let Some(ts) = &self.timescale {
+            ts.store_funding_rate(&funding).await?;
+        }
+
+        info!(
+            "Funding {} - Rate: {} (annualized {})",
+            funding.inst_id,
+            funding.funding_rate,
+            funding.annualized(FundingRate::DEFAULT_INTERVAL_HOURS)
+        );
+        Ok(())
+    }
+
+    /// Replay a raw OKX capture (segment file or directory) through the parser
+    /// and the normal processing pipeline
+    pub async fn replay_capture(&self, path: impl AsRef<Path>) -> Result<ReplaySummary> {
+        let path = path.as_ref().to_path_buf();
+        let replayed = tokio::task::spawn_blocking(move || replay_capture(path))
+            .await
+            .map_err(|e| Error::Internal(format!("Replay task failed: {}", e)))??;
+
+        let mut summary = ReplaySummary {
+            frames: replayed.len(),
+            ..Default::default()
+        };
+
+        for replayed_frame in replayed {
+            match replayed_frame.event {
+                Ok(event) => {
+                    summary.parsed += 1;
+                    let processed = match market_event(event) {
+                        Ok(Some(event)) => self.process_event(event).await,
+                        Ok(None) => Ok(()),
+                        Err(e) => Err(e.into()),
+                    };
+                    if let Err(e) = processed {
+                        summary.processing_failures += 1;
+                        warn!("Replayed frame failed processing: {}", e);
+                    }
+                }
+                Err(e) => {
+                    summary.parse_failures += 1;
+                    warn!(
+                        "Replayed frame from {} failed to parse: {} ({})",
+                        replayed_frame.frame.received_at, e, replayed_frame.frame.payload
+                    );
+                }
+            }
+        }
+
+        info!(
+            "Replayed {} frames: {} parsed, {} parse failures",
+            summary.frames, summary.parsed, summary.parse_failures
+        );
+        Ok(summary)
+    }
+
     /// Stop the collector
     pub async fn stop(&mut self) -> Result<()> {
         if let Some(tx) = self.shutdown_tx.take() {
             let _ = tx.send(()).await;
         }
 
-        if let Some(ws) = self.ws_client.as_ref() {
-            ws.disconnect()
-                .await
-                .map_err(|e| Error::WebSocketError(e))?;
+        if let Some(exchange) = self.exchange.as_ref() {
+            exchange.disconnect().await?;
+        }
+
+        // Dropping the adapter closes the raw frame channel, which lets the
+        // recorder flush and close its segment
+        self.exchange = None;
+        if let Some(recorder) = self.recorder.take() {
+            match recorder.await {
+                Ok(Ok(frames)) => info!("Raw feed recorder stopped after {} frames", frames),
+                Ok(Err(e)) => error!("Raw feed recorder failed: {}", e),
+                Err(e) => error!("Raw feed recorder task failed: {}", e),
+            }
         }
 
         Ok(())
@@ -338,6 +562,11 @@ impl MarketDataCollector {
     pub fn get_stats(&self) -> crate::quality::QualityStats {
         self.quality_control.get_stats()
     }
+
+    /// Shared quality control, for reading per-symbol quality scores
+    pub fn quality_control(&self) -> Arc<QualityControl> {
+        self.quality_control.clone()
+    }
 }
 
 #[cfg(test)]
@@ -351,10 +580,83 @@ mod tests {
         assert!(config.symbols.contains(&"BTC-USDT".to_string()));
     }
 
+    #[tokio::test]
+    async fn test_replay_capture_counts_parse_failures() {
+        use crate::recorder::RawFeedRecorder;
+        use ea_okx_client::websocket::{FrameSource, RawFrame};
+
+        let dir = std::env::temp_dir().join(format!("collector-replay-{}", uuid::Uuid::new_v4()));
+        let mut recorder = RawFeedRecorder::new(RawFeedConfig::new(&dir)).unwrap();
+        for payload in [
+            r#"{"event":"subscribe","arg":{"channel":"tickers","instId":"BTC-USDT"}}"#,
+            r#"{"arg":{"channel":"unknown-channel"},"data":{}}"#,
+        ] {
+            recorder
+                .record(&RawFrame {
+                    received_at: Utc::now(),
+                    source: FrameSource::Public,
+                    payload: payload.to_string(),
+                })
+                .unwrap();
+        }
+        recorder.finish().unwrap();
+
+        let collector = MarketDataCollector::new(CollectorConfig::default());
+        let summary = collector.replay_capture(&dir).await.unwrap();
+        assert_eq!(summary.frames, 2);
+        assert_eq!(summary.parsed, 1);
+        assert_eq!(summary.parse_failures, 1);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_trades_emit_sub_minute_bars() {
+        let collector = MarketDataCollector::new(CollectorConfig {
+            sub_minute_bars: Some(BarConfig {
+                intervals: vec![Interval::OneSecond],
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        let mut bars = collector.subscribe_bars().unwrap();
+        assert!(collector.subscribe_bars().is_none());
+
+        // Recent enough to pass the staleness check
+        let second = Utc::now().timestamp_millis() / 1000 * 1000 - 3000;
+        for (trade_id, px, ts) in [("1", "100", second + 100), ("2", "101", second + 1100)] {
+            collector
+                .process_trade(TradeTick {
+                    symbol: Symbol::new("BTC-USDT").unwrap(),
+                    trade_id: trade_id.to_string(),
+                    price: px.parse().unwrap(),
+                    quantity: rust_decimal::Decimal::new(5, 1),
+                    side: OrderSide::Buy,
+                    timestamp: chrono::DateTime::from_timestamp_millis(ts).unwrap(),
+                })
+                .await
+                .unwrap();
+        }
+
+        let bar = bars.try_recv().unwrap();
+        assert_eq!(bar.interval, Interval::OneSecond);
+        assert_eq!(bar.close.as_decimal(), rust_decimal::Decimal::from(100));
+        assert!(bars.try_recv().is_err());
+
+        collector
+            .flush_bars(chrono::DateTime::from_timestamp_millis(second + 2000).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(
+            bars.try_recv().unwrap().close.as_decimal(),
+            rust_decimal::Decimal::from(101)
+        );
+    }
+
     #[test]
     fn test_collector_creation() {
         let config = CollectorConfig::default();
         let collector = MarketDataCollector::new(config);
-        assert!(collector.ws_client.is_none());
+        assert!(collector.exchange.is_none());
     }
 }
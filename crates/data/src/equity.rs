@@ -0,0 +1,211 @@
+//! Live account equity curve
+//!
+//! [`EquityRecorder`] samples total equity, per-currency equity and
+//! per-symbol exposure from an [`EquitySource`] once a minute and stores
+//! them in the `account_equity` hypertable. Raw samples are kept for
+//! [`RAW_RETENTION_DAYS`]; the `account_equity_1h` continuous aggregate keeps
+//! the hourly curve for years, and serves any query that asks for hourly or
+//! coarser points or reaches back past the raw retention window.
+
+use crate::error::Result;
+use crate::storage::TimescaleStorage;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use ea_okx_core::models::{Position, PositionSide};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+/// Days raw samples stay in `account_equity` (matches its retention policy)
+pub const RAW_RETENTION_DAYS: i64 = 90;
+
+/// Account equity, balances and exposure at one instant
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EquitySnapshot {
+    pub timestamp: DateTime<Utc>,
+
+    /// Total account equity in USD
+    pub total_equity: Decimal,
+
+    /// Account margin ratio (cross margin only)
+    pub margin_ratio: Option<Decimal>,
+
+    /// Equity per currency
+    pub balances: BTreeMap<String, Decimal>,
+
+    /// Signed position notional per symbol, negative when short
+    pub exposures: BTreeMap<String, Decimal>,
+
+    /// Sum of absolute exposures
+    pub gross_exposure: Decimal,
+
+    /// Sum of signed exposures
+    pub net_exposure: Decimal,
+}
+
+impl EquitySnapshot {
+    /// Snapshot without positions
+    pub fn new(
+        timestamp: DateTime<Utc>,
+        total_equity: Decimal,
+        margin_ratio: Option<Decimal>,
+        balances: BTreeMap<String, Decimal>,
+    ) -> Self {
+        Self {
+            timestamp,
+            total_equity,
+            margin_ratio,
+            balances,
+            exposures: BTreeMap::new(),
+            gross_exposure: Decimal::ZERO,
+            net_exposure: Decimal::ZERO,
+        }
+    }
+
+    /// Add the exposure of `positions`, valued at their current price
+    pub fn with_positions(mut self, positions: &[Position]) -> Self {
+        for position in positions {
+            let notional = position.quantity.as_decimal() * position.current_price.as_decimal();
+            let signed = match position.side {
+                PositionSide::Short => -notional,
+                PositionSide::Long | PositionSide::Net => notional,
+            };
+            *self
+                .exposures
+                .entry(position.symbol.as_str().to_string())
+                .or_default() += signed;
+        }
+        self.gross_exposure = self.exposures.values().map(|e| e.abs()).sum();
+        self.net_exposure = self.exposures.values().copied().sum();
+        self
+    }
+}
+
+/// Table holding the curve for a query starting at `start`
+///
+/// Hourly or coarser buckets, and ranges older than the raw retention
+/// window, are read from the hourly aggregate.
+pub fn equity_table(
+    start: DateTime<Utc>,
+    bucket: Option<Duration>,
+    now: DateTime<Utc>,
+) -> &'static str {
+    let coarse = bucket.is_some_and(|bucket| bucket >= Duration::hours(1));
+    if coarse || start < now - Duration::days(RAW_RETENTION_DAYS) {
+        "account_equity_1h"
+    } else {
+        "account_equity"
+    }
+}
+
+/// Where live account state is read from
+#[async_trait]
+pub trait EquitySource: Send + Sync {
+    /// Current account state, or `None` while there is nothing to record
+    async fn sample(&self, now: DateTime<Utc>) -> Result<Option<EquitySnapshot>>;
+}
+
+/// Periodically stores live equity samples
+pub struct EquityRecorder {
+    source: Arc<dyn EquitySource>,
+    storage: Arc<TimescaleStorage>,
+    interval: std::time::Duration,
+}
+
+impl EquityRecorder {
+    pub fn new(source: Arc<dyn EquitySource>, storage: Arc<TimescaleStorage>) -> Self {
+        Self {
+            source,
+            storage,
+            interval: std::time::Duration::from_secs(60),
+        }
+    }
+
+    pub fn with_interval(mut self, interval: std::time::Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sample and store once; returns what was stored
+    pub async fn record(&self, now: DateTime<Utc>) -> Result<Option<EquitySnapshot>> {
+        let Some(snapshot) = self.source.sample(now).await? else {
+            return Ok(None);
+        };
+        self.storage.store_equity_snapshot(&snapshot).await?;
+        Ok(Some(snapshot))
+    }
+
+    /// Record every interval until the task is aborted
+    pub fn spawn(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self.interval);
+            loop {
+                ticker.tick().await;
+                match self.record(Utc::now()).await {
+                    Ok(Some(snapshot)) => {
+                        debug!("Recorded account equity {}", snapshot.total_equity)
+                    }
+                    Ok(None) => {}
+                    Err(e) => warn!("Failed to record account equity: {}", e),
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use ea_okx_core::{Price, Quantity, Symbol};
+    use rust_decimal_macros::dec;
+    use uuid::Uuid;
+
+    fn position(symbol: &str, side: PositionSide, quantity: Decimal, price: Decimal) -> Position {
+        let mut position = Position::new(
+            Uuid::new_v4(),
+            Symbol::new(symbol).unwrap(),
+            side,
+            Quantity::new(quantity).unwrap(),
+            Price::new(price).unwrap(),
+        );
+        position.current_price = Price::new(price).unwrap();
+        position
+    }
+
+    #[test]
+    fn test_exposure_from_positions() {
+        let snapshot = EquitySnapshot::new(Utc::now(), dec!(10000), None, BTreeMap::new())
+            .with_positions(&[
+                position("BTC-USDT", PositionSide::Long, dec!(0.1), dec!(50000)),
+                position("BTC-USDT", PositionSide::Short, dec!(0.02), dec!(50000)),
+                position("ETH-USDT", PositionSide::Short, dec!(1), dec!(3000)),
+            ]);
+
+        assert_eq!(snapshot.exposures["BTC-USDT"], dec!(4000));
+        assert_eq!(snapshot.exposures["ETH-USDT"], dec!(-3000));
+        assert_eq!(snapshot.gross_exposure, dec!(7000));
+        assert_eq!(snapshot.net_exposure, dec!(1000));
+    }
+
+    #[test]
+    fn test_coarse_or_old_ranges_read_the_hourly_aggregate() {
+        let now = Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap();
+        let recent = now - Duration::days(7);
+        let old = now - Duration::days(RAW_RETENTION_DAYS + 1);
+
+        assert_eq!(equity_table(recent, None, now), "account_equity");
+        assert_eq!(
+            equity_table(recent, Some(Duration::minutes(1)), now),
+            "account_equity"
+        );
+        assert_eq!(
+            equity_table(recent, Some(Duration::hours(1)), now),
+            "account_equity_1h"
+        );
+        assert_eq!(equity_table(old, None, now), "account_equity_1h");
+    }
+}
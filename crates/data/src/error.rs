@@ -31,6 +31,9 @@ pub enum Error {
     #[error("Parse error: {0}")]
     ParseError(String),
 
+    #[error("Compression error: {0}")]
+    CompressionError(String),
+
     #[error("Serialization error: {0}")]
     SerializationError(#[from] serde_json::Error),
 
@@ -0,0 +1,355 @@
+//! Candle data integrity checksums
+//!
+//! After a backfill, a SHA-256 checksum is recorded for each UTC day of each
+//! symbol's candles. Verification recomputes those checksums from what is
+//! stored now and reports days that no longer match, so silently corrupted
+//! or truncated history is caught before it feeds a backtest. Mismatched days
+//! can be re-downloaded through a [`CandleDownloader`].
+
+use crate::error::{Error, Result};
+use crate::storage::Candle;
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use ea_okx_client::OkxRestClient;
+use ea_okx_client::models::CandleData;
+use ea_okx_core::Interval;
+use ea_okx_core::types::{Price, Quantity, Symbol};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+/// Checksum over one UTC day of candles for a symbol and interval
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DayChecksum {
+    pub symbol: Symbol,
+    pub interval: Interval,
+    pub day: NaiveDate,
+    pub candle_count: usize,
+    /// Hex-encoded SHA-256
+    pub checksum: String,
+}
+
+/// How a day's data disagrees with its recorded checksum
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MismatchKind {
+    /// Candles differ from when the checksum was recorded
+    Changed,
+    /// A checksum was recorded but no candles remain
+    Missing,
+    /// Candles exist but no checksum was ever recorded
+    Unrecorded,
+}
+
+/// One day that failed verification
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntegrityMismatch {
+    pub day: NaiveDate,
+    pub kind: MismatchKind,
+    pub expected_count: Option<usize>,
+    pub actual_count: usize,
+    pub expected_checksum: Option<String>,
+    pub actual_checksum: Option<String>,
+}
+
+/// Result of verifying a symbol's candles over a range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    pub symbol: Symbol,
+    pub interval: Interval,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub days_checked: usize,
+    pub mismatches: Vec<IntegrityMismatch>,
+    /// Days replaced by a fresh download
+    pub redownloaded: Vec<NaiveDate>,
+}
+
+impl IntegrityReport {
+    /// Whether every day matched its recorded checksum
+    pub fn is_clean(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+/// Fetches candles from the exchange for targeted re-downloads
+#[async_trait]
+pub trait CandleDownloader: Send + Sync {
+    async fn download_candles(
+        &self,
+        symbol: &Symbol,
+        interval: Interval,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<Candle>>;
+}
+
+/// Re-downloads walk OKX history candles back from `end`
+#[async_trait]
+impl CandleDownloader for OkxRestClient {
+    async fn download_candles(
+        &self,
+        symbol: &Symbol,
+        interval: Interval,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<Candle>> {
+        let mut pages = self
+            .history_candles(symbol.as_str(), interval)?
+            .with_limit(100)
+            .starting_at(end.timestamp_millis().to_string());
+
+        // Pages are newest first; stop at the first page reaching past `start`
+        let mut candles = Vec::new();
+        while let Some(page) = pages.next_page().await? {
+            let mut reached_start = false;
+            for bar in page {
+                let candle = history_candle(symbol, interval, &bar.0)?;
+                if candle.timestamp < start {
+                    reached_start = true;
+                } else if candle.timestamp < end {
+                    candles.push(candle);
+                }
+            }
+            if reached_start {
+                break;
+            }
+        }
+
+        candles.sort_by_key(|c| c.timestamp);
+        Ok(candles)
+    }
+}
+
+/// Stored form of an OKX history candle
+fn history_candle(symbol: &Symbol, interval: Interval, bar: &CandleData) -> Result<Candle> {
+    let parsed = bar.parse()?;
+    let timestamp = DateTime::from_timestamp_millis(parsed.timestamp).ok_or_else(|| {
+        Error::ParseError(format!("Invalid candle timestamp '{}'", bar.timestamp))
+    })?;
+
+    Ok(Candle {
+        symbol: symbol.clone(),
+        timestamp,
+        interval,
+        open: Price::new(parsed.open)?,
+        high: Price::new(parsed.high)?,
+        low: Price::new(parsed.low)?,
+        close: Price::new(parsed.close)?,
+        volume: Quantity::new(parsed.volume)?,
+        quote_volume: bar.volume_currency.parse().unwrap_or(Decimal::ZERO),
+        trade_count: 0,
+        vwap: None,
+    })
+}
+
+/// Expand `[start, end)` to whole UTC days so no day is checksummed partially
+pub fn whole_days(start: DateTime<Utc>, end: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>) {
+    let first = start.date_naive();
+    let mut last = end.date_naive();
+    if end.time() != NaiveTime::MIN || last == first {
+        last = last.succ_opt().unwrap_or(last);
+    }
+    (day_start(first), day_start(last))
+}
+
+/// Midnight UTC of `day`
+pub fn day_start(day: NaiveDate) -> DateTime<Utc> {
+    day.and_time(NaiveTime::MIN).and_utc()
+}
+
+/// Checksum of a run of candles, independent of input order and of trailing
+/// zeros in decimal values
+pub fn candle_checksum(candles: &[Candle]) -> String {
+    let mut sorted: Vec<&Candle> = candles.iter().collect();
+    sorted.sort_by_key(|c| c.timestamp);
+
+    let mut hasher = Sha256::new();
+    for c in sorted {
+        let line = format!(
+            "{}|{}|{}|{}|{}|{}|{}|{}\n",
+            c.timestamp.timestamp_millis(),
+            c.open.as_decimal().normalize(),
+            c.high.as_decimal().normalize(),
+            c.low.as_decimal().normalize(),
+            c.close.as_decimal().normalize(),
+            c.volume.as_decimal().normalize(),
+            c.quote_volume.normalize(),
+            c.trade_count
+        );
+        hasher.update(line.as_bytes());
+    }
+
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Per-day checksums of `candles`, which must share a symbol and interval
+pub fn daily_checksums(
+    symbol: &Symbol,
+    interval: Interval,
+    candles: &[Candle],
+) -> Vec<DayChecksum> {
+    let mut days: BTreeMap<NaiveDate, Vec<Candle>> = BTreeMap::new();
+    for candle in candles {
+        days.entry(candle.timestamp.date_naive())
+            .or_default()
+            .push(candle.clone());
+    }
+
+    days.into_iter()
+        .map(|(day, candles)| DayChecksum {
+            symbol: symbol.clone(),
+            interval,
+            day,
+            candle_count: candles.len(),
+            checksum: candle_checksum(&candles),
+        })
+        .collect()
+}
+
+/// Compare recorded checksums with ones recomputed from current data
+pub fn compare_checksums(
+    recorded: &[DayChecksum],
+    recomputed: &[DayChecksum],
+) -> Vec<IntegrityMismatch> {
+    let recorded: BTreeMap<NaiveDate, &DayChecksum> = recorded.iter().map(|c| (c.day, c)).collect();
+    let recomputed: BTreeMap<NaiveDate, &DayChecksum> =
+        recomputed.iter().map(|c| (c.day, c)).collect();
+
+    let mut days: Vec<NaiveDate> = recorded.keys().chain(recomputed.keys()).copied().collect();
+    days.sort();
+    days.dedup();
+
+    days.into_iter()
+        .filter_map(|day| {
+            let expected = recorded.get(&day);
+            let actual = recomputed.get(&day);
+            let kind = match (expected, actual) {
+                (Some(e), Some(a)) if e.checksum == a.checksum => return None,
+                (Some(_), Some(_)) => MismatchKind::Changed,
+                (Some(_), None) => MismatchKind::Missing,
+                (None, Some(_)) => MismatchKind::Unrecorded,
+                (None, None) => return None,
+            };
+            Some(IntegrityMismatch {
+                day,
+                kind,
+                expected_count: expected.map(|e| e.candle_count),
+                actual_count: actual.map_or(0, |a| a.candle_count),
+                expected_checksum: expected.map(|e| e.checksum.clone()),
+                actual_checksum: actual.map(|a| a.checksum.clone()),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use rust_decimal_macros::dec;
+
+    fn candle(hours: i64, close: Decimal) -> Candle {
+        Candle {
+            symbol: Symbol::new("BTC-USDT").unwrap(),
+            timestamp: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::hours(hours),
+            interval: Interval::OneHour,
+            open: Price::new(dec!(100)).unwrap(),
+            high: Price::new(dec!(110)).unwrap(),
+            low: Price::new(dec!(90)).unwrap(),
+            close: Price::new(close).unwrap(),
+            volume: Quantity::new(dec!(5)).unwrap(),
+            quote_volume: dec!(500),
+            trade_count: 10,
+            vwap: None,
+        }
+    }
+
+    #[test]
+    fn test_checksum_ignores_order_and_scale() {
+        let a = vec![candle(0, dec!(105)), candle(1, dec!(106))];
+        let b = vec![candle(1, dec!(106.00)), candle(0, dec!(105.0))];
+        assert_eq!(candle_checksum(&a), candle_checksum(&b));
+        assert_ne!(
+            candle_checksum(&a),
+            candle_checksum(&[candle(0, dec!(105)), candle(1, dec!(107))])
+        );
+    }
+
+    #[test]
+    fn test_history_candle_is_converted() {
+        let symbol = Symbol::new("BTC-USDT").unwrap();
+        let bar = CandleData {
+            timestamp: "1704067200000".to_string(),
+            open: "42000.1".to_string(),
+            high: "42100".to_string(),
+            low: "41900".to_string(),
+            close: "42050".to_string(),
+            volume: "12.5".to_string(),
+            volume_currency: "525000".to_string(),
+            volume_usd: None,
+            confirm: "1".to_string(),
+        };
+
+        let candle = history_candle(&symbol, Interval::OneHour, &bar).unwrap();
+        assert_eq!(
+            candle.timestamp,
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        );
+        assert_eq!(candle.open.as_decimal(), dec!(42000.1));
+        assert_eq!(candle.volume.as_decimal(), dec!(12.5));
+        assert_eq!(candle.quote_volume, dec!(525000));
+
+        let bad = CandleData {
+            close: "n/a".to_string(),
+            ..bar
+        };
+        assert!(history_candle(&symbol, Interval::OneHour, &bad).is_err());
+    }
+
+    #[test]
+    fn test_whole_days_rounds_out_to_midnight() {
+        let at = |d, h| Utc.with_ymd_and_hms(2024, 1, d, h, 0, 0).unwrap();
+        assert_eq!(whole_days(at(1, 6), at(2, 12)), (at(1, 0), at(3, 0)));
+        assert_eq!(whole_days(at(1, 0), at(3, 0)), (at(1, 0), at(3, 0)));
+        assert_eq!(whole_days(at(1, 0), at(1, 0)), (at(1, 0), at(2, 0)));
+    }
+
+    #[test]
+    fn test_compare_reports_changed_missing_and_unrecorded_days() {
+        let symbol = Symbol::new("BTC-USDT").unwrap();
+        // Two days recorded: Jan 1 and Jan 2
+        let original = vec![
+            candle(0, dec!(105)),
+            candle(1, dec!(106)),
+            candle(24, dec!(107)),
+        ];
+        let recorded = daily_checksums(&symbol, Interval::OneHour, &original);
+        assert_eq!(recorded.len(), 2);
+        assert!(compare_checksums(&recorded, &recorded).is_empty());
+
+        // Jan 1 loses a candle, Jan 2 is gone, Jan 3 appears
+        let current = vec![candle(0, dec!(105)), candle(48, dec!(108))];
+        let mismatches = compare_checksums(
+            &recorded,
+            &daily_checksums(&symbol, Interval::OneHour, &current),
+        );
+
+        let kinds: Vec<_> = mismatches.iter().map(|m| m.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                MismatchKind::Changed,
+                MismatchKind::Missing,
+                MismatchKind::Unrecorded
+            ]
+        );
+        assert_eq!(mismatches[0].expected_count, Some(2));
+        assert_eq!(mismatches[0].actual_count, 1);
+    }
+}
@@ -6,16 +6,71 @@
 //! # Features
 //!
 //! - Market data collection from WebSocket streams
-//! - Real-time data quality validation
+//! - Real-time data quality validation with rolling per-symbol scores
 //! - Deduplication and anomaly detection
 //! - TimescaleDB and Redis integration
+//! - Strategy persistence on SQLite or PostgreSQL
 //! - Automatic data enrichment
+//! - Raw feed recording and replay
+//! - Compressed order book storage with tiered retention and sampled
+//!   column-wise reads for research
+//! - Tick hypertable chunking, compression and per-symbol retention
+//! - Per-day candle checksums with integrity verification
+//! - Two-tier (local LRU and Redis) latest price cache with hit/miss metrics
+//! - Blended multi-source reference prices with outlier rejection
+//! - Sub-minute candles aggregated from trades
+//! - On-the-fly resampling of stored candles into longer intervals
+//! - Live account equity curve recording
+//! - Open interest, taker volume and long/short ratio collection
+//! - Embedded, versioned TimescaleDB schema migrations
 
+pub mod bars;
 pub mod collector;
+pub mod equity;
 pub mod error;
+pub mod integrity;
+pub mod orderbook;
+pub mod positioning;
+pub mod price_cache;
 pub mod quality;
+pub mod recorder;
+pub mod reference;
+pub mod resample;
+pub mod schema;
 pub mod storage;
+pub mod strategy_store;
+pub mod ticks;
 
-pub use collector::MarketDataCollector;
+pub use bars::{BarAggregator, BarConfig};
+pub use collector::{MarketDataCollector, ReplaySummary};
+pub use equity::{EquityRecorder, EquitySnapshot, EquitySource};
 pub use error::{Error, Result};
-pub use quality::QualityControl;
+pub use integrity::{
+    CandleDownloader, DayChecksum, IntegrityMismatch, IntegrityReport, MismatchKind,
+};
+pub use orderbook::{
+    OrderBookBlock, OrderBookBlockBuilder, OrderBookColumns, OrderBookQuery,
+    OrderBookStorageConfig, RetentionAction, RetentionPolicy, VacuumReport,
+};
+pub use positioning::{
+    LongShortRatio, OkxPositioningSource, OpenInterest, PositioningCollector, PositioningConfig,
+    PositioningSource, PositioningStat, TakerVolume,
+};
+pub use price_cache::{
+    LruPriceCache, PriceCache, PriceCacheConfig, PriceCacheStats, TieredPriceCache,
+};
+pub use quality::{QualityControl, QualityWindowStats, SymbolQuality};
+pub use recorder::{RawFeedConfig, RawFeedRecorder, ReplayedFrame, load_capture, replay_capture};
+pub use reference::{
+    HttpTickerSource, OkxPriceKind, OkxPriceSource, PriceSource, ReferenceConfig, ReferencePrice,
+    ReferencePriceService, RejectedQuote, SourceQuote,
+};
+pub use resample::{can_resample, resample_candles};
+pub use schema::{MigrationReport, SchemaMigrator, SchemaVersion};
+pub use strategy_store::{
+    InMemoryStrategyRepository, SqlStrategyRepository, StrategyRepository, StrategyStatusChange,
+};
+pub use ticks::{
+    TickDeleteScope, TickMaintenanceReport, TickMaintenanceState, TickMaintenanceStatus,
+    TickRetentionPlan, TickStorageConfig,
+};
@@ -0,0 +1,723 @@
+//! Compressed order book storage
+//!
+//! Storing every `books50` snapshot as its own JSONB row costs several
+//! kilobytes per update. Snapshots are instead grouped into per-symbol blocks:
+//! the first snapshot of a block is stored in full and every later one only as
+//! the levels that changed since its predecessor. The encoded block is
+//! zstd-compressed, and each block decodes on its own.
+//!
+//! Retention is tiered per symbol. Blocks keep full depth for a while, are then
+//! cut down to the top few levels, and are finally deleted (see
+//! [`RetentionPolicy`]).
+//!
+//! For research and replay, stored history is read back as
+//! [`OrderBookColumns`]: optionally one snapshot per N seconds and only the
+//! best levels, laid out column by column instead of one object per level.
+
+use crate::error::{Error, Result};
+use crate::storage::OrderBookSnapshot;
+use chrono::{DateTime, Duration, Utc};
+use ea_okx_core::types::{Price, Quantity, Symbol};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Tiered retention for one symbol's order book blocks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    /// Days a block keeps every level
+    pub full_depth_days: i64,
+
+    /// Levels per side kept once the full-depth window has passed
+    pub reduced_depth: usize,
+
+    /// Days a block is kept at all; older blocks are deleted
+    pub reduced_depth_days: i64,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            full_depth_days: 7,
+            reduced_depth: 5,
+            reduced_depth_days: 90,
+        }
+    }
+}
+
+/// What the vacuum job should do with a block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetentionAction {
+    Keep,
+    /// Re-encode the block keeping this many levels per side
+    Truncate(usize),
+    Delete,
+}
+
+impl RetentionPolicy {
+    /// Action for a block whose newest snapshot is at `end_time`
+    ///
+    /// `max_depth` is the depth the block was already cut to, if any.
+    pub fn action(
+        &self,
+        end_time: DateTime<Utc>,
+        max_depth: Option<usize>,
+        now: DateTime<Utc>,
+    ) -> RetentionAction {
+        let age = now - end_time;
+        if age >= Duration::days(self.reduced_depth_days) {
+            RetentionAction::Delete
+        } else if age >= Duration::days(self.full_depth_days)
+            && max_depth.is_none_or(|depth| depth > self.reduced_depth)
+        {
+            RetentionAction::Truncate(self.reduced_depth)
+        } else {
+            RetentionAction::Keep
+        }
+    }
+}
+
+/// Order book storage configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderBookStorageConfig {
+    /// Snapshots per block; larger blocks compress better but are coarser to
+    /// query and vacuum
+    pub snapshots_per_block: usize,
+
+    /// zstd compression level
+    pub compression_level: i32,
+
+    /// Policy for symbols without an override
+    pub default_policy: RetentionPolicy,
+
+    /// Per-symbol overrides keyed by symbol (e.g., "BTC-USDT")
+    pub symbol_policies: HashMap<String, RetentionPolicy>,
+}
+
+impl Default for OrderBookStorageConfig {
+    fn default() -> Self {
+        Self {
+            snapshots_per_block: 600,
+            compression_level: 3,
+            default_policy: RetentionPolicy::default(),
+            symbol_policies: HashMap::new(),
+        }
+    }
+}
+
+impl OrderBookStorageConfig {
+    pub fn policy_for(&self, symbol: &str) -> &RetentionPolicy {
+        self.symbol_policies
+            .get(symbol)
+            .unwrap_or(&self.default_policy)
+    }
+
+    /// Shortest full-depth window across all policies; no block younger than
+    /// this needs vacuuming
+    pub fn min_full_depth_days(&self) -> i64 {
+        self.symbol_policies
+            .values()
+            .chain(std::iter::once(&self.default_policy))
+            .map(|policy| policy.full_depth_days.min(policy.reduced_depth_days))
+            .min()
+            .unwrap_or(self.default_policy.full_depth_days)
+    }
+}
+
+/// Outcome of one vacuum pass
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VacuumReport {
+    pub blocks_truncated: usize,
+    pub blocks_deleted: usize,
+    /// Payload bytes freed by truncation and deletion
+    pub bytes_reclaimed: u64,
+}
+
+/// Changes to one side of the book
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct SideDelta {
+    set: Vec<(Price, Quantity)>,
+    removed: Vec<Price>,
+}
+
+impl SideDelta {
+    fn between(prev: &[(Price, Quantity)], next: &[(Price, Quantity)]) -> Self {
+        let before: BTreeMap<Price, Quantity> = prev.iter().copied().collect();
+        let after: BTreeMap<Price, Quantity> = next.iter().copied().collect();
+
+        Self {
+            set: next
+                .iter()
+                .filter(|(price, qty)| before.get(price) != Some(qty))
+                .copied()
+                .collect(),
+            removed: prev
+                .iter()
+                .map(|(price, _)| *price)
+                .filter(|price| !after.contains_key(price))
+                .collect(),
+        }
+    }
+
+    fn apply(&self, levels: &mut BTreeMap<Price, Quantity>) {
+        for price in &self.removed {
+            levels.remove(price);
+        }
+        levels.extend(self.set.iter().copied());
+    }
+}
+
+/// One encoded snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum BookFrame {
+    Key {
+        timestamp: DateTime<Utc>,
+        checksum: Option<i32>,
+        bids: Vec<(Price, Quantity)>,
+        asks: Vec<(Price, Quantity)>,
+    },
+    Delta {
+        timestamp: DateTime<Utc>,
+        checksum: Option<i32>,
+        bids: SideDelta,
+        asks: SideDelta,
+    },
+}
+
+/// A compressed run of consecutive snapshots for one symbol
+#[derive(Debug, Clone)]
+pub struct OrderBookBlock {
+    pub symbol: Symbol,
+    pub depth_level: String,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    pub snapshot_count: usize,
+    /// Levels per side retained, or `None` for the depth as received
+    pub max_depth: Option<usize>,
+    pub payload: Vec<u8>,
+}
+
+impl OrderBookBlock {
+    /// Delta-encode and compress `snapshots`
+    ///
+    /// Snapshots must be non-empty, for a single symbol and in time order.
+    /// Bids are expected best (highest) first and asks best (lowest) first,
+    /// which is how the exchange sends them; decoding restores that order.
+    pub fn encode(snapshots: &[OrderBookSnapshot], compression_level: i32) -> Result<Self> {
+        let (first, last) = match (snapshots.first(), snapshots.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => {
+                return Err(Error::ValidationError(
+                    "Cannot encode an empty order book block".to_string(),
+                ));
+            }
+        };
+        if let Some(other) = snapshots.iter().find(|s| s.symbol != first.symbol) {
+            return Err(Error::ValidationError(format!(
+                "Order book block mixes {} and {}",
+                first.symbol.as_str(),
+                other.symbol.as_str()
+            )));
+        }
+
+        let mut frames = Vec::with_capacity(snapshots.len());
+        frames.push(BookFrame::Key {
+            timestamp: first.timestamp,
+            checksum: first.checksum,
+            bids: first.bids.clone(),
+            asks: first.asks.clone(),
+        });
+        for pair in snapshots.windows(2) {
+            let (prev, next) = (&pair[0], &pair[1]);
+            frames.push(BookFrame::Delta {
+                timestamp: next.timestamp,
+                checksum: next.checksum,
+                bids: SideDelta::between(&prev.bids, &next.bids),
+                asks: SideDelta::between(&prev.asks, &next.asks),
+            });
+        }
+
+        let encoded = serde_json::to_vec(&frames)?;
+        let payload = zstd::encode_all(encoded.as_slice(), compression_level)
+            .map_err(|e| Error::CompressionError(e.to_string()))?;
+
+        Ok(Self {
+            symbol: first.symbol.clone(),
+            depth_level: first.depth_level.clone(),
+            start_time: first.timestamp,
+            end_time: last.timestamp,
+            snapshot_count: snapshots.len(),
+            max_depth: None,
+            payload,
+        })
+    }
+
+    /// Decompress and rebuild every snapshot in the block
+    pub fn decode(&self) -> Result<Vec<OrderBookSnapshot>> {
+        let encoded = zstd::decode_all(self.payload.as_slice())
+            .map_err(|e| Error::CompressionError(e.to_string()))?;
+        let frames: Vec<BookFrame> = serde_json::from_slice(&encoded)?;
+
+        let mut bids = BTreeMap::new();
+        let mut asks = BTreeMap::new();
+        let mut snapshots = Vec::with_capacity(frames.len());
+
+        for (index, frame) in frames.into_iter().enumerate() {
+            let (timestamp, checksum) = match frame {
+                BookFrame::Key {
+                    timestamp,
+                    checksum,
+                    bids: key_bids,
+                    asks: key_asks,
+                } => {
+                    bids = key_bids.into_iter().collect();
+                    asks = key_asks.into_iter().collect();
+                    (timestamp, checksum)
+                }
+                BookFrame::Delta {
+                    timestamp,
+                    checksum,
+                    bids: bid_delta,
+                    asks: ask_delta,
+                } => {
+                    if index == 0 {
+                        return Err(Error::ParseError(
+                            "Order book block does not start with a key frame".to_string(),
+                        ));
+                    }
+                    bid_delta.apply(&mut bids);
+                    ask_delta.apply(&mut asks);
+                    (timestamp, checksum)
+                }
+            };
+
+            snapshots.push(OrderBookSnapshot {
+                symbol: self.symbol.clone(),
+                timestamp,
+                bids: bids.iter().rev().map(|(p, q)| (*p, *q)).collect(),
+                asks: asks.iter().map(|(p, q)| (*p, *q)).collect(),
+                checksum,
+                depth_level: self.depth_level.clone(),
+            });
+        }
+
+        Ok(snapshots)
+    }
+
+    /// Re-encode the block keeping only the best `depth` levels per side
+    ///
+    /// Checksums cover the full book, so they are dropped.
+    pub fn truncate_depth(&self, depth: usize, compression_level: i32) -> Result<Self> {
+        let snapshots: Vec<_> = self
+            .decode()?
+            .into_iter()
+            .map(|mut snapshot| {
+                snapshot.bids.truncate(depth);
+                snapshot.asks.truncate(depth);
+                snapshot.checksum = None;
+                snapshot
+            })
+            .collect();
+
+        let mut block = Self::encode(&snapshots, compression_level)?;
+        block.max_depth = Some(self.max_depth.map_or(depth, |current| current.min(depth)));
+        Ok(block)
+    }
+}
+
+/// Groups incoming snapshots into blocks per symbol
+#[derive(Debug)]
+pub struct OrderBookBlockBuilder {
+    snapshots_per_block: usize,
+    compression_level: i32,
+    pending: HashMap<Symbol, Vec<OrderBookSnapshot>>,
+}
+
+impl OrderBookBlockBuilder {
+    pub fn new(config: &OrderBookStorageConfig) -> Self {
+        Self {
+            snapshots_per_block: config.snapshots_per_block.max(1),
+            compression_level: config.compression_level,
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Add a snapshot, returning a finished block once one fills up
+    ///
+    /// A change of depth level (e.g., switching channels) also closes the
+    /// pending block so every block has a single depth level.
+    pub fn push(&mut self, snapshot: OrderBookSnapshot) -> Result<Option<OrderBookBlock>> {
+        let pending = self.pending.entry(snapshot.symbol.clone()).or_default();
+
+        let mut finished = None;
+        if pending
+            .last()
+            .is_some_and(|last| last.depth_level != snapshot.depth_level)
+        {
+            finished = Some(OrderBookBlock::encode(pending, self.compression_level)?);
+            pending.clear();
+        }
+
+        pending.push(snapshot);
+        if finished.is_none() && pending.len() >= self.snapshots_per_block {
+            finished = Some(OrderBookBlock::encode(pending, self.compression_level)?);
+            pending.clear();
+        }
+
+        Ok(finished)
+    }
+
+    /// Encode every partially filled block
+    pub fn flush(&mut self) -> Result<Vec<OrderBookBlock>> {
+        let mut blocks = Vec::new();
+        for pending in self.pending.values_mut() {
+            if !pending.is_empty() {
+                blocks.push(OrderBookBlock::encode(pending, self.compression_level)?);
+                pending.clear();
+            }
+        }
+        Ok(blocks)
+    }
+
+    /// Snapshots buffered and not yet encoded
+    pub fn pending_count(&self) -> usize {
+        self.pending.values().map(Vec::len).sum()
+    }
+}
+
+/// How stored order books are read back
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderBookQuery {
+    /// Keep only the first snapshot of every `sample_secs` seconds
+    #[serde(default)]
+    pub sample_secs: Option<u32>,
+
+    /// Keep only the best `depth` levels per side
+    #[serde(default)]
+    pub depth: Option<usize>,
+}
+
+impl OrderBookQuery {
+    /// Whether a snapshot at `timestamp` is kept, given the last one kept
+    pub fn keeps(&self, last_kept: Option<DateTime<Utc>>, timestamp: DateTime<Utc>) -> bool {
+        match (self.sample_secs.filter(|secs| *secs > 0), last_kept) {
+            (Some(secs), Some(last)) => {
+                let bucket = |t: DateTime<Utc>| t.timestamp().div_euclid(secs as i64);
+                bucket(timestamp) > bucket(last)
+            }
+            _ => true,
+        }
+    }
+}
+
+/// Order book history laid out by column
+///
+/// Snapshot `i` taken at `timestamps[i]` (Unix milliseconds) has
+/// `bid_levels[i]` bids, stored best first in `bid_prices` and `bid_sizes`
+/// right after the bids of snapshot `i - 1`. Asks are laid out the same way.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderBookColumns {
+    pub symbol: Symbol,
+    pub timestamps: Vec<i64>,
+    pub bid_levels: Vec<u32>,
+    pub bid_prices: Vec<Decimal>,
+    pub bid_sizes: Vec<Decimal>,
+    pub ask_levels: Vec<u32>,
+    pub ask_prices: Vec<Decimal>,
+    pub ask_sizes: Vec<Decimal>,
+}
+
+impl OrderBookColumns {
+    pub fn new(symbol: Symbol) -> Self {
+        Self {
+            symbol,
+            timestamps: Vec::new(),
+            bid_levels: Vec::new(),
+            bid_prices: Vec::new(),
+            bid_sizes: Vec::new(),
+            ask_levels: Vec::new(),
+            ask_prices: Vec::new(),
+            ask_sizes: Vec::new(),
+        }
+    }
+
+    /// Columns of the `snapshots` (in time order) that `query` keeps
+    pub fn from_snapshots<'a>(
+        symbol: Symbol,
+        snapshots: impl IntoIterator<Item = &'a OrderBookSnapshot>,
+        query: &OrderBookQuery,
+    ) -> Self {
+        let mut columns = Self::new(symbol);
+        for snapshot in snapshots {
+            columns.push_sampled(snapshot, query);
+        }
+        columns
+    }
+
+    /// Append `snapshot` unless `query` samples it out
+    pub fn push_sampled(&mut self, snapshot: &OrderBookSnapshot, query: &OrderBookQuery) -> bool {
+        if !query.keeps(self.last_timestamp(), snapshot.timestamp) {
+            return false;
+        }
+        let depth = query.depth.unwrap_or(usize::MAX);
+        self.timestamps.push(snapshot.timestamp.timestamp_millis());
+        push_side(
+            &snapshot.bids,
+            depth,
+            &mut self.bid_levels,
+            &mut self.bid_prices,
+            &mut self.bid_sizes,
+        );
+        push_side(
+            &snapshot.asks,
+            depth,
+            &mut self.ask_levels,
+            &mut self.ask_prices,
+            &mut self.ask_sizes,
+        );
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.timestamps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.timestamps.is_empty()
+    }
+
+    fn last_timestamp(&self) -> Option<DateTime<Utc>> {
+        self.timestamps
+            .last()
+            .and_then(|ms| DateTime::from_timestamp_millis(*ms))
+    }
+
+    /// Rebuild the snapshots, e.g. to feed a replay
+    ///
+    /// Depth levels and checksums are not kept in columns, so every snapshot
+    /// comes back with an empty depth level and no checksum.
+    pub fn snapshots(&self) -> Result<Vec<OrderBookSnapshot>> {
+        let (mut bid, mut ask) = (0, 0);
+        let mut snapshots = Vec::with_capacity(self.len());
+        for (i, ms) in self.timestamps.iter().enumerate() {
+            let timestamp = DateTime::from_timestamp_millis(*ms)
+                .ok_or_else(|| Error::ParseError(format!("Invalid order book timestamp {}", ms)))?;
+            let bids_end = bid + self.bid_levels[i] as usize;
+            let asks_end = ask + self.ask_levels[i] as usize;
+            snapshots.push(OrderBookSnapshot {
+                symbol: self.symbol.clone(),
+                timestamp,
+                bids: levels(
+                    &self.bid_prices[bid..bids_end],
+                    &self.bid_sizes[bid..bids_end],
+                )?,
+                asks: levels(
+                    &self.ask_prices[ask..asks_end],
+                    &self.ask_sizes[ask..asks_end],
+                )?,
+                checksum: None,
+                depth_level: String::new(),
+            });
+            (bid, ask) = (bids_end, asks_end);
+        }
+        Ok(snapshots)
+    }
+}
+
+fn push_side(
+    side: &[(Price, Quantity)],
+    depth: usize,
+    counts: &mut Vec<u32>,
+    prices: &mut Vec<Decimal>,
+    sizes: &mut Vec<Decimal>,
+) {
+    let side = &side[..side.len().min(depth)];
+    counts.push(side.len() as u32);
+    prices.extend(side.iter().map(|(price, _)| price.as_decimal()));
+    sizes.extend(side.iter().map(|(_, size)| size.as_decimal()));
+}
+
+fn levels(prices: &[Decimal], sizes: &[Decimal]) -> Result<Vec<(Price, Quantity)>> {
+    prices
+        .iter()
+        .zip(sizes)
+        .map(|(price, size)| Ok((Price::new(*price)?, Quantity::new(*size)?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    fn snapshot(symbol: &Symbol, i: i64) -> OrderBookSnapshot {
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let mid = dec!(50000) + Decimal::from(i % 3);
+        let level = |price: Decimal, qty: Decimal| {
+            (Price::new(price).unwrap(), Quantity::new(qty).unwrap())
+        };
+
+        OrderBookSnapshot {
+            symbol: symbol.clone(),
+            timestamp: start + Duration::milliseconds(100 * i),
+            bids: (1..=50)
+                .map(|d| level(mid - Decimal::from(d), Decimal::from(d + i % 2)))
+                .collect(),
+            asks: (1..=50)
+                .map(|d| level(mid + Decimal::from(d), Decimal::from(d)))
+                .collect(),
+            checksum: Some(i as i32),
+            depth_level: "books50".to_string(),
+        }
+    }
+
+    fn book_eq(a: &OrderBookSnapshot, b: &OrderBookSnapshot) -> bool {
+        a.timestamp == b.timestamp
+            && a.bids == b.bids
+            && a.asks == b.asks
+            && a.checksum == b.checksum
+    }
+
+    #[test]
+    fn test_block_round_trip_and_compresses() {
+        let symbol = Symbol::new("BTC-USDT").unwrap();
+        let snapshots: Vec<_> = (0..200).map(|i| snapshot(&symbol, i)).collect();
+
+        let block = OrderBookBlock::encode(&snapshots, 3).unwrap();
+        assert_eq!(block.snapshot_count, 200);
+        assert_eq!(block.end_time, snapshots[199].timestamp);
+
+        let decoded = block.decode().unwrap();
+        assert_eq!(decoded.len(), snapshots.len());
+        assert!(decoded.iter().zip(&snapshots).all(|(a, b)| book_eq(a, b)));
+
+        let raw = serde_json::to_vec(&snapshots).unwrap().len();
+        assert!(block.payload.len() * 20 < raw);
+    }
+
+    #[test]
+    fn test_truncate_keeps_best_levels() {
+        let symbol = Symbol::new("BTC-USDT").unwrap();
+        let snapshots: Vec<_> = (0..10).map(|i| snapshot(&symbol, i)).collect();
+
+        let block = OrderBookBlock::encode(&snapshots, 3)
+            .unwrap()
+            .truncate_depth(5, 3)
+            .unwrap();
+        assert_eq!(block.max_depth, Some(5));
+
+        let decoded = block.decode().unwrap();
+        assert_eq!(decoded[3].bids, snapshots[3].bids[..5].to_vec());
+        assert_eq!(decoded[3].asks, snapshots[3].asks[..5].to_vec());
+        assert_eq!(decoded[3].checksum, None);
+    }
+
+    #[test]
+    fn test_retention_tiers() {
+        let policy = RetentionPolicy::default();
+        let now = Utc::now();
+
+        assert_eq!(
+            policy.action(now - Duration::days(1), None, now),
+            RetentionAction::Keep
+        );
+        assert_eq!(
+            policy.action(now - Duration::days(8), None, now),
+            RetentionAction::Truncate(5)
+        );
+        assert_eq!(
+            policy.action(now - Duration::days(8), Some(5), now),
+            RetentionAction::Keep
+        );
+        assert_eq!(
+            policy.action(now - Duration::days(91), Some(5), now),
+            RetentionAction::Delete
+        );
+    }
+
+    #[test]
+    fn test_symbol_policy_override() {
+        let mut config = OrderBookStorageConfig::default();
+        config.symbol_policies.insert(
+            "BTC-USDT".to_string(),
+            RetentionPolicy {
+                full_depth_days: 30,
+                reduced_depth: 10,
+                reduced_depth_days: 365,
+            },
+        );
+
+        assert_eq!(config.policy_for("BTC-USDT").full_depth_days, 30);
+        assert_eq!(config.policy_for("ETH-USDT").full_depth_days, 7);
+        assert_eq!(config.min_full_depth_days(), 7);
+    }
+
+    #[test]
+    fn test_builder_splits_blocks() {
+        let symbol = Symbol::new("BTC-USDT").unwrap();
+        let mut builder = OrderBookBlockBuilder::new(&OrderBookStorageConfig {
+            snapshots_per_block: 4,
+            ..Default::default()
+        });
+
+        let mut blocks = Vec::new();
+        for i in 0..10 {
+            blocks.extend(builder.push(snapshot(&symbol, i)).unwrap());
+        }
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(builder.pending_count(), 2);
+
+        let mut other_depth = snapshot(&symbol, 10);
+        other_depth.depth_level = "books5".to_string();
+        let closed = builder.push(other_depth).unwrap().unwrap();
+        assert_eq!(closed.snapshot_count, 2);
+
+        let rest = builder.flush().unwrap();
+        assert_eq!(rest.len(), 1);
+        assert_eq!(rest[0].depth_level, "books5");
+        assert_eq!(builder.pending_count(), 0);
+    }
+
+    #[test]
+    fn test_columns_sample_and_truncate() {
+        let symbol = Symbol::new("BTC-USDT").unwrap();
+        // 100ms apart, so 25 snapshots span 2.4 seconds
+        let snapshots: Vec<_> = (0..25).map(|i| snapshot(&symbol, i)).collect();
+
+        let all = OrderBookColumns::from_snapshots(
+            symbol.clone(),
+            &snapshots,
+            &OrderBookQuery::default(),
+        );
+        assert_eq!(all.len(), 25);
+        assert_eq!(all.bid_prices.len(), 25 * 50);
+        let restored = all.snapshots().unwrap();
+        assert!(
+            restored.iter().zip(&snapshots).all(|(a, b)| {
+                a.timestamp == b.timestamp && a.bids == b.bids && a.asks == b.asks
+            })
+        );
+
+        let query = OrderBookQuery {
+            sample_secs: Some(1),
+            depth: Some(5),
+        };
+        let sampled = OrderBookColumns::from_snapshots(symbol, &snapshots, &query);
+        assert_eq!(sampled.len(), 3);
+        assert_eq!(
+            sampled.timestamps,
+            vec![
+                snapshots[0].timestamp.timestamp_millis(),
+                snapshots[10].timestamp.timestamp_millis(),
+                snapshots[20].timestamp.timestamp_millis(),
+            ]
+        );
+        assert_eq!(sampled.bid_levels, vec![5, 5, 5]);
+        assert_eq!(sampled.ask_prices.len(), 15);
+
+        let restored = sampled.snapshots().unwrap();
+        assert_eq!(restored[1].bids, snapshots[10].bids[..5].to_vec());
+        assert_eq!(restored[2].asks, snapshots[20].asks[..5].to_vec());
+    }
+}
@@ -0,0 +1,400 @@
+//! Positioning data collection
+//!
+//! OKX publishes per-currency trading statistics aggregated across all
+//! contracts: open interest, taker buy/sell volume and the ratio of accounts
+//! net long to net short. [`PositioningCollector`] polls them for each
+//! configured currency, stores new rows and emits them to subscribers, so
+//! strategies can use crowd positioning as a (usually contrarian) input.
+
+use crate::error::{Error, Result};
+use crate::storage::TimescaleStorage;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use ea_okx_client::OkxRestClient;
+use parking_lot::{Mutex, RwLock};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+/// Open interest and trading volume, both in USD
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenInterest {
+    /// Base currency (e.g. "BTC")
+    pub currency: String,
+    /// Reporting period (e.g. "5m")
+    pub period: String,
+    /// Start of the period
+    pub timestamp: DateTime<Utc>,
+    pub open_interest: Decimal,
+    pub volume: Decimal,
+}
+
+/// Volume bought and sold by takers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TakerVolume {
+    pub currency: String,
+    /// `SPOT` or `CONTRACTS`
+    pub inst_type: String,
+    pub period: String,
+    pub timestamp: DateTime<Utc>,
+    pub buy_volume: Decimal,
+    pub sell_volume: Decimal,
+}
+
+impl TakerVolume {
+    /// Net taker buying as a share of taker volume, from -1 (all sells) to 1
+    pub fn imbalance(&self) -> Option<Decimal> {
+        let total = self.buy_volume + self.sell_volume;
+        if total.is_zero() {
+            return None;
+        }
+        Some((self.buy_volume - self.sell_volume) / total)
+    }
+}
+
+/// Ratio of accounts net long to accounts net short
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LongShortRatio {
+    pub currency: String,
+    pub period: String,
+    pub timestamp: DateTime<Utc>,
+    pub ratio: Decimal,
+}
+
+/// One positioning statistic
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PositioningStat {
+    OpenInterest(OpenInterest),
+    TakerVolume(TakerVolume),
+    LongShortRatio(LongShortRatio),
+}
+
+impl PositioningStat {
+    /// Statistic name (e.g., "open_interest")
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::OpenInterest(_) => "open_interest",
+            Self::TakerVolume(_) => "taker_volume",
+            Self::LongShortRatio(_) => "long_short_ratio",
+        }
+    }
+
+    pub fn currency(&self) -> &str {
+        match self {
+            Self::OpenInterest(stat) => &stat.currency,
+            Self::TakerVolume(stat) => &stat.currency,
+            Self::LongShortRatio(stat) => &stat.currency,
+        }
+    }
+
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        match self {
+            Self::OpenInterest(stat) => stat.timestamp,
+            Self::TakerVolume(stat) => stat.timestamp,
+            Self::LongShortRatio(stat) => stat.timestamp,
+        }
+    }
+}
+
+/// Positioning collector configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositioningConfig {
+    /// Base currencies to collect (e.g. "BTC")
+    pub currencies: Vec<String>,
+
+    /// Reporting period: `5m`, `1H` or `1D`
+    pub period: String,
+
+    /// Market whose taker volume is collected: `SPOT` or `CONTRACTS`
+    pub taker_inst_type: String,
+
+    /// Polling interval of [`PositioningCollector::start`]
+    pub poll_interval_secs: u64,
+}
+
+impl Default for PositioningConfig {
+    fn default() -> Self {
+        Self {
+            currencies: vec!["BTC".to_string(), "ETH".to_string()],
+            period: "5m".to_string(),
+            taker_inst_type: "CONTRACTS".to_string(),
+            poll_interval_secs: 300,
+        }
+    }
+}
+
+/// Somewhere positioning statistics can be fetched from
+#[async_trait]
+pub trait PositioningSource: Send + Sync {
+    /// Recent statistics of every kind for `currency`, in any order
+    async fn fetch_stats(
+        &self,
+        currency: &str,
+        config: &PositioningConfig,
+    ) -> Result<Vec<PositioningStat>>;
+}
+
+/// OKX trading statistics endpoints
+pub struct OkxPositioningSource {
+    client: Arc<OkxRestClient>,
+}
+
+impl OkxPositioningSource {
+    pub fn new(client: Arc<OkxRestClient>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl PositioningSource for OkxPositioningSource {
+    async fn fetch_stats(
+        &self,
+        currency: &str,
+        config: &PositioningConfig,
+    ) -> Result<Vec<PositioningStat>> {
+        let period = config.period.as_str();
+        let (open_interest, taker, ratio) = tokio::try_join!(
+            self.client.open_interest_volume(currency, period),
+            self.client
+                .taker_volume(currency, &config.taker_inst_type, period),
+            self.client.long_short_ratio(currency, period),
+        )?;
+
+        let mut stats = Vec::with_capacity(open_interest.len() + taker.len() + ratio.len());
+        for row in open_interest {
+            stats.push(PositioningStat::OpenInterest(OpenInterest {
+                currency: currency.to_string(),
+                period: config.period.clone(),
+                timestamp: parse_millis(&row.ts)?,
+                open_interest: parse_decimal("open interest", &row.oi)?,
+                volume: parse_decimal("volume", &row.vol)?,
+            }));
+        }
+        for row in taker {
+            stats.push(PositioningStat::TakerVolume(TakerVolume {
+                currency: currency.to_string(),
+                inst_type: config.taker_inst_type.clone(),
+                period: config.period.clone(),
+                timestamp: parse_millis(&row.ts)?,
+                buy_volume: parse_decimal("taker buy volume", &row.buy_vol)?,
+                sell_volume: parse_decimal("taker sell volume", &row.sell_vol)?,
+            }));
+        }
+        for row in ratio {
+            stats.push(PositioningStat::LongShortRatio(LongShortRatio {
+                currency: currency.to_string(),
+                period: config.period.clone(),
+                timestamp: parse_millis(&row.ts)?,
+                ratio: parse_decimal("long/short ratio", &row.ratio)?,
+            }));
+        }
+        Ok(stats)
+    }
+}
+
+fn parse_decimal(field: &str, value: &str) -> Result<Decimal> {
+    value
+        .parse()
+        .map_err(|e| Error::ParseError(format!("Invalid {} '{}': {}", field, value, e)))
+}
+
+fn parse_millis(value: &str) -> Result<DateTime<Utc>> {
+    value
+        .parse()
+        .ok()
+        .and_then(DateTime::from_timestamp_millis)
+        .ok_or_else(|| Error::ParseError(format!("Invalid timestamp '{}'", value)))
+}
+
+/// Polls positioning statistics, storing and publishing new rows
+pub struct PositioningCollector {
+    config: PositioningConfig,
+    source: Arc<dyn PositioningSource>,
+    storage: Option<Arc<TimescaleStorage>>,
+
+    /// Newest timestamp seen per (currency, statistic)
+    latest: Mutex<HashMap<(String, &'static str), DateTime<Utc>>>,
+
+    /// Event channel
+    event_tx: mpsc::UnboundedSender<PositioningStat>,
+    event_rx: RwLock<Option<mpsc::UnboundedReceiver<PositioningStat>>>,
+}
+
+impl PositioningCollector {
+    pub fn new(config: PositioningConfig, source: Arc<dyn PositioningSource>) -> Self {
+        let (event_tx, event_rx) = mpsc::unbounded_channel();
+        Self {
+            config,
+            source,
+            storage: None,
+            latest: Mutex::new(HashMap::new()),
+            event_tx,
+            event_rx: RwLock::new(Some(event_rx)),
+        }
+    }
+
+    /// Store every new statistic in TimescaleDB
+    pub fn with_storage(mut self, storage: Arc<TimescaleStorage>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    pub fn config(&self) -> &PositioningConfig {
+        &self.config
+    }
+
+    /// Fetch `currency` once; returns the statistics not seen before, oldest first
+    ///
+    /// The first poll returns everything the source still reports, which
+    /// backfills storage after a restart.
+    pub async fn collect(&self, currency: &str) -> Result<Vec<PositioningStat>> {
+        let mut stats = self.source.fetch_stats(currency, &self.config).await?;
+        stats.sort_by_key(|stat| stat.timestamp());
+
+        let fresh: Vec<PositioningStat> = {
+            let mut latest = self.latest.lock();
+            stats
+                .into_iter()
+                .filter(|stat| {
+                    let seen = latest
+                        .entry((stat.currency().to_string(), stat.kind()))
+                        .or_insert(DateTime::<Utc>::MIN_UTC);
+                    if stat.timestamp() <= *seen {
+                        return false;
+                    }
+                    *seen = stat.timestamp();
+                    true
+                })
+                .collect()
+        };
+
+        if let Some(storage) = &self.storage {
+            for stat in &fresh {
+                storage.store_positioning_stat(stat).await?;
+            }
+        }
+        for stat in &fresh {
+            let _ = self.event_tx.send(stat.clone());
+        }
+        Ok(fresh)
+    }
+
+    /// Collect every configured currency each `poll_interval_secs` until the
+    /// task is aborted
+    pub fn start(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(std::time::Duration::from_secs(
+                self.config.poll_interval_secs.max(1),
+            ));
+            loop {
+                ticker.tick().await;
+                for currency in &self.config.currencies {
+                    match self.collect(currency).await {
+                        Ok(fresh) => debug!(
+                            "Collected {} positioning statistics for {}",
+                            fresh.len(),
+                            currency
+                        ),
+                        Err(e) => warn!("Positioning collection failed for {}: {}", currency, e),
+                    }
+                }
+            }
+        })
+    }
+
+    /// Get event receiver (can only be called once)
+    pub fn subscribe_events(&self) -> Option<mpsc::UnboundedReceiver<PositioningStat>> {
+        self.event_rx.write().take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+
+    /// Reports a fixed window of ratios ending at the latest `end` minute
+    struct WindowSource {
+        end: Mutex<i64>,
+    }
+
+    #[async_trait]
+    impl PositioningSource for WindowSource {
+        async fn fetch_stats(
+            &self,
+            currency: &str,
+            config: &PositioningConfig,
+        ) -> Result<Vec<PositioningStat>> {
+            let end = *self.end.lock();
+            Ok((end - 2..=end)
+                .rev()
+                .map(|minute| {
+                    PositioningStat::LongShortRatio(LongShortRatio {
+                        currency: currency.to_string(),
+                        period: config.period.clone(),
+                        timestamp: Utc.timestamp_opt(minute * 60, 0).unwrap(),
+                        ratio: Decimal::from(minute),
+                    })
+                })
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_collect_emits_only_new_statistics() {
+        let source = Arc::new(WindowSource {
+            end: Mutex::new(10),
+        });
+        let collector = PositioningCollector::new(PositioningConfig::default(), source.clone());
+        let mut events = collector.subscribe_events().unwrap();
+        assert!(collector.subscribe_events().is_none());
+
+        let first = collector.collect("BTC").await.unwrap();
+        let minutes: Vec<i64> = first
+            .iter()
+            .map(|s| s.timestamp().timestamp() / 60)
+            .collect();
+        assert_eq!(minutes, vec![8, 9, 10]);
+
+        *source.end.lock() = 12;
+        let second = collector.collect("BTC").await.unwrap();
+        assert_eq!(second.len(), 2);
+        assert_eq!(second[0].timestamp().timestamp(), 11 * 60);
+
+        // Other currencies are tracked separately
+        assert_eq!(collector.collect("ETH").await.unwrap().len(), 3);
+
+        let mut received = 0;
+        while events.try_recv().is_ok() {
+            received += 1;
+        }
+        assert_eq!(received, 8);
+    }
+
+    #[test]
+    fn test_taker_imbalance() {
+        let mut taker = TakerVolume {
+            currency: "BTC".to_string(),
+            inst_type: "CONTRACTS".to_string(),
+            period: "5m".to_string(),
+            timestamp: Utc::now(),
+            buy_volume: dec!(75),
+            sell_volume: dec!(25),
+        };
+        assert_eq!(taker.imbalance(), Some(dec!(0.5)));
+
+        taker.buy_volume = Decimal::ZERO;
+        taker.sell_volume = Decimal::ZERO;
+        assert_eq!(taker.imbalance(), None);
+
+        let stat = PositioningStat::TakerVolume(taker);
+        assert_eq!(stat.kind(), "taker_volume");
+        assert_eq!(serde_json::to_value(&stat).unwrap()["type"], "taker_volume");
+    }
+}
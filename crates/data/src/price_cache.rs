@@ -0,0 +1,389 @@
+//! Two-tier cache of latest prices
+//!
+//! Every price lookup against [`RedisStorage`] is a network round trip, yet
+//! the execution engine, risk checks and UI ask for the same handful of
+//! symbols many times a second. [`TieredPriceCache`] answers from a small
+//! process-local LRU with a short TTL and only falls through to Redis on a
+//! local miss, writing prices through to both tiers. It counts hits and
+//! lookup latency per tier so the saving can be measured.
+
+use crate::error::Result;
+use crate::storage::RedisStorage;
+use async_trait::async_trait;
+use ea_okx_core::types::{Price, Symbol};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Latest price per symbol
+#[async_trait]
+pub trait PriceCache: Send + Sync {
+    async fn get_price(&self, symbol: &Symbol) -> Result<Option<Price>>;
+
+    async fn put_price(&self, symbol: &Symbol, price: Price) -> Result<()>;
+}
+
+#[async_trait]
+impl PriceCache for RedisStorage {
+    async fn get_price(&self, symbol: &Symbol) -> Result<Option<Price>> {
+        RedisStorage::get_price(self, symbol).await
+    }
+
+    async fn put_price(&self, symbol: &Symbol, price: Price) -> Result<()> {
+        self.cache_price(symbol, &price).await
+    }
+}
+
+/// Sizing of the process-local tier
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceCacheConfig {
+    /// Symbols kept before the least recently used is evicted
+    pub capacity: usize,
+    /// How long a local entry is served before Redis is asked again
+    pub ttl_ms: u64,
+}
+
+impl Default for PriceCacheConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 1024,
+            ttl_ms: 500,
+        }
+    }
+}
+
+struct LruEntry {
+    price: Price,
+    stored_at: Instant,
+    /// Key of the entry in `LruState::recency`
+    used: u64,
+}
+
+#[derive(Default)]
+struct LruState {
+    entries: HashMap<Symbol, LruEntry>,
+    /// Symbols by last use, least recent first
+    recency: BTreeMap<u64, Symbol>,
+    clock: u64,
+}
+
+impl LruState {
+    fn touch(&mut self, symbol: &Symbol) {
+        self.clock += 1;
+        let clock = self.clock;
+        if let Some(entry) = self.entries.get_mut(symbol) {
+            self.recency.remove(&entry.used);
+            entry.used = clock;
+            self.recency.insert(clock, symbol.clone());
+        }
+    }
+
+    fn remove(&mut self, symbol: &Symbol) {
+        if let Some(entry) = self.entries.remove(symbol) {
+            self.recency.remove(&entry.used);
+        }
+    }
+}
+
+/// Process-local LRU of prices that expire after a TTL
+pub struct LruPriceCache {
+    capacity: usize,
+    ttl: Duration,
+    state: Mutex<LruState>,
+}
+
+impl LruPriceCache {
+    pub fn new(config: &PriceCacheConfig) -> Self {
+        Self {
+            capacity: config.capacity.max(1),
+            ttl: Duration::from_millis(config.ttl_ms),
+            state: Mutex::new(LruState::default()),
+        }
+    }
+
+    /// Cached price of `symbol` unless missing or expired
+    pub fn get(&self, symbol: &Symbol) -> Option<Price> {
+        self.get_at(symbol, Instant::now())
+    }
+
+    pub fn put(&self, symbol: &Symbol, price: Price) {
+        self.put_at(symbol, price, Instant::now())
+    }
+
+    /// Symbols currently held, expired or not
+    pub fn len(&self) -> usize {
+        self.state.lock().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn get_at(&self, symbol: &Symbol, now: Instant) -> Option<Price> {
+        let mut state = self.state.lock();
+        let entry = state.entries.get(symbol)?;
+        if now.saturating_duration_since(entry.stored_at) >= self.ttl {
+            state.remove(symbol);
+            return None;
+        }
+        let price = entry.price;
+        state.touch(symbol);
+        Some(price)
+    }
+
+    fn put_at(&self, symbol: &Symbol, price: Price, now: Instant) {
+        let mut state = self.state.lock();
+        if !state.entries.contains_key(symbol)
+            && state.entries.len() >= self.capacity
+            && let Some((_, oldest)) = state.recency.pop_first()
+        {
+            state.entries.remove(&oldest);
+        }
+
+        let entry = state.entries.entry(symbol.clone()).or_insert(LruEntry {
+            price,
+            stored_at: now,
+            used: 0,
+        });
+        entry.price = price;
+        entry.stored_at = now;
+        state.touch(symbol);
+    }
+}
+
+/// Hit, miss and latency counters of a [`TieredPriceCache`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PriceCacheStats {
+    /// Lookups answered by the local tier
+    pub local_hits: u64,
+    /// Lookups answered by Redis after a local miss
+    pub remote_hits: u64,
+    /// Lookups neither tier could answer
+    pub misses: u64,
+    /// Lookups where Redis failed
+    pub remote_errors: u64,
+    /// Share of lookups answered by either tier
+    pub hit_rate: f64,
+    /// Mean latency of a local hit in microseconds
+    pub avg_local_micros: f64,
+    /// Mean latency of a lookup that went to Redis in microseconds
+    pub avg_remote_micros: f64,
+    /// Symbols held by the local tier
+    pub local_entries: usize,
+}
+
+#[derive(Default)]
+struct Counters {
+    local_hits: AtomicU64,
+    local_nanos: AtomicU64,
+    remote_hits: AtomicU64,
+    remote_lookups: AtomicU64,
+    remote_nanos: AtomicU64,
+    misses: AtomicU64,
+    remote_errors: AtomicU64,
+}
+
+/// Local LRU in front of an optional remote cache such as Redis
+pub struct TieredPriceCache {
+    local: LruPriceCache,
+    remote: Option<Arc<dyn PriceCache>>,
+    counters: Counters,
+}
+
+impl TieredPriceCache {
+    /// Local tier only, until [`TieredPriceCache::with_remote`] adds one
+    pub fn new(config: &PriceCacheConfig) -> Self {
+        Self {
+            local: LruPriceCache::new(config),
+            remote: None,
+            counters: Counters::default(),
+        }
+    }
+
+    /// Fall through to `remote` on local misses and write prices to it
+    pub fn with_remote(mut self, remote: Arc<dyn PriceCache>) -> Self {
+        self.remote = Some(remote);
+        self
+    }
+
+    pub fn stats(&self) -> PriceCacheStats {
+        let c = &self.counters;
+        let local_hits = c.local_hits.load(Ordering::Relaxed);
+        let remote_hits = c.remote_hits.load(Ordering::Relaxed);
+        let remote_lookups = c.remote_lookups.load(Ordering::Relaxed);
+        let misses = c.misses.load(Ordering::Relaxed);
+        let remote_errors = c.remote_errors.load(Ordering::Relaxed);
+        let lookups = local_hits + remote_hits + misses + remote_errors;
+        let mean_micros = |nanos: &AtomicU64, count: u64| {
+            if count == 0 {
+                0.0
+            } else {
+                nanos.load(Ordering::Relaxed) as f64 / count as f64 / 1000.0
+            }
+        };
+
+        PriceCacheStats {
+            local_hits,
+            remote_hits,
+            misses,
+            remote_errors,
+            hit_rate: if lookups == 0 {
+                0.0
+            } else {
+                (local_hits + remote_hits) as f64 / lookups as f64
+            },
+            avg_local_micros: mean_micros(&c.local_nanos, local_hits),
+            avg_remote_micros: mean_micros(&c.remote_nanos, remote_lookups),
+            local_entries: self.local.len(),
+        }
+    }
+}
+
+fn elapsed_nanos(started: Instant) -> u64 {
+    u64::try_from(started.elapsed().as_nanos()).unwrap_or(u64::MAX)
+}
+
+#[async_trait]
+impl PriceCache for TieredPriceCache {
+    async fn get_price(&self, symbol: &Symbol) -> Result<Option<Price>> {
+        let c = &self.counters;
+        let started = Instant::now();
+        if let Some(price) = self.local.get(symbol) {
+            c.local_hits.fetch_add(1, Ordering::Relaxed);
+            c.local_nanos
+                .fetch_add(elapsed_nanos(started), Ordering::Relaxed);
+            return Ok(Some(price));
+        }
+
+        let Some(remote) = &self.remote else {
+            c.misses.fetch_add(1, Ordering::Relaxed);
+            return Ok(None);
+        };
+        let result = remote.get_price(symbol).await;
+        c.remote_lookups.fetch_add(1, Ordering::Relaxed);
+        c.remote_nanos
+            .fetch_add(elapsed_nanos(started), Ordering::Relaxed);
+
+        match result {
+            Ok(Some(price)) => {
+                c.remote_hits.fetch_add(1, Ordering::Relaxed);
+                self.local.put(symbol, price);
+                Ok(Some(price))
+            }
+            Ok(None) => {
+                c.misses.fetch_add(1, Ordering::Relaxed);
+                Ok(None)
+            }
+            Err(e) => {
+                c.remote_errors.fetch_add(1, Ordering::Relaxed);
+                warn!("Price cache lookup for {} failed: {}", symbol.as_str(), e);
+                Err(e)
+            }
+        }
+    }
+
+    async fn put_price(&self, symbol: &Symbol, price: Price) -> Result<()> {
+        self.local.put(symbol, price);
+        match &self.remote {
+            Some(remote) => remote.put_price(symbol, price).await,
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn symbol(s: &str) -> Symbol {
+        Symbol::new(s).unwrap()
+    }
+
+    fn price(p: rust_decimal::Decimal) -> Price {
+        Price::new(p).unwrap()
+    }
+
+    #[test]
+    fn test_lru_evicts_least_recent_and_expires() {
+        let cache = LruPriceCache::new(&PriceCacheConfig {
+            capacity: 2,
+            ttl_ms: 1000,
+        });
+        let now = Instant::now();
+        let (btc, eth, sol) = (symbol("BTC-USDT"), symbol("ETH-USDT"), symbol("SOL-USDT"));
+
+        cache.put_at(&btc, price(dec!(50000)), now);
+        cache.put_at(&eth, price(dec!(3000)), now);
+        // Reading BTC makes ETH the least recently used
+        assert!(cache.get_at(&btc, now).is_some());
+        cache.put_at(&sol, price(dec!(150)), now);
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get_at(&eth, now).is_none());
+        assert_eq!(cache.get_at(&btc, now), Some(price(dec!(50000))));
+
+        let later = now + Duration::from_millis(1000);
+        assert!(cache.get_at(&sol, later).is_none());
+        assert_eq!(cache.len(), 1);
+    }
+
+    /// Remote tier backed by a map, counting lookups
+    #[derive(Default)]
+    struct MapCache {
+        prices: Mutex<HashMap<Symbol, Price>>,
+        lookups: AtomicU64,
+    }
+
+    #[async_trait]
+    impl PriceCache for MapCache {
+        async fn get_price(&self, symbol: &Symbol) -> Result<Option<Price>> {
+            self.lookups.fetch_add(1, Ordering::Relaxed);
+            Ok(self.prices.lock().get(symbol).copied())
+        }
+
+        async fn put_price(&self, symbol: &Symbol, price: Price) -> Result<()> {
+            self.prices.lock().insert(symbol.clone(), price);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tiered_cache_falls_through_and_counts() {
+        let remote = Arc::new(MapCache::default());
+        remote
+            .put_price(&symbol("BTC-USDT"), price(dec!(50000)))
+            .await
+            .unwrap();
+        let cache = TieredPriceCache::new(&PriceCacheConfig::default()).with_remote(remote.clone());
+
+        let btc = symbol("BTC-USDT");
+        assert_eq!(
+            cache.get_price(&btc).await.unwrap(),
+            Some(price(dec!(50000)))
+        );
+        assert_eq!(
+            cache.get_price(&btc).await.unwrap(),
+            Some(price(dec!(50000)))
+        );
+        assert_eq!(cache.get_price(&symbol("ETH-USDT")).await.unwrap(), None);
+        assert_eq!(remote.lookups.load(Ordering::Relaxed), 2);
+
+        cache
+            .put_price(&symbol("SOL-USDT"), price(dec!(150)))
+            .await
+            .unwrap();
+        assert!(remote.prices.lock().contains_key(&symbol("SOL-USDT")));
+
+        let stats = cache.stats();
+        assert_eq!(stats.local_hits, 1);
+        assert_eq!(stats.remote_hits, 1);
+        assert_eq!(stats.misses, 1);
+        assert!((stats.hit_rate - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(stats.local_entries, 2);
+    }
+}
@@ -6,16 +6,21 @@
 //! - Range validation (price within reasonable bounds)
 //! - Missing field detection
 //! - Anomaly detection using statistical methods
+//! - Rolling per-symbol quality scores over the last hour and day
 
 use crate::error::{Error, Result};
 use chrono::{DateTime, Duration, Utc};
 use ea_okx_core::types::{Price, Symbol};
 use parking_lot::RwLock;
 use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 use tracing::{debug, warn};
 
+/// Reference price and when it was computed
+type TimedPrice = (Decimal, DateTime<Utc>);
+
 /// Quality control configuration
 #[derive(Debug, Clone)]
 pub struct QualityConfig {
@@ -39,6 +44,15 @@ pub struct QualityConfig {
 
     /// Duplicate detection window size
     pub dedup_window_size: usize,
+
+    /// Maximum price deviation from the blended reference price (e.g., 0.03 for 3%)
+    pub max_reference_deviation_pct: Decimal,
+
+    /// Reference prices older than this are ignored (seconds)
+    pub reference_max_age_secs: i64,
+
+    /// Score points lost per feed gap per hour
+    pub gap_score_penalty: f64,
 }
 
 impl Default for QualityConfig {
@@ -51,6 +65,9 @@ impl Default for QualityConfig {
             anomaly_zscore_threshold: 3.0,
             enable_dedup: true,
             dedup_window_size: 1000,
+            max_reference_deviation_pct: Decimal::new(3, 2), // 0.03 = 3%
+            reference_max_age_secs: 30,
+            gap_score_penalty: 10.0,
         }
     }
 }
@@ -65,11 +82,59 @@ pub struct QualityControl {
     /// Price history for anomaly detection per symbol
     price_history: Arc<RwLock<HashMap<Symbol, VecDeque<Decimal>>>>,
 
+    /// Externally blended reference prices per symbol
+    reference_prices: Arc<RwLock<HashMap<Symbol, TimedPrice>>>,
+
     /// Recent message IDs for deduplication
     recent_message_ids: Arc<RwLock<VecDeque<String>>>,
 
     /// Statistics
     stats: Arc<RwLock<QualityStats>>,
+
+    /// Per-minute outcome counts per symbol, covering the last day
+    activity: Arc<RwLock<HashMap<Symbol, VecDeque<ActivityBucket>>>>,
+}
+
+/// Outcome counts for one symbol over one minute
+#[derive(Debug, Clone, Copy, Default)]
+struct ActivityBucket {
+    minute: i64,
+    updates: u64,
+    rejected: u64,
+    gaps: u64,
+    anomalies: u64,
+}
+
+/// Quality of one symbol's feed over a trailing window
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QualityWindowStats {
+    pub window_secs: i64,
+    pub updates: u64,
+    pub rejected: u64,
+    pub gaps: u64,
+    pub anomalies: u64,
+
+    /// Share of updates rejected, in percent
+    pub rejected_pct: f64,
+    pub gaps_per_hour: f64,
+
+    /// Share of updates flagged as anomalous, in percent
+    pub anomaly_pct: f64,
+
+    /// 0 to 100, `None` without any updates in the window
+    pub score: Option<f64>,
+}
+
+/// Rolling quality of one symbol's feed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SymbolQuality {
+    pub symbol: Symbol,
+    pub last_hour: QualityWindowStats,
+    pub last_day: QualityWindowStats,
+
+    /// Score over the last hour, the figure trading decisions use
+    pub score: Option<f64>,
+    pub computed_at: DateTime<Utc>,
 }
 
 /// Quality control statistics
@@ -85,6 +150,13 @@ pub struct QualityStats {
     pub missing_field_rejections: u64,
 }
 
+impl Default for QualityControl {
+    /// Create with default configuration
+    fn default() -> Self {
+        Self::new(QualityConfig::default())
+    }
+}
+
 impl QualityControl {
     /// Create a new quality control instance
     pub fn new(config: QualityConfig) -> Self {
@@ -92,16 +164,13 @@ impl QualityControl {
             config,
             last_prices: Arc::new(RwLock::new(HashMap::new())),
             price_history: Arc::new(RwLock::new(HashMap::new())),
+            reference_prices: Arc::new(RwLock::new(HashMap::new())),
             recent_message_ids: Arc::new(RwLock::new(VecDeque::new())),
             stats: Arc::new(RwLock::new(QualityStats::default())),
+            activity: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
-    /// Create with default configuration
-    pub fn default() -> Self {
-        Self::new(QualityConfig::default())
-    }
-
     /// Validate timestamp
     pub fn validate_timestamp(&self, timestamp: DateTime<Utc>) -> Result<()> {
         let now = Utc::now();
@@ -132,8 +201,41 @@ impl QualityControl {
         Ok(())
     }
 
-    /// Validate price against historical data
+    /// Set the reference price prices are checked against
+    pub fn set_reference_price(&self, symbol: &Symbol, price: Decimal, timestamp: DateTime<Utc>) {
+        self.reference_prices
+            .write()
+            .insert(symbol.clone(), (price, timestamp));
+    }
+
+    /// Reference price for a symbol, if one is fresh
+    pub fn reference_price(&self, symbol: &Symbol) -> Option<Decimal> {
+        let (price, timestamp) = *self.reference_prices.read().get(symbol)?;
+        let age = Utc::now().signed_duration_since(timestamp);
+        (age.num_seconds() <= self.config.reference_max_age_secs).then_some(price)
+    }
+
+    /// Validate price against the reference price, or historical data
+    ///
+    /// A fresh reference price takes precedence over the last accepted
+    /// price, so a run of bad prints cannot drag the baseline with it.
     pub fn validate_price(&self, symbol: &Symbol, price: &Price) -> Result<()> {
+        if let Some(reference) = self.reference_price(symbol)
+            && reference > Decimal::ZERO
+        {
+            let deviation = (price.as_decimal() - reference).abs() / reference;
+            if deviation > self.config.max_reference_deviation_pct {
+                self.stats.write().price_deviation_rejections += 1;
+                return Err(Error::ValidationError(format!(
+                    "Price deviates {:.2}% from reference {} (max: {:.2}%)",
+                    deviation * Decimal::new(100, 0),
+                    reference,
+                    self.config.max_reference_deviation_pct * Decimal::new(100, 0)
+                )));
+            }
+            return Ok(());
+        }
+
         let last_prices = self.last_prices.read();
 
         if let Some(last_price) = last_prices.get(symbol) {
@@ -159,9 +261,7 @@ impl QualityControl {
     /// Detect anomalies using Z-score
     pub fn detect_anomaly(&self, symbol: &Symbol, price: &Price) -> Result<()> {
         let mut price_history = self.price_history.write();
-        let history = price_history
-            .entry(symbol.clone())
-            .or_insert_with(VecDeque::new);
+        let history = price_history.entry(symbol.clone()).or_default();
 
         // Need sufficient history for anomaly detection
         if history.len() < 10 {
@@ -248,6 +348,26 @@ impl QualityControl {
     ) -> Result<()> {
         self.stats.write().total_processed += 1;
 
+        let result = self.check_market_data(symbol, price, timestamp, message_id);
+        self.record_activity(symbol, Utc::now(), |bucket| {
+            bucket.updates += 1;
+            match &result {
+                Ok(true) => bucket.anomalies += 1,
+                Ok(false) => {}
+                Err(_) => bucket.rejected += 1,
+            }
+        });
+        result.map(|_| ())
+    }
+
+    /// Run every check on one update, returning whether it looked anomalous
+    fn check_market_data(
+        &self,
+        symbol: &Symbol,
+        price: &Price,
+        timestamp: DateTime<Utc>,
+        message_id: Option<&str>,
+    ) -> Result<bool> {
         // Timestamp validation
         self.validate_timestamp(timestamp)?;
 
@@ -255,10 +375,14 @@ impl QualityControl {
         self.validate_price(symbol, price)?;
 
         // Anomaly detection
-        if let Err(e) = self.detect_anomaly(symbol, price) {
-            debug!("Anomaly check failed: {}", e);
-            // Don't reject on anomaly, just log warning
-        }
+        let anomalous = match self.detect_anomaly(symbol, price) {
+            Ok(()) => false,
+            Err(e) => {
+                debug!("Anomaly check failed: {}", e);
+                // Don't reject on anomaly, just log warning
+                true
+            }
+        };
 
         // Deduplication
         if let Some(msg_id) = message_id {
@@ -266,11 +390,129 @@ impl QualityControl {
         }
 
         // Update last valid price
-        self.last_prices
-            .write()
-            .insert(symbol.clone(), price.clone());
+        self.last_prices.write().insert(symbol.clone(), *price);
 
-        Ok(())
+        Ok(anomalous)
+    }
+
+    /// Count a gap in `symbol`'s feed towards its quality score
+    pub fn record_gap(&self, symbol: &Symbol) {
+        self.record_gap_at(symbol, Utc::now());
+    }
+
+    /// Count a gap detected at `at`
+    pub fn record_gap_at(&self, symbol: &Symbol, at: DateTime<Utc>) {
+        self.record_activity(symbol, at, |bucket| bucket.gaps += 1);
+    }
+
+    /// Apply `update` to `symbol`'s bucket for the minute of `at`, dropping
+    /// buckets that have fallen out of the day window
+    fn record_activity(
+        &self,
+        symbol: &Symbol,
+        at: DateTime<Utc>,
+        update: impl FnOnce(&mut ActivityBucket),
+    ) {
+        let minute = at.timestamp().div_euclid(60);
+        let mut activity = self.activity.write();
+        let buckets = activity.entry(symbol.clone()).or_default();
+
+        match buckets.iter_mut().rev().find(|b| b.minute == minute) {
+            Some(bucket) => update(bucket),
+            None => {
+                let mut bucket = ActivityBucket {
+                    minute,
+                    ..Default::default()
+                };
+                update(&mut bucket);
+                let position = buckets.partition_point(|b| b.minute < minute);
+                buckets.insert(position, bucket);
+            }
+        }
+
+        let oldest = minute - Duration::days(1).num_minutes();
+        while buckets.front().is_some_and(|b| b.minute <= oldest) {
+            buckets.pop_front();
+        }
+    }
+
+    /// Rolling quality of `symbol`'s feed, `None` if it was never seen
+    pub fn symbol_quality(&self, symbol: &Symbol) -> Option<SymbolQuality> {
+        self.symbol_quality_at(symbol, Utc::now())
+    }
+
+    /// Rolling quality of `symbol`'s feed as of `now`
+    pub fn symbol_quality_at(&self, symbol: &Symbol, now: DateTime<Utc>) -> Option<SymbolQuality> {
+        let activity = self.activity.read();
+        let buckets = activity.get(symbol)?;
+        let last_hour = self.window_stats(buckets, now, Duration::hours(1));
+        let last_day = self.window_stats(buckets, now, Duration::days(1));
+
+        Some(SymbolQuality {
+            symbol: symbol.clone(),
+            score: last_hour.score,
+            last_hour,
+            last_day,
+            computed_at: now,
+        })
+    }
+
+    /// Rolling quality of every symbol seen in the last day
+    pub fn symbol_qualities(&self) -> Vec<SymbolQuality> {
+        let now = Utc::now();
+        let symbols: Vec<Symbol> = self.activity.read().keys().cloned().collect();
+        let mut qualities: Vec<SymbolQuality> = symbols
+            .iter()
+            .filter_map(|symbol| self.symbol_quality_at(symbol, now))
+            .collect();
+        qualities.sort_by(|a, b| a.symbol.as_str().cmp(b.symbol.as_str()));
+        qualities
+    }
+
+    /// Sum the buckets inside `window` before `now` and score them
+    ///
+    /// The score starts at 100 and loses the rejected and anomalous
+    /// percentages plus `gap_score_penalty` per gap per hour.
+    fn window_stats(
+        &self,
+        buckets: &VecDeque<ActivityBucket>,
+        now: DateTime<Utc>,
+        window: Duration,
+    ) -> QualityWindowStats {
+        let current = now.timestamp().div_euclid(60);
+        let oldest = current - window.num_minutes();
+        let mut stats = QualityWindowStats {
+            window_secs: window.num_seconds(),
+            updates: 0,
+            rejected: 0,
+            gaps: 0,
+            anomalies: 0,
+            rejected_pct: 0.0,
+            gaps_per_hour: 0.0,
+            anomaly_pct: 0.0,
+            score: None,
+        };
+        for bucket in buckets
+            .iter()
+            .filter(|b| b.minute > oldest && b.minute <= current)
+        {
+            stats.updates += bucket.updates;
+            stats.rejected += bucket.rejected;
+            stats.gaps += bucket.gaps;
+            stats.anomalies += bucket.anomalies;
+        }
+
+        stats.gaps_per_hour = stats.gaps as f64 * 3600.0 / window.num_seconds() as f64;
+        if stats.updates > 0 {
+            stats.rejected_pct = stats.rejected as f64 * 100.0 / stats.updates as f64;
+            stats.anomaly_pct = stats.anomalies as f64 * 100.0 / stats.updates as f64;
+            let score = 100.0
+                - stats.rejected_pct
+                - stats.anomaly_pct
+                - self.config.gap_score_penalty * stats.gaps_per_hour;
+            stats.score = Some(score.clamp(0.0, 100.0));
+        }
+        stats
     }
 
     /// Get quality control statistics
@@ -314,6 +556,20 @@ mod tests {
         assert!(qc.validate_timestamp(now).is_ok());
     }
 
+    #[test]
+    fn test_default_uses_default_config() {
+        let qc = QualityControl::default();
+        let defaults = QualityConfig::default();
+        assert_eq!(qc.config.max_data_age_secs, defaults.max_data_age_secs);
+        assert_eq!(qc.config.dedup_window_size, defaults.dedup_window_size);
+
+        // Early prices only build up the anomaly history
+        let symbol = Symbol::new("BTC-USDT").unwrap();
+        let price = Price::new(dec!(50000)).unwrap();
+        assert!(qc.detect_anomaly(&symbol, &price).is_ok());
+        assert_eq!(qc.price_history.read()[&symbol].len(), 1);
+    }
+
     #[test]
     fn test_validate_price_no_history() {
         let qc = QualityControl::default();
@@ -345,6 +601,35 @@ mod tests {
         assert!(qc.validate_price(&symbol, &price2).is_err());
     }
 
+    #[test]
+    fn test_validate_price_against_reference() {
+        let qc = QualityControl::default();
+        let symbol = Symbol::new("BTC-USDT").unwrap();
+
+        // Last accepted price was already a bad print; the reference wins
+        qc.last_prices
+            .write()
+            .insert(symbol.clone(), Price::new(dec!(45000)).unwrap());
+        qc.set_reference_price(&symbol, dec!(50000), Utc::now());
+
+        assert!(
+            qc.validate_price(&symbol, &Price::new(dec!(50500)).unwrap())
+                .is_ok()
+        );
+        assert!(
+            qc.validate_price(&symbol, &Price::new(dec!(46000)).unwrap())
+                .is_err()
+        );
+
+        // A stale reference falls back to the last price
+        qc.set_reference_price(&symbol, dec!(50000), Utc::now() - Duration::minutes(5));
+        assert!(qc.reference_price(&symbol).is_none());
+        assert!(
+            qc.validate_price(&symbol, &Price::new(dec!(46000)).unwrap())
+                .is_ok()
+        );
+    }
+
     #[test]
     fn test_check_duplicate() {
         let qc = QualityControl::default();
@@ -354,6 +639,51 @@ mod tests {
         assert!(qc.check_duplicate("msg-456").is_ok());
     }
 
+    #[test]
+    fn test_symbol_quality_scores_rolling_windows() {
+        let qc = QualityControl::default();
+        let btc = Symbol::new("BTC-USDT").unwrap();
+        let eth = Symbol::new("ETH-USDT").unwrap();
+        let now = Utc::now();
+        assert!(qc.symbol_quality_at(&btc, now).is_none());
+
+        for i in 0..20 {
+            let price = Price::new(dec!(50000) + Decimal::from(i % 3)).unwrap();
+            qc.validate_market_data(&btc, &price, Utc::now(), None)
+                .unwrap();
+        }
+        // Stale update rejected, then a feed gap
+        let price = Price::new(dec!(50000)).unwrap();
+        assert!(
+            qc.validate_market_data(&btc, &price, Utc::now() - Duration::seconds(30), None)
+                .is_err()
+        );
+        qc.record_gap(&btc);
+        // A gap three hours ago only counts towards the day
+        qc.record_gap_at(&btc, now - Duration::hours(3));
+        qc.record_gap_at(&eth, now);
+
+        let quality = qc.symbol_quality_at(&btc, Utc::now()).unwrap();
+        assert_eq!(quality.last_hour.updates, 21);
+        assert_eq!(quality.last_hour.rejected, 1);
+        assert_eq!(quality.last_hour.gaps, 1);
+        assert_eq!(quality.last_day.gaps, 2);
+        let expected = 100.0 - 100.0 / 21.0 - 10.0;
+        assert!((quality.score.unwrap() - expected).abs() < 1e-9);
+        assert!(quality.last_day.score.unwrap() > quality.score.unwrap());
+
+        // Gaps without updates leave nothing to score
+        let eth_quality = qc.symbol_quality_at(&eth, now).unwrap();
+        assert_eq!(eth_quality.score, None);
+
+        // Everything ages out after a day
+        let later = qc
+            .symbol_quality_at(&btc, now + Duration::days(1) + Duration::minutes(2))
+            .unwrap();
+        assert_eq!(later.last_day.updates, 0);
+        assert_eq!(qc.symbol_qualities().len(), 2);
+    }
+
     #[test]
     fn test_quality_stats() {
         let qc = QualityControl::default();
@@ -0,0 +1,369 @@
+//! Raw market data recording
+//!
+//! Archives every raw WebSocket frame, with its receive timestamp, to
+//! gzip-compressed JSON-lines segment files, and loads those captures back
+//! for replay through the message parser. Captures are useful for debugging
+//! parse failures and for turning production incidents into regression
+//! fixtures. Closed segments are immutable and can be synced to object
+//! storage as-is.
+
+use crate::error::{Error, Result};
+use chrono::{DateTime, Utc};
+use ea_okx_client::models::WebSocketEvent;
+use ea_okx_client::websocket::RawFrame;
+use flate2::Compression;
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// Segment file extension
+const SEGMENT_EXTENSION: &str = ".jsonl.gz";
+
+/// Raw feed recording configuration
+#[derive(Debug, Clone)]
+pub struct RawFeedConfig {
+    /// Directory segment files are written to
+    pub directory: PathBuf,
+
+    /// Rotate after this many uncompressed bytes
+    pub max_segment_bytes: u64,
+
+    /// Rotate after this many seconds
+    pub max_segment_secs: i64,
+}
+
+impl RawFeedConfig {
+    /// Record into `directory` with default rotation
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for RawFeedConfig {
+    fn default() -> Self {
+        Self {
+            directory: PathBuf::from("data/raw_feed"),
+            max_segment_bytes: 256 * 1024 * 1024,
+            max_segment_secs: 3600,
+        }
+    }
+}
+
+/// Open segment being written
+struct Segment {
+    path: PathBuf,
+    encoder: GzEncoder<BufWriter<File>>,
+    opened_at: DateTime<Utc>,
+    bytes: u64,
+}
+
+/// Writes raw frames to rotating compressed segment files
+pub struct RawFeedRecorder {
+    config: RawFeedConfig,
+    current: Option<Segment>,
+    sequence: u64,
+    frames_recorded: u64,
+}
+
+impl RawFeedRecorder {
+    /// Create a recorder, creating the capture directory if needed
+    pub fn new(config: RawFeedConfig) -> Result<Self> {
+        fs::create_dir_all(&config.directory).map_err(|e| {
+            Error::ConfigError(format!(
+                "Failed to create capture directory {}: {}",
+                config.directory.display(),
+                e
+            ))
+        })?;
+
+        Ok(Self {
+            config,
+            current: None,
+            sequence: 0,
+            frames_recorded: 0,
+        })
+    }
+
+    /// Append a frame, rotating the segment if it is full or too old
+    pub fn record(&mut self, frame: &RawFrame) -> Result<()> {
+        let now = Utc::now();
+        let rotate = self.current.as_ref().is_some_and(|segment| {
+            segment.bytes >= self.config.max_segment_bytes
+                || (now - segment.opened_at).num_seconds() >= self.config.max_segment_secs
+        });
+        if rotate {
+            self.finish()?;
+        }
+
+        if self.current.is_none() {
+            self.current = Some(self.open_segment(now)?);
+        }
+
+        let mut line = serde_json::to_vec(frame)?;
+        line.push(b'\n');
+
+        let segment = self.current.as_mut().expect("segment opened above");
+        segment.encoder.write_all(&line).map_err(io_error)?;
+        segment.bytes += line.len() as u64;
+        self.frames_recorded += 1;
+
+        Ok(())
+    }
+
+    /// Close the current segment, returning its path
+    pub fn finish(&mut self) -> Result<Option<PathBuf>> {
+        let Some(segment) = self.current.take() else {
+            return Ok(None);
+        };
+
+        let mut writer = segment.encoder.finish().map_err(io_error)?;
+        writer.flush().map_err(io_error)?;
+
+        info!(
+            "Closed raw feed segment {} ({} bytes uncompressed)",
+            segment.path.display(),
+            segment.bytes
+        );
+        Ok(Some(segment.path))
+    }
+
+    /// Total frames recorded by this recorder
+    pub fn frames_recorded(&self) -> u64 {
+        self.frames_recorded
+    }
+
+    /// Record frames from `rx` on a blocking thread until the channel closes
+    pub fn spawn(mut self, mut rx: mpsc::UnboundedReceiver<RawFrame>) -> JoinHandle<Result<u64>> {
+        tokio::task::spawn_blocking(move || {
+            while let Some(frame) = rx.blocking_recv() {
+                if let Err(e) = self.record(&frame) {
+                    warn!("Failed to record raw frame: {}", e);
+                }
+            }
+            self.finish()?;
+            Ok(self.frames_recorded)
+        })
+    }
+
+    fn open_segment(&mut self, now: DateTime<Utc>) -> Result<Segment> {
+        self.sequence += 1;
+        let path = self.config.directory.join(format!(
+            "okx-raw-{}-{:04}{}",
+            now.format("%Y%m%dT%H%M%S"),
+            self.sequence,
+            SEGMENT_EXTENSION
+        ));
+
+        let file = File::create(&path).map_err(io_error)?;
+        Ok(Segment {
+            path,
+            encoder: GzEncoder::new(BufWriter::new(file), Compression::default()),
+            opened_at: now,
+            bytes: 0,
+        })
+    }
+}
+
+impl Drop for RawFeedRecorder {
+    fn drop(&mut self) {
+        if let Err(e) = self.finish() {
+            warn!("Failed to close raw feed segment: {}", e);
+        }
+    }
+}
+
+/// A captured frame together with the parser's verdict
+#[derive(Debug)]
+pub struct ReplayedFrame {
+    pub frame: RawFrame,
+    pub event: std::result::Result<WebSocketEvent, String>,
+}
+
+/// Load frames from a segment file, or from every segment in a directory
+///
+/// A segment cut short by a crash yields the frames written before the cut.
+pub fn load_capture(path: impl AsRef<Path>) -> Result<Vec<RawFrame>> {
+    let path = path.as_ref();
+    let mut frames = Vec::new();
+    for segment in segment_files(path)? {
+        read_segment(&segment, &mut frames)?;
+    }
+    Ok(frames)
+}
+
+/// Load a capture and run every frame back through the WebSocket parser
+pub fn replay_capture(path: impl AsRef<Path>) -> Result<Vec<ReplayedFrame>> {
+    Ok(load_capture(path)?
+        .into_iter()
+        .map(|frame| {
+            let event = serde_json::from_str(&frame.payload)
+                .map_err(|e| format!("Invalid JSON: {}", e))
+                .and_then(|value| WebSocketEvent::from_json(&value).map_err(|e| e.to_string()));
+            ReplayedFrame { frame, event }
+        })
+        .collect())
+}
+
+/// Segment files under `path`, oldest first
+fn segment_files(path: &Path) -> Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut files: Vec<PathBuf> = fs::read_dir(path)
+        .map_err(io_error)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.ends_with(SEGMENT_EXTENSION))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+fn read_segment(path: &Path, frames: &mut Vec<RawFrame>) -> Result<()> {
+    let file = File::open(path).map_err(io_error)?;
+    let reader = BufReader::new(MultiGzDecoder::new(file));
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                warn!(
+                    "Capture {} truncated after {} frames: {}",
+                    path.display(),
+                    index,
+                    e
+                );
+                break;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(frame) => frames.push(frame),
+            Err(e) => {
+                warn!(
+                    "Capture {} has a corrupt frame at line {}: {}",
+                    path.display(),
+                    index + 1,
+                    e
+                );
+                break;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn io_error(e: std::io::Error) -> Error {
+    Error::Internal(format!("Raw feed I/O error: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ea_okx_client::websocket::FrameSource;
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("raw-feed-{}", uuid::Uuid::new_v4()))
+    }
+
+    fn frame(payload: &str) -> RawFrame {
+        RawFrame {
+            received_at: Utc::now(),
+            source: FrameSource::Public,
+            payload: payload.to_string(),
+        }
+    }
+
+    const TICKER: &str = r#"{"arg":{"channel":"tickers","instId":"BTC-USDT"},"data":{"instType":"SPOT","instId":"BTC-USDT","last":"43000.5","lastSz":"0.1","askPx":"43001","askSz":"1","bidPx":"43000","bidSz":"1","open24h":"42000","high24h":"44000","low24h":"41000","volCcy24h":"1000","vol24h":"10","ts":"1700000000000","sodUtc0":"42500","sodUtc8":"42600"}}"#;
+
+    #[test]
+    fn test_record_and_replay_round_trip() {
+        let dir = temp_dir();
+        let mut recorder = RawFeedRecorder::new(RawFeedConfig::new(&dir)).unwrap();
+        recorder.record(&frame(TICKER)).unwrap();
+        recorder.record(&frame("{not json")).unwrap();
+        recorder.finish().unwrap();
+        assert_eq!(recorder.frames_recorded(), 2);
+
+        let replayed = replay_capture(&dir).unwrap();
+        assert_eq!(replayed.len(), 2);
+        assert_eq!(replayed[0].frame.payload, TICKER);
+        assert!(matches!(replayed[0].event, Ok(WebSocketEvent::Ticker(_))));
+        assert!(replayed[1].event.is_err());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_rotation_by_size() {
+        let dir = temp_dir();
+        let config = RawFeedConfig {
+            directory: dir.clone(),
+            max_segment_bytes: 1,
+            ..Default::default()
+        };
+        let mut recorder = RawFeedRecorder::new(config).unwrap();
+        for i in 0..3 {
+            recorder.record(&frame(&format!("frame-{}", i))).unwrap();
+        }
+        recorder.finish().unwrap();
+
+        assert_eq!(segment_files(&dir).unwrap().len(), 3);
+        let frames = load_capture(&dir).unwrap();
+        let payloads: Vec<_> = frames.iter().map(|f| f.payload.as_str()).collect();
+        assert_eq!(payloads, vec!["frame-0", "frame-1", "frame-2"]);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_truncated_segment_yields_complete_frames() {
+        let dir = temp_dir();
+        let mut recorder = RawFeedRecorder::new(RawFeedConfig::new(&dir)).unwrap();
+        for i in 0..50 {
+            recorder.record(&frame(&format!("frame-{}", i))).unwrap();
+        }
+        let path = recorder.finish().unwrap().unwrap();
+
+        let bytes = fs::read(&path).unwrap();
+        fs::write(&path, &bytes[..bytes.len() - 8]).unwrap();
+
+        let frames = load_capture(&path).unwrap();
+        assert!(frames.len() <= 50);
+        assert!(frames.iter().all(|f| f.payload.starts_with("frame-")));
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_spawned_recorder_drains_channel() {
+        let dir = temp_dir();
+        let recorder = RawFeedRecorder::new(RawFeedConfig::new(&dir)).unwrap();
+        let (tx, rx) = mpsc::unbounded_channel();
+        let handle = recorder.spawn(rx);
+
+        tx.send(frame(TICKER)).unwrap();
+        tx.send(frame(TICKER)).unwrap();
+        drop(tx);
+
+        assert_eq!(handle.await.unwrap().unwrap(), 2);
+        assert_eq!(load_capture(&dir).unwrap().len(), 2);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,537 @@
+//! Blended multi-source reference prices
+//!
+//! A single venue's last trade is a poor yardstick for risk checks: one bad
+//! OKX print would move the baseline that bad prints are judged against.
+//! [`ReferencePriceService`] polls several [`PriceSource`]s (OKX index and
+//! mark prices, plus external HTTP tickers), drops quotes that stray too far
+//! from the cross-source median and publishes the median of the rest. The
+//! result is pushed into [`QualityControl`] and emitted to subscribers such
+//! as the fat-finger guard.
+
+use crate::error::{Error, Result};
+use crate::quality::QualityControl;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use ea_okx_client::OkxRestClient;
+use ea_okx_core::types::Symbol;
+use parking_lot::RwLock;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+/// Quotes needed before any of them can be rejected as an outlier
+pub const MIN_SOURCES_FOR_OUTLIERS: usize = 3;
+
+/// A price observed at one source
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceQuote {
+    pub source: String,
+    pub price: Decimal,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Somewhere a price for a symbol can be fetched from
+#[async_trait]
+pub trait PriceSource: Send + Sync {
+    /// Short name used in reports (e.g., "okx-index")
+    fn name(&self) -> &str;
+
+    async fn fetch_price(&self, symbol: &Symbol) -> Result<SourceQuote>;
+}
+
+/// Which OKX price to use as a source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OkxPriceKind {
+    /// Index price aggregated by OKX across spot venues
+    Index,
+    /// Mark price of the USDT-margined perpetual swap
+    Mark,
+}
+
+/// OKX index or mark price
+pub struct OkxPriceSource {
+    client: Arc<OkxRestClient>,
+    kind: OkxPriceKind,
+}
+
+impl OkxPriceSource {
+    pub fn new(client: Arc<OkxRestClient>, kind: OkxPriceKind) -> Self {
+        Self { client, kind }
+    }
+}
+
+#[async_trait]
+impl PriceSource for OkxPriceSource {
+    fn name(&self) -> &str {
+        match self.kind {
+            OkxPriceKind::Index => "okx-index",
+            OkxPriceKind::Mark => "okx-mark",
+        }
+    }
+
+    async fn fetch_price(&self, symbol: &Symbol) -> Result<SourceQuote> {
+        let (price, ts) = match self.kind {
+            OkxPriceKind::Index => {
+                let ticker = self.client.index_ticker(symbol.as_str()).await?;
+                (ticker.idx_px, ticker.ts)
+            }
+            OkxPriceKind::Mark => {
+                let swap = format!("{}-SWAP", symbol.as_str());
+                let mark = self.client.mark_price("SWAP", &swap).await?;
+                (mark.mark_px, mark.ts)
+            }
+        };
+
+        Ok(SourceQuote {
+            source: self.name().to_string(),
+            price: parse_price(self.name(), &price)?,
+            timestamp: parse_millis(&ts).unwrap_or_else(Utc::now),
+        })
+    }
+}
+
+/// Ticker from an arbitrary HTTP JSON endpoint
+///
+/// The URL template may contain `{symbol}` (`BTC-USDT`), `{base}`, `{quote}`
+/// and `{compact}` (`BTCUSDT`). The price is read from the response with a
+/// JSON pointer and may be a number or a numeric string.
+pub struct HttpTickerSource {
+    name: String,
+    url_template: String,
+    price_pointer: String,
+    http: reqwest::Client,
+}
+
+impl HttpTickerSource {
+    pub fn new(
+        name: impl Into<String>,
+        url_template: impl Into<String>,
+        price_pointer: impl Into<String>,
+    ) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(5))
+            .build()
+            .map_err(|e| Error::ConfigError(format!("HTTP client: {}", e)))?;
+        Ok(Self {
+            name: name.into(),
+            url_template: url_template.into(),
+            price_pointer: price_pointer.into(),
+            http,
+        })
+    }
+
+    fn url(&self, symbol: &Symbol) -> String {
+        self.url_template
+            .replace("{symbol}", symbol.as_str())
+            .replace("{base}", symbol.base())
+            .replace("{quote}", symbol.quote())
+            .replace("{compact}", &format!("{}{}", symbol.base(), symbol.quote()))
+    }
+}
+
+#[async_trait]
+impl PriceSource for HttpTickerSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn fetch_price(&self, symbol: &Symbol) -> Result<SourceQuote> {
+        let url = self.url(symbol);
+        let body: JsonValue = self
+            .http
+            .get(&url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| Error::Internal(format!("{}: {}", self.name, e)))?
+            .json()
+            .await
+            .map_err(|e| Error::ParseError(format!("{}: {}", self.name, e)))?;
+
+        let price = match body.pointer(&self.price_pointer) {
+            Some(JsonValue::String(s)) => parse_price(&self.name, s)?,
+            Some(JsonValue::Number(n)) => parse_price(&self.name, &n.to_string())?,
+            _ => {
+                return Err(Error::ParseError(format!(
+                    "{}: no price at {} in response from {}",
+                    self.name, self.price_pointer, url
+                )));
+            }
+        };
+
+        Ok(SourceQuote {
+            source: self.name.clone(),
+            price,
+            timestamp: Utc::now(),
+        })
+    }
+}
+
+/// Blending configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReferenceConfig {
+    /// Quotes further than this from the median are rejected (e.g., 0.01 = 1%)
+    pub max_deviation: Decimal,
+
+    /// Accepted quotes required to publish a reference
+    pub min_sources: usize,
+
+    /// Quotes older than this are ignored
+    pub max_quote_age_secs: i64,
+
+    /// Polling interval of [`ReferencePriceService::start`]
+    pub refresh_interval_secs: u64,
+}
+
+impl Default for ReferenceConfig {
+    fn default() -> Self {
+        Self {
+            max_deviation: Decimal::new(1, 2), // 1%
+            min_sources: 1,
+            max_quote_age_secs: 30,
+            refresh_interval_secs: 5,
+        }
+    }
+}
+
+/// A quote dropped as an outlier
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RejectedQuote {
+    pub quote: SourceQuote,
+
+    /// Distance from the cross-source median
+    pub deviation: Decimal,
+}
+
+/// Blended reference price for a symbol
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReferencePrice {
+    pub symbol: Symbol,
+    pub price: Decimal,
+    pub accepted: Vec<SourceQuote>,
+    pub rejected: Vec<RejectedQuote>,
+
+    /// Sources that could not be fetched or were stale
+    pub unavailable: Vec<String>,
+    pub computed_at: DateTime<Utc>,
+}
+
+/// Median of the quotes, then median of those within `max_deviation` of it
+///
+/// Outliers are only rejected when at least [`MIN_SOURCES_FOR_OUTLIERS`]
+/// fresh quotes are available; with fewer, every fresh quote is accepted.
+pub fn blend(
+    symbol: &Symbol,
+    quotes: Vec<SourceQuote>,
+    config: &ReferenceConfig,
+) -> Result<ReferencePrice> {
+    let now = Utc::now();
+    let max_age = Duration::seconds(config.max_quote_age_secs);
+    let (fresh, stale): (Vec<_>, Vec<_>) = quotes
+        .into_iter()
+        .filter(|q| q.price > Decimal::ZERO)
+        .partition(|q| now - q.timestamp <= max_age);
+    let unavailable = stale.into_iter().map(|q| q.source).collect();
+
+    let center = median(fresh.iter().map(|q| q.price).collect()).ok_or_else(|| {
+        Error::ValidationError(format!("No fresh reference quotes for {}", symbol.as_str()))
+    })?;
+
+    // With fewer than three quotes the median is no consensus: two sources
+    // that disagree leave no way to tell which one is wrong
+    let judge_outliers = fresh.len() >= MIN_SOURCES_FOR_OUTLIERS;
+
+    let mut accepted = Vec::new();
+    let mut rejected = Vec::new();
+    for quote in fresh {
+        let deviation = (quote.price - center).abs() / center;
+        if judge_outliers && deviation > config.max_deviation {
+            warn!(
+                "Rejecting {} quote {} for {}: {:.2}% from median {}",
+                quote.source,
+                quote.price,
+                symbol.as_str(),
+                deviation * Decimal::ONE_HUNDRED,
+                center
+            );
+            rejected.push(RejectedQuote { quote, deviation });
+        } else {
+            accepted.push(quote);
+        }
+    }
+
+    if accepted.len() < config.min_sources.max(1) {
+        return Err(Error::ValidationError(format!(
+            "Only {} of {} reference sources agree for {}",
+            accepted.len(),
+            accepted.len() + rejected.len(),
+            symbol.as_str()
+        )));
+    }
+
+    let price = median(accepted.iter().map(|q| q.price).collect()).unwrap_or(center);
+    Ok(ReferencePrice {
+        symbol: symbol.clone(),
+        price,
+        accepted,
+        rejected,
+        unavailable,
+        computed_at: now,
+    })
+}
+
+fn median(mut values: Vec<Decimal>) -> Option<Decimal> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort();
+    let mid = values.len() / 2;
+    Some(if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / Decimal::TWO
+    } else {
+        values[mid]
+    })
+}
+
+fn parse_price(source: &str, value: &str) -> Result<Decimal> {
+    value
+        .parse()
+        .map_err(|e| Error::ParseError(format!("{}: invalid price '{}': {}", source, value, e)))
+}
+
+fn parse_millis(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp_millis(value.parse().ok()?)
+}
+
+/// Polls price sources and publishes blended reference prices
+pub struct ReferencePriceService {
+    config: ReferenceConfig,
+    sources: Vec<Arc<dyn PriceSource>>,
+    quality: Option<Arc<QualityControl>>,
+    latest: RwLock<HashMap<Symbol, ReferencePrice>>,
+
+    /// Event channel
+    event_tx: mpsc::UnboundedSender<ReferencePrice>,
+    event_rx: RwLock<Option<mpsc::UnboundedReceiver<ReferencePrice>>>,
+}
+
+impl ReferencePriceService {
+    pub fn new(config: ReferenceConfig, sources: Vec<Arc<dyn PriceSource>>) -> Self {
+        let (event_tx, event_rx) = mpsc::unbounded_channel();
+        Self {
+            config,
+            sources,
+            quality: None,
+            latest: RwLock::new(HashMap::new()),
+            event_tx,
+            event_rx: RwLock::new(Some(event_rx)),
+        }
+    }
+
+    /// Push every new reference price into a quality control instance
+    pub fn with_quality_control(mut self, quality: Arc<QualityControl>) -> Self {
+        self.quality = Some(quality);
+        self
+    }
+
+    pub fn source_names(&self) -> Vec<String> {
+        self.sources.iter().map(|s| s.name().to_string()).collect()
+    }
+
+    /// Last published reference for `symbol`
+    pub fn latest(&self, symbol: &Symbol) -> Option<ReferencePrice> {
+        self.latest.read().get(symbol).cloned()
+    }
+
+    /// Fetch every source, blend and publish a new reference for `symbol`
+    pub async fn refresh(&self, symbol: &Symbol) -> Result<ReferencePrice> {
+        let results =
+            futures::future::join_all(self.sources.iter().map(|source| source.fetch_price(symbol)))
+                .await;
+
+        let mut quotes = Vec::new();
+        let mut failed = Vec::new();
+        for (source, result) in self.sources.iter().zip(results) {
+            match result {
+                Ok(quote) => quotes.push(quote),
+                Err(e) => {
+                    debug!(
+                        "Reference source {} failed for {}: {}",
+                        source.name(),
+                        symbol.as_str(),
+                        e
+                    );
+                    failed.push(source.name().to_string());
+                }
+            }
+        }
+
+        let mut reference = blend(symbol, quotes, &self.config)?;
+        reference.unavailable.extend(failed);
+
+        if let Some(quality) = &self.quality {
+            quality.set_reference_price(symbol, reference.price, reference.computed_at);
+        }
+        self.latest
+            .write()
+            .insert(symbol.clone(), reference.clone());
+        let _ = self.event_tx.send(reference.clone());
+        Ok(reference)
+    }
+
+    /// Refresh `symbols` every `refresh_interval_secs` until the task is aborted
+    pub fn start(self: Arc<Self>, symbols: Vec<Symbol>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(std::time::Duration::from_secs(
+                self.config.refresh_interval_secs.max(1),
+            ));
+            loop {
+                ticker.tick().await;
+                for symbol in &symbols {
+                    if let Err(e) = self.refresh(symbol).await {
+                        warn!(
+                            "Reference price refresh failed for {}: {}",
+                            symbol.as_str(),
+                            e
+                        );
+                    }
+                }
+            }
+        })
+    }
+
+    /// Get event receiver (can only be called once)
+    pub fn subscribe_events(&self) -> Option<mpsc::UnboundedReceiver<ReferencePrice>> {
+        self.event_rx.write().take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    struct FixedSource {
+        name: &'static str,
+        price: Option<Decimal>,
+    }
+
+    #[async_trait]
+    impl PriceSource for FixedSource {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        async fn fetch_price(&self, _symbol: &Symbol) -> Result<SourceQuote> {
+            let price = self
+                .price
+                .ok_or_else(|| Error::Internal("source down".to_string()))?;
+            Ok(SourceQuote {
+                source: self.name.to_string(),
+                price,
+                timestamp: Utc::now(),
+            })
+        }
+    }
+
+    fn source(name: &'static str, price: Option<Decimal>) -> Arc<dyn PriceSource> {
+        Arc::new(FixedSource { name, price })
+    }
+
+    fn quote(source: &str, price: Decimal) -> SourceQuote {
+        SourceQuote {
+            source: source.to_string(),
+            price,
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_blend_rejects_outlier() {
+        let symbol = Symbol::new("BTC-USDT").unwrap();
+        let quotes = vec![
+            quote("okx-index", dec!(50000)),
+            quote("okx-mark", dec!(50020)),
+            quote("external", dec!(50010)),
+            quote("bad", dec!(45000)),
+        ];
+
+        let reference = blend(&symbol, quotes, &ReferenceConfig::default()).unwrap();
+        assert_eq!(reference.price, dec!(50010));
+        assert_eq!(reference.accepted.len(), 3);
+        assert_eq!(reference.rejected[0].quote.source, "bad");
+    }
+
+    #[test]
+    fn test_blend_fails_when_sources_disagree_or_are_stale() {
+        let symbol = Symbol::new("BTC-USDT").unwrap();
+        let config = ReferenceConfig {
+            min_sources: 2,
+            ..Default::default()
+        };
+
+        let split = vec![
+            quote("a", dec!(50000)),
+            quote("b", dec!(52000)),
+            quote("c", dec!(54000)),
+        ];
+        assert!(blend(&symbol, split, &config).is_err());
+
+        let mut stale = quote("a", dec!(50000));
+        stale.timestamp = Utc::now() - Duration::minutes(5);
+        assert!(blend(&symbol, vec![stale], &config).is_err());
+    }
+
+    #[test]
+    fn test_blend_needs_three_sources_to_reject_outliers() {
+        let symbol = Symbol::new("BTC-USDT").unwrap();
+        let config = ReferenceConfig::default();
+
+        // Two sources 4% apart: neither can be called the outlier
+        let pair = vec![
+            quote("okx-index", dec!(50000)),
+            quote("external", dec!(52000)),
+        ];
+        let reference = blend(&symbol, pair, &config).unwrap();
+        assert_eq!(reference.price, dec!(51000));
+        assert!(reference.rejected.is_empty());
+
+        let three = vec![
+            quote("okx-index", dec!(50000)),
+            quote("okx-mark", dec!(50010)),
+            quote("external", dec!(52000)),
+        ];
+        let reference = blend(&symbol, three, &config).unwrap();
+        assert_eq!(reference.price, dec!(50005));
+        assert_eq!(reference.rejected[0].quote.source, "external");
+    }
+
+    #[tokio::test]
+    async fn test_refresh_publishes_to_quality_control_and_subscribers() {
+        let symbol = Symbol::new("BTC-USDT").unwrap();
+        let quality = Arc::new(QualityControl::default());
+        let service = ReferencePriceService::new(
+            ReferenceConfig::default(),
+            vec![
+                source("okx-index", Some(dec!(50000))),
+                source("external", Some(dec!(50100))),
+                source("down", None),
+            ],
+        )
+        .with_quality_control(quality.clone());
+        let mut events = service.subscribe_events().unwrap();
+
+        let reference = service.refresh(&symbol).await.unwrap();
+        assert_eq!(reference.price, dec!(50050));
+        assert_eq!(reference.unavailable, vec!["down".to_string()]);
+        assert_eq!(quality.reference_price(&symbol), Some(dec!(50050)));
+        assert_eq!(events.recv().await.unwrap(), reference);
+        assert_eq!(service.latest(&symbol), Some(reference));
+    }
+}
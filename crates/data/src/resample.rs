@@ -0,0 +1,182 @@
+//! Candles of one interval merged into a longer one
+//!
+//! Only some intervals are stored, often just `1m`, while charts want any
+//! timeframe. [`resample_candles`] builds longer bars from stored ones on
+//! the fly: the first open, the highest high, the lowest low and the last
+//! close of the bars inside each longer bar, with volumes, quote volumes and
+//! trade counts summed. A longer bar is built from whatever shorter bars
+//! exist, so gaps in the source leave it with less volume rather than
+//! dropping it, and the latest one may still be forming.
+
+use crate::error::{Error, Result};
+use crate::storage::Candle;
+use chrono::{DateTime, Duration, Utc};
+use ea_okx_core::Interval;
+use ea_okx_core::types::Quantity;
+use rust_decimal::Decimal;
+
+/// Whether bars of `source` fit exactly into bars of `target`
+///
+/// `target` must be a whole number of `source` bars and open on one of
+/// `source`'s boundaries, so `1m` resamples into `3m`, `1H` or `1M` but `4H`
+/// does not resample into `6H`, nor Hong Kong anchored `6H` into `1Dutc`.
+pub fn can_resample(source: Interval, target: Interval) -> bool {
+    let Some(length) = source.duration() else {
+        return false;
+    };
+    // Month bars open at midnight, so source bars have to tile a day
+    let period = target.duration().unwrap_or(Duration::days(1));
+    if period < length || period.num_milliseconds() % length.num_milliseconds() != 0 {
+        return false;
+    }
+
+    let boundary = target.bar_start(DateTime::<Utc>::UNIX_EPOCH);
+    source.bar_start(boundary) == boundary
+}
+
+/// Merge `candles` of one symbol and interval into bars of `target`,
+/// oldest first
+///
+/// Input order does not matter; a repeated timestamp keeps its last candle.
+pub fn resample_candles(candles: &[Candle], target: Interval) -> Result<Vec<Candle>> {
+    let Some(first) = candles.first() else {
+        return Ok(Vec::new());
+    };
+    if let Some(other) = candles
+        .iter()
+        .find(|c| c.symbol != first.symbol || c.interval != first.interval)
+    {
+        return Err(Error::ValidationError(format!(
+            "Cannot resample {} {} candles together with {} {}",
+            first.symbol.as_str(),
+            first.interval,
+            other.symbol.as_str(),
+            other.interval
+        )));
+    }
+    if !can_resample(first.interval, target) {
+        return Err(Error::ValidationError(format!(
+            "{} candles cannot be resampled into {}",
+            first.interval, target
+        )));
+    }
+
+    let mut sorted: Vec<&Candle> = candles.iter().collect();
+    sorted.sort_by_key(|c| c.timestamp);
+    sorted.dedup_by(|later, earlier| {
+        let repeated = later.timestamp == earlier.timestamp;
+        if repeated {
+            *earlier = *later;
+        }
+        repeated
+    });
+
+    let mut resampled: Vec<Candle> = Vec::new();
+    for candle in sorted {
+        let start = target.bar_start(candle.timestamp);
+        match resampled.last_mut() {
+            Some(bar) if bar.timestamp == start => merge(bar, candle)?,
+            _ => resampled.push(Candle {
+                timestamp: start,
+                interval: target,
+                ..candle.clone()
+            }),
+        }
+    }
+    for bar in &mut resampled {
+        bar.vwap = vwap(bar);
+    }
+    Ok(resampled)
+}
+
+fn merge(bar: &mut Candle, candle: &Candle) -> Result<()> {
+    bar.high = bar.high.max(candle.high);
+    bar.low = bar.low.min(candle.low);
+    bar.close = candle.close;
+    bar.volume = Quantity::new(bar.volume.as_decimal() + candle.volume.as_decimal())?;
+    bar.quote_volume += candle.quote_volume;
+    bar.trade_count = bar.trade_count.saturating_add(candle.trade_count);
+    Ok(())
+}
+
+/// Volume-weighted price of a merged bar; unknown without quote volume
+fn vwap(bar: &Candle) -> Option<Decimal> {
+    let volume = bar.volume.as_decimal();
+    (!volume.is_zero() && !bar.quote_volume.is_zero()).then(|| bar.quote_volume / volume)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ea_okx_core::types::{Price, Symbol};
+    use rust_decimal_macros::dec;
+
+    fn candle(minute: i64, open: Decimal, high: Decimal, low: Decimal, close: Decimal) -> Candle {
+        let price = |p| Price::new(p).unwrap();
+        Candle {
+            symbol: Symbol::new("BTC-USDT").unwrap(),
+            timestamp: "2024-01-02T00:00:00Z".parse::<DateTime<Utc>>().unwrap()
+                + Duration::minutes(minute),
+            interval: Interval::OneMinute,
+            open: price(open),
+            high: price(high),
+            low: price(low),
+            close: price(close),
+            volume: Quantity::new(dec!(2)).unwrap(),
+            quote_volume: close * dec!(2),
+            trade_count: 3,
+            vwap: Some(close),
+        }
+    }
+
+    #[test]
+    fn test_can_resample() {
+        assert!(can_resample(Interval::OneMinute, Interval::OneMinute));
+        assert!(can_resample(Interval::OneMinute, Interval::FiveMinutes));
+        assert!(can_resample(Interval::OneMinute, Interval::OneDay));
+        assert!(can_resample(Interval::OneHour, Interval::OneMonth));
+        assert!(can_resample(Interval::OneDay, Interval::OneWeek));
+        assert!(can_resample(Interval::OneDayUtc, Interval::OneWeekUtc));
+        assert!(!can_resample(Interval::FiveMinutes, Interval::OneMinute));
+        assert!(!can_resample(Interval::ThreeMinutes, Interval::FiveMinutes));
+        assert!(!can_resample(Interval::FourHours, Interval::SixHours));
+        assert!(!can_resample(Interval::SixHours, Interval::OneDayUtc));
+        assert!(!can_resample(Interval::OneWeek, Interval::OneMonth));
+        assert!(!can_resample(Interval::OneMonth, Interval::ThreeMonths));
+    }
+
+    #[test]
+    fn test_resample_merges_ohlcv() {
+        // Out of order, with a gap at minute 3 and a second five-minute bar
+        let candles = vec![
+            candle(1, dec!(101), dec!(104), dec!(100), dec!(103)),
+            candle(0, dec!(100), dec!(102), dec!(99), dec!(101)),
+            candle(4, dec!(103), dec!(103), dec!(97), dec!(98)),
+            candle(2, dec!(103), dec!(106), dec!(102), dec!(105)),
+            candle(5, dec!(98), dec!(99), dec!(96), dec!(97)),
+        ];
+        let bars = resample_candles(&candles, Interval::FiveMinutes).unwrap();
+        assert_eq!(bars.len(), 2);
+
+        let bar = &bars[0];
+        assert_eq!(bar.timestamp, candles[1].timestamp);
+        assert_eq!(bar.interval, Interval::FiveMinutes);
+        assert_eq!(bar.open.as_decimal(), dec!(100));
+        assert_eq!(bar.high.as_decimal(), dec!(106));
+        assert_eq!(bar.low.as_decimal(), dec!(97));
+        assert_eq!(bar.close.as_decimal(), dec!(98));
+        assert_eq!(bar.volume.as_decimal(), dec!(8));
+        assert_eq!(bar.quote_volume, dec!(814));
+        assert_eq!(bar.trade_count, 12);
+        assert_eq!(bar.vwap, Some(dec!(101.75)));
+
+        assert_eq!(bars[1].open.as_decimal(), dec!(98));
+        assert_eq!(bars[1].trade_count, 3);
+
+        let mut mixed = candles.clone();
+        mixed[0].interval = Interval::ThreeMinutes;
+        assert!(resample_candles(&mixed, Interval::FiveMinutes).is_err());
+        assert!(resample_candles(&candles, Interval::ThirtyMinutes).is_ok());
+        assert!(resample_candles(&[], Interval::OneHour).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,437 @@
+//! TimescaleDB schema migrations
+//!
+//! The SQL files in the repository's `migrations/` directory are embedded
+//! in the crate and applied in version order by [`SchemaMigrator::run`],
+//! normally on startup. Each applied migration is recorded in
+//! `schema_migrations` with a SHA-256 checksum of its SQL, so a file edited
+//! after it was applied is reported instead of silently diverging.
+//!
+//! Migrations run in a transaction unless the file contains a
+//! `-- migrate:no-transaction` line, which TimescaleDB needs for statements
+//! such as creating continuous aggregates. Databases created before
+//! migrations were tracked (e.g. by loading the files with `psql`) are
+//! adopted with [`SchemaMigrator::with_baseline`].
+//!
+//! The strategy store keeps its own migrations (see
+//! [`crate::strategy_store`]): it is usually a local SQLite file rather
+//! than this database, and the advisory lock, TimescaleDB statements and
+//! non-transactional migrations here are PostgreSQL-only.
+
+use crate::error::{Error, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{Connection, PgConnection, PgPool, Row};
+use tracing::{info, warn};
+
+/// Versioned schema migrations, oldest first
+const MIGRATIONS: &[(i64, &str, &str)] = &[
+    (
+        1,
+        "initial schema",
+        include_str!("../../../migrations/001_initial_schema.sql"),
+    ),
+    (
+        2,
+        "continuous aggregates",
+        include_str!("../../../migrations/002_continuous_aggregates.sql"),
+    ),
+    (
+        3,
+        "funding rates",
+        include_str!("../../../migrations/003_funding_rates.sql"),
+    ),
+    (
+        4,
+        "order book blocks",
+        include_str!("../../../migrations/004_order_book_blocks.sql"),
+    ),
+    (
+        5,
+        "candle checksums",
+        include_str!("../../../migrations/005_candle_checksums.sql"),
+    ),
+    (
+        6,
+        "account equity",
+        include_str!("../../../migrations/006_account_equity.sql"),
+    ),
+    (
+        7,
+        "positioning stats",
+        include_str!("../../../migrations/007_positioning_stats.sql"),
+    ),
+    (
+        8,
+        "orders and alerts",
+        include_str!("../../../migrations/008_orders_and_alerts.sql"),
+    ),
+    (
+        9,
+        "tick retention",
+        include_str!("../../../migrations/009_tick_retention.sql"),
+    ),
+    (
+        10,
+        "candle intervals",
+        include_str!("../../../migrations/010_candle_intervals.sql"),
+    ),
+];
+
+/// Marks a migration that must not run inside a transaction
+const NO_TRANSACTION: &str = "-- migrate:no-transaction";
+
+/// Advisory lock serializing migrators sharing a database
+const MIGRATION_LOCK: i64 = 0x6561_6f6b_785f_6462;
+
+/// Applied and pending migrations of a database
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SchemaVersion {
+    /// Newest applied migration, `None` for an untracked database
+    pub current: Option<i64>,
+    /// Newest migration this build knows
+    pub latest: i64,
+    /// Known migrations not applied yet
+    pub pending: Vec<i64>,
+    /// When the newest migration was applied
+    pub applied_at: Option<DateTime<Utc>>,
+}
+
+impl SchemaVersion {
+    /// Whether every known migration has been applied
+    pub fn is_current(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+/// What a migration run did
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MigrationReport {
+    /// Migrations recorded as applied without running them
+    pub baselined: Vec<i64>,
+    /// Migrations run
+    pub applied: Vec<i64>,
+}
+
+/// Applies the embedded migrations to a TimescaleDB database
+#[derive(Debug, Clone, Default)]
+pub struct SchemaMigrator {
+    baseline: Option<i64>,
+}
+
+impl SchemaMigrator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Treat migrations up to `version` as already applied when the
+    /// database has none recorded
+    pub fn with_baseline(mut self, version: i64) -> Self {
+        self.baseline = Some(version);
+        self
+    }
+
+    /// Newest migration this build knows
+    pub fn latest_version() -> i64 {
+        MIGRATIONS.last().map_or(0, |(version, _, _)| *version)
+    }
+
+    /// Apply every migration that has not been run yet
+    ///
+    /// Fails before running any migration if an applied migration's SQL has
+    /// changed, or if the database has tables but no recorded migrations
+    /// and no baseline is set.
+    pub async fn run(&self, pool: &PgPool) -> Result<MigrationReport> {
+        let mut conn = pool.acquire().await?;
+        sqlx::query("SELECT pg_advisory_lock($1)")
+            .bind(MIGRATION_LOCK)
+            .execute(&mut *conn)
+            .await?;
+        let result = self.run_locked(&mut conn).await;
+        sqlx::query("SELECT pg_advisory_unlock($1)")
+            .bind(MIGRATION_LOCK)
+            .execute(&mut *conn)
+            .await?;
+        result
+    }
+
+    /// Applied and pending migrations
+    pub async fn version(pool: &PgPool) -> Result<SchemaVersion> {
+        let mut conn = pool.acquire().await?;
+        let tracked: bool =
+            sqlx::query_scalar("SELECT to_regclass('schema_migrations') IS NOT NULL")
+                .fetch_one(&mut *conn)
+                .await?;
+        let applied = if tracked {
+            applied_migrations(&mut conn).await?
+        } else {
+            Vec::new()
+        };
+
+        let last = applied.last();
+        Ok(SchemaVersion {
+            current: last.map(|m| m.version),
+            latest: Self::latest_version(),
+            pending: MIGRATIONS
+                .iter()
+                .map(|(version, _, _)| *version)
+                .filter(|version| !applied.iter().any(|m| m.version == *version))
+                .collect(),
+            applied_at: last.map(|m| m.applied_at),
+        })
+    }
+
+    async fn run_locked(&self, conn: &mut PgConnection) -> Result<MigrationReport> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS schema_migrations (
+                version BIGINT PRIMARY KEY,
+                description TEXT NOT NULL,
+                checksum BYTEA NOT NULL,
+                applied_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+            "#,
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        let mut applied = applied_migrations(conn).await?;
+        let mut report = MigrationReport::default();
+
+        if applied.is_empty() {
+            match self.baseline {
+                Some(baseline) => {
+                    for (version, description, sql) in MIGRATIONS {
+                        if *version > baseline {
+                            break;
+                        }
+                        record(&mut *conn, *version, description, sql).await?;
+                        report.baselined.push(*version);
+                    }
+                    if !report.baselined.is_empty() {
+                        info!("Baselined schema at migration {}", baseline);
+                    }
+                    applied = applied_migrations(conn).await?;
+                }
+                None => {
+                    let untracked: bool =
+                        sqlx::query_scalar("SELECT to_regclass('market_ohlcv') IS NOT NULL")
+                            .fetch_one(&mut *conn)
+                            .await?;
+                    if untracked {
+                        return Err(Error::ConfigError(
+                            "Database has tables but no recorded migrations; \
+                             set the schema baseline to the last migration it was created with"
+                                .to_string(),
+                        ));
+                    }
+                }
+            }
+        }
+
+        for migration in &applied {
+            match MIGRATIONS.iter().find(|(v, _, _)| *v == migration.version) {
+                Some((version, description, sql)) if checksum(sql) != migration.checksum => {
+                    return Err(Error::ConfigError(format!(
+                        "Migration {} ({}) was changed after it was applied",
+                        version, description
+                    )));
+                }
+                Some(_) => {}
+                None => warn!(
+                    "Database has migration {} which this build does not know",
+                    migration.version
+                ),
+            }
+        }
+
+        for (version, description, sql) in MIGRATIONS {
+            if applied.iter().any(|m| m.version == *version) {
+                continue;
+            }
+            apply(&mut *conn, *version, description, sql).await?;
+            info!("Applied schema migration {}: {}", version, description);
+            report.applied.push(*version);
+        }
+
+        Ok(report)
+    }
+}
+
+struct AppliedMigration {
+    version: i64,
+    checksum: Vec<u8>,
+    applied_at: DateTime<Utc>,
+}
+
+async fn applied_migrations(conn: &mut PgConnection) -> Result<Vec<AppliedMigration>> {
+    let rows =
+        sqlx::query("SELECT version, checksum, applied_at FROM schema_migrations ORDER BY version")
+            .fetch_all(&mut *conn)
+            .await?;
+
+    rows.iter()
+        .map(|row| {
+            Ok(AppliedMigration {
+                version: row.try_get("version")?,
+                checksum: row.try_get("checksum")?,
+                applied_at: row.try_get("applied_at")?,
+            })
+        })
+        .collect()
+}
+
+async fn apply(conn: &mut PgConnection, version: i64, description: &str, sql: &str) -> Result<()> {
+    if !transactional(sql) {
+        for statement in split_sql(sql) {
+            sqlx::query(statement).execute(&mut *conn).await?;
+        }
+        return record(conn, version, description, sql).await;
+    }
+
+    let mut tx = conn.begin().await?;
+    for statement in split_sql(sql) {
+        sqlx::query(statement).execute(&mut *tx).await?;
+    }
+    record(&mut tx, version, description, sql).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+async fn record(conn: &mut PgConnection, version: i64, description: &str, sql: &str) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO schema_migrations (version, description, checksum) VALUES ($1, $2, $3)",
+    )
+    .bind(version)
+    .bind(description)
+    .bind(checksum(sql))
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+fn checksum(sql: &str) -> Vec<u8> {
+    Sha256::digest(sql.as_bytes()).to_vec()
+}
+
+fn transactional(sql: &str) -> bool {
+    !sql.lines()
+        .any(|line| line.trim_start().starts_with(NO_TRANSACTION))
+}
+
+/// Split a script into statements at semicolons outside comments, quoted
+/// strings and identifiers and `$tag$` bodies
+fn split_sql(sql: &str) -> Vec<&str> {
+    let bytes = sql.as_bytes();
+    let mut statements = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                i = sql[i..].find('\n').map_or(bytes.len(), |end| i + end);
+            }
+            quote @ (b'\'' | b'"') => {
+                i = sql[i + 1..]
+                    .find(quote as char)
+                    .map_or(bytes.len(), |end| i + 1 + end);
+            }
+            b'$' => {
+                let tag_len = sql[i + 1..]
+                    .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .filter(|&len| bytes.get(i + 1 + len) == Some(&b'$'));
+                if let Some(len) = tag_len {
+                    let tag = &sql[i..i + len + 2];
+                    let body = i + tag.len();
+                    i = sql[body..]
+                        .find(tag)
+                        .map_or(bytes.len(), |end| body + end + tag.len() - 1);
+                }
+            }
+            b';' => {
+                statements.push(&sql[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    statements.push(&sql[start.min(sql.len())..]);
+
+    statements
+        .into_iter()
+        .map(str::trim)
+        .filter(|statement| {
+            statement
+                .lines()
+                .any(|line| !line.trim().is_empty() && !line.trim_start().starts_with("--"))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_migration_file_is_embedded_in_order() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../migrations");
+        let mut files: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|name| name.ends_with(".sql"))
+            .collect();
+        files.sort();
+
+        let versions: Vec<i64> = files
+            .iter()
+            .map(|name| name.split('_').next().unwrap().parse().unwrap())
+            .collect();
+        let embedded: Vec<i64> = MIGRATIONS.iter().map(|(version, _, _)| *version).collect();
+        assert_eq!(versions, embedded);
+        assert_eq!(SchemaMigrator::latest_version(), *embedded.last().unwrap());
+    }
+
+    #[test]
+    fn test_split_keeps_function_bodies_and_strings_whole() {
+        let sql = "-- header; not a statement\n\
+            CREATE TABLE a (x TEXT DEFAULT 'a;b'); -- trailing; comment\n\
+            CREATE FUNCTION f() RETURNS TRIGGER AS $$\nBEGIN\n    NEW.x = 'y';\n    RETURN NEW;\nEND;\n$$ LANGUAGE plpgsql;\n\
+            CREATE INDEX \"odd;name\" ON a(x)";
+        let statements = split_sql(sql);
+        assert_eq!(statements.len(), 3);
+        assert!(statements[0].ends_with("DEFAULT 'a;b')"));
+        assert!(statements[1].starts_with("-- trailing; comment\nCREATE FUNCTION"));
+        assert!(statements[1].ends_with("$$ LANGUAGE plpgsql"));
+        assert_eq!(statements[2], "CREATE INDEX \"odd;name\" ON a(x)");
+    }
+
+    #[test]
+    fn test_embedded_migrations_split_and_flag_transactions() {
+        for (version, _, sql) in MIGRATIONS {
+            let statements = split_sql(sql);
+            assert!(!statements.is_empty(), "migration {} is empty", version);
+            assert!(statements.iter().all(|s| !s.ends_with(';')));
+        }
+        let function = split_sql(MIGRATIONS[0].2)
+            .into_iter()
+            .find(|s| s.contains("FUNCTION update_updated_at_column"))
+            .unwrap();
+        assert!(function.ends_with("LANGUAGE plpgsql"));
+
+        // Continuous aggregates cannot be created in a transaction
+        assert!(!transactional(MIGRATIONS[1].2));
+        assert!(transactional(MIGRATIONS[0].2));
+        assert_ne!(checksum(MIGRATIONS[0].2), checksum(MIGRATIONS[1].2));
+    }
+
+    #[test]
+    fn test_candle_table_accepts_every_interval() {
+        let sql = MIGRATIONS.iter().find(|(v, _, _)| *v == 10).unwrap().2;
+        for interval in ea_okx_core::Interval::ALL {
+            let label = format!("'{}'", interval.storage_label());
+            assert!(sql.contains(&label), "{} missing", label);
+        }
+    }
+}
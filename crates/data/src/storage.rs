@@ -3,12 +3,34 @@
 //! This module provides interfaces for storing market data
 //! in TimescaleDB and Redis.
 
+use crate::equity::{EquitySnapshot, equity_table};
 use crate::error::Result;
-use chrono::{DateTime, Utc};
+use crate::integrity::{
+    CandleDownloader, DayChecksum, IntegrityMismatch, IntegrityReport, MismatchKind,
+    compare_checksums, daily_checksums, day_start, whole_days,
+};
+use crate::orderbook::{
+    OrderBookBlock, OrderBookBlockBuilder, OrderBookColumns, OrderBookQuery,
+    OrderBookStorageConfig, RetentionAction, VacuumReport,
+};
+use crate::positioning::{LongShortRatio, OpenInterest, PositioningStat, TakerVolume};
+use crate::resample::{can_resample, resample_candles};
+use crate::schema::{MigrationReport, SchemaMigrator, SchemaVersion};
+use crate::ticks::{
+    TickDeleteScope, TickMaintenanceReport, TickMaintenanceStatus, TickStorageConfig,
+};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use ea_okx_core::Interval;
 use ea_okx_core::types::{Price, Quantity, Symbol};
+use parking_lot::Mutex;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use sqlx::FromRow;
+use sqlx::types::Json;
+use sqlx::{FromRow, PgExecutor};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
 
 /// Database row for OHLCV data
 #[derive(Debug, FromRow)]
@@ -26,12 +48,30 @@ struct CandleRow {
     vwap: Option<Decimal>,
 }
 
+impl CandleRow {
+    fn into_candle(self) -> Candle {
+        Candle {
+            symbol: Symbol::new(&self.symbol).unwrap(),
+            timestamp: self.timestamp,
+            interval: self.interval.parse().unwrap(),
+            open: Price::new(self.open).unwrap(),
+            high: Price::new(self.high).unwrap(),
+            low: Price::new(self.low).unwrap(),
+            close: Price::new(self.close).unwrap(),
+            volume: Quantity::new(self.volume).unwrap(),
+            quote_volume: self.quote_volume,
+            trade_count: self.trade_count,
+            vwap: self.vwap,
+        }
+    }
+}
+
 /// OHLCV candle data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Candle {
     pub symbol: Symbol,
     pub timestamp: DateTime<Utc>,
-    pub interval: String,
+    pub interval: Interval,
     pub open: Price,
     pub high: Price,
     pub low: Price,
@@ -65,9 +105,193 @@ pub struct OrderBookSnapshot {
     pub depth_level: String,
 }
 
+/// Database row for a compressed order book block
+#[derive(Debug, FromRow)]
+struct OrderBookBlockRow {
+    symbol: String,
+    depth_level: String,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+    snapshot_count: i32,
+    max_depth: Option<i32>,
+    payload: Vec<u8>,
+}
+
+impl OrderBookBlockRow {
+    fn into_block(self) -> Result<OrderBookBlock> {
+        Ok(OrderBookBlock {
+            symbol: Symbol::new(&self.symbol)?,
+            depth_level: self.depth_level,
+            start_time: self.start_time,
+            end_time: self.end_time,
+            snapshot_count: self.snapshot_count as usize,
+            max_depth: self.max_depth.map(|depth| depth as usize),
+            payload: self.payload,
+        })
+    }
+}
+
+/// Order book block metadata examined by the vacuum job
+#[derive(Debug, FromRow)]
+struct OrderBookBlockMeta {
+    id: i64,
+    symbol: String,
+    end_time: DateTime<Utc>,
+    max_depth: Option<i32>,
+    payload_bytes: i32,
+}
+
+/// Database row for funding rate data
+#[derive(Debug, FromRow)]
+struct FundingRateRow {
+    inst_id: String,
+    symbol: String,
+    funding_time: DateTime<Utc>,
+    funding_rate: Decimal,
+    next_funding_rate: Option<Decimal>,
+}
+
+/// Database row for open interest data
+#[derive(Debug, FromRow)]
+struct OpenInterestRow {
+    currency: String,
+    period: String,
+    timestamp: DateTime<Utc>,
+    open_interest: Decimal,
+    volume: Decimal,
+}
+
+/// Database row for taker volume data
+#[derive(Debug, FromRow)]
+struct TakerVolumeRow {
+    currency: String,
+    inst_type: String,
+    period: String,
+    timestamp: DateTime<Utc>,
+    buy_volume: Decimal,
+    sell_volume: Decimal,
+}
+
+/// Database row for long/short account ratio data
+#[derive(Debug, FromRow)]
+struct LongShortRatioRow {
+    currency: String,
+    period: String,
+    timestamp: DateTime<Utc>,
+    ratio: Decimal,
+}
+
+/// Database row for a live equity sample
+#[derive(Debug, FromRow)]
+struct EquityRow {
+    timestamp: DateTime<Utc>,
+    total_equity: Decimal,
+    margin_ratio: Option<Decimal>,
+    gross_exposure: Decimal,
+    net_exposure: Decimal,
+    balances: Json<BTreeMap<String, Decimal>>,
+    exposures: Json<BTreeMap<String, Decimal>>,
+}
+
+impl From<EquityRow> for EquitySnapshot {
+    fn from(row: EquityRow) -> Self {
+        Self {
+            timestamp: row.timestamp,
+            total_equity: row.total_equity,
+            margin_ratio: row.margin_ratio,
+            balances: row.balances.0,
+            exposures: row.exposures.0,
+            gross_exposure: row.gross_exposure,
+            net_exposure: row.net_exposure,
+        }
+    }
+}
+
+/// Database row for a recorded candle checksum
+#[derive(Debug, FromRow)]
+struct CandleChecksumRow {
+    symbol: String,
+    interval: String,
+    day: NaiveDate,
+    candle_count: i32,
+    checksum: String,
+}
+
+/// Perpetual swap funding rate record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FundingRate {
+    /// Swap instrument ID (e.g. "BTC-USDT-SWAP")
+    pub inst_id: String,
+    /// Underlying spot pair
+    pub symbol: Symbol,
+    pub funding_time: DateTime<Utc>,
+    pub funding_rate: Decimal,
+    pub next_funding_rate: Option<Decimal>,
+}
+
+impl FundingRate {
+    /// OKX settles perpetual funding every 8 hours
+    pub const DEFAULT_INTERVAL_HOURS: u32 = 8;
+
+    /// Create a funding rate record, deriving the underlying pair from the instrument ID
+    pub fn new(
+        inst_id: impl Into<String>,
+        funding_time: DateTime<Utc>,
+        funding_rate: Decimal,
+        next_funding_rate: Option<Decimal>,
+    ) -> Result<Self> {
+        let inst_id = inst_id.into();
+        let symbol = Self::underlying_symbol(&inst_id)?;
+        Ok(Self {
+            inst_id,
+            symbol,
+            funding_time,
+            funding_rate,
+            next_funding_rate,
+        })
+    }
+
+    /// Underlying spot pair of a swap instrument ("BTC-USDT-SWAP" -> "BTC-USDT")
+    pub fn underlying_symbol(inst_id: &str) -> Result<Symbol> {
+        Ok(Symbol::new(inst_id)?.underlying())
+    }
+
+    /// Annualized funding rate for the given settlement interval
+    pub fn annualized(&self, interval_hours: u32) -> Decimal {
+        let periods_per_year = Decimal::from(365 * 24) / Decimal::from(interval_hours.max(1));
+        self.funding_rate * periods_per_year
+    }
+}
+
+/// Connection pool occupancy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolStats {
+    /// Open connections, idle or in use
+    pub size: u32,
+    pub idle: u32,
+    pub max_connections: u32,
+}
+
+impl PoolStats {
+    pub fn in_use(&self) -> u32 {
+        self.size.saturating_sub(self.idle)
+    }
+
+    /// Share of the maximum pool size in use, 0.0 to 1.0
+    pub fn utilization(&self) -> f64 {
+        if self.max_connections == 0 {
+            return 1.0;
+        }
+        self.in_use() as f64 / self.max_connections as f64
+    }
+}
+
 /// Storage interface for TimescaleDB
 pub struct TimescaleStorage {
     pool: sqlx::PgPool,
+
+    /// Order book snapshots waiting to fill a block
+    orderbook_blocks: Mutex<OrderBookBlockBuilder>,
 }
 
 impl TimescaleStorage {
@@ -79,44 +303,58 @@ impl TimescaleStorage {
                 crate::error::Error::Internal(format!("Failed to connect to database: {}", e))
             })?;
 
-        Ok(Self { pool })
+        Ok(Self::from_pool(pool))
+    }
+
+    /// Storage over an existing connection pool
+    pub fn from_pool(pool: sqlx::PgPool) -> Self {
+        Self {
+            pool,
+            orderbook_blocks: Mutex::new(OrderBookBlockBuilder::new(
+                &OrderBookStorageConfig::default(),
+            )),
+        }
+    }
+
+    /// Group stored order books into blocks as `config` sets out
+    pub fn with_orderbook_config(mut self, config: &OrderBookStorageConfig) -> Self {
+        self.orderbook_blocks = Mutex::new(OrderBookBlockBuilder::new(config));
+        self
+    }
+
+    /// Current connection pool occupancy
+    pub fn pool_stats(&self) -> PoolStats {
+        PoolStats {
+            size: self.pool.size(),
+            idle: self.pool.num_idle() as u32,
+            max_connections: self.pool.options().get_max_connections(),
+        }
+    }
+
+    /// Bring the schema up to date with the embedded migrations
+    pub async fn migrate(&self, migrator: &SchemaMigrator) -> Result<MigrationReport> {
+        migrator.run(&self.pool).await
+    }
+
+    /// Applied and pending schema migrations
+    pub async fn schema_version(&self) -> Result<SchemaVersion> {
+        SchemaMigrator::version(&self.pool).await
     }
 
     /// Store candle data
     pub async fn store_candle(&self, candle: &Candle) -> Result<()> {
-        sqlx::query(
-            r#"
-            INSERT INTO market_ohlcv (
-                symbol, timestamp, interval, open, high, low, close, 
-                volume, quote_volume, trade_count, vwap
-            )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
-            ON CONFLICT (symbol, interval, timestamp) DO UPDATE
-            SET open = EXCLUDED.open,
-                high = EXCLUDED.high,
-                low = EXCLUDED.low,
-                close = EXCLUDED.close,
-                volume = EXCLUDED.volume,
-                quote_volume = EXCLUDED.quote_volume,
-                trade_count = EXCLUDED.trade_count,
-                vwap = EXCLUDED.vwap
-            "#,
-        )
-        .bind(candle.symbol.as_str())
-        .bind(candle.timestamp)
-        .bind(&candle.interval)
-        .bind(candle.open.as_decimal())
-        .bind(candle.high.as_decimal())
-        .bind(candle.low.as_decimal())
-        .bind(candle.close.as_decimal())
-        .bind(candle.volume.as_decimal())
-        .bind(candle.quote_volume)
-        .bind(candle.trade_count)
-        .bind(candle.vwap)
-        .execute(&self.pool)
-        .await?;
+        insert_candle(&self.pool, candle).await
+    }
 
-        Ok(())
+    /// Delete `interval` candles older than `before`, returning the rows removed
+    pub async fn prune_candles(&self, interval: Interval, before: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM market_ohlcv WHERE interval = $1 AND timestamp < $2")
+            .bind(interval.storage_label())
+            .bind(before)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
     }
 
     /// Store tick data
@@ -143,50 +381,357 @@ impl TimescaleStorage {
         Ok(())
     }
 
-    /// Store order book snapshot
+    /// Buffer an order book snapshot, storing its block once it fills up
+    ///
+    /// Snapshots are only written as compressed blocks; call
+    /// [`flush_orderbooks`](Self::flush_orderbooks) before shutting down to
+    /// store partially filled ones.
     pub async fn store_orderbook(&self, snapshot: &OrderBookSnapshot) -> Result<()> {
-        // Convert bids and asks to JSONB
-        let bids_json = serde_json::to_value(
-            snapshot
-                .bids
-                .iter()
-                .map(|(p, q)| vec![p.as_decimal(), q.as_decimal()])
-                .collect::<Vec<_>>(),
-        )?;
-
-        let asks_json = serde_json::to_value(
-            snapshot
-                .asks
-                .iter()
-                .map(|(p, q)| vec![p.as_decimal(), q.as_decimal()])
-                .collect::<Vec<_>>(),
-        )?;
+        let finished = self.orderbook_blocks.lock().push(snapshot.clone())?;
+        if let Some(block) = finished {
+            self.store_orderbook_block(&block).await?;
+        }
+        Ok(())
+    }
+
+    /// Store every partially filled order book block
+    pub async fn flush_orderbooks(&self) -> Result<usize> {
+        let blocks = self.orderbook_blocks.lock().flush()?;
+        for block in &blocks {
+            self.store_orderbook_block(block).await?;
+        }
+        Ok(blocks.len())
+    }
 
+    /// Store a compressed order book block
+    pub async fn store_orderbook_block(&self, block: &OrderBookBlock) -> Result<()> {
         sqlx::query(
             r#"
-            INSERT INTO order_book_snapshots (
-                symbol, timestamp, bids, asks, checksum, depth_level
+            INSERT INTO order_book_blocks (
+                symbol, depth_level, start_time, end_time, snapshot_count, max_depth, payload
             )
-            VALUES ($1, $2, $3, $4, $5, $6)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
             "#,
         )
-        .bind(snapshot.symbol.as_str())
-        .bind(snapshot.timestamp)
-        .bind(bids_json)
-        .bind(asks_json)
-        .bind(snapshot.checksum)
-        .bind(&snapshot.depth_level)
+        .bind(block.symbol.as_str())
+        .bind(&block.depth_level)
+        .bind(block.start_time)
+        .bind(block.end_time)
+        .bind(block.snapshot_count as i32)
+        .bind(block.max_depth.map(|depth| depth as i32))
+        .bind(&block.payload)
         .execute(&self.pool)
         .await?;
 
         Ok(())
     }
 
+    /// Query order book snapshots within time range from compressed blocks
+    pub async fn query_orderbooks(
+        &self,
+        symbol: &Symbol,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<OrderBookSnapshot>> {
+        let mut snapshots = Vec::new();
+        for row in self.orderbook_blocks(symbol, start, end).await? {
+            snapshots.extend(
+                row.into_block()?
+                    .decode()?
+                    .into_iter()
+                    .filter(|s| s.timestamp >= start && s.timestamp < end),
+            );
+        }
+
+        Ok(snapshots)
+    }
+
+    /// Order book history within `[start, end)` in column form, sampled and
+    /// truncated as `query` asks
+    ///
+    /// Blocks are decoded one at a time, so only the kept snapshots are held
+    /// in memory.
+    pub async fn query_orderbook_columns(
+        &self,
+        symbol: &Symbol,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        query: &OrderBookQuery,
+    ) -> Result<OrderBookColumns> {
+        let mut columns = OrderBookColumns::new(symbol.clone());
+        for row in self.orderbook_blocks(symbol, start, end).await? {
+            for snapshot in row.into_block()?.decode()? {
+                if snapshot.timestamp >= start && snapshot.timestamp < end {
+                    columns.push_sampled(&snapshot, query);
+                }
+            }
+        }
+        Ok(columns)
+    }
+
+    /// Blocks overlapping `[start, end)`, oldest first
+    async fn orderbook_blocks(
+        &self,
+        symbol: &Symbol,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<OrderBookBlockRow>> {
+        let rows = sqlx::query_as(
+            r#"
+            SELECT symbol, depth_level, start_time, end_time,
+                   snapshot_count, max_depth, payload
+            FROM order_book_blocks
+            WHERE symbol = $1
+              AND end_time >= $2 AND start_time < $3
+            ORDER BY start_time ASC
+            "#,
+        )
+        .bind(symbol.as_str())
+        .bind(start)
+        .bind(end)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Latest stored order book snapshot at or before `at`, if one was taken
+    /// within `max_age` of it
+    pub async fn query_orderbook_at(
+        &self,
+        symbol: &Symbol,
+        at: DateTime<Utc>,
+        max_age: Duration,
+    ) -> Result<Option<OrderBookSnapshot>> {
+        let snapshots = self
+            .query_orderbooks(symbol, at - max_age, at + Duration::microseconds(1))
+            .await?;
+        Ok(snapshots.into_iter().max_by_key(|s| s.timestamp))
+    }
+
+    /// Apply the tiered retention policies to stored order book blocks
+    ///
+    /// Blocks past their full-depth window are re-encoded at reduced depth
+    /// and blocks past their retention window are deleted.
+    pub async fn vacuum_orderbooks(
+        &self,
+        config: &OrderBookStorageConfig,
+        now: DateTime<Utc>,
+    ) -> Result<VacuumReport> {
+        let horizon = now - Duration::days(config.min_full_depth_days());
+        let candidates: Vec<OrderBookBlockMeta> = sqlx::query_as(
+            r#"
+            SELECT id, symbol, end_time, max_depth, octet_length(payload) AS payload_bytes
+            FROM order_book_blocks
+            WHERE end_time < $1
+            "#,
+        )
+        .bind(horizon)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut report = VacuumReport::default();
+        let mut to_delete = Vec::new();
+
+        for block in candidates {
+            let (id, size) = (block.id, block.payload_bytes);
+            let max_depth = block.max_depth.map(|depth| depth as usize);
+            match config
+                .policy_for(&block.symbol)
+                .action(block.end_time, max_depth, now)
+            {
+                RetentionAction::Keep => {}
+                RetentionAction::Delete => {
+                    to_delete.push(id);
+                    report.bytes_reclaimed += size as u64;
+                }
+                RetentionAction::Truncate(depth) => {
+                    let row: OrderBookBlockRow = sqlx::query_as(
+                        r#"
+                        SELECT symbol, depth_level, start_time, end_time,
+                               snapshot_count, max_depth, payload
+                        FROM order_book_blocks
+                        WHERE id = $1
+                        "#,
+                    )
+                    .bind(id)
+                    .fetch_one(&self.pool)
+                    .await?;
+
+                    let truncated = row
+                        .into_block()?
+                        .truncate_depth(depth, config.compression_level)?;
+
+                    sqlx::query(
+                        "UPDATE order_book_blocks SET max_depth = $2, payload = $3 WHERE id = $1",
+                    )
+                    .bind(id)
+                    .bind(truncated.max_depth.map(|depth| depth as i32))
+                    .bind(&truncated.payload)
+                    .execute(&self.pool)
+                    .await?;
+
+                    report.blocks_truncated += 1;
+                    report.bytes_reclaimed +=
+                        (size as u64).saturating_sub(truncated.payload.len() as u64);
+                }
+            }
+        }
+
+        if !to_delete.is_empty() {
+            report.blocks_deleted = sqlx::query("DELETE FROM order_book_blocks WHERE id = ANY($1)")
+                .bind(&to_delete)
+                .execute(&self.pool)
+                .await?
+                .rows_affected() as usize;
+        }
+
+        Ok(report)
+    }
+
+    /// Run [`vacuum_orderbooks`](Self::vacuum_orderbooks) every `interval`
+    /// until the task is aborted
+    pub fn spawn_orderbook_vacuum(
+        self: Arc<Self>,
+        config: OrderBookStorageConfig,
+        interval: std::time::Duration,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match self.vacuum_orderbooks(&config, Utc::now()).await {
+                    Ok(report) => info!(
+                        "Order book vacuum: {} truncated, {} deleted, {} bytes reclaimed",
+                        report.blocks_truncated, report.blocks_deleted, report.bytes_reclaimed
+                    ),
+                    Err(e) => warn!("Order book vacuum failed: {}", e),
+                }
+            }
+        })
+    }
+
+    /// Bring `market_ticks` in line with its partitioning, compression and
+    /// retention settings
+    ///
+    /// Sets the chunk interval for new chunks, drops chunks past the longest
+    /// retention, deletes the ticks of symbols kept for less time, then
+    /// compresses chunks past the compression age.
+    pub async fn maintain_ticks(
+        &self,
+        config: &TickStorageConfig,
+        now: DateTime<Utc>,
+    ) -> Result<TickMaintenanceReport> {
+        config.validate()?;
+        let started_at = Utc::now();
+
+        sqlx::query("SELECT set_chunk_time_interval('market_ticks', make_interval(hours => $1))")
+            .bind(config.chunk_interval_hours as i32)
+            .execute(&self.pool)
+            .await?;
+
+        let plan = config.retention_plan(now);
+        let dropped: Vec<String> =
+            sqlx::query_scalar("SELECT drop_chunks('market_ticks', older_than => $1)::text")
+                .bind(plan.drop_chunks_before)
+                .fetch_all(&self.pool)
+                .await?;
+
+        let mut rows_deleted = 0;
+        for (scope, before) in &plan.deletes {
+            let result = match scope {
+                TickDeleteScope::Symbol(symbol) => {
+                    sqlx::query("DELETE FROM market_ticks WHERE symbol = $1 AND timestamp < $2")
+                        .bind(symbol)
+                        .bind(before)
+                        .execute(&self.pool)
+                        .await?
+                }
+                TickDeleteScope::Default { overridden } => {
+                    sqlx::query(
+                        "DELETE FROM market_ticks WHERE timestamp < $1 AND symbol <> ALL($2)",
+                    )
+                    .bind(before)
+                    .bind(overridden)
+                    .execute(&self.pool)
+                    .await?
+                }
+            };
+            rows_deleted += result.rows_affected();
+        }
+        if rows_deleted > 0 {
+            // Reclaim the deleted rows in uncompressed chunks for new ticks
+            sqlx::query("VACUUM (ANALYZE) market_ticks")
+                .execute(&self.pool)
+                .await?;
+        }
+
+        let uncompressed: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT format('%I.%I', chunk_schema, chunk_name)
+            FROM timescaledb_information.chunks
+            WHERE hypertable_name = 'market_ticks'
+              AND NOT is_compressed
+              AND range_end < $1
+            "#,
+        )
+        .bind(config.compress_before(now))
+        .fetch_all(&self.pool)
+        .await?;
+        for chunk in &uncompressed {
+            sqlx::query("SELECT compress_chunk($1::regclass, if_not_compressed => TRUE)")
+                .bind(chunk)
+                .execute(&self.pool)
+                .await?;
+        }
+
+        let table_bytes: Option<i64> = sqlx::query_scalar("SELECT hypertable_size('market_ticks')")
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(TickMaintenanceReport {
+            started_at,
+            finished_at: Utc::now(),
+            chunks_dropped: dropped.len(),
+            rows_deleted,
+            chunks_compressed: uncompressed.len(),
+            table_bytes: table_bytes.unwrap_or(0),
+        })
+    }
+
+    /// Run [`maintain_ticks`](Self::maintain_ticks) every `interval` until
+    /// the task is aborted, recording each pass in `status`
+    pub fn spawn_tick_maintenance(
+        self: Arc<Self>,
+        config: TickStorageConfig,
+        interval: std::time::Duration,
+        status: TickMaintenanceStatus,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match self.maintain_ticks(&config, Utc::now()).await {
+                    Ok(report) => {
+                        info!(
+                            "Tick maintenance: {} chunks dropped, {} rows deleted, {} chunks compressed",
+                            report.chunks_dropped, report.rows_deleted, report.chunks_compressed
+                        );
+                        status.record_success(report);
+                    }
+                    Err(e) => {
+                        warn!("Tick maintenance failed: {}", e);
+                        status.record_failure(Utc::now(), e.to_string());
+                    }
+                }
+            }
+        })
+    }
+
     /// Query candles within time range
     pub async fn query_candles(
         &self,
         symbol: &Symbol,
-        interval: &str,
+        interval: Interval,
         start: DateTime<Utc>,
         end: DateTime<Utc>,
     ) -> Result<Vec<Candle>> {
@@ -201,37 +746,544 @@ impl TimescaleStorage {
             "#,
         )
         .bind(symbol.as_str())
-        .bind(interval)
+        .bind(interval.storage_label())
+        .bind(start)
+        .bind(end)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(CandleRow::into_candle).collect())
+    }
+
+    /// Bars of `target` opening within `[start, end)`, resampled from stored
+    /// `source` candles
+    ///
+    /// The range is widened to whole `target` bars, so the first and last
+    /// bars cover every stored candle that belongs to them.
+    pub async fn query_candles_resampled(
+        &self,
+        symbol: &Symbol,
+        source: Interval,
+        target: Interval,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<Candle>> {
+        if !can_resample(source, target) {
+            return Err(crate::error::Error::ValidationError(format!(
+                "{} candles cannot be resampled into {}",
+                source, target
+            )));
+        }
+        let from = target.bar_start(start);
+        let to = if target.bar_start(end) == end {
+            end
+        } else {
+            target.next_bar_start(end)
+        };
+
+        let candles = self.query_candles(symbol, source, from, to).await?;
+        resample_candles(&candles, target)
+    }
+
+    /// Up to `before` candles opening at or before `at` and up to `after`
+    /// opening after it, oldest first
+    pub async fn query_candles_around(
+        &self,
+        symbol: &Symbol,
+        interval: Interval,
+        at: DateTime<Utc>,
+        before: usize,
+        after: usize,
+    ) -> Result<Vec<Candle>> {
+        let mut earlier: Vec<CandleRow> = sqlx::query_as(
+            r#"
+            SELECT symbol, timestamp, interval, open, high, low, close,
+                   volume, quote_volume, trade_count, vwap
+            FROM market_ohlcv
+            WHERE symbol = $1 AND interval = $2 AND timestamp <= $3
+            ORDER BY timestamp DESC
+            LIMIT $4
+            "#,
+        )
+        .bind(symbol.as_str())
+        .bind(interval.storage_label())
+        .bind(at)
+        .bind(before as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        earlier.reverse();
+
+        let later: Vec<CandleRow> = sqlx::query_as(
+            r#"
+            SELECT symbol, timestamp, interval, open, high, low, close,
+                   volume, quote_volume, trade_count, vwap
+            FROM market_ohlcv
+            WHERE symbol = $1 AND interval = $2 AND timestamp > $3
+            ORDER BY timestamp ASC
+            LIMIT $4
+            "#,
+        )
+        .bind(symbol.as_str())
+        .bind(interval.storage_label())
+        .bind(at)
+        .bind(after as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(earlier
+            .into_iter()
+            .chain(later)
+            .map(CandleRow::into_candle)
+            .collect())
+    }
+
+    /// Store funding rate data
+    pub async fn store_funding_rate(&self, funding: &FundingRate) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO funding_rates (
+                inst_id, symbol, funding_time, funding_rate, next_funding_rate
+            )
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (inst_id, funding_time) DO UPDATE
+            SET funding_rate = EXCLUDED.funding_rate,
+                next_funding_rate = EXCLUDED.next_funding_rate
+            "#,
+        )
+        .bind(&funding.inst_id)
+        .bind(funding.symbol.as_str())
+        .bind(funding.funding_time)
+        .bind(funding.funding_rate)
+        .bind(funding.next_funding_rate)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Query funding rate history within time range
+    pub async fn query_funding_rates(
+        &self,
+        inst_id: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<FundingRate>> {
+        let rows: Vec<FundingRateRow> = sqlx::query_as(
+            r#"
+            SELECT inst_id, symbol, funding_time, funding_rate, next_funding_rate
+            FROM funding_rates
+            WHERE inst_id = $1
+              AND funding_time >= $2 AND funding_time < $3
+            ORDER BY funding_time ASC
+            "#,
+        )
+        .bind(inst_id)
+        .bind(start)
+        .bind(end)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(FundingRate {
+                    inst_id: row.inst_id,
+                    symbol: Symbol::new(&row.symbol)?,
+                    funding_time: row.funding_time,
+                    funding_rate: row.funding_rate,
+                    next_funding_rate: row.next_funding_rate,
+                })
+            })
+            .collect()
+    }
+
+    /// Store a positioning statistic, replacing one for the same period
+    pub async fn store_positioning_stat(&self, stat: &PositioningStat) -> Result<()> {
+        match stat {
+            PositioningStat::OpenInterest(oi) => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO open_interest (currency, period, timestamp, open_interest, volume)
+                    VALUES ($1, $2, $3, $4, $5)
+                    ON CONFLICT (currency, period, timestamp) DO UPDATE
+                    SET open_interest = EXCLUDED.open_interest,
+                        volume = EXCLUDED.volume
+                    "#,
+                )
+                .bind(&oi.currency)
+                .bind(&oi.period)
+                .bind(oi.timestamp)
+                .bind(oi.open_interest)
+                .bind(oi.volume)
+                .execute(&self.pool)
+                .await?;
+            }
+            PositioningStat::TakerVolume(taker) => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO taker_volume (
+                        currency, inst_type, period, timestamp, buy_volume, sell_volume
+                    )
+                    VALUES ($1, $2, $3, $4, $5, $6)
+                    ON CONFLICT (currency, inst_type, period, timestamp) DO UPDATE
+                    SET buy_volume = EXCLUDED.buy_volume,
+                        sell_volume = EXCLUDED.sell_volume
+                    "#,
+                )
+                .bind(&taker.currency)
+                .bind(&taker.inst_type)
+                .bind(&taker.period)
+                .bind(taker.timestamp)
+                .bind(taker.buy_volume)
+                .bind(taker.sell_volume)
+                .execute(&self.pool)
+                .await?;
+            }
+            PositioningStat::LongShortRatio(ratio) => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO long_short_ratio (currency, period, timestamp, ratio)
+                    VALUES ($1, $2, $3, $4)
+                    ON CONFLICT (currency, period, timestamp) DO UPDATE
+                    SET ratio = EXCLUDED.ratio
+                    "#,
+                )
+                .bind(&ratio.currency)
+                .bind(&ratio.period)
+                .bind(ratio.timestamp)
+                .bind(ratio.ratio)
+                .execute(&self.pool)
+                .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Query open interest history of `currency` within time range
+    pub async fn query_open_interest(
+        &self,
+        currency: &str,
+        period: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<OpenInterest>> {
+        let rows: Vec<OpenInterestRow> = sqlx::query_as(
+            r#"
+            SELECT currency, period, timestamp, open_interest, volume
+            FROM open_interest
+            WHERE currency = $1 AND period = $2
+              AND timestamp >= $3 AND timestamp < $4
+            ORDER BY timestamp ASC
+            "#,
+        )
+        .bind(currency)
+        .bind(period)
         .bind(start)
         .bind(end)
         .fetch_all(&self.pool)
         .await?;
 
-        let candles = rows
+        Ok(rows
             .into_iter()
-            .map(|row| Candle {
-                symbol: Symbol::new(&row.symbol).unwrap(),
+            .map(|row| OpenInterest {
+                currency: row.currency,
+                period: row.period,
                 timestamp: row.timestamp,
-                interval: row.interval,
-                open: Price::new(row.open).unwrap(),
-                high: Price::new(row.high).unwrap(),
-                low: Price::new(row.low).unwrap(),
-                close: Price::new(row.close).unwrap(),
-                volume: Quantity::new(row.volume).unwrap(),
-                quote_volume: row.quote_volume,
-                trade_count: row.trade_count,
-                vwap: row.vwap,
+                open_interest: row.open_interest,
+                volume: row.volume,
+            })
+            .collect())
+    }
+
+    /// Query taker volume history of `currency` on `inst_type` within time range
+    pub async fn query_taker_volume(
+        &self,
+        currency: &str,
+        inst_type: &str,
+        period: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<TakerVolume>> {
+        let rows: Vec<TakerVolumeRow> = sqlx::query_as(
+            r#"
+            SELECT currency, inst_type, period, timestamp, buy_volume, sell_volume
+            FROM taker_volume
+            WHERE currency = $1 AND inst_type = $2 AND period = $3
+              AND timestamp >= $4 AND timestamp < $5
+            ORDER BY timestamp ASC
+            "#,
+        )
+        .bind(currency)
+        .bind(inst_type)
+        .bind(period)
+        .bind(start)
+        .bind(end)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| TakerVolume {
+                currency: row.currency,
+                inst_type: row.inst_type,
+                period: row.period,
+                timestamp: row.timestamp,
+                buy_volume: row.buy_volume,
+                sell_volume: row.sell_volume,
+            })
+            .collect())
+    }
+
+    /// Query long/short account ratio history of `currency` within time range
+    pub async fn query_long_short_ratio(
+        &self,
+        currency: &str,
+        period: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<LongShortRatio>> {
+        let rows: Vec<LongShortRatioRow> = sqlx::query_as(
+            r#"
+            SELECT currency, period, timestamp, ratio
+            FROM long_short_ratio
+            WHERE currency = $1 AND period = $2
+              AND timestamp >= $3 AND timestamp < $4
+            ORDER BY timestamp ASC
+            "#,
+        )
+        .bind(currency)
+        .bind(period)
+        .bind(start)
+        .bind(end)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| LongShortRatio {
+                currency: row.currency,
+                period: row.period,
+                timestamp: row.timestamp,
+                ratio: row.ratio,
+            })
+            .collect())
+    }
+
+    /// Recompute and record per-day checksums after a backfill
+    ///
+    /// The range is widened to whole UTC days. Days whose data changed or
+    /// disappeared since their checksum was last recorded are logged and
+    /// returned before the new checksums overwrite them.
+    pub async fn record_candle_checksums(
+        &self,
+        symbol: &Symbol,
+        interval: Interval,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<IntegrityMismatch>> {
+        let (start, end) = whole_days(start, end);
+        let candles = self.query_candles(symbol, interval, start, end).await?;
+        let recomputed = daily_checksums(symbol, interval, &candles);
+        let recorded = self
+            .load_candle_checksums(symbol, interval, start, end)
+            .await?;
+
+        let mismatches: Vec<IntegrityMismatch> = compare_checksums(&recorded, &recomputed)
+            .into_iter()
+            .filter(|m| m.kind != MismatchKind::Unrecorded)
+            .collect();
+        for mismatch in &mismatches {
+            warn!(
+                "Candle data for {} {} on {} changed after backfill ({:?}: {:?} -> {} candles)",
+                symbol.as_str(),
+                interval,
+                mismatch.day,
+                mismatch.kind,
+                mismatch.expected_count,
+                mismatch.actual_count
+            );
+        }
+
+        for checksum in &recomputed {
+            insert_candle_checksum(&self.pool, checksum).await?;
+        }
+
+        Ok(mismatches)
+    }
+
+    /// Recorded checksums for the UTC days touched by `[start, end)`
+    pub async fn load_candle_checksums(
+        &self,
+        symbol: &Symbol,
+        interval: Interval,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<DayChecksum>> {
+        let (start, end) = whole_days(start, end);
+        let rows: Vec<CandleChecksumRow> = sqlx::query_as(
+            r#"
+            SELECT symbol, interval, day, candle_count, checksum
+            FROM candle_checksums
+            WHERE symbol = $1 AND interval = $2
+              AND day >= $3 AND day < $4
+            ORDER BY day ASC
+            "#,
+        )
+        .bind(symbol.as_str())
+        .bind(interval.storage_label())
+        .bind(start.date_naive())
+        .bind(end.date_naive())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(DayChecksum {
+                    symbol: Symbol::new(&row.symbol)?,
+                    interval: row.interval.parse()?,
+                    day: row.day,
+                    candle_count: row.candle_count.max(0) as usize,
+                    checksum: row.checksum,
+                })
             })
+            .collect()
+    }
+
+    /// Verify stored candles against their recorded checksums
+    ///
+    /// With a downloader, days that changed or went missing are deleted,
+    /// fetched again and re-checksummed. Days that were never checksummed are
+    /// reported but left alone since there is nothing to compare them with.
+    pub async fn verify_data_integrity(
+        &self,
+        symbol: &Symbol,
+        interval: Interval,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        downloader: Option<&dyn CandleDownloader>,
+    ) -> Result<IntegrityReport> {
+        let (start, end) = whole_days(start, end);
+        let candles = self.query_candles(symbol, interval, start, end).await?;
+        let recomputed = daily_checksums(symbol, interval, &candles);
+        let recorded = self
+            .load_candle_checksums(symbol, interval, start, end)
+            .await?;
+
+        let mismatches = compare_checksums(&recorded, &recomputed);
+        let mut days_checked: Vec<NaiveDate> = recorded
+            .iter()
+            .chain(recomputed.iter())
+            .map(|c| c.day)
             .collect();
+        days_checked.sort();
+        days_checked.dedup();
+
+        let mut redownloaded = Vec::new();
+        if let Some(downloader) = downloader {
+            for mismatch in &mismatches {
+                if mismatch.kind == MismatchKind::Unrecorded {
+                    continue;
+                }
+                if self
+                    .redownload_day(symbol, interval, mismatch.day, downloader)
+                    .await?
+                {
+                    redownloaded.push(mismatch.day);
+                }
+            }
+        }
+
+        if !mismatches.is_empty() {
+            warn!(
+                "Integrity check for {} {} found {} mismatched day(s), re-downloaded {}",
+                symbol.as_str(),
+                interval,
+                mismatches.len(),
+                redownloaded.len()
+            );
+        }
+
+        Ok(IntegrityReport {
+            symbol: symbol.clone(),
+            interval,
+            start,
+            end,
+            days_checked: days_checked.len(),
+            mismatches,
+            redownloaded,
+        })
+    }
+
+    /// Replace one day of candles with a fresh download
+    ///
+    /// Returns false (keeping the stored data) when the download is empty.
+    async fn redownload_day(
+        &self,
+        symbol: &Symbol,
+        interval: Interval,
+        day: NaiveDate,
+        downloader: &dyn CandleDownloader,
+    ) -> Result<bool> {
+        let start = day_start(day);
+        let end = start + Duration::days(1);
+        let candles = downloader
+            .download_candles(symbol, interval, start, end)
+            .await?;
+        if candles.is_empty() {
+            warn!(
+                "Re-download of {} {} on {} returned no candles; keeping stored data",
+                symbol.as_str(),
+                interval,
+                day
+            );
+            return Ok(false);
+        }
+
+        // Replace the day atomically so a failed insert keeps the old data
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            r#"
+            DELETE FROM market_ohlcv
+            WHERE symbol = $1 AND interval = $2
+              AND timestamp >= $3 AND timestamp < $4
+            "#,
+        )
+        .bind(symbol.as_str())
+        .bind(interval.storage_label())
+        .bind(start)
+        .bind(end)
+        .execute(&mut *tx)
+        .await?;
+
+        for candle in candles
+            .iter()
+            .filter(|c| c.timestamp >= start && c.timestamp < end)
+        {
+            insert_candle(&mut *tx, candle).await?;
+        }
 
-        Ok(candles)
+        for checksum in daily_checksums(symbol, interval, &candles) {
+            if checksum.day == day {
+                insert_candle_checksum(&mut *tx, &checksum).await?;
+            }
+        }
+        tx.commit().await?;
+
+        info!(
+            "Re-downloaded {} {} candles for {} on {}",
+            candles.len(),
+            interval,
+            symbol.as_str(),
+            day
+        );
+        Ok(true)
     }
 
     /// Get latest candle
     pub async fn get_latest_candle(
         &self,
         symbol: &Symbol,
-        interval: &str,
+        interval: Interval,
     ) -> Result<Option<Candle>> {
         let row: Option<CandleRow> = sqlx::query_as(
             r#"
@@ -244,24 +1296,165 @@ impl TimescaleStorage {
             "#,
         )
         .bind(symbol.as_str())
-        .bind(interval)
+        .bind(interval.storage_label())
         .fetch_optional(&self.pool)
         .await?;
 
-        Ok(row.map(|row| Candle {
-            symbol: Symbol::new(&row.symbol).unwrap(),
-            timestamp: row.timestamp,
-            interval: row.interval,
-            open: Price::new(row.open).unwrap(),
-            high: Price::new(row.high).unwrap(),
-            low: Price::new(row.low).unwrap(),
-            close: Price::new(row.close).unwrap(),
-            volume: Quantity::new(row.volume).unwrap(),
-            quote_volume: row.quote_volume,
-            trade_count: row.trade_count,
-            vwap: row.vwap,
-        }))
+        Ok(row.map(CandleRow::into_candle))
     }
+
+    /// Store a live equity sample, replacing one at the same timestamp
+    pub async fn store_equity_snapshot(&self, snapshot: &EquitySnapshot) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO account_equity (
+                timestamp, total_equity, margin_ratio, gross_exposure, net_exposure,
+                balances, exposures
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (timestamp) DO UPDATE
+            SET total_equity = EXCLUDED.total_equity,
+                margin_ratio = EXCLUDED.margin_ratio,
+                gross_exposure = EXCLUDED.gross_exposure,
+                net_exposure = EXCLUDED.net_exposure,
+                balances = EXCLUDED.balances,
+                exposures = EXCLUDED.exposures
+            "#,
+        )
+        .bind(snapshot.timestamp)
+        .bind(snapshot.total_equity)
+        .bind(snapshot.margin_ratio)
+        .bind(snapshot.gross_exposure)
+        .bind(snapshot.net_exposure)
+        .bind(Json(&snapshot.balances))
+        .bind(Json(&snapshot.exposures))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Live equity samples within time range, reduced to the last sample in
+    /// each `bucket` when given
+    ///
+    /// Ranges reaching past the raw retention window, and buckets of an hour
+    /// or more, come from the hourly aggregate.
+    pub async fn query_equity_curve(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        bucket: Option<Duration>,
+    ) -> Result<Vec<EquitySnapshot>> {
+        let table = equity_table(start, bucket, Utc::now());
+        let rows: Vec<EquityRow> = match bucket {
+            Some(bucket) => {
+                let sql = format!(
+                    r#"
+                    SELECT time_bucket($3::interval, timestamp) AS timestamp,
+                           last(total_equity, timestamp) AS total_equity,
+                           last(margin_ratio, timestamp) AS margin_ratio,
+                           last(gross_exposure, timestamp) AS gross_exposure,
+                           last(net_exposure, timestamp) AS net_exposure,
+                           last(balances, timestamp) AS balances,
+                           last(exposures, timestamp) AS exposures
+                    FROM {}
+                    WHERE timestamp >= $1 AND timestamp < $2
+                    GROUP BY 1
+                    ORDER BY 1 ASC
+                    "#,
+                    table
+                );
+                sqlx::query_as(&sql)
+                    .bind(start)
+                    .bind(end)
+                    .bind(bucket)
+                    .fetch_all(&self.pool)
+                    .await?
+            }
+            None => {
+                let sql = format!(
+                    r#"
+                    SELECT timestamp, total_equity, margin_ratio, gross_exposure,
+                           net_exposure, balances, exposures
+                    FROM {}
+                    WHERE timestamp >= $1 AND timestamp < $2
+                    ORDER BY timestamp ASC
+                    "#,
+                    table
+                );
+                sqlx::query_as(&sql)
+                    .bind(start)
+                    .bind(end)
+                    .fetch_all(&self.pool)
+                    .await?
+            }
+        };
+
+        Ok(rows.into_iter().map(EquitySnapshot::from).collect())
+    }
+}
+
+/// Upsert one candle through `executor`, a pool or an open transaction
+async fn insert_candle<'e>(executor: impl PgExecutor<'e>, candle: &Candle) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO market_ohlcv (
+            symbol, timestamp, interval, open, high, low, close, 
+            volume, quote_volume, trade_count, vwap
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        ON CONFLICT (symbol, interval, timestamp) DO UPDATE
+        SET open = EXCLUDED.open,
+            high = EXCLUDED.high,
+            low = EXCLUDED.low,
+            close = EXCLUDED.close,
+            volume = EXCLUDED.volume,
+            quote_volume = EXCLUDED.quote_volume,
+            trade_count = EXCLUDED.trade_count,
+            vwap = EXCLUDED.vwap
+        "#,
+    )
+    .bind(candle.symbol.as_str())
+    .bind(candle.timestamp)
+    .bind(candle.interval.storage_label())
+    .bind(candle.open.as_decimal())
+    .bind(candle.high.as_decimal())
+    .bind(candle.low.as_decimal())
+    .bind(candle.close.as_decimal())
+    .bind(candle.volume.as_decimal())
+    .bind(candle.quote_volume)
+    .bind(candle.trade_count)
+    .bind(candle.vwap)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Upsert one day's checksum through `executor`
+async fn insert_candle_checksum<'e>(
+    executor: impl PgExecutor<'e>,
+    checksum: &DayChecksum,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO candle_checksums (symbol, interval, day, candle_count, checksum)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (symbol, interval, day) DO UPDATE
+        SET candle_count = EXCLUDED.candle_count,
+            checksum = EXCLUDED.checksum,
+            computed_at = NOW()
+        "#,
+    )
+    .bind(checksum.symbol.as_str())
+    .bind(checksum.interval.storage_label())
+    .bind(checksum.day)
+    .bind(checksum.candle_count as i32)
+    .bind(&checksum.checksum)
+    .execute(executor)
+    .await?;
+
+    Ok(())
 }
 
 /// Storage interface for Redis cache
@@ -276,6 +1469,15 @@ impl RedisStorage {
         Ok(Self { client })
     }
 
+    /// Round-trip a PING on a fresh connection
+    pub async fn ping(&self) -> Result<()> {
+        let mut con = self.client.get_async_connection().await?;
+        redis::cmd("PING")
+            .query_async::<_, String>(&mut con)
+            .await?;
+        Ok(())
+    }
+
     /// Cache latest candle
     pub async fn cache_latest_candle(&self, candle: &Candle) -> Result<()> {
         let mut con = self.client.get_async_connection().await?;
@@ -297,7 +1499,7 @@ impl RedisStorage {
     pub async fn get_latest_candle(
         &self,
         symbol: &Symbol,
-        interval: &str,
+        interval: Interval,
     ) -> Result<Option<Candle>> {
         let mut con = self.client.get_async_connection().await?;
         let key = format!("candle:{}:{}", symbol.as_str(), interval);
@@ -358,7 +1560,7 @@ mod tests {
         let candle = Candle {
             symbol: symbol.clone(),
             timestamp: Utc::now(),
-            interval: "1m".to_string(),
+            interval: Interval::OneMinute,
             open: Price::new(dec!(50000)).unwrap(),
             high: Price::new(dec!(50100)).unwrap(),
             low: Price::new(dec!(49900)).unwrap(),
@@ -370,7 +1572,7 @@ mod tests {
         };
 
         assert_eq!(candle.symbol, symbol);
-        assert_eq!(candle.interval, "1m");
+        assert_eq!(candle.interval, Interval::OneMinute);
     }
 
     #[test]
@@ -389,4 +1591,15 @@ mod tests {
         assert_eq!(tick.symbol, symbol);
         assert_eq!(tick.side, "buy");
     }
+
+    #[test]
+    fn test_funding_rate_underlying_and_annualized() {
+        let funding = FundingRate::new("BTC-USDT-SWAP", Utc::now(), dec!(0.0001), None).unwrap();
+
+        assert_eq!(funding.symbol.as_str(), "BTC-USDT");
+        assert_eq!(
+            funding.annualized(FundingRate::DEFAULT_INTERVAL_HOURS),
+            dec!(0.1095)
+        );
+    }
 }
@@ -0,0 +1,552 @@
+//! Strategy persistence
+//!
+//! This module provides a repository for strategy definitions, their
+//! configuration and lifecycle history so that user-managed strategies
+//! survive application restarts. The SQL implementation runs on either
+//! SQLite (desktop) or PostgreSQL, selected by the connection URL.
+
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use ea_okx_core::models::strategy::{Strategy, StrategyStatus};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use sqlx::AnyPool;
+use sqlx::any::AnyPoolOptions;
+use sqlx::{FromRow, Row};
+use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Versioned schema migrations for the strategy store
+///
+/// Kept apart from [`crate::schema`], which only runs against TimescaleDB:
+/// these must also apply to the desktop's SQLite file, so they stick to SQL
+/// both backends accept and track themselves in their own table.
+const MIGRATIONS: &[(i64, &str, &str)] = &[(
+    1,
+    "create strategy store",
+    include_str!("../migrations/strategy_store/001_create_strategy_store.sql"),
+)];
+
+/// A single strategy status transition
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StrategyStatusChange {
+    pub strategy_id: Uuid,
+    pub from_status: Option<StrategyStatus>,
+    pub to_status: StrategyStatus,
+    pub reason: Option<String>,
+    pub changed_at: DateTime<Utc>,
+}
+
+impl StrategyStatusChange {
+    /// Create a status change recorded at the current time
+    pub fn new(
+        strategy_id: Uuid,
+        from_status: Option<StrategyStatus>,
+        to_status: StrategyStatus,
+        reason: Option<String>,
+    ) -> Self {
+        Self {
+            strategy_id,
+            from_status,
+            to_status,
+            reason,
+            changed_at: Utc::now(),
+        }
+    }
+}
+
+/// Persistence backend for strategies
+#[async_trait]
+pub trait StrategyRepository: Send + Sync {
+    /// Insert or replace a strategy
+    async fn save(&self, strategy: &Strategy) -> Result<()>;
+
+    /// Load a strategy by ID
+    async fn load(&self, id: Uuid) -> Result<Option<Strategy>>;
+
+    /// Load all stored strategies
+    async fn load_all(&self) -> Result<Vec<Strategy>>;
+
+    /// Delete a strategy and its status history
+    async fn delete(&self, id: Uuid) -> Result<()>;
+
+    /// Append a status transition to the strategy's history
+    async fn record_status_change(&self, change: &StrategyStatusChange) -> Result<()>;
+
+    /// Status history for a strategy, oldest first
+    async fn status_history(&self, id: Uuid) -> Result<Vec<StrategyStatusChange>>;
+}
+
+/// In-memory repository, used when no database is configured and in tests
+#[derive(Default)]
+pub struct InMemoryStrategyRepository {
+    strategies: RwLock<HashMap<Uuid, Strategy>>,
+    history: RwLock<Vec<StrategyStatusChange>>,
+}
+
+impl InMemoryStrategyRepository {
+    /// Create an empty repository
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl StrategyRepository for InMemoryStrategyRepository {
+    async fn save(&self, strategy: &Strategy) -> Result<()> {
+        self.strategies
+            .write()
+            .insert(strategy.id, strategy.clone());
+        Ok(())
+    }
+
+    async fn load(&self, id: Uuid) -> Result<Option<Strategy>> {
+        Ok(self.strategies.read().get(&id).cloned())
+    }
+
+    async fn load_all(&self) -> Result<Vec<Strategy>> {
+        Ok(self.strategies.read().values().cloned().collect())
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<()> {
+        self.strategies.write().remove(&id);
+        self.history.write().retain(|c| c.strategy_id != id);
+        Ok(())
+    }
+
+    async fn record_status_change(&self, change: &StrategyStatusChange) -> Result<()> {
+        self.history.write().push(change.clone());
+        Ok(())
+    }
+
+    async fn status_history(&self, id: Uuid) -> Result<Vec<StrategyStatusChange>> {
+        Ok(self
+            .history
+            .read()
+            .iter()
+            .filter(|c| c.strategy_id == id)
+            .cloned()
+            .collect())
+    }
+}
+
+/// Database row for a status transition
+///
+/// Nullable columns are selected through `COALESCE(.., '')` because the `Any`
+/// driver cannot decode SQLite NULLs into `Option<String>`.
+#[derive(Debug, FromRow)]
+struct StatusChangeRow {
+    strategy_id: String,
+    from_status: String,
+    to_status: String,
+    reason: String,
+    changed_at: String,
+}
+
+impl StatusChangeRow {
+    fn into_change(self) -> Result<StrategyStatusChange> {
+        Ok(StrategyStatusChange {
+            strategy_id: parse_uuid(&self.strategy_id)?,
+            from_status: Some(self.from_status.as_str())
+                .filter(|s| !s.is_empty())
+                .map(StrategyStatus::from_str)
+                .transpose()?,
+            to_status: StrategyStatus::from_str(&self.to_status)?,
+            reason: Some(self.reason).filter(|s| !s.is_empty()),
+            changed_at: parse_timestamp(&self.changed_at)?,
+        })
+    }
+}
+
+/// SQL-backed repository for SQLite or PostgreSQL
+pub struct SqlStrategyRepository {
+    pool: AnyPool,
+}
+
+impl SqlStrategyRepository {
+    /// Connect to the database at `url` and apply pending migrations
+    ///
+    /// Accepts `sqlite:` and `postgres:` URLs.
+    pub async fn connect(url: &str) -> Result<Self> {
+        sqlx::any::install_default_drivers();
+
+        // Every connection to an in-memory SQLite database sees its own
+        // empty database, so keep those pools to a single connection.
+        let max_connections = if url.contains(":memory:") || url.contains("mode=memory") {
+            1
+        } else {
+            5
+        };
+
+        let pool = AnyPoolOptions::new()
+            .max_connections(max_connections)
+            .connect(url)
+            .await
+            .map_err(|e| Error::Internal(format!("Failed to connect to database: {}", e)))?;
+
+        let repository = Self { pool };
+        repository.migrate().await?;
+        Ok(repository)
+    }
+
+    /// Open (creating if needed) a SQLite database file
+    pub async fn open_sqlite(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                Error::ConfigError(format!(
+                    "Failed to create directory {}: {}",
+                    parent.display(),
+                    e
+                ))
+            })?;
+        }
+
+        Self::connect(&format!("sqlite://{}?mode=rwc", path.display())).await
+    }
+
+    /// Apply any migrations that have not been run yet
+    async fn migrate(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS strategy_store_migrations (
+                version BIGINT PRIMARY KEY,
+                description TEXT NOT NULL,
+                applied_at TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        let applied: Vec<i64> = sqlx::query("SELECT version FROM strategy_store_migrations")
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(|row| row.try_get::<i64, _>("version"))
+            .collect::<std::result::Result<_, _>>()?;
+
+        for (version, description, sql) in MIGRATIONS {
+            if applied.contains(version) {
+                continue;
+            }
+
+            let mut tx = self.pool.begin().await?;
+            for statement in split_statements(sql) {
+                sqlx::query(&statement).execute(&mut *tx).await?;
+            }
+            sqlx::query(
+                "INSERT INTO strategy_store_migrations (version, description, applied_at) VALUES ($1, $2, $3)",
+            )
+            .bind(*version)
+            .bind(*description)
+            .bind(Utc::now().to_rfc3339())
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await?;
+
+            tracing::info!(
+                "Applied strategy store migration {}: {}",
+                version,
+                description
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl StrategyRepository for SqlStrategyRepository {
+    async fn save(&self, strategy: &Strategy) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO strategy_records (
+                id, name, strategy_type, status, config, document, created_at, updated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (id) DO UPDATE
+            SET name = EXCLUDED.name,
+                strategy_type = EXCLUDED.strategy_type,
+                status = EXCLUDED.status,
+                config = EXCLUDED.config,
+                document = EXCLUDED.document,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(strategy.id.to_string())
+        .bind(&strategy.name)
+        .bind(&strategy.strategy_type)
+        .bind(status_str(strategy.status))
+        .bind(serde_json::to_string(&strategy.config)?)
+        .bind(serde_json::to_string(strategy)?)
+        .bind(strategy.created_at.to_rfc3339())
+        .bind(strategy.updated_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn load(&self, id: Uuid) -> Result<Option<Strategy>> {
+        let document: Option<String> =
+            sqlx::query_scalar("SELECT document FROM strategy_records WHERE id = $1")
+                .bind(id.to_string())
+                .fetch_optional(&self.pool)
+                .await?;
+
+        document
+            .map(|doc| serde_json::from_str(&doc).map_err(Error::from))
+            .transpose()
+    }
+
+    async fn load_all(&self) -> Result<Vec<Strategy>> {
+        let documents: Vec<String> =
+            sqlx::query_scalar("SELECT document FROM strategy_records ORDER BY created_at ASC")
+                .fetch_all(&self.pool)
+                .await?;
+
+        documents
+            .iter()
+            .map(|doc| serde_json::from_str(doc).map_err(Error::from))
+            .collect()
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM strategy_status_history WHERE strategy_id = $1")
+            .bind(id.to_string())
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM strategy_records WHERE id = $1")
+            .bind(id.to_string())
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn record_status_change(&self, change: &StrategyStatusChange) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO strategy_status_history (
+                id, strategy_id, from_status, to_status, reason, changed_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(change.strategy_id.to_string())
+        .bind(change.from_status.map(status_str))
+        .bind(status_str(change.to_status))
+        .bind(change.reason.clone())
+        .bind(change.changed_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn status_history(&self, id: Uuid) -> Result<Vec<StrategyStatusChange>> {
+        let rows: Vec<StatusChangeRow> = sqlx::query_as(
+            r#"
+            SELECT strategy_id,
+                   COALESCE(from_status, '') AS from_status,
+                   to_status,
+                   COALESCE(reason, '') AS reason,
+                   changed_at
+            FROM strategy_status_history
+            WHERE strategy_id = $1
+            ORDER BY changed_at ASC
+            "#,
+        )
+        .bind(id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(StatusChangeRow::into_change).collect()
+    }
+}
+
+/// Storage representation of a status, matching its serde form
+fn status_str(status: StrategyStatus) -> String {
+    serde_json::to_value(status)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn parse_uuid(s: &str) -> Result<Uuid> {
+    Uuid::parse_str(s).map_err(|e| Error::ParseError(format!("Invalid UUID {}: {}", s, e)))
+}
+
+fn parse_timestamp(s: &str) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| Error::ParseError(format!("Invalid timestamp {}: {}", s, e)))
+}
+
+/// Split a migration script into individual statements, dropping comments
+fn split_statements(sql: &str) -> Vec<String> {
+    let without_comments: String = sql
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("--"))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    without_comments
+        .split(';')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ea_okx_core::models::strategy::StrategyConfig;
+    use ea_okx_core::types::Symbol;
+    use rust_decimal_macros::dec;
+
+    fn sample_strategy(name: &str) -> Strategy {
+        let config = StrategyConfig::new(
+            serde_json::json!({ "short_period": 20, "long_period": 50 }),
+            vec![Symbol::new("BTC-USDT").unwrap()],
+            dec!(10000),
+        );
+        Strategy::new(
+            name.to_string(),
+            "ma_crossover".to_string(),
+            "1.0.0".to_string(),
+            config,
+            Uuid::new_v4(),
+        )
+        .unwrap()
+    }
+
+    async fn memory_repository() -> SqlStrategyRepository {
+        SqlStrategyRepository::connect("sqlite::memory:")
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn test_split_statements() {
+        let statements =
+            split_statements("-- comment\nCREATE TABLE a (x TEXT);\n\nCREATE INDEX i ON a(x);\n");
+        assert_eq!(statements.len(), 2);
+        assert!(statements[0].starts_with("CREATE TABLE"));
+    }
+
+    #[test]
+    fn test_status_str_round_trip() {
+        for status in [StrategyStatus::PaperTrading, StrategyStatus::Active] {
+            assert_eq!(
+                StrategyStatus::from_str(&status_str(status)).unwrap(),
+                status
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_save_and_load() {
+        let repo = memory_repository().await;
+        let mut strategy = sample_strategy("MA Crossover");
+        repo.save(&strategy).await.unwrap();
+
+        strategy.config.parameters = serde_json::json!({ "short_period": 10 });
+        strategy.set_status(StrategyStatus::Active);
+        repo.save(&strategy).await.unwrap();
+
+        let all = repo.load_all().await.unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].status, StrategyStatus::Active);
+        assert_eq!(all[0].config.parameters["short_period"], 10);
+        assert_eq!(all[0].config.allocated_capital, dec!(10000));
+
+        let loaded = repo.load(strategy.id).await.unwrap().unwrap();
+        assert_eq!(loaded.name, "MA Crossover");
+        assert!(repo.load(Uuid::new_v4()).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_status_history_and_delete() {
+        let repo = memory_repository().await;
+        let strategy = sample_strategy("Grid");
+        repo.save(&strategy).await.unwrap();
+
+        repo.record_status_change(&StrategyStatusChange::new(
+            strategy.id,
+            None,
+            StrategyStatus::Draft,
+            Some("created".to_string()),
+        ))
+        .await
+        .unwrap();
+        repo.record_status_change(&StrategyStatusChange::new(
+            strategy.id,
+            Some(StrategyStatus::Draft),
+            StrategyStatus::Active,
+            None,
+        ))
+        .await
+        .unwrap();
+
+        let history = repo.status_history(strategy.id).await.unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].from_status, None);
+        assert_eq!(history[1].to_status, StrategyStatus::Active);
+
+        repo.delete(strategy.id).await.unwrap();
+        assert!(repo.load_all().await.unwrap().is_empty());
+        assert!(repo.status_history(strategy.id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_file_persists_across_connections() {
+        let dir = std::env::temp_dir().join(format!("strategy-store-{}", Uuid::new_v4()));
+        let path = dir.join("strategies.db");
+        let strategy = sample_strategy("RSI");
+
+        {
+            let repo = SqlStrategyRepository::open_sqlite(&path).await.unwrap();
+            repo.save(&strategy).await.unwrap();
+        }
+
+        // Reopening must not re-run migrations or lose data
+        let repo = SqlStrategyRepository::open_sqlite(&path).await.unwrap();
+        let all = repo.load_all().await.unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].id, strategy.id);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_repository() {
+        let repo = InMemoryStrategyRepository::new();
+        let strategy = sample_strategy("Momentum");
+        repo.save(&strategy).await.unwrap();
+        repo.record_status_change(&StrategyStatusChange::new(
+            strategy.id,
+            None,
+            StrategyStatus::Draft,
+            None,
+        ))
+        .await
+        .unwrap();
+
+        assert_eq!(repo.load_all().await.unwrap().len(), 1);
+        assert_eq!(repo.status_history(strategy.id).await.unwrap().len(), 1);
+
+        repo.delete(strategy.id).await.unwrap();
+        assert!(repo.load(strategy.id).await.unwrap().is_none());
+    }
+}
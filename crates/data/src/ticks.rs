@@ -0,0 +1,272 @@
+//! Tick storage partitioning and retention
+//!
+//! `market_ticks` is a TimescaleDB hypertable. How large its chunks are, when
+//! they are compressed and how long each symbol's ticks are kept is set by a
+//! [`TickStorageConfig`] and applied by the maintenance job
+//! ([`TimescaleStorage::maintain_ticks`](crate::storage::TimescaleStorage::maintain_ticks)):
+//! chunks entirely older than the longest retention are dropped whole, ticks
+//! of symbols kept for less time are deleted row by row, and chunks past the
+//! compression age are compressed. Each run is recorded in a shared
+//! [`TickMaintenanceStatus`] for health reporting.
+
+use crate::error::{Error, Result};
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Partitioning, compression and retention for stored ticks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TickStorageConfig {
+    /// Hours of ticks per hypertable chunk, applied to chunks created from
+    /// now on
+    pub chunk_interval_hours: i64,
+
+    /// Days after which a chunk is compressed
+    pub compress_after_days: i64,
+
+    /// Days ticks are kept for symbols without an override
+    pub default_retention_days: i64,
+
+    /// Per-symbol retention overrides in days, keyed by symbol (e.g., "BTC-USDT")
+    pub symbol_retention_days: HashMap<String, i64>,
+}
+
+impl Default for TickStorageConfig {
+    fn default() -> Self {
+        Self {
+            chunk_interval_hours: 24,
+            compress_after_days: 7,
+            default_retention_days: 90,
+            symbol_retention_days: HashMap::new(),
+        }
+    }
+}
+
+/// Ticks to delete row by row in one maintenance pass
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TickDeleteScope {
+    /// Ticks of this symbol
+    Symbol(String),
+    /// Ticks of every symbol without a retention override
+    Default { overridden: Vec<String> },
+}
+
+/// What one maintenance pass removes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TickRetentionPlan {
+    /// Chunks ending before this are dropped whole
+    pub drop_chunks_before: DateTime<Utc>,
+
+    /// Row deletes for retention shorter than the longest one
+    pub deletes: Vec<(TickDeleteScope, DateTime<Utc>)>,
+}
+
+impl TickStorageConfig {
+    pub fn retention_days_for(&self, symbol: &str) -> i64 {
+        self.symbol_retention_days
+            .get(symbol)
+            .copied()
+            .unwrap_or(self.default_retention_days)
+    }
+
+    /// Longest retention across all symbols; no tick older than this is kept
+    pub fn max_retention_days(&self) -> i64 {
+        self.symbol_retention_days
+            .values()
+            .copied()
+            .chain(std::iter::once(self.default_retention_days))
+            .max()
+            .unwrap_or(self.default_retention_days)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.chunk_interval_hours <= 0 {
+            return Err(Error::ConfigError(
+                "Tick chunk interval must be positive".to_string(),
+            ));
+        }
+        if self.compress_after_days <= 0 {
+            return Err(Error::ConfigError(
+                "Tick compression age must be positive".to_string(),
+            ));
+        }
+        if let Some((symbol, days)) = self
+            .symbol_retention_days
+            .iter()
+            .map(|(symbol, days)| (symbol.as_str(), *days))
+            .chain(std::iter::once(("default", self.default_retention_days)))
+            .find(|(_, days)| *days <= 0)
+        {
+            return Err(Error::ConfigError(format!(
+                "Tick retention for {} must be positive, got {} days",
+                symbol, days
+            )));
+        }
+        Ok(())
+    }
+
+    /// Chunk drop and row deletes that bring the table within retention
+    pub fn retention_plan(&self, now: DateTime<Utc>) -> TickRetentionPlan {
+        let max_days = self.max_retention_days();
+        let cutoff = |days: i64| now - Duration::days(days);
+
+        let mut overrides: Vec<(&String, &i64)> = self.symbol_retention_days.iter().collect();
+        overrides.sort();
+
+        let mut deletes = Vec::new();
+        if self.default_retention_days < max_days {
+            deletes.push((
+                TickDeleteScope::Default {
+                    overridden: overrides.iter().map(|(s, _)| (*s).clone()).collect(),
+                },
+                cutoff(self.default_retention_days),
+            ));
+        }
+        deletes.extend(
+            overrides
+                .into_iter()
+                .filter(|(_, days)| **days < max_days)
+                .map(|(symbol, days)| (TickDeleteScope::Symbol(symbol.clone()), cutoff(*days))),
+        );
+
+        TickRetentionPlan {
+            drop_chunks_before: cutoff(max_days),
+            deletes,
+        }
+    }
+
+    /// Chunks ending before this are compressed
+    pub fn compress_before(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        now - Duration::days(self.compress_after_days)
+    }
+}
+
+/// Outcome of one maintenance pass
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TickMaintenanceReport {
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub chunks_dropped: usize,
+    pub rows_deleted: u64,
+    pub chunks_compressed: usize,
+
+    /// Size of the hypertable after the pass, compressed chunks included
+    pub table_bytes: i64,
+}
+
+/// Recorded state of the maintenance job
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TickMaintenanceState {
+    pub last_success: Option<TickMaintenanceReport>,
+    pub last_failure: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+
+    /// Failed passes since the last successful one
+    pub consecutive_failures: u32,
+}
+
+/// Shared record of maintenance runs; clones share the state
+#[derive(Debug, Clone, Default)]
+pub struct TickMaintenanceStatus {
+    state: Arc<RwLock<TickMaintenanceState>>,
+}
+
+impl TickMaintenanceStatus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_success(&self, report: TickMaintenanceReport) {
+        let mut state = self.state.write();
+        state.last_success = Some(report);
+        state.consecutive_failures = 0;
+    }
+
+    pub fn record_failure(&self, at: DateTime<Utc>, error: impl Into<String>) {
+        let mut state = self.state.write();
+        state.last_failure = Some(at);
+        state.last_error = Some(error.into());
+        state.consecutive_failures += 1;
+    }
+
+    pub fn snapshot(&self) -> TickMaintenanceState {
+        self.state.read().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_retention_plan_drops_chunks_past_the_longest_retention() {
+        let now = Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap();
+        let config = TickStorageConfig {
+            default_retention_days: 30,
+            symbol_retention_days: HashMap::from([
+                ("BTC-USDT".to_string(), 180),
+                ("DOGE-USDT".to_string(), 7),
+            ]),
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+        assert_eq!(config.retention_days_for("ETH-USDT"), 30);
+
+        let plan = config.retention_plan(now);
+        assert_eq!(plan.drop_chunks_before, now - Duration::days(180));
+        assert_eq!(
+            plan.deletes,
+            vec![
+                (
+                    TickDeleteScope::Default {
+                        overridden: vec!["BTC-USDT".to_string(), "DOGE-USDT".to_string()],
+                    },
+                    now - Duration::days(30),
+                ),
+                (
+                    TickDeleteScope::Symbol("DOGE-USDT".to_string()),
+                    now - Duration::days(7),
+                ),
+            ]
+        );
+
+        // One retention for everything needs no row deletes
+        let plan = TickStorageConfig::default().retention_plan(now);
+        assert_eq!(plan.drop_chunks_before, now - Duration::days(90));
+        assert!(plan.deletes.is_empty());
+    }
+
+    #[test]
+    fn test_status_counts_failures_until_a_success() {
+        let status = TickMaintenanceStatus::new();
+        let watched = status.clone();
+        let at = Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap();
+
+        status.record_failure(at, "connection refused");
+        status.record_failure(at, "connection refused");
+        assert_eq!(watched.snapshot().consecutive_failures, 2);
+
+        status.record_success(TickMaintenanceReport {
+            started_at: at,
+            finished_at: at,
+            chunks_dropped: 1,
+            rows_deleted: 0,
+            chunks_compressed: 2,
+            table_bytes: 1024,
+        });
+        let state = watched.snapshot();
+        assert_eq!(state.consecutive_failures, 0);
+        assert_eq!(state.last_error.as_deref(), Some("connection refused"));
+        assert_eq!(state.last_success.unwrap().chunks_compressed, 2);
+
+        let invalid = TickStorageConfig {
+            symbol_retention_days: HashMap::from([("BTC-USDT".to_string(), 0)]),
+            ..Default::default()
+        };
+        assert!(invalid.validate().is_err());
+    }
+}
@@ -0,0 +1,100 @@
+//! Market data collector against the in-repo mock OKX server
+
+use async_trait::async_trait;
+use ea_okx_client::{Credentials, OkxAdapter, OkxRestClient, OkxWebSocketClient};
+use ea_okx_core::Interval;
+use ea_okx_core::exchange::MarketStream;
+use ea_okx_core::types::{Price, Symbol};
+use ea_okx_data::collector::CollectorConfig;
+use ea_okx_data::{BarConfig, MarketDataCollector, PriceCache};
+use ea_okx_mock::MockOkx;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Cache forwarding every price written to it
+struct PriceTap(mpsc::UnboundedSender<(Symbol, Price)>);
+
+#[async_trait]
+impl PriceCache for PriceTap {
+    async fn get_price(&self, _symbol: &Symbol) -> ea_okx_data::Result<Option<Price>> {
+        Ok(None)
+    }
+
+    async fn put_price(&self, symbol: &Symbol, price: Price) -> ea_okx_data::Result<()> {
+        let _ = self.0.send((symbol.clone(), price));
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_collector_caches_tickers_and_builds_bars() {
+    let mock = MockOkx::start().await.unwrap();
+    let credentials = Credentials::new("key", "secret", "pass");
+    let rest = OkxRestClient::new(credentials.clone(), true)
+        .unwrap()
+        .with_base_url(&mock.rest_url())
+        .unwrap();
+    let mut ws = OkxWebSocketClient::new(credentials, true)
+        .with_urls(&mock.ws_public_url(), &mock.ws_private_url());
+    ws.connect().await.unwrap();
+    let exchange = Arc::new(OkxAdapter::new(Arc::new(rest)).with_websocket(ws));
+
+    let (price_tx, mut prices) = mpsc::unbounded_channel();
+    let mut collector = MarketDataCollector::new(CollectorConfig {
+        symbols: vec!["BTC-USDT".to_string()],
+        streams: vec![MarketStream::Ticker, MarketStream::Trades],
+        sub_minute_bars: Some(BarConfig {
+            intervals: vec![Interval::OneSecond],
+            ..BarConfig::default()
+        }),
+        ..CollectorConfig::default()
+    })
+    .with_price_cache(Arc::new(PriceTap(price_tx)));
+    let mut bars = collector.subscribe_bars().unwrap();
+    let quality = collector.quality_control();
+    collector
+        .initialize_with(exchange, None, None)
+        .await
+        .unwrap();
+
+    let timeout = Duration::from_secs(5);
+    assert!(
+        mock.wait_for_subscription("tickers", "BTC-USDT", timeout)
+            .await
+    );
+    assert!(
+        mock.wait_for_subscription("trades", "BTC-USDT", timeout)
+            .await
+    );
+    let running = tokio::spawn(async move { collector.start().await });
+
+    // Tickers for symbols not collected never reach the collector
+    mock.push_ticker("ETH-USDT", dec!(3000));
+    mock.push_ticker("BTC-USDT", dec!(50000));
+    let (symbol, price) = tokio::time::timeout(timeout, prices.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(symbol.as_str(), "BTC-USDT");
+    assert_eq!(price.as_decimal(), dec!(50000));
+
+    // Trades close one-second bars once their second has passed
+    mock.push_trade("BTC-USDT", "1", dec!(50010), dec!(0.5));
+    mock.push_trade("BTC-USDT", "2", dec!(50030), dec!(0.25));
+    let mut volume = Decimal::ZERO;
+    while volume < dec!(0.75) {
+        let bar = tokio::time::timeout(timeout, bars.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(bar.interval, Interval::OneSecond);
+        volume += bar.volume.as_decimal();
+    }
+    assert_eq!(volume, dec!(0.75));
+    assert_eq!(quality.get_stats().total_rejected, 0);
+
+    running.abort();
+}
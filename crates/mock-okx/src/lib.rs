@@ -0,0 +1,354 @@
+//! In-process mock of the OKX v5 API for integration tests
+//!
+//! [`MockOkx`] serves the subset of OKX's REST and WebSocket API the
+//! clients in this workspace use on a local port, so the order manager,
+//! collector and WebSocket client can be exercised end to end without
+//! network access or credentials:
+//!
+//! - `GET /api/v5/public/time` and `GET /api/v5/public/instruments`
+//! - `POST /api/v5/trade/order`, `GET /api/v5/trade/order` and
+//!   `POST /api/v5/trade/cancel-order`
+//! - `/ws/v5/public` and `/ws/v5/private`, with login, subscribe,
+//!   unsubscribe and ping, pushing `data` arrays as OKX does
+//!
+//! Orders follow an [`OrderScript`] — a delayed acknowledgement, a
+//! rejection code, a sequence of partial fills reported on the orders
+//! channel — and any REST path can be made to fail once with a [`Fault`].
+//! Requests are recorded for assertions. Signatures are not verified, only
+//! required to be present.
+//!
+//! ```no_run
+//! use ea_okx_mock::{MockOkx, OrderScript};
+//! use rust_decimal::Decimal;
+//!
+//! # async fn example() -> std::io::Result<()> {
+//! let mock = MockOkx::start().await?;
+//! mock.add_instrument("BTC-USDT", "0.1", "0.0001", "0.0001");
+//! mock.script_order(OrderScript::accept().fill(Decimal::new(5, 1)).fill_rest());
+//! // Point OkxRestClient::with_base_url at mock.rest_url() and
+//! // OkxWebSocketClient::with_urls at mock.ws_public_url(), mock.ws_private_url()
+//! # Ok(())
+//! # }
+//! ```
+
+mod rest;
+mod scenario;
+mod ws;
+
+pub use scenario::{Fault, OrderScript};
+
+use chrono::Utc;
+use parking_lot::Mutex;
+use rust_decimal::Decimal;
+use scenario::MockOrder;
+use serde_json::{Value, json};
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// Channels served on the private endpoint, after login
+const PRIVATE_CHANNELS: &[&str] = &[
+    "orders",
+    "orders-algo",
+    "account",
+    "positions",
+    "balance_and_position",
+];
+
+/// REST request the mock received
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub query: String,
+    /// JSON body, `Null` when empty or not JSON
+    pub body: Value,
+    /// Carried every OK-ACCESS signing header
+    pub signed: bool,
+}
+
+/// Frame pushed to the connections subscribed to its channel
+#[derive(Debug, Clone)]
+pub(crate) struct Push {
+    pub channel: String,
+    pub inst_id: String,
+    pub frame: String,
+}
+
+/// State shared by the REST and WebSocket handlers
+pub(crate) struct MockState {
+    pub instruments: Mutex<Vec<Value>>,
+    pub orders: Mutex<Vec<MockOrder>>,
+    pub scripts: Mutex<VecDeque<OrderScript>>,
+    pub faults: Mutex<HashMap<String, VecDeque<Fault>>>,
+    pub requests: Mutex<Vec<RecordedRequest>>,
+    pub last_prices: Mutex<HashMap<String, Decimal>>,
+    /// Subscription args per WebSocket connection
+    pub subscriptions: Mutex<HashMap<u64, Vec<Value>>>,
+    pub pushes: broadcast::Sender<Push>,
+    next_id: AtomicU64,
+}
+
+impl MockState {
+    fn new() -> Self {
+        Self {
+            instruments: Mutex::new(Vec::new()),
+            orders: Mutex::new(Vec::new()),
+            scripts: Mutex::new(VecDeque::new()),
+            faults: Mutex::new(HashMap::new()),
+            requests: Mutex::new(Vec::new()),
+            last_prices: Mutex::new(HashMap::new()),
+            subscriptions: Mutex::new(HashMap::new()),
+            pushes: broadcast::channel(1024).0,
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    pub fn next_id(&self) -> u64 {
+        self.next_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    pub fn take_fault(&self, path: &str) -> Option<Fault> {
+        self.faults.lock().get_mut(path)?.pop_front()
+    }
+
+    /// Push `data` to the subscribers of `channel` for `inst_id`
+    pub fn push(&self, channel: &str, inst_id: &str, data: Value) {
+        let frame = json!({
+            "arg": { "channel": channel, "instId": inst_id },
+            "data": [data],
+        });
+        // Nobody listening is not an error
+        let _ = self.pushes.send(Push {
+            channel: channel.to_string(),
+            inst_id: inst_id.to_string(),
+            frame: frame.to_string(),
+        });
+    }
+
+    pub fn push_order(&self, order: &MockOrder) {
+        self.push("orders", &order.inst_id, order.to_json());
+    }
+
+    /// Whether connection `conn_id` subscribed to `channel` for `inst_id`
+    pub fn is_subscribed(&self, conn_id: u64, channel: &str, inst_id: &str) -> bool {
+        self.subscriptions
+            .lock()
+            .get(&conn_id)
+            .is_some_and(|args| args.iter().any(|arg| matches(arg, channel, inst_id)))
+    }
+
+    /// Fill the order with exchange ID `ord_id` and push the update
+    pub fn fill(&self, ord_id: &str, quantity: Option<Decimal>, price: Decimal) -> bool {
+        let mut orders = self.orders.lock();
+        let Some(order) = orders.iter_mut().find(|o| o.ord_id == ord_id) else {
+            return false;
+        };
+        if !order.fill(quantity, price, now()) {
+            return false;
+        }
+        self.push_order(order);
+        true
+    }
+}
+
+/// Whether subscription `arg` covers `channel` for `inst_id`
+fn matches(arg: &Value, channel: &str, inst_id: &str) -> bool {
+    arg["channel"] == channel && arg["instId"].as_str().is_none_or(|id| id == inst_id)
+}
+
+pub(crate) fn is_private(channel: &str) -> bool {
+    PRIVATE_CHANNELS.contains(&channel)
+}
+
+pub(crate) fn now() -> i64 {
+    Utc::now().timestamp_millis()
+}
+
+/// Mock OKX server listening on a local port until dropped
+pub struct MockOkx {
+    addr: SocketAddr,
+    state: Arc<MockState>,
+    server: JoinHandle<()>,
+}
+
+impl MockOkx {
+    /// Start serving on an ephemeral port of 127.0.0.1
+    pub async fn start() -> std::io::Result<Self> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(MockState::new());
+        let app = rest::router(state.clone()).merge(ws::router(state.clone()));
+        let server = tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, app).await {
+                warn!("Mock OKX server stopped: {}", e);
+            }
+        });
+        Ok(Self {
+            addr,
+            state,
+            server,
+        })
+    }
+
+    /// Base URL for `OkxRestClient::with_base_url`
+    pub fn rest_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    pub fn ws_public_url(&self) -> String {
+        format!("ws://{}/ws/v5/public", self.addr)
+    }
+
+    pub fn ws_private_url(&self) -> String {
+        format!("ws://{}/ws/v5/private", self.addr)
+    }
+
+    /// List a live instrument; once any is listed, orders for unlisted
+    /// ones are refused with `51001`
+    pub fn add_instrument(&self, inst_id: &str, tick_sz: &str, lot_sz: &str, min_sz: &str) {
+        self.state.instruments.lock().push(json!({
+            "instType": scenario::inst_type(inst_id),
+            "instId": inst_id,
+            "tickSz": tick_sz,
+            "lotSz": lot_sz,
+            "minSz": min_sz,
+            "state": "live",
+        }));
+    }
+
+    /// Handle the next order placed with `script`; unscripted orders are
+    /// accepted and left resting
+    pub fn script_order(&self, script: OrderScript) {
+        self.state.scripts.lock().push_back(script);
+    }
+
+    /// Answer the next request on `path` with `fault`; queued faults are
+    /// used in order
+    pub fn fail_next(&self, path: &str, fault: Fault) {
+        self.state
+            .faults
+            .lock()
+            .entry(path.to_string())
+            .or_default()
+            .push_back(fault);
+    }
+
+    /// Push one `data` item on `channel` for `inst_id`
+    pub fn push(&self, channel: &str, inst_id: &str, data: Value) {
+        self.state.push(channel, inst_id, data);
+    }
+
+    /// Push a ticker at `last`, timestamped now; market orders fill at the
+    /// latest ticker price
+    pub fn push_ticker(&self, inst_id: &str, last: Decimal) {
+        self.state
+            .last_prices
+            .lock()
+            .insert(inst_id.to_string(), last);
+        let last = last.to_string();
+        self.push(
+            "tickers",
+            inst_id,
+            json!({
+                "instType": scenario::inst_type(inst_id),
+                "instId": inst_id,
+                "last": last,
+                "lastSz": "1",
+                "askPx": last,
+                "askSz": "1",
+                "bidPx": last,
+                "bidSz": "1",
+                "open24h": last,
+                "high24h": last,
+                "low24h": last,
+                "volCcy24h": "0",
+                "vol24h": "0",
+                "ts": now().to_string(),
+            }),
+        );
+    }
+
+    /// Push a public trade, timestamped now
+    pub fn push_trade(&self, inst_id: &str, trade_id: &str, price: Decimal, size: Decimal) {
+        self.push(
+            "trades",
+            inst_id,
+            json!({
+                "instId": inst_id,
+                "tradeId": trade_id,
+                "px": price.to_string(),
+                "sz": size.to_string(),
+                "side": "buy",
+                "ts": now().to_string(),
+            }),
+        );
+    }
+
+    /// Fill `quantity` of the order with client order ID `cl_ord_id` at
+    /// `price`, pushing the update; false if it is not open
+    pub fn fill_order(&self, cl_ord_id: &str, quantity: Decimal, price: Decimal) -> bool {
+        let ord_id = self
+            .state
+            .orders
+            .lock()
+            .iter()
+            .find(|o| o.cl_ord_id == cl_ord_id)
+            .map(|o| o.ord_id.clone());
+        ord_id.is_some_and(|id| self.state.fill(&id, Some(quantity), price))
+    }
+
+    /// Order with client order ID `cl_ord_id`, as OKX reports it
+    pub fn order(&self, cl_ord_id: &str) -> Option<Value> {
+        self.state
+            .orders
+            .lock()
+            .iter()
+            .find(|o| o.cl_ord_id == cl_ord_id)
+            .map(MockOrder::to_json)
+    }
+
+    /// REST requests received so far
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state.requests.lock().clone()
+    }
+
+    /// Whether any connection is subscribed to `channel` for `inst_id`
+    pub fn is_subscribed(&self, channel: &str, inst_id: &str) -> bool {
+        self.state
+            .subscriptions
+            .lock()
+            .values()
+            .flatten()
+            .any(|arg| matches(arg, channel, inst_id))
+    }
+
+    /// Wait up to `timeout` for a subscription to `channel` for `inst_id`,
+    /// so that pushes are not sent before anyone listens
+    pub async fn wait_for_subscription(
+        &self,
+        channel: &str,
+        inst_id: &str,
+        timeout: Duration,
+    ) -> bool {
+        let deadline = tokio::time::Instant::now() + timeout;
+        while !self.is_subscribed(channel, inst_id) {
+            if tokio::time::Instant::now() >= deadline {
+                return false;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        true
+    }
+}
+
+impl Drop for MockOkx {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
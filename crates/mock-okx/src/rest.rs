@@ -0,0 +1,255 @@
+//! REST endpoints
+
+use crate::scenario::{Fault, MockOrder, envelope};
+use crate::{MockState, RecordedRequest, now};
+use axum::body::Body;
+use axum::extract::{Query, Request, State};
+use axum::http::StatusCode;
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use rust_decimal::Decimal;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::warn;
+
+/// Headers every private OKX request is signed with
+const SIGNING_HEADERS: &[&str] = &[
+    "OK-ACCESS-KEY",
+    "OK-ACCESS-SIGN",
+    "OK-ACCESS-TIMESTAMP",
+    "OK-ACCESS-PASSPHRASE",
+];
+
+pub(crate) fn router(state: Arc<MockState>) -> Router {
+    Router::new()
+        .route("/api/v5/public/time", get(server_time))
+        .route("/api/v5/public/instruments", get(instruments))
+        .route("/api/v5/trade/order", post(place_order).get(order))
+        .route("/api/v5/trade/cancel-order", post(cancel_order))
+        .fallback(unknown_path)
+        .layer(middleware::from_fn_with_state(state.clone(), intercept))
+        .with_state(state)
+}
+
+/// Record every request and answer it with a queued fault, if any
+async fn intercept(State(state): State<Arc<MockState>>, request: Request, next: Next) -> Response {
+    let (parts, body) = request.into_parts();
+    let Ok(bytes) = axum::body::to_bytes(body, usize::MAX).await else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    state.requests.lock().push(RecordedRequest {
+        method: parts.method.to_string(),
+        path: parts.uri.path().to_string(),
+        query: parts.uri.query().unwrap_or_default().to_string(),
+        body: serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+        signed: SIGNING_HEADERS
+            .iter()
+            .all(|header| parts.headers.contains_key(*header)),
+    });
+
+    if let Some(fault) = state.take_fault(parts.uri.path()) {
+        if let Some(response) = fault.response() {
+            return response;
+        }
+        if let Fault::Delay(delay) = fault {
+            tokio::time::sleep(delay).await;
+        }
+    }
+    next.run(Request::from_parts(parts, Body::from(bytes)))
+        .await
+}
+
+async fn server_time() -> Json<Value> {
+    envelope("0", "", vec![json!({ "ts": now().to_string() })])
+}
+
+async fn instruments(
+    State(state): State<Arc<MockState>>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Json<Value> {
+    let inst_type = query.get("instType").map(String::as_str);
+    let instruments = state
+        .instruments
+        .lock()
+        .iter()
+        .filter(|i| inst_type.is_none_or(|t| i["instType"] == t))
+        .cloned()
+        .collect();
+    envelope("0", "", instruments)
+}
+
+async fn place_order(State(state): State<Arc<MockState>>, Json(body): Json<Value>) -> Response {
+    let script = state.scripts.lock().pop_front().unwrap_or_default();
+    let field = |name: &str| body[name].as_str().unwrap_or_default().to_string();
+    let cl_ord_id = field("clOrdId");
+
+    let refusal = if let Some((code, msg)) = &script.rejection {
+        Some((code.as_str(), msg.clone()))
+    } else if field("instId").is_empty() {
+        Some(("50014", "Parameter instId can not be empty".to_string()))
+    } else if !Decimal::from_str(&field("sz")).is_ok_and(|sz| sz > Decimal::ZERO) {
+        Some(("51000", "Parameter sz error".to_string()))
+    } else if !is_listed(&state, &field("instId")) {
+        Some(("51001", "Instrument ID does not exist".to_string()))
+    } else if !cl_ord_id.is_empty() && state.orders.lock().iter().any(|o| o.cl_ord_id == cl_ord_id)
+    {
+        Some(("51016", "Duplicated clOrdId".to_string()))
+    } else {
+        None
+    };
+    if let Some((code, msg)) = refusal {
+        tokio::time::sleep(script.ack_delay).await;
+        return order_failed(&cl_ord_id, code, &msg);
+    }
+
+    let created = now();
+    let order = MockOrder {
+        inst_id: field("instId"),
+        ord_id: (600_000_000_000_000_000 + state.next_id()).to_string(),
+        cl_ord_id: cl_ord_id.clone(),
+        tag: field("tag"),
+        side: field("side"),
+        ord_type: field("ordType"),
+        td_mode: field("tdMode"),
+        px: field("px"),
+        sz: Decimal::from_str(&field("sz")).unwrap_or_default(),
+        acc_fill_sz: Decimal::ZERO,
+        avg_px: Decimal::ZERO,
+        fill_sz: Decimal::ZERO,
+        fill_px: Decimal::ZERO,
+        state: "live".to_string(),
+        c_time: created,
+        u_time: created,
+    };
+    state.orders.lock().push(order.clone());
+    state.push_order(&order);
+
+    if !script.fills.is_empty() {
+        let state = state.clone();
+        let ord_id = order.ord_id.clone();
+        let price = script
+            .fill_price
+            .or_else(|| Decimal::from_str(&order.px).ok())
+            .or_else(|| state.last_prices.lock().get(&order.inst_id).copied());
+        tokio::spawn(async move {
+            let Some(price) = price else {
+                warn!("No price to fill order {} at", ord_id);
+                return;
+            };
+            for quantity in script.fills {
+                tokio::time::sleep(script.fill_delay).await;
+                state.fill(&ord_id, quantity, price);
+            }
+        });
+    }
+
+    tokio::time::sleep(script.ack_delay).await;
+    envelope(
+        "0",
+        "",
+        vec![json!({
+            "ordId": order.ord_id,
+            "clOrdId": order.cl_ord_id,
+            "tag": order.tag,
+            "ts": created.to_string(),
+            "sCode": "0",
+            "sMsg": "Order placed",
+        })],
+    )
+    .into_response()
+}
+
+async fn order(
+    State(state): State<Arc<MockState>>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Json<Value> {
+    let id = |name: &str| query.get(name).filter(|id| !id.is_empty());
+    let (ord_id, cl_ord_id) = (id("ordId"), id("clOrdId"));
+    let found = state
+        .orders
+        .lock()
+        .iter()
+        .find(|o| {
+            query.get("instId") == Some(&o.inst_id)
+                && (ord_id == Some(&o.ord_id) || cl_ord_id == Some(&o.cl_ord_id))
+        })
+        .map(MockOrder::to_json);
+    match found {
+        Some(order) => envelope("0", "", vec![order]),
+        None => envelope("51603", "Order does not exist", Vec::new()),
+    }
+}
+
+async fn cancel_order(State(state): State<Arc<MockState>>, Json(body): Json<Value>) -> Response {
+    let field = |name: &str| body[name].as_str().filter(|id| !id.is_empty());
+    let (inst_id, ord_id, cl_ord_id) = (field("instId"), field("ordId"), field("clOrdId"));
+
+    let mut orders = state.orders.lock();
+    let Some(order) = orders.iter_mut().find(|o| {
+        inst_id == Some(o.inst_id.as_str())
+            && (ord_id == Some(o.ord_id.as_str()) || cl_ord_id == Some(o.cl_ord_id.as_str()))
+    }) else {
+        return order_failed(
+            cl_ord_id.unwrap_or_default(),
+            "51400",
+            "Order cancellation failed as the order does not exist",
+        );
+    };
+    match order.state.as_str() {
+        "canceled" => return order_failed(&order.cl_ord_id, "51401", "Order has been canceled"),
+        "filled" => return order_failed(&order.cl_ord_id, "51402", "Order has been completed"),
+        _ => {}
+    }
+
+    order.state = "canceled".to_string();
+    order.u_time = now();
+    state.push_order(order);
+    envelope(
+        "0",
+        "",
+        vec![json!({
+            "ordId": order.ord_id,
+            "clOrdId": order.cl_ord_id,
+            "sCode": "0",
+            "sMsg": "",
+        })],
+    )
+    .into_response()
+}
+
+async fn unknown_path(request: Request) -> Response {
+    (
+        StatusCode::NOT_FOUND,
+        envelope(
+            "50000",
+            &format!("Mock OKX has no route for {}", request.uri().path()),
+            Vec::new(),
+        ),
+    )
+        .into_response()
+}
+
+fn is_listed(state: &MockState, inst_id: &str) -> bool {
+    let instruments = state.instruments.lock();
+    instruments.is_empty() || instruments.iter().any(|i| i["instId"] == inst_id)
+}
+
+/// Order request refused with per-order `code`, as OKX answers them
+fn order_failed(cl_ord_id: &str, code: &str, msg: &str) -> Response {
+    Json(json!({
+        "code": "1",
+        "msg": "All operations failed",
+        "data": [{
+            "ordId": "",
+            "clOrdId": cl_ord_id,
+            "tag": "",
+            "sCode": code,
+            "sMsg": msg,
+        }],
+    }))
+    .into_response()
+}
@@ -0,0 +1,270 @@
+//! Scripted exchange behavior
+//!
+//! An [`OrderScript`] decides what happens to the next order placed: how
+//! long its acknowledgement takes, whether it is refused, and which fills
+//! the orders channel then reports. A [`Fault`] replaces the answer to the
+//! next request on a path, whatever endpoint serves it.
+
+use axum::Json;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use rust_decimal::Decimal;
+use serde_json::{Value, json};
+use std::time::Duration;
+
+/// Fate of one placed order
+#[derive(Debug, Clone)]
+pub struct OrderScript {
+    pub(crate) ack_delay: Duration,
+    pub(crate) rejection: Option<(String, String)>,
+    pub(crate) fills: Vec<Option<Decimal>>,
+    pub(crate) fill_price: Option<Decimal>,
+    pub(crate) fill_delay: Duration,
+}
+
+impl Default for OrderScript {
+    fn default() -> Self {
+        Self {
+            ack_delay: Duration::ZERO,
+            rejection: None,
+            fills: Vec::new(),
+            fill_price: None,
+            fill_delay: Duration::from_millis(10),
+        }
+    }
+}
+
+impl OrderScript {
+    /// Acknowledge the order at once and leave it resting
+    pub fn accept() -> Self {
+        Self::default()
+    }
+
+    /// Refuse the order with OKX error `code`, e.g. `51008` for an
+    /// insufficient balance
+    pub fn reject(code: &str, msg: &str) -> Self {
+        Self {
+            rejection: Some((code.to_string(), msg.to_string())),
+            ..Self::default()
+        }
+    }
+
+    /// Hold the acknowledgement back for `delay`
+    ///
+    /// The order is live on the exchange, and pushed on the orders channel,
+    /// before the acknowledgement is sent, as when a response is lost in
+    /// transit.
+    pub fn ack_after(mut self, delay: Duration) -> Self {
+        self.ack_delay = delay;
+        self
+    }
+
+    /// Fill `quantity` of the order; repeat for several partial fills
+    pub fn fill(mut self, quantity: Decimal) -> Self {
+        self.fills.push(Some(quantity));
+        self
+    }
+
+    /// Fill whatever quantity is left
+    pub fn fill_rest(mut self) -> Self {
+        self.fills.push(None);
+        self
+    }
+
+    /// Fill at `price` rather than the order's limit price
+    pub fn fill_at(mut self, price: Decimal) -> Self {
+        self.fill_price = Some(price);
+        self
+    }
+
+    /// Wait `delay` before each fill; 10ms by default
+    pub fn fill_every(mut self, delay: Duration) -> Self {
+        self.fill_delay = delay;
+        self
+    }
+}
+
+/// Answer replacing the next response on a path
+#[derive(Debug, Clone)]
+pub enum Fault {
+    /// OKX error envelope with `code`, answered with HTTP 200 as OKX does
+    Api { code: String, msg: String },
+
+    /// HTTP 429 with OKX's rate limit error
+    RateLimited,
+
+    /// Bare HTTP status, such as a gateway error in front of the API
+    Status(u16),
+
+    /// The normal answer, after `delay`
+    Delay(Duration),
+}
+
+impl Fault {
+    pub fn api(code: &str, msg: &str) -> Self {
+        Self::Api {
+            code: code.to_string(),
+            msg: msg.to_string(),
+        }
+    }
+
+    /// Response for the fault, or `None` when the request is served normally
+    pub(crate) fn response(&self) -> Option<Response> {
+        match self {
+            Self::Api { code, msg } => Some(envelope(code, msg, Vec::new()).into_response()),
+            Self::RateLimited => Some(
+                (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    envelope("50011", "Too Many Requests", Vec::new()),
+                )
+                    .into_response(),
+            ),
+            Self::Status(status) => Some(
+                StatusCode::from_u16(*status)
+                    .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+                    .into_response(),
+            ),
+            Self::Delay(_) => None,
+        }
+    }
+}
+
+/// OKX response envelope
+pub(crate) fn envelope(code: &str, msg: &str, data: Vec<Value>) -> Json<Value> {
+    Json(json!({ "code": code, "msg": msg, "data": data }))
+}
+
+/// Order resting on the mock exchange
+#[derive(Debug, Clone)]
+pub(crate) struct MockOrder {
+    pub inst_id: String,
+    pub ord_id: String,
+    pub cl_ord_id: String,
+    pub tag: String,
+    pub side: String,
+    pub ord_type: String,
+    pub td_mode: String,
+    pub px: String,
+    pub sz: Decimal,
+    pub acc_fill_sz: Decimal,
+    pub avg_px: Decimal,
+    pub fill_sz: Decimal,
+    pub fill_px: Decimal,
+    pub state: String,
+    pub c_time: i64,
+    pub u_time: i64,
+}
+
+impl MockOrder {
+    pub fn is_open(&self) -> bool {
+        matches!(self.state.as_str(), "live" | "partially_filled")
+    }
+
+    /// Fill `quantity`, or everything left when `None`, at `price`
+    ///
+    /// Returns false when nothing was left to fill.
+    pub fn fill(&mut self, quantity: Option<Decimal>, price: Decimal, now: i64) -> bool {
+        let remaining = self.sz - self.acc_fill_sz;
+        let quantity = quantity.unwrap_or(remaining).min(remaining);
+        if !self.is_open() || quantity <= Decimal::ZERO {
+            return false;
+        }
+
+        let filled = self.acc_fill_sz + quantity;
+        self.avg_px = (self.avg_px * self.acc_fill_sz + price * quantity) / filled;
+        self.acc_fill_sz = filled;
+        self.fill_sz = quantity;
+        self.fill_px = price;
+        self.state = if filled >= self.sz {
+            "filled"
+        } else {
+            "partially_filled"
+        }
+        .to_string();
+        self.u_time = now;
+        true
+    }
+
+    /// The order as `GET /api/v5/trade/order` and the orders channel show it
+    pub fn to_json(&self) -> Value {
+        let optional = |value: Decimal| {
+            if value.is_zero() {
+                String::new()
+            } else {
+                value.normalize().to_string()
+            }
+        };
+        json!({
+            "instType": inst_type(&self.inst_id),
+            "instId": self.inst_id,
+            "ordId": self.ord_id,
+            "clOrdId": self.cl_ord_id,
+            "tag": self.tag,
+            "px": self.px,
+            "sz": self.sz.normalize().to_string(),
+            "ordType": self.ord_type,
+            "side": self.side,
+            "tdMode": self.td_mode,
+            "fillPx": optional(self.fill_px),
+            "fillSz": optional(self.fill_sz),
+            "accFillSz": self.acc_fill_sz.normalize().to_string(),
+            "avgPx": optional(self.avg_px),
+            "state": self.state,
+            "uTime": self.u_time.to_string(),
+            "cTime": self.c_time.to_string(),
+        })
+    }
+}
+
+/// OKX instrument type of `inst_id`
+pub(crate) fn inst_type(inst_id: &str) -> &'static str {
+    let parts: Vec<&str> = inst_id.split('-').collect();
+    match parts.as_slice() {
+        [.., "SWAP"] => "SWAP",
+        [_, _, _, _, _] => "OPTION",
+        [_, _, _] => "FUTURES",
+        _ => "SPOT",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_fills_accumulate_until_filled() {
+        let mut order = MockOrder {
+            inst_id: "BTC-USDT".to_string(),
+            ord_id: "1".to_string(),
+            cl_ord_id: "c1".to_string(),
+            tag: String::new(),
+            side: "buy".to_string(),
+            ord_type: "limit".to_string(),
+            td_mode: "cash".to_string(),
+            px: "100".to_string(),
+            sz: dec!(1),
+            acc_fill_sz: Decimal::ZERO,
+            avg_px: Decimal::ZERO,
+            fill_sz: Decimal::ZERO,
+            fill_px: Decimal::ZERO,
+            state: "live".to_string(),
+            c_time: 0,
+            u_time: 0,
+        };
+        assert_eq!(order.to_json()["avgPx"], "");
+
+        assert!(order.fill(Some(dec!(0.4)), dec!(100), 1));
+        assert_eq!(order.state, "partially_filled");
+        assert!(order.fill(None, dec!(90), 2));
+        assert_eq!(order.state, "filled");
+        assert_eq!(order.avg_px, dec!(94));
+        assert!(!order.fill(None, dec!(90), 3));
+
+        let json = order.to_json();
+        assert_eq!(json["accFillSz"], "1");
+        assert_eq!(json["fillSz"], "0.6");
+        assert_eq!(inst_type("BTC-USDT-SWAP"), "SWAP");
+        assert_eq!(inst_type("BTC-USD-241227"), "FUTURES");
+    }
+}
@@ -0,0 +1,125 @@
+//! WebSocket endpoints
+
+use crate::{MockState, is_private};
+use axum::Router;
+use axum::extract::State;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::response::Response;
+use axum::routing::get;
+use serde_json::{Value, json};
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+
+pub(crate) fn router(state: Arc<MockState>) -> Router {
+    Router::new()
+        .route("/ws/v5/public", get(public))
+        .route("/ws/v5/private", get(private))
+        .with_state(state)
+}
+
+async fn public(ws: WebSocketUpgrade, State(state): State<Arc<MockState>>) -> Response {
+    ws.on_upgrade(move |socket| serve(socket, state, false))
+}
+
+async fn private(ws: WebSocketUpgrade, State(state): State<Arc<MockState>>) -> Response {
+    ws.on_upgrade(move |socket| serve(socket, state, true))
+}
+
+/// One connection: answer its requests and forward the pushes it
+/// subscribed to, until either side closes
+async fn serve(mut socket: WebSocket, state: Arc<MockState>, private: bool) {
+    let conn_id = state.next_id();
+    let mut pushes = state.pushes.subscribe();
+    let mut logged_in = false;
+
+    'connection: loop {
+        let frames = tokio::select! {
+            message = socket.recv() => match message {
+                Some(Ok(Message::Text(text))) if text == "ping" => vec!["pong".to_string()],
+                Some(Ok(Message::Text(text))) => {
+                    handle(&state, conn_id, private, &mut logged_in, &text)
+                        .iter()
+                        .map(Value::to_string)
+                        .collect()
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => continue,
+            },
+            push = pushes.recv() => match push {
+                Ok(push) if state.is_subscribed(conn_id, &push.channel, &push.inst_id) => {
+                    vec![push.frame]
+                }
+                Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            },
+        };
+        for frame in frames {
+            if socket.send(Message::Text(frame)).await.is_err() {
+                break 'connection;
+            }
+        }
+    }
+
+    state.subscriptions.lock().remove(&conn_id);
+}
+
+/// Replies to one request frame
+fn handle(
+    state: &MockState,
+    conn_id: u64,
+    private: bool,
+    logged_in: &mut bool,
+    text: &str,
+) -> Vec<Value> {
+    let Ok(request) = serde_json::from_str::<Value>(text) else {
+        return vec![error("60012", &format!("Invalid request: {}", text))];
+    };
+    let args = request["args"].as_array().cloned().unwrap_or_default();
+
+    match request["op"].as_str() {
+        Some("login") if private => {
+            let signed = args.first().is_some_and(|arg| {
+                ["apiKey", "passphrase", "timestamp", "sign"]
+                    .iter()
+                    .all(|key| arg[key].as_str().is_some_and(|v| !v.is_empty()))
+            });
+            if !signed {
+                return vec![error("60009", "Login failed.")];
+            }
+            *logged_in = true;
+            vec![json!({ "event": "login", "code": "0", "msg": "", "connId": conn_id.to_string() })]
+        }
+        Some("subscribe") => args
+            .into_iter()
+            .map(|arg| {
+                let channel = arg["channel"].as_str().unwrap_or_default();
+                if is_private(channel) != private {
+                    return error("60018", &format!("Wrong URL or channel:{}", channel));
+                }
+                if private && !*logged_in {
+                    return error("60011", "Please log in");
+                }
+                let mut subscriptions = state.subscriptions.lock();
+                let subscribed = subscriptions.entry(conn_id).or_default();
+                if !subscribed.contains(&arg) {
+                    subscribed.push(arg.clone());
+                }
+                json!({ "event": "subscribe", "arg": arg, "connId": conn_id.to_string() })
+            })
+            .collect(),
+        Some("unsubscribe") => args
+            .into_iter()
+            .map(|arg| {
+                if let Some(subscribed) = state.subscriptions.lock().get_mut(&conn_id) {
+                    subscribed.retain(|s| *s != arg);
+                }
+                json!({ "event": "unsubscribe", "arg": arg, "connId": conn_id.to_string() })
+            })
+            .collect(),
+        _ => vec![error("60012", &format!("Invalid request: {}", text))],
+    }
+}
+
+fn error(code: &str, msg: &str) -> Value {
+    json!({ "event": "error", "code": code, "msg": msg })
+}
@@ -32,6 +32,32 @@ pub enum ComparisonOperator {
     LessThanOrEqual,
 }
 
+impl ComparisonOperator {
+    /// Apply the operator as `value <op> threshold`
+    pub fn compare(&self, value: f64, threshold: f64) -> bool {
+        match self {
+            ComparisonOperator::GreaterThan => value > threshold,
+            ComparisonOperator::LessThan => value < threshold,
+            ComparisonOperator::Equals => (value - threshold).abs() < f64::EPSILON,
+            ComparisonOperator::NotEquals => (value - threshold).abs() >= f64::EPSILON,
+            ComparisonOperator::GreaterThanOrEqual => value >= threshold,
+            ComparisonOperator::LessThanOrEqual => value <= threshold,
+        }
+    }
+
+    /// Operator as written in rule expressions
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            ComparisonOperator::GreaterThan => ">",
+            ComparisonOperator::LessThan => "<",
+            ComparisonOperator::Equals => "==",
+            ComparisonOperator::NotEquals => "!=",
+            ComparisonOperator::GreaterThanOrEqual => ">=",
+            ComparisonOperator::LessThanOrEqual => "<=",
+        }
+    }
+}
+
 /// Alert rule definition
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AlertRule {
@@ -80,18 +106,9 @@ impl AlertRule {
             return false;
         }
 
-        match self.condition.operator {
-            ComparisonOperator::GreaterThan => metric_value > self.condition.threshold,
-            ComparisonOperator::LessThan => metric_value < self.condition.threshold,
-            ComparisonOperator::Equals => {
-                (metric_value - self.condition.threshold).abs() < f64::EPSILON
-            }
-            ComparisonOperator::NotEquals => {
-                (metric_value - self.condition.threshold).abs() >= f64::EPSILON
-            }
-            ComparisonOperator::GreaterThanOrEqual => metric_value >= self.condition.threshold,
-            ComparisonOperator::LessThanOrEqual => metric_value <= self.condition.threshold,
-        }
+        self.condition
+            .operator
+            .compare(metric_value, self.condition.threshold)
     }
 }
 
@@ -132,6 +149,30 @@ impl Alert {
         }
     }
 
+    /// Alert raised directly by a component rather than by a rule
+    pub fn event(
+        source: impl Into<String>,
+        severity: AlertSeverity,
+        message: impl Into<String>,
+    ) -> Self {
+        let source = source.into();
+        Self {
+            id: Uuid::new_v4(),
+            rule_id: Uuid::nil(),
+            rule_name: source.clone(),
+            severity,
+            message: message.into(),
+            metric_name: source,
+            metric_value: 0.0,
+            threshold: 0.0,
+            triggered_at: Utc::now(),
+            acknowledged: false,
+            acknowledged_at: None,
+            acknowledged_by: None,
+            metadata: HashMap::new(),
+        }
+    }
+
     /// Acknowledge the alert
     pub fn acknowledge(&mut self, user: impl Into<String>) {
         self.acknowledged = true;
@@ -0,0 +1,473 @@
+//! Health checkers for storage backends and the host
+//!
+//! Each checker probes the real dependency: a Redis PING round trip, the
+//! TimescaleDB connection pool occupancy and schema version, and the free
+//! space on the volume holding the data directory. Tick storage maintenance
+//! is judged from the runs its job records.
+
+use crate::metrics::HealthCheck;
+use crate::service::HealthChecker;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use ea_okx_data::storage::{PoolStats, RedisStorage, TimescaleStorage};
+use ea_okx_data::{SchemaVersion, TickMaintenanceState, TickMaintenanceStatus};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Redis health from PING latency
+pub struct RedisHealthChecker {
+    name: String,
+    storage: Arc<RedisStorage>,
+    slow_ms: u64,
+    timeout: Duration,
+}
+
+impl RedisHealthChecker {
+    pub fn new(storage: Arc<RedisStorage>) -> Self {
+        Self {
+            name: "redis".to_string(),
+            storage,
+            slow_ms: 50,
+            timeout: Duration::from_secs(2),
+        }
+    }
+
+    /// Degraded above `slow_ms`, unhealthy when no reply within `timeout`
+    pub fn with_thresholds(mut self, slow_ms: u64, timeout: Duration) -> Self {
+        self.slow_ms = slow_ms;
+        self.timeout = timeout;
+        self
+    }
+}
+
+#[async_trait]
+impl HealthChecker for RedisHealthChecker {
+    async fn check(&self) -> HealthCheck {
+        let start = Instant::now();
+        let result = tokio::time::timeout(self.timeout, self.storage.ping()).await;
+        let elapsed = start.elapsed().as_millis() as u64;
+
+        match result {
+            Err(_) => HealthCheck::unhealthy(
+                &self.name,
+                format!("PING timed out after {}ms", self.timeout.as_millis()),
+                elapsed,
+            ),
+            Ok(Err(e)) => {
+                HealthCheck::unhealthy(&self.name, format!("PING failed: {}", e), elapsed)
+            }
+            Ok(Ok(())) if elapsed > self.slow_ms => {
+                HealthCheck::degraded(&self.name, format!("PING took {}ms", elapsed), elapsed)
+            }
+            Ok(Ok(())) => HealthCheck::healthy(&self.name, "PONG", elapsed),
+        }
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// TimescaleDB health from connection pool saturation
+pub struct PoolHealthChecker {
+    name: String,
+    storage: Arc<TimescaleStorage>,
+    degraded_utilization: f64,
+}
+
+impl PoolHealthChecker {
+    pub fn new(storage: Arc<TimescaleStorage>) -> Self {
+        Self {
+            name: "timescaledb".to_string(),
+            storage,
+            degraded_utilization: 0.8,
+        }
+    }
+
+    /// Degraded once this share of the pool is in use (0.0 to 1.0)
+    pub fn with_degraded_utilization(mut self, utilization: f64) -> Self {
+        self.degraded_utilization = utilization;
+        self
+    }
+
+    /// Judge a pool snapshot; an exhausted pool is unhealthy since queries
+    /// queue behind it
+    pub fn assess(&self, stats: &PoolStats) -> HealthCheck {
+        let summary = format!(
+            "{}/{} connections in use",
+            stats.in_use(),
+            stats.max_connections
+        );
+        if stats.in_use() >= stats.max_connections {
+            HealthCheck::unhealthy(&self.name, format!("Pool exhausted: {}", summary), 0)
+        } else if stats.utilization() >= self.degraded_utilization {
+            HealthCheck::degraded(&self.name, format!("Pool nearly full: {}", summary), 0)
+        } else {
+            HealthCheck::healthy(&self.name, summary, 0)
+        }
+    }
+}
+
+#[async_trait]
+impl HealthChecker for PoolHealthChecker {
+    async fn check(&self) -> HealthCheck {
+        self.assess(&self.storage.pool_stats())
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// TimescaleDB schema against the migrations this build expects
+pub struct SchemaHealthChecker {
+    name: String,
+    storage: Arc<TimescaleStorage>,
+}
+
+impl SchemaHealthChecker {
+    pub fn new(storage: Arc<TimescaleStorage>) -> Self {
+        Self {
+            name: "schema".to_string(),
+            storage,
+        }
+    }
+
+    /// Degraded while migrations are pending: queries against tables they
+    /// create or change will fail
+    pub fn assess(&self, version: &SchemaVersion) -> HealthCheck {
+        let current = version
+            .current
+            .map_or_else(|| "none".to_string(), |v| v.to_string());
+        if version.is_current() {
+            HealthCheck::healthy(&self.name, format!("Schema at migration {}", current), 0)
+        } else {
+            HealthCheck::degraded(
+                &self.name,
+                format!(
+                    "Schema at migration {}, {} of {} pending",
+                    current,
+                    version.pending.len(),
+                    version.latest
+                ),
+                0,
+            )
+        }
+    }
+}
+
+#[async_trait]
+impl HealthChecker for SchemaHealthChecker {
+    async fn check(&self) -> HealthCheck {
+        let start = Instant::now();
+        let version = self.storage.schema_version().await;
+        let elapsed = start.elapsed().as_millis() as u64;
+
+        match version {
+            Ok(version) => {
+                let mut check = self.assess(&version);
+                check.response_time_ms = elapsed;
+                check
+            }
+            Err(e) => HealthCheck::unhealthy(
+                &self.name,
+                format!("Cannot read schema version: {}", e),
+                elapsed,
+            ),
+        }
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Free space on the volume holding a directory
+pub struct DiskSpaceHealthChecker {
+    name: String,
+    path: PathBuf,
+    degraded_below: f64,
+    unhealthy_below: f64,
+}
+
+impl DiskSpaceHealthChecker {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            name: "disk".to_string(),
+            path: path.into(),
+            degraded_below: 0.10,
+            unhealthy_below: 0.05,
+        }
+    }
+
+    /// Degraded and unhealthy below these shares of the volume free
+    pub fn with_thresholds(mut self, degraded_below: f64, unhealthy_below: f64) -> Self {
+        self.degraded_below = degraded_below;
+        self.unhealthy_below = unhealthy_below;
+        self
+    }
+
+    /// Judge `available` of `total` bytes free
+    pub fn assess(&self, available: u64, total: u64) -> HealthCheck {
+        let free = if total == 0 {
+            0.0
+        } else {
+            available as f64 / total as f64
+        };
+        let summary = format!(
+            "{:.1} GiB free of {:.1} GiB ({:.1}%) under {}",
+            available as f64 / GIB,
+            total as f64 / GIB,
+            free * 100.0,
+            self.path.display()
+        );
+
+        if free < self.unhealthy_below {
+            HealthCheck::unhealthy(&self.name, summary, 0)
+        } else if free < self.degraded_below {
+            HealthCheck::degraded(&self.name, summary, 0)
+        } else {
+            HealthCheck::healthy(&self.name, summary, 0)
+        }
+    }
+}
+
+const GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// Nearest existing ancestor, so a data directory not created yet is
+/// measured on the volume it will live on
+fn existing_ancestor(path: &Path) -> Option<&Path> {
+    path.ancestors()
+        .find(|p| !p.as_os_str().is_empty() && p.exists())
+}
+
+#[async_trait]
+impl HealthChecker for DiskSpaceHealthChecker {
+    async fn check(&self) -> HealthCheck {
+        let start = Instant::now();
+        let target = existing_ancestor(&self.path).unwrap_or(Path::new("."));
+        let space = fs2::available_space(target)
+            .and_then(|available| fs2::total_space(target).map(|total| (available, total)));
+        let elapsed = start.elapsed().as_millis() as u64;
+
+        match space {
+            Ok((available, total)) => {
+                let mut check = self.assess(available, total);
+                check.response_time_ms = elapsed;
+                check
+            }
+            Err(e) => HealthCheck::unhealthy(
+                &self.name,
+                format!("Cannot read free space for {}: {}", self.path.display(), e),
+                elapsed,
+            ),
+        }
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Tick storage maintenance from the job's recorded runs
+pub struct TickMaintenanceHealthChecker {
+    name: String,
+    status: TickMaintenanceStatus,
+    max_age: chrono::Duration,
+    unhealthy_after_failures: u32,
+}
+
+impl TickMaintenanceHealthChecker {
+    /// `interval` is how often the job runs; a success older than two
+    /// intervals degrades the check
+    pub fn new(status: TickMaintenanceStatus, interval: Duration) -> Self {
+        Self {
+            name: "tick_maintenance".to_string(),
+            status,
+            max_age: chrono::Duration::from_std(interval * 2).unwrap_or(chrono::Duration::MAX),
+            unhealthy_after_failures: 3,
+        }
+    }
+
+    /// Judge the recorded runs at `now`
+    ///
+    /// Degraded when the newest run failed or the last success is overdue,
+    /// unhealthy after several failures in a row: ticks then pile up past
+    /// their retention, uncompressed.
+    pub fn assess(&self, state: &TickMaintenanceState, now: DateTime<Utc>) -> HealthCheck {
+        if state.consecutive_failures >= self.unhealthy_after_failures {
+            return HealthCheck::unhealthy(
+                &self.name,
+                format!(
+                    "{} maintenance runs failed in a row: {}",
+                    state.consecutive_failures,
+                    state.last_error.as_deref().unwrap_or("unknown error")
+                ),
+                0,
+            );
+        }
+        if state.consecutive_failures > 0 {
+            return HealthCheck::degraded(
+                &self.name,
+                format!(
+                    "Last maintenance run failed: {}",
+                    state.last_error.as_deref().unwrap_or("unknown error")
+                ),
+                0,
+            );
+        }
+
+        let Some(report) = &state.last_success else {
+            return HealthCheck::healthy(&self.name, "Waiting for the first maintenance run", 0);
+        };
+        let summary = format!(
+            "{} chunks dropped, {} rows deleted, {} chunks compressed, table at {:.1} GiB",
+            report.chunks_dropped,
+            report.rows_deleted,
+            report.chunks_compressed,
+            report.table_bytes as f64 / GIB
+        );
+        let age = now - report.finished_at;
+        if age > self.max_age {
+            HealthCheck::degraded(
+                &self.name,
+                format!(
+                    "No maintenance run for {} minutes; last: {}",
+                    age.num_minutes(),
+                    summary
+                ),
+                0,
+            )
+        } else {
+            HealthCheck::healthy(&self.name, format!("Last run: {}", summary), 0)
+        }
+    }
+}
+
+#[async_trait]
+impl HealthChecker for TickMaintenanceHealthChecker {
+    async fn check(&self) -> HealthCheck {
+        self.assess(&self.status.snapshot(), Utc::now())
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::HealthStatus;
+    use ea_okx_data::TickMaintenanceReport;
+
+    #[tokio::test]
+    async fn test_pool_saturation_levels() {
+        let stats = |size, idle| PoolStats {
+            size,
+            idle,
+            max_connections: 10,
+        };
+        // Never connects: `assess` judges the stats it is given
+        let pool = sqlx::PgPool::connect_lazy("postgres://127.0.0.1:1/none").unwrap();
+        let checker = PoolHealthChecker::new(Arc::new(TimescaleStorage::from_pool(pool)));
+        let status = |size, idle| checker.assess(&stats(size, idle)).status;
+
+        assert_eq!(status(4, 2), HealthStatus::Healthy);
+        assert_eq!(status(10, 2), HealthStatus::Degraded);
+        assert_eq!(status(10, 0), HealthStatus::Unhealthy);
+    }
+
+    #[tokio::test]
+    async fn test_pending_migrations_degrade_schema_health() {
+        let pool = sqlx::PgPool::connect_lazy("postgres://127.0.0.1:1/none").unwrap();
+        let checker = SchemaHealthChecker::new(Arc::new(TimescaleStorage::from_pool(pool)));
+        let version = |current: Option<i64>, pending: Vec<i64>| SchemaVersion {
+            current,
+            latest: 8,
+            pending,
+            applied_at: None,
+        };
+
+        let current = checker.assess(&version(Some(8), vec![]));
+        assert_eq!(current.status, HealthStatus::Healthy);
+        assert_eq!(current.message, "Schema at migration 8");
+
+        let behind = checker.assess(&version(Some(6), vec![7, 8]));
+        assert_eq!(behind.status, HealthStatus::Degraded);
+        assert_eq!(behind.message, "Schema at migration 6, 2 of 8 pending");
+        assert_eq!(
+            checker.assess(&version(None, (1..=8).collect())).status,
+            HealthStatus::Degraded
+        );
+    }
+
+    #[test]
+    fn test_tick_maintenance_failures_and_overdue_runs() {
+        let checker = TickMaintenanceHealthChecker::new(
+            TickMaintenanceStatus::new(),
+            Duration::from_secs(3600),
+        );
+        let now = Utc::now();
+        let report = |finished_at| TickMaintenanceReport {
+            started_at: finished_at,
+            finished_at,
+            chunks_dropped: 1,
+            rows_deleted: 200,
+            chunks_compressed: 3,
+            table_bytes: 0,
+        };
+        let status = |state: &TickMaintenanceState| checker.assess(state, now).status;
+
+        let mut state = TickMaintenanceState::default();
+        assert_eq!(status(&state), HealthStatus::Healthy);
+
+        state.last_success = Some(report(now - chrono::Duration::minutes(30)));
+        let check = checker.assess(&state, now);
+        assert_eq!(check.status, HealthStatus::Healthy);
+        assert!(check.message.contains("3 chunks compressed"));
+
+        state.last_success = Some(report(now - chrono::Duration::hours(3)));
+        assert_eq!(status(&state), HealthStatus::Degraded);
+
+        state.last_success = Some(report(now));
+        state.consecutive_failures = 1;
+        state.last_error = Some("connection refused".to_string());
+        assert_eq!(status(&state), HealthStatus::Degraded);
+        state.consecutive_failures = 3;
+        assert_eq!(status(&state), HealthStatus::Unhealthy);
+    }
+
+    #[test]
+    fn test_disk_space_thresholds() {
+        let checker = DiskSpaceHealthChecker::new("/data");
+        assert_eq!(checker.assess(50, 100).status, HealthStatus::Healthy);
+        assert_eq!(checker.assess(8, 100).status, HealthStatus::Degraded);
+        assert_eq!(checker.assess(2, 100).status, HealthStatus::Unhealthy);
+        assert_eq!(checker.assess(0, 0).status, HealthStatus::Unhealthy);
+    }
+
+    #[tokio::test]
+    async fn test_disk_space_measures_missing_directory_on_its_volume() {
+        let missing = std::env::temp_dir()
+            .join("ea-okx-health")
+            .join("not-created");
+        let check = DiskSpaceHealthChecker::new(missing)
+            .with_thresholds(0.0, 0.0)
+            .check()
+            .await;
+        assert_eq!(check.status, HealthStatus::Healthy);
+        assert!(check.message.contains("GiB free"));
+    }
+
+    #[tokio::test]
+    async fn test_redis_unreachable_is_unhealthy() {
+        let storage = Arc::new(RedisStorage::new("redis://127.0.0.1:1/").unwrap());
+        let check = RedisHealthChecker::new(storage)
+            .with_thresholds(50, Duration::from_millis(500))
+            .check()
+            .await;
+        assert_eq!(check.status, HealthStatus::Unhealthy);
+    }
+}
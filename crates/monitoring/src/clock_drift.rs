@@ -0,0 +1,159 @@
+//! Exchange clock drift monitoring
+//!
+//! The REST client signs requests on the exchange clock by adding the offset
+//! it last measured ([`ClockSync`]), so drift alone does not break trading.
+//! It still points at a host whose clock is wrong, and once syncs start
+//! failing the compensation goes stale. Every check reports the offset as a
+//! gauge and raises one Warning alert per excursion past the threshold or
+//! run of failed syncs, plus an Info alert once the clock is back in line.
+
+use crate::alerts::{Alert, AlertSeverity};
+use crate::error::Result;
+use crate::service::MonitoringService;
+use ea_okx_client::{ClockSync, ClockSyncMetrics};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Exchange clock minus local clock, in milliseconds
+pub const CLOCK_OFFSET_MS: &str = "okx_clock_offset_ms";
+
+/// Alerts on the offset to the exchange clock measured by the REST client
+pub struct ClockDriftMonitor {
+    clock: ClockSync,
+    monitoring: Arc<MonitoringService>,
+
+    /// Offset in either direction worth alerting on
+    max_offset_ms: i64,
+
+    /// Failed syncs in a row worth alerting on
+    max_failures: u32,
+
+    drifting: AtomicBool,
+}
+
+impl ClockDriftMonitor {
+    pub fn new(clock: ClockSync, monitoring: Arc<MonitoringService>) -> Self {
+        Self {
+            clock,
+            monitoring,
+            max_offset_ms: 1000,
+            max_failures: 3,
+            drifting: AtomicBool::new(false),
+        }
+    }
+
+    pub fn with_max_offset_ms(mut self, max_offset_ms: i64) -> Self {
+        self.max_offset_ms = max_offset_ms;
+        self
+    }
+
+    pub fn with_max_failures(mut self, max_failures: u32) -> Self {
+        self.max_failures = max_failures;
+        self
+    }
+
+    /// Why the clock needs attention, if it does
+    pub fn assess(&self, metrics: &ClockSyncMetrics) -> Vec<String> {
+        let mut reasons = Vec::new();
+        let offset = metrics.offset_ms();
+        if offset.abs() > self.max_offset_ms {
+            reasons.push(format!(
+                "Local clock is {}ms {} OKX (limit {}ms)",
+                offset.abs(),
+                if offset > 0 { "behind" } else { "ahead of" },
+                self.max_offset_ms
+            ));
+        }
+        if metrics.consecutive_failures >= self.max_failures {
+            reasons.push(format!(
+                "Clock sync failed {} times in a row: {}",
+                metrics.consecutive_failures,
+                metrics.last_error.as_deref().unwrap_or("unknown error")
+            ));
+        }
+        reasons
+    }
+
+    /// Report the offset and alert on entering or leaving drift
+    pub async fn check(&self) -> Result<()> {
+        let metrics = self.clock.snapshot().await;
+        if metrics.last_sample.is_some() {
+            self.monitoring
+                .evaluate_metric(CLOCK_OFFSET_MS, metrics.offset_ms() as f64)
+                .await?;
+        }
+
+        let reasons = self.assess(&metrics);
+        let drifting = !reasons.is_empty();
+        if self.drifting.swap(drifting, Ordering::SeqCst) == drifting {
+            return Ok(());
+        }
+
+        let mut alert = if drifting {
+            Alert::event("clock_drift", AlertSeverity::Warning, reasons.join("; "))
+        } else {
+            Alert::event(
+                "clock_drift_recovered",
+                AlertSeverity::Info,
+                "Clock offset to OKX is back within limits",
+            )
+        };
+        alert
+            .metadata
+            .insert("offset_ms".to_string(), metrics.offset_ms().to_string());
+        self.monitoring.raise_alert(alert).await;
+        Ok(())
+    }
+
+    /// Check every `interval` until the task is aborted
+    pub fn start(self: Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.check().await {
+                    tracing::warn!("Clock drift check failed: {}", e);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use ea_okx_client::ClockSample;
+
+    fn metrics(offset_ms: i64, consecutive_failures: u32) -> ClockSyncMetrics {
+        ClockSyncMetrics {
+            last_sample: Some(ClockSample {
+                offset_ms,
+                round_trip_ms: 50,
+                measured_at: Utc::now(),
+            }),
+            consecutive_failures,
+            last_error: Some("timeout".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_assess_flags_offset_and_failed_syncs() {
+        let monitor = ClockDriftMonitor::new(ClockSync::new(), Arc::new(MonitoringService::new()));
+
+        assert!(monitor.assess(&metrics(-800, 0)).is_empty());
+        assert!(monitor.assess(&ClockSyncMetrics::default()).is_empty());
+
+        let reasons = monitor.assess(&metrics(-2500, 0));
+        assert_eq!(
+            reasons,
+            vec!["Local clock is 2500ms ahead of OKX (limit 1000ms)"]
+        );
+
+        let reasons = monitor.assess(&metrics(1500, 3));
+        assert_eq!(reasons.len(), 2);
+        assert!(reasons[1].contains("3 times in a row: timeout"));
+    }
+}
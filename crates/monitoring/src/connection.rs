@@ -0,0 +1,287 @@
+//! WebSocket connection health
+//!
+//! Feeds [`ConnectionMetrics`] from the OKX WebSocket client into the alert
+//! pipeline so rules can fire on reconnect storms, slow pings or a market
+//! data feed that has gone silent.
+
+use crate::alerts::{AlertCondition, AlertRule, AlertSeverity, ComparisonOperator};
+use crate::error::Result;
+use crate::metrics::HealthCheck;
+use crate::service::{HealthChecker, MonitoringService};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use ea_okx_client::{ConnectionMetrics, ConnectionTelemetry};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Reconnects since the client was created
+pub const WS_RECONNECT_COUNT: &str = "ws_reconnect_count";
+
+/// Seconds since any market data arrived
+pub const WS_SECONDS_SINCE_LAST_MESSAGE: &str = "ws_seconds_since_last_message";
+
+/// Most recent ping round-trip time
+pub const WS_PING_RTT_MS: &str = "ws_ping_rtt_ms";
+
+/// Active subscriptions
+pub const WS_SUBSCRIPTION_COUNT: &str = "ws_subscription_count";
+
+/// Frames that failed to parse or deliver
+pub const WS_DROPPED_MESSAGES: &str = "ws_dropped_messages";
+
+/// Metric name for silence on one `channel:instId` key,
+/// e.g. `ws_seconds_since_last_message.tickers:BTC-USDT`
+pub fn channel_silence_metric(key: &str) -> String {
+    format!("{}.{}", WS_SECONDS_SINCE_LAST_MESSAGE, key)
+}
+
+/// Alert rule that fires when no market data has arrived for `seconds`
+pub fn market_data_silence_rule(seconds: u64) -> AlertRule {
+    AlertRule::new(
+        "Market Data Silent",
+        format!("No market data received for {} seconds", seconds),
+        AlertCondition {
+            metric_name: WS_SECONDS_SINCE_LAST_MESSAGE.to_string(),
+            operator: ComparisonOperator::GreaterThan,
+            threshold: seconds as f64,
+            duration_seconds: seconds,
+        },
+        AlertSeverity::Critical,
+    )
+}
+
+impl MonitoringService {
+    /// Evaluate alert rules against a connection metrics snapshot
+    pub async fn report_connection_metrics(&self, metrics: &ConnectionMetrics) -> Result<()> {
+        let now = Utc::now();
+
+        let mut values = vec![
+            (
+                WS_RECONNECT_COUNT.to_string(),
+                metrics.reconnect_count as f64,
+            ),
+            (
+                WS_SUBSCRIPTION_COUNT.to_string(),
+                metrics.subscription_count as f64,
+            ),
+            (
+                WS_DROPPED_MESSAGES.to_string(),
+                metrics.dropped_messages as f64,
+            ),
+        ];
+        if let Some(rtt) = metrics.ping_rtt_ms {
+            values.push((WS_PING_RTT_MS.to_string(), rtt));
+        }
+        if let Some(silence) = metrics.seconds_since_any_message(now) {
+            values.push((WS_SECONDS_SINCE_LAST_MESSAGE.to_string(), silence));
+        }
+        for key in metrics.last_message_at.keys() {
+            if let Some(silence) = metrics.seconds_since_last_message(key, now) {
+                values.push((channel_silence_metric(key), silence));
+            }
+        }
+
+        for (name, value) in values {
+            tracing::debug!(metric = %name, value = value, "Set gauge");
+            self.evaluate_metric(&name, value).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Report connection metrics every `interval` until the task is aborted
+    pub fn spawn_connection_reporter(
+        self: Arc<Self>,
+        telemetry: ConnectionTelemetry,
+        interval: Duration,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let metrics = telemetry.snapshot().await;
+                if let Err(e) = self.report_connection_metrics(&metrics).await {
+                    tracing::warn!("Failed to report connection metrics: {}", e);
+                }
+            }
+        })
+    }
+}
+
+/// Health checker for the market data WebSocket
+pub struct WebSocketHealthChecker {
+    name: String,
+    telemetry: ConnectionTelemetry,
+    max_silence_secs: f64,
+}
+
+impl WebSocketHealthChecker {
+    pub fn new(telemetry: ConnectionTelemetry, max_silence_secs: u64) -> Self {
+        Self {
+            name: "websocket".to_string(),
+            telemetry,
+            max_silence_secs: max_silence_secs as f64,
+        }
+    }
+}
+
+impl WebSocketHealthChecker {
+    /// Judge a metrics snapshot taken at `now`
+    ///
+    /// The feed as a whole is unhealthy once nothing has arrived for the
+    /// silence limit; individual `channel:instId` keys past the limit while
+    /// others still tick leave it degraded, naming the stale keys.
+    pub fn assess(&self, metrics: &ConnectionMetrics, now: DateTime<Utc>) -> HealthCheck {
+        let rtt = metrics.ping_rtt_ms.unwrap_or(0.0) as u64;
+        let Some(silence) = metrics.seconds_since_any_message(now) else {
+            return HealthCheck::unhealthy(&self.name, "Not connected", rtt);
+        };
+        if silence > self.max_silence_secs {
+            return HealthCheck::unhealthy(
+                &self.name,
+                format!("No market data for {:.0}s", silence),
+                rtt,
+            );
+        }
+
+        let mut stale: Vec<(&String, f64)> = metrics
+            .last_message_at
+            .keys()
+            .filter_map(|key| {
+                metrics
+                    .seconds_since_last_message(key, now)
+                    .filter(|s| *s > self.max_silence_secs)
+                    .map(|s| (key, s))
+            })
+            .collect();
+        if !stale.is_empty() {
+            stale.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+            let listed: Vec<String> = stale
+                .iter()
+                .map(|(key, s)| format!("{} ({:.0}s)", key, s))
+                .collect();
+            return HealthCheck::degraded(
+                &self.name,
+                format!("Stale market data: {}", listed.join(", ")),
+                rtt,
+            );
+        }
+
+        if silence > self.max_silence_secs / 2.0 {
+            HealthCheck::degraded(
+                &self.name,
+                format!("Market data delayed {:.0}s", silence),
+                rtt,
+            )
+        } else {
+            HealthCheck::healthy(
+                &self.name,
+                format!(
+                    "{} subscriptions, {} reconnects",
+                    metrics.subscription_count, metrics.reconnect_count
+                ),
+                rtt,
+            )
+        }
+    }
+}
+
+#[async_trait]
+impl HealthChecker for WebSocketHealthChecker {
+    async fn check(&self) -> HealthCheck {
+        let metrics = self.telemetry.snapshot().await;
+        self.assess(&metrics, Utc::now())
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::HealthStatus;
+    use chrono::Duration as ChronoDuration;
+
+    #[tokio::test]
+    async fn test_silence_rule_fires_when_feed_stalls() {
+        let service = MonitoringService::new();
+        service
+            .register_alert_rule(market_data_silence_rule(30))
+            .await
+            .unwrap();
+
+        let mut metrics = ConnectionMetrics {
+            subscription_count: 2,
+            ..Default::default()
+        };
+        metrics
+            .last_message_at
+            .insert("tickers:BTC-USDT".to_string(), Utc::now());
+        service.report_connection_metrics(&metrics).await.unwrap();
+        assert!(service.get_active_alerts().await.is_empty());
+
+        metrics.last_message_at.insert(
+            "tickers:BTC-USDT".to_string(),
+            Utc::now() - ChronoDuration::seconds(45),
+        );
+        service.report_connection_metrics(&metrics).await.unwrap();
+
+        let alerts = service.get_active_alerts().await;
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].metric_name, WS_SECONDS_SINCE_LAST_MESSAGE);
+        assert_eq!(alerts[0].severity, AlertSeverity::Critical);
+    }
+
+    #[tokio::test]
+    async fn test_per_channel_silence_metric() {
+        let service = MonitoringService::new();
+        let mut rule = market_data_silence_rule(10);
+        rule.condition.metric_name = channel_silence_metric("trades:ETH-USDT");
+        service.register_alert_rule(rule).await.unwrap();
+
+        let mut metrics = ConnectionMetrics::default();
+        metrics
+            .last_message_at
+            .insert("tickers:BTC-USDT".to_string(), Utc::now());
+        metrics.last_message_at.insert(
+            "trades:ETH-USDT".to_string(),
+            Utc::now() - ChronoDuration::seconds(20),
+        );
+        service.report_connection_metrics(&metrics).await.unwrap();
+
+        assert_eq!(service.get_active_alerts().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_health_checker_reports_disconnected() {
+        let checker = WebSocketHealthChecker::new(ConnectionTelemetry::new(), 30);
+        let check = checker.check().await;
+        assert_eq!(check.status, HealthStatus::Unhealthy);
+        assert_eq!(checker.name(), "websocket");
+    }
+
+    #[test]
+    fn test_health_checker_names_stale_symbols() {
+        let checker = WebSocketHealthChecker::new(ConnectionTelemetry::new(), 30);
+        let now = Utc::now();
+        let mut metrics = ConnectionMetrics::default();
+        metrics
+            .last_message_at
+            .insert("tickers:BTC-USDT".to_string(), now);
+        metrics.last_message_at.insert(
+            "tickers:ETH-USDT".to_string(),
+            now - ChronoDuration::seconds(90),
+        );
+
+        let check = checker.assess(&metrics, now);
+        assert_eq!(check.status, HealthStatus::Degraded);
+        assert!(check.message.contains("tickers:ETH-USDT (90s)"));
+        assert!(!check.message.contains("BTC-USDT"));
+
+        let check = checker.assess(&metrics, now + ChronoDuration::seconds(31));
+        assert_eq!(check.status, HealthStatus::Unhealthy);
+    }
+}
@@ -0,0 +1,99 @@
+//! Market data quality gauges
+//!
+//! Reports the rolling per-symbol feed quality kept by [`QualityControl`] so
+//! rules can alert when a symbol's updates start getting rejected, gapping or
+//! looking anomalous. Each figure is reported per symbol, e.g.
+//! `data_quality_score.BTC-USDT`.
+//!
+//! [`QualityControl`]: ea_okx_data::QualityControl
+
+use crate::alerts::{AlertCondition, AlertRule, AlertSeverity, ComparisonOperator};
+use crate::error::Result;
+use crate::service::MonitoringService;
+use ea_okx_data::SymbolQuality;
+
+/// Feed quality score over the last hour, 0 to 100
+pub const DATA_QUALITY_SCORE: &str = "data_quality_score";
+
+/// Share of updates rejected over the last hour, in percent
+pub const DATA_REJECTED_PCT: &str = "data_rejected_pct";
+
+/// Share of updates flagged as anomalous over the last hour, in percent
+pub const DATA_ANOMALY_PCT: &str = "data_anomaly_pct";
+
+/// Feed gaps per hour over the last day
+pub const DATA_GAPS_PER_HOUR: &str = "data_gaps_per_hour";
+
+/// Metric name for `base` on one symbol, e.g. `data_quality_score.BTC-USDT`
+pub fn data_quality_metric(base: &str, symbol: &str) -> String {
+    format!("{}.{}", base, symbol)
+}
+
+/// Alert rule that fires when `symbol`'s quality score drops below `min_score`
+pub fn data_quality_rule(symbol: &str, min_score: f64) -> AlertRule {
+    AlertRule::new(
+        format!("Poor Data Quality {}", symbol),
+        format!("{} feed quality score below {:.0}", symbol, min_score),
+        AlertCondition {
+            metric_name: data_quality_metric(DATA_QUALITY_SCORE, symbol),
+            operator: ComparisonOperator::LessThan,
+            threshold: min_score,
+            duration_seconds: 0,
+        },
+        AlertSeverity::Warning,
+    )
+}
+
+impl MonitoringService {
+    /// Evaluate alert rules against one symbol's feed quality
+    pub async fn report_data_quality(&self, quality: &SymbolQuality) -> Result<()> {
+        let symbol = quality.symbol.as_str();
+        let mut values = vec![(DATA_GAPS_PER_HOUR, quality.last_day.gaps_per_hour)];
+        if let Some(score) = quality.score {
+            values.push((DATA_QUALITY_SCORE, score));
+            values.push((DATA_REJECTED_PCT, quality.last_hour.rejected_pct));
+            values.push((DATA_ANOMALY_PCT, quality.last_hour.anomaly_pct));
+        }
+
+        for (base, value) in values {
+            let name = data_quality_metric(base, symbol);
+            tracing::debug!(metric = %name, value = value, "Set gauge");
+            self.evaluate_metric(&name, value).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    use ea_okx_core::types::{Price, Symbol};
+    use ea_okx_data::QualityControl;
+    use rust_decimal_macros::dec;
+
+    #[tokio::test]
+    async fn test_low_score_fires_symbol_rule() {
+        let service = MonitoringService::new();
+        service
+            .register_alert_rule(data_quality_rule("BTC-USDT", 90.0))
+            .await
+            .unwrap();
+
+        let qc = QualityControl::default();
+        let btc = Symbol::new("BTC-USDT").unwrap();
+        let price = Price::new(dec!(50000)).unwrap();
+        qc.validate_market_data(&btc, &price, Utc::now(), None)
+            .unwrap();
+        let stale = Utc::now() - Duration::minutes(1);
+        assert!(qc.validate_market_data(&btc, &price, stale, None).is_err());
+
+        let quality = qc.symbol_quality(&btc).unwrap();
+        assert_eq!(quality.score, Some(50.0));
+        service.report_data_quality(&quality).await.unwrap();
+
+        let alerts = service.get_active_alerts().await;
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].metric_name, "data_quality_score.BTC-USDT");
+    }
+}
@@ -0,0 +1,486 @@
+//! Strategy performance decay detection
+//!
+//! A strategy whose edge is fading rarely hits its drawdown limit first: its
+//! hit rate and average trade slide for weeks while each loss stays small.
+//! [`DecayMonitor`] compares a live strategy's recent trades and daily
+//! returns with the backtest baseline recorded when it was deployed, and
+//! raises one Warning alert when the live hit rate, expectancy or Sharpe
+//! ratio falls below the baseline's confidence band, plus an Info alert once
+//! all of them are back inside.
+//!
+//! Each band is `z` standard errors of the live sample either side of the
+//! baseline figure, so it narrows as live trades accumulate:
+//!
+//! - hit rate: `sqrt(p (1 - p) / n)` over the last `n` trades
+//! - expectancy: the baseline's per-trade P&L deviation over `sqrt(n)`
+//! - Sharpe: `sqrt((1 + SR² / 2) / d)` of the daily ratio over the last `d`
+//!   days, annualized like the baseline
+//!
+//! Only falling below a band counts as decay; doing better than the
+//! backtest is reported but never alerted on.
+
+use crate::alerts::{Alert, AlertSeverity};
+use crate::error::{Error, Result};
+use crate::service::MonitoringService;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use uuid::Uuid;
+
+/// Crypto markets trade every day, so daily ratios annualize over 365 days
+const DAYS_PER_YEAR: f64 = 365.0;
+
+/// Backtest performance a strategy is expected to keep up live
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PerformanceBaseline {
+    pub strategy_id: Uuid,
+    /// Backtest run the figures come from
+    pub source: String,
+    pub recorded_at: DateTime<Utc>,
+    /// Trades the backtest closed
+    pub trades: usize,
+    /// Share of trades closed at a profit, 0 to 1
+    pub hit_rate: f64,
+    /// Mean P&L per trade
+    pub expectancy: f64,
+    /// Standard deviation of P&L per trade
+    pub pnl_std: f64,
+    /// Annualized Sharpe ratio of daily returns
+    pub sharpe_ratio: Option<f64>,
+}
+
+/// How much live data is judged and how wide the bands are
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecayConfig {
+    /// Standard errors between a band's edge and the baseline
+    pub z_score: f64,
+    /// Live trades needed before hit rate and expectancy are judged
+    pub min_trades: usize,
+    /// Most recent trades judged
+    pub window_trades: usize,
+    /// Live days needed before the Sharpe ratio is judged
+    pub min_days: usize,
+    /// Most recent daily returns judged
+    pub window_days: usize,
+}
+
+impl Default for DecayConfig {
+    fn default() -> Self {
+        Self {
+            z_score: 1.96,
+            min_trades: 20,
+            window_trades: 50,
+            min_days: 14,
+            window_days: 30,
+        }
+    }
+}
+
+/// Live results of one strategy, oldest first
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LivePerformance {
+    /// Realized P&L of each closed trade
+    pub trade_pnls: Vec<f64>,
+    /// Return of each day on the strategy's capital
+    pub daily_returns: Vec<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DecayMetric {
+    HitRate,
+    Expectancy,
+    SharpeRatio,
+}
+
+impl DecayMetric {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DecayMetric::HitRate => "hit_rate",
+            DecayMetric::Expectancy => "expectancy",
+            DecayMetric::SharpeRatio => "sharpe_ratio",
+        }
+    }
+}
+
+/// One live figure against the baseline's confidence band
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricBand {
+    pub metric: DecayMetric,
+    pub live: f64,
+    pub baseline: f64,
+    pub lower: f64,
+    pub upper: f64,
+    /// Trades or days the live figure is computed over
+    pub sample: usize,
+}
+
+impl MetricBand {
+    /// Whether the live figure fell below the band
+    pub fn decayed(&self) -> bool {
+        self.live < self.lower
+    }
+}
+
+/// Live performance of a strategy judged against its baseline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecayAssessment {
+    pub strategy_id: Uuid,
+    pub assessed_at: DateTime<Utc>,
+    /// Figures with enough live data to judge
+    pub bands: Vec<MetricBand>,
+}
+
+impl DecayAssessment {
+    pub fn decaying(&self) -> bool {
+        self.bands.iter().any(MetricBand::decayed)
+    }
+}
+
+/// Judge `live` against `baseline`, leaving out figures without enough live
+/// data behind them
+pub fn assess(
+    baseline: &PerformanceBaseline,
+    live: &LivePerformance,
+    config: &DecayConfig,
+) -> DecayAssessment {
+    let z = config.z_score;
+    let mut bands = Vec::new();
+
+    let trades = tail(&live.trade_pnls, config.window_trades);
+    if !trades.is_empty() && trades.len() >= config.min_trades {
+        let n = trades.len() as f64;
+        let p = baseline.hit_rate;
+        let margin = z * (p * (1.0 - p) / n).sqrt();
+        bands.push(MetricBand {
+            metric: DecayMetric::HitRate,
+            live: trades.iter().filter(|pnl| **pnl > 0.0).count() as f64 / n,
+            baseline: p,
+            lower: p - margin,
+            upper: p + margin,
+            sample: trades.len(),
+        });
+
+        let margin = z * baseline.pnl_std / n.sqrt();
+        bands.push(MetricBand {
+            metric: DecayMetric::Expectancy,
+            live: trades.iter().sum::<f64>() / n,
+            baseline: baseline.expectancy,
+            lower: baseline.expectancy - margin,
+            upper: baseline.expectancy + margin,
+            sample: trades.len(),
+        });
+    }
+
+    let days = tail(&live.daily_returns, config.window_days);
+    if let Some(sharpe) = baseline.sharpe_ratio
+        && !days.is_empty()
+        && days.len() >= config.min_days
+        && let Some(live_sharpe) = annualized_sharpe(days)
+    {
+        let d = days.len() as f64;
+        let daily = sharpe / DAYS_PER_YEAR.sqrt();
+        let margin = z * ((1.0 + daily * daily / 2.0) / d).sqrt() * DAYS_PER_YEAR.sqrt();
+        bands.push(MetricBand {
+            metric: DecayMetric::SharpeRatio,
+            live: live_sharpe,
+            baseline: sharpe,
+            lower: sharpe - margin,
+            upper: sharpe + margin,
+            sample: days.len(),
+        });
+    }
+
+    DecayAssessment {
+        strategy_id: baseline.strategy_id,
+        assessed_at: Utc::now(),
+        bands,
+    }
+}
+
+fn tail(values: &[f64], window: usize) -> &[f64] {
+    &values[values.len().saturating_sub(window)..]
+}
+
+/// `None` for returns without variation
+fn annualized_sharpe(returns: &[f64]) -> Option<f64> {
+    let n = returns.len() as f64;
+    let mean = returns.iter().sum::<f64>() / n;
+    let std_dev = (returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / n).sqrt();
+    (std_dev > 0.0).then(|| mean / std_dev * DAYS_PER_YEAR.sqrt())
+}
+
+/// Alerts when live strategies fall behind their deployment baselines
+pub struct DecayMonitor {
+    config: DecayConfig,
+    monitoring: Arc<MonitoringService>,
+    baselines: RwLock<HashMap<Uuid, PerformanceBaseline>>,
+    /// Baselines are saved here as JSON when set
+    path: Option<PathBuf>,
+    decaying: Mutex<HashSet<Uuid>>,
+}
+
+impl DecayMonitor {
+    pub fn new(config: DecayConfig, monitoring: Arc<MonitoringService>) -> Self {
+        Self {
+            config,
+            monitoring,
+            baselines: RwLock::new(HashMap::new()),
+            path: None,
+            decaying: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Keep baselines in the JSON file at `path`, loading those already there
+    pub fn with_file(mut self, path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if path.exists() {
+            let bytes = fs::read(&path).map_err(|e| {
+                Error::BaselineError(format!("Failed to read {}: {}", path.display(), e))
+            })?;
+            let baselines: Vec<PerformanceBaseline> =
+                serde_json::from_slice(&bytes).map_err(|e| {
+                    Error::BaselineError(format!("Failed to parse {}: {}", path.display(), e))
+                })?;
+            self.baselines = RwLock::new(
+                baselines
+                    .into_iter()
+                    .map(|baseline| (baseline.strategy_id, baseline))
+                    .collect(),
+            );
+        }
+        self.path = Some(path);
+        Ok(self)
+    }
+
+    pub fn config(&self) -> &DecayConfig {
+        &self.config
+    }
+
+    /// Judge the strategy against `baseline` from now on
+    pub fn set_baseline(&self, baseline: PerformanceBaseline) -> Result<()> {
+        let mut baselines = self.baselines.write().unwrap_or_else(|e| e.into_inner());
+        self.decaying
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&baseline.strategy_id);
+        baselines.insert(baseline.strategy_id, baseline);
+        self.save(&baselines)
+    }
+
+    pub fn remove_baseline(&self, strategy_id: Uuid) -> Result<Option<PerformanceBaseline>> {
+        let mut baselines = self.baselines.write().unwrap_or_else(|e| e.into_inner());
+        let removed = baselines.remove(&strategy_id);
+        self.decaying
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&strategy_id);
+        if removed.is_some() {
+            self.save(&baselines)?;
+        }
+        Ok(removed)
+    }
+
+    pub fn baseline(&self, strategy_id: Uuid) -> Option<PerformanceBaseline> {
+        self.baselines
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&strategy_id)
+            .cloned()
+    }
+
+    /// Judge `live` against the strategy's baseline, alerting on entering or
+    /// leaving decay; `None` when the strategy has no baseline
+    pub async fn check(
+        &self,
+        strategy_id: Uuid,
+        strategy_name: &str,
+        live: &LivePerformance,
+    ) -> Option<DecayAssessment> {
+        let baseline = self.baseline(strategy_id)?;
+        let assessment = assess(&baseline, live, &self.config);
+
+        let decaying = assessment.decaying();
+        let changed = {
+            let mut flagged = self.decaying.lock().unwrap_or_else(|e| e.into_inner());
+            if decaying {
+                flagged.insert(strategy_id)
+            } else {
+                flagged.remove(&strategy_id)
+            }
+        };
+        if !changed {
+            return Some(assessment);
+        }
+
+        let mut alert = if decaying {
+            let reasons: Vec<String> = assessment
+                .bands
+                .iter()
+                .filter(|band| band.decayed())
+                .map(|band| {
+                    format!(
+                        "{} {:.4} below {:.4} (backtest {:.4}, {} live)",
+                        band.metric.as_str(),
+                        band.live,
+                        band.lower,
+                        band.baseline,
+                        band.sample
+                    )
+                })
+                .collect();
+            Alert::event(
+                "strategy_decay",
+                AlertSeverity::Warning,
+                format!(
+                    "{} is underperforming its backtest: {}",
+                    strategy_name,
+                    reasons.join("; ")
+                ),
+            )
+        } else {
+            Alert::event(
+                "strategy_decay_recovered",
+                AlertSeverity::Info,
+                format!("{} is back within its backtest band", strategy_name),
+            )
+        };
+        alert
+            .metadata
+            .insert("strategy_id".to_string(), strategy_id.to_string());
+        alert
+            .metadata
+            .insert("baseline".to_string(), baseline.source.clone());
+        for band in &assessment.bands {
+            alert
+                .metadata
+                .insert(band.metric.as_str().to_string(), band.live.to_string());
+        }
+        self.monitoring.raise_alert(alert).await;
+
+        Some(assessment)
+    }
+
+    fn save(&self, baselines: &HashMap<Uuid, PerformanceBaseline>) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let mut all: Vec<&PerformanceBaseline> = baselines.values().collect();
+        all.sort_by_key(|baseline| baseline.recorded_at);
+        let json =
+            serde_json::to_vec_pretty(&all).map_err(|e| Error::BaselineError(e.to_string()))?;
+        fs::write(path, json)
+            .map_err(|e| Error::BaselineError(format!("Failed to write {}: {}", path.display(), e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn baseline() -> PerformanceBaseline {
+        PerformanceBaseline {
+            strategy_id: Uuid::new_v4(),
+            source: "backtest_1".to_string(),
+            recorded_at: Utc::now(),
+            trades: 200,
+            hit_rate: 0.55,
+            expectancy: 10.0,
+            pnl_std: 40.0,
+            sharpe_ratio: Some(1.5),
+        }
+    }
+
+    /// `wins` trades of +30 followed by losses of -20, `total` in all
+    fn trades(wins: usize, total: usize) -> Vec<f64> {
+        (0..total)
+            .map(|i| if i < wins { 30.0 } else { -20.0 })
+            .collect()
+    }
+
+    #[test]
+    fn test_assess_flags_only_figures_below_band() {
+        let baseline = baseline();
+        let config = DecayConfig::default();
+
+        // 27 of 50 won: in line with the backtest
+        let live = LivePerformance {
+            trade_pnls: trades(27, 50),
+            daily_returns: Vec::new(),
+        };
+        let assessment = assess(&baseline, &live, &config);
+        assert_eq!(assessment.bands.len(), 2);
+        assert!(!assessment.decaying());
+
+        // 12 of 50 won: hit rate and expectancy both collapse
+        let live = LivePerformance {
+            trade_pnls: trades(12, 50),
+            daily_returns: [-0.002, 0.0002].repeat(10),
+        };
+        let assessment = assess(&baseline, &live, &config);
+        let decayed: Vec<DecayMetric> = assessment
+            .bands
+            .iter()
+            .filter(|band| band.decayed())
+            .map(|band| band.metric)
+            .collect();
+        assert_eq!(
+            decayed,
+            vec![
+                DecayMetric::HitRate,
+                DecayMetric::Expectancy,
+                DecayMetric::SharpeRatio
+            ]
+        );
+
+        // Too few trades to judge
+        let live = LivePerformance {
+            trade_pnls: trades(0, 10),
+            daily_returns: Vec::new(),
+        };
+        assert!(assess(&baseline, &live, &config).bands.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_monitor_alerts_once_per_excursion() {
+        let monitoring = Arc::new(MonitoringService::new());
+        let dir = std::env::temp_dir().join(format!("decay_{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("baselines.json");
+        let monitor = DecayMonitor::new(DecayConfig::default(), monitoring.clone())
+            .with_file(&path)
+            .unwrap();
+        let baseline = baseline();
+        let id = baseline.strategy_id;
+        monitor.set_baseline(baseline.clone()).unwrap();
+
+        let bad = LivePerformance {
+            trade_pnls: trades(12, 50),
+            daily_returns: Vec::new(),
+        };
+        assert!(monitor.check(id, "Grid", &bad).await.unwrap().decaying());
+        assert!(monitor.check(id, "Grid", &bad).await.unwrap().decaying());
+        let good = LivePerformance {
+            trade_pnls: trades(28, 50),
+            daily_returns: Vec::new(),
+        };
+        assert!(!monitor.check(id, "Grid", &good).await.unwrap().decaying());
+        assert!(monitor.check(Uuid::new_v4(), "Other", &bad).await.is_none());
+
+        let alerts = monitoring.get_all_alerts().await;
+        let sources: Vec<&str> = alerts.iter().map(|a| a.rule_name.as_str()).collect();
+        assert_eq!(sources.len(), 2);
+        assert!(sources.contains(&"strategy_decay"));
+        assert!(sources.contains(&"strategy_decay_recovered"));
+
+        // Baselines survive a restart
+        let reopened = DecayMonitor::new(DecayConfig::default(), monitoring)
+            .with_file(&path)
+            .unwrap();
+        assert_eq!(reopened.baseline(id), Some(baseline));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -14,6 +14,12 @@ pub enum Error {
     #[error("Exporter error: {0}")]
     ExporterError(String),
 
+    #[error("Report error: {0}")]
+    ReportError(String),
+
+    #[error("Baseline error: {0}")]
+    BaselineError(String),
+
     #[error(transparent)]
     Other(#[from] anyhow::Error),
 }
@@ -0,0 +1,711 @@
+//! Composite alert rule expressions
+//!
+//! [`AlertCondition`](crate::alerts::AlertCondition) compares one metric to
+//! one threshold. An [`AlertExpr`] composes conditions with AND/OR/NOT and
+//! adds two time-aware forms, both evaluated against a [`MetricHistory`]:
+//!
+//! - rate of change: `error_rate increased 3x in 5m`
+//! - absence: `no ticks.BTC-USDT for 30s`
+//!
+//! Expressions are written either in that small DSL, parsed with
+//! [`AlertExpr::parse`], or as tagged JSON via serde.
+//!
+//! ```text
+//! expr      := or
+//! or        := and ("or" and)*
+//! and       := unary ("and" unary)*
+//! unary     := "not" unary | "(" expr ")" | condition
+//! condition := metric op number
+//!            | metric "increased" number "x" "in" duration
+//!            | "no" metric "for" duration
+//! op        := ">" | "<" | ">=" | "<=" | "==" | "!="
+//! duration  := number ("s" | "m" | "h")
+//! ```
+
+use crate::alerts::{Alert, AlertSeverity, ComparisonOperator};
+use crate::error::{Error, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Composite alert condition
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AlertExpr {
+    /// Latest value of `metric` compared with `threshold`
+    Threshold {
+        metric: String,
+        operator: ComparisonOperator,
+        threshold: f64,
+    },
+    /// Latest value is at least `factor` times the value `window_seconds` ago
+    RateOfChange {
+        metric: String,
+        factor: f64,
+        window_seconds: u64,
+    },
+    /// `metric` has not been recorded for `seconds`
+    Absent {
+        metric: String,
+        seconds: u64,
+    },
+    And {
+        all: Vec<AlertExpr>,
+    },
+    Or {
+        any: Vec<AlertExpr>,
+    },
+    Not {
+        expr: Box<AlertExpr>,
+    },
+}
+
+impl AlertExpr {
+    /// Parse the rule DSL
+    pub fn parse(input: &str) -> Result<Self> {
+        let mut parser = Parser {
+            tokens: tokenize(input)?,
+            pos: 0,
+        };
+        let expr = parser.or()?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some(token) => Err(parse_error(format!("unexpected '{}'", token))),
+        }
+    }
+
+    /// Metrics the expression reads
+    pub fn metrics(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        self.collect_metrics(&mut names);
+        names
+    }
+
+    fn collect_metrics<'a>(&'a self, names: &mut Vec<&'a str>) {
+        match self {
+            AlertExpr::Threshold { metric, .. }
+            | AlertExpr::RateOfChange { metric, .. }
+            | AlertExpr::Absent { metric, .. } => {
+                if !names.contains(&metric.as_str()) {
+                    names.push(metric)
+                }
+            }
+            AlertExpr::And { all: exprs } | AlertExpr::Or { any: exprs } => {
+                exprs.iter().for_each(|e| e.collect_metrics(names))
+            }
+            AlertExpr::Not { expr } => expr.collect_metrics(names),
+        }
+    }
+
+    /// Longest look-back the expression needs from the history
+    pub fn max_window(&self) -> Duration {
+        match self {
+            AlertExpr::Threshold { .. } => Duration::zero(),
+            AlertExpr::RateOfChange { window_seconds, .. } => {
+                Duration::seconds(*window_seconds as i64)
+            }
+            AlertExpr::Absent { seconds, .. } => Duration::seconds(*seconds as i64),
+            AlertExpr::And { all: exprs } | AlertExpr::Or { any: exprs } => exprs
+                .iter()
+                .map(AlertExpr::max_window)
+                .max()
+                .unwrap_or_else(Duration::zero),
+            AlertExpr::Not { expr } => expr.max_window(),
+        }
+    }
+
+    /// Whether the expression holds at `now`
+    ///
+    /// Conditions on metrics without enough history are false, except
+    /// absence, which counts from when the history started.
+    pub fn evaluate(&self, history: &MetricHistory, now: DateTime<Utc>) -> bool {
+        match self {
+            AlertExpr::Threshold {
+                metric,
+                operator,
+                threshold,
+            } => history
+                .latest(metric)
+                .is_some_and(|value| operator.compare(value, *threshold)),
+            AlertExpr::RateOfChange {
+                metric,
+                factor,
+                window_seconds,
+            } => {
+                let since = now - Duration::seconds(*window_seconds as i64);
+                match (history.latest(metric), history.value_at(metric, since)) {
+                    (Some(current), Some(baseline)) if baseline > 0.0 => {
+                        current >= baseline * factor
+                    }
+                    _ => false,
+                }
+            }
+            AlertExpr::Absent { metric, seconds } => {
+                now - history.last_seen(metric) >= Duration::seconds(*seconds as i64)
+            }
+            AlertExpr::And { all } => all.iter().all(|e| e.evaluate(history, now)),
+            AlertExpr::Or { any } => any.iter().any(|e| e.evaluate(history, now)),
+            AlertExpr::Not { expr } => !expr.evaluate(history, now),
+        }
+    }
+}
+
+impl FromStr for AlertExpr {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for AlertExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlertExpr::Threshold {
+                metric,
+                operator,
+                threshold,
+            } => write!(f, "{} {} {}", metric, operator.symbol(), threshold),
+            AlertExpr::RateOfChange {
+                metric,
+                factor,
+                window_seconds,
+            } => write!(f, "{} increased {}x in {}s", metric, factor, window_seconds),
+            AlertExpr::Absent { metric, seconds } => write!(f, "no {} for {}s", metric, seconds),
+            AlertExpr::And { all } => write_joined(f, all, " and "),
+            AlertExpr::Or { any } => write_joined(f, any, " or "),
+            AlertExpr::Not { expr } => write!(f, "not ({})", expr),
+        }
+    }
+}
+
+fn write_joined(f: &mut fmt::Formatter<'_>, exprs: &[AlertExpr], sep: &str) -> fmt::Result {
+    for (i, expr) in exprs.iter().enumerate() {
+        if i > 0 {
+            f.write_str(sep)?;
+        }
+        match expr {
+            AlertExpr::And { .. } | AlertExpr::Or { .. } => write!(f, "({})", expr)?,
+            _ => write!(f, "{}", expr)?,
+        }
+    }
+    Ok(())
+}
+
+/// Recent samples per metric, used by time-aware conditions
+#[derive(Debug, Clone)]
+pub struct MetricHistory {
+    started_at: DateTime<Utc>,
+    retention: Duration,
+    samples: HashMap<String, VecDeque<(DateTime<Utc>, f64)>>,
+}
+
+impl MetricHistory {
+    pub fn new(retention: Duration) -> Self {
+        Self {
+            started_at: Utc::now(),
+            retention,
+            samples: HashMap::new(),
+        }
+    }
+
+    /// Keep at least `window` of history
+    pub fn ensure_retention(&mut self, window: Duration) {
+        self.retention = self.retention.max(window);
+    }
+
+    pub fn record(&mut self, metric: &str, value: f64, at: DateTime<Utc>) {
+        if !self.samples.contains_key(metric) {
+            self.samples.insert(metric.to_string(), VecDeque::new());
+        }
+        let Some(samples) = self.samples.get_mut(metric) else {
+            return;
+        };
+        samples.push_back((at, value));
+
+        // Keep one sample older than the retention window as the baseline
+        let cutoff = at - self.retention;
+        while samples.len() > 1 && samples[1].0 <= cutoff {
+            samples.pop_front();
+        }
+    }
+
+    /// Most recent value
+    pub fn latest(&self, metric: &str) -> Option<f64> {
+        self.samples
+            .get(metric)
+            .and_then(|s| s.back())
+            .map(|(_, v)| *v)
+    }
+
+    /// Value in effect at `at`: the last sample recorded at or before it
+    pub fn value_at(&self, metric: &str, at: DateTime<Utc>) -> Option<f64> {
+        self.samples
+            .get(metric)?
+            .iter()
+            .take_while(|(ts, _)| *ts <= at)
+            .last()
+            .map(|(_, v)| *v)
+    }
+
+    /// When `metric` was last recorded, or when the history started
+    pub fn last_seen(&self, metric: &str) -> DateTime<Utc> {
+        self.samples
+            .get(metric)
+            .and_then(|s| s.back())
+            .map_or(self.started_at, |(ts, _)| *ts)
+    }
+}
+
+impl Default for MetricHistory {
+    fn default() -> Self {
+        Self::new(Duration::hours(1))
+    }
+}
+
+/// Alert rule driven by an [`AlertExpr`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpressionRule {
+    pub id: Uuid,
+    pub name: String,
+    pub description: String,
+    pub expression: AlertExpr,
+    pub severity: AlertSeverity,
+    pub enabled: bool,
+    pub cooldown_seconds: u64,
+    pub last_triggered: Option<DateTime<Utc>>,
+}
+
+impl ExpressionRule {
+    pub fn new(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        expression: AlertExpr,
+        severity: AlertSeverity,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name: name.into(),
+            description: description.into(),
+            expression,
+            severity,
+            enabled: true,
+            cooldown_seconds: 300, // 5 minutes default
+            last_triggered: None,
+        }
+    }
+
+    /// Build a rule from the DSL
+    pub fn parse(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        expression: &str,
+        severity: AlertSeverity,
+    ) -> Result<Self> {
+        Ok(Self::new(
+            name,
+            description,
+            AlertExpr::parse(expression)?,
+            severity,
+        ))
+    }
+
+    pub fn is_in_cooldown(&self, now: DateTime<Utc>) -> bool {
+        self.last_triggered
+            .is_some_and(|at| (now - at).num_seconds() < self.cooldown_seconds as i64)
+    }
+
+    /// Evaluate the expression, respecting `enabled` and the cooldown
+    pub fn evaluate(&self, history: &MetricHistory, now: DateTime<Utc>) -> bool {
+        self.enabled && !self.is_in_cooldown(now) && self.expression.evaluate(history, now)
+    }
+
+    /// Alert for a triggered rule
+    ///
+    /// Carries the first metric the expression reads and its latest value;
+    /// the full expression is in the `expression` metadata entry.
+    pub fn alert(&self, history: &MetricHistory) -> Alert {
+        let metrics = self.expression.metrics();
+        let metric_name = metrics.first().copied().unwrap_or_default();
+        let metric_value = history.latest(metric_name).unwrap_or(0.0);
+
+        let mut metadata = HashMap::new();
+        metadata.insert("expression".to_string(), self.expression.to_string());
+
+        Alert {
+            id: Uuid::new_v4(),
+            rule_id: self.id,
+            rule_name: self.name.clone(),
+            severity: self.severity,
+            message: format!("{}: {}", self.name, self.expression),
+            metric_name: metric_name.to_string(),
+            metric_value,
+            threshold: 0.0,
+            triggered_at: Utc::now(),
+            acknowledged: false,
+            acknowledged_at: None,
+            acknowledged_by: None,
+            metadata,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Number(f64),
+    Op(ComparisonOperator),
+    LParen,
+    RParen,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Word(word) => f.write_str(word),
+            Token::Number(n) => write!(f, "{}", n),
+            Token::Op(op) => f.write_str(op.symbol()),
+            Token::LParen => f.write_str("("),
+            Token::RParen => f.write_str(")"),
+        }
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | ':' | '-')
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '(' {
+            tokens.push(Token::LParen);
+            i += 1;
+        } else if c == ')' {
+            tokens.push(Token::RParen);
+            i += 1;
+        } else if matches!(c, '>' | '<' | '=' | '!') {
+            let (op, len) = match (c, next) {
+                ('>', Some('=')) => (ComparisonOperator::GreaterThanOrEqual, 2),
+                ('<', Some('=')) => (ComparisonOperator::LessThanOrEqual, 2),
+                ('=', Some('=')) => (ComparisonOperator::Equals, 2),
+                ('!', Some('=')) => (ComparisonOperator::NotEquals, 2),
+                ('>', _) => (ComparisonOperator::GreaterThan, 1),
+                ('<', _) => (ComparisonOperator::LessThan, 1),
+                _ => return Err(parse_error(format!("unexpected '{}'", c))),
+            };
+            tokens.push(Token::Op(op));
+            i += len;
+        } else if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) {
+            // Numbers end at the first non-numeric char so "3x" and "30s"
+            // lex as a number followed by a unit word
+            let start = i;
+            i += 1;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let value = text
+                .parse()
+                .map_err(|_| parse_error(format!("invalid number '{}'", text)))?;
+            tokens.push(Token::Number(value));
+        } else if is_word_char(c) {
+            let start = i;
+            while i < chars.len() && is_word_char(chars[i]) {
+                i += 1;
+            }
+            tokens.push(Token::Word(chars[start..i].iter().collect()));
+        } else {
+            return Err(parse_error(format!("unexpected '{}'", c)));
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<Token> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or_else(|| parse_error("unexpected end of expression"))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn at_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(w)) if w.eq_ignore_ascii_case(keyword))
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<()> {
+        if self.at_keyword(keyword) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(parse_error(format!("expected '{}'", keyword)))
+        }
+    }
+
+    fn or(&mut self) -> Result<AlertExpr> {
+        let mut terms = vec![self.and()?];
+        while self.at_keyword("or") {
+            self.pos += 1;
+            terms.push(self.and()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            AlertExpr::Or { any: terms }
+        })
+    }
+
+    fn and(&mut self) -> Result<AlertExpr> {
+        let mut terms = vec![self.unary()?];
+        while self.at_keyword("and") {
+            self.pos += 1;
+            terms.push(self.unary()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            AlertExpr::And { all: terms }
+        })
+    }
+
+    fn unary(&mut self) -> Result<AlertExpr> {
+        if self.at_keyword("not") {
+            self.pos += 1;
+            return Ok(AlertExpr::Not {
+                expr: Box::new(self.unary()?),
+            });
+        }
+        if self.peek() == Some(&Token::LParen) {
+            self.pos += 1;
+            let expr = self.or()?;
+            return match self.next()? {
+                Token::RParen => Ok(expr),
+                token => Err(parse_error(format!("expected ')', found '{}'", token))),
+            };
+        }
+        self.condition()
+    }
+
+    fn condition(&mut self) -> Result<AlertExpr> {
+        if self.at_keyword("no") {
+            self.pos += 1;
+            let metric = self.metric()?;
+            self.expect_keyword("for")?;
+            let seconds = self.duration()?;
+            return Ok(AlertExpr::Absent { metric, seconds });
+        }
+
+        let metric = self.metric()?;
+        if self.at_keyword("increased") {
+            self.pos += 1;
+            let factor = self.number()?;
+            self.expect_keyword("x")?;
+            self.expect_keyword("in")?;
+            let window_seconds = self.duration()?;
+            return Ok(AlertExpr::RateOfChange {
+                metric,
+                factor,
+                window_seconds,
+            });
+        }
+
+        match self.next()? {
+            Token::Op(operator) => Ok(AlertExpr::Threshold {
+                metric,
+                operator,
+                threshold: self.number()?,
+            }),
+            token => Err(parse_error(format!(
+                "expected comparison after '{}', found '{}'",
+                metric, token
+            ))),
+        }
+    }
+
+    fn metric(&mut self) -> Result<String> {
+        match self.next()? {
+            Token::Word(word) => Ok(word),
+            token => Err(parse_error(format!(
+                "expected metric name, found '{}'",
+                token
+            ))),
+        }
+    }
+
+    fn number(&mut self) -> Result<f64> {
+        match self.next()? {
+            Token::Number(n) => Ok(n),
+            token => Err(parse_error(format!("expected number, found '{}'", token))),
+        }
+    }
+
+    fn duration(&mut self) -> Result<u64> {
+        let value = self.number()?;
+        let unit = match self.next()? {
+            Token::Word(unit) => unit,
+            token => return Err(parse_error(format!("expected unit, found '{}'", token))),
+        };
+        let scale = match unit.to_ascii_lowercase().as_str() {
+            "s" => 1.0,
+            "m" => 60.0,
+            "h" => 3600.0,
+            _ => return Err(parse_error(format!("unknown duration unit '{}'", unit))),
+        };
+        if value < 0.0 {
+            return Err(parse_error("duration must not be negative"));
+        }
+        Ok((value * scale).round() as u64)
+    }
+}
+
+fn parse_error(message: impl Into<String>) -> Error {
+    Error::AlertError(format!("Invalid alert expression: {}", message.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_composite_expression() {
+        let expr = AlertExpr::parse(
+            "error_rate increased 3x in 5m and (latency_ms > 250 or no ticks.BTC-USDT for 30s)",
+        )
+        .unwrap();
+
+        assert_eq!(
+            expr,
+            AlertExpr::And {
+                all: vec![
+                    AlertExpr::RateOfChange {
+                        metric: "error_rate".to_string(),
+                        factor: 3.0,
+                        window_seconds: 300,
+                    },
+                    AlertExpr::Or {
+                        any: vec![
+                            AlertExpr::Threshold {
+                                metric: "latency_ms".to_string(),
+                                operator: ComparisonOperator::GreaterThan,
+                                threshold: 250.0,
+                            },
+                            AlertExpr::Absent {
+                                metric: "ticks.BTC-USDT".to_string(),
+                                seconds: 30,
+                            },
+                        ],
+                    },
+                ],
+            }
+        );
+        assert_eq!(
+            expr.metrics(),
+            vec!["error_rate", "latency_ms", "ticks.BTC-USDT"]
+        );
+        assert_eq!(expr.max_window(), Duration::minutes(5));
+
+        // Display round-trips through the parser
+        assert_eq!(AlertExpr::parse(&expr.to_string()).unwrap(), expr);
+    }
+
+    #[test]
+    fn test_parse_json_and_errors() {
+        let expr: AlertExpr = serde_json::from_value(serde_json::json!({
+            "type": "not",
+            "expr": { "type": "threshold", "metric": "cpu", "operator": "GreaterThan", "threshold": 90.0 }
+        }))
+        .unwrap();
+        assert_eq!(expr.to_string(), "not (cpu > 90)");
+
+        assert!(AlertExpr::parse("cpu >").is_err());
+        assert!(AlertExpr::parse("cpu > 90 and").is_err());
+        assert!(AlertExpr::parse("no ticks for 30d").is_err());
+        assert!(AlertExpr::parse("(cpu > 90").is_err());
+    }
+
+    #[test]
+    fn test_rate_of_change() {
+        let expr = AlertExpr::parse("error_rate increased 3x in 5m").unwrap();
+        let start = Utc::now();
+        let mut history = MetricHistory::default();
+
+        history.record("error_rate", 0.01, start);
+        history.record("error_rate", 0.02, start + Duration::minutes(4));
+        assert!(!expr.evaluate(&history, start + Duration::minutes(4)));
+
+        history.record("error_rate", 0.035, start + Duration::minutes(6));
+        assert!(expr.evaluate(&history, start + Duration::minutes(6)));
+
+        // Baseline is now the 0.02 sample, so 0.035 is under 3x
+        assert!(!expr.evaluate(&history, start + Duration::minutes(9)));
+    }
+
+    #[test]
+    fn test_absence() {
+        let expr = AlertExpr::parse("no ticks.BTC-USDT for 30s").unwrap();
+        let mut history = MetricHistory::default();
+        let now = Utc::now();
+
+        // Nothing recorded yet: counts from when the history started
+        assert!(!expr.evaluate(&history, now));
+        assert!(expr.evaluate(&history, now + Duration::seconds(31)));
+
+        history.record("ticks.BTC-USDT", 1.0, now + Duration::seconds(20));
+        assert!(!expr.evaluate(&history, now + Duration::seconds(45)));
+        assert!(expr.evaluate(&history, now + Duration::seconds(50)));
+    }
+
+    #[test]
+    fn test_rule_cooldown_and_alert() {
+        let mut rule = ExpressionRule::parse(
+            "Overloaded",
+            "CPU and latency high",
+            "cpu > 90 and latency_ms >= 200",
+            AlertSeverity::Critical,
+        )
+        .unwrap();
+        let now = Utc::now();
+        let mut history = MetricHistory::default();
+        history.record("cpu", 95.0, now);
+        history.record("latency_ms", 150.0, now);
+        assert!(!rule.evaluate(&history, now));
+
+        history.record("latency_ms", 200.0, now);
+        assert!(rule.evaluate(&history, now));
+
+        let alert = rule.alert(&history);
+        assert_eq!(alert.metric_name, "cpu");
+        assert_eq!(
+            alert.metadata["expression"],
+            "cpu > 90 and latency_ms >= 200"
+        );
+
+        rule.last_triggered = Some(now);
+        assert!(!rule.evaluate(&history, now + Duration::seconds(10)));
+    }
+}
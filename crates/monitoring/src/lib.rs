@@ -5,9 +5,22 @@
 //! ## Features
 //!
 //! - **Metrics Collection**: Track trading performance, system health, and operational metrics
-//! - **Health Checks**: Monitor component health (database, exchange API, cache)
+//! - **Health Checks**: Redis PING latency, TimescaleDB pool saturation and schema version,
+//!   disk space, market data staleness per symbol and tick storage maintenance
+//! - **Connection Health**: WebSocket reconnects, ping RTT and market data silence alerts
+//! - **Data Quality**: Rolling per-symbol feed quality scores, rejection, gap and anomaly rates
+//! - **Outage Detection**: REST latency, error rates and feed silence judged into a degraded
+//!   or normal exchange state, with hysteresis on recovery
+//! - **Clock Drift**: Offset to the OKX clock as a gauge, with alerts on large drift or
+//!   failing clock syncs
+//! - **Task Watchdog**: Heartbeat, exit and dead channel detection for long-running tasks,
+//!   with Critical alerts and automatic restarts
 //! - **Alerting**: Configurable alert rules with severity levels and cooldown periods
+//! - **Rule Expressions**: AND/OR composition, rate-of-change and absence conditions
+//! - **Performance Decay**: Live hit rate, expectancy and Sharpe judged against each strategy's
+//!   deployment backtest, with Warning alerts when they fall below its confidence band
 //! - **Performance Tracking**: Real-time performance snapshots and historical data
+//! - **Daily Reports**: Scheduled per-strategy and portfolio summaries stored as JSON and HTML
 //!
 //! ## Usage
 //!
@@ -45,11 +58,38 @@
 //! ```
 
 pub mod alerts;
+pub mod checkers;
+pub mod clock_drift;
+pub mod connection;
+pub mod data_quality;
+pub mod decay;
 pub mod error;
+pub mod expression;
 pub mod metrics;
+pub mod outage;
+pub mod reports;
 pub mod service;
+pub mod watchdog;
 
 pub use alerts::{Alert, AlertCondition, AlertRule, AlertSeverity, ComparisonOperator};
+pub use checkers::{
+    DiskSpaceHealthChecker, PoolHealthChecker, RedisHealthChecker, SchemaHealthChecker,
+    TickMaintenanceHealthChecker,
+};
+pub use clock_drift::{CLOCK_OFFSET_MS, ClockDriftMonitor};
+pub use connection::{WebSocketHealthChecker, market_data_silence_rule};
+pub use data_quality::{data_quality_metric, data_quality_rule};
+pub use decay::{
+    DecayAssessment, DecayConfig, DecayMetric, DecayMonitor, LivePerformance, MetricBand,
+    PerformanceBaseline,
+};
 pub use error::{Error, Result};
+pub use expression::{AlertExpr, ExpressionRule, MetricHistory};
 pub use metrics::{HealthCheck, HealthReport, HealthStatus, MetricsCollector, PerformanceSnapshot};
+pub use outage::{ExchangeHealthEvent, ExchangeHealthStatus, OutageDetector, OutageThresholds};
+pub use reports::{
+    DailyReport, DailyReporter, FileReportStore, InMemoryReportStore, ReportConfig,
+    ReportDataSource, ReportNotifier, ReportStore, RiskBreach,
+};
 pub use service::{DatabaseHealthChecker, ExchangeHealthChecker, HealthChecker, MonitoringService};
+pub use watchdog::{ChannelProbe, TaskHealth, TaskState, Watchdog, WatchdogConfig, WatchdogEvent};
@@ -161,6 +161,36 @@ impl MetricsCollector {
         tracing::debug!(metric = "realized_pnl_usd", value = pnl, "Set gauge");
     }
 
+    /// Realized vs. target annualized portfolio volatility
+    pub fn set_portfolio_volatility(&self, realized: f64, target: f64) {
+        tracing::debug!(
+            metric = "portfolio_volatility_realized",
+            value = realized,
+            "Set gauge"
+        );
+        tracing::debug!(
+            metric = "portfolio_volatility_target",
+            value = target,
+            "Set gauge"
+        );
+    }
+
+    /// Exposure scale factor applied by the volatility-targeting overlay
+    pub fn set_vol_target_scale(&self, scale: f64) {
+        tracing::debug!(metric = "vol_target_scale", value = scale, "Set gauge");
+    }
+
+    /// Portfolio drawdown from its equity peak and the size factor new
+    /// positions are throttled by
+    pub fn set_drawdown_throttle(&self, drawdown: f64, factor: f64) {
+        tracing::debug!(metric = "portfolio_drawdown", value = drawdown, "Set gauge");
+        tracing::debug!(
+            metric = "drawdown_throttle_factor",
+            value = factor,
+            "Set gauge"
+        );
+    }
+
     // Histogram methods
     pub fn record_order_latency(&self, latency_ms: f64) {
         tracing::debug!(
@@ -278,6 +308,9 @@ mod tests {
         // Test gauge updates
         collector.set_active_positions(5);
         collector.set_portfolio_value(100000.0);
+        collector.set_portfolio_volatility(0.18, 0.15);
+        collector.set_vol_target_scale(0.83);
+        collector.set_drawdown_throttle(0.07, 0.5);
 
         // Test histogram recordings
         collector.record_order_latency(25.5);
@@ -0,0 +1,389 @@
+//! Exchange outage detection
+//!
+//! Watches OKX REST latency and error rates ([`RestTelemetry`]) and market
+//! data silence on the WebSocket ([`ConnectionTelemetry`]) and decides whether
+//! the exchange is degraded. Entering degraded mode happens on the first
+//! check that breaches a threshold; leaving it takes several consecutive
+//! clean checks, so a flapping API does not toggle trading on every tick.
+//! Transitions are published as [`ExchangeHealthEvent`]s for the trading side
+//! to act on, and every check reports the REST figures as gauges.
+
+use crate::error::Result;
+use crate::service::MonitoringService;
+use chrono::{DateTime, Utc};
+use ea_okx_client::{ConnectionMetrics, ConnectionTelemetry, RestMetrics, RestTelemetry};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// 95th percentile REST latency over the telemetry window
+pub const REST_P95_LATENCY_MS: &str = "rest_p95_latency_ms";
+
+/// Share of REST requests failing with a 5xx or network error, in percent
+pub const REST_ERROR_PCT: &str = "rest_error_pct";
+
+/// REST requests answered with HTTP 429
+pub const REST_RATE_LIMITED: &str = "rest_rate_limited";
+
+/// When the exchange counts as degraded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutageThresholds {
+    pub max_rest_p95_latency_ms: f64,
+
+    /// Percentage of requests failing with a server or network error
+    pub max_rest_error_pct: f64,
+
+    /// Fewer requests than this in the window are too few to judge REST by
+    pub min_rest_requests: usize,
+
+    pub max_ws_silence_secs: f64,
+
+    /// Consecutive clean checks before leaving degraded mode
+    pub recovery_checks: u32,
+}
+
+impl Default for OutageThresholds {
+    fn default() -> Self {
+        Self {
+            max_rest_p95_latency_ms: 2000.0,
+            max_rest_error_pct: 20.0,
+            min_rest_requests: 5,
+            max_ws_silence_secs: 30.0,
+            recovery_checks: 3,
+        }
+    }
+}
+
+/// Current judgement of the exchange
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExchangeHealthStatus {
+    pub degraded: bool,
+
+    /// Thresholds breached on the last check
+    pub reasons: Vec<String>,
+
+    /// When the exchange entered its current state
+    pub since: DateTime<Utc>,
+
+    /// Clean checks so far while degraded
+    pub clean_checks: u32,
+
+    pub checked_at: Option<DateTime<Utc>>,
+}
+
+/// Transition between normal and degraded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExchangeHealthEvent {
+    Degraded {
+        reasons: Vec<String>,
+        at: DateTime<Utc>,
+    },
+    Recovered {
+        at: DateTime<Utc>,
+        degraded_secs: i64,
+    },
+}
+
+/// Decides from telemetry whether the exchange is degraded
+pub struct OutageDetector {
+    thresholds: OutageThresholds,
+    rest: Option<RestTelemetry>,
+    market_feed: Option<ConnectionTelemetry>,
+    monitoring: Option<Arc<MonitoringService>>,
+    status: Mutex<ExchangeHealthStatus>,
+    event_tx: mpsc::UnboundedSender<ExchangeHealthEvent>,
+    event_rx: Mutex<Option<mpsc::UnboundedReceiver<ExchangeHealthEvent>>>,
+}
+
+impl OutageDetector {
+    pub fn new(thresholds: OutageThresholds) -> Self {
+        let (event_tx, event_rx) = mpsc::unbounded_channel();
+
+        Self {
+            thresholds,
+            rest: None,
+            market_feed: None,
+            monitoring: None,
+            status: Mutex::new(ExchangeHealthStatus {
+                degraded: false,
+                reasons: Vec::new(),
+                since: Utc::now(),
+                clean_checks: 0,
+                checked_at: None,
+            }),
+            event_tx,
+            event_rx: Mutex::new(Some(event_rx)),
+        }
+    }
+
+    /// Judge REST health from `telemetry`
+    pub fn with_rest(mut self, telemetry: RestTelemetry) -> Self {
+        self.rest = Some(telemetry);
+        self
+    }
+
+    /// Judge market data silence from `telemetry`
+    pub fn with_market_feed(mut self, telemetry: ConnectionTelemetry) -> Self {
+        self.market_feed = Some(telemetry);
+        self
+    }
+
+    /// Report REST figures to `monitoring` on every check
+    pub fn with_monitoring(mut self, monitoring: Arc<MonitoringService>) -> Self {
+        self.monitoring = Some(monitoring);
+        self
+    }
+
+    pub fn thresholds(&self) -> &OutageThresholds {
+        &self.thresholds
+    }
+
+    pub fn status(&self) -> ExchangeHealthStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    /// Take the transition stream (once)
+    pub fn subscribe_events(&self) -> Option<mpsc::UnboundedReceiver<ExchangeHealthEvent>> {
+        self.event_rx.lock().ok()?.take()
+    }
+
+    /// Thresholds breached by the given metrics at `now`
+    pub fn assess(
+        &self,
+        rest: Option<&RestMetrics>,
+        market_feed: Option<&ConnectionMetrics>,
+        now: DateTime<Utc>,
+    ) -> Vec<String> {
+        let t = &self.thresholds;
+        let mut reasons = Vec::new();
+
+        if let Some(rest) = rest.filter(|r| r.requests >= t.min_rest_requests) {
+            if let Some(p95) = rest.p95_latency_ms
+                && p95 > t.max_rest_p95_latency_ms
+            {
+                reasons.push(format!(
+                    "REST p95 latency {:.0}ms over {:.0}ms",
+                    p95, t.max_rest_p95_latency_ms
+                ));
+            }
+            let error_pct = rest.error_pct();
+            if error_pct > t.max_rest_error_pct {
+                reasons.push(format!(
+                    "REST error rate {:.1}% over {:.1}% ({} of {} requests)",
+                    error_pct,
+                    t.max_rest_error_pct,
+                    rest.server_errors + rest.network_errors,
+                    rest.requests
+                ));
+            }
+        }
+
+        if let Some(feed) = market_feed {
+            match feed.seconds_since_any_message(now) {
+                Some(silence) if silence > t.max_ws_silence_secs => {
+                    reasons.push(format!("No market data for {:.0}s", silence))
+                }
+                None => reasons.push("Market data feed not connected".to_string()),
+                _ => {}
+            }
+        }
+
+        reasons
+    }
+
+    /// Fold one check's breached thresholds into the status, returning the
+    /// transition it caused, if any
+    pub fn observe(&self, reasons: Vec<String>, now: DateTime<Utc>) -> Option<ExchangeHealthEvent> {
+        let mut status = self.status.lock().unwrap();
+        status.checked_at = Some(now);
+
+        let event = match (status.degraded, reasons.is_empty()) {
+            (false, true) => None,
+            (false, false) => {
+                status.degraded = true;
+                status.since = now;
+                status.clean_checks = 0;
+                Some(ExchangeHealthEvent::Degraded {
+                    reasons: reasons.clone(),
+                    at: now,
+                })
+            }
+            (true, false) => {
+                status.clean_checks = 0;
+                None
+            }
+            (true, true) => {
+                status.clean_checks += 1;
+                if status.clean_checks >= self.thresholds.recovery_checks {
+                    let degraded_secs = (now - status.since).num_seconds();
+                    status.degraded = false;
+                    status.since = now;
+                    status.clean_checks = 0;
+                    Some(ExchangeHealthEvent::Recovered {
+                        at: now,
+                        degraded_secs,
+                    })
+                } else {
+                    None
+                }
+            }
+        };
+        status.reasons = reasons;
+
+        if let Some(event) = &event {
+            match event {
+                ExchangeHealthEvent::Degraded { reasons, .. } => {
+                    tracing::warn!("Exchange degraded: {}", reasons.join("; "))
+                }
+                ExchangeHealthEvent::Recovered { degraded_secs, .. } => {
+                    tracing::info!("Exchange recovered after {}s", degraded_secs)
+                }
+            }
+            let _ = self.event_tx.send(event.clone());
+        }
+        event
+    }
+
+    /// Snapshot the telemetry, report it and update the status
+    pub async fn check(&self) -> Result<Option<ExchangeHealthEvent>> {
+        let now = Utc::now();
+        let rest = match &self.rest {
+            Some(telemetry) => Some(telemetry.snapshot_at(now).await),
+            None => None,
+        };
+        let feed = match &self.market_feed {
+            Some(telemetry) => Some(telemetry.snapshot().await),
+            None => None,
+        };
+
+        if let (Some(monitoring), Some(rest)) = (&self.monitoring, &rest) {
+            monitoring.report_rest_metrics(rest).await?;
+        }
+
+        let reasons = self.assess(rest.as_ref(), feed.as_ref(), now);
+        Ok(self.observe(reasons, now))
+    }
+
+    /// Check every `interval` until the task is aborted
+    pub fn start(self: Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.check().await {
+                    tracing::warn!("Exchange health check failed: {}", e);
+                }
+            }
+        })
+    }
+}
+
+impl MonitoringService {
+    /// Evaluate alert rules against a REST metrics snapshot
+    pub async fn report_rest_metrics(&self, metrics: &RestMetrics) -> Result<()> {
+        let mut values = vec![
+            (REST_ERROR_PCT, metrics.error_pct()),
+            (REST_RATE_LIMITED, metrics.rate_limited as f64),
+        ];
+        if let Some(p95) = metrics.p95_latency_ms {
+            values.push((REST_P95_LATENCY_MS, p95));
+        }
+
+        for (name, value) in values {
+            tracing::debug!(metric = %name, value = value, "Set gauge");
+            self.evaluate_metric(name, value).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration as ChronoDuration;
+
+    fn rest(requests: usize, server_errors: usize, p95: f64) -> RestMetrics {
+        RestMetrics {
+            requests,
+            server_errors,
+            p95_latency_ms: Some(p95),
+            ..Default::default()
+        }
+    }
+
+    fn feed(silence_secs: i64, now: DateTime<Utc>) -> ConnectionMetrics {
+        ConnectionMetrics {
+            connected_at: Some(now - ChronoDuration::seconds(silence_secs)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_assess_flags_each_threshold() {
+        let detector = OutageDetector::new(OutageThresholds::default());
+        let now = Utc::now();
+
+        assert!(
+            detector
+                .assess(Some(&rest(10, 0, 300.0)), Some(&feed(1, now)), now)
+                .is_empty()
+        );
+
+        let reasons = detector.assess(Some(&rest(10, 5, 4000.0)), Some(&feed(60, now)), now);
+        assert_eq!(reasons.len(), 3);
+        assert!(reasons[0].contains("latency"));
+        assert!(reasons[1].contains("50.0%"));
+        assert!(reasons[2].contains("60s"));
+
+        // Too few requests to judge REST by
+        assert!(
+            detector
+                .assess(Some(&rest(2, 2, 9000.0)), None, now)
+                .is_empty()
+        );
+        assert_eq!(
+            detector.assess(None, Some(&ConnectionMetrics::default()), now),
+            vec!["Market data feed not connected".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_recovery_needs_consecutive_clean_checks() {
+        let detector = OutageDetector::new(OutageThresholds {
+            recovery_checks: 2,
+            ..Default::default()
+        });
+        let mut events = detector.subscribe_events().unwrap();
+        let t0 = Utc::now();
+        let at = |secs| t0 + ChronoDuration::seconds(secs);
+        let down = || vec!["REST error rate".to_string()];
+
+        assert!(detector.observe(Vec::new(), at(0)).is_none());
+        assert!(matches!(
+            detector.observe(down(), at(10)),
+            Some(ExchangeHealthEvent::Degraded { .. })
+        ));
+        assert!(detector.observe(down(), at(20)).is_none());
+
+        // A breach in between resets the clean streak
+        assert!(detector.observe(Vec::new(), at(30)).is_none());
+        assert!(detector.observe(down(), at(40)).is_none());
+        assert!(detector.observe(Vec::new(), at(50)).is_none());
+        assert!(detector.status().degraded);
+        assert_eq!(
+            detector.observe(Vec::new(), at(60)),
+            Some(ExchangeHealthEvent::Recovered {
+                at: at(60),
+                degraded_secs: 50
+            })
+        );
+
+        let status = detector.status();
+        assert!(!status.degraded);
+        assert_eq!(status.since, at(60));
+        assert_eq!(std::iter::from_fn(|| events.try_recv().ok()).count(), 2);
+    }
+}
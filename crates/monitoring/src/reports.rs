@@ -0,0 +1,721 @@
+//! Daily performance reports
+//!
+//! [`DailyReporter`] runs once a day at a configured UTC time, collects the
+//! previous day's trades from a [`ReportDataSource`], and builds a
+//! [`DailyReport`] with per-strategy and portfolio summaries. Each report is
+//! stored as JSON alongside a rendered HTML page and handed to any configured
+//! [`ReportNotifier`] channels.
+
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
+use ea_okx_core::models::Trade;
+use ea_okx_core::types::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+/// Reporter configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportConfig {
+    /// UTC time of day the previous day's report is generated
+    pub generate_at: NaiveTime,
+
+    /// Number of best and worst trades listed
+    pub top_trades: usize,
+}
+
+impl Default for ReportConfig {
+    fn default() -> Self {
+        Self {
+            generate_at: NaiveTime::from_hms_opt(0, 5, 0).unwrap_or_default(),
+            top_trades: 5,
+        }
+    }
+}
+
+/// Risk limit breach recorded during the day
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RiskBreach {
+    pub occurred_at: DateTime<Utc>,
+    pub strategy_id: Option<Uuid>,
+    /// Limit that was breached (e.g., "daily_loss_limit")
+    pub limit: String,
+    pub message: String,
+}
+
+/// Raw data for one day's report
+#[derive(Debug, Clone, Default)]
+pub struct ReportInput {
+    pub trades: Vec<Trade>,
+    pub strategy_names: HashMap<Uuid, String>,
+    pub breaches: Vec<RiskBreach>,
+}
+
+/// Summary of one strategy's day
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StrategySummary {
+    pub strategy_id: Uuid,
+    pub name: String,
+    pub trades: usize,
+    pub winning_trades: usize,
+    pub losing_trades: usize,
+    pub volume: Decimal,
+    pub realized_pnl: Decimal,
+    pub fees: Decimal,
+    /// Realized PnL less fees
+    pub net_pnl: Decimal,
+    /// Largest peak-to-trough drop in cumulative net PnL during the day
+    pub max_intraday_drawdown: Decimal,
+    pub risk_breaches: usize,
+}
+
+/// Summary across all strategies
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PortfolioSummary {
+    pub trades: usize,
+    pub winning_trades: usize,
+    pub losing_trades: usize,
+    pub volume: Decimal,
+    pub realized_pnl: Decimal,
+    pub fees: Decimal,
+    pub net_pnl: Decimal,
+    pub max_intraday_drawdown: Decimal,
+    pub risk_breaches: usize,
+}
+
+/// A single notable trade
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TradeHighlight {
+    pub trade_id: Uuid,
+    pub strategy_id: Uuid,
+    pub symbol: String,
+    pub net_pnl: Decimal,
+    pub executed_at: DateTime<Utc>,
+}
+
+/// One day's performance report
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyReport {
+    pub date: NaiveDate,
+    pub generated_at: DateTime<Utc>,
+    pub portfolio: PortfolioSummary,
+    pub strategies: Vec<StrategySummary>,
+    pub top_winners: Vec<TradeHighlight>,
+    pub top_losers: Vec<TradeHighlight>,
+    pub breaches: Vec<RiskBreach>,
+}
+
+#[derive(Default)]
+struct Tally {
+    trades: usize,
+    winning: usize,
+    losing: usize,
+    volume: Decimal,
+    realized: Decimal,
+    fees: Decimal,
+    cumulative: Decimal,
+    peak: Decimal,
+    max_drawdown: Decimal,
+}
+
+impl Tally {
+    fn add(&mut self, trade: &Trade) {
+        let realized = trade.realized_pnl.unwrap_or(Decimal::ZERO);
+        self.trades += 1;
+        if realized > Decimal::ZERO {
+            self.winning += 1;
+        } else if realized < Decimal::ZERO {
+            self.losing += 1;
+        }
+        self.volume += trade.trade_value();
+        self.realized += realized;
+        self.fees += trade.commission;
+
+        self.cumulative += realized - trade.commission;
+        self.peak = self.peak.max(self.cumulative);
+        self.max_drawdown = self.max_drawdown.max(self.peak - self.cumulative);
+    }
+}
+
+impl DailyReport {
+    /// Build the report for `date` from that day's trades
+    pub fn build(date: NaiveDate, mut input: ReportInput, top_trades: usize) -> Self {
+        input.trades.retain(|t| t.executed_at.date_naive() == date);
+        input.trades.sort_by_key(|t| t.executed_at);
+        input
+            .breaches
+            .retain(|b| b.occurred_at.date_naive() == date);
+        input.breaches.sort_by_key(|b| b.occurred_at);
+
+        let mut portfolio = Tally::default();
+        let mut per_strategy: BTreeMap<Uuid, Tally> = BTreeMap::new();
+        for trade in &input.trades {
+            portfolio.add(trade);
+            per_strategy
+                .entry(trade.strategy_id)
+                .or_default()
+                .add(trade);
+        }
+
+        let strategies = per_strategy
+            .into_iter()
+            .map(|(strategy_id, tally)| StrategySummary {
+                strategy_id,
+                name: input
+                    .strategy_names
+                    .get(&strategy_id)
+                    .cloned()
+                    .unwrap_or_else(|| strategy_id.to_string()),
+                trades: tally.trades,
+                winning_trades: tally.winning,
+                losing_trades: tally.losing,
+                volume: tally.volume,
+                realized_pnl: tally.realized,
+                fees: tally.fees,
+                net_pnl: tally.realized - tally.fees,
+                max_intraday_drawdown: tally.max_drawdown,
+                risk_breaches: input
+                    .breaches
+                    .iter()
+                    .filter(|b| b.strategy_id == Some(strategy_id))
+                    .count(),
+            })
+            .collect();
+
+        let mut highlights: Vec<TradeHighlight> = input
+            .trades
+            .iter()
+            .filter(|t| t.realized_pnl.is_some())
+            .map(|t| TradeHighlight {
+                trade_id: t.id,
+                strategy_id: t.strategy_id,
+                symbol: t.symbol.as_str().to_string(),
+                net_pnl: t.realized_pnl.unwrap_or(Decimal::ZERO) - t.commission,
+                executed_at: t.executed_at,
+            })
+            .collect();
+        highlights.sort_by_key(|h| std::cmp::Reverse(h.net_pnl));
+
+        let top_winners = highlights
+            .iter()
+            .filter(|h| h.net_pnl > Decimal::ZERO)
+            .take(top_trades)
+            .cloned()
+            .collect();
+        let top_losers = highlights
+            .iter()
+            .rev()
+            .filter(|h| h.net_pnl < Decimal::ZERO)
+            .take(top_trades)
+            .cloned()
+            .collect();
+
+        Self {
+            date,
+            generated_at: Utc::now(),
+            portfolio: PortfolioSummary {
+                trades: portfolio.trades,
+                winning_trades: portfolio.winning,
+                losing_trades: portfolio.losing,
+                volume: portfolio.volume,
+                realized_pnl: portfolio.realized,
+                fees: portfolio.fees,
+                net_pnl: portfolio.realized - portfolio.fees,
+                max_intraday_drawdown: portfolio.max_drawdown,
+                risk_breaches: input.breaches.len(),
+            },
+            strategies,
+            top_winners,
+            top_losers,
+            breaches: input.breaches,
+        }
+    }
+
+    /// Render the report as a standalone HTML page
+    pub fn render_html(&self) -> String {
+        let p = &self.portfolio;
+        let mut html = String::new();
+
+        html.push_str(&format!(
+            "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>Daily Report {date}</title>\n\
+             <style>body{{font-family:sans-serif}}table{{border-collapse:collapse}}\
+             td,th{{border:1px solid #ccc;padding:4px 8px;text-align:right}}\
+             td:first-child,th:first-child{{text-align:left}}</style></head><body>\n\
+             <h1>Daily Report {date}</h1>\n<p>Generated {generated}</p>\n",
+            date = self.date,
+            generated = self.generated_at.format("%Y-%m-%d %H:%M:%S UTC"),
+        ));
+
+        html.push_str("<h2>Portfolio</h2>\n<table>\n");
+        for (label, value) in [
+            ("Net PnL", p.net_pnl.to_string()),
+            ("Realized PnL", p.realized_pnl.to_string()),
+            ("Fees", p.fees.to_string()),
+            ("Trades", p.trades.to_string()),
+            (
+                "Winning / Losing",
+                format!("{} / {}", p.winning_trades, p.losing_trades),
+            ),
+            ("Volume", p.volume.to_string()),
+            ("Max Intraday Drawdown", p.max_intraday_drawdown.to_string()),
+            ("Risk Limit Breaches", p.risk_breaches.to_string()),
+        ] {
+            html.push_str(&format!("<tr><th>{}</th><td>{}</td></tr>\n", label, value));
+        }
+        html.push_str("</table>\n");
+
+        html.push_str(
+            "<h2>Strategies</h2>\n<table>\n<tr><th>Strategy</th><th>Net PnL</th><th>Fees</th>\
+             <th>Trades</th><th>Win / Loss</th><th>Max DD</th><th>Breaches</th></tr>\n",
+        );
+        for s in &self.strategies {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{} / {}</td><td>{}</td><td>{}</td></tr>\n",
+                escape(&s.name),
+                s.net_pnl,
+                s.fees,
+                s.trades,
+                s.winning_trades,
+                s.losing_trades,
+                s.max_intraday_drawdown,
+                s.risk_breaches
+            ));
+        }
+        html.push_str("</table>\n");
+
+        for (title, trades) in [
+            ("Top Winners", &self.top_winners),
+            ("Top Losers", &self.top_losers),
+        ] {
+            html.push_str(&format!(
+                "<h2>{}</h2>\n<table>\n<tr><th>Symbol</th><th>Net PnL</th><th>Time</th></tr>\n",
+                title
+            ));
+            for t in trades {
+                html.push_str(&format!(
+                    "<tr><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                    escape(&t.symbol),
+                    t.net_pnl,
+                    t.executed_at.format("%H:%M:%S")
+                ));
+            }
+            html.push_str("</table>\n");
+        }
+
+        if !self.breaches.is_empty() {
+            html.push_str("<h2>Risk Limit Breaches</h2>\n<ul>\n");
+            for b in &self.breaches {
+                html.push_str(&format!(
+                    "<li>{} <b>{}</b>: {}</li>\n",
+                    b.occurred_at.format("%H:%M:%S"),
+                    escape(&b.limit),
+                    escape(&b.message)
+                ));
+            }
+            html.push_str("</ul>\n");
+        }
+
+        html.push_str("</body></html>\n");
+        html
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Supplies the raw data for a day's report
+#[async_trait]
+pub trait ReportDataSource: Send + Sync {
+    async fn collect(&self, date: NaiveDate) -> Result<ReportInput>;
+}
+
+/// Delivery channel for finished reports (e.g., email)
+#[async_trait]
+pub trait ReportNotifier: Send + Sync {
+    fn name(&self) -> &str;
+
+    async fn deliver(&self, report: &DailyReport, html: &str) -> Result<()>;
+}
+
+/// Storage backend for daily reports
+pub trait ReportStore: Send + Sync {
+    /// Insert or replace the report for its date
+    fn save(&self, report: &DailyReport, html: &str) -> Result<()>;
+
+    /// Reports dated within `[start, end]`, oldest first
+    fn list(&self, start: NaiveDate, end: NaiveDate) -> Result<Vec<DailyReport>>;
+
+    /// Rendered HTML for one day
+    fn html(&self, date: NaiveDate) -> Result<Option<String>>;
+}
+
+/// In-memory store, mainly for tests
+#[derive(Debug, Default)]
+pub struct InMemoryReportStore {
+    reports: RwLock<BTreeMap<NaiveDate, (DailyReport, String)>>,
+}
+
+impl InMemoryReportStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ReportStore for InMemoryReportStore {
+    fn save(&self, report: &DailyReport, html: &str) -> Result<()> {
+        self.reports
+            .write()
+            .map_err(|e| Error::ReportError(e.to_string()))?
+            .insert(report.date, (report.clone(), html.to_string()));
+        Ok(())
+    }
+
+    fn list(&self, start: NaiveDate, end: NaiveDate) -> Result<Vec<DailyReport>> {
+        let reports = self
+            .reports
+            .read()
+            .map_err(|e| Error::ReportError(e.to_string()))?;
+        Ok(reports
+            .range(start..=end)
+            .map(|(_, (report, _))| report.clone())
+            .collect())
+    }
+
+    fn html(&self, date: NaiveDate) -> Result<Option<String>> {
+        let reports = self
+            .reports
+            .read()
+            .map_err(|e| Error::ReportError(e.to_string()))?;
+        Ok(reports.get(&date).map(|(_, html)| html.clone()))
+    }
+}
+
+/// File-backed store writing `YYYY-MM-DD.json` and `YYYY-MM-DD.html` per day
+#[derive(Debug)]
+pub struct FileReportStore {
+    dir: PathBuf,
+}
+
+impl FileReportStore {
+    /// Creates the store, creating the directory if needed
+    pub fn new(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).map_err(|e| {
+            Error::ReportError(format!("Failed to create {}: {}", dir.display(), e))
+        })?;
+        Ok(Self { dir })
+    }
+
+    fn path_for(&self, date: NaiveDate, extension: &str) -> PathBuf {
+        self.dir
+            .join(format!("{}.{}", date.format("%Y-%m-%d"), extension))
+    }
+}
+
+impl ReportStore for FileReportStore {
+    fn save(&self, report: &DailyReport, html: &str) -> Result<()> {
+        let json =
+            serde_json::to_vec_pretty(report).map_err(|e| Error::ReportError(e.to_string()))?;
+        for (path, contents) in [
+            (self.path_for(report.date, "json"), json.as_slice()),
+            (self.path_for(report.date, "html"), html.as_bytes()),
+        ] {
+            fs::write(&path, contents).map_err(|e| {
+                Error::ReportError(format!("Failed to write {}: {}", path.display(), e))
+            })?;
+        }
+        Ok(())
+    }
+
+    fn list(&self, start: NaiveDate, end: NaiveDate) -> Result<Vec<DailyReport>> {
+        let mut reports = Vec::new();
+        let mut date = start;
+        while date <= end {
+            let path = self.path_for(date, "json");
+            if path.exists() {
+                let bytes = fs::read(&path).map_err(|e| {
+                    Error::ReportError(format!("Failed to read {}: {}", path.display(), e))
+                })?;
+                match serde_json::from_slice(&bytes) {
+                    Ok(report) => reports.push(report),
+                    Err(e) => {
+                        tracing::warn!("Skipping unreadable report {}: {}", path.display(), e)
+                    }
+                }
+            }
+            date += Duration::days(1);
+        }
+        Ok(reports)
+    }
+
+    fn html(&self, date: NaiveDate) -> Result<Option<String>> {
+        let path = self.path_for(date, "html");
+        if !path.exists() {
+            return Ok(None);
+        }
+        fs::read_to_string(&path)
+            .map(Some)
+            .map_err(|e| Error::ReportError(format!("Failed to read {}: {}", path.display(), e)))
+    }
+}
+
+/// Generates, stores and delivers a report every day
+pub struct DailyReporter {
+    config: ReportConfig,
+    source: Arc<dyn ReportDataSource>,
+    store: Arc<dyn ReportStore>,
+    notifiers: Vec<Arc<dyn ReportNotifier>>,
+}
+
+impl DailyReporter {
+    pub fn new(
+        config: ReportConfig,
+        source: Arc<dyn ReportDataSource>,
+        store: Arc<dyn ReportStore>,
+    ) -> Self {
+        Self {
+            config,
+            source,
+            store,
+            notifiers: Vec::new(),
+        }
+    }
+
+    /// Also deliver each report through `notifier`
+    pub fn with_notifier(mut self, notifier: Arc<dyn ReportNotifier>) -> Self {
+        self.notifiers.push(notifier);
+        self
+    }
+
+    /// Build, store and deliver the report for `date`
+    ///
+    /// Delivery failures are logged; the stored report is still returned.
+    pub async fn generate(&self, date: NaiveDate) -> Result<DailyReport> {
+        let input = self.source.collect(date).await?;
+        let report = DailyReport::build(date, input, self.config.top_trades);
+        let html = report.render_html();
+        self.store.save(&report, &html)?;
+
+        for notifier in &self.notifiers {
+            if let Err(e) = notifier.deliver(&report, &html).await {
+                tracing::warn!(
+                    "Failed to deliver daily report for {} via {}: {}",
+                    date,
+                    notifier.name(),
+                    e
+                );
+            }
+        }
+
+        tracing::info!(
+            date = %date,
+            net_pnl = %report.portfolio.net_pnl,
+            trades = report.portfolio.trades,
+            "Daily report generated"
+        );
+        Ok(report)
+    }
+
+    /// Stored reports dated within `[start, end]`
+    pub fn get_daily_reports(&self, start: NaiveDate, end: NaiveDate) -> Result<Vec<DailyReport>> {
+        self.store.list(start, end)
+    }
+
+    /// Rendered HTML of a stored report
+    pub fn get_report_html(&self, date: NaiveDate) -> Result<Option<String>> {
+        self.store.html(date)
+    }
+
+    /// First scheduled run strictly after `now`
+    pub fn next_run_after(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let today = now.date_naive().and_time(self.config.generate_at).and_utc();
+        if today > now {
+            today
+        } else {
+            today + Duration::days(1)
+        }
+    }
+
+    /// Generate the previous UTC day's report at the configured time each day
+    /// until the task is aborted
+    pub fn spawn(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                let now = Utc::now();
+                let next = self.next_run_after(now);
+                let wait = (next - now).to_std().unwrap_or_default();
+                tokio::time::sleep(wait).await;
+
+                let date = next.date_naive() - Duration::days(1);
+                if let Err(e) = self.generate(date).await {
+                    tracing::error!("Failed to generate daily report for {}: {}", date, e);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use ea_okx_core::models::{OrderSide, OrderType};
+    use ea_okx_core::types::{Price, Quantity, Symbol};
+    use rust_decimal_macros::dec;
+
+    fn trade(strategy_id: Uuid, hour: u32, pnl: Decimal) -> Trade {
+        let mut trade = Trade::new(
+            strategy_id,
+            "cl".to_string(),
+            Symbol::new("BTC-USDT").unwrap(),
+            OrderSide::Sell,
+            OrderType::Market,
+            Quantity::new(dec!(0.1)).unwrap(),
+            Price::new(dec!(50000)).unwrap(),
+            dec!(1),
+        );
+        trade.realized_pnl = Some(pnl);
+        trade.executed_at = Utc.with_ymd_and_hms(2024, 3, 1, hour, 0, 0).unwrap();
+        trade
+    }
+
+    struct FixedSource(ReportInput);
+
+    #[async_trait]
+    impl ReportDataSource for FixedSource {
+        async fn collect(&self, _date: NaiveDate) -> Result<ReportInput> {
+            Ok(self.0.clone())
+        }
+    }
+
+    fn input() -> (Uuid, Uuid, ReportInput) {
+        let (trend, carry) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut late = trade(carry, 0, dec!(999));
+        late.executed_at = Utc.with_ymd_and_hms(2024, 3, 2, 0, 30, 0).unwrap();
+
+        let input = ReportInput {
+            trades: vec![
+                trade(trend, 1, dec!(100)),
+                trade(trend, 2, dec!(-150)),
+                trade(trend, 3, dec!(-50)),
+                trade(trend, 4, dec!(300)),
+                trade(carry, 5, dec!(20)),
+                late,
+            ],
+            strategy_names: HashMap::from([(trend, "Trend".to_string())]),
+            breaches: vec![RiskBreach {
+                occurred_at: Utc.with_ymd_and_hms(2024, 3, 1, 3, 0, 0).unwrap(),
+                strategy_id: Some(trend),
+                limit: "daily_loss_limit".to_string(),
+                message: "Loss <limit>".to_string(),
+            }],
+        };
+        (trend, carry, input)
+    }
+
+    #[test]
+    fn test_build_summaries() {
+        let (trend, carry, input) = input();
+        let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let report = DailyReport::build(date, input, 2);
+
+        assert_eq!(report.portfolio.trades, 5);
+        assert_eq!(report.portfolio.realized_pnl, dec!(220));
+        assert_eq!(report.portfolio.fees, dec!(5));
+        assert_eq!(report.portfolio.net_pnl, dec!(215));
+
+        let trend_summary = report
+            .strategies
+            .iter()
+            .find(|s| s.strategy_id == trend)
+            .unwrap();
+        assert_eq!(trend_summary.name, "Trend");
+        assert_eq!(trend_summary.winning_trades, 2);
+        assert_eq!(trend_summary.losing_trades, 2);
+        // Cumulative net: 99, -52, -103, 196 -> peak 99, trough -103
+        assert_eq!(trend_summary.max_intraday_drawdown, dec!(202));
+        assert_eq!(trend_summary.risk_breaches, 1);
+
+        let carry_summary = report
+            .strategies
+            .iter()
+            .find(|s| s.strategy_id == carry)
+            .unwrap();
+        assert_eq!(carry_summary.name, carry.to_string());
+
+        assert_eq!(report.top_winners.len(), 2);
+        assert_eq!(report.top_winners[0].net_pnl, dec!(299));
+        assert_eq!(report.top_losers[0].net_pnl, dec!(-151));
+        assert_eq!(report.top_losers.len(), 2);
+
+        let html = report.render_html();
+        assert!(html.contains("Daily Report 2024-03-01"));
+        assert!(html.contains("Loss &lt;limit&gt;"));
+    }
+
+    #[tokio::test]
+    async fn test_generate_stores_and_lists_reports() {
+        let (_, _, input) = input();
+        let store = Arc::new(InMemoryReportStore::new());
+        let reporter = DailyReporter::new(
+            ReportConfig::default(),
+            Arc::new(FixedSource(input)),
+            store.clone(),
+        );
+
+        let first = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let second = NaiveDate::from_ymd_opt(2024, 3, 2).unwrap();
+        reporter.generate(first).await.unwrap();
+        reporter.generate(second).await.unwrap();
+
+        let reports = reporter.get_daily_reports(first, second).unwrap();
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[1].portfolio.trades, 1);
+        assert_eq!(reporter.get_daily_reports(second, second).unwrap().len(), 1);
+        assert!(reporter.get_report_html(first).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_file_store_round_trip() {
+        let dir = std::env::temp_dir().join(format!("ea-okx-reports-{}", Uuid::new_v4()));
+        let store = FileReportStore::new(&dir).unwrap();
+        let (_, _, input) = input();
+        let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let report = DailyReport::build(date, input, 3);
+
+        store.save(&report, &report.render_html()).unwrap();
+        assert_eq!(store.list(date, date).unwrap(), vec![report]);
+        assert!(store.html(date).unwrap().unwrap().contains("<table>"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_next_run_after() {
+        let reporter = DailyReporter::new(
+            ReportConfig::default(),
+            Arc::new(FixedSource(ReportInput::default())),
+            Arc::new(InMemoryReportStore::new()),
+        );
+
+        let before = Utc.with_ymd_and_hms(2024, 3, 1, 0, 1, 0).unwrap();
+        assert_eq!(
+            reporter.next_run_after(before),
+            Utc.with_ymd_and_hms(2024, 3, 1, 0, 5, 0).unwrap()
+        );
+        let after = Utc.with_ymd_and_hms(2024, 3, 1, 0, 5, 0).unwrap();
+        assert_eq!(
+            reporter.next_run_after(after),
+            Utc.with_ymd_and_hms(2024, 3, 2, 0, 5, 0).unwrap()
+        );
+    }
+}
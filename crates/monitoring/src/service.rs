@@ -1,19 +1,26 @@
 use crate::alerts::{Alert, AlertRule};
 use crate::error::Result;
+use crate::expression::{ExpressionRule, MetricHistory};
 use crate::metrics::{HealthCheck, HealthReport, MetricsCollector, PerformanceSnapshot};
 use async_trait::async_trait;
 use chrono::Utc;
 use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{RwLock, mpsc};
+use tokio::task::JoinHandle;
 use uuid::Uuid;
 
 /// Monitoring service that coordinates metrics collection, health checks, and alerting
 pub struct MonitoringService {
     metrics: Arc<MetricsCollector>,
     alert_rules: Arc<RwLock<HashMap<Uuid, AlertRule>>>,
+    expression_rules: Arc<RwLock<HashMap<Uuid, ExpressionRule>>>,
+    metric_history: Arc<RwLock<MetricHistory>>,
     active_alerts: Arc<RwLock<HashMap<Uuid, Alert>>>,
     health_checks: Arc<RwLock<Vec<Box<dyn HealthChecker>>>>,
+    alert_tx: mpsc::UnboundedSender<Alert>,
+    alert_rx: Mutex<Option<mpsc::UnboundedReceiver<Alert>>>,
 }
 
 /// Trait for components that can perform health checks
@@ -25,14 +32,24 @@ pub trait HealthChecker: Send + Sync {
 
 impl MonitoringService {
     pub fn new() -> Self {
+        let (alert_tx, alert_rx) = mpsc::unbounded_channel();
         Self {
             metrics: Arc::new(MetricsCollector::new()),
             alert_rules: Arc::new(RwLock::new(HashMap::new())),
+            expression_rules: Arc::new(RwLock::new(HashMap::new())),
+            metric_history: Arc::new(RwLock::new(MetricHistory::default())),
             active_alerts: Arc::new(RwLock::new(HashMap::new())),
             health_checks: Arc::new(RwLock::new(Vec::new())),
+            alert_tx,
+            alert_rx: Mutex::new(Some(alert_rx)),
         }
     }
 
+    /// Get a receiver of every newly raised alert (can only be called once)
+    pub fn subscribe_alerts(&self) -> Option<mpsc::UnboundedReceiver<Alert>> {
+        self.alert_rx.lock().ok()?.take()
+    }
+
     /// Get reference to metrics collector
     pub fn metrics(&self) -> Arc<MetricsCollector> {
         self.metrics.clone()
@@ -58,8 +75,43 @@ impl MonitoringService {
         rules.values().cloned().collect()
     }
 
+    /// Register a composite expression rule
+    pub async fn register_expression_rule(&self, rule: ExpressionRule) -> Result<()> {
+        self.metric_history
+            .write()
+            .await
+            .ensure_retention(rule.expression.max_window());
+        self.expression_rules.write().await.insert(rule.id, rule);
+        Ok(())
+    }
+
+    /// Remove a composite expression rule
+    pub async fn remove_expression_rule(&self, rule_id: Uuid) -> Result<()> {
+        self.expression_rules.write().await.remove(&rule_id);
+        Ok(())
+    }
+
+    /// Get all composite expression rules
+    pub async fn get_expression_rules(&self) -> Vec<ExpressionRule> {
+        let rules = self.expression_rules.read().await;
+        rules.values().cloned().collect()
+    }
+
     /// Evaluate all alert rules against a metric
+    ///
+    /// The value is also recorded in the metric history and expression rules
+    /// are re-evaluated.
     pub async fn evaluate_metric(&self, metric_name: &str, value: f64) -> Result<()> {
+        self.metric_history
+            .write()
+            .await
+            .record(metric_name, value, Utc::now());
+
+        self.evaluate_threshold_rules(metric_name, value).await;
+        self.evaluate_expression_rules().await
+    }
+
+    async fn evaluate_threshold_rules(&self, metric_name: &str, value: f64) {
         let mut rules = self.alert_rules.write().await;
         let mut alerts = self.active_alerts.write().await;
 
@@ -72,6 +124,7 @@ impl MonitoringService {
 
                 let alert = Alert::new(rule, value, message);
                 alerts.insert(alert.id, alert.clone());
+                let _ = self.alert_tx.send(alert);
 
                 // Update last triggered time
                 rule.last_triggered = Some(Utc::now());
@@ -85,10 +138,65 @@ impl MonitoringService {
                 );
             }
         }
+    }
+
+    /// Evaluate composite expression rules against the metric history
+    ///
+    /// Absence conditions only change with time, so this should also run
+    /// periodically (see [`spawn_rule_evaluator`](Self::spawn_rule_evaluator)).
+    pub async fn evaluate_expression_rules(&self) -> Result<()> {
+        let now = Utc::now();
+        let history = self.metric_history.read().await;
+        let mut rules = self.expression_rules.write().await;
+        let mut alerts = self.active_alerts.write().await;
+
+        for rule in rules.values_mut() {
+            if rule.evaluate(&history, now) {
+                let alert = rule.alert(&history);
+                alerts.insert(alert.id, alert.clone());
+                let _ = self.alert_tx.send(alert);
+
+                rule.last_triggered = Some(now);
+
+                tracing::warn!(
+                    rule_name = %rule.name,
+                    expression = %rule.expression,
+                    "Alert triggered"
+                );
+            }
+        }
 
         Ok(())
     }
 
+    /// Evaluate expression rules every `interval` until the task is aborted
+    pub fn spawn_rule_evaluator(self: Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.evaluate_expression_rules().await {
+                    tracing::warn!("Failed to evaluate expression rules: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Record an alert raised outside the rule engine (see [`Alert::event`])
+    pub async fn raise_alert(&self, alert: Alert) {
+        tracing::warn!(
+            source = %alert.rule_name,
+            severity = ?alert.severity,
+            message = %alert.message,
+            "Alert raised"
+        );
+        self.active_alerts
+            .write()
+            .await
+            .insert(alert.id, alert.clone());
+        let _ = self.alert_tx.send(alert);
+    }
+
     /// Get all active (unacknowledged) alerts
     pub async fn get_active_alerts(&self) -> Vec<Alert> {
         let alerts = self.active_alerts.read().await;
@@ -192,6 +300,12 @@ impl DatabaseHealthChecker {
     }
 }
 
+impl Default for DatabaseHealthChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[async_trait]
 impl HealthChecker for DatabaseHealthChecker {
     async fn check(&self) -> HealthCheck {
@@ -230,6 +344,12 @@ impl ExchangeHealthChecker {
     }
 }
 
+impl Default for ExchangeHealthChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[async_trait]
 impl HealthChecker for ExchangeHealthChecker {
     async fn check(&self) -> HealthCheck {
@@ -318,6 +438,49 @@ mod tests {
         assert_eq!(active_alerts[0].severity, AlertSeverity::Critical);
     }
 
+    #[tokio::test]
+    async fn test_raised_alerts_are_streamed_once() {
+        let service = MonitoringService::new();
+        let mut alerts = service.subscribe_alerts().unwrap();
+        assert!(service.subscribe_alerts().is_none());
+
+        service
+            .raise_alert(Alert::event(
+                "instrument_status",
+                AlertSeverity::Critical,
+                "BTC-USDT is suspended",
+            ))
+            .await;
+
+        let alert = alerts.try_recv().unwrap();
+        assert_eq!(alert.rule_name, "instrument_status");
+        assert!(alerts.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_expression_rule_fires_on_composite_condition() {
+        let service = MonitoringService::new();
+        let rule = ExpressionRule::parse(
+            "Degraded Execution",
+            "Slow orders while errors are elevated",
+            "order_latency > 100 and error_rate >= 0.02",
+            AlertSeverity::Warning,
+        )
+        .unwrap();
+        service.register_expression_rule(rule).await.unwrap();
+
+        service
+            .evaluate_metric("order_latency", 150.0)
+            .await
+            .unwrap();
+        assert!(service.get_active_alerts().await.is_empty());
+
+        service.evaluate_metric("error_rate", 0.03).await.unwrap();
+        let active_alerts = service.get_active_alerts().await;
+        assert_eq!(active_alerts.len(), 1);
+        assert_eq!(active_alerts[0].rule_name, "Degraded Execution");
+    }
+
     #[tokio::test]
     async fn test_acknowledge_alert() {
         let service = MonitoringService::new();
@@ -349,6 +512,23 @@ mod tests {
         assert_eq!(active_alerts_after.len(), 0);
     }
 
+    #[tokio::test]
+    async fn test_raise_alert() {
+        let service = MonitoringService::new();
+        service
+            .raise_alert(Alert::event(
+                "instrument_status",
+                AlertSeverity::Critical,
+                "XYZ-USDT suspended",
+            ))
+            .await;
+
+        let alerts = service.get_active_alerts().await;
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].rule_name, "instrument_status");
+        assert!(alerts[0].rule_id.is_nil());
+    }
+
     #[tokio::test]
     async fn test_health_check() {
         let service = MonitoringService::new();
@@ -0,0 +1,506 @@
+//! Watchdog for long-running tasks
+//!
+//! A processor task that panics or ends its loop leaves nothing behind: the
+//! data it moved simply stops. The [`Watchdog`] watches registered tasks
+//! through a [`Heartbeat`] each one beats, the task's `JoinHandle` and
+//! optionally the channel it feeds, and treats a missed heartbeat, an exited
+//! task or a closed channel as a failure. Failures raise a Critical alert;
+//! tasks registered with [`supervise`](Watchdog::supervise) are also aborted
+//! and spawned again, up to a restart limit.
+
+use crate::alerts::{Alert, AlertSeverity};
+use crate::error::Result;
+use crate::service::MonitoringService;
+use chrono::{DateTime, Utc};
+use ea_okx_core::heartbeat::Heartbeat;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Spawns a supervised task, handing it the heartbeat to beat
+pub type SpawnTask = Box<dyn Fn(Heartbeat) -> JoinHandle<()> + Send + Sync>;
+
+/// Watchdog settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatchdogConfig {
+    /// Restarts per supervised task before it is left failed
+    pub max_restarts: u32,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self { max_restarts: 3 }
+    }
+}
+
+/// How a watched task is doing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskState {
+    Alive,
+    /// No heartbeat within the timeout
+    Stalled,
+    /// The task returned or panicked
+    Exited,
+    /// The channel it feeds or drains can no longer deliver
+    ChannelClosed,
+}
+
+/// One watched task, as reported by [`Watchdog::status`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskHealth {
+    pub name: String,
+    pub state: TaskState,
+
+    /// Why the task counts as failed
+    pub reason: Option<String>,
+
+    pub last_beat: Option<DateTime<Utc>>,
+    pub timeout_secs: Option<u64>,
+    pub supervised: bool,
+    pub restarts: u32,
+
+    /// When the task entered its current state
+    pub since: DateTime<Utc>,
+}
+
+/// Failure, restart or recovery of a watched task
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WatchdogEvent {
+    Failed {
+        task: String,
+        state: TaskState,
+        reason: String,
+        at: DateTime<Utc>,
+    },
+    Restarted {
+        task: String,
+        restarts: u32,
+        at: DateTime<Utc>,
+    },
+    /// A stalled task beat again without being restarted
+    Recovered { task: String, at: DateTime<Utc> },
+}
+
+/// Tells whether a channel can still deliver
+pub struct ChannelProbe(Box<dyn Fn() -> bool + Send + Sync>);
+
+impl ChannelProbe {
+    /// Probe with a custom check returning true once the channel is closed
+    pub fn new(closed: impl Fn() -> bool + Send + Sync + 'static) -> Self {
+        Self(Box::new(closed))
+    }
+
+    /// Closed once the receiver is dropped or every sender is gone; the
+    /// probe holds only a weak sender, so it does not keep the channel open
+    pub fn sender<T: Send + 'static>(sender: &mpsc::Sender<T>) -> Self {
+        let weak = sender.downgrade();
+        Self::new(move || weak.upgrade().is_none_or(|sender| sender.is_closed()))
+    }
+
+    /// As [`sender`](Self::sender), for an unbounded channel
+    pub fn unbounded<T: Send + 'static>(sender: &mpsc::UnboundedSender<T>) -> Self {
+        let weak = sender.downgrade();
+        Self::new(move || weak.upgrade().is_none_or(|sender| sender.is_closed()))
+    }
+
+    fn is_closed(&self) -> bool {
+        (self.0)()
+    }
+}
+
+struct WatchedTask {
+    heartbeat: Option<(Heartbeat, Duration)>,
+    handle: Option<JoinHandle<()>>,
+    channel: Option<ChannelProbe>,
+    spawn: Option<SpawnTask>,
+    state: TaskState,
+    reason: Option<String>,
+    restarts: u32,
+    since: DateTime<Utc>,
+}
+
+impl WatchedTask {
+    fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            heartbeat: None,
+            handle: None,
+            channel: None,
+            spawn: None,
+            state: TaskState::Alive,
+            reason: None,
+            restarts: 0,
+            since: now,
+        }
+    }
+
+    /// The failure the task shows at `now`, if any
+    fn assess(&self, now: DateTime<Utc>) -> Option<(TaskState, String)> {
+        if self.channel.as_ref().is_some_and(ChannelProbe::is_closed) {
+            return Some((TaskState::ChannelClosed, "channel closed".to_string()));
+        }
+        if self.handle.as_ref().is_some_and(JoinHandle::is_finished) {
+            return Some((TaskState::Exited, "task exited".to_string()));
+        }
+        if let Some((heartbeat, timeout)) = &self.heartbeat {
+            let silent = heartbeat.silent_for(now);
+            if silent.to_std().unwrap_or_default() > *timeout {
+                return Some((
+                    TaskState::Stalled,
+                    format!(
+                        "no heartbeat for {}s (timeout {}s)",
+                        silent.num_seconds(),
+                        timeout.as_secs()
+                    ),
+                ));
+            }
+        }
+        None
+    }
+}
+
+/// Watches long-running tasks and restarts supervised ones that fail
+pub struct Watchdog {
+    config: WatchdogConfig,
+    monitoring: Option<Arc<MonitoringService>>,
+    tasks: Mutex<BTreeMap<String, WatchedTask>>,
+    event_tx: mpsc::UnboundedSender<WatchdogEvent>,
+    event_rx: Mutex<Option<mpsc::UnboundedReceiver<WatchdogEvent>>>,
+}
+
+impl Watchdog {
+    pub fn new(config: WatchdogConfig) -> Self {
+        let (event_tx, event_rx) = mpsc::unbounded_channel();
+
+        Self {
+            config,
+            monitoring: None,
+            tasks: Mutex::new(BTreeMap::new()),
+            event_tx,
+            event_rx: Mutex::new(Some(event_rx)),
+        }
+    }
+
+    /// Raise alerts for failures, restarts and recoveries on `monitoring`
+    pub fn with_monitoring(mut self, monitoring: Arc<MonitoringService>) -> Self {
+        self.monitoring = Some(monitoring);
+        self
+    }
+
+    pub fn config(&self) -> &WatchdogConfig {
+        &self.config
+    }
+
+    /// Take the event stream (once)
+    pub fn subscribe_events(&self) -> Option<mpsc::UnboundedReceiver<WatchdogEvent>> {
+        self.event_rx.lock().ok()?.take()
+    }
+
+    /// Expect `heartbeat` to beat at least every `timeout`
+    ///
+    /// Replaces the heartbeat of a task already watched under `name`.
+    pub fn watch_heartbeat(&self, name: &str, heartbeat: Heartbeat, timeout: Duration) {
+        self.with_task(name, |task| task.heartbeat = Some((heartbeat, timeout)));
+    }
+
+    /// Register a task that will beat the returned heartbeat itself
+    pub fn register(&self, name: &str, timeout: Duration) -> Heartbeat {
+        let heartbeat = Heartbeat::new();
+        self.watch_heartbeat(name, heartbeat.clone(), timeout);
+        heartbeat
+    }
+
+    /// Count the task as failed once `handle` finishes
+    pub fn watch_handle(&self, name: &str, handle: JoinHandle<()>) {
+        self.with_task(name, |task| task.handle = Some(handle));
+    }
+
+    /// Count the task as failed once `probe` reports its channel closed
+    pub fn watch_channel(&self, name: &str, probe: ChannelProbe) {
+        self.with_task(name, |task| task.channel = Some(probe));
+    }
+
+    /// Spawn a task now and again whenever it fails, up to the restart limit
+    ///
+    /// `spawn` is handed the heartbeat the task must beat at least every
+    /// `timeout`; a failed instance is aborted before the next is spawned.
+    pub fn supervise(
+        &self,
+        name: &str,
+        timeout: Duration,
+        spawn: impl Fn(Heartbeat) -> JoinHandle<()> + Send + Sync + 'static,
+    ) {
+        let heartbeat = Heartbeat::new();
+        let handle = spawn(heartbeat.clone());
+        self.with_task(name, |task| {
+            if let Some(old) = task.handle.replace(handle) {
+                old.abort();
+            }
+            task.heartbeat = Some((heartbeat, timeout));
+            task.spawn = Some(Box::new(spawn));
+        });
+    }
+
+    /// Stop watching `name`; a supervised task keeps running
+    pub fn unregister(&self, name: &str) -> bool {
+        self.tasks.lock().unwrap().remove(name).is_some()
+    }
+
+    /// Every watched task, by name
+    pub fn status(&self) -> Vec<TaskHealth> {
+        self.tasks
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, task)| TaskHealth {
+                name: name.clone(),
+                state: task.state,
+                reason: task.reason.clone(),
+                last_beat: task.heartbeat.as_ref().map(|(hb, _)| hb.last_beat()),
+                timeout_secs: task.heartbeat.as_ref().map(|(_, t)| t.as_secs()),
+                supervised: task.spawn.is_some(),
+                restarts: task.restarts,
+                since: task.since,
+            })
+            .collect()
+    }
+
+    /// Judge every task at `now`, restarting failed supervised ones, and
+    /// return what changed
+    pub fn check_at(&self, now: DateTime<Utc>) -> Vec<WatchdogEvent> {
+        let mut events = Vec::new();
+        let mut tasks = self.tasks.lock().unwrap();
+
+        for (name, task) in tasks.iter_mut() {
+            let Some((state, reason)) = task.assess(now) else {
+                if task.state == TaskState::Stalled {
+                    task.state = TaskState::Alive;
+                    task.reason = None;
+                    task.since = now;
+                    events.push(WatchdogEvent::Recovered {
+                        task: name.clone(),
+                        at: now,
+                    });
+                }
+                continue;
+            };
+            if task.state != TaskState::Alive {
+                continue;
+            }
+
+            task.state = state;
+            task.reason = Some(reason.clone());
+            task.since = now;
+            events.push(WatchdogEvent::Failed {
+                task: name.clone(),
+                state,
+                reason,
+                at: now,
+            });
+
+            let Some(spawn) = &task.spawn else {
+                continue;
+            };
+            if task.restarts >= self.config.max_restarts {
+                continue;
+            }
+            if let Some(old) = task.handle.take() {
+                old.abort();
+            }
+            let heartbeat = Heartbeat::new();
+            heartbeat.beat_at(now);
+            task.handle = Some(spawn(heartbeat.clone()));
+            if let Some((current, _)) = &mut task.heartbeat {
+                *current = heartbeat;
+            }
+            task.restarts += 1;
+            task.state = TaskState::Alive;
+            task.reason = None;
+            events.push(WatchdogEvent::Restarted {
+                task: name.clone(),
+                restarts: task.restarts,
+                at: now,
+            });
+        }
+        drop(tasks);
+
+        for event in &events {
+            match event {
+                WatchdogEvent::Failed { task, reason, .. } => {
+                    tracing::error!("Task {} failed: {}", task, reason)
+                }
+                WatchdogEvent::Restarted { task, restarts, .. } => {
+                    tracing::warn!("Restarted task {} ({} restarts)", task, restarts)
+                }
+                WatchdogEvent::Recovered { task, .. } => {
+                    tracing::info!("Task {} is beating again", task)
+                }
+            }
+            let _ = self.event_tx.send(event.clone());
+        }
+        events
+    }
+
+    /// Judge every task now and raise alerts for what changed
+    pub async fn check(&self) -> Result<Vec<WatchdogEvent>> {
+        let events = self.check_at(Utc::now());
+        if let Some(monitoring) = &self.monitoring {
+            for event in &events {
+                monitoring.raise_alert(self.alert(event)).await;
+            }
+        }
+        Ok(events)
+    }
+
+    /// Check every `interval` until the task is aborted
+    pub fn start(self: Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.check().await {
+                    tracing::warn!("Watchdog check failed: {}", e);
+                }
+            }
+        })
+    }
+
+    fn alert(&self, event: &WatchdogEvent) -> Alert {
+        let (task, mut alert) = match event {
+            WatchdogEvent::Failed { task, reason, .. } => (
+                task,
+                Alert::event(
+                    "task_failed",
+                    AlertSeverity::Critical,
+                    format!("Task {} failed: {}", task, reason),
+                ),
+            ),
+            WatchdogEvent::Restarted { task, restarts, .. } => (
+                task,
+                Alert::event(
+                    "task_restarted",
+                    AlertSeverity::Warning,
+                    format!(
+                        "Task {} restarted ({} of {})",
+                        task, restarts, self.config.max_restarts
+                    ),
+                ),
+            ),
+            WatchdogEvent::Recovered { task, .. } => (
+                task,
+                Alert::event(
+                    "task_recovered",
+                    AlertSeverity::Info,
+                    format!("Task {} is beating again", task),
+                ),
+            ),
+        };
+        alert.metadata.insert("task".to_string(), task.clone());
+        alert
+    }
+
+    fn with_task(&self, name: &str, update: impl FnOnce(&mut WatchedTask)) {
+        let mut tasks = self.tasks.lock().unwrap();
+        let task = tasks
+            .entry(name.to_string())
+            .or_insert_with(|| WatchedTask::new(Utc::now()));
+        update(task);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn secs(n: i64) -> chrono::Duration {
+        chrono::Duration::seconds(n)
+    }
+
+    #[tokio::test]
+    async fn test_stall_alerts_and_recovery() {
+        let monitoring = Arc::new(MonitoringService::new());
+        let watchdog = Watchdog::new(WatchdogConfig::default()).with_monitoring(monitoring.clone());
+        let heartbeat = watchdog.register("ws_public_processor", Duration::from_secs(30));
+        let start = Utc::now();
+
+        heartbeat.beat_at(start);
+        assert!(watchdog.check_at(start + secs(20)).is_empty());
+
+        let events = watchdog.check_at(start + secs(45));
+        assert!(matches!(
+            &events[..],
+            [WatchdogEvent::Failed { state: TaskState::Stalled, reason, .. }]
+                if reason == "no heartbeat for 45s (timeout 30s)"
+        ));
+        // A failure is reported once, not on every check
+        assert!(watchdog.check_at(start + secs(50)).is_empty());
+        assert_eq!(watchdog.status()[0].state, TaskState::Stalled);
+
+        heartbeat.beat_at(start + secs(55));
+        let events = watchdog.check_at(start + secs(60));
+        assert!(matches!(&events[..], [WatchdogEvent::Recovered { .. }]));
+
+        heartbeat.beat_at(start - secs(60));
+        watchdog.check().await.unwrap();
+        let alerts = monitoring.get_active_alerts().await;
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].severity, AlertSeverity::Critical);
+        assert_eq!(alerts[0].metadata["task"], "ws_public_processor");
+    }
+
+    #[tokio::test]
+    async fn test_supervised_task_restarts_up_to_limit() {
+        let watchdog = Watchdog::new(WatchdogConfig { max_restarts: 1 });
+        let spawned = Arc::new(AtomicU32::new(0));
+        let counter = spawned.clone();
+        // Exits straight away, as a processor whose stream ended would
+        watchdog.supervise("signal_processor", Duration::from_secs(30), move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            tokio::spawn(async {})
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        let events = watchdog.check_at(Utc::now());
+        assert!(matches!(
+            &events[..],
+            [
+                WatchdogEvent::Failed {
+                    state: TaskState::Exited,
+                    ..
+                },
+                WatchdogEvent::Restarted { restarts: 1, .. }
+            ]
+        ));
+        assert_eq!(spawned.load(Ordering::SeqCst), 2);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        // Out of restarts, the task stays failed
+        let events = watchdog.check_at(Utc::now());
+        assert!(matches!(&events[..], [WatchdogEvent::Failed { .. }]));
+        assert_eq!(spawned.load(Ordering::SeqCst), 2);
+        let status = &watchdog.status()[0];
+        assert_eq!((status.state, status.restarts), (TaskState::Exited, 1));
+    }
+
+    #[test]
+    fn test_dead_channel() {
+        let watchdog = Watchdog::new(WatchdogConfig::default());
+        let (tx, rx) = mpsc::unbounded_channel::<u32>();
+        watchdog.watch_channel("market_events", ChannelProbe::unbounded(&tx));
+
+        assert!(watchdog.check_at(Utc::now()).is_empty());
+        drop(rx);
+        assert!(matches!(
+            &watchdog.check_at(Utc::now())[..],
+            [WatchdogEvent::Failed {
+                state: TaskState::ChannelClosed,
+                ..
+            }]
+        ));
+    }
+}
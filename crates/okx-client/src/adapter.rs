@@ -0,0 +1,419 @@
+//! OKX implementation of the venue-neutral [`ExchangeAdapter`]
+//!
+//! Orders, lookups, cancellations and instrument metadata go over REST; the
+//! streaming half needs a connected [`OkxWebSocketClient`] and reports as
+//! ended without one. Pushes are translated into [`MarketEvent`]s by
+//! [`market_event`], which replays of raw OKX captures use as well.
+
+use crate::error::Error;
+use crate::models::request::{AmendOrderRequest, CancelOrderRequest, PlaceOrderRequest};
+use crate::models::response::InstrumentSpec;
+use crate::models::websocket::{Channel, OrderData, SubscriptionRequest, WebSocketEvent};
+use crate::rest::OkxRestClient;
+use crate::websocket::OkxWebSocketClient;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use ea_okx_core::exchange::{
+    CandleUpdate, ExchangeAdapter, ExchangeOrder, FundingUpdate, InstrumentInfo, MarketEvent,
+    MarketStream, MarketSubscription, TickerUpdate, TradeTick,
+};
+use ea_okx_core::models::{Order, OrderSide, OrderStatus, OrderType};
+use ea_okx_core::{InstrumentKind, Symbol};
+use rust_decimal::Decimal;
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+type CoreResult<T> = ea_okx_core::Result<T>;
+
+impl From<Error> for ea_okx_core::Error {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::ApiError { code, message } => {
+                ea_okx_core::Error::ExchangeRejected { code, message }
+            }
+            Error::RateLimitExceeded(message) => ea_okx_core::Error::RateLimited(message),
+            e @ (Error::Timeout(_) | Error::ConnectionError(_) | Error::HttpError(_)) => {
+                ea_okx_core::Error::ExchangeUnavailable(e.to_string())
+            }
+            e => ea_okx_core::Error::ExchangeError(e.to_string()),
+        }
+    }
+}
+
+/// OKX behind the [`ExchangeAdapter`] interface
+pub struct OkxAdapter {
+    rest: Arc<OkxRestClient>,
+    ws: Option<OkxWebSocketClient>,
+    /// `tdMode` orders are placed with: cash, cross or isolated
+    trade_mode: String,
+}
+
+impl OkxAdapter {
+    /// Adapter trading spot in cash mode, without a streaming connection
+    pub fn new(rest: Arc<OkxRestClient>) -> Self {
+        Self {
+            rest,
+            ws: None,
+            trade_mode: "cash".to_string(),
+        }
+    }
+
+    /// Stream events from `ws`, which must already be connected
+    pub fn with_websocket(mut self, ws: OkxWebSocketClient) -> Self {
+        self.ws = Some(ws);
+        self
+    }
+
+    /// Place orders in `trade_mode` (cash, cross or isolated)
+    pub fn with_trade_mode(mut self, trade_mode: impl Into<String>) -> Self {
+        self.trade_mode = trade_mode.into();
+        self
+    }
+
+    pub fn rest(&self) -> &Arc<OkxRestClient> {
+        &self.rest
+    }
+
+    fn websocket(&self) -> CoreResult<&OkxWebSocketClient> {
+        self.ws.as_ref().ok_or_else(|| {
+            ea_okx_core::Error::ConfigError("OKX adapter has no WebSocket connection".to_string())
+        })
+    }
+}
+
+#[async_trait]
+impl ExchangeAdapter for OkxAdapter {
+    fn name(&self) -> &'static str {
+        "okx"
+    }
+
+    async fn place_order(&self, order: &Order) -> CoreResult<String> {
+        let ord_type = match order.order_type {
+            OrderType::Market => "market",
+            OrderType::Limit => "limit",
+            OrderType::PostOnly => "post_only",
+            OrderType::Ioc => "ioc",
+            OrderType::Fok => "fok",
+            other => {
+                return Err(ea_okx_core::Error::InvalidOrderType(format!(
+                    "{:?} orders are placed as OKX algo orders",
+                    other
+                )));
+            }
+        };
+        let request = PlaceOrderRequest {
+            inst_id: order.symbol.as_str().to_string(),
+            td_mode: self.trade_mode.clone(),
+            side: side_str(order.side).to_string(),
+            ord_type: ord_type.to_string(),
+            sz: order.quantity.as_decimal().normalize().to_string(),
+            px: order.price.map(|p| p.as_decimal().normalize().to_string()),
+            cl_ord_id: Some(order.client_order_id.clone()),
+            tag: order.tag.clone(),
+            reduce_only: order.reduce_only.then_some(true),
+            attach_algo_ords: None,
+        };
+        let placed = match order.expires_at {
+            Some(expires_at) => self.rest.place_order_until(&request, expires_at).await?,
+            None => self.rest.place_order(&request).await?,
+        };
+        Ok(placed.ord_id)
+    }
+
+    async fn cancel_order(&self, symbol: &Symbol, client_order_id: &str) -> CoreResult<()> {
+        let request = CancelOrderRequest {
+            inst_id: symbol.as_str().to_string(),
+            ord_id: None,
+            cl_ord_id: Some(client_order_id.to_string()),
+        };
+        self.rest.cancel_order(&request).await?;
+        Ok(())
+    }
+
+    async fn amend_order(
+        &self,
+        symbol: &Symbol,
+        client_order_id: &str,
+        quantity: Option<Decimal>,
+        price: Option<Decimal>,
+    ) -> CoreResult<()> {
+        let request = AmendOrderRequest {
+            inst_id: symbol.as_str().to_string(),
+            ord_id: None,
+            cl_ord_id: Some(client_order_id.to_string()),
+            new_sz: quantity.map(|q| q.normalize().to_string()),
+            new_px: price.map(|p| p.normalize().to_string()),
+        };
+        self.rest.amend_order(&request).await?;
+        Ok(())
+    }
+
+    async fn order(
+        &self,
+        symbol: &Symbol,
+        client_order_id: &str,
+    ) -> CoreResult<Option<ExchangeOrder>> {
+        self.rest
+            .order_by_client_id(symbol.as_str(), client_order_id)
+            .await?
+            .map(|order| exchange_order(&order))
+            .transpose()
+    }
+
+    async fn instruments(&self, kind: InstrumentKind) -> CoreResult<Vec<InstrumentInfo>> {
+        self.rest
+            .instruments(kind.as_str())
+            .await?
+            .iter()
+            .map(|spec| instrument_info(kind, spec))
+            .collect()
+    }
+
+    async fn subscribe(&self, subscriptions: &[MarketSubscription]) -> CoreResult<()> {
+        let requests = subscriptions.iter().map(subscription_request).collect();
+        self.websocket()?.subscribe(requests).await?;
+        Ok(())
+    }
+
+    async fn next_event(&self) -> CoreResult<Option<MarketEvent>> {
+        let Some(ws) = &self.ws else {
+            return Ok(None);
+        };
+        loop {
+            let Some(event) = ws.next_message().await? else {
+                return Ok(None);
+            };
+            match market_event(event) {
+                Ok(Some(event)) => return Ok(Some(event)),
+                Ok(None) => {}
+                Err(e) => warn!("Skipped OKX push: {}", e),
+            }
+        }
+    }
+
+    async fn disconnect(&self) -> CoreResult<()> {
+        if let Some(ws) = &self.ws {
+            ws.disconnect().await?;
+        }
+        Ok(())
+    }
+}
+
+/// OKX subscription for a venue-neutral one
+///
+/// Funding rates are published on the perpetual swap, not the spot pair, and
+/// order updates are keyed by instrument type as well as ID.
+pub fn subscription_request(subscription: &MarketSubscription) -> SubscriptionRequest {
+    let symbol = subscription.symbol.as_str();
+    match subscription.stream {
+        MarketStream::Ticker => SubscriptionRequest::new(Channel::Tickers, symbol),
+        MarketStream::Trades => SubscriptionRequest::new(Channel::Trades, symbol),
+        MarketStream::Candles(interval) => {
+            SubscriptionRequest::new(Channel::Candle(interval), symbol)
+        }
+        MarketStream::FundingRate if !symbol.ends_with("-SWAP") => {
+            SubscriptionRequest::new(Channel::FundingRate, format!("{}-SWAP", symbol))
+        }
+        MarketStream::FundingRate => SubscriptionRequest::new(Channel::FundingRate, symbol),
+        MarketStream::Orders => SubscriptionRequest {
+            channel: Channel::Orders,
+            instrument_id: Some(symbol.to_string()),
+            instrument_type: Some("ANY".to_string()),
+        },
+    }
+}
+
+/// Venue-neutral event for an OKX push; `None` for pushes with no
+/// counterpart, such as subscription acks and account snapshots
+pub fn market_event(event: WebSocketEvent) -> CoreResult<Option<MarketEvent>> {
+    let event = match event {
+        WebSocketEvent::Ticker(ticker) => MarketEvent::Ticker(TickerUpdate {
+            symbol: Symbol::new(&ticker.inst_id)?,
+            last: decimal("last price", &ticker.last)?,
+            bid: optional_decimal("bid price", &ticker.bid_px)?,
+            ask: optional_decimal("ask price", &ticker.ask_px)?,
+            timestamp: timestamp(&ticker.ts)?,
+        }),
+        WebSocketEvent::Trade(trade) => MarketEvent::Trade(TradeTick {
+            symbol: Symbol::new(&trade.inst_id)?,
+            price: decimal("trade price", &trade.px)?,
+            quantity: decimal("trade size", &trade.sz)?,
+            side: OrderSide::from_str(&trade.side)?,
+            timestamp: timestamp(&trade.ts)?,
+            trade_id: trade.trade_id,
+        }),
+        WebSocketEvent::Candle(interval, candle) => {
+            let parsed = candle.parse()?;
+            MarketEvent::Candle(CandleUpdate {
+                symbol: None,
+                interval,
+                open_time: DateTime::from_timestamp_millis(parsed.timestamp).ok_or_else(|| {
+                    ea_okx_core::Error::ValidationError("Invalid candle timestamp".to_string())
+                })?,
+                open: parsed.open,
+                high: parsed.high,
+                low: parsed.low,
+                close: parsed.close,
+                volume: parsed.volume,
+                closed: parsed.is_confirmed,
+            })
+        }
+        WebSocketEvent::FundingRate(funding) => {
+            let parsed = funding.parse()?;
+            MarketEvent::FundingRate(FundingUpdate {
+                symbol: Symbol::new(&parsed.inst_id)?,
+                rate: parsed.funding_rate,
+                next_rate: parsed.next_funding_rate,
+                funding_time: DateTime::from_timestamp_millis(parsed.funding_time).ok_or_else(
+                    || ea_okx_core::Error::ValidationError("Invalid funding time".to_string()),
+                )?,
+            })
+        }
+        WebSocketEvent::Order(order) => MarketEvent::Order(Box::new(exchange_order(&order)?)),
+        WebSocketEvent::DataGap(gap) => MarketEvent::Gap {
+            symbol: gap.inst_id.as_deref().and_then(|id| Symbol::new(id).ok()),
+            stream: gap.channel,
+        },
+        WebSocketEvent::Error { code, msg } => {
+            return Err(ea_okx_core::Error::ExchangeError(format!(
+                "OKX WebSocket error {}: {}",
+                code, msg
+            )));
+        }
+        other => {
+            debug!("No venue-neutral event for {:?}", other);
+            return Ok(None);
+        }
+    };
+    Ok(Some(event))
+}
+
+/// Venue-neutral view of an OKX order
+pub fn exchange_order(order: &OrderData) -> CoreResult<ExchangeOrder> {
+    let status = match order.state.as_str() {
+        "live" => OrderStatus::Submitted,
+        "partially_filled" => OrderStatus::Partial,
+        "filled" => OrderStatus::Filled,
+        "canceled" | "mmp_canceled" => OrderStatus::Cancelled,
+        other => return Err(ea_okx_core::Error::InvalidOrderStatus(other.to_string())),
+    };
+    Ok(ExchangeOrder {
+        exchange_order_id: order.ord_id.clone(),
+        client_order_id: order.cl_ord_id.clone(),
+        symbol: Symbol::new(&order.inst_id)?,
+        side: OrderSide::from_str(&order.side)?,
+        order_type: OrderType::from_str(&order.ord_type).unwrap_or(OrderType::Limit),
+        quantity: decimal("order size", &order.sz)?,
+        price: optional_decimal("order price", &order.px)?,
+        filled_quantity: optional_decimal("filled size", &order.acc_fill_sz)?.unwrap_or_default(),
+        avg_fill_price: optional_decimal("average price", &order.avg_px)?.filter(|p| !p.is_zero()),
+        status,
+        updated_at: timestamp(&order.u_time).unwrap_or_else(|_| Utc::now()),
+    })
+}
+
+fn instrument_info(kind: InstrumentKind, spec: &InstrumentSpec) -> CoreResult<InstrumentInfo> {
+    Ok(InstrumentInfo {
+        symbol: Symbol::new(&spec.inst_id)?,
+        kind,
+        tick_size: decimal("tick size", &spec.tick_sz)?,
+        lot_size: decimal("lot size", &spec.lot_sz)?,
+        min_size: decimal("min size", &spec.min_sz)?,
+        tradable: spec.state == "live",
+    })
+}
+
+fn side_str(side: OrderSide) -> &'static str {
+    match side {
+        OrderSide::Buy => "buy",
+        OrderSide::Sell => "sell",
+    }
+}
+
+fn decimal(field: &str, value: &str) -> CoreResult<Decimal> {
+    Decimal::from_str(value).map_err(|e| {
+        ea_okx_core::Error::DecimalError(format!("Invalid {} '{}': {}", field, value, e))
+    })
+}
+
+fn optional_decimal(field: &str, value: &str) -> CoreResult<Option<Decimal>> {
+    match value {
+        "" => Ok(None),
+        value => decimal(field, value).map(Some),
+    }
+}
+
+fn timestamp(millis: &str) -> CoreResult<DateTime<Utc>> {
+    millis
+        .parse()
+        .ok()
+        .and_then(DateTime::from_timestamp_millis)
+        .ok_or_else(|| {
+            ea_okx_core::Error::ValidationError(format!("Invalid timestamp '{}'", millis))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::websocket::{CandleData, TradeData};
+    use ea_okx_core::Interval;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_pushes_translate_to_market_events() {
+        let trade = market_event(WebSocketEvent::Trade(TradeData {
+            inst_id: "BTC-USDT".to_string(),
+            trade_id: "42".to_string(),
+            px: "50000.5".to_string(),
+            sz: "0.01".to_string(),
+            side: "sell".to_string(),
+            ts: "1700000000000".to_string(),
+            count: None,
+        }))
+        .unwrap();
+        let Some(MarketEvent::Trade(trade)) = trade else {
+            panic!("expected a trade, got {:?}", trade);
+        };
+        assert_eq!(trade.price, dec!(50000.5));
+        assert_eq!(trade.side, OrderSide::Sell);
+        assert_eq!(trade.timestamp.timestamp_millis(), 1_700_000_000_000);
+
+        let candle: CandleData = serde_json::from_value(serde_json::json!({
+            "ts": "1700000000000", "o": "1", "h": "3", "l": "0.5", "c": "2",
+            "vol": "10", "volCcy": "20", "confirm": "1"
+        }))
+        .unwrap();
+        let Some(MarketEvent::Candle(candle)) =
+            market_event(WebSocketEvent::Candle(Interval::OneMinute, candle)).unwrap()
+        else {
+            panic!("expected a candle");
+        };
+        assert!(candle.closed);
+        assert_eq!(candle.high, dec!(3));
+
+        assert!(
+            market_event(WebSocketEvent::Login {
+                code: "0".to_string(),
+                msg: String::new()
+            })
+            .unwrap()
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn test_funding_subscriptions_target_the_swap() {
+        let symbol = Symbol::new("BTC-USDT").unwrap();
+        let request = subscription_request(&MarketSubscription::new(
+            symbol.clone(),
+            MarketStream::FundingRate,
+        ));
+        assert_eq!(request.instrument_id.as_deref(), Some("BTC-USDT-SWAP"));
+
+        let request = subscription_request(&MarketSubscription::new(symbol, MarketStream::Orders));
+        assert_eq!(request.channel, Channel::Orders);
+        assert_eq!(request.instrument_type.as_deref(), Some("ANY"));
+    }
+}
@@ -2,7 +2,7 @@
 
 use crate::error::{Error, Result};
 use base64::{engine::general_purpose, Engine as _};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use hmac::{Hmac, Mac};
 use sha2::Sha256;
 
@@ -85,7 +85,12 @@ impl Credentials {
 
     /// Generates current timestamp in ISO 8601 format
     pub fn timestamp() -> String {
-        Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()
+        Self::timestamp_at(Utc::now())
+    }
+
+    /// Formats `at` as a request timestamp
+    pub fn timestamp_at(at: DateTime<Utc>) -> String {
+        at.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()
     }
 }
 
@@ -109,7 +114,18 @@ impl RequestSigner {
         request_path: &str,
         body: &str,
     ) -> Result<(String, String)> {
-        let timestamp = Credentials::timestamp();
+        self.sign_request_at(Utc::now(), method, request_path, body)
+    }
+
+    /// Signs a request stamped with `at` rather than the local clock
+    pub fn sign_request_at(
+        &self,
+        at: DateTime<Utc>,
+        method: &str,
+        request_path: &str,
+        body: &str,
+    ) -> Result<(String, String)> {
+        let timestamp = Credentials::timestamp_at(at);
         let signature = self
             .credentials
             .sign(&timestamp, method, request_path, body)?;
@@ -0,0 +1,134 @@
+//! Clock skew compensation
+//!
+//! OKX rejects signed requests whose `OK-ACCESS-TIMESTAMP` is more than 30
+//! seconds away from its own clock. [`OkxRestClient`](crate::rest::OkxRestClient)
+//! measures the offset to the exchange clock from `GET /api/v5/public/time`,
+//! assuming the server stamped its reply halfway through the round trip,
+//! and signs every request with local time plus the last measured offset.
+//! A [`ClockSync`] handle is shared, so monitoring can watch the drift.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// One measurement of the exchange clock
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClockSample {
+    /// Exchange clock minus local clock; positive when the local clock is behind
+    pub offset_ms: i64,
+    pub round_trip_ms: i64,
+    pub measured_at: DateTime<Utc>,
+}
+
+impl ClockSample {
+    /// Offset from a server timestamp received between `sent` and `received`
+    /// on the local clock
+    pub fn measure(
+        sent: DateTime<Utc>,
+        server_time: DateTime<Utc>,
+        received: DateTime<Utc>,
+    ) -> Self {
+        let round_trip = (received - sent).max(Duration::zero());
+        let midpoint = sent + round_trip / 2;
+        Self {
+            offset_ms: (server_time - midpoint).num_milliseconds(),
+            round_trip_ms: round_trip.num_milliseconds(),
+            measured_at: received,
+        }
+    }
+}
+
+/// Point-in-time view of clock synchronization
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClockSyncMetrics {
+    /// Offset applied to signed requests, if one was ever measured
+    pub last_sample: Option<ClockSample>,
+
+    /// Failed syncs since the last successful one
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+}
+
+impl ClockSyncMetrics {
+    pub fn offset_ms(&self) -> i64 {
+        self.last_sample.map_or(0, |sample| sample.offset_ms)
+    }
+}
+
+/// Shared clock offset handle; cheap to clone
+#[derive(Debug, Clone, Default)]
+pub struct ClockSync {
+    state: Arc<Mutex<ClockSyncMetrics>>,
+}
+
+impl ClockSync {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Current time on the exchange clock, as best known
+    pub async fn now(&self) -> DateTime<Utc> {
+        let offset = self.state.lock().await.offset_ms();
+        Utc::now() + Duration::milliseconds(offset)
+    }
+
+    pub async fn snapshot(&self) -> ClockSyncMetrics {
+        self.state.lock().await.clone()
+    }
+
+    pub(crate) async fn record(&self, sample: ClockSample) {
+        let mut state = self.state.lock().await;
+        state.last_sample = Some(sample);
+        state.consecutive_failures = 0;
+    }
+
+    pub(crate) async fn record_failure(&self, error: impl Into<String>) {
+        let mut state = self.state.lock().await;
+        state.consecutive_failures += 1;
+        state.last_error = Some(error.into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_offset_measured_from_round_trip_midpoint() {
+        let sent = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let received = sent + Duration::milliseconds(200);
+
+        // Server stamped 100ms into the trip with a clock 2.5s ahead
+        let server_time = sent + Duration::milliseconds(2600);
+        let sample = ClockSample::measure(sent, server_time, received);
+        assert_eq!(sample.offset_ms, 2500);
+        assert_eq!(sample.round_trip_ms, 200);
+
+        let behind = ClockSample::measure(sent, sent - Duration::seconds(40), received);
+        assert_eq!(behind.offset_ms, -40_100);
+    }
+
+    #[tokio::test]
+    async fn test_failures_keep_the_last_offset() {
+        let clock = ClockSync::new();
+        assert_eq!(clock.snapshot().await.offset_ms(), 0);
+
+        let sent = Utc::now();
+        clock
+            .record(ClockSample::measure(
+                sent,
+                sent + Duration::seconds(5),
+                sent,
+            ))
+            .await;
+        clock.record_failure("timeout").await;
+        clock.record_failure("timeout").await;
+
+        let metrics = clock.snapshot().await;
+        assert_eq!(metrics.offset_ms(), 5000);
+        assert_eq!(metrics.consecutive_failures, 2);
+        assert!(clock.now().await - Utc::now() > Duration::seconds(4));
+    }
+}
@@ -0,0 +1,317 @@
+//! Fault injection for resilience testing
+//!
+//! Only compiled with the `fault-injection` feature. A [`FaultInjector`]
+//! adds artificial latency, drops or duplicates WebSocket events and turns
+//! requests into exchange rejects according to configurable probabilities,
+//! so order handling and strategies can be exercised against a degraded
+//! exchange. Faults are driven by a seeded generator, making a run
+//! reproducible for a given seed.
+
+use crate::error::Error;
+use crate::models::websocket::WebSocketEvent;
+use crate::websocket::OkxWebSocketClient;
+use std::collections::VecDeque;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::debug;
+
+/// Probabilities and shapes of injected faults
+///
+/// Probabilities are in `[0, 1]`; the default injects nothing.
+#[derive(Debug, Clone)]
+pub struct FaultConfig {
+    /// Chance that a request or event is delayed
+    pub latency_probability: f64,
+    pub min_latency: Duration,
+    pub max_latency: Duration,
+
+    /// Chance that a WebSocket event is silently dropped
+    pub drop_probability: f64,
+
+    /// Chance that an order/fill event is delivered twice
+    pub duplicate_probability: f64,
+
+    /// Chance that a request is rejected by the "exchange"
+    pub reject_probability: f64,
+    /// OKX error code and message carried by injected rejects
+    pub reject_code: String,
+    pub reject_message: String,
+
+    /// Seed for the fault generator
+    pub seed: u64,
+}
+
+impl Default for FaultConfig {
+    fn default() -> Self {
+        Self {
+            latency_probability: 0.0,
+            min_latency: Duration::from_millis(50),
+            max_latency: Duration::from_millis(500),
+            drop_probability: 0.0,
+            duplicate_probability: 0.0,
+            reject_probability: 0.0,
+            reject_code: "50013".to_string(),
+            reject_message: "Systems are busy. Please try again later.".to_string(),
+            seed: 0x5eed_fa17,
+        }
+    }
+}
+
+/// Counts of faults injected so far
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FaultStats {
+    pub delayed: u64,
+    pub dropped: u64,
+    pub duplicated: u64,
+    pub rejected: u64,
+}
+
+/// Decides which faults to inject
+pub struct FaultInjector {
+    config: FaultConfig,
+    state: Mutex<u64>,
+    delayed: AtomicU64,
+    dropped: AtomicU64,
+    duplicated: AtomicU64,
+    rejected: AtomicU64,
+}
+
+impl FaultInjector {
+    pub fn new(config: FaultConfig) -> Self {
+        let state = Mutex::new(config.seed);
+        Self {
+            config,
+            state,
+            delayed: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            duplicated: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+        }
+    }
+
+    pub fn config(&self) -> &FaultConfig {
+        &self.config
+    }
+
+    pub fn stats(&self) -> FaultStats {
+        FaultStats {
+            delayed: self.delayed.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            duplicated: self.duplicated.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Uniform sample in `[0, 1)` (SplitMix64)
+    fn sample(&self) -> f64 {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = *state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn roll(&self, probability: f64) -> bool {
+        probability > 0.0 && self.sample() < probability
+    }
+
+    /// Latency to inject before the next request or event, if any
+    pub fn next_latency(&self) -> Option<Duration> {
+        if !self.roll(self.config.latency_probability) {
+            return None;
+        }
+        self.delayed.fetch_add(1, Ordering::Relaxed);
+        let min = self.config.min_latency;
+        let span = self.config.max_latency.saturating_sub(min);
+        Some(min + span.mul_f64(self.sample()))
+    }
+
+    /// Sleep for an injected latency, if one is rolled
+    pub async fn delay(&self) {
+        if let Some(latency) = self.next_latency() {
+            debug!("Injecting {:?} latency", latency);
+            tokio::time::sleep(latency).await;
+        }
+    }
+
+    /// An exchange reject to return instead of the real response, if one is rolled
+    pub fn reject(&self) -> Option<Error> {
+        if !self.roll(self.config.reject_probability) {
+            return None;
+        }
+        self.rejected.fetch_add(1, Ordering::Relaxed);
+        debug!("Injecting reject {}", self.config.reject_code);
+        Some(Error::ApiError {
+            code: self.config.reject_code.clone(),
+            message: self.config.reject_message.clone(),
+        })
+    }
+
+    /// Events to deliver in place of `event`: none if dropped, two copies
+    /// of an order update if duplicated
+    pub fn apply(&self, event: WebSocketEvent) -> Vec<WebSocketEvent> {
+        if self.roll(self.config.drop_probability) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            debug!("Dropping WebSocket event");
+            return Vec::new();
+        }
+
+        if matches!(event, WebSocketEvent::Order(_)) && self.roll(self.config.duplicate_probability)
+        {
+            self.duplicated.fetch_add(1, Ordering::Relaxed);
+            debug!("Duplicating order event");
+            return vec![event.clone(), event];
+        }
+
+        vec![event]
+    }
+}
+
+/// WebSocket client whose incoming events pass through a [`FaultInjector`]
+///
+/// Derefs to the wrapped client for connecting and subscribing.
+pub struct FaultyWebSocketClient {
+    inner: OkxWebSocketClient,
+    injector: Arc<FaultInjector>,
+    pending: tokio::sync::Mutex<VecDeque<WebSocketEvent>>,
+}
+
+impl FaultyWebSocketClient {
+    pub fn new(inner: OkxWebSocketClient, injector: Arc<FaultInjector>) -> Self {
+        Self {
+            inner,
+            injector,
+            pending: tokio::sync::Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn injector(&self) -> &Arc<FaultInjector> {
+        &self.injector
+    }
+
+    /// Next event after injected latency, drops and duplicates
+    pub async fn next_message(&self) -> crate::Result<Option<WebSocketEvent>> {
+        let mut pending = self.pending.lock().await;
+        loop {
+            if let Some(event) = pending.pop_front() {
+                return Ok(Some(event));
+            }
+
+            let Some(event) = self.inner.next_message().await? else {
+                return Ok(None);
+            };
+            self.injector.delay().await;
+            pending.extend(self.injector.apply(event));
+        }
+    }
+
+    pub fn into_inner(self) -> OkxWebSocketClient {
+        self.inner
+    }
+}
+
+impl Deref for FaultyWebSocketClient {
+    type Target = OkxWebSocketClient;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl DerefMut for FaultyWebSocketClient {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::websocket::TickerData;
+
+    fn ticker() -> WebSocketEvent {
+        let price = "42000".to_string();
+        WebSocketEvent::Ticker(TickerData {
+            inst_type: "SPOT".to_string(),
+            inst_id: "BTC-USDT".to_string(),
+            last: price.clone(),
+            last_sz: "0.1".to_string(),
+            ask_px: price.clone(),
+            ask_sz: "1".to_string(),
+            bid_px: price.clone(),
+            bid_sz: "1".to_string(),
+            open_24h: price.clone(),
+            high_24h: price.clone(),
+            low_24h: price,
+            vol_ccy_24h: "1000000".to_string(),
+            vol_24h: "25".to_string(),
+            ts: "1700000000000".to_string(),
+            sod_utc0: None,
+            sod_utc8: None,
+        })
+    }
+
+    #[test]
+    fn test_default_config_injects_nothing() {
+        let injector = FaultInjector::new(FaultConfig::default());
+        for _ in 0..100 {
+            assert!(injector.next_latency().is_none());
+            assert!(injector.reject().is_none());
+            assert_eq!(injector.apply(ticker()).len(), 1);
+        }
+        assert_eq!(injector.stats(), FaultStats::default());
+    }
+
+    #[test]
+    fn test_faults_follow_probabilities_and_seed() {
+        let config = FaultConfig {
+            reject_probability: 0.25,
+            drop_probability: 0.5,
+            ..Default::default()
+        };
+        let first = FaultInjector::new(config.clone());
+        let second = FaultInjector::new(config);
+
+        let rejects: Vec<bool> = (0..1000).map(|_| first.reject().is_some()).collect();
+        let replayed: Vec<bool> = (0..1000).map(|_| second.reject().is_some()).collect();
+        assert_eq!(rejects, replayed);
+
+        let rejected = first.stats().rejected;
+        assert!((200..300).contains(&rejected), "rejected {}", rejected);
+
+        let delivered: usize = (0..1000).map(|_| first.apply(ticker()).len()).sum();
+        assert!((400..600).contains(&delivered), "delivered {}", delivered);
+        assert_eq!(first.stats().dropped as usize, 1000 - delivered);
+    }
+
+    #[test]
+    fn test_reject_carries_configured_code() {
+        let injector = FaultInjector::new(FaultConfig {
+            reject_probability: 1.0,
+            ..Default::default()
+        });
+        match injector.reject() {
+            Some(Error::ApiError { code, .. }) => assert_eq!(code, "50013"),
+            other => panic!("expected API error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_latency_within_bounds() {
+        let injector = FaultInjector::new(FaultConfig {
+            latency_probability: 1.0,
+            min_latency: Duration::from_millis(10),
+            max_latency: Duration::from_millis(20),
+            ..Default::default()
+        });
+        for _ in 0..100 {
+            let latency = injector.next_latency().unwrap();
+            assert!(latency >= Duration::from_millis(10) && latency <= Duration::from_millis(20));
+        }
+        assert_eq!(injector.stats().delayed, 100);
+    }
+}
@@ -6,9 +6,14 @@
 //! # Features
 //!
 //! - REST API client with automatic authentication
+//! - OKX implementation of the venue-neutral exchange adapter
+//! - Exchange clock sync so request timestamps survive local clock drift
+//! - Funding account transfers, deposit addresses, withdrawals and balances
+//! - Typed response envelope and cursor pagination for history endpoints
 //! - WebSocket client for real-time market data
 //! - Rate limiting and retry logic
 //! - Type-safe request/response models
+//! - Fault injection for resilience testing (`fault-injection` feature)
 //!
 //! # Examples
 //!
@@ -26,13 +31,32 @@
 //! }
 //! ```
 
+pub mod adapter;
 pub mod auth;
+pub mod clock;
 pub mod error;
+#[cfg(feature = "fault-injection")]
+pub mod fault;
 pub mod models;
+pub mod pagination;
+pub mod rejection;
 pub mod rest;
+pub mod sequence;
+pub mod telemetry;
 pub mod websocket;
 
+pub use adapter::OkxAdapter;
 pub use auth::Credentials;
+pub use clock::{ClockSample, ClockSync, ClockSyncMetrics};
 pub use error::{Error, Result};
+#[cfg(feature = "fault-injection")]
+pub use fault::{FaultConfig, FaultInjector, FaultStats, FaultyWebSocketClient};
+pub use models::response::OkxResponse;
+pub use pagination::{PageDirection, Paginator};
+pub use rejection::RejectionReason;
 pub use rest::OkxRestClient;
-pub use websocket::OkxWebSocketClient;
+pub use sequence::{SequenceCheck, SequenceTracker};
+pub use telemetry::{
+    ConnectionMetrics, ConnectionTelemetry, RestMetrics, RestOutcome, RestTelemetry,
+};
+pub use websocket::{OkxWebSocketClient, WsTaskHeartbeats};
@@ -28,6 +28,46 @@ pub struct PlaceOrderRequest {
     /// Client order ID
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cl_ord_id: Option<String>,
+
+    /// Order tag, echoed back on fills
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+
+    /// Only reduce an open position (margin and derivatives)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reduce_only: Option<bool>,
+
+    /// Take-profit and stop-loss the exchange arms once the order fills
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attach_algo_ords: Option<Vec<AttachedAlgoOrder>>,
+}
+
+/// Take-profit/stop-loss pair attached to an order placement
+///
+/// Once the order fills OKX turns the pair into an `oco` algo order whose
+/// client algo order ID is `attach_algo_cl_ord_id`.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachedAlgoOrder {
+    /// Client algo order ID of the resulting algo order
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attach_algo_cl_ord_id: Option<String>,
+
+    /// Take-profit trigger price
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tp_trigger_px: Option<String>,
+
+    /// Take-profit order price; `-1` sends a market order
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tp_ord_px: Option<String>,
+
+    /// Stop-loss trigger price
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sl_trigger_px: Option<String>,
+
+    /// Stop-loss order price; `-1` sends a market order
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sl_ord_px: Option<String>,
 }
 
 /// Cancel order request
@@ -45,3 +85,236 @@ pub struct CancelOrderRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cl_ord_id: Option<String>,
 }
+
+/// Body of `POST /api/v5/trade/amend-order`
+///
+/// The order is named by `ord_id` or `cl_ord_id`; only the `new_*` fields
+/// that are set change.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AmendOrderRequest {
+    /// Instrument ID
+    pub inst_id: String,
+
+    /// Order ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ord_id: Option<String>,
+
+    /// Client order ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cl_ord_id: Option<String>,
+
+    /// New total size, filled quantity included
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_sz: Option<String>,
+
+    /// New limit price
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_px: Option<String>,
+}
+
+/// Body of `POST /api/v5/trade/order-algo`
+///
+/// Only the fields of the algo types used here are modelled: take-profit
+/// `conditional` orders and `move_order_stop` trailing stops.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlgoOrderRequest {
+    /// Instrument ID
+    pub inst_id: String,
+
+    /// Trade mode: cash, cross, isolated
+    pub td_mode: String,
+
+    /// Order side: buy, sell
+    pub side: String,
+
+    /// Algo type: conditional, move_order_stop
+    pub ord_type: String,
+
+    /// Order size
+    pub sz: String,
+
+    /// Only reduce an open position (margin and derivatives)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reduce_only: Option<bool>,
+
+    /// Take-profit trigger price (conditional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tp_trigger_px: Option<String>,
+
+    /// Take-profit order price; `-1` sends a market order (conditional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tp_ord_px: Option<String>,
+
+    /// Callback ratio, `0.05` for 5% (move_order_stop)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub callback_ratio: Option<String>,
+
+    /// Price the trailing stop starts tracking at (move_order_stop)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_px: Option<String>,
+
+    /// Client algo order ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub algo_cl_ord_id: Option<String>,
+}
+
+/// Body of `POST /api/v5/trade/amend-algos`
+///
+/// The algo order is named by `algo_id` or `algo_cl_ord_id`; only the `new_*`
+/// fields that are set change.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AmendAlgoOrderRequest {
+    /// Instrument ID
+    pub inst_id: String,
+
+    /// Algo order ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub algo_id: Option<String>,
+
+    /// Client algo order ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub algo_cl_ord_id: Option<String>,
+
+    /// New order size
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_sz: Option<String>,
+
+    /// New take-profit trigger price
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_tp_trigger_px: Option<String>,
+
+    /// New stop-loss trigger price
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_sl_trigger_px: Option<String>,
+}
+
+/// One entry of the `POST /api/v5/trade/cancel-algos` body
+///
+/// The algo order is named by `algo_id`, or by `algo_cl_ord_id` when the ID
+/// is left empty.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelAlgoOrderRequest {
+    /// Instrument ID
+    pub inst_id: String,
+
+    /// Algo order ID
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub algo_id: String,
+
+    /// Client algo order ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub algo_cl_ord_id: Option<String>,
+}
+
+/// Query for `GET /api/v5/account/max-size` and `GET /api/v5/account/max-avail-size`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaxSizeRequest {
+    /// Instrument ID
+    pub inst_id: String,
+
+    /// Trade mode: cash, cross, isolated
+    pub td_mode: String,
+
+    /// Margin currency (cross/isolated margin only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ccy: Option<String>,
+
+    /// Price used to compute the size (max-size only; defaults to last price)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub px: Option<String>,
+}
+
+/// OKX account a funds transfer moves between
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum AccountType {
+    /// Funding account (deposits and withdrawals)
+    #[serde(rename = "6")]
+    Funding,
+
+    /// Trading account
+    #[serde(rename = "18")]
+    Trading,
+}
+
+/// Body of `POST /api/v5/asset/transfer`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FundsTransferRequest {
+    /// Currency (e.g., "USDT")
+    pub ccy: String,
+
+    /// Amount to transfer
+    pub amt: String,
+
+    /// Source account
+    pub from: AccountType,
+
+    /// Destination account
+    pub to: AccountType,
+
+    /// Client-supplied ID for idempotency and lookup
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+}
+
+/// Query for `GET /api/v5/asset/withdrawal-history`
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WithdrawalHistoryRequest {
+    /// Currency filter
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ccy: Option<String>,
+
+    /// Return records older than this timestamp (ms)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<String>,
+
+    /// Return records newer than this timestamp (ms)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<String>,
+
+    /// Maximum records to return (max 100)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+/// Query for `GET /api/v5/trade/orders-history` (last 7 days)
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrdersHistoryRequest {
+    /// Instrument type: SPOT, MARGIN, SWAP, FUTURES, OPTION
+    pub inst_type: String,
+
+    /// Instrument ID filter
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inst_id: Option<String>,
+
+    /// Order type filter
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ord_type: Option<String>,
+
+    /// State filter: canceled, filled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+}
+
+/// Query for `GET /api/v5/trade/fills-history` (last 3 months)
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FillsHistoryRequest {
+    /// Instrument type: SPOT, MARGIN, SWAP, FUTURES, OPTION
+    pub inst_type: String,
+
+    /// Instrument ID filter
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inst_id: Option<String>,
+
+    /// Order ID filter
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ord_id: Option<String>,
+}
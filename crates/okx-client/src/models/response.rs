@@ -1,10 +1,14 @@
 //! Response models for OKX API
 
-use serde::Deserialize;
+use crate::error::{Error, Result};
+use crate::models::websocket::CandleData;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
-/// Generic API response wrapper
+/// `{code, msg, data}` envelope around every REST response
 #[derive(Debug, Clone, Deserialize)]
-pub struct ApiResponse<T> {
+pub struct OkxResponse<T> {
     /// Response code ("0" for success)
     pub code: String,
 
@@ -12,17 +16,88 @@ pub struct ApiResponse<T> {
     pub msg: String,
 
     /// Response data
-    #[serde(default)]
+    #[serde(default = "Vec::new")]
     pub data: Vec<T>,
 }
 
-impl<T> ApiResponse<T> {
+impl<T> OkxResponse<T> {
     /// Checks if the response is successful
     pub fn is_success(&self) -> bool {
         self.code == "0"
     }
 }
 
+impl<T: DeserializeOwned> OkxResponse<T> {
+    /// Parse a response body into its data, mapping a failure code to an error
+    ///
+    /// The envelope is checked before `data` is typed, since failed requests
+    /// carry per-item `sCode`/`sMsg` entries instead of `T`.
+    pub fn parse(body: &str) -> Result<Vec<T>> {
+        let envelope: OkxResponse<Value> = serde_json::from_str(body)
+            .map_err(|e| Error::InvalidResponse(format!("{} ({})", e, body)))?;
+        if !envelope.is_success() {
+            return Err(envelope.into_error());
+        }
+
+        envelope
+            .data
+            .into_iter()
+            .map(|item| {
+                serde_json::from_value(item)
+                    .map_err(|e| Error::InvalidResponse(format!("{} ({})", e, body)))
+            })
+            .collect()
+    }
+}
+
+impl OkxResponse<Value> {
+    /// Error for a failed response
+    ///
+    /// Batch and order endpoints answer `1` (all failed) or `2` (partially
+    /// failed) with the real reason in the first failing item's `sCode`.
+    /// Rate limiting and gateway timeouts map to their own variants so
+    /// callers can back off; everything else is an [`Error::ApiError`].
+    pub fn into_error(self) -> Error {
+        let item_error = self.data.iter().find_map(|item| {
+            let code = item.get("sCode")?.as_str()?;
+            (!code.is_empty() && code != "0").then(|| {
+                let message = item.get("sMsg").and_then(Value::as_str).unwrap_or_default();
+                (code.to_string(), message.to_string())
+            })
+        });
+        let (code, message) = match item_error {
+            Some(item_error) if self.code == "1" || self.code == "2" => item_error,
+            _ => (self.code, self.msg),
+        };
+
+        match code.as_str() {
+            "50011" | "50061" => Error::RateLimitExceeded(format!("{}: {}", code, message)),
+            "50004" => Error::Timeout(format!("{}: {}", code, message)),
+            _ => Error::ApiError { code, message },
+        }
+    }
+}
+
+/// Instrument from `GET /api/v5/public/instruments`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstrumentSpec {
+    pub inst_type: String,
+    pub inst_id: String,
+
+    /// Price increment
+    pub tick_sz: String,
+
+    /// Size increment
+    pub lot_sz: String,
+
+    /// Minimum order size
+    pub min_sz: String,
+
+    /// live, suspend, preopen, test, or (for expiring contracts) settlement/expired
+    pub state: String,
+}
+
 /// Order response data from REST API
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -34,5 +109,443 @@ pub struct OrderResponse {
     pub cl_ord_id: String,
 
     /// Order state
+    #[serde(default)]
+    pub state: String,
+
+    /// Per-order result code ("0" for success)
+    #[serde(default)]
+    pub s_code: String,
+
+    /// Per-order result message
+    #[serde(default)]
+    pub s_msg: String,
+}
+
+impl OrderResponse {
+    /// Rejection reason when the exchange refused this order
+    pub fn rejection(&self) -> Option<crate::rejection::RejectionReason> {
+        if self.s_code.is_empty() || self.s_code == "0" {
+            None
+        } else {
+            Some(crate::rejection::RejectionReason::from_code(
+                &self.s_code,
+                &self.s_msg,
+            ))
+        }
+    }
+}
+
+/// Result of placing or cancelling an algo order
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlgoOrderData {
+    /// Algo order ID
+    pub algo_id: String,
+
+    /// Client algo order ID
+    #[serde(default)]
+    pub algo_cl_ord_id: String,
+
+    /// Per-order result code ("0" for success)
+    #[serde(default)]
+    pub s_code: String,
+
+    /// Per-order result message
+    #[serde(default)]
+    pub s_msg: String,
+}
+
+/// Maximum order size data from `GET /api/v5/account/max-size`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaxSizeData {
+    /// Instrument ID
+    pub inst_id: String,
+
+    /// Currency the sizes are denominated in
+    #[serde(default)]
+    pub ccy: String,
+
+    /// Maximum buy size
+    pub max_buy: String,
+
+    /// Maximum sell size
+    pub max_sell: String,
+}
+
+/// Available order size data from `GET /api/v5/account/max-avail-size`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaxAvailSizeData {
+    /// Instrument ID
+    pub inst_id: String,
+
+    /// Size available for buying
+    pub avail_buy: String,
+
+    /// Size available for selling
+    pub avail_sell: String,
+}
+
+/// Result of `POST /api/v5/asset/transfer`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferData {
+    /// Transfer ID
+    pub trans_id: String,
+
+    /// Currency
+    pub ccy: String,
+
+    /// Amount transferred
+    pub amt: String,
+
+    /// Source account type
+    pub from: String,
+
+    /// Destination account type
+    pub to: String,
+
+    /// Client-supplied ID
+    #[serde(default)]
+    pub client_id: String,
+}
+
+/// Deposit address from `GET /api/v5/asset/deposit-address`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DepositAddressData {
+    /// Currency
+    pub ccy: String,
+
+    /// Chain name (e.g., "USDT-TRC20")
+    pub chain: String,
+
+    /// Deposit address
+    pub addr: String,
+
+    /// Tag or memo required by some chains
+    #[serde(default)]
+    pub tag: Option<String>,
+    #[serde(default)]
+    pub memo: Option<String>,
+
+    /// Whether this is the default address for the chain
+    #[serde(default)]
+    pub selected: bool,
+}
+
+/// Withdrawal record from `GET /api/v5/asset/withdrawal-history`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WithdrawalRecord {
+    /// Withdrawal ID
+    pub wd_id: String,
+
+    /// Currency
+    pub ccy: String,
+
+    /// Chain name
+    pub chain: String,
+
+    /// Amount withdrawn
+    pub amt: String,
+
+    /// Network fee
+    #[serde(default)]
+    pub fee: String,
+
+    /// Destination address
+    pub to: String,
+
+    /// On-chain transaction hash, once broadcast
+    #[serde(default)]
+    pub tx_id: String,
+
+    /// Withdrawal state code (e.g., "2" for success)
     pub state: String,
+
+    /// Request time (ms)
+    pub ts: String,
+}
+
+/// Funding account balance from `GET /api/v5/asset/balances`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetBalanceData {
+    /// Currency
+    pub ccy: String,
+
+    /// Total balance
+    pub bal: String,
+
+    /// Frozen balance
+    pub frozen_bal: String,
+
+    /// Available balance
+    pub avail_bal: String,
+}
+
+/// Index price from `GET /api/v5/market/index-tickers`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexTickerData {
+    /// Index ID (e.g., "BTC-USDT")
+    pub inst_id: String,
+
+    /// Latest index price
+    pub idx_px: String,
+
+    /// Update time (ms)
+    pub ts: String,
+}
+
+/// Exchange clock from `GET /api/v5/public/time`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerTimeData {
+    /// Server time (ms)
+    pub ts: String,
+}
+
+/// Mark price from `GET /api/v5/public/mark-price`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarkPriceData {
+    /// Instrument type (e.g., "SWAP")
+    pub inst_type: String,
+
+    /// Instrument ID (e.g., "BTC-USDT-SWAP")
+    pub inst_id: String,
+
+    /// Mark price
+    pub mark_px: String,
+
+    /// Update time (ms)
+    pub ts: String,
+}
+
+/// Fill from `GET /api/v5/trade/fills-history`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FillData {
+    pub inst_type: String,
+    pub inst_id: String,
+    pub trade_id: String,
+    pub ord_id: String,
+    #[serde(default)]
+    pub cl_ord_id: String,
+
+    /// Bill ID, the pagination cursor for fills
+    pub bill_id: String,
+    #[serde(default)]
+    pub tag: String,
+    pub fill_px: String,
+    pub fill_sz: String,
+    pub side: String,
+    #[serde(default)]
+    pub pos_side: String,
+
+    /// Liquidity: `M` maker, `T` taker
+    pub exec_type: String,
+    pub fee_ccy: String,
+
+    /// Fee; negative when charged, positive for a rebate
+    pub fee: String,
+
+    /// Fill time (ms)
+    pub ts: String,
+}
+
+/// Candle from `GET /api/v5/market/history-candles`, sent as an array
+/// `[ts, o, h, l, c, vol, volCcy, volCcyQuote, confirm]`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "Vec<String>")]
+pub struct CandleBar(pub CandleData);
+
+impl TryFrom<Vec<String>> for CandleBar {
+    type Error = String;
+
+    fn try_from(fields: Vec<String>) -> std::result::Result<Self, Self::Error> {
+        if fields.len() < 6 {
+            return Err(format!(
+                "Candle has {} fields, expected at least 6",
+                fields.len()
+            ));
+        }
+        let field = |i: usize| fields.get(i).cloned();
+        Ok(Self(CandleData {
+            timestamp: fields[0].clone(),
+            open: fields[1].clone(),
+            high: fields[2].clone(),
+            low: fields[3].clone(),
+            close: fields[4].clone(),
+            volume: fields[5].clone(),
+            volume_currency: field(6).unwrap_or_default(),
+            volume_usd: field(7),
+            // Older responses omit the flag; history is always closed
+            confirm: field(8).unwrap_or_else(|| "1".to_string()),
+        }))
+    }
+}
+
+/// Contract open interest and volume from
+/// `GET /api/v5/rubik/stat/contracts/open-interest-volume`, sent as an
+/// array `[ts, oi, vol]`; both values are in USD
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "Vec<String>")]
+pub struct OpenInterestVolumeData {
+    pub ts: String,
+    pub oi: String,
+    pub vol: String,
+}
+
+impl TryFrom<Vec<String>> for OpenInterestVolumeData {
+    type Error = String;
+
+    fn try_from(fields: Vec<String>) -> std::result::Result<Self, Self::Error> {
+        let [ts, oi, vol] = stat_fields("Open interest", fields)?;
+        Ok(Self { ts, oi, vol })
+    }
+}
+
+/// Taker volume from `GET /api/v5/rubik/stat/taker-volume`, sent as an
+/// array `[ts, sellVol, buyVol]`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "Vec<String>")]
+pub struct TakerVolumeData {
+    pub ts: String,
+    pub sell_vol: String,
+    pub buy_vol: String,
+}
+
+impl TryFrom<Vec<String>> for TakerVolumeData {
+    type Error = String;
+
+    fn try_from(fields: Vec<String>) -> std::result::Result<Self, Self::Error> {
+        let [ts, sell_vol, buy_vol] = stat_fields("Taker volume", fields)?;
+        Ok(Self {
+            ts,
+            sell_vol,
+            buy_vol,
+        })
+    }
+}
+
+/// Ratio of long to short accounts from
+/// `GET /api/v5/rubik/stat/contracts/long-short-account-ratio`, sent as an
+/// array `[ts, ratio]`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "Vec<String>")]
+pub struct LongShortRatioData {
+    pub ts: String,
+    pub ratio: String,
+}
+
+impl TryFrom<Vec<String>> for LongShortRatioData {
+    type Error = String;
+
+    fn try_from(fields: Vec<String>) -> std::result::Result<Self, Self::Error> {
+        let [ts, ratio] = stat_fields("Long/short ratio", fields)?;
+        Ok(Self { ts, ratio })
+    }
+}
+
+/// First `N` fields of a trading statistics row
+fn stat_fields<const N: usize>(
+    kind: &str,
+    fields: Vec<String>,
+) -> std::result::Result<[String; N], String> {
+    let len = fields.len();
+    fields
+        .into_iter()
+        .take(N)
+        .collect::<Vec<_>>()
+        .try_into()
+        .map_err(|_| format!("{} has {} fields, expected {}", kind, len, N))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_envelope_maps_failure_codes() {
+        let data = OkxResponse::<IndexTickerData>::parse(
+            r#"{"code":"0","msg":"","data":[{"instId":"BTC-USDT","idxPx":"43000.1","ts":"1"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(data[0].idx_px, "43000.1");
+
+        let err = OkxResponse::<IndexTickerData>::parse(
+            r#"{"code":"50011","msg":"Too Many Requests","data":[]}"#,
+        )
+        .unwrap_err();
+        assert!(matches!(err, Error::RateLimitExceeded(_)));
+
+        // Item-level codes replace the generic batch failure
+        let err = OkxResponse::<OrderResponse>::parse(
+            r#"{"code":"1","msg":"Operation failed.","data":[
+                {"ordId":"","clOrdId":"a1","tag":"","sCode":"51008","sMsg":"Insufficient balance"}
+            ]}"#,
+        )
+        .unwrap_err();
+        match err {
+            Error::ApiError { code, message } => {
+                assert_eq!(code, "51008");
+                assert_eq!(message, "Insufficient balance");
+            }
+            other => panic!("expected API error, got {:?}", other),
+        }
+
+        assert!(matches!(
+            OkxResponse::<IndexTickerData>::parse("<html>502</html>"),
+            Err(Error::InvalidResponse(_))
+        ));
+    }
+
+    #[test]
+    fn test_candle_bar_from_array() {
+        let bars = OkxResponse::<CandleBar>::parse(
+            r#"{"code":"0","msg":"","data":[
+                ["1700003600000","101","102","100","101.5","12","1218","1218","1"],
+                ["1700000000000","100","101","99","101","8","808"]
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(bars.len(), 2);
+        assert_eq!(bars[0].0.close, "101.5");
+        assert_eq!(bars[1].0.volume_usd, None);
+        assert!(bars[1].0.parse().unwrap().is_confirmed);
+
+        assert!(
+            OkxResponse::<CandleBar>::parse(r#"{"code":"0","msg":"","data":[["1","2"]]}"#).is_err()
+        );
+    }
+
+    #[test]
+    fn test_trading_statistics_from_arrays() {
+        let oi = OkxResponse::<OpenInterestVolumeData>::parse(
+            r#"{"code":"0","msg":"","data":[["1700000000000","2853219950.5","640127.3"]]}"#,
+        )
+        .unwrap();
+        assert_eq!(oi[0].oi, "2853219950.5");
+
+        let taker = OkxResponse::<TakerVolumeData>::parse(
+            r#"{"code":"0","msg":"","data":[["1700000000000","120.5","98.25"]]}"#,
+        )
+        .unwrap();
+        assert_eq!(taker[0].sell_vol, "120.5");
+        assert_eq!(taker[0].buy_vol, "98.25");
+
+        let ratio = OkxResponse::<LongShortRatioData>::parse(
+            r#"{"code":"0","msg":"","data":[["1700000000000","1.42"]]}"#,
+        )
+        .unwrap();
+        assert_eq!(ratio[0].ratio, "1.42");
+
+        assert!(
+            OkxResponse::<TakerVolumeData>::parse(r#"{"code":"0","msg":"","data":[["1","2"]]}"#)
+                .is_err()
+        );
+    }
 }
@@ -4,35 +4,31 @@
 //! including subscription requests, channel types, and event messages.
 
 use crate::error::{Error, Result};
+use ea_okx_core::Interval;
 use rust_decimal::Decimal;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
+use std::str::FromStr;
 
 /// WebSocket channel types
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
+///
+/// Serialized as the OKX channel name, e.g. `tickers` or `candle1H`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Channel {
     /// Ticker channel - real-time ticker updates
     Tickers,
-    /// Candle channel - OHLCV data
-    #[serde(rename = "candle1m")]
-    Candle1m,
-    #[serde(rename = "candle5m")]
-    Candle5m,
-    #[serde(rename = "candle15m")]
-    Candle15m,
-    #[serde(rename = "candle1H")]
-    Candle1h,
-    #[serde(rename = "candle4H")]
-    Candle4h,
-    #[serde(rename = "candle1D")]
-    Candle1d,
+    /// Candle channel - OHLCV data of one interval
+    Candle(Interval),
     /// Order book channels
     Books5, // Top 5 levels
     Books50,    // Top 50 levels
     BooksL2Tbt, // Level 2 tick-by-tick
     /// Recent trades
     Trades,
+    /// Perpetual swap funding rate
+    FundingRate,
+    /// Instrument listings and state changes, per instrument type
+    Instruments,
     /// Account channel (private)
     Account,
     /// Position channel (private)
@@ -56,22 +52,64 @@ impl Channel {
     pub fn as_str(&self) -> &str {
         match self {
             Channel::Tickers => "tickers",
-            Channel::Candle1m => "candle1m",
-            Channel::Candle5m => "candle5m",
-            Channel::Candle15m => "candle15m",
-            Channel::Candle1h => "candle1H",
-            Channel::Candle4h => "candle4H",
-            Channel::Candle1d => "candle1D",
+            Channel::Candle(interval) => interval.candle_channel(),
             Channel::Books5 => "books5",
             Channel::Books50 => "books50",
             Channel::BooksL2Tbt => "books-l2-tbt",
             Channel::Trades => "trades",
+            Channel::FundingRate => "funding-rate",
+            Channel::Instruments => "instruments",
             Channel::Account => "account",
             Channel::Positions => "positions",
             Channel::Orders => "orders",
             Channel::BalanceAndPosition => "balance_and_position",
         }
     }
+
+    /// Interval of a candle channel name such as `candle4H`
+    pub fn candle_interval(channel: &str) -> Option<Interval> {
+        channel
+            .strip_prefix("candle")
+            .and_then(|bar| bar.parse().ok())
+            .filter(Interval::is_exchange_native)
+    }
+}
+
+impl FromStr for Channel {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if let Some(interval) = Self::candle_interval(s) {
+            return Ok(Channel::Candle(interval));
+        }
+        Ok(match s {
+            "tickers" => Channel::Tickers,
+            "books5" => Channel::Books5,
+            "books50" => Channel::Books50,
+            "books-l2-tbt" => Channel::BooksL2Tbt,
+            "trades" => Channel::Trades,
+            "funding-rate" => Channel::FundingRate,
+            "instruments" => Channel::Instruments,
+            "account" => Channel::Account,
+            "positions" => Channel::Positions,
+            "orders" => Channel::Orders,
+            "balance_and_position" => Channel::BalanceAndPosition,
+            other => return Err(Error::ParseError(format!("Unknown channel: {}", other))),
+        })
+    }
+}
+
+impl Serialize for Channel {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Channel {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
 }
 
 /// Subscription request
@@ -79,6 +117,8 @@ impl Channel {
 pub struct SubscriptionRequest {
     pub channel: Channel,
     pub instrument_id: Option<String>,
+    /// Instrument type for channels keyed by type (e.g., "SPOT" for instruments)
+    pub instrument_type: Option<String>,
 }
 
 impl SubscriptionRequest {
@@ -87,6 +127,16 @@ impl SubscriptionRequest {
         Self {
             channel,
             instrument_id: Some(instrument_id.into()),
+            instrument_type: None,
+        }
+    }
+
+    /// Create a subscription request keyed by instrument type (e.g., "SPOT", "SWAP")
+    pub fn new_instrument_type(channel: Channel, instrument_type: impl Into<String>) -> Self {
+        Self {
+            channel,
+            instrument_id: None,
+            instrument_type: Some(instrument_type.into()),
         }
     }
 
@@ -95,6 +145,7 @@ impl SubscriptionRequest {
         Self {
             channel,
             instrument_id: None,
+            instrument_type: None,
         }
     }
 
@@ -105,6 +156,11 @@ impl SubscriptionRequest {
                 "channel": self.channel.as_str(),
                 "instId": inst_id
             })
+        } else if let Some(inst_type) = &self.instrument_type {
+            serde_json::json!({
+                "channel": self.channel.as_str(),
+                "instType": inst_type
+            })
         } else {
             serde_json::json!({
                 "channel": self.channel.as_str()
@@ -141,13 +197,20 @@ pub enum WebSocketEvent {
     },
     /// Market data events
     Ticker(TickerData),
-    Candle(CandleData),
+    Candle(Interval, CandleData),
     OrderBook(OrderBookData),
     Trade(TradeData),
+    FundingRate(FundingRateData),
+    /// Instrument state updates
+    Instruments(Vec<InstrumentData>),
     /// Account events
     Account(AccountData),
-    Position(PositionData),
-    Order(OrderData),
+    Position(Box<PositionData>),
+    Order(Box<OrderData>),
+    BalanceAndPosition(BalanceAndPositionData),
+    /// Order book updates were lost; the book is being resynced and updates
+    /// are withheld until the fresh snapshot arrives
+    DataGap(DataGap),
 }
 
 impl WebSocketEvent {
@@ -213,7 +276,14 @@ impl WebSocketEvent {
                 .get("data")
                 .ok_or_else(|| Error::ParseError("Missing data field".to_string()))?;
 
-            return Self::parse_data_event(channel, data);
+            let mut event = Self::parse_data_event(channel, data)?;
+            // Book items do not name their instrument; the subscription does
+            if let WebSocketEvent::OrderBook(book) = &mut event
+                && book.inst_id.is_none()
+            {
+                book.inst_id = arg.get("instId").and_then(|v| v.as_str()).map(String::from);
+            }
+            return Ok(event);
         }
 
         Err(Error::ParseError(
@@ -221,6 +291,22 @@ impl WebSocketEvent {
         ))
     }
 
+    /// Parse every event in a frame
+    ///
+    /// OKX pushes `data` as an array, which may carry several items; each
+    /// becomes its own event. Instrument pushes stay whole, and a `data`
+    /// object is parsed as a single item.
+    pub fn from_frame(value: &Value) -> Result<Vec<Self>> {
+        let arg = &value["arg"];
+        match &value["data"] {
+            Value::Array(items) if arg["channel"] != "instruments" => items
+                .iter()
+                .map(|item| Self::from_json(&serde_json::json!({ "arg": arg, "data": item })))
+                .collect(),
+            _ => Self::from_json(value).map(|event| vec![event]),
+        }
+    }
+
     /// Parse data event based on channel type
     fn parse_data_event(channel: &str, data: &Value) -> Result<Self> {
         match channel {
@@ -230,9 +316,11 @@ impl WebSocketEvent {
                 Ok(WebSocketEvent::Ticker(ticker))
             }
             ch if ch.starts_with("candle") => {
+                let interval = Channel::candle_interval(ch)
+                    .ok_or_else(|| Error::ParseError(format!("Unknown candle channel: {}", ch)))?;
                 let candle: CandleData = serde_json::from_value(data.clone())
                     .map_err(|e| Error::ParseError(format!("Invalid candle data: {}", e)))?;
-                Ok(WebSocketEvent::Candle(candle))
+                Ok(WebSocketEvent::Candle(interval, candle))
             }
             "books5" | "books50" | "books-l2-tbt" => {
                 let book: OrderBookData = serde_json::from_value(data.clone())
@@ -244,6 +332,19 @@ impl WebSocketEvent {
                     .map_err(|e| Error::ParseError(format!("Invalid trade data: {}", e)))?;
                 Ok(WebSocketEvent::Trade(trade))
             }
+            "funding-rate" => {
+                let funding: FundingRateData = serde_json::from_value(data.clone())
+                    .map_err(|e| Error::ParseError(format!("Invalid funding rate data: {}", e)))?;
+                Ok(WebSocketEvent::FundingRate(funding))
+            }
+            "instruments" => {
+                let instruments: Vec<InstrumentData> = match data {
+                    Value::Array(_) => serde_json::from_value(data.clone()),
+                    _ => serde_json::from_value(data.clone()).map(|one| vec![one]),
+                }
+                .map_err(|e| Error::ParseError(format!("Invalid instrument data: {}", e)))?;
+                Ok(WebSocketEvent::Instruments(instruments))
+            }
             "account" => {
                 let account: AccountData = serde_json::from_value(data.clone())
                     .map_err(|e| Error::ParseError(format!("Invalid account data: {}", e)))?;
@@ -252,12 +353,19 @@ impl WebSocketEvent {
             "positions" => {
                 let position: PositionData = serde_json::from_value(data.clone())
                     .map_err(|e| Error::ParseError(format!("Invalid position data: {}", e)))?;
-                Ok(WebSocketEvent::Position(position))
+                Ok(WebSocketEvent::Position(Box::new(position)))
+            }
+            "balance_and_position" => {
+                let update: BalanceAndPositionData =
+                    serde_json::from_value(data.clone()).map_err(|e| {
+                        Error::ParseError(format!("Invalid balance and position data: {}", e))
+                    })?;
+                Ok(WebSocketEvent::BalanceAndPosition(update))
             }
             "orders" => {
                 let order: OrderData = serde_json::from_value(data.clone())
                     .map_err(|e| Error::ParseError(format!("Invalid order data: {}", e)))?;
-                Ok(WebSocketEvent::Order(order))
+                Ok(WebSocketEvent::Order(Box::new(order)))
             }
             _ => Err(Error::ParseError(format!("Unknown channel: {}", channel))),
         }
@@ -367,6 +475,9 @@ pub struct ParsedCandle {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct OrderBookData {
+    /// Instrument of the book, taken from the push's `arg`
+    #[serde(default)]
+    pub inst_id: Option<String>,
     pub asks: Vec<BookLevel>,
     pub bids: Vec<BookLevel>,
     pub ts: String,
@@ -375,6 +486,20 @@ pub struct OrderBookData {
     pub seq_id: Option<i64>,
 }
 
+/// Break in an order book's `prevSeqId`/`seqId` chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataGap {
+    pub channel: String,
+    pub inst_id: Option<String>,
+    /// `seqId` of the last update applied
+    pub expected_prev_seq_id: i64,
+    /// `prevSeqId` the update actually carried
+    pub prev_seq_id: i64,
+    pub seq_id: i64,
+    pub detected_at: chrono::DateTime<chrono::Utc>,
+}
+
 /// Order book level [price, quantity, deprecated, num_orders]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BookLevel(pub String, pub String, pub String, pub String);
@@ -415,6 +540,79 @@ pub struct TradeData {
     pub count: Option<String>,
 }
 
+/// Instrument definition and trading state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstrumentData {
+    pub inst_type: String,
+    pub inst_id: String,
+    /// live, suspend, preopen, test, or (for expiring contracts) settlement/expired
+    pub state: String,
+    #[serde(default)]
+    pub list_time: Option<String>,
+    #[serde(default)]
+    pub exp_time: Option<String>,
+}
+
+/// Funding rate data (perpetual swaps)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FundingRateData {
+    pub inst_type: String,
+    pub inst_id: String,
+    pub funding_rate: String,
+    pub next_funding_rate: Option<String>,
+    pub funding_time: String,
+    pub next_funding_time: Option<String>,
+    pub ts: Option<String>,
+}
+
+impl FundingRateData {
+    /// Parse into typed values
+    pub fn parse(&self) -> Result<ParsedFundingRate> {
+        let parse_opt = |v: &Option<String>| -> Result<Option<Decimal>> {
+            match v.as_deref() {
+                None | Some("") => Ok(None),
+                Some(s) => s
+                    .parse()
+                    .map(Some)
+                    .map_err(|e| Error::ParseError(format!("Invalid funding rate: {}", e))),
+            }
+        };
+
+        Ok(ParsedFundingRate {
+            inst_id: self.inst_id.clone(),
+            funding_rate: self
+                .funding_rate
+                .parse()
+                .map_err(|e| Error::ParseError(format!("Invalid funding rate: {}", e)))?,
+            next_funding_rate: parse_opt(&self.next_funding_rate)?,
+            funding_time: self
+                .funding_time
+                .parse()
+                .map_err(|e| Error::ParseError(format!("Invalid funding time: {}", e)))?,
+            next_funding_time: match self.next_funding_time.as_deref() {
+                None | Some("") => None,
+                Some(s) => Some(
+                    s.parse()
+                        .map_err(|e| Error::ParseError(format!("Invalid funding time: {}", e)))?,
+                ),
+            },
+        })
+    }
+}
+
+/// Parsed funding rate with typed values
+#[derive(Debug, Clone)]
+pub struct ParsedFundingRate {
+    pub inst_id: String,
+    pub funding_rate: Decimal,
+    pub next_funding_rate: Option<Decimal>,
+    /// Funding settlement time (milliseconds)
+    pub funding_time: i64,
+    pub next_funding_time: Option<i64>,
+}
+
 /// Account data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -458,6 +656,45 @@ pub struct AccountDetail {
     pub notional_lever: Option<String>,
 }
 
+/// Balance and position push, sent on fills, transfers and settlements
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BalanceAndPositionData {
+    pub p_time: String,
+    /// Trigger, e.g. `filled`, `transferred`, `delivered`, `snapshot`
+    pub event_type: String,
+    #[serde(default)]
+    pub bal_data: Vec<BalanceData>,
+    #[serde(default)]
+    pub pos_data: Vec<BalancePositionData>,
+}
+
+/// Cash balance entry of a balance and position push
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BalanceData {
+    pub ccy: String,
+    pub cash_bal: String,
+    pub u_time: String,
+}
+
+/// Position entry of a balance and position push
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BalancePositionData {
+    pub pos_id: String,
+    pub inst_id: String,
+    pub inst_type: String,
+    pub mgn_mode: String,
+    pub pos_side: String,
+    pub pos: String,
+    pub ccy: String,
+    pub pos_ccy: Option<String>,
+    pub avg_px: String,
+    pub trade_id: Option<String>,
+    pub u_time: String,
+}
+
 /// Position data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -530,6 +767,11 @@ pub struct OrderData {
     pub sl_ord_px: Option<String>,
     pub fee_ccy: Option<String>,
     pub fee: Option<String>,
+    /// Fee on the latest fill; negative when charged, positive for a rebate
+    pub fill_fee: Option<String>,
+    pub fill_fee_ccy: Option<String>,
+    /// Liquidity of the latest fill: `M` maker, `T` taker
+    pub exec_type: Option<String>,
     pub rebate_ccy: Option<String>,
     pub rebate: Option<String>,
     pub category: Option<String>,
@@ -541,6 +783,37 @@ pub struct OrderData {
     pub msg: Option<String>,
 }
 
+impl OrderData {
+    /// Fee on the latest fill, if this update carries one
+    pub fn fill_fee(&self) -> Result<Option<FillFee>> {
+        let (Some(fee), Some(currency)) = (
+            self.fill_fee.as_deref().filter(|f| !f.is_empty()),
+            self.fill_fee_ccy.as_deref().filter(|c| !c.is_empty()),
+        ) else {
+            return Ok(None);
+        };
+        let fee: Decimal = fee
+            .parse()
+            .map_err(|e| Error::ParseError(format!("Invalid fill fee: {}", e)))?;
+
+        Ok(Some(FillFee {
+            commission: -fee,
+            currency: currency.to_string(),
+            maker: self.exec_type.as_deref() == Some("M"),
+        }))
+    }
+}
+
+/// Fee charged on one fill
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FillFee {
+    /// Fee paid; negative for a maker rebate
+    pub commission: Decimal,
+    pub currency: String,
+    /// Whether the fill added liquidity
+    pub maker: bool,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -548,7 +821,7 @@ mod tests {
     #[test]
     fn test_channel_is_public() {
         assert!(Channel::Tickers.is_public());
-        assert!(Channel::Candle1m.is_public());
+        assert!(Channel::Candle(Interval::OneMinute).is_public());
         assert!(Channel::Books5.is_public());
         assert!(Channel::Trades.is_public());
 
@@ -566,6 +839,26 @@ mod tests {
         assert_eq!(json["instId"], "BTC-USDT");
     }
 
+    #[test]
+    fn test_candle_channels_follow_the_interval() {
+        let channel = Channel::Candle(Interval::FourHours);
+        assert_eq!(channel.as_str(), "candle4H");
+        assert_eq!("candle4H".parse::<Channel>().unwrap(), channel);
+        assert_eq!(
+            "candle1Mutc".parse::<Channel>().unwrap(),
+            Channel::Candle(Interval::OneMonthUtc)
+        );
+        assert!("candle5s".parse::<Channel>().is_err());
+        assert_eq!(
+            serde_json::to_value(Channel::Candle(Interval::OneSecond)).unwrap(),
+            "candle1s"
+        );
+        assert_eq!(
+            serde_json::from_str::<Channel>("\"funding-rate\"").unwrap(),
+            Channel::FundingRate
+        );
+    }
+
     #[test]
     fn test_subscription_request_account() {
         let req = SubscriptionRequest::new_account(Channel::Account);
@@ -592,7 +885,128 @@ mod tests {
         let parsed = candle.parse().unwrap();
         assert_eq!(parsed.timestamp, 1234567890000);
         assert_eq!(parsed.open, Decimal::new(5000000, 2));
-        assert_eq!(parsed.is_confirmed, true);
+        assert!(parsed.is_confirmed);
+    }
+
+    #[test]
+    fn test_frame_items_become_events() {
+        let json = serde_json::json!({
+            "arg": {"channel": "candle1m", "instId": "BTC-USDT"},
+            "data": [
+                ["1700000000000", "50000", "50100", "49900", "50050", "2", "100000", "100000", "1"],
+                ["1700000060000", "50050", "50200", "50000", "50150", "3", "150000", "150000", "0"]
+            ]
+        });
+
+        let events = WebSocketEvent::from_frame(&json).unwrap();
+        assert_eq!(events.len(), 2);
+        let WebSocketEvent::Candle(_, candle) = &events[1] else {
+            panic!("Expected Candle event");
+        };
+        assert_eq!(candle.close, "50150");
+        assert!(!candle.parse().unwrap().is_confirmed);
+
+        // Acks carry no data and instrument pushes stay whole
+        let ack = serde_json::json!({"event": "subscribe", "arg": json["arg"]});
+        assert_eq!(WebSocketEvent::from_frame(&ack).unwrap().len(), 1);
+        let instruments = serde_json::json!({
+            "arg": {"channel": "instruments", "instType": "SPOT"},
+            "data": [{"instType": "SPOT", "instId": "BTC-USDT", "state": "live"}]
+        });
+        assert_eq!(WebSocketEvent::from_frame(&instruments).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_parse_funding_rate_event() {
+        let json = serde_json::json!({
+            "arg": {"channel": "funding-rate", "instId": "BTC-USDT-SWAP"},
+            "data": {
+                "instType": "SWAP",
+                "instId": "BTC-USDT-SWAP",
+                "fundingRate": "0.0001",
+                "nextFundingRate": "",
+                "fundingTime": "1700000000000",
+                "nextFundingTime": "1700028800000"
+            }
+        });
+
+        let event = WebSocketEvent::from_json(&json).unwrap();
+        let WebSocketEvent::FundingRate(data) = event else {
+            panic!("Expected FundingRate event");
+        };
+        let parsed = data.parse().unwrap();
+        assert_eq!(parsed.funding_rate, Decimal::new(1, 4));
+        assert_eq!(parsed.next_funding_rate, None);
+        assert_eq!(parsed.next_funding_time, Some(1700028800000));
+        assert!(Channel::FundingRate.is_public());
+    }
+
+    #[test]
+    fn test_parse_instruments_event() {
+        let json = serde_json::json!({
+            "arg": {"channel": "instruments", "instType": "SPOT"},
+            "data": [
+                {"instType": "SPOT", "instId": "BTC-USDT", "state": "live", "listTime": "1606468572000", "expTime": ""},
+                {"instType": "SPOT", "instId": "XYZ-USDT", "state": "suspend"}
+            ]
+        });
+
+        let event = WebSocketEvent::from_json(&json).unwrap();
+        let WebSocketEvent::Instruments(instruments) = event else {
+            panic!("Expected Instruments event");
+        };
+        assert_eq!(instruments.len(), 2);
+        assert_eq!(instruments[1].inst_id, "XYZ-USDT");
+        assert_eq!(instruments[1].state, "suspend");
+
+        let req = SubscriptionRequest::new_instrument_type(Channel::Instruments, "SPOT");
+        let json = req.to_json();
+        assert_eq!(json["channel"], "instruments");
+        assert_eq!(json["instType"], "SPOT");
+        assert!(json.get("instId").is_none());
+    }
+
+    #[test]
+    fn test_parse_balance_and_position_event() {
+        let json = serde_json::json!({
+            "arg": {"channel": "balance_and_position"},
+            "data": {
+                "pTime": "1700000000000",
+                "eventType": "filled",
+                "balData": [
+                    {"ccy": "USDT", "cashBal": "1000.5", "uTime": "1700000000000"}
+                ],
+                "posData": []
+            }
+        });
+
+        let event = WebSocketEvent::from_json(&json).unwrap();
+        let WebSocketEvent::BalanceAndPosition(data) = event else {
+            panic!("Expected BalanceAndPosition event");
+        };
+        assert_eq!(data.event_type, "filled");
+        assert_eq!(data.bal_data[0].ccy, "USDT");
+        assert_eq!(data.bal_data[0].cash_bal, "1000.5");
+        assert!(!Channel::BalanceAndPosition.is_public());
+    }
+
+    #[test]
+    fn test_order_book_names_its_instrument() {
+        let frame = serde_json::json!({
+            "arg": { "channel": "books5", "instId": "BTC-USDT" },
+            "data": [{
+                "asks": [["50001", "2", "0", "3"]],
+                "bids": [["50000", "1", "0", "2"]],
+                "ts": "1700000000000",
+            }],
+        });
+
+        let events = WebSocketEvent::from_frame(&frame).unwrap();
+        let WebSocketEvent::OrderBook(book) = &events[0] else {
+            panic!("Expected OrderBook event");
+        };
+        assert_eq!(book.inst_id.as_deref(), Some("BTC-USDT"));
+        assert_eq!(book.bids[0].price().unwrap(), Decimal::new(50000, 0));
     }
 
     #[test]
@@ -627,6 +1041,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_order_fill_fee_sign() {
+        let mut order: OrderData = serde_json::from_value(serde_json::json!({
+            "instType": "SPOT", "instId": "BTC-USDT", "ordId": "1", "clOrdId": "c1",
+            "px": "40000", "sz": "0.1", "ordType": "post_only", "side": "buy",
+            "tdMode": "cash", "fillPx": "40000", "fillSz": "0.1", "accFillSz": "0.1",
+            "avgPx": "40000", "state": "filled", "uTime": "0", "cTime": "0",
+            "fillFee": "0.000002", "fillFeeCcy": "BTC", "execType": "M"
+        }))
+        .unwrap();
+
+        let fee = order.fill_fee().unwrap().unwrap();
+        assert_eq!(fee.commission, Decimal::new(-2, 6));
+        assert_eq!(fee.currency, "BTC");
+        assert!(fee.maker);
+
+        order.fill_fee = Some("-4".to_string());
+        order.exec_type = Some("T".to_string());
+        let fee = order.fill_fee().unwrap().unwrap();
+        assert_eq!(fee.commission, Decimal::new(4, 0));
+        assert!(!fee.maker);
+
+        order.fill_fee_ccy = None;
+        assert!(order.fill_fee().unwrap().is_none());
+    }
+
     #[test]
     fn test_websocket_event_login() {
         let json = serde_json::json!({
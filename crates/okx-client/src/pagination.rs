@@ -0,0 +1,273 @@
+//! Cursor pagination for REST history endpoints
+//!
+//! OKX history endpoints return at most `limit` records per request, newest
+//! first, and page with a cursor taken from the records themselves: `after`
+//! asks for records older than the cursor and `before` for newer ones. The
+//! cursor field differs per endpoint (`ordId` for orders, `billId` for fills,
+//! `ts` for candles), so a [`Paginator`] is built with a function extracting
+//! it from a record.
+
+use crate::error::{Error, Result};
+use crate::rest::OkxRestClient;
+use futures::{Stream, TryStreamExt, stream};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+/// Which way to walk from the cursor
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PageDirection {
+    /// Older records via `after`, starting from the newest
+    #[default]
+    Older,
+    /// Newer records via `before`; needs a starting cursor
+    Newer,
+}
+
+/// Walks a cursor-paginated endpoint one page at a time
+pub struct Paginator<'a, T> {
+    client: &'a OkxRestClient,
+    path: String,
+    query: Vec<(String, String)>,
+    cursor_of: fn(&T) -> String,
+    direction: PageDirection,
+    limit: Option<u32>,
+    cursor: Option<String>,
+    done: bool,
+}
+
+impl<'a, T: DeserializeOwned> Paginator<'a, T> {
+    /// Paginate `path` with filters `query`, reading cursors with `cursor_of`
+    pub fn new(
+        client: &'a OkxRestClient,
+        path: impl Into<String>,
+        query: &impl Serialize,
+        cursor_of: fn(&T) -> String,
+    ) -> Result<Self> {
+        Ok(Self {
+            client,
+            path: path.into(),
+            query: crate::rest::query_pairs(query)?,
+            cursor_of,
+            direction: PageDirection::Older,
+            limit: None,
+            cursor: None,
+            done: false,
+        })
+    }
+
+    /// Records per request; the endpoint default when unset
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Start from `cursor` instead of the newest record
+    pub fn starting_at(mut self, cursor: impl Into<String>) -> Self {
+        self.cursor = Some(cursor.into());
+        self
+    }
+
+    pub fn with_direction(mut self, direction: PageDirection) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Cursor the next request will use
+    pub fn cursor(&self) -> Option<&str> {
+        self.cursor.as_deref()
+    }
+
+    /// Fetch the next page; `None` once the history is exhausted
+    ///
+    /// A page shorter than the limit ends the walk without another request.
+    pub async fn next_page(&mut self) -> Result<Option<Vec<T>>> {
+        if self.done {
+            return Ok(None);
+        }
+
+        let mut query = self.query.clone();
+        if let Some(limit) = self.limit {
+            query.push(("limit".to_string(), limit.to_string()));
+        }
+        match (&self.cursor, self.direction) {
+            (Some(cursor), PageDirection::Older) => {
+                query.push(("after".to_string(), cursor.clone()))
+            }
+            (Some(cursor), PageDirection::Newer) => {
+                query.push(("before".to_string(), cursor.clone()))
+            }
+            (None, PageDirection::Older) => {}
+            (None, PageDirection::Newer) => {
+                return Err(Error::Internal(
+                    "Paging towards newer records needs a starting cursor".to_string(),
+                ));
+            }
+        }
+
+        let page: Vec<T> = self.client.get(&self.path, &query).await?;
+        if page.is_empty() {
+            self.done = true;
+            return Ok(None);
+        }
+
+        // Pages are newest first: older continues from the last record,
+        // newer from the first
+        let edge = match self.direction {
+            PageDirection::Older => page.last(),
+            PageDirection::Newer => page.first(),
+        };
+        let next = edge.map(self.cursor_of);
+        if next == self.cursor || self.limit.is_some_and(|limit| page.len() < limit as usize) {
+            self.done = true;
+        }
+        self.cursor = next;
+        Ok(Some(page))
+    }
+
+    /// Every remaining record as a stream, fetching pages on demand
+    pub fn into_stream(self) -> impl Stream<Item = Result<T>> + 'a
+    where
+        T: 'a,
+    {
+        stream::try_unfold(self, |mut pages| async move {
+            let page = pages.next_page().await?;
+            Ok::<_, Error>(page.map(|page| (stream::iter(page.into_iter().map(Ok)), pages)))
+        })
+        .try_flatten()
+    }
+
+    /// Collect every remaining record
+    pub async fn collect_all(self) -> Result<Vec<T>>
+    where
+        T: 'a,
+    {
+        self.into_stream().try_collect().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::Credentials;
+    use crate::models::request::FillsHistoryRequest;
+    use wiremock::matchers::{method, path, query_param, query_param_is_missing};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn fill(bill_id: u32) -> serde_json::Value {
+        serde_json::json!({
+            "instType": "SPOT", "instId": "BTC-USDT", "tradeId": bill_id.to_string(),
+            "ordId": "1", "clOrdId": "", "billId": bill_id.to_string(), "tag": "",
+            "fillPx": "43000", "fillSz": "0.01", "side": "buy", "posSide": "net",
+            "execType": "T", "feeCcy": "BTC", "fee": "-0.00001", "ts": "1700000000000"
+        })
+    }
+
+    fn page(bill_ids: &[u32]) -> ResponseTemplate {
+        let data: Vec<_> = bill_ids.iter().copied().map(fill).collect();
+        ResponseTemplate::new(200)
+            .set_body_json(serde_json::json!({ "code": "0", "msg": "", "data": data }))
+    }
+
+    async fn client(server: &MockServer) -> OkxRestClient {
+        OkxRestClient::new(Credentials::new("key", "secret", "pass"), false)
+            .unwrap()
+            .with_base_url(&server.uri())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_walks_older_pages_until_short_page() {
+        let server = MockServer::start().await;
+        let fills = "/api/v5/trade/fills-history";
+        Mock::given(method("GET"))
+            .and(path(fills))
+            .and(query_param("instType", "SPOT"))
+            .and(query_param_is_missing("after"))
+            .respond_with(page(&[9, 8]))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(fills))
+            .and(query_param("after", "8"))
+            .respond_with(page(&[7, 6]))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(fills))
+            .and(query_param("after", "6"))
+            .respond_with(page(&[5]))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = client(&server).await;
+        let fills = client
+            .fills_history(&FillsHistoryRequest {
+                inst_type: "SPOT".to_string(),
+                ..Default::default()
+            })
+            .unwrap()
+            .with_limit(2)
+            .collect_all()
+            .await
+            .unwrap();
+
+        let ids: Vec<_> = fills.iter().map(|f| f.bill_id.as_str()).collect();
+        assert_eq!(ids, ["9", "8", "7", "6", "5"]);
+    }
+
+    #[tokio::test]
+    async fn test_newer_pages_and_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v5/trade/fills-history"))
+            .and(query_param("before", "5"))
+            .respond_with(page(&[7, 6]))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v5/trade/fills-history"))
+            .and(query_param("before", "7"))
+            .respond_with(page(&[]))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v5/trade/orders-history"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": "50011", "msg": "Too Many Requests", "data": []
+            })))
+            .mount(&server)
+            .await;
+
+        let client = client(&server).await;
+        let request = FillsHistoryRequest {
+            inst_type: "SPOT".to_string(),
+            ..Default::default()
+        };
+
+        let mut newer = client
+            .fills_history(&request)
+            .unwrap()
+            .with_direction(PageDirection::Newer)
+            .starting_at("5");
+        assert_eq!(newer.next_page().await.unwrap().unwrap().len(), 2);
+        assert_eq!(newer.cursor(), Some("7"));
+        assert!(newer.next_page().await.unwrap().is_none());
+        assert!(newer.next_page().await.unwrap().is_none());
+
+        let mut unanchored = client
+            .fills_history(&request)
+            .unwrap()
+            .with_direction(PageDirection::Newer);
+        assert!(unanchored.next_page().await.is_err());
+
+        let orders = client
+            .orders_history(&Default::default())
+            .unwrap()
+            .collect_all()
+            .await;
+        assert!(matches!(orders, Err(Error::RateLimitExceeded(_))));
+    }
+}
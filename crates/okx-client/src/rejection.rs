@@ -0,0 +1,230 @@
+//! Order rejection taxonomy
+//!
+//! OKX reports order failures as numeric codes (top-level `code` or per-order
+//! `sCode`). This module maps them onto a typed [`RejectionReason`] so callers
+//! can react without matching on raw strings.
+
+use crate::error::Error;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Why the exchange refused an order
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RejectionReason {
+    /// Not enough balance or margin to cover the order
+    InsufficientBalance,
+
+    /// Size is not a multiple of the instrument lot size
+    SizePrecision,
+
+    /// Size is below the instrument minimum
+    SizeTooSmall,
+
+    /// Size exceeds the per-order maximum
+    SizeTooLarge,
+
+    /// Order would exceed the position limit for the account tier
+    PositionLimit,
+
+    /// Price is outside the exchange's current price band
+    PriceOutOfLimits {
+        max_buy: Option<Decimal>,
+        min_sell: Option<Decimal>,
+    },
+
+    /// Request rate limit hit
+    RateLimited,
+
+    /// Instrument does not exist, is suspended, expired or settling
+    InstrumentUnavailable,
+
+    /// Reduce-only order with no position to reduce
+    ReduceOnlyNoPosition,
+
+    /// Client order ID already used
+    DuplicateClientOrderId,
+
+    /// Account is frozen or restricted from trading
+    AccountRestricted,
+
+    /// Malformed or invalid request parameter
+    InvalidParameter,
+
+    /// Exchange-side outage, timeout or overload
+    ExchangeUnavailable,
+
+    /// Unmapped error code
+    Unknown { code: String },
+}
+
+impl RejectionReason {
+    /// Classify an OKX error code and its accompanying message
+    pub fn from_code(code: &str, message: &str) -> Self {
+        match code {
+            "51008" | "51119" | "51127" | "51131" => Self::InsufficientBalance,
+            "51121" => Self::SizePrecision,
+            "51020" => Self::SizeTooSmall,
+            "51201" | "51202" => Self::SizeTooLarge,
+            "51004" => Self::PositionLimit,
+            "51006" => {
+                let (max_buy, min_sell) = parse_price_limits(message);
+                Self::PriceOutOfLimits { max_buy, min_sell }
+            }
+            "50011" | "50061" => Self::RateLimited,
+            "51001" | "51027" | "51028" | "51029" => Self::InstrumentUnavailable,
+            "51169" => Self::ReduceOnlyNoPosition,
+            "51016" => Self::DuplicateClientOrderId,
+            "51009" | "51024" => Self::AccountRestricted,
+            "50014" | "51000" => Self::InvalidParameter,
+            "50001" | "50004" | "50013" | "50026" => Self::ExchangeUnavailable,
+            _ => Self::Unknown {
+                code: code.to_string(),
+            },
+        }
+    }
+
+    /// Classify a client error
+    pub fn from_error(error: &Error) -> Self {
+        match error {
+            Error::ApiError { code, message } => Self::from_code(code, message),
+            Error::RateLimitExceeded(_) => Self::RateLimited,
+            Error::Timeout(_) | Error::ConnectionError(_) | Error::HttpError(_) => {
+                Self::ExchangeUnavailable
+            }
+            _ => Self::Unknown {
+                code: String::new(),
+            },
+        }
+    }
+
+    /// Classify an error returned through the exchange adapter, or `None`
+    /// when the exchange did not refuse the request
+    pub fn from_exchange_error(error: &ea_okx_core::Error) -> Option<Self> {
+        match error {
+            ea_okx_core::Error::ExchangeRejected { code, message } => {
+                Some(Self::from_code(code, message))
+            }
+            ea_okx_core::Error::RateLimited(_) => Some(Self::RateLimited),
+            ea_okx_core::Error::ExchangeUnavailable(_) => Some(Self::ExchangeUnavailable),
+            _ => None,
+        }
+    }
+
+    /// Whether resubmitting the identical order may succeed later
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::RateLimited | Self::ExchangeUnavailable)
+    }
+
+    /// Stable snake_case identifier, matching the serialized `type` tag
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::InsufficientBalance => "insufficient_balance",
+            Self::SizePrecision => "size_precision",
+            Self::SizeTooSmall => "size_too_small",
+            Self::SizeTooLarge => "size_too_large",
+            Self::PositionLimit => "position_limit",
+            Self::PriceOutOfLimits { .. } => "price_out_of_limits",
+            Self::RateLimited => "rate_limited",
+            Self::InstrumentUnavailable => "instrument_unavailable",
+            Self::ReduceOnlyNoPosition => "reduce_only_no_position",
+            Self::DuplicateClientOrderId => "duplicate_client_order_id",
+            Self::AccountRestricted => "account_restricted",
+            Self::InvalidParameter => "invalid_parameter",
+            Self::ExchangeUnavailable => "exchange_unavailable",
+            Self::Unknown { .. } => "unknown",
+        }
+    }
+}
+
+/// Extract the price band from a 51006 message, e.g.
+/// "Order price is not within the price limit (max buy price: 43,210.5 min sell price: 41,000)"
+fn parse_price_limits(message: &str) -> (Option<Decimal>, Option<Decimal>) {
+    let extract = |label: &str| {
+        let lower = message.to_lowercase();
+        let start = lower.find(label)? + label.len();
+        let number: String = message
+            .get(start..)?
+            .trim_start_matches([':', ' '])
+            .chars()
+            .take_while(|c| c.is_ascii_digit() || *c == '.' || *c == ',')
+            .filter(|c| *c != ',')
+            .collect();
+        Decimal::from_str(number.trim_end_matches('.')).ok()
+    };
+
+    (extract("max buy price"), extract("min sell price"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_from_code() {
+        assert_eq!(
+            RejectionReason::from_code("51008", "Insufficient balance"),
+            RejectionReason::InsufficientBalance
+        );
+        assert_eq!(
+            RejectionReason::from_code("51121", ""),
+            RejectionReason::SizePrecision
+        );
+        assert_eq!(
+            RejectionReason::from_code("50011", "Too many requests"),
+            RejectionReason::RateLimited
+        );
+        assert_eq!(
+            RejectionReason::from_code("59999", "?"),
+            RejectionReason::Unknown {
+                code: "59999".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_price_limits_parsed_from_message() {
+        let reason = RejectionReason::from_code(
+            "51006",
+            "Order price is not within the price limit (max buy price: 43,210.5 min sell price: 41,000)",
+        );
+        assert_eq!(
+            reason,
+            RejectionReason::PriceOutOfLimits {
+                max_buy: Some(dec!(43210.5)),
+                min_sell: Some(dec!(41000)),
+            }
+        );
+    }
+
+    #[test]
+    fn test_from_error() {
+        let err = Error::ApiError {
+            code: "51020".to_string(),
+            message: "Order amount should be greater than the min available amount".to_string(),
+        };
+        assert_eq!(
+            RejectionReason::from_error(&err),
+            RejectionReason::SizeTooSmall
+        );
+        assert!(RejectionReason::from_error(&Error::Timeout("5s".to_string())).is_transient());
+        assert!(!RejectionReason::InsufficientBalance.is_transient());
+
+        // The reason survives the adapter's error conversion
+        assert_eq!(
+            RejectionReason::from_exchange_error(&err.into()),
+            Some(RejectionReason::SizeTooSmall)
+        );
+        let parse: ea_okx_core::Error = Error::ParseError("bad json".to_string()).into();
+        assert_eq!(RejectionReason::from_exchange_error(&parse), None);
+    }
+
+    #[test]
+    fn test_serialized_tag_matches_as_str() {
+        let reason = RejectionReason::PositionLimit;
+        let json = serde_json::to_value(&reason).unwrap();
+        assert_eq!(json["type"], reason.as_str());
+    }
+}
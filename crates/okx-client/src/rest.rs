@@ -1,9 +1,1030 @@
 //! REST API client implementation
+//!
+//! Requests are signed with the account credentials; demo-trading clients
+//! add the `x-simulated-trading` header. Funding-account operations
+//! (transfers, deposit addresses, withdrawal history and asset balances),
+//! trading account balances and order size limits, index/mark prices, trading statistics (open interest, taker volume
+//! and long/short ratio), order placement and algo orders are exposed as typed methods. Every response is
+//! unwrapped through [`OkxResponse`]; order history, fills and candle
+//! history are walked with a [`Paginator`]. Latency and failures of every
+//! request are recorded in the client's [`RestTelemetry`]. Requests are
+//! stamped on the exchange clock as last measured by [`OkxRestClient::sync_clock`]
+//! (see [`ClockSync`]).
 
-pub struct OkxRestClient;
+use crate::auth::{Credentials, RequestSigner};
+use crate::clock::{ClockSample, ClockSync};
+use crate::error::{Error, Result};
+use crate::models::request::{
+    AlgoOrderRequest, AmendAlgoOrderRequest, AmendOrderRequest, CancelAlgoOrderRequest,
+    CancelOrderRequest, FillsHistoryRequest, FundsTransferRequest, MaxSizeRequest,
+    OrdersHistoryRequest, PlaceOrderRequest, WithdrawalHistoryRequest,
+};
+use crate::models::response::{
+    AlgoOrderData, AssetBalanceData, CandleBar, DepositAddressData, FillData, IndexTickerData,
+    InstrumentSpec, LongShortRatioData, MarkPriceData, MaxAvailSizeData, MaxSizeData, OkxResponse,
+    OpenInterestVolumeData, OrderResponse, ServerTimeData, TakerVolumeData, TransferData,
+    WithdrawalRecord,
+};
+use crate::models::websocket::{AccountData, OrderData};
+use crate::pagination::Paginator;
+use crate::telemetry::{RestOutcome, RestTelemetry};
+use chrono::{DateTime, Utc};
+use ea_okx_core::Interval;
+use reqwest::{Method, StatusCode};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use url::Url;
+
+/// Production REST endpoint; demo trading uses the same host
+const OKX_REST_URL: &str = "https://www.okx.com";
+
+/// Request timeout
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub struct OkxRestClient {
+    http: reqwest::Client,
+    signer: RequestSigner,
+    base_url: Url,
+    is_testnet: bool,
+    telemetry: RestTelemetry,
+    clock: ClockSync,
+}
 
 impl OkxRestClient {
-    pub fn new(_credentials: crate::Credentials, _testnet: bool) -> crate::Result<Self> {
-        Ok(Self)
+    pub fn new(credentials: Credentials, testnet: bool) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()?;
+        Ok(Self {
+            http,
+            signer: RequestSigner::new(credentials),
+            base_url: Url::parse(OKX_REST_URL)?,
+            is_testnet: testnet,
+            telemetry: RestTelemetry::new(),
+            clock: ClockSync::new(),
+        })
+    }
+
+    /// Send requests to another host (e.g. a mock server)
+    pub fn with_base_url(mut self, base_url: &str) -> Result<Self> {
+        self.base_url = Url::parse(base_url)?;
+        Ok(self)
+    }
+
+    /// Record request telemetry into a shared handle
+    pub fn with_telemetry(mut self, telemetry: RestTelemetry) -> Self {
+        self.telemetry = telemetry;
+        self
+    }
+
+    /// Keep the exchange clock offset in a shared handle
+    pub fn with_clock(mut self, clock: ClockSync) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Whether requests go to demo trading
+    pub fn is_testnet(&self) -> bool {
+        self.is_testnet
+    }
+
+    /// Shared handle to this client's request telemetry
+    pub fn telemetry(&self) -> RestTelemetry {
+        self.telemetry.clone()
+    }
+
+    /// Shared handle to the exchange clock offset used for signing
+    pub fn clock(&self) -> ClockSync {
+        self.clock.clone()
+    }
+
+    /// Current time on the exchange clock
+    pub async fn server_time(&self) -> Result<DateTime<Utc>> {
+        let data = self
+            .get::<ServerTimeData>("/api/v5/public/time", &())
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::InvalidResponse("No server time".to_string()))?;
+        data.ts
+            .parse::<i64>()
+            .ok()
+            .and_then(DateTime::from_timestamp_millis)
+            .ok_or_else(|| Error::ParseError(format!("Invalid server time: {}", data.ts)))
+    }
+
+    /// Measure the offset to the exchange clock and sign with it from now on
+    ///
+    /// A failed measurement keeps the previous offset.
+    pub async fn sync_clock(&self) -> Result<ClockSample> {
+        let sent = Utc::now();
+        match self.server_time().await {
+            Ok(server_time) => {
+                let sample = ClockSample::measure(sent, server_time, Utc::now());
+                self.clock.record(sample).await;
+                Ok(sample)
+            }
+            Err(e) => {
+                self.clock.record_failure(e.to_string()).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Sync the clock now and every `interval` until the task is aborted
+    pub fn spawn_clock_sync(self: Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match self.sync_clock().await {
+                    Ok(sample) => tracing::debug!(
+                        offset_ms = sample.offset_ms,
+                        round_trip_ms = sample.round_trip_ms,
+                        "Synced OKX clock"
+                    ),
+                    Err(e) => tracing::warn!("OKX clock sync failed: {}", e),
+                }
+            }
+        })
+    }
+
+    /// Move funds between the funding and trading accounts
+    pub async fn transfer_funds(&self, request: &FundsTransferRequest) -> Result<TransferData> {
+        self.post::<TransferData, _>("/api/v5/asset/transfer", request)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::InvalidResponse("Transfer returned no data".to_string()))
+    }
+
+    /// Deposit addresses for a currency, one per chain
+    pub async fn deposit_addresses(&self, ccy: &str) -> Result<Vec<DepositAddressData>> {
+        self.get("/api/v5/asset/deposit-address", &[("ccy", ccy)])
+            .await
+    }
+
+    /// Withdrawal records, newest first
+    pub async fn withdrawal_history(
+        &self,
+        request: &WithdrawalHistoryRequest,
+    ) -> Result<Vec<WithdrawalRecord>> {
+        self.get("/api/v5/asset/withdrawal-history", request).await
+    }
+
+    /// Funding account balances, optionally for a single currency
+    pub async fn asset_balances(&self, ccy: Option<&str>) -> Result<Vec<AssetBalanceData>> {
+        let query: Vec<(&str, &str)> = ccy.map(|c| ("ccy", c)).into_iter().collect();
+        self.get("/api/v5/asset/balances", &query).await
+    }
+
+    /// Trading account equity and per-currency balances, optionally for a
+    /// single currency
+    pub async fn account_balance(&self, ccy: Option<&str>) -> Result<AccountData> {
+        let query: Vec<(&str, &str)> = ccy.map(|c| ("ccy", c)).into_iter().collect();
+        self.get::<AccountData>("/api/v5/account/balance", &query)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::InvalidResponse("Account balance returned no data".to_string()))
+    }
+
+    /// Largest order the instrument allows at the account's leverage
+    pub async fn max_size(&self, request: &MaxSizeRequest) -> Result<MaxSizeData> {
+        self.get::<MaxSizeData>("/api/v5/account/max-size", request)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::InvalidResponse(format!("No max size for {}", request.inst_id)))
+    }
+
+    /// Largest order the account's available balance can open
+    pub async fn max_avail_size(&self, request: &MaxSizeRequest) -> Result<MaxAvailSizeData> {
+        self.get::<MaxAvailSizeData>("/api/v5/account/max-avail-size", request)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                Error::InvalidResponse(format!("No available size for {}", request.inst_id))
+            })
+    }
+
+    /// Index price for an index such as `BTC-USDT`
+    pub async fn index_ticker(&self, index: &str) -> Result<IndexTickerData> {
+        self.get::<IndexTickerData>("/api/v5/market/index-tickers", &[("instId", index)])
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::InvalidResponse(format!("No index ticker for {}", index)))
+    }
+
+    /// Mark price for a derivative instrument such as `BTC-USDT-SWAP`
+    pub async fn mark_price(&self, inst_type: &str, inst_id: &str) -> Result<MarkPriceData> {
+        self.get::<MarkPriceData>(
+            "/api/v5/public/mark-price",
+            &[("instType", inst_type), ("instId", inst_id)],
+        )
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| Error::InvalidResponse(format!("No mark price for {}", inst_id)))
+    }
+
+    /// Open interest and volume of all `ccy` contracts per `period`
+    /// (`5m`, `1H` or `1D`), newest first
+    pub async fn open_interest_volume(
+        &self,
+        ccy: &str,
+        period: &str,
+    ) -> Result<Vec<OpenInterestVolumeData>> {
+        self.get(
+            "/api/v5/rubik/stat/contracts/open-interest-volume",
+            &[("ccy", ccy), ("period", period)],
+        )
+        .await
+    }
+
+    /// Taker buy and sell volume of `ccy` on `inst_type` (`SPOT` or
+    /// `CONTRACTS`) per `period`, newest first
+    pub async fn taker_volume(
+        &self,
+        ccy: &str,
+        inst_type: &str,
+        period: &str,
+    ) -> Result<Vec<TakerVolumeData>> {
+        self.get(
+            "/api/v5/rubik/stat/taker-volume",
+            &[("ccy", ccy), ("instType", inst_type), ("period", period)],
+        )
+        .await
+    }
+
+    /// Ratio of accounts net long to net short on `ccy` contracts per
+    /// `period`, newest first
+    pub async fn long_short_ratio(
+        &self,
+        ccy: &str,
+        period: &str,
+    ) -> Result<Vec<LongShortRatioData>> {
+        self.get(
+            "/api/v5/rubik/stat/contracts/long-short-account-ratio",
+            &[("ccy", ccy), ("period", period)],
+        )
+        .await
+    }
+
+    /// Trading rules and state of every instrument of `inst_type`
+    pub async fn instruments(&self, inst_type: &str) -> Result<Vec<InstrumentSpec>> {
+        self.get("/api/v5/public/instruments", &[("instType", inst_type)])
+            .await
+    }
+
+    /// Place an order, with any attached take-profit/stop-loss
+    ///
+    /// Fails with the rejection when OKX refuses the order itself.
+    pub async fn place_order(&self, request: &PlaceOrderRequest) -> Result<OrderResponse> {
+        self.place(request, &[]).await
+    }
+
+    /// Place an order that OKX drops if the request reaches it after `exp_time`
+    ///
+    /// OKX has no good-till-date time in force: `expTime` only bounds when
+    /// the order may be accepted, so an order that rests must still be
+    /// cancelled by the caller at its expiry.
+    pub async fn place_order_until(
+        &self,
+        request: &PlaceOrderRequest,
+        exp_time: DateTime<Utc>,
+    ) -> Result<OrderResponse> {
+        self.place(
+            request,
+            &[("expTime", exp_time.timestamp_millis().to_string())],
+        )
+        .await
+    }
+
+    async fn place(
+        &self,
+        request: &PlaceOrderRequest,
+        headers: &[(&str, String)],
+    ) -> Result<OrderResponse> {
+        let url = self.base_url.join("/api/v5/trade/order")?;
+        let body = serde_json::to_string(request)?;
+        let placed = self
+            .send::<OrderResponse>(Method::POST, url, body, headers)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                Error::InvalidResponse("Order placement returned no data".to_string())
+            })?;
+        match placed.rejection() {
+            Some(_) => Err(Error::ApiError {
+                code: placed.s_code,
+                message: placed.s_msg,
+            }),
+            None => Ok(placed),
+        }
+    }
+
+    /// Order placed with client order ID `cl_ord_id`, or `None` if OKX has
+    /// no such order
+    pub async fn order_by_client_id(
+        &self,
+        inst_id: &str,
+        cl_ord_id: &str,
+    ) -> Result<Option<OrderData>> {
+        let query = [("instId", inst_id), ("clOrdId", cl_ord_id)];
+        match self.get::<OrderData>("/api/v5/trade/order", &query).await {
+            Ok(orders) => Ok(orders.into_iter().next()),
+            Err(Error::ApiError { code, .. }) if code == ORDER_NOT_FOUND => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Cancel a resting order
+    ///
+    /// Fails with the rejection when OKX refuses the cancellation, e.g.
+    /// because the order has already completed.
+    pub async fn cancel_order(&self, request: &CancelOrderRequest) -> Result<OrderResponse> {
+        let cancelled = self
+            .post::<OrderResponse, _>("/api/v5/trade/cancel-order", request)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                Error::InvalidResponse("Order cancellation returned no data".to_string())
+            })?;
+        match cancelled.rejection() {
+            Some(_) => Err(Error::ApiError {
+                code: cancelled.s_code,
+                message: cancelled.s_msg,
+            }),
+            None => Ok(cancelled),
+        }
+    }
+
+    /// Change the size or price of a resting order
+    ///
+    /// Fails with the rejection when OKX refuses the amendment, e.g.
+    /// because the order has already completed.
+    pub async fn amend_order(&self, request: &AmendOrderRequest) -> Result<OrderResponse> {
+        let amended = self
+            .post::<OrderResponse, _>("/api/v5/trade/amend-order", request)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                Error::InvalidResponse("Order amendment returned no data".to_string())
+            })?;
+        match amended.rejection() {
+            Some(_) => Err(Error::ApiError {
+                code: amended.s_code,
+                message: amended.s_msg,
+            }),
+            None => Ok(amended),
+        }
+    }
+
+    /// Place an algo order (take profit, trailing stop, ...) that rests on
+    /// the exchange until triggered
+    pub async fn place_algo_order(&self, request: &AlgoOrderRequest) -> Result<AlgoOrderData> {
+        self.post::<AlgoOrderData, _>("/api/v5/trade/order-algo", request)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::InvalidResponse("Algo order returned no data".to_string()))
+    }
+
+    /// Change the size or trigger prices of a resting algo order
+    pub async fn amend_algo_order(&self, request: &AmendAlgoOrderRequest) -> Result<AlgoOrderData> {
+        self.post::<AlgoOrderData, _>("/api/v5/trade/amend-algos", request)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::InvalidResponse("Algo amendment returned no data".to_string()))
+    }
+
+    /// Cancel resting algo orders
+    pub async fn cancel_algo_orders(
+        &self,
+        requests: &[CancelAlgoOrderRequest],
+    ) -> Result<Vec<AlgoOrderData>> {
+        self.post("/api/v5/trade/cancel-algos", &requests).await
+    }
+
+    /// Orders completed in the last 7 days, newest first
+    pub fn orders_history(
+        &self,
+        request: &OrdersHistoryRequest,
+    ) -> Result<Paginator<'_, OrderData>> {
+        Paginator::new(
+            self,
+            "/api/v5/trade/orders-history",
+            request,
+            |o: &OrderData| o.ord_id.clone(),
+        )
+    }
+
+    /// Fills from the last 3 months, newest first
+    pub fn fills_history(&self, request: &FillsHistoryRequest) -> Result<Paginator<'_, FillData>> {
+        Paginator::new(
+            self,
+            "/api/v5/trade/fills-history",
+            request,
+            |f: &FillData| f.bill_id.clone(),
+        )
+    }
+
+    /// Closed candles of `bar` for `inst_id`, newest first
+    pub fn history_candles(
+        &self,
+        inst_id: &str,
+        bar: Interval,
+    ) -> Result<Paginator<'_, CandleBar>> {
+        Paginator::new(
+            self,
+            "/api/v5/market/history-candles",
+            &[("instId", inst_id), ("bar", bar.as_str())],
+            |c: &CandleBar| c.0.timestamp.clone(),
+        )
+    }
+
+    /// Signed GET with `query` encoded into the path
+    pub async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &impl Serialize,
+    ) -> Result<Vec<T>> {
+        let mut url = self.base_url.join(path)?;
+        let pairs = query_pairs(query)?;
+        if !pairs.is_empty() {
+            url.query_pairs_mut().extend_pairs(pairs);
+        }
+        self.send(Method::GET, url, String::new(), &[]).await
+    }
+
+    /// Signed POST with a JSON body
+    pub async fn post<T: DeserializeOwned, B: Serialize>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<Vec<T>> {
+        let url = self.base_url.join(path)?;
+        let body = serde_json::to_string(body)?;
+        self.send(Method::POST, url, body, &[]).await
+    }
+
+    async fn send<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        url: Url,
+        body: String,
+        headers: &[(&str, String)],
+    ) -> Result<Vec<T>> {
+        let request_path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        let (timestamp, signature) = self.signer.sign_request_at(
+            self.clock.now().await,
+            method.as_str(),
+            &request_path,
+            &body,
+        )?;
+
+        let mut request = self
+            .http
+            .request(method, url)
+            .header("OK-ACCESS-KEY", self.signer.api_key())
+            .header("OK-ACCESS-SIGN", signature)
+            .header("OK-ACCESS-TIMESTAMP", timestamp)
+            .header("OK-ACCESS-PASSPHRASE", self.signer.passphrase())
+            .header("Content-Type", "application/json");
+        if self.is_testnet {
+            request = request.header("x-simulated-trading", "1");
+        }
+        for (name, value) in headers {
+            request = request.header(*name, value);
+        }
+        if !body.is_empty() {
+            request = request.body(body);
+        }
+
+        let started = Instant::now();
+        let response = match request.send().await {
+            Ok(response) => response,
+            Err(e) => {
+                self.telemetry
+                    .record(started.elapsed(), RestOutcome::NetworkError)
+                    .await;
+                return Err(e.into());
+            }
+        };
+        let status = response.status();
+        let outcome = if status == StatusCode::TOO_MANY_REQUESTS {
+            RestOutcome::RateLimited
+        } else if status.is_server_error() {
+            RestOutcome::ServerError
+        } else {
+            RestOutcome::Success
+        };
+        self.telemetry.record(started.elapsed(), outcome).await;
+        let text = response.text().await?;
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            return Err(Error::RateLimitExceeded(text));
+        }
+
+        OkxResponse::parse(&text).map_err(|e| match e {
+            Error::InvalidResponse(message) => {
+                Error::InvalidResponse(format!("HTTP {}: {}", status, message))
+            }
+            other => other,
+        })
+    }
+}
+
+/// Error code OKX answers order queries with when no order matches
+const ORDER_NOT_FOUND: &str = "51603";
+
+/// Flatten a serializable query into string pairs, skipping nulls
+pub(crate) fn query_pairs(query: &impl Serialize) -> Result<Vec<(String, String)>> {
+    let pairs = match serde_json::to_value(query)? {
+        Value::Null => Vec::new(),
+        Value::Object(map) => map.into_iter().collect(),
+        Value::Array(items) => items
+            .into_iter()
+            .filter_map(|item| match item {
+                Value::Array(mut pair) if pair.len() == 2 => {
+                    let value = pair.pop()?;
+                    let key = pair.pop()?.as_str()?.to_string();
+                    Some((key, value))
+                }
+                _ => None,
+            })
+            .collect(),
+        other => {
+            return Err(Error::Internal(format!(
+                "Unsupported query parameters: {}",
+                other
+            )));
+        }
+    };
+
+    Ok(pairs
+        .into_iter()
+        .filter_map(|(key, value)| match value {
+            Value::Null => None,
+            Value::String(s) => Some((key, s)),
+            other => Some((key, other.to_string())),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::request::AccountType;
+    use wiremock::matchers::{body_json, header, header_exists, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn client(server: &MockServer) -> OkxRestClient {
+        OkxRestClient::new(Credentials::new("key", "secret", "pass"), true)
+            .unwrap()
+            .with_base_url(&server.uri())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_transfer_is_signed_and_parsed() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/v5/asset/transfer"))
+            .and(header("OK-ACCESS-KEY", "key"))
+            .and(header("x-simulated-trading", "1"))
+            .and(header_exists("OK-ACCESS-SIGN"))
+            .and(body_json(serde_json::json!({
+                "ccy": "USDT", "amt": "100", "from": "6", "to": "18"
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": "0",
+                "msg": "",
+                "data": [{
+                    "transId": "754147", "ccy": "USDT", "clientId": "",
+                    "from": "6", "amt": "100", "to": "18"
+                }]
+            })))
+            .mount(&server)
+            .await;
+
+        let transfer = client(&server)
+            .await
+            .transfer_funds(&FundsTransferRequest {
+                ccy: "USDT".to_string(),
+                amt: "100".to_string(),
+                from: AccountType::Funding,
+                to: AccountType::Trading,
+                client_id: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(transfer.trans_id, "754147");
+    }
+
+    #[tokio::test]
+    async fn test_query_parameters_and_api_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v5/asset/withdrawal-history"))
+            .and(query_param("ccy", "BTC"))
+            .and(query_param("limit", "20"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": "0",
+                "msg": "",
+                "data": [{
+                    "wdId": "1", "ccy": "BTC", "chain": "BTC-Bitcoin", "amt": "0.1",
+                    "fee": "0.0002", "to": "bc1q", "txId": "", "state": "2",
+                    "ts": "1700000000000"
+                }]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v5/asset/balances"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": "50113", "msg": "Invalid Sign", "data": []
+            })))
+            .mount(&server)
+            .await;
+
+        let client = client(&server).await;
+        let history = client
+            .withdrawal_history(&WithdrawalHistoryRequest {
+                ccy: Some("BTC".to_string()),
+                limit: Some(20),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(history[0].state, "2");
+
+        match client.asset_balances(None).await {
+            Err(Error::ApiError { code, .. }) => assert_eq!(code, "50113"),
+            other => panic!("expected API error, got {:?}", other.map(|b| b.len())),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_account_balance_is_parsed() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v5/account/balance"))
+            .and(header_exists("OK-ACCESS-SIGN"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": "0",
+                "msg": "",
+                "data": [{
+                    "uTime": "1700000000000", "totalEq": "10500",
+                    "details": [{
+                        "ccy": "USDT", "eq": "10500", "cashBal": "10000",
+                        "uTime": "1700000000000", "availBal": "9000"
+                    }]
+                }]
+            })))
+            .mount(&server)
+            .await;
+
+        let account = client(&server).await.account_balance(None).await.unwrap();
+        assert_eq!(account.total_eq, "10500");
+        assert_eq!(account.details[0].cash_bal, "10000");
+        assert_eq!(account.details[0].avail_bal.as_deref(), Some("9000"));
+    }
+
+    #[tokio::test]
+    async fn test_size_limits_send_instrument_and_mode() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v5/account/max-size"))
+            .and(query_param("instId", "BTC-USDT"))
+            .and(query_param("tdMode", "cash"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": "0", "msg": "",
+                "data": [{ "instId": "BTC-USDT", "ccy": "BTC", "maxBuy": "2", "maxSell": "1" }]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v5/account/max-avail-size"))
+            .and(query_param("tdMode", "cash"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": "0", "msg": "",
+                "data": [{ "instId": "BTC-USDT", "availBuy": "1.5", "availSell": "0.8" }]
+            })))
+            .mount(&server)
+            .await;
+
+        let client = client(&server).await;
+        let request = MaxSizeRequest {
+            inst_id: "BTC-USDT".to_string(),
+            td_mode: "cash".to_string(),
+            ccy: None,
+            px: None,
+        };
+        assert_eq!(client.max_size(&request).await.unwrap().max_buy, "2");
+        assert_eq!(
+            client.max_avail_size(&request).await.unwrap().avail_sell,
+            "0.8"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_place_algo_order_sends_only_set_fields() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/v5/trade/order-algo"))
+            .and(body_json(serde_json::json!({
+                "instId": "BTC-USDT-SWAP", "tdMode": "cross", "side": "sell",
+                "ordType": "conditional", "sz": "2", "reduceOnly": true,
+                "tpTriggerPx": "51000", "tpOrdPx": "-1"
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": "0",
+                "msg": "",
+                "data": [{"algoId": "681096944655273984", "algoClOrdId": "", "sCode": "0", "sMsg": ""}]
+            })))
+            .mount(&server)
+            .await;
+
+        let placed = client(&server)
+            .await
+            .place_algo_order(&AlgoOrderRequest {
+                inst_id: "BTC-USDT-SWAP".to_string(),
+                td_mode: "cross".to_string(),
+                side: "sell".to_string(),
+                ord_type: "conditional".to_string(),
+                sz: "2".to_string(),
+                reduce_only: Some(true),
+                tp_trigger_px: Some("51000".to_string()),
+                tp_ord_px: Some("-1".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(placed.algo_id, "681096944655273984");
+    }
+
+    #[tokio::test]
+    async fn test_place_order_attaches_take_profit_and_stop_loss() {
+        use crate::models::request::AttachedAlgoOrder;
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/v5/trade/order"))
+            .and(body_json(serde_json::json!({
+                "instId": "BTC-USDT-SWAP", "tdMode": "cross", "side": "buy",
+                "ordType": "limit", "sz": "1", "px": "50000",
+                "attachAlgoOrds": [{
+                    "attachAlgoClOrdId": "bracket1",
+                    "tpTriggerPx": "52000", "tpOrdPx": "-1",
+                    "slTriggerPx": "49000", "slOrdPx": "-1"
+                }]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": "0",
+                "msg": "",
+                "data": [{"ordId": "312269865356374016", "clOrdId": "", "sCode": "0", "sMsg": ""}]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v5/trade/amend-algos"))
+            .and(body_json(serde_json::json!({
+                "instId": "BTC-USDT-SWAP", "algoClOrdId": "bracket1", "newSz": "0.5"
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": "0",
+                "msg": "",
+                "data": [{"algoId": "681096944655273984", "algoClOrdId": "bracket1", "sCode": "0", "sMsg": ""}]
+            })))
+            .mount(&server)
+            .await;
+
+        let client = client(&server).await;
+        let placed = client
+            .place_order(&PlaceOrderRequest {
+                inst_id: "BTC-USDT-SWAP".to_string(),
+                td_mode: "cross".to_string(),
+                side: "buy".to_string(),
+                ord_type: "limit".to_string(),
+                sz: "1".to_string(),
+                px: Some("50000".to_string()),
+                cl_ord_id: None,
+                tag: None,
+                reduce_only: None,
+                attach_algo_ords: Some(vec![AttachedAlgoOrder {
+                    attach_algo_cl_ord_id: Some("bracket1".to_string()),
+                    tp_trigger_px: Some("52000".to_string()),
+                    tp_ord_px: Some("-1".to_string()),
+                    sl_trigger_px: Some("49000".to_string()),
+                    sl_ord_px: Some("-1".to_string()),
+                }]),
+            })
+            .await
+            .unwrap();
+        assert_eq!(placed.ord_id, "312269865356374016");
+
+        let amended = client
+            .amend_algo_order(&AmendAlgoOrderRequest {
+                inst_id: "BTC-USDT-SWAP".to_string(),
+                algo_cl_ord_id: Some("bracket1".to_string()),
+                new_sz: Some("0.5".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(amended.algo_id, "681096944655273984");
+    }
+
+    #[tokio::test]
+    async fn test_place_order_until_sends_exp_time() {
+        let server = MockServer::start().await;
+        let exp_time = Utc::now() + chrono::Duration::seconds(5);
+        Mock::given(method("POST"))
+            .and(path("/api/v5/trade/order"))
+            .and(header(
+                "expTime",
+                exp_time.timestamp_millis().to_string().as_str(),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": "0",
+                "msg": "",
+                "data": [{"ordId": "312269865356374017", "clOrdId": "", "sCode": "0", "sMsg": ""}]
+            })))
+            .mount(&server)
+            .await;
+
+        let placed = client(&server)
+            .await
+            .place_order_until(
+                &PlaceOrderRequest {
+                    inst_id: "BTC-USDT".to_string(),
+                    td_mode: "cash".to_string(),
+                    side: "buy".to_string(),
+                    ord_type: "limit".to_string(),
+                    sz: "0.01".to_string(),
+                    px: Some("50000".to_string()),
+                    cl_ord_id: None,
+                    tag: None,
+                    reduce_only: None,
+                    attach_algo_ords: None,
+                },
+                exp_time,
+            )
+            .await
+            .unwrap();
+        assert_eq!(placed.ord_id, "312269865356374017");
+    }
+
+    #[tokio::test]
+    async fn test_amend_order_reports_rejection() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/v5/trade/amend-order"))
+            .and(body_json(serde_json::json!({
+                "instId": "BTC-USDT", "clOrdId": "s1a2b3", "newSz": "0.02", "newPx": "49000"
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": "0",
+                "msg": "",
+                "data": [{"ordId": "312269865356374018", "clOrdId": "s1a2b3", "sCode": "0", "sMsg": ""}]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v5/trade/amend-order"))
+            .and(body_json(serde_json::json!({
+                "instId": "BTC-USDT", "clOrdId": "s9z8y7", "newPx": "49000"
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": "1",
+                "msg": "",
+                "data": [{"ordId": "", "clOrdId": "s9z8y7", "sCode": "51503", "sMsg": "Order does not exist"}]
+            })))
+            .mount(&server)
+            .await;
+
+        let client = client(&server).await;
+        let amended = client
+            .amend_order(&AmendOrderRequest {
+                inst_id: "BTC-USDT".to_string(),
+                cl_ord_id: Some("s1a2b3".to_string()),
+                new_sz: Some("0.02".to_string()),
+                new_px: Some("49000".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(amended.ord_id, "312269865356374018");
+
+        let refused = client
+            .amend_order(&AmendOrderRequest {
+                inst_id: "BTC-USDT".to_string(),
+                cl_ord_id: Some("s9z8y7".to_string()),
+                new_px: Some("49000".to_string()),
+                ..Default::default()
+            })
+            .await;
+        assert!(matches!(refused, Err(Error::ApiError { code, .. }) if code == "51503"));
+    }
+
+    #[tokio::test]
+    async fn test_order_by_client_id_is_none_when_unknown() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v5/trade/order"))
+            .and(query_param("clOrdId", "s1a2b3"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": "51603",
+                "msg": "Order does not exist",
+                "data": []
+            })))
+            .mount(&server)
+            .await;
+
+        let order = client(&server)
+            .await
+            .order_by_client_id("BTC-USDT", "s1a2b3")
+            .await
+            .unwrap();
+        assert!(order.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_telemetry_counts_server_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v5/asset/balances"))
+            .respond_with(ResponseTemplate::new(503).set_body_string("Service Unavailable"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v5/asset/deposit-address"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": "0", "msg": "", "data": []
+            })))
+            .mount(&server)
+            .await;
+
+        let client = client(&server).await;
+        assert!(client.asset_balances(None).await.is_err());
+        assert!(client.deposit_addresses("BTC").await.unwrap().is_empty());
+
+        let metrics = client.telemetry().snapshot().await;
+        assert_eq!(metrics.requests, 2);
+        assert_eq!(metrics.server_errors, 1);
+        assert_eq!(metrics.error_pct(), 50.0);
+        assert!(metrics.last_success_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_requests_are_stamped_on_the_synced_clock() {
+        let server = MockServer::start().await;
+        let ahead = Utc::now() + chrono::Duration::seconds(60);
+        Mock::given(method("GET"))
+            .and(path("/api/v5/public/time"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": "0", "msg": "",
+                "data": [{ "ts": ahead.timestamp_millis().to_string() }]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v5/asset/balances"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": "0", "msg": "", "data": []
+            })))
+            .mount(&server)
+            .await;
+
+        let client = client(&server).await;
+        let sample = client.sync_clock().await.unwrap();
+        assert!((sample.offset_ms - 60_000).abs() < 5_000);
+
+        client.asset_balances(None).await.unwrap();
+        let requests = server.received_requests().await.unwrap();
+        let stamped = requests[1].headers["OK-ACCESS-TIMESTAMP"].to_str().unwrap();
+        let stamped = DateTime::parse_from_rfc3339(stamped).unwrap();
+        assert!((stamped.with_timezone(&Utc) - Utc::now()).num_seconds() >= 55);
+
+        // A failed sync keeps the offset
+        server.reset().await;
+        assert!(client.sync_clock().await.is_err());
+        let metrics = client.clock().snapshot().await;
+        assert_eq!(metrics.offset_ms(), sample.offset_ms);
+        assert_eq!(metrics.consecutive_failures, 1);
     }
 }
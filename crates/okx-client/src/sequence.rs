@@ -0,0 +1,214 @@
+//! Order book sequence tracking
+//!
+//! Incremental order book channels chain every push to the previous one:
+//! an update's `prevSeqId` is the `seqId` of the push before it, and a
+//! snapshot carries `prevSeqId` -1. A break in that chain means updates were
+//! lost and the local book no longer matches the exchange. The tracker keys
+//! chains by `channel:instId`, reports the first broken link as a
+//! [`DataGap`] and withholds further updates until a fresh snapshot arrives.
+//!
+//! `books5` pushes a full five-level book every time, so it is not tracked.
+
+use crate::models::websocket::{DataGap, OrderBookData};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+/// `prevSeqId` OKX sends on a snapshot
+const SNAPSHOT_PREV_SEQ_ID: i64 = -1;
+
+/// Channels whose pushes are self-contained
+const FULL_BOOK_CHANNELS: &[&str] = &["books5"];
+
+/// What to do with an order book push
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SequenceCheck {
+    /// Continues the chain, or starts it; deliver it
+    Accept,
+    /// Breaks the chain; the book must be resynced
+    Gap(DataGap),
+    /// Arrived while waiting for a resync snapshot; drop it
+    Discard,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ChainState {
+    InSync { last_seq_id: i64 },
+    AwaitingSnapshot,
+}
+
+/// Per `channel:instId` order book sequence state
+#[derive(Debug, Default)]
+pub struct SequenceTracker {
+    chains: HashMap<String, ChainState>,
+}
+
+impl SequenceTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check a push on `channel` for `inst_id`
+    ///
+    /// `action` is the frame's `action` field (`snapshot` or `update`) when
+    /// present. Pushes without sequence IDs are accepted untracked.
+    pub fn check(
+        &mut self,
+        channel: &str,
+        inst_id: Option<&str>,
+        action: Option<&str>,
+        book: &OrderBookData,
+        now: DateTime<Utc>,
+    ) -> SequenceCheck {
+        if FULL_BOOK_CHANNELS.contains(&channel) {
+            return SequenceCheck::Accept;
+        }
+        let (Some(prev_seq_id), Some(seq_id)) = (book.prev_seq_id, book.seq_id) else {
+            return SequenceCheck::Accept;
+        };
+
+        let key = chain_key(channel, inst_id);
+        if action == Some("snapshot") || prev_seq_id == SNAPSHOT_PREV_SEQ_ID {
+            self.chains.insert(
+                key,
+                ChainState::InSync {
+                    last_seq_id: seq_id,
+                },
+            );
+            return SequenceCheck::Accept;
+        }
+
+        match self.chains.get(&key).copied() {
+            Some(ChainState::AwaitingSnapshot) => SequenceCheck::Discard,
+            Some(ChainState::InSync { last_seq_id }) if last_seq_id != prev_seq_id => {
+                self.chains.insert(key, ChainState::AwaitingSnapshot);
+                SequenceCheck::Gap(DataGap {
+                    channel: channel.to_string(),
+                    inst_id: inst_id.map(str::to_string),
+                    expected_prev_seq_id: last_seq_id,
+                    prev_seq_id,
+                    seq_id,
+                    detected_at: now,
+                })
+            }
+            // In order, or the first push seen on this chain
+            _ => {
+                self.chains.insert(
+                    key,
+                    ChainState::InSync {
+                        last_seq_id: seq_id,
+                    },
+                );
+                SequenceCheck::Accept
+            }
+        }
+    }
+
+    /// Whether `channel:instId` is waiting for a resync snapshot
+    pub fn is_awaiting_snapshot(&self, channel: &str, inst_id: Option<&str>) -> bool {
+        matches!(
+            self.chains.get(&chain_key(channel, inst_id)),
+            Some(ChainState::AwaitingSnapshot)
+        )
+    }
+
+    /// Forget all chains, e.g. after reconnecting
+    pub fn clear(&mut self) {
+        self.chains.clear();
+    }
+}
+
+fn chain_key(channel: &str, inst_id: Option<&str>) -> String {
+    match inst_id {
+        Some(inst_id) => format!("{}:{}", channel, inst_id),
+        None => channel.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book(prev_seq_id: i64, seq_id: i64) -> OrderBookData {
+        OrderBookData {
+            inst_id: None,
+            asks: Vec::new(),
+            bids: Vec::new(),
+            ts: "1700000000000".to_string(),
+            checksum: None,
+            prev_seq_id: Some(prev_seq_id),
+            seq_id: Some(seq_id),
+        }
+    }
+
+    fn check(tracker: &mut SequenceTracker, prev_seq_id: i64, seq_id: i64) -> SequenceCheck {
+        tracker.check(
+            "books-l2-tbt",
+            Some("BTC-USDT"),
+            None,
+            &book(prev_seq_id, seq_id),
+            Utc::now(),
+        )
+    }
+
+    #[test]
+    fn test_continuous_chain_is_accepted() {
+        let mut tracker = SequenceTracker::new();
+        assert_eq!(check(&mut tracker, -1, 100), SequenceCheck::Accept);
+        assert_eq!(check(&mut tracker, 100, 105), SequenceCheck::Accept);
+        // No-change heartbeat repeats the last seqId
+        assert_eq!(check(&mut tracker, 105, 105), SequenceCheck::Accept);
+        // Sequence reset after maintenance still links to the last seqId
+        assert_eq!(check(&mut tracker, 105, 3), SequenceCheck::Accept);
+        assert_eq!(check(&mut tracker, 3, 4), SequenceCheck::Accept);
+    }
+
+    #[test]
+    fn test_gap_withholds_updates_until_snapshot() {
+        let mut tracker = SequenceTracker::new();
+        check(&mut tracker, -1, 100);
+
+        let SequenceCheck::Gap(gap) = check(&mut tracker, 110, 120) else {
+            panic!("expected a gap");
+        };
+        assert_eq!(gap.inst_id.as_deref(), Some("BTC-USDT"));
+        assert_eq!(gap.expected_prev_seq_id, 100);
+        assert_eq!(gap.prev_seq_id, 110);
+        assert!(tracker.is_awaiting_snapshot("books-l2-tbt", Some("BTC-USDT")));
+
+        // Updates that happen to link up again are still stale
+        assert_eq!(check(&mut tracker, 120, 130), SequenceCheck::Discard);
+
+        let resync = tracker.check(
+            "books-l2-tbt",
+            Some("BTC-USDT"),
+            Some("snapshot"),
+            &book(-1, 200),
+            Utc::now(),
+        );
+        assert_eq!(resync, SequenceCheck::Accept);
+        assert_eq!(check(&mut tracker, 200, 201), SequenceCheck::Accept);
+    }
+
+    #[test]
+    fn test_chains_are_independent_and_full_books_untracked() {
+        let mut tracker = SequenceTracker::new();
+        check(&mut tracker, -1, 100);
+        let eth = |tracker: &mut SequenceTracker, prev, seq| {
+            tracker.check(
+                "books-l2-tbt",
+                Some("ETH-USDT"),
+                None,
+                &book(prev, seq),
+                Utc::now(),
+            )
+        };
+        assert_eq!(eth(&mut tracker, -1, 7), SequenceCheck::Accept);
+        assert!(matches!(eth(&mut tracker, 9, 10), SequenceCheck::Gap(_)));
+        assert_eq!(check(&mut tracker, 100, 101), SequenceCheck::Accept);
+
+        for seq in [5, 50, 2] {
+            let full = tracker.check("books5", Some("BTC-USDT"), None, &book(1, seq), Utc::now());
+            assert_eq!(full, SequenceCheck::Accept);
+        }
+    }
+}
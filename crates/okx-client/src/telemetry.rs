@@ -0,0 +1,395 @@
+//! Connection telemetry
+//!
+//! Tracks connection-level health for [`OkxWebSocketClient`](crate::websocket::OkxWebSocketClient):
+//! reconnects, per-channel message recency, ping round-trip time,
+//! subscription count, dropped messages and order book sequence gaps.
+//! [`RestTelemetry`] does the same for [`OkxRestClient`](crate::rest::OkxRestClient)
+//! requests: latency, server errors, network failures and rate limiting
+//! over a sliding window.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Point-in-time view of connection health
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConnectionMetrics {
+    /// Successful connects after the first one
+    pub reconnect_count: u64,
+
+    /// When the current connection was established
+    pub connected_at: Option<DateTime<Utc>>,
+
+    /// Active subscriptions
+    pub subscription_count: usize,
+
+    /// Data frames received
+    pub messages_received: u64,
+
+    /// Frames that could not be parsed or delivered
+    pub dropped_messages: u64,
+
+    /// Order book sequence breaks that forced a resync
+    #[serde(default)]
+    pub data_gaps: u64,
+
+    /// Round-trip time of the most recent ping
+    pub ping_rtt_ms: Option<f64>,
+
+    /// Last message per `channel:instId` key
+    pub last_message_at: HashMap<String, DateTime<Utc>>,
+}
+
+impl ConnectionMetrics {
+    /// Seconds since the last message on a `channel:instId` key
+    pub fn seconds_since_last_message(&self, key: &str, now: DateTime<Utc>) -> Option<f64> {
+        self.last_message_at
+            .get(key)
+            .map(|at| seconds_between(*at, now))
+    }
+
+    /// Seconds since any data arrived, counting from the connect time if
+    /// nothing has been received yet
+    pub fn seconds_since_any_message(&self, now: DateTime<Utc>) -> Option<f64> {
+        self.last_message_at
+            .values()
+            .max()
+            .copied()
+            .or(self.connected_at)
+            .map(|at| seconds_between(at, now))
+    }
+}
+
+fn seconds_between(from: DateTime<Utc>, to: DateTime<Utc>) -> f64 {
+    (to - from).num_milliseconds().max(0) as f64 / 1000.0
+}
+
+#[derive(Debug, Default)]
+struct TelemetryState {
+    metrics: ConnectionMetrics,
+    connects: u64,
+    ping_sent_at: Option<Instant>,
+}
+
+/// Shared telemetry handle; cheap to clone
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionTelemetry {
+    state: Arc<Mutex<TelemetryState>>,
+}
+
+impl ConnectionTelemetry {
+    /// Create an empty telemetry handle
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Current metrics
+    pub async fn snapshot(&self) -> ConnectionMetrics {
+        self.state.lock().await.metrics.clone()
+    }
+
+    pub(crate) async fn on_connected(&self) {
+        let mut state = self.state.lock().await;
+        state.connects += 1;
+        state.metrics.reconnect_count = state.connects.saturating_sub(1);
+        state.metrics.connected_at = Some(Utc::now());
+    }
+
+    pub(crate) async fn on_message(&self, key: Option<String>) {
+        let mut state = self.state.lock().await;
+        state.metrics.messages_received += 1;
+        if let Some(key) = key {
+            state.metrics.last_message_at.insert(key, Utc::now());
+        }
+    }
+
+    pub(crate) async fn on_dropped(&self) {
+        self.state.lock().await.metrics.dropped_messages += 1;
+    }
+
+    pub(crate) async fn on_data_gap(&self) {
+        self.state.lock().await.metrics.data_gaps += 1;
+    }
+
+    pub(crate) async fn on_ping_sent(&self) {
+        self.state.lock().await.ping_sent_at = Some(Instant::now());
+    }
+
+    pub(crate) async fn on_pong(&self) {
+        let mut state = self.state.lock().await;
+        if let Some(sent) = state.ping_sent_at.take() {
+            state.metrics.ping_rtt_ms = Some(sent.elapsed().as_secs_f64() * 1000.0);
+        }
+    }
+
+    pub(crate) async fn set_subscription_count(&self, count: usize) {
+        self.state.lock().await.metrics.subscription_count = count;
+    }
+}
+
+/// How a REST request ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RestOutcome {
+    /// A response below 500, including OKX business errors
+    Success,
+    /// HTTP 429
+    RateLimited,
+    /// HTTP 5xx
+    ServerError,
+    /// Timeout, connection or TLS failure
+    NetworkError,
+}
+
+/// REST request health over the telemetry window
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RestMetrics {
+    /// Requests completed within the window
+    pub requests: usize,
+    pub server_errors: usize,
+    pub network_errors: usize,
+    pub rate_limited: usize,
+    pub avg_latency_ms: Option<f64>,
+    pub p95_latency_ms: Option<f64>,
+    pub last_success_at: Option<DateTime<Utc>>,
+    pub window_secs: u64,
+}
+
+impl RestMetrics {
+    /// Share of requests that failed with a server or network error, in percent
+    pub fn error_pct(&self) -> f64 {
+        if self.requests == 0 {
+            return 0.0;
+        }
+        (self.server_errors + self.network_errors) as f64 / self.requests as f64 * 100.0
+    }
+}
+
+#[derive(Debug)]
+struct RestState {
+    window: Duration,
+    samples: VecDeque<(DateTime<Utc>, f64, RestOutcome)>,
+    last_success_at: Option<DateTime<Utc>>,
+}
+
+/// Shared REST telemetry handle; cheap to clone
+#[derive(Debug, Clone)]
+pub struct RestTelemetry {
+    state: Arc<Mutex<RestState>>,
+}
+
+impl RestTelemetry {
+    /// Telemetry over the last minute of requests
+    pub fn new() -> Self {
+        Self::with_window(Duration::from_secs(60))
+    }
+
+    pub fn with_window(window: Duration) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(RestState {
+                window,
+                samples: VecDeque::new(),
+                last_success_at: None,
+            })),
+        }
+    }
+
+    /// Current metrics
+    pub async fn snapshot(&self) -> RestMetrics {
+        self.snapshot_at(Utc::now()).await
+    }
+
+    pub async fn snapshot_at(&self, now: DateTime<Utc>) -> RestMetrics {
+        let mut state = self.state.lock().await;
+        let cutoff = now - state.window;
+        while state.samples.front().is_some_and(|(at, ..)| *at < cutoff) {
+            state.samples.pop_front();
+        }
+
+        let count = |outcome: RestOutcome| state.samples.iter().filter(|s| s.2 == outcome).count();
+        let mut latencies: Vec<f64> = state.samples.iter().map(|s| s.1).collect();
+        latencies.sort_by(f64::total_cmp);
+        let avg_latency_ms =
+            (!latencies.is_empty()).then(|| latencies.iter().sum::<f64>() / latencies.len() as f64);
+        let p95_latency_ms = (!latencies.is_empty()).then(|| {
+            let rank = (latencies.len() as f64 * 0.95).ceil() as usize;
+            latencies[rank.clamp(1, latencies.len()) - 1]
+        });
+
+        RestMetrics {
+            requests: state.samples.len(),
+            server_errors: count(RestOutcome::ServerError),
+            network_errors: count(RestOutcome::NetworkError),
+            rate_limited: count(RestOutcome::RateLimited),
+            avg_latency_ms,
+            p95_latency_ms,
+            last_success_at: state.last_success_at,
+            window_secs: state.window.as_secs(),
+        }
+    }
+
+    pub(crate) async fn record(&self, latency: Duration, outcome: RestOutcome) {
+        self.record_at(Utc::now(), latency, outcome).await;
+    }
+
+    pub(crate) async fn record_at(
+        &self,
+        at: DateTime<Utc>,
+        latency: Duration,
+        outcome: RestOutcome,
+    ) {
+        let mut state = self.state.lock().await;
+        if outcome == RestOutcome::Success {
+            state.last_success_at = Some(at);
+        }
+        state
+            .samples
+            .push_back((at, latency.as_secs_f64() * 1000.0, outcome));
+    }
+}
+
+impl Default for RestTelemetry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Telemetry key for a data frame: `channel:instId`, or just the channel
+pub(crate) fn message_key(value: &serde_json::Value) -> Option<String> {
+    let arg = value.get("arg")?;
+    let channel = arg.get("channel")?.as_str()?;
+    Some(match arg.get("instId").and_then(|v| v.as_str()) {
+        Some(inst_id) => format!("{}:{}", channel, inst_id),
+        None => channel.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[tokio::test]
+    async fn test_reconnect_count_excludes_first_connect() {
+        let telemetry = ConnectionTelemetry::new();
+        telemetry.on_connected().await;
+        assert_eq!(telemetry.snapshot().await.reconnect_count, 0);
+
+        telemetry.on_connected().await;
+        telemetry.on_connected().await;
+        assert_eq!(telemetry.snapshot().await.reconnect_count, 2);
+    }
+
+    #[tokio::test]
+    async fn test_message_recency_and_drops() {
+        let telemetry = ConnectionTelemetry::new();
+        telemetry.on_connected().await;
+        telemetry
+            .on_message(Some("tickers:BTC-USDT".to_string()))
+            .await;
+        telemetry.on_dropped().await;
+        telemetry.on_data_gap().await;
+
+        let metrics = telemetry.snapshot().await;
+        let later = Utc::now() + Duration::seconds(30);
+        assert_eq!(metrics.messages_received, 1);
+        assert_eq!(metrics.dropped_messages, 1);
+        assert_eq!(metrics.data_gaps, 1);
+        assert!(
+            metrics
+                .seconds_since_last_message("tickers:BTC-USDT", later)
+                .unwrap()
+                >= 29.0
+        );
+        assert!(
+            metrics
+                .seconds_since_last_message("trades:BTC-USDT", later)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_silence_falls_back_to_connect_time() {
+        let now = Utc::now();
+        let metrics = ConnectionMetrics {
+            connected_at: Some(now - Duration::seconds(90)),
+            ..Default::default()
+        };
+        assert_eq!(metrics.seconds_since_any_message(now), Some(90.0));
+        assert_eq!(
+            ConnectionMetrics::default().seconds_since_any_message(now),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ping_rtt_recorded_on_pong() {
+        let telemetry = ConnectionTelemetry::new();
+        telemetry.on_pong().await;
+        assert!(telemetry.snapshot().await.ping_rtt_ms.is_none());
+
+        telemetry.on_ping_sent().await;
+        telemetry.on_pong().await;
+        assert!(telemetry.snapshot().await.ping_rtt_ms.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_rest_metrics_over_window() {
+        let telemetry = RestTelemetry::with_window(std::time::Duration::from_secs(60));
+        let now = Utc::now();
+        let ms = std::time::Duration::from_millis;
+
+        // Aged out of the window by `now`
+        telemetry
+            .record_at(
+                now - Duration::seconds(120),
+                ms(5000),
+                RestOutcome::ServerError,
+            )
+            .await;
+        for latency in [100, 120, 140, 160] {
+            telemetry
+                .record_at(
+                    now - Duration::seconds(10),
+                    ms(latency),
+                    RestOutcome::Success,
+                )
+                .await;
+        }
+        telemetry
+            .record_at(
+                now - Duration::seconds(5),
+                ms(900),
+                RestOutcome::ServerError,
+            )
+            .await;
+        telemetry
+            .record_at(now, ms(10_000), RestOutcome::NetworkError)
+            .await;
+
+        let metrics = telemetry.snapshot_at(now).await;
+        assert_eq!(metrics.requests, 6);
+        assert_eq!(metrics.server_errors, 1);
+        assert_eq!(metrics.network_errors, 1);
+        assert!((metrics.error_pct() - 100.0 / 3.0).abs() < 1e-9);
+        assert_eq!(metrics.p95_latency_ms, Some(10_000.0));
+        assert_eq!(metrics.last_success_at, Some(now - Duration::seconds(10)));
+        assert_eq!(RestMetrics::default().error_pct(), 0.0);
+    }
+
+    #[test]
+    fn test_message_key() {
+        let value = serde_json::json!({
+            "arg": { "channel": "tickers", "instId": "BTC-USDT" },
+            "data": {}
+        });
+        assert_eq!(message_key(&value).as_deref(), Some("tickers:BTC-USDT"));
+        assert_eq!(
+            message_key(&serde_json::json!({ "event": "subscribe" })),
+            None
+        );
+    }
+}
@@ -16,6 +16,7 @@
 //! - Subscription management (subscribe/unsubscribe)
 //! - Heartbeat/ping-pong mechanism
 //! - Message validation and parsing
+//! - Order book sequence gap detection with automatic resync
 //! - Connection state management
 //!
 //! # Example
@@ -48,8 +49,13 @@
 use crate::auth::Credentials;
 use crate::error::{Error, Result};
 use crate::models::websocket::{SubscriptionRequest, WebSocketEvent};
-use chrono::Utc;
+use crate::sequence::{SequenceCheck, SequenceTracker};
+use crate::telemetry::{ConnectionMetrics, ConnectionTelemetry, message_key};
+use chrono::{DateTime, Utc};
+use ea_okx_core::heartbeat::Heartbeat;
+use futures::stream::{SplitSink, SplitStream};
 use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::sync::Arc;
 use std::time::Duration;
@@ -67,6 +73,12 @@ const WS_PRIVATE_URL: &str = "wss://ws.okx.com:8443/ws/v5/private";
 const WS_PUBLIC_TESTNET_URL: &str = "wss://wspap.okx.com:8443/ws/v5/public?brokerId=9999";
 const WS_PRIVATE_TESTNET_URL: &str = "wss://wspap.okx.com:8443/ws/v5/private?brokerId=9999";
 
+type WsStream = WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>;
+
+/// Write half of a connection; the read half belongs to its processor task,
+/// so sending never waits on a pending read
+type WsHandle = Arc<Mutex<Option<SplitSink<WsStream, WsMessage>>>>;
+
 /// Connection state
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ConnectionState {
@@ -77,6 +89,25 @@ pub enum ConnectionState {
     Failed,
 }
 
+/// Which connection a raw frame arrived on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FrameSource {
+    Public,
+    Private,
+}
+
+/// Unparsed text frame as received from the exchange
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RawFrame {
+    /// Local receive time
+    pub received_at: DateTime<Utc>,
+    /// Connection the frame arrived on
+    pub source: FrameSource,
+    /// Frame payload, verbatim
+    pub payload: String,
+}
+
 /// WebSocket client configuration
 #[derive(Debug, Clone)]
 pub struct WebSocketConfig {
@@ -107,44 +138,81 @@ impl Default for WebSocketConfig {
     }
 }
 
+/// Liveness of the client's background tasks, for a supervisor to watch
+///
+/// The keepalive task beats on every tick; the processors beat on every
+/// frame, which includes the pongs to the keepalive pings, so a healthy
+/// connection beats them at least once per heartbeat interval.
+#[derive(Debug, Clone, Default)]
+pub struct WsTaskHeartbeats {
+    pub keepalive: Heartbeat,
+    pub public_processor: Heartbeat,
+    pub private_processor: Heartbeat,
+}
+
 /// OKX WebSocket client
 pub struct OkxWebSocketClient {
     credentials: Credentials,
     is_testnet: bool,
     config: WebSocketConfig,
+    public_url: String,
+    private_url: String,
 
     // Connection management
-    public_ws: Arc<Mutex<Option<WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>>>>,
-    private_ws: Arc<Mutex<Option<WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>>>>,
+    public_ws: WsHandle,
+    private_ws: WsHandle,
     state: Arc<Mutex<ConnectionState>>,
 
     // Message channels
     message_tx: mpsc::UnboundedSender<WebSocketEvent>,
     message_rx: Arc<Mutex<mpsc::UnboundedReceiver<WebSocketEvent>>>,
 
+    // Raw frame capture, enabled by `capture_raw_frames`
+    raw_tx: Option<mpsc::UnboundedSender<RawFrame>>,
+
     // Subscription tracking
     subscriptions: Arc<Mutex<Vec<SubscriptionRequest>>>,
 
     // Heartbeat tracking
     last_pong: Arc<Mutex<std::time::Instant>>,
+
+    // Connection health
+    telemetry: ConnectionTelemetry,
+
+    // Order book sequence chains
+    sequences: Arc<Mutex<SequenceTracker>>,
+
+    // Background task liveness
+    task_heartbeats: WsTaskHeartbeats,
 }
 
 impl OkxWebSocketClient {
     /// Create a new WebSocket client
     pub fn new(credentials: Credentials, is_testnet: bool) -> Self {
         let (message_tx, message_rx) = mpsc::unbounded_channel();
+        let (public_url, private_url) = if is_testnet {
+            (WS_PUBLIC_TESTNET_URL, WS_PRIVATE_TESTNET_URL)
+        } else {
+            (WS_PUBLIC_URL, WS_PRIVATE_URL)
+        };
 
         Self {
             credentials,
             is_testnet,
             config: WebSocketConfig::default(),
+            public_url: public_url.to_string(),
+            private_url: private_url.to_string(),
             public_ws: Arc::new(Mutex::new(None)),
             private_ws: Arc::new(Mutex::new(None)),
             state: Arc::new(Mutex::new(ConnectionState::Disconnected)),
             message_tx,
             message_rx: Arc::new(Mutex::new(message_rx)),
+            raw_tx: None,
             subscriptions: Arc::new(Mutex::new(Vec::new())),
             last_pong: Arc::new(Mutex::new(std::time::Instant::now())),
+            telemetry: ConnectionTelemetry::new(),
+            sequences: Arc::new(Mutex::new(SequenceTracker::new())),
+            task_heartbeats: WsTaskHeartbeats::default(),
         }
     }
 
@@ -159,63 +227,93 @@ impl OkxWebSocketClient {
         client
     }
 
+    /// Connect to `public_url` and `private_url` instead of OKX, e.g. a
+    /// local mock server
+    pub fn with_urls(mut self, public_url: &str, private_url: &str) -> Self {
+        self.public_url = public_url.to_string();
+        self.private_url = private_url.to_string();
+        self
+    }
+
+    /// Record connection health in `telemetry`, e.g. a handle a health check
+    /// already watches, instead of a telemetry of its own
+    pub fn with_telemetry(mut self, telemetry: ConnectionTelemetry) -> Self {
+        self.telemetry = telemetry;
+        self
+    }
+
+    /// Whether the default URLs are those of demo trading
+    pub fn is_testnet(&self) -> bool {
+        self.is_testnet
+    }
+
     /// Get current connection state
     pub async fn state(&self) -> ConnectionState {
         *self.state.lock().await
     }
 
+    /// Shared handle to this client's connection telemetry
+    pub fn telemetry(&self) -> ConnectionTelemetry {
+        self.telemetry.clone()
+    }
+
+    /// Heartbeats of the keepalive and message processor tasks
+    pub fn task_heartbeats(&self) -> WsTaskHeartbeats {
+        self.task_heartbeats.clone()
+    }
+
+    /// Snapshot of connection health metrics
+    pub async fn connection_metrics(&self) -> ConnectionMetrics {
+        self.telemetry.snapshot().await
+    }
+
     /// Connect to WebSocket servers
     pub async fn connect(&mut self) -> Result<()> {
         self.set_state(ConnectionState::Connecting).await;
+        // A new connection starts every book from a fresh snapshot
+        self.sequences.lock().await.clear();
 
         // Connect to public channel
-        let public_url = if self.is_testnet {
-            WS_PUBLIC_TESTNET_URL
-        } else {
-            WS_PUBLIC_URL
-        };
-
-        match connect_async(public_url).await {
+        let public_stream = match connect_async(self.public_url.as_str()).await {
             Ok((ws_stream, _)) => {
-                *self.public_ws.lock().await = Some(ws_stream);
+                let (sink, stream) = ws_stream.split();
+                *self.public_ws.lock().await = Some(sink);
                 info!("Connected to OKX public WebSocket");
+                stream
             }
             Err(e) => {
                 error!("Failed to connect to public WebSocket: {}", e);
                 self.set_state(ConnectionState::Failed).await;
                 return Err(Error::WebSocketConnection(e.to_string()));
             }
-        }
-
-        // Connect to private channel (requires authentication)
-        let private_url = if self.is_testnet {
-            WS_PRIVATE_TESTNET_URL
-        } else {
-            WS_PRIVATE_URL
         };
 
-        match connect_async(private_url).await {
+        // Connect to private channel (requires authentication)
+        let private_stream = match connect_async(self.private_url.as_str()).await {
             Ok((ws_stream, _)) => {
-                *self.private_ws.lock().await = Some(ws_stream);
+                let (sink, stream) = ws_stream.split();
+                *self.private_ws.lock().await = Some(sink);
                 info!("Connected to OKX private WebSocket");
 
                 // Authenticate private channel
                 self.authenticate().await?;
+                stream
             }
             Err(e) => {
                 error!("Failed to connect to private WebSocket: {}", e);
                 self.set_state(ConnectionState::Failed).await;
                 return Err(Error::WebSocketConnection(e.to_string()));
             }
-        }
+        };
 
         self.set_state(ConnectionState::Connected).await;
+        self.telemetry.on_connected().await;
 
         // Start heartbeat task
         self.start_heartbeat();
 
         // Start message processing task
-        self.start_message_processor();
+        self.start_message_processor(public_stream, private_stream);
 
         Ok(())
     }
@@ -273,6 +371,7 @@ impl OkxWebSocketClient {
         // Store subscriptions for reconnection
         let mut subs = self.subscriptions.lock().await;
         subs.extend(requests);
+        self.telemetry.set_subscription_count(subs.len()).await;
 
         Ok(())
     }
@@ -298,6 +397,7 @@ impl OkxWebSocketClient {
         // Remove from stored subscriptions
         let mut subs = self.subscriptions.lock().await;
         subs.retain(|s| !requests.contains(s));
+        self.telemetry.set_subscription_count(subs.len()).await;
 
         Ok(())
     }
@@ -366,6 +466,16 @@ impl OkxWebSocketClient {
         Ok(())
     }
 
+    /// Receive a copy of every text frame before it is parsed
+    ///
+    /// Must be called before [`connect`](Self::connect). Frames that fail to
+    /// parse are still delivered, which makes this suitable for recording.
+    pub fn capture_raw_frames(&mut self) -> mpsc::UnboundedReceiver<RawFrame> {
+        let (raw_tx, raw_rx) = mpsc::unbounded_channel();
+        self.raw_tx = Some(raw_tx);
+        raw_rx
+    }
+
     /// Get next message from the message queue
     pub async fn next_message(&self) -> Result<Option<WebSocketEvent>> {
         let mut rx = self.message_rx.lock().await;
@@ -379,12 +489,15 @@ impl OkxWebSocketClient {
         let last_pong = self.last_pong.clone();
         let config = self.config.clone();
         let state = self.state.clone();
+        let telemetry = self.telemetry.clone();
+        let alive = self.task_heartbeats.keepalive.clone();
 
         tokio::spawn(async move {
             let mut ticker = interval(Duration::from_secs(config.heartbeat_interval_secs));
 
             loop {
                 ticker.tick().await;
+                alive.beat();
 
                 // Check if we should send ping
                 let current_state = *state.lock().await;
@@ -393,17 +506,18 @@ impl OkxWebSocketClient {
                 }
 
                 // Send ping to public channel
-                if let Some(ws) = public_ws.lock().await.as_mut() {
-                    if let Err(e) = ws.send(WsMessage::Text("ping".to_string().into())).await {
-                        warn!("Failed to send ping to public channel: {}", e);
-                    }
+                telemetry.on_ping_sent().await;
+                if let Some(ws) = public_ws.lock().await.as_mut()
+                    && let Err(e) = ws.send(WsMessage::Text("ping".to_string().into())).await
+                {
+                    warn!("Failed to send ping to public channel: {}", e);
                 }
 
                 // Send ping to private channel
-                if let Some(ws) = private_ws.lock().await.as_mut() {
-                    if let Err(e) = ws.send(WsMessage::Text("ping".to_string().into())).await {
-                        warn!("Failed to send ping to private channel: {}", e);
-                    }
+                if let Some(ws) = private_ws.lock().await.as_mut()
+                    && let Err(e) = ws.send(WsMessage::Text("ping".to_string().into())).await
+                {
+                    warn!("Failed to send ping to private channel: {}", e);
                 }
 
                 // Check pong timeout
@@ -417,103 +531,142 @@ impl OkxWebSocketClient {
     }
 
     /// Start message processor task
-    fn start_message_processor(&self) {
-        let public_ws = self.public_ws.clone();
-        let private_ws = self.private_ws.clone();
-        let message_tx = self.message_tx.clone();
-        let last_pong = self.last_pong.clone();
-        let last_pong_clone = last_pong.clone();
-
-        // Process public channel messages
-        tokio::spawn(async move {
-            loop {
-                let mut ws_guard = public_ws.lock().await;
-                if let Some(ws) = ws_guard.as_mut() {
-                    match ws.next().await {
-                        Some(Ok(msg)) => {
-                            drop(ws_guard); // Release lock before processing
-
-                            if let Err(e) =
-                                Self::process_message(msg, &message_tx, &last_pong).await
-                            {
-                                error!("Error processing public message: {}", e);
-                            }
-                        }
-                        Some(Err(e)) => {
-                            error!("WebSocket error on public channel: {}", e);
-                            break;
-                        }
-                        None => {
-                            warn!("Public WebSocket stream ended");
-                            break;
-                        }
-                    }
-                } else {
-                    break;
-                }
-            }
-        });
-
-        // Process private channel messages
-        let private_ws_clone = private_ws.clone();
-        let message_tx_clone = self.message_tx.clone();
-
-        tokio::spawn(async move {
-            loop {
-                let mut ws_guard = private_ws_clone.lock().await;
-                if let Some(ws) = ws_guard.as_mut() {
-                    match ws.next().await {
+    fn start_message_processor(
+        &self,
+        public_stream: SplitStream<WsStream>,
+        private_stream: SplitStream<WsStream>,
+    ) {
+        for (mut stream, source) in [
+            (public_stream, FrameSource::Public),
+            (private_stream, FrameSource::Private),
+        ] {
+            let (ws, alive) = match source {
+                FrameSource::Public => (
+                    self.public_ws.clone(),
+                    self.task_heartbeats.public_processor.clone(),
+                ),
+                FrameSource::Private => (
+                    self.private_ws.clone(),
+                    self.task_heartbeats.private_processor.clone(),
+                ),
+            };
+            let message_tx = self.message_tx.clone();
+            let raw_tx = self.raw_tx.clone();
+            let telemetry = self.telemetry.clone();
+            let last_pong = self.last_pong.clone();
+            let sequences = self.sequences.clone();
+
+            tokio::spawn(async move {
+                loop {
+                    match stream.next().await {
                         Some(Ok(msg)) => {
-                            drop(ws_guard);
-
-                            if let Err(e) =
-                                Self::process_message(msg, &message_tx_clone, &last_pong_clone)
-                                    .await
+                            alive.beat();
+
+                            if let Err(e) = Self::process_message(
+                                msg,
+                                source,
+                                &ws,
+                                &message_tx,
+                                &raw_tx,
+                                &telemetry,
+                                &last_pong,
+                                &sequences,
+                            )
+                            .await
                             {
-                                error!("Error processing private message: {}", e);
+                                telemetry.on_dropped().await;
+                                error!("Error processing {:?} message: {}", source, e);
                             }
                         }
                         Some(Err(e)) => {
-                            error!("WebSocket error on private channel: {}", e);
+                            error!("WebSocket error on {:?} channel: {}", source, e);
                             break;
                         }
                         None => {
-                            warn!("Private WebSocket stream ended");
+                            warn!("{:?} WebSocket stream ended", source);
                             break;
                         }
                     }
-                } else {
-                    break;
                 }
-            }
-        });
+            });
+        }
     }
 
     /// Process a WebSocket message
+    #[allow(clippy::too_many_arguments)]
     async fn process_message(
         msg: WsMessage,
+        source: FrameSource,
+        ws: &WsHandle,
         tx: &mpsc::UnboundedSender<WebSocketEvent>,
+        raw_tx: &Option<mpsc::UnboundedSender<RawFrame>>,
+        telemetry: &ConnectionTelemetry,
         last_pong: &Arc<Mutex<std::time::Instant>>,
+        sequences: &Mutex<SequenceTracker>,
     ) -> Result<()> {
         match msg {
             WsMessage::Text(text) => {
                 // Handle pong response
                 if text == "pong" {
                     *last_pong.lock().await = std::time::Instant::now();
+                    telemetry.on_pong().await;
                     debug!("Received pong");
                     return Ok(());
                 }
 
+                // Capture before parsing so malformed frames are recorded too
+                if let Some(raw_tx) = raw_tx {
+                    let _ = raw_tx.send(RawFrame {
+                        received_at: Utc::now(),
+                        source,
+                        payload: text.to_string(),
+                    });
+                }
+
                 // Parse JSON message
                 let value: Value = serde_json::from_str(&text)
                     .map_err(|e| Error::ParseError(format!("Invalid JSON: {}", e)))?;
 
-                // Parse into WebSocketEvent
-                let event = WebSocketEvent::from_json(&value)?;
+                // Parse into WebSocketEvents, one per pushed item
+                let events = WebSocketEvent::from_frame(&value)?;
+                telemetry.on_message(message_key(&value)).await;
+
+                for event in events {
+                    // Never hand out a book update that does not follow the last one
+                    if let WebSocketEvent::OrderBook(book) = &event {
+                        let arg = &value["arg"];
+                        let check = sequences.lock().await.check(
+                            arg["channel"].as_str().unwrap_or_default(),
+                            arg["instId"].as_str(),
+                            value["action"].as_str(),
+                            book,
+                            Utc::now(),
+                        );
+                        match check {
+                            SequenceCheck::Accept => {}
+                            SequenceCheck::Discard => {
+                                debug!("Discarding book update while awaiting resync: {}", arg);
+                                continue;
+                            }
+                            SequenceCheck::Gap(gap) => {
+                                warn!(
+                                    "Order book gap on {}: expected prevSeqId {}, got {}",
+                                    arg, gap.expected_prev_seq_id, gap.prev_seq_id
+                                );
+                                telemetry.on_data_gap().await;
+                                tx.send(WebSocketEvent::DataGap(gap)).map_err(|e| {
+                                    Error::Internal(format!("Failed to send message: {}", e))
+                                })?;
+                                Self::resubscribe(ws, arg).await?;
+                                return Ok(());
+                            }
+                        }
+                    }
 
-                // Send to message channel
-                tx.send(event)
-                    .map_err(|e| Error::Internal(format!("Failed to send message: {}", e)))?;
+                    // Send to message channel
+                    tx.send(event)
+                        .map_err(|e| Error::Internal(format!("Failed to send message: {}", e)))?;
+                }
             }
             WsMessage::Binary(_) => {
                 debug!("Received binary message (ignoring)");
@@ -523,6 +676,7 @@ impl OkxWebSocketClient {
             }
             WsMessage::Pong(_) => {
                 *last_pong.lock().await = std::time::Instant::now();
+                telemetry.on_pong().await;
                 debug!("Received pong");
             }
             WsMessage::Close(_) => {
@@ -534,6 +688,22 @@ impl OkxWebSocketClient {
         Ok(())
     }
 
+    /// Unsubscribe and resubscribe `arg` so the exchange pushes a fresh snapshot
+    async fn resubscribe(ws: &WsHandle, arg: &Value) -> Result<()> {
+        let mut ws = ws.lock().await;
+        let Some(ws) = ws.as_mut() else {
+            return Err(Error::WebSocketConnection("Not connected".to_string()));
+        };
+        for op in ["unsubscribe", "subscribe"] {
+            let msg = serde_json::json!({ "op": op, "args": [arg] });
+            ws.send(WsMessage::Text(msg.to_string().into()))
+                .await
+                .map_err(|e| Error::WebSocketSend(e.to_string()))?;
+        }
+        debug!("Requested fresh snapshot for {}", arg);
+        Ok(())
+    }
+
     /// Set connection state
     async fn set_state(&self, state: ConnectionState) {
         *self.state.lock().await = state;
@@ -546,14 +716,14 @@ impl OkxWebSocketClient {
 
         // Close public connection
         if let Some(mut ws) = self.public_ws.lock().await.take() {
-            ws.close(None)
+            ws.close()
                 .await
                 .map_err(|e| Error::WebSocketConnection(e.to_string()))?;
         }
 
         // Close private connection
         if let Some(mut ws) = self.private_ws.lock().await.take() {
-            ws.close(None)
+            ws.close()
                 .await
                 .map_err(|e| Error::WebSocketConnection(e.to_string()))?;
         }
@@ -576,7 +746,7 @@ mod tests {
     #[test]
     fn test_websocket_config_default() {
         let config = WebSocketConfig::default();
-        assert_eq!(config.auto_reconnect, true);
+        assert!(config.auto_reconnect);
         assert_eq!(config.max_reconnect_attempts, 0);
         assert_eq!(config.reconnect_delay_ms, 1000);
         assert_eq!(config.heartbeat_interval_secs, 20);
@@ -587,7 +757,7 @@ mod tests {
         let credentials = Credentials::new("test-key", "test-secret", "test-pass");
         let client = OkxWebSocketClient::new(credentials, true);
         assert_eq!(client.state().await, ConnectionState::Disconnected);
-        assert_eq!(client.is_testnet, true);
+        assert!(client.is_testnet);
     }
 
     #[tokio::test]
@@ -603,8 +773,18 @@ mod tests {
         };
 
         let client = OkxWebSocketClient::with_config(credentials, false, config.clone());
-        assert_eq!(client.config.auto_reconnect, false);
+        assert!(!client.config.auto_reconnect);
         assert_eq!(client.config.max_reconnect_attempts, 5);
         assert_eq!(client.config.reconnect_delay_ms, 2000);
     }
+
+    #[tokio::test]
+    async fn test_client_reports_to_shared_telemetry() {
+        let credentials = Credentials::new("test-key", "test-secret", "test-pass");
+        let feed = ConnectionTelemetry::new();
+        let client = OkxWebSocketClient::new(credentials, true).with_telemetry(feed.clone());
+
+        client.telemetry().on_connected().await;
+        assert!(feed.snapshot().await.connected_at.is_some());
+    }
 }
@@ -0,0 +1,229 @@
+//! REST and WebSocket clients against the in-repo mock OKX server
+
+use ea_okx_client::models::{Channel, SubscriptionRequest, WebSocketEvent};
+use ea_okx_client::{
+    Credentials, Error, OkxAdapter, OkxRestClient, OkxWebSocketClient, RejectionReason,
+};
+use ea_okx_core::exchange::ExchangeAdapter;
+use ea_okx_core::models::{Order, OrderSide, OrderType};
+use ea_okx_core::{Price, Quantity, Symbol};
+use ea_okx_mock::{Fault, MockOkx, OrderScript};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+fn credentials() -> Credentials {
+    Credentials::new("key", "secret", "pass")
+}
+
+async fn mock() -> MockOkx {
+    let mock = MockOkx::start().await.unwrap();
+    mock.add_instrument("BTC-USDT", "0.1", "0.0001", "0.0001");
+    mock
+}
+
+fn rest(mock: &MockOkx) -> Arc<OkxRestClient> {
+    Arc::new(
+        OkxRestClient::new(credentials(), true)
+            .unwrap()
+            .with_base_url(&mock.rest_url())
+            .unwrap(),
+    )
+}
+
+fn limit_buy(quantity: Decimal, price: Decimal) -> Order {
+    Order::new(
+        Uuid::new_v4(),
+        Symbol::new("BTC-USDT").unwrap(),
+        OrderSide::Buy,
+        OrderType::Limit,
+        Quantity::new(quantity).unwrap(),
+        Some(Price::new(price).unwrap()),
+    )
+}
+
+/// Next event the client hands out matching `wanted`, skipping others
+async fn next_matching<T>(
+    ws: &OkxWebSocketClient,
+    mut wanted: impl FnMut(WebSocketEvent) -> Option<T>,
+) -> T {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let event = ws.next_message().await.unwrap().expect("stream ended");
+            if let Some(found) = wanted(event) {
+                return found;
+            }
+        }
+    })
+    .await
+    .expect("timed out waiting for event")
+}
+
+#[tokio::test]
+async fn test_rest_orders_and_scripted_errors() {
+    let mock = mock().await;
+    let client = rest(&mock);
+    let adapter = OkxAdapter::new(client.clone());
+
+    client.server_time().await.unwrap();
+    let instruments = client.instruments("SPOT").await.unwrap();
+    assert_eq!(instruments[0].inst_id, "BTC-USDT");
+
+    let order = limit_buy(dec!(0.01), dec!(50000));
+    let ord_id = adapter.place_order(&order).await.unwrap();
+    let resting = client
+        .order_by_client_id("BTC-USDT", &order.client_order_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        (resting.ord_id.as_str(), resting.state.as_str()),
+        (ord_id.as_str(), "live")
+    );
+
+    // Rejections surface the per-order code
+    mock.script_order(OrderScript::reject("51008", "Insufficient USDT balance"));
+    let refused = adapter
+        .place_order(&limit_buy(dec!(1), dec!(50000)))
+        .await
+        .unwrap_err();
+    assert!(refused.to_string().contains("51008"), "{}", refused);
+    let duplicate = adapter.place_order(&order).await.unwrap_err();
+    assert!(duplicate.to_string().contains("51016"), "{}", duplicate);
+    assert!(
+        client
+            .order_by_client_id("BTC-USDT", "never-placed")
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    // Faults replace the next answer on their path only
+    let instruments_path = "/api/v5/public/instruments";
+    mock.fail_next(instruments_path, Fault::RateLimited);
+    mock.fail_next(
+        instruments_path,
+        Fault::api("50001", "Service temporarily unavailable"),
+    );
+    let limited = client.instruments("SPOT").await.unwrap_err();
+    assert_eq!(
+        RejectionReason::from_error(&limited),
+        RejectionReason::RateLimited
+    );
+    match client.instruments("SPOT").await {
+        Err(Error::ApiError { code, .. }) => assert_eq!(code, "50001"),
+        other => panic!("expected API error, got {:?}", other.map(|i| i.len())),
+    }
+    assert_eq!(client.instruments("SPOT").await.unwrap().len(), 1);
+
+    // Cancelling twice reports the order as already cancelled
+    let symbol = order.symbol.clone();
+    adapter
+        .cancel_order(&symbol, &order.client_order_id)
+        .await
+        .unwrap();
+    let again = adapter
+        .cancel_order(&symbol, &order.client_order_id)
+        .await
+        .unwrap_err();
+    assert!(again.to_string().contains("51401"), "{}", again);
+    assert_eq!(
+        mock.order(&order.client_order_id).unwrap()["state"],
+        "canceled"
+    );
+
+    assert!(mock.requests().iter().all(|r| r.signed));
+}
+
+#[tokio::test]
+async fn test_delayed_ack_leaves_order_live_on_exchange() {
+    let mock = mock().await;
+    let adapter = OkxAdapter::new(rest(&mock));
+
+    mock.script_order(OrderScript::accept().ack_after(Duration::from_millis(500)));
+    let order = limit_buy(dec!(0.01), dec!(50000));
+    let placed =
+        tokio::time::timeout(Duration::from_millis(100), adapter.place_order(&order)).await;
+    assert!(placed.is_err(), "ack should not arrive in time");
+
+    // The order is on the exchange even though its ack never arrived
+    let found = adapter
+        .order(&order.symbol, &order.client_order_id)
+        .await
+        .unwrap()
+        .expect("order placed");
+    assert_eq!(found.client_order_id, order.client_order_id);
+}
+
+#[tokio::test]
+async fn test_websocket_streams_tickers_and_order_fills() {
+    let mock = mock().await;
+    let adapter = OkxAdapter::new(rest(&mock));
+    let mut ws = OkxWebSocketClient::new(credentials(), true)
+        .with_urls(&mock.ws_public_url(), &mock.ws_private_url());
+    ws.connect().await.unwrap();
+
+    let login = next_matching(&ws, |event| match event {
+        WebSocketEvent::Login { code, .. } => Some(code),
+        _ => None,
+    })
+    .await;
+    assert_eq!(login, "0");
+
+    ws.subscribe(vec![
+        SubscriptionRequest::new(Channel::Tickers, "BTC-USDT"),
+        SubscriptionRequest {
+            channel: Channel::Orders,
+            instrument_id: Some("BTC-USDT".to_string()),
+            instrument_type: Some("ANY".to_string()),
+        },
+    ])
+    .await
+    .unwrap();
+    let timeout = Duration::from_secs(5);
+    assert!(
+        mock.wait_for_subscription("tickers", "BTC-USDT", timeout)
+            .await
+    );
+    assert!(
+        mock.wait_for_subscription("orders", "BTC-USDT", timeout)
+            .await
+    );
+
+    mock.push_ticker("BTC-USDT", dec!(50123.4));
+    let last = next_matching(&ws, |event| match event {
+        WebSocketEvent::Ticker(ticker) => Some(ticker.last),
+        _ => None,
+    })
+    .await;
+    assert_eq!(last, "50123.4");
+
+    // A partial fill, then the rest, each reported on the orders channel
+    mock.script_order(
+        OrderScript::accept()
+            .fill(dec!(0.4))
+            .fill_rest()
+            .fill_at(dec!(49990)),
+    );
+    let order = limit_buy(dec!(1), dec!(50000));
+    adapter.place_order(&order).await.unwrap();
+
+    let mut states = Vec::new();
+    while states.last().map(String::as_str) != Some("filled") {
+        let update = next_matching(&ws, |event| match event {
+            WebSocketEvent::Order(update) => Some(update),
+            _ => None,
+        })
+        .await;
+        assert_eq!(update.cl_ord_id, order.client_order_id);
+        if update.state == "partially_filled" {
+            assert_eq!(update.acc_fill_sz, "0.4");
+        }
+        states.push(update.state);
+    }
+    assert_eq!(states, ["live", "partially_filled", "filled"]);
+
+    ws.disconnect().await.unwrap();
+}
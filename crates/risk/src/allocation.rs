@@ -0,0 +1,647 @@
+//! Strategy allocation optimizer
+//!
+//! Chooses capital weights across strategies from their backtest return
+//! series, either by mean-variance optimization or by equalizing each
+//! strategy's contribution to portfolio risk. Weights stay within per
+//! strategy bounds and sum to one; a turnover cap limits how far a single
+//! rebalance moves from the current allocation. Alongside the chosen weights
+//! the optimizer traces the efficient frontier by sweeping risk aversion.
+
+use crate::error::{Error, Result};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+/// Projected gradient and risk parity iterations
+const MAX_ITERATIONS: usize = 5_000;
+const TOLERANCE: f64 = 1e-10;
+
+/// Risk aversion range swept to trace the efficient frontier
+const FRONTIER_MIN_RISK_AVERSION: f64 = 0.1;
+const FRONTIER_MAX_RISK_AVERSION: f64 = 1_000.0;
+
+/// How weights are chosen
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OptimizationMethod {
+    /// Maximize `return - risk_aversion / 2 * variance`
+    MeanVariance { risk_aversion: f64 },
+    /// Equal contribution to portfolio volatility from every strategy
+    RiskParity,
+}
+
+/// Bounds the chosen weights must respect
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AllocationConstraints {
+    /// Smallest weight per strategy (0.0 excludes nothing but allows dropping)
+    pub min_weight: f64,
+
+    /// Largest weight per strategy (0.4 = 40% of capital)
+    pub max_weight: f64,
+
+    /// Largest sum of absolute weight changes against the current allocation
+    /// (0.2 = move at most 20% of capital); unlimited when unset
+    pub max_turnover: Option<f64>,
+}
+
+impl Default for AllocationConstraints {
+    fn default() -> Self {
+        Self {
+            min_weight: 0.0,
+            max_weight: 1.0,
+            max_turnover: None,
+        }
+    }
+}
+
+/// Optimizer configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OptimizerConfig {
+    pub method: OptimizationMethod,
+
+    pub constraints: AllocationConstraints,
+
+    /// Return periods per year, used to annualize (365 for daily returns)
+    pub periods_per_year: f64,
+
+    /// Common return periods required across all strategies
+    pub min_observations: usize,
+
+    /// Points traced on the efficient frontier
+    pub frontier_points: usize,
+}
+
+impl Default for OptimizerConfig {
+    fn default() -> Self {
+        Self {
+            method: OptimizationMethod::RiskParity,
+            constraints: AllocationConstraints::default(),
+            periods_per_year: 365.0,
+            min_observations: 30,
+            frontier_points: 20,
+        }
+    }
+}
+
+impl OptimizerConfig {
+    fn validate(&self, strategies: usize) -> Result<()> {
+        let AllocationConstraints {
+            min_weight,
+            max_weight,
+            max_turnover,
+        } = self.constraints;
+        if min_weight < 0.0 || min_weight > max_weight || max_weight > 1.0 {
+            return Err(Error::ValidationFailed(format!(
+                "Invalid weight bounds [{}, {}]",
+                min_weight, max_weight
+            )));
+        }
+        if (strategies as f64) * max_weight < 1.0 - TOLERANCE
+            || (strategies as f64) * min_weight > 1.0 + TOLERANCE
+        {
+            return Err(Error::ValidationFailed(format!(
+                "Weight bounds [{}, {}] cannot sum to 1 across {} strategies",
+                min_weight, max_weight, strategies
+            )));
+        }
+        if max_turnover.is_some_and(|turnover| turnover < 0.0) {
+            return Err(Error::ValidationFailed(
+                "Max turnover must not be negative".to_string(),
+            ));
+        }
+        if let OptimizationMethod::MeanVariance { risk_aversion } = self.method
+            && risk_aversion <= 0.0
+        {
+            return Err(Error::ValidationFailed(
+                "Risk aversion must be positive".to_string(),
+            ));
+        }
+        if self.periods_per_year <= 0.0 {
+            return Err(Error::ValidationFailed(
+                "Periods per year must be positive".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// One strategy's periodic returns from a backtest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyReturns {
+    pub strategy_id: Uuid,
+
+    /// Return of the period ending at each timestamp (0.01 = +1%)
+    pub returns: Vec<(DateTime<Utc>, f64)>,
+}
+
+impl StrategyReturns {
+    /// Period returns between consecutive points of an equity curve
+    pub fn from_equity_curve(strategy_id: Uuid, curve: &[(DateTime<Utc>, Decimal)]) -> Self {
+        let returns = curve
+            .windows(2)
+            .filter(|pair| pair[0].1 > Decimal::ZERO)
+            .filter_map(|pair| {
+                let ret = (pair[1].1 - pair[0].1) / pair[0].1;
+                Some((pair[1].0, ret.to_f64()?))
+            })
+            .collect();
+        Self {
+            strategy_id,
+            returns,
+        }
+    }
+}
+
+/// A set of weights with its annualized statistics
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PortfolioPoint {
+    pub weights: HashMap<Uuid, f64>,
+    pub expected_return: f64,
+    pub volatility: f64,
+    /// Return over volatility, with a zero risk-free rate
+    pub sharpe_ratio: f64,
+}
+
+/// Chosen allocation and the frontier it was picked from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptimizationResult {
+    pub method: OptimizationMethod,
+    pub allocation: PortfolioPoint,
+    /// Share of portfolio variance each strategy contributes
+    pub risk_contributions: HashMap<Uuid, f64>,
+    /// Sum of absolute weight changes against the current allocation
+    pub turnover: f64,
+    /// Whether the turnover cap held the allocation short of the optimum
+    pub turnover_limited: bool,
+    /// Mean-variance optima from low to high volatility
+    pub frontier: Vec<PortfolioPoint>,
+    /// Common return periods the estimates are based on
+    pub observations: usize,
+}
+
+impl OptimizationResult {
+    /// Capital per strategy when `total` is split by the chosen weights
+    pub fn capital(&self, total: Decimal) -> HashMap<Uuid, Decimal> {
+        self.allocation
+            .weights
+            .iter()
+            .map(|(id, weight)| {
+                let weight = Decimal::from_f64(*weight).unwrap_or_default();
+                (*id, (total * weight).round_dp(2))
+            })
+            .collect()
+    }
+}
+
+/// Annualized return and covariance estimates over aligned series
+struct Moments {
+    ids: Vec<Uuid>,
+    mean: Vec<f64>,
+    covariance: Vec<Vec<f64>>,
+    observations: usize,
+}
+
+impl Moments {
+    fn variance(&self, weights: &[f64]) -> f64 {
+        let sigma_w = mat_vec(&self.covariance, weights);
+        dot(weights, &sigma_w).max(0.0)
+    }
+
+    fn point(&self, weights: &[f64]) -> PortfolioPoint {
+        let expected_return = dot(&self.mean, weights);
+        let volatility = self.variance(weights).sqrt();
+        PortfolioPoint {
+            weights: self
+                .ids
+                .iter()
+                .copied()
+                .zip(weights.iter().copied())
+                .collect(),
+            expected_return,
+            volatility,
+            sharpe_ratio: if volatility > 0.0 {
+                expected_return / volatility
+            } else {
+                0.0
+            },
+        }
+    }
+
+    fn risk_contributions(&self, weights: &[f64]) -> HashMap<Uuid, f64> {
+        let sigma_w = mat_vec(&self.covariance, weights);
+        let variance = dot(weights, &sigma_w);
+        self.ids
+            .iter()
+            .zip(weights.iter().zip(&sigma_w))
+            .map(|(id, (w, s))| {
+                let share = if variance > 0.0 {
+                    w * s / variance
+                } else {
+                    0.0
+                };
+                (*id, share)
+            })
+            .collect()
+    }
+}
+
+/// Allocation optimizer over strategy return series
+#[derive(Debug, Clone)]
+pub struct PortfolioOptimizer {
+    config: OptimizerConfig,
+}
+
+impl PortfolioOptimizer {
+    pub fn new(config: OptimizerConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &OptimizerConfig {
+        &self.config
+    }
+
+    /// Choose weights for `series`, moving from `current` weights (keyed by
+    /// strategy; an empty map means nothing is allocated yet)
+    ///
+    /// Series are aligned on the timestamps they all share. Current weights
+    /// of strategies outside `series` are ignored and the rest are
+    /// renormalized; when the turnover cap binds the result is the point on
+    /// the way from the current allocation to the optimum where the cap is
+    /// reached.
+    pub fn optimize(
+        &self,
+        series: &[StrategyReturns],
+        current: &HashMap<Uuid, f64>,
+    ) -> Result<OptimizationResult> {
+        self.config.validate(series.len())?;
+        let moments = self.moments(series)?;
+        let bounds = (
+            self.config.constraints.min_weight,
+            self.config.constraints.max_weight,
+        );
+
+        let optimum = match self.config.method {
+            OptimizationMethod::MeanVariance { risk_aversion } => {
+                mean_variance(&moments, risk_aversion, bounds)
+            }
+            OptimizationMethod::RiskParity => risk_parity(&moments, bounds)?,
+        };
+
+        let current = normalized_current(&moments.ids, current);
+        let (weights, turnover, turnover_limited) =
+            match (&current, self.config.constraints.max_turnover) {
+                (Some(current), Some(max_turnover)) => {
+                    let full = turnover(current, &optimum);
+                    if full > max_turnover {
+                        let step = max_turnover / full;
+                        let limited: Vec<f64> = current
+                            .iter()
+                            .zip(&optimum)
+                            .map(|(c, o)| c + step * (o - c))
+                            .collect();
+                        (limited, max_turnover, true)
+                    } else {
+                        (optimum, full, false)
+                    }
+                }
+                (Some(current), None) => {
+                    let full = turnover(current, &optimum);
+                    (optimum, full, false)
+                }
+                (None, _) => (optimum, 0.0, false),
+            };
+
+        Ok(OptimizationResult {
+            method: self.config.method,
+            allocation: moments.point(&weights),
+            risk_contributions: moments.risk_contributions(&weights),
+            turnover,
+            turnover_limited,
+            frontier: self.frontier(&moments, bounds),
+            observations: moments.observations,
+        })
+    }
+
+    /// Mean-variance optima across the risk aversion sweep, deduplicated
+    fn frontier(&self, moments: &Moments, bounds: (f64, f64)) -> Vec<PortfolioPoint> {
+        let count = self.config.frontier_points;
+        let mut points: Vec<PortfolioPoint> = Vec::with_capacity(count);
+        let ratio = FRONTIER_MAX_RISK_AVERSION / FRONTIER_MIN_RISK_AVERSION;
+        for i in 0..count {
+            let t = if count > 1 {
+                i as f64 / (count - 1) as f64
+            } else {
+                0.0
+            };
+            let risk_aversion = FRONTIER_MIN_RISK_AVERSION * ratio.powf(t);
+            let point = moments.point(&mean_variance(moments, risk_aversion, bounds));
+            let duplicate = points.iter().any(|p| {
+                (p.volatility - point.volatility).abs() < 1e-9
+                    && (p.expected_return - point.expected_return).abs() < 1e-9
+            });
+            if !duplicate {
+                points.push(point);
+            }
+        }
+        points.sort_by(|a, b| a.volatility.total_cmp(&b.volatility));
+        points
+    }
+
+    fn moments(&self, series: &[StrategyReturns]) -> Result<Moments> {
+        let mut aligned: BTreeMap<DateTime<Utc>, Vec<f64>> = BTreeMap::new();
+        for (index, strategy) in series.iter().enumerate() {
+            for (at, ret) in &strategy.returns {
+                let row = aligned.entry(*at).or_default();
+                if row.len() == index {
+                    row.push(*ret);
+                }
+            }
+        }
+        let rows: Vec<Vec<f64>> = aligned
+            .into_values()
+            .filter(|row| row.len() == series.len())
+            .collect();
+
+        let observations = rows.len();
+        if observations < self.config.min_observations.max(2) {
+            return Err(Error::ValidationFailed(format!(
+                "{} common return periods across strategies, need {}",
+                observations,
+                self.config.min_observations.max(2)
+            )));
+        }
+
+        let n = series.len();
+        let periods = self.config.periods_per_year;
+        let mean: Vec<f64> = (0..n)
+            .map(|i| rows.iter().map(|row| row[i]).sum::<f64>() / observations as f64)
+            .collect();
+        let mut covariance = vec![vec![0.0; n]; n];
+        for i in 0..n {
+            for j in i..n {
+                let cov = rows
+                    .iter()
+                    .map(|row| (row[i] - mean[i]) * (row[j] - mean[j]))
+                    .sum::<f64>()
+                    / (observations - 1) as f64
+                    * periods;
+                covariance[i][j] = cov;
+                covariance[j][i] = cov;
+            }
+        }
+
+        Ok(Moments {
+            ids: series.iter().map(|s| s.strategy_id).collect(),
+            mean: mean.into_iter().map(|m| m * periods).collect(),
+            covariance,
+            observations,
+        })
+    }
+}
+
+/// Projected gradient ascent on `return - risk_aversion / 2 * variance`
+fn mean_variance(moments: &Moments, risk_aversion: f64, bounds: (f64, f64)) -> Vec<f64> {
+    let n = moments.ids.len();
+    // Row-sum bound on the largest covariance eigenvalue keeps the step stable
+    let lipschitz = moments
+        .covariance
+        .iter()
+        .map(|row| row.iter().map(|c| c.abs()).sum::<f64>())
+        .fold(0.0, f64::max)
+        * risk_aversion;
+    let step = 1.0 / lipschitz.max(1e-12);
+
+    let mut weights = project(&vec![1.0 / n as f64; n], bounds);
+    for _ in 0..MAX_ITERATIONS {
+        let sigma_w = mat_vec(&moments.covariance, &weights);
+        let ascended: Vec<f64> = weights
+            .iter()
+            .zip(moments.mean.iter().zip(&sigma_w))
+            .map(|(w, (mu, s))| w + step * (mu - risk_aversion * s))
+            .collect();
+        let next = project(&ascended, bounds);
+        let moved = turnover(&weights, &next);
+        weights = next;
+        if moved < TOLERANCE {
+            break;
+        }
+    }
+    weights
+}
+
+/// Multiplicative updates towards equal risk contributions, starting from
+/// inverse volatility
+fn risk_parity(moments: &Moments, bounds: (f64, f64)) -> Result<Vec<f64>> {
+    let n = moments.ids.len();
+    if let Some(i) = (0..n).find(|&i| moments.covariance[i][i] <= 0.0) {
+        return Err(Error::CalculationError(format!(
+            "Strategy {} has no return variance",
+            moments.ids[i]
+        )));
+    }
+
+    let inverse_vol: Vec<f64> = (0..n)
+        .map(|i| 1.0 / moments.covariance[i][i].sqrt())
+        .collect();
+    let total: f64 = inverse_vol.iter().sum();
+    let mut weights = project(
+        &inverse_vol.iter().map(|v| v / total).collect::<Vec<_>>(),
+        bounds,
+    );
+
+    for _ in 0..MAX_ITERATIONS {
+        let sigma_w = mat_vec(&moments.covariance, &weights);
+        let variance = dot(&weights, &sigma_w);
+        if variance <= 0.0 {
+            break;
+        }
+        let target = variance / n as f64;
+        let scaled: Vec<f64> = weights
+            .iter()
+            .zip(&sigma_w)
+            .map(|(w, s)| {
+                let contribution = w * s;
+                if contribution > 0.0 {
+                    w * (target / contribution).sqrt()
+                } else {
+                    *w
+                }
+            })
+            .collect();
+        let total: f64 = scaled.iter().sum();
+        let next = project(
+            &scaled.iter().map(|w| w / total).collect::<Vec<_>>(),
+            bounds,
+        );
+        let moved = turnover(&weights, &next);
+        weights = next;
+        if moved < TOLERANCE {
+            break;
+        }
+    }
+    Ok(weights)
+}
+
+/// Euclidean projection onto `{w : min <= w_i <= max, sum(w) = 1}`
+fn project(values: &[f64], (min, max): (f64, f64)) -> Vec<f64> {
+    let sum_at = |shift: f64| -> f64 { values.iter().map(|v| (v - shift).clamp(min, max)).sum() };
+
+    let mut low = values.iter().copied().fold(f64::INFINITY, f64::min) - max;
+    let mut high = values.iter().copied().fold(f64::NEG_INFINITY, f64::max) - min;
+    for _ in 0..200 {
+        let mid = (low + high) / 2.0;
+        if sum_at(mid) > 1.0 {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    let shift = (low + high) / 2.0;
+    values.iter().map(|v| (v - shift).clamp(min, max)).collect()
+}
+
+fn normalized_current(ids: &[Uuid], current: &HashMap<Uuid, f64>) -> Option<Vec<f64>> {
+    let weights: Vec<f64> = ids
+        .iter()
+        .map(|id| current.get(id).copied().unwrap_or(0.0).max(0.0))
+        .collect();
+    let total: f64 = weights.iter().sum();
+    (total > 0.0).then(|| weights.iter().map(|w| w / total).collect())
+}
+
+fn turnover(from: &[f64], to: &[f64]) -> f64 {
+    from.iter().zip(to).map(|(a, b)| (a - b).abs()).sum()
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn mat_vec(matrix: &[Vec<f64>], vector: &[f64]) -> Vec<f64> {
+    matrix.iter().map(|row| dot(row, vector)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    /// Daily returns alternating around `drift` with amplitude `vol`,
+    /// phase-shifted so series are uncorrelated
+    fn series(id: Uuid, drift: f64, vol: f64, phase: usize) -> StrategyReturns {
+        let start = DateTime::<Utc>::from_timestamp(1_700_000_000, 0).unwrap();
+        let returns = (0..120)
+            .map(|i| {
+                let sign = if ((i + phase) / (1 + phase)).is_multiple_of(2) {
+                    1.0
+                } else {
+                    -1.0
+                };
+                (start + Duration::days(i as i64), drift + sign * vol)
+            })
+            .collect();
+        StrategyReturns {
+            strategy_id: id,
+            returns,
+        }
+    }
+
+    fn optimizer(
+        method: OptimizationMethod,
+        constraints: AllocationConstraints,
+    ) -> PortfolioOptimizer {
+        PortfolioOptimizer::new(OptimizerConfig {
+            method,
+            constraints,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_risk_parity_equalizes_contributions() {
+        let (calm, wild) = (Uuid::new_v4(), Uuid::new_v4());
+        let input = [series(calm, 0.001, 0.01, 0), series(wild, 0.001, 0.03, 1)];
+        let result = optimizer(OptimizationMethod::RiskParity, Default::default())
+            .optimize(&input, &HashMap::new())
+            .unwrap();
+
+        let weights = &result.allocation.weights;
+        assert!((weights.values().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(weights[&calm] > 2.0 * weights[&wild]);
+        for share in result.risk_contributions.values() {
+            assert!((share - 0.5).abs() < 1e-3, "contribution {}", share);
+        }
+        assert!(!result.frontier.is_empty());
+        assert!(
+            result
+                .frontier
+                .windows(2)
+                .all(|pair| pair[0].volatility <= pair[1].volatility)
+        );
+    }
+
+    #[test]
+    fn test_mean_variance_respects_max_weight_and_turnover() {
+        let ids: Vec<Uuid> = (0..3).map(|_| Uuid::new_v4()).collect();
+        let input = [
+            series(ids[0], 0.004, 0.01, 0),
+            series(ids[1], 0.001, 0.01, 1),
+            series(ids[2], 0.0005, 0.01, 2),
+        ];
+        let constraints = AllocationConstraints {
+            max_weight: 0.5,
+            ..Default::default()
+        };
+        let free = optimizer(
+            OptimizationMethod::MeanVariance { risk_aversion: 2.0 },
+            constraints.clone(),
+        )
+        .optimize(&input, &HashMap::new())
+        .unwrap();
+        let weights = &free.allocation.weights;
+        assert!((weights[&ids[0]] - 0.5).abs() < 1e-6);
+        assert!(weights.values().all(|w| *w <= 0.5 + 1e-9));
+
+        let current = HashMap::from([(ids[1], 1.0), (ids[2], 1.0)]);
+        let limited = optimizer(
+            OptimizationMethod::MeanVariance { risk_aversion: 2.0 },
+            AllocationConstraints {
+                max_turnover: Some(0.2),
+                ..constraints
+            },
+        )
+        .optimize(&input, &current)
+        .unwrap();
+        assert!(limited.turnover_limited);
+        assert!((limited.turnover - 0.2).abs() < 1e-9);
+        assert!((limited.allocation.weights[&ids[0]] - 0.1).abs() < 1e-6);
+
+        let capital = limited.capital(Decimal::from(10_000));
+        assert_eq!(capital[&ids[0]], Decimal::from(1_000));
+    }
+
+    #[test]
+    fn test_rejects_short_or_infeasible_input() {
+        let ids = [Uuid::new_v4(), Uuid::new_v4()];
+        let input = [series(ids[0], 0.0, 0.01, 0), series(ids[1], 0.0, 0.01, 1)];
+        let too_tight = optimizer(
+            OptimizationMethod::RiskParity,
+            AllocationConstraints {
+                max_weight: 0.4,
+                ..Default::default()
+            },
+        );
+        assert!(too_tight.optimize(&input, &HashMap::new()).is_err());
+
+        let mut short = input.clone();
+        short[1].returns.truncate(10);
+        assert!(
+            optimizer(OptimizationMethod::RiskParity, Default::default())
+                .optimize(&short, &HashMap::new())
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,399 @@
+//! Drawdown-aware position throttling
+//!
+//! New positions shrink as the portfolio falls from its equity peak. A
+//! [`DrawdownSchedule`] maps drawdown bands to a size factor; the default
+//! trades full size up to a 5% drawdown, half size up to 10% and opens
+//! nothing beyond. Strategies can be given their own schedule in place of
+//! the global one. Orders that only reduce a position are never throttled,
+//! so a strategy can always get flat.
+
+use crate::error::{Error, Result};
+use chrono::{DateTime, Utc};
+use ea_okx_core::models::Order;
+use ea_okx_core::types::Quantity;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Size factor applied from a drawdown onward
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DrawdownBand {
+    /// Drawdown from the equity peak the band starts at (e.g., 0.05 = 5%)
+    pub from_drawdown: Decimal,
+
+    /// Share of the requested size allowed, from 0 (no new positions) to 1
+    pub factor: Decimal,
+}
+
+/// Size factors by drawdown; below the first band orders go through in full
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DrawdownSchedule {
+    /// Bands in increasing drawdown order
+    pub bands: Vec<DrawdownBand>,
+}
+
+impl Default for DrawdownSchedule {
+    fn default() -> Self {
+        Self {
+            bands: vec![
+                DrawdownBand {
+                    from_drawdown: dec!(0.05),
+                    factor: dec!(0.5),
+                },
+                DrawdownBand {
+                    from_drawdown: dec!(0.10),
+                    factor: Decimal::ZERO,
+                },
+            ],
+        }
+    }
+}
+
+impl DrawdownSchedule {
+    /// Factor of the deepest band `drawdown` has reached
+    pub fn factor(&self, drawdown: Decimal) -> Decimal {
+        self.bands
+            .iter()
+            .rev()
+            .find(|band| drawdown >= band.from_drawdown)
+            .map_or(Decimal::ONE, |band| band.factor)
+    }
+
+    fn validate(&self) -> Result<()> {
+        for band in &self.bands {
+            if band.from_drawdown < Decimal::ZERO || band.from_drawdown > Decimal::ONE {
+                return Err(Error::ValidationFailed(format!(
+                    "Drawdown band must start within [0, 1], got {}",
+                    band.from_drawdown
+                )));
+            }
+            if band.factor < Decimal::ZERO || band.factor > Decimal::ONE {
+                return Err(Error::ValidationFailed(format!(
+                    "Drawdown factor must be within [0, 1], got {}",
+                    band.factor
+                )));
+            }
+        }
+        if self
+            .bands
+            .windows(2)
+            .any(|pair| pair[0].from_drawdown >= pair[1].from_drawdown)
+        {
+            return Err(Error::ValidationFailed(
+                "Drawdown bands must be in increasing drawdown order".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Global schedule and per-strategy overrides
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DrawdownThrottleConfig {
+    pub global: DrawdownSchedule,
+
+    /// Schedules replacing the global one for a strategy ID
+    #[serde(default)]
+    pub strategies: HashMap<Uuid, DrawdownSchedule>,
+}
+
+/// How an order's size was throttled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThrottleDecision {
+    /// Portfolio drawdown the factor was chosen at
+    pub drawdown: Decimal,
+    pub factor: Decimal,
+    pub requested: Decimal,
+    pub allowed: Decimal,
+}
+
+impl ThrottleDecision {
+    /// Whether the order was refused: nothing of it is allowed
+    pub fn is_blocked(&self) -> bool {
+        self.allowed <= Decimal::ZERO
+    }
+
+    pub fn describe(&self) -> String {
+        let drawdown = (self.drawdown * Decimal::ONE_HUNDRED).round_dp(2);
+        if self.is_blocked() {
+            format!("New positions halted at {}% drawdown", drawdown)
+        } else if self.allowed < self.requested {
+            format!(
+                "Scaled from {} to {} (factor {}) at {}% drawdown",
+                self.requested, self.allowed, self.factor, drawdown
+            )
+        } else {
+            format!("Full size at {}% drawdown", drawdown)
+        }
+    }
+}
+
+/// Current throttle state, suitable for publishing as metrics
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DrawdownThrottleSnapshot {
+    pub equity: Option<Decimal>,
+    pub peak_equity: Option<Decimal>,
+    pub drawdown: Decimal,
+
+    /// Factor of the global schedule at the current drawdown
+    pub factor: Decimal,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Scales new positions down as portfolio drawdown deepens
+#[derive(Debug, Clone, Default)]
+pub struct DrawdownThrottle {
+    config: DrawdownThrottleConfig,
+    equity: Option<Decimal>,
+    peak_equity: Option<Decimal>,
+    updated_at: Option<DateTime<Utc>>,
+}
+
+impl DrawdownThrottle {
+    pub fn new(config: DrawdownThrottleConfig) -> Result<Self> {
+        config.global.validate()?;
+        for schedule in config.strategies.values() {
+            schedule.validate()?;
+        }
+
+        Ok(Self {
+            config,
+            equity: None,
+            peak_equity: None,
+            updated_at: None,
+        })
+    }
+
+    pub fn config(&self) -> &DrawdownThrottleConfig {
+        &self.config
+    }
+
+    pub fn set_global_schedule(&mut self, schedule: DrawdownSchedule) -> Result<()> {
+        schedule.validate()?;
+        self.config.global = schedule;
+        Ok(())
+    }
+
+    pub fn set_strategy_schedule(
+        &mut self,
+        strategy_id: Uuid,
+        schedule: DrawdownSchedule,
+    ) -> Result<()> {
+        schedule.validate()?;
+        self.config.strategies.insert(strategy_id, schedule);
+        Ok(())
+    }
+
+    /// Drop a strategy's schedule so the global one applies again
+    pub fn clear_strategy_schedule(&mut self, strategy_id: Uuid) -> bool {
+        self.config.strategies.remove(&strategy_id).is_some()
+    }
+
+    /// Record the portfolio's current equity, raising the peak when it is
+    /// a new high
+    ///
+    /// Returns the drawdown from the peak.
+    pub fn record_equity(&mut self, equity: Decimal, at: DateTime<Utc>) -> Decimal {
+        self.equity = Some(equity);
+        if self.peak_equity.is_none_or(|peak| equity > peak) {
+            self.peak_equity = Some(equity);
+        }
+        self.updated_at = Some(at);
+        self.drawdown()
+    }
+
+    /// Drawdown from the equity peak (e.g., 0.07 = 7% below it)
+    pub fn drawdown(&self) -> Decimal {
+        match (self.equity, self.peak_equity) {
+            (Some(equity), Some(peak)) if peak > Decimal::ZERO && equity < peak => {
+                (peak - equity) / peak
+            }
+            _ => Decimal::ZERO,
+        }
+    }
+
+    /// Size factor for the strategy's new positions at the current drawdown
+    pub fn factor(&self, strategy_id: Uuid) -> Decimal {
+        self.config
+            .strategies
+            .get(&strategy_id)
+            .unwrap_or(&self.config.global)
+            .factor(self.drawdown())
+    }
+
+    /// Scale `order` by its strategy's factor
+    ///
+    /// The scaled size is rounded down to `lot_size`. Reduce-only orders
+    /// keep their size. A blocked order is left untouched for the caller
+    /// to refuse.
+    pub fn apply(&self, order: &mut Order, lot_size: Option<Decimal>) -> Result<ThrottleDecision> {
+        let requested = order.quantity.as_decimal();
+        let factor = if order.reduce_only {
+            Decimal::ONE
+        } else {
+            self.factor(order.strategy_id)
+        };
+        let mut decision = ThrottleDecision {
+            drawdown: self.drawdown(),
+            factor,
+            requested,
+            allowed: requested,
+        };
+        if factor >= Decimal::ONE {
+            return Ok(decision);
+        }
+
+        decision.allowed = match lot_size {
+            Some(lot) if lot > Decimal::ZERO => (requested * factor / lot).floor() * lot,
+            _ => requested * factor,
+        };
+        if !decision.is_blocked() {
+            order.quantity = Quantity::new(decision.allowed)?;
+        }
+        Ok(decision)
+    }
+
+    pub fn snapshot(&self) -> DrawdownThrottleSnapshot {
+        let drawdown = self.drawdown();
+        DrawdownThrottleSnapshot {
+            equity: self.equity,
+            peak_equity: self.peak_equity,
+            drawdown,
+            factor: self.config.global.factor(drawdown),
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ea_okx_core::Symbol;
+    use ea_okx_core::models::{OrderSide, OrderType};
+
+    fn order(strategy_id: Uuid, quantity: Decimal) -> Order {
+        Order::new(
+            strategy_id,
+            Symbol::new("BTC-USDT").unwrap(),
+            OrderSide::Buy,
+            OrderType::Market,
+            Quantity::new(quantity).unwrap(),
+            None,
+        )
+    }
+
+    fn throttle_at(drawdown: Decimal) -> DrawdownThrottle {
+        let mut throttle = DrawdownThrottle::default();
+        let now = Utc::now();
+        throttle.record_equity(dec!(100000), now);
+        throttle.record_equity(dec!(100000) * (Decimal::ONE - drawdown), now);
+        throttle
+    }
+
+    #[test]
+    fn test_default_schedule_steps_down() {
+        let schedule = DrawdownSchedule::default();
+        assert_eq!(schedule.factor(dec!(0)), dec!(1));
+        assert_eq!(schedule.factor(dec!(0.0499)), dec!(1));
+        assert_eq!(schedule.factor(dec!(0.05)), dec!(0.5));
+        assert_eq!(schedule.factor(dec!(0.0999)), dec!(0.5));
+        assert_eq!(schedule.factor(dec!(0.10)), dec!(0));
+        assert_eq!(schedule.factor(dec!(0.35)), dec!(0));
+    }
+
+    #[test]
+    fn test_drawdown_is_measured_from_the_peak() {
+        let mut throttle = DrawdownThrottle::default();
+        let now = Utc::now();
+        assert_eq!(throttle.record_equity(dec!(1000), now), dec!(0));
+        assert_eq!(throttle.record_equity(dec!(1200), now), dec!(0));
+        assert_eq!(throttle.record_equity(dec!(1080), now), dec!(0.1));
+
+        let snapshot = throttle.snapshot();
+        assert_eq!(snapshot.peak_equity, Some(dec!(1200)));
+        assert_eq!(snapshot.factor, dec!(0));
+
+        // Recovering lifts the throttle again
+        throttle.record_equity(dec!(1150), now);
+        assert_eq!(throttle.snapshot().factor, dec!(1));
+    }
+
+    #[test]
+    fn test_scales_new_positions_to_lot_size() {
+        let throttle = throttle_at(dec!(0.07));
+        let mut buy = order(Uuid::new_v4(), dec!(0.333));
+
+        let decision = throttle.apply(&mut buy, Some(dec!(0.01))).unwrap();
+        assert_eq!(decision.factor, dec!(0.5));
+        assert_eq!(decision.allowed, dec!(0.16));
+        assert_eq!(buy.quantity.as_decimal(), dec!(0.16));
+        assert!(!decision.is_blocked());
+    }
+
+    #[test]
+    fn test_blocks_new_positions_but_not_reductions() {
+        let throttle = throttle_at(dec!(0.12));
+        let mut buy = order(Uuid::new_v4(), dec!(1));
+        let decision = throttle.apply(&mut buy, None).unwrap();
+        assert!(decision.is_blocked());
+        assert_eq!(buy.quantity.as_decimal(), dec!(1));
+
+        let mut close = order(Uuid::new_v4(), dec!(1)).with_reduce_only();
+        let decision = throttle.apply(&mut close, None).unwrap();
+        assert_eq!(decision.allowed, dec!(1));
+    }
+
+    #[test]
+    fn test_strategy_schedule_overrides_global() {
+        let mut throttle = throttle_at(dec!(0.12));
+        let tolerant = Uuid::new_v4();
+        throttle
+            .set_strategy_schedule(
+                tolerant,
+                DrawdownSchedule {
+                    bands: vec![DrawdownBand {
+                        from_drawdown: dec!(0.1),
+                        factor: dec!(0.25),
+                    }],
+                },
+            )
+            .unwrap();
+
+        assert_eq!(throttle.factor(tolerant), dec!(0.25));
+        assert_eq!(throttle.factor(Uuid::new_v4()), dec!(0));
+        assert!(throttle.clear_strategy_schedule(tolerant));
+        assert_eq!(throttle.factor(tolerant), dec!(0));
+    }
+
+    #[test]
+    fn test_rejects_invalid_schedule() {
+        let mut throttle = DrawdownThrottle::default();
+        let unordered = DrawdownSchedule {
+            bands: vec![
+                DrawdownBand {
+                    from_drawdown: dec!(0.1),
+                    factor: dec!(0.5),
+                },
+                DrawdownBand {
+                    from_drawdown: dec!(0.05),
+                    factor: dec!(0),
+                },
+            ],
+        };
+        assert!(throttle.set_global_schedule(unordered).is_err());
+
+        let amplifying = DrawdownSchedule {
+            bands: vec![DrawdownBand {
+                from_drawdown: dec!(0.05),
+                factor: dec!(1.5),
+            }],
+        };
+        assert!(
+            throttle
+                .set_strategy_schedule(Uuid::new_v4(), amplifying)
+                .is_err()
+        );
+    }
+}
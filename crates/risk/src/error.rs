@@ -25,6 +25,15 @@ pub enum Error {
 
     #[error("Calculation error: {0}")]
     CalculationError(String),
+
+    #[error("Limit change not found: {0}")]
+    LimitChangeNotFound(uuid::Uuid),
+
+    #[error("Approval denied: {0}")]
+    ApprovalDenied(String),
+
+    #[error("Audit log error: {0}")]
+    AuditLogError(String),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
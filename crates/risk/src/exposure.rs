@@ -0,0 +1,221 @@
+//! Net and gross exposure of a portfolio
+//!
+//! [`ExposureBreakdown`] groups open positions by symbol, strategy,
+//! instrument kind and settlement currency. Each position contributes its
+//! notional at the current price, in the symbol's quote currency: longs add
+//! to net exposure and shorts take from it, while both add to gross.
+//! Percentages are of the portfolio's total equity, and left out when there
+//! is no equity to measure against.
+
+use crate::validators::PortfolioState;
+use ea_okx_core::models::{Position, PositionSide};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+/// Decimal places percentages of equity are rounded to
+const PERCENT_DP: u32 = 4;
+
+/// Exposure of one group of positions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Exposure {
+    /// Symbol, strategy ID, instrument kind or currency the group shares
+    pub key: String,
+
+    /// Notional of long positions
+    pub long: Decimal,
+
+    /// Notional of short positions, as a positive amount
+    pub short: Decimal,
+
+    /// Long minus short notional
+    pub net: Decimal,
+
+    /// Long plus short notional
+    pub gross: Decimal,
+
+    /// Net notional as a percentage of total equity
+    pub net_pct_of_equity: Option<Decimal>,
+
+    /// Gross notional as a percentage of total equity
+    pub gross_pct_of_equity: Option<Decimal>,
+
+    /// Unrealized profit/loss of the group's positions
+    pub unrealized_pnl: Decimal,
+
+    /// Open positions in the group
+    pub positions: usize,
+}
+
+impl Exposure {
+    fn new(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            long: Decimal::ZERO,
+            short: Decimal::ZERO,
+            net: Decimal::ZERO,
+            gross: Decimal::ZERO,
+            net_pct_of_equity: None,
+            gross_pct_of_equity: None,
+            unrealized_pnl: Decimal::ZERO,
+            positions: 0,
+        }
+    }
+
+    fn add(&mut self, position: &Position) {
+        let notional = position.position_value().abs();
+        match position.side {
+            PositionSide::Short => self.short += notional,
+            PositionSide::Long | PositionSide::Net => self.long += notional,
+        }
+        self.net = self.long - self.short;
+        self.gross = self.long + self.short;
+        self.unrealized_pnl += position.unrealized_pnl;
+        self.positions += 1;
+    }
+
+    fn with_equity(mut self, equity: Decimal) -> Self {
+        if equity > Decimal::ZERO {
+            let pct = |amount: Decimal| (amount / equity * dec!(100)).round_dp(PERCENT_DP);
+            self.net_pct_of_equity = Some(pct(self.net));
+            self.gross_pct_of_equity = Some(pct(self.gross));
+        }
+        self
+    }
+}
+
+/// Exposure of a portfolio, in total and per group
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExposureBreakdown {
+    /// Equity percentages are measured against
+    pub total_equity: Decimal,
+
+    /// Every open position, keyed `total`
+    pub total: Exposure,
+
+    /// Per symbol, largest gross exposure first, as are the other groups
+    pub by_symbol: Vec<Exposure>,
+
+    /// Per strategy ID
+    pub by_strategy: Vec<Exposure>,
+
+    /// Per instrument kind (`SPOT`, `SWAP`, `FUTURES`, `OPTION`)
+    pub by_instrument_kind: Vec<Exposure>,
+
+    /// Per settlement currency
+    pub by_settlement_currency: Vec<Exposure>,
+}
+
+impl ExposureBreakdown {
+    /// Break down the exposure of the open ones among `positions`
+    pub fn from_positions(positions: &[Position], total_equity: Decimal) -> Self {
+        let open: Vec<&Position> = positions.iter().filter(|p| !p.is_closed()).collect();
+        let group = |key: fn(&Position) -> String| -> Vec<Exposure> {
+            let mut groups: BTreeMap<String, Exposure> = BTreeMap::new();
+            for position in &open {
+                let key = key(position);
+                groups
+                    .entry(key.clone())
+                    .or_insert_with(|| Exposure::new(key))
+                    .add(position);
+            }
+            let mut groups: Vec<Exposure> = groups
+                .into_values()
+                .map(|g| g.with_equity(total_equity))
+                .collect();
+            // Stable, so equal exposures stay in key order
+            groups.sort_by_key(|g| Reverse(g.gross));
+            groups
+        };
+
+        let mut total = Exposure::new("total");
+        for position in &open {
+            total.add(position);
+        }
+
+        Self {
+            total_equity,
+            total: total.with_equity(total_equity),
+            by_symbol: group(|p| p.symbol.as_str().to_string()),
+            by_strategy: group(|p| p.strategy_id.to_string()),
+            by_instrument_kind: group(|p| p.symbol.kind().to_string()),
+            by_settlement_currency: group(|p| p.symbol.settlement_currency().to_string()),
+        }
+    }
+}
+
+impl PortfolioState {
+    /// Net and gross exposure of the open positions
+    pub fn exposure(&self) -> ExposureBreakdown {
+        ExposureBreakdown::from_positions(&self.positions, self.total_equity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ea_okx_core::{Price, Quantity, Symbol};
+    use uuid::Uuid;
+
+    fn position(strategy: Uuid, symbol: &str, side: PositionSide, qty: Decimal) -> Position {
+        let mut position = Position::new(
+            strategy,
+            Symbol::new(symbol).unwrap(),
+            side,
+            Quantity::new(qty).unwrap(),
+            Price::new(dec!(100)).unwrap(),
+        );
+        position.update_price(Price::new(dec!(110)).unwrap());
+        position
+    }
+
+    #[test]
+    fn test_exposure_breakdown() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let portfolio = PortfolioState {
+            total_equity: dec!(1000),
+            available_margin: dec!(1000),
+            positions: vec![
+                position(a, "BTC-USDT", PositionSide::Long, dec!(2)),
+                position(a, "BTC-USDT-SWAP", PositionSide::Short, dec!(1)),
+                position(b, "BTC-USD-SWAP", PositionSide::Long, dec!(4)),
+                position(b, "ETH-USDT", PositionSide::Long, dec!(0)),
+            ],
+            daily_pnl: Decimal::ZERO,
+        };
+
+        let exposure = portfolio.exposure();
+        assert_eq!(exposure.total.long, dec!(660));
+        assert_eq!(exposure.total.short, dec!(110));
+        assert_eq!(exposure.total.net, dec!(550));
+        assert_eq!(exposure.total.gross, dec!(770));
+        assert_eq!(exposure.total.net_pct_of_equity, Some(dec!(55)));
+        assert_eq!(exposure.total.gross_pct_of_equity, Some(dec!(77)));
+        assert_eq!(exposure.total.unrealized_pnl, dec!(50));
+        assert_eq!(exposure.total.positions, 3);
+
+        let keys = |groups: &[Exposure]| groups.iter().map(|g| g.key.clone()).collect::<Vec<_>>();
+        assert_eq!(
+            keys(&exposure.by_symbol),
+            ["BTC-USD-SWAP", "BTC-USDT", "BTC-USDT-SWAP"]
+        );
+        assert_eq!(keys(&exposure.by_instrument_kind), ["SWAP", "SPOT"]);
+        assert_eq!(keys(&exposure.by_settlement_currency), ["BTC", "USDT"]);
+
+        let swaps = &exposure.by_instrument_kind[0];
+        assert_eq!(swaps.net, dec!(330));
+        assert_eq!(swaps.gross, dec!(550));
+        let strategy_a = exposure
+            .by_strategy
+            .iter()
+            .find(|g| g.key == a.to_string())
+            .unwrap();
+        assert_eq!(strategy_a.net, dec!(110));
+        assert_eq!(strategy_a.gross_pct_of_equity, Some(dec!(33)));
+
+        let no_equity = ExposureBreakdown::from_positions(&portfolio.positions, Decimal::ZERO);
+        assert_eq!(no_equity.total.net_pct_of_equity, None);
+    }
+}
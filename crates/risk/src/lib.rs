@@ -1,10 +1,38 @@
+pub mod allocation;
+pub mod drawdown_throttle;
 pub mod error;
+pub mod exposure;
+pub mod limit_changes;
+pub mod passcode;
+pub mod stress;
 pub mod validators;
 pub mod var;
+pub mod var_budget;
+pub mod vol_target;
 
+pub use allocation::{
+    AllocationConstraints, OptimizationMethod, OptimizationResult, OptimizerConfig,
+    PortfolioOptimizer, PortfolioPoint, StrategyReturns,
+};
+pub use drawdown_throttle::{
+    DrawdownBand, DrawdownSchedule, DrawdownThrottle, DrawdownThrottleConfig,
+    DrawdownThrottleSnapshot, ThrottleDecision,
+};
 pub use error::{Error, Result};
+pub use exposure::{Exposure, ExposureBreakdown};
+pub use limit_changes::{
+    ApprovalPolicy, AuditAction, AuditEntry, AuditQuery, LimitChange, LimitChangeManager,
+    LimitChangeStatus,
+};
+pub use passcode::PasscodeHash;
+pub use stress::{
+    AssetClass, PositionStress, PriceShock, ShockTarget, StressConfig, StressResult,
+    StressScenario, StressTester,
+};
 pub use validators::{
     PortfolioState, PreTradeValidator, RiskLimits, RiskViolation, ValidationResult,
     ViolationSeverity,
 };
 pub use var::{VarCalculator, VarConfig, VarMethod, VarResult};
+pub use var_budget::{MarginalVar, VarBudget, marginal_var, returns_from_closes};
+pub use vol_target::{EwmaVolatility, VolTargetConfig, VolTargetOverlay, VolTargetSnapshot};
@@ -0,0 +1,694 @@
+//! Two-step approval for risk limit changes
+//!
+//! A new set of [`RiskLimits`] is first proposed, then confirmed (by a second
+//! person and/or with a passcode, depending on the [`ApprovalPolicy`]), and
+//! takes effect at its scheduled time. Only one change may be open at once so
+//! a confirmation always applies to the limits it was reviewed against.
+//!
+//! Every step is appended to an audit log whose entries are hash-chained: an
+//! entry records the hash of the one before it, so editing or dropping an
+//! entry breaks [`AuditEntry::hash`] verification from that point on. With a
+//! journal file the log is appended to disk as it grows and replayed on open,
+//! which also restores the active limits and any open change.
+
+use crate::error::{Error, Result};
+use crate::passcode::PasscodeHash;
+use crate::validators::RiskLimits;
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sha2::{Digest, Sha256};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Who may confirm a proposed change and when it may take effect
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApprovalPolicy {
+    /// The confirmer must not be the proposer
+    pub require_second_approver: bool,
+
+    /// Salted hash of the passcode required to confirm, if any
+    pub passcode: Option<PasscodeHash>,
+
+    /// Earliest a change may take effect after it is proposed
+    pub min_delay_secs: i64,
+}
+
+impl ApprovalPolicy {
+    /// Require `passcode` to confirm changes
+    pub fn with_passcode(mut self, passcode: &str) -> Self {
+        self.passcode = Some(PasscodeHash::new(passcode));
+        self
+    }
+
+    fn passcode_matches(&self, passcode: Option<&str>) -> bool {
+        match (&self.passcode, passcode) {
+            (None, _) => true,
+            (Some(expected), Some(given)) => expected.verify(given),
+            (Some(_), None) => false,
+        }
+    }
+}
+
+/// Where a limit change is in the workflow
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitChangeStatus {
+    /// Awaiting confirmation
+    Pending,
+    /// Confirmed, waiting for its effective time
+    Scheduled,
+    /// In force
+    Applied,
+    Cancelled,
+}
+
+impl LimitChangeStatus {
+    pub fn is_open(&self) -> bool {
+        matches!(
+            self,
+            LimitChangeStatus::Pending | LimitChangeStatus::Scheduled
+        )
+    }
+}
+
+/// A proposed replacement of the active risk limits
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LimitChange {
+    pub id: Uuid,
+    pub status: LimitChangeStatus,
+
+    /// Limits in force when the change was proposed
+    pub previous: RiskLimits,
+    pub proposed: RiskLimits,
+
+    pub proposed_by: String,
+    pub proposed_at: DateTime<Utc>,
+    pub reason: Option<String>,
+    pub effective_at: DateTime<Utc>,
+
+    pub confirmed_by: Option<String>,
+    pub confirmed_at: Option<DateTime<Utc>>,
+    pub applied_at: Option<DateTime<Utc>>,
+}
+
+/// Workflow step recorded in the audit log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Proposed,
+    Confirmed,
+    /// A confirmation attempt failed the approval policy
+    ConfirmationDenied,
+    Applied,
+    Cancelled,
+}
+
+/// One immutable audit log entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub sequence: u64,
+    pub at: DateTime<Utc>,
+    pub actor: String,
+    pub action: AuditAction,
+
+    /// The change as it stood after this step
+    pub change: LimitChange,
+    pub note: Option<String>,
+
+    /// Hash of the previous entry, empty for the first
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+impl AuditEntry {
+    /// Hash over every field but `hash` itself
+    ///
+    /// Objects are hashed with sorted keys, so the per-symbol position limits
+    /// hash the same however their map happens to iterate.
+    pub fn compute_hash(&self) -> Result<String> {
+        let mut value = serde_json::to_value(self)
+            .map_err(|e| Error::AuditLogError(format!("serialize entry: {}", e)))?;
+        if let JsonValue::Object(fields) = &mut value {
+            fields.remove("hash");
+        }
+        let mut canonical = String::new();
+        write_canonical(&value, &mut canonical);
+        Ok(sha256_hex(canonical.as_bytes()))
+    }
+}
+
+/// Audit log filter, newest entries first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuditQuery {
+    pub change_id: Option<Uuid>,
+    pub since: Option<DateTime<Utc>>,
+    pub limit: Option<usize>,
+}
+
+/// Risk limits guarded by the proposal/confirmation workflow
+pub struct LimitChangeManager {
+    policy: ApprovalPolicy,
+    active: RiskLimits,
+    open: Option<LimitChange>,
+    log: Vec<AuditEntry>,
+    journal: Option<PathBuf>,
+}
+
+impl LimitChangeManager {
+    pub fn new(limits: RiskLimits, policy: ApprovalPolicy) -> Self {
+        Self {
+            policy,
+            active: limits,
+            open: None,
+            log: Vec::new(),
+            journal: None,
+        }
+    }
+
+    /// Manager backed by an append-only journal at `path`
+    ///
+    /// An existing journal is verified and replayed: the last applied change
+    /// sets the active limits (`initial` when none was applied) and an open
+    /// change is restored as it was left.
+    pub fn open_journal(
+        path: impl AsRef<Path>,
+        initial: RiskLimits,
+        policy: ApprovalPolicy,
+    ) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut manager = Self::new(initial, policy);
+
+        if path.exists() {
+            let file = File::open(&path)
+                .map_err(|e| Error::AuditLogError(format!("open {}: {}", path.display(), e)))?;
+            for (line_no, line) in BufReader::new(file).lines().enumerate() {
+                let line = line.map_err(|e| Error::AuditLogError(e.to_string()))?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let entry: AuditEntry = serde_json::from_str(&line)
+                    .map_err(|e| Error::AuditLogError(format!("line {}: {}", line_no + 1, e)))?;
+                manager.replay(entry)?;
+            }
+        } else if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| Error::AuditLogError(format!("create {}: {}", dir.display(), e)))?;
+        }
+
+        manager.journal = Some(path);
+        Ok(manager)
+    }
+
+    pub fn policy(&self) -> &ApprovalPolicy {
+        &self.policy
+    }
+
+    /// Limits currently in force
+    pub fn active_limits(&self) -> &RiskLimits {
+        &self.active
+    }
+
+    /// The change awaiting confirmation or its effective time, if any
+    pub fn open_change(&self) -> Option<&LimitChange> {
+        self.open.as_ref()
+    }
+
+    /// Propose replacing the active limits
+    ///
+    /// Takes effect no earlier than the policy's minimum delay, and not
+    /// before it is confirmed.
+    pub fn propose(
+        &mut self,
+        proposed: RiskLimits,
+        actor: &str,
+        reason: Option<String>,
+        effective_at: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> Result<LimitChange> {
+        if let Some(open) = &self.open {
+            return Err(Error::ValidationFailed(format!(
+                "Limit change {} is still {:?}; confirm or cancel it first",
+                open.id, open.status
+            )));
+        }
+        validate_limits(&proposed)?;
+
+        let earliest = now + Duration::seconds(self.policy.min_delay_secs);
+        let effective_at = match effective_at {
+            Some(at) if at < earliest => {
+                return Err(Error::ValidationFailed(format!(
+                    "Effective time {} is before the earliest allowed {}",
+                    at, earliest
+                )));
+            }
+            Some(at) => at,
+            None => earliest,
+        };
+
+        let change = LimitChange {
+            id: Uuid::new_v4(),
+            status: LimitChangeStatus::Pending,
+            previous: self.active.clone(),
+            proposed,
+            proposed_by: actor.to_string(),
+            proposed_at: now,
+            reason,
+            effective_at,
+            confirmed_by: None,
+            confirmed_at: None,
+            applied_at: None,
+        };
+        self.record(now, actor, AuditAction::Proposed, change.clone(), None)?;
+        self.open = Some(change.clone());
+        Ok(change)
+    }
+
+    /// Confirm the open change, applying it right away if it is already due
+    ///
+    /// A confirmation refused by the approval policy is logged before the
+    /// error is returned.
+    pub fn confirm(
+        &mut self,
+        id: Uuid,
+        actor: &str,
+        passcode: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<LimitChange> {
+        let mut change = self.open_with_status(id, LimitChangeStatus::Pending)?;
+
+        let denial = if self.policy.require_second_approver && actor == change.proposed_by {
+            Some("confirmer must differ from proposer")
+        } else if !self.policy.passcode_matches(passcode) {
+            Some("passcode missing or incorrect")
+        } else {
+            None
+        };
+        if let Some(denial) = denial {
+            self.record(
+                now,
+                actor,
+                AuditAction::ConfirmationDenied,
+                change,
+                Some(denial.to_string()),
+            )?;
+            return Err(Error::ApprovalDenied(denial.to_string()));
+        }
+
+        change.status = LimitChangeStatus::Scheduled;
+        change.confirmed_by = Some(actor.to_string());
+        change.confirmed_at = Some(now);
+        self.record(now, actor, AuditAction::Confirmed, change.clone(), None)?;
+        self.open = Some(change.clone());
+
+        Ok(self.apply_due(now)?.unwrap_or(change))
+    }
+
+    /// Withdraw the open change before it takes effect
+    pub fn cancel(
+        &mut self,
+        id: Uuid,
+        actor: &str,
+        reason: Option<String>,
+        now: DateTime<Utc>,
+    ) -> Result<LimitChange> {
+        let mut change = self
+            .open
+            .clone()
+            .filter(|c| c.id == id)
+            .ok_or(Error::LimitChangeNotFound(id))?;
+
+        change.status = LimitChangeStatus::Cancelled;
+        self.record(now, actor, AuditAction::Cancelled, change.clone(), reason)?;
+        self.open = None;
+        Ok(change)
+    }
+
+    /// Put a confirmed change into force once its effective time has come
+    pub fn apply_due(&mut self, now: DateTime<Utc>) -> Result<Option<LimitChange>> {
+        let Some(mut change) = self
+            .open
+            .clone()
+            .filter(|c| c.status == LimitChangeStatus::Scheduled && c.effective_at <= now)
+        else {
+            return Ok(None);
+        };
+
+        change.status = LimitChangeStatus::Applied;
+        change.applied_at = Some(now);
+        self.record(now, "system", AuditAction::Applied, change.clone(), None)?;
+        self.active = change.proposed.clone();
+        self.open = None;
+        Ok(Some(change))
+    }
+
+    /// Audit entries matching `query`, newest first
+    pub fn audit_log(&self, query: &AuditQuery) -> Vec<AuditEntry> {
+        self.log
+            .iter()
+            .rev()
+            .filter(|e| query.change_id.is_none_or(|id| e.change.id == id))
+            .filter(|e| query.since.is_none_or(|since| e.at >= since))
+            .take(query.limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect()
+    }
+
+    /// Check the hash chain of the whole log
+    pub fn verify_log(&self) -> Result<()> {
+        let mut prev_hash = String::new();
+        for entry in &self.log {
+            verify_entry(entry, &prev_hash)?;
+            prev_hash = entry.hash.clone();
+        }
+        Ok(())
+    }
+
+    fn open_with_status(&self, id: Uuid, status: LimitChangeStatus) -> Result<LimitChange> {
+        let change = self
+            .open
+            .clone()
+            .filter(|c| c.id == id)
+            .ok_or(Error::LimitChangeNotFound(id))?;
+        if change.status != status {
+            return Err(Error::ValidationFailed(format!(
+                "Limit change {} is {:?}, expected {:?}",
+                id, change.status, status
+            )));
+        }
+        Ok(change)
+    }
+
+    fn record(
+        &mut self,
+        at: DateTime<Utc>,
+        actor: &str,
+        action: AuditAction,
+        change: LimitChange,
+        note: Option<String>,
+    ) -> Result<()> {
+        let mut entry = AuditEntry {
+            sequence: self.log.len() as u64,
+            at,
+            actor: actor.to_string(),
+            action,
+            change,
+            note,
+            prev_hash: self.log.last().map(|e| e.hash.clone()).unwrap_or_default(),
+            hash: String::new(),
+        };
+        entry.hash = entry.compute_hash()?;
+
+        // Journal first: an entry that is not on disk must not take effect
+        if let Some(path) = &self.journal {
+            let line = serde_json::to_string(&entry)
+                .map_err(|e| Error::AuditLogError(format!("serialize entry: {}", e)))?;
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|e| Error::AuditLogError(format!("open {}: {}", path.display(), e)))?;
+            writeln!(file, "{}", line)
+                .and_then(|_| file.sync_data())
+                .map_err(|e| Error::AuditLogError(format!("write {}: {}", path.display(), e)))?;
+        }
+
+        self.log.push(entry);
+        Ok(())
+    }
+
+    fn replay(&mut self, entry: AuditEntry) -> Result<()> {
+        if entry.sequence != self.log.len() as u64 {
+            return Err(Error::AuditLogError(format!(
+                "entry {} out of sequence, expected {}",
+                entry.sequence,
+                self.log.len()
+            )));
+        }
+        let prev_hash = self.log.last().map(|e| e.hash.as_str()).unwrap_or_default();
+        verify_entry(&entry, prev_hash)?;
+
+        match entry.change.status {
+            LimitChangeStatus::Applied => {
+                self.active = entry.change.proposed.clone();
+                self.open = None;
+            }
+            LimitChangeStatus::Cancelled => self.open = None,
+            LimitChangeStatus::Pending | LimitChangeStatus::Scheduled => {
+                self.open = Some(entry.change.clone());
+            }
+        }
+        self.log.push(entry);
+        Ok(())
+    }
+}
+
+/// Reject limits that would disable or invert a check
+pub fn validate_limits(limits: &RiskLimits) -> Result<()> {
+    let invalid = |message: &str| Err(Error::ValidationFailed(message.to_string()));
+
+    if limits.max_portfolio_value <= Decimal::ZERO {
+        return invalid("max_portfolio_value must be positive");
+    }
+    if limits.max_leverage <= Decimal::ZERO {
+        return invalid("max_leverage must be positive");
+    }
+    if limits.daily_loss_limit < Decimal::ZERO {
+        return invalid("daily_loss_limit must not be negative");
+    }
+    if limits.max_concentration_pct <= Decimal::ZERO
+        || limits.max_concentration_pct > Decimal::ONE_HUNDRED
+    {
+        return invalid("max_concentration_pct must be within (0, 100]");
+    }
+    if limits.min_margin_ratio < Decimal::ZERO || limits.min_margin_ratio > Decimal::ONE {
+        return invalid("min_margin_ratio must be within [0, 1]");
+    }
+    if limits.max_open_positions == 0 {
+        return invalid("max_open_positions must be at least 1");
+    }
+    for budget in limits.var_budgets.values() {
+        budget.validate()?;
+    }
+    Ok(())
+}
+
+fn verify_entry(entry: &AuditEntry, prev_hash: &str) -> Result<()> {
+    if entry.prev_hash != prev_hash || entry.compute_hash()? != entry.hash {
+        return Err(Error::AuditLogError(format!(
+            "audit entry {} fails hash verification",
+            entry.sequence
+        )));
+    }
+    Ok(())
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn write_canonical(value: &JsonValue, out: &mut String) {
+    match value {
+        JsonValue::Object(fields) => {
+            let mut keys: Vec<&String> = fields.keys().collect();
+            keys.sort();
+            out.push('{');
+            for (i, key) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&JsonValue::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(&fields[key], out);
+            }
+            out.push('}');
+        }
+        JsonValue::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        other => out.push_str(&other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn t(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000 + secs, 0).unwrap()
+    }
+
+    fn tighter() -> RiskLimits {
+        RiskLimits {
+            max_leverage: dec!(2),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_change_takes_effect_only_after_confirmation_and_schedule() {
+        let mut manager = LimitChangeManager::new(
+            RiskLimits::default(),
+            ApprovalPolicy {
+                min_delay_secs: 60,
+                ..Default::default()
+            },
+        );
+
+        let change = manager
+            .propose(
+                tighter(),
+                "alice",
+                Some("lower leverage".into()),
+                None,
+                t(0),
+            )
+            .unwrap();
+        assert_eq!(change.effective_at, t(60));
+        assert!(
+            manager
+                .propose(tighter(), "alice", None, None, t(1))
+                .is_err()
+        );
+
+        // Due but unconfirmed: nothing happens
+        assert!(manager.apply_due(t(120)).unwrap().is_none());
+        assert_eq!(manager.active_limits().max_leverage, dec!(3.0));
+
+        let confirmed = manager.confirm(change.id, "bob", None, t(30)).unwrap();
+        assert_eq!(confirmed.status, LimitChangeStatus::Scheduled);
+        assert!(manager.apply_due(t(59)).unwrap().is_none());
+
+        let applied = manager.apply_due(t(60)).unwrap().unwrap();
+        assert_eq!(applied.status, LimitChangeStatus::Applied);
+        assert_eq!(manager.active_limits().max_leverage, dec!(2));
+        assert!(manager.open_change().is_none());
+
+        let actions: Vec<AuditAction> = manager
+            .audit_log(&AuditQuery::default())
+            .iter()
+            .map(|e| e.action)
+            .collect();
+        assert_eq!(
+            actions,
+            vec![
+                AuditAction::Applied,
+                AuditAction::Confirmed,
+                AuditAction::Proposed
+            ]
+        );
+        manager.verify_log().unwrap();
+    }
+
+    #[test]
+    fn test_confirmation_enforces_second_approver_and_passcode() {
+        let policy = ApprovalPolicy {
+            require_second_approver: true,
+            ..Default::default()
+        }
+        .with_passcode("hunter2");
+        let mut manager = LimitChangeManager::new(RiskLimits::default(), policy);
+        let change = manager
+            .propose(tighter(), "alice", None, None, t(0))
+            .unwrap();
+
+        assert!(matches!(
+            manager.confirm(change.id, "alice", Some("hunter2"), t(1)),
+            Err(Error::ApprovalDenied(_))
+        ));
+        assert!(matches!(
+            manager.confirm(change.id, "bob", Some("wrong"), t(2)),
+            Err(Error::ApprovalDenied(_))
+        ));
+
+        // Already due, so confirmation applies it
+        let applied = manager
+            .confirm(change.id, "bob", Some("hunter2"), t(3))
+            .unwrap();
+        assert_eq!(applied.status, LimitChangeStatus::Applied);
+        assert_eq!(applied.confirmed_by.as_deref(), Some("bob"));
+
+        let denied = manager.audit_log(&AuditQuery {
+            change_id: Some(change.id),
+            ..Default::default()
+        });
+        assert_eq!(
+            denied
+                .iter()
+                .filter(|e| e.action == AuditAction::ConfirmationDenied)
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_invalid_limits_are_rejected() {
+        let mut manager = LimitChangeManager::new(RiskLimits::default(), ApprovalPolicy::default());
+        let limits = RiskLimits {
+            max_concentration_pct: dec!(150),
+            ..Default::default()
+        };
+        assert!(manager.propose(limits, "alice", None, None, t(0)).is_err());
+        assert!(
+            manager
+                .propose(tighter(), "alice", None, Some(t(-10)), t(0))
+                .is_err()
+        );
+        assert!(manager.audit_log(&AuditQuery::default()).is_empty());
+    }
+
+    #[test]
+    fn test_journal_replays_state_and_detects_tampering() {
+        let dir = std::env::temp_dir().join(format!("limit-changes-{}", Uuid::new_v4()));
+        let path = dir.join("audit.jsonl");
+        let policy = ApprovalPolicy::default();
+
+        let mut manager =
+            LimitChangeManager::open_journal(&path, RiskLimits::default(), policy.clone()).unwrap();
+        let mut limits = tighter();
+        limits.max_position_size.insert(
+            ea_okx_core::Symbol::new("BTC-USDT").unwrap(),
+            ea_okx_core::Quantity::new(dec!(1)).unwrap(),
+        );
+        limits.max_position_size.insert(
+            ea_okx_core::Symbol::new("ETH-USDT").unwrap(),
+            ea_okx_core::Quantity::new(dec!(10)).unwrap(),
+        );
+        let first = manager.propose(limits, "alice", None, None, t(0)).unwrap();
+        manager.confirm(first.id, "bob", None, t(1)).unwrap();
+        let second = manager
+            .propose(RiskLimits::default(), "alice", None, Some(t(3600)), t(2))
+            .unwrap();
+        drop(manager);
+
+        let reopened =
+            LimitChangeManager::open_journal(&path, RiskLimits::default(), policy.clone()).unwrap();
+        assert_eq!(reopened.active_limits().max_leverage, dec!(2));
+        assert_eq!(reopened.active_limits().max_position_size.len(), 2);
+        assert_eq!(reopened.open_change().map(|c| c.id), Some(second.id));
+        assert_eq!(reopened.audit_log(&AuditQuery::default()).len(), 4);
+
+        let journal = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, journal.replacen("\"alice\"", "\"mallory\"", 1)).unwrap();
+        assert!(matches!(
+            LimitChangeManager::open_journal(&path, RiskLimits::default(), policy),
+            Err(Error::AuditLogError(_))
+        ));
+
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
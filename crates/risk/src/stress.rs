@@ -0,0 +1,593 @@
+//! Portfolio stress testing
+//!
+//! [`StressTester`] revalues the current portfolio under a [`StressScenario`]:
+//! instantaneous price shocks by asset or asset class, a volatility multiplier
+//! that scales maintenance margin (exchanges raise margin tiers in turbulent
+//! markets) and a funding rate shift charged over a number of funding
+//! settlements. Each position is checked for liquidation and the account's
+//! margin ratio is recomputed, so the question "would we survive another
+//! FTX week?" has a number attached.
+//!
+//! A small library of historical scenarios is built in; custom scenarios are
+//! plain data and can be defined by the user.
+
+use crate::error::{Error, Result};
+use crate::validators::PortfolioState;
+use ea_okx_core::Symbol;
+use ea_okx_core::models::PositionSide;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+
+/// Broad asset class used to target shocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetClass {
+    Btc,
+    Eth,
+    /// Everything else except stablecoins
+    Alt,
+    Stablecoin,
+}
+
+impl AssetClass {
+    /// Classify a symbol by its base currency
+    pub fn of(symbol: &Symbol) -> Self {
+        match symbol.base() {
+            "BTC" => Self::Btc,
+            "ETH" => Self::Eth,
+            "USDT" | "USDC" | "DAI" | "TUSD" | "FDUSD" => Self::Stablecoin,
+            _ => Self::Alt,
+        }
+    }
+}
+
+/// What a price shock applies to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum ShockTarget {
+    /// A single base currency (e.g. "SOL"), covering spot and derivatives
+    Asset(String),
+    Class(AssetClass),
+    All,
+}
+
+impl ShockTarget {
+    /// Higher wins when several shocks match a symbol
+    fn specificity(&self) -> u8 {
+        match self {
+            Self::Asset(_) => 2,
+            Self::Class(_) => 1,
+            Self::All => 0,
+        }
+    }
+
+    fn matches(&self, symbol: &Symbol) -> bool {
+        match self {
+            Self::Asset(asset) => symbol.base().eq_ignore_ascii_case(asset),
+            Self::Class(class) => AssetClass::of(symbol) == *class,
+            Self::All => true,
+        }
+    }
+}
+
+/// Instantaneous price move
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceShock {
+    pub target: ShockTarget,
+
+    /// Relative change (e.g., -0.30 = -30%)
+    pub change: Decimal,
+}
+
+impl PriceShock {
+    pub fn new(target: ShockTarget, change: Decimal) -> Self {
+        Self { target, change }
+    }
+}
+
+/// A set of shocks applied together
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StressScenario {
+    pub name: String,
+    pub description: String,
+    pub price_shocks: Vec<PriceShock>,
+
+    /// Multiplier on maintenance margin rates (1 = unchanged)
+    pub volatility_multiplier: Decimal,
+
+    /// Change in funding rate per settlement, in basis points; positive
+    /// means longs pay more
+    pub funding_shift_bps: Decimal,
+
+    /// Funding settlements over the scenario horizon
+    pub funding_periods: u32,
+}
+
+impl StressScenario {
+    /// Scenario with no shocks, to be filled in by the caller
+    pub fn custom(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            description: String::new(),
+            price_shocks: Vec::new(),
+            volatility_multiplier: Decimal::ONE,
+            funding_shift_bps: Decimal::ZERO,
+            funding_periods: 1,
+        }
+    }
+
+    pub fn with_price_shock(mut self, target: ShockTarget, change: Decimal) -> Self {
+        self.price_shocks.push(PriceShock::new(target, change));
+        self
+    }
+
+    pub fn with_volatility_multiplier(mut self, multiplier: Decimal) -> Self {
+        self.volatility_multiplier = multiplier;
+        self
+    }
+
+    pub fn with_funding_shift(mut self, bps: Decimal, periods: u32) -> Self {
+        self.funding_shift_bps = bps;
+        self.funding_periods = periods;
+        self
+    }
+
+    /// 12-13 March 2020 ("Black Thursday"), approximate peak-to-trough moves
+    pub fn march_2020() -> Self {
+        Self {
+            description: "COVID crash of 12-13 March 2020: BTC halved intraday, \
+                          BitMEX liquidations cascaded and perpetual funding \
+                          went deeply negative"
+                .to_string(),
+            ..Self::custom("march_2020")
+        }
+        .with_price_shock(ShockTarget::Class(AssetClass::Btc), dec!(-0.50))
+        .with_price_shock(ShockTarget::Class(AssetClass::Eth), dec!(-0.60))
+        .with_price_shock(ShockTarget::Class(AssetClass::Alt), dec!(-0.60))
+        .with_volatility_multiplier(dec!(4))
+        .with_funding_shift(dec!(-30), 3)
+    }
+
+    /// FTX collapse, 6-9 November 2022
+    pub fn ftx_collapse() -> Self {
+        Self {
+            description: "FTX insolvency in November 2022: majors sold off, \
+                          Solana ecosystem and exchange tokens collapsed"
+                .to_string(),
+            ..Self::custom("ftx_collapse")
+        }
+        .with_price_shock(ShockTarget::Class(AssetClass::Btc), dec!(-0.25))
+        .with_price_shock(ShockTarget::Class(AssetClass::Eth), dec!(-0.30))
+        .with_price_shock(ShockTarget::Class(AssetClass::Alt), dec!(-0.40))
+        .with_price_shock(ShockTarget::Asset("SOL".to_string()), dec!(-0.60))
+        .with_price_shock(ShockTarget::Asset("FTT".to_string()), dec!(-0.90))
+        .with_volatility_multiplier(dec!(2.5))
+        .with_funding_shift(dec!(-10), 9)
+    }
+
+    /// Hypothetical broad crypto crash: BTC -30%, alts -50%, vol x3, funding +100bps
+    pub fn crypto_crash() -> Self {
+        Self {
+            description: "Hypothetical crash with crowded longs: BTC -30%, \
+                          ETH and alts -50%, volatility tripled and funding \
+                          up 100bps"
+                .to_string(),
+            ..Self::custom("crypto_crash")
+        }
+        .with_price_shock(ShockTarget::Class(AssetClass::Btc), dec!(-0.30))
+        .with_price_shock(ShockTarget::Class(AssetClass::Eth), dec!(-0.50))
+        .with_price_shock(ShockTarget::Class(AssetClass::Alt), dec!(-0.50))
+        .with_volatility_multiplier(dec!(3))
+        .with_funding_shift(dec!(100), 1)
+    }
+
+    /// Built-in scenario library
+    pub fn library() -> Vec<Self> {
+        vec![
+            Self::march_2020(),
+            Self::ftx_collapse(),
+            Self::crypto_crash(),
+        ]
+    }
+
+    /// Look up a built-in scenario by name
+    pub fn by_name(name: &str) -> Option<Self> {
+        Self::library().into_iter().find(|s| s.name == name)
+    }
+
+    /// Price change applied to `symbol`; the most specific matching shock wins
+    pub fn price_change(&self, symbol: &Symbol) -> Decimal {
+        self.price_shocks
+            .iter()
+            .filter(|s| s.target.matches(symbol))
+            .max_by_key(|s| s.target.specificity())
+            .map_or(Decimal::ZERO, |s| s.change)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if let Some(shock) = self.price_shocks.iter().find(|s| s.change <= dec!(-1)) {
+            return Err(Error::ValidationFailed(format!(
+                "Price shock on {:?} must be above -100%, got {}",
+                shock.target, shock.change
+            )));
+        }
+        if self.volatility_multiplier <= Decimal::ZERO {
+            return Err(Error::ValidationFailed(format!(
+                "Volatility multiplier must be positive, got {}",
+                self.volatility_multiplier
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Stress testing configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StressConfig {
+    /// Maintenance margin rate before the volatility multiplier
+    pub maintenance_margin_rate: Decimal,
+
+    /// Cross margin pools all positions against account equity; otherwise
+    /// each leveraged position stands alone on its own margin
+    pub cross_margin: bool,
+}
+
+impl Default for StressConfig {
+    fn default() -> Self {
+        Self {
+            maintenance_margin_rate: dec!(0.005), // OKX tier 1 for BTC perpetuals
+            cross_margin: true,
+        }
+    }
+}
+
+/// Revaluation of one position under a scenario
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionStress {
+    pub symbol: Symbol,
+    pub side: PositionSide,
+    pub quantity: Decimal,
+    pub price_before: Decimal,
+    pub price_after: Decimal,
+    pub price_change: Decimal,
+    pub notional_after: Decimal,
+
+    /// PnL from the price move
+    pub price_pnl: Decimal,
+
+    /// PnL from the funding shift
+    pub funding_pnl: Decimal,
+
+    /// Total PnL, capped at the posted margin for isolated liquidations
+    pub pnl: Decimal,
+
+    /// Maintenance margin after the volatility multiplier (zero for spot)
+    pub maintenance_margin: Decimal,
+
+    /// Isolated liquidation price, before the shock
+    pub liquidation_price: Option<Decimal>,
+    pub liquidated: bool,
+}
+
+/// Portfolio revaluation under a scenario
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StressResult {
+    pub scenario: String,
+    pub equity_before: Decimal,
+    pub equity_after: Decimal,
+    pub pnl: Decimal,
+
+    /// PnL as a fraction of starting equity
+    pub pnl_pct: Decimal,
+
+    pub maintenance_margin: Decimal,
+
+    /// Stressed equity over maintenance margin; below 1 means liquidation
+    pub margin_ratio: Option<Decimal>,
+
+    /// Cross-margin account would be liquidated
+    pub account_liquidated: bool,
+
+    pub positions: Vec<PositionStress>,
+}
+
+impl StressResult {
+    /// Symbols of positions that would be liquidated
+    pub fn liquidated_symbols(&self) -> Vec<&Symbol> {
+        self.positions
+            .iter()
+            .filter(|p| p.liquidated)
+            .map(|p| &p.symbol)
+            .collect()
+    }
+}
+
+/// Revalues a portfolio under stress scenarios
+pub struct StressTester {
+    config: StressConfig,
+}
+
+impl StressTester {
+    pub fn new(config: StressConfig) -> Self {
+        Self { config }
+    }
+
+    /// Run every scenario in the built-in library
+    pub fn run_library(&self, portfolio: &PortfolioState) -> Result<Vec<StressResult>> {
+        StressScenario::library()
+            .iter()
+            .map(|scenario| self.run(portfolio, scenario))
+            .collect()
+    }
+
+    /// Revalue `portfolio` under `scenario`
+    pub fn run(
+        &self,
+        portfolio: &PortfolioState,
+        scenario: &StressScenario,
+    ) -> Result<StressResult> {
+        scenario.validate()?;
+
+        let mmr = self.config.maintenance_margin_rate * scenario.volatility_multiplier;
+        let mut positions: Vec<PositionStress> = portfolio
+            .positions
+            .iter()
+            .filter(|p| !p.is_closed())
+            .map(|p| {
+                let quantity = p.quantity.as_decimal();
+                let direction = match p.side {
+                    PositionSide::Short => -Decimal::ONE,
+                    PositionSide::Long | PositionSide::Net => Decimal::ONE,
+                };
+                let price_before = p.current_price.as_decimal();
+                let price_change = scenario.price_change(&p.symbol);
+                let price_after = price_before * (Decimal::ONE + price_change);
+                let notional_after = price_after * quantity;
+
+                let price_pnl = direction * (price_after - price_before) * quantity;
+                let leveraged = p.leverage.is_some_and(|l| l > Decimal::ONE) || p.margin.is_some();
+                let funding_pnl = if leveraged {
+                    -direction
+                        * notional_after
+                        * scenario.funding_shift_bps
+                        * Decimal::from(scenario.funding_periods)
+                        / dec!(10000)
+                } else {
+                    Decimal::ZERO
+                };
+
+                let margin = p.margin.or_else(|| {
+                    p.leverage
+                        .filter(|l| *l > Decimal::ZERO)
+                        .map(|l| price_before * quantity / l)
+                });
+                let maintenance_margin = if leveraged {
+                    notional_after * mmr
+                } else {
+                    Decimal::ZERO
+                };
+
+                let liquidation_price = match (leveraged, margin) {
+                    (true, Some(margin)) if !quantity.is_zero() => {
+                        liquidation_price(direction, price_before, quantity, margin, mmr)
+                    }
+                    _ => None,
+                };
+
+                let mut pnl = price_pnl + funding_pnl;
+                let mut liquidated = false;
+                if !self.config.cross_margin
+                    && let Some(margin) = margin
+                    && leveraged
+                    && margin + pnl <= maintenance_margin
+                {
+                    liquidated = true;
+                    pnl = -margin;
+                }
+
+                PositionStress {
+                    symbol: p.symbol.clone(),
+                    side: p.side,
+                    quantity,
+                    price_before,
+                    price_after,
+                    price_change,
+                    notional_after,
+                    price_pnl,
+                    funding_pnl,
+                    pnl,
+                    maintenance_margin,
+                    liquidation_price,
+                    liquidated,
+                }
+            })
+            .collect();
+
+        let pnl: Decimal = positions.iter().map(|p| p.pnl).sum();
+        let equity_after = portfolio.total_equity + pnl;
+        let maintenance_margin: Decimal = positions
+            .iter()
+            .filter(|p| !p.liquidated)
+            .map(|p| p.maintenance_margin)
+            .sum();
+        let margin_ratio =
+            (!maintenance_margin.is_zero()).then(|| equity_after / maintenance_margin);
+
+        let account_liquidated = self.config.cross_margin
+            && (equity_after <= Decimal::ZERO || margin_ratio.is_some_and(|r| r <= Decimal::ONE));
+        if account_liquidated {
+            for position in positions
+                .iter_mut()
+                .filter(|p| !p.maintenance_margin.is_zero())
+            {
+                position.liquidated = true;
+            }
+        }
+
+        let pnl_pct = if portfolio.total_equity.is_zero() {
+            Decimal::ZERO
+        } else {
+            pnl / portfolio.total_equity
+        };
+
+        Ok(StressResult {
+            scenario: scenario.name.clone(),
+            equity_before: portfolio.total_equity,
+            equity_after,
+            pnl,
+            pnl_pct,
+            maintenance_margin,
+            margin_ratio,
+            account_liquidated,
+            positions,
+        })
+    }
+}
+
+impl Default for StressTester {
+    fn default() -> Self {
+        Self::new(StressConfig::default())
+    }
+}
+
+/// Price at which an isolated position's margin falls to maintenance
+fn liquidation_price(
+    direction: Decimal,
+    price: Decimal,
+    quantity: Decimal,
+    margin: Decimal,
+    mmr: Decimal,
+) -> Option<Decimal> {
+    // Long: margin + q(p' - p) = mmr q p'; short: margin - q(p' - p) = mmr q p'
+    let denominator = quantity * (direction - mmr);
+    if denominator.is_zero() {
+        return None;
+    }
+    let liq = (direction * quantity * price - margin) / denominator;
+    (liq > Decimal::ZERO).then_some(liq)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ea_okx_core::models::Position;
+    use ea_okx_core::{Price, Quantity};
+    use uuid::Uuid;
+
+    fn position(symbol: &str, side: PositionSide, qty: Decimal, price: Decimal) -> Position {
+        Position::new(
+            Uuid::new_v4(),
+            Symbol::new(symbol).unwrap(),
+            side,
+            Quantity::new(qty).unwrap(),
+            Price::new(price).unwrap(),
+        )
+    }
+
+    fn leveraged(mut p: Position, leverage: Decimal) -> Position {
+        p.leverage = Some(leverage);
+        p
+    }
+
+    fn portfolio(equity: Decimal, positions: Vec<Position>) -> PortfolioState {
+        PortfolioState {
+            total_equity: equity,
+            available_margin: equity,
+            positions,
+            daily_pnl: Decimal::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_most_specific_shock_wins() {
+        let scenario = StressScenario::ftx_collapse();
+        let change = |s: &str| scenario.price_change(&Symbol::new(s).unwrap());
+        assert_eq!(change("BTC-USDT"), dec!(-0.25));
+        assert_eq!(change("SOL-USDT"), dec!(-0.60));
+        assert_eq!(change("DOGE-USDT"), dec!(-0.40));
+        assert_eq!(change("USDC-USDT"), Decimal::ZERO);
+        assert!(StressScenario::by_name("march_2020").is_some());
+    }
+
+    #[test]
+    fn test_spot_portfolio_revaluation_and_funding() {
+        let scenario = StressScenario::custom("btc_down")
+            .with_price_shock(ShockTarget::Class(AssetClass::Btc), dec!(-0.30))
+            .with_price_shock(ShockTarget::All, dec!(-0.50))
+            .with_funding_shift(dec!(100), 1);
+        let state = portfolio(
+            dec!(100000),
+            vec![
+                position("BTC-USDT", PositionSide::Long, dec!(1), dec!(50000)),
+                position("ETH-USDT", PositionSide::Short, dec!(10), dec!(3000)),
+            ],
+        );
+
+        let result = StressTester::default().run(&state, &scenario).unwrap();
+        // BTC long loses 15000, ETH short gains 15000; spot pays no funding
+        assert_eq!(result.positions[0].pnl, dec!(-15000));
+        assert_eq!(result.positions[1].pnl, dec!(15000));
+        assert_eq!(result.pnl, Decimal::ZERO);
+        assert_eq!(result.margin_ratio, None);
+        assert!(!result.account_liquidated);
+
+        // Leveraged long pays 1% funding on the shocked notional
+        let state = portfolio(
+            dec!(100000),
+            vec![leveraged(
+                position("BTC-USDT", PositionSide::Long, dec!(1), dec!(50000)),
+                dec!(2),
+            )],
+        );
+        let result = StressTester::default().run(&state, &scenario).unwrap();
+        assert_eq!(result.positions[0].funding_pnl, dec!(-350));
+        assert_eq!(result.pnl, dec!(-15350));
+    }
+
+    #[test]
+    fn test_liquidation_isolated_and_cross() {
+        let btc = leveraged(
+            position("BTC-USDT", PositionSide::Long, dec!(1), dec!(50000)),
+            dec!(5),
+        );
+        let eth = leveraged(
+            position("ETH-USDT", PositionSide::Long, dec!(10), dec!(3000)),
+            dec!(2),
+        );
+        let state = portfolio(dec!(25000), vec![btc, eth]);
+        let scenario = StressScenario::crypto_crash();
+
+        // Isolated: 5x BTC (margin 10000) loses 15350 and 2x ETH (margin
+        // 15000) loses 15150 after funding, so both are wiped out
+        let isolated = StressTester::new(StressConfig {
+            cross_margin: false,
+            ..Default::default()
+        })
+        .run(&state, &scenario)
+        .unwrap();
+        assert!(isolated.positions.iter().all(|p| p.liquidated));
+        assert_eq!(isolated.positions[0].pnl, dec!(-10000));
+        let liq = isolated.positions[0].liquidation_price.unwrap();
+        assert!(liq > dec!(40000) && liq < dec!(41000), "liq {}", liq);
+
+        // Cross: a 30500 loss exceeds the 25000 of account equity
+        let cross = StressTester::default().run(&state, &scenario).unwrap();
+        assert!(cross.account_liquidated);
+        assert_eq!(cross.liquidated_symbols().len(), 2);
+
+        // A milder scenario leaves the cross account standing
+        let mild = StressScenario::custom("mild").with_price_shock(ShockTarget::All, dec!(-0.05));
+        let result = StressTester::default().run(&state, &mild).unwrap();
+        assert!(!result.account_liquidated);
+        assert!(result.margin_ratio.unwrap() > Decimal::ONE);
+        assert!(result.liquidated_symbols().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_scenario_rejected() {
+        let scenario = StressScenario::custom("bad").with_price_shock(ShockTarget::All, dec!(-1.2));
+        let result = StressTester::default().run(&portfolio(dec!(1000), Vec::new()), &scenario);
+        assert!(matches!(result, Err(Error::ValidationFailed(_))));
+    }
+}
@@ -1,4 +1,5 @@
 use crate::error::{Error, Result};
+use crate::var_budget::{MarginalVar, VarBudget, marginal_var};
 use ea_okx_core::models::{Order, OrderSide, Position};
 use ea_okx_core::{Quantity, Symbol};
 use rust_decimal::Decimal;
@@ -6,6 +7,7 @@ use rust_decimal_macros::dec;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use tracing::warn;
+use uuid::Uuid;
 
 /// Risk limits configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +32,10 @@ pub struct RiskLimits {
 
     /// Minimum required margin ratio
     pub min_margin_ratio: Decimal,
+
+    /// VaR budget per strategy ID; strategies without one are not checked
+    #[serde(default)]
+    pub var_budgets: HashMap<Uuid, VarBudget>,
 }
 
 impl Default for RiskLimits {
@@ -42,6 +48,7 @@ impl Default for RiskLimits {
             max_concentration_pct: dec!(25.0),
             max_open_positions: 10,
             min_margin_ratio: dec!(0.15), // 15% minimum margin
+            var_budgets: HashMap::new(),
         }
     }
 }
@@ -58,11 +65,31 @@ pub struct PortfolioState {
 /// Pre-trade risk validator
 pub struct PreTradeValidator {
     limits: RiskLimits,
+    /// Daily returns per symbol, oldest first, for VaR budgets
+    returns: HashMap<Symbol, Vec<Decimal>>,
+    /// Latest prices per symbol, for VaR budgets
+    prices: HashMap<Symbol, Decimal>,
 }
 
 impl PreTradeValidator {
     pub fn new(limits: RiskLimits) -> Self {
-        Self { limits }
+        Self {
+            limits,
+            returns: HashMap::new(),
+            prices: HashMap::new(),
+        }
+    }
+
+    /// Daily returns (oldest first) and latest prices that strategies' VaR
+    /// budgets are measured with
+    pub fn with_market_history(
+        mut self,
+        returns: HashMap<Symbol, Vec<Decimal>>,
+        prices: HashMap<Symbol, Decimal>,
+    ) -> Self {
+        self.returns = returns;
+        self.prices = prices;
+        self
     }
 
     /// Validate an order before execution
@@ -127,6 +154,17 @@ impl PreTradeValidator {
             });
         }
 
+        // 7. VaR budget check
+        if let Some(budget) = self.limits.var_budgets.get(&order.strategy_id)
+            && let Err(e) = self.check_var_budget(order, portfolio, budget, &mut result)
+        {
+            result.add_violation(RiskViolation {
+                severity: ViolationSeverity::Critical,
+                rule: "VaR Budget".to_string(),
+                message: e.to_string(),
+            });
+        }
+
         Ok(result)
     }
 
@@ -253,6 +291,42 @@ impl PreTradeValidator {
         Ok(())
     }
 
+    /// Check the strategy's VaR after the order against its budget
+    ///
+    /// A budget that cannot be measured, for want of return history or a
+    /// price, fails the check rather than letting the order through.
+    fn check_var_budget(
+        &self,
+        order: &Order,
+        portfolio: &PortfolioState,
+        budget: &VarBudget,
+        result: &mut ValidationResult,
+    ) -> Result<()> {
+        let marginal = marginal_var(
+            order,
+            &portfolio.positions,
+            budget,
+            &self.returns,
+            &self.prices,
+        )
+        .map_err(|e| Error::RiskLimitExceeded(format!("VaR budget cannot be checked: {}", e)))?;
+        let exceeds = marginal.exceeds_budget();
+        let message = format!(
+            "{}-day {}% VaR {:.2} after the order exceeds budget {:.2} (marginal VaR {:.2})",
+            budget.horizon_days,
+            budget.confidence_level * 100.0,
+            marginal.var_after,
+            budget.max_var,
+            marginal.marginal_var
+        );
+        result.marginal_var = Some(marginal);
+
+        if exceeds {
+            return Err(Error::RiskLimitExceeded(message));
+        }
+        Ok(())
+    }
+
     /// Check maximum positions limit
     fn check_max_positions(&self, order: &Order, portfolio: &PortfolioState) -> Result<()> {
         // Check if this would open a new position
@@ -271,9 +345,13 @@ impl PreTradeValidator {
 }
 
 /// Validation result
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ValidationResult {
     pub violations: Vec<RiskViolation>,
+
+    /// The order's effect on its strategy's VaR, when it has a budget
+    #[serde(default)]
+    pub marginal_var: Option<MarginalVar>,
 }
 
 impl ValidationResult {
@@ -299,7 +377,7 @@ impl ValidationResult {
 }
 
 /// Risk violation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RiskViolation {
     pub severity: ViolationSeverity,
     pub rule: String,
@@ -307,7 +385,7 @@ pub struct RiskViolation {
 }
 
 /// Violation severity
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ViolationSeverity {
     Critical,
     Warning,
@@ -389,4 +467,42 @@ mod tests {
         let result = validator.validate_order(&order, &portfolio).unwrap();
         assert!(!result.is_valid());
     }
+
+    #[test]
+    fn test_var_budget_check() {
+        let order = create_test_order(dec!(1.0), dec!(50000.0));
+        let limits = RiskLimits {
+            var_budgets: HashMap::from([(order.strategy_id, VarBudget::new(dec!(2000.0)))]),
+            ..Default::default()
+        };
+        let portfolio = create_test_portfolio();
+
+        // Without return history the budget cannot be checked
+        let validator = PreTradeValidator::new(limits.clone());
+        let result = validator.validate_order(&order, &portfolio).unwrap();
+        assert!(!result.is_valid());
+        assert!(result.marginal_var.is_none());
+
+        // A 5% one-day loss on $50k is $2,500 of VaR
+        let mut returns = vec![dec!(-0.08), dec!(-0.05)];
+        returns.extend(std::iter::repeat_n(dec!(0.01), 18));
+        let validator = PreTradeValidator::new(limits).with_market_history(
+            HashMap::from([(order.symbol.clone(), returns)]),
+            HashMap::new(),
+        );
+        let result = validator.validate_order(&order, &portfolio).unwrap();
+        assert!(!result.is_valid());
+        assert!(result.violations.iter().any(|v| v.rule == "VaR Budget"));
+        assert_eq!(result.marginal_var.unwrap().marginal_var, dec!(2500));
+
+        let small = create_test_order(dec!(0.5), dec!(50000.0));
+        let other = PreTradeValidator::new(RiskLimits::default());
+        assert!(
+            other
+                .validate_order(&small, &portfolio)
+                .unwrap()
+                .marginal_var
+                .is_none()
+        );
+    }
 }
@@ -1,5 +1,5 @@
 use crate::error::{Error, Result};
-use ea_okx_core::models::Position;
+use ea_okx_core::models::{Position, PositionSide};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use serde::{Deserialize, Serialize};
@@ -196,7 +196,8 @@ impl VarCalculator {
         self.calculate_historical_var(positions, historical_returns, portfolio_value)
     }
 
-    /// Calculate portfolio returns from position returns
+    /// Calculate portfolio returns from position returns, weighting each
+    /// position by its share of gross value
     fn calculate_portfolio_returns(
         &self,
         positions: &[Position],
@@ -222,16 +223,21 @@ impl VarCalculator {
                 .sum();
 
             for (pos_idx, position) in positions.iter().enumerate() {
-                if let Some(returns) = historical_returns.get(pos_idx) {
-                    if let Some(ret) = returns.get(period) {
-                        let weight = if total_value > Decimal::ZERO {
-                            (position.quantity.as_decimal() * position.current_price.as_decimal())
-                                / total_value
-                        } else {
-                            Decimal::ZERO
-                        };
-                        period_return += ret * weight;
-                    }
+                if let Some(returns) = historical_returns.get(pos_idx)
+                    && let Some(ret) = returns.get(period)
+                {
+                    // Shorts gain when prices fall
+                    let value =
+                        position.quantity.as_decimal() * position.current_price.as_decimal();
+                    let weight = if total_value > Decimal::ZERO {
+                        match position.side {
+                            PositionSide::Short => -value / total_value,
+                            PositionSide::Long | PositionSide::Net => value / total_value,
+                        }
+                    } else {
+                        Decimal::ZERO
+                    };
+                    period_return += ret * weight;
                 }
             }
 
@@ -0,0 +1,346 @@
+//! Per-strategy risk budgets in VaR terms
+//!
+//! A [`VarBudget`] caps the value at risk of one strategy's positions, e.g.
+//! 1-day 95% VaR of at most $2,000. Before an order is placed its marginal
+//! VaR is measured: the historical VaR of the strategy's positions as they
+//! would be after the order fills, less their VaR now. The order is refused
+//! when the VaR after it would exceed the budget, unless it lowers VaR, so
+//! a strategy over budget can always trade back towards it.
+//!
+//! Return histories are one period per day, oldest first; longer horizons
+//! scale the one-day VaR by the square root of the number of days.
+
+use crate::error::{Error, Result};
+use crate::var::{VarCalculator, VarConfig, VarMethod};
+use ea_okx_core::models::{Order, OrderSide, Position, PositionSide};
+use ea_okx_core::{Price, Quantity, Symbol};
+use rust_decimal::Decimal;
+use rust_decimal::MathematicalOps;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+/// VaR a strategy's positions may carry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VarBudget {
+    /// Largest VaR allowed, in the quote currency
+    pub max_var: Decimal,
+
+    /// Confidence level (e.g., 0.95, 0.99)
+    #[serde(default = "default_confidence_level")]
+    pub confidence_level: f64,
+
+    /// Time horizon in days
+    #[serde(default = "default_horizon_days")]
+    pub horizon_days: u32,
+}
+
+fn default_confidence_level() -> f64 {
+    0.95
+}
+
+fn default_horizon_days() -> u32 {
+    1
+}
+
+impl VarBudget {
+    /// 1-day 95% budget of `max_var`
+    pub fn new(max_var: Decimal) -> Self {
+        Self {
+            max_var,
+            confidence_level: default_confidence_level(),
+            horizon_days: default_horizon_days(),
+        }
+    }
+
+    /// Reject budgets that cannot be measured against
+    pub fn validate(&self) -> Result<()> {
+        if self.max_var <= Decimal::ZERO {
+            return Err(Error::ValidationFailed(
+                "VaR budget must be positive".to_string(),
+            ));
+        }
+        if !(self.confidence_level > 0.0 && self.confidence_level < 1.0) {
+            return Err(Error::ValidationFailed(
+                "VaR budget confidence level must be within (0, 1)".to_string(),
+            ));
+        }
+        if self.horizon_days == 0 {
+            return Err(Error::ValidationFailed(
+                "VaR budget horizon must be at least 1 day".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// An order's effect on its strategy's VaR
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarginalVar {
+    pub strategy_id: Uuid,
+    pub budget: VarBudget,
+
+    /// VaR of the strategy's positions before the order
+    pub var_before: Decimal,
+
+    /// VaR of the strategy's positions once the order fills
+    pub var_after: Decimal,
+
+    /// `var_after - var_before`; negative when the order lowers VaR
+    pub marginal_var: Decimal,
+
+    /// `var_after` as a percentage of the budget
+    pub budget_used_pct: Decimal,
+}
+
+impl MarginalVar {
+    /// Whether the order leaves the strategy over budget and adds to its VaR
+    pub fn exceeds_budget(&self) -> bool {
+        self.var_after > self.budget.max_var && self.marginal_var > Decimal::ZERO
+    }
+}
+
+/// Measure the VaR `order` adds to its strategy's share of `positions`
+///
+/// `returns` holds the daily returns of each symbol, oldest first; the
+/// strategy's symbols are compared over the days they all have. Holdings are
+/// valued at `prices`, else at the position's current price, else at the
+/// order's limit price.
+pub fn marginal_var(
+    order: &Order,
+    positions: &[Position],
+    budget: &VarBudget,
+    returns: &HashMap<Symbol, Vec<Decimal>>,
+    prices: &HashMap<Symbol, Decimal>,
+) -> Result<MarginalVar> {
+    let mut net: BTreeMap<String, (Symbol, Decimal, Option<Decimal>)> = BTreeMap::new();
+    for position in positions
+        .iter()
+        .filter(|p| p.strategy_id == order.strategy_id && !p.is_closed())
+    {
+        let entry = net.entry(position.symbol.as_str().to_string()).or_insert((
+            position.symbol.clone(),
+            Decimal::ZERO,
+            None,
+        ));
+        entry.1 += signed_quantity(position);
+        entry.2 = Some(
+            prices
+                .get(&position.symbol)
+                .copied()
+                .unwrap_or(position.current_price.as_decimal()),
+        );
+    }
+    let before = net.clone();
+
+    let entry = net.entry(order.symbol.as_str().to_string()).or_insert((
+        order.symbol.clone(),
+        Decimal::ZERO,
+        None,
+    ));
+    entry.1 += match order.side {
+        OrderSide::Buy => order.quantity.as_decimal(),
+        OrderSide::Sell => -order.quantity.as_decimal(),
+    };
+    if entry.2.is_none() {
+        entry.2 = prices
+            .get(&order.symbol)
+            .copied()
+            .or(order.price.map(|p| p.as_decimal()));
+    }
+
+    // One-day VaR, scaled to the budget's horizon below
+    let calculator = VarCalculator::new(VarConfig {
+        confidence_level: budget.confidence_level,
+        method: VarMethod::Historical,
+        ..Default::default()
+    });
+    let var_before = strategy_var(&calculator, order.strategy_id, &before, returns)?;
+    let var_after = strategy_var(&calculator, order.strategy_id, &net, returns)?;
+
+    let scale = Decimal::from(budget.horizon_days)
+        .sqrt()
+        .unwrap_or(Decimal::ONE);
+    let (var_before, var_after) = (var_before * scale, var_after * scale);
+    Ok(MarginalVar {
+        strategy_id: order.strategy_id,
+        budget: budget.clone(),
+        var_before,
+        var_after,
+        marginal_var: var_after - var_before,
+        budget_used_pct: (var_after / budget.max_var * dec!(100)).round_dp(2),
+    })
+}
+
+/// Daily returns of consecutive daily closes, oldest first
+pub fn returns_from_closes(closes: &[Decimal]) -> Vec<Decimal> {
+    closes
+        .windows(2)
+        .filter(|w| !w[0].is_zero())
+        .map(|w| w[1] / w[0] - Decimal::ONE)
+        .collect()
+}
+
+fn signed_quantity(position: &Position) -> Decimal {
+    match position.side {
+        PositionSide::Short => -position.quantity.as_decimal(),
+        PositionSide::Long | PositionSide::Net => position.quantity.as_decimal(),
+    }
+}
+
+/// One-day VaR of net quantities per symbol, priced at their last price
+fn strategy_var(
+    calculator: &VarCalculator,
+    strategy_id: Uuid,
+    net: &BTreeMap<String, (Symbol, Decimal, Option<Decimal>)>,
+    returns: &HashMap<Symbol, Vec<Decimal>>,
+) -> Result<Decimal> {
+    let mut positions = Vec::new();
+    let mut histories = Vec::new();
+    for (symbol, quantity, price) in net.values().filter(|(_, qty, _)| !qty.is_zero()) {
+        let price = price.ok_or_else(|| {
+            Error::CalculationError(format!("No price to value {} at", symbol.as_str()))
+        })?;
+        let history = returns
+            .get(symbol)
+            .filter(|r| !r.is_empty())
+            .ok_or_else(|| {
+                Error::CalculationError(format!("No return history for {}", symbol.as_str()))
+            })?;
+        let side = if quantity.is_sign_negative() {
+            PositionSide::Short
+        } else {
+            PositionSide::Long
+        };
+        positions.push(Position::new(
+            strategy_id,
+            symbol.clone(),
+            side,
+            Quantity::new(quantity.abs())?,
+            Price::new(price)?,
+        ));
+        histories.push(history);
+    }
+    if positions.is_empty() {
+        return Ok(Decimal::ZERO);
+    }
+
+    // Compare the symbols over the latest days they all have
+    let days = histories.iter().map(|r| r.len()).min().unwrap_or(0);
+    let aligned: Vec<Vec<Decimal>> = histories
+        .iter()
+        .map(|r| r[r.len() - days..].to_vec())
+        .collect();
+    let gross: Decimal = positions.iter().map(|p| p.position_value().abs()).sum();
+    let result = calculator.calculate_var(&positions, &aligned, gross)?;
+    Ok(result.var_amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ea_okx_core::models::OrderType;
+
+    /// Two bad days in twenty: the 95% one-day VaR is the second worst, 5%
+    fn history() -> Vec<Decimal> {
+        let mut returns = vec![dec!(-0.10), dec!(-0.05)];
+        returns.extend(std::iter::repeat_n(dec!(0.01), 18));
+        returns
+    }
+
+    fn order(strategy: Uuid, symbol: &str, side: OrderSide, qty: Decimal) -> Order {
+        Order::new(
+            strategy,
+            Symbol::new(symbol).unwrap(),
+            side,
+            OrderType::Limit,
+            Quantity::new(qty).unwrap(),
+            Some(Price::new(dec!(100)).unwrap()),
+        )
+    }
+
+    #[test]
+    fn test_marginal_var_against_budget() {
+        let (strategy, other) = (Uuid::new_v4(), Uuid::new_v4());
+        let btc = Symbol::new("BTC-USDT").unwrap();
+        let positions = vec![
+            Position::new(
+                strategy,
+                btc.clone(),
+                PositionSide::Long,
+                Quantity::new(dec!(1)).unwrap(),
+                Price::new(dec!(100)).unwrap(),
+            ),
+            // Other strategies' positions do not count
+            Position::new(
+                other,
+                btc.clone(),
+                PositionSide::Long,
+                Quantity::new(dec!(50)).unwrap(),
+                Price::new(dec!(100)).unwrap(),
+            ),
+        ];
+        let returns = HashMap::from([
+            (btc.clone(), history()),
+            (Symbol::new("ETH-USDT").unwrap(), history()),
+        ]);
+        let prices = HashMap::new();
+        let budget = VarBudget::new(dec!(8));
+
+        let buy = order(strategy, "BTC-USDT", OrderSide::Buy, dec!(1));
+        let marginal = marginal_var(&buy, &positions, &budget, &returns, &prices).unwrap();
+        assert_eq!(marginal.var_before, dec!(5));
+        assert_eq!(marginal.var_after, dec!(10));
+        assert_eq!(marginal.marginal_var, dec!(5));
+        assert_eq!(marginal.budget_used_pct, dec!(125));
+        assert!(marginal.exceeds_budget());
+
+        // Flipping short loses on the good days instead
+        let sell = order(strategy, "BTC-USDT", OrderSide::Sell, dec!(2));
+        let marginal = marginal_var(&sell, &positions, &budget, &returns, &prices).unwrap();
+        assert_eq!(marginal.var_after, dec!(1));
+        assert!(!marginal.exceeds_budget());
+
+        // A short in a perfectly correlated symbol hedges it away
+        let hedge = order(strategy, "ETH-USDT", OrderSide::Sell, dec!(1));
+        let marginal = marginal_var(&hedge, &positions, &budget, &returns, &prices).unwrap();
+        assert_eq!(marginal.var_after, Decimal::ZERO);
+
+        // Over budget already, an order that lowers VaR still passes
+        let tight = VarBudget::new(dec!(2));
+        let reduce = order(strategy, "BTC-USDT", OrderSide::Sell, dec!(0.5));
+        let marginal = marginal_var(&reduce, &positions, &tight, &returns, &prices).unwrap();
+        assert_eq!(marginal.var_after, dec!(2.5));
+        assert!(!marginal.exceeds_budget());
+
+        let four_days = VarBudget {
+            horizon_days: 4,
+            ..budget.clone()
+        };
+        let marginal = marginal_var(&buy, &positions, &four_days, &returns, &prices).unwrap();
+        assert_eq!(marginal.var_after, dec!(20));
+
+        let unknown = order(strategy, "SOL-USDT", OrderSide::Buy, dec!(1));
+        assert!(marginal_var(&unknown, &positions, &budget, &returns, &prices).is_err());
+    }
+
+    #[test]
+    fn test_budget_validation_and_returns() {
+        assert!(VarBudget::new(dec!(2000)).validate().is_ok());
+        assert!(VarBudget::new(Decimal::ZERO).validate().is_err());
+        let budget = VarBudget {
+            confidence_level: 1.0,
+            ..VarBudget::new(dec!(2000))
+        };
+        assert!(budget.validate().is_err());
+
+        let parsed: VarBudget = serde_json::from_str(r#"{"max_var":"2000"}"#).unwrap();
+        assert_eq!(parsed, VarBudget::new(dec!(2000)));
+
+        assert_eq!(
+            returns_from_closes(&[dec!(100), dec!(110), dec!(99)]),
+            [dec!(0.1), dec!(-0.1)]
+        );
+    }
+}
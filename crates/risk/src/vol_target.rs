@@ -0,0 +1,316 @@
+//! Volatility-targeting portfolio overlay
+//!
+//! Scales every strategy's target exposure by a single factor chosen so the
+//! portfolio's annualized volatility, estimated from an EWMA of portfolio
+//! returns, lands on a configured target. The factor is recomputed at most
+//! once per interval (daily by default) and clamped so a quiet stretch cannot
+//! lever the book up without bound.
+
+use crate::error::{Error, Result};
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Volatility targeting configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VolTargetConfig {
+    /// Annualized portfolio volatility to aim for (e.g., 0.15 = 15%)
+    pub target_volatility: f64,
+
+    /// EWMA decay factor (RiskMetrics uses 0.94 for daily returns)
+    pub ewma_lambda: f64,
+
+    /// Return periods per year (365 for daily crypto returns)
+    pub periods_per_year: f64,
+
+    /// Lower bound on the scale factor
+    pub min_scale: f64,
+
+    /// Upper bound on the scale factor
+    pub max_scale: f64,
+
+    /// Returns required before the estimate is trusted; until then the
+    /// scale stays at 1
+    pub min_observations: usize,
+
+    /// Minimum time between scale recomputations
+    pub recompute_interval_secs: i64,
+}
+
+impl Default for VolTargetConfig {
+    fn default() -> Self {
+        Self {
+            target_volatility: 0.15,
+            ewma_lambda: 0.94,
+            periods_per_year: 365.0,
+            min_scale: 0.25,
+            max_scale: 2.0,
+            min_observations: 20,
+            recompute_interval_secs: 86_400,
+        }
+    }
+}
+
+impl VolTargetConfig {
+    fn validate(&self) -> Result<()> {
+        if !(self.ewma_lambda > 0.0 && self.ewma_lambda < 1.0) {
+            return Err(Error::ValidationFailed(format!(
+                "EWMA lambda must be in (0, 1), got {}",
+                self.ewma_lambda
+            )));
+        }
+        if self.target_volatility <= 0.0 || self.periods_per_year <= 0.0 {
+            return Err(Error::ValidationFailed(
+                "Target volatility and periods per year must be positive".to_string(),
+            ));
+        }
+        if self.min_scale <= 0.0 || self.min_scale > self.max_scale {
+            return Err(Error::ValidationFailed(format!(
+                "Invalid scale bounds [{}, {}]",
+                self.min_scale, self.max_scale
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Exponentially weighted moving variance of returns
+#[derive(Debug, Clone)]
+pub struct EwmaVolatility {
+    lambda: f64,
+    variance: Option<f64>,
+    observations: usize,
+}
+
+impl EwmaVolatility {
+    pub fn new(lambda: f64) -> Self {
+        Self {
+            lambda,
+            variance: None,
+            observations: 0,
+        }
+    }
+
+    /// Fold in one period's return; the first return seeds the variance
+    pub fn update(&mut self, ret: f64) {
+        let squared = ret * ret;
+        self.variance = Some(match self.variance {
+            Some(prev) => self.lambda * prev + (1.0 - self.lambda) * squared,
+            None => squared,
+        });
+        self.observations += 1;
+    }
+
+    pub fn observations(&self) -> usize {
+        self.observations
+    }
+
+    /// Per-period volatility
+    pub fn volatility(&self) -> Option<f64> {
+        self.variance.map(f64::sqrt)
+    }
+
+    /// Volatility scaled to a year of `periods_per_year` periods
+    pub fn annualized(&self, periods_per_year: f64) -> Option<f64> {
+        self.volatility().map(|vol| vol * periods_per_year.sqrt())
+    }
+}
+
+/// Current overlay state, suitable for publishing as metrics
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VolTargetSnapshot {
+    pub target_volatility: f64,
+    pub realized_volatility: Option<f64>,
+    pub scale: Decimal,
+    pub computed_at: Option<DateTime<Utc>>,
+}
+
+/// Portfolio overlay scaling target exposures toward a volatility target
+#[derive(Debug, Clone)]
+pub struct VolTargetOverlay {
+    config: VolTargetConfig,
+    estimator: EwmaVolatility,
+    scale: Decimal,
+    computed_at: Option<DateTime<Utc>>,
+}
+
+impl VolTargetOverlay {
+    pub fn new(config: VolTargetConfig) -> Result<Self> {
+        config.validate()?;
+
+        Ok(Self {
+            estimator: EwmaVolatility::new(config.ewma_lambda),
+            config,
+            scale: Decimal::ONE,
+            computed_at: None,
+        })
+    }
+
+    /// Record one period's portfolio return (e.g., 0.01 = +1%)
+    pub fn record_return(&mut self, portfolio_return: Decimal) {
+        if let Some(ret) = portfolio_return.to_f64() {
+            self.estimator.update(ret);
+        }
+    }
+
+    /// Annualized realized volatility estimate
+    pub fn realized_volatility(&self) -> Option<f64> {
+        self.estimator.annualized(self.config.periods_per_year)
+    }
+
+    /// Scale factor currently applied to exposures
+    pub fn scale(&self) -> Decimal {
+        self.scale
+    }
+
+    /// Recompute the scale factor if the interval has elapsed
+    ///
+    /// Returns the new factor when it was recomputed.
+    pub fn recompute_if_due(&mut self, now: DateTime<Utc>) -> Option<Decimal> {
+        let interval = Duration::seconds(self.config.recompute_interval_secs);
+        if self.computed_at.is_some_and(|at| now - at < interval) {
+            return None;
+        }
+
+        self.scale = self.target_scale();
+        self.computed_at = Some(now);
+        Some(self.scale)
+    }
+
+    /// Scale each strategy's target exposure by the current factor
+    pub fn apply<K>(&self, exposures: &HashMap<K, Decimal>) -> HashMap<K, Decimal>
+    where
+        K: Clone + Eq + Hash,
+    {
+        exposures
+            .iter()
+            .map(|(key, exposure)| (key.clone(), *exposure * self.scale))
+            .collect()
+    }
+
+    pub fn snapshot(&self) -> VolTargetSnapshot {
+        VolTargetSnapshot {
+            target_volatility: self.config.target_volatility,
+            realized_volatility: self.realized_volatility(),
+            scale: self.scale,
+            computed_at: self.computed_at,
+        }
+    }
+
+    fn target_scale(&self) -> Decimal {
+        if self.estimator.observations() < self.config.min_observations {
+            return Decimal::ONE;
+        }
+
+        let raw = match self.realized_volatility() {
+            Some(realized) if realized > 0.0 => self.config.target_volatility / realized,
+            _ => self.config.max_scale,
+        };
+        let clamped = raw.clamp(self.config.min_scale, self.config.max_scale);
+
+        Decimal::from_f64(clamped)
+            .map(|scale| scale.round_dp(4))
+            .unwrap_or(Decimal::ONE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn new_overlay(min_observations: usize) -> VolTargetOverlay {
+        VolTargetOverlay::new(VolTargetConfig {
+            min_observations,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_ewma_tracks_constant_volatility() {
+        let mut ewma = EwmaVolatility::new(0.94);
+        for i in 0..200 {
+            ewma.update(if i % 2 == 0 { 0.02 } else { -0.02 });
+        }
+        assert!((ewma.volatility().unwrap() - 0.02).abs() < 1e-9);
+        assert!((ewma.annualized(365.0).unwrap() - 0.02 * 365f64.sqrt()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_scale_hits_target_and_is_clamped() {
+        // 1% daily moves: ~19.1% annualized, above the 15% target
+        let mut overlay = new_overlay(5);
+        for i in 0..50 {
+            overlay.record_return(if i % 2 == 0 { dec!(0.01) } else { dec!(-0.01) });
+        }
+        let scale = overlay.recompute_if_due(Utc::now()).unwrap();
+        let expected = 0.15 / (0.01 * 365f64.sqrt());
+        assert!((scale.to_f64().unwrap() - expected).abs() < 1e-3);
+
+        // 10% daily moves would need a scale below the floor
+        let mut wild = new_overlay(5);
+        for i in 0..50 {
+            wild.record_return(if i % 2 == 0 { dec!(0.1) } else { dec!(-0.1) });
+        }
+        assert_eq!(wild.recompute_if_due(Utc::now()), Some(dec!(0.25)));
+    }
+
+    #[test]
+    fn test_neutral_until_enough_observations() {
+        let mut overlay = new_overlay(20);
+        for _ in 0..5 {
+            overlay.record_return(dec!(0.05));
+        }
+        assert_eq!(overlay.recompute_if_due(Utc::now()), Some(Decimal::ONE));
+    }
+
+    #[test]
+    fn test_recomputes_at_most_daily() {
+        let mut overlay = new_overlay(1);
+        let start = Utc::now();
+        overlay.record_return(dec!(0.001));
+        assert!(overlay.recompute_if_due(start).is_some());
+
+        overlay.record_return(dec!(0.2));
+        assert!(
+            overlay
+                .recompute_if_due(start + Duration::hours(12))
+                .is_none()
+        );
+        assert!(
+            overlay
+                .recompute_if_due(start + Duration::days(1))
+                .is_some()
+        );
+        assert_eq!(
+            overlay.snapshot().computed_at,
+            Some(start + Duration::days(1))
+        );
+    }
+
+    #[test]
+    fn test_apply_scales_every_strategy() {
+        let mut overlay = new_overlay(1);
+        overlay.record_return(dec!(0.1));
+        overlay.recompute_if_due(Utc::now());
+        let scale = overlay.scale();
+
+        let exposures = HashMap::from([("trend", dec!(10000)), ("carry", dec!(-4000))]);
+        let scaled = overlay.apply(&exposures);
+        assert_eq!(scaled["trend"], dec!(10000) * scale);
+        assert_eq!(scaled["carry"], dec!(-4000) * scale);
+    }
+
+    #[test]
+    fn test_rejects_invalid_config() {
+        let config = VolTargetConfig {
+            min_scale: 3.0,
+            ..Default::default()
+        };
+        assert!(VolTargetOverlay::new(config).is_err());
+    }
+}
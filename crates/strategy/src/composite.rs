@@ -0,0 +1,784 @@
+//! Strategy composition
+//!
+//! A [`CompositeStrategy`] runs child strategies side by side and combines
+//! their signals, so entry filters are configured once instead of being
+//! re-implemented inside every strategy. Voting children decide the direction
+//! through a [`Combiner`]; veto and confirm children, and the built-in
+//! [`FilterConfig`] filters, can then block the entry. Filters only ever
+//! block entries: exits from any voting child pass unchanged.
+//!
+//! The composite is itself a [`Strategy`], so it runs unchanged in the
+//! backtest engine and in live execution. Its layout is plain data:
+//!
+//! ```json
+//! {
+//!   "combiner": { "type": "weighted_confidence", "threshold": 0.3 },
+//!   "children": [
+//!     { "name": "fast", "strategy": "ma_crossover", "weight": 2.0 },
+//!     { "name": "rsi", "strategy": "rsi_reversion" },
+//!     { "name": "funding", "strategy": "funding_bias", "role": "veto" }
+//!   ],
+//!   "filters": [{ "type": "trend", "interval": "4H", "period": 20 }]
+//! }
+//! ```
+
+use crate::custom_metrics::StrategyMetrics;
+use crate::error::{Error, Result};
+use crate::metrics::PerformanceMetrics;
+use crate::signal::{Signal, SignalType};
+use crate::traits::{MarketDataEvent, Strategy, StrategyConfig};
+use async_trait::async_trait;
+use ea_okx_core::Interval;
+use ea_okx_core::models::Order;
+use ea_okx_core::types::Symbol;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value as JsonValue, json};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// How voting children's signals are merged into one
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Combiner {
+    /// Enter when more than half of the voting children agree
+    MajorityVote,
+
+    /// Enter when the weighted mean of signed confidences (buys positive,
+    /// sells negative, holds zero) reaches `threshold`
+    WeightedConfidence { threshold: f64 },
+}
+
+/// Part a child plays in the composite
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChildRole {
+    /// Votes on the direction
+    #[default]
+    Vote,
+    /// Blocks entries opposite to its own signal
+    Veto,
+    /// Blocks entries unless its own signal agrees
+    Confirm,
+}
+
+/// One child strategy of a composite
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChildConfig {
+    /// Unique within the composite; used in signal metadata and state
+    pub name: String,
+
+    /// Strategy to build, interpreted by the caller's factory
+    pub strategy: String,
+
+    #[serde(default)]
+    pub role: ChildRole,
+
+    /// Vote weight for [`Combiner::WeightedConfidence`]
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+
+    /// Parameters merged over the composite's own for this child
+    #[serde(default)]
+    pub parameters: HashMap<String, JsonValue>,
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+/// Built-in entry filter
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FilterConfig {
+    /// Buy only while the last `interval` close is above its `period`-bar
+    /// average, sell only while below; blocks until `period` bars are seen
+    Trend { interval: Interval, period: usize },
+
+    /// Entries need at least `min` combined confidence
+    MinConfidence { min: f64 },
+}
+
+impl FilterConfig {
+    fn name(&self) -> String {
+        match self {
+            FilterConfig::Trend { interval, .. } => format!("trend_{}", interval),
+            FilterConfig::MinConfidence { .. } => "min_confidence".to_string(),
+        }
+    }
+}
+
+/// Declarative layout of a composite strategy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompositeConfig {
+    pub combiner: Combiner,
+    pub children: Vec<ChildConfig>,
+    #[serde(default)]
+    pub filters: Vec<FilterConfig>,
+}
+
+impl CompositeConfig {
+    pub fn validate(&self) -> Result<()> {
+        if !self
+            .children
+            .iter()
+            .any(|child| child.role == ChildRole::Vote)
+        {
+            return Err(Error::InvalidConfig(
+                "composite needs at least one voting child".to_string(),
+            ));
+        }
+
+        let mut names = HashSet::new();
+        for child in &self.children {
+            if !names.insert(child.name.as_str()) {
+                return Err(Error::InvalidConfig(format!(
+                    "duplicate child name '{}'",
+                    child.name
+                )));
+            }
+            if !(child.weight.is_finite() && child.weight > 0.0) {
+                return Err(Error::InvalidConfig(format!(
+                    "child '{}' weight must be positive",
+                    child.name
+                )));
+            }
+        }
+
+        if let Combiner::WeightedConfidence { threshold } = self.combiner
+            && !(threshold > 0.0 && threshold <= 1.0)
+        {
+            return Err(Error::InvalidConfig(
+                "weighted confidence threshold must be in (0, 1]".to_string(),
+            ));
+        }
+
+        for filter in &self.filters {
+            if let FilterConfig::Trend { period: 0, .. } = filter {
+                return Err(Error::InvalidConfig(
+                    "trend filter period must be positive".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Entry direction of a signal: 1 buy, -1 sell, 0 anything else
+fn direction(signal_type: SignalType) -> i8 {
+    match signal_type {
+        SignalType::Buy => 1,
+        SignalType::Sell => -1,
+        SignalType::Hold | SignalType::CloseLong | SignalType::CloseShort => 0,
+    }
+}
+
+fn is_exit(signal_type: SignalType) -> bool {
+    matches!(signal_type, SignalType::CloseLong | SignalType::CloseShort)
+}
+
+/// Merge voting children's signals; `None` when no entry or exit wins
+fn combine(combiner: Combiner, votes: &[(&ChildConfig, Signal)]) -> Option<Signal> {
+    let strongest = |dir: i8, confidence: f64| {
+        votes
+            .iter()
+            .filter(|(_, signal)| direction(signal.signal_type) == dir)
+            .max_by(|a, b| a.1.confidence.total_cmp(&b.1.confidence))
+            .map(|(_, signal)| Signal {
+                confidence,
+                ..signal.clone()
+            })
+    };
+
+    let entry = match combiner {
+        Combiner::MajorityVote => [1, -1].into_iter().find_map(|dir| {
+            let agreeing: Vec<f64> = votes
+                .iter()
+                .filter(|(_, signal)| direction(signal.signal_type) == dir)
+                .map(|(_, signal)| signal.confidence)
+                .collect();
+            if agreeing.len() * 2 <= votes.len() {
+                return None;
+            }
+            let mean = agreeing.iter().sum::<f64>() / agreeing.len() as f64;
+            strongest(dir, mean)
+        }),
+        Combiner::WeightedConfidence { threshold } => {
+            let total: f64 = votes.iter().map(|(child, _)| child.weight).sum();
+            let score = votes
+                .iter()
+                .map(|(child, signal)| {
+                    child.weight * signal.confidence * f64::from(direction(signal.signal_type))
+                })
+                .sum::<f64>()
+                / total;
+            if score >= threshold {
+                strongest(1, score)
+            } else if score <= -threshold {
+                strongest(-1, -score)
+            } else {
+                None
+            }
+        }
+    };
+
+    entry.or_else(|| {
+        votes
+            .iter()
+            .filter(|(_, signal)| is_exit(signal.signal_type))
+            .max_by(|a, b| a.1.confidence.total_cmp(&b.1.confidence))
+            .map(|(_, signal)| signal.clone())
+    })
+}
+
+/// Closes of one symbol on one interval, for the trend filter
+#[derive(Debug, Default)]
+struct TrendWindow {
+    closes: VecDeque<Decimal>,
+}
+
+impl TrendWindow {
+    fn push(&mut self, close: Decimal, period: usize) {
+        self.closes.push_back(close);
+        while self.closes.len() > period {
+            self.closes.pop_front();
+        }
+    }
+
+    /// Direction of the last close against the window average, once full
+    fn trend(&self, period: usize) -> Option<i8> {
+        if self.closes.len() < period {
+            return None;
+        }
+        let last = *self.closes.back()?;
+        let mean = self.closes.iter().sum::<Decimal>() / Decimal::from(self.closes.len());
+        Some(match last.cmp(&mean) {
+            std::cmp::Ordering::Greater => 1,
+            std::cmp::Ordering::Less => -1,
+            std::cmp::Ordering::Equal => 0,
+        })
+    }
+}
+
+/// Meta-strategy combining the signals of its child strategies
+pub struct CompositeStrategy {
+    config: CompositeConfig,
+    children: Vec<Box<dyn Strategy>>,
+
+    /// Trend filter state per (filter index, symbol)
+    trends: HashMap<(usize, Symbol), TrendWindow>,
+
+    /// Symbol of the latest market data, used when a signal names none
+    last_symbol: Option<Symbol>,
+}
+
+impl CompositeStrategy {
+    /// Composite over `children`, given in the order of `config.children`
+    pub fn new(config: CompositeConfig, children: Vec<Box<dyn Strategy>>) -> Result<Self> {
+        config.validate()?;
+        if children.len() != config.children.len() {
+            return Err(Error::InvalidConfig(format!(
+                "{} child strategies given for {} configured",
+                children.len(),
+                config.children.len()
+            )));
+        }
+        Ok(Self {
+            config,
+            children,
+            trends: HashMap::new(),
+            last_symbol: None,
+        })
+    }
+
+    /// Composite whose children are built by `factory` from their config
+    pub fn from_factory(
+        config: CompositeConfig,
+        factory: impl Fn(&ChildConfig) -> Result<Box<dyn Strategy>>,
+    ) -> Result<Self> {
+        let children = config
+            .children
+            .iter()
+            .map(&factory)
+            .collect::<Result<Vec<_>>>()?;
+        Self::new(config, children)
+    }
+
+    pub fn config(&self) -> &CompositeConfig {
+        &self.config
+    }
+
+    /// Name of the first filter or child blocking an entry in direction
+    /// `dir`, if any
+    fn blocked_by(
+        &self,
+        dir: i8,
+        confidence: f64,
+        symbol: Option<&Symbol>,
+        signals: &[Signal],
+    ) -> Option<String> {
+        for (child, signal) in self.config.children.iter().zip(signals) {
+            let agrees = direction(signal.signal_type);
+            let blocks = match child.role {
+                ChildRole::Vote => false,
+                ChildRole::Veto => agrees == -dir,
+                ChildRole::Confirm => agrees != dir,
+            };
+            if blocks {
+                return Some(child.name.clone());
+            }
+        }
+
+        for (index, filter) in self.config.filters.iter().enumerate() {
+            let passes = match filter {
+                FilterConfig::Trend { period, .. } => symbol
+                    .and_then(|symbol| self.trends.get(&(index, symbol.clone())))
+                    .and_then(|window| window.trend(*period))
+                    .is_some_and(|trend| trend == dir),
+                FilterConfig::MinConfidence { min } => confidence >= *min,
+            };
+            if !passes {
+                return Some(filter.name());
+            }
+        }
+        None
+    }
+}
+
+#[async_trait]
+impl Strategy for CompositeStrategy {
+    /// Each child reports under `strategy.<id>.<child name>.`
+    fn attach_metrics(&mut self, metrics: StrategyMetrics) {
+        for (child, strategy) in self.config.children.iter().zip(&mut self.children) {
+            strategy.attach_metrics(metrics.scoped(&child.name));
+        }
+    }
+
+    async fn initialize(&mut self, config: StrategyConfig) -> Result<()> {
+        for (child, strategy) in self.config.children.iter().zip(&mut self.children) {
+            let mut child_config = config.clone();
+            child_config.name = format!("{}/{}", config.name, child.name);
+            child_config.parameters.extend(child.parameters.clone());
+            strategy.initialize(child_config).await?;
+        }
+        Ok(())
+    }
+
+    async fn on_market_data(&mut self, event: MarketDataEvent) -> Result<()> {
+        if let MarketDataEvent::Candle {
+            symbol,
+            interval,
+            close,
+            ..
+        } = &event
+        {
+            for (index, filter) in self.config.filters.iter().enumerate() {
+                if let FilterConfig::Trend {
+                    interval: wanted,
+                    period,
+                } = filter
+                    && wanted == interval
+                {
+                    self.trends
+                        .entry((index, symbol.clone()))
+                        .or_default()
+                        .push(*close, *period);
+                }
+            }
+        }
+        let symbol = match &event {
+            MarketDataEvent::Ticker { symbol, .. }
+            | MarketDataEvent::Candle { symbol, .. }
+            | MarketDataEvent::Trade { symbol, .. }
+            | MarketDataEvent::FundingRate { symbol, .. }
+            | MarketDataEvent::OpenInterest { symbol, .. }
+            | MarketDataEvent::TakerVolume { symbol, .. }
+            | MarketDataEvent::LongShortRatio { symbol, .. }
+            | MarketDataEvent::Gap { symbol, .. } => symbol.clone(),
+        };
+        self.last_symbol = Some(symbol);
+
+        for child in &mut self.children {
+            child.on_market_data(event.clone()).await?;
+        }
+        Ok(())
+    }
+
+    async fn generate_signal(&self) -> Result<Signal> {
+        let mut signals = Vec::with_capacity(self.children.len());
+        for child in &self.children {
+            signals.push(child.generate_signal().await?);
+        }
+
+        let votes: Vec<(&ChildConfig, Signal)> = self
+            .config
+            .children
+            .iter()
+            .zip(&signals)
+            .filter(|(child, _)| child.role == ChildRole::Vote)
+            .map(|(child, signal)| (child, signal.clone()))
+            .collect();
+        let votes_json: Map<String, JsonValue> = self
+            .config
+            .children
+            .iter()
+            .zip(&signals)
+            .map(|(child, signal)| (child.name.clone(), json!(signal.signal_type)))
+            .collect();
+
+        let mut blocked_by = None;
+        let mut signal = match combine(self.config.combiner, &votes) {
+            Some(signal) if direction(signal.signal_type) != 0 => {
+                let symbol = signal
+                    .metadata
+                    .get("symbol")
+                    .and_then(JsonValue::as_str)
+                    .and_then(|s| Symbol::new(s).ok())
+                    .or_else(|| self.last_symbol.clone());
+                blocked_by = self.blocked_by(
+                    direction(signal.signal_type),
+                    signal.confidence,
+                    symbol.as_ref(),
+                    &signals,
+                );
+                if blocked_by.is_some() {
+                    Signal::hold()
+                } else {
+                    signal
+                }
+            }
+            Some(exit) => exit,
+            None => Signal::hold(),
+        };
+
+        if !signal.metadata.is_object() {
+            signal.metadata = json!({});
+        }
+        if let Some(metadata) = signal.metadata.as_object_mut() {
+            metadata.insert(
+                "composite".to_string(),
+                json!({ "votes": votes_json, "blocked_by": blocked_by }),
+            );
+        }
+        Ok(signal)
+    }
+
+    async fn on_order_fill(&mut self, order: &Order) -> Result<()> {
+        for child in &mut self.children {
+            child.on_order_fill(order).await?;
+        }
+        Ok(())
+    }
+
+    async fn on_order_reject(&mut self, order: &Order, reason: &str) -> Result<()> {
+        for child in &mut self.children {
+            child.on_order_reject(order, reason).await?;
+        }
+        Ok(())
+    }
+
+    /// Metrics of the first voting child; children see the same fills
+    fn get_metrics(&self) -> PerformanceMetrics {
+        self.config
+            .children
+            .iter()
+            .zip(&self.children)
+            .find(|(child, _)| child.role == ChildRole::Vote)
+            .map(|(_, strategy)| strategy.get_metrics())
+            .unwrap_or_default()
+    }
+
+    fn serialize_state(&self) -> Result<JsonValue> {
+        let mut children = Map::new();
+        for (child, strategy) in self.config.children.iter().zip(&self.children) {
+            children.insert(child.name.clone(), strategy.serialize_state()?);
+        }
+        Ok(json!({ "children": children }))
+    }
+
+    fn deserialize_state(&mut self, state: JsonValue) -> Result<()> {
+        let Some(states) = state.get("children").and_then(JsonValue::as_object) else {
+            return Err(Error::InvalidConfig(
+                "composite state has no children".to_string(),
+            ));
+        };
+        for (child, strategy) in self.config.children.iter().zip(&mut self.children) {
+            if let Some(child_state) = states.get(&child.name) {
+                strategy.deserialize_state(child_state.clone())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Changes reach every child that does not override them in its own
+    /// parameters, so a child refusing live updates refuses the whole update
+    async fn on_parameters_updated(
+        &mut self,
+        parameters: &HashMap<String, JsonValue>,
+    ) -> Result<()> {
+        for (child, strategy) in self.config.children.iter().zip(&mut self.children) {
+            let inherited: HashMap<String, JsonValue> = parameters
+                .iter()
+                .filter(|(name, _)| !child.parameters.contains_key(*name))
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect();
+            if inherited.is_empty() {
+                continue;
+            }
+            strategy
+                .on_parameters_updated(&inherited)
+                .await
+                .map_err(|e| Error::InvalidConfig(format!("child '{}': {}", child.name, e)))?;
+        }
+        Ok(())
+    }
+
+    async fn shutdown(&mut self) -> Result<()> {
+        for child in &mut self.children {
+            child.shutdown().await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone, Utc};
+    use parking_lot::Mutex;
+    use std::sync::Arc;
+
+    /// Emits whatever signal the test last set
+    struct Scripted(Arc<Mutex<Signal>>);
+
+    #[async_trait]
+    impl Strategy for Scripted {
+        async fn initialize(&mut self, _config: StrategyConfig) -> Result<()> {
+            Ok(())
+        }
+
+        async fn on_market_data(&mut self, _event: MarketDataEvent) -> Result<()> {
+            Ok(())
+        }
+
+        async fn generate_signal(&self) -> Result<Signal> {
+            Ok(self.0.lock().clone())
+        }
+
+        async fn on_order_fill(&mut self, _order: &Order) -> Result<()> {
+            Ok(())
+        }
+
+        async fn on_order_reject(&mut self, _order: &Order, _reason: &str) -> Result<()> {
+            Ok(())
+        }
+
+        fn get_metrics(&self) -> PerformanceMetrics {
+            PerformanceMetrics::default()
+        }
+
+        fn serialize_state(&self) -> Result<JsonValue> {
+            Ok(json!({ "signal": self.0.lock().signal_type }))
+        }
+
+        fn deserialize_state(&mut self, _state: JsonValue) -> Result<()> {
+            Ok(())
+        }
+
+        async fn shutdown(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    fn composite(config: JsonValue) -> (CompositeStrategy, HashMap<String, Arc<Mutex<Signal>>>) {
+        let config: CompositeConfig = serde_json::from_value(config).unwrap();
+        let handles: HashMap<_, _> = config
+            .children
+            .iter()
+            .map(|child| (child.name.clone(), Arc::new(Mutex::new(Signal::hold()))))
+            .collect();
+        let strategy = CompositeStrategy::from_factory(config, |child| {
+            Ok(Box::new(Scripted(handles[&child.name].clone())) as Box<dyn Strategy>)
+        })
+        .unwrap();
+        (strategy, handles)
+    }
+
+    fn set(handles: &HashMap<String, Arc<Mutex<Signal>>>, name: &str, signal: Signal) {
+        *handles[name].lock() = signal;
+    }
+
+    fn candle(interval: Interval, hour: i64, close: i64) -> MarketDataEvent {
+        MarketDataEvent::Candle {
+            symbol: Symbol::new("BTC-USDT").unwrap(),
+            interval,
+            open: Decimal::from(close),
+            high: Decimal::from(close),
+            low: Decimal::from(close),
+            close: Decimal::from(close),
+            volume: Decimal::ONE,
+            timestamp: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::hours(hour),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_majority_vote_and_exits() {
+        let (strategy, handles) = composite(json!({
+            "combiner": { "type": "majority_vote" },
+            "children": [
+                { "name": "a", "strategy": "scripted" },
+                { "name": "b", "strategy": "scripted" },
+                { "name": "c", "strategy": "scripted" }
+            ]
+        }));
+
+        set(&handles, "a", Signal::buy(0.9));
+        set(&handles, "b", Signal::buy(0.5));
+        let signal = strategy.generate_signal().await.unwrap();
+        assert_eq!(signal.signal_type, SignalType::Buy);
+        assert!((signal.confidence - 0.7).abs() < 1e-9);
+        assert_eq!(signal.metadata["composite"]["votes"]["c"], json!("Hold"));
+
+        // One buy and one sell out of three is no majority
+        set(&handles, "b", Signal::sell(0.5));
+        assert_eq!(
+            strategy.generate_signal().await.unwrap().signal_type,
+            SignalType::Hold
+        );
+
+        // Without a winning entry, an exit from any child passes
+        set(
+            &handles,
+            "c",
+            Signal {
+                signal_type: SignalType::CloseLong,
+                ..Signal::hold()
+            },
+        );
+        assert_eq!(
+            strategy.generate_signal().await.unwrap().signal_type,
+            SignalType::CloseLong
+        );
+    }
+
+    #[tokio::test]
+    async fn test_weighted_confidence_with_veto() {
+        let (strategy, handles) = composite(json!({
+            "combiner": { "type": "weighted_confidence", "threshold": 0.3 },
+            "children": [
+                { "name": "trend", "strategy": "scripted", "weight": 3.0 },
+                { "name": "reversion", "strategy": "scripted" },
+                { "name": "funding", "strategy": "scripted", "role": "veto" }
+            ]
+        }));
+
+        // (3 * 0.6 - 1 * 0.8) / 4 = 0.25: below the threshold
+        set(&handles, "trend", Signal::buy(0.6));
+        set(&handles, "reversion", Signal::sell(0.8));
+        assert_eq!(
+            strategy.generate_signal().await.unwrap().signal_type,
+            SignalType::Hold
+        );
+
+        // (3 * 0.6) / 4 = 0.45
+        set(&handles, "reversion", Signal::hold());
+        let signal = strategy.generate_signal().await.unwrap();
+        assert_eq!(signal.signal_type, SignalType::Buy);
+        assert!((signal.confidence - 0.45).abs() < 1e-9);
+
+        set(&handles, "funding", Signal::sell(1.0));
+        let vetoed = strategy.generate_signal().await.unwrap();
+        assert_eq!(vetoed.signal_type, SignalType::Hold);
+        assert_eq!(vetoed.metadata["composite"]["blocked_by"], json!("funding"));
+    }
+
+    #[tokio::test]
+    async fn test_trend_filter_follows_higher_timeframe() {
+        let (mut strategy, handles) = composite(json!({
+            "combiner": { "type": "majority_vote" },
+            "children": [{ "name": "entry", "strategy": "scripted" }],
+            "filters": [{ "type": "trend", "interval": "4H", "period": 3 }]
+        }));
+        set(&handles, "entry", Signal::buy(1.0));
+
+        // Not enough 4H bars yet
+        strategy
+            .on_market_data(candle(Interval::FourHours, 0, 100))
+            .await
+            .unwrap();
+        let blocked = strategy.generate_signal().await.unwrap();
+        assert_eq!(
+            blocked.metadata["composite"]["blocked_by"],
+            json!("trend_4H")
+        );
+
+        strategy
+            .on_market_data(candle(Interval::FourHours, 4, 101))
+            .await
+            .unwrap();
+        strategy
+            .on_market_data(candle(Interval::FourHours, 8, 105))
+            .await
+            .unwrap();
+        // Base-interval bars do not move the 4H trend
+        strategy
+            .on_market_data(candle(Interval::OneHour, 9, 50))
+            .await
+            .unwrap();
+        assert_eq!(
+            strategy.generate_signal().await.unwrap().signal_type,
+            SignalType::Buy
+        );
+
+        // The 4H trend disagrees with a sell
+        set(&handles, "entry", Signal::sell(1.0));
+        assert_eq!(
+            strategy.generate_signal().await.unwrap().signal_type,
+            SignalType::Hold
+        );
+    }
+
+    #[test]
+    fn test_invalid_layouts_are_rejected() {
+        let parse = |value: JsonValue| serde_json::from_value::<CompositeConfig>(value).unwrap();
+
+        let no_voters = parse(json!({
+            "combiner": { "type": "majority_vote" },
+            "children": [{ "name": "a", "strategy": "x", "role": "confirm" }]
+        }));
+        assert!(no_voters.validate().is_err());
+
+        let duplicate = parse(json!({
+            "combiner": { "type": "majority_vote" },
+            "children": [
+                { "name": "a", "strategy": "x" },
+                { "name": "a", "strategy": "y" }
+            ]
+        }));
+        assert!(duplicate.validate().is_err());
+
+        let threshold = parse(json!({
+            "combiner": { "type": "weighted_confidence", "threshold": 1.5 },
+            "children": [{ "name": "a", "strategy": "x" }]
+        }));
+        assert!(threshold.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_live_updates_skip_overridden_parameters() {
+        let (mut strategy, _handles) = composite(json!({
+            "combiner": { "type": "majority_vote" },
+            "children": [
+                { "name": "a", "strategy": "scripted", "parameters": { "period": 5 } }
+            ]
+        }));
+
+        // The child sets its own period, so the composite's does not reach it
+        let period = HashMap::from([("period".to_string(), json!(9))]);
+        assert!(strategy.on_parameters_updated(&period).await.is_ok());
+
+        // Anything it inherits does, and it refuses live updates
+        let other = HashMap::from([("threshold".to_string(), json!(1))]);
+        let refused = strategy.on_parameters_updated(&other).await.unwrap_err();
+        assert!(refused.to_string().contains("child 'a'"));
+    }
+}
@@ -0,0 +1,609 @@
+//! Custom strategy metrics
+//!
+//! Strategies report their own metrics, such as a grid's fill ratio or the
+//! spread captured per trade, through the [`StrategyMetrics`] handle passed
+//! to [`Strategy::attach_metrics`](crate::traits::Strategy::attach_metrics)
+//! before they are initialized. Every name lives under `strategy.<id>.`, so
+//! strategies cannot collide with each other or with system metrics:
+//!
+//! ```ignore
+//! let fills = metrics.counter("fills"); // strategy.<id>.fills
+//! fills.increment();
+//! metrics.gauge("fill_ratio").set(0.42);
+//! metrics.histogram("spread_captured_bps").record(3.5);
+//! ```
+//!
+//! The shared [`MetricsRegistry`] renders every metric in the Prometheus
+//! text format, served at `/metrics` by [`serve_metrics`], and keeps a
+//! history of each metric for charting, appended whenever its owner calls
+//! [`MetricsRegistry::sample`].
+
+use crate::error::{Error, Result};
+use axum::Router;
+use axum::extract::State;
+use axum::http::header::CONTENT_TYPE;
+use axum::routing::get;
+use chrono::{DateTime, Utc};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Histogram buckets used unless a strategy asks for its own
+pub const DEFAULT_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Samples kept per metric; a day at one sample a minute
+pub const HISTORY_LEN: usize = 1440;
+
+/// Monotonically increasing count
+#[derive(Debug, Clone, Default)]
+pub struct Counter(Arc<AtomicU64>);
+
+impl Counter {
+    pub fn increment(&self) {
+        self.add(1);
+    }
+
+    pub fn add(&self, n: u64) {
+        self.0.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn value(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Value that can go up and down
+#[derive(Debug, Clone, Default)]
+pub struct Gauge(Arc<AtomicU64>);
+
+impl Gauge {
+    pub fn set(&self, value: f64) {
+        self.0.store(value.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn add(&self, delta: f64) {
+        let _ = self
+            .0
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+                Some((f64::from_bits(bits) + delta).to_bits())
+            });
+    }
+
+    pub fn value(&self) -> f64 {
+        f64::from_bits(self.0.load(Ordering::Relaxed))
+    }
+}
+
+#[derive(Debug)]
+struct HistogramState {
+    bounds: Vec<f64>,
+    /// Observations per bucket, not cumulative; the last is above every bound
+    counts: Vec<u64>,
+    count: u64,
+    sum: f64,
+}
+
+/// Distribution of observed values over fixed buckets
+#[derive(Debug, Clone)]
+pub struct Histogram(Arc<Mutex<HistogramState>>);
+
+impl Histogram {
+    fn new(buckets: &[f64]) -> Self {
+        let mut bounds: Vec<f64> = buckets.iter().copied().filter(|b| b.is_finite()).collect();
+        bounds.sort_by(f64::total_cmp);
+        bounds.dedup();
+        let counts = vec![0; bounds.len() + 1];
+        Self(Arc::new(Mutex::new(HistogramState {
+            bounds,
+            counts,
+            count: 0,
+            sum: 0.0,
+        })))
+    }
+
+    /// Record one observation; NaN is ignored
+    pub fn record(&self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        let mut state = self.0.lock();
+        let bucket = state.bounds.partition_point(|bound| *bound < value);
+        state.counts[bucket] += 1;
+        state.count += 1;
+        state.sum += value;
+    }
+
+    pub fn snapshot(&self) -> HistogramSnapshot {
+        let state = self.0.lock();
+        let mut cumulative = 0;
+        let buckets = state
+            .bounds
+            .iter()
+            .zip(&state.counts)
+            .map(|(bound, count)| {
+                cumulative += count;
+                (*bound, cumulative)
+            })
+            .collect();
+        HistogramSnapshot {
+            count: state.count,
+            sum: state.sum,
+            buckets,
+        }
+    }
+}
+
+/// Point-in-time view of a histogram
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistogramSnapshot {
+    pub count: u64,
+    pub sum: f64,
+    /// Upper bound and cumulative count of each bucket; `count` is the
+    /// implicit `+Inf` bucket
+    pub buckets: Vec<(f64, u64)>,
+}
+
+impl HistogramSnapshot {
+    pub fn mean(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum / self.count as f64)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricKind {
+    Counter,
+    Gauge,
+    Histogram,
+}
+
+/// Current value of one metric
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MetricValue {
+    Counter { value: u64 },
+    Gauge { value: f64 },
+    Histogram(HistogramSnapshot),
+}
+
+impl MetricValue {
+    pub fn kind(&self) -> MetricKind {
+        match self {
+            Self::Counter { .. } => MetricKind::Counter,
+            Self::Gauge { .. } => MetricKind::Gauge,
+            Self::Histogram(_) => MetricKind::Histogram,
+        }
+    }
+
+    /// Value charted for the metric; a histogram charts its mean
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Counter { value } => Some(*value as f64),
+            Self::Gauge { value } => Some(*value),
+            Self::Histogram(snapshot) => snapshot.mean(),
+        }
+    }
+}
+
+/// One metric of one strategy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricSample {
+    pub strategy_id: Uuid,
+    /// Name within the strategy's namespace, e.g. `fills`
+    pub name: String,
+    pub value: MetricValue,
+}
+
+impl MetricSample {
+    /// Fully qualified name, e.g. `strategy.<id>.fills`
+    pub fn full_name(&self) -> String {
+        format!("{}.{}", namespace(self.strategy_id), self.name)
+    }
+}
+
+/// A charted value of a metric
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MetricPoint {
+    pub timestamp: DateTime<Utc>,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone)]
+enum Metric {
+    Counter(Counter),
+    Gauge(Gauge),
+    Histogram(Histogram),
+}
+
+impl Metric {
+    fn kind(&self) -> MetricKind {
+        match self {
+            Self::Counter(_) => MetricKind::Counter,
+            Self::Gauge(_) => MetricKind::Gauge,
+            Self::Histogram(_) => MetricKind::Histogram,
+        }
+    }
+
+    fn value(&self) -> MetricValue {
+        match self {
+            Self::Counter(counter) => MetricValue::Counter {
+                value: counter.value(),
+            },
+            Self::Gauge(gauge) => MetricValue::Gauge {
+                value: gauge.value(),
+            },
+            Self::Histogram(histogram) => MetricValue::Histogram(histogram.snapshot()),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    metric: Metric,
+    history: VecDeque<MetricPoint>,
+}
+
+/// Every strategy's custom metrics; cheap to clone
+#[derive(Debug, Clone, Default)]
+pub struct MetricsRegistry {
+    entries: Arc<RwLock<BTreeMap<(Uuid, String), Entry>>>,
+}
+
+impl MetricsRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Handle registering metrics under `strategy.<strategy_id>.`
+    pub fn for_strategy(&self, strategy_id: Uuid) -> StrategyMetrics {
+        StrategyMetrics {
+            strategy_id,
+            prefix: String::new(),
+            registry: self.clone(),
+        }
+    }
+
+    /// Current value of every metric, ordered by strategy and name
+    pub fn snapshot(&self) -> Vec<MetricSample> {
+        self.entries
+            .read()
+            .iter()
+            .map(|((strategy_id, name), entry)| MetricSample {
+                strategy_id: *strategy_id,
+                name: name.clone(),
+                value: entry.metric.value(),
+            })
+            .collect()
+    }
+
+    pub fn strategy_snapshot(&self, strategy_id: Uuid) -> Vec<MetricSample> {
+        self.snapshot()
+            .into_iter()
+            .filter(|sample| sample.strategy_id == strategy_id)
+            .collect()
+    }
+
+    /// Charted values of one metric, oldest first
+    pub fn history(&self, strategy_id: Uuid, name: &str) -> Vec<MetricPoint> {
+        self.entries
+            .read()
+            .get(&(strategy_id, name.to_string()))
+            .map(|entry| entry.history.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Append the current value of every metric to its history
+    ///
+    /// Histograms with no observations yet are skipped.
+    pub fn sample(&self, now: DateTime<Utc>) {
+        for entry in self.entries.write().values_mut() {
+            let Some(value) = entry.metric.value().as_f64() else {
+                continue;
+            };
+            if entry.history.len() >= HISTORY_LEN {
+                entry.history.pop_front();
+            }
+            entry.history.push_back(MetricPoint {
+                timestamp: now,
+                value,
+            });
+        }
+    }
+
+    /// Drop every metric of a strategy that was removed
+    pub fn remove_strategy(&self, strategy_id: Uuid) {
+        self.entries.write().retain(|(id, _), _| *id != strategy_id);
+    }
+
+    /// Every metric in the Prometheus text exposition format
+    ///
+    /// `strategy.<id>.fills` is exported as `strategy_fills` with a
+    /// `strategy_id` label, so one series covers every strategy.
+    pub fn render_prometheus(&self) -> String {
+        let mut samples = self.snapshot();
+        samples.sort_by(|a, b| a.name.cmp(&b.name).then(a.strategy_id.cmp(&b.strategy_id)));
+
+        let mut out = String::new();
+        let mut last_name = None;
+        for sample in &samples {
+            let name = format!("strategy_{}", sample.name.replace('.', "_"));
+            let label = format!("strategy_id=\"{}\"", sample.strategy_id);
+            if last_name.as_ref() != Some(&name) {
+                let kind = match sample.value.kind() {
+                    MetricKind::Counter => "counter",
+                    MetricKind::Gauge => "gauge",
+                    MetricKind::Histogram => "histogram",
+                };
+                let _ = writeln!(out, "# TYPE {} {}", name, kind);
+                last_name = Some(name.clone());
+            }
+            match &sample.value {
+                MetricValue::Counter { value } => {
+                    let _ = writeln!(out, "{}{{{}}} {}", name, label, value);
+                }
+                MetricValue::Gauge { value } => {
+                    let _ = writeln!(out, "{}{{{}}} {}", name, label, value);
+                }
+                MetricValue::Histogram(histogram) => {
+                    for (bound, count) in &histogram.buckets {
+                        let _ = writeln!(
+                            out,
+                            "{}_bucket{{{},le=\"{}\"}} {}",
+                            name, label, bound, count
+                        );
+                    }
+                    let _ = writeln!(
+                        out,
+                        "{}_bucket{{{},le=\"+Inf\"}} {}",
+                        name, label, histogram.count
+                    );
+                    let _ = writeln!(out, "{}_sum{{{}}} {}", name, label, histogram.sum);
+                    let _ = writeln!(out, "{}_count{{{}}} {}", name, label, histogram.count);
+                }
+            }
+        }
+        out
+    }
+
+    fn register(&self, strategy_id: Uuid, name: String, metric: Metric) -> Metric {
+        let mut entries = self.entries.write();
+        let entry = entries
+            .entry((strategy_id, name))
+            .or_insert_with_key(|_| Entry {
+                metric: metric.clone(),
+                history: VecDeque::new(),
+            });
+        if entry.metric.kind() == metric.kind() {
+            return entry.metric.clone();
+        }
+        warn!(
+            "Strategy {} metric is already a {:?}, not registering it as a {:?}",
+            strategy_id,
+            entry.metric.kind(),
+            metric.kind()
+        );
+        // Usable but never exported
+        metric
+    }
+}
+
+/// Registers a strategy's metrics in its namespace; cheap to clone
+#[derive(Debug, Clone)]
+pub struct StrategyMetrics {
+    strategy_id: Uuid,
+    /// Added in front of every name, for children of a composite
+    prefix: String,
+    registry: MetricsRegistry,
+}
+
+impl StrategyMetrics {
+    /// Handle backed by a registry of its own, for strategies run without one
+    pub fn detached(strategy_id: Uuid) -> Self {
+        MetricsRegistry::new().for_strategy(strategy_id)
+    }
+
+    /// `strategy.<id>`, or `strategy.<id>.<prefix>` for a scoped handle
+    pub fn namespace(&self) -> String {
+        match self.prefix.as_str() {
+            "" => namespace(self.strategy_id),
+            prefix => format!("{}.{}", namespace(self.strategy_id), prefix),
+        }
+    }
+
+    /// Handle registering under `<prefix>.` within this namespace
+    pub fn scoped(&self, prefix: &str) -> Self {
+        Self {
+            strategy_id: self.strategy_id,
+            prefix: self.qualify(prefix),
+            registry: self.registry.clone(),
+        }
+    }
+
+    pub fn counter(&self, name: &str) -> Counter {
+        match self.register(name, Metric::Counter(Counter::default())) {
+            Metric::Counter(counter) => counter,
+            _ => Counter::default(),
+        }
+    }
+
+    pub fn gauge(&self, name: &str) -> Gauge {
+        match self.register(name, Metric::Gauge(Gauge::default())) {
+            Metric::Gauge(gauge) => gauge,
+            _ => Gauge::default(),
+        }
+    }
+
+    /// Histogram over [`DEFAULT_BUCKETS`]
+    pub fn histogram(&self, name: &str) -> Histogram {
+        self.histogram_with_buckets(name, &DEFAULT_BUCKETS)
+    }
+
+    /// Histogram over `buckets`; ignored if the histogram already exists
+    pub fn histogram_with_buckets(&self, name: &str, buckets: &[f64]) -> Histogram {
+        match self.register(name, Metric::Histogram(Histogram::new(buckets))) {
+            Metric::Histogram(histogram) => histogram,
+            _ => Histogram::new(buckets),
+        }
+    }
+
+    fn register(&self, name: &str, metric: Metric) -> Metric {
+        self.registry
+            .register(self.strategy_id, self.qualify(name), metric)
+    }
+
+    /// Name within the strategy's namespace
+    ///
+    /// Fully qualified names (`strategy.<id>.fills`) are accepted too, and
+    /// anything but lowercase letters, digits, `_` and `.` becomes `_`.
+    fn qualify(&self, name: &str) -> String {
+        let own = format!("{}.", namespace(self.strategy_id));
+        let name = name.strip_prefix(&own).unwrap_or(name);
+        let name: String = name
+            .trim_matches('.')
+            .chars()
+            .map(|c| match c.to_ascii_lowercase() {
+                c @ ('a'..='z' | '0'..='9' | '_' | '.') => c,
+                _ => '_',
+            })
+            .collect();
+        match self.prefix.as_str() {
+            "" => name,
+            prefix => format!("{}.{}", prefix, name),
+        }
+    }
+}
+
+fn namespace(strategy_id: Uuid) -> String {
+    format!("strategy.{}", strategy_id)
+}
+
+async fn render_metrics(
+    State(registry): State<MetricsRegistry>,
+) -> ([(axum::http::HeaderName, &'static str); 1], String) {
+    (
+        [(CONTENT_TYPE, "text/plain; version=0.0.4")],
+        registry.render_prometheus(),
+    )
+}
+
+/// Routes `GET /metrics` to the Prometheus rendering of `registry`
+pub fn metrics_router(registry: MetricsRegistry) -> Router {
+    Router::new()
+        .route("/metrics", get(render_metrics))
+        .with_state(registry)
+}
+
+/// Serve [`metrics_router`] on `addr` until the task is aborted
+pub async fn serve_metrics(registry: MetricsRegistry, addr: SocketAddr) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .map_err(|e| Error::Internal(format!("failed to bind {}: {}", addr, e)))?;
+    info!("Serving strategy metrics on {}", addr);
+    axum::serve(listener, metrics_router(registry))
+        .await
+        .map_err(|e| Error::Internal(format!("metrics server failed: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_metrics_are_namespaced_per_strategy() {
+        let registry = MetricsRegistry::new();
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let metrics = registry.for_strategy(a);
+
+        metrics.counter("fills").add(3);
+        // Same counter, by its full name
+        metrics
+            .counter(&format!("strategy.{}.fills", a))
+            .increment();
+        registry.for_strategy(b).counter("fills").increment();
+        metrics.scoped("grid").gauge("Fill Ratio").set(0.5);
+
+        let samples = registry.strategy_snapshot(a);
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[0].full_name(), format!("strategy.{}.fills", a));
+        assert_eq!(samples[0].value, MetricValue::Counter { value: 4 });
+        assert_eq!(samples[1].name, "grid.fill_ratio");
+        assert_eq!(registry.strategy_snapshot(b).len(), 1);
+
+        // A name keeps the kind it was first registered with
+        metrics.gauge("fills").set(10.0);
+        assert_eq!(
+            registry.strategy_snapshot(a)[0].value,
+            MetricValue::Counter { value: 4 }
+        );
+
+        registry.remove_strategy(a);
+        assert_eq!(registry.snapshot().len(), 1);
+    }
+
+    #[test]
+    fn test_histogram_and_history() {
+        let registry = MetricsRegistry::new();
+        let id = Uuid::new_v4();
+        let spread = registry
+            .for_strategy(id)
+            .histogram_with_buckets("spread_bps", &[1.0, 5.0]);
+
+        registry.sample(Utc::now());
+        assert!(registry.history(id, "spread_bps").is_empty());
+
+        for value in [0.5, 2.0, 4.0, 9.5] {
+            spread.record(value);
+        }
+        let snapshot = spread.snapshot();
+        assert_eq!(snapshot.buckets, vec![(1.0, 1), (5.0, 3)]);
+        assert_eq!(snapshot.count, 4);
+        assert_eq!(snapshot.mean(), Some(4.0));
+
+        registry.sample(Utc::now());
+        let history = registry.history(id, "spread_bps");
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].value, 4.0);
+    }
+
+    #[tokio::test]
+    async fn test_prometheus_endpoint() {
+        let registry = MetricsRegistry::new();
+        let id = Uuid::new_v4();
+        let metrics = registry.for_strategy(id);
+        metrics.counter("fills").add(2);
+        metrics
+            .histogram_with_buckets("spread.bps", &[1.0])
+            .record(0.5);
+
+        let response = metrics_router(registry)
+            .oneshot(Request::get("/metrics").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+
+        let label = format!("strategy_id=\"{}\"", id);
+        assert!(text.contains("# TYPE strategy_fills counter"));
+        assert!(text.contains(&format!("strategy_fills{{{}}} 2", label)));
+        assert!(text.contains("# TYPE strategy_spread_bps histogram"));
+        assert!(text.contains(&format!(
+            "strategy_spread_bps_bucket{{{},le=\"+Inf\"}} 1",
+            label
+        )));
+        assert!(text.contains(&format!("strategy_spread_bps_count{{{}}} 1", label)));
+    }
+}
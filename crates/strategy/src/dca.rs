@@ -0,0 +1,786 @@
+//! Dollar-cost averaging
+//!
+//! A [`DcaStrategy`] buys a fixed quote amount of each of its symbols on a
+//! daily or weekly [`DcaSchedule`], whatever the price. [`DipRule`]s scale a
+//! buy up when the price trades well below its recent high, and
+//! [`SkipRule`]s pass on a buy when the market looks overheated; skipped
+//! buys are not made up later. Buys are emitted as limit signals at the last
+//! price, meant to be worked passively (post-only) by the execution policy.
+//!
+//! Buys missed while the strategy was not running are not caught up either:
+//! the first buy is scheduled after the first price seen. [`DcaLedger`]
+//! tracks the accumulated position and cost basis from the fills, and can be
+//! rebuilt from the strategy's filled orders.
+
+use crate::error::{Error, Result};
+use crate::metrics::PerformanceMetrics;
+use crate::signal::Signal;
+use crate::traits::{MarketDataEvent, Strategy, StrategyConfig};
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Duration, NaiveTime, Utc, Weekday};
+use ea_okx_core::Interval;
+use ea_okx_core::models::{Order, OrderSide};
+use ea_okx_core::types::{Price, Quantity, Symbol};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value as JsonValue, json};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use tracing::info;
+
+/// Strategy type of dollar-cost averaging strategies
+pub const DCA_STRATEGY_TYPE: &str = "dca";
+
+/// When recurring buys happen (UTC)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "every", rename_all = "snake_case")]
+pub enum DcaSchedule {
+    Daily { at: NaiveTime },
+    Weekly { weekday: Weekday, at: NaiveTime },
+}
+
+impl DcaSchedule {
+    /// First scheduled buy strictly after `after`
+    pub fn next_after(&self, after: DateTime<Utc>) -> DateTime<Utc> {
+        let (at, step, offset) = match *self {
+            DcaSchedule::Daily { at } => (at, Duration::days(1), 0),
+            DcaSchedule::Weekly { weekday, at } => {
+                let days = (7 + weekday.num_days_from_monday()
+                    - after.weekday().num_days_from_monday())
+                    % 7;
+                (at, Duration::weeks(1), days)
+            }
+        };
+        let candidate = (after.date_naive() + Duration::days(offset.into()))
+            .and_time(at)
+            .and_utc();
+        if candidate > after {
+            candidate
+        } else {
+            candidate + step
+        }
+    }
+}
+
+/// Buy more when the price is down from its recent high
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DipRule {
+    /// Fractional drop from the highest recent close, e.g. 0.1 for 10%
+    pub drop_pct: Decimal,
+
+    /// Multiple of the regular amount bought at that drop
+    pub multiplier: Decimal,
+}
+
+/// Conditions under which a scheduled buy is skipped
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SkipRule {
+    /// Skip while the RSI over `period` closes is above `threshold`
+    RsiAbove { period: usize, threshold: f64 },
+
+    /// Skip while the price is above `price`
+    PriceAbove { price: Decimal },
+}
+
+/// Parameters of a DCA strategy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DcaConfig {
+    pub schedule: DcaSchedule,
+
+    /// Quote currency spent per symbol on each regular buy
+    pub quote_amount: Decimal,
+
+    /// Dip accelerations; the deepest rule reached applies
+    #[serde(default)]
+    pub dip_rules: Vec<DipRule>,
+
+    #[serde(default)]
+    pub skip_rules: Vec<SkipRule>,
+
+    /// Candles feeding the dip reference and the RSI
+    #[serde(default = "default_indicator_interval")]
+    pub indicator_interval: Interval,
+
+    /// Closes the recent high for dip rules is taken over
+    #[serde(default = "default_dip_lookback")]
+    pub dip_lookback: usize,
+}
+
+fn default_indicator_interval() -> Interval {
+    Interval::OneHour
+}
+
+fn default_dip_lookback() -> usize {
+    168
+}
+
+impl DcaConfig {
+    /// Parse and validate strategy parameters
+    pub fn from_parameters(parameters: &HashMap<String, JsonValue>) -> Result<Self> {
+        let value = JsonValue::Object(parameters.clone().into_iter().collect());
+        let config: DcaConfig = serde_json::from_value(value)
+            .map_err(|e| Error::InvalidConfig(format!("invalid DCA parameters: {}", e)))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.quote_amount <= Decimal::ZERO {
+            return Err(Error::InvalidConfig(
+                "DCA quote amount must be positive".to_string(),
+            ));
+        }
+        if self.dip_rules.iter().any(|r| {
+            r.drop_pct <= Decimal::ZERO
+                || r.drop_pct >= Decimal::ONE
+                || r.multiplier <= Decimal::ZERO
+        }) {
+            return Err(Error::InvalidConfig(
+                "DCA dip rules need a drop between 0 and 1 and a positive multiplier".to_string(),
+            ));
+        }
+        if self
+            .skip_rules
+            .iter()
+            .any(|r| matches!(r, SkipRule::RsiAbove { period: 0, .. }))
+        {
+            return Err(Error::InvalidConfig(
+                "DCA RSI skip rules need a period".to_string(),
+            ));
+        }
+        if self.dip_lookback == 0 {
+            return Err(Error::InvalidConfig(
+                "DCA dip lookback must be at least one close".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Closes kept per symbol for the rules
+    fn history(&self) -> usize {
+        let rsi = self
+            .skip_rules
+            .iter()
+            .filter_map(|r| match r {
+                SkipRule::RsiAbove { period, .. } => Some(period + 1),
+                SkipRule::PriceAbove { .. } => None,
+            })
+            .max()
+            .unwrap_or(0);
+        self.dip_lookback.max(rsi)
+    }
+
+    /// Multiplier of the deepest dip rule reached at `price`
+    fn multiplier(&self, closes: &VecDeque<Decimal>, price: Decimal) -> Decimal {
+        let Some(high) = closes
+            .iter()
+            .rev()
+            .take(self.dip_lookback)
+            .max()
+            .filter(|h| **h > Decimal::ZERO)
+        else {
+            return Decimal::ONE;
+        };
+        let drop = (*high - price) / *high;
+        self.dip_rules
+            .iter()
+            .filter(|r| drop >= r.drop_pct)
+            .max_by_key(|r| r.drop_pct)
+            .map_or(Decimal::ONE, |r| r.multiplier)
+    }
+
+    /// Why a buy at `price` should be skipped, if it should
+    fn skip_reason(&self, closes: &VecDeque<Decimal>, price: Decimal) -> Option<String> {
+        self.skip_rules.iter().find_map(|rule| match *rule {
+            SkipRule::RsiAbove { period, threshold } => {
+                let closes: Vec<f64> = closes.iter().filter_map(|c| c.to_f64()).collect();
+                let rsi = rsi(&closes, period)?;
+                (rsi > threshold).then(|| format!("RSI({}) {:.1} above {}", period, rsi, threshold))
+            }
+            SkipRule::PriceAbove { price: limit } => {
+                (price > limit).then(|| format!("price {} above {}", price, limit))
+            }
+        })
+    }
+}
+
+/// Wilder's RSI over the last closes; `None` until `period + 1` are known
+fn rsi(closes: &[f64], period: usize) -> Option<f64> {
+    if period == 0 || closes.len() <= period {
+        return None;
+    }
+    let changes: Vec<f64> = closes.windows(2).map(|w| w[1] - w[0]).collect();
+    let mut gain = changes[..period].iter().map(|c| c.max(0.0)).sum::<f64>() / period as f64;
+    let mut loss = changes[..period].iter().map(|c| (-c).max(0.0)).sum::<f64>() / period as f64;
+    for change in &changes[period..] {
+        gain = (gain * (period - 1) as f64 + change.max(0.0)) / period as f64;
+        loss = (loss * (period - 1) as f64 + (-change).max(0.0)) / period as f64;
+    }
+    if loss == 0.0 {
+        return Some(if gain == 0.0 { 50.0 } else { 100.0 });
+    }
+    Some(100.0 - 100.0 / (1.0 + gain / loss))
+}
+
+/// What happened at one scheduled buy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum DcaAction {
+    Bought { quantity: Decimal, price: Decimal },
+    Skipped { reason: String },
+}
+
+/// One row of the DCA history, with the position after it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DcaEntry {
+    pub timestamp: DateTime<Utc>,
+    pub symbol: String,
+    #[serde(flatten)]
+    pub action: DcaAction,
+    /// Accumulated quantity
+    pub total_quantity: Decimal,
+    /// Quote currency spent in total
+    pub total_cost: Decimal,
+}
+
+/// Position accumulated in one symbol
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DcaPosition {
+    pub quantity: Decimal,
+    pub cost: Decimal,
+    pub buys: usize,
+    pub skips: usize,
+}
+
+impl DcaPosition {
+    /// Average price paid, once something was bought
+    pub fn average_cost(&self) -> Option<Decimal> {
+        (!self.quantity.is_zero()).then(|| self.cost / self.quantity)
+    }
+}
+
+/// Accumulated positions and the history of buys and skips
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DcaLedger {
+    pub positions: BTreeMap<String, DcaPosition>,
+    pub entries: Vec<DcaEntry>,
+}
+
+impl DcaLedger {
+    /// Ledger of the filled buy orders among `orders`, in fill order
+    pub fn from_orders<'a>(orders: impl IntoIterator<Item = &'a Order>) -> Self {
+        let mut fills: Vec<&Order> = orders
+            .into_iter()
+            .filter(|o| o.side == OrderSide::Buy && o.avg_fill_price.is_some())
+            .filter(|o| !o.filled_quantity.as_decimal().is_zero())
+            .collect();
+        fills.sort_by_key(|o| o.first_fill_at.unwrap_or(o.created_at));
+
+        let mut ledger = Self::default();
+        for order in fills {
+            ledger.record_fill(order);
+        }
+        ledger
+    }
+
+    /// Add a filled buy; other orders are ignored
+    pub fn record_fill(&mut self, order: &Order) {
+        let Some(price) = order.avg_fill_price else {
+            return;
+        };
+        let quantity = order.filled_quantity.as_decimal();
+        if order.side != OrderSide::Buy || quantity.is_zero() {
+            return;
+        }
+        let timestamp = order
+            .completed_at
+            .or(order.first_fill_at)
+            .unwrap_or(order.created_at);
+        let price = price.as_decimal();
+        self.push(
+            timestamp,
+            order.symbol.as_str(),
+            DcaAction::Bought { quantity, price },
+            |position| {
+                position.quantity += quantity;
+                position.cost += quantity * price;
+                position.buys += 1;
+            },
+        );
+    }
+
+    pub fn record_skip(&mut self, timestamp: DateTime<Utc>, symbol: &str, reason: String) {
+        self.push(
+            timestamp,
+            symbol,
+            DcaAction::Skipped { reason },
+            |position| {
+                position.skips += 1;
+            },
+        );
+    }
+
+    fn push(
+        &mut self,
+        timestamp: DateTime<Utc>,
+        symbol: &str,
+        action: DcaAction,
+        update: impl FnOnce(&mut DcaPosition),
+    ) {
+        let position = self.positions.entry(symbol.to_string()).or_default();
+        update(position);
+        self.entries.push(DcaEntry {
+            timestamp,
+            symbol: symbol.to_string(),
+            action,
+            total_quantity: position.quantity,
+            total_cost: position.cost,
+        });
+    }
+}
+
+/// Schedule and price history of one symbol
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct SymbolState {
+    next_buy: Option<DateTime<Utc>>,
+    last_price: Option<Decimal>,
+    closes: VecDeque<Decimal>,
+}
+
+/// Recurring buys of the configured symbols
+#[derive(Debug, Default)]
+pub struct DcaStrategy {
+    config: Option<DcaConfig>,
+    /// Parameters `config` was parsed from, kept to apply partial updates to
+    parameters: HashMap<String, JsonValue>,
+    symbols: BTreeMap<String, SymbolState>,
+    ledger: DcaLedger,
+    /// Buys due on the last event
+    pending: Vec<Signal>,
+}
+
+impl DcaStrategy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check DCA parameters without creating a strategy
+    pub fn check(parameters: &HashMap<String, JsonValue>) -> Result<()> {
+        DcaConfig::from_parameters(parameters).map(|_| ())
+    }
+
+    pub fn ledger(&self) -> &DcaLedger {
+        &self.ledger
+    }
+
+    /// Next scheduled buy of `symbol`, once a price has been seen
+    pub fn next_buy(&self, symbol: &str) -> Option<DateTime<Utc>> {
+        self.symbols.get(symbol)?.next_buy
+    }
+
+    fn config(&self) -> Result<&DcaConfig> {
+        self.config
+            .as_ref()
+            .ok_or_else(|| Error::InitializationError("DCA strategy not initialized".to_string()))
+    }
+
+    /// Buy signal for a due buy, or `None` after recording a skip
+    fn due(
+        &mut self,
+        symbol: &Symbol,
+        price: Decimal,
+        now: DateTime<Utc>,
+    ) -> Result<Option<Signal>> {
+        let config = self.config()?;
+        let closes = &self.symbols[symbol.as_str()].closes;
+        let skip = config.skip_reason(closes, price);
+        let multiplier = config.multiplier(closes, price);
+        let amount = config.quote_amount * multiplier;
+        if let Some(reason) = skip {
+            info!("Skipping DCA buy of {}: {}", symbol.as_str(), reason);
+            self.ledger.record_skip(now, symbol.as_str(), reason);
+            return Ok(None);
+        }
+
+        let quantity = (amount / price).round_dp(8);
+        if quantity.is_zero() {
+            self.ledger.record_skip(
+                now,
+                symbol.as_str(),
+                format!("{} buys nothing at {}", amount, price),
+            );
+            return Ok(None);
+        }
+
+        let mut signal = Signal::buy(1.0);
+        signal.target_price = Some(Price::new(price)?);
+        signal.suggested_quantity = Some(Quantity::new(quantity)?);
+        signal.metadata = json!({
+            "symbol": symbol.as_str(),
+            "quote_amount": amount,
+            "multiplier": multiplier,
+        });
+        Ok(Some(signal))
+    }
+}
+
+#[async_trait]
+impl Strategy for DcaStrategy {
+    async fn initialize(&mut self, config: StrategyConfig) -> Result<()> {
+        if config.symbols.is_empty() {
+            return Err(Error::InvalidConfig(format!(
+                "DCA strategy '{}' has no symbols",
+                config.name
+            )));
+        }
+        self.config = Some(DcaConfig::from_parameters(&config.parameters)?);
+        self.parameters = config.parameters;
+        for symbol in config.symbols {
+            self.symbols.entry(symbol).or_default();
+        }
+        Ok(())
+    }
+
+    async fn on_market_data(&mut self, event: MarketDataEvent) -> Result<()> {
+        // A buy only stands until the next event
+        self.pending.clear();
+
+        let (symbol, price, timestamp) = match &event {
+            MarketDataEvent::Ticker {
+                symbol,
+                price,
+                timestamp,
+                ..
+            }
+            | MarketDataEvent::Trade {
+                symbol,
+                price,
+                timestamp,
+                ..
+            } => (symbol, *price, *timestamp),
+            MarketDataEvent::Candle {
+                symbol,
+                close,
+                timestamp,
+                ..
+            } => (symbol, *close, *timestamp),
+            _ => return Ok(()),
+        };
+        let config = self.config()?.clone();
+        let Some(state) = self.symbols.get_mut(symbol.as_str()) else {
+            return Ok(());
+        };
+        state.last_price = Some(price);
+        if let MarketDataEvent::Candle { interval, .. } = &event
+            && *interval == config.indicator_interval
+        {
+            state.closes.push_back(price);
+            while state.closes.len() > config.history() {
+                state.closes.pop_front();
+            }
+        }
+
+        match state.next_buy {
+            None => state.next_buy = Some(config.schedule.next_after(timestamp)),
+            Some(due) if timestamp >= due => {
+                state.next_buy = Some(config.schedule.next_after(timestamp));
+                if let Some(signal) = self.due(symbol, price, timestamp)? {
+                    self.pending.push(signal);
+                }
+            }
+            Some(_) => {}
+        }
+        Ok(())
+    }
+
+    async fn generate_signal(&self) -> Result<Signal> {
+        Ok(self.pending.first().cloned().unwrap_or_else(Signal::hold))
+    }
+
+    async fn generate_signals(&self) -> Result<Vec<Signal>> {
+        if self.pending.is_empty() {
+            return Ok(vec![Signal::hold()]);
+        }
+        Ok(self.pending.clone())
+    }
+
+    async fn on_order_fill(&mut self, order: &Order) -> Result<()> {
+        if self.symbols.contains_key(order.symbol.as_str()) {
+            self.ledger.record_fill(order);
+        }
+        Ok(())
+    }
+
+    async fn on_order_reject(&mut self, order: &Order, reason: &str) -> Result<()> {
+        if self.symbols.contains_key(order.symbol.as_str()) && order.side == OrderSide::Buy {
+            self.ledger.record_skip(
+                order.created_at,
+                order.symbol.as_str(),
+                format!("order rejected: {}", reason),
+            );
+        }
+        Ok(())
+    }
+
+    fn get_metrics(&self) -> PerformanceMetrics {
+        PerformanceMetrics::default()
+    }
+
+    fn serialize_state(&self) -> Result<JsonValue> {
+        Ok(json!({
+            "symbols": self.symbols,
+            "ledger": self.ledger,
+        }))
+    }
+
+    fn deserialize_state(&mut self, state: JsonValue) -> Result<()> {
+        if let Some(symbols) = state.get("symbols") {
+            let restored: BTreeMap<String, SymbolState> = serde_json::from_value(symbols.clone())?;
+            for (symbol, state) in restored {
+                // Symbols dropped from the configuration stay dropped
+                if let Some(current) = self.symbols.get_mut(&symbol) {
+                    *current = state;
+                }
+            }
+        }
+        if let Some(ledger) = state.get("ledger") {
+            self.ledger = serde_json::from_value(ledger.clone())?;
+        }
+        Ok(())
+    }
+
+    /// Reparses the whole configuration with the changes applied; a new
+    /// schedule takes effect from the next price seen
+    async fn on_parameters_updated(
+        &mut self,
+        parameters: &HashMap<String, JsonValue>,
+    ) -> Result<()> {
+        let mut updated = self.parameters.clone();
+        updated.extend(parameters.clone());
+        let config = DcaConfig::from_parameters(&updated)?;
+        if self.config()?.schedule != config.schedule {
+            for state in self.symbols.values_mut() {
+                state.next_buy = None;
+            }
+        }
+        info!("DCA parameters updated: {:?}", parameters.keys());
+        self.config = Some(config);
+        self.parameters = updated;
+        Ok(())
+    }
+
+    async fn shutdown(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signal::SignalType;
+    use crate::traits::RiskLimits;
+    use chrono::TimeZone;
+    use ea_okx_core::models::{OrderStatus, OrderType};
+    use rust_decimal_macros::dec;
+    use uuid::Uuid;
+
+    fn config(parameters: JsonValue) -> StrategyConfig {
+        StrategyConfig {
+            strategy_id: Uuid::new_v4(),
+            name: "dca".to_string(),
+            version: "1.0.0".to_string(),
+            symbols: vec!["BTC-USDT".to_string()],
+            parameters: serde_json::from_value(parameters).unwrap(),
+            risk_limits: RiskLimits {
+                max_position_size: Decimal::ONE,
+                max_leverage: Decimal::ONE,
+                stop_loss_pct: Decimal::new(2, 2),
+                take_profit_pct: None,
+            },
+        }
+    }
+
+    fn candle(hours: i64, close: Decimal) -> MarketDataEvent {
+        MarketDataEvent::Candle {
+            symbol: Symbol::new("BTC-USDT").unwrap(),
+            interval: Interval::OneHour,
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: Decimal::ONE,
+            timestamp: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::hours(hours),
+        }
+    }
+
+    #[test]
+    fn test_schedule_next_after() {
+        let monday = Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap();
+        let at = NaiveTime::from_hms_opt(9, 0, 0).unwrap();
+
+        let daily = DcaSchedule::Daily { at };
+        assert_eq!(
+            daily.next_after(monday),
+            Utc.with_ymd_and_hms(2024, 1, 2, 9, 0, 0).unwrap()
+        );
+
+        let weekly = DcaSchedule::Weekly {
+            weekday: Weekday::Wed,
+            at,
+        };
+        assert_eq!(
+            weekly.next_after(monday),
+            Utc.with_ymd_and_hms(2024, 1, 3, 9, 0, 0).unwrap()
+        );
+        let monday_weekly = DcaSchedule::Weekly {
+            weekday: Weekday::Mon,
+            at,
+        };
+        assert_eq!(
+            monday_weekly.next_after(monday),
+            Utc.with_ymd_and_hms(2024, 1, 8, 9, 0, 0).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_daily_buys_accelerate_on_dips_and_skip_when_overheated() {
+        let mut strategy = DcaStrategy::new();
+        strategy
+            .initialize(config(json!({
+                "schedule": { "every": "daily", "at": "00:00:00" },
+                "quote_amount": "100",
+                "dip_rules": [
+                    { "drop_pct": "0.1", "multiplier": "2" },
+                    { "drop_pct": "0.2", "multiplier": "3" }
+                ],
+                "skip_rules": [{ "type": "price_above", "price": "150" }]
+            })))
+            .await
+            .unwrap();
+
+        // The first price schedules the first buy; nothing is bought at once
+        strategy.on_market_data(candle(0, dec!(100))).await.unwrap();
+        assert_eq!(
+            strategy.generate_signal().await.unwrap().signal_type,
+            SignalType::Hold
+        );
+
+        // Day two, 15% below the high: the 10% rule doubles the buy
+        strategy
+            .on_market_data(candle(23, dec!(100)))
+            .await
+            .unwrap();
+        strategy.on_market_data(candle(24, dec!(85))).await.unwrap();
+        let signals = strategy.generate_signals().await.unwrap();
+        assert_eq!(signals.len(), 1);
+        assert_eq!(signals[0].signal_type, SignalType::Buy);
+        assert_eq!(signals[0].metadata["multiplier"], json!("2"));
+        assert_eq!(
+            signals[0].suggested_quantity.unwrap().as_decimal(),
+            (dec!(200) / dec!(85)).round_dp(8)
+        );
+
+        // The buy is not repeated within the day
+        strategy.on_market_data(candle(25, dec!(85))).await.unwrap();
+        assert_eq!(
+            strategy.generate_signal().await.unwrap().signal_type,
+            SignalType::Hold
+        );
+
+        // Day three, overheated: skipped and recorded
+        strategy
+            .on_market_data(candle(48, dec!(160)))
+            .await
+            .unwrap();
+        assert_eq!(
+            strategy.generate_signal().await.unwrap().signal_type,
+            SignalType::Hold
+        );
+        let position = &strategy.ledger().positions["BTC-USDT"];
+        assert_eq!(position.skips, 1);
+
+        let mut order = Order::new(
+            Uuid::new_v4(),
+            Symbol::new("BTC-USDT").unwrap(),
+            OrderSide::Buy,
+            OrderType::PostOnly,
+            Quantity::new(dec!(2)).unwrap(),
+            Some(Price::new(dec!(85)).unwrap()),
+        );
+        order.filled_quantity = Quantity::new(dec!(2)).unwrap();
+        order.avg_fill_price = Some(Price::new(dec!(85)).unwrap());
+        order.status = OrderStatus::Filled;
+        strategy.on_order_fill(&order).await.unwrap();
+
+        let ledger = DcaLedger::from_orders([&order]);
+        assert_eq!(ledger.positions["BTC-USDT"].cost, dec!(170));
+        assert_eq!(ledger.positions["BTC-USDT"].average_cost(), Some(dec!(85)));
+
+        // Schedule and history survive a reload
+        let state = strategy.serialize_state().unwrap();
+        let mut reloaded = DcaStrategy::new();
+        reloaded
+            .initialize(config(json!({
+                "schedule": { "every": "daily", "at": "00:00:00" },
+                "quote_amount": "100"
+            })))
+            .await
+            .unwrap();
+        reloaded.deserialize_state(state).unwrap();
+        assert_eq!(reloaded.ledger(), strategy.ledger());
+        assert_eq!(reloaded.next_buy("BTC-USDT"), strategy.next_buy("BTC-USDT"));
+
+        assert!(
+            DcaStrategy::check(
+                &config(json!({
+                    "schedule": { "every": "daily", "at": "00:00:00" },
+                    "quote_amount": "0"
+                }))
+                .parameters
+            )
+            .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_live_update_keeps_untouched_parameters() {
+        let mut strategy = DcaStrategy::new();
+        strategy
+            .initialize(config(json!({
+                "schedule": { "every": "daily", "at": "00:00:00" },
+                "quote_amount": "100",
+                "skip_rules": [{ "type": "price_above", "price": "150" }]
+            })))
+            .await
+            .unwrap();
+        strategy.on_market_data(candle(0, dec!(100))).await.unwrap();
+
+        let update = HashMap::from([("quote_amount".to_string(), json!("50"))]);
+        strategy.on_parameters_updated(&update).await.unwrap();
+        strategy
+            .on_market_data(candle(24, dec!(100)))
+            .await
+            .unwrap();
+        let signals = strategy.generate_signals().await.unwrap();
+        assert_eq!(
+            signals[0].suggested_quantity.unwrap().as_decimal(),
+            dec!(0.5)
+        );
+
+        // The skip rule still applies
+        strategy
+            .on_market_data(candle(48, dec!(160)))
+            .await
+            .unwrap();
+        assert_eq!(
+            strategy.generate_signal().await.unwrap().signal_type,
+            SignalType::Hold
+        );
+
+        // Invalid values are refused and the running configuration kept
+        let invalid = HashMap::from([("quote_amount".to_string(), json!("0"))]);
+        assert!(strategy.on_parameters_updated(&invalid).await.is_err());
+        assert_eq!(strategy.config().unwrap().quote_amount, dec!(50));
+    }
+}
@@ -24,6 +24,21 @@ pub enum Error {
     #[error("Serialization error: {0}")]
     SerializationError(#[from] serde_json::Error),
 
+    #[error("Bundle error: {0}")]
+    BundleError(String),
+
+    #[error("Script error: {0}")]
+    ScriptError(String),
+
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
+    #[error("Rate limited: {0}")]
+    RateLimited(String),
+
     #[error("Internal error: {0}")]
     Internal(String),
 }
@@ -0,0 +1,992 @@
+//! External signal ingestion
+//!
+//! Third-party signals (TradingView alerts, signal vendors, scripts) reach a
+//! strategy through a [`SignalIngestor`]. Every configured source is
+//! attributed to one strategy and carries its own authentication and rate
+//! limit. Payloads arrive by webhook ([`webhook_router`]) or by polling an
+//! [`ExternalSignalSource`] such as a URL or a JSON-lines file, and are
+//! validated and normalized into [`ExternalSignal`]s. The application turns
+//! those into execution signals, so they pass the same risk pipeline as
+//! signals generated in-process.
+
+use crate::error::{Error, Result};
+use crate::signal::SignalType;
+use async_trait::async_trait;
+use axum::Router;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use chrono::{DateTime, Utc};
+use ea_okx_core::types::{Price, Quantity, Symbol};
+use hmac::{Hmac, Mac};
+use parking_lot::{Mutex, RwLock};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value as JsonValue, json};
+use sha2::Sha256;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use subtle::ConstantTimeEq;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Header carrying a shared token
+pub const TOKEN_HEADER: &str = "x-signal-token";
+
+/// Header carrying the hex HMAC-SHA256 of `{timestamp}.{nonce}.{body}`
+pub const SIGNATURE_HEADER: &str = "x-signature";
+
+/// Header carrying the unix time in seconds a signed request was made
+pub const TIMESTAMP_HEADER: &str = "x-signal-timestamp";
+
+/// Header carrying a value the sender never reuses
+pub const NONCE_HEADER: &str = "x-signal-nonce";
+
+/// How far a signed request's timestamp may be from our clock
+const SIGNATURE_WINDOW_SECS: i64 = 300;
+
+/// Quote currencies recognized when splitting tickers such as `BTCUSDT`
+const QUOTE_CURRENCIES: [&str; 5] = ["USDT", "USDC", "USD", "BTC", "ETH"];
+
+/// How a source proves a payload came from it
+///
+/// Secrets are never serialized back out.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SourceAuth {
+    /// Accept anything; only for sources on a trusted network
+    #[default]
+    None,
+    /// Shared token in the `x-signal-token` header or a `passphrase`/`token`
+    /// body field (TradingView alerts cannot set headers)
+    Token {
+        #[serde(default, skip_serializing)]
+        token: String,
+    },
+    /// HMAC-SHA256 in the `x-signature` header over the `x-signal-timestamp`
+    /// and `x-signal-nonce` headers and the raw body, joined by `.`
+    ///
+    /// Requests more than five minutes old or reusing a nonce are refused.
+    HmacSha256 {
+        #[serde(default, skip_serializing)]
+        secret: String,
+    },
+}
+
+/// A source of external signals and the strategy its signals trade for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignalSourceConfig {
+    pub name: String,
+    pub strategy_id: Uuid,
+    #[serde(default)]
+    pub auth: SourceAuth,
+
+    /// Signals accepted per rolling minute
+    pub max_signals_per_minute: u32,
+
+    /// Symbols the source may trade; empty allows any
+    #[serde(default)]
+    pub allowed_symbols: Vec<Symbol>,
+
+    /// Quantity used when an open signal does not specify one
+    #[serde(default)]
+    pub default_quantity: Option<Decimal>,
+}
+
+impl SignalSourceConfig {
+    pub fn new(name: impl Into<String>, strategy_id: Uuid) -> Self {
+        Self {
+            name: name.into(),
+            strategy_id,
+            auth: SourceAuth::None,
+            max_signals_per_minute: 10,
+            allowed_symbols: Vec::new(),
+            default_quantity: None,
+        }
+    }
+
+    pub fn with_auth(mut self, auth: SourceAuth) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    pub fn with_rate_limit(mut self, max_signals_per_minute: u32) -> Self {
+        self.max_signals_per_minute = max_signals_per_minute;
+        self
+    }
+
+    pub fn with_allowed_symbols(mut self, symbols: Vec<Symbol>) -> Self {
+        self.allowed_symbols = symbols;
+        self
+    }
+
+    pub fn with_default_quantity(mut self, quantity: Decimal) -> Self {
+        self.default_quantity = Some(quantity);
+        self
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(Error::InvalidConfig("source name is empty".to_string()));
+        }
+        let secret_missing = match &self.auth {
+            SourceAuth::None => false,
+            SourceAuth::Token { token } => token.is_empty(),
+            SourceAuth::HmacSha256 { secret } => secret.is_empty(),
+        };
+        if secret_missing {
+            return Err(Error::InvalidConfig(format!(
+                "source '{}' has an empty secret",
+                self.name
+            )));
+        }
+        if self.max_signals_per_minute == 0 {
+            return Err(Error::InvalidConfig(format!(
+                "source '{}' allows no signals",
+                self.name
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// A payload as received, before authentication
+#[derive(Debug, Clone, Default)]
+pub struct InboundPayload {
+    /// Header names are lowercase
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+impl InboundPayload {
+    pub fn new(body: impl Into<String>) -> Self {
+        Self {
+            headers: HashMap::new(),
+            body: body.into(),
+        }
+    }
+
+    pub fn with_header(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.headers.insert(name.to_ascii_lowercase(), value.into());
+        self
+    }
+}
+
+/// A validated signal attributed to a strategy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalSignal {
+    pub id: Uuid,
+    pub source: String,
+    pub strategy_id: Uuid,
+    pub symbol: Symbol,
+    pub signal_type: SignalType,
+
+    /// Required for opens; closes without one close the whole position
+    pub quantity: Option<Quantity>,
+    pub price: Option<Price>,
+    pub stop_loss: Option<Price>,
+    pub take_profit: Option<Price>,
+    pub confidence: f64,
+    pub received_at: DateTime<Utc>,
+
+    /// Original payload with any token field removed
+    pub payload: JsonValue,
+}
+
+/// Parse tickers such as `BTCUSDT`, `BINANCE:BTCUSDT.P`, `BTC/USDT` or
+/// `BTC-USDT-SWAP` into a symbol
+///
+/// Spot tickers become `BASE-QUOTE`; perpetuals, marked `.P`, `-SWAP` or
+/// `PERP`, become `BASE-QUOTE-SWAP`.
+pub fn normalize_symbol(ticker: &str) -> Result<Symbol> {
+    let mut ticker = ticker.trim().to_ascii_uppercase();
+    if let Some((_, rest)) = ticker.split_once(':') {
+        ticker = rest.to_string();
+    }
+    let mut perpetual = false;
+    for suffix in [".P", "-SWAP", "PERP"] {
+        if let Some(stripped) = ticker.strip_suffix(suffix) {
+            ticker = stripped.trim_end_matches(['-', '_']).to_string();
+            perpetual = true;
+        }
+    }
+    let ticker = ticker.replace(['/', '_'], "-");
+
+    let pair = if ticker.contains('-') {
+        ticker
+    } else {
+        QUOTE_CURRENCIES
+            .iter()
+            .find_map(|quote| {
+                let base = ticker.strip_suffix(quote)?;
+                (!base.is_empty()).then(|| format!("{}-{}", base, quote))
+            })
+            .ok_or_else(|| Error::SignalError(format!("unrecognized ticker '{}'", ticker)))?
+    };
+    let symbol = if perpetual {
+        format!("{}-SWAP", pair)
+    } else {
+        pair
+    };
+    Ok(Symbol::new(symbol)?)
+}
+
+/// Parse an action such as `buy`, `short` or `close_long`
+pub fn parse_action(action: &str) -> Result<SignalType> {
+    match action.trim().to_ascii_lowercase().as_str() {
+        "buy" | "long" | "enter_long" => Ok(SignalType::Buy),
+        "sell" | "short" | "enter_short" => Ok(SignalType::Sell),
+        "close_long" | "exit_long" => Ok(SignalType::CloseLong),
+        "close_short" | "exit_short" | "cover" => Ok(SignalType::CloseShort),
+        other => Err(Error::SignalError(format!(
+            "unsupported action '{}' (expected buy, sell, close_long or close_short)",
+            other
+        ))),
+    }
+}
+
+fn field<'a>(payload: &'a JsonValue, aliases: &[&str]) -> Option<&'a JsonValue> {
+    aliases
+        .iter()
+        .find_map(|name| payload.get(*name))
+        .filter(|v| !v.is_null())
+}
+
+fn decimal_field(payload: &JsonValue, aliases: &[&str]) -> Result<Option<Decimal>> {
+    let Some(value) = field(payload, aliases) else {
+        return Ok(None);
+    };
+    let text = match value {
+        JsonValue::String(s) => s.trim().to_string(),
+        JsonValue::Number(n) => n.to_string(),
+        other => {
+            return Err(Error::SignalError(format!(
+                "{} must be a number, got {}",
+                aliases[0], other
+            )));
+        }
+    };
+    text.parse::<Decimal>()
+        .map(Some)
+        .map_err(|e| Error::SignalError(format!("invalid {} '{}': {}", aliases[0], text, e)))
+}
+
+fn price_field(payload: &JsonValue, aliases: &[&str]) -> Result<Option<Price>> {
+    decimal_field(payload, aliases)?
+        .map(|p| Price::new(p).map_err(Error::from))
+        .transpose()
+}
+
+/// HMAC-SHA256 of `message` under `secret`, ready to finalize or verify
+fn mac(secret: &str, message: &str) -> Result<HmacSha256> {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
+        .map_err(|e| Error::InvalidConfig(format!("invalid HMAC secret: {}", e)))?;
+    mac.update(message.as_bytes());
+    Ok(mac)
+}
+
+/// Check a signed request's timestamp and nonce and its signature over both
+///
+/// `nonces` holds the nonces seen inside the window, by timestamp.
+fn verify_signature(
+    secret: &str,
+    inbound: &InboundPayload,
+    nonces: &mut HashMap<String, i64>,
+) -> Result<bool> {
+    let (Some(timestamp), Some(nonce), Some(given)) = (
+        inbound.headers.get(TIMESTAMP_HEADER),
+        inbound.headers.get(NONCE_HEADER),
+        inbound.headers.get(SIGNATURE_HEADER),
+    ) else {
+        return Ok(false);
+    };
+    let Ok(sent_at) = timestamp.trim().parse::<i64>() else {
+        return Ok(false);
+    };
+    let now = Utc::now().timestamp();
+    if (now - sent_at).abs() > SIGNATURE_WINDOW_SECS {
+        return Err(Error::Unauthorized(format!(
+            "request timestamp {} is outside the {}s window",
+            sent_at, SIGNATURE_WINDOW_SECS
+        )));
+    }
+
+    let Ok(given) = hex::decode(given.trim()) else {
+        return Ok(false);
+    };
+    let message = format!("{}.{}.{}", timestamp, nonce, inbound.body);
+    if mac(secret, &message)?.verify_slice(&given).is_err() {
+        return Ok(false);
+    }
+
+    nonces.retain(|_, seen_at| (now - *seen_at).abs() <= SIGNATURE_WINDOW_SECS);
+    if nonces.insert(nonce.clone(), sent_at).is_some() {
+        return Err(Error::Unauthorized(format!(
+            "nonce '{}' was already used",
+            nonce
+        )));
+    }
+    Ok(true)
+}
+
+fn authenticate(
+    auth: &SourceAuth,
+    inbound: &InboundPayload,
+    body: &JsonValue,
+    nonces: &mut HashMap<String, i64>,
+) -> Result<()> {
+    let authorized = match auth {
+        SourceAuth::None => true,
+        SourceAuth::Token { token } => inbound
+            .headers
+            .get(TOKEN_HEADER)
+            .map(String::as_str)
+            .or_else(|| field(body, &["passphrase", "token"]).and_then(JsonValue::as_str))
+            .is_some_and(|given| given.as_bytes().ct_eq(token.as_bytes()).into()),
+        SourceAuth::HmacSha256 { secret } => verify_signature(secret, inbound, nonces)?,
+    };
+    if authorized {
+        Ok(())
+    } else {
+        Err(Error::Unauthorized(
+            "invalid or missing credentials".to_string(),
+        ))
+    }
+}
+
+/// Validate and normalize a parsed payload for `config`
+fn normalize(config: &SignalSourceConfig, mut payload: JsonValue) -> Result<ExternalSignal> {
+    if !payload.is_object() {
+        return Err(Error::SignalError(
+            "payload must be a JSON object".to_string(),
+        ));
+    }
+    if let Some(object) = payload.as_object_mut() {
+        object.remove("passphrase");
+        object.remove("token");
+    }
+
+    let ticker = field(&payload, &["symbol", "ticker", "instrument"])
+        .and_then(JsonValue::as_str)
+        .ok_or_else(|| Error::SignalError("missing symbol".to_string()))?;
+    let symbol = normalize_symbol(ticker)?;
+    if !config.allowed_symbols.is_empty() && !config.allowed_symbols.contains(&symbol) {
+        return Err(Error::SignalError(format!(
+            "{} is not allowed for source '{}'",
+            symbol.as_str(),
+            config.name
+        )));
+    }
+
+    let action = field(&payload, &["action", "side", "signal"])
+        .and_then(JsonValue::as_str)
+        .ok_or_else(|| Error::SignalError("missing action".to_string()))?;
+    let signal_type = parse_action(action)?;
+
+    let quantity = decimal_field(&payload, &["quantity", "qty", "size", "contracts"])?;
+    let quantity = match signal_type {
+        SignalType::Buy | SignalType::Sell => Some(
+            quantity
+                .or(config.default_quantity)
+                .ok_or_else(|| Error::SignalError("open signals need a quantity".to_string()))?,
+        ),
+        _ => quantity,
+    }
+    .map(Quantity::new)
+    .transpose()?;
+
+    let confidence = decimal_field(&payload, &["confidence"])?
+        .map(|c| c.to_string().parse::<f64>().unwrap_or(1.0))
+        .unwrap_or(1.0);
+    if !(0.0..=1.0).contains(&confidence) {
+        return Err(Error::SignalError(format!(
+            "confidence {} is outside [0, 1]",
+            confidence
+        )));
+    }
+
+    Ok(ExternalSignal {
+        id: Uuid::new_v4(),
+        source: config.name.clone(),
+        strategy_id: config.strategy_id,
+        symbol,
+        signal_type,
+        quantity,
+        price: price_field(&payload, &["price", "limit_price"])?,
+        stop_loss: price_field(&payload, &["stop_loss", "sl"])?,
+        take_profit: price_field(&payload, &["take_profit", "tp"])?,
+        confidence,
+        received_at: Utc::now(),
+        payload,
+    })
+}
+
+/// Somewhere external signals can be polled from
+#[async_trait]
+pub trait ExternalSignalSource: Send + Sync {
+    /// Name of the [`SignalSourceConfig`] the payloads belong to
+    fn name(&self) -> &str;
+
+    /// Payloads that arrived since the previous poll
+    async fn poll(&self) -> Result<Vec<InboundPayload>>;
+}
+
+/// Appended JSON lines in a file, one signal per line
+pub struct FileSignalSource {
+    name: String,
+    path: PathBuf,
+    offset: Mutex<usize>,
+}
+
+impl FileSignalSource {
+    /// Starts at the end of the file: only lines appended later are read
+    pub fn new(name: impl Into<String>, path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let offset = std::fs::metadata(&path).map_or(0, |m| m.len() as usize);
+        Self {
+            name: name.into(),
+            path,
+            offset: Mutex::new(offset),
+        }
+    }
+}
+
+#[async_trait]
+impl ExternalSignalSource for FileSignalSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn poll(&self) -> Result<Vec<InboundPayload>> {
+        let contents = match tokio::fs::read_to_string(&self.path).await {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(Error::Internal(format!(
+                    "failed to read {}: {}",
+                    self.path.display(),
+                    e
+                )));
+            }
+        };
+
+        let mut offset = self.offset.lock();
+        if contents.len() < *offset {
+            // Truncated or rotated: start over
+            *offset = 0;
+        }
+        // Leave a partially written last line for the next poll
+        let Some(end) = contents[*offset..].rfind('\n').map(|i| *offset + i + 1) else {
+            return Ok(Vec::new());
+        };
+        let payloads = contents[*offset..end]
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(InboundPayload::new)
+            .collect();
+        *offset = end;
+        Ok(payloads)
+    }
+}
+
+/// JSON endpoint returning a signal object or an array of them
+///
+/// Signals carrying an `id` field are delivered once; endpoints without ids
+/// should serve each signal only once themselves.
+pub struct UrlSignalSource {
+    name: String,
+    url: String,
+    http: reqwest::Client,
+    seen: Mutex<HashSet<String>>,
+}
+
+impl UrlSignalSource {
+    pub fn new(name: impl Into<String>, url: impl Into<String>) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e| Error::InvalidConfig(format!("HTTP client: {}", e)))?;
+        Ok(Self {
+            name: name.into(),
+            url: url.into(),
+            http,
+            seen: Mutex::new(HashSet::new()),
+        })
+    }
+}
+
+#[async_trait]
+impl ExternalSignalSource for UrlSignalSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn poll(&self) -> Result<Vec<InboundPayload>> {
+        let body: JsonValue = self
+            .http
+            .get(&self.url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| Error::Internal(format!("{}: {}", self.url, e)))?
+            .json()
+            .await
+            .map_err(|e| Error::SignalError(format!("{}: {}", self.url, e)))?;
+
+        let items = match body {
+            JsonValue::Array(items) => items,
+            JsonValue::Null => Vec::new(),
+            item => vec![item],
+        };
+        let mut seen = self.seen.lock();
+        Ok(items
+            .into_iter()
+            .filter(|item| match item.get("id") {
+                Some(id) => seen.insert(id.to_string()),
+                None => true,
+            })
+            .map(|item| InboundPayload::new(item.to_string()))
+            .collect())
+    }
+}
+
+struct SourceState {
+    config: SignalSourceConfig,
+    recent: VecDeque<Instant>,
+    nonces: HashMap<String, i64>,
+}
+
+/// Authenticates, rate-limits and normalizes external signals
+pub struct SignalIngestor {
+    sources: RwLock<HashMap<String, SourceState>>,
+
+    /// Set once webhooks listen beyond loopback; every source is then
+    /// reachable from the network and must authenticate
+    require_auth: AtomicBool,
+
+    /// Event channel
+    signal_tx: mpsc::UnboundedSender<ExternalSignal>,
+    signal_rx: RwLock<Option<mpsc::UnboundedReceiver<ExternalSignal>>>,
+}
+
+impl SignalIngestor {
+    pub fn new() -> Self {
+        let (signal_tx, signal_rx) = mpsc::unbounded_channel();
+        Self {
+            sources: RwLock::new(HashMap::new()),
+            require_auth: AtomicBool::new(false),
+            signal_tx,
+            signal_rx: RwLock::new(Some(signal_rx)),
+        }
+    }
+
+    /// Add or replace a source
+    pub fn register_source(&self, config: SignalSourceConfig) -> Result<()> {
+        config.validate()?;
+        let mut sources = self.sources.write();
+        if self.require_auth.load(Ordering::SeqCst) && matches!(config.auth, SourceAuth::None) {
+            return Err(Error::InvalidConfig(format!(
+                "source '{}' needs authentication while webhooks are public",
+                config.name
+            )));
+        }
+        sources.insert(
+            config.name.clone(),
+            SourceState {
+                config,
+                recent: VecDeque::new(),
+                nonces: HashMap::new(),
+            },
+        );
+        Ok(())
+    }
+
+    pub fn remove_source(&self, name: &str) -> bool {
+        self.sources.write().remove(name).is_some()
+    }
+
+    pub fn sources(&self) -> Vec<SignalSourceConfig> {
+        let mut sources: Vec<_> = self
+            .sources
+            .read()
+            .values()
+            .map(|s| s.config.clone())
+            .collect();
+        sources.sort_by(|a, b| a.name.cmp(&b.name));
+        sources
+    }
+
+    /// Accept a payload from `source` and publish the normalized signal
+    pub fn ingest(&self, source: &str, inbound: &InboundPayload) -> Result<ExternalSignal> {
+        let mut sources = self.sources.write();
+        let state = sources
+            .get_mut(source)
+            .ok_or_else(|| Error::NotFound(format!("signal source '{}'", source)))?;
+
+        let body: JsonValue = serde_json::from_str(&inbound.body)
+            .map_err(|e| Error::SignalError(format!("payload is not JSON: {}", e)))?;
+        authenticate(&state.config.auth, inbound, &body, &mut state.nonces)?;
+
+        let now = Instant::now();
+        while state
+            .recent
+            .front()
+            .is_some_and(|at| now.duration_since(*at) >= Duration::from_secs(60))
+        {
+            state.recent.pop_front();
+        }
+        if state.recent.len() >= state.config.max_signals_per_minute as usize {
+            return Err(Error::RateLimited(format!(
+                "source '{}' is limited to {} signals per minute",
+                source, state.config.max_signals_per_minute
+            )));
+        }
+
+        let signal = normalize(&state.config, body)?;
+        state.recent.push_back(now);
+        drop(sources);
+
+        info!(
+            "External signal from {}: {:?} {}",
+            signal.source,
+            signal.signal_type,
+            signal.symbol.as_str()
+        );
+        let _ = self.signal_tx.send(signal.clone());
+        Ok(signal)
+    }
+
+    /// Poll `source` every `interval` until the task is aborted
+    pub fn spawn_poller(
+        self: Arc<Self>,
+        source: Arc<dyn ExternalSignalSource>,
+        interval: Duration,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let payloads = match source.poll().await {
+                    Ok(payloads) => payloads,
+                    Err(e) => {
+                        warn!("Polling signal source {} failed: {}", source.name(), e);
+                        continue;
+                    }
+                };
+                for payload in payloads {
+                    if let Err(e) = self.ingest(source.name(), &payload) {
+                        warn!("Rejected signal from {}: {}", source.name(), e);
+                    }
+                }
+            }
+        })
+    }
+
+    /// Get signal receiver (can only be called once)
+    pub fn subscribe_signals(&self) -> Option<mpsc::UnboundedReceiver<ExternalSignal>> {
+        self.signal_rx.write().take()
+    }
+}
+
+impl Default for SignalIngestor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+async fn receive_webhook(
+    State(ingestor): State<Arc<SignalIngestor>>,
+    Path(source): Path<String>,
+    headers: HeaderMap,
+    body: String,
+) -> (StatusCode, axum::Json<JsonValue>) {
+    let mut inbound = InboundPayload::new(body);
+    for (name, value) in &headers {
+        if let Ok(value) = value.to_str() {
+            inbound = inbound.with_header(name.as_str(), value);
+        }
+    }
+
+    match ingestor.ingest(&source, &inbound) {
+        Ok(signal) => (StatusCode::ACCEPTED, axum::Json(json!({ "id": signal.id }))),
+        Err(e) => {
+            let status = match e {
+                Error::NotFound(_) => StatusCode::NOT_FOUND,
+                Error::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+                Error::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+                _ => StatusCode::UNPROCESSABLE_ENTITY,
+            };
+            warn!("Rejected webhook signal for {}: {}", source, e);
+            (status, axum::Json(json!({ "error": e.to_string() })))
+        }
+    }
+}
+
+/// Routes `POST /signals/{source}` into `ingestor`
+pub fn webhook_router(ingestor: Arc<SignalIngestor>) -> Router {
+    Router::new()
+        .route("/signals/:source", post(receive_webhook))
+        .with_state(ingestor)
+}
+
+/// Serve [`webhook_router`] on `addr` until the task is aborted
+///
+/// Beyond loopback, every source must authenticate: the server refuses to
+/// start while one does not, and unauthenticated sources cannot be added
+/// afterwards.
+pub async fn serve_webhooks(ingestor: Arc<SignalIngestor>, addr: SocketAddr) -> Result<()> {
+    if !addr.ip().is_loopback() {
+        // Hold the lock so no source slips in between the check and the flag
+        let sources = ingestor.sources.read();
+        if let Some(open) = sources
+            .values()
+            .find(|s| matches!(s.config.auth, SourceAuth::None))
+        {
+            return Err(Error::InvalidConfig(format!(
+                "refusing to serve webhooks on {}: source '{}' has no authentication",
+                addr, open.config.name
+            )));
+        }
+        ingestor.require_auth.store(true, Ordering::SeqCst);
+    }
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .map_err(|e| Error::Internal(format!("failed to bind {}: {}", addr, e)))?;
+    info!("Listening for signal webhooks on {}", addr);
+    axum::serve(listener, webhook_router(ingestor))
+        .await
+        .map_err(|e| Error::Internal(format!("webhook server failed: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use rust_decimal_macros::dec;
+    use tower::ServiceExt;
+
+    /// Hex signature the ingestor expects for `message`
+    fn sign(secret: &str, message: &str) -> Result<String> {
+        Ok(hex::encode(mac(secret, message)?.finalize().into_bytes()))
+    }
+
+    fn tradingview() -> SignalSourceConfig {
+        SignalSourceConfig::new("tradingview", Uuid::new_v4()).with_auth(SourceAuth::Token {
+            token: "hunter2".to_string(),
+        })
+    }
+
+    #[test]
+    fn test_tradingview_alert_is_normalized() {
+        let ingestor = SignalIngestor::new();
+        ingestor.register_source(tradingview()).unwrap();
+        let mut signals = ingestor.subscribe_signals().unwrap();
+
+        let alert = InboundPayload::new(
+            r#"{"passphrase":"hunter2","ticker":"BINANCE:BTCUSDT.P","action":"buy",
+                "contracts":"0.05","price":64250.5,"sl":63000}"#,
+        );
+        let signal = ingestor.ingest("tradingview", &alert).unwrap();
+        assert_eq!(signal.symbol.as_str(), "BTC-USDT-SWAP");
+        assert_eq!(signal.signal_type, SignalType::Buy);
+        assert_eq!(signal.quantity.unwrap().as_decimal(), dec!(0.05));
+        assert_eq!(signal.stop_loss.unwrap().as_decimal(), dec!(63000));
+        assert!(signal.payload.get("passphrase").is_none());
+        assert_eq!(signals.try_recv().unwrap().id, signal.id);
+
+        assert_eq!(normalize_symbol("eth/usdc").unwrap().as_str(), "ETH-USDC");
+        assert_eq!(normalize_symbol("BTCUSDT").unwrap().as_str(), "BTC-USDT");
+        assert_eq!(normalize_symbol("BTC-USDT-SWAP").unwrap().as_str(), "BTC-USDT-SWAP");
+        assert_eq!(normalize_symbol("ETHUSDTPERP").unwrap().as_str(), "ETH-USDT-SWAP");
+        assert_eq!(parse_action("exit_short").unwrap(), SignalType::CloseShort);
+    }
+
+    #[test]
+    fn test_authentication_per_source() {
+        let ingestor = SignalIngestor::new();
+        ingestor.register_source(tradingview()).unwrap();
+        ingestor
+            .register_source(SignalSourceConfig::new("vendor", Uuid::new_v4()).with_auth(
+                SourceAuth::HmacSha256 {
+                    secret: "s3cret".to_string(),
+                },
+            ))
+            .unwrap();
+
+        let body = r#"{"symbol":"ETH-USDT","action":"close_long"}"#;
+        assert!(matches!(
+            ingestor.ingest("tradingview", &InboundPayload::new(body)),
+            Err(Error::Unauthorized(_))
+        ));
+        assert!(
+            ingestor
+                .ingest(
+                    "tradingview",
+                    &InboundPayload::new(body).with_header("X-Signal-Token", "hunter2")
+                )
+                .is_ok()
+        );
+
+        let signed = |timestamp: i64, nonce: &str, signature: Option<&str>| {
+            let timestamp = timestamp.to_string();
+            let signature = signature.map_or_else(
+                || sign("s3cret", &format!("{}.{}.{}", timestamp, nonce, body)).unwrap(),
+                str::to_string,
+            );
+            InboundPayload::new(body)
+                .with_header(TIMESTAMP_HEADER, timestamp)
+                .with_header(NONCE_HEADER, nonce)
+                .with_header(SIGNATURE_HEADER, signature)
+        };
+        let now = Utc::now().timestamp();
+        assert!(ingestor.ingest("vendor", &signed(now, "n1", None)).is_ok());
+        assert!(matches!(
+            ingestor.ingest("vendor", &signed(now, "n2", Some("00"))),
+            Err(Error::Unauthorized(_))
+        ));
+    }
+
+    #[test]
+    fn test_signed_requests_expire_and_cannot_be_replayed() {
+        let ingestor = SignalIngestor::new();
+        ingestor
+            .register_source(SignalSourceConfig::new("vendor", Uuid::new_v4()).with_auth(
+                SourceAuth::HmacSha256 {
+                    secret: "s3cret".to_string(),
+                },
+            ))
+            .unwrap();
+        let body = r#"{"symbol":"ETH-USDT","action":"close_long"}"#;
+        let signed = |timestamp: i64, nonce: &str| {
+            let timestamp = timestamp.to_string();
+            let signature = sign("s3cret", &format!("{}.{}.{}", timestamp, nonce, body)).unwrap();
+            InboundPayload::new(body)
+                .with_header(TIMESTAMP_HEADER, timestamp)
+                .with_header(NONCE_HEADER, nonce)
+                .with_header(SIGNATURE_HEADER, signature)
+        };
+        let now = Utc::now().timestamp();
+
+        let request = signed(now, "once");
+        assert!(ingestor.ingest("vendor", &request).is_ok());
+        assert!(matches!(
+            ingestor.ingest("vendor", &request),
+            Err(Error::Unauthorized(_))
+        ));
+
+        // A correctly signed but stale request
+        assert!(matches!(
+            ingestor.ingest("vendor", &signed(now - SIGNATURE_WINDOW_SECS - 60, "old")),
+            Err(Error::Unauthorized(_))
+        ));
+
+        // The body-only signature of earlier versions is no longer accepted
+        let legacy =
+            InboundPayload::new(body).with_header(SIGNATURE_HEADER, sign("s3cret", body).unwrap());
+        assert!(matches!(
+            ingestor.ingest("vendor", &legacy),
+            Err(Error::Unauthorized(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_public_webhooks_require_authenticated_sources() {
+        let ingestor = Arc::new(SignalIngestor::new());
+        ingestor
+            .register_source(SignalSourceConfig::new("open", Uuid::new_v4()))
+            .unwrap();
+        let public: SocketAddr = "0.0.0.0:0".parse().unwrap();
+        assert!(matches!(
+            serve_webhooks(ingestor.clone(), public).await,
+            Err(Error::InvalidConfig(_))
+        ));
+
+        // Once public, unauthenticated sources cannot be added
+        assert!(ingestor.remove_source("open"));
+        ingestor.register_source(tradingview()).unwrap();
+        let server = tokio::spawn(serve_webhooks(ingestor.clone(), public));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(matches!(
+            ingestor.register_source(SignalSourceConfig::new("open", Uuid::new_v4())),
+            Err(Error::InvalidConfig(_))
+        ));
+        server.abort();
+    }
+
+    #[test]
+    fn test_rate_limit_and_validation() {
+        let ingestor = SignalIngestor::new();
+        ingestor
+            .register_source(
+                SignalSourceConfig::new("script", Uuid::new_v4())
+                    .with_rate_limit(2)
+                    .with_allowed_symbols(vec![Symbol::new("BTC-USDT").unwrap()]),
+            )
+            .unwrap();
+        let signal = |body: &str| ingestor.ingest("script", &InboundPayload::new(body));
+
+        // Opens need a quantity; other symbols are refused
+        assert!(matches!(
+            signal(r#"{"symbol":"BTC-USDT","action":"buy"}"#),
+            Err(Error::SignalError(_))
+        ));
+        assert!(matches!(
+            signal(r#"{"symbol":"ETH-USDT","action":"sell","qty":1}"#),
+            Err(Error::SignalError(_))
+        ));
+
+        let close = r#"{"symbol":"BTC-USDT","action":"close_long"}"#;
+        assert!(signal(close).is_ok());
+        assert!(signal(close).is_ok());
+        assert!(matches!(signal(close), Err(Error::RateLimited(_))));
+    }
+
+    #[tokio::test]
+    async fn test_webhook_and_file_sources() {
+        let ingestor = Arc::new(SignalIngestor::new());
+        ingestor.register_source(tradingview()).unwrap();
+
+        let request = |uri: &str, body: &str| {
+            Request::post(uri)
+                .header("x-signal-token", "hunter2")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+        let router = webhook_router(ingestor.clone());
+        let body = r#"{"ticker":"SOLUSDT","action":"sell","qty":2}"#;
+        let accepted = router
+            .clone()
+            .oneshot(request("/signals/tradingview", body))
+            .await
+            .unwrap();
+        assert_eq!(accepted.status(), StatusCode::ACCEPTED);
+        let unknown = router
+            .oneshot(request("/signals/nobody", body))
+            .await
+            .unwrap();
+        assert_eq!(unknown.status(), StatusCode::NOT_FOUND);
+
+        let path = std::env::temp_dir().join(format!("signals-{}.jsonl", Uuid::new_v4()));
+        std::fs::write(&path, "{\"old\":true}\n").unwrap();
+        let file = FileSignalSource::new("file", &path);
+        assert!(file.poll().await.unwrap().is_empty());
+
+        let mut appended = std::fs::read_to_string(&path).unwrap();
+        appended.push_str("{\"symbol\":\"BTC-USDT\",\"action\":\"buy\"}\n{\"partial\":");
+        std::fs::write(&path, appended).unwrap();
+        let payloads = file.poll().await.unwrap();
+        assert_eq!(payloads.len(), 1);
+        assert!(payloads[0].body.contains("BTC-USDT"));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -8,17 +8,61 @@
 //! - Trait-based strategy interface
 //! - Strategy lifecycle state machine
 //! - Hot-reload mechanism with state serialization
-//! - Performance metrics tracking
+//! - Live tuning of parameters flagged hot-tunable in the schema
+//! - Performance metrics tracking with rolling-window series
+//! - Namespaced custom strategy metrics with Prometheus export and history
 //! - Signal generation framework
+//! - Composite strategies combining child signals with vote and filter rules
+//! - Scheduled dollar-cost averaging with dip acceleration and skip rules
+//! - Hedge ratio, spread z-score and stationarity statistics for pairs strategies
+//! - Sandboxed Rhai scripts for lightweight strategies
+//! - Signed strategy bundles for sharing between installations
+//! - External signal ingestion by webhook or polling, per-source auth and rate limits
+//! - Synthetic market scenarios and a mock context for deterministic strategy tests
+//! - Panic isolation for running strategies with order cleanup and backoff restarts
 
+pub mod bundle;
+pub mod composite;
+pub mod custom_metrics;
+pub mod dca;
 pub mod error;
+pub mod external;
 pub mod lifecycle;
 pub mod metrics;
+pub mod script;
 pub mod signal;
+pub mod stats;
+pub mod supervisor;
+pub mod testkit;
 pub mod traits;
+pub mod tuning;
 
+pub use bundle::{BacktestEvidence, SignatureAlgorithm, StrategyBundle};
+pub use composite::{
+    ChildConfig, ChildRole, Combiner, CompositeConfig, CompositeStrategy, FilterConfig,
+};
+pub use custom_metrics::{
+    Counter, Gauge, Histogram, HistogramSnapshot, MetricKind, MetricPoint, MetricSample,
+    MetricValue, MetricsRegistry, StrategyMetrics, metrics_router, serve_metrics,
+};
+pub use dca::{
+    DCA_STRATEGY_TYPE, DcaAction, DcaConfig, DcaEntry, DcaLedger, DcaPosition, DcaSchedule,
+    DcaStrategy, DipRule, SkipRule,
+};
 pub use error::{Error, Result};
+pub use external::{
+    ExternalSignal, ExternalSignalSource, FileSignalSource, InboundPayload, SignalIngestor,
+    SignalSourceConfig, SourceAuth, UrlSignalSource, serve_webhooks, webhook_router,
+};
 pub use lifecycle::{StrategyLifecycle, StrategyState};
-pub use metrics::PerformanceMetrics;
+pub use metrics::{
+    DEFAULT_ROLLING_WINDOWS, PerformanceMetrics, RollingMetrics, RollingPoint, RollingSeries,
+};
+pub use script::{SCRIPT_PARAMETER, SCRIPT_STRATEGY_TYPE, ScriptLimits, ScriptStrategy};
 pub use signal::{Signal, SignalType};
+pub use supervisor::{
+    LiveStrategy, OrderCanceller, OrderCleanup, RestartPolicy, StrategyFactory, StrategyFailure,
+    StrategyInput, StrategySupervisor, SupervisorEvent, SupervisorExit, builtin_factory,
+};
 pub use traits::{MarketDataEvent, Strategy, StrategyConfig};
+pub use tuning::{HOT_TUNABLE, ParameterUpdate, apply_live_update, hot_tunable_parameters};
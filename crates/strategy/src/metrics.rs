@@ -1,7 +1,15 @@
 //! Performance metrics calculation
 
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Rolling window lengths reported by default, in days
+pub const DEFAULT_ROLLING_WINDOWS: [u32; 3] = [30, 90, 180];
+
+/// Crypto markets trade every day, so daily returns annualize over 365 days
+const DAYS_PER_YEAR: f64 = 365.0;
 
 /// Strategy performance metrics
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,6 +27,8 @@ pub struct PerformanceMetrics {
     pub sharpe_ratio: Option<f64>,
     pub max_drawdown: f64,
     pub total_volume: Decimal,
+    #[serde(default)]
+    pub rolling: RollingMetrics,
 }
 
 impl Default for PerformanceMetrics {
@@ -37,6 +47,7 @@ impl Default for PerformanceMetrics {
             sharpe_ratio: None,
             max_drawdown: 0.0,
             total_volume: Decimal::ZERO,
+            rolling: RollingMetrics::default(),
         }
     }
 }
@@ -66,4 +77,185 @@ impl PerformanceMetrics {
             self.profit_factor = total_wins / total_losses;
         }
     }
+
+    /// Recompute the rolling series over the default windows
+    pub fn calculate_rolling(
+        &mut self,
+        equity_curve: &[(DateTime<Utc>, Decimal)],
+        closed_trades: &[(DateTime<Utc>, Decimal)],
+    ) {
+        self.rolling =
+            RollingMetrics::calculate(equity_curve, closed_trades, &DEFAULT_ROLLING_WINDOWS);
+    }
+}
+
+/// Metrics over the window ending on one day
+///
+/// Ratios are `None` when the window has no variation to divide by, and the
+/// win rate is `None` when no trades closed in the window.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RollingPoint {
+    pub date: NaiveDate,
+    pub sharpe_ratio: Option<f64>,
+    pub sortino_ratio: Option<f64>,
+    /// Annualized standard deviation of daily returns
+    pub volatility: f64,
+    pub win_rate: Option<f64>,
+}
+
+/// Daily series of metrics over a fixed-length trailing window
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RollingSeries {
+    pub window_days: u32,
+    pub points: Vec<RollingPoint>,
+}
+
+/// Rolling Sharpe, Sortino, volatility and win rate for charting
+///
+/// The equity curve is sampled at each UTC day's last value; a point is
+/// emitted for every day with a full window of daily returns behind it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RollingMetrics {
+    pub series: Vec<RollingSeries>,
+}
+
+impl RollingMetrics {
+    /// Compute rolling series from an equity curve and `(exit_time, pnl)` of
+    /// closed trades
+    pub fn calculate(
+        equity_curve: &[(DateTime<Utc>, Decimal)],
+        closed_trades: &[(DateTime<Utc>, Decimal)],
+        windows: &[u32],
+    ) -> Self {
+        let mut daily_close: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+        for (timestamp, equity) in equity_curve {
+            let equity = equity.to_string().parse::<f64>().unwrap_or(0.0);
+            daily_close.insert(timestamp.date_naive(), equity);
+        }
+
+        let closes: Vec<(NaiveDate, f64)> = daily_close.into_iter().collect();
+        let returns: Vec<(NaiveDate, f64)> = closes
+            .windows(2)
+            .filter(|pair| pair[0].1 > 0.0)
+            .map(|pair| (pair[1].0, (pair[1].1 - pair[0].1) / pair[0].1))
+            .collect();
+
+        let series = windows
+            .iter()
+            .map(|&window_days| RollingSeries {
+                window_days,
+                points: Self::window_points(&returns, closed_trades, window_days),
+            })
+            .collect();
+
+        Self { series }
+    }
+
+    /// The series for a window length, if it was calculated
+    pub fn window(&self, days: u32) -> Option<&RollingSeries> {
+        self.series.iter().find(|s| s.window_days == days)
+    }
+
+    fn window_points(
+        returns: &[(NaiveDate, f64)],
+        closed_trades: &[(DateTime<Utc>, Decimal)],
+        window_days: u32,
+    ) -> Vec<RollingPoint> {
+        let window = window_days as usize;
+        if window == 0 || returns.len() < window {
+            return Vec::new();
+        }
+
+        returns
+            .windows(window)
+            .map(|slice| {
+                let date = slice[window - 1].0;
+                let values: Vec<f64> = slice.iter().map(|(_, r)| *r).collect();
+                let n = values.len() as f64;
+                let mean = values.iter().sum::<f64>() / n;
+                let std_dev = (values.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / n).sqrt();
+
+                let downside: Vec<f64> = values.iter().copied().filter(|r| *r < 0.0).collect();
+                let downside_dev = if downside.is_empty() {
+                    0.0
+                } else {
+                    (downside.iter().map(|r| r * r).sum::<f64>() / downside.len() as f64).sqrt()
+                };
+
+                let annualize = DAYS_PER_YEAR.sqrt();
+                let ratio = |dev: f64| (dev > 0.0).then(|| mean / dev * annualize);
+
+                // Trades that closed within the window's days
+                let window_start = date - Duration::days(window_days as i64 - 1);
+                let pnls: Vec<&Decimal> = closed_trades
+                    .iter()
+                    .filter(|(exit, _)| {
+                        let day = exit.date_naive();
+                        day >= window_start && day <= date
+                    })
+                    .map(|(_, pnl)| pnl)
+                    .collect();
+                let win_rate = (!pnls.is_empty()).then(|| {
+                    pnls.iter().filter(|p| ***p > Decimal::ZERO).count() as f64 / pnls.len() as f64
+                });
+
+                RollingPoint {
+                    date,
+                    sharpe_ratio: ratio(std_dev),
+                    sortino_ratio: ratio(downside_dev),
+                    volatility: std_dev * annualize,
+                    win_rate,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+
+    fn day(n: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap() + Duration::days(n)
+    }
+
+    #[test]
+    fn test_rolling_series_emitted_once_window_is_full() {
+        // Alternating +2% / -1% days
+        let mut equity = dec!(10000);
+        let mut curve = vec![(day(0), equity)];
+        for n in 1..=40 {
+            equity *= if n % 2 == 1 { dec!(1.02) } else { dec!(0.99) };
+            curve.push((day(n), equity));
+        }
+        let trades = vec![(day(35), dec!(50)), (day(38), dec!(-20))];
+
+        let rolling = RollingMetrics::calculate(&curve, &trades, &[30, 90]);
+
+        let thirty = rolling.window(30).unwrap();
+        assert_eq!(thirty.points.len(), 11);
+        assert_eq!(thirty.points[0].date, day(30).date_naive());
+
+        let last = thirty.points.last().unwrap();
+        assert!(last.sharpe_ratio.unwrap() > 0.0);
+        assert!(last.sortino_ratio.unwrap() > last.sharpe_ratio.unwrap());
+        assert!(last.volatility > 0.0);
+        assert_eq!(last.win_rate, Some(0.5));
+        assert_eq!(thirty.points[0].win_rate, None);
+
+        assert!(rolling.window(90).unwrap().points.is_empty());
+    }
+
+    #[test]
+    fn test_flat_equity_has_no_ratios() {
+        let curve: Vec<_> = (0..=5).map(|n| (day(n), dec!(1000))).collect();
+        let rolling = RollingMetrics::calculate(&curve, &[], &[5]);
+
+        let point = &rolling.window(5).unwrap().points[0];
+        assert_eq!(point.sharpe_ratio, None);
+        assert_eq!(point.sortino_ratio, None);
+        assert_eq!(point.volatility, 0.0);
+    }
 }
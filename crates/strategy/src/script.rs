@@ -0,0 +1,592 @@
+//! Scripted strategies
+//!
+//! A [`ScriptStrategy`] runs a small [Rhai](https://rhai.rs) script, stored
+//! in the strategy's `script` parameter, on every candle. Simple entry and
+//! exit logic can then be changed without rebuilding the application, and a
+//! script flagged hot-tunable is swapped into a running strategy in place.
+//!
+//! The script sees:
+//!
+//! - `candle`: the bar just received (`symbol`, `interval`, `open`, `high`,
+//!   `low`, `close`, `volume`, `timestamp` in ms)
+//! - `params`: the strategy's other parameters
+//! - `state`: a map kept between runs and saved with the strategy state
+//!
+//! and can call `emit_signal(kind)` or `emit_signal(kind, confidence)` with
+//! `kind` one of `buy`, `sell`, `close_long`, `close_short` or `hold`.
+//! Indicators over the bars of the current symbol and interval are
+//! `sma(n)`, `ema(n)`, `rsi(n)`, `highest(n)` and `lowest(n)`; they return
+//! NaN, which compares false, until `ready(n)` bars have been seen.
+//!
+//! ```rhai
+//! if ready(30) {
+//!     let fast = sma(10);
+//!     let slow = sma(30);
+//!     if fast > slow && state.side != "long" {
+//!         state.side = "long";
+//!         emit_signal("buy", 0.7);
+//!     } else if fast < slow && state.side == "long" {
+//!         state.side = "flat";
+//!         emit_signal("close_long");
+//!     }
+//! }
+//! ```
+//!
+//! Scripts run sandboxed: there is no file, network or module access, no
+//! `eval`, and every run is bounded by [`ScriptLimits`].
+
+use crate::error::{Error, Result};
+use crate::metrics::PerformanceMetrics;
+use crate::signal::{Signal, SignalType};
+use crate::traits::{MarketDataEvent, Strategy, StrategyConfig};
+use async_trait::async_trait;
+use ea_okx_core::Interval;
+use ea_okx_core::models::Order;
+use ea_okx_core::types::Symbol;
+use parking_lot::Mutex;
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{AST, Dynamic, Engine, EvalAltResult, Map, Scope};
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value as JsonValue, json};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tracing::{debug, info};
+
+/// Strategy type of strategies built from a script
+pub const SCRIPT_STRATEGY_TYPE: &str = "script";
+
+/// Parameter holding the script source
+pub const SCRIPT_PARAMETER: &str = "script";
+
+/// Resource bounds of a script run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScriptLimits {
+    /// Operations one run may execute before it is aborted
+    pub max_operations: u64,
+
+    /// Bars kept per symbol and interval for indicators
+    pub history: usize,
+}
+
+impl Default for ScriptLimits {
+    fn default() -> Self {
+        Self {
+            max_operations: 100_000,
+            history: 500,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bar {
+    high: f64,
+    low: f64,
+    close: f64,
+}
+
+/// Data shared between the strategy and the functions it registers
+#[derive(Debug, Default)]
+struct ScriptContext {
+    series: HashMap<(Symbol, Interval), VecDeque<Bar>>,
+
+    /// Series of the candle being processed
+    current: Option<(Symbol, Interval)>,
+
+    /// Signal emitted by the last run
+    emitted: Option<Signal>,
+}
+
+impl ScriptContext {
+    fn bars(&self) -> Option<&VecDeque<Bar>> {
+        self.series.get(self.current.as_ref()?)
+    }
+
+    /// Last `n` bars, or `None` when fewer have been seen
+    fn last(&self, n: i64) -> Option<impl Iterator<Item = &Bar>> {
+        let bars = self.bars()?;
+        let n = usize::try_from(n)
+            .ok()
+            .filter(|n| *n > 0 && *n <= bars.len())?;
+        Some(bars.iter().skip(bars.len() - n))
+    }
+
+    fn sma(&self, n: i64) -> f64 {
+        self.last(n)
+            .map(|bars| bars.map(|b| b.close).sum::<f64>() / n as f64)
+            .unwrap_or(f64::NAN)
+    }
+
+    /// Seeded with the average of the first `n` closes
+    fn ema(&self, n: i64) -> f64 {
+        let Some(bars) = self.bars() else {
+            return f64::NAN;
+        };
+        let Some(n) = usize::try_from(n)
+            .ok()
+            .filter(|n| *n > 0 && *n <= bars.len())
+        else {
+            return f64::NAN;
+        };
+        let alpha = 2.0 / (n as f64 + 1.0);
+        let seed = bars.iter().take(n).map(|b| b.close).sum::<f64>() / n as f64;
+        bars.iter()
+            .skip(n)
+            .fold(seed, |ema, bar| ema + alpha * (bar.close - ema))
+    }
+
+    /// Wilder's RSI; needs `n + 1` bars
+    fn rsi(&self, n: i64) -> f64 {
+        let Some(bars) = self.bars() else {
+            return f64::NAN;
+        };
+        let Some(n) = usize::try_from(n)
+            .ok()
+            .filter(|n| *n > 0 && *n < bars.len())
+        else {
+            return f64::NAN;
+        };
+        let changes: Vec<f64> = bars
+            .iter()
+            .zip(bars.iter().skip(1))
+            .map(|(prev, bar)| bar.close - prev.close)
+            .collect();
+        let mut gain = changes[..n].iter().map(|c| c.max(0.0)).sum::<f64>() / n as f64;
+        let mut loss = changes[..n].iter().map(|c| (-c).max(0.0)).sum::<f64>() / n as f64;
+        for change in &changes[n..] {
+            gain = (gain * (n - 1) as f64 + change.max(0.0)) / n as f64;
+            loss = (loss * (n - 1) as f64 + (-change).max(0.0)) / n as f64;
+        }
+        if loss == 0.0 {
+            return if gain == 0.0 { 50.0 } else { 100.0 };
+        }
+        100.0 - 100.0 / (1.0 + gain / loss)
+    }
+
+    fn highest(&self, n: i64) -> f64 {
+        self.last(n)
+            .map(|bars| bars.map(|b| b.high).fold(f64::MIN, f64::max))
+            .unwrap_or(f64::NAN)
+    }
+
+    fn lowest(&self, n: i64) -> f64 {
+        self.last(n)
+            .map(|bars| bars.map(|b| b.low).fold(f64::MAX, f64::min))
+            .unwrap_or(f64::NAN)
+    }
+}
+
+fn signal_type(kind: &str) -> Option<SignalType> {
+    match kind {
+        "buy" => Some(SignalType::Buy),
+        "sell" => Some(SignalType::Sell),
+        "hold" => Some(SignalType::Hold),
+        "close_long" => Some(SignalType::CloseLong),
+        "close_short" => Some(SignalType::CloseShort),
+        _ => None,
+    }
+}
+
+/// Sandboxed engine with the strategy API registered against `context`
+fn build_engine(limits: ScriptLimits, context: Arc<Mutex<ScriptContext>>) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(limits.max_operations);
+    engine.set_max_call_levels(32);
+    engine.set_max_expr_depths(64, 32);
+    engine.set_max_string_size(4096);
+    engine.set_max_array_size(10_000);
+    engine.set_max_map_size(10_000);
+    engine.set_max_modules(0);
+    engine.set_module_resolver(DummyModuleResolver::new());
+    engine.disable_symbol("eval");
+    engine.on_print(|text| info!("Strategy script: {}", text));
+    engine.on_debug(|text, _, _| debug!("Strategy script: {}", text));
+
+    let emit = {
+        let context = context.clone();
+        move |kind: &str, confidence: f64| -> std::result::Result<(), Box<EvalAltResult>> {
+            let signal_type =
+                signal_type(kind).ok_or_else(|| format!("unknown signal kind '{}'", kind))?;
+            if !(0.0..=1.0).contains(&confidence) {
+                return Err(format!("confidence {} is not between 0 and 1", confidence).into());
+            }
+            let mut context = context.lock();
+            let metadata = match &context.current {
+                Some((symbol, _)) => json!({ "symbol": symbol.as_str() }),
+                None => json!({}),
+            };
+            context.emitted = Some(Signal {
+                signal_type,
+                confidence,
+                metadata,
+                ..Signal::hold()
+            });
+            Ok(())
+        }
+    };
+    let emit_full = emit.clone();
+    engine.register_fn("emit_signal", move |kind: &str| emit(kind, 1.0));
+    engine.register_fn("emit_signal", emit_full);
+
+    let indicator = |engine: &mut Engine, name: &str, f: fn(&ScriptContext, i64) -> f64| {
+        let context = context.clone();
+        engine.register_fn(name, move |n: i64| f(&context.lock(), n));
+    };
+    indicator(&mut engine, "sma", ScriptContext::sma);
+    indicator(&mut engine, "ema", ScriptContext::ema);
+    indicator(&mut engine, "rsi", ScriptContext::rsi);
+    indicator(&mut engine, "highest", ScriptContext::highest);
+    indicator(&mut engine, "lowest", ScriptContext::lowest);
+    engine.register_fn("ready", move |n: i64| {
+        context
+            .lock()
+            .bars()
+            .is_some_and(|bars| i64::try_from(bars.len()).unwrap_or(i64::MAX) >= n)
+    });
+
+    engine
+}
+
+fn script_error(e: impl std::fmt::Display) -> Error {
+    Error::ScriptError(e.to_string())
+}
+
+fn to_float(value: rust_decimal::Decimal) -> f64 {
+    value.to_f64().unwrap_or(f64::NAN)
+}
+
+/// Strategy whose logic is a Rhai script
+pub struct ScriptStrategy {
+    limits: ScriptLimits,
+    engine: Engine,
+    context: Arc<Mutex<ScriptContext>>,
+    ast: Option<AST>,
+    params: Map,
+    state: Dynamic,
+}
+
+impl ScriptStrategy {
+    pub fn new(limits: ScriptLimits) -> Self {
+        let context = Arc::new(Mutex::new(ScriptContext::default()));
+        Self {
+            limits,
+            engine: build_engine(limits, context.clone()),
+            context,
+            ast: None,
+            params: Map::new(),
+            state: Dynamic::from_map(Map::new()),
+        }
+    }
+
+    /// Check that `source` compiles, e.g. before saving it
+    pub fn check(source: &str) -> Result<()> {
+        let context = Arc::new(Mutex::new(ScriptContext::default()));
+        build_engine(ScriptLimits::default(), context)
+            .compile(source)
+            .map(|_| ())
+            .map_err(script_error)
+    }
+
+    fn load(&mut self, parameters: &HashMap<String, JsonValue>) -> Result<()> {
+        if let Some(source) = parameters.get(SCRIPT_PARAMETER) {
+            let source = source.as_str().ok_or_else(|| {
+                Error::InvalidConfig(format!("'{}' must be a string", SCRIPT_PARAMETER))
+            })?;
+            self.ast = Some(self.engine.compile(source).map_err(script_error)?);
+        }
+        for (name, value) in parameters {
+            if name != SCRIPT_PARAMETER {
+                let value = rhai::serde::to_dynamic(value).map_err(script_error)?;
+                self.params.insert(name.as_str().into(), value);
+            }
+        }
+        Ok(())
+    }
+
+    /// Run the script for one candle
+    fn run(&mut self, candle: Map) -> Result<()> {
+        let Some(ast) = &self.ast else {
+            return Ok(());
+        };
+        let mut scope = Scope::new();
+        scope.push_constant("candle", candle);
+        scope.push_constant("params", self.params.clone());
+        scope.push("state", std::mem::take(&mut self.state));
+
+        let result = self.engine.run_ast_with_scope(&mut scope, ast);
+        self.state = scope
+            .remove::<Dynamic>("state")
+            .unwrap_or_else(|| Dynamic::from_map(Map::new()));
+        result.map_err(script_error)
+    }
+}
+
+impl Default for ScriptStrategy {
+    fn default() -> Self {
+        Self::new(ScriptLimits::default())
+    }
+}
+
+#[async_trait]
+impl Strategy for ScriptStrategy {
+    async fn initialize(&mut self, config: StrategyConfig) -> Result<()> {
+        if !config.parameters.contains_key(SCRIPT_PARAMETER) {
+            return Err(Error::InvalidConfig(format!(
+                "scripted strategy '{}' has no '{}' parameter",
+                config.name, SCRIPT_PARAMETER
+            )));
+        }
+        self.load(&config.parameters)
+    }
+
+    async fn on_market_data(&mut self, event: MarketDataEvent) -> Result<()> {
+        // A signal only stands until the next event
+        self.context.lock().emitted = None;
+
+        let MarketDataEvent::Candle {
+            symbol,
+            interval,
+            open,
+            high,
+            low,
+            close,
+            volume,
+            timestamp,
+        } = event
+        else {
+            return Ok(());
+        };
+
+        {
+            let mut context = self.context.lock();
+            let key = (symbol.clone(), interval);
+            let bars = context.series.entry(key.clone()).or_default();
+            bars.push_back(Bar {
+                high: to_float(high),
+                low: to_float(low),
+                close: to_float(close),
+            });
+            while bars.len() > self.limits.history.max(1) {
+                bars.pop_front();
+            }
+            context.current = Some(key);
+        }
+
+        let mut candle = Map::new();
+        candle.insert("symbol".into(), symbol.as_str().into());
+        candle.insert("interval".into(), interval.as_str().into());
+        candle.insert("open".into(), to_float(open).into());
+        candle.insert("high".into(), to_float(high).into());
+        candle.insert("low".into(), to_float(low).into());
+        candle.insert("close".into(), to_float(close).into());
+        candle.insert("volume".into(), to_float(volume).into());
+        candle.insert("timestamp".into(), timestamp.timestamp_millis().into());
+        self.run(candle)
+    }
+
+    async fn generate_signal(&self) -> Result<Signal> {
+        Ok(self
+            .context
+            .lock()
+            .emitted
+            .clone()
+            .unwrap_or_else(Signal::hold))
+    }
+
+    async fn on_order_fill(&mut self, _order: &Order) -> Result<()> {
+        Ok(())
+    }
+
+    async fn on_order_reject(&mut self, _order: &Order, _reason: &str) -> Result<()> {
+        Ok(())
+    }
+
+    fn get_metrics(&self) -> PerformanceMetrics {
+        PerformanceMetrics::default()
+    }
+
+    fn serialize_state(&self) -> Result<JsonValue> {
+        let state: JsonValue = rhai::serde::from_dynamic(&self.state).map_err(script_error)?;
+        Ok(json!({ "state": state }))
+    }
+
+    fn deserialize_state(&mut self, state: JsonValue) -> Result<()> {
+        if let Some(state) = state.get("state") {
+            self.state = rhai::serde::to_dynamic(state).map_err(script_error)?;
+        }
+        Ok(())
+    }
+
+    /// Recompile a changed script in place; state and bar history are kept
+    async fn on_parameters_updated(
+        &mut self,
+        parameters: &HashMap<String, JsonValue>,
+    ) -> Result<()> {
+        self.load(parameters)
+    }
+
+    async fn shutdown(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::RiskLimits;
+    use chrono::{Duration, TimeZone, Utc};
+    use rust_decimal::Decimal;
+    use uuid::Uuid;
+
+    fn config(script: &str) -> StrategyConfig {
+        StrategyConfig {
+            strategy_id: Uuid::new_v4(),
+            name: "scripted".to_string(),
+            version: "1.0.0".to_string(),
+            symbols: vec!["BTC-USDT".to_string()],
+            parameters: HashMap::from([
+                (SCRIPT_PARAMETER.to_string(), json!(script)),
+                ("period".to_string(), json!(3)),
+            ]),
+            risk_limits: RiskLimits {
+                max_position_size: Decimal::ONE,
+                max_leverage: Decimal::ONE,
+                stop_loss_pct: Decimal::new(2, 2),
+                take_profit_pct: None,
+            },
+        }
+    }
+
+    fn candle(minute: i64, close: i64) -> MarketDataEvent {
+        MarketDataEvent::Candle {
+            symbol: Symbol::new("BTC-USDT").unwrap(),
+            interval: Interval::OneMinute,
+            open: Decimal::from(close),
+            high: Decimal::from(close + 1),
+            low: Decimal::from(close - 1),
+            close: Decimal::from(close),
+            volume: Decimal::ONE,
+            timestamp: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+                + Duration::minutes(minute),
+        }
+    }
+
+    const BREAKOUT: &str = r#"
+        if ready(params.period) && candle.close > sma(params.period) && state.side != "long" {
+            state.side = "long";
+            emit_signal("buy", 0.8);
+        }
+    "#;
+
+    #[tokio::test]
+    async fn test_script_emits_signals_from_indicators() {
+        let mut strategy = ScriptStrategy::default();
+        strategy.initialize(config(BREAKOUT)).await.unwrap();
+
+        for (minute, close) in [(0, 100), (1, 100)] {
+            strategy
+                .on_market_data(candle(minute, close))
+                .await
+                .unwrap();
+            assert_eq!(
+                strategy.generate_signal().await.unwrap().signal_type,
+                SignalType::Hold
+            );
+        }
+
+        strategy.on_market_data(candle(2, 106)).await.unwrap();
+        let signal = strategy.generate_signal().await.unwrap();
+        assert_eq!(signal.signal_type, SignalType::Buy);
+        assert!((signal.confidence - 0.8).abs() < 1e-9);
+        assert_eq!(signal.metadata["symbol"], json!("BTC-USDT"));
+
+        // State persists between runs, so the entry is not repeated
+        strategy.on_market_data(candle(3, 110)).await.unwrap();
+        assert_eq!(
+            strategy.generate_signal().await.unwrap().signal_type,
+            SignalType::Hold
+        );
+        assert_eq!(
+            strategy.serialize_state().unwrap(),
+            json!({ "state": { "side": "long" } })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sandbox_limits_and_bad_scripts() {
+        assert!(ScriptStrategy::check("let x = ;").is_err());
+        assert!(ScriptStrategy::check(r#"import "os" as os;"#).is_ok());
+
+        let mut strategy = ScriptStrategy::default();
+        strategy
+            .initialize(config(r#"import "os" as os;"#))
+            .await
+            .unwrap();
+        assert!(strategy.on_market_data(candle(0, 100)).await.is_err());
+
+        let mut strategy = ScriptStrategy::default();
+        strategy.initialize(config("loop {}")).await.unwrap();
+        assert!(matches!(
+            strategy.on_market_data(candle(0, 100)).await,
+            Err(Error::ScriptError(_))
+        ));
+
+        let mut strategy = ScriptStrategy::default();
+        strategy
+            .initialize(config(r#"emit_signal("moon");"#))
+            .await
+            .unwrap();
+        assert!(strategy.on_market_data(candle(0, 100)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_script_hot_swap_keeps_state() {
+        let mut strategy = ScriptStrategy::default();
+        strategy
+            .initialize(config("state.runs = (state.runs ?? 0) + 1;"))
+            .await
+            .unwrap();
+        strategy.on_market_data(candle(0, 100)).await.unwrap();
+
+        let update = HashMap::from([(
+            SCRIPT_PARAMETER.to_string(),
+            json!(r#"state.runs += 1; emit_signal("sell", 0.5);"#),
+        )]);
+        strategy.on_parameters_updated(&update).await.unwrap();
+        strategy.on_market_data(candle(1, 100)).await.unwrap();
+
+        assert_eq!(
+            strategy.generate_signal().await.unwrap().signal_type,
+            SignalType::Sell
+        );
+        assert_eq!(strategy.serialize_state().unwrap()["state"]["runs"], 2);
+    }
+
+    #[test]
+    fn test_indicators() {
+        let mut context = ScriptContext::default();
+        let key = (Symbol::new("BTC-USDT").unwrap(), Interval::OneMinute);
+        context.series.insert(
+            key.clone(),
+            [1.0, 2.0, 3.0, 4.0, 5.0]
+                .into_iter()
+                .map(|close| Bar {
+                    high: close + 1.0,
+                    low: close - 1.0,
+                    close,
+                })
+                .collect(),
+        );
+        context.current = Some(key);
+
+        assert_eq!(context.sma(2), 4.5);
+        assert!(context.sma(6).is_nan());
+        assert!(context.sma(0).is_nan());
+        assert_eq!(context.highest(3), 6.0);
+        assert_eq!(context.lowest(3), 2.0);
+        // Seeded at 2.0, then 3.0 and 4.0 with alpha 0.5
+        assert_eq!(context.ema(3), 4.0);
+        assert_eq!(context.rsi(3), 100.0);
+        assert!(context.rsi(5).is_nan());
+    }
+}
@@ -0,0 +1,228 @@
+//! Statistics for spread and pairs strategies
+//!
+//! Ordinary least squares hedge ratios, spread z-scores and a Dickey-Fuller
+//! stationarity check. Everything works on `f64` price series, aligned by
+//! index, and returns `None` rather than a meaningless number when a series
+//! is too short or flat.
+//!
+//! The stationarity check is a heuristic, not a full cointegration test: it
+//! runs the Dickey-Fuller regression without lag terms and compares the
+//! statistic with the 5% critical value for a regression with a constant.
+//! Used on the residual spread of a fitted pair it also ignores that the
+//! hedge ratio was estimated, which makes it lenient; treat a pass as "worth
+//! trading", not as proof.
+
+use serde::{Deserialize, Serialize};
+
+/// 5% critical value of the Dickey-Fuller statistic for a regression with a
+/// constant, large sample
+pub const DF_CRITICAL_5PCT: f64 = -2.86;
+
+/// Fit of `y = alpha + beta * x`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct OlsFit {
+    pub alpha: f64,
+    /// Hedge ratio: units of `x` per unit of `y`
+    pub beta: f64,
+    pub r_squared: f64,
+}
+
+impl OlsFit {
+    /// Residual of one observation: `y - alpha - beta * x`
+    pub fn residual(&self, y: f64, x: f64) -> f64 {
+        y - self.alpha - self.beta * x
+    }
+}
+
+/// Regress `y` on `x`; `None` for fewer than three points, mismatched
+/// lengths or a constant `x`
+pub fn ols(y: &[f64], x: &[f64]) -> Option<OlsFit> {
+    if y.len() != x.len() || y.len() < 3 {
+        return None;
+    }
+    let y_mean = mean(y);
+    let x_mean = mean(x);
+    let (mut sxy, mut sxx, mut syy) = (0.0, 0.0, 0.0);
+    for (yi, xi) in y.iter().zip(x) {
+        let (dy, dx) = (yi - y_mean, xi - x_mean);
+        sxy += dx * dy;
+        sxx += dx * dx;
+        syy += dy * dy;
+    }
+    if sxx <= f64::EPSILON {
+        return None;
+    }
+
+    let beta = sxy / sxx;
+    let r_squared = if syy <= f64::EPSILON {
+        1.0
+    } else {
+        (sxy * sxy) / (sxx * syy)
+    };
+    Some(OlsFit {
+        alpha: y_mean - beta * x_mean,
+        beta,
+        r_squared,
+    })
+}
+
+/// Hedge ratio over each trailing `window`, `None` until the window is full
+pub fn rolling_hedge_ratio(y: &[f64], x: &[f64], window: usize) -> Vec<Option<f64>> {
+    let len = y.len().min(x.len());
+    (0..len)
+        .map(|end| {
+            let start = (end + 1).checked_sub(window)?;
+            ols(&y[start..=end], &x[start..=end]).map(|fit| fit.beta)
+        })
+        .collect()
+}
+
+/// Residual spread of every observation under `fit`
+pub fn spread(y: &[f64], x: &[f64], fit: &OlsFit) -> Vec<f64> {
+    y.iter()
+        .zip(x)
+        .map(|(yi, xi)| fit.residual(*yi, *xi))
+        .collect()
+}
+
+/// Standard score of the last value against all of `values`
+pub fn zscore(values: &[f64]) -> Option<f64> {
+    let last = *values.last()?;
+    if values.len() < 2 {
+        return None;
+    }
+    let mean = mean(values);
+    let std_dev =
+        (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64).sqrt();
+    (std_dev > f64::EPSILON).then(|| (last - mean) / std_dev)
+}
+
+/// Z-score of each value against its trailing `window`
+pub fn rolling_zscore(values: &[f64], window: usize) -> Vec<Option<f64>> {
+    (0..values.len())
+        .map(|end| {
+            let start = (end + 1).checked_sub(window)?;
+            zscore(&values[start..=end])
+        })
+        .collect()
+}
+
+/// Outcome of [`stationarity`]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Stationarity {
+    /// Dickey-Fuller t-statistic; more negative is more mean-reverting
+    pub statistic: f64,
+    pub critical_value: f64,
+    pub stationary: bool,
+
+    /// Observations for a deviation to halve, when the series reverts
+    pub half_life: Option<f64>,
+}
+
+/// Dickey-Fuller check of whether `series` reverts to a mean
+///
+/// Regresses `series[t] - series[t-1]` on `series[t-1]` with a constant. The
+/// series is judged stationary when the t-statistic of the slope is below
+/// [`DF_CRITICAL_5PCT`]. `None` for fewer than 10 points or a flat series.
+pub fn stationarity(series: &[f64]) -> Option<Stationarity> {
+    if series.len() < 10 {
+        return None;
+    }
+    let lagged = &series[..series.len() - 1];
+    let diffs: Vec<f64> = series.windows(2).map(|w| w[1] - w[0]).collect();
+    let fit = ols(&diffs, lagged)?;
+
+    let n = diffs.len() as f64;
+    let lagged_mean = mean(lagged);
+    let sxx: f64 = lagged.iter().map(|v| (v - lagged_mean).powi(2)).sum();
+    let ssr: f64 = diffs
+        .iter()
+        .zip(lagged)
+        .map(|(d, l)| fit.residual(*d, *l).powi(2))
+        .sum();
+    let std_error = (ssr / (n - 2.0) / sxx).sqrt();
+    if std_error <= f64::EPSILON {
+        return None;
+    }
+
+    let gamma = fit.beta;
+    let statistic = gamma / std_error;
+    let half_life =
+        (gamma < 0.0 && gamma > -1.0).then(|| -std::f64::consts::LN_2 / (1.0 + gamma).ln());
+    Some(Stationarity {
+        statistic,
+        critical_value: DF_CRITICAL_5PCT,
+        stationary: statistic < DF_CRITICAL_5PCT,
+        half_life,
+    })
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic noise in [-0.5, 0.5)
+    fn noise(len: usize, seed: u64) -> Vec<f64> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (state >> 11) as f64 / (1u64 << 53) as f64 - 0.5
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ols_recovers_hedge_ratio() {
+        let x: Vec<f64> = (0..50).map(|i| 100.0 + i as f64).collect();
+        let y: Vec<f64> = x.iter().map(|x| 5.0 + 2.0 * x).collect();
+        let fit = ols(&y, &x).unwrap();
+        assert!((fit.beta - 2.0).abs() < 1e-9);
+        assert!((fit.alpha - 5.0).abs() < 1e-6);
+        assert!((fit.r_squared - 1.0).abs() < 1e-9);
+        assert!(spread(&y, &x, &fit).iter().all(|s| s.abs() < 1e-6));
+
+        let betas = rolling_hedge_ratio(&y, &x, 10);
+        assert!(betas[..9].iter().all(Option::is_none));
+        assert!((betas[49].unwrap() - 2.0).abs() < 1e-9);
+
+        assert!(ols(&y, &[1.0; 50]).is_none());
+        assert!(ols(&y[..2], &x[..2]).is_none());
+    }
+
+    #[test]
+    fn test_zscore_of_last_value() {
+        assert_eq!(zscore(&[1.0, 1.0, 1.0]), None);
+        let z = zscore(&[1.0, 2.0, 3.0, 4.0, 10.0]).unwrap();
+        assert!(z > 1.5 && z < 2.0);
+        assert_eq!(rolling_zscore(&[1.0, 2.0, 3.0], 2)[0], None);
+    }
+
+    #[test]
+    fn test_stationarity_tells_reverting_from_random_walk() {
+        let shocks = noise(500, 7);
+
+        // Mean reverting: each step closes half the gap to zero
+        let mut reverting = vec![0.0];
+        for shock in &shocks {
+            reverting.push(reverting.last().unwrap() * 0.5 + shock);
+        }
+        let check = stationarity(&reverting).unwrap();
+        assert!(check.stationary, "statistic {}", check.statistic);
+        assert!((check.half_life.unwrap() - 1.0).abs() < 0.3);
+
+        let mut walk = vec![0.0];
+        for shock in &shocks {
+            walk.push(walk.last().unwrap() + shock);
+        }
+        assert!(!stationarity(&walk).unwrap().stationary);
+
+        assert!(stationarity(&[1.0; 5]).is_none());
+    }
+}
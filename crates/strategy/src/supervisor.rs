@@ -0,0 +1,794 @@
+//! Panic isolation and restarts for running strategies
+//!
+//! A [`StrategySupervisor`] owns one strategy instance and feeds it market
+//! data and order updates on its own task. Every call into the strategy runs
+//! under `catch_unwind`, so a panicking strategy takes down neither the task
+//! feeding other strategies nor the supervisor itself. When a strategy panics
+//! or fails to initialize, the supervisor:
+//!
+//! 1. records the failure, with the backtrace for a panic
+//! 2. cancels the strategy's open orders through its [`OrderCanceller`],
+//!    unless [`OrderCleanup::LeaveOpen`] is set
+//! 3. publishes [`SupervisorEvent::Failed`], upon which the owner moves the
+//!    strategy to its error state
+//! 4. waits out the [`RestartPolicy`] backoff, builds a fresh instance and
+//!    publishes [`SupervisorEvent::Restarted`], or gives up once the restart
+//!    cap is reached
+//!
+//! The panicked instance is dropped rather than reused, since its state may
+//! be half-updated, and the input that triggered the panic is not delivered
+//! again. Inputs arriving during the backoff queue up for the new instance.
+//!
+//! The running instance is reachable through the supervisor's
+//! [`LiveStrategy`] handle, e.g. to retune it or snapshot its state. Live
+//! parameter updates are kept for the instances built by later restarts.
+
+use crate::custom_metrics::MetricsRegistry;
+use crate::dca::{DCA_STRATEGY_TYPE, DcaStrategy};
+use crate::error::Result;
+use crate::script::{SCRIPT_STRATEGY_TYPE, ScriptStrategy};
+use crate::signal::{Signal, SignalType};
+use crate::traits::{MarketDataEvent, Strategy, StrategyConfig};
+use crate::tuning::{ParameterUpdate, apply_live_update};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use ea_okx_core::models::Order;
+use futures::FutureExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, Mutex, Once};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+/// How a failed strategy is restarted
+#[derive(Debug, Clone, PartialEq)]
+pub struct RestartPolicy {
+    /// Restarts allowed before the supervisor gives up
+    pub max_restarts: u32,
+
+    /// Delay before the first restart
+    pub initial_backoff: Duration,
+
+    /// Each further restart waits this many times longer
+    pub multiplier: f64,
+
+    pub max_backoff: Duration,
+
+    /// A strategy running this long without failing has its restart count
+    /// reset
+    pub reset_after: Duration,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            max_restarts: 3,
+            initial_backoff: Duration::from_secs(1),
+            multiplier: 2.0,
+            max_backoff: Duration::from_secs(60),
+            reset_after: Duration::from_secs(600),
+        }
+    }
+}
+
+impl RestartPolicy {
+    /// Never restart
+    pub fn never() -> Self {
+        Self {
+            max_restarts: 0,
+            ..Default::default()
+        }
+    }
+
+    /// Delay before restart number `restart`, counting from 1
+    pub fn backoff(&self, restart: u32) -> Duration {
+        let factor = self
+            .multiplier
+            .max(1.0)
+            .powi(restart.saturating_sub(1) as i32);
+        self.initial_backoff
+            .mul_f64(factor.min(u32::MAX as f64))
+            .min(self.max_backoff)
+    }
+}
+
+/// What happens to a failed strategy's open orders
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderCleanup {
+    /// Cancel them, so nothing trades for a strategy that is not running
+    #[default]
+    CancelOpenOrders,
+
+    /// Leave them working, e.g. for resting exits the strategy placed
+    LeaveOpen,
+}
+
+/// Cancels the open orders of a strategy
+#[async_trait]
+pub trait OrderCanceller: Send + Sync {
+    /// Returns how many orders were cancelled
+    async fn cancel_strategy_orders(&self, strategy_id: Uuid) -> Result<usize>;
+}
+
+/// A strategy panic or initialization failure
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StrategyFailure {
+    pub strategy_id: Uuid,
+    pub at: DateTime<Utc>,
+    pub message: String,
+
+    /// Where the strategy panicked; `None` for initialization errors
+    pub backtrace: Option<String>,
+}
+
+impl StrategyFailure {
+    pub fn is_panic(&self) -> bool {
+        self.backtrace.is_some()
+    }
+}
+
+/// Supervisor notifications
+#[derive(Debug, Clone)]
+pub enum SupervisorEvent {
+    /// The strategy failed; it is not running until restarted
+    Failed {
+        failure: StrategyFailure,
+        /// Open orders cancelled in response
+        orders_cancelled: usize,
+        /// Restarts already made since the count was last reset
+        restarts: u32,
+    },
+    /// A fresh instance is running again
+    Restarted { strategy_id: Uuid, restarts: u32 },
+    /// The restart cap was reached; the strategy stays down
+    GaveUp { strategy_id: Uuid, restarts: u32 },
+}
+
+/// Input delivered to a supervised strategy
+#[derive(Debug, Clone)]
+pub enum StrategyInput {
+    Market(MarketDataEvent),
+    OrderFill(Order),
+    OrderReject { order: Order, reason: String },
+}
+
+/// Why a supervisor stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SupervisorExit {
+    /// The input channel closed and the strategy was shut down
+    InputClosed,
+    /// The restart cap was reached
+    GaveUp { restarts: u32 },
+}
+
+/// Builds a fresh instance of the supervised strategy
+pub type StrategyFactory = Box<dyn Fn() -> Box<dyn Strategy> + Send + Sync>;
+
+/// Factory for the strategy shipped with the framework under
+/// `strategy_type`, if there is one
+pub fn builtin_factory(strategy_type: &str) -> Option<StrategyFactory> {
+    match strategy_type {
+        DCA_STRATEGY_TYPE => Some(Box::new(|| Box::new(DcaStrategy::new()))),
+        SCRIPT_STRATEGY_TYPE => Some(Box::new(|| Box::new(ScriptStrategy::default()))),
+        _ => None,
+    }
+}
+
+/// Shared access to the instance a supervisor is running
+///
+/// Empty while the strategy is starting, restarting or stopped.
+#[derive(Clone)]
+pub struct LiveStrategy {
+    instance: Arc<tokio::sync::Mutex<Option<Box<dyn Strategy>>>>,
+
+    /// Parameters the next instance is initialized with
+    parameters: Arc<Mutex<HashMap<String, JsonValue>>>,
+}
+
+impl LiveStrategy {
+    fn new(parameters: HashMap<String, JsonValue>) -> Self {
+        Self {
+            instance: Arc::new(tokio::sync::Mutex::new(None)),
+            parameters: Arc::new(Mutex::new(parameters)),
+        }
+    }
+
+    pub async fn is_running(&self) -> bool {
+        self.instance.lock().await.is_some()
+    }
+
+    /// Push a hot-tunable update into the running instance; see
+    /// [`apply_live_update`]
+    ///
+    /// Returns `None` without applying anything when no instance is running.
+    /// Applied changes are also used by instances started later.
+    pub async fn apply_update(
+        &self,
+        schema: &JsonValue,
+        current: &JsonValue,
+        proposed: &JsonValue,
+    ) -> Result<Option<ParameterUpdate>> {
+        let mut instance = self.instance.lock().await;
+        let Some(strategy) = instance.as_mut() else {
+            return Ok(None);
+        };
+        let update = apply_live_update(strategy.as_mut(), schema, current, proposed).await?;
+        if let Ok(mut parameters) = self.parameters.lock() {
+            for name in &update.changed {
+                match proposed.get(name) {
+                    Some(value) => parameters.insert(name.clone(), value.clone()),
+                    None => parameters.remove(name),
+                };
+            }
+        }
+        Ok(Some(update))
+    }
+
+    /// State of the running instance, if there is one
+    pub async fn serialize_state(&self) -> Result<Option<JsonValue>> {
+        match self.instance.lock().await.as_ref() {
+            Some(strategy) => strategy.serialize_state().map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Restore the running instance's state; returns false when none runs
+    pub async fn deserialize_state(&self, state: JsonValue) -> Result<bool> {
+        match self.instance.lock().await.as_mut() {
+            Some(strategy) => strategy.deserialize_state(state).map(|_| true),
+            None => Ok(false),
+        }
+    }
+}
+
+/// Runs one strategy with panic isolation and restarts
+pub struct StrategySupervisor {
+    config: StrategyConfig,
+    factory: StrategyFactory,
+    live: LiveStrategy,
+    policy: RestartPolicy,
+    cleanup: OrderCleanup,
+    canceller: Option<Arc<dyn OrderCanceller>>,
+    metrics: Option<MetricsRegistry>,
+
+    /// Event channel
+    event_tx: mpsc::UnboundedSender<SupervisorEvent>,
+    event_rx: Mutex<Option<mpsc::UnboundedReceiver<SupervisorEvent>>>,
+}
+
+impl StrategySupervisor {
+    pub fn new(config: StrategyConfig, factory: StrategyFactory) -> Self {
+        let (event_tx, event_rx) = mpsc::unbounded_channel();
+        Self {
+            live: LiveStrategy::new(config.parameters.clone()),
+            config,
+            factory,
+            policy: RestartPolicy::default(),
+            cleanup: OrderCleanup::default(),
+            canceller: None,
+            metrics: None,
+            event_tx,
+            event_rx: Mutex::new(Some(event_rx)),
+        }
+    }
+
+    pub fn with_restart_policy(mut self, policy: RestartPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Cancel open orders through `canceller` on failure, per `cleanup`
+    pub fn with_order_cleanup(
+        mut self,
+        cleanup: OrderCleanup,
+        canceller: Arc<dyn OrderCanceller>,
+    ) -> Self {
+        self.cleanup = cleanup;
+        self.canceller = Some(canceller);
+        self
+    }
+
+    /// Attach each instance's custom metrics to `registry`
+    ///
+    /// Restarted instances get the same handles back, so counters carry on
+    /// from where the failed instance left them.
+    pub fn with_metrics(mut self, registry: MetricsRegistry) -> Self {
+        self.metrics = Some(registry);
+        self
+    }
+
+    pub fn strategy_id(&self) -> Uuid {
+        self.config.strategy_id
+    }
+
+    /// Handle on the running instance
+    pub fn live(&self) -> LiveStrategy {
+        self.live.clone()
+    }
+
+    /// Take the event receiver; only the first call gets it
+    pub fn subscribe_events(&self) -> Option<mpsc::UnboundedReceiver<SupervisorEvent>> {
+        self.event_rx.lock().ok()?.take()
+    }
+
+    /// Feed `inputs` to the strategy until the channel closes or the restart
+    /// cap is reached, sending every non-hold signal to `signals`
+    pub async fn run(
+        self,
+        mut inputs: mpsc::Receiver<StrategyInput>,
+        signals: mpsc::UnboundedSender<Signal>,
+    ) -> SupervisorExit {
+        install_backtrace_hook();
+        let strategy_id = self.config.strategy_id;
+        let mut restarts = 0u32;
+
+        loop {
+            let started = Instant::now();
+            let failure = match self.start().await {
+                Ok(strategy) => {
+                    *self.live.instance.lock().await = Some(strategy);
+                    let failure = self.drive(&mut inputs, &signals).await;
+                    // A panicked instance is dropped; a finished one shut down
+                    let strategy = self.live.instance.lock().await.take();
+                    match (failure, strategy) {
+                        (Some(failure), _) => failure,
+                        (None, strategy) => {
+                            if let Some(mut strategy) = strategy
+                                && let Err(e) = strategy.shutdown().await
+                            {
+                                warn!("Strategy {} shutdown failed: {}", strategy_id, e);
+                            }
+                            return SupervisorExit::InputClosed;
+                        }
+                    }
+                }
+                Err(failure) => failure,
+            };
+
+            if started.elapsed() >= self.policy.reset_after {
+                restarts = 0;
+            }
+            error!(
+                "Strategy {} failed: {}{}",
+                strategy_id,
+                failure.message,
+                failure
+                    .backtrace
+                    .as_deref()
+                    .map(|bt| format!("\n{}", bt))
+                    .unwrap_or_default()
+            );
+            let orders_cancelled = self.cancel_orders().await;
+            let _ = self.event_tx.send(SupervisorEvent::Failed {
+                failure,
+                orders_cancelled,
+                restarts,
+            });
+
+            if restarts >= self.policy.max_restarts {
+                warn!(
+                    "Strategy {} not restarted after {} restarts",
+                    strategy_id, restarts
+                );
+                let _ = self.event_tx.send(SupervisorEvent::GaveUp {
+                    strategy_id,
+                    restarts,
+                });
+                return SupervisorExit::GaveUp { restarts };
+            }
+
+            restarts += 1;
+            let delay = self.policy.backoff(restarts);
+            info!(
+                "Restarting strategy {} in {:?} (restart {} of {})",
+                strategy_id, delay, restarts, self.policy.max_restarts
+            );
+            tokio::time::sleep(delay).await;
+            let _ = self.event_tx.send(SupervisorEvent::Restarted {
+                strategy_id,
+                restarts,
+            });
+        }
+    }
+
+    /// Run [`run`](Self::run) on its own task
+    pub fn spawn(
+        self,
+        inputs: mpsc::Receiver<StrategyInput>,
+        signals: mpsc::UnboundedSender<Signal>,
+    ) -> JoinHandle<SupervisorExit> {
+        tokio::spawn(self.run(inputs, signals))
+    }
+
+    /// A fresh, initialized instance
+    async fn start(&self) -> std::result::Result<Box<dyn Strategy>, StrategyFailure> {
+        let mut config = self.config.clone();
+        if let Ok(parameters) = self.live.parameters.lock() {
+            config.parameters = parameters.clone();
+        }
+        let started = AssertUnwindSafe(async {
+            let mut strategy = (self.factory)();
+            if let Some(registry) = &self.metrics {
+                strategy.attach_metrics(registry.for_strategy(config.strategy_id));
+            }
+            strategy.initialize(config).await.map(|_| strategy)
+        })
+        .catch_unwind()
+        .await;
+
+        match started {
+            Ok(Ok(strategy)) => Ok(strategy),
+            Ok(Err(e)) => Err(self.failure(format!("initialization failed: {}", e), None)),
+            Err(payload) => Err(self.panic_failure(payload)),
+        }
+    }
+
+    /// Deliver inputs until the channel closes (`None`) or the strategy panics
+    async fn drive(
+        &self,
+        inputs: &mut mpsc::Receiver<StrategyInput>,
+        signals: &mpsc::UnboundedSender<Signal>,
+    ) -> Option<StrategyFailure> {
+        let strategy_id = self.config.strategy_id;
+        while let Some(input) = inputs.recv().await {
+            let mut instance = self.live.instance.lock().await;
+            let strategy = instance.as_mut()?;
+            let handled = AssertUnwindSafe(async {
+                match input {
+                    StrategyInput::Market(event) => {
+                        strategy.on_market_data(event).await?;
+                        strategy.generate_signals().await
+                    }
+                    StrategyInput::OrderFill(order) => {
+                        strategy.on_order_fill(&order).await.map(|_| Vec::new())
+                    }
+                    StrategyInput::OrderReject { order, reason } => strategy
+                        .on_order_reject(&order, &reason)
+                        .await
+                        .map(|_| Vec::new()),
+                }
+            })
+            .catch_unwind()
+            .await;
+
+            match handled {
+                Ok(Ok(emitted)) => {
+                    for signal in emitted {
+                        if signal.signal_type != SignalType::Hold {
+                            let _ = signals.send(signal);
+                        }
+                    }
+                }
+                // Errors are the strategy's own to report; it keeps running
+                Ok(Err(e)) => warn!("Strategy {} error: {}", strategy_id, e),
+                Err(payload) => return Some(self.panic_failure(payload)),
+            }
+        }
+        None
+    }
+
+    async fn cancel_orders(&self) -> usize {
+        let strategy_id = self.config.strategy_id;
+        match (&self.canceller, self.cleanup) {
+            (Some(canceller), OrderCleanup::CancelOpenOrders) => {
+                match canceller.cancel_strategy_orders(strategy_id).await {
+                    Ok(cancelled) => cancelled,
+                    Err(e) => {
+                        error!(
+                            "Failed to cancel orders of failed strategy {}: {}",
+                            strategy_id, e
+                        );
+                        0
+                    }
+                }
+            }
+            _ => 0,
+        }
+    }
+
+    fn failure(&self, message: String, backtrace: Option<String>) -> StrategyFailure {
+        StrategyFailure {
+            strategy_id: self.config.strategy_id,
+            at: Utc::now(),
+            message,
+            backtrace,
+        }
+    }
+
+    fn panic_failure(&self, payload: Box<dyn Any + Send>) -> StrategyFailure {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "non-string panic payload".to_string());
+        let backtrace = LAST_BACKTRACE
+            .with(|last| last.borrow_mut().take())
+            .unwrap_or_else(|| "backtrace unavailable".to_string());
+        self.failure(format!("panicked: {}", message), Some(backtrace))
+    }
+}
+
+thread_local! {
+    /// Backtrace of the latest panic on this thread
+    static LAST_BACKTRACE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Record each panic's backtrace for the supervisor that catches it
+///
+/// `catch_unwind` only hands back the panic payload. The hook runs on the
+/// panicking thread before unwinding, which is the thread polling the
+/// supervisor, so the backtrace is picked up from a thread-local. The
+/// previous hook still runs.
+fn install_backtrace_hook() {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let backtrace = Backtrace::force_capture().to_string();
+            LAST_BACKTRACE.with(|last| *last.borrow_mut() = Some(backtrace));
+            previous(info);
+        }));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::PerformanceMetrics;
+    use crate::testkit::{ScenarioBuilder, strategy_config};
+    use ea_okx_core::Symbol;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Buys on every bar, panicking on the bar numbered `panic_on`
+    struct Fragile {
+        bars: usize,
+        panic_on: Option<usize>,
+        starts: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Strategy for Fragile {
+        async fn initialize(&mut self, _config: StrategyConfig) -> Result<()> {
+            self.starts.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+
+        async fn on_market_data(&mut self, _event: MarketDataEvent) -> Result<()> {
+            self.bars += 1;
+            if Some(self.bars) == self.panic_on {
+                panic!("bar {} is too much", self.bars);
+            }
+            Ok(())
+        }
+
+        async fn generate_signal(&self) -> Result<Signal> {
+            Ok(Signal::buy(1.0))
+        }
+
+        async fn on_order_fill(&mut self, _order: &Order) -> Result<()> {
+            Ok(())
+        }
+
+        async fn on_order_reject(&mut self, _order: &Order, _reason: &str) -> Result<()> {
+            Ok(())
+        }
+
+        fn get_metrics(&self) -> PerformanceMetrics {
+            PerformanceMetrics::default()
+        }
+
+        fn serialize_state(&self) -> Result<serde_json::Value> {
+            Ok(serde_json::json!({}))
+        }
+
+        fn deserialize_state(&mut self, _state: serde_json::Value) -> Result<()> {
+            Ok(())
+        }
+
+        async fn shutdown(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct CountingCanceller {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl OrderCanceller for CountingCanceller {
+        async fn cancel_strategy_orders(&self, _strategy_id: Uuid) -> Result<usize> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(2)
+        }
+    }
+
+    fn policy(max_restarts: u32) -> RestartPolicy {
+        RestartPolicy {
+            max_restarts,
+            initial_backoff: Duration::from_millis(1),
+            ..Default::default()
+        }
+    }
+
+    fn supervisor(panic_on: Option<usize>, starts: Arc<AtomicUsize>) -> StrategySupervisor {
+        StrategySupervisor::new(
+            strategy_config("BTC-USDT", serde_json::json!({})),
+            Box::new(move || {
+                Box::new(Fragile {
+                    bars: 0,
+                    panic_on,
+                    starts: starts.clone(),
+                })
+            }),
+        )
+    }
+
+    async fn feed(bars: usize) -> mpsc::Receiver<StrategyInput> {
+        let (tx, rx) = mpsc::channel(bars.max(1));
+        let events = ScenarioBuilder::new(Symbol::new("BTC-USDT").unwrap(), 100.0)
+            .flat(bars)
+            .build();
+        for event in events {
+            tx.send(StrategyInput::Market(event)).await.unwrap();
+        }
+        rx
+    }
+
+    #[test]
+    fn test_backoff_grows_to_its_cap() {
+        let policy = RestartPolicy {
+            initial_backoff: Duration::from_secs(1),
+            multiplier: 2.0,
+            max_backoff: Duration::from_secs(5),
+            ..Default::default()
+        };
+        assert_eq!(policy.backoff(1), Duration::from_secs(1));
+        assert_eq!(policy.backoff(2), Duration::from_secs(2));
+        assert_eq!(policy.backoff(3), Duration::from_secs(4));
+        assert_eq!(policy.backoff(4), Duration::from_secs(5));
+        assert_eq!(policy.backoff(60), Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_live_handle_retunes_the_running_instance() {
+        let current = serde_json::json!({
+            "schedule": { "every": "daily", "at": "00:00:00" },
+            "quote_amount": "100"
+        });
+        let mut proposed = current.clone();
+        proposed["quote_amount"] = serde_json::json!("50");
+        let mut schema = crate::bundle::parameter_schema(&current);
+        crate::tuning::set_hot_tunable(&mut schema, "quote_amount", true).unwrap();
+
+        let supervisor = StrategySupervisor::new(
+            strategy_config("BTC-USDT", current.clone()),
+            builtin_factory(DCA_STRATEGY_TYPE).unwrap(),
+        );
+        let live = supervisor.live();
+        // Nothing runs before the supervisor starts
+        assert!(
+            live.apply_update(&schema, &current, &proposed)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let (tx, rx) = mpsc::channel(1);
+        let (signal_tx, _signal_rx) = mpsc::unbounded_channel();
+        let handle = supervisor.spawn(rx, signal_tx);
+        while !live.is_running().await {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        let update = live
+            .apply_update(&schema, &current, &proposed)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(update.changed, vec!["quote_amount".to_string()]);
+        assert!(live.serialize_state().await.unwrap().is_some());
+
+        // Restarted instances start from the tuned parameters
+        assert_eq!(
+            live.parameters.lock().unwrap()["quote_amount"],
+            serde_json::json!("50")
+        );
+
+        drop(tx);
+        assert_eq!(handle.await.unwrap(), SupervisorExit::InputClosed);
+        assert!(!live.is_running().await);
+    }
+
+    #[test]
+    fn test_builtin_factories() {
+        assert!(builtin_factory(DCA_STRATEGY_TYPE).is_some());
+        assert!(builtin_factory(SCRIPT_STRATEGY_TYPE).is_some());
+        assert!(builtin_factory("ma_crossover").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_panic_cancels_orders_and_restarts_a_fresh_instance() {
+        let starts = Arc::new(AtomicUsize::new(0));
+        let canceller = Arc::new(CountingCanceller::default());
+        let supervisor = supervisor(Some(3), starts.clone())
+            .with_restart_policy(policy(3))
+            .with_order_cleanup(OrderCleanup::CancelOpenOrders, canceller.clone());
+        let mut events = supervisor.subscribe_events().unwrap();
+        let (signal_tx, mut signal_rx) = mpsc::unbounded_channel();
+
+        // Bar 3 panics; the new instance sees bars 4 and 5 as its 1st and 2nd
+        let exit = supervisor.run(feed(5).await, signal_tx).await;
+        assert_eq!(exit, SupervisorExit::InputClosed);
+        assert_eq!(starts.load(Ordering::SeqCst), 2);
+        assert_eq!(canceller.calls.load(Ordering::SeqCst), 1);
+
+        let mut signals = 0;
+        while signal_rx.try_recv().is_ok() {
+            signals += 1;
+        }
+        assert_eq!(signals, 4);
+
+        match events.try_recv().unwrap() {
+            SupervisorEvent::Failed {
+                failure,
+                orders_cancelled,
+                restarts,
+            } => {
+                assert!(failure.is_panic());
+                assert_eq!(failure.message, "panicked: bar 3 is too much");
+                assert!(!failure.backtrace.unwrap().is_empty());
+                assert_eq!(orders_cancelled, 2);
+                assert_eq!(restarts, 0);
+            }
+            other => panic!("expected a failure, got {:?}", other),
+        }
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            SupervisorEvent::Restarted { restarts: 1, .. }
+        ));
+        assert!(events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_at_the_restart_cap() {
+        let starts = Arc::new(AtomicUsize::new(0));
+        // Every instance panics on its first bar
+        let supervisor = supervisor(Some(1), starts.clone()).with_restart_policy(policy(2));
+        let mut events = supervisor.subscribe_events().unwrap();
+        let (signal_tx, _signal_rx) = mpsc::unbounded_channel();
+
+        let exit = supervisor.run(feed(10).await, signal_tx).await;
+        assert_eq!(exit, SupervisorExit::GaveUp { restarts: 2 });
+        assert_eq!(starts.load(Ordering::SeqCst), 3);
+
+        let mut failed = 0;
+        let mut gave_up = false;
+        while let Ok(event) = events.try_recv() {
+            match event {
+                SupervisorEvent::Failed {
+                    orders_cancelled, ..
+                } => {
+                    // No canceller configured
+                    assert_eq!(orders_cancelled, 0);
+                    failed += 1;
+                }
+                SupervisorEvent::GaveUp { restarts, .. } => {
+                    assert_eq!(restarts, 2);
+                    gave_up = true;
+                }
+                SupervisorEvent::Restarted { .. } => {}
+            }
+        }
+        assert_eq!(failed, 3);
+        assert!(gave_up);
+    }
+}
@@ -0,0 +1,700 @@
+//! Deterministic test harness for strategy authors
+//!
+//! [`ScenarioBuilder`] lays out synthetic candle sequences from named
+//! market shapes (trends, chop, gaps, flash crashes), and [`MockContext`]
+//! drives a [`Strategy`] through them, capturing every non-hold signal so a
+//! unit test can assert on when and what the strategy traded, without
+//! standing up the backtest engine.
+//!
+//! ```ignore
+//! let events = ScenarioBuilder::new(symbol, 100.0)
+//!     .chop(20, 0.5)
+//!     .trend(10, 1.0)
+//!     .flash_crash(15.0, 5)
+//!     .build();
+//!
+//! let mut ctx = MockContext::new(MyStrategy::default()).with_fills(FillMode::Fill);
+//! ctx.initialize(strategy_config("BTC-USDT", json!({ "period": 5 }))).await?;
+//! ctx.run(events).await?;
+//! ctx.assert_first_signal(SignalType::Buy);
+//! ```
+
+use crate::custom_metrics::MetricsRegistry;
+use crate::error::Result;
+use crate::signal::{Signal, SignalType};
+use crate::traits::{MarketDataEvent, RiskLimits, Strategy, StrategyConfig};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use ea_okx_core::Interval;
+use ea_okx_core::models::{Order, OrderSide, OrderType};
+use ea_okx_core::types::{Price, Quantity, Symbol};
+use rust_decimal::Decimal;
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Volume multiple on the crash bar of [`ScenarioBuilder::flash_crash`]
+const CRASH_VOLUME_MULTIPLE: f64 = 10.0;
+
+/// One synthetic bar, in floats until it is emitted
+#[derive(Debug, Clone, Copy)]
+struct Bar {
+    /// Offset from the scenario start, in bar steps
+    slot: i64,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    volume: f64,
+}
+
+/// Builder for a deterministic candle sequence
+///
+/// Segments are appended in call order and each one starts where the last
+/// one closed. Price moves are given in percent. Nothing is random, so the
+/// same calls always produce the same candles.
+#[derive(Debug, Clone)]
+pub struct ScenarioBuilder {
+    symbol: Symbol,
+    interval: Interval,
+    step: Duration,
+    start: DateTime<Utc>,
+    volume: f64,
+    price: f64,
+    slot: i64,
+    bars: Vec<Bar>,
+}
+
+impl ScenarioBuilder {
+    /// One-minute bars from 2024-01-01 00:00 UTC, starting at `start_price`
+    pub fn new(symbol: Symbol, start_price: f64) -> Self {
+        Self {
+            symbol,
+            interval: Interval::OneMinute,
+            step: Duration::minutes(1),
+            start: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+            volume: 1.0,
+            price: start_price,
+            slot: 0,
+            bars: Vec::new(),
+        }
+    }
+
+    /// Bar interval and the time between bars
+    pub fn with_interval(mut self, interval: Interval, step: Duration) -> Self {
+        self.interval = interval;
+        self.step = step;
+        self
+    }
+
+    /// Timestamp of the first bar
+    pub fn with_start(mut self, start: DateTime<Utc>) -> Self {
+        self.start = start;
+        self
+    }
+
+    /// Base volume per bar
+    pub fn with_volume(mut self, volume: f64) -> Self {
+        self.volume = volume;
+        self
+    }
+
+    /// `bars` bars that open, close and range at the current price
+    pub fn flat(self, bars: usize) -> Self {
+        self.trend(bars, 0.0)
+    }
+
+    /// `bars` bars each moving `pct_per_bar` percent from the last close
+    pub fn trend(mut self, bars: usize, pct_per_bar: f64) -> Self {
+        for _ in 0..bars {
+            let close = self.price * (1.0 + pct_per_bar / 100.0);
+            self.push(self.price, close, self.volume);
+        }
+        self
+    }
+
+    /// `bars` bars alternating `amplitude_pct` percent above and below the
+    /// current price, ending back at it when `bars` is even
+    pub fn chop(mut self, bars: usize, amplitude_pct: f64) -> Self {
+        let base = self.price;
+        for bar in 0..bars {
+            let close = if bar == bars - 1 && bar % 2 == 1 {
+                base
+            } else if bar % 2 == 0 {
+                base * (1.0 + amplitude_pct / 100.0)
+            } else {
+                base * (1.0 - amplitude_pct / 100.0)
+            };
+            self.push(self.price, close, self.volume);
+        }
+        self
+    }
+
+    /// Move the price `pct` percent between bars, so the next bar opens
+    /// away from the last close
+    pub fn gap(mut self, pct: f64) -> Self {
+        self.price *= 1.0 + pct / 100.0;
+        self
+    }
+
+    /// Leave `bars` slots without a candle, as after a feed outage
+    pub fn missing(mut self, bars: usize) -> Self {
+        self.slot += bars as i64;
+        self
+    }
+
+    /// One bar dropping `drop_pct` percent on heavy volume, then a straight
+    /// line back to the pre-crash price over `recovery_bars` bars
+    pub fn flash_crash(mut self, drop_pct: f64, recovery_bars: usize) -> Self {
+        let before = self.price;
+        let bottom = before * (1.0 - drop_pct / 100.0);
+        self.push(before, bottom, self.volume * CRASH_VOLUME_MULTIPLE);
+        for bar in 1..=recovery_bars {
+            let close = bottom + (before - bottom) * bar as f64 / recovery_bars as f64;
+            self.push(self.price, close, self.volume);
+        }
+        self
+    }
+
+    /// Number of bars laid out so far
+    pub fn len(&self) -> usize {
+        self.bars.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bars.is_empty()
+    }
+
+    /// Close of the last bar, or the start price before any bar
+    pub fn last_price(&self) -> f64 {
+        self.price
+    }
+
+    /// Candle events in time order
+    pub fn build(&self) -> Vec<MarketDataEvent> {
+        self.bars
+            .iter()
+            .map(|bar| MarketDataEvent::Candle {
+                symbol: self.symbol.clone(),
+                interval: self.interval,
+                open: to_decimal(bar.open),
+                high: to_decimal(bar.high),
+                low: to_decimal(bar.low),
+                close: to_decimal(bar.close),
+                volume: to_decimal(bar.volume),
+                timestamp: self.start + self.step * bar.slot as i32,
+            })
+            .collect()
+    }
+
+    fn push(&mut self, open: f64, close: f64, volume: f64) {
+        self.bars.push(Bar {
+            slot: self.slot,
+            open,
+            high: open.max(close),
+            low: open.min(close),
+            close,
+            volume,
+        });
+        self.price = close;
+        self.slot += 1;
+    }
+}
+
+fn to_decimal(value: f64) -> Decimal {
+    Decimal::try_from(value).unwrap_or_default().round_dp(8)
+}
+
+/// Strategy config for `symbol` with `parameters` given as a JSON object
+pub fn strategy_config(symbol: &str, parameters: JsonValue) -> StrategyConfig {
+    let parameters: HashMap<String, JsonValue> = match parameters {
+        JsonValue::Object(map) => map.into_iter().collect(),
+        _ => HashMap::new(),
+    };
+    StrategyConfig {
+        strategy_id: Uuid::new_v4(),
+        name: "under-test".to_string(),
+        version: "0.0.0".to_string(),
+        symbols: vec![symbol.to_string()],
+        parameters,
+        risk_limits: RiskLimits {
+            max_position_size: Decimal::ONE,
+            max_leverage: Decimal::ONE,
+            stop_loss_pct: Decimal::new(2, 2),
+            take_profit_pct: None,
+        },
+    }
+}
+
+/// A non-hold signal captured by [`MockContext`]
+#[derive(Debug, Clone)]
+pub struct EmittedSignal {
+    /// Position of the triggering event in the feed, from zero
+    pub index: usize,
+    pub timestamp: DateTime<Utc>,
+    /// Close of the triggering candle, if it was one
+    pub close: Option<Decimal>,
+    pub signal: Signal,
+}
+
+/// What [`MockContext`] does with an entry or exit signal
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum FillMode {
+    /// Only capture the signal
+    #[default]
+    Ignore,
+    /// Fill a market order at the candle close and report it to the strategy
+    Fill,
+    /// Reject the order with this reason and report it to the strategy
+    Reject(String),
+}
+
+/// Drives a strategy through market events and captures what it emits
+///
+/// After every event the strategy is asked for a signal; anything other than
+/// hold is recorded with the event index. With a [`FillMode`] other than
+/// `Ignore`, the context also plays the exchange: each recorded entry or
+/// exit becomes a market order that is filled or rejected straight away, so
+/// strategies that track their own position see the callbacks they expect.
+pub struct MockContext<S: Strategy> {
+    strategy: S,
+    fill_mode: FillMode,
+    strategy_id: Uuid,
+    events: usize,
+    signals: Vec<EmittedSignal>,
+    orders: Vec<Order>,
+    metrics: MetricsRegistry,
+}
+
+impl<S: Strategy> MockContext<S> {
+    pub fn new(strategy: S) -> Self {
+        Self {
+            strategy,
+            fill_mode: FillMode::default(),
+            strategy_id: Uuid::nil(),
+            events: 0,
+            signals: Vec::new(),
+            orders: Vec::new(),
+            metrics: MetricsRegistry::new(),
+        }
+    }
+
+    pub fn with_fills(mut self, fill_mode: FillMode) -> Self {
+        self.fill_mode = fill_mode;
+        self
+    }
+
+    pub async fn initialize(&mut self, config: StrategyConfig) -> Result<()> {
+        self.strategy_id = config.strategy_id;
+        self.strategy
+            .attach_metrics(self.metrics.for_strategy(config.strategy_id));
+        self.strategy.initialize(config).await
+    }
+
+    /// Feed one event, returning the signal it produced, if any
+    pub async fn feed(&mut self, event: MarketDataEvent) -> Result<Option<&EmittedSignal>> {
+        let index = self.events;
+        self.events += 1;
+        let (symbol, timestamp, close) = event_summary(&event);
+
+        self.strategy.on_market_data(event).await?;
+        let signal = self.strategy.generate_signal().await?;
+        if signal.signal_type == SignalType::Hold {
+            return Ok(None);
+        }
+
+        if let Some(side) = order_side(signal.signal_type)
+            && self.fill_mode != FillMode::Ignore
+        {
+            self.execute(&signal, symbol, side, close).await?;
+        }
+        self.signals.push(EmittedSignal {
+            index,
+            timestamp,
+            close,
+            signal,
+        });
+        Ok(self.signals.last())
+    }
+
+    /// Feed every event in order, returning all signals captured so far
+    pub async fn run(
+        &mut self,
+        events: impl IntoIterator<Item = MarketDataEvent>,
+    ) -> Result<&[EmittedSignal]> {
+        for event in events {
+            self.feed(event).await?;
+        }
+        Ok(&self.signals)
+    }
+
+    async fn execute(
+        &mut self,
+        signal: &Signal,
+        symbol: Symbol,
+        side: OrderSide,
+        close: Option<Decimal>,
+    ) -> Result<()> {
+        let quantity = signal
+            .suggested_quantity
+            .unwrap_or_else(|| Quantity::new(Decimal::ONE).unwrap());
+        let mut order = Order::new(
+            self.strategy_id,
+            symbol,
+            side,
+            OrderType::Market,
+            quantity,
+            None,
+        );
+        match &self.fill_mode {
+            FillMode::Ignore => return Ok(()),
+            FillMode::Fill => {
+                let price = close
+                    .or(signal.target_price.map(|price| price.as_decimal()))
+                    .and_then(|price| Price::new(price).ok())
+                    .unwrap_or_else(|| Price::new(Decimal::ONE).unwrap());
+                order.update_fill(quantity, price);
+                self.strategy.on_order_fill(&order).await?;
+            }
+            FillMode::Reject(reason) => {
+                self.strategy.on_order_reject(&order, reason).await?;
+            }
+        }
+        self.orders.push(order);
+        Ok(())
+    }
+
+    pub fn strategy(&self) -> &S {
+        &self.strategy
+    }
+
+    pub fn strategy_mut(&mut self) -> &mut S {
+        &mut self.strategy
+    }
+
+    pub fn into_inner(self) -> S {
+        self.strategy
+    }
+
+    /// Number of events fed so far
+    pub fn events(&self) -> usize {
+        self.events
+    }
+
+    /// Every non-hold signal, in order
+    pub fn signals(&self) -> &[EmittedSignal] {
+        &self.signals
+    }
+
+    /// Signals of one type, in order
+    pub fn signals_of(&self, signal_type: SignalType) -> Vec<&EmittedSignal> {
+        self.signals
+            .iter()
+            .filter(|emitted| emitted.signal.signal_type == signal_type)
+            .collect()
+    }
+
+    /// Orders filled or rejected under the [`FillMode`]
+    pub fn orders(&self) -> &[Order] {
+        &self.orders
+    }
+
+    /// Custom metrics the strategy reported
+    pub fn metrics(&self) -> &MetricsRegistry {
+        &self.metrics
+    }
+
+    /// Panic unless no signal was emitted
+    #[track_caller]
+    pub fn assert_no_signals(&self) {
+        assert!(
+            self.signals.is_empty(),
+            "expected no signals, got {}",
+            describe(&self.signals)
+        );
+    }
+
+    /// Panic unless the event at `index` produced a `signal_type` signal
+    #[track_caller]
+    pub fn assert_signal_at(&self, index: usize, signal_type: SignalType) -> &EmittedSignal {
+        match self.signals.iter().find(|emitted| emitted.index == index) {
+            Some(emitted) if emitted.signal.signal_type == signal_type => emitted,
+            Some(emitted) => panic!(
+                "expected {:?} at event {}, got {:?}",
+                signal_type, index, emitted.signal.signal_type
+            ),
+            None => panic!(
+                "expected {:?} at event {}, got none; signals were {}",
+                signal_type,
+                index,
+                describe(&self.signals)
+            ),
+        }
+    }
+
+    /// Panic unless the first signal emitted is a `signal_type` one
+    #[track_caller]
+    pub fn assert_first_signal(&self, signal_type: SignalType) -> &EmittedSignal {
+        match self.signals.first() {
+            Some(emitted) if emitted.signal.signal_type == signal_type => emitted,
+            _ => panic!(
+                "expected first signal {:?}, signals were {}",
+                signal_type,
+                describe(&self.signals)
+            ),
+        }
+    }
+
+    /// Panic unless exactly `count` `signal_type` signals were emitted
+    #[track_caller]
+    pub fn assert_signal_count(&self, signal_type: SignalType, count: usize) {
+        let actual = self.signals_of(signal_type).len();
+        assert_eq!(
+            actual,
+            count,
+            "expected {} {:?} signals, got {}; signals were {}",
+            count,
+            signal_type,
+            actual,
+            describe(&self.signals)
+        );
+    }
+
+    /// Panic if any signal came from an event in `start..end`
+    #[track_caller]
+    pub fn assert_quiet_between(&self, start: usize, end: usize) {
+        let noisy: Vec<_> = self
+            .signals
+            .iter()
+            .filter(|emitted| (start..end).contains(&emitted.index))
+            .cloned()
+            .collect();
+        assert!(
+            noisy.is_empty(),
+            "expected no signals in events {}..{}, got {}",
+            start,
+            end,
+            describe(&noisy)
+        );
+    }
+}
+
+fn event_summary(event: &MarketDataEvent) -> (Symbol, DateTime<Utc>, Option<Decimal>) {
+    match event {
+        MarketDataEvent::Candle {
+            symbol,
+            close,
+            timestamp,
+            ..
+        } => (symbol.clone(), *timestamp, Some(*close)),
+        MarketDataEvent::Ticker {
+            symbol,
+            price,
+            timestamp,
+            ..
+        }
+        | MarketDataEvent::Trade {
+            symbol,
+            price,
+            timestamp,
+            ..
+        } => (symbol.clone(), *timestamp, Some(*price)),
+        MarketDataEvent::FundingRate {
+            symbol, timestamp, ..
+        }
+        | MarketDataEvent::OpenInterest {
+            symbol, timestamp, ..
+        }
+        | MarketDataEvent::TakerVolume {
+            symbol, timestamp, ..
+        }
+        | MarketDataEvent::LongShortRatio {
+            symbol, timestamp, ..
+        }
+        | MarketDataEvent::Gap {
+            symbol, timestamp, ..
+        } => (symbol.clone(), *timestamp, None),
+    }
+}
+
+fn order_side(signal_type: SignalType) -> Option<OrderSide> {
+    match signal_type {
+        SignalType::Buy | SignalType::CloseShort => Some(OrderSide::Buy),
+        SignalType::Sell | SignalType::CloseLong => Some(OrderSide::Sell),
+        SignalType::Hold => None,
+    }
+}
+
+fn describe(signals: &[EmittedSignal]) -> String {
+    let entries: Vec<String> = signals
+        .iter()
+        .map(|emitted| format!("{:?}@{}", emitted.signal.signal_type, emitted.index))
+        .collect();
+    format!("[{}]", entries.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::PerformanceMetrics;
+    use crate::script::{SCRIPT_PARAMETER, ScriptStrategy};
+    use async_trait::async_trait;
+    use serde_json::json;
+
+    fn symbol() -> Symbol {
+        Symbol::new("BTC-USDT").unwrap()
+    }
+
+    fn closes(events: &[MarketDataEvent]) -> Vec<Decimal> {
+        events
+            .iter()
+            .filter_map(|event| event_summary(event).2)
+            .collect()
+    }
+
+    #[test]
+    fn test_scenario_shapes_are_deterministic() {
+        let builder = ScenarioBuilder::new(symbol(), 100.0)
+            .trend(2, 10.0)
+            .chop(4, 1.0)
+            .gap(-10.0)
+            .missing(3)
+            .flash_crash(50.0, 2);
+        let events = builder.build();
+        assert_eq!(builder.len(), 9);
+        assert_eq!(events.len(), 9);
+
+        assert_eq!(
+            closes(&events),
+            [
+                "110", "121", "122.21", "119.79", "122.21", "121", "54.45", "81.675", "108.9",
+            ]
+            .map(|close| close.parse::<Decimal>().unwrap())
+        );
+
+        // The gap shows in the open of the bar after it, and the missing
+        // slots in its timestamp
+        let MarketDataEvent::Candle {
+            open,
+            low,
+            volume,
+            timestamp,
+            ..
+        } = &events[6]
+        else {
+            panic!("expected a candle");
+        };
+        assert_eq!(*open, Decimal::new(1089, 1));
+        assert_eq!(*low, Decimal::new(5445, 2));
+        assert_eq!(*volume, Decimal::TEN);
+        assert_eq!(
+            *timestamp,
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 9, 0).unwrap()
+        );
+
+        assert_eq!(closes(&builder.build()), closes(&events));
+    }
+
+    const CROSSOVER: &str = r#"
+        if ready(params.period) && candle.close > sma(params.period) && state.side != "long" {
+            state.side = "long";
+            emit_signal("buy", 0.8);
+        } else if ready(params.period) && candle.close < sma(params.period) && state.side == "long" {
+            state.side = "flat";
+            emit_signal("close_long", 0.8);
+        }
+    "#;
+
+    #[tokio::test]
+    async fn test_mock_context_captures_signals() {
+        let events = ScenarioBuilder::new(symbol(), 100.0)
+            .flat(5)
+            .trend(5, 1.0)
+            .flash_crash(20.0, 3)
+            .build();
+
+        let mut ctx = MockContext::new(ScriptStrategy::default()).with_fills(FillMode::Fill);
+        ctx.initialize(strategy_config(
+            "BTC-USDT",
+            json!({ SCRIPT_PARAMETER: CROSSOVER, "period": 3 }),
+        ))
+        .await
+        .unwrap();
+        ctx.run(events).await.unwrap();
+
+        assert_eq!(ctx.events(), 14);
+        ctx.assert_quiet_between(0, 5);
+        let entry = ctx.assert_first_signal(SignalType::Buy);
+        assert_eq!(entry.index, 5);
+        assert_eq!(entry.close, Some(Decimal::new(101, 0)));
+        ctx.assert_signal_at(10, SignalType::CloseLong);
+        ctx.assert_signal_count(SignalType::Buy, 2);
+        assert_eq!(ctx.orders().len(), ctx.signals().len());
+        assert_eq!(ctx.orders()[1].side, OrderSide::Sell);
+    }
+
+    /// Counts fills and rejections it is told about
+    #[derive(Default)]
+    struct AlwaysBuy {
+        fills: usize,
+        rejects: Vec<String>,
+    }
+
+    #[async_trait]
+    impl Strategy for AlwaysBuy {
+        async fn initialize(&mut self, _config: StrategyConfig) -> Result<()> {
+            Ok(())
+        }
+
+        async fn on_market_data(&mut self, _event: MarketDataEvent) -> Result<()> {
+            Ok(())
+        }
+
+        async fn generate_signal(&self) -> Result<Signal> {
+            Ok(Signal::buy(1.0))
+        }
+
+        async fn on_order_fill(&mut self, _order: &Order) -> Result<()> {
+            self.fills += 1;
+            Ok(())
+        }
+
+        async fn on_order_reject(&mut self, _order: &Order, reason: &str) -> Result<()> {
+            self.rejects.push(reason.to_string());
+            Ok(())
+        }
+
+        fn get_metrics(&self) -> PerformanceMetrics {
+            PerformanceMetrics::default()
+        }
+
+        fn serialize_state(&self) -> Result<JsonValue> {
+            Ok(json!({}))
+        }
+
+        fn deserialize_state(&mut self, _state: JsonValue) -> Result<()> {
+            Ok(())
+        }
+
+        async fn shutdown(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fill_modes() {
+        let events = ScenarioBuilder::new(symbol(), 100.0).flat(2).build();
+
+        let mut ctx = MockContext::new(AlwaysBuy::default());
+        ctx.run(events.clone()).await.unwrap();
+        ctx.assert_signal_count(SignalType::Buy, 2);
+        assert!(ctx.orders().is_empty());
+        assert_eq!(ctx.strategy().fills, 0);
+
+        let mut ctx = MockContext::new(AlwaysBuy::default())
+            .with_fills(FillMode::Reject("insufficient margin".to_string()));
+        ctx.run(events).await.unwrap();
+        let strategy = ctx.into_inner();
+        assert_eq!(strategy.fills, 0);
+        assert_eq!(strategy.rejects, ["insufficient margin"; 2]);
+    }
+}
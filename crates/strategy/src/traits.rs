@@ -1,9 +1,11 @@
 //! Core strategy trait definitions
 
-use crate::error::Result;
+use crate::custom_metrics::StrategyMetrics;
+use crate::error::{Error, Result};
 use crate::metrics::PerformanceMetrics;
 use crate::signal::Signal;
 use async_trait::async_trait;
+use ea_okx_core::Interval;
 use ea_okx_core::models::Order;
 use ea_okx_core::types::Symbol;
 use serde::{Deserialize, Serialize};
@@ -21,6 +23,8 @@ pub enum MarketDataEvent {
     },
     Candle {
         symbol: Symbol,
+        /// Bar length; sub-minute bars are built from trades
+        interval: Interval,
         open: rust_decimal::Decimal,
         high: rust_decimal::Decimal,
         low: rust_decimal::Decimal,
@@ -35,6 +39,42 @@ pub enum MarketDataEvent {
         side: String,
         timestamp: chrono::DateTime<chrono::Utc>,
     },
+    /// Perpetual swap funding rate for the underlying `symbol`
+    FundingRate {
+        symbol: Symbol,
+        funding_rate: rust_decimal::Decimal,
+        timestamp: chrono::DateTime<chrono::Utc>,
+    },
+    /// Open interest and volume (USD) across all contracts on the base
+    /// currency of `symbol`
+    OpenInterest {
+        symbol: Symbol,
+        open_interest: rust_decimal::Decimal,
+        volume: rust_decimal::Decimal,
+        timestamp: chrono::DateTime<chrono::Utc>,
+    },
+    /// Taker buy and sell volume on the base currency of `symbol`
+    TakerVolume {
+        symbol: Symbol,
+        buy_volume: rust_decimal::Decimal,
+        sell_volume: rust_decimal::Decimal,
+        timestamp: chrono::DateTime<chrono::Utc>,
+    },
+    /// Ratio of accounts net long to net short on the base currency of `symbol`
+    LongShortRatio {
+        symbol: Symbol,
+        ratio: rust_decimal::Decimal,
+        timestamp: chrono::DateTime<chrono::Utc>,
+    },
+    /// `missing_bars` bars of `interval` are missing before the candle of
+    /// `symbol` opening at `timestamp`; sent just ahead of that candle so
+    /// time-based indicators can account for the elapsed time
+    Gap {
+        symbol: Symbol,
+        interval: Interval,
+        missing_bars: u32,
+        timestamp: chrono::DateTime<chrono::Utc>,
+    },
 }
 
 /// Strategy configuration
@@ -60,6 +100,13 @@ pub struct RiskLimits {
 /// Core strategy trait
 #[async_trait]
 pub trait Strategy: Send + Sync {
+    /// Receive the handle for reporting custom metrics
+    ///
+    /// Called before [`Strategy::initialize`] by runners that export
+    /// metrics; strategies keep the handle and register counters, gauges
+    /// and histograms on it. The default ignores it.
+    fn attach_metrics(&mut self, _metrics: StrategyMetrics) {}
+
     /// Initialize strategy with configuration
     async fn initialize(&mut self, config: StrategyConfig) -> Result<()>;
 
@@ -69,6 +116,15 @@ pub trait Strategy: Send + Sync {
     /// Generate trading signal
     async fn generate_signal(&self) -> Result<Signal>;
 
+    /// Generate every signal for the latest event
+    ///
+    /// Strategies that trade several symbols at once override this to move
+    /// all legs together, naming each leg's symbol in `metadata["symbol"]`.
+    /// Runners call this rather than [`Strategy::generate_signal`].
+    async fn generate_signals(&self) -> Result<Vec<Signal>> {
+        Ok(vec![self.generate_signal().await?])
+    }
+
     /// Handle order fill notification
     async fn on_order_fill(&mut self, order: &Order) -> Result<()>;
 
@@ -84,6 +140,19 @@ pub trait Strategy: Send + Sync {
     /// Deserialize and restore strategy state
     fn deserialize_state(&mut self, state: serde_json::Value) -> Result<()>;
 
+    /// Apply changed hot-tunable parameters while running
+    ///
+    /// Only the parameters that changed are passed. Strategies that cannot
+    /// retune in place keep the default, which refuses the update.
+    async fn on_parameters_updated(
+        &mut self,
+        _parameters: &HashMap<String, serde_json::Value>,
+    ) -> Result<()> {
+        Err(Error::InvalidConfig(
+            "strategy does not support live parameter updates".to_string(),
+        ))
+    }
+
     /// Clean shutdown
     async fn shutdown(&mut self) -> Result<()>;
 }
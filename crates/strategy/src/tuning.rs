@@ -0,0 +1,263 @@
+//! Live parameter tuning
+//!
+//! Properties of a strategy's parameter schema can be flagged hot-tunable
+//! with the `x-hot-tunable` keyword. Changes confined to those parameters are
+//! pushed into the running strategy through
+//! [`Strategy::on_parameters_updated`](crate::traits::Strategy::on_parameters_updated);
+//! any other change still needs the strategy to be stopped and restarted.
+
+use crate::bundle::validate_parameters;
+use crate::error::{Error, Result};
+use crate::traits::Strategy;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value as JsonValue, json};
+use std::collections::{BTreeSet, HashMap};
+
+/// Schema keyword marking a property as safe to change while running
+pub const HOT_TUNABLE: &str = "x-hot-tunable";
+
+/// Names of the hot-tunable parameters declared in `schema`
+pub fn hot_tunable_parameters(schema: &JsonValue) -> BTreeSet<String> {
+    schema
+        .get("properties")
+        .and_then(JsonValue::as_object)
+        .into_iter()
+        .flatten()
+        .filter(|(_, property)| property.get(HOT_TUNABLE) == Some(&JsonValue::Bool(true)))
+        .map(|(name, _)| name.clone())
+        .collect()
+}
+
+/// Flag or unflag a schema property as hot-tunable
+pub fn set_hot_tunable(schema: &mut JsonValue, parameter: &str, hot: bool) -> Result<()> {
+    let property = schema
+        .get_mut("properties")
+        .and_then(|p| p.get_mut(parameter))
+        .and_then(JsonValue::as_object_mut)
+        .ok_or_else(|| {
+            Error::InvalidConfig(format!("parameter '{}' is not in the schema", parameter))
+        })?;
+    if hot {
+        property.insert(HOT_TUNABLE.to_string(), json!(true));
+    } else {
+        property.remove(HOT_TUNABLE);
+    }
+    Ok(())
+}
+
+/// Which parameters a proposed update changes
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParameterUpdate {
+    /// Parameters whose value differs, added or removed
+    pub changed: Vec<String>,
+
+    /// Changed parameters that are not hot-tunable
+    pub restart_required: Vec<String>,
+}
+
+impl ParameterUpdate {
+    /// Compare `proposed` with `current` after validating it against `schema`
+    pub fn plan(schema: &JsonValue, current: &JsonValue, proposed: &JsonValue) -> Result<Self> {
+        validate_parameters(schema, proposed)?;
+
+        let empty = serde_json::Map::new();
+        let current = current.as_object().unwrap_or(&empty);
+        let proposed = proposed.as_object().unwrap_or(&empty);
+        let keys: BTreeSet<&String> = current.keys().chain(proposed.keys()).collect();
+        let changed: Vec<String> = keys
+            .into_iter()
+            .filter(|key| current.get(*key) != proposed.get(*key))
+            .cloned()
+            .collect();
+
+        let hot = hot_tunable_parameters(schema);
+        let restart_required = changed
+            .iter()
+            .filter(|name| !hot.contains(*name))
+            .cloned()
+            .collect();
+
+        Ok(Self {
+            changed,
+            restart_required,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty()
+    }
+
+    /// Whether the update can be applied to a running strategy
+    pub fn is_live(&self) -> bool {
+        !self.is_empty() && self.restart_required.is_empty()
+    }
+}
+
+/// Push a hot-tunable update into a running strategy
+///
+/// Only the changed parameters are passed to the hook. Fails without
+/// touching the strategy if any changed parameter requires a restart.
+pub async fn apply_live_update(
+    strategy: &mut dyn Strategy,
+    schema: &JsonValue,
+    current: &JsonValue,
+    proposed: &JsonValue,
+) -> Result<ParameterUpdate> {
+    let update = ParameterUpdate::plan(schema, current, proposed)?;
+    if update.is_empty() {
+        return Ok(update);
+    }
+    if !update.restart_required.is_empty() {
+        return Err(Error::InvalidConfig(format!(
+            "parameters require a restart: {}",
+            update.restart_required.join(", ")
+        )));
+    }
+
+    let changed: HashMap<String, JsonValue> = update
+        .changed
+        .iter()
+        .filter_map(|name| Some((name.clone(), proposed.get(name)?.clone())))
+        .collect();
+    strategy.on_parameters_updated(&changed).await?;
+    Ok(update)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bundle::parameter_schema;
+    use crate::metrics::PerformanceMetrics;
+    use crate::signal::Signal;
+    use crate::traits::{MarketDataEvent, StrategyConfig};
+    use async_trait::async_trait;
+    use ea_okx_core::models::Order;
+
+    #[derive(Default)]
+    struct GridStrategy {
+        spacing: f64,
+        updates: usize,
+    }
+
+    #[async_trait]
+    impl Strategy for GridStrategy {
+        async fn initialize(&mut self, _config: StrategyConfig) -> Result<()> {
+            Ok(())
+        }
+
+        async fn on_market_data(&mut self, _event: MarketDataEvent) -> Result<()> {
+            Ok(())
+        }
+
+        async fn generate_signal(&self) -> Result<Signal> {
+            Err(Error::SignalError("no signal".to_string()))
+        }
+
+        async fn on_order_fill(&mut self, _order: &Order) -> Result<()> {
+            Ok(())
+        }
+
+        async fn on_order_reject(&mut self, _order: &Order, _reason: &str) -> Result<()> {
+            Ok(())
+        }
+
+        fn get_metrics(&self) -> PerformanceMetrics {
+            PerformanceMetrics::default()
+        }
+
+        fn serialize_state(&self) -> Result<JsonValue> {
+            Ok(json!({}))
+        }
+
+        fn deserialize_state(&mut self, _state: JsonValue) -> Result<()> {
+            Ok(())
+        }
+
+        async fn on_parameters_updated(
+            &mut self,
+            parameters: &HashMap<String, JsonValue>,
+        ) -> Result<()> {
+            if let Some(spacing) = parameters.get("grid_spacing").and_then(JsonValue::as_f64) {
+                self.spacing = spacing;
+            }
+            self.updates += 1;
+            Ok(())
+        }
+
+        async fn shutdown(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    fn schema() -> JsonValue {
+        let mut schema = parameter_schema(&json!({ "grid_spacing": 0.5, "levels": 10 }));
+        set_hot_tunable(&mut schema, "grid_spacing", true).unwrap();
+        schema
+    }
+
+    #[test]
+    fn test_plan_separates_hot_and_cold_changes() {
+        let schema = schema();
+        assert_eq!(
+            hot_tunable_parameters(&schema),
+            BTreeSet::from(["grid_spacing".to_string()])
+        );
+
+        let current = json!({ "grid_spacing": 0.5, "levels": 10 });
+        let hot = ParameterUpdate::plan(
+            &schema,
+            &current,
+            &json!({ "grid_spacing": 0.8, "levels": 10 }),
+        )
+        .unwrap();
+        assert!(hot.is_live());
+
+        let cold = ParameterUpdate::plan(
+            &schema,
+            &current,
+            &json!({ "grid_spacing": 0.8, "levels": 12 }),
+        )
+        .unwrap();
+        assert_eq!(cold.changed, vec!["grid_spacing", "levels"]);
+        assert_eq!(cold.restart_required, vec!["levels"]);
+        assert!(!cold.is_live());
+
+        // Values must still match the schema
+        assert!(
+            ParameterUpdate::plan(
+                &schema,
+                &current,
+                &json!({ "grid_spacing": "wide", "levels": 10 })
+            )
+            .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_live_update_reaches_running_strategy() {
+        let schema = schema();
+        let current = json!({ "grid_spacing": 0.5, "levels": 10 });
+        let mut strategy = GridStrategy::default();
+
+        let update = apply_live_update(
+            &mut strategy,
+            &schema,
+            &current,
+            &json!({ "grid_spacing": 0.8, "levels": 10 }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(update.changed, vec!["grid_spacing"]);
+        assert_eq!(strategy.spacing, 0.8);
+
+        let refused = apply_live_update(
+            &mut strategy,
+            &schema,
+            &current,
+            &json!({ "grid_spacing": 0.8, "levels": 20 }),
+        )
+        .await;
+        assert!(matches!(refused, Err(Error::InvalidConfig(_))));
+        assert_eq!(strategy.updates, 1);
+    }
+}
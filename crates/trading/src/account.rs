@@ -0,0 +1,553 @@
+//! Live account state and balance reconciliation
+//!
+//! [`AccountTracker`] keeps an [`AccountState`] up to date from the private
+//! `account` and `balance_and_position` WebSocket channels, and periodically
+//! compares it with a REST snapshot. When local bookkeeping drifts from the
+//! exchange by more than the configured tolerance, an
+//! [`AccountEvent::Diverged`] is emitted and the exchange figures are adopted.
+
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use ea_okx_client::OkxRestClient;
+use ea_okx_client::models::{
+    AccountData, BalanceAndPositionData, Channel, SubscriptionRequest, WebSocketEvent,
+};
+use ea_okx_client::websocket::OkxWebSocketClient;
+use ea_okx_core::heartbeat::Heartbeat;
+use parking_lot::RwLock;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// Balance of a single currency
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CurrencyBalance {
+    pub ccy: String,
+
+    /// Currency equity
+    pub equity: Decimal,
+
+    /// Cash balance
+    pub cash_balance: Decimal,
+
+    /// Available balance
+    pub available: Option<Decimal>,
+
+    /// Balance frozen by open orders
+    pub frozen: Option<Decimal>,
+
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Account balances and margin
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AccountState {
+    /// Total equity in USD
+    pub total_equity: Decimal,
+
+    /// Account margin ratio (cross margin only)
+    pub margin_ratio: Option<Decimal>,
+
+    /// Balances keyed by currency
+    pub balances: HashMap<String, CurrencyBalance>,
+
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl AccountState {
+    /// Build a state from an account snapshot
+    pub fn from_account_data(data: &AccountData) -> Result<Self> {
+        let mut state = Self::default();
+        state.apply_account(data)?;
+        Ok(state)
+    }
+
+    /// Apply an `account` channel push
+    ///
+    /// Pushes only carry currencies that changed, so balances are merged.
+    pub fn apply_account(&mut self, data: &AccountData) -> Result<()> {
+        self.total_equity = parse_decimal("totalEq", &data.total_eq)?;
+        self.margin_ratio = parse_optional("mgnRatio", data.mgn_ratio.as_deref())?;
+
+        for detail in &data.details {
+            let balance = CurrencyBalance {
+                ccy: detail.ccy.clone(),
+                equity: parse_decimal("eq", &detail.eq)?,
+                cash_balance: parse_decimal("cashBal", &detail.cash_bal)?,
+                available: parse_optional("availBal", detail.avail_bal.as_deref())?,
+                frozen: parse_optional("frozenBal", detail.frozen_bal.as_deref())?,
+                updated_at: parse_millis(&detail.u_time)?,
+            };
+            self.balances.insert(detail.ccy.clone(), balance);
+        }
+
+        self.updated_at = Some(parse_millis(&data.u_time)?);
+        Ok(())
+    }
+
+    /// Apply a `balance_and_position` push (cash balances only)
+    pub fn apply_balance_update(&mut self, data: &BalanceAndPositionData) -> Result<()> {
+        for entry in &data.bal_data {
+            let cash_balance = parse_decimal("cashBal", &entry.cash_bal)?;
+            let updated_at = parse_millis(&entry.u_time)?;
+
+            match self.balances.get_mut(&entry.ccy) {
+                Some(balance) => {
+                    // Equity moves with cash; unrealized P&L arrives on the account channel
+                    balance.equity += cash_balance - balance.cash_balance;
+                    balance.cash_balance = cash_balance;
+                    balance.updated_at = updated_at;
+                }
+                None => {
+                    self.balances.insert(
+                        entry.ccy.clone(),
+                        CurrencyBalance {
+                            ccy: entry.ccy.clone(),
+                            equity: cash_balance,
+                            cash_balance,
+                            available: None,
+                            frozen: None,
+                            updated_at,
+                        },
+                    );
+                }
+            }
+        }
+
+        self.updated_at = Some(parse_millis(&data.p_time)?);
+        Ok(())
+    }
+
+    /// Balance for a currency
+    pub fn balance(&self, ccy: &str) -> Option<&CurrencyBalance> {
+        self.balances.get(ccy)
+    }
+
+    /// Cash balance for a currency, zero if absent
+    pub fn cash_balance(&self, ccy: &str) -> Decimal {
+        self.balances
+            .get(ccy)
+            .map(|b| b.cash_balance)
+            .unwrap_or(Decimal::ZERO)
+    }
+}
+
+/// Reconciliation settings
+#[derive(Debug, Clone)]
+pub struct ReconciliationConfig {
+    /// Seconds between REST snapshots
+    pub interval_secs: u64,
+
+    /// Allowed difference as a fraction of the exchange balance
+    pub relative_tolerance: Decimal,
+
+    /// Allowed absolute difference, for small balances
+    pub absolute_tolerance: Decimal,
+}
+
+impl Default for ReconciliationConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: 60,
+            relative_tolerance: Decimal::new(1, 3), // 0.1%
+            absolute_tolerance: Decimal::new(1, 2), // 0.01
+        }
+    }
+}
+
+/// Cash balance that differs between local state and the exchange
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BalanceDivergence {
+    pub ccy: String,
+    pub local: Decimal,
+    pub exchange: Decimal,
+
+    /// `local - exchange`
+    pub difference: Decimal,
+}
+
+/// Outcome of comparing local state with an exchange snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReconciliationReport {
+    pub checked_at: DateTime<Utc>,
+    pub divergences: Vec<BalanceDivergence>,
+}
+
+impl ReconciliationReport {
+    /// Whether local bookkeeping matched the exchange
+    pub fn is_consistent(&self) -> bool {
+        self.divergences.is_empty()
+    }
+}
+
+/// Account tracker events
+#[derive(Debug, Clone)]
+pub enum AccountEvent {
+    /// Local balances diverged from the exchange beyond tolerance
+    Diverged(ReconciliationReport),
+}
+
+/// Source of authoritative account snapshots (the REST balance endpoint)
+#[async_trait]
+pub trait AccountSnapshotSource: Send + Sync {
+    async fn fetch_account(&self) -> Result<AccountData>;
+}
+
+#[async_trait]
+impl AccountSnapshotSource for OkxRestClient {
+    async fn fetch_account(&self) -> Result<AccountData> {
+        Ok(self.account_balance(None).await?)
+    }
+}
+
+/// Maintains live account state and reconciles it against the exchange
+pub struct AccountTracker {
+    config: ReconciliationConfig,
+    state: Arc<RwLock<AccountState>>,
+    last_report: Arc<RwLock<Option<ReconciliationReport>>>,
+    heartbeat: Heartbeat,
+
+    /// Event channel
+    event_tx: mpsc::UnboundedSender<AccountEvent>,
+    event_rx: Arc<RwLock<Option<mpsc::UnboundedReceiver<AccountEvent>>>>,
+}
+
+impl AccountTracker {
+    /// Create a tracker with empty state
+    pub fn new(config: ReconciliationConfig) -> Self {
+        let (event_tx, event_rx) = mpsc::unbounded_channel();
+
+        Self {
+            config,
+            state: Arc::new(RwLock::new(AccountState::default())),
+            last_report: Arc::new(RwLock::new(None)),
+            heartbeat: Heartbeat::new(),
+            event_tx,
+            event_rx: Arc::new(RwLock::new(Some(event_rx))),
+        }
+    }
+
+    /// Private channels the tracker consumes
+    pub fn subscriptions() -> Vec<SubscriptionRequest> {
+        vec![
+            SubscriptionRequest::new_account(Channel::Account),
+            SubscriptionRequest::new_account(Channel::BalanceAndPosition),
+        ]
+    }
+
+    /// Current account state
+    pub fn state(&self) -> AccountState {
+        self.state.read().clone()
+    }
+
+    /// Result of the most recent reconciliation
+    pub fn last_report(&self) -> Option<ReconciliationReport> {
+        self.last_report.read().clone()
+    }
+
+    /// Apply a WebSocket event; returns whether it touched account state
+    pub fn handle_event(&self, event: &WebSocketEvent) -> Result<bool> {
+        match event {
+            WebSocketEvent::Account(data) => {
+                self.state.write().apply_account(data)?;
+                Ok(true)
+            }
+            WebSocketEvent::BalanceAndPosition(data) => {
+                self.state.write().apply_balance_update(data)?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Subscribe to the private channels and apply events until the stream ends
+    ///
+    /// Events that do not touch account state, such as order updates from
+    /// channels the caller subscribed to on the same client, go to `forward`.
+    pub async fn run(
+        &self,
+        client: &OkxWebSocketClient,
+        forward: Option<&mpsc::UnboundedSender<WebSocketEvent>>,
+    ) -> Result<()> {
+        client.subscribe(Self::subscriptions()).await?;
+        info!("Account tracker subscribed to private channels");
+
+        while let Some(event) = client.next_message().await? {
+            match self.handle_event(&event) {
+                Ok(true) => {}
+                Ok(false) => {
+                    if let Some(forward) = forward {
+                        let _ = forward.send(event);
+                    }
+                }
+                Err(e) => warn!("Failed to apply account update: {}", e),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Compare local state with an exchange snapshot, then adopt the snapshot
+    ///
+    /// The first snapshot only seeds the state: there is nothing local to
+    /// compare it with yet.
+    pub async fn reconcile(
+        &self,
+        source: &dyn AccountSnapshotSource,
+    ) -> Result<ReconciliationReport> {
+        let snapshot = AccountState::from_account_data(&source.fetch_account().await?)?;
+
+        let report = {
+            let mut state = self.state.write();
+            let report = match state.updated_at {
+                Some(_) => self.compare(&state, &snapshot),
+                None => ReconciliationReport {
+                    checked_at: Utc::now(),
+                    divergences: Vec::new(),
+                },
+            };
+            *state = snapshot;
+            report
+        };
+
+        if report.is_consistent() {
+            debug!("Account reconciled with exchange");
+        } else {
+            for d in &report.divergences {
+                warn!(
+                    "Balance divergence for {}: local {} vs exchange {} ({})",
+                    d.ccy, d.local, d.exchange, d.difference
+                );
+            }
+            let _ = self.event_tx.send(AccountEvent::Diverged(report.clone()));
+        }
+
+        *self.last_report.write() = Some(report.clone());
+        Ok(report)
+    }
+
+    /// Beaten on every pass of the reconciliation loop
+    pub fn reconciliation_heartbeat(&self) -> Heartbeat {
+        self.heartbeat.clone()
+    }
+
+    /// Reconcile every `interval_secs` until the task is aborted
+    pub fn start_reconciliation(
+        self: Arc<Self>,
+        source: Arc<dyn AccountSnapshotSource>,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker =
+                tokio::time::interval(Duration::from_secs(self.config.interval_secs.max(1)));
+            loop {
+                ticker.tick().await;
+                self.heartbeat.beat();
+                if let Err(e) = self.reconcile(source.as_ref()).await {
+                    warn!("Account reconciliation failed: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Get event receiver (can only be called once)
+    pub fn subscribe_events(&self) -> Option<mpsc::UnboundedReceiver<AccountEvent>> {
+        self.event_rx.write().take()
+    }
+
+    fn compare(&self, local: &AccountState, exchange: &AccountState) -> ReconciliationReport {
+        let currencies: BTreeSet<&String> = local
+            .balances
+            .keys()
+            .chain(exchange.balances.keys())
+            .collect();
+
+        let divergences = currencies
+            .into_iter()
+            .filter_map(|ccy| {
+                let local = local.cash_balance(ccy);
+                let exchange = exchange.cash_balance(ccy);
+                let difference = local - exchange;
+                let tolerance = (exchange.abs() * self.config.relative_tolerance)
+                    .max(self.config.absolute_tolerance);
+
+                (difference.abs() > tolerance).then(|| BalanceDivergence {
+                    ccy: ccy.clone(),
+                    local,
+                    exchange,
+                    difference,
+                })
+            })
+            .collect();
+
+        ReconciliationReport {
+            checked_at: Utc::now(),
+            divergences,
+        }
+    }
+}
+
+fn parse_decimal(field: &str, value: &str) -> Result<Decimal> {
+    value
+        .parse()
+        .map_err(|e| parse_error(format!("Invalid {} '{}': {}", field, value, e)))
+}
+
+fn parse_optional(field: &str, value: Option<&str>) -> Result<Option<Decimal>> {
+    match value {
+        None | Some("") => Ok(None),
+        Some(v) => parse_decimal(field, v).map(Some),
+    }
+}
+
+fn parse_millis(value: &str) -> Result<DateTime<Utc>> {
+    value
+        .parse::<i64>()
+        .ok()
+        .and_then(|ms| Utc.timestamp_millis_opt(ms).single())
+        .ok_or_else(|| parse_error(format!("Invalid timestamp '{}'", value)))
+}
+
+fn parse_error(message: String) -> Error {
+    Error::ClientError(ea_okx_client::Error::ParseError(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn account(total_eq: &str, balances: &[(&str, &str)]) -> AccountData {
+        serde_json::from_value(serde_json::json!({
+            "uTime": "1700000000000",
+            "totalEq": total_eq,
+            "mgnRatio": "",
+            "details": balances.iter().map(|(ccy, bal)| serde_json::json!({
+                "ccy": ccy,
+                "eq": bal,
+                "cashBal": bal,
+                "availBal": bal,
+                "uTime": "1700000000000"
+            })).collect::<Vec<_>>()
+        }))
+        .unwrap()
+    }
+
+    struct FixedSnapshot(AccountData);
+
+    #[async_trait]
+    impl AccountSnapshotSource for FixedSnapshot {
+        async fn fetch_account(&self) -> Result<AccountData> {
+            Ok(self.0.clone())
+        }
+    }
+
+    #[test]
+    fn test_account_push_merges_balances() {
+        let tracker = AccountTracker::new(ReconciliationConfig::default());
+        let full = account("15000", &[("USDT", "10000"), ("BTC", "0.1")]);
+        let partial = account("15100", &[("USDT", "10100")]);
+
+        tracker
+            .handle_event(&WebSocketEvent::Account(full))
+            .unwrap();
+        tracker
+            .handle_event(&WebSocketEvent::Account(partial))
+            .unwrap();
+
+        let state = tracker.state();
+        assert_eq!(state.total_equity, dec!(15100));
+        assert_eq!(state.margin_ratio, None);
+        assert_eq!(state.cash_balance("USDT"), dec!(10100));
+        assert_eq!(state.cash_balance("BTC"), dec!(0.1));
+    }
+
+    #[test]
+    fn test_balance_and_position_updates_cash() {
+        let tracker = AccountTracker::new(ReconciliationConfig::default());
+        tracker
+            .handle_event(&WebSocketEvent::Account(account(
+                "10000",
+                &[("USDT", "10000")],
+            )))
+            .unwrap();
+
+        let update: BalanceAndPositionData = serde_json::from_value(serde_json::json!({
+            "pTime": "1700000001000",
+            "eventType": "filled",
+            "balData": [
+                {"ccy": "USDT", "cashBal": "9500", "uTime": "1700000001000"},
+                {"ccy": "ETH", "cashBal": "0.2", "uTime": "1700000001000"}
+            ],
+            "posData": []
+        }))
+        .unwrap();
+        assert!(
+            tracker
+                .handle_event(&WebSocketEvent::BalanceAndPosition(update))
+                .unwrap()
+        );
+
+        let state = tracker.state();
+        assert_eq!(state.cash_balance("USDT"), dec!(9500));
+        assert_eq!(state.balance("USDT").unwrap().equity, dec!(9500));
+        assert_eq!(state.cash_balance("ETH"), dec!(0.2));
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_reports_divergence_and_adopts_snapshot() {
+        let tracker = AccountTracker::new(ReconciliationConfig::default());
+        let mut events = tracker.subscribe_events().unwrap();
+        tracker
+            .handle_event(&WebSocketEvent::Account(account(
+                "10000",
+                &[("USDT", "10000"), ("BTC", "0.1")],
+            )))
+            .unwrap();
+
+        // USDT within 0.1%, BTC off by half
+        let exchange = FixedSnapshot(account("9900", &[("USDT", "10005"), ("BTC", "0.05")]));
+        let report = tracker.reconcile(&exchange).await.unwrap();
+
+        assert_eq!(report.divergences.len(), 1);
+        assert_eq!(report.divergences[0].ccy, "BTC");
+        assert_eq!(report.divergences[0].difference, dec!(0.05));
+        assert!(matches!(events.try_recv(), Ok(AccountEvent::Diverged(_))));
+
+        assert_eq!(tracker.state().cash_balance("BTC"), dec!(0.05));
+        assert_eq!(tracker.last_report(), Some(report));
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_consistent_emits_nothing() {
+        let tracker = AccountTracker::new(ReconciliationConfig::default());
+        let mut events = tracker.subscribe_events().unwrap();
+        let data = account("10000", &[("USDT", "10000")]);
+        tracker
+            .handle_event(&WebSocketEvent::Account(data.clone()))
+            .unwrap();
+
+        let report = tracker.reconcile(&FixedSnapshot(data)).await.unwrap();
+        assert!(report.is_consistent());
+        assert!(events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_first_reconcile_seeds_balances() {
+        let tracker = AccountTracker::new(ReconciliationConfig::default());
+        let mut events = tracker.subscribe_events().unwrap();
+        assert!(tracker.state().updated_at.is_none());
+
+        let exchange = FixedSnapshot(account("10000", &[("USDT", "10000")]));
+        let report = tracker.reconcile(&exchange).await.unwrap();
+        assert!(report.is_consistent());
+        assert!(events.try_recv().is_err());
+        assert_eq!(tracker.state().cash_balance("USDT"), dec!(10000));
+        assert!(tracker.state().updated_at.is_some());
+    }
+}
@@ -1,12 +1,18 @@
-use crate::error::Result;
+use crate::error::{Error, Result};
+use crate::execution_store::{AlgoExecution, AlgoExecutionStatus, AlgoExecutionStore, AlgoParams};
+use crate::liquidity::{LocalOrderBook, OrderBooks};
 use crate::order_manager::OrderManager;
+use crate::trade_volume::TradeVolumes;
+use crate::volume_profile::VolumeProfileEstimator;
 use chrono::{DateTime, Duration, Timelike, Utc};
 use ea_okx_core::models::{Order, OrderSide, OrderType};
 use ea_okx_core::{Price, Quantity, Symbol};
 use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
 use rust_decimal_macros::dec;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use tokio::sync::watch;
 use tracing::{debug, info, warn};
 use uuid::Uuid;
 
@@ -33,6 +39,14 @@ pub struct TwapConfig {
 
     /// Use market order for final slice
     pub aggressive_on_final: bool,
+
+    /// Adaptive pacing (disabled when `None`)
+    #[serde(default)]
+    pub adaptive: Option<AdaptiveTwapConfig>,
+
+    /// Order book timing of each slice (disabled when `None`)
+    #[serde(default)]
+    pub micro_timing: Option<MicroTimingConfig>,
 }
 
 impl Default for TwapConfig {
@@ -45,7 +59,259 @@ impl Default for TwapConfig {
             order_type: OrderType::Limit,
             price_offset_bps: 0,
             aggressive_on_final: true,
+            adaptive: None,
+            micro_timing: None,
+        }
+    }
+}
+
+/// Adaptive TWAP pacing configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdaptiveTwapConfig {
+    /// A slice counts as unfilled below this fill ratio (0-1)
+    pub min_fill_ratio: Decimal,
+
+    /// Consecutive unfilled slices before widening the offset
+    pub widen_after_unfilled: u32,
+
+    /// Offset added per widening step in basis points
+    pub widen_step_bps: i32,
+
+    /// Maximum price offset in basis points
+    pub max_offset_bps: i32,
+
+    /// Consecutive unfilled slices before switching to marketable orders
+    pub marketable_after_unfilled: u32,
+
+    /// Favorable drift from the arrival price that triggers acceleration (bps)
+    pub favorable_drift_bps: Decimal,
+
+    /// Slice size multiplier and interval divisor while accelerating
+    pub acceleration_factor: Decimal,
+
+    /// Adverse drift from the arrival price that triggers a pause (bps)
+    pub adverse_drift_bps: Decimal,
+
+    /// Total time the execution may spend paused in seconds
+    pub max_pause_seconds: u64,
+}
+
+impl Default for AdaptiveTwapConfig {
+    fn default() -> Self {
+        Self {
+            min_fill_ratio: dec!(0.5),
+            widen_after_unfilled: 2,
+            widen_step_bps: 5,
+            max_offset_bps: 50,
+            marketable_after_unfilled: 4,
+            favorable_drift_bps: dec!(20.0),
+            acceleration_factor: dec!(1.5),
+            adverse_drift_bps: dec!(30.0),
+            max_pause_seconds: 300,
+        }
+    }
+}
+
+/// When a due TWAP slice is sent, judged on the order book
+///
+/// A book leaning toward our side (bids outweighing asks for a buy) tends to
+/// precede a move away from us, so the slice is sent as soon as that lean and
+/// a tight spread coincide, or at the deadline otherwise.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MicroTimingConfig {
+    /// Imbalance toward our side needed to send before the deadline (-1 to 1)
+    pub min_imbalance: Decimal,
+
+    /// Book levels per side the imbalance is measured over
+    pub depth_levels: usize,
+
+    /// Widest spread to send at before the deadline, in basis points
+    pub max_spread_bps: Decimal,
+
+    /// Longest wait after a slice is due in seconds, capped by the slice
+    /// interval
+    pub max_wait_seconds: u64,
+
+    /// How often the book is checked while waiting, in milliseconds
+    pub poll_interval_ms: u64,
+}
+
+impl Default for MicroTimingConfig {
+    fn default() -> Self {
+        Self {
+            min_imbalance: dec!(0.2),
+            depth_levels: 5,
+            max_spread_bps: dec!(5.0),
+            max_wait_seconds: 30,
+            poll_interval_ms: 250,
+        }
+    }
+}
+
+impl MicroTimingConfig {
+    /// Imbalance and spread of `book`, and whether they favor sending now
+    pub fn assess(
+        &self,
+        book: &LocalOrderBook,
+        side: OrderSide,
+    ) -> (Option<Decimal>, Option<Decimal>, bool) {
+        let imbalance = book.imbalance(side, self.depth_levels);
+        let spread_bps = book.spread_bps();
+        let favorable = imbalance.is_some_and(|i| i >= self.min_imbalance)
+            && spread_bps.is_some_and(|s| s <= self.max_spread_bps);
+        (imbalance, spread_bps, favorable)
+    }
+}
+
+/// How long a slice waited for the book and what that was worth
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SliceTiming {
+    /// Time between the slice being due and being sent
+    pub waited_ms: u64,
+
+    /// Whether the book turned favorable before the deadline
+    pub triggered: bool,
+
+    /// Imbalance toward our side when sent
+    pub imbalance: Option<Decimal>,
+
+    /// Spread when sent, in basis points
+    pub spread_bps: Option<Decimal>,
+
+    /// Touch price when the slice was due
+    pub due_price: Option<Decimal>,
+
+    /// Touch price when the slice was sent
+    pub sent_price: Option<Decimal>,
+
+    /// Improvement of the sent touch over the due touch in basis points,
+    /// negative when waiting cost money
+    pub benefit_bps: Option<Decimal>,
+}
+
+/// Improvement of `sent` over `due` for an order on `side`, in basis points
+fn timing_benefit_bps(due: Decimal, sent: Decimal, side: OrderSide) -> Option<Decimal> {
+    if due.is_zero() {
+        return None;
+    }
+    let move_bps = (sent - due) / due * dec!(10000.0);
+    Some(match side {
+        OrderSide::Buy => -move_bps,
+        OrderSide::Sell => move_bps,
+    })
+}
+
+/// Adaptation decision taken by the adaptive TWAP pacer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AdaptationDecision {
+    /// Limit offset widened after repeated unfilled slices
+    WidenOffset { from_bps: i32, to_bps: i32 },
+
+    /// Child orders switched to marketable orders
+    SwitchToMarketable { unfilled_slices: u32 },
+
+    /// Slice enlarged and schedule tightened on favorable drift
+    Accelerate { drift_bps: Decimal, factor: Decimal },
+
+    /// Execution paused on adverse drift
+    Pause { drift_bps: Decimal, seconds: u64 },
+}
+
+/// Tracks fill rate and price drift to adapt TWAP pacing
+#[derive(Debug, Clone)]
+pub struct AdaptivePacer {
+    config: AdaptiveTwapConfig,
+    offset_bps: i32,
+    marketable: bool,
+    consecutive_unfilled: u32,
+    paused_seconds: u64,
+}
+
+impl AdaptivePacer {
+    pub fn new(config: AdaptiveTwapConfig, base_offset_bps: i32) -> Self {
+        Self {
+            config,
+            offset_bps: base_offset_bps,
+            marketable: false,
+            consecutive_unfilled: 0,
+            paused_seconds: 0,
+        }
+    }
+
+    /// Current limit price offset in basis points
+    pub fn offset_bps(&self) -> i32 {
+        self.offset_bps
+    }
+
+    /// Whether child orders should be marketable
+    pub fn is_marketable(&self) -> bool {
+        self.marketable
+    }
+
+    /// React to price drift (positive = favorable) before a slice.
+    /// `slice_interval_seconds` bounds a single pause.
+    pub fn on_price(
+        &mut self,
+        drift_bps: Decimal,
+        slice_interval_seconds: u64,
+    ) -> Option<AdaptationDecision> {
+        if drift_bps >= self.config.favorable_drift_bps {
+            return Some(AdaptationDecision::Accelerate {
+                drift_bps,
+                factor: self.config.acceleration_factor,
+            });
         }
+
+        if -drift_bps >= self.config.adverse_drift_bps {
+            let budget = self
+                .config
+                .max_pause_seconds
+                .saturating_sub(self.paused_seconds);
+            if budget > 0 {
+                let seconds = budget.min(slice_interval_seconds.max(1));
+                self.paused_seconds += seconds;
+                return Some(AdaptationDecision::Pause { drift_bps, seconds });
+            }
+        }
+
+        None
+    }
+
+    /// React to the fill outcome of a slice
+    pub fn on_slice_result(
+        &mut self,
+        target: Decimal,
+        executed: Decimal,
+    ) -> Vec<AdaptationDecision> {
+        let mut decisions = Vec::new();
+
+        let filled = target > Decimal::ZERO && executed / target >= self.config.min_fill_ratio;
+        if filled {
+            self.consecutive_unfilled = 0;
+            return decisions;
+        }
+        self.consecutive_unfilled += 1;
+
+        if !self.marketable && self.consecutive_unfilled >= self.config.marketable_after_unfilled {
+            self.marketable = true;
+            decisions.push(AdaptationDecision::SwitchToMarketable {
+                unfilled_slices: self.consecutive_unfilled,
+            });
+        } else if !self.marketable
+            && self.consecutive_unfilled >= self.config.widen_after_unfilled
+            && self.offset_bps < self.config.max_offset_bps
+        {
+            let from_bps = self.offset_bps;
+            self.offset_bps =
+                (self.offset_bps + self.config.widen_step_bps).min(self.config.max_offset_bps);
+            decisions.push(AdaptationDecision::WidenOffset {
+                from_bps,
+                to_bps: self.offset_bps,
+            });
+        }
+
+        decisions
     }
 }
 
@@ -61,7 +327,8 @@ pub struct VwapConfig {
     /// End time
     pub end_time: DateTime<Utc>,
 
-    /// Historical volume profile (hour -> volume percentage)
+    /// Historical volume profile (hour -> volume percentage), used when no
+    /// fresh learned profile is available for the symbol
     pub volume_profile: Vec<(u32, Decimal)>,
 
     /// Minimum slice size
@@ -69,6 +336,10 @@ pub struct VwapConfig {
 
     /// Price offset in basis points
     pub price_offset_bps: i32,
+
+    /// Pacing of child orders against live traded volume
+    #[serde(default)]
+    pub participation: ParticipationConfig,
 }
 
 impl Default for VwapConfig {
@@ -108,6 +379,84 @@ impl Default for VwapConfig {
             volume_profile,
             min_slice_size: Quantity::new(dec!(0.001)).unwrap(),
             price_offset_bps: 0,
+            participation: ParticipationConfig::default(),
+        }
+    }
+}
+
+/// Participation (POV) pacing of a VWAP execution
+///
+/// Child orders take a share of the volume traded since the previous one.
+/// The share rises toward `max_rate` as the execution falls behind the
+/// volume profile and drops toward `min_rate` as it gets ahead, reaching
+/// either once the gap is `tolerance` of the parent order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParticipationConfig {
+    /// Share of market volume taken while on schedule
+    pub target_rate: Decimal,
+
+    /// Share taken when furthest behind schedule
+    pub max_rate: Decimal,
+
+    /// Share taken when furthest ahead of schedule
+    pub min_rate: Decimal,
+
+    /// Schedule gap, as a share of the parent order, at which the rate
+    /// reaches its bounds
+    pub tolerance: Decimal,
+
+    /// Seconds between participation checks
+    pub check_interval_seconds: u64,
+}
+
+impl Default for ParticipationConfig {
+    fn default() -> Self {
+        Self {
+            target_rate: dec!(0.10),
+            max_rate: dec!(0.25),
+            min_rate: dec!(0.02),
+            tolerance: dec!(0.05),
+            check_interval_seconds: 10,
+        }
+    }
+}
+
+impl ParticipationConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.min_rate < Decimal::ZERO
+            || self.min_rate > self.target_rate
+            || self.target_rate > self.max_rate
+            || self.max_rate > Decimal::ONE
+        {
+            return Err(Error::InvalidPolicy(
+                "Participation rates must satisfy 0 <= min <= target <= max <= 1".to_string(),
+            ));
+        }
+        if self.tolerance <= Decimal::ZERO {
+            return Err(Error::InvalidPolicy(
+                "Participation tolerance must be positive".to_string(),
+            ));
+        }
+        if self.check_interval_seconds == 0 {
+            return Err(Error::InvalidPolicy(
+                "Participation check interval must be positive".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Share of market volume to take with `executed` done against
+    /// `scheduled` due by now, out of `total`
+    pub fn rate(&self, executed: Decimal, scheduled: Decimal, total: Decimal) -> Decimal {
+        if total <= Decimal::ZERO {
+            return self.target_rate;
+        }
+        let ahead =
+            ((executed - scheduled) / total / self.tolerance).clamp(-Decimal::ONE, Decimal::ONE);
+        if ahead < Decimal::ZERO {
+            self.target_rate - (self.max_rate - self.target_rate) * ahead
+        } else {
+            self.target_rate - (self.target_rate - self.min_rate) * ahead
         }
     }
 }
@@ -131,6 +480,14 @@ pub struct VwapResult {
     pub slices_executed: u32,
     pub total_duration: Duration,
     pub vwap_deviation_bps: Decimal,
+
+    /// Market volume traded while executing; zero without a trade feed
+    #[serde(default)]
+    pub market_volume: Decimal,
+
+    /// Executed quantity over market volume
+    #[serde(default)]
+    pub participation_rate: Decimal,
 }
 
 /// Individual slice execution details
@@ -142,6 +499,14 @@ pub struct SliceExecution {
     pub price: Price,
     pub timestamp: DateTime<Utc>,
     pub success: bool,
+
+    /// Adaptive pacing decisions taken around this slice
+    #[serde(default)]
+    pub adaptations: Vec<AdaptationDecision>,
+
+    /// Order book timing of the slice, when micro-timing ran
+    #[serde(default)]
+    pub timing: Option<SliceTiming>,
 }
 
 /// TWAP executor
@@ -150,6 +515,9 @@ pub struct TwapExecutor {
     symbol: Symbol,
     side: OrderSide,
     order_manager: Arc<OrderManager>,
+    store: Option<Arc<dyn AlgoExecutionStore>>,
+    price_feed: Option<watch::Receiver<Price>>,
+    books: Option<Arc<OrderBooks>>,
 }
 
 impl TwapExecutor {
@@ -164,6 +532,47 @@ impl TwapExecutor {
             symbol,
             side,
             order_manager,
+            store: None,
+            price_feed: None,
+            books: None,
+        }
+    }
+
+    /// Persist execution progress to the given store after every slice
+    pub fn with_store(mut self, store: Arc<dyn AlgoExecutionStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Use live prices for slice pricing and adaptive drift detection
+    pub fn with_price_feed(mut self, price_feed: watch::Receiver<Price>) -> Self {
+        self.price_feed = Some(price_feed);
+        self
+    }
+
+    /// Time slices on these books when micro-timing is configured
+    pub fn with_order_books(mut self, books: Arc<OrderBooks>) -> Self {
+        self.books = Some(books);
+        self
+    }
+
+    /// Rebuild an executor from a persisted execution
+    pub fn from_execution(
+        execution: &AlgoExecution,
+        order_manager: Arc<OrderManager>,
+    ) -> Result<Self> {
+        match &execution.params {
+            AlgoParams::Twap(config) => Ok(Self::new(
+                config.clone(),
+                execution.symbol.clone(),
+                execution.side,
+                order_manager,
+            )),
+            other => Err(Error::ExecutionError(format!(
+                "Execution {} is a {} execution, not TWAP",
+                execution.id,
+                other.name()
+            ))),
         }
     }
 
@@ -177,27 +586,96 @@ impl TwapExecutor {
             self.config.duration_minutes
         );
 
-        let start_time = Utc::now();
+        let execution = AlgoExecution::new_twap(
+            self.config.clone(),
+            self.symbol.clone(),
+            self.side,
+            current_price,
+        );
+        self.run(execution, current_price).await
+    }
 
-        // Calculate number of slices
-        let total_seconds = self.config.duration_minutes as u64 * 60;
-        let slice_count = (total_seconds / self.config.slice_interval_seconds as u64).max(1);
-        let base_slice_size = self.config.total_quantity.as_decimal() / Decimal::from(slice_count);
+    /// Resume a persisted TWAP execution from its next pending slice
+    pub async fn resume(
+        &self,
+        execution: AlgoExecution,
+        current_price: Price,
+    ) -> Result<TwapResult> {
+        if !matches!(execution.params, AlgoParams::Twap(_)) {
+            return Err(Error::ExecutionError(format!(
+                "Execution {} is not a TWAP execution",
+                execution.id
+            )));
+        }
+
+        info!(
+            "Resuming TWAP execution {}: {}/{} slices done, {} remaining",
+            execution.id,
+            execution.slices_done,
+            execution.slices_total,
+            execution.remaining_quantity()
+        );
+
+        self.run(execution, current_price).await
+    }
+
+    async fn run(&self, mut execution: AlgoExecution, current_price: Price) -> Result<TwapResult> {
+        let slice_count = execution.slices_total;
+        let base_slice_size = execution.total_quantity.as_decimal() / Decimal::from(slice_count);
 
         debug!("TWAP: {} slices of ~{} each", slice_count, base_slice_size);
 
-        let mut remaining = self.config.total_quantity.as_decimal();
-        let mut total_cost = Decimal::ZERO;
-        let mut total_executed = Decimal::ZERO;
         let mut slice_details = Vec::new();
-        let mut slices_executed = 0u32;
-        let mut slices_failed = 0u32;
-
-        for slice_num in 0..slice_count {
+        let mut pending_adaptations = Vec::new();
+        let mut pacer = self
+            .config
+            .adaptive
+            .clone()
+            .map(|config| AdaptivePacer::new(config, self.config.price_offset_bps));
+        self.persist(&execution);
+
+        while execution.slices_done < slice_count {
+            let slice_num = execution.slices_done;
+            let remaining = execution.remaining_quantity();
             if remaining <= Decimal::ZERO {
                 break;
             }
 
+            // Wait for the slice to become due
+            wait_until(execution.next_slice_at).await;
+
+            let market_price = self.latest_price().unwrap_or(current_price);
+            let mut adaptations = std::mem::take(&mut pending_adaptations);
+            let mut size_factor = dec!(1.0);
+
+            // React to price drift since arrival
+            if let Some(pacer) = pacer.as_mut() {
+                let drift_bps = drift_bps(execution.reference_price, market_price, self.side);
+                match pacer.on_price(drift_bps, execution.slice_interval_seconds) {
+                    Some(AdaptationDecision::Pause { drift_bps, seconds }) => {
+                        info!(
+                            "TWAP {} pausing {}s on adverse drift of {} bps",
+                            execution.id, seconds, drift_bps
+                        );
+                        tokio::time::sleep(tokio::time::Duration::from_secs(seconds)).await;
+                        pending_adaptations.push(AdaptationDecision::Pause { drift_bps, seconds });
+                        continue;
+                    }
+                    Some(decision @ AdaptationDecision::Accelerate { factor, .. }) => {
+                        size_factor = factor;
+                        adaptations.push(decision);
+                    }
+                    _ => {}
+                }
+            }
+
+            // Then for the book to favor sending, up to the deadline
+            let timing = self.await_book(execution.slice_interval_seconds).await;
+            let market_price = match timing {
+                Some(_) => self.latest_price().unwrap_or(market_price),
+                None => market_price,
+            };
+
             // Apply randomization
             let random_factor = if self.config.randomization_pct > Decimal::ZERO {
                 let random_val = (rand::random::<f64>() - 0.5) * 2.0; // -1 to 1
@@ -209,103 +687,121 @@ impl TwapExecutor {
                 dec!(1.0)
             };
 
-            let slice_size = (base_slice_size * random_factor).min(remaining);
+            let slice_size = (base_slice_size * random_factor * size_factor).min(remaining);
 
             // Determine if this is the final slice
             let is_final = slice_num == slice_count - 1 || slice_size >= remaining;
 
             // Choose order type
-            let order_type = if is_final && self.config.aggressive_on_final {
+            let marketable = pacer.as_ref().is_some_and(|p| p.is_marketable());
+            let order_type = if marketable || (is_final && self.config.aggressive_on_final) {
                 OrderType::Market
             } else {
                 self.config.order_type
             };
 
             // Calculate price with offset
-            let slice_price = self.calculate_price_with_offset(current_price);
+            let offset_bps = pacer
+                .as_ref()
+                .map(|p| p.offset_bps())
+                .unwrap_or(self.config.price_offset_bps);
+            let slice_price = self.calculate_price_with_offset(market_price, offset_bps);
 
             // Execute slice
-            match self
-                .execute_slice(slice_size, slice_price, order_type)
+            let (executed_qty, success) = match self
+                .execute_slice(&mut execution, slice_size, slice_price, order_type)
                 .await
             {
                 Ok(executed_qty) => {
-                    let executed_dec = executed_qty.as_decimal();
-                    total_executed += executed_dec;
-                    total_cost += executed_dec * slice_price.as_decimal();
-                    remaining -= executed_dec;
-                    slices_executed += 1;
-
-                    slice_details.push(SliceExecution {
-                        slice_number: slice_num as u32,
-                        target_quantity: Quantity::new(slice_size).unwrap(),
-                        executed_quantity: executed_qty,
-                        price: slice_price,
-                        timestamp: Utc::now(),
-                        success: true,
-                    });
-
                     debug!(
                         "TWAP slice {}/{} executed: {} @ {}",
                         slice_num + 1,
                         slice_count,
-                        executed_dec,
+                        executed_qty.as_decimal(),
                         slice_price.as_decimal()
                     );
+                    (executed_qty, true)
                 }
                 Err(e) => {
                     warn!("TWAP slice {}/{} failed: {}", slice_num + 1, slice_count, e);
-                    slices_failed += 1;
-
-                    slice_details.push(SliceExecution {
-                        slice_number: slice_num as u32,
-                        target_quantity: Quantity::new(slice_size).unwrap(),
-                        executed_quantity: Quantity::new(Decimal::ZERO).unwrap(),
-                        price: slice_price,
-                        timestamp: Utc::now(),
-                        success: false,
-                    });
+                    (Quantity::new(Decimal::ZERO)?, false)
                 }
+            };
+
+            execution.record_slice(executed_qty.as_decimal(), slice_price, success);
+
+            if let Some(pacer) = pacer.as_mut() {
+                adaptations.extend(pacer.on_slice_result(slice_size, executed_qty.as_decimal()));
+            }
+
+            // Tighten the schedule while accelerating
+            if size_factor > dec!(1.0) {
+                let interval = Decimal::from(execution.slice_interval_seconds) / size_factor;
+                let interval_secs = interval.to_i64().unwrap_or(0);
+                execution.next_slice_at = Utc::now() + Duration::seconds(interval_secs);
             }
 
-            // Wait for next slice (unless it's the last one)
-            if !is_final {
-                tokio::time::sleep(tokio::time::Duration::from_secs(
-                    self.config.slice_interval_seconds as u64,
-                ))
-                .await;
+            for decision in &adaptations {
+                info!("TWAP {} adaptation: {:?}", execution.id, decision);
+            }
+
+            slice_details.push(SliceExecution {
+                slice_number: slice_num,
+                target_quantity: Quantity::new(slice_size)?,
+                executed_quantity: executed_qty,
+                price: slice_price,
+                timestamp: Utc::now(),
+                success,
+                adaptations,
+                timing,
+            });
+
+            self.persist(&execution);
+
+            if is_final {
+                break;
             }
         }
 
-        let avg_price = if total_executed > Decimal::ZERO {
-            Price::new(total_cost / total_executed)?
-        } else {
-            current_price
-        };
+        if !execution.status.is_terminal() {
+            execution.finish(AlgoExecutionStatus::Completed, None);
+            self.persist(&execution);
+        }
+
+        let avg_price = execution.average_price().unwrap_or(current_price);
 
         let result = TwapResult {
-            total_executed: Quantity::new(total_executed)?,
+            total_executed: Quantity::new(execution.executed_quantity)?,
             average_price: avg_price,
-            slices_executed,
-            slices_failed,
-            total_duration: Utc::now() - start_time,
+            slices_executed: execution.slices_done - execution.slices_failed,
+            slices_failed: execution.slices_failed,
+            total_duration: Utc::now() - execution.started_at,
             slice_details,
         };
 
         info!(
             "TWAP completed: executed {} @ avg {} ({}/{} slices)",
-            total_executed,
+            execution.executed_quantity,
             avg_price.as_decimal(),
-            slices_executed,
+            result.slices_executed,
             slice_count
         );
 
         Ok(result)
     }
 
+    fn persist(&self, execution: &AlgoExecution) {
+        if let Some(store) = &self.store
+            && let Err(e) = store.save(execution)
+        {
+            warn!("Failed to persist TWAP execution {}: {}", execution.id, e);
+        }
+    }
+
     /// Execute a single slice
     async fn execute_slice(
         &self,
+        execution: &mut AlgoExecution,
         quantity: Decimal,
         price: Price,
         order_type: OrderType,
@@ -319,8 +815,12 @@ impl TwapExecutor {
             Some(price),
         );
 
+        // Persisted before sending so a crash cannot orphan the order
+        execution.record_child_order(order.client_order_id.clone());
+        self.persist(execution);
+
         // Submit order
-        let order_id = self.order_manager.submit_order(order).await?;
+        self.order_manager.submit_order(order).await?;
 
         // Wait for fill (simplified - in production would monitor events)
         tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
@@ -329,9 +829,49 @@ impl TwapExecutor {
         Ok(Quantity::new(quantity)?)
     }
 
+    /// Wait until the book is favorable or the micro-timing deadline passes;
+    /// `None` without micro-timing or books
+    async fn await_book(&self, slice_interval_seconds: u64) -> Option<SliceTiming> {
+        let config = self.config.micro_timing.as_ref()?;
+        let books = self.books.as_ref()?;
+
+        let started = tokio::time::Instant::now();
+        let deadline =
+            tokio::time::Duration::from_secs(config.max_wait_seconds.min(slice_interval_seconds));
+        let poll = tokio::time::Duration::from_millis(config.poll_interval_ms.max(1));
+        let due_price = books
+            .book(&self.symbol)
+            .and_then(|book| book.touch(self.side));
+
+        loop {
+            let book = books.book(&self.symbol).unwrap_or_default();
+            let (imbalance, spread_bps, favorable) = config.assess(&book, self.side);
+            if favorable || started.elapsed() >= deadline {
+                let sent_price = book.touch(self.side);
+                return Some(SliceTiming {
+                    waited_ms: started.elapsed().as_millis() as u64,
+                    triggered: favorable,
+                    imbalance,
+                    spread_bps,
+                    due_price,
+                    sent_price,
+                    benefit_bps: due_price
+                        .zip(sent_price)
+                        .and_then(|(due, sent)| timing_benefit_bps(due, sent, self.side)),
+                });
+            }
+            tokio::time::sleep(poll.min(deadline.saturating_sub(started.elapsed()))).await;
+        }
+    }
+
+    /// Latest price from the live feed, if any
+    fn latest_price(&self) -> Option<Price> {
+        self.price_feed.as_ref().map(|rx| *rx.borrow())
+    }
+
     /// Calculate price with offset
-    fn calculate_price_with_offset(&self, base_price: Price) -> Price {
-        let offset_decimal = Decimal::from(self.config.price_offset_bps) / dec!(10000.0);
+    fn calculate_price_with_offset(&self, base_price: Price, offset_bps: i32) -> Price {
+        let offset_decimal = Decimal::from(offset_bps) / dec!(10000.0);
         let offset_amount = base_price.as_decimal() * offset_decimal;
 
         let adjusted_price = match self.side {
@@ -349,6 +889,9 @@ pub struct VwapExecutor {
     symbol: Symbol,
     side: OrderSide,
     order_manager: Arc<OrderManager>,
+    store: Option<Arc<dyn AlgoExecutionStore>>,
+    volume_profiles: Option<Arc<VolumeProfileEstimator>>,
+    trade_volumes: Option<Arc<TradeVolumes>>,
 }
 
 impl VwapExecutor {
@@ -363,6 +906,48 @@ impl VwapExecutor {
             symbol,
             side,
             order_manager,
+            store: None,
+            volume_profiles: None,
+            trade_volumes: None,
+        }
+    }
+
+    /// Persist execution progress to the given store after every slice
+    pub fn with_store(mut self, store: Arc<dyn AlgoExecutionStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Weight hours by the symbol's learned volume profile while it is fresh
+    pub fn with_volume_profiles(mut self, estimator: Arc<VolumeProfileEstimator>) -> Self {
+        self.volume_profiles = Some(estimator);
+        self
+    }
+
+    /// Pace child orders to the live volume in `volumes`; without it they
+    /// follow the volume profile alone
+    pub fn with_trade_volumes(mut self, volumes: Arc<TradeVolumes>) -> Self {
+        self.trade_volumes = Some(volumes);
+        self
+    }
+
+    /// Rebuild an executor from a persisted execution
+    pub fn from_execution(
+        execution: &AlgoExecution,
+        order_manager: Arc<OrderManager>,
+    ) -> Result<Self> {
+        match &execution.params {
+            AlgoParams::Vwap(config) => Ok(Self::new(
+                config.clone(),
+                execution.symbol.clone(),
+                execution.side,
+                order_manager,
+            )),
+            other => Err(Error::ExecutionError(format!(
+                "Execution {} is a {} execution, not VWAP",
+                execution.id,
+                other.name()
+            ))),
         }
     }
 
@@ -376,79 +961,137 @@ impl VwapExecutor {
             self.config.end_time
         );
 
-        let start_time = Utc::now();
-        let duration = self.config.end_time - self.config.start_time;
-        let duration_hours = duration.num_hours() as u32;
+        let execution = AlgoExecution::new_vwap(
+            self.config.clone(),
+            self.symbol.clone(),
+            self.side,
+            current_price,
+        );
+        self.run(execution, current_price).await
+    }
 
-        // Calculate quantity per hour based on volume profile
-        let total_volume_weight: Decimal = self
-            .config
-            .volume_profile
-            .iter()
-            .map(|(_, weight)| weight)
-            .sum();
+    /// Resume a persisted VWAP execution where it left off
+    pub async fn resume(
+        &self,
+        execution: AlgoExecution,
+        current_price: Price,
+    ) -> Result<VwapResult> {
+        if !matches!(execution.params, AlgoParams::Vwap(_)) {
+            return Err(Error::ExecutionError(format!(
+                "Execution {} is not a VWAP execution",
+                execution.id
+            )));
+        }
 
-        let mut remaining = self.config.total_quantity.as_decimal();
-        let mut total_cost = Decimal::ZERO;
-        let mut total_executed = Decimal::ZERO;
-        let mut slices_executed = 0u32;
+        info!(
+            "Resuming VWAP execution {}: {} slices done, {} remaining",
+            execution.id,
+            execution.slices_done,
+            execution.remaining_quantity()
+        );
 
-        for hour in 0..duration_hours {
-            if remaining <= Decimal::ZERO {
+        self.run(execution, current_price).await
+    }
+
+    async fn run(&self, mut execution: AlgoExecution, current_price: Price) -> Result<VwapResult> {
+        let participation = &self.config.participation;
+        participation.validate()?;
+
+        let total = execution.total_quantity.as_decimal();
+        let window = self.config.end_time - self.config.start_time;
+        let hours = (window.num_seconds().max(0) as u32).div_ceil(3600);
+        let weights = self.hour_weights(hours);
+        let interval = Duration::seconds(execution.slice_interval_seconds as i64);
+
+        self.persist(&execution);
+        wait_until(self.config.start_time).await;
+
+        // Market volume traded since the last child order
+        let mut volume_mark = self
+            .trade_volumes
+            .as_ref()
+            .map(|volumes| volumes.volume(&self.symbol));
+        let mut unmatched = Decimal::ZERO;
+        let mut market_volume = Decimal::ZERO;
+
+        loop {
+            wait_until(execution.next_slice_at).await;
+            let now = Utc::now();
+            let remaining = execution.remaining_quantity();
+            if remaining <= Decimal::ZERO || now >= self.config.end_time {
                 break;
             }
+            execution.next_slice_at = now + interval;
+
+            let scheduled = total * profile_fraction(&weights, self.config.start_time, now);
+            let wanted = match (&self.trade_volumes, volume_mark) {
+                (Some(volumes), Some(mark)) => {
+                    let traded = volumes.volume(&self.symbol);
+                    volume_mark = Some(traded);
+                    market_volume += traded - mark;
+                    unmatched += traded - mark;
+                    participation.rate(execution.executed_quantity, scheduled, total) * unmatched
+                }
+                // No live volume: follow the profile
+                _ => scheduled - execution.executed_quantity,
+            };
 
-            // Get volume weight for this hour
-            let hour_of_day = (self.config.start_time + Duration::hours(hour as i64)).hour();
-
-            let volume_weight = self
-                .config
-                .volume_profile
-                .iter()
-                .find(|(h, _)| *h == hour_of_day)
-                .map(|(_, w)| *w)
-                .unwrap_or(dec!(4.0)); // Default weight
-
-            // Calculate slice size based on volume profile
-            let slice_ratio = volume_weight / total_volume_weight;
-            let slice_size = (self.config.total_quantity.as_decimal() * slice_ratio)
-                .max(self.config.min_slice_size.as_decimal())
-                .min(remaining);
+            // Let small amounts build up into a slice worth sending
+            let slice_size = wanted.min(remaining);
+            if slice_size <= Decimal::ZERO
+                || (slice_size < self.config.min_slice_size.as_decimal() && slice_size < remaining)
+            {
+                continue;
+            }
+            unmatched = Decimal::ZERO;
 
-            // Execute slice
-            let slice_price = self.calculate_price_with_offset(current_price);
+            let market_price = self
+                .trade_volumes
+                .as_ref()
+                .and_then(|volumes| volumes.tally(&self.symbol))
+                .and_then(|tally| Price::new(tally.last_price).ok())
+                .unwrap_or(current_price);
+            let slice_price = self.calculate_price_with_offset(market_price);
 
-            match self.execute_slice(slice_size, slice_price).await {
+            match self
+                .execute_slice(&mut execution, slice_size, slice_price)
+                .await
+            {
                 Ok(executed_qty) => {
                     let executed_dec = executed_qty.as_decimal();
-                    total_executed += executed_dec;
-                    total_cost += executed_dec * slice_price.as_decimal();
-                    remaining -= executed_dec;
-                    slices_executed += 1;
+                    execution.record_slice(executed_dec, slice_price, true);
 
                     debug!(
-                        "VWAP hour {} executed: {} @ {}",
-                        hour,
+                        "VWAP slice {} executed: {} @ {} ({} scheduled by now)",
+                        execution.slices_done,
                         executed_dec,
-                        slice_price.as_decimal()
+                        slice_price.as_decimal(),
+                        scheduled
                     );
                 }
                 Err(e) => {
-                    warn!("VWAP hour {} failed: {}", hour, e);
+                    warn!("VWAP slice {} failed: {}", execution.slices_done + 1, e);
+                    execution.record_slice(Decimal::ZERO, slice_price, false);
                 }
             }
 
-            // Wait for next hour
-            if hour < duration_hours - 1 {
-                tokio::time::sleep(tokio::time::Duration::from_secs(3600)).await;
-            }
+            self.persist(&execution);
         }
 
-        let avg_price = if total_executed > Decimal::ZERO {
-            Price::new(total_cost / total_executed)?
-        } else {
-            current_price
-        };
+        let shortfall = execution.remaining_quantity();
+        if shortfall > Decimal::ZERO {
+            warn!(
+                "VWAP {} ended with {} unexecuted at the end of its window",
+                execution.id, shortfall
+            );
+        }
+
+        if !execution.status.is_terminal() {
+            execution.finish(AlgoExecutionStatus::Completed, None);
+            self.persist(&execution);
+        }
+
+        let avg_price = execution.average_price().unwrap_or(current_price);
 
         // Calculate VWAP deviation
         let vwap_deviation_bps = ((avg_price.as_decimal() - current_price.as_decimal())
@@ -456,16 +1099,22 @@ impl VwapExecutor {
             * dec!(10000.0);
 
         let result = VwapResult {
-            total_executed: Quantity::new(total_executed)?,
+            total_executed: Quantity::new(execution.executed_quantity)?,
             average_price: avg_price,
-            slices_executed,
-            total_duration: Utc::now() - start_time,
+            slices_executed: execution.slices_done - execution.slices_failed,
+            total_duration: Utc::now() - execution.started_at,
             vwap_deviation_bps,
+            market_volume,
+            participation_rate: if market_volume > Decimal::ZERO {
+                execution.executed_quantity / market_volume
+            } else {
+                Decimal::ZERO
+            },
         };
 
         info!(
             "VWAP completed: executed {} @ avg {} (deviation: {} bps)",
-            total_executed,
+            execution.executed_quantity,
             avg_price.as_decimal(),
             vwap_deviation_bps
         );
@@ -473,8 +1122,56 @@ impl VwapExecutor {
         Ok(result)
     }
 
+    /// Weight of each hour of the window, from the learned profile when a
+    /// fresh one exists and the configured profile otherwise
+    fn hour_weights(&self, hours: u32) -> Vec<Decimal> {
+        let learned = self
+            .volume_profiles
+            .as_ref()
+            .and_then(|p| p.fresh_profile(&self.symbol, Utc::now()));
+        let hour_start = |hour: u32| self.config.start_time + Duration::hours(hour as i64);
+
+        if let Some(profile) = learned {
+            let weights: Vec<Decimal> = (0..hours)
+                .map(|h| profile.weight_at(hour_start(h)))
+                .collect();
+            if weights.iter().any(|w| !w.is_zero()) {
+                return weights;
+            }
+            warn!(
+                "Learned volume profile for {} has no volume in the VWAP window, using configured profile",
+                self.symbol.as_str()
+            );
+        }
+
+        (0..hours)
+            .map(|h| {
+                let hour_of_day = hour_start(h).hour();
+                self.config
+                    .volume_profile
+                    .iter()
+                    .find(|(h, _)| *h == hour_of_day)
+                    .map(|(_, w)| *w)
+                    .unwrap_or(dec!(4.0)) // Default weight
+            })
+            .collect()
+    }
+
+    fn persist(&self, execution: &AlgoExecution) {
+        if let Some(store) = &self.store
+            && let Err(e) = store.save(execution)
+        {
+            warn!("Failed to persist VWAP execution {}: {}", execution.id, e);
+        }
+    }
+
     /// Execute a single slice
-    async fn execute_slice(&self, quantity: Decimal, price: Price) -> Result<Quantity> {
+    async fn execute_slice(
+        &self,
+        execution: &mut AlgoExecution,
+        quantity: Decimal,
+        price: Price,
+    ) -> Result<Quantity> {
         let order = Order::new(
             Uuid::new_v4(),
             self.symbol.clone(),
@@ -484,7 +1181,11 @@ impl VwapExecutor {
             Some(price),
         );
 
-        let order_id = self.order_manager.submit_order(order).await?;
+        // Persisted before sending so a crash cannot orphan the order
+        execution.record_child_order(order.client_order_id.clone());
+        self.persist(execution);
+
+        self.order_manager.submit_order(order).await?;
 
         // Wait for fill (simplified)
         tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
@@ -506,12 +1207,47 @@ impl VwapExecutor {
     }
 }
 
+/// Price drift from the arrival price in basis points, positive when favorable
+fn drift_bps(reference: Price, current: Price, side: OrderSide) -> Decimal {
+    let reference = reference.as_decimal();
+    let move_bps = (current.as_decimal() - reference) / reference * dec!(10000.0);
+    match side {
+        OrderSide::Buy => -move_bps,
+        OrderSide::Sell => move_bps,
+    }
+}
+
+/// Share of the profile's volume due by `at`, with hourly `weights` from
+/// `start` and volume spread evenly within each hour
+fn profile_fraction(weights: &[Decimal], start: DateTime<Utc>, at: DateTime<Utc>) -> Decimal {
+    let total: Decimal = weights.iter().sum();
+    if total <= Decimal::ZERO {
+        return Decimal::ONE;
+    }
+
+    let elapsed = (at - start).num_seconds().max(0);
+    let full_hours = (elapsed / 3600) as usize;
+    let partial = Decimal::from(elapsed % 3600) / dec!(3600);
+    let due: Decimal = weights.iter().take(full_hours).sum::<Decimal>()
+        + weights
+            .get(full_hours)
+            .map_or(Decimal::ZERO, |w| *w * partial);
+    (due / total).min(Decimal::ONE)
+}
+
+/// Sleep until the given instant (returns immediately if it has passed)
+async fn wait_until(at: DateTime<Utc>) {
+    if let Ok(wait) = (at - Utc::now()).to_std() {
+        tokio::time::sleep(wait).await;
+    }
+}
+
 // Use a simple random implementation since we don't have rand crate
 mod rand {
     use std::cell::Cell;
 
     thread_local! {
-        static SEED: Cell<u64> = Cell::new(0x123456789abcdef);
+        static SEED: Cell<u64> = const { Cell::new(0x123456789abcdef) };
     }
 
     pub fn random<T: From<f64>>() -> T {
@@ -523,3 +1259,183 @@ mod rand {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drift_sign_follows_side() {
+        let reference = Price::new(dec!(100)).unwrap();
+        let lower = Price::new(dec!(99)).unwrap();
+
+        assert_eq!(drift_bps(reference, lower, OrderSide::Buy), dec!(100));
+        assert_eq!(drift_bps(reference, lower, OrderSide::Sell), dec!(-100));
+    }
+
+    #[test]
+    fn test_micro_timing_waits_for_lean_and_tight_spread() {
+        use ea_okx_client::models::{BookLevel, OrderBookData};
+
+        let level =
+            |price: &str, size: &str| BookLevel(price.into(), size.into(), "0".into(), "1".into());
+        let book = |bids: Vec<BookLevel>, asks: Vec<BookLevel>| {
+            let mut book = LocalOrderBook::default();
+            let data = OrderBookData {
+                inst_id: None,
+                asks,
+                bids,
+                ts: Utc::now().timestamp_millis().to_string(),
+                checksum: None,
+                prev_seq_id: None,
+                seq_id: None,
+            };
+            book.apply(&data, true).unwrap();
+            book
+        };
+        let config = MicroTimingConfig::default();
+
+        // Bids outweigh asks 3:1 with a 2 bps spread: send a buy, hold a sell
+        let leaning = book(
+            vec![level("100.00", "3"), level("99.99", "3")],
+            vec![level("100.02", "1"), level("100.03", "1")],
+        );
+        let (imbalance, spread, favorable) = config.assess(&leaning, OrderSide::Buy);
+        assert_eq!(imbalance, Some(dec!(0.5)));
+        assert!(spread.unwrap() < dec!(2.1));
+        assert!(favorable);
+        assert!(!config.assess(&leaning, OrderSide::Sell).2);
+
+        // Same lean but a 50 bps spread
+        let wide = book(vec![level("100.00", "3")], vec![level("100.50", "1")]);
+        assert!(!config.assess(&wide, OrderSide::Buy).2);
+        assert!(!config.assess(&LocalOrderBook::default(), OrderSide::Buy).2);
+
+        // A buy sent at a lower ask than when due gained
+        assert_eq!(
+            timing_benefit_bps(dec!(100), dec!(99.9), OrderSide::Buy),
+            Some(dec!(10))
+        );
+        assert_eq!(
+            timing_benefit_bps(dec!(100), dec!(99.9), OrderSide::Sell),
+            Some(dec!(-10))
+        );
+    }
+
+    #[test]
+    fn test_pacer_widens_then_goes_marketable() {
+        let config = AdaptiveTwapConfig {
+            widen_after_unfilled: 1,
+            widen_step_bps: 10,
+            max_offset_bps: 15,
+            marketable_after_unfilled: 3,
+            ..Default::default()
+        };
+        let mut pacer = AdaptivePacer::new(config, 0);
+
+        let decisions = pacer.on_slice_result(dec!(1), dec!(0.1));
+        assert_eq!(
+            decisions,
+            vec![AdaptationDecision::WidenOffset {
+                from_bps: 0,
+                to_bps: 10
+            }]
+        );
+
+        pacer.on_slice_result(dec!(1), Decimal::ZERO);
+        assert_eq!(pacer.offset_bps(), 15);
+        assert!(!pacer.is_marketable());
+
+        let decisions = pacer.on_slice_result(dec!(1), Decimal::ZERO);
+        assert_eq!(
+            decisions,
+            vec![AdaptationDecision::SwitchToMarketable { unfilled_slices: 3 }]
+        );
+        assert!(pacer.is_marketable());
+    }
+
+    #[test]
+    fn test_pacer_resets_on_fill() {
+        let mut pacer = AdaptivePacer::new(AdaptiveTwapConfig::default(), 0);
+
+        pacer.on_slice_result(dec!(1), Decimal::ZERO);
+        assert!(pacer.on_slice_result(dec!(1), dec!(0.9)).is_empty());
+        assert!(pacer.on_slice_result(dec!(1), Decimal::ZERO).is_empty());
+        assert_eq!(pacer.offset_bps(), 0);
+    }
+
+    #[test]
+    fn test_participation_follows_schedule_gap() {
+        let config = ParticipationConfig::default();
+        config.validate().unwrap();
+
+        // On schedule, then 2.5% and 10% of the parent order behind
+        assert_eq!(config.rate(dec!(5), dec!(5), dec!(100)), dec!(0.10));
+        assert_eq!(config.rate(dec!(5), dec!(7.5), dec!(100)), dec!(0.175));
+        assert_eq!(config.rate(dec!(5), dec!(15), dec!(100)), dec!(0.25));
+        // Far ahead slows to the floor
+        assert_eq!(config.rate(dec!(20), dec!(5), dec!(100)), dec!(0.02));
+
+        let inverted = ParticipationConfig {
+            min_rate: dec!(0.3),
+            ..Default::default()
+        };
+        assert!(inverted.validate().is_err());
+    }
+
+    #[test]
+    fn test_profile_fraction_spreads_each_hour() {
+        let start = Utc::now();
+        let weights = [dec!(1), dec!(3)];
+
+        assert_eq!(profile_fraction(&weights, start, start), Decimal::ZERO);
+        assert_eq!(
+            profile_fraction(&weights, start, start + Duration::minutes(30)),
+            dec!(0.125)
+        );
+        assert_eq!(
+            profile_fraction(&weights, start, start + Duration::minutes(90)),
+            dec!(0.625)
+        );
+        assert_eq!(
+            profile_fraction(&weights, start, start + Duration::hours(3)),
+            Decimal::ONE
+        );
+        assert_eq!(
+            profile_fraction(&[Decimal::ZERO], start, start),
+            Decimal::ONE
+        );
+    }
+
+    #[test]
+    fn test_pacer_price_drift() {
+        let config = AdaptiveTwapConfig {
+            max_pause_seconds: 90,
+            ..Default::default()
+        };
+        let mut pacer = AdaptivePacer::new(config, 0);
+
+        assert!(matches!(
+            pacer.on_price(dec!(25), 60),
+            Some(AdaptationDecision::Accelerate { .. })
+        ));
+        assert_eq!(pacer.on_price(dec!(5), 60), None);
+
+        // Pauses are capped by the total pause budget
+        assert_eq!(
+            pacer.on_price(dec!(-40), 60),
+            Some(AdaptationDecision::Pause {
+                drift_bps: dec!(-40),
+                seconds: 60
+            })
+        );
+        assert_eq!(
+            pacer.on_price(dec!(-40), 60),
+            Some(AdaptationDecision::Pause {
+                drift_bps: dec!(-40),
+                seconds: 30
+            })
+        );
+        assert_eq!(pacer.on_price(dec!(-40), 60), None);
+    }
+}
@@ -0,0 +1,479 @@
+//! Exchange-native take-profit/stop-loss brackets
+//!
+//! An opening order with both a stop loss and a take profit can carry them
+//! to OKX in the placement call itself: once the order fills the exchange
+//! arms them as a one-cancels-other algo order, so the position is protected
+//! even while this process is down. [`BracketManager`] keeps one bracket per
+//! strategy position and keeps its size in line with the position as it is
+//! added to or partly closed, cancelling it once the position is flat.
+
+use crate::error::{Error, Result};
+use crate::size_limits::TradeMode;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use ea_okx_client::OkxRestClient;
+use ea_okx_client::models::{
+    AmendAlgoOrderRequest, AttachedAlgoOrder, CancelAlgoOrderRequest, PlaceOrderRequest,
+};
+use ea_okx_core::Symbol;
+use ea_okx_core::models::{Order, OrderSide, OrderType};
+use parking_lot::RwLock;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Protective exits of a position, both triggering reduce-only market orders
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bracket {
+    pub stop_loss: Decimal,
+    pub take_profit: Decimal,
+}
+
+impl Bracket {
+    /// Check the exits sit on the right sides of an entry on `side`, and of
+    /// `entry_price` when there is one
+    pub fn validate(&self, side: OrderSide, entry_price: Option<Decimal>) -> Result<()> {
+        if self.stop_loss <= Decimal::ZERO || self.take_profit <= Decimal::ZERO {
+            return Err(Error::InvalidBracket(
+                "stop loss and take profit must be positive".to_string(),
+            ));
+        }
+        let (below, above) = match side {
+            OrderSide::Buy => (self.stop_loss, self.take_profit),
+            OrderSide::Sell => (self.take_profit, self.stop_loss),
+        };
+        let ordered = match entry_price {
+            Some(entry) => below < entry && entry < above,
+            None => below < above,
+        };
+        if !ordered {
+            return Err(Error::InvalidBracket(format!(
+                "stop loss {} and take profit {} do not bracket a {:?} entry at {}",
+                self.stop_loss,
+                self.take_profit,
+                side,
+                entry_price.map_or_else(|| "market".to_string(), |p| p.to_string())
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Bracket protecting one strategy position
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProtectedPosition {
+    pub strategy_id: Uuid,
+    pub symbol: Symbol,
+    pub bracket: Bracket,
+    /// Client algo order ID the exchange gives the armed bracket
+    pub attach_id: String,
+    /// Opening order the bracket was attached to
+    pub order_id: Uuid,
+    /// Size the bracket closes
+    pub quantity: Decimal,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// What reconciling a bracket against its position did
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BracketChange {
+    /// Bracket resized to the position
+    Resized { from: Decimal, to: Decimal },
+    /// Position is flat, bracket cancelled
+    Cancelled,
+}
+
+/// Exchange that can attach brackets to order placements
+#[async_trait]
+pub trait BracketVenue: Send + Sync {
+    /// Place `order` with `bracket` attached, named `attach_id` once armed;
+    /// returns the exchange order ID
+    async fn place_with_bracket(
+        &self,
+        order: &Order,
+        bracket: &Bracket,
+        attach_id: &str,
+    ) -> Result<String>;
+
+    /// Change the size the armed bracket closes
+    async fn resize(&self, symbol: &Symbol, attach_id: &str, quantity: Decimal) -> Result<()>;
+
+    /// Cancel the armed bracket
+    async fn cancel(&self, symbol: &Symbol, attach_id: &str) -> Result<()>;
+}
+
+/// OKX orders with attached TP/SL (`attachAlgoOrds`), armed as `oco` algo orders
+pub struct OkxBracketVenue {
+    client: Arc<OkxRestClient>,
+    trade_mode: TradeMode,
+}
+
+impl OkxBracketVenue {
+    pub fn new(client: Arc<OkxRestClient>, trade_mode: TradeMode) -> Self {
+        Self { client, trade_mode }
+    }
+}
+
+#[async_trait]
+impl BracketVenue for OkxBracketVenue {
+    async fn place_with_bracket(
+        &self,
+        order: &Order,
+        bracket: &Bracket,
+        attach_id: &str,
+    ) -> Result<String> {
+        let ord_type = match order.order_type {
+            OrderType::Market => "market",
+            OrderType::Limit => "limit",
+            OrderType::PostOnly => "post_only",
+            OrderType::Ioc => "ioc",
+            OrderType::Fok => "fok",
+            other => {
+                return Err(Error::InvalidBracket(format!(
+                    "{:?} orders cannot carry a bracket",
+                    other
+                )));
+            }
+        };
+        let request = PlaceOrderRequest {
+            inst_id: order.symbol.as_str().to_string(),
+            td_mode: self.trade_mode.as_str().to_string(),
+            side: match order.side {
+                OrderSide::Buy => "buy",
+                OrderSide::Sell => "sell",
+            }
+            .to_string(),
+            ord_type: ord_type.to_string(),
+            sz: order.quantity.as_decimal().normalize().to_string(),
+            px: order.price.map(|p| p.as_decimal().normalize().to_string()),
+            cl_ord_id: Some(order.client_order_id.clone()),
+            tag: order.tag.clone(),
+            reduce_only: None,
+            attach_algo_ords: Some(vec![AttachedAlgoOrder {
+                attach_algo_cl_ord_id: Some(attach_id.to_string()),
+                tp_trigger_px: Some(bracket.take_profit.normalize().to_string()),
+                tp_ord_px: Some("-1".to_string()),
+                sl_trigger_px: Some(bracket.stop_loss.normalize().to_string()),
+                sl_ord_px: Some("-1".to_string()),
+            }]),
+        };
+        Ok(self.client.place_order(&request).await?.ord_id)
+    }
+
+    async fn resize(&self, symbol: &Symbol, attach_id: &str, quantity: Decimal) -> Result<()> {
+        let request = AmendAlgoOrderRequest {
+            inst_id: symbol.as_str().to_string(),
+            algo_cl_ord_id: Some(attach_id.to_string()),
+            new_sz: Some(quantity.normalize().to_string()),
+            ..Default::default()
+        };
+        self.client.amend_algo_order(&request).await?;
+        Ok(())
+    }
+
+    async fn cancel(&self, symbol: &Symbol, attach_id: &str) -> Result<()> {
+        let request = CancelAlgoOrderRequest {
+            inst_id: symbol.as_str().to_string(),
+            algo_cl_ord_id: Some(attach_id.to_string()),
+            ..Default::default()
+        };
+        self.client.cancel_algo_orders(&[request]).await?;
+        Ok(())
+    }
+}
+
+/// Exchange-side brackets of open positions, one per strategy and symbol
+#[derive(Default)]
+pub struct BracketManager {
+    venue: Option<Arc<dyn BracketVenue>>,
+    brackets: RwLock<HashMap<(Uuid, Symbol), ProtectedPosition>>,
+}
+
+impl BracketManager {
+    /// Manager without a venue: nothing is placed
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Place bracketed orders on `venue`
+    pub fn with_venue(mut self, venue: Arc<dyn BracketVenue>) -> Self {
+        self.venue = Some(venue);
+        self
+    }
+
+    /// Whether bracketed orders can be placed
+    pub fn is_enabled(&self) -> bool {
+        self.venue.is_some()
+    }
+
+    /// Place an opening order with `bracket` attached, returning the exchange
+    /// order ID
+    ///
+    /// A bracket already on the position is cancelled once the new one is
+    /// placed; the new one is resized to the whole position when the fill is
+    /// reconciled.
+    pub async fn place(&self, order: &Order, bracket: Bracket) -> Result<String> {
+        let venue = self
+            .venue
+            .as_ref()
+            .ok_or_else(|| Error::ExecutionError("no bracket venue configured".to_string()))?;
+        if order.reduce_only {
+            return Err(Error::InvalidBracket(
+                "reduce-only orders do not open a position to protect".to_string(),
+            ));
+        }
+        bracket.validate(order.side, order.price.map(|p| p.as_decimal()))?;
+
+        let attach_id = Uuid::new_v4().simple().to_string();
+        let exchange_id = venue
+            .place_with_bracket(order, &bracket, &attach_id)
+            .await?;
+        info!(
+            "Placed {} with stop loss {} and take profit {} on the exchange",
+            order.id, bracket.stop_loss, bracket.take_profit
+        );
+
+        let key = (order.strategy_id, order.symbol.clone());
+        let replaced = self.brackets.write().insert(
+            key,
+            ProtectedPosition {
+                strategy_id: order.strategy_id,
+                symbol: order.symbol.clone(),
+                bracket,
+                attach_id,
+                order_id: order.id,
+                quantity: order.quantity.as_decimal(),
+                updated_at: Utc::now(),
+            },
+        );
+        if let Some(replaced) = replaced
+            && let Err(e) = venue.cancel(&replaced.symbol, &replaced.attach_id).await
+        {
+            warn!(
+                "Failed to cancel replaced bracket {}: {}",
+                replaced.attach_id, e
+            );
+        }
+        Ok(exchange_id)
+    }
+
+    /// Bring the position's bracket in line with its net size `position`
+    /// (positive long, negative short)
+    ///
+    /// A flat position cancels the bracket; failing to cancel is only logged,
+    /// since a bracket that closed the position is already gone.
+    pub async fn reconcile(
+        &self,
+        strategy_id: Uuid,
+        symbol: &Symbol,
+        position: Decimal,
+    ) -> Result<Option<BracketChange>> {
+        let Some(venue) = &self.venue else {
+            return Ok(None);
+        };
+        let key = (strategy_id, symbol.clone());
+        let Some(current) = self.brackets.read().get(&key).cloned() else {
+            return Ok(None);
+        };
+
+        if position.is_zero() {
+            self.brackets.write().remove(&key);
+            if let Err(e) = venue.cancel(symbol, &current.attach_id).await {
+                info!(
+                    "Bracket {} not cancelled, likely already triggered: {}",
+                    current.attach_id, e
+                );
+            }
+            return Ok(Some(BracketChange::Cancelled));
+        }
+
+        let size = position.abs();
+        if size == current.quantity {
+            return Ok(None);
+        }
+        venue.resize(symbol, &current.attach_id, size).await?;
+        if let Some(protected) = self.brackets.write().get_mut(&key) {
+            protected.quantity = size;
+            protected.updated_at = Utc::now();
+        }
+        Ok(Some(BracketChange::Resized {
+            from: current.quantity,
+            to: size,
+        }))
+    }
+
+    /// Bracket protecting the strategy's position, if any
+    pub fn bracket(&self, strategy_id: Uuid, symbol: &Symbol) -> Option<ProtectedPosition> {
+        self.brackets
+            .read()
+            .get(&(strategy_id, symbol.clone()))
+            .cloned()
+    }
+
+    /// Every tracked bracket
+    pub fn brackets(&self) -> Vec<ProtectedPosition> {
+        self.brackets.read().values().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ea_okx_core::{Price, Quantity};
+    use parking_lot::Mutex;
+    use rust_decimal_macros::dec;
+
+    #[derive(Default)]
+    struct RecordingVenue {
+        placed: Mutex<Vec<String>>,
+        resized: Mutex<Vec<(String, Decimal)>>,
+        cancelled: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl BracketVenue for RecordingVenue {
+        async fn place_with_bracket(
+            &self,
+            order: &Order,
+            _bracket: &Bracket,
+            attach_id: &str,
+        ) -> Result<String> {
+            self.placed.lock().push(attach_id.to_string());
+            Ok(format!("okx-{}", order.id))
+        }
+
+        async fn resize(&self, _symbol: &Symbol, attach_id: &str, quantity: Decimal) -> Result<()> {
+            self.resized.lock().push((attach_id.to_string(), quantity));
+            Ok(())
+        }
+
+        async fn cancel(&self, _symbol: &Symbol, attach_id: &str) -> Result<()> {
+            self.cancelled.lock().push(attach_id.to_string());
+            Ok(())
+        }
+    }
+
+    fn buy(strategy_id: Uuid, quantity: Decimal) -> Order {
+        Order::new(
+            strategy_id,
+            Symbol::new("BTC-USDT-SWAP").unwrap(),
+            OrderSide::Buy,
+            OrderType::Limit,
+            Quantity::new(quantity).unwrap(),
+            Some(Price::new(dec!(50000)).unwrap()),
+        )
+    }
+
+    fn bracket() -> Bracket {
+        Bracket {
+            stop_loss: dec!(49000),
+            take_profit: dec!(52000),
+        }
+    }
+
+    #[test]
+    fn test_validate_sides() {
+        assert!(
+            bracket()
+                .validate(OrderSide::Buy, Some(dec!(50000)))
+                .is_ok()
+        );
+        assert!(
+            bracket()
+                .validate(OrderSide::Sell, Some(dec!(50000)))
+                .is_err()
+        );
+        assert!(
+            bracket()
+                .validate(OrderSide::Buy, Some(dec!(53000)))
+                .is_err()
+        );
+
+        let short = Bracket {
+            stop_loss: dec!(52000),
+            take_profit: dec!(49000),
+        };
+        assert!(short.validate(OrderSide::Sell, None).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_bracket_follows_position_size() {
+        let venue = Arc::new(RecordingVenue::default());
+        let manager = BracketManager::new().with_venue(venue.clone());
+        let strategy_id = Uuid::new_v4();
+        let order = buy(strategy_id, dec!(2));
+        let symbol = order.symbol.clone();
+
+        manager.place(&order, bracket()).await.unwrap();
+        let attach_id = manager.bracket(strategy_id, &symbol).unwrap().attach_id;
+        assert_eq!(venue.placed.lock()[0], attach_id);
+
+        // Filled as placed: nothing to change
+        assert_eq!(
+            manager
+                .reconcile(strategy_id, &symbol, dec!(2))
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            manager
+                .reconcile(strategy_id, &symbol, dec!(0.5))
+                .await
+                .unwrap(),
+            Some(BracketChange::Resized {
+                from: dec!(2),
+                to: dec!(0.5)
+            })
+        );
+        assert_eq!(venue.resized.lock()[0], (attach_id.clone(), dec!(0.5)));
+
+        assert_eq!(
+            manager
+                .reconcile(strategy_id, &symbol, Decimal::ZERO)
+                .await
+                .unwrap(),
+            Some(BracketChange::Cancelled)
+        );
+        assert_eq!(venue.cancelled.lock().as_slice(), [attach_id]);
+        assert!(manager.bracket(strategy_id, &symbol).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_new_bracket_replaces_old_and_rejects_bad_levels() {
+        let venue = Arc::new(RecordingVenue::default());
+        let manager = BracketManager::new().with_venue(venue.clone());
+        let strategy_id = Uuid::new_v4();
+
+        manager
+            .place(&buy(strategy_id, dec!(1)), bracket())
+            .await
+            .unwrap();
+        let first = venue.placed.lock()[0].clone();
+        manager
+            .place(&buy(strategy_id, dec!(1)), bracket())
+            .await
+            .unwrap();
+        assert_eq!(venue.cancelled.lock().as_slice(), [first]);
+        assert_eq!(manager.brackets().len(), 1);
+
+        let inverted = Bracket {
+            stop_loss: dec!(52000),
+            take_profit: dec!(49000),
+        };
+        assert!(matches!(
+            manager.place(&buy(strategy_id, dec!(1)), inverted).await,
+            Err(Error::InvalidBracket(_))
+        ));
+        assert!(
+            BracketManager::new()
+                .place(&buy(strategy_id, dec!(1)), bracket())
+                .await
+                .is_err()
+        );
+    }
+}
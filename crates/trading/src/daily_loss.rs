@@ -0,0 +1,458 @@
+//! Account-level daily loss lock
+//!
+//! Realized and unrealized P&L is tracked per account for each UTC trading
+//! day. Once an account's total for the day falls to `-limit` it is locked:
+//! the gate refuses its orders that are not reduce-only, so positions can
+//! still be closed but no new risk is taken on. The lock lifts by itself at
+//! the next trading day, or earlier by an override that has to spell out a
+//! confirmation phrase naming the account. An overridden account stays
+//! unlocked for the rest of the day.
+//!
+//! Strategies trade on [`DEFAULT_ACCOUNT`] unless assigned to another one.
+
+use crate::error::{Error, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use ea_okx_core::models::Order;
+use parking_lot::RwLock;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Account strategies trade on unless assigned elsewhere
+pub const DEFAULT_ACCOUNT: &str = "main";
+
+/// One account's P&L for one trading day
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DailyPnl {
+    pub account: String,
+    pub trading_day: NaiveDate,
+    pub realized: Decimal,
+
+    /// Unrealized P&L of open positions, as last reported
+    pub unrealized: Decimal,
+
+    /// When the limit was hit, while locked
+    pub locked_at: Option<DateTime<Utc>>,
+
+    /// Who lifted the lock early, if anyone did
+    pub overridden_by: Option<String>,
+}
+
+impl DailyPnl {
+    fn new(account: &str, trading_day: NaiveDate) -> Self {
+        Self {
+            account: account.to_string(),
+            trading_day,
+            realized: Decimal::ZERO,
+            unrealized: Decimal::ZERO,
+            locked_at: None,
+            overridden_by: None,
+        }
+    }
+
+    pub fn total(&self) -> Decimal {
+        self.realized + self.unrealized
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked_at.is_some()
+    }
+}
+
+/// Why a lock was lifted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum UnlockReason {
+    /// A new trading day started
+    Rollover,
+    Override {
+        by: String,
+    },
+}
+
+/// Lock state changes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DailyLossEvent {
+    Locked {
+        account: String,
+        trading_day: NaiveDate,
+        pnl: Decimal,
+        limit: Decimal,
+        at: DateTime<Utc>,
+    },
+    Unlocked {
+        account: String,
+        trading_day: NaiveDate,
+        reason: UnlockReason,
+        at: DateTime<Utc>,
+    },
+}
+
+/// Daily P&L and lock state of every account
+#[derive(Debug)]
+pub struct DailyLossLock {
+    /// Loss (positive) at which an account locks; zero disables the lock
+    limit: RwLock<Decimal>,
+    days: RwLock<HashMap<String, DailyPnl>>,
+    strategy_accounts: RwLock<HashMap<Uuid, String>>,
+    event_tx: mpsc::UnboundedSender<DailyLossEvent>,
+    event_rx: RwLock<Option<mpsc::UnboundedReceiver<DailyLossEvent>>>,
+}
+
+impl DailyLossLock {
+    pub fn new(limit: Decimal) -> Self {
+        let (event_tx, event_rx) = mpsc::unbounded_channel();
+
+        Self {
+            limit: RwLock::new(limit),
+            days: RwLock::new(HashMap::new()),
+            strategy_accounts: RwLock::new(HashMap::new()),
+            event_tx,
+            event_rx: RwLock::new(Some(event_rx)),
+        }
+    }
+
+    pub fn limit(&self) -> Decimal {
+        *self.limit.read()
+    }
+
+    /// Change the limit; takes effect with the next P&L update
+    pub fn set_limit(&self, limit: Decimal) {
+        *self.limit.write() = limit;
+    }
+
+    /// Count the strategy's orders and P&L against `account`
+    pub fn assign_strategy(&self, strategy_id: Uuid, account: impl Into<String>) {
+        self.strategy_accounts
+            .write()
+            .insert(strategy_id, account.into());
+    }
+
+    pub fn account_for(&self, strategy_id: Uuid) -> String {
+        self.strategy_accounts
+            .read()
+            .get(&strategy_id)
+            .cloned()
+            .unwrap_or_else(|| DEFAULT_ACCOUNT.to_string())
+    }
+
+    /// Add realized P&L (a loss is negative) to the account's day
+    pub fn record_realized(
+        &self,
+        account: &str,
+        pnl: Decimal,
+        at: DateTime<Utc>,
+    ) -> Option<DailyLossEvent> {
+        self.update(account, at, |day| day.realized += pnl)
+    }
+
+    /// Replace the account's unrealized P&L
+    pub fn set_unrealized(
+        &self,
+        account: &str,
+        unrealized: Decimal,
+        at: DateTime<Utc>,
+    ) -> Option<DailyLossEvent> {
+        self.update(account, at, |day| day.unrealized = unrealized)
+    }
+
+    /// Start a new trading day for every account still on an earlier one,
+    /// unlocking the locked ones
+    pub fn roll_over(&self, now: DateTime<Utc>) -> Vec<DailyLossEvent> {
+        let today = now.date_naive();
+        let mut days = self.days.write();
+        let events: Vec<DailyLossEvent> = days
+            .values_mut()
+            .filter(|day| day.trading_day < today)
+            .filter_map(|day| roll(day, today, now))
+            .collect();
+        drop(days);
+
+        for event in &events {
+            self.publish(event);
+        }
+        events
+    }
+
+    /// Phrase an override of `account`'s lock must be confirmed with
+    pub fn override_phrase(account: &str) -> String {
+        format!("UNLOCK {}", account)
+    }
+
+    /// Lift `account`'s lock for the rest of the trading day
+    ///
+    /// `confirmation` must equal [`override_phrase`](Self::override_phrase).
+    pub fn override_lock(
+        &self,
+        account: &str,
+        confirmation: &str,
+        by: &str,
+        at: DateTime<Utc>,
+    ) -> Result<DailyLossEvent> {
+        let expected = Self::override_phrase(account);
+        if confirmation.trim() != expected {
+            return Err(Error::DailyLossLocked(format!(
+                "Override of {} must be confirmed with \"{}\"",
+                account, expected
+            )));
+        }
+
+        let mut days = self.days.write();
+        let Some(day) = days
+            .get_mut(account)
+            .filter(|day| day.trading_day == at.date_naive() && day.is_locked())
+        else {
+            return Err(Error::DailyLossLocked(format!(
+                "Account {} is not locked",
+                account
+            )));
+        };
+        day.locked_at = None;
+        day.overridden_by = Some(by.to_string());
+        let event = DailyLossEvent::Unlocked {
+            account: account.to_string(),
+            trading_day: day.trading_day,
+            reason: UnlockReason::Override { by: by.to_string() },
+            at,
+        };
+        drop(days);
+
+        warn!("Daily loss lock of {} overridden by {}", account, by);
+        self.publish(&event);
+        Ok(event)
+    }
+
+    /// The account's current trading day, if it has P&L today
+    pub fn status(&self, account: &str) -> Option<DailyPnl> {
+        self.days
+            .read()
+            .get(account)
+            .filter(|day| day.trading_day == Utc::now().date_naive())
+            .cloned()
+    }
+
+    /// Latest trading day of every account
+    pub fn statuses(&self) -> Vec<DailyPnl> {
+        let mut days: Vec<DailyPnl> = self.days.read().values().cloned().collect();
+        days.sort_by(|a, b| a.account.cmp(&b.account));
+        days
+    }
+
+    pub fn is_locked(&self, account: &str) -> bool {
+        self.is_locked_at(account, Utc::now())
+    }
+
+    /// Whether `account` is locked on the trading day of `now`; a lock from
+    /// an earlier day counts as lifted even before [`roll_over`](Self::roll_over)
+    pub fn is_locked_at(&self, account: &str, now: DateTime<Utc>) -> bool {
+        self.days
+            .read()
+            .get(account)
+            .is_some_and(|day| day.trading_day == now.date_naive() && day.is_locked())
+    }
+
+    /// Why `order` may not be sent, if its account is locked and it could
+    /// add risk
+    pub fn blocked_reason(&self, order: &Order) -> Option<String> {
+        if order.reduce_only {
+            return None;
+        }
+        let account = self.account_for(order.strategy_id);
+        let days = self.days.read();
+        let day = days
+            .get(&account)
+            .filter(|day| day.trading_day == Utc::now().date_naive() && day.is_locked())?;
+        Some(format!(
+            "Daily loss limit hit on account {} ({} against a limit of {}), \
+             only reduce-only orders are sent until the next UTC day",
+            account,
+            day.total(),
+            self.limit()
+        ))
+    }
+
+    /// Take the lock and unlock stream (once)
+    pub fn subscribe_events(&self) -> Option<mpsc::UnboundedReceiver<DailyLossEvent>> {
+        self.event_rx.write().take()
+    }
+
+    fn update(
+        &self,
+        account: &str,
+        at: DateTime<Utc>,
+        apply: impl FnOnce(&mut DailyPnl),
+    ) -> Option<DailyLossEvent> {
+        let limit = self.limit();
+        let today = at.date_naive();
+        let mut days = self.days.write();
+        let day = days
+            .entry(account.to_string())
+            .or_insert_with(|| DailyPnl::new(account, today));
+
+        let mut events = Vec::new();
+        if day.trading_day < today {
+            events.extend(roll(day, today, at));
+        }
+        apply(day);
+
+        let breached = limit > Decimal::ZERO && day.total() <= -limit;
+        if breached && !day.is_locked() && day.overridden_by.is_none() {
+            day.locked_at = Some(at);
+            warn!(
+                "Daily loss limit hit on {}: {} against {}",
+                account,
+                day.total(),
+                limit
+            );
+            events.push(DailyLossEvent::Locked {
+                account: account.to_string(),
+                trading_day: day.trading_day,
+                pnl: day.total(),
+                limit,
+                at,
+            });
+        }
+        drop(days);
+
+        for event in &events {
+            self.publish(event);
+        }
+        events.pop()
+    }
+
+    fn publish(&self, event: &DailyLossEvent) {
+        let _ = self.event_tx.send(event.clone());
+    }
+}
+
+impl Default for DailyLossLock {
+    /// Disabled until a limit is set
+    fn default() -> Self {
+        Self::new(Decimal::ZERO)
+    }
+}
+
+/// Start `today` for `day`'s account, unlocking it
+///
+/// Both totals restart at zero. Unrealized P&L comes back with the next
+/// report, measured from entry rather than from the start of the day.
+fn roll(day: &mut DailyPnl, today: NaiveDate, at: DateTime<Utc>) -> Option<DailyLossEvent> {
+    let was_locked = day.is_locked();
+    let previous = day.trading_day;
+    *day = DailyPnl::new(&day.account, today);
+    info!("Trading day {} started for {}", today, day.account);
+
+    was_locked.then(|| DailyLossEvent::Unlocked {
+        account: day.account.clone(),
+        trading_day: previous,
+        reason: UnlockReason::Rollover,
+        at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use ea_okx_core::models::{OrderSide, OrderType};
+    use ea_okx_core::{Quantity, Symbol};
+    use rust_decimal_macros::dec;
+
+    fn order(strategy_id: Uuid, reduce_only: bool) -> Order {
+        let order = Order::new(
+            strategy_id,
+            Symbol::new("BTC-USDT").unwrap(),
+            OrderSide::Buy,
+            OrderType::Market,
+            Quantity::new(dec!(1)).unwrap(),
+            None,
+        );
+        if reduce_only {
+            order.with_reduce_only()
+        } else {
+            order
+        }
+    }
+
+    #[test]
+    fn test_realized_and_unrealized_losses_lock_until_rollover() {
+        let lock = DailyLossLock::new(dec!(1000));
+        let mut events = lock.subscribe_events().unwrap();
+        let morning = Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap();
+
+        assert!(lock.record_realized("main", dec!(-600), morning).is_none());
+        let locked = lock
+            .set_unrealized("main", dec!(-450), morning + Duration::hours(1))
+            .unwrap();
+        assert!(matches!(
+            locked,
+            DailyLossEvent::Locked { pnl, .. } if pnl == dec!(-1050)
+        ));
+        assert!(lock.is_locked_at("main", morning));
+        assert!(!lock.is_locked_at("other", morning));
+        assert_eq!(events.try_recv().unwrap(), locked);
+
+        // Recovering within the day does not unlock
+        assert!(lock.set_unrealized("main", dec!(500), morning).is_none());
+        assert!(lock.is_locked_at("main", morning));
+
+        let next_day = morning + Duration::days(1);
+        assert!(!lock.is_locked_at("main", next_day));
+        let unlocked = lock.roll_over(next_day);
+        assert_eq!(unlocked.len(), 1);
+        assert!(matches!(
+            &unlocked[0],
+            DailyLossEvent::Unlocked {
+                reason: UnlockReason::Rollover,
+                ..
+            }
+        ));
+        assert_eq!(lock.statuses()[0].total(), Decimal::ZERO);
+        assert!(lock.roll_over(next_day).is_empty());
+    }
+
+    #[test]
+    fn test_locked_account_allows_only_reduce_only_orders() {
+        let lock = DailyLossLock::new(dec!(100));
+        let strategy = Uuid::new_v4();
+        let elsewhere = Uuid::new_v4();
+        lock.assign_strategy(elsewhere, "sub-1");
+
+        lock.record_realized(DEFAULT_ACCOUNT, dec!(-150), Utc::now());
+        assert!(lock.blocked_reason(&order(strategy, false)).is_some());
+        assert!(lock.blocked_reason(&order(strategy, true)).is_none());
+        assert!(lock.blocked_reason(&order(elsewhere, false)).is_none());
+    }
+
+    #[test]
+    fn test_override_needs_the_confirmation_phrase() {
+        let lock = DailyLossLock::new(dec!(100));
+        let now = Utc::now();
+        assert!(
+            lock.override_lock("main", "UNLOCK main", "ops", now)
+                .is_err()
+        );
+
+        lock.record_realized("main", dec!(-200), now);
+        assert!(lock.override_lock("main", "yes", "ops", now).is_err());
+        assert!(lock.is_locked_at("main", now));
+
+        let event = lock
+            .override_lock("main", "UNLOCK main", "ops", now)
+            .unwrap();
+        assert!(matches!(
+            event,
+            DailyLossEvent::Unlocked {
+                reason: UnlockReason::Override { .. },
+                ..
+            }
+        ));
+        // Further losses today do not lock again
+        assert!(lock.record_realized("main", dec!(-200), now).is_none());
+        assert!(!lock.is_locked_at("main", now));
+    }
+}
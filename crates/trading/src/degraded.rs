@@ -0,0 +1,208 @@
+//! Degraded-mode trading policy
+//!
+//! While the exchange is degraded (slow or failing REST calls, a silent
+//! market data feed) the system should not take on new risk, but must keep
+//! managing the risk it has. The gate consults [`DegradedMode`] to refuse
+//! orders that are not reduce-only, and limit orders that do go out have
+//! their price moved further through the book so exits still fill against
+//! a market the system may be seeing late.
+
+use chrono::{DateTime, Utc};
+use ea_okx_core::Price;
+use ea_okx_core::models::{Order, OrderSide};
+use parking_lot::RwLock;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+/// What changes while the exchange is degraded
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DegradedModePolicy {
+    /// Refuse orders that are not reduce-only
+    pub block_opening_orders: bool,
+
+    /// Basis points limit prices are moved towards the other side of the book
+    pub limit_offset_bps: u32,
+}
+
+impl Default for DegradedModePolicy {
+    fn default() -> Self {
+        Self {
+            block_opening_orders: true,
+            limit_offset_bps: 20,
+        }
+    }
+}
+
+/// Why and since when the exchange is degraded
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DegradedState {
+    pub reasons: Vec<String>,
+    pub since: DateTime<Utc>,
+}
+
+/// Degraded-mode switch and policy shared through the execution gate
+#[derive(Debug, Default)]
+pub struct DegradedMode {
+    policy: RwLock<DegradedModePolicy>,
+    state: RwLock<Option<DegradedState>>,
+}
+
+impl DegradedMode {
+    pub fn new(policy: DegradedModePolicy) -> Self {
+        Self {
+            policy: RwLock::new(policy),
+            state: RwLock::new(None),
+        }
+    }
+
+    pub fn policy(&self) -> DegradedModePolicy {
+        self.policy.read().clone()
+    }
+
+    pub fn set_policy(&self, policy: DegradedModePolicy) {
+        *self.policy.write() = policy;
+    }
+
+    /// Enter degraded mode, or update the reasons if already in it
+    ///
+    /// Returns whether this call entered it.
+    pub fn enter(&self, reasons: Vec<String>) -> bool {
+        let mut state = self.state.write();
+        match state.as_mut() {
+            Some(current) => {
+                current.reasons = reasons;
+                false
+            }
+            None => {
+                warn!("Entering degraded mode: {}", reasons.join("; "));
+                *state = Some(DegradedState {
+                    reasons,
+                    since: Utc::now(),
+                });
+                true
+            }
+        }
+    }
+
+    /// Return to normal trading, yielding the state that was left
+    pub fn exit(&self) -> Option<DegradedState> {
+        let left = self.state.write().take();
+        if let Some(state) = &left {
+            info!("Leaving degraded mode entered at {}", state.since);
+        }
+        left
+    }
+
+    pub fn state(&self) -> Option<DegradedState> {
+        self.state.read().clone()
+    }
+
+    pub fn is_degraded(&self) -> bool {
+        self.state.read().is_some()
+    }
+
+    /// Why `order` may not be sent right now, if it may not
+    pub fn blocked_reason(&self, order: &Order) -> Option<String> {
+        if order.reduce_only || !self.policy.read().block_opening_orders {
+            return None;
+        }
+        self.state.read().as_ref().map(|state| {
+            format!(
+                "Exchange degraded, only reduce-only orders are sent: {}",
+                state.reasons.join("; ")
+            )
+        })
+    }
+
+    /// Move a limit order's price `limit_offset_bps` through the book while
+    /// degraded, returning the original price if it changed
+    pub fn widen_limit(&self, order: &mut Order) -> Option<Price> {
+        let bps = self.policy.read().limit_offset_bps;
+        if bps == 0 || !self.is_degraded() {
+            return None;
+        }
+        let original = order.price?;
+        let price = original.as_decimal();
+        let offset = price * Decimal::from(bps) / Decimal::from(10_000);
+        let widened = match order.side {
+            OrderSide::Buy => price + offset,
+            OrderSide::Sell => price - offset,
+        };
+        let widened = Price::new(widened.round_dp(price.scale())).ok()?;
+        if widened == original {
+            return None;
+        }
+
+        order.price = Some(widened);
+        Some(original)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ea_okx_core::models::OrderType;
+    use ea_okx_core::{Quantity, Symbol};
+    use rust_decimal_macros::dec;
+    use uuid::Uuid;
+
+    fn limit(side: OrderSide, price: Decimal) -> Order {
+        Order::new(
+            Uuid::new_v4(),
+            Symbol::new("BTC-USDT").unwrap(),
+            side,
+            OrderType::Limit,
+            Quantity::new(dec!(0.1)).unwrap(),
+            Some(Price::new(price).unwrap()),
+        )
+    }
+
+    #[test]
+    fn test_only_reduce_only_orders_pass_while_degraded() {
+        let mode = DegradedMode::default();
+        let open = limit(OrderSide::Buy, dec!(50000));
+        let close = limit(OrderSide::Sell, dec!(50000)).with_reduce_only();
+        assert!(mode.blocked_reason(&open).is_none());
+
+        assert!(mode.enter(vec!["REST error rate".to_string()]));
+        assert!(!mode.enter(vec!["No market data".to_string()]));
+        assert!(
+            mode.blocked_reason(&open)
+                .unwrap()
+                .contains("No market data")
+        );
+        assert!(mode.blocked_reason(&close).is_none());
+
+        mode.set_policy(DegradedModePolicy {
+            block_opening_orders: false,
+            ..Default::default()
+        });
+        assert!(mode.blocked_reason(&open).is_none());
+
+        assert!(mode.exit().is_some());
+        assert!(mode.exit().is_none());
+    }
+
+    #[test]
+    fn test_limit_prices_widen_through_the_book() {
+        let mode = DegradedMode::default();
+        let mut buy = limit(OrderSide::Buy, dec!(50000.0));
+        assert!(mode.widen_limit(&mut buy).is_none());
+
+        mode.enter(vec!["REST latency".to_string()]);
+        assert_eq!(
+            mode.widen_limit(&mut buy),
+            Some(Price::new(dec!(50000.0)).unwrap())
+        );
+        assert_eq!(buy.price.unwrap().as_decimal(), dec!(50100.0));
+
+        let mut sell = limit(OrderSide::Sell, dec!(2000.00));
+        mode.widen_limit(&mut sell);
+        assert_eq!(sell.price.unwrap().as_decimal(), dec!(1996.00));
+
+        let mut market = limit(OrderSide::Sell, dec!(2000));
+        market.price = None;
+        assert!(mode.widen_limit(&mut market).is_none());
+    }
+}
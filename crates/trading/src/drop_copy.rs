@@ -0,0 +1,766 @@
+//! Order lifecycle journal and compliance drop copies
+//!
+//! [`OrderJournal`] appends every step of an order's life (accepted, sent,
+//! filled in part or in full, cancelled, expired, rejected or failed) to one
+//! JSON Lines file per UTC day. Entries are only ever appended and each
+//! carries the order as it stood at that step. The journal is fed snapshots
+//! of orders as they change; steps an order went through between two
+//! snapshots, such as being sent and filled before it was first seen, are
+//! filled in from the order's own timestamps.
+//!
+//! [`OrderJournal::export`] turns a finished day of the journal into a drop
+//! copy for external compliance archives: one record per event in the order
+//! the events happened, as CSV or as FIX 4.4 style execution reports
+//! (`tag=value` pairs separated by `|`). The file is written once and made
+//! read-only, and its SHA-256 is written next to it in a `.sha256` file that
+//! `sha256sum -c` can check. Exporting the same day again verifies and
+//! returns the existing file rather than writing a new one.
+
+use crate::error::{Error, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use ea_okx_core::models::{Order, OrderSide, OrderStatus, OrderType};
+use parking_lot::Mutex;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Step in an order's life
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LifecycleEvent {
+    /// Accepted by the engine, not sent yet
+    New,
+    /// Sent to and acknowledged by the exchange
+    Submitted,
+    PartialFill,
+    Fill,
+    Cancelled,
+    /// Cancelled on reaching its good-till-date
+    Expired,
+    Rejected,
+    Failed,
+}
+
+impl LifecycleEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::New => "new",
+            Self::Submitted => "submitted",
+            Self::PartialFill => "partial_fill",
+            Self::Fill => "fill",
+            Self::Cancelled => "cancelled",
+            Self::Expired => "expired",
+            Self::Rejected => "rejected",
+            Self::Failed => "failed",
+        }
+    }
+
+    /// FIX `ExecType` (150)
+    fn exec_type(&self) -> &'static str {
+        match self {
+            Self::New => "A",
+            Self::Submitted => "0",
+            Self::PartialFill | Self::Fill => "F",
+            Self::Cancelled => "4",
+            Self::Expired => "C",
+            Self::Rejected | Self::Failed => "8",
+        }
+    }
+}
+
+/// One journaled step of an order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Position in the day's journal, from 1
+    pub seq: u64,
+
+    /// When the step was journaled
+    pub recorded_at: DateTime<Utc>,
+
+    /// When the step happened, from the order's timestamps where it has one
+    pub transact_time: DateTime<Utc>,
+
+    pub event: LifecycleEvent,
+
+    /// Quantity filled by this step
+    pub last_qty: Option<Decimal>,
+
+    /// Average price of the quantity filled by this step
+    pub last_px: Option<Decimal>,
+
+    /// Rejection or failure reason
+    pub text: Option<String>,
+
+    /// The order as it stood after the step
+    pub order: Order,
+}
+
+/// Drop copy file layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DropCopyFormat {
+    Csv,
+    /// FIX 4.4 execution reports, `|` separated
+    Fix,
+}
+
+impl DropCopyFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Fix => "fix",
+        }
+    }
+}
+
+impl std::str::FromStr for DropCopyFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "fix" => Ok(Self::Fix),
+            other => Err(Error::ExecutionError(format!(
+                "Unknown drop copy format '{}'",
+                other
+            ))),
+        }
+    }
+}
+
+/// An exported drop copy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DropCopyFile {
+    pub day: NaiveDate,
+    pub format: DropCopyFormat,
+    pub path: PathBuf,
+
+    /// Lifecycle events in the file
+    pub records: usize,
+
+    /// Hex SHA-256 of the file, also written to `<path>.sha256`
+    pub sha256: String,
+}
+
+/// Status and fill quantity an order was last journaled with
+#[derive(Debug, Clone, Copy)]
+struct Seen {
+    status: OrderStatus,
+    filled: Decimal,
+    avg_price: Option<Decimal>,
+}
+
+impl Seen {
+    fn of(order: &Order) -> Self {
+        Self {
+            status: order.status,
+            filled: order.filled_quantity.as_decimal(),
+            avg_price: order.avg_fill_price.map(|p| p.as_decimal()),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct JournalState {
+    /// Day of the file being appended to, and its last sequence number
+    day: Option<NaiveDate>,
+    seq: u64,
+    seen: HashMap<Uuid, Seen>,
+}
+
+/// Append-only journal of order lifecycle events, one file per UTC day
+#[derive(Debug)]
+pub struct OrderJournal {
+    dir: PathBuf,
+    state: Mutex<JournalState>,
+}
+
+impl OrderJournal {
+    /// Opens the journal in `dir`, creating the directory if needed
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).map_err(|e| {
+            Error::PersistenceError(format!("Failed to create {}: {}", dir.display(), e))
+        })?;
+        Ok(Self {
+            dir,
+            state: Mutex::new(JournalState::default()),
+        })
+    }
+
+    /// Journal file of `day`
+    pub fn path_for(&self, day: NaiveDate) -> PathBuf {
+        self.dir
+            .join(format!("orders-{}.jsonl", day.format("%Y-%m-%d")))
+    }
+
+    /// Journal the steps `order` took since it was last seen, returning them
+    pub fn observe(&self, order: &Order) -> Result<Vec<JournalEntry>> {
+        self.observe_at(order, Utc::now())
+    }
+
+    /// [`observe`](Self::observe) with the time the snapshot was taken
+    pub fn observe_at(&self, order: &Order, at: DateTime<Utc>) -> Result<Vec<JournalEntry>> {
+        let mut state = self.state.lock();
+        self.roll_to(&mut state, at.date_naive())?;
+
+        let current = Seen::of(order);
+        let previous = state.seen.get(&order.id).copied();
+        if previous.is_some_and(|p| p.status == current.status && p.filled == current.filled) {
+            return Ok(Vec::new());
+        }
+
+        let mut steps = Vec::new();
+        if previous.is_none() {
+            // Steps the order passed before it was first seen
+            if order.status != OrderStatus::Created {
+                steps.push((LifecycleEvent::New, order.created_at));
+            }
+            if let Some(submitted_at) = order.submitted_at
+                && order.status != OrderStatus::Submitted
+            {
+                steps.push((LifecycleEvent::Submitted, submitted_at));
+            }
+        }
+        let event = match order.status {
+            OrderStatus::Created => LifecycleEvent::New,
+            OrderStatus::Submitted => LifecycleEvent::Submitted,
+            OrderStatus::Partial => LifecycleEvent::PartialFill,
+            OrderStatus::Filled => LifecycleEvent::Fill,
+            OrderStatus::Cancelled if order.is_expired(at) => LifecycleEvent::Expired,
+            OrderStatus::Cancelled => LifecycleEvent::Cancelled,
+            OrderStatus::Rejected => LifecycleEvent::Rejected,
+            OrderStatus::Failed => LifecycleEvent::Failed,
+        };
+        let transact_time = match event {
+            LifecycleEvent::New => order.created_at,
+            LifecycleEvent::Submitted => order.submitted_at.unwrap_or(at),
+            _ => order.completed_at.unwrap_or(at),
+        };
+
+        let mut entries = Vec::new();
+        for (event, time) in steps {
+            entries.push(self.append(&mut state, at, time, event, None, order)?);
+        }
+        let fill = last_fill(previous, current);
+        entries.push(self.append(&mut state, at, transact_time, event, fill, order)?);
+        state.seen.insert(order.id, current);
+        Ok(entries)
+    }
+
+    /// Entries journaled on `day`, in journal order
+    pub fn entries(&self, day: NaiveDate) -> Result<Vec<JournalEntry>> {
+        let path = self.path_for(day);
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(Error::PersistenceError(format!(
+                    "Failed to read {}: {}",
+                    path.display(),
+                    e
+                )));
+            }
+        };
+        contents
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| serde_json::from_str(l).map_err(Error::from))
+            .collect()
+    }
+
+    /// Write the drop copy of `day` to `out_dir`
+    ///
+    /// Only days that have ended can be exported, so a drop copy never
+    /// misses events journaled after it was written.
+    pub fn export(
+        &self,
+        day: NaiveDate,
+        format: DropCopyFormat,
+        out_dir: impl AsRef<Path>,
+    ) -> Result<DropCopyFile> {
+        if day >= Utc::now().date_naive() {
+            return Err(Error::ExecutionError(format!(
+                "{} has not ended yet; only past days can be exported",
+                day
+            )));
+        }
+
+        let out_dir = out_dir.as_ref();
+        let name = format!(
+            "drop-copy-{}.{}",
+            day.format("%Y-%m-%d"),
+            format.extension()
+        );
+        let path = out_dir.join(&name);
+        let checksum_path = out_dir.join(format!("{}.sha256", name));
+        let io_err = |path: &Path, e: std::io::Error| {
+            Error::PersistenceError(format!("Failed to write {}: {}", path.display(), e))
+        };
+
+        if path.exists() {
+            return verify_existing(day, format, &path, &checksum_path);
+        }
+
+        let mut entries = self.entries(day)?;
+        entries.sort_by_key(|e| (e.transact_time, e.seq));
+        let content = match format {
+            DropCopyFormat::Csv => render_csv(&entries),
+            DropCopyFormat::Fix => render_fix(&entries),
+        };
+        let sha256 = sha256_hex(content.as_bytes());
+
+        fs::create_dir_all(out_dir).map_err(|e| io_err(out_dir, e))?;
+        write_once(&path, content.as_bytes()).map_err(|e| io_err(&path, e))?;
+        write_once(&checksum_path, format!("{}  {}\n", sha256, name).as_bytes())
+            .map_err(|e| io_err(&checksum_path, e))?;
+
+        Ok(DropCopyFile {
+            day,
+            format,
+            path,
+            records: entries.len(),
+            sha256,
+        })
+    }
+
+    /// Point the journal at `day`'s file, picking up its sequence numbers
+    /// and the orders it already holds
+    fn roll_to(&self, state: &mut JournalState, day: NaiveDate) -> Result<()> {
+        if state.day == Some(day) {
+            return Ok(());
+        }
+        // Orders finished on earlier days will not change again
+        state.seen.retain(|_, seen| !is_final(seen.status));
+        let entries = self.entries(day)?;
+        state.seq = entries.last().map_or(0, |e| e.seq);
+        for entry in entries {
+            state.seen.insert(entry.order.id, Seen::of(&entry.order));
+        }
+        state.day = Some(day);
+        Ok(())
+    }
+
+    fn append(
+        &self,
+        state: &mut JournalState,
+        at: DateTime<Utc>,
+        transact_time: DateTime<Utc>,
+        event: LifecycleEvent,
+        fill: Option<(Decimal, Decimal)>,
+        order: &Order,
+    ) -> Result<JournalEntry> {
+        let entry = JournalEntry {
+            seq: state.seq + 1,
+            recorded_at: at,
+            transact_time,
+            event,
+            last_qty: fill.map(|(qty, _)| qty),
+            last_px: fill.map(|(_, px)| px),
+            text: matches!(event, LifecycleEvent::Rejected | LifecycleEvent::Failed)
+                .then(|| order.reject_reason.clone())
+                .flatten(),
+            order: order.clone(),
+        };
+
+        let path = self.path_for(at.date_naive());
+        let line = serde_json::to_string(&entry)?;
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| {
+                writeln!(file, "{}", line)?;
+                file.sync_data()
+            })
+            .map_err(|e| {
+                Error::PersistenceError(format!("Failed to append to {}: {}", path.display(), e))
+            })?;
+
+        state.seq = entry.seq;
+        Ok(entry)
+    }
+}
+
+fn is_final(status: OrderStatus) -> bool {
+    matches!(
+        status,
+        OrderStatus::Filled | OrderStatus::Cancelled | OrderStatus::Rejected | OrderStatus::Failed
+    )
+}
+
+/// Quantity and average price filled between two snapshots of an order
+fn last_fill(previous: Option<Seen>, current: Seen) -> Option<(Decimal, Decimal)> {
+    let (before, before_avg) = previous.map_or((Decimal::ZERO, None), |p| (p.filled, p.avg_price));
+    let qty = current.filled - before;
+    if qty <= Decimal::ZERO {
+        return None;
+    }
+    let avg = current.avg_price?;
+    let px = match before_avg {
+        Some(before_avg) if before > Decimal::ZERO => {
+            (avg * current.filled - before_avg * before) / qty
+        }
+        _ => avg,
+    };
+    Some((qty, px))
+}
+
+fn write_once(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let mut file = OpenOptions::new().write(true).create_new(true).open(path)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+    let mut permissions = file.metadata()?.permissions();
+    permissions.set_readonly(true);
+    fs::set_permissions(path, permissions)
+}
+
+fn verify_existing(
+    day: NaiveDate,
+    format: DropCopyFormat,
+    path: &Path,
+    checksum_path: &Path,
+) -> Result<DropCopyFile> {
+    let read = |path: &Path| {
+        fs::read(path).map_err(|e| {
+            Error::PersistenceError(format!("Failed to read {}: {}", path.display(), e))
+        })
+    };
+    let content = read(path)?;
+    let recorded = String::from_utf8_lossy(&read(checksum_path)?)
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    let sha256 = sha256_hex(&content);
+    if sha256 != recorded {
+        return Err(Error::PersistenceError(format!(
+            "Drop copy {} does not match its recorded SHA-256",
+            path.display()
+        )));
+    }
+
+    let lines = content.iter().filter(|b| **b == b'\n').count();
+    Ok(DropCopyFile {
+        day,
+        format,
+        path: path.to_path_buf(),
+        // CSV files start with a header
+        records: match format {
+            DropCopyFormat::Csv => lines.saturating_sub(1),
+            DropCopyFormat::Fix => lines,
+        },
+        sha256,
+    })
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Quantity left to fill, zero once the order is done
+fn leaves_qty(order: &Order) -> Decimal {
+    if is_final(order.status) {
+        Decimal::ZERO
+    } else {
+        (order.quantity.as_decimal() - order.filled_quantity.as_decimal()).max(Decimal::ZERO)
+    }
+}
+
+fn order_type_str(order_type: OrderType) -> String {
+    serde_json::to_value(order_type)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+const CSV_HEADER: &str = "seq,transact_time,recorded_at,event,order_id,client_order_id,\
+exchange_order_id,strategy_id,symbol,side,order_type,quantity,price,status,last_qty,last_px,\
+cum_qty,leaves_qty,avg_px,text";
+
+fn render_csv(entries: &[JournalEntry]) -> String {
+    let mut out = format!("{}\n", CSV_HEADER);
+    for e in entries {
+        let o = &e.order;
+        let opt = |d: Option<Decimal>| d.map(|d| d.to_string()).unwrap_or_default();
+        let fields = [
+            e.seq.to_string(),
+            e.transact_time.to_rfc3339(),
+            e.recorded_at.to_rfc3339(),
+            e.event.as_str().to_string(),
+            o.id.to_string(),
+            o.client_order_id.clone(),
+            o.okx_order_id.clone().unwrap_or_default(),
+            o.strategy_id.to_string(),
+            o.symbol.as_str().to_string(),
+            match o.side {
+                OrderSide::Buy => "buy",
+                OrderSide::Sell => "sell",
+            }
+            .to_string(),
+            order_type_str(o.order_type),
+            o.quantity.as_decimal().to_string(),
+            opt(o.price.map(|p| p.as_decimal())),
+            format!("{:?}", o.status).to_lowercase(),
+            opt(e.last_qty),
+            opt(e.last_px),
+            o.filled_quantity.as_decimal().to_string(),
+            leaves_qty(o).to_string(),
+            opt(o.avg_fill_price.map(|p| p.as_decimal())),
+            e.text.clone().unwrap_or_default(),
+        ];
+        let row: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// FIX `UTCTimestamp` with milliseconds
+fn fix_time(at: DateTime<Utc>) -> String {
+    at.format("%Y%m%d-%H:%M:%S%.3f").to_string()
+}
+
+fn render_fix(entries: &[JournalEntry]) -> String {
+    let mut out = String::new();
+    for (n, e) in entries.iter().enumerate() {
+        let o = &e.order;
+        let mut tags: Vec<(u32, String)> = vec![
+            (8, "FIX.4.4".to_string()),
+            (35, "8".to_string()),
+            (34, (n + 1).to_string()),
+            (52, fix_time(e.recorded_at)),
+            (
+                37,
+                o.okx_order_id.clone().unwrap_or_else(|| o.id.to_string()),
+            ),
+            (11, o.client_order_id.clone()),
+            (17, format!("{}-{}", e.recorded_at.format("%Y%m%d"), e.seq)),
+            (150, e.event.exec_type().to_string()),
+            (39, ord_status(o.status, e.event).to_string()),
+            (55, o.symbol.as_str().to_string()),
+            (
+                54,
+                match o.side {
+                    OrderSide::Buy => "1",
+                    OrderSide::Sell => "2",
+                }
+                .to_string(),
+            ),
+            (40, fix_ord_type(o.order_type).to_string()),
+            (38, o.quantity.as_decimal().to_string()),
+        ];
+        if let Some(price) = o.price {
+            tags.push((44, price.as_decimal().to_string()));
+        }
+        match o.order_type {
+            OrderType::Ioc => tags.push((59, "3".to_string())),
+            OrderType::Fok => tags.push((59, "4".to_string())),
+            // Participate, don't initiate
+            OrderType::PostOnly => tags.push((18, "6".to_string())),
+            _ => {}
+        }
+        if let (Some(qty), Some(px)) = (e.last_qty, e.last_px) {
+            tags.push((32, qty.to_string()));
+            tags.push((31, px.to_string()));
+        }
+        tags.push((14, o.filled_quantity.as_decimal().to_string()));
+        tags.push((151, leaves_qty(o).to_string()));
+        tags.push((
+            6,
+            o.avg_fill_price
+                .map_or(Decimal::ZERO, |p| p.as_decimal())
+                .to_string(),
+        ));
+        tags.push((60, fix_time(e.transact_time)));
+        if let Some(text) = &e.text {
+            tags.push((58, text.replace('|', "/")));
+        }
+        // User-defined tags keep what FIX has no field for
+        tags.push((7001, order_type_str(o.order_type)));
+        tags.push((7002, o.strategy_id.to_string()));
+        tags.push((7003, o.id.to_string()));
+        tags.push((7004, e.event.as_str().to_string()));
+
+        let line: Vec<String> = tags.iter().map(|(t, v)| format!("{}={}", t, v)).collect();
+        out.push_str(&line.join("|"));
+        out.push('\n');
+    }
+    out
+}
+
+/// FIX `OrdStatus` (39)
+fn ord_status(status: OrderStatus, event: LifecycleEvent) -> &'static str {
+    match status {
+        OrderStatus::Created => "A",
+        OrderStatus::Submitted => "0",
+        OrderStatus::Partial => "1",
+        OrderStatus::Filled => "2",
+        OrderStatus::Cancelled if event == LifecycleEvent::Expired => "C",
+        OrderStatus::Cancelled => "4",
+        OrderStatus::Rejected | OrderStatus::Failed => "8",
+    }
+}
+
+/// FIX `OrdType` (40); the exact type is in tag 7001
+fn fix_ord_type(order_type: OrderType) -> &'static str {
+    match order_type {
+        OrderType::Market => "1",
+        OrderType::StopLoss => "3",
+        OrderType::TakeProfit | OrderType::TrailingStop => "4",
+        OrderType::Limit
+        | OrderType::PostOnly
+        | OrderType::Ioc
+        | OrderType::Fok
+        | OrderType::Iceberg => "2",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use ea_okx_core::types::{Price, Quantity, Symbol};
+    use rust_decimal_macros::dec;
+
+    fn order(order_type: OrderType) -> Order {
+        Order::new(
+            Uuid::new_v4(),
+            Symbol::new("BTC-USDT").unwrap(),
+            OrderSide::Buy,
+            order_type,
+            Quantity::new(dec!(1)).unwrap(),
+            Some(Price::new(dec!(100)).unwrap()),
+        )
+    }
+
+    #[test]
+    fn test_journal_fills_in_steps_and_fill_prices() {
+        let dir = std::env::temp_dir().join(format!("order-journal-{}", Uuid::new_v4()));
+        let journal = OrderJournal::open(&dir).unwrap();
+        let at = Utc.with_ymd_and_hms(2024, 3, 4, 10, 0, 0).unwrap();
+
+        // First seen already sent and half filled
+        let mut order = order(OrderType::Limit);
+        order.mark_submitted("ex-1".to_string());
+        order.update_fill(
+            Quantity::new(dec!(0.5)).unwrap(),
+            Price::new(dec!(100)).unwrap(),
+        );
+        let steps = journal.observe_at(&order, at).unwrap();
+        let events: Vec<_> = steps.iter().map(|e| e.event).collect();
+        assert_eq!(
+            events,
+            [
+                LifecycleEvent::New,
+                LifecycleEvent::Submitted,
+                LifecycleEvent::PartialFill
+            ]
+        );
+        assert_eq!(steps[2].last_qty, Some(dec!(0.5)));
+        assert!(journal.observe_at(&order, at).unwrap().is_empty());
+
+        // The rest fills at 104, moving the average to 102
+        order.update_fill(
+            Quantity::new(dec!(1)).unwrap(),
+            Price::new(dec!(102)).unwrap(),
+        );
+        let fill = journal
+            .observe_at(&order, at + Duration::seconds(1))
+            .unwrap();
+        assert_eq!(fill.len(), 1);
+        assert_eq!(fill[0].event, LifecycleEvent::Fill);
+        assert_eq!(fill[0].last_qty, Some(dec!(0.5)));
+        assert_eq!(fill[0].last_px, Some(dec!(104)));
+        assert_eq!(fill[0].seq, 4);
+
+        let mut rejected = self::order(OrderType::PostOnly);
+        rejected.reject_reason = Some("Post only, would take".to_string());
+        rejected.set_status(OrderStatus::Rejected);
+        let steps = journal.observe_at(&rejected, at).unwrap();
+        assert_eq!(steps.last().unwrap().event, LifecycleEvent::Rejected);
+        assert_eq!(
+            steps.last().unwrap().text.as_deref(),
+            Some("Post only, would take")
+        );
+
+        // A reopened journal carries on numbering and knows the orders
+        let reopened = OrderJournal::open(&dir).unwrap();
+        assert!(reopened.observe_at(&order, at).unwrap().is_empty());
+        assert_eq!(reopened.entries(at.date_naive()).unwrap().len(), 6);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_export_is_written_once_and_verified() {
+        let dir = std::env::temp_dir().join(format!("order-journal-{}", Uuid::new_v4()));
+        let journal = OrderJournal::open(dir.join("journal")).unwrap();
+        let at = Utc.with_ymd_and_hms(2024, 3, 4, 10, 0, 0).unwrap();
+
+        let mut order = order(OrderType::Ioc);
+        order.mark_submitted("ex-2".to_string());
+        journal.observe_at(&order, at).unwrap();
+        order.set_status(OrderStatus::Cancelled);
+        journal.observe_at(&order, at).unwrap();
+
+        let out = dir.join("exports");
+        let csv = journal
+            .export(at.date_naive(), DropCopyFormat::Csv, &out)
+            .unwrap();
+        assert_eq!(csv.records, 3);
+        let content = fs::read_to_string(&csv.path).unwrap();
+        assert!(content.starts_with("seq,transact_time"));
+        assert!(content.lines().nth(3).unwrap().contains(",cancelled,"));
+        assert_eq!(sha256_hex(content.as_bytes()), csv.sha256);
+
+        let fix = journal
+            .export(at.date_naive(), DropCopyFormat::Fix, &out)
+            .unwrap();
+        let reports = fs::read_to_string(&fix.path).unwrap();
+        let last = reports.lines().last().unwrap();
+        assert!(last.starts_with("8=FIX.4.4|35=8|34=3|"));
+        assert!(last.contains("|150=4|39=4|") && last.contains("|59=3|"));
+        assert!(last.contains("|151=0|"));
+
+        // Exporting again returns the verified file; tampering is caught
+        let again = journal
+            .export(at.date_naive(), DropCopyFormat::Csv, &out)
+            .unwrap();
+        assert_eq!((again.sha256, again.records), (csv.sha256, 3));
+        let checksum = out.join("drop-copy-2024-03-04.fix.sha256");
+        let mut permissions = fs::metadata(&checksum).unwrap().permissions();
+        #[allow(clippy::permissions_set_readonly_false)]
+        permissions.set_readonly(false);
+        fs::set_permissions(&checksum, permissions).unwrap();
+        fs::write(&checksum, format!("{}  x\n", "0".repeat(64))).unwrap();
+        assert!(
+            journal
+                .export(at.date_naive(), DropCopyFormat::Fix, &out)
+                .is_err()
+        );
+
+        assert!(
+            journal
+                .export(Utc::now().date_naive(), DropCopyFormat::Csv, &out)
+                .is_err()
+        );
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -20,9 +20,64 @@ pub enum Error {
     #[error("Timeout error: {0}")]
     TimeoutError(String),
 
+    #[error("Trading disabled: {0}")]
+    TradingDisabled(String),
+
+    #[error("Order size {requested} exceeds max available {allowed}")]
+    SizeLimitExceeded {
+        requested: rust_decimal::Decimal,
+        allowed: rust_decimal::Decimal,
+    },
+
+    #[error("Order size {requested} exceeds {allowed} allowed by visible liquidity")]
+    LiquidityExceeded {
+        requested: rust_decimal::Decimal,
+        allowed: rust_decimal::Decimal,
+    },
+
+    #[error("Order needs {requested} {ccy} but only {available} is unreserved")]
+    InsufficientBalance {
+        ccy: String,
+        requested: rust_decimal::Decimal,
+        available: rust_decimal::Decimal,
+    },
+
+    #[error("Order rejected by fat-finger guard: {0}")]
+    FatFingerRejected(String),
+
+    #[error("Order requires confirmation: {0}")]
+    ConfirmationRequired(String),
+
+    #[error("Reduce-only order rejected: {0}")]
+    ReduceOnlyRejected(String),
+
+    #[error("Invalid scale-out plan: {0}")]
+    InvalidPlan(String),
+
+    #[error("Invalid bracket: {0}")]
+    InvalidBracket(String),
+
+    #[error("Invalid execution policy: {0}")]
+    InvalidPolicy(String),
+
+    #[error("Unknown symbol: {0}")]
+    UnknownSymbol(String),
+
+    #[error("Quota exceeded: {0}")]
+    QuotaExceeded(String),
+
+    #[error("Daily loss lock: {0}")]
+    DailyLossLocked(String),
+
+    #[error("Signal queue full: {0}")]
+    QueueFull(String),
+
     #[error("Execution error: {0}")]
     ExecutionError(String),
 
+    #[error("Persistence error: {0}")]
+    PersistenceError(String),
+
     #[error("Serialization error: {0}")]
     SerializationError(#[from] serde_json::Error),
 }
@@ -0,0 +1,525 @@
+//! Per-strategy execution policies
+//!
+//! A signal says what to trade, not how to work the order. Each strategy's
+//! [`ExecutionPolicy`] picks the order style for its opens and closes, sends
+//! orders above a notional threshold to a TWAP instead of the book in one go,
+//! and caps how far from the last price an order may fill. Risk exits (stop
+//! loss, take profit, risk management) always go out as market orders so a
+//! policy can never keep a position from being closed.
+//!
+//! The policy also bounds how long limit orders rest on the book: each order
+//! expires after the policy's time to live or at the daily session end,
+//! whichever comes first, and by default a strategy's resting orders are
+//! cancelled when it stops.
+//!
+//! Strategies without a policy of their own use the default, which keeps the
+//! engine's original behavior: limit opens at the signal price and market
+//! closes, with no algo routing, no slippage cap and no expiry.
+
+use crate::algorithms::TwapConfig;
+use crate::error::{Error, Result};
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+use ea_okx_core::models::{OrderSide, OrderType};
+use ea_okx_core::{Price, Quantity};
+use parking_lot::RwLock;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// How a direct order is placed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderStyle {
+    /// Take liquidity; sent as an IOC limit at the slippage bound when the
+    /// policy caps slippage
+    Market,
+    /// Rest at the signal price, or the last price without one
+    Limit,
+    /// Post-only limit that OKX cancels rather than let it take liquidity
+    PassiveLimit,
+}
+
+/// What the order is for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderPurpose {
+    Open,
+    Close,
+    /// Stop loss, take profit or risk management exit
+    Exit,
+}
+
+/// Orders large enough to be worked by a TWAP
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TwapRouting {
+    /// Orders worth at least this much (quantity x price) are sliced
+    pub min_notional: Decimal,
+    pub duration_minutes: u32,
+    pub slice_interval_seconds: u32,
+}
+
+/// Execution rules of one strategy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionPolicy {
+    pub open: OrderStyle,
+    pub close: OrderStyle,
+
+    /// Slice large opens and closes instead of sending them whole
+    #[serde(default)]
+    pub twap: Option<TwapRouting>,
+
+    /// Furthest an order may fill from the last price, in basis points
+    #[serde(default)]
+    pub max_slippage_bps: Option<Decimal>,
+
+    /// Limit orders still resting this long after placement are cancelled
+    #[serde(default)]
+    pub order_ttl_secs: Option<u64>,
+
+    /// Time of day (UTC) at which resting orders are cancelled
+    #[serde(default)]
+    pub session_end: Option<NaiveTime>,
+
+    /// Cancel the strategy's resting orders when it stops
+    #[serde(default = "cancel_on_stop_default")]
+    pub cancel_on_stop: bool,
+}
+
+fn cancel_on_stop_default() -> bool {
+    true
+}
+
+impl Default for ExecutionPolicy {
+    fn default() -> Self {
+        Self {
+            open: OrderStyle::Limit,
+            close: OrderStyle::Market,
+            twap: None,
+            max_slippage_bps: None,
+            order_ttl_secs: None,
+            session_end: None,
+            cancel_on_stop: true,
+        }
+    }
+}
+
+/// Order type and price of a directly placed order
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct OrderPlan {
+    pub order_type: OrderType,
+    pub price: Option<Price>,
+}
+
+/// Where a policy sends an order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "route", rename_all = "snake_case")]
+pub enum ExecutionRoute {
+    Direct(OrderPlan),
+    /// Sliced by a TWAP whose child orders follow the policy's style
+    Twap(TwapConfig),
+}
+
+impl ExecutionPolicy {
+    pub fn validate(&self) -> Result<()> {
+        if let Some(twap) = &self.twap {
+            if twap.min_notional <= Decimal::ZERO {
+                return Err(Error::InvalidPolicy(
+                    "TWAP notional threshold must be positive".to_string(),
+                ));
+            }
+            if twap.duration_minutes == 0 || twap.slice_interval_seconds == 0 {
+                return Err(Error::InvalidPolicy(
+                    "TWAP duration and slice interval must be positive".to_string(),
+                ));
+            }
+            if twap.slice_interval_seconds as u64 > twap.duration_minutes as u64 * 60 {
+                return Err(Error::InvalidPolicy(
+                    "TWAP slice interval is longer than its duration".to_string(),
+                ));
+            }
+        }
+        if self.max_slippage_bps.is_some_and(|bps| bps < Decimal::ZERO) {
+            return Err(Error::InvalidPolicy(
+                "Max slippage must not be negative".to_string(),
+            ));
+        }
+        if self.order_ttl_secs == Some(0) {
+            return Err(Error::InvalidPolicy(
+                "Order time to live must be positive".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// When a limit order placed at `now` stops resting: after its time to
+    /// live or at the next session end, whichever is earlier
+    pub fn expiry(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let ttl = self
+            .order_ttl_secs
+            .map(|secs| now + Duration::seconds(secs as i64));
+        let session_end = self.session_end.map(|end| {
+            let today = now.date_naive().and_time(end).and_utc();
+            if today > now {
+                today
+            } else {
+                today + Duration::days(1)
+            }
+        });
+        match (ttl, session_end) {
+            (Some(ttl), Some(end)) => Some(ttl.min(end)),
+            (ttl, end) => ttl.or(end),
+        }
+    }
+
+    pub fn style(&self, purpose: OrderPurpose) -> OrderStyle {
+        match purpose {
+            OrderPurpose::Open => self.open,
+            OrderPurpose::Close => self.close,
+            OrderPurpose::Exit => OrderStyle::Market,
+        }
+    }
+
+    /// Route an order of `quantity` given the signal's limit price, if any,
+    /// and the last traded price, if known
+    pub fn route(
+        &self,
+        purpose: OrderPurpose,
+        side: OrderSide,
+        quantity: Quantity,
+        limit_price: Option<Price>,
+        last_price: Option<Price>,
+    ) -> Result<ExecutionRoute> {
+        if purpose == OrderPurpose::Exit {
+            return Ok(ExecutionRoute::Direct(OrderPlan {
+                order_type: OrderType::Market,
+                price: None,
+            }));
+        }
+
+        let style = self.style(purpose);
+        if let Some(twap) = &self.twap {
+            let notional = limit_price
+                .or(last_price)
+                .map(|price| quantity.as_decimal() * price.as_decimal());
+            if notional.is_some_and(|n| n >= twap.min_notional) {
+                return Ok(ExecutionRoute::Twap(TwapConfig {
+                    total_quantity: quantity,
+                    duration_minutes: twap.duration_minutes,
+                    slice_interval_seconds: twap.slice_interval_seconds,
+                    order_type: match style {
+                        OrderStyle::Market => OrderType::Market,
+                        OrderStyle::Limit => OrderType::Limit,
+                        OrderStyle::PassiveLimit => OrderType::PostOnly,
+                    },
+                    // Passive slices must never be turned into market orders
+                    aggressive_on_final: style != OrderStyle::PassiveLimit,
+                    ..Default::default()
+                }));
+            }
+        }
+
+        let bound = last_price.and_then(|last| self.worst_price(side, last));
+        let plan = match style {
+            OrderStyle::Market => match bound {
+                Some(bound) => OrderPlan {
+                    order_type: OrderType::Ioc,
+                    price: Some(bound),
+                },
+                None => OrderPlan {
+                    order_type: OrderType::Market,
+                    price: None,
+                },
+            },
+            OrderStyle::Limit | OrderStyle::PassiveLimit => {
+                let price = limit_price.or(last_price).ok_or_else(|| {
+                    Error::InvalidPolicy("No price to place a limit order at".to_string())
+                })?;
+                OrderPlan {
+                    order_type: if style == OrderStyle::Limit {
+                        OrderType::Limit
+                    } else {
+                        OrderType::PostOnly
+                    },
+                    price: Some(match bound {
+                        Some(bound) => clamp_price(side, price, bound),
+                        None => price,
+                    }),
+                }
+            }
+        };
+        Ok(ExecutionRoute::Direct(plan))
+    }
+
+    /// Least favorable price the slippage cap allows around `last`
+    pub fn worst_price(&self, side: OrderSide, last: Price) -> Option<Price> {
+        let offset = last.as_decimal() * self.max_slippage_bps? / dec!(10000);
+        let price = match side {
+            OrderSide::Buy => last.as_decimal() + offset,
+            OrderSide::Sell => last.as_decimal() - offset,
+        };
+        Price::new(price).ok()
+    }
+}
+
+fn clamp_price(side: OrderSide, price: Price, bound: Price) -> Price {
+    match side {
+        OrderSide::Buy if price > bound => bound,
+        OrderSide::Sell if price < bound => bound,
+        _ => price,
+    }
+}
+
+/// Execution policies of every strategy, with a default for the rest
+#[derive(Debug, Default)]
+pub struct ExecutionPolicies {
+    default_policy: RwLock<ExecutionPolicy>,
+    overrides: RwLock<HashMap<Uuid, ExecutionPolicy>>,
+}
+
+impl ExecutionPolicies {
+    pub fn new(default_policy: ExecutionPolicy) -> Self {
+        Self {
+            default_policy: RwLock::new(default_policy),
+            overrides: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn default_policy(&self) -> ExecutionPolicy {
+        self.default_policy.read().clone()
+    }
+
+    pub fn set_default_policy(&self, policy: ExecutionPolicy) -> Result<()> {
+        policy.validate()?;
+        *self.default_policy.write() = policy;
+        Ok(())
+    }
+
+    /// Give one strategy its own policy instead of the default
+    pub fn set_strategy_policy(&self, strategy_id: Uuid, policy: ExecutionPolicy) -> Result<()> {
+        policy.validate()?;
+        self.overrides.write().insert(strategy_id, policy);
+        Ok(())
+    }
+
+    /// Put a strategy back on the default policy
+    pub fn clear_strategy_policy(&self, strategy_id: Uuid) {
+        self.overrides.write().remove(&strategy_id);
+    }
+
+    /// Policy in force for `strategy_id`
+    pub fn policy(&self, strategy_id: Uuid) -> ExecutionPolicy {
+        self.overrides
+            .read()
+            .get(&strategy_id)
+            .cloned()
+            .unwrap_or_else(|| self.default_policy())
+    }
+
+    /// Strategies with a policy of their own
+    pub fn overrides(&self) -> HashMap<Uuid, ExecutionPolicy> {
+        self.overrides.read().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price(value: Decimal) -> Option<Price> {
+        Some(Price::new(value).unwrap())
+    }
+
+    fn qty(value: Decimal) -> Quantity {
+        Quantity::new(value).unwrap()
+    }
+
+    fn direct(route: ExecutionRoute) -> OrderPlan {
+        match route {
+            ExecutionRoute::Direct(plan) => plan,
+            ExecutionRoute::Twap(config) => panic!("unexpected TWAP route: {:?}", config),
+        }
+    }
+
+    #[test]
+    fn test_default_policy_keeps_limit_opens_and_market_closes() {
+        let policy = ExecutionPolicy::default();
+        let open = direct(
+            policy
+                .route(
+                    OrderPurpose::Open,
+                    OrderSide::Buy,
+                    qty(dec!(1)),
+                    price(dec!(100)),
+                    price(dec!(101)),
+                )
+                .unwrap(),
+        );
+        assert_eq!(open.order_type, OrderType::Limit);
+        assert_eq!(open.price, price(dec!(100)));
+
+        let close = direct(
+            policy
+                .route(
+                    OrderPurpose::Close,
+                    OrderSide::Sell,
+                    qty(dec!(1)),
+                    None,
+                    price(dec!(101)),
+                )
+                .unwrap(),
+        );
+        assert_eq!(close.order_type, OrderType::Market);
+        assert_eq!(close.price, None);
+    }
+
+    #[test]
+    fn test_slippage_cap_bounds_market_and_limit_orders() {
+        let policy = ExecutionPolicy {
+            open: OrderStyle::PassiveLimit,
+            max_slippage_bps: Some(dec!(50)),
+            ..Default::default()
+        };
+
+        // Buy limit above the bound is pulled down to it
+        let open = direct(
+            policy
+                .route(
+                    OrderPurpose::Open,
+                    OrderSide::Buy,
+                    qty(dec!(1)),
+                    price(dec!(102)),
+                    price(dec!(100)),
+                )
+                .unwrap(),
+        );
+        assert_eq!(open.order_type, OrderType::PostOnly);
+        assert_eq!(open.price, price(dec!(100.5)));
+
+        let close = direct(
+            policy
+                .route(
+                    OrderPurpose::Close,
+                    OrderSide::Sell,
+                    qty(dec!(1)),
+                    None,
+                    price(dec!(100)),
+                )
+                .unwrap(),
+        );
+        assert_eq!(close.order_type, OrderType::Ioc);
+        assert_eq!(close.price, price(dec!(99.5)));
+
+        // Exits ignore the cap
+        let exit = direct(
+            policy
+                .route(
+                    OrderPurpose::Exit,
+                    OrderSide::Sell,
+                    qty(dec!(1)),
+                    None,
+                    price(dec!(100)),
+                )
+                .unwrap(),
+        );
+        assert_eq!(exit.order_type, OrderType::Market);
+    }
+
+    #[test]
+    fn test_large_orders_route_to_twap() {
+        let policy = ExecutionPolicy {
+            open: OrderStyle::PassiveLimit,
+            twap: Some(TwapRouting {
+                min_notional: dec!(50000),
+                duration_minutes: 10,
+                slice_interval_seconds: 60,
+            }),
+            ..Default::default()
+        };
+
+        let small = policy
+            .route(
+                OrderPurpose::Open,
+                OrderSide::Buy,
+                qty(dec!(1)),
+                price(dec!(40000)),
+                None,
+            )
+            .unwrap();
+        assert!(matches!(small, ExecutionRoute::Direct(_)));
+
+        let ExecutionRoute::Twap(config) = policy
+            .route(
+                OrderPurpose::Open,
+                OrderSide::Buy,
+                qty(dec!(2)),
+                price(dec!(40000)),
+                None,
+            )
+            .unwrap()
+        else {
+            panic!("expected a TWAP route");
+        };
+        assert_eq!(config.total_quantity, qty(dec!(2)));
+        assert_eq!(config.order_type, OrderType::PostOnly);
+        assert!(!config.aggressive_on_final);
+    }
+
+    #[test]
+    fn test_expiry_is_the_earlier_of_ttl_and_session_end() {
+        let now = "2026-03-02T21:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        assert_eq!(ExecutionPolicy::default().expiry(now), None);
+
+        let mut policy = ExecutionPolicy {
+            order_ttl_secs: Some(600),
+            ..Default::default()
+        };
+        assert_eq!(policy.expiry(now), Some(now + Duration::minutes(10)));
+
+        policy.session_end = Some(NaiveTime::from_hms_opt(21, 5, 0).unwrap());
+        assert_eq!(policy.expiry(now), Some(now + Duration::minutes(5)));
+
+        // Past today's session end, orders rest until tomorrow's
+        policy.order_ttl_secs = None;
+        policy.session_end = Some(NaiveTime::from_hms_opt(20, 0, 0).unwrap());
+        assert_eq!(policy.expiry(now), Some(now + Duration::hours(23)));
+
+        // Older policies without the field still cancel on stop
+        let parsed: ExecutionPolicy =
+            serde_json::from_str(r#"{"open": "limit", "close": "market"}"#).unwrap();
+        assert!(parsed.cancel_on_stop);
+    }
+
+    #[test]
+    fn test_strategy_policy_overrides_default() {
+        let policies = ExecutionPolicies::default();
+        let strategy_id = Uuid::new_v4();
+        let market = ExecutionPolicy {
+            open: OrderStyle::Market,
+            ..Default::default()
+        };
+
+        policies
+            .set_strategy_policy(strategy_id, market.clone())
+            .unwrap();
+        assert_eq!(policies.policy(strategy_id), market);
+        assert_eq!(policies.policy(Uuid::new_v4()), ExecutionPolicy::default());
+
+        policies.clear_strategy_policy(strategy_id);
+        assert_eq!(policies.policy(strategy_id), ExecutionPolicy::default());
+
+        let invalid = ExecutionPolicy {
+            twap: Some(TwapRouting {
+                min_notional: dec!(1000),
+                duration_minutes: 1,
+                slice_interval_seconds: 120,
+            }),
+            ..Default::default()
+        };
+        assert!(matches!(
+            policies.set_strategy_policy(strategy_id, invalid),
+            Err(Error::InvalidPolicy(_))
+        ));
+    }
+}
@@ -0,0 +1,669 @@
+use crate::algorithms::{TwapConfig, VwapConfig};
+use crate::error::{Error, Result};
+use chrono::{DateTime, Duration, Utc};
+use ea_okx_core::models::OrderSide;
+use ea_okx_core::{ExchangeAdapter, Price, Quantity, Symbol};
+use parking_lot::RwLock;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+/// Algorithm parameters of a persisted execution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "algo", content = "config", rename_all = "lowercase")]
+pub enum AlgoParams {
+    Twap(TwapConfig),
+    Vwap(VwapConfig),
+}
+
+impl AlgoParams {
+    /// Short algorithm name
+    pub fn name(&self) -> &'static str {
+        match self {
+            AlgoParams::Twap(_) => "twap",
+            AlgoParams::Vwap(_) => "vwap",
+        }
+    }
+}
+
+/// Lifecycle status of an algorithm execution
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlgoExecutionStatus {
+    Running,
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+impl AlgoExecutionStatus {
+    /// Check if the execution will not make further progress
+    pub fn is_terminal(&self) -> bool {
+        !matches!(self, AlgoExecutionStatus::Running)
+    }
+}
+
+/// Persisted state of a TWAP/VWAP execution (parent order and slice schedule)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlgoExecution {
+    pub id: Uuid,
+    pub params: AlgoParams,
+    pub symbol: Symbol,
+    pub side: OrderSide,
+    pub reference_price: Price,
+    pub total_quantity: Quantity,
+    pub executed_quantity: Decimal,
+    pub total_cost: Decimal,
+    pub slices_total: u32,
+    pub slices_done: u32,
+    pub slices_failed: u32,
+    pub slice_interval_seconds: u64,
+    pub next_slice_at: DateTime<Utc>,
+    pub deadline: DateTime<Utc>,
+    pub status: AlgoExecutionStatus,
+    pub status_reason: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+
+    /// Client order IDs of the child orders sent so far
+    #[serde(default)]
+    pub child_orders: Vec<String>,
+}
+
+impl AlgoExecution {
+    /// Creates a new execution for a TWAP parent order
+    pub fn new_twap(
+        config: TwapConfig,
+        symbol: Symbol,
+        side: OrderSide,
+        reference_price: Price,
+    ) -> Self {
+        let now = Utc::now();
+        let total_seconds = config.duration_minutes as u64 * 60;
+        let slice_interval_seconds = config.slice_interval_seconds.max(1) as u64;
+        let slices_total = (total_seconds / slice_interval_seconds).max(1) as u32;
+        let total_quantity = config.total_quantity;
+        let deadline = now + Duration::seconds(total_seconds as i64);
+
+        Self::new(
+            AlgoParams::Twap(config),
+            symbol,
+            side,
+            reference_price,
+            total_quantity,
+            slices_total,
+            slice_interval_seconds,
+            now,
+            deadline,
+        )
+    }
+
+    /// Creates a new execution for a VWAP parent order
+    pub fn new_vwap(
+        config: VwapConfig,
+        symbol: Symbol,
+        side: OrderSide,
+        reference_price: Price,
+    ) -> Self {
+        let now = Utc::now();
+        // Child orders are sent on participation checks, so the count is an
+        // upper bound
+        let slice_interval_seconds = config.participation.check_interval_seconds.max(1);
+        let window_seconds = (config.end_time - config.start_time).num_seconds().max(0) as u64;
+        let slices_total = (window_seconds / slice_interval_seconds) as u32;
+        let total_quantity = config.total_quantity;
+        let deadline = config.end_time;
+
+        Self::new(
+            AlgoParams::Vwap(config),
+            symbol,
+            side,
+            reference_price,
+            total_quantity,
+            slices_total,
+            slice_interval_seconds,
+            now,
+            deadline,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        params: AlgoParams,
+        symbol: Symbol,
+        side: OrderSide,
+        reference_price: Price,
+        total_quantity: Quantity,
+        slices_total: u32,
+        slice_interval_seconds: u64,
+        now: DateTime<Utc>,
+        deadline: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            params,
+            symbol,
+            side,
+            reference_price,
+            total_quantity,
+            executed_quantity: Decimal::ZERO,
+            total_cost: Decimal::ZERO,
+            slices_total,
+            slices_done: 0,
+            slices_failed: 0,
+            slice_interval_seconds,
+            next_slice_at: now,
+            deadline,
+            status: AlgoExecutionStatus::Running,
+            status_reason: None,
+            started_at: now,
+            updated_at: now,
+            child_orders: Vec::new(),
+        }
+    }
+
+    /// Quantity still to be executed
+    pub fn remaining_quantity(&self) -> Decimal {
+        (self.total_quantity.as_decimal() - self.executed_quantity).max(Decimal::ZERO)
+    }
+
+    /// Slices not yet attempted
+    pub fn slices_remaining(&self) -> u32 {
+        self.slices_total.saturating_sub(self.slices_done)
+    }
+
+    /// Executed quantity as a percentage of the parent order
+    pub fn progress_pct(&self) -> Decimal {
+        let total = self.total_quantity.as_decimal();
+        if total.is_zero() {
+            return Decimal::ZERO;
+        }
+        (self.executed_quantity / total * dec!(100)).min(dec!(100))
+    }
+
+    /// Average fill price so far
+    pub fn average_price(&self) -> Option<Price> {
+        if self.executed_quantity > Decimal::ZERO {
+            Price::new(self.total_cost / self.executed_quantity).ok()
+        } else {
+            None
+        }
+    }
+
+    /// Records the outcome of a slice and schedules the next one
+    pub fn record_slice(&mut self, executed: Decimal, price: Price, success: bool) {
+        let now = Utc::now();
+        self.slices_done += 1;
+        if success {
+            self.executed_quantity += executed;
+            self.total_cost += executed * price.as_decimal();
+        } else {
+            self.slices_failed += 1;
+        }
+        self.next_slice_at = now + Duration::seconds(self.slice_interval_seconds as i64);
+        self.updated_at = now;
+
+        if self.slices_remaining() == 0 || self.remaining_quantity().is_zero() {
+            self.finish(AlgoExecutionStatus::Completed, None);
+        }
+    }
+
+    /// Remembers a child order before it is sent, so recovery can cancel it
+    pub fn record_child_order(&mut self, client_order_id: impl Into<String>) {
+        self.child_orders.push(client_order_id.into());
+        self.updated_at = Utc::now();
+    }
+
+    /// Moves the execution into a terminal status
+    pub fn finish(&mut self, status: AlgoExecutionStatus, reason: Option<String>) {
+        self.status = status;
+        self.status_reason = reason;
+        self.updated_at = Utc::now();
+    }
+}
+
+/// Storage backend for algorithm executions
+pub trait AlgoExecutionStore: Send + Sync {
+    /// Insert or update an execution
+    fn save(&self, execution: &AlgoExecution) -> Result<()>;
+
+    /// Load a single execution
+    fn load(&self, id: Uuid) -> Result<Option<AlgoExecution>>;
+
+    /// Load all known executions
+    fn load_all(&self) -> Result<Vec<AlgoExecution>>;
+
+    /// Remove an execution
+    fn remove(&self, id: Uuid) -> Result<()>;
+}
+
+/// In-memory store, mainly for tests and paper trading
+#[derive(Debug, Default)]
+pub struct InMemoryAlgoExecutionStore {
+    executions: RwLock<HashMap<Uuid, AlgoExecution>>,
+}
+
+impl InMemoryAlgoExecutionStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl AlgoExecutionStore for InMemoryAlgoExecutionStore {
+    fn save(&self, execution: &AlgoExecution) -> Result<()> {
+        self.executions
+            .write()
+            .insert(execution.id, execution.clone());
+        Ok(())
+    }
+
+    fn load(&self, id: Uuid) -> Result<Option<AlgoExecution>> {
+        Ok(self.executions.read().get(&id).cloned())
+    }
+
+    fn load_all(&self) -> Result<Vec<AlgoExecution>> {
+        Ok(self.executions.read().values().cloned().collect())
+    }
+
+    fn remove(&self, id: Uuid) -> Result<()> {
+        self.executions.write().remove(&id);
+        Ok(())
+    }
+}
+
+/// File-backed store keeping one JSON document per execution
+#[derive(Debug)]
+pub struct FileAlgoExecutionStore {
+    dir: PathBuf,
+    lock: RwLock<()>,
+}
+
+impl FileAlgoExecutionStore {
+    /// Creates the store, creating the directory if needed
+    pub fn new(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).map_err(|e| {
+            Error::PersistenceError(format!("Failed to create {}: {}", dir.display(), e))
+        })?;
+        Ok(Self {
+            dir,
+            lock: RwLock::new(()),
+        })
+    }
+
+    fn path_for(&self, id: Uuid) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+}
+
+impl AlgoExecutionStore for FileAlgoExecutionStore {
+    fn save(&self, execution: &AlgoExecution) -> Result<()> {
+        let _guard = self.lock.write();
+        let path = self.path_for(execution.id);
+        let tmp = path.with_extension("json.tmp");
+        let json = serde_json::to_vec_pretty(execution)?;
+
+        // Write to a temp file and rename so a crash never leaves a torn record
+        fs::write(&tmp, json)
+            .and_then(|_| fs::rename(&tmp, &path))
+            .map_err(|e| {
+                Error::PersistenceError(format!("Failed to write {}: {}", path.display(), e))
+            })
+    }
+
+    fn load(&self, id: Uuid) -> Result<Option<AlgoExecution>> {
+        let _guard = self.lock.read();
+        let path = self.path_for(id);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let bytes = fs::read(&path).map_err(|e| {
+            Error::PersistenceError(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+        Ok(Some(serde_json::from_slice(&bytes)?))
+    }
+
+    fn load_all(&self) -> Result<Vec<AlgoExecution>> {
+        let _guard = self.lock.read();
+        let entries = fs::read_dir(&self.dir).map_err(|e| {
+            Error::PersistenceError(format!("Failed to list {}: {}", self.dir.display(), e))
+        })?;
+
+        let mut executions = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            match fs::read(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|b| serde_json::from_slice(&b).map_err(|e| e.to_string()))
+            {
+                Ok(execution) => executions.push(execution),
+                Err(e) => warn!("Skipping unreadable execution {}: {}", path.display(), e),
+            }
+        }
+
+        executions.sort_by_key(|e: &AlgoExecution| e.started_at);
+        Ok(executions)
+    }
+
+    fn remove(&self, id: Uuid) -> Result<()> {
+        let _guard = self.lock.write();
+        let path = self.path_for(id);
+        match fs::remove_file(&path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(Error::PersistenceError(format!(
+                "Failed to remove {}: {}",
+                path.display(),
+                e
+            ))),
+        }
+    }
+}
+
+/// Startup recovery policy for in-flight executions
+#[derive(Debug, Clone)]
+pub struct RecoveryPolicy {
+    /// Resume executions that are still within their schedule
+    pub resume_in_flight: bool,
+
+    /// Cancel executions not updated for longer than this
+    pub max_staleness_secs: i64,
+}
+
+impl Default for RecoveryPolicy {
+    fn default() -> Self {
+        Self {
+            resume_in_flight: true,
+            max_staleness_secs: 900,
+        }
+    }
+}
+
+/// Outcome of startup recovery
+#[derive(Debug, Default, Clone)]
+pub struct RecoveryReport {
+    /// Executions that should be handed back to their executor
+    pub resumable: Vec<AlgoExecution>,
+
+    /// Executions that were cancelled during recovery
+    pub cancelled: Vec<AlgoExecution>,
+}
+
+/// Inspect persisted executions on startup, cancelling those that cannot be
+/// resumed safely and returning the rest for resumption
+pub fn recover_executions(
+    store: &dyn AlgoExecutionStore,
+    policy: &RecoveryPolicy,
+) -> Result<RecoveryReport> {
+    let now = Utc::now();
+    let mut report = RecoveryReport::default();
+
+    for mut execution in store.load_all()? {
+        if execution.status.is_terminal() {
+            continue;
+        }
+
+        let reason = if !policy.resume_in_flight {
+            Some("Resumption disabled on restart".to_string())
+        } else if now >= execution.deadline {
+            Some("Schedule expired while offline".to_string())
+        } else if (now - execution.updated_at).num_seconds() > policy.max_staleness_secs {
+            Some("Execution state is stale".to_string())
+        } else {
+            None
+        };
+
+        match reason {
+            Some(reason) => {
+                warn!(
+                    "Cancelling {} execution {}: {}",
+                    execution.params.name(),
+                    execution.id,
+                    reason
+                );
+                execution.finish(AlgoExecutionStatus::Cancelled, Some(reason));
+                store.save(&execution)?;
+                report.cancelled.push(execution);
+            }
+            None => {
+                info!(
+                    "Resuming {} execution {} ({}/{} slices done)",
+                    execution.params.name(),
+                    execution.id,
+                    execution.slices_done,
+                    execution.slices_total
+                );
+                report.resumable.push(execution);
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Cancel the child orders of `execution` at the exchange, returning how
+/// many were cancelled
+///
+/// Child orders that already filled or were cancelled fail to cancel; those
+/// failures are only logged.
+pub async fn cancel_child_orders(
+    execution: &AlgoExecution,
+    exchange: &dyn ExchangeAdapter,
+) -> usize {
+    let mut cancelled = 0;
+    for client_order_id in &execution.child_orders {
+        match exchange
+            .cancel_order(&execution.symbol, client_order_id)
+            .await
+        {
+            Ok(()) => cancelled += 1,
+            Err(e) => debug!(
+                "Child order {} of execution {} not cancelled: {}",
+                client_order_id, execution.id, e
+            ),
+        }
+    }
+    if cancelled > 0 {
+        warn!(
+            "Cancelled {} resting child orders of {} execution {}",
+            cancelled,
+            execution.params.name(),
+            execution.id
+        );
+    }
+    cancelled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ea_okx_core::exchange::{ExchangeOrder, InstrumentInfo, MarketEvent, MarketSubscription};
+    use ea_okx_core::models::Order;
+    use ea_okx_core::types::InstrumentKind;
+
+    fn sample_twap() -> AlgoExecution {
+        let config = TwapConfig {
+            total_quantity: Quantity::new(dec!(1.0)).unwrap(),
+            duration_minutes: 10,
+            slice_interval_seconds: 60,
+            ..Default::default()
+        };
+        AlgoExecution::new_twap(
+            config,
+            Symbol::new("BTC-USDT").unwrap(),
+            OrderSide::Buy,
+            Price::new(dec!(50000)).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_twap_schedule() {
+        let execution = sample_twap();
+        assert_eq!(execution.slices_total, 10);
+        assert_eq!(execution.slice_interval_seconds, 60);
+        assert_eq!(execution.status, AlgoExecutionStatus::Running);
+    }
+
+    #[test]
+    fn test_record_slice_progress() {
+        let mut execution = sample_twap();
+        let price = Price::new(dec!(50000)).unwrap();
+
+        execution.record_slice(dec!(0.1), price, true);
+        execution.record_slice(Decimal::ZERO, price, false);
+
+        assert_eq!(execution.slices_done, 2);
+        assert_eq!(execution.slices_failed, 1);
+        assert_eq!(execution.remaining_quantity(), dec!(0.9));
+        assert_eq!(execution.progress_pct(), dec!(10));
+        assert_eq!(execution.average_price(), Some(price));
+
+        execution.record_slice(dec!(0.9), price, true);
+        assert_eq!(execution.status, AlgoExecutionStatus::Completed);
+    }
+
+    #[test]
+    fn test_file_store_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("algo-store-{}", Uuid::new_v4()));
+        let store = FileAlgoExecutionStore::new(&dir).unwrap();
+        let execution = sample_twap();
+
+        store.save(&execution).unwrap();
+        let loaded = store.load(execution.id).unwrap().unwrap();
+        assert_eq!(loaded.id, execution.id);
+        assert_eq!(loaded.slices_total, execution.slices_total);
+        assert_eq!(store.load_all().unwrap().len(), 1);
+
+        store.remove(execution.id).unwrap();
+        assert!(store.load(execution.id).unwrap().is_none());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_recovery_resumes_and_cancels() {
+        let store = InMemoryAlgoExecutionStore::new();
+
+        let fresh = sample_twap();
+        let mut expired = sample_twap();
+        expired.deadline = Utc::now() - Duration::minutes(1);
+        let mut stale = sample_twap();
+        stale.updated_at = Utc::now() - Duration::hours(1);
+        let mut done = sample_twap();
+        done.finish(AlgoExecutionStatus::Completed, None);
+
+        for execution in [&fresh, &expired, &stale, &done] {
+            store.save(execution).unwrap();
+        }
+
+        let report = recover_executions(&store, &RecoveryPolicy::default()).unwrap();
+        assert_eq!(report.resumable.len(), 1);
+        assert_eq!(report.resumable[0].id, fresh.id);
+        assert_eq!(report.cancelled.len(), 2);
+
+        let persisted = store.load(expired.id).unwrap().unwrap();
+        assert_eq!(persisted.status, AlgoExecutionStatus::Cancelled);
+    }
+
+    /// Exchange where only `resting` orders can still be cancelled
+    #[derive(Default)]
+    struct RestingOrders {
+        resting: Vec<String>,
+        cancelled: parking_lot::Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl ExchangeAdapter for RestingOrders {
+        fn name(&self) -> &'static str {
+            "resting"
+        }
+
+        async fn place_order(&self, _order: &Order) -> ea_okx_core::Result<String> {
+            unimplemented!()
+        }
+
+        async fn cancel_order(&self, _symbol: &Symbol, id: &str) -> ea_okx_core::Result<()> {
+            if !self.resting.iter().any(|resting| resting == id) {
+                return Err(ea_okx_core::Error::NotFound(id.to_string()));
+            }
+            self.cancelled.lock().push(id.to_string());
+            Ok(())
+        }
+
+        async fn amend_order(
+            &self,
+            _symbol: &Symbol,
+            _id: &str,
+            _quantity: Option<Decimal>,
+            _price: Option<Decimal>,
+        ) -> ea_okx_core::Result<()> {
+            unimplemented!()
+        }
+
+        async fn order(
+            &self,
+            _symbol: &Symbol,
+            _id: &str,
+        ) -> ea_okx_core::Result<Option<ExchangeOrder>> {
+            unimplemented!()
+        }
+
+        async fn instruments(
+            &self,
+            _kind: InstrumentKind,
+        ) -> ea_okx_core::Result<Vec<InstrumentInfo>> {
+            unimplemented!()
+        }
+
+        async fn subscribe(&self, _subs: &[MarketSubscription]) -> ea_okx_core::Result<()> {
+            unimplemented!()
+        }
+
+        async fn next_event(&self) -> ea_okx_core::Result<Option<MarketEvent>> {
+            unimplemented!()
+        }
+
+        async fn disconnect(&self) -> ea_okx_core::Result<()> {
+            unimplemented!()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancel_child_orders_at_exchange() {
+        let mut execution = sample_twap();
+        execution.record_child_order("filled-slice");
+        execution.record_child_order("resting-slice");
+
+        let exchange = RestingOrders {
+            resting: vec!["resting-slice".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(cancel_child_orders(&execution, &exchange).await, 1);
+        assert_eq!(
+            *exchange.cancelled.lock(),
+            vec!["resting-slice".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_recovery_without_resume_cancels_all() {
+        let store = InMemoryAlgoExecutionStore::new();
+        store.save(&sample_twap()).unwrap();
+
+        let policy = RecoveryPolicy {
+            resume_in_flight: false,
+            ..Default::default()
+        };
+        let report = recover_executions(&store, &policy).unwrap();
+        assert!(report.resumable.is_empty());
+        assert_eq!(report.cancelled.len(), 1);
+    }
+}
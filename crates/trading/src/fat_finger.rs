@@ -0,0 +1,498 @@
+//! Fat-finger guard
+//!
+//! Last sanity check before an order leaves the process. [`FatFingerGuard`]
+//! compares each order with the local view of its market and flags orders
+//! priced too far from mid, with a notional above a cap, or sized at a large
+//! multiple of the average recent trade. Depending on the symbol's limits a
+//! breach either rejects the order outright or holds it until it is
+//! explicitly confirmed, so a bad strategy output or a mistyped UI order
+//! cannot reach the exchange unnoticed.
+//!
+//! When a blended multi-source reference price is fed in it replaces the
+//! local mid as the yardstick, so a bad OKX print cannot move the baseline.
+
+use chrono::{DateTime, Duration, Utc};
+use ea_okx_core::Symbol;
+use ea_okx_core::models::Order;
+use parking_lot::RwLock;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use tracing::warn;
+use uuid::Uuid;
+
+/// What to do with an order that breaches a limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreachAction {
+    /// Refuse the order
+    Reject,
+    /// Hold the order until it is explicitly confirmed
+    Confirm,
+}
+
+/// Sanity limits for one symbol
+///
+/// A `None` limit is not checked.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FatFingerLimits {
+    /// Largest distance of a limit price from mid (e.g., 0.05 = 5%)
+    pub max_price_deviation: Option<Decimal>,
+
+    /// Largest order notional in quote currency
+    pub max_notional: Option<Decimal>,
+
+    /// Largest order size as a multiple of the average recent trade size
+    pub max_size_multiple: Option<Decimal>,
+
+    pub action: BreachAction,
+}
+
+impl Default for FatFingerLimits {
+    fn default() -> Self {
+        Self {
+            max_price_deviation: Some(dec!(0.05)),
+            max_notional: Some(dec!(100000)),
+            max_size_multiple: Some(dec!(50)),
+            action: BreachAction::Confirm,
+        }
+    }
+}
+
+/// Fat-finger guard configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FatFingerConfig {
+    /// Limits for symbols without their own entry
+    pub default_limits: FatFingerLimits,
+
+    pub symbol_limits: HashMap<Symbol, FatFingerLimits>,
+
+    /// Recent trades averaged for the size check
+    pub trade_window: usize,
+
+    /// Quotes older than this are not used for the price check
+    pub max_quote_age_secs: i64,
+}
+
+impl Default for FatFingerConfig {
+    fn default() -> Self {
+        Self {
+            default_limits: FatFingerLimits::default(),
+            symbol_limits: HashMap::new(),
+            trade_window: 100,
+            max_quote_age_secs: 10,
+        }
+    }
+}
+
+impl FatFingerConfig {
+    pub fn limits_for(&self, symbol: &Symbol) -> &FatFingerLimits {
+        self.symbol_limits
+            .get(symbol)
+            .unwrap_or(&self.default_limits)
+    }
+}
+
+/// A limit an order exceeded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "check", rename_all = "snake_case")]
+pub enum FatFingerBreach {
+    PriceDeviation {
+        price: Decimal,
+        mid: Decimal,
+        deviation: Decimal,
+        max: Decimal,
+    },
+    Notional {
+        notional: Decimal,
+        max: Decimal,
+    },
+    Size {
+        quantity: Decimal,
+        average_trade: Decimal,
+        multiple: Decimal,
+        max: Decimal,
+    },
+}
+
+impl std::fmt::Display for FatFingerBreach {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PriceDeviation {
+                price,
+                mid,
+                deviation,
+                max,
+            } => write!(
+                f,
+                "price {} is {:.2}% from mid {} (max {:.2}%)",
+                price,
+                deviation * dec!(100),
+                mid,
+                max * dec!(100)
+            ),
+            Self::Notional { notional, max } => {
+                write!(f, "notional {} exceeds cap {}", notional, max)
+            }
+            Self::Size {
+                quantity,
+                average_trade,
+                multiple,
+                max,
+            } => write!(
+                f,
+                "size {} is {:.1}x the average trade {} (max {}x)",
+                quantity, multiple, average_trade, max
+            ),
+        }
+    }
+}
+
+/// Outcome of the fat-finger check
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum FatFingerDecision {
+    Pass,
+    /// Order may be sent once confirmed
+    ConfirmationRequired {
+        breaches: Vec<FatFingerBreach>,
+    },
+    Rejected {
+        breaches: Vec<FatFingerBreach>,
+    },
+}
+
+impl FatFingerDecision {
+    /// Breaches joined into one line for errors and logs
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Pass => "no limits breached".to_string(),
+            Self::ConfirmationRequired { breaches } | Self::Rejected { breaches } => breaches
+                .iter()
+                .map(|b| b.to_string())
+                .collect::<Vec<_>>()
+                .join("; "),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct MarketView {
+    mid: Option<(Decimal, DateTime<Utc>)>,
+    reference: Option<(Decimal, DateTime<Utc>)>,
+    trade_sizes: VecDeque<Decimal>,
+}
+
+/// Checks orders against per-symbol sanity limits and the local market view
+pub struct FatFingerGuard {
+    config: RwLock<FatFingerConfig>,
+    markets: RwLock<HashMap<Symbol, MarketView>>,
+    confirmed: RwLock<HashSet<Uuid>>,
+}
+
+impl FatFingerGuard {
+    pub fn new(config: FatFingerConfig) -> Self {
+        Self {
+            config: RwLock::new(config),
+            markets: RwLock::new(HashMap::new()),
+            confirmed: RwLock::new(HashSet::new()),
+        }
+    }
+
+    pub fn config(&self) -> FatFingerConfig {
+        self.config.read().clone()
+    }
+
+    pub fn set_default_limits(&self, limits: FatFingerLimits) {
+        self.config.write().default_limits = limits;
+    }
+
+    /// Override the limits for one symbol
+    pub fn set_symbol_limits(&self, symbol: Symbol, limits: FatFingerLimits) {
+        self.config.write().symbol_limits.insert(symbol, limits);
+    }
+
+    pub fn clear_symbol_limits(&self, symbol: &Symbol) {
+        self.config.write().symbol_limits.remove(symbol);
+    }
+
+    /// Record the top of the local order book
+    pub fn update_quote(&self, symbol: &Symbol, best_bid: Decimal, best_ask: Decimal) {
+        if best_bid <= Decimal::ZERO || best_ask < best_bid {
+            return;
+        }
+        let mid = (best_bid + best_ask) / dec!(2);
+        self.markets.write().entry(symbol.clone()).or_default().mid = Some((mid, Utc::now()));
+    }
+
+    /// Record a blended reference price, preferred over the local mid
+    pub fn update_reference_price(&self, symbol: &Symbol, price: Decimal) {
+        if price <= Decimal::ZERO {
+            return;
+        }
+        self.markets
+            .write()
+            .entry(symbol.clone())
+            .or_default()
+            .reference = Some((price, Utc::now()));
+    }
+
+    /// Record a public trade for the average trade size
+    pub fn record_trade(&self, symbol: &Symbol, size: Decimal) {
+        let window = self.config.read().trade_window.max(1);
+        let mut markets = self.markets.write();
+        let sizes = &mut markets.entry(symbol.clone()).or_default().trade_sizes;
+        sizes.push_back(size);
+        while sizes.len() > window {
+            sizes.pop_front();
+        }
+    }
+
+    /// Current mid, if a fresh quote is known
+    pub fn mid(&self, symbol: &Symbol) -> Option<Decimal> {
+        let max_age = Duration::seconds(self.config.read().max_quote_age_secs);
+        let (mid, at) = self.markets.read().get(symbol)?.mid?;
+        (Utc::now() - at <= max_age).then_some(mid)
+    }
+
+    /// Price orders are judged against: a fresh reference price, else mid
+    pub fn fair_price(&self, symbol: &Symbol) -> Option<Decimal> {
+        let max_age = Duration::seconds(self.config.read().max_quote_age_secs);
+        let reference = self
+            .markets
+            .read()
+            .get(symbol)?
+            .reference
+            .filter(|(_, at)| Utc::now() - *at <= max_age)
+            .map(|(price, _)| price);
+        reference.or_else(|| self.mid(symbol))
+    }
+
+    pub fn average_trade_size(&self, symbol: &Symbol) -> Option<Decimal> {
+        let markets = self.markets.read();
+        let sizes = &markets.get(symbol)?.trade_sizes;
+        if sizes.is_empty() {
+            return None;
+        }
+        Some(sizes.iter().sum::<Decimal>() / Decimal::from(sizes.len()))
+    }
+
+    /// Allow `order_id` through a confirmation-required breach once
+    pub fn confirm(&self, order_id: Uuid) {
+        self.confirmed.write().insert(order_id);
+    }
+
+    /// Check `order` against its limits
+    ///
+    /// Prices are compared with [`fair_price`](Self::fair_price). Market
+    /// orders are valued at it and skip the price check. Checks
+    /// whose market data is missing are skipped rather than failed.
+    pub fn check(&self, order: &Order) -> FatFingerDecision {
+        let limits = self.config.read().limits_for(&order.symbol).clone();
+        let mid = self.fair_price(&order.symbol);
+        let quantity = order.quantity.as_decimal();
+        let mut breaches = Vec::new();
+
+        if let (Some(max), Some(price), Some(mid)) = (limits.max_price_deviation, order.price, mid)
+        {
+            let price = price.as_decimal();
+            let deviation = (price - mid).abs() / mid;
+            if deviation > max {
+                breaches.push(FatFingerBreach::PriceDeviation {
+                    price,
+                    mid,
+                    deviation,
+                    max,
+                });
+            }
+        }
+
+        if let Some(max) = limits.max_notional
+            && let Some(price) = order.price.map(|p| p.as_decimal()).or(mid)
+        {
+            let notional = price * quantity;
+            if notional > max {
+                breaches.push(FatFingerBreach::Notional { notional, max });
+            }
+        }
+
+        if let Some(max) = limits.max_size_multiple
+            && let Some(average_trade) = self.average_trade_size(&order.symbol)
+            && average_trade > Decimal::ZERO
+        {
+            let multiple = quantity / average_trade;
+            if multiple > max {
+                breaches.push(FatFingerBreach::Size {
+                    quantity,
+                    average_trade,
+                    multiple,
+                    max,
+                });
+            }
+        }
+
+        if breaches.is_empty() {
+            self.confirmed.write().remove(&order.id);
+            return FatFingerDecision::Pass;
+        }
+
+        let decision = match limits.action {
+            BreachAction::Confirm if self.confirmed.write().remove(&order.id) => {
+                warn!(
+                    "Order {} sent on confirmation despite fat-finger breaches",
+                    order.id
+                );
+                return FatFingerDecision::Pass;
+            }
+            BreachAction::Confirm => FatFingerDecision::ConfirmationRequired { breaches },
+            BreachAction::Reject => FatFingerDecision::Rejected { breaches },
+        };
+        warn!(
+            "Order {} on {} held by fat-finger guard: {}",
+            order.id,
+            order.symbol.as_str(),
+            decision.describe()
+        );
+        decision
+    }
+}
+
+impl Default for FatFingerGuard {
+    fn default() -> Self {
+        Self::new(FatFingerConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ea_okx_core::models::{OrderSide, OrderType};
+    use ea_okx_core::{Price, Quantity};
+
+    fn symbol() -> Symbol {
+        Symbol::new("BTC-USDT").unwrap()
+    }
+
+    fn order(qty: Decimal, price: Option<Decimal>) -> Order {
+        Order::new(
+            Uuid::new_v4(),
+            symbol(),
+            OrderSide::Buy,
+            if price.is_some() {
+                OrderType::Limit
+            } else {
+                OrderType::Market
+            },
+            Quantity::new(qty).unwrap(),
+            price.map(|p| Price::new(p).unwrap()),
+        )
+    }
+
+    fn guard(action: BreachAction) -> FatFingerGuard {
+        let guard = FatFingerGuard::new(FatFingerConfig {
+            default_limits: FatFingerLimits {
+                action,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        guard.update_quote(&symbol(), dec!(49990), dec!(50010));
+        for _ in 0..10 {
+            guard.record_trade(&symbol(), dec!(0.01));
+        }
+        guard
+    }
+
+    #[test]
+    fn test_sane_orders_pass() {
+        let guard = guard(BreachAction::Reject);
+        assert_eq!(guard.mid(&symbol()), Some(dec!(50000)));
+        assert_eq!(
+            guard.check(&order(dec!(0.1), Some(dec!(51000)))),
+            FatFingerDecision::Pass
+        );
+        assert_eq!(
+            guard.check(&order(dec!(0.1), None)),
+            FatFingerDecision::Pass
+        );
+    }
+
+    #[test]
+    fn test_each_limit_is_reported() {
+        let guard = guard(BreachAction::Reject);
+
+        // Typo: 5000 instead of 50000
+        let decision = guard.check(&order(dec!(0.1), Some(dec!(5000))));
+        assert!(matches!(
+            &decision,
+            FatFingerDecision::Rejected { breaches }
+                if matches!(breaches[..], [FatFingerBreach::PriceDeviation { .. }])
+        ));
+
+        // 3 BTC at market: 150000 notional and 300x the average trade
+        let FatFingerDecision::Rejected { breaches } = guard.check(&order(dec!(3), None)) else {
+            panic!("expected rejection");
+        };
+        assert!(matches!(
+            breaches[..],
+            [
+                FatFingerBreach::Notional { .. },
+                FatFingerBreach::Size { .. }
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_confirmation_lets_order_through_once() {
+        let guard = guard(BreachAction::Confirm);
+        let order = order(dec!(0.1), Some(dec!(60000)));
+
+        assert!(matches!(
+            guard.check(&order),
+            FatFingerDecision::ConfirmationRequired { .. }
+        ));
+        guard.confirm(order.id);
+        assert_eq!(guard.check(&order), FatFingerDecision::Pass);
+        assert!(matches!(
+            guard.check(&order),
+            FatFingerDecision::ConfirmationRequired { .. }
+        ));
+    }
+
+    #[test]
+    fn test_reference_price_outranks_bad_mid() {
+        let guard = guard(BreachAction::Reject);
+        // A bad print drags the local book far from the market
+        guard.update_quote(&symbol(), dec!(9990), dec!(10010));
+        guard.update_reference_price(&symbol(), dec!(50000));
+        assert_eq!(guard.fair_price(&symbol()), Some(dec!(50000)));
+
+        assert_eq!(
+            guard.check(&order(dec!(0.1), Some(dec!(50100)))),
+            FatFingerDecision::Pass
+        );
+        assert!(matches!(
+            guard.check(&order(dec!(0.1), Some(dec!(10000)))),
+            FatFingerDecision::Rejected { .. }
+        ));
+    }
+
+    #[test]
+    fn test_symbol_limits_override_default() {
+        let guard = guard(BreachAction::Reject);
+        guard.set_symbol_limits(
+            symbol(),
+            FatFingerLimits {
+                max_price_deviation: None,
+                max_notional: None,
+                max_size_multiple: None,
+                action: BreachAction::Reject,
+            },
+        );
+        assert_eq!(
+            guard.check(&order(dec!(10), Some(dec!(5000)))),
+            FatFingerDecision::Pass
+        );
+    }
+}
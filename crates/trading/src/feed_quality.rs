@@ -0,0 +1,138 @@
+//! Feed-quality trading thresholds
+//!
+//! Strategies can be configured to refuse opening positions on a symbol
+//! whose market data feed scores below a minimum, since signals computed
+//! from rejected, gapping or anomalous data are not worth acting on. Scores
+//! (0 to 100) are pushed in from data quality control; symbols without a
+//! score are not held back. Reduce-only orders always pass so positions can
+//! still be closed.
+
+use ea_okx_core::Symbol;
+use ea_okx_core::models::Order;
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use tracing::info;
+use uuid::Uuid;
+
+/// Latest feed scores and the per-strategy minimums they are held to
+#[derive(Debug, Default)]
+pub struct FeedQualityGuard {
+    scores: RwLock<HashMap<Symbol, f64>>,
+    min_scores: RwLock<HashMap<Uuid, f64>>,
+}
+
+impl FeedQualityGuard {
+    /// Record `symbol`'s latest score, forgetting it on `None`
+    pub fn update_score(&self, symbol: &Symbol, score: Option<f64>) {
+        match score {
+            Some(score) => {
+                self.scores.write().insert(symbol.clone(), score);
+            }
+            None => {
+                self.scores.write().remove(symbol);
+            }
+        }
+    }
+
+    pub fn score(&self, symbol: &Symbol) -> Option<f64> {
+        self.scores.read().get(symbol).copied()
+    }
+
+    /// Refuse the strategy's opening orders on symbols scoring below
+    /// `min_score`, or clear its minimum with `None`
+    pub fn set_min_score(&self, strategy_id: Uuid, min_score: Option<f64>) {
+        match min_score {
+            Some(min_score) => {
+                info!(
+                    "Strategy {} requires feed quality {:.0}",
+                    strategy_id, min_score
+                );
+                self.min_scores.write().insert(strategy_id, min_score);
+            }
+            None => {
+                if self.min_scores.write().remove(&strategy_id).is_some() {
+                    info!("Strategy {} feed quality minimum cleared", strategy_id);
+                }
+            }
+        }
+    }
+
+    pub fn min_score(&self, strategy_id: Uuid) -> Option<f64> {
+        self.min_scores.read().get(&strategy_id).copied()
+    }
+
+    /// Every strategy with a minimum, and that minimum
+    pub fn min_scores(&self) -> Vec<(Uuid, f64)> {
+        self.min_scores
+            .read()
+            .iter()
+            .map(|(id, score)| (*id, *score))
+            .collect()
+    }
+
+    /// Why `order` may not be sent on its symbol's feed quality, if it may not
+    pub fn blocked_reason(&self, order: &Order) -> Option<String> {
+        if order.reduce_only {
+            return None;
+        }
+        let min_score = self.min_score(order.strategy_id)?;
+        let score = self.score(&order.symbol)?;
+        (score < min_score).then(|| {
+            format!(
+                "{} feed quality {:.1} below strategy minimum {:.1}",
+                order.symbol.as_str(),
+                score,
+                min_score
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ea_okx_core::Quantity;
+    use ea_okx_core::models::{OrderSide, OrderType};
+    use rust_decimal_macros::dec;
+
+    fn order(strategy_id: Uuid, symbol: &Symbol) -> Order {
+        Order::new(
+            strategy_id,
+            symbol.clone(),
+            OrderSide::Buy,
+            OrderType::Market,
+            Quantity::new(dec!(0.1)).unwrap(),
+            None,
+        )
+    }
+
+    #[test]
+    fn test_opening_orders_blocked_below_strategy_minimum() {
+        let guard = FeedQualityGuard::default();
+        let strict = Uuid::new_v4();
+        let relaxed = Uuid::new_v4();
+        let btc = Symbol::new("BTC-USDT").unwrap();
+        guard.set_min_score(strict, Some(80.0));
+
+        // No score yet: nothing to judge by
+        assert!(guard.blocked_reason(&order(strict, &btc)).is_none());
+
+        guard.update_score(&btc, Some(62.5));
+        let reason = guard.blocked_reason(&order(strict, &btc)).unwrap();
+        assert!(reason.contains("62.5"));
+        assert!(guard.blocked_reason(&order(relaxed, &btc)).is_none());
+        assert!(
+            guard
+                .blocked_reason(&order(strict, &btc).with_reduce_only())
+                .is_none()
+        );
+
+        guard.update_score(&btc, Some(95.0));
+        assert!(guard.blocked_reason(&order(strict, &btc)).is_none());
+
+        guard.update_score(&btc, Some(10.0));
+        guard.set_min_score(strict, None);
+        assert!(guard.blocked_reason(&order(strict, &btc)).is_none());
+        assert!(guard.min_scores().is_empty());
+    }
+}
@@ -0,0 +1,415 @@
+//! Execution gate
+//!
+//! Single choke point every outgoing order passes through before it reaches
+//! the exchange. Enforces a global "trading disabled" switch, per-symbol halts,
+//! per-strategy dry-run flags, the degraded-mode policy, the account daily
+//! loss lock, per-strategy feed quality minimums and order, cancel and signal
+//! quotas, logging what would have been sent whenever an order is held back.
+
+use crate::daily_loss::DailyLossLock;
+use crate::degraded::DegradedMode;
+use crate::feed_quality::FeedQualityGuard;
+use crate::quotas::{QuotaKind, QuotaTracker};
+use ea_okx_core::Symbol;
+use ea_okx_core::models::Order;
+use parking_lot::RwLock;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// What the gate decided for an order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GateDecision {
+    /// Send to the exchange
+    Send,
+
+    /// Strategy is in dry-run: simulate locally, send nothing
+    DryRun,
+
+    /// Trading is disabled, the symbol is halted, the exchange is degraded,
+    /// the account hit its daily loss limit, the symbol's feed quality is too
+    /// poor or the strategy is paused: drop the order
+    Blocked(String),
+
+    /// The strategy is over its quota: drop the order
+    Throttled(String),
+}
+
+/// Global and per-strategy execution switches
+#[derive(Debug)]
+pub struct ExecutionGate {
+    trading_enabled: AtomicBool,
+    dry_run_strategies: RwLock<HashSet<Uuid>>,
+    halted_symbols: RwLock<HashMap<Symbol, String>>,
+    degraded: DegradedMode,
+    daily_loss: DailyLossLock,
+    feed_quality: FeedQualityGuard,
+    quotas: QuotaTracker,
+}
+
+impl ExecutionGate {
+    /// Create a gate with trading enabled and no dry-run strategies
+    pub fn new() -> Self {
+        Self {
+            trading_enabled: AtomicBool::new(true),
+            dry_run_strategies: RwLock::new(HashSet::new()),
+            halted_symbols: RwLock::new(HashMap::new()),
+            degraded: DegradedMode::default(),
+            daily_loss: DailyLossLock::default(),
+            feed_quality: FeedQualityGuard::default(),
+            quotas: QuotaTracker::default(),
+        }
+    }
+
+    /// Degraded-mode switch and policy
+    pub fn degraded(&self) -> &DegradedMode {
+        &self.degraded
+    }
+
+    /// Daily P&L per account and the loss lock
+    pub fn daily_loss(&self) -> &DailyLossLock {
+        &self.daily_loss
+    }
+
+    /// Feed quality scores and per-strategy minimums
+    pub fn feed_quality(&self) -> &FeedQualityGuard {
+        &self.feed_quality
+    }
+
+    /// Per-strategy quotas, usage and quota pauses
+    pub fn quotas(&self) -> &QuotaTracker {
+        &self.quotas
+    }
+
+    /// Enable or disable sending orders for every strategy
+    pub fn set_trading_enabled(&self, enabled: bool) {
+        let was = self.trading_enabled.swap(enabled, Ordering::SeqCst);
+        if was != enabled {
+            warn!("Trading {}", if enabled { "enabled" } else { "disabled" });
+        }
+    }
+
+    pub fn is_trading_enabled(&self) -> bool {
+        self.trading_enabled.load(Ordering::SeqCst)
+    }
+
+    /// Put a strategy into or out of dry-run
+    pub fn set_strategy_dry_run(&self, strategy_id: Uuid, dry_run: bool) {
+        let changed = if dry_run {
+            self.dry_run_strategies.write().insert(strategy_id)
+        } else {
+            self.dry_run_strategies.write().remove(&strategy_id)
+        };
+        if changed {
+            info!("Strategy {} dry-run: {}", strategy_id, dry_run);
+        }
+    }
+
+    pub fn is_dry_run(&self, strategy_id: Uuid) -> bool {
+        self.dry_run_strategies.read().contains(&strategy_id)
+    }
+
+    /// Strategies currently in dry-run
+    pub fn dry_run_strategies(&self) -> Vec<Uuid> {
+        self.dry_run_strategies.read().iter().copied().collect()
+    }
+
+    /// Stop sending orders for `symbol` (e.g., the instrument was suspended)
+    pub fn halt_symbol(&self, symbol: &Symbol, reason: impl Into<String>) {
+        let reason = reason.into();
+        warn!("Trading halted for {}: {}", symbol.as_str(), reason);
+        self.halted_symbols.write().insert(symbol.clone(), reason);
+    }
+
+    /// Allow orders for `symbol` again
+    pub fn resume_symbol(&self, symbol: &Symbol) {
+        if self.halted_symbols.write().remove(symbol).is_some() {
+            info!("Trading resumed for {}", symbol.as_str());
+        }
+    }
+
+    /// Reason `symbol` is halted, if it is
+    pub fn halt_reason(&self, symbol: &Symbol) -> Option<String> {
+        self.halted_symbols.read().get(symbol).cloned()
+    }
+
+    /// Symbols currently halted, with the reason
+    pub fn halted_symbols(&self) -> Vec<(Symbol, String)> {
+        self.halted_symbols
+            .read()
+            .iter()
+            .map(|(symbol, reason)| (symbol.clone(), reason.clone()))
+            .collect()
+    }
+
+    /// Decide whether `order` may be sent, counting it against the
+    /// strategy's order quota if it is
+    ///
+    /// Dry-run strategies keep simulating while trading is disabled, since
+    /// nothing they produce reaches the exchange.
+    pub fn check(&self, order: &Order) -> GateDecision {
+        self.evaluate(order, true)
+    }
+
+    /// What [`check`](Self::check) would decide, without using up quota
+    pub fn preview(&self, order: &Order) -> GateDecision {
+        self.evaluate(order, false)
+    }
+
+    /// Decide whether the strategy may cancel an order, counting it against
+    /// its cancel quota if it is
+    ///
+    /// Quota pauses do not block cancels, so a paused strategy's resting
+    /// orders can still be cleaned up.
+    pub fn check_cancel(&self, strategy_id: Uuid) -> GateDecision {
+        if self.is_dry_run(strategy_id) {
+            return GateDecision::DryRun;
+        }
+        match self.quotas.acquire(strategy_id, QuotaKind::Cancel) {
+            Ok(()) => GateDecision::Send,
+            Err(breach) => GateDecision::Throttled(breach.describe()),
+        }
+    }
+
+    /// Decide whether a signal from the strategy may be queued, counting it
+    /// against its signal quota if it is
+    pub fn check_signal(&self, strategy_id: Uuid) -> GateDecision {
+        if let Some(reason) = self.quotas.paused_reason(strategy_id) {
+            return GateDecision::Blocked(reason);
+        }
+        match self.quotas.acquire(strategy_id, QuotaKind::Signal) {
+            Ok(()) => GateDecision::Send,
+            Err(breach) => GateDecision::Throttled(breach.describe()),
+        }
+    }
+
+    fn evaluate(&self, order: &Order, record: bool) -> GateDecision {
+        if self.is_dry_run(order.strategy_id) {
+            info!("Dry run, not sending: {}", describe(order));
+            return GateDecision::DryRun;
+        }
+
+        if !self.is_trading_enabled() {
+            warn!("Trading disabled, dropping: {}", describe(order));
+            return GateDecision::Blocked("Trading is disabled".to_string());
+        }
+
+        if let Some(reason) = self.halt_reason(&order.symbol) {
+            warn!(
+                "{} halted, dropping: {}",
+                order.symbol.as_str(),
+                describe(order)
+            );
+            return GateDecision::Blocked(format!(
+                "Trading halted for {}: {}",
+                order.symbol.as_str(),
+                reason
+            ));
+        }
+
+        if let Some(reason) = self.degraded.blocked_reason(order) {
+            warn!("Exchange degraded, dropping: {}", describe(order));
+            return GateDecision::Blocked(reason);
+        }
+
+        if let Some(reason) = self.daily_loss.blocked_reason(order) {
+            warn!("Daily loss limit hit, dropping: {}", describe(order));
+            return GateDecision::Blocked(reason);
+        }
+
+        if let Some(reason) = self.feed_quality.blocked_reason(order) {
+            warn!("Feed quality too poor, dropping: {}", describe(order));
+            return GateDecision::Blocked(reason);
+        }
+
+        if let Some(reason) = self.quotas.paused_reason(order.strategy_id) {
+            warn!("Strategy paused, dropping: {}", describe(order));
+            return GateDecision::Blocked(reason);
+        }
+
+        if !record {
+            if self
+                .quotas
+                .would_exceed(order.strategy_id, QuotaKind::Order)
+            {
+                return GateDecision::Throttled(format!(
+                    "Strategy {} is at its order quota",
+                    order.strategy_id
+                ));
+            }
+        } else if let Err(breach) = self.quotas.acquire(order.strategy_id, QuotaKind::Order) {
+            warn!("Over quota, dropping: {}", describe(order));
+            return GateDecision::Throttled(breach.describe());
+        }
+
+        GateDecision::Send
+    }
+}
+
+impl Default for ExecutionGate {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn describe(order: &Order) -> String {
+    format!(
+        "order {} (strategy {}) {:?} {:?} {} {} @ {}",
+        order.id,
+        order.strategy_id,
+        order.side,
+        order.order_type,
+        order.quantity.as_decimal(),
+        order.symbol.as_str(),
+        order
+            .price
+            .map(|p| p.as_decimal().to_string())
+            .unwrap_or_else(|| "market".to_string())
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quotas::StrategyQuota;
+    use ea_okx_core::models::{OrderSide, OrderType};
+    use ea_okx_core::{Quantity, Symbol};
+    use rust_decimal_macros::dec;
+
+    fn order(strategy_id: Uuid) -> Order {
+        Order::new(
+            strategy_id,
+            Symbol::new("BTC-USDT").unwrap(),
+            OrderSide::Buy,
+            OrderType::Market,
+            Quantity::new(dec!(0.1)).unwrap(),
+            None,
+        )
+    }
+
+    #[test]
+    fn test_global_switch_blocks_orders() {
+        let gate = ExecutionGate::new();
+        let strategy = Uuid::new_v4();
+        assert_eq!(gate.check(&order(strategy)), GateDecision::Send);
+
+        gate.set_trading_enabled(false);
+        assert!(matches!(
+            gate.check(&order(strategy)),
+            GateDecision::Blocked(_)
+        ));
+
+        gate.set_trading_enabled(true);
+        assert_eq!(gate.check(&order(strategy)), GateDecision::Send);
+    }
+
+    #[test]
+    fn test_dry_run_is_per_strategy_and_survives_disable() {
+        let gate = ExecutionGate::new();
+        let dry = Uuid::new_v4();
+        let live = Uuid::new_v4();
+        gate.set_strategy_dry_run(dry, true);
+
+        assert_eq!(gate.check(&order(dry)), GateDecision::DryRun);
+        assert_eq!(gate.check(&order(live)), GateDecision::Send);
+
+        gate.set_trading_enabled(false);
+        assert_eq!(gate.check(&order(dry)), GateDecision::DryRun);
+
+        gate.set_strategy_dry_run(dry, false);
+        assert!(gate.dry_run_strategies().is_empty());
+        assert!(matches!(gate.check(&order(dry)), GateDecision::Blocked(_)));
+    }
+
+    #[test]
+    fn test_halted_symbol_blocks_only_that_symbol() {
+        let gate = ExecutionGate::new();
+        let strategy = Uuid::new_v4();
+        let btc = Symbol::new("BTC-USDT").unwrap();
+        let mut eth_order = order(strategy);
+        eth_order.symbol = Symbol::new("ETH-USDT").unwrap();
+
+        gate.halt_symbol(&btc, "instrument suspended");
+        assert!(matches!(
+            gate.check(&order(strategy)),
+            GateDecision::Blocked(reason) if reason.contains("instrument suspended")
+        ));
+        assert_eq!(gate.check(&eth_order), GateDecision::Send);
+
+        gate.resume_symbol(&btc);
+        assert!(gate.halted_symbols().is_empty());
+        assert_eq!(gate.check(&order(strategy)), GateDecision::Send);
+    }
+
+    #[test]
+    fn test_degraded_mode_blocks_opening_orders() {
+        let gate = ExecutionGate::new();
+        let strategy = Uuid::new_v4();
+        gate.degraded().enter(vec!["REST error rate".to_string()]);
+
+        assert!(matches!(
+            gate.check(&order(strategy)),
+            GateDecision::Blocked(reason) if reason.contains("REST error rate")
+        ));
+        assert_eq!(
+            gate.check(&order(strategy).with_reduce_only()),
+            GateDecision::Send
+        );
+
+        gate.degraded().exit();
+        assert_eq!(gate.check(&order(strategy)), GateDecision::Send);
+    }
+
+    #[test]
+    fn test_quota_throttles_then_pauses_strategy() {
+        let gate = ExecutionGate::new();
+        let strategy = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        gate.quotas().set_strategy_quota(
+            strategy,
+            StrategyQuota {
+                max_orders_per_minute: Some(2),
+                max_signals_per_second: Some(1),
+                pause_after_breaches: 2,
+                ..StrategyQuota::unlimited()
+            },
+        );
+
+        assert_eq!(gate.preview(&order(strategy)), GateDecision::Send);
+        assert_eq!(gate.check(&order(strategy)), GateDecision::Send);
+        assert_eq!(gate.check(&order(strategy)), GateDecision::Send);
+        assert!(matches!(
+            gate.preview(&order(strategy)),
+            GateDecision::Throttled(_)
+        ));
+        assert!(matches!(
+            gate.check(&order(strategy)),
+            GateDecision::Throttled(_)
+        ));
+        assert_eq!(gate.check(&order(other)), GateDecision::Send);
+
+        assert_eq!(gate.check_signal(strategy), GateDecision::Send);
+        assert!(matches!(
+            gate.check_signal(strategy),
+            GateDecision::Throttled(reason) if reason.contains("paused")
+        ));
+
+        // Paused: orders and signals are blocked, cancels still go through
+        assert!(matches!(
+            gate.check(&order(strategy)),
+            GateDecision::Blocked(_)
+        ));
+        assert!(matches!(
+            gate.check_signal(strategy),
+            GateDecision::Blocked(_)
+        ));
+        assert_eq!(gate.check_cancel(strategy), GateDecision::Send);
+
+        assert!(gate.quotas().resume(strategy));
+        assert!(matches!(
+            gate.check(&order(strategy)),
+            GateDecision::Throttled(_)
+        ));
+    }
+}
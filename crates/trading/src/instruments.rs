@@ -0,0 +1,425 @@
+//! Exchange instrument status
+//!
+//! OKX suspends, settles and delists instruments. [`InstrumentStatusTracker`]
+//! follows the public `instruments` channel (or polls an
+//! [`InstrumentStatusSource`]) and emits an [`InstrumentEvent`] whenever a
+//! symbol changes state. With an [`ExecutionGate`] attached, symbols that stop
+//! being tradable are halted there so no new orders reach the exchange, and
+//! resumed once they go live again.
+
+use crate::error::Result;
+use crate::gate::ExecutionGate;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use ea_okx_client::OkxRestClient;
+use ea_okx_client::models::{Channel, InstrumentData, SubscriptionRequest, WebSocketEvent};
+use ea_okx_client::websocket::OkxWebSocketClient;
+use ea_okx_core::Symbol;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// Trading state of an instrument
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstrumentStatus {
+    Live,
+    Suspended,
+    /// Listed but not yet open for trading
+    PreOpen,
+    Test,
+    /// Expiring contract in settlement
+    Settlement,
+    /// Expired or removed from the exchange listing
+    Delisted,
+}
+
+impl InstrumentStatus {
+    /// Map the OKX `state` field
+    pub fn from_okx(state: &str) -> Option<Self> {
+        match state {
+            "live" => Some(Self::Live),
+            "suspend" => Some(Self::Suspended),
+            "preopen" => Some(Self::PreOpen),
+            "test" => Some(Self::Test),
+            "settlement" => Some(Self::Settlement),
+            "expired" | "delisted" => Some(Self::Delisted),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Live => "live",
+            Self::Suspended => "suspended",
+            Self::PreOpen => "preopen",
+            Self::Test => "test",
+            Self::Settlement => "settlement",
+            Self::Delisted => "delisted",
+        }
+    }
+
+    /// Whether orders can be placed
+    pub fn is_tradable(&self) -> bool {
+        *self == Self::Live
+    }
+}
+
+/// A symbol's status changed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstrumentStatusChange {
+    pub symbol: Symbol,
+    /// Status before the change; `None` the first time the symbol is seen
+    pub previous: Option<InstrumentStatus>,
+    pub current: InstrumentStatus,
+    pub changed_at: DateTime<Utc>,
+}
+
+impl InstrumentStatusChange {
+    /// Trading on the symbol just stopped
+    pub fn halts_trading(&self) -> bool {
+        !self.current.is_tradable() && self.previous.is_none_or(|p| p.is_tradable())
+    }
+
+    /// Trading on the symbol just resumed
+    pub fn resumes_trading(&self) -> bool {
+        self.current.is_tradable() && self.previous.is_some_and(|p| !p.is_tradable())
+    }
+}
+
+/// Instrument tracker events
+#[derive(Debug, Clone)]
+pub enum InstrumentEvent {
+    StatusChanged(InstrumentStatusChange),
+}
+
+/// Source of full instrument listings (the REST instruments endpoint)
+#[async_trait]
+pub trait InstrumentStatusSource: Send + Sync {
+    async fn fetch_instruments(&self, inst_type: &str) -> Result<Vec<InstrumentData>>;
+}
+
+#[async_trait]
+impl InstrumentStatusSource for OkxRestClient {
+    async fn fetch_instruments(&self, inst_type: &str) -> Result<Vec<InstrumentData>> {
+        let specs = self.instruments(inst_type).await?;
+        Ok(specs
+            .into_iter()
+            .map(|spec| InstrumentData {
+                inst_type: spec.inst_type,
+                inst_id: spec.inst_id,
+                state: spec.state,
+                list_time: None,
+                exp_time: None,
+            })
+            .collect())
+    }
+}
+
+#[derive(Debug, Clone)]
+struct TrackedInstrument {
+    inst_type: String,
+    status: InstrumentStatus,
+}
+
+/// Tracks instrument status and propagates changes
+pub struct InstrumentStatusTracker {
+    inst_types: Vec<String>,
+    instruments: RwLock<HashMap<Symbol, TrackedInstrument>>,
+    gate: Option<Arc<ExecutionGate>>,
+
+    /// Event channel
+    event_tx: mpsc::UnboundedSender<InstrumentEvent>,
+    event_rx: RwLock<Option<mpsc::UnboundedReceiver<InstrumentEvent>>>,
+}
+
+impl InstrumentStatusTracker {
+    /// Track instruments of the given types (e.g., "SPOT", "SWAP")
+    pub fn new(inst_types: Vec<String>) -> Self {
+        let (event_tx, event_rx) = mpsc::unbounded_channel();
+
+        Self {
+            inst_types,
+            instruments: RwLock::new(HashMap::new()),
+            gate: None,
+            event_tx,
+            event_rx: RwLock::new(Some(event_rx)),
+        }
+    }
+
+    /// Halt and resume symbols in `gate` as their status changes
+    pub fn with_gate(mut self, gate: Arc<ExecutionGate>) -> Self {
+        self.gate = Some(gate);
+        self
+    }
+
+    /// Public channels the tracker consumes
+    pub fn subscriptions(&self) -> Vec<SubscriptionRequest> {
+        self.inst_types
+            .iter()
+            .map(|t| SubscriptionRequest::new_instrument_type(Channel::Instruments, t.as_str()))
+            .collect()
+    }
+
+    /// Last known status of `symbol`
+    pub fn status(&self, symbol: &Symbol) -> Option<InstrumentStatus> {
+        self.instruments.read().get(symbol).map(|i| i.status)
+    }
+
+    /// Known symbols that cannot currently be traded
+    pub fn untradable(&self) -> Vec<(Symbol, InstrumentStatus)> {
+        self.instruments
+            .read()
+            .iter()
+            .filter(|(_, i)| !i.status.is_tradable())
+            .map(|(symbol, i)| (symbol.clone(), i.status))
+            .collect()
+    }
+
+    /// Record `status` for `symbol`, emitting an event if it changed
+    ///
+    /// A symbol seen for the first time only produces an event when it is
+    /// not tradable, so the initial listing does not flood subscribers.
+    pub fn apply(
+        &self,
+        symbol: Symbol,
+        inst_type: &str,
+        status: InstrumentStatus,
+    ) -> Option<InstrumentStatusChange> {
+        let previous = {
+            let mut instruments = self.instruments.write();
+            let previous = instruments.get(&symbol).map(|i| i.status);
+            if previous == Some(status) {
+                return None;
+            }
+            instruments.insert(
+                symbol.clone(),
+                TrackedInstrument {
+                    inst_type: inst_type.to_string(),
+                    status,
+                },
+            );
+            previous
+        };
+
+        if previous.is_none() && status.is_tradable() {
+            return None;
+        }
+
+        let change = InstrumentStatusChange {
+            symbol,
+            previous,
+            current: status,
+            changed_at: Utc::now(),
+        };
+
+        if let Some(gate) = &self.gate {
+            if change.current.is_tradable() {
+                gate.resume_symbol(&change.symbol);
+            } else {
+                gate.halt_symbol(
+                    &change.symbol,
+                    format!("instrument {}", change.current.as_str()),
+                );
+            }
+        }
+
+        info!(
+            "Instrument {} status: {} -> {}",
+            change.symbol.as_str(),
+            change.previous.map_or("unknown", |p| p.as_str()),
+            change.current.as_str()
+        );
+        let _ = self
+            .event_tx
+            .send(InstrumentEvent::StatusChanged(change.clone()));
+        Some(change)
+    }
+
+    /// Apply a WebSocket event; returns the status changes it caused
+    pub fn handle_event(&self, event: &WebSocketEvent) -> Result<Vec<InstrumentStatusChange>> {
+        match event {
+            WebSocketEvent::Instruments(instruments) => Ok(instruments
+                .iter()
+                .filter_map(|data| self.apply_data(data))
+                .collect()),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// Apply a full listing of `inst_type`; tracked symbols missing from it
+    /// are marked delisted
+    pub fn apply_snapshot(
+        &self,
+        inst_type: &str,
+        instruments: &[InstrumentData],
+    ) -> Vec<InstrumentStatusChange> {
+        let mut changes: Vec<_> = instruments
+            .iter()
+            .filter_map(|data| self.apply_data(data))
+            .collect();
+
+        let listed: Vec<&str> = instruments.iter().map(|i| i.inst_id.as_str()).collect();
+        let missing: Vec<Symbol> = self
+            .instruments
+            .read()
+            .iter()
+            .filter(|(symbol, i)| {
+                i.inst_type == inst_type
+                    && i.status != InstrumentStatus::Delisted
+                    && !listed.contains(&symbol.as_str())
+            })
+            .map(|(symbol, _)| symbol.clone())
+            .collect();
+        for symbol in missing {
+            changes.extend(self.apply(symbol, inst_type, InstrumentStatus::Delisted));
+        }
+
+        changes
+    }
+
+    fn apply_data(&self, data: &InstrumentData) -> Option<InstrumentStatusChange> {
+        let Some(status) = InstrumentStatus::from_okx(&data.state) else {
+            warn!("Unknown state '{}' for {}", data.state, data.inst_id);
+            return None;
+        };
+        let symbol = match Symbol::new(&data.inst_id) {
+            Ok(symbol) => symbol,
+            Err(e) => {
+                warn!("Skipping instrument {}: {}", data.inst_id, e);
+                return None;
+            }
+        };
+        self.apply(symbol, &data.inst_type, status)
+    }
+
+    /// Subscribe to the instruments channel and apply events until the stream ends
+    pub async fn run(&self, client: &OkxWebSocketClient) -> Result<()> {
+        client.subscribe(self.subscriptions()).await?;
+        info!("Instrument tracker subscribed to {:?}", self.inst_types);
+
+        while let Some(event) = client.next_message().await? {
+            self.handle_event(&event)?;
+        }
+
+        Ok(())
+    }
+
+    /// Fetch and apply a full listing of every tracked instrument type
+    pub async fn poll(
+        &self,
+        source: &dyn InstrumentStatusSource,
+    ) -> Result<Vec<InstrumentStatusChange>> {
+        let mut changes = Vec::new();
+        for inst_type in &self.inst_types {
+            let instruments = source.fetch_instruments(inst_type).await?;
+            changes.extend(self.apply_snapshot(inst_type, &instruments));
+        }
+        Ok(changes)
+    }
+
+    /// Poll every `interval` until the task is aborted
+    pub fn start_polling(
+        self: Arc<Self>,
+        source: Arc<dyn InstrumentStatusSource>,
+        interval: Duration,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.poll(source.as_ref()).await {
+                    warn!("Instrument status poll failed: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Get event receiver (can only be called once)
+    pub fn subscribe_events(&self) -> Option<mpsc::UnboundedReceiver<InstrumentEvent>> {
+        self.event_rx.write().take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instrument(inst_id: &str, state: &str) -> InstrumentData {
+        serde_json::from_value(serde_json::json!({
+            "instType": "SPOT",
+            "instId": inst_id,
+            "state": state
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_suspension_halts_and_relisting_resumes() {
+        let gate = Arc::new(ExecutionGate::new());
+        let tracker =
+            InstrumentStatusTracker::new(vec!["SPOT".to_string()]).with_gate(gate.clone());
+        let mut events = tracker.subscribe_events().unwrap();
+        let symbol = Symbol::new("XYZ-USDT").unwrap();
+
+        // Initial listing of a live instrument is silent
+        let listing = WebSocketEvent::Instruments(vec![instrument("XYZ-USDT", "live")]);
+        assert!(tracker.handle_event(&listing).unwrap().is_empty());
+
+        let suspend = WebSocketEvent::Instruments(vec![instrument("XYZ-USDT", "suspend")]);
+        let changes = tracker.handle_event(&suspend).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert!(changes[0].halts_trading());
+        assert!(gate.halt_reason(&symbol).is_some());
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            InstrumentEvent::StatusChanged(c) if c.current == InstrumentStatus::Suspended
+        ));
+
+        // Repeated state is not a change
+        assert!(tracker.handle_event(&suspend).unwrap().is_empty());
+
+        let changes = tracker.handle_event(&listing).unwrap();
+        assert!(changes[0].resumes_trading());
+        assert!(gate.halt_reason(&symbol).is_none());
+    }
+
+    #[test]
+    fn test_snapshot_marks_missing_symbols_delisted() {
+        let tracker = InstrumentStatusTracker::new(vec!["SPOT".to_string()]);
+        tracker.apply_snapshot(
+            "SPOT",
+            &[
+                instrument("BTC-USDT", "live"),
+                instrument("XYZ-USDT", "live"),
+            ],
+        );
+
+        let changes = tracker.apply_snapshot("SPOT", &[instrument("BTC-USDT", "live")]);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].symbol.as_str(), "XYZ-USDT");
+        assert_eq!(changes[0].current, InstrumentStatus::Delisted);
+        assert_eq!(tracker.untradable().len(), 1);
+
+        // Already delisted: no repeat event
+        assert!(
+            tracker
+                .apply_snapshot("SPOT", &[instrument("BTC-USDT", "live")])
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_first_sighting_of_suspended_symbol_is_reported() {
+        let tracker = InstrumentStatusTracker::new(vec!["SPOT".to_string()]);
+        let changes = tracker.apply_snapshot("SPOT", &[instrument("XYZ-USDT", "suspend")]);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].previous, None);
+        assert!(changes[0].halts_trading());
+    }
+}
@@ -0,0 +1,585 @@
+//! Write-ahead log of order intents
+//!
+//! Between deciding to place an order and hearing back from the exchange, a
+//! crash leaves it unknown whether the order exists. Each intent is written
+//! durably to an [`IntentLog`] before the order is sent and resolved once the
+//! outcome is known. On restart, [`recover_intents`] asks the exchange about
+//! every intent still pending by its client order ID: orders that exist are
+//! recovered (or cancelled, if configured to), and intents the exchange never
+//! saw are closed as not placed.
+
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use ea_okx_client::OkxRestClient;
+use ea_okx_client::models::{CancelOrderRequest, OrderData};
+use ea_okx_core::Symbol;
+use ea_okx_core::models::Order;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Outcome of an order intent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntentStatus {
+    /// Written before sending; the outcome is not known yet
+    Pending,
+    /// Exchange accepted the order
+    Acknowledged,
+    /// Exchange refused the order
+    Rejected,
+    /// Order could not be sent
+    Failed,
+    /// Found missing on the exchange during recovery
+    NotPlaced,
+    /// Found open on the exchange during recovery and cancelled
+    Cancelled,
+}
+
+impl IntentStatus {
+    /// Whether the outcome is known
+    pub fn is_resolved(self) -> bool {
+        self != IntentStatus::Pending
+    }
+}
+
+/// An order about to be sent, keyed by its client order ID
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderIntent {
+    pub client_order_id: String,
+    pub order: Order,
+    pub status: IntentStatus,
+    pub exchange_id: Option<String>,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl OrderIntent {
+    pub fn new(order: &Order) -> Self {
+        let now = Utc::now();
+        Self {
+            client_order_id: order.client_order_id.clone(),
+            order: order.clone(),
+            status: IntentStatus::Pending,
+            exchange_id: None,
+            reason: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Record the outcome
+    pub fn resolve(
+        &mut self,
+        status: IntentStatus,
+        exchange_id: Option<String>,
+        reason: Option<String>,
+    ) {
+        self.status = status;
+        self.exchange_id = exchange_id.or(self.exchange_id.take());
+        self.reason = reason;
+        self.updated_at = Utc::now();
+    }
+}
+
+/// Durable storage for order intents
+pub trait IntentLog: Send + Sync {
+    /// Insert or update an intent; must be durable once this returns
+    fn record(&self, intent: &OrderIntent) -> Result<()>;
+
+    /// Load the intent for a client order ID
+    fn load(&self, client_order_id: &str) -> Result<Option<OrderIntent>>;
+
+    /// Load all known intents, oldest first
+    fn load_all(&self) -> Result<Vec<OrderIntent>>;
+
+    /// Remove an intent
+    fn remove(&self, client_order_id: &str) -> Result<()>;
+
+    /// Intents whose outcome is not known
+    fn unresolved(&self) -> Result<Vec<OrderIntent>> {
+        Ok(self
+            .load_all()?
+            .into_iter()
+            .filter(|i| !i.status.is_resolved())
+            .collect())
+    }
+
+    /// Record the outcome of a logged intent
+    fn resolve(
+        &self,
+        client_order_id: &str,
+        status: IntentStatus,
+        exchange_id: Option<String>,
+        reason: Option<String>,
+    ) -> Result<()> {
+        let mut intent = self.load(client_order_id)?.ok_or_else(|| {
+            Error::PersistenceError(format!("No intent logged for {}", client_order_id))
+        })?;
+        intent.resolve(status, exchange_id, reason);
+        self.record(&intent)
+    }
+
+    /// Remove resolved intents last updated before `before`, returning how
+    /// many were removed
+    fn prune(&self, before: DateTime<Utc>) -> Result<usize> {
+        let mut removed = 0;
+        for intent in self.load_all()? {
+            if intent.status.is_resolved() && intent.updated_at < before {
+                self.remove(&intent.client_order_id)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+/// In-memory log, mainly for tests and paper trading
+#[derive(Debug, Default)]
+pub struct InMemoryIntentLog {
+    intents: RwLock<HashMap<String, OrderIntent>>,
+}
+
+impl InMemoryIntentLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl IntentLog for InMemoryIntentLog {
+    fn record(&self, intent: &OrderIntent) -> Result<()> {
+        self.intents
+            .write()
+            .insert(intent.client_order_id.clone(), intent.clone());
+        Ok(())
+    }
+
+    fn load(&self, client_order_id: &str) -> Result<Option<OrderIntent>> {
+        Ok(self.intents.read().get(client_order_id).cloned())
+    }
+
+    fn load_all(&self) -> Result<Vec<OrderIntent>> {
+        let mut intents: Vec<OrderIntent> = self.intents.read().values().cloned().collect();
+        intents.sort_by_key(|i| i.created_at);
+        Ok(intents)
+    }
+
+    fn remove(&self, client_order_id: &str) -> Result<()> {
+        self.intents.write().remove(client_order_id);
+        Ok(())
+    }
+}
+
+/// File-backed log keeping one JSON document per intent
+///
+/// Each record is written to a temp file, synced to disk and renamed into
+/// place, so after a crash an intent is either fully logged or not at all.
+#[derive(Debug)]
+pub struct FileIntentLog {
+    dir: PathBuf,
+    lock: RwLock<()>,
+}
+
+impl FileIntentLog {
+    /// Creates the log, creating the directory if needed
+    pub fn new(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).map_err(|e| {
+            Error::PersistenceError(format!("Failed to create {}: {}", dir.display(), e))
+        })?;
+        Ok(Self {
+            dir,
+            lock: RwLock::new(()),
+        })
+    }
+
+    fn path_for(&self, client_order_id: &str) -> Result<PathBuf> {
+        // OKX client order IDs are alphanumeric; anything else could escape the directory
+        if client_order_id.is_empty() || !client_order_id.chars().all(|c| c.is_ascii_alphanumeric())
+        {
+            return Err(Error::PersistenceError(format!(
+                "Invalid client order ID '{}'",
+                client_order_id
+            )));
+        }
+        Ok(self.dir.join(format!("{}.json", client_order_id)))
+    }
+
+    fn write_durably(&self, path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+        let tmp = path.with_extension("json.tmp");
+        let mut file = File::create(&tmp)?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+        fs::rename(&tmp, path)?;
+        // Persist the rename itself; directories cannot be opened for this on Windows
+        #[cfg(unix)]
+        File::open(&self.dir)?.sync_all()?;
+        Ok(())
+    }
+}
+
+impl IntentLog for FileIntentLog {
+    fn record(&self, intent: &OrderIntent) -> Result<()> {
+        let _guard = self.lock.write();
+        let path = self.path_for(&intent.client_order_id)?;
+        let json = serde_json::to_vec_pretty(intent)?;
+        self.write_durably(&path, &json).map_err(|e| {
+            Error::PersistenceError(format!("Failed to write {}: {}", path.display(), e))
+        })
+    }
+
+    fn load(&self, client_order_id: &str) -> Result<Option<OrderIntent>> {
+        let _guard = self.lock.read();
+        let path = self.path_for(client_order_id)?;
+        if !path.exists() {
+            return Ok(None);
+        }
+        let bytes = fs::read(&path).map_err(|e| {
+            Error::PersistenceError(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+        Ok(Some(serde_json::from_slice(&bytes)?))
+    }
+
+    fn load_all(&self) -> Result<Vec<OrderIntent>> {
+        let _guard = self.lock.read();
+        let entries = fs::read_dir(&self.dir).map_err(|e| {
+            Error::PersistenceError(format!("Failed to list {}: {}", self.dir.display(), e))
+        })?;
+
+        let mut intents = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            match fs::read(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|b| serde_json::from_slice(&b).map_err(|e| e.to_string()))
+            {
+                Ok(intent) => intents.push(intent),
+                Err(e) => warn!("Skipping unreadable intent {}: {}", path.display(), e),
+            }
+        }
+
+        intents.sort_by_key(|i: &OrderIntent| i.created_at);
+        Ok(intents)
+    }
+
+    fn remove(&self, client_order_id: &str) -> Result<()> {
+        let _guard = self.lock.write();
+        let path = self.path_for(client_order_id)?;
+        match fs::remove_file(&path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(Error::PersistenceError(format!(
+                "Failed to remove {}: {}",
+                path.display(),
+                e
+            ))),
+        }
+    }
+}
+
+/// Exchange queried for the orders behind unresolved intents
+#[async_trait]
+pub trait IntentVenue: Send + Sync {
+    /// Order placed with `client_order_id`, if the exchange has one
+    async fn find_order(&self, symbol: &Symbol, client_order_id: &str)
+    -> Result<Option<OrderData>>;
+
+    /// Cancel the order placed with `client_order_id`
+    async fn cancel_order(&self, symbol: &Symbol, client_order_id: &str) -> Result<()>;
+}
+
+/// OKX orders looked up and cancelled by `clOrdId`
+pub struct OkxIntentVenue {
+    client: Arc<OkxRestClient>,
+}
+
+impl OkxIntentVenue {
+    pub fn new(client: Arc<OkxRestClient>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl IntentVenue for OkxIntentVenue {
+    async fn find_order(
+        &self,
+        symbol: &Symbol,
+        client_order_id: &str,
+    ) -> Result<Option<OrderData>> {
+        Ok(self
+            .client
+            .order_by_client_id(symbol.as_str(), client_order_id)
+            .await?)
+    }
+
+    async fn cancel_order(&self, symbol: &Symbol, client_order_id: &str) -> Result<()> {
+        let request = CancelOrderRequest {
+            inst_id: symbol.as_str().to_string(),
+            ord_id: None,
+            cl_ord_id: Some(client_order_id.to_string()),
+        };
+        self.client.cancel_order(&request).await?;
+        Ok(())
+    }
+}
+
+/// Startup recovery policy for unresolved intents
+#[derive(Debug, Clone, Default)]
+pub struct IntentRecoveryPolicy {
+    /// Cancel orders found still open instead of recovering them
+    pub cancel_open: bool,
+}
+
+/// Outcome of intent recovery
+#[derive(Debug, Default, Clone)]
+pub struct IntentRecoveryReport {
+    /// Exchange orders behind recovered intents, to be tracked again
+    pub recovered: Vec<OrderData>,
+
+    /// Intents whose open orders were cancelled
+    pub cancelled: Vec<OrderIntent>,
+
+    /// Intents whose orders never reached the exchange
+    pub not_placed: Vec<OrderIntent>,
+
+    /// Intents left pending because the exchange could not settle them;
+    /// retried on the next recovery
+    pub unresolved: Vec<OrderIntent>,
+}
+
+/// Settle every intent left pending by the previous run against `venue`
+pub async fn recover_intents(
+    log: &dyn IntentLog,
+    venue: &dyn IntentVenue,
+    policy: &IntentRecoveryPolicy,
+) -> Result<IntentRecoveryReport> {
+    let mut report = IntentRecoveryReport::default();
+
+    for mut intent in log.unresolved()? {
+        let symbol = intent.order.symbol.clone();
+        let found = match venue.find_order(&symbol, &intent.client_order_id).await {
+            Ok(found) => found,
+            Err(e) => {
+                warn!(
+                    "Could not look up order intent {}: {}",
+                    intent.client_order_id, e
+                );
+                report.unresolved.push(intent);
+                continue;
+            }
+        };
+
+        let Some(order) = found else {
+            info!(
+                "Order intent {} never reached the exchange",
+                intent.client_order_id
+            );
+            intent.resolve(
+                IntentStatus::NotPlaced,
+                None,
+                Some("Not found on the exchange after restart".to_string()),
+            );
+            log.record(&intent)?;
+            report.not_placed.push(intent);
+            continue;
+        };
+
+        let open = matches!(order.state.as_str(), "live" | "partially_filled");
+        if open && policy.cancel_open {
+            if let Err(e) = venue.cancel_order(&symbol, &intent.client_order_id).await {
+                warn!(
+                    "Could not cancel order {} of intent {}: {}",
+                    order.ord_id, intent.client_order_id, e
+                );
+                report.unresolved.push(intent);
+                continue;
+            }
+            warn!(
+                "Cancelled order {} left open by intent {}",
+                order.ord_id, intent.client_order_id
+            );
+            intent.resolve(
+                IntentStatus::Cancelled,
+                Some(order.ord_id.clone()),
+                Some("Cancelled on restart".to_string()),
+            );
+            log.record(&intent)?;
+            report.cancelled.push(intent);
+            continue;
+        }
+
+        info!(
+            "Recovered order {} ({}) of intent {}",
+            order.ord_id, order.state, intent.client_order_id
+        );
+        intent.resolve(IntentStatus::Acknowledged, Some(order.ord_id.clone()), None);
+        log.record(&intent)?;
+        report.recovered.push(order);
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use ea_okx_core::Quantity;
+    use ea_okx_core::models::{OrderSide, OrderType};
+    use rust_decimal_macros::dec;
+    use uuid::Uuid;
+
+    fn order() -> Order {
+        Order::new(
+            Uuid::new_v4(),
+            Symbol::new("BTC-USDT").unwrap(),
+            OrderSide::Buy,
+            OrderType::Market,
+            Quantity::new(dec!(0.1)).unwrap(),
+            None,
+        )
+    }
+
+    fn exchange_order(intent: &OrderIntent, state: &str) -> OrderData {
+        serde_json::from_value(serde_json::json!({
+            "instType": "SPOT",
+            "instId": "BTC-USDT",
+            "ordId": format!("ex{}", intent.client_order_id),
+            "clOrdId": intent.client_order_id,
+            "px": "",
+            "sz": "0.1",
+            "ordType": "market",
+            "side": "buy",
+            "tdMode": "cash",
+            "fillPx": "",
+            "fillSz": "0",
+            "accFillSz": "0",
+            "avgPx": "",
+            "state": state,
+            "uTime": "0",
+            "cTime": "0"
+        }))
+        .unwrap()
+    }
+
+    /// Exchange knowing some orders and failing lookups of others
+    #[derive(Default)]
+    struct MockVenue {
+        orders: HashMap<String, OrderData>,
+        unreachable: Vec<String>,
+        cancelled: RwLock<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl IntentVenue for MockVenue {
+        async fn find_order(
+            &self,
+            _symbol: &Symbol,
+            client_order_id: &str,
+        ) -> Result<Option<OrderData>> {
+            if self.unreachable.iter().any(|id| id == client_order_id) {
+                return Err(Error::TimeoutError("exchange unreachable".to_string()));
+            }
+            Ok(self.orders.get(client_order_id).cloned())
+        }
+
+        async fn cancel_order(&self, _symbol: &Symbol, client_order_id: &str) -> Result<()> {
+            self.cancelled.write().push(client_order_id.to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_file_log_roundtrip_and_prune() {
+        let dir = std::env::temp_dir().join(format!("intent-log-{}", Uuid::new_v4()));
+        let log = FileIntentLog::new(&dir).unwrap();
+        let pending = OrderIntent::new(&order());
+        let acked = OrderIntent::new(&order());
+
+        log.record(&pending).unwrap();
+        log.record(&acked).unwrap();
+        log.resolve(
+            &acked.client_order_id,
+            IntentStatus::Acknowledged,
+            Some("123".to_string()),
+            None,
+        )
+        .unwrap();
+
+        let unresolved = log.unresolved().unwrap();
+        assert_eq!(unresolved.len(), 1);
+        assert_eq!(unresolved[0].client_order_id, pending.client_order_id);
+        let loaded = log.load(&acked.client_order_id).unwrap().unwrap();
+        assert_eq!(loaded.exchange_id.as_deref(), Some("123"));
+
+        // Pending intents are never pruned, however old
+        assert_eq!(log.prune(Utc::now() + Duration::days(1)).unwrap(), 1);
+        assert_eq!(log.load_all().unwrap().len(), 1);
+        assert!(log.load("../escape").is_err());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_recovery_settles_pending_intents() {
+        let log = InMemoryIntentLog::new();
+        let [filled, open, missing, unreachable] =
+            std::array::from_fn(|_| OrderIntent::new(&order()));
+        for intent in [&filled, &open, &missing, &unreachable] {
+            log.record(intent).unwrap();
+        }
+
+        let venue = MockVenue {
+            orders: HashMap::from([
+                (
+                    filled.client_order_id.clone(),
+                    exchange_order(&filled, "filled"),
+                ),
+                (open.client_order_id.clone(), exchange_order(&open, "live")),
+            ]),
+            unreachable: vec![unreachable.client_order_id.clone()],
+            ..Default::default()
+        };
+
+        let report = recover_intents(&log, &venue, &IntentRecoveryPolicy::default())
+            .await
+            .unwrap();
+        assert_eq!(report.recovered.len(), 2);
+        assert_eq!(report.not_placed.len(), 1);
+        assert_eq!(
+            report.not_placed[0].client_order_id,
+            missing.client_order_id
+        );
+        assert_eq!(report.unresolved.len(), 1);
+        assert_eq!(
+            log.load(&filled.client_order_id).unwrap().unwrap().status,
+            IntentStatus::Acknowledged
+        );
+        assert_eq!(log.unresolved().unwrap().len(), 1);
+
+        // With cancellation on, an open order is cancelled rather than recovered
+        let mut reopened = open.clone();
+        reopened.resolve(IntentStatus::Pending, None, None);
+        log.record(&reopened).unwrap();
+        let report = recover_intents(&log, &venue, &IntentRecoveryPolicy { cancel_open: true })
+            .await
+            .unwrap();
+        assert!(report.recovered.is_empty());
+        assert_eq!(report.cancelled.len(), 1);
+        assert_eq!(*venue.cancelled.read(), vec![open.client_order_id.clone()]);
+        assert_eq!(
+            log.load(&open.client_order_id).unwrap().unwrap().status,
+            IntentStatus::Cancelled
+        );
+    }
+}
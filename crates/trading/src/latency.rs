@@ -0,0 +1,584 @@
+//! Order latency budget tracking
+//!
+//! Each live order carries an [`OrderTimeline`] of marks from the moment its
+//! signal was received to its first fill. Consecutive marks bound a stage,
+//! and every stage is attributed to a [`LatencySource`]: the system's own
+//! code, the network, or the exchange. Completed timelines are logged as
+//! structured events, published for metrics and kept for a while so
+//! [`LatencyTracker::breakdown`] can show where time goes over a range and
+//! which stages are over their [`LatencyBudget`].
+
+use chrono::{DateTime, Duration, Utc};
+use ea_okx_core::Symbol;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+/// Completed timelines kept for breakdowns
+const MAX_RECORDS: usize = 10_000;
+
+/// Received signals not yet turned into orders are forgotten after this long
+const PENDING_SIGNAL_TTL_SECS: i64 = 3600;
+
+/// Point on an order's way to the exchange
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LatencyMark {
+    SignalReceived,
+    /// Sizing, fat-finger, gate and other pre-trade checks passed
+    RiskChecked,
+    /// Request body built
+    Serialized,
+    /// Request handed to the HTTP client
+    Sent,
+    /// Exchange accepted the order
+    Acknowledged,
+    FirstFill,
+}
+
+/// Marks in the order an order passes them
+const PIPELINE: [LatencyMark; 6] = [
+    LatencyMark::SignalReceived,
+    LatencyMark::RiskChecked,
+    LatencyMark::Serialized,
+    LatencyMark::Sent,
+    LatencyMark::Acknowledged,
+    LatencyMark::FirstFill,
+];
+
+/// Who a stage's time is spent by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LatencySource {
+    Local,
+    Network,
+    Exchange,
+}
+
+impl LatencyMark {
+    /// Stage ending at this mark, `None` for the first mark
+    pub fn stage(&self) -> Option<&'static str> {
+        match self {
+            LatencyMark::SignalReceived => None,
+            LatencyMark::RiskChecked => Some("risk_check"),
+            LatencyMark::Serialized => Some("serialization"),
+            LatencyMark::Sent => Some("http_send"),
+            LatencyMark::Acknowledged => Some("exchange_ack"),
+            LatencyMark::FirstFill => Some("first_fill"),
+        }
+    }
+
+    /// Who the stage ending at this mark is attributed to
+    ///
+    /// The exchange acknowledgement includes the network round trip, which
+    /// cannot be told apart from OKX's processing time client-side.
+    pub fn source(&self) -> LatencySource {
+        match self {
+            LatencyMark::SignalReceived | LatencyMark::RiskChecked | LatencyMark::Serialized => {
+                LatencySource::Local
+            }
+            LatencyMark::Sent => LatencySource::Network,
+            LatencyMark::Acknowledged | LatencyMark::FirstFill => LatencySource::Exchange,
+        }
+    }
+}
+
+/// Marks collected for one order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderTimeline {
+    pub order_id: Uuid,
+    pub strategy_id: Uuid,
+    pub symbol: Symbol,
+    pub marks: Vec<(LatencyMark, DateTime<Utc>)>,
+}
+
+impl OrderTimeline {
+    /// Timeline for an order whose signal was received at `received_at`
+    pub fn new(
+        order_id: Uuid,
+        strategy_id: Uuid,
+        symbol: Symbol,
+        received_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            order_id,
+            strategy_id,
+            symbol,
+            marks: vec![(LatencyMark::SignalReceived, received_at)],
+        }
+    }
+
+    pub fn mark(&mut self, mark: LatencyMark) {
+        self.mark_at(mark, Utc::now());
+    }
+
+    pub fn mark_at(&mut self, mark: LatencyMark, at: DateTime<Utc>) {
+        self.marks.push((mark, at));
+    }
+
+    pub fn marked(&self, mark: LatencyMark) -> Option<DateTime<Utc>> {
+        self.marks
+            .iter()
+            .find(|(m, _)| *m == mark)
+            .map(|(_, at)| *at)
+    }
+}
+
+/// Time spent in one stage of one order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StageTiming {
+    pub stage: String,
+    pub source: LatencySource,
+    pub ms: f64,
+}
+
+/// Stage timings of one completed order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderLatency {
+    pub order_id: Uuid,
+    pub strategy_id: Uuid,
+    pub symbol: Symbol,
+    pub started_at: DateTime<Utc>,
+    pub stages: Vec<StageTiming>,
+
+    /// Signal received to exchange acknowledgement, if acknowledged
+    pub time_to_ack_ms: Option<f64>,
+}
+
+impl OrderLatency {
+    /// Stage timings between consecutive marks, in mark order
+    pub fn from_timeline(timeline: &OrderTimeline) -> Self {
+        let mut marks = timeline.marks.clone();
+        marks.sort_by_key(|(mark, _)| *mark);
+
+        let stages = marks
+            .windows(2)
+            .filter_map(|pair| {
+                let (_, from) = pair[0];
+                let (mark, to) = pair[1];
+                Some(StageTiming {
+                    stage: mark.stage()?.to_string(),
+                    source: mark.source(),
+                    ms: elapsed_ms(from, to),
+                })
+            })
+            .collect();
+        let started_at = marks.first().map_or_else(Utc::now, |(_, at)| *at);
+        let time_to_ack_ms = timeline
+            .marked(LatencyMark::Acknowledged)
+            .map(|acked| elapsed_ms(started_at, acked));
+
+        Self {
+            order_id: timeline.order_id,
+            strategy_id: timeline.strategy_id,
+            symbol: timeline.symbol.clone(),
+            started_at,
+            stages,
+            time_to_ack_ms,
+        }
+    }
+}
+
+fn elapsed_ms(from: DateTime<Utc>, to: DateTime<Utc>) -> f64 {
+    (to - from).num_microseconds().unwrap_or(i64::MAX) as f64 / 1000.0
+}
+
+/// Milliseconds each stage should take at most
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LatencyBudget {
+    pub risk_check_ms: f64,
+    pub serialization_ms: f64,
+    pub http_send_ms: f64,
+    pub exchange_ack_ms: f64,
+
+    /// Signal received to exchange acknowledgement
+    pub time_to_ack_ms: f64,
+}
+
+impl Default for LatencyBudget {
+    fn default() -> Self {
+        Self {
+            risk_check_ms: 5.0,
+            serialization_ms: 1.0,
+            http_send_ms: 20.0,
+            exchange_ack_ms: 150.0,
+            time_to_ack_ms: 200.0,
+        }
+    }
+}
+
+impl LatencyBudget {
+    /// Budget for a stage; first fills wait on the market and have none
+    pub fn for_stage(&self, stage: &str) -> Option<f64> {
+        match stage {
+            "risk_check" => Some(self.risk_check_ms),
+            "serialization" => Some(self.serialization_ms),
+            "http_send" => Some(self.http_send_ms),
+            "exchange_ack" => Some(self.exchange_ack_ms),
+            "time_to_ack" => Some(self.time_to_ack_ms),
+            _ => None,
+        }
+    }
+}
+
+/// Distribution of one stage over a range
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StageStats {
+    pub stage: String,
+
+    /// `None` for the end-to-end figure spanning several sources
+    pub source: Option<LatencySource>,
+    pub count: usize,
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+    pub budget_ms: Option<f64>,
+    pub over_budget: usize,
+}
+
+impl StageStats {
+    fn from_samples(
+        stage: &str,
+        source: Option<LatencySource>,
+        mut samples: Vec<f64>,
+        budget_ms: Option<f64>,
+    ) -> Self {
+        samples.sort_by(|a, b| a.total_cmp(b));
+        let percentile = |p: f64| {
+            let rank = (samples.len() as f64 * p).ceil() as usize;
+            samples[rank.clamp(1, samples.len()) - 1]
+        };
+
+        Self {
+            stage: stage.to_string(),
+            source,
+            count: samples.len(),
+            mean_ms: samples.iter().sum::<f64>() / samples.len() as f64,
+            p50_ms: percentile(0.50),
+            p95_ms: percentile(0.95),
+            p99_ms: percentile(0.99),
+            max_ms: samples[samples.len() - 1],
+            budget_ms,
+            over_budget: budget_ms.map_or(0, |budget| {
+                samples.iter().filter(|ms| **ms > budget).count()
+            }),
+        }
+    }
+}
+
+/// Time spent by one source, summed over every order in a range
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceShare {
+    pub source: LatencySource,
+    pub total_ms: f64,
+
+    /// Share of the time to acknowledgement, in percent
+    pub share_pct: f64,
+}
+
+/// Where order latency went over a time range
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LatencyBreakdown {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub orders: usize,
+
+    /// Per stage, in pipeline order
+    pub stages: Vec<StageStats>,
+    pub time_to_ack: Option<StageStats>,
+
+    /// Local, network and exchange time up to acknowledgement
+    pub sources: Vec<SourceShare>,
+}
+
+/// Collects order timelines and summarizes them against the budget
+#[derive(Debug)]
+pub struct LatencyTracker {
+    budget: RwLock<LatencyBudget>,
+    pending_signals: Mutex<HashMap<Uuid, DateTime<Utc>>>,
+    records: RwLock<VecDeque<OrderLatency>>,
+    event_tx: mpsc::UnboundedSender<OrderLatency>,
+    event_rx: Mutex<Option<mpsc::UnboundedReceiver<OrderLatency>>>,
+}
+
+impl Default for LatencyTracker {
+    fn default() -> Self {
+        Self::new(LatencyBudget::default())
+    }
+}
+
+impl LatencyTracker {
+    pub fn new(budget: LatencyBudget) -> Self {
+        let (event_tx, event_rx) = mpsc::unbounded_channel();
+        Self {
+            budget: RwLock::new(budget),
+            pending_signals: Mutex::new(HashMap::new()),
+            records: RwLock::new(VecDeque::new()),
+            event_tx,
+            event_rx: Mutex::new(Some(event_rx)),
+        }
+    }
+
+    pub fn budget(&self) -> LatencyBudget {
+        self.budget.read().clone()
+    }
+
+    pub fn set_budget(&self, budget: LatencyBudget) {
+        *self.budget.write() = budget;
+    }
+
+    /// Take the stream of completed order latencies (once)
+    pub fn subscribe_events(&self) -> Option<mpsc::UnboundedReceiver<OrderLatency>> {
+        self.event_rx.lock().take()
+    }
+
+    /// Note when a signal arrived, for the order it later turns into
+    pub fn signal_received(&self, signal_id: Uuid) {
+        let now = Utc::now();
+        let mut pending = self.pending_signals.lock();
+        pending.retain(|_, at| now - *at < Duration::seconds(PENDING_SIGNAL_TTL_SECS));
+        pending.insert(signal_id, now);
+    }
+
+    /// When a signal arrived, forgetting it
+    pub fn take_signal_received(&self, signal_id: Uuid) -> Option<DateTime<Utc>> {
+        self.pending_signals.lock().remove(&signal_id)
+    }
+
+    /// Record a finished timeline, logging its stages and flagging those
+    /// over budget
+    pub fn record(&self, timeline: &OrderTimeline) -> OrderLatency {
+        let latency = OrderLatency::from_timeline(timeline);
+        let budget = self.budget();
+
+        for stage in &latency.stages {
+            debug!(
+                order_id = %latency.order_id,
+                stage = %stage.stage,
+                source = ?stage.source,
+                elapsed_ms = stage.ms,
+                "Order stage"
+            );
+            if let Some(limit) = budget.for_stage(&stage.stage)
+                && stage.ms > limit
+            {
+                warn!(
+                    "Order {} {} took {:.1}ms, over its {:.1}ms budget",
+                    latency.order_id, stage.stage, stage.ms, limit
+                );
+            }
+        }
+
+        let mut records = self.records.write();
+        records.push_back(latency.clone());
+        while records.len() > MAX_RECORDS {
+            records.pop_front();
+        }
+        drop(records);
+
+        let _ = self.event_tx.send(latency.clone());
+        latency
+    }
+
+    /// Orders started within `[start, end)`
+    pub fn records(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<OrderLatency> {
+        self.records
+            .read()
+            .iter()
+            .filter(|r| r.started_at >= start && r.started_at < end)
+            .cloned()
+            .collect()
+    }
+
+    /// Per-stage distributions of orders started within `[start, end)`
+    pub fn breakdown(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> LatencyBreakdown {
+        let records = self.records(start, end);
+        let budget = self.budget();
+
+        let mut by_stage: Vec<(String, LatencySource, Vec<f64>)> = Vec::new();
+        let mut by_source: Vec<(LatencySource, f64)> = Vec::new();
+        for record in &records {
+            for stage in &record.stages {
+                match by_stage
+                    .iter_mut()
+                    .find(|(name, _, _)| *name == stage.stage)
+                {
+                    Some((_, _, samples)) => samples.push(stage.ms),
+                    None => by_stage.push((stage.stage.clone(), stage.source, vec![stage.ms])),
+                }
+                if stage.stage == "first_fill" || record.time_to_ack_ms.is_none() {
+                    continue;
+                }
+                match by_source
+                    .iter_mut()
+                    .find(|(source, _)| *source == stage.source)
+                {
+                    Some((_, total)) => *total += stage.ms,
+                    None => by_source.push((stage.source, stage.ms)),
+                }
+            }
+        }
+        by_stage.sort_by_key(|(name, _, _)| {
+            PIPELINE
+                .iter()
+                .position(|mark| mark.stage() == Some(name.as_str()))
+        });
+
+        let stages = by_stage
+            .into_iter()
+            .map(|(name, source, samples)| {
+                let budget_ms = budget.for_stage(&name);
+                StageStats::from_samples(&name, Some(source), samples, budget_ms)
+            })
+            .collect();
+        let acks: Vec<f64> = records.iter().filter_map(|r| r.time_to_ack_ms).collect();
+        let time_to_ack = (!acks.is_empty()).then(|| {
+            StageStats::from_samples("time_to_ack", None, acks, Some(budget.time_to_ack_ms))
+        });
+        let total: f64 = by_source.iter().map(|(_, ms)| ms).sum();
+        let sources = by_source
+            .into_iter()
+            .map(|(source, total_ms)| SourceShare {
+                source,
+                total_ms,
+                share_pct: if total > 0.0 {
+                    total_ms / total * 100.0
+                } else {
+                    0.0
+                },
+            })
+            .collect();
+
+        LatencyBreakdown {
+            start,
+            end,
+            orders: records.len(),
+            stages,
+            time_to_ack,
+            sources,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timeline(start: DateTime<Utc>, offsets_ms: &[(LatencyMark, i64)]) -> OrderTimeline {
+        let mut timeline = OrderTimeline::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Symbol::new("BTC-USDT").unwrap(),
+            start,
+        );
+        for (mark, ms) in offsets_ms {
+            timeline.mark_at(*mark, start + Duration::milliseconds(*ms));
+        }
+        timeline
+    }
+
+    #[test]
+    fn test_stages_between_consecutive_marks() {
+        let start = Utc::now();
+        // Serialization skipped: the send stage runs from the risk check
+        let latency = OrderLatency::from_timeline(&timeline(
+            start,
+            &[
+                (LatencyMark::RiskChecked, 2),
+                (LatencyMark::Sent, 5),
+                (LatencyMark::Acknowledged, 85),
+                (LatencyMark::FirstFill, 300),
+            ],
+        ));
+
+        let stages: Vec<(&str, f64)> = latency
+            .stages
+            .iter()
+            .map(|s| (s.stage.as_str(), s.ms))
+            .collect();
+        assert_eq!(
+            stages,
+            vec![
+                ("risk_check", 2.0),
+                ("http_send", 3.0),
+                ("exchange_ack", 80.0),
+                ("first_fill", 215.0)
+            ]
+        );
+        assert_eq!(latency.time_to_ack_ms, Some(85.0));
+    }
+
+    #[test]
+    fn test_breakdown_attributes_time_and_flags_budget() {
+        let tracker = LatencyTracker::default();
+        let mut events = tracker.subscribe_events().unwrap();
+        let start = Utc::now();
+        for ack_ms in [60, 70, 400] {
+            tracker.record(&timeline(
+                start,
+                &[
+                    (LatencyMark::RiskChecked, 1),
+                    (LatencyMark::Serialized, 1),
+                    (LatencyMark::Sent, 1),
+                    (LatencyMark::Acknowledged, 1 + ack_ms),
+                ],
+            ));
+        }
+        // Never acknowledged: its stages stay out of the source shares
+        tracker.record(&timeline(start, &[(LatencyMark::RiskChecked, 50)]));
+
+        let breakdown =
+            tracker.breakdown(start - Duration::seconds(1), start + Duration::seconds(1));
+        assert_eq!(breakdown.orders, 4);
+
+        let ack = breakdown
+            .stages
+            .iter()
+            .find(|s| s.stage == "exchange_ack")
+            .unwrap();
+        assert_eq!(ack.count, 3);
+        assert_eq!(ack.max_ms, 400.0);
+        assert_eq!(ack.over_budget, 1);
+        let risk = breakdown
+            .stages
+            .iter()
+            .find(|s| s.stage == "risk_check")
+            .unwrap();
+        assert_eq!(risk.count, 4);
+        assert_eq!(risk.over_budget, 1);
+
+        let time_to_ack = breakdown.time_to_ack.unwrap();
+        assert_eq!(time_to_ack.count, 3);
+        assert_eq!(time_to_ack.p50_ms, 71.0);
+
+        let exchange = breakdown
+            .sources
+            .iter()
+            .find(|s| s.source == LatencySource::Exchange)
+            .unwrap();
+        assert_eq!(exchange.total_ms, 530.0);
+        assert!((exchange.share_pct - 530.0 / 533.0 * 100.0).abs() < 1e-9);
+
+        assert_eq!(std::iter::from_fn(|| events.try_recv().ok()).count(), 4);
+        assert!(
+            tracker
+                .breakdown(start + Duration::seconds(1), start + Duration::seconds(2))
+                .stages
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_pending_signals_are_taken_once() {
+        let tracker = LatencyTracker::default();
+        let signal_id = Uuid::new_v4();
+        tracker.signal_received(signal_id);
+        assert!(tracker.take_signal_received(signal_id).is_some());
+        assert!(tracker.take_signal_received(signal_id).is_none());
+    }
+}
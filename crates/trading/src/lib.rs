@@ -1,11 +1,102 @@
+pub mod account;
 pub mod algorithms;
+pub mod brackets;
+pub mod daily_loss;
+pub mod degraded;
+pub mod drop_copy;
 pub mod error;
+pub mod execution_policy;
+pub mod execution_store;
+pub mod fat_finger;
+pub mod feed_quality;
+pub mod gate;
+pub mod instruments;
+pub mod intent_log;
+pub mod latency;
+pub mod liquidity;
 pub mod order_manager;
+pub mod pre_trade;
+pub mod quotas;
+pub mod reduce_only;
+pub mod reservations;
+pub mod retry_advisor;
+pub mod scale_out;
+pub mod signal_queue;
+pub mod size_limits;
+pub mod snapshot;
 pub mod state_machine;
+pub mod symbol_catalog;
+pub mod trade_volume;
+pub mod volume_profile;
 
+pub use account::{
+    AccountEvent, AccountSnapshotSource, AccountState, AccountTracker, BalanceDivergence,
+    CurrencyBalance, ReconciliationConfig, ReconciliationReport,
+};
 pub use algorithms::{
-    SliceExecution, TwapConfig, TwapExecutor, TwapResult, VwapConfig, VwapExecutor, VwapResult,
+    AdaptationDecision, AdaptivePacer, AdaptiveTwapConfig, MicroTimingConfig,
+    ParticipationConfig, SliceExecution, SliceTiming, TwapConfig, TwapExecutor, TwapResult,
+    VwapConfig, VwapExecutor, VwapResult,
+};
+pub use brackets::{
+    Bracket, BracketChange, BracketManager, BracketVenue, OkxBracketVenue, ProtectedPosition,
 };
+pub use daily_loss::{DEFAULT_ACCOUNT, DailyLossEvent, DailyLossLock, DailyPnl, UnlockReason};
+pub use degraded::{DegradedMode, DegradedModePolicy, DegradedState};
+pub use drop_copy::{DropCopyFile, DropCopyFormat, JournalEntry, LifecycleEvent, OrderJournal};
 pub use error::{Error, Result};
+pub use execution_policy::{
+    ExecutionPolicies, ExecutionPolicy, ExecutionRoute, OrderPlan, OrderPurpose, OrderStyle,
+    TwapRouting,
+};
+pub use execution_store::{
+    AlgoExecution, AlgoExecutionStatus, AlgoExecutionStore, AlgoParams, FileAlgoExecutionStore,
+    InMemoryAlgoExecutionStore, RecoveryPolicy, RecoveryReport, cancel_child_orders,
+    recover_executions,
+};
+pub use fat_finger::{
+    BreachAction, FatFingerBreach, FatFingerConfig, FatFingerDecision, FatFingerGuard,
+    FatFingerLimits,
+};
+pub use feed_quality::FeedQualityGuard;
+pub use gate::{ExecutionGate, GateDecision};
+pub use instruments::{
+    InstrumentEvent, InstrumentStatus, InstrumentStatusChange, InstrumentStatusSource,
+    InstrumentStatusTracker,
+};
+pub use intent_log::{
+    FileIntentLog, InMemoryIntentLog, IntentLog, IntentRecoveryPolicy, IntentRecoveryReport,
+    IntentStatus, IntentVenue, OkxIntentVenue, OrderIntent, recover_intents,
+};
+pub use latency::{
+    LatencyBreakdown, LatencyBudget, LatencyMark, LatencySource, LatencyTracker, OrderLatency,
+    OrderTimeline, SourceShare, StageStats, StageTiming,
+};
+pub use liquidity::{LiquidityConfig, LiquidityGuard, LocalOrderBook, OrderBooks};
 pub use order_manager::{OrderEvent, OrderManager, OrderManagerConfig, OrderManagerStats};
+pub use pre_trade::{PreTradeCheck, PreTradeChecks, PreTradeReport};
+pub use quotas::{QuotaBreach, QuotaKind, QuotaTracker, QuotaUsage, StrategyQuota};
+pub use reduce_only::{PositionSource, ReduceOnlyDecision, ReduceOnlyGuard, enforce_reduce_only};
+pub use reservations::{BalanceReservations, BalanceSource, Reservation};
+pub use retry_advisor::{OrderConstraints, Remediation, RetryAdvice, RetryAdvisor};
+pub use scale_out::{
+    AlgoOrderVenue, ExitStatus, OkxAlgoVenue, PlanManagement, PositionPlan, ProfitTarget,
+    ScaleOutManager, ScaleOutPlan, TrailState, TrailingExit, TrancheState,
+};
+pub use signal_queue::{SignalPriority, SignalQueue, SignalQueueConfig, SignalQueueMetrics};
+pub use size_limits::{
+    OversizeAction, SizeDecision, SizeLimitConfig, SizeLimitGuard, SizeLimitSource, SizeLimits,
+    TradeMode,
+};
+pub use snapshot::{
+    EngineSnapshot, FileSnapshotStore, InMemorySnapshotStore, SNAPSHOT_SCHEMA_VERSION,
+    SnapshotConfig, SnapshotDataSource, SnapshotInfo, SnapshotScheduler, SnapshotStore,
+    StrategySnapshot, reconcile_orders,
+};
 pub use state_machine::{OrderState, OrderStateMachine, StateTransition};
+pub use symbol_catalog::{CatalogSync, InstrumentListing, SymbolCatalog, SymbolQuery};
+pub use trade_volume::{TradeTally, TradeVolumes};
+pub use volume_profile::{
+    FileVolumeProfileStore, InMemoryVolumeProfileStore, VolumeProfile, VolumeProfileConfig,
+    VolumeProfileEstimator, VolumeProfileStore,
+};
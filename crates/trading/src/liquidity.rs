@@ -0,0 +1,401 @@
+//! Liquidity-aware sizing from order book depth
+//!
+//! An order much larger than what rests near the mid walks the book and
+//! fills far from the price the strategy saw. [`LiquidityGuard`] caps each
+//! order at a fraction of the visible depth within a band around the mid on
+//! the side the order takes from: asks for buys, bids for sells. Depth comes
+//! from the local books in [`OrderBooks`], kept up to date from the order
+//! book channel.
+//!
+//! Orders are let through uncapped when the symbol has no book or the book
+//! is stale, and reduce-only orders are never capped so a position can
+//! always be closed.
+
+use crate::error::{Error, Result};
+use crate::retry_advisor::round_down;
+use crate::size_limits::{OversizeAction, SizeDecision};
+use chrono::{DateTime, Duration, Utc};
+use ea_okx_client::models::{BookLevel, OrderBookData};
+use ea_okx_core::Symbol;
+use ea_okx_core::models::{Order, OrderSide};
+use ea_okx_core::types::Quantity;
+use parking_lot::RwLock;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// Order book of one symbol, rebuilt from snapshots and incremental updates
+#[derive(Debug, Clone, Default)]
+pub struct LocalOrderBook {
+    /// Size resting at each price
+    bids: BTreeMap<Decimal, Decimal>,
+    asks: BTreeMap<Decimal, Decimal>,
+    updated_at: Option<DateTime<Utc>>,
+}
+
+impl LocalOrderBook {
+    /// Apply a push; a snapshot replaces the book, an update merges into it
+    /// with zero-size levels removed
+    pub fn apply(&mut self, book: &OrderBookData, snapshot: bool) -> Result<()> {
+        if snapshot {
+            self.bids.clear();
+            self.asks.clear();
+        }
+        merge_levels(&mut self.bids, &book.bids)?;
+        merge_levels(&mut self.asks, &book.asks)?;
+        self.updated_at = Some(
+            book.ts
+                .parse()
+                .ok()
+                .and_then(DateTime::from_timestamp_millis)
+                .unwrap_or_else(Utc::now),
+        );
+        Ok(())
+    }
+
+    pub fn best_bid(&self) -> Option<Decimal> {
+        self.bids.keys().next_back().copied()
+    }
+
+    pub fn best_ask(&self) -> Option<Decimal> {
+        self.asks.keys().next().copied()
+    }
+
+    pub fn mid(&self) -> Option<Decimal> {
+        Some((self.best_bid()? + self.best_ask()?) / dec!(2))
+    }
+
+    pub fn updated_at(&self) -> Option<DateTime<Utc>> {
+        self.updated_at
+    }
+
+    /// Best price an order on `side` takes: the ask for a buy, the bid for
+    /// a sell
+    pub fn touch(&self, side: OrderSide) -> Option<Decimal> {
+        match side {
+            OrderSide::Buy => self.best_ask(),
+            OrderSide::Sell => self.best_bid(),
+        }
+    }
+
+    /// Best ask minus best bid in basis points of mid
+    pub fn spread_bps(&self) -> Option<Decimal> {
+        let mid = self.mid()?;
+        if mid.is_zero() {
+            return None;
+        }
+        Some((self.best_ask()? - self.best_bid()?) / mid * dec!(10000))
+    }
+
+    /// Size resting on `side`'s half of the book (bids for a buy) minus the
+    /// other half, over both, across the best `levels` of each; from -1 to 1
+    pub fn imbalance(&self, side: OrderSide, levels: usize) -> Option<Decimal> {
+        let bids: Decimal = self.bids.values().rev().take(levels).sum();
+        let asks: Decimal = self.asks.values().take(levels).sum();
+        let total = bids + asks;
+        if total.is_zero() {
+            return None;
+        }
+        Some(match side {
+            OrderSide::Buy => (bids - asks) / total,
+            OrderSide::Sell => (asks - bids) / total,
+        })
+    }
+
+    /// Size an order on `side` could take within `bps` of mid
+    pub fn depth_within(&self, side: OrderSide, bps: Decimal) -> Option<Decimal> {
+        let mid = self.mid()?;
+        let band = mid * bps / dec!(10000);
+        let depth = match side {
+            OrderSide::Buy => self.asks.range(..=mid + band).map(|(_, size)| size).sum(),
+            OrderSide::Sell => self.bids.range(mid - band..).map(|(_, size)| size).sum(),
+        };
+        Some(depth)
+    }
+}
+
+fn merge_levels(side: &mut BTreeMap<Decimal, Decimal>, levels: &[BookLevel]) -> Result<()> {
+    for level in levels {
+        let price = level.price()?;
+        let size = level.quantity()?;
+        if size.is_zero() {
+            side.remove(&price);
+        } else {
+            side.insert(price, size);
+        }
+    }
+    Ok(())
+}
+
+/// Local order books of every symbol; shared between the feed and guards
+#[derive(Debug, Default)]
+pub struct OrderBooks {
+    books: RwLock<HashMap<Symbol, LocalOrderBook>>,
+}
+
+impl OrderBooks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply a push for `symbol`; `books5` pushes are always snapshots
+    pub fn apply(&self, symbol: &Symbol, book: &OrderBookData, snapshot: bool) -> Result<()> {
+        self.books
+            .write()
+            .entry(symbol.clone())
+            .or_default()
+            .apply(book, snapshot)
+    }
+
+    pub fn book(&self, symbol: &Symbol) -> Option<LocalOrderBook> {
+        self.books.read().get(symbol).cloned()
+    }
+
+    /// Forget a book that no longer matches the exchange, e.g. after a
+    /// sequence gap, until its next snapshot
+    pub fn remove(&self, symbol: &Symbol) {
+        self.books.write().remove(symbol);
+    }
+}
+
+/// Liquidity guard configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LiquidityConfig {
+    /// Largest share of the visible depth one order may take
+    pub max_depth_fraction: Decimal,
+
+    /// Depth counted within this many basis points of mid
+    pub depth_bps: Decimal,
+
+    /// Books older than this are not trusted
+    pub max_book_age_secs: i64,
+
+    pub oversize_action: OversizeAction,
+}
+
+impl Default for LiquidityConfig {
+    fn default() -> Self {
+        Self {
+            max_depth_fraction: dec!(0.25),
+            depth_bps: dec!(10),
+            max_book_age_secs: 5,
+            oversize_action: OversizeAction::Clamp,
+        }
+    }
+}
+
+impl LiquidityConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.max_depth_fraction <= Decimal::ZERO || self.max_depth_fraction > Decimal::ONE {
+            return Err(Error::InvalidPolicy(
+                "Depth fraction must be above 0 and at most 1".to_string(),
+            ));
+        }
+        if self.depth_bps <= Decimal::ZERO {
+            return Err(Error::InvalidPolicy(
+                "Depth band must be positive".to_string(),
+            ));
+        }
+        if self.max_book_age_secs <= 0 {
+            return Err(Error::InvalidPolicy(
+                "Max book age must be positive".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Caps order sizes to a fraction of the depth near mid
+pub struct LiquidityGuard {
+    config: RwLock<LiquidityConfig>,
+    books: Arc<OrderBooks>,
+}
+
+impl LiquidityGuard {
+    pub fn new(config: LiquidityConfig, books: Arc<OrderBooks>) -> Self {
+        Self {
+            config: RwLock::new(config),
+            books,
+        }
+    }
+
+    pub fn config(&self) -> LiquidityConfig {
+        self.config.read().clone()
+    }
+
+    pub fn set_config(&self, config: LiquidityConfig) -> Result<()> {
+        config.validate()?;
+        *self.config.write() = config;
+        Ok(())
+    }
+
+    pub fn books(&self) -> &Arc<OrderBooks> {
+        &self.books
+    }
+
+    /// Largest order on `side` the visible depth allows, if the book is fresh
+    pub fn cap(&self, symbol: &Symbol, side: OrderSide, now: DateTime<Utc>) -> Option<Decimal> {
+        let config = self.config();
+        let book = self.books.book(symbol)?;
+        if now - book.updated_at()? > Duration::seconds(config.max_book_age_secs) {
+            return None;
+        }
+        Some(book.depth_within(side, config.depth_bps)? * config.max_depth_fraction)
+    }
+
+    /// Check `order` against the depth cap, clamping its quantity if
+    /// configured to; a clamped size is rounded down to `lot_size`
+    pub fn apply(&self, order: &mut Order, lot_size: Option<Decimal>) -> Result<SizeDecision> {
+        let cap = self.cap(&order.symbol, order.side, Utc::now());
+        let requested = order.quantity.as_decimal();
+        let cap = match cap {
+            Some(cap) if !order.reduce_only && requested > cap => cap,
+            cap => return Ok(SizeDecision::Within { cap }),
+        };
+
+        let allowed = round_down(cap, lot_size);
+        if self.config.read().oversize_action == OversizeAction::Reject || allowed <= Decimal::ZERO
+        {
+            return Ok(SizeDecision::Rejected { requested, allowed });
+        }
+
+        order.quantity = Quantity::new(allowed)?;
+        Ok(SizeDecision::Clamped { requested, allowed })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ea_okx_core::models::OrderType;
+    use uuid::Uuid;
+
+    fn level(price: &str, size: &str) -> BookLevel {
+        BookLevel(price.into(), size.into(), "0".into(), "1".into())
+    }
+
+    fn push(bids: Vec<BookLevel>, asks: Vec<BookLevel>) -> OrderBookData {
+        OrderBookData {
+            inst_id: None,
+            asks,
+            bids,
+            ts: Utc::now().timestamp_millis().to_string(),
+            checksum: None,
+            prev_seq_id: None,
+            seq_id: None,
+        }
+    }
+
+    fn order(side: OrderSide, qty: Decimal) -> Order {
+        Order::new(
+            Uuid::new_v4(),
+            Symbol::new("BTC-USDT").unwrap(),
+            side,
+            OrderType::Market,
+            Quantity::new(qty).unwrap(),
+            None,
+        )
+    }
+
+    fn guard(config: LiquidityConfig) -> LiquidityGuard {
+        let books = Arc::new(OrderBooks::new());
+        let symbol = Symbol::new("BTC-USDT").unwrap();
+        // Mid 100; 10 bps reaches 99.9 and 100.1
+        books
+            .apply(
+                &symbol,
+                &push(
+                    vec![level("99.95", "2"), level("99.9", "2"), level("99", "50")],
+                    vec![
+                        level("100.05", "1"),
+                        level("100.1", "3"),
+                        level("101", "50"),
+                    ],
+                ),
+                true,
+            )
+            .unwrap();
+        LiquidityGuard::new(config, books)
+    }
+
+    #[test]
+    fn test_updates_merge_into_the_book() {
+        let mut book = LocalOrderBook::default();
+        book.apply(
+            &push(
+                vec![level("99", "1"), level("98", "1")],
+                vec![level("101", "1")],
+            ),
+            true,
+        )
+        .unwrap();
+        book.apply(
+            &push(vec![level("99", "0")], vec![level("100.5", "2")]),
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(book.best_bid(), Some(dec!(98)));
+        assert_eq!(book.best_ask(), Some(dec!(100.5)));
+        assert_eq!(book.mid(), Some(dec!(99.25)));
+    }
+
+    #[test]
+    fn test_caps_to_fraction_of_depth_near_mid() {
+        let guard = guard(LiquidityConfig::default());
+
+        let mut buy = order(OrderSide::Buy, dec!(5));
+        assert_eq!(
+            guard.apply(&mut buy, Some(dec!(0.1))).unwrap(),
+            SizeDecision::Clamped {
+                requested: dec!(5),
+                allowed: dec!(1)
+            }
+        );
+        assert_eq!(buy.quantity.as_decimal(), dec!(1));
+
+        let mut sell = order(OrderSide::Sell, dec!(0.5));
+        assert_eq!(
+            guard.apply(&mut sell, None).unwrap(),
+            SizeDecision::Within {
+                cap: Some(dec!(1.00))
+            }
+        );
+
+        // Closing a position is never held back by thin books
+        let mut close = order(OrderSide::Sell, dec!(5)).with_reduce_only();
+        assert!(matches!(
+            guard.apply(&mut close, None).unwrap(),
+            SizeDecision::Within { .. }
+        ));
+    }
+
+    #[test]
+    fn test_reject_and_stale_books() {
+        let guard = guard(LiquidityConfig {
+            oversize_action: OversizeAction::Reject,
+            ..Default::default()
+        });
+        let mut buy = order(OrderSide::Buy, dec!(5));
+        assert!(matches!(
+            guard.apply(&mut buy, None).unwrap(),
+            SizeDecision::Rejected { allowed, .. } if allowed == dec!(1)
+        ));
+        assert_eq!(buy.quantity.as_decimal(), dec!(5));
+
+        let symbol = Symbol::new("BTC-USDT").unwrap();
+        let later = Utc::now() + Duration::seconds(30);
+        assert_eq!(guard.cap(&symbol, OrderSide::Buy, later), None);
+
+        assert!(
+            guard
+                .set_config(LiquidityConfig {
+                    max_depth_fraction: dec!(1.5),
+                    ..Default::default()
+                })
+                .is_err()
+        );
+    }
+}
@@ -1,11 +1,29 @@
 use crate::error::{Error, Result};
+use crate::fat_finger::FatFingerGuard;
+use crate::gate::{ExecutionGate, GateDecision};
+use crate::intent_log::{
+    IntentLog, IntentRecoveryPolicy, IntentRecoveryReport, IntentStatus, IntentVenue,
+    recover_intents,
+};
+use crate::liquidity::LiquidityGuard;
+use crate::pre_trade::PreTradeChecks;
+use crate::reduce_only::{ReduceOnlyDecision, ReduceOnlyGuard};
+use crate::reservations::BalanceReservations;
+use crate::retry_advisor::{OrderConstraints, RetryAdvice, RetryAdvisor};
+use crate::size_limits::{SizeDecision, SizeLimitGuard};
 use crate::state_machine::{OrderState, OrderStateMachine};
-use chrono::{DateTime, Duration, Utc};
-use ea_okx_client::OkxRestClient;
-use ea_okx_core::models::Order;
-use ea_okx_core::{Price, Quantity};
+use chrono::{DateTime, Utc};
+#[cfg(feature = "fault-injection")]
+use ea_okx_client::FaultInjector;
+use ea_okx_client::RejectionReason;
+use ea_okx_client::models::OrderData;
+use ea_okx_core::ExchangeAdapter;
+use ea_okx_core::models::{Order, OrderAttribution, OrderSide, OrderStatus, OrderType};
+use ea_okx_core::{Price, Quantity, Symbol};
 use parking_lot::RwLock;
+use rust_decimal::Decimal;
 use std::collections::HashMap;
+use std::str::FromStr;
 use std::sync::Arc;
 use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
@@ -17,7 +35,7 @@ pub struct OrderManagerConfig {
     /// Reconciliation interval in seconds
     pub reconciliation_interval_secs: u64,
 
-    /// Order timeout in seconds
+    /// Seconds an order may wait for the exchange to acknowledge it
     pub order_timeout_secs: u64,
 
     /// Maximum retry attempts
@@ -44,13 +62,42 @@ struct ManagedOrder {
     order: Order,
     state_machine: OrderStateMachine,
     retry_count: u32,
-    last_sync: DateTime<Utc>,
+    /// Acknowledged locally and never sent to the exchange
+    dry_run: bool,
+}
+
+impl ManagedOrder {
+    /// Whether the order was sent and may still be resting
+    fn is_resting(&self) -> bool {
+        matches!(
+            self.state_machine.current_state,
+            OrderState::Submitted | OrderState::Acknowledged | OrderState::PartiallyFilled
+        )
+    }
 }
 
 /// Order event types
 #[derive(Debug, Clone)]
 pub enum OrderEvent {
     OrderCreated(Uuid),
+    /// Order was reduced to the exchange's size cap before submission
+    OrderSizeClamped {
+        order_id: Uuid,
+        requested: Decimal,
+        allowed: Decimal,
+    },
+    /// Order was reduced to its share of the visible book depth before submission
+    OrderLiquidityClamped {
+        order_id: Uuid,
+        requested: Decimal,
+        allowed: Decimal,
+    },
+    /// Reduce-only order was reduced to the open position before submission
+    OrderReduceOnlyClamped {
+        order_id: Uuid,
+        requested: Decimal,
+        allowed: Decimal,
+    },
     OrderSubmitted(Uuid),
     OrderAcknowledged {
         order_id: Uuid,
@@ -65,21 +112,43 @@ pub enum OrderEvent {
         avg_price: Price,
     },
     OrderCancelled(Uuid),
+    /// Resting order's size or limit price was changed on the exchange
+    OrderAmended {
+        order_id: Uuid,
+        quantity: Quantity,
+        price: Option<Price>,
+    },
     OrderRejected {
         order_id: Uuid,
         reason: String,
+        rejection: RejectionReason,
+        advice: RetryAdvice,
     },
     OrderFailed {
         order_id: Uuid,
         reason: String,
     },
     OrderExpired(Uuid),
+    /// Exchange order placed by this system but unknown to the manager,
+    /// such as one left open across a restart, now tracked again
+    OrderAdopted {
+        order_id: Uuid,
+        strategy_id: Uuid,
+        exchange_id: String,
+    },
+    /// Exchange order whose client order ID names no strategy
+    OrderUnattributed {
+        exchange_id: String,
+        client_order_id: String,
+    },
 }
 
 /// Main order manager
 pub struct OrderManager {
     config: OrderManagerConfig,
-    client: Arc<OkxRestClient>,
+
+    /// Venue orders are sent to
+    exchange: Arc<dyn ExchangeAdapter>,
 
     /// Active orders indexed by internal ID
     orders: Arc<RwLock<HashMap<Uuid, ManagedOrder>>>,
@@ -87,6 +156,19 @@ pub struct OrderManager {
     /// Map exchange order ID to internal ID
     exchange_id_map: Arc<RwLock<HashMap<String, Uuid>>>,
 
+    /// Per-symbol limits used when advising on rejections
+    constraints: Arc<RwLock<HashMap<Symbol, OrderConstraints>>>,
+
+    /// Rejection remediation
+    advisor: RetryAdvisor,
+
+    /// Gate, guards, reservations and intent log run before anything is sent
+    checks: PreTradeChecks,
+
+    /// Simulated exchange latency and rejects
+    #[cfg(feature = "fault-injection")]
+    faults: Option<Arc<FaultInjector>>,
+
     /// Event channel
     event_tx: mpsc::UnboundedSender<OrderEvent>,
     event_rx: Arc<RwLock<Option<mpsc::UnboundedReceiver<OrderEvent>>>>,
@@ -94,22 +176,123 @@ pub struct OrderManager {
 
 impl OrderManager {
     /// Create new order manager
-    pub fn new(config: OrderManagerConfig, client: Arc<OkxRestClient>) -> Self {
+    pub fn new(config: OrderManagerConfig, exchange: Arc<dyn ExchangeAdapter>) -> Self {
         let (event_tx, event_rx) = mpsc::unbounded_channel();
+        let advisor = RetryAdvisor::new(config.max_retries, config.retry_backoff_ms);
 
         Self {
             config,
-            client,
+            exchange,
             orders: Arc::new(RwLock::new(HashMap::new())),
             exchange_id_map: Arc::new(RwLock::new(HashMap::new())),
+            constraints: Arc::new(RwLock::new(HashMap::new())),
+            advisor,
+            checks: PreTradeChecks::new(),
+            #[cfg(feature = "fault-injection")]
+            faults: None,
             event_tx,
             event_rx: Arc::new(RwLock::new(Some(event_rx))),
         }
     }
 
+    /// Share an execution gate with other order paths
+    pub fn with_gate(mut self, gate: Arc<ExecutionGate>) -> Self {
+        self.checks = self.checks.with_gate(gate);
+        self
+    }
+
+    /// Clamp or reject orders above the exchange's max available size
+    pub fn with_size_guard(mut self, guard: Arc<SizeLimitGuard>) -> Self {
+        self.checks = self.checks.with_size_guard(guard);
+        self
+    }
+
+    /// Clamp or reject orders above their share of the visible book depth
+    pub fn with_liquidity_guard(mut self, guard: Arc<LiquidityGuard>) -> Self {
+        self.checks = self.checks.with_liquidity_guard(guard);
+        self
+    }
+
+    /// Hold or refuse orders that fail the fat-finger sanity checks
+    pub fn with_fat_finger_guard(mut self, guard: Arc<FatFingerGuard>) -> Self {
+        self.checks = self.checks.with_fat_finger_guard(guard);
+        self
+    }
+
+    /// Check reduce-only orders against the open position
+    pub fn with_reduce_only_guard(mut self, guard: Arc<ReduceOnlyGuard>) -> Self {
+        self.checks = self.checks.with_reduce_only_guard(guard);
+        self
+    }
+
+    /// Log each order durably before it is sent, so a crash before the
+    /// exchange answers can be settled by [`OrderManager::recover_intents`]
+    pub fn with_intent_log(mut self, log: Arc<dyn IntentLog>) -> Self {
+        self.checks = self.checks.with_intent_log(log);
+        self
+    }
+
+    /// Reserve balance for each pending spot order, refusing orders the
+    /// unreserved balance cannot cover
+    pub fn with_balance_reservations(mut self, reservations: Arc<BalanceReservations>) -> Self {
+        self.checks = self.checks.with_balance_reservations(reservations);
+        self
+    }
+
+    /// Inject latency and rejects into exchange calls
+    #[cfg(feature = "fault-injection")]
+    pub fn with_fault_injector(mut self, injector: Arc<FaultInjector>) -> Self {
+        self.faults = Some(injector);
+        self
+    }
+
+    /// Apply injected latency, then fail with an injected reject if one is rolled
+    #[cfg(feature = "fault-injection")]
+    async fn inject_faults(&self) -> Result<()> {
+        if let Some(faults) = &self.faults {
+            faults.delay().await;
+            if let Some(reject) = faults.reject() {
+                return Err(Error::ClientError(reject));
+            }
+        }
+        Ok(())
+    }
+
+    /// Execution gate consulted before each submission
+    pub fn gate(&self) -> &Arc<ExecutionGate> {
+        self.checks.gate()
+    }
+
     /// Submit a new order
-    pub async fn submit_order(&self, order: Order) -> Result<Uuid> {
+    ///
+    /// The order passes the [`PreTradeChecks`] first. If a size guard is configured, an order above the exchange cap is
+    /// either clamped (reported via [`OrderEvent::OrderSizeClamped`]) or
+    /// refused with [`Error::SizeLimitExceeded`]; a liquidity guard does the
+    /// same against the visible book depth, reporting
+    /// [`OrderEvent::OrderLiquidityClamped`] or [`Error::LiquidityExceeded`].
+    /// Orders failing the fat-finger guard return
+    /// [`Error::FatFingerRejected`] or, if the breach is confirmable,
+    /// [`Error::ConfirmationRequired`]; resubmitting the same order after
+    /// [`FatFingerGuard::confirm`] sends it. With a reduce-only guard, a
+    /// reduce-only order is clamped to the open position (reported via
+    /// [`OrderEvent::OrderReduceOnlyClamped`]) and refused with
+    /// [`Error::ReduceOnlyRejected`] when there is nothing on the other side
+    /// to reduce. While the exchange is degraded, limit prices are widened
+    /// per the gate's [`DegradedModePolicy`](crate::DegradedModePolicy).
+    /// With balance reservations, an order needing more than the unreserved
+    /// balance is refused with [`Error::InsufficientBalance`]; its
+    /// reservation is released once it fills or ends. A resting order is
+    /// expired when its expiry passes; an order already past its expiry is
+    /// refused. With an intent log, an order that cannot be logged is not
+    /// sent.
+    pub async fn submit_order(&self, mut order: Order) -> Result<Uuid> {
         let order_id = order.id;
+        if order.is_expired(Utc::now()) {
+            return Err(Error::ExecutionError(format!(
+                "Order {} expired before submission",
+                order_id
+            )));
+        }
 
         let price_str = order
             .price
@@ -123,6 +306,17 @@ impl OrderManager {
             price_str
         );
 
+        let lot_size = self
+            .constraints
+            .read()
+            .get(&order.symbol)
+            .and_then(|c| c.lot_size);
+        let report = self.checks.run(&mut order, lot_size, None).await;
+        if let Some((_, e)) = report.refused {
+            return Err(e);
+        }
+        let dry_run = report.dry_run;
+
         // Create state machine
         let mut state_machine = OrderStateMachine::new(order_id);
         state_machine.transition(OrderState::Validated, "Pre-trade checks passed")?;
@@ -132,70 +326,181 @@ impl OrderManager {
             order: order.clone(),
             state_machine,
             retry_count: 0,
-            last_sync: Utc::now(),
+            dry_run,
         };
 
         self.orders.write().insert(order_id, managed_order);
 
         // Emit event
         let _ = self.event_tx.send(OrderEvent::OrderCreated(order_id));
+        if let Some(ReduceOnlyDecision::Clamped { requested, allowed }) = report.reduce_only {
+            let _ = self.event_tx.send(OrderEvent::OrderReduceOnlyClamped {
+                order_id,
+                requested,
+                allowed,
+            });
+        }
+        if let Some(SizeDecision::Clamped { requested, allowed }) = report.size {
+            let _ = self.event_tx.send(OrderEvent::OrderSizeClamped {
+                order_id,
+                requested,
+                allowed,
+            });
+        }
+        if let Some(SizeDecision::Clamped { requested, allowed }) = report.liquidity {
+            let _ = self.event_tx.send(OrderEvent::OrderLiquidityClamped {
+                order_id,
+                requested,
+                allowed,
+            });
+        }
 
         // Submit to exchange (async)
         let self_clone = Self {
             config: self.config.clone(),
-            client: self.client.clone(),
+            exchange: self.exchange.clone(),
             orders: self.orders.clone(),
             exchange_id_map: self.exchange_id_map.clone(),
+            constraints: self.constraints.clone(),
+            advisor: self.advisor.clone(),
+            checks: self.checks.clone(),
+            #[cfg(feature = "fault-injection")]
+            faults: self.faults.clone(),
             event_tx: self.event_tx.clone(),
             event_rx: self.event_rx.clone(),
         };
 
+        let expires_at = order.expires_at;
+        let client_order_id = order.client_order_id.clone();
         tokio::spawn(async move {
-            if let Err(e) = self_clone.submit_to_exchange(order_id).await {
-                error!("Failed to submit order {}: {}", order_id, e);
-                let _ = self_clone.event_tx.send(OrderEvent::OrderFailed {
-                    order_id,
-                    reason: e.to_string(),
-                });
+            let submitted = self_clone.submit_to_exchange(order_id, dry_run).await;
+            if !dry_run {
+                self_clone.resolve_intent(&client_order_id, &submitted);
+            }
+            match submitted {
+                Ok(_) => {
+                    if let Some(expires_at) = expires_at {
+                        let delay = (expires_at - Utc::now()).to_std().unwrap_or_default();
+                        tokio::time::sleep(delay).await;
+                        if let Err(e) = self_clone.expire_order(order_id).await {
+                            warn!("Failed to expire order {}: {}", order_id, e);
+                        }
+                    }
+                }
+                Err(e) => match rejection_of(&e) {
+                    Some((rejection, message)) => {
+                        if let Err(err) = self_clone.handle_rejection(order_id, rejection, &message)
+                        {
+                            error!("Failed to record rejection of order {}: {}", order_id, err);
+                        }
+                    }
+                    None => {
+                        error!("Failed to submit order {}: {}", order_id, e);
+                        self_clone.release_reservation(order_id);
+                        let _ = self_clone.event_tx.send(OrderEvent::OrderFailed {
+                            order_id,
+                            reason: e.to_string(),
+                        });
+                    }
+                },
             }
         });
 
         Ok(order_id)
     }
 
-    /// Submit order to exchange
-    async fn submit_to_exchange(&self, order_id: Uuid) -> Result<()> {
-        // Get order
-        let order = {
-            let orders = self.orders.read();
-            orders
-                .get(&order_id)
-                .ok_or_else(|| Error::OrderNotFound(order_id.to_string()))?
-                .order
-                .clone()
+    /// Release the balance reserved for an order that ended
+    fn release_reservation(&self, order_id: Uuid) {
+        if let Some(reservations) = self.checks.reservations()
+            && let Some(released) = reservations.release(order_id)
+        {
+            debug!(
+                "Released {} {} reserved for order {}",
+                released.amount(),
+                released.ccy,
+                order_id
+            );
+        }
+    }
+
+    /// Record the outcome of a submission in the intent log
+    ///
+    /// Transport failures leave the intent pending: the request may have
+    /// reached the exchange, so only recovery can tell.
+    fn resolve_intent(&self, client_order_id: &str, submitted: &Result<String>) {
+        let Some(log) = self.checks.intent_log() else {
+            return;
+        };
+        let (status, exchange_id, reason) = match submitted {
+            Ok(exchange_id) => (IntentStatus::Acknowledged, Some(exchange_id.clone()), None),
+            Err(Error::ClientError(e @ ea_okx_client::Error::ApiError { .. })) => {
+                (IntentStatus::Rejected, None, Some(e.to_string()))
+            }
+            Err(Error::CoreError(e @ ea_okx_core::Error::ExchangeRejected { .. })) => {
+                (IntentStatus::Rejected, None, Some(e.to_string()))
+            }
+            Err(Error::ClientError(_))
+            | Err(Error::CoreError(
+                ea_okx_core::Error::ExchangeError(_)
+                | ea_okx_core::Error::RateLimited(_)
+                | ea_okx_core::Error::ExchangeUnavailable(_),
+            )) => return,
+            Err(e) => (IntentStatus::Failed, None, Some(e.to_string())),
         };
+        if let Err(e) = log.resolve(client_order_id, status, exchange_id, reason) {
+            error!("Failed to resolve order intent {}: {}", client_order_id, e);
+        }
+    }
+
+    /// Settle intents left pending by a previous run against `venue`,
+    /// tracking their recovered orders again
+    pub async fn recover_intents(
+        &self,
+        venue: &dyn IntentVenue,
+        policy: &IntentRecoveryPolicy,
+    ) -> Result<IntentRecoveryReport> {
+        let Some(log) = self.checks.intent_log() else {
+            return Ok(IntentRecoveryReport::default());
+        };
+        let report = recover_intents(log.as_ref(), venue, policy).await?;
+        for order in &report.recovered {
+            self.resolve_exchange_order(order)?;
+        }
+        Ok(report)
+    }
 
+    /// Submit order to exchange, returning the exchange order ID
+    async fn submit_to_exchange(&self, order_id: Uuid, dry_run: bool) -> Result<String> {
         // Update state
-        {
+        let order = {
             let mut orders = self.orders.write();
-            if let Some(managed) = orders.get_mut(&order_id) {
-                managed
-                    .state_machine
-                    .transition(OrderState::Submitted, "Sending to exchange")?;
-            }
-        }
+            let managed = orders
+                .get_mut(&order_id)
+                .ok_or_else(|| Error::OrderNotFound(order_id.to_string()))?;
+            managed
+                .state_machine
+                .transition(OrderState::Submitted, "Sending to exchange")?;
+            managed.order.clone()
+        };
 
         let _ = self.event_tx.send(OrderEvent::OrderSubmitted(order_id));
 
-        // Submit via REST API
-        // Note: This would call the actual OKX client
-        // For now, we'll simulate acknowledgment
-        info!("Order {} submitted to exchange", order_id);
+        let exchange_id = if dry_run {
+            // Acknowledge locally; nothing leaves the process
+            format!("DRYRUN-{}", order_id)
+        } else {
+            #[cfg(feature = "fault-injection")]
+            self.inject_faults().await?;
 
-        // Simulate exchange response
-        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-
-        let exchange_id = format!("OKX-{}", order_id);
+            let exchange_id = self.exchange.place_order(&order).await?;
+            info!(
+                "Order {} placed on {} as {}",
+                order_id,
+                self.exchange.name(),
+                exchange_id
+            );
+            exchange_id
+        };
 
         // Update state
         {
@@ -204,6 +509,7 @@ impl OrderManager {
                 managed
                     .state_machine
                     .transition(OrderState::Acknowledged, "Exchange confirmed")?;
+                managed.order.mark_submitted(exchange_id.clone());
             }
         }
 
@@ -214,16 +520,16 @@ impl OrderManager {
 
         let _ = self.event_tx.send(OrderEvent::OrderAcknowledged {
             order_id,
-            exchange_id,
+            exchange_id: exchange_id.clone(),
         });
 
-        Ok(())
+        Ok(exchange_id)
     }
 
     /// Cancel an order
     pub async fn cancel_order(&self, order_id: Uuid) -> Result<()> {
         // Check if order can be cancelled
-        {
+        let (symbol, client_order_id, on_exchange) = {
             let orders = self.orders.read();
             let managed = orders
                 .get(&order_id)
@@ -235,12 +541,31 @@ impl OrderManager {
                     order_id, managed.state_machine.current_state
                 )));
             }
-        }
+
+            if let GateDecision::Throttled(reason) =
+                self.checks.gate().check_cancel(managed.order.strategy_id)
+            {
+                return Err(Error::QuotaExceeded(reason));
+            }
+
+            (
+                managed.order.symbol.clone(),
+                managed.order.client_order_id.clone(),
+                managed.is_resting() && !managed.dry_run,
+            )
+        };
 
         info!("Cancelling order {}", order_id);
 
-        // Send cancel request to exchange
-        // (Would use actual OKX client here)
+        // Orders not sent yet are stopped before they reach the exchange
+        if on_exchange {
+            #[cfg(feature = "fault-injection")]
+            self.inject_faults().await?;
+
+            self.exchange
+                .cancel_order(&symbol, &client_order_id)
+                .await?;
+        }
 
         // Update state
         {
@@ -252,11 +577,279 @@ impl OrderManager {
             }
         }
 
+        self.release_reservation(order_id);
         let _ = self.event_tx.send(OrderEvent::OrderCancelled(order_id));
 
         Ok(())
     }
 
+    /// Change the size or limit price of a resting order
+    ///
+    /// `quantity` is the new total size, filled part included, and must stay
+    /// above what has filled. Amendments count against the cancel quota.
+    /// With balance reservations, the amended order is reserved again and
+    /// refused with [`Error::InsufficientBalance`] if the unreserved balance
+    /// cannot cover it.
+    pub async fn amend_order(
+        &self,
+        order_id: Uuid,
+        quantity: Option<Quantity>,
+        price: Option<Price>,
+    ) -> Result<()> {
+        let (original, dry_run) = {
+            let orders = self.orders.read();
+            let managed = orders
+                .get(&order_id)
+                .ok_or_else(|| Error::OrderNotFound(order_id.to_string()))?;
+
+            if !managed.is_resting() {
+                return Err(Error::ExecutionError(format!(
+                    "Order {} cannot be amended in state {:?}",
+                    order_id, managed.state_machine.current_state
+                )));
+            }
+            (managed.order.clone(), managed.dry_run)
+        };
+
+        if let Some(quantity) = quantity
+            && quantity <= original.filled_quantity
+        {
+            return Err(Error::ExecutionError(format!(
+                "Order {} cannot be amended to {}, {} has already filled",
+                order_id,
+                quantity.as_decimal(),
+                original.filled_quantity.as_decimal()
+            )));
+        }
+
+        if let GateDecision::Throttled(reason) =
+            self.checks.gate().check_cancel(original.strategy_id)
+        {
+            return Err(Error::QuotaExceeded(reason));
+        }
+
+        let mut amended = original.clone();
+        if let Some(quantity) = quantity {
+            amended.quantity = quantity;
+        }
+        if price.is_some() {
+            amended.price = price;
+        }
+
+        info!("Amending order {}", order_id);
+
+        if !dry_run {
+            if let Some(reservations) = self.checks.reservations() {
+                reservations.reserve(&amended)?;
+            }
+
+            let sent = self
+                .exchange
+                .amend_order(
+                    &original.symbol,
+                    &original.client_order_id,
+                    quantity.map(|q| q.as_decimal()),
+                    price.map(|p| p.as_decimal()),
+                )
+                .await;
+            if let Err(e) = sent {
+                if let Some(reservations) = self.checks.reservations()
+                    && let Err(err) = reservations.reserve(&original)
+                {
+                    warn!(
+                        "Failed to restore reservation of order {}: {}",
+                        order_id, err
+                    );
+                }
+                return Err(e.into());
+            }
+        }
+
+        if let Some(managed) = self.orders.write().get_mut(&order_id) {
+            managed.order.quantity = amended.quantity;
+            managed.order.price = amended.price;
+        }
+
+        let _ = self.event_tx.send(OrderEvent::OrderAmended {
+            order_id,
+            quantity: amended.quantity,
+            price: amended.price,
+        });
+        Ok(())
+    }
+
+    /// Cancel a resting order whose good-till-date has come
+    ///
+    /// Returns `false` when the order is no longer resting, such as one that
+    /// filled or was cancelled before its expiry.
+    pub async fn expire_order(&self, order_id: Uuid) -> Result<bool> {
+        let (order, dry_run) = {
+            let orders = self.orders.read();
+            let managed = orders
+                .get(&order_id)
+                .ok_or_else(|| Error::OrderNotFound(order_id.to_string()))?;
+            if !managed.is_resting() {
+                return Ok(false);
+            }
+            (managed.order.clone(), managed.dry_run)
+        };
+
+        info!("Order {} reached its expiry, cancelling", order_id);
+
+        if !dry_run {
+            #[cfg(feature = "fault-injection")]
+            self.inject_faults().await?;
+
+            self.exchange
+                .cancel_order(&order.symbol, &order.client_order_id)
+                .await?;
+        }
+
+        {
+            let mut orders = self.orders.write();
+            if let Some(managed) = orders.get_mut(&order_id) {
+                managed
+                    .state_machine
+                    .transition(OrderState::Expired, "Good-till-date passed")?;
+                managed.order.set_status(OrderStatus::Cancelled);
+            }
+        }
+
+        self.release_reservation(order_id);
+        let _ = self.event_tx.send(OrderEvent::OrderExpired(order_id));
+        Ok(true)
+    }
+
+    /// Expire every resting order past its expiry, returning their IDs
+    pub async fn expire_due(&self, now: DateTime<Utc>) -> Vec<Uuid> {
+        let order_ids: Vec<Uuid> = self
+            .orders
+            .read()
+            .values()
+            .filter(|m| m.state_machine.is_active() && m.order.is_expired(now))
+            .map(|m| m.order.id)
+            .collect();
+
+        let mut expired = Vec::with_capacity(order_ids.len());
+        for order_id in order_ids {
+            match self.expire_order(order_id).await {
+                Ok(true) => expired.push(order_id),
+                Ok(false) => {}
+                Err(e) => warn!("Failed to expire order {}: {}", order_id, e),
+            }
+        }
+        expired
+    }
+
+    /// Record an exchange rejection and advise on how to resubmit
+    ///
+    /// Emits [`OrderEvent::OrderRejected`] carrying the typed reason and the
+    /// suggested remediation.
+    pub fn handle_rejection(
+        &self,
+        order_id: Uuid,
+        rejection: RejectionReason,
+        message: &str,
+    ) -> Result<RetryAdvice> {
+        let advice = {
+            let mut orders = self.orders.write();
+            let managed = orders
+                .get_mut(&order_id)
+                .ok_or_else(|| Error::OrderNotFound(order_id.to_string()))?;
+
+            managed
+                .state_machine
+                .transition(OrderState::Rejected, rejection.as_str())?;
+            managed.order.set_status(OrderStatus::Rejected);
+            managed.order.reject_reason = Some(message.to_string());
+
+            // Cached caps are evidently stale once the exchange refuses an order
+            if let Some(guard) = self.checks.size_guard() {
+                guard.invalidate(&managed.order.symbol);
+            }
+
+            let constraints = self
+                .constraints
+                .read()
+                .get(&managed.order.symbol)
+                .cloned()
+                .unwrap_or_default();
+
+            self.advisor.advise(
+                &rejection,
+                &managed.order,
+                &constraints,
+                managed.retry_count,
+            )
+        };
+
+        self.release_reservation(order_id);
+        warn!(
+            "Order {} rejected ({}): {} - {}",
+            order_id,
+            rejection.as_str(),
+            message,
+            advice.remediation
+        );
+
+        let _ = self.event_tx.send(OrderEvent::OrderRejected {
+            order_id,
+            reason: message.to_string(),
+            rejection,
+            advice: advice.clone(),
+        });
+
+        Ok(advice)
+    }
+
+    /// Record the cumulative fill of an order
+    ///
+    /// Emits [`OrderEvent::OrderPartiallyFilled`] or
+    /// [`OrderEvent::OrderFilled`], and shrinks the order's balance
+    /// reservation to its unfilled remainder.
+    pub fn record_fill(&self, order_id: Uuid, filled: Quantity, avg_price: Price) -> Result<()> {
+        let event = {
+            let mut orders = self.orders.write();
+            let managed = orders
+                .get_mut(&order_id)
+                .ok_or_else(|| Error::OrderNotFound(order_id.to_string()))?;
+
+            let quantity = managed.order.quantity;
+            let (state, event) = if filled >= quantity {
+                (
+                    OrderState::Filled,
+                    OrderEvent::OrderFilled {
+                        order_id,
+                        avg_price,
+                    },
+                )
+            } else {
+                (
+                    OrderState::PartiallyFilled,
+                    OrderEvent::OrderPartiallyFilled {
+                        order_id,
+                        filled_qty: filled,
+                    },
+                )
+            };
+            managed.state_machine.transition(state, "Fill received")?;
+            managed.order.update_fill(filled, avg_price);
+
+            if let Some(reservations) = self.checks.reservations() {
+                reservations.fill(order_id, quantity.as_decimal(), filled.as_decimal());
+            }
+            event
+        };
+
+        let _ = self.event_tx.send(event);
+        Ok(())
+    }
+
+    /// Update the limits used to size remediation for a symbol
+    pub fn set_order_constraints(&self, symbol: Symbol, constraints: OrderConstraints) {
+        self.constraints.write().insert(symbol, constraints);
+    }
+
     /// Get order status
     pub fn get_order(&self, order_id: Uuid) -> Option<(Order, OrderState)> {
         let orders = self.orders.read();
@@ -275,9 +868,105 @@ impl OrderManager {
             .collect()
     }
 
+    /// Cancel every cancellable order on `symbol`, returning the cancelled IDs
+    pub async fn cancel_orders_for_symbol(&self, symbol: &Symbol) -> Vec<Uuid> {
+        let order_ids: Vec<Uuid> = self
+            .orders
+            .read()
+            .values()
+            .filter(|m| &m.order.symbol == symbol && m.state_machine.current_state.can_cancel())
+            .map(|m| m.order.id)
+            .collect();
+
+        let mut cancelled = Vec::with_capacity(order_ids.len());
+        for order_id in order_ids {
+            match self.cancel_order(order_id).await {
+                Ok(()) => cancelled.push(order_id),
+                Err(e) => warn!("Failed to cancel order {}: {}", order_id, e),
+            }
+        }
+        cancelled
+    }
+
+    /// Cancel every cancellable order of a strategy, returning the cancelled IDs
+    pub async fn cancel_orders_for_strategy(&self, strategy_id: Uuid) -> Vec<Uuid> {
+        let order_ids: Vec<Uuid> = self
+            .orders
+            .read()
+            .values()
+            .filter(|m| {
+                m.order.strategy_id == strategy_id && m.state_machine.current_state.can_cancel()
+            })
+            .map(|m| m.order.id)
+            .collect();
+
+        let mut cancelled = Vec::with_capacity(order_ids.len());
+        for order_id in order_ids {
+            match self.cancel_order(order_id).await {
+                Ok(()) => cancelled.push(order_id),
+                Err(e) => warn!("Failed to cancel order {}: {}", order_id, e),
+            }
+        }
+        cancelled
+    }
+
+    /// Internal order an orders-channel update or fill belongs to
+    ///
+    /// Orders are matched by exchange ID, then client order ID. An unknown
+    /// order whose client order ID encodes a strategy is adopted with the
+    /// state the update reports; anything else is reported as unattributed
+    /// and `None` is returned.
+    pub fn resolve_exchange_order(&self, update: &OrderData) -> Result<Option<Uuid>> {
+        if let Some(order_id) = self.exchange_id_map.read().get(&update.ord_id) {
+            return Ok(Some(*order_id));
+        }
+
+        let known = self
+            .orders
+            .read()
+            .values()
+            .find(|m| !update.cl_ord_id.is_empty() && m.order.client_order_id == update.cl_ord_id)
+            .map(|m| m.order.id);
+        if let Some(order_id) = known {
+            self.exchange_id_map
+                .write()
+                .insert(update.ord_id.clone(), order_id);
+            return Ok(Some(order_id));
+        }
+
+        let Some(attribution) = OrderAttribution::parse(&update.cl_ord_id, update.tag.as_deref())
+        else {
+            warn!(
+                "Exchange order {} ({:?}) has no strategy attribution",
+                update.ord_id, update.cl_ord_id
+            );
+            let _ = self.event_tx.send(OrderEvent::OrderUnattributed {
+                exchange_id: update.ord_id.clone(),
+                client_order_id: update.cl_ord_id.clone(),
+            });
+            return Ok(None);
+        };
+
+        let managed = adopt_order(update, &attribution)?;
+        let order_id = managed.order.id;
+        info!(
+            "Adopted exchange order {} for strategy {}",
+            update.ord_id, attribution.strategy_id
+        );
+        self.orders.write().insert(order_id, managed);
+        self.exchange_id_map
+            .write()
+            .insert(update.ord_id.clone(), order_id);
+        let _ = self.event_tx.send(OrderEvent::OrderAdopted {
+            order_id,
+            strategy_id: attribution.strategy_id,
+            exchange_id: update.ord_id.clone(),
+        });
+        Ok(Some(order_id))
+    }
+
     /// Start reconciliation loop
     pub async fn start_reconciliation(&self) {
-        let interval = Duration::seconds(self.config.reconciliation_interval_secs as i64);
         let mut ticker = tokio::time::interval(tokio::time::Duration::from_secs(
             self.config.reconciliation_interval_secs,
         ));
@@ -295,6 +984,9 @@ impl OrderManager {
     async fn reconcile(&self) -> Result<()> {
         debug!("Starting order reconciliation");
 
+        // Catch expiries the per-order timers have not handled yet
+        self.expire_due(Utc::now()).await;
+
         let active_orders: Vec<Uuid> = {
             let orders = self.orders.read();
             orders
@@ -305,30 +997,55 @@ impl OrderManager {
         };
 
         for order_id in active_orders {
-            // Check order timeout
-            let should_timeout = {
+            // Acknowledged orders rest until filled, cancelled or expired;
+            // only orders the exchange never confirmed time out
+            let timed_out = {
                 let orders = self.orders.read();
-                if let Some(managed) = orders.get(&order_id) {
-                    let time_in_state = managed.state_machine.time_in_state();
-                    time_in_state.num_seconds() > self.config.order_timeout_secs as i64
-                } else {
-                    false
-                }
+                orders
+                    .get(&order_id)
+                    .filter(|m| {
+                        !matches!(
+                            m.state_machine.current_state,
+                            OrderState::Acknowledged | OrderState::PartiallyFilled
+                        ) && m.state_machine.time_in_state().num_seconds()
+                            > self.config.order_timeout_secs as i64
+                    })
+                    .map(|m| {
+                        (
+                            m.order.symbol.clone(),
+                            m.order.client_order_id.clone(),
+                            m.is_resting() && !m.dry_run,
+                        )
+                    })
+            };
+            let Some((symbol, client_order_id, sent)) = timed_out else {
+                continue;
             };
 
-            if should_timeout {
-                warn!("Order {} timed out", order_id);
+            warn!("Order {} timed out waiting for acknowledgement", order_id);
+
+            // A request that may have reached the exchange keeps its
+            // reservation until the exchange confirms the cancel
+            if sent
+                && let Err(e) = self
+                    .exchange
+                    .cancel_order(&symbol, &client_order_id)
+                    .await
+            {
+                warn!("Failed to cancel timed-out order {}: {}", order_id, e);
+                continue;
+            }
+
+            {
                 let mut orders = self.orders.write();
                 if let Some(managed) = orders.get_mut(&order_id) {
                     let _ = managed
                         .state_machine
                         .transition(OrderState::Expired, "Timeout");
                 }
-                let _ = self.event_tx.send(OrderEvent::OrderExpired(order_id));
             }
-
-            // Fetch order status from exchange
-            // (Would query actual OKX API here)
+            self.release_reservation(order_id);
+            let _ = self.event_tx.send(OrderEvent::OrderExpired(order_id));
         }
 
         debug!("Reconciliation completed");
@@ -344,8 +1061,10 @@ impl OrderManager {
     pub fn get_stats(&self) -> OrderManagerStats {
         let orders = self.orders.read();
 
-        let mut stats = OrderManagerStats::default();
-        stats.total_orders = orders.len();
+        let mut stats = OrderManagerStats {
+            total_orders: orders.len(),
+            ..Default::default()
+        };
 
         for managed in orders.values() {
             match managed.state_machine.current_state {
@@ -361,6 +1080,77 @@ impl OrderManager {
     }
 }
 
+/// Rebuild a managed order from an exchange update
+fn adopt_order(update: &OrderData, attribution: &OrderAttribution) -> Result<ManagedOrder> {
+    let parse = |field: &str, value: &str| -> Result<Option<Decimal>> {
+        match value {
+            "" => Ok(None),
+            value => Decimal::from_str(value).map(Some).map_err(|e| {
+                Error::ReconciliationError(format!("Invalid {} '{}': {}", field, value, e))
+            }),
+        }
+    };
+
+    let mut order = Order::new(
+        attribution.strategy_id,
+        Symbol::new(&update.inst_id)?,
+        OrderSide::from_str(&update.side)?,
+        OrderType::from_str(&update.ord_type).unwrap_or(OrderType::Limit),
+        Quantity::new(parse("size", &update.sz)?.unwrap_or_default())?,
+        parse("price", &update.px)?.map(Price::new).transpose()?,
+    );
+    order.client_order_id = update.cl_ord_id.clone();
+    order.tag = update.tag.clone().filter(|t| !t.is_empty());
+    order.mark_submitted(update.ord_id.clone());
+
+    let mut state_machine = OrderStateMachine::new(order.id);
+    for state in [
+        OrderState::Validated,
+        OrderState::Submitted,
+        OrderState::Acknowledged,
+    ] {
+        state_machine.transition(state, "Adopted from exchange")?;
+    }
+
+    let filled = parse("filled size", &update.acc_fill_sz)?.unwrap_or_default();
+    if let Some(avg_price) = parse("average price", &update.avg_px)?
+        && !filled.is_zero()
+    {
+        order.update_fill(Quantity::new(filled)?, Price::new(avg_price)?);
+    }
+    let state = match update.state.as_str() {
+        "partially_filled" => Some(OrderState::PartiallyFilled),
+        "filled" => Some(OrderState::Filled),
+        "canceled" | "mmp_canceled" => {
+            order.set_status(OrderStatus::Cancelled);
+            Some(OrderState::Cancelled)
+        }
+        _ => None,
+    };
+    if let Some(state) = state {
+        state_machine.transition(state, format!("Exchange reports {}", update.state))?;
+    }
+
+    Ok(ManagedOrder {
+        order,
+        state_machine,
+        retry_count: 0,
+        dry_run: false,
+    })
+}
+
+/// Exchange refusal behind a failed submission, with its message
+///
+/// Injected faults arrive as client errors, exchange answers through the
+/// adapter as core errors; anything else failed before reaching the exchange.
+fn rejection_of(error: &Error) -> Option<(RejectionReason, String)> {
+    match error {
+        Error::ClientError(e) => Some((RejectionReason::from_error(e), e.to_string())),
+        Error::CoreError(e) => RejectionReason::from_exchange_error(e).map(|r| (r, e.to_string())),
+        _ => None,
+    }
+}
+
 /// Order manager statistics
 #[derive(Debug, Default, Clone)]
 pub struct OrderManagerStats {
@@ -371,3 +1161,683 @@ pub struct OrderManagerStats {
     pub rejected_orders: usize,
     pub failed_orders: usize,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::retry_advisor::Remediation;
+    use ea_okx_core::InstrumentKind;
+    use ea_okx_core::exchange::{ExchangeOrder, InstrumentInfo, MarketEvent, MarketSubscription};
+    use ea_okx_core::models::{OrderSide, OrderType};
+    use rust_decimal_macros::dec;
+
+    /// Exchange recording every order call, accepting all but scripted rejects
+    #[derive(Default)]
+    struct RecordingExchange {
+        calls: parking_lot::Mutex<Vec<String>>,
+
+        /// Code the next placement is refused with
+        reject_next: parking_lot::Mutex<Option<&'static str>>,
+    }
+
+    impl RecordingExchange {
+        fn calls(&self) -> Vec<String> {
+            self.calls.lock().clone()
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ExchangeAdapter for RecordingExchange {
+        fn name(&self) -> &'static str {
+            "recording"
+        }
+
+        async fn place_order(&self, order: &Order) -> ea_okx_core::Result<String> {
+            self.calls
+                .lock()
+                .push(format!("place {}", order.client_order_id));
+            match self.reject_next.lock().take() {
+                Some(code) => Err(ea_okx_core::Error::ExchangeRejected {
+                    code: code.to_string(),
+                    message: "Order refused".to_string(),
+                }),
+                None => Ok(format!("EX-{}", order.client_order_id)),
+            }
+        }
+
+        async fn cancel_order(
+            &self,
+            _symbol: &Symbol,
+            client_order_id: &str,
+        ) -> ea_okx_core::Result<()> {
+            self.calls
+                .lock()
+                .push(format!("cancel {}", client_order_id));
+            Ok(())
+        }
+
+        async fn amend_order(
+            &self,
+            _symbol: &Symbol,
+            client_order_id: &str,
+            quantity: Option<Decimal>,
+            price: Option<Decimal>,
+        ) -> ea_okx_core::Result<()> {
+            self.calls.lock().push(format!(
+                "amend {} {:?} {:?}",
+                client_order_id, quantity, price
+            ));
+            Ok(())
+        }
+
+        async fn order(
+            &self,
+            _symbol: &Symbol,
+            _client_order_id: &str,
+        ) -> ea_okx_core::Result<Option<ExchangeOrder>> {
+            Ok(None)
+        }
+
+        async fn instruments(
+            &self,
+            _kind: InstrumentKind,
+        ) -> ea_okx_core::Result<Vec<InstrumentInfo>> {
+            Ok(Vec::new())
+        }
+
+        async fn subscribe(
+            &self,
+            _subscriptions: &[MarketSubscription],
+        ) -> ea_okx_core::Result<()> {
+            Ok(())
+        }
+
+        async fn next_event(&self) -> ea_okx_core::Result<Option<MarketEvent>> {
+            Ok(None)
+        }
+
+        async fn disconnect(&self) -> ea_okx_core::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn manager_on(exchange: Arc<RecordingExchange>) -> OrderManager {
+        OrderManager::new(OrderManagerConfig::default(), exchange)
+    }
+
+    fn manager() -> OrderManager {
+        manager_on(Arc::new(RecordingExchange::default()))
+    }
+
+    fn limit_buy(quantity: Decimal, price: Decimal) -> Order {
+        Order::new(
+            Uuid::new_v4(),
+            Symbol::new("BTC-USDT").unwrap(),
+            OrderSide::Buy,
+            OrderType::Limit,
+            Quantity::new(quantity).unwrap(),
+            Some(Price::new(price).unwrap()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_orders_are_placed_amended_and_cancelled_on_the_exchange() {
+        let exchange = Arc::new(RecordingExchange::default());
+        let manager = manager_on(exchange.clone());
+        let mut events = manager.subscribe_events().unwrap();
+        let order = limit_buy(dec!(0.1), dec!(40000));
+        let client_order_id = order.client_order_id.clone();
+
+        let order_id = manager.submit_order(order).await.unwrap();
+        let exchange_id = loop {
+            if let OrderEvent::OrderAcknowledged { exchange_id, .. } = events.recv().await.unwrap()
+            {
+                break exchange_id;
+            }
+        };
+        assert_eq!(exchange_id, format!("EX-{}", client_order_id));
+        let (order, _) = manager.get_order(order_id).unwrap();
+        assert_eq!(order.okx_order_id, Some(exchange_id));
+
+        // Amending to no more than has filled is refused locally
+        manager
+            .record_fill(
+                order_id,
+                Quantity::new(dec!(0.05)).unwrap(),
+                Price::new(dec!(40000)).unwrap(),
+            )
+            .unwrap();
+        assert!(
+            manager
+                .amend_order(order_id, Some(Quantity::new(dec!(0.05)).unwrap()), None)
+                .await
+                .is_err()
+        );
+
+        manager
+            .amend_order(
+                order_id,
+                Some(Quantity::new(dec!(0.2)).unwrap()),
+                Some(Price::new(dec!(39000)).unwrap()),
+            )
+            .await
+            .unwrap();
+        let (order, _) = manager.get_order(order_id).unwrap();
+        assert_eq!(order.quantity.as_decimal(), dec!(0.2));
+        assert_eq!(order.price.unwrap().as_decimal(), dec!(39000));
+
+        manager.cancel_order(order_id).await.unwrap();
+        assert_eq!(
+            exchange.calls(),
+            [
+                format!("place {}", client_order_id),
+                format!("amend {} Some(0.2) Some(39000)", client_order_id),
+                format!("cancel {}", client_order_id),
+            ]
+        );
+
+        // Nothing left to amend once cancelled
+        assert!(
+            manager
+                .amend_order(order_id, None, Some(Price::new(dec!(38000)).unwrap()))
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_exchange_refusal_is_classified() {
+        let exchange = Arc::new(RecordingExchange::default());
+        *exchange.reject_next.lock() = Some("51008");
+        let manager = manager_on(exchange);
+        let mut events = manager.subscribe_events().unwrap();
+
+        let order_id = manager
+            .submit_order(limit_buy(dec!(1), dec!(40000)))
+            .await
+            .unwrap();
+        let rejection = loop {
+            if let OrderEvent::OrderRejected { rejection, .. } = events.recv().await.unwrap() {
+                break rejection;
+            }
+        };
+        assert_eq!(rejection, RejectionReason::InsufficientBalance);
+        assert_eq!(manager.get_order(order_id).unwrap().1, OrderState::Rejected);
+    }
+
+    #[tokio::test]
+    async fn test_rejection_event_carries_reason_and_remediation() {
+        let manager = manager();
+        let mut events = manager.subscribe_events().unwrap();
+        let symbol = Symbol::new("BTC-USDT").unwrap();
+        manager.set_order_constraints(
+            symbol.clone(),
+            OrderConstraints {
+                lot_size: Some(dec!(0.001)),
+                max_available: Some(dec!(0.25)),
+                ..Default::default()
+            },
+        );
+
+        let order = Order::new(
+            Uuid::new_v4(),
+            symbol,
+            OrderSide::Buy,
+            OrderType::Limit,
+            Quantity::new(dec!(1)).unwrap(),
+            Some(Price::new(dec!(42000)).unwrap()),
+        );
+        let order_id = manager.submit_order(order).await.unwrap();
+
+        let advice = manager
+            .handle_rejection(
+                order_id,
+                RejectionReason::from_code("51008", "Insufficient USDT balance"),
+                "Insufficient USDT balance",
+            )
+            .unwrap();
+        assert!(advice.retryable);
+
+        let (order, state) = manager.get_order(order_id).unwrap();
+        assert_eq!(state, OrderState::Rejected);
+        assert_eq!(order.status, OrderStatus::Rejected);
+
+        let mut rejected = None;
+        while let Ok(event) = events.try_recv() {
+            if let OrderEvent::OrderRejected {
+                rejection, advice, ..
+            } = event
+            {
+                rejected = Some((rejection, advice));
+            }
+        }
+        let (rejection, advice) = rejected.expect("rejection event");
+        assert_eq!(rejection, RejectionReason::InsufficientBalance);
+        assert_eq!(
+            advice.remediation,
+            Remediation::ReduceSize {
+                max_quantity: Some(dec!(0.25))
+            }
+        );
+        assert_eq!(manager.get_stats().rejected_orders, 1);
+    }
+
+    #[tokio::test]
+    async fn test_gate_blocks_and_dry_runs_submissions() {
+        let gate = Arc::new(ExecutionGate::new());
+        let exchange = Arc::new(RecordingExchange::default());
+        let manager = manager_on(exchange.clone()).with_gate(gate.clone());
+        let strategy_id = Uuid::new_v4();
+        let order = || {
+            Order::new(
+                strategy_id,
+                Symbol::new("BTC-USDT").unwrap(),
+                OrderSide::Buy,
+                OrderType::Market,
+                Quantity::new(dec!(0.1)).unwrap(),
+                None,
+            )
+        };
+
+        gate.set_trading_enabled(false);
+        assert!(matches!(
+            manager.submit_order(order()).await,
+            Err(Error::TradingDisabled(_))
+        ));
+        assert_eq!(manager.get_stats().total_orders, 0);
+
+        gate.set_strategy_dry_run(strategy_id, true);
+        let mut events = manager.subscribe_events().unwrap();
+        let order_id = manager.submit_order(order()).await.unwrap();
+
+        let exchange_id = loop {
+            match events.recv().await.unwrap() {
+                OrderEvent::OrderAcknowledged { exchange_id, .. } => break exchange_id,
+                _ => continue,
+            }
+        };
+        assert_eq!(exchange_id, format!("DRYRUN-{}", order_id));
+
+        // Dry-run orders never reach the exchange
+        manager.cancel_order(order_id).await.unwrap();
+        assert!(exchange.calls().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_resting_orders_expire_at_their_good_till_date() {
+        let manager = manager();
+        let mut events = manager.subscribe_events().unwrap();
+        let limit = |expires_at| {
+            Order::new(
+                Uuid::new_v4(),
+                Symbol::new("BTC-USDT").unwrap(),
+                OrderSide::Buy,
+                OrderType::Limit,
+                Quantity::new(dec!(0.1)).unwrap(),
+                Some(Price::new(dec!(40000)).unwrap()),
+            )
+            .with_expiry(expires_at)
+        };
+
+        assert!(matches!(
+            manager
+                .submit_order(limit(Utc::now() - chrono::Duration::seconds(1)))
+                .await,
+            Err(Error::ExecutionError(_))
+        ));
+
+        let order_id = manager
+            .submit_order(limit(Utc::now() + chrono::Duration::milliseconds(200)))
+            .await
+            .unwrap();
+        loop {
+            if let OrderEvent::OrderExpired(id) = events.recv().await.unwrap() {
+                assert_eq!(id, order_id);
+                break;
+            }
+        }
+        let (order, state) = manager.get_order(order_id).unwrap();
+        assert_eq!(state, OrderState::Expired);
+        assert_eq!(order.status, OrderStatus::Cancelled);
+
+        // Already expired: nothing left to do
+        assert!(!manager.expire_order(order_id).await.unwrap());
+        assert!(manager.expire_due(Utc::now()).await.is_empty());
+    }
+
+    struct FixedBalance(Decimal);
+
+    impl crate::reservations::BalanceSource for FixedBalance {
+        fn cash_balance(&self, _ccy: &str) -> Option<Decimal> {
+            Some(self.0)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reservations_released_on_fill_and_cancel() {
+        let reservations = Arc::new(BalanceReservations::new(Arc::new(FixedBalance(dec!(1000)))));
+        let manager = manager().with_balance_reservations(reservations.clone());
+        let mut events = manager.subscribe_events().unwrap();
+        let buy = || {
+            Order::new(
+                Uuid::new_v4(),
+                Symbol::new("BTC-USDT").unwrap(),
+                OrderSide::Buy,
+                OrderType::Limit,
+                Quantity::new(dec!(0.01)).unwrap(),
+                Some(Price::new(dec!(60000)).unwrap()),
+            )
+        };
+
+        let first = manager.submit_order(buy()).await.unwrap();
+        assert!(matches!(
+            manager.submit_order(buy()).await,
+            Err(Error::InsufficientBalance { available, .. }) if available == dec!(400)
+        ));
+        loop {
+            if let OrderEvent::OrderAcknowledged { order_id, .. } = events.recv().await.unwrap() {
+                assert_eq!(order_id, first);
+                break;
+            }
+        }
+
+        manager
+            .record_fill(
+                first,
+                Quantity::new(dec!(0.004)).unwrap(),
+                Price::new(dec!(60000)).unwrap(),
+            )
+            .unwrap();
+        assert_eq!(reservations.reserved("USDT"), dec!(360));
+        let (order, state) = manager.get_order(first).unwrap();
+        assert_eq!(state, OrderState::PartiallyFilled);
+        assert_eq!(order.status, OrderStatus::Partial);
+
+        manager.cancel_order(first).await.unwrap();
+        assert_eq!(reservations.reserved("USDT"), Decimal::ZERO);
+        assert!(manager.submit_order(buy()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_resting_orders_keep_their_reservation_across_reconcile() {
+        let reservations = Arc::new(BalanceReservations::new(Arc::new(FixedBalance(dec!(1000)))));
+        let exchange = Arc::new(RecordingExchange::default());
+        let config = OrderManagerConfig {
+            order_timeout_secs: 0,
+            ..Default::default()
+        };
+        let manager = OrderManager::new(config, exchange.clone())
+            .with_balance_reservations(reservations.clone());
+        let mut events = manager.subscribe_events().unwrap();
+
+        let order_id = manager
+            .submit_order(limit_buy(dec!(0.01), dec!(60000)))
+            .await
+            .unwrap();
+        loop {
+            if let OrderEvent::OrderAcknowledged { .. } = events.recv().await.unwrap() {
+                break;
+            }
+        }
+
+        // Long past the timeout, but the order rests on the exchange
+        tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+        manager.reconcile().await.unwrap();
+
+        let (_, state) = manager.get_order(order_id).unwrap();
+        assert_eq!(state, OrderState::Acknowledged);
+        assert_eq!(reservations.reserved("USDT"), dec!(600));
+        assert!(exchange.calls().iter().all(|c| !c.starts_with("cancel")));
+    }
+
+    #[tokio::test]
+    async fn test_reservation_released_when_exchange_rejects() {
+        let reservations = Arc::new(BalanceReservations::new(Arc::new(FixedBalance(dec!(1000)))));
+        let exchange = Arc::new(RecordingExchange::default());
+        *exchange.reject_next.lock() = Some("51008");
+        let manager = manager_on(exchange).with_balance_reservations(reservations.clone());
+        let mut events = manager.subscribe_events().unwrap();
+
+        let order_id = manager
+            .submit_order(limit_buy(dec!(0.01), dec!(60000)))
+            .await
+            .unwrap();
+        loop {
+            if let OrderEvent::OrderRejected { order_id: id, .. } = events.recv().await.unwrap() {
+                assert_eq!(id, order_id);
+                break;
+            }
+        }
+        assert_eq!(reservations.reserved("USDT"), Decimal::ZERO);
+        assert!(reservations.reservations().is_empty());
+    }
+
+    struct CappedBuys;
+
+    #[async_trait::async_trait]
+    impl crate::size_limits::SizeLimitSource for CappedBuys {
+        async fn fetch_size_limits(
+            &self,
+            _symbol: &Symbol,
+            _mode: crate::size_limits::TradeMode,
+        ) -> Result<crate::size_limits::SizeLimits> {
+            Ok(crate::size_limits::SizeLimits {
+                max_buy: Some(dec!(1)),
+                max_sell: None,
+                avail_buy: Some(dec!(0.256)),
+                avail_sell: None,
+                fetched_at: Utc::now(),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_intent_is_logged_before_sending_and_resolved_on_ack() {
+        let log = Arc::new(crate::intent_log::InMemoryIntentLog::new());
+        let manager = manager().with_intent_log(log.clone());
+        let mut events = manager.subscribe_events().unwrap();
+        let order = Order::new(
+            Uuid::new_v4(),
+            Symbol::new("BTC-USDT").unwrap(),
+            OrderSide::Buy,
+            OrderType::Market,
+            Quantity::new(dec!(0.1)).unwrap(),
+            None,
+        );
+        let client_order_id = order.client_order_id.clone();
+
+        manager.submit_order(order).await.unwrap();
+        assert_eq!(
+            log.load(&client_order_id).unwrap().unwrap().status,
+            IntentStatus::Pending
+        );
+
+        while !matches!(
+            events.recv().await.unwrap(),
+            OrderEvent::OrderAcknowledged { .. }
+        ) {}
+        for _ in 0..50 {
+            if log.unresolved().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+        }
+        let intent = log.load(&client_order_id).unwrap().unwrap();
+        assert_eq!(intent.status, IntentStatus::Acknowledged);
+        assert!(intent.exchange_id.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_size_guard_clamps_before_submission() {
+        let guard = Arc::new(SizeLimitGuard::new(
+            Default::default(),
+            Arc::new(CappedBuys),
+        ));
+        let manager = manager().with_size_guard(guard);
+        let symbol = Symbol::new("BTC-USDT").unwrap();
+        manager.set_order_constraints(
+            symbol.clone(),
+            OrderConstraints {
+                lot_size: Some(dec!(0.01)),
+                ..Default::default()
+            },
+        );
+        let mut events = manager.subscribe_events().unwrap();
+
+        let order = Order::new(
+            Uuid::new_v4(),
+            symbol,
+            OrderSide::Buy,
+            OrderType::Market,
+            Quantity::new(dec!(0.5)).unwrap(),
+            None,
+        );
+        let order_id = manager.submit_order(order).await.unwrap();
+
+        let (order, _) = manager.get_order(order_id).unwrap();
+        assert_eq!(order.quantity.as_decimal(), dec!(0.25));
+        let clamped = loop {
+            if let OrderEvent::OrderSizeClamped {
+                requested, allowed, ..
+            } = events.recv().await.unwrap()
+            {
+                break (requested, allowed);
+            }
+        };
+        assert_eq!(clamped, (dec!(0.5), dec!(0.25)));
+    }
+
+    struct LongPosition(Decimal);
+
+    #[async_trait::async_trait]
+    impl crate::reduce_only::PositionSource for LongPosition {
+        async fn net_position(&self, _symbol: &Symbol) -> Result<Decimal> {
+            Ok(self.0)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reduce_only_guard_clamps_and_rejects() {
+        let guard = Arc::new(ReduceOnlyGuard::new(Arc::new(LongPosition(dec!(0.3)))));
+        let manager = manager().with_reduce_only_guard(guard);
+        let mut events = manager.subscribe_events().unwrap();
+        let close = |side| {
+            Order::new(
+                Uuid::new_v4(),
+                Symbol::new("BTC-USDT-SWAP").unwrap(),
+                side,
+                OrderType::Market,
+                Quantity::new(dec!(1)).unwrap(),
+                None,
+            )
+            .with_reduce_only()
+        };
+
+        let order_id = manager.submit_order(close(OrderSide::Sell)).await.unwrap();
+        let (order, _) = manager.get_order(order_id).unwrap();
+        assert_eq!(order.quantity.as_decimal(), dec!(0.3));
+        let clamped = loop {
+            if let OrderEvent::OrderReduceOnlyClamped {
+                requested, allowed, ..
+            } = events.recv().await.unwrap()
+            {
+                break (requested, allowed);
+            }
+        };
+        assert_eq!(clamped, (dec!(1), dec!(0.3)));
+
+        assert!(matches!(
+            manager.submit_order(close(OrderSide::Buy)).await,
+            Err(Error::ReduceOnlyRejected(_))
+        ));
+        assert_eq!(manager.get_stats().total_orders, 1);
+    }
+
+    #[cfg(feature = "fault-injection")]
+    #[tokio::test]
+    async fn test_injected_reject_flows_through_rejection_handling() {
+        use ea_okx_client::{FaultConfig, FaultInjector};
+
+        let faults = Arc::new(FaultInjector::new(FaultConfig {
+            reject_probability: 1.0,
+            ..Default::default()
+        }));
+        let manager = manager().with_fault_injector(faults.clone());
+        let mut events = manager.subscribe_events().unwrap();
+
+        let order = Order::new(
+            Uuid::new_v4(),
+            Symbol::new("BTC-USDT").unwrap(),
+            OrderSide::Buy,
+            OrderType::Market,
+            Quantity::new(dec!(0.1)).unwrap(),
+            None,
+        );
+        let order_id = manager.submit_order(order).await.unwrap();
+
+        let (rejection, advice) = loop {
+            if let OrderEvent::OrderRejected {
+                rejection, advice, ..
+            } = events.recv().await.unwrap()
+            {
+                break (rejection, advice);
+            }
+        };
+        assert_eq!(rejection, RejectionReason::ExchangeUnavailable);
+        assert!(advice.retryable);
+        assert_eq!(manager.get_order(order_id).unwrap().1, OrderState::Rejected);
+        assert_eq!(faults.stats().rejected, 1);
+    }
+
+    fn order_update(cl_ord_id: &str, state: &str) -> OrderData {
+        serde_json::from_value(serde_json::json!({
+            "instType": "SPOT", "instId": "BTC-USDT", "ordId": "312269865356374016",
+            "clOrdId": cl_ord_id, "tag": "", "px": "42000", "sz": "0.5",
+            "ordType": "limit", "side": "sell", "tdMode": "cash", "fillPx": "42000",
+            "fillSz": "0.2", "accFillSz": "0.2", "avgPx": "42000", "state": state,
+            "uTime": "0", "cTime": "0"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_orders_placed_before_restart_are_adopted_by_strategy() {
+        let manager = manager();
+        let mut events = manager.subscribe_events().unwrap();
+        let strategy_id = Uuid::new_v4();
+        let cl_ord_id = ea_okx_core::models::client_order_id(strategy_id, Uuid::new_v4());
+
+        let update = order_update(&cl_ord_id, "partially_filled");
+        let order_id = manager.resolve_exchange_order(&update).unwrap().unwrap();
+        let (order, state) = manager.get_order(order_id).unwrap();
+        assert_eq!(order.strategy_id, strategy_id);
+        assert_eq!(order.side, OrderSide::Sell);
+        assert_eq!(order.filled_quantity.as_decimal(), dec!(0.2));
+        assert_eq!(state, OrderState::PartiallyFilled);
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            OrderEvent::OrderAdopted { strategy_id: s, .. } if s == strategy_id
+        ));
+
+        // Later updates resolve to the adopted order without another event
+        assert_eq!(
+            manager.resolve_exchange_order(&update).unwrap(),
+            Some(order_id)
+        );
+        assert!(events.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_foreign_orders_are_reported_unattributed() {
+        let manager = manager();
+        let mut events = manager.subscribe_events().unwrap();
+
+        let update = order_update("", "live");
+        assert_eq!(manager.resolve_exchange_order(&update).unwrap(), None);
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            OrderEvent::OrderUnattributed { .. }
+        ));
+        assert_eq!(manager.get_stats().total_orders, 0);
+    }
+}
@@ -0,0 +1,465 @@
+//! Checks an order passes on its way to the exchange
+//!
+//! [`OrderManager`](crate::OrderManager) and the strategy execution engine
+//! send orders through the same [`PreTradeChecks`], so whichever path an
+//! order takes the guards run in one order: the execution gate first, then
+//! the fat-finger, reduce-only, size and liquidity guards, and for orders
+//! that will really be sent, the balance reservation and the intent log.
+
+use crate::error::{Error, Result};
+use crate::fat_finger::{FatFingerDecision, FatFingerGuard};
+use crate::gate::{ExecutionGate, GateDecision};
+use crate::intent_log::{IntentLog, OrderIntent};
+use crate::liquidity::LiquidityGuard;
+use crate::reduce_only::{ReduceOnlyDecision, ReduceOnlyGuard, enforce_reduce_only};
+use crate::reservations::{BalanceReservations, Reservation};
+use crate::size_limits::{SizeDecision, SizeLimitGuard};
+use ea_okx_core::Price;
+use ea_okx_core::models::Order;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::warn;
+
+/// One of the pre-trade checks, in the order they run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PreTradeCheck {
+    Gate,
+    FatFinger,
+    ReduceOnly,
+    Size,
+    Liquidity,
+    Balance,
+    IntentLog,
+}
+
+/// What the pre-trade checks decided for one order
+#[derive(Debug, Default)]
+pub struct PreTradeReport {
+    /// Strategy is in dry-run: acknowledge locally, send nothing
+    pub dry_run: bool,
+
+    /// Limit price before the degraded-mode policy widened it
+    pub widened_from: Option<Price>,
+
+    pub fat_finger: Option<FatFingerDecision>,
+
+    /// Set for reduce-only orders
+    pub reduce_only: Option<ReduceOnlyDecision>,
+
+    pub size: Option<SizeDecision>,
+
+    pub liquidity: Option<SizeDecision>,
+
+    pub reservation: Option<Reservation>,
+
+    /// Check that refused the order and why; checks after it did not run
+    pub refused: Option<(PreTradeCheck, Error)>,
+}
+
+/// Guards every order passes before it is sent
+#[derive(Clone)]
+pub struct PreTradeChecks {
+    /// Trading switch and dry-run flags, checked first
+    gate: Arc<ExecutionGate>,
+
+    /// Price, notional and size sanity checks
+    fat_finger: Option<Arc<FatFingerGuard>>,
+
+    /// Open-position checks for reduce-only orders
+    reduce_only: Option<Arc<ReduceOnlyGuard>>,
+
+    /// Exchange size caps
+    size_guard: Option<Arc<SizeLimitGuard>>,
+
+    /// Order book depth caps
+    liquidity: Option<Arc<LiquidityGuard>>,
+
+    /// Balance earmarked by pending orders
+    reservations: Option<Arc<BalanceReservations>>,
+
+    /// Write-ahead log of orders sent but not yet acknowledged
+    intent_log: Option<Arc<dyn IntentLog>>,
+}
+
+impl PreTradeChecks {
+    /// Checks behind a fresh gate, with no guards configured
+    pub fn new() -> Self {
+        Self {
+            gate: Arc::new(ExecutionGate::new()),
+            fat_finger: None,
+            reduce_only: None,
+            size_guard: None,
+            liquidity: None,
+            reservations: None,
+            intent_log: None,
+        }
+    }
+
+    /// Share an execution gate with other order paths
+    pub fn with_gate(mut self, gate: Arc<ExecutionGate>) -> Self {
+        self.gate = gate;
+        self
+    }
+
+    /// Hold or refuse orders that fail the fat-finger sanity checks
+    pub fn with_fat_finger_guard(mut self, guard: Arc<FatFingerGuard>) -> Self {
+        self.fat_finger = Some(guard);
+        self
+    }
+
+    /// Check reduce-only orders against the open position
+    pub fn with_reduce_only_guard(mut self, guard: Arc<ReduceOnlyGuard>) -> Self {
+        self.reduce_only = Some(guard);
+        self
+    }
+
+    /// Clamp or reject orders above the exchange's max available size
+    pub fn with_size_guard(mut self, guard: Arc<SizeLimitGuard>) -> Self {
+        self.size_guard = Some(guard);
+        self
+    }
+
+    /// Clamp or reject orders above their share of the visible book depth
+    pub fn with_liquidity_guard(mut self, guard: Arc<LiquidityGuard>) -> Self {
+        self.liquidity = Some(guard);
+        self
+    }
+
+    /// Reserve balance for each order sent, refusing orders the unreserved
+    /// balance cannot cover
+    pub fn with_balance_reservations(mut self, reservations: Arc<BalanceReservations>) -> Self {
+        self.reservations = Some(reservations);
+        self
+    }
+
+    /// Log each order durably before it is sent
+    pub fn with_intent_log(mut self, log: Arc<dyn IntentLog>) -> Self {
+        self.intent_log = Some(log);
+        self
+    }
+
+    /// Execution gate checked first
+    pub fn gate(&self) -> &Arc<ExecutionGate> {
+        &self.gate
+    }
+
+    /// Fat-finger guard, if configured
+    pub fn fat_finger(&self) -> Option<&Arc<FatFingerGuard>> {
+        self.fat_finger.as_ref()
+    }
+
+    /// Exchange size cap guard, if configured
+    pub fn size_guard(&self) -> Option<&Arc<SizeLimitGuard>> {
+        self.size_guard.as_ref()
+    }
+
+    /// Order book depth guard, if configured
+    pub fn liquidity(&self) -> Option<&Arc<LiquidityGuard>> {
+        self.liquidity.as_ref()
+    }
+
+    /// Balance reservations, if configured
+    pub fn reservations(&self) -> Option<&Arc<BalanceReservations>> {
+        self.reservations.as_ref()
+    }
+
+    /// Intent log, if configured
+    pub fn intent_log(&self) -> Option<&Arc<dyn IntentLog>> {
+        self.intent_log.as_ref()
+    }
+
+    /// Run every check on `order`, resizing or repricing it as they require
+    ///
+    /// The gate's decision counts against the strategy's order quota. A
+    /// reduce-only order is checked against `position` when the caller
+    /// tracks it and against the reduce-only guard otherwise. Orders that
+    /// will be sent have balance reserved and their intent logged last, so
+    /// anything the report does not refuse may go straight to the exchange;
+    /// if the intent cannot be logged the reservation is released again.
+    pub async fn run(
+        &self,
+        order: &mut Order,
+        lot_size: Option<Decimal>,
+        position: Option<Decimal>,
+    ) -> PreTradeReport {
+        let mut report = PreTradeReport::default();
+        let mut at = PreTradeCheck::Gate;
+        if let Err(e) = self
+            .check(order, lot_size, position, true, &mut at, &mut report)
+            .await
+        {
+            report.refused = Some((at, e));
+        }
+        report
+    }
+
+    /// What [`run`](Self::run) would decide for `order`, without using up
+    /// quota, reserving balance or logging an intent
+    pub async fn preview(
+        &self,
+        order: &mut Order,
+        lot_size: Option<Decimal>,
+        position: Option<Decimal>,
+    ) -> PreTradeReport {
+        let mut report = PreTradeReport::default();
+        let mut at = PreTradeCheck::Gate;
+        if let Err(e) = self
+            .check(order, lot_size, position, false, &mut at, &mut report)
+            .await
+        {
+            report.refused = Some((at, e));
+        }
+        report
+    }
+
+    async fn check(
+        &self,
+        order: &mut Order,
+        lot_size: Option<Decimal>,
+        position: Option<Decimal>,
+        send: bool,
+        at: &mut PreTradeCheck,
+        report: &mut PreTradeReport,
+    ) -> Result<()> {
+        *at = PreTradeCheck::Gate;
+        let decision = if send {
+            self.gate.check(order)
+        } else {
+            self.gate.preview(order)
+        };
+        report.dry_run = match decision {
+            GateDecision::Send => false,
+            GateDecision::DryRun => true,
+            GateDecision::Blocked(reason) => return Err(Error::TradingDisabled(reason)),
+            GateDecision::Throttled(reason) => return Err(Error::QuotaExceeded(reason)),
+        };
+
+        if !report.dry_run
+            && let Some(original) = self.gate.degraded().widen_limit(order)
+        {
+            warn!(
+                "Exchange degraded, order {} limit moved from {} to {}",
+                order.id,
+                original.as_decimal(),
+                order.price.map(|p| p.as_decimal()).unwrap_or_default()
+            );
+            report.widened_from = Some(original);
+        }
+
+        *at = PreTradeCheck::FatFinger;
+        if let Some(guard) = &self.fat_finger {
+            let decision = guard.check(order);
+            let refusal = match &decision {
+                FatFingerDecision::Pass => None,
+                FatFingerDecision::ConfirmationRequired { .. } => {
+                    Some(Error::ConfirmationRequired(decision.describe()))
+                }
+                FatFingerDecision::Rejected { .. } => {
+                    Some(Error::FatFingerRejected(decision.describe()))
+                }
+            };
+            report.fat_finger = Some(decision);
+            if let Some(e) = refusal {
+                return Err(e);
+            }
+        }
+
+        *at = PreTradeCheck::ReduceOnly;
+        if order.reduce_only {
+            let decision = match (position, &self.reduce_only) {
+                (Some(position), _) => Some(enforce_reduce_only(order, position)?),
+                (None, Some(guard)) => Some(guard.apply(order).await?),
+                (None, None) => None,
+            };
+            if let Some(decision) = decision {
+                if let ReduceOnlyDecision::Clamped { requested, allowed } = &decision {
+                    warn!(
+                        "Reduce-only order {} clamped from {} to open size {}",
+                        order.id, requested, allowed
+                    );
+                }
+                let rejected = decision.is_rejected().then(|| decision.describe());
+                report.reduce_only = Some(decision);
+                if let Some(reason) = rejected {
+                    return Err(Error::ReduceOnlyRejected(reason));
+                }
+            }
+        }
+
+        *at = PreTradeCheck::Size;
+        if let Some(guard) = &self.size_guard {
+            let decision = guard.apply(order, lot_size).await?;
+            report.size = Some(decision.clone());
+            match decision {
+                SizeDecision::Within { .. } => {}
+                SizeDecision::Clamped { requested, allowed } => {
+                    warn!(
+                        "Order {} clamped from {} to max available {}",
+                        order.id, requested, allowed
+                    );
+                }
+                SizeDecision::Rejected { requested, allowed } => {
+                    return Err(Error::SizeLimitExceeded { requested, allowed });
+                }
+            }
+        }
+
+        *at = PreTradeCheck::Liquidity;
+        if let Some(guard) = &self.liquidity {
+            let decision = guard.apply(order, lot_size)?;
+            report.liquidity = Some(decision.clone());
+            match decision {
+                SizeDecision::Within { .. } => {}
+                SizeDecision::Clamped { requested, allowed } => {
+                    warn!(
+                        "Order {} clamped from {} to {} by visible liquidity",
+                        order.id, requested, allowed
+                    );
+                }
+                SizeDecision::Rejected { requested, allowed } => {
+                    return Err(Error::LiquidityExceeded { requested, allowed });
+                }
+            }
+        }
+
+        // Dry runs never touch the exchange balance
+        if report.dry_run {
+            return Ok(());
+        }
+        *at = PreTradeCheck::Balance;
+        if let Some(reservations) = &self.reservations {
+            if send {
+                report.reservation = reservations.reserve(order)?;
+            } else if let Some(reservation) = BalanceReservations::reservation_for(order)
+                && let Some(available) = reservations.unreserved(&reservation.ccy)
+            {
+                let requested = reservation.amount();
+                let ccy = reservation.ccy.clone();
+                report.reservation = Some(reservation);
+                if requested > available {
+                    return Err(Error::InsufficientBalance {
+                        ccy,
+                        requested,
+                        available,
+                    });
+                }
+            }
+        }
+        if !send {
+            return Ok(());
+        }
+
+        *at = PreTradeCheck::IntentLog;
+        // Nothing is sent that a restart could not account for
+        if let Some(log) = &self.intent_log
+            && let Err(e) = log.record(&OrderIntent::new(order))
+        {
+            if let Some(reservations) = &self.reservations {
+                reservations.release(order.id);
+            }
+            return Err(e);
+        }
+        Ok(())
+    }
+}
+
+impl Default for PreTradeChecks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fat_finger::{BreachAction, FatFingerConfig, FatFingerLimits};
+    use crate::intent_log::InMemoryIntentLog;
+    use crate::reservations::BalanceSource;
+    use ea_okx_core::models::{OrderSide, OrderType};
+    use ea_okx_core::{Quantity, Symbol};
+    use rust_decimal_macros::dec;
+    use uuid::Uuid;
+
+    struct Usdt(Decimal);
+
+    impl BalanceSource for Usdt {
+        fn cash_balance(&self, ccy: &str) -> Option<Decimal> {
+            (ccy == "USDT").then_some(self.0)
+        }
+    }
+
+    fn buy(quantity: Decimal, price: Decimal) -> Order {
+        Order::new(
+            Uuid::new_v4(),
+            Symbol::new("BTC-USDT").unwrap(),
+            OrderSide::Buy,
+            OrderType::Limit,
+            Quantity::new(quantity).unwrap(),
+            Some(Price::new(price).unwrap()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_gate_runs_before_the_guards() {
+        let guard = FatFingerGuard::new(FatFingerConfig {
+            default_limits: FatFingerLimits {
+                max_price_deviation: None,
+                max_notional: Some(dec!(100)),
+                max_size_multiple: None,
+                action: BreachAction::Reject,
+            },
+            ..FatFingerConfig::default()
+        });
+        let checks = PreTradeChecks::new().with_fat_finger_guard(Arc::new(guard));
+        let mut order = buy(dec!(1), dec!(50000));
+
+        checks.gate().set_trading_enabled(false);
+        let report = checks.run(&mut order, None, None).await;
+        assert!(matches!(
+            report.refused,
+            Some((PreTradeCheck::Gate, Error::TradingDisabled(_)))
+        ));
+        assert!(report.fat_finger.is_none());
+
+        checks.gate().set_trading_enabled(true);
+        let report = checks.run(&mut order, None, None).await;
+        assert!(matches!(
+            report.refused,
+            Some((PreTradeCheck::FatFinger, Error::FatFingerRejected(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_preview_reserves_and_logs_nothing() {
+        let reservations = Arc::new(BalanceReservations::new(Arc::new(Usdt(dec!(1000)))));
+        let log = Arc::new(InMemoryIntentLog::new());
+        let checks = PreTradeChecks::new()
+            .with_balance_reservations(reservations.clone())
+            .with_intent_log(log.clone());
+        let mut order = buy(dec!(0.01), dec!(50000));
+
+        let report = checks.preview(&mut order, None, None).await;
+        assert!(report.refused.is_none());
+        assert_eq!(report.reservation.map(|r| r.amount()), Some(dec!(500)));
+        assert_eq!(reservations.reserved("USDT"), Decimal::ZERO);
+        assert!(log.load_all().unwrap().is_empty());
+
+        let report = checks.run(&mut order, None, None).await;
+        assert!(report.refused.is_none());
+        assert_eq!(reservations.reserved("USDT"), dec!(500));
+        assert_eq!(log.load_all().unwrap().len(), 1);
+
+        // A second order the unreserved balance cannot cover is neither
+        // reserved nor logged
+        let mut order = buy(dec!(0.02), dec!(50000));
+        let report = checks.run(&mut order, None, None).await;
+        assert!(matches!(
+            report.refused,
+            Some((PreTradeCheck::Balance, Error::InsufficientBalance { .. }))
+        ));
+        assert_eq!(reservations.reserved("USDT"), dec!(500));
+        assert_eq!(log.load_all().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,489 @@
+//! Per-strategy resource quotas
+//!
+//! OKX rate limits are shared by every strategy trading on the account, so a
+//! single runaway strategy can starve all the others. The tracker counts each
+//! strategy's orders and cancels over the last minute and its signals over the
+//! last second, and refuses the action that would take it over its quota.
+//! A strategy that keeps hitting its quota is paused: the gate blocks its
+//! orders and signals until it is resumed.
+
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use tokio::sync::mpsc;
+use tracing::warn;
+use uuid::Uuid;
+
+/// Rate-limited action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaKind {
+    Order,
+    Cancel,
+    Signal,
+}
+
+impl QuotaKind {
+    /// Window the quota is counted over
+    pub fn window(&self) -> Duration {
+        match self {
+            QuotaKind::Order | QuotaKind::Cancel => Duration::minutes(1),
+            QuotaKind::Signal => Duration::seconds(1),
+        }
+    }
+
+    fn unit(&self) -> &'static str {
+        match self {
+            QuotaKind::Order => "orders per minute",
+            QuotaKind::Cancel => "cancels per minute",
+            QuotaKind::Signal => "signals per second",
+        }
+    }
+}
+
+/// Limits for one strategy; `None` leaves an action unlimited
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StrategyQuota {
+    pub max_orders_per_minute: Option<u32>,
+    pub max_cancels_per_minute: Option<u32>,
+    pub max_signals_per_second: Option<u32>,
+
+    /// Breaches within `breach_window_secs` that pause the strategy; 0 never pauses
+    pub pause_after_breaches: u32,
+    pub breach_window_secs: u64,
+}
+
+impl Default for StrategyQuota {
+    fn default() -> Self {
+        Self {
+            max_orders_per_minute: Some(120),
+            max_cancels_per_minute: Some(120),
+            max_signals_per_second: Some(20),
+            pause_after_breaches: 5,
+            breach_window_secs: 60,
+        }
+    }
+}
+
+impl StrategyQuota {
+    /// No limits and no pausing
+    pub fn unlimited() -> Self {
+        Self {
+            max_orders_per_minute: None,
+            max_cancels_per_minute: None,
+            max_signals_per_second: None,
+            pause_after_breaches: 0,
+            breach_window_secs: 60,
+        }
+    }
+
+    pub fn limit(&self, kind: QuotaKind) -> Option<u32> {
+        match kind {
+            QuotaKind::Order => self.max_orders_per_minute,
+            QuotaKind::Cancel => self.max_cancels_per_minute,
+            QuotaKind::Signal => self.max_signals_per_second,
+        }
+    }
+}
+
+/// An action refused for exceeding the strategy's quota
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaBreach {
+    pub strategy_id: Uuid,
+    pub kind: QuotaKind,
+    pub limit: u32,
+    pub at: DateTime<Utc>,
+
+    /// Breaches within the quota's breach window, this one included
+    pub recent_breaches: u32,
+
+    /// This breach paused the strategy
+    pub paused: bool,
+}
+
+impl QuotaBreach {
+    pub fn describe(&self) -> String {
+        let mut text = format!(
+            "Strategy {} exceeded its quota of {} {}",
+            self.strategy_id,
+            self.limit,
+            self.kind.unit()
+        );
+        if self.paused {
+            text.push_str(&format!("; paused after {} breaches", self.recent_breaches));
+        }
+        text
+    }
+}
+
+/// Current usage of one strategy against its quota
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaUsage {
+    pub strategy_id: Uuid,
+    pub quota: StrategyQuota,
+    pub orders_last_minute: usize,
+    pub cancels_last_minute: usize,
+    pub signals_last_second: usize,
+
+    /// Breaches within the breach window
+    pub recent_breaches: usize,
+    pub total_breaches: u64,
+
+    /// Why the strategy is paused, if it is
+    pub paused: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct StrategyUsage {
+    orders: VecDeque<DateTime<Utc>>,
+    cancels: VecDeque<DateTime<Utc>>,
+    signals: VecDeque<DateTime<Utc>>,
+    breaches: VecDeque<DateTime<Utc>>,
+    total_breaches: u64,
+    paused: Option<String>,
+}
+
+impl StrategyUsage {
+    fn window(&self, kind: QuotaKind) -> &VecDeque<DateTime<Utc>> {
+        match kind {
+            QuotaKind::Order => &self.orders,
+            QuotaKind::Cancel => &self.cancels,
+            QuotaKind::Signal => &self.signals,
+        }
+    }
+
+    fn window_mut(&mut self, kind: QuotaKind) -> &mut VecDeque<DateTime<Utc>> {
+        match kind {
+            QuotaKind::Order => &mut self.orders,
+            QuotaKind::Cancel => &mut self.cancels,
+            QuotaKind::Signal => &mut self.signals,
+        }
+    }
+}
+
+/// Number of timestamps after `cutoff`
+fn count_since(times: &VecDeque<DateTime<Utc>>, cutoff: DateTime<Utc>) -> usize {
+    times.iter().filter(|t| **t > cutoff).count()
+}
+
+fn prune(times: &mut VecDeque<DateTime<Utc>>, cutoff: DateTime<Utc>) {
+    while times.front().is_some_and(|t| *t <= cutoff) {
+        times.pop_front();
+    }
+}
+
+/// Sliding-window counters and pause state for every strategy
+#[derive(Debug)]
+pub struct QuotaTracker {
+    default_quota: RwLock<StrategyQuota>,
+    overrides: RwLock<HashMap<Uuid, StrategyQuota>>,
+    usage: Mutex<HashMap<Uuid, StrategyUsage>>,
+    event_tx: mpsc::UnboundedSender<QuotaBreach>,
+    event_rx: RwLock<Option<mpsc::UnboundedReceiver<QuotaBreach>>>,
+}
+
+impl QuotaTracker {
+    /// Create a tracker applying `default_quota` to strategies without their own
+    pub fn new(default_quota: StrategyQuota) -> Self {
+        let (event_tx, event_rx) = mpsc::unbounded_channel();
+
+        Self {
+            default_quota: RwLock::new(default_quota),
+            overrides: RwLock::new(HashMap::new()),
+            usage: Mutex::new(HashMap::new()),
+            event_tx,
+            event_rx: RwLock::new(Some(event_rx)),
+        }
+    }
+
+    pub fn default_quota(&self) -> StrategyQuota {
+        self.default_quota.read().clone()
+    }
+
+    pub fn set_default_quota(&self, quota: StrategyQuota) {
+        *self.default_quota.write() = quota;
+    }
+
+    /// Give one strategy its own quota instead of the default
+    pub fn set_strategy_quota(&self, strategy_id: Uuid, quota: StrategyQuota) {
+        self.overrides.write().insert(strategy_id, quota);
+    }
+
+    /// Put a strategy back on the default quota
+    pub fn clear_strategy_quota(&self, strategy_id: Uuid) {
+        self.overrides.write().remove(&strategy_id);
+    }
+
+    /// Quota in force for `strategy_id`
+    pub fn quota(&self, strategy_id: Uuid) -> StrategyQuota {
+        self.overrides
+            .read()
+            .get(&strategy_id)
+            .cloned()
+            .unwrap_or_else(|| self.default_quota())
+    }
+
+    /// Count one `kind` action for the strategy, or refuse it if that would
+    /// exceed the quota
+    pub fn acquire(&self, strategy_id: Uuid, kind: QuotaKind) -> Result<(), QuotaBreach> {
+        self.acquire_at(strategy_id, kind, Utc::now())
+    }
+
+    pub fn acquire_at(
+        &self,
+        strategy_id: Uuid,
+        kind: QuotaKind,
+        now: DateTime<Utc>,
+    ) -> Result<(), QuotaBreach> {
+        let quota = self.quota(strategy_id);
+        let mut usage = self.usage.lock();
+        let entry = usage.entry(strategy_id).or_default();
+
+        let window = entry.window_mut(kind);
+        prune(window, now - kind.window());
+
+        let Some(limit) = quota.limit(kind) else {
+            window.push_back(now);
+            return Ok(());
+        };
+        if window.len() < limit as usize {
+            window.push_back(now);
+            return Ok(());
+        }
+
+        prune(
+            &mut entry.breaches,
+            now - Duration::seconds(quota.breach_window_secs as i64),
+        );
+        entry.breaches.push_back(now);
+        entry.total_breaches += 1;
+
+        let recent_breaches = entry.breaches.len() as u32;
+        let paused = entry.paused.is_none()
+            && quota.pause_after_breaches > 0
+            && recent_breaches >= quota.pause_after_breaches;
+
+        let breach = QuotaBreach {
+            strategy_id,
+            kind,
+            limit,
+            at: now,
+            recent_breaches,
+            paused,
+        };
+        if paused {
+            entry.paused = Some(breach.describe());
+        }
+        warn!("{}", breach.describe());
+
+        let _ = self.event_tx.send(breach.clone());
+        Err(breach)
+    }
+
+    /// Whether one more `kind` action would exceed the quota, without counting it
+    pub fn would_exceed(&self, strategy_id: Uuid, kind: QuotaKind) -> bool {
+        let Some(limit) = self.quota(strategy_id).limit(kind) else {
+            return false;
+        };
+        let cutoff = Utc::now() - kind.window();
+        let used = self
+            .usage
+            .lock()
+            .get(&strategy_id)
+            .map(|u| count_since(u.window(kind), cutoff))
+            .unwrap_or(0);
+        used >= limit as usize
+    }
+
+    /// Why the strategy is paused, if it is
+    pub fn paused_reason(&self, strategy_id: Uuid) -> Option<String> {
+        self.usage
+            .lock()
+            .get(&strategy_id)
+            .and_then(|u| u.paused.clone())
+    }
+
+    /// Lift a quota pause and forget recent breaches; returns whether it was paused
+    pub fn resume(&self, strategy_id: Uuid) -> bool {
+        let mut usage = self.usage.lock();
+        let Some(entry) = usage.get_mut(&strategy_id) else {
+            return false;
+        };
+        entry.breaches.clear();
+        entry.paused.take().is_some()
+    }
+
+    /// Usage of every strategy that has acted or has its own quota
+    pub fn usage(&self) -> Vec<QuotaUsage> {
+        self.usage_at(Utc::now())
+    }
+
+    pub fn usage_at(&self, now: DateTime<Utc>) -> Vec<QuotaUsage> {
+        let mut ids: Vec<Uuid> = self.usage.lock().keys().copied().collect();
+        for id in self.overrides.read().keys() {
+            if !ids.contains(id) {
+                ids.push(*id);
+            }
+        }
+        ids.into_iter()
+            .map(|id| self.strategy_usage_at(id, now))
+            .collect()
+    }
+
+    /// Usage of one strategy
+    pub fn strategy_usage(&self, strategy_id: Uuid) -> QuotaUsage {
+        self.strategy_usage_at(strategy_id, Utc::now())
+    }
+
+    fn strategy_usage_at(&self, strategy_id: Uuid, now: DateTime<Utc>) -> QuotaUsage {
+        let quota = self.quota(strategy_id);
+        let usage = self.usage.lock();
+        let entry = usage.get(&strategy_id);
+        let count = |kind: QuotaKind| {
+            entry
+                .map(|u| count_since(u.window(kind), now - kind.window()))
+                .unwrap_or(0)
+        };
+        let breach_cutoff = now - Duration::seconds(quota.breach_window_secs as i64);
+
+        QuotaUsage {
+            strategy_id,
+            orders_last_minute: count(QuotaKind::Order),
+            cancels_last_minute: count(QuotaKind::Cancel),
+            signals_last_second: count(QuotaKind::Signal),
+            recent_breaches: entry
+                .map(|u| count_since(&u.breaches, breach_cutoff))
+                .unwrap_or(0),
+            total_breaches: entry.map(|u| u.total_breaches).unwrap_or(0),
+            paused: entry.and_then(|u| u.paused.clone()),
+            quota,
+        }
+    }
+
+    /// Take the breach stream (once)
+    pub fn subscribe_breaches(&self) -> Option<mpsc::UnboundedReceiver<QuotaBreach>> {
+        self.event_rx.write().take()
+    }
+}
+
+impl Default for QuotaTracker {
+    fn default() -> Self {
+        Self::new(StrategyQuota::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quota(orders: u32, pause_after: u32) -> StrategyQuota {
+        StrategyQuota {
+            max_orders_per_minute: Some(orders),
+            pause_after_breaches: pause_after,
+            ..StrategyQuota::unlimited()
+        }
+    }
+
+    #[test]
+    fn test_window_slides() {
+        let tracker = QuotaTracker::new(quota(2, 0));
+        let strategy = Uuid::new_v4();
+        let t0 = Utc::now();
+
+        assert!(tracker.acquire_at(strategy, QuotaKind::Order, t0).is_ok());
+        assert!(
+            tracker
+                .acquire_at(strategy, QuotaKind::Order, t0 + Duration::seconds(10))
+                .is_ok()
+        );
+        let breach = tracker
+            .acquire_at(strategy, QuotaKind::Order, t0 + Duration::seconds(20))
+            .unwrap_err();
+        assert_eq!(breach.limit, 2);
+        assert!(!breach.paused);
+
+        // The first order has left the window
+        assert!(
+            tracker
+                .acquire_at(strategy, QuotaKind::Order, t0 + Duration::seconds(61))
+                .is_ok()
+        );
+
+        // Other kinds and other strategies are counted separately
+        assert!(tracker.acquire_at(strategy, QuotaKind::Cancel, t0).is_ok());
+        assert!(
+            tracker
+                .acquire_at(Uuid::new_v4(), QuotaKind::Order, t0)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_repeated_breaches_pause_until_resumed() {
+        let tracker = QuotaTracker::new(quota(1, 3));
+        let mut breaches = tracker.subscribe_breaches().unwrap();
+        assert!(tracker.subscribe_breaches().is_none());
+        let strategy = Uuid::new_v4();
+        let t0 = Utc::now();
+
+        tracker.acquire_at(strategy, QuotaKind::Order, t0).unwrap();
+        for i in 1..=3 {
+            let at = t0 + Duration::seconds(i);
+            let breach = tracker
+                .acquire_at(strategy, QuotaKind::Order, at)
+                .unwrap_err();
+            assert_eq!(breach.recent_breaches, i as u32);
+            assert_eq!(breach.paused, i == 3);
+        }
+        assert!(tracker.paused_reason(strategy).unwrap().contains("paused"));
+        assert_eq!(std::iter::from_fn(|| breaches.try_recv().ok()).count(), 3);
+
+        let usage = tracker.strategy_usage_at(strategy, t0 + Duration::seconds(4));
+        assert_eq!(usage.orders_last_minute, 1);
+        assert_eq!(usage.recent_breaches, 3);
+        assert_eq!(usage.total_breaches, 3);
+
+        assert!(tracker.resume(strategy));
+        assert!(tracker.paused_reason(strategy).is_none());
+        assert!(!tracker.resume(strategy));
+    }
+
+    #[test]
+    fn test_breaches_outside_window_do_not_pause() {
+        let tracker = QuotaTracker::new(quota(1, 2));
+        let strategy = Uuid::new_v4();
+        let t0 = Utc::now();
+
+        tracker.acquire_at(strategy, QuotaKind::Order, t0).unwrap();
+        let first = tracker
+            .acquire_at(strategy, QuotaKind::Order, t0 + Duration::seconds(1))
+            .unwrap_err();
+        assert!(!first.paused);
+
+        // Two minutes on, the earlier breach no longer counts
+        let t1 = t0 + Duration::minutes(2);
+        tracker.acquire_at(strategy, QuotaKind::Order, t1).unwrap();
+        let second = tracker
+            .acquire_at(strategy, QuotaKind::Order, t1 + Duration::seconds(1))
+            .unwrap_err();
+        assert_eq!(second.recent_breaches, 1);
+        assert!(!second.paused);
+    }
+
+    #[test]
+    fn test_override_replaces_default() {
+        let tracker = QuotaTracker::new(quota(1, 0));
+        let strategy = Uuid::new_v4();
+        tracker.set_strategy_quota(strategy, StrategyQuota::unlimited());
+
+        let t0 = Utc::now();
+        for _ in 0..10 {
+            assert!(tracker.acquire_at(strategy, QuotaKind::Order, t0).is_ok());
+        }
+        assert_eq!(tracker.usage_at(t0).len(), 1);
+
+        tracker.clear_strategy_quota(strategy);
+        assert!(tracker.would_exceed(strategy, QuotaKind::Order));
+    }
+}
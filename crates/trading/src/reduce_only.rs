@@ -0,0 +1,178 @@
+//! Position-aware reduce-only checks
+//!
+//! OKX only enforces `reduceOnly` on margin and derivatives orders, and a
+//! close signal racing a fill can still ask for more than is open. Before a
+//! reduce-only order is sent, [`ReduceOnlyGuard`] looks up the current net
+//! position: the order must trade against it, and is clamped to its size so
+//! it can close the position but never flip it.
+
+use crate::error::Result;
+use async_trait::async_trait;
+use ea_okx_core::Symbol;
+use ea_okx_core::models::{Order, OrderSide};
+use ea_okx_core::types::Quantity;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Source of current open positions
+#[async_trait]
+pub trait PositionSource: Send + Sync {
+    /// Net open size of `symbol`: positive long, negative short, zero flat
+    async fn net_position(&self, symbol: &Symbol) -> Result<Decimal>;
+}
+
+/// Outcome of checking an order against the open position
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum ReduceOnlyDecision {
+    /// Not reduce-only, or no larger than the position
+    Within,
+    /// Order was reduced to the open size
+    Clamped {
+        requested: Decimal,
+        allowed: Decimal,
+    },
+    /// Nothing is open on the symbol
+    NoPosition,
+    /// Order is on the same side as the position and would add to it
+    WrongSide { position: Decimal },
+}
+
+impl ReduceOnlyDecision {
+    /// Whether the order must not be sent
+    pub fn is_rejected(&self) -> bool {
+        matches!(self, Self::NoPosition | Self::WrongSide { .. })
+    }
+
+    /// Human-readable summary for rejections and logs
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Within => "within open position".to_string(),
+            Self::Clamped { requested, allowed } => {
+                format!("clamped from {} to open size {}", requested, allowed)
+            }
+            Self::NoPosition => "no open position to reduce".to_string(),
+            Self::WrongSide { position } => {
+                format!("would increase open position {}", position)
+            }
+        }
+    }
+}
+
+/// Check a reduce-only `order` against the net `position`, clamping its
+/// quantity to the open size
+pub fn enforce_reduce_only(order: &mut Order, position: Decimal) -> Result<ReduceOnlyDecision> {
+    if !order.reduce_only {
+        return Ok(ReduceOnlyDecision::Within);
+    }
+    if position.is_zero() {
+        return Ok(ReduceOnlyDecision::NoPosition);
+    }
+
+    let reduces = match order.side {
+        OrderSide::Buy => position < Decimal::ZERO,
+        OrderSide::Sell => position > Decimal::ZERO,
+    };
+    if !reduces {
+        return Ok(ReduceOnlyDecision::WrongSide { position });
+    }
+
+    let requested = order.quantity.as_decimal();
+    let allowed = position.abs();
+    if requested <= allowed {
+        return Ok(ReduceOnlyDecision::Within);
+    }
+
+    order.quantity = Quantity::new(allowed)?;
+    Ok(ReduceOnlyDecision::Clamped { requested, allowed })
+}
+
+/// Enforces reduce-only orders against live positions before submission
+pub struct ReduceOnlyGuard {
+    source: Arc<dyn PositionSource>,
+}
+
+impl ReduceOnlyGuard {
+    pub fn new(source: Arc<dyn PositionSource>) -> Self {
+        Self { source }
+    }
+
+    /// Check `order`, clamping it if it is larger than the open position
+    ///
+    /// Other orders pass without a lookup. If the position cannot be read
+    /// the error is returned: sending blind could flip the position.
+    pub async fn apply(&self, order: &mut Order) -> Result<ReduceOnlyDecision> {
+        if !order.reduce_only {
+            return Ok(ReduceOnlyDecision::Within);
+        }
+        let position = self.source.net_position(&order.symbol).await?;
+        enforce_reduce_only(order, position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ea_okx_core::models::OrderType;
+    use rust_decimal_macros::dec;
+    use uuid::Uuid;
+
+    fn close(side: OrderSide, qty: Decimal) -> Order {
+        Order::new(
+            Uuid::new_v4(),
+            Symbol::new("BTC-USDT-SWAP").unwrap(),
+            side,
+            OrderType::Market,
+            Quantity::new(qty).unwrap(),
+            None,
+        )
+        .with_reduce_only()
+    }
+
+    #[test]
+    fn test_clamps_to_open_size() {
+        let mut sell = close(OrderSide::Sell, dec!(2));
+        assert_eq!(
+            enforce_reduce_only(&mut sell, dec!(0.5)).unwrap(),
+            ReduceOnlyDecision::Clamped {
+                requested: dec!(2),
+                allowed: dec!(0.5)
+            }
+        );
+        assert_eq!(sell.quantity.as_decimal(), dec!(0.5));
+
+        let mut buy = close(OrderSide::Buy, dec!(1));
+        assert_eq!(
+            enforce_reduce_only(&mut buy, dec!(-3)).unwrap(),
+            ReduceOnlyDecision::Within
+        );
+        assert_eq!(buy.quantity.as_decimal(), dec!(1));
+    }
+
+    #[test]
+    fn test_rejects_without_position_or_on_wrong_side() {
+        let mut sell = close(OrderSide::Sell, dec!(1));
+        let flat = enforce_reduce_only(&mut sell, Decimal::ZERO).unwrap();
+        assert_eq!(flat, ReduceOnlyDecision::NoPosition);
+        assert!(flat.is_rejected());
+
+        let mut buy = close(OrderSide::Buy, dec!(1));
+        let adds = enforce_reduce_only(&mut buy, dec!(0.5)).unwrap();
+        assert_eq!(
+            adds,
+            ReduceOnlyDecision::WrongSide {
+                position: dec!(0.5)
+            }
+        );
+        assert!(adds.is_rejected());
+
+        // Orders that are not reduce-only are left alone
+        let mut open = close(OrderSide::Buy, dec!(1));
+        open.reduce_only = false;
+        assert_eq!(
+            enforce_reduce_only(&mut open, Decimal::ZERO).unwrap(),
+            ReduceOnlyDecision::Within
+        );
+    }
+}
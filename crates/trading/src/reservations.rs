@@ -0,0 +1,333 @@
+//! Local balance reservations for pending orders
+//!
+//! The exchange only freezes funds once it has accepted an order, so several
+//! limit buys sent back to back can together commit more quote currency than
+//! the account holds. [`BalanceReservations`] earmarks what each pending spot
+//! order will spend — the quote notional of a buy, the base quantity of a
+//! sell — and refuses an order when it exceeds the balance not yet earmarked.
+//! A reservation shrinks as the order fills and is released when it ends.
+//!
+//! Reservations are measured against the cash balance rather than OKX's
+//! available balance, which already nets out resting orders and would count
+//! them twice. Orders placed outside this process are not seen, and derivative
+//! orders, which draw on margin, are never reserved. Until the balances are
+//! known orders are let through, unless the ledger is built with
+//! [`BalanceReservations::requiring_balances`].
+
+use crate::account::AccountTracker;
+use crate::error::{Error, Result};
+use ea_okx_core::models::{Order, OrderSide, OrderType};
+use ea_okx_core::{InstrumentKind, Symbol};
+use parking_lot::RwLock;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::debug;
+use uuid::Uuid;
+
+/// Source of current cash balances
+pub trait BalanceSource: Send + Sync {
+    /// Cash balance of `ccy`, or `None` while balances are not known yet
+    fn cash_balance(&self, ccy: &str) -> Option<Decimal>;
+}
+
+impl BalanceSource for AccountTracker {
+    fn cash_balance(&self, ccy: &str) -> Option<Decimal> {
+        let state = self.state();
+        state.updated_at.map(|_| state.cash_balance(ccy))
+    }
+}
+
+/// Balance earmarked for one pending order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reservation {
+    pub order_id: Uuid,
+    pub symbol: Symbol,
+    pub ccy: String,
+
+    /// Currency spent per unit of order quantity: the limit price for buys,
+    /// one for sells
+    pub unit_cost: Decimal,
+
+    /// Order quantity not filled yet
+    pub remaining: Decimal,
+}
+
+impl Reservation {
+    /// Balance still earmarked
+    pub fn amount(&self) -> Decimal {
+        self.unit_cost * self.remaining
+    }
+}
+
+/// Per-currency ledger of balance reserved by pending orders
+pub struct BalanceReservations {
+    source: Arc<dyn BalanceSource>,
+    reservations: RwLock<HashMap<Uuid, Reservation>>,
+
+    /// Refuse reserved orders while their currency's balance is unknown
+    require_balances: bool,
+}
+
+impl BalanceReservations {
+    pub fn new(source: Arc<dyn BalanceSource>) -> Self {
+        Self {
+            source,
+            reservations: RwLock::new(HashMap::new()),
+            require_balances: false,
+        }
+    }
+
+    /// Refuse orders that would be reserved until the balance they draw on
+    /// is known, instead of letting them through unchecked
+    pub fn requiring_balances(mut self) -> Self {
+        self.require_balances = true;
+        self
+    }
+
+    /// What `order` would reserve, or `None` when it is not reserved
+    ///
+    /// Market buys carry no price to value them at and are not reserved.
+    pub fn reservation_for(order: &Order) -> Option<Reservation> {
+        if order.symbol.kind() != InstrumentKind::Spot {
+            return None;
+        }
+        let (ccy, unit_cost) = match order.side {
+            OrderSide::Buy if order.order_type == OrderType::Market => return None,
+            OrderSide::Buy => (order.symbol.quote(), order.price?.as_decimal()),
+            OrderSide::Sell => (order.symbol.base(), Decimal::ONE),
+        };
+        Some(Reservation {
+            order_id: order.id,
+            symbol: order.symbol.clone(),
+            ccy: ccy.to_string(),
+            unit_cost,
+            remaining: order.quantity.as_decimal() - order.filled_quantity.as_decimal(),
+        })
+    }
+
+    /// Reserve balance for `order`, refusing it with
+    /// [`Error::InsufficientBalance`] when it exceeds the unreserved balance
+    ///
+    /// While the currency's balance is not known orders are let through
+    /// unchecked, or refused with [`Error::TradingDisabled`] when balances are
+    /// required. Reserving an order twice replaces its reservation.
+    pub fn reserve(&self, order: &Order) -> Result<Option<Reservation>> {
+        let Some(reservation) = Self::reservation_for(order) else {
+            return Ok(None);
+        };
+
+        let mut reservations = self.reservations.write();
+        let balance = self.source.cash_balance(&reservation.ccy);
+        if balance.is_none() && self.require_balances {
+            return Err(Error::TradingDisabled(format!(
+                "{} balance is not known yet",
+                reservation.ccy
+            )));
+        }
+        if let Some(balance) = balance {
+            let reserved: Decimal = reservations
+                .values()
+                .filter(|r| r.ccy == reservation.ccy && r.order_id != order.id)
+                .map(Reservation::amount)
+                .sum();
+            let available = (balance - reserved).max(Decimal::ZERO);
+            if reservation.amount() > available {
+                return Err(Error::InsufficientBalance {
+                    requested: reservation.amount(),
+                    ccy: reservation.ccy,
+                    available,
+                });
+            }
+        }
+
+        debug!(
+            "Reserved {} {} for order {}",
+            reservation.amount(),
+            reservation.ccy,
+            order.id
+        );
+        reservations.insert(order.id, reservation.clone());
+        Ok(Some(reservation))
+    }
+
+    /// Shrink an order's reservation to what its cumulative `filled`
+    /// quantity leaves, releasing it once nothing remains
+    pub fn fill(&self, order_id: Uuid, quantity: Decimal, filled: Decimal) {
+        let mut reservations = self.reservations.write();
+        let Some(reservation) = reservations.get_mut(&order_id) else {
+            return;
+        };
+        reservation.remaining = (quantity - filled).max(Decimal::ZERO);
+        if reservation.remaining.is_zero() {
+            reservations.remove(&order_id);
+        }
+    }
+
+    /// Release an order's reservation, such as when it is cancelled or rejected
+    pub fn release(&self, order_id: Uuid) -> Option<Reservation> {
+        self.reservations.write().remove(&order_id)
+    }
+
+    /// Balance reserved in `ccy`
+    pub fn reserved(&self, ccy: &str) -> Decimal {
+        self.reservations
+            .read()
+            .values()
+            .filter(|r| r.ccy == ccy)
+            .map(Reservation::amount)
+            .sum()
+    }
+
+    /// Cash balance of `ccy` not reserved, if the balance is known
+    pub fn unreserved(&self, ccy: &str) -> Option<Decimal> {
+        let balance = self.source.cash_balance(ccy)?;
+        Some((balance - self.reserved(ccy)).max(Decimal::ZERO))
+    }
+
+    /// Current reservations
+    pub fn reservations(&self) -> Vec<Reservation> {
+        self.reservations.read().values().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ea_okx_core::{Price, Quantity};
+    use rust_decimal_macros::dec;
+
+    struct Balances(HashMap<&'static str, Decimal>);
+
+    impl BalanceSource for Balances {
+        fn cash_balance(&self, ccy: &str) -> Option<Decimal> {
+            self.0.get(ccy).copied()
+        }
+    }
+
+    fn limit(symbol: &str, side: OrderSide, quantity: Decimal, price: Decimal) -> Order {
+        Order::new(
+            Uuid::new_v4(),
+            Symbol::new(symbol).unwrap(),
+            side,
+            OrderType::Limit,
+            Quantity::new(quantity).unwrap(),
+            Some(Price::new(price).unwrap()),
+        )
+    }
+
+    #[test]
+    fn test_reservations_limit_pending_orders_to_balance() {
+        let ledger = BalanceReservations::new(Arc::new(Balances(HashMap::from([
+            ("USDT", dec!(1000)),
+            ("BTC", dec!(0.5)),
+        ]))));
+
+        let first = limit("BTC-USDT", OrderSide::Buy, dec!(0.01), dec!(60000));
+        assert_eq!(ledger.reserve(&first).unwrap().unwrap().amount(), dec!(600));
+
+        // The second buy alone fits the balance, but not alongside the first
+        let second = limit("ETH-USDT", OrderSide::Buy, dec!(0.2), dec!(3000));
+        assert!(matches!(
+            ledger.reserve(&second),
+            Err(Error::InsufficientBalance { requested, available, .. })
+                if requested == dec!(600) && available == dec!(400)
+        ));
+        assert_eq!(ledger.unreserved("USDT"), Some(dec!(400)));
+
+        // Sells reserve base currency; currencies with no known balance pass
+        let sell = limit("BTC-USDT", OrderSide::Sell, dec!(0.5), dec!(70000));
+        assert!(ledger.reserve(&sell).unwrap().is_some());
+        let sol = limit("SOL-EUR", OrderSide::Buy, dec!(100), dec!(150));
+        assert!(ledger.reserve(&sol).is_ok());
+
+        // Half filled frees room for the second, then cancelled
+        ledger.fill(first.id, dec!(0.01), dec!(0.005));
+        assert_eq!(ledger.reserved("USDT"), dec!(300));
+        assert!(ledger.reserve(&second).is_ok());
+        ledger.release(first.id);
+        assert_eq!(ledger.unreserved("USDT"), Some(dec!(400)));
+
+        ledger.fill(sell.id, dec!(0.5), dec!(0.5));
+        assert_eq!(ledger.reserved("BTC"), Decimal::ZERO);
+
+        let swap = limit("BTC-USDT-SWAP", OrderSide::Buy, dec!(100), dec!(60000));
+        assert!(ledger.reserve(&swap).unwrap().is_none());
+    }
+
+    fn ledger(usdt: Decimal) -> BalanceReservations {
+        BalanceReservations::new(Arc::new(Balances(HashMap::from([("USDT", usdt)]))))
+    }
+
+    #[test]
+    fn test_market_buys_are_not_reserved() {
+        let ledger = ledger(dec!(10));
+        let buy = Order::new(
+            Uuid::new_v4(),
+            Symbol::new("BTC-USDT").unwrap(),
+            OrderSide::Buy,
+            OrderType::Market,
+            Quantity::new(dec!(1)).unwrap(),
+            None,
+        );
+
+        assert!(BalanceReservations::reservation_for(&buy).is_none());
+        assert!(ledger.reserve(&buy).unwrap().is_none());
+        assert!(ledger.reservations().is_empty());
+        assert_eq!(ledger.unreserved("USDT"), Some(dec!(10)));
+    }
+
+    #[test]
+    fn test_reserving_again_replaces_the_reservation() {
+        let ledger = ledger(dec!(1000));
+        let mut buy = limit("BTC-USDT", OrderSide::Buy, dec!(0.01), dec!(60000));
+        ledger.reserve(&buy).unwrap();
+        ledger.reserve(&buy).unwrap();
+        assert_eq!(ledger.reserved("USDT"), dec!(600));
+
+        // Resizing is checked against the balance less other orders only
+        buy.quantity = Quantity::new(dec!(0.015)).unwrap();
+        ledger.reserve(&buy).unwrap();
+        assert_eq!(ledger.reserved("USDT"), dec!(900));
+        assert_eq!(ledger.reservations().len(), 1);
+    }
+
+    #[test]
+    fn test_unknown_balances_refuse_orders_when_required() {
+        let ledger = ledger(dec!(1000)).requiring_balances();
+        let buy = limit("BTC-USDT", OrderSide::Buy, dec!(0.01), dec!(60000));
+        assert!(ledger.reserve(&buy).unwrap().is_some());
+
+        let sell = limit("BTC-USDT", OrderSide::Sell, dec!(0.01), dec!(60000));
+        assert!(matches!(
+            ledger.reserve(&sell),
+            Err(Error::TradingDisabled(msg)) if msg.contains("BTC")
+        ));
+        assert!(ledger.reservations().iter().all(|r| r.ccy == "USDT"));
+
+        // The tracker knows no balance before its first update
+        let tracker = Arc::new(AccountTracker::new(Default::default()));
+        let ledger = BalanceReservations::new(tracker).requiring_balances();
+        assert!(ledger.reserve(&buy).is_err());
+    }
+
+    #[test]
+    fn test_fill_takes_cumulative_quantity() {
+        let ledger = ledger(dec!(1000));
+        let buy = limit("BTC-USDT", OrderSide::Buy, dec!(0.01), dec!(60000));
+        ledger.reserve(&buy).unwrap();
+
+        ledger.fill(buy.id, dec!(0.01), dec!(0.004));
+        assert_eq!(ledger.reserved("USDT"), dec!(360));
+        // Repeating a fill report changes nothing
+        ledger.fill(buy.id, dec!(0.01), dec!(0.004));
+        assert_eq!(ledger.reserved("USDT"), dec!(360));
+        ledger.fill(buy.id, dec!(0.01), dec!(0.006));
+        assert_eq!(ledger.reserved("USDT"), dec!(240));
+
+        // Overfills release the reservation
+        ledger.fill(buy.id, dec!(0.01), dec!(0.02));
+        assert!(ledger.reservations().is_empty());
+    }
+}
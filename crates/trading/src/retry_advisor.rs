@@ -0,0 +1,343 @@
+//! Retry advice for rejected orders
+//!
+//! Turns a typed [`RejectionReason`] into a concrete [`Remediation`] (for
+//! example "reduce size to max available: 0.42") and decides whether the
+//! order can be resubmitted automatically.
+
+use ea_okx_client::RejectionReason;
+use ea_okx_core::models::{Order, OrderSide};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Instrument and account limits used to size remediation suggestions
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OrderConstraints {
+    /// Lot size (order size must be a multiple of this)
+    pub lot_size: Option<Decimal>,
+
+    /// Minimum order size
+    pub min_size: Option<Decimal>,
+
+    /// Maximum order size
+    pub max_size: Option<Decimal>,
+
+    /// Maximum size the account can currently afford
+    pub max_available: Option<Decimal>,
+}
+
+/// Suggested fix for a rejected order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Remediation {
+    /// Resubmit with a smaller size
+    ReduceSize { max_quantity: Option<Decimal> },
+
+    /// Resubmit with the size rounded to the lot size
+    RoundSize { quantity: Option<Decimal> },
+
+    /// Resubmit with at least the minimum size
+    IncreaseSize { min_quantity: Option<Decimal> },
+
+    /// Resubmit with a price inside the exchange band
+    AdjustPrice { price: Option<Decimal> },
+
+    /// Resubmit unchanged after a delay
+    RetryAfter { delay_ms: u64 },
+
+    /// Resubmit with a fresh client order ID
+    NewClientOrderId,
+
+    /// Drop the order (e.g. nothing left to reduce)
+    Abandon,
+
+    /// Requires operator action (account restrictions, unmapped errors)
+    ManualIntervention,
+}
+
+impl fmt::Display for Remediation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Remediation::ReduceSize {
+                max_quantity: Some(q),
+            } => write!(f, "reduce size to max available: {}", q),
+            Remediation::ReduceSize { max_quantity: None } => write!(f, "reduce size"),
+            Remediation::RoundSize { quantity: Some(q) } => {
+                write!(f, "round size to lot size: {}", q)
+            }
+            Remediation::RoundSize { quantity: None } => write!(f, "round size to the lot size"),
+            Remediation::IncreaseSize {
+                min_quantity: Some(q),
+            } => write!(f, "increase size to minimum: {}", q),
+            Remediation::IncreaseSize { min_quantity: None } => {
+                write!(f, "increase size above the minimum")
+            }
+            Remediation::AdjustPrice { price: Some(p) } => {
+                write!(f, "adjust price to within limits: {}", p)
+            }
+            Remediation::AdjustPrice { price: None } => write!(f, "adjust price to within limits"),
+            Remediation::RetryAfter { delay_ms } => write!(f, "retry after {}ms", delay_ms),
+            Remediation::NewClientOrderId => write!(f, "resubmit with a new client order ID"),
+            Remediation::Abandon => write!(f, "abandon order"),
+            Remediation::ManualIntervention => write!(f, "manual intervention required"),
+        }
+    }
+}
+
+/// Outcome of evaluating a rejection
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetryAdvice {
+    /// Suggested fix
+    pub remediation: Remediation,
+
+    /// Whether the order can be resubmitted automatically with the remediation applied
+    pub retryable: bool,
+}
+
+/// Maps rejection reasons to remediations
+#[derive(Debug, Clone)]
+pub struct RetryAdvisor {
+    /// Maximum automatic resubmissions per order
+    pub max_retries: u32,
+
+    /// Base backoff for transient rejections (doubled per attempt)
+    pub retry_backoff_ms: u64,
+}
+
+impl RetryAdvisor {
+    /// Create a new advisor
+    pub fn new(max_retries: u32, retry_backoff_ms: u64) -> Self {
+        Self {
+            max_retries,
+            retry_backoff_ms,
+        }
+    }
+
+    /// Advise on a rejected order; `attempt` is the number of retries already made
+    pub fn advise(
+        &self,
+        reason: &RejectionReason,
+        order: &Order,
+        constraints: &OrderConstraints,
+        attempt: u32,
+    ) -> RetryAdvice {
+        let quantity = order.quantity.as_decimal();
+
+        let remediation = match reason {
+            RejectionReason::InsufficientBalance | RejectionReason::PositionLimit => {
+                Remediation::ReduceSize {
+                    max_quantity: constraints
+                        .max_available
+                        .map(|max| round_down(max, constraints.lot_size))
+                        .filter(|max| *max > Decimal::ZERO && *max < quantity),
+                }
+            }
+            RejectionReason::SizeTooLarge => Remediation::ReduceSize {
+                max_quantity: constraints.max_size,
+            },
+            RejectionReason::SizePrecision => Remediation::RoundSize {
+                quantity: constraints
+                    .lot_size
+                    .map(|lot| round_down(quantity, Some(lot)))
+                    .filter(|q| *q > Decimal::ZERO),
+            },
+            RejectionReason::SizeTooSmall => Remediation::IncreaseSize {
+                min_quantity: constraints.min_size,
+            },
+            RejectionReason::PriceOutOfLimits { max_buy, min_sell } => Remediation::AdjustPrice {
+                price: match order.side {
+                    OrderSide::Buy => *max_buy,
+                    OrderSide::Sell => *min_sell,
+                },
+            },
+            RejectionReason::RateLimited | RejectionReason::ExchangeUnavailable => {
+                Remediation::RetryAfter {
+                    delay_ms: self.backoff_ms(attempt),
+                }
+            }
+            RejectionReason::DuplicateClientOrderId => Remediation::NewClientOrderId,
+            RejectionReason::ReduceOnlyNoPosition | RejectionReason::InstrumentUnavailable => {
+                Remediation::Abandon
+            }
+            RejectionReason::AccountRestricted
+            | RejectionReason::InvalidParameter
+            | RejectionReason::Unknown { .. } => Remediation::ManualIntervention,
+        };
+
+        let actionable = match &remediation {
+            Remediation::ReduceSize { max_quantity } => max_quantity.is_some(),
+            Remediation::RoundSize { quantity } => quantity.is_some(),
+            Remediation::IncreaseSize { min_quantity } => min_quantity.is_some(),
+            Remediation::AdjustPrice { price } => price.is_some(),
+            Remediation::RetryAfter { .. } | Remediation::NewClientOrderId => true,
+            Remediation::Abandon | Remediation::ManualIntervention => false,
+        };
+
+        RetryAdvice {
+            remediation,
+            retryable: actionable && attempt < self.max_retries,
+        }
+    }
+
+    /// Exponential backoff for the given attempt
+    fn backoff_ms(&self, attempt: u32) -> u64 {
+        self.retry_backoff_ms
+            .saturating_mul(1u64 << attempt.min(16))
+    }
+}
+
+/// Round a size down to a multiple of the lot size
+pub(crate) fn round_down(quantity: Decimal, lot_size: Option<Decimal>) -> Decimal {
+    match lot_size {
+        Some(lot) if lot > Decimal::ZERO => (quantity / lot).floor() * lot,
+        _ => quantity,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ea_okx_core::models::OrderType;
+    use ea_okx_core::types::{Price, Quantity, Symbol};
+    use rust_decimal_macros::dec;
+    use uuid::Uuid;
+
+    fn order(side: OrderSide, qty: Decimal) -> Order {
+        Order::new(
+            Uuid::new_v4(),
+            Symbol::new("BTC-USDT").unwrap(),
+            side,
+            OrderType::Limit,
+            Quantity::new(qty).unwrap(),
+            Some(Price::new(dec!(42000)).unwrap()),
+        )
+    }
+
+    #[test]
+    fn test_insufficient_balance_reduces_to_max_available() {
+        let advisor = RetryAdvisor::new(3, 1000);
+        let constraints = OrderConstraints {
+            lot_size: Some(dec!(0.01)),
+            max_available: Some(dec!(0.427)),
+            ..Default::default()
+        };
+
+        let advice = advisor.advise(
+            &RejectionReason::InsufficientBalance,
+            &order(OrderSide::Buy, dec!(1)),
+            &constraints,
+            0,
+        );
+
+        assert_eq!(
+            advice.remediation,
+            Remediation::ReduceSize {
+                max_quantity: Some(dec!(0.42))
+            }
+        );
+        assert!(advice.retryable);
+        assert_eq!(
+            advice.remediation.to_string(),
+            "reduce size to max available: 0.42"
+        );
+    }
+
+    #[test]
+    fn test_unknown_max_available_is_not_retryable() {
+        let advisor = RetryAdvisor::new(3, 1000);
+        let advice = advisor.advise(
+            &RejectionReason::InsufficientBalance,
+            &order(OrderSide::Buy, dec!(1)),
+            &OrderConstraints::default(),
+            0,
+        );
+
+        assert_eq!(
+            advice.remediation,
+            Remediation::ReduceSize { max_quantity: None }
+        );
+        assert!(!advice.retryable);
+    }
+
+    #[test]
+    fn test_size_precision_rounds_to_lot() {
+        let advisor = RetryAdvisor::new(3, 1000);
+        let constraints = OrderConstraints {
+            lot_size: Some(dec!(0.001)),
+            ..Default::default()
+        };
+
+        let advice = advisor.advise(
+            &RejectionReason::SizePrecision,
+            &order(OrderSide::Sell, dec!(0.12345)),
+            &constraints,
+            0,
+        );
+
+        assert_eq!(
+            advice.remediation,
+            Remediation::RoundSize {
+                quantity: Some(dec!(0.123))
+            }
+        );
+    }
+
+    #[test]
+    fn test_price_limit_uses_side_specific_bound() {
+        let advisor = RetryAdvisor::new(3, 1000);
+        let reason = RejectionReason::PriceOutOfLimits {
+            max_buy: Some(dec!(43000)),
+            min_sell: Some(dec!(41000)),
+        };
+        let constraints = OrderConstraints::default();
+
+        let buy = advisor.advise(&reason, &order(OrderSide::Buy, dec!(1)), &constraints, 0);
+        let sell = advisor.advise(&reason, &order(OrderSide::Sell, dec!(1)), &constraints, 0);
+
+        assert_eq!(
+            buy.remediation,
+            Remediation::AdjustPrice {
+                price: Some(dec!(43000))
+            }
+        );
+        assert_eq!(
+            sell.remediation,
+            Remediation::AdjustPrice {
+                price: Some(dec!(41000))
+            }
+        );
+    }
+
+    #[test]
+    fn test_rate_limit_backs_off_and_stops_after_max_retries() {
+        let advisor = RetryAdvisor::new(2, 500);
+        let o = order(OrderSide::Buy, dec!(1));
+        let constraints = OrderConstraints::default();
+
+        let first = advisor.advise(&RejectionReason::RateLimited, &o, &constraints, 0);
+        let second = advisor.advise(&RejectionReason::RateLimited, &o, &constraints, 1);
+        let exhausted = advisor.advise(&RejectionReason::RateLimited, &o, &constraints, 2);
+
+        assert_eq!(first.remediation, Remediation::RetryAfter { delay_ms: 500 });
+        assert_eq!(
+            second.remediation,
+            Remediation::RetryAfter { delay_ms: 1000 }
+        );
+        assert!(first.retryable && second.retryable);
+        assert!(!exhausted.retryable);
+    }
+
+    #[test]
+    fn test_account_restricted_requires_manual_intervention() {
+        let advisor = RetryAdvisor::new(3, 1000);
+        let advice = advisor.advise(
+            &RejectionReason::AccountRestricted,
+            &order(OrderSide::Buy, dec!(1)),
+            &OrderConstraints::default(),
+            0,
+        );
+
+        assert_eq!(advice.remediation, Remediation::ManualIntervention);
+        assert!(!advice.retryable);
+    }
+}
@@ -0,0 +1,1014 @@
+//! Scaling out of positions in tranches
+//!
+//! A [`ScaleOutPlan`] takes profit on a position in steps, e.g. half at +2%,
+//! a quarter at +4% and a trailing stop on the rest. [`ScaleOutManager`]
+//! attaches plans to open positions. When an [`AlgoOrderVenue`] is
+//! configured and the instrument is a derivative, every exit rests on the
+//! exchange as a reduce-only algo order, so it fires even while this process
+//! is down. Otherwise the plan is managed locally: [`ScaleOutManager::on_price`]
+//! watches prices and returns the reduce-only market orders to send as
+//! targets are reached. An exit only counts as done once the caller reports
+//! its order executed through [`ScaleOutManager::exit_filled`]; a failed
+//! order goes back to watching via [`ScaleOutManager::exit_failed`].
+
+use crate::error::{Error, Result};
+use crate::size_limits::TradeMode;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use ea_okx_client::OkxRestClient;
+use ea_okx_client::models::{AlgoOrderRequest, CancelAlgoOrderRequest};
+use ea_okx_core::Symbol;
+use ea_okx_core::models::{Order, OrderSide, OrderType, PositionSide};
+use ea_okx_core::types::Quantity;
+use parking_lot::RwLock;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// One take-profit step
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfitTarget {
+    /// Gain from entry at which to exit, in percent (2.0 = +2%)
+    pub profit_pct: Decimal,
+    /// Share of the initial position to close, from 0 to 1
+    pub fraction: Decimal,
+}
+
+/// Trailing stop on the part of the position the targets leave open
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrailingExit {
+    /// Retracement from the best price that closes the rest, in percent
+    pub callback_pct: Decimal,
+}
+
+/// How to take profit on a position
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScaleOutPlan {
+    /// Take-profit steps, nearest first
+    pub targets: Vec<ProfitTarget>,
+
+    /// Trails the remainder once the last target's price is reached (or
+    /// from entry if there are no targets)
+    #[serde(default)]
+    pub trail: Option<TrailingExit>,
+}
+
+impl ScaleOutPlan {
+    /// Check the plan closes at most the whole position in ascending steps
+    pub fn validate(&self) -> Result<()> {
+        if self.targets.is_empty() && self.trail.is_none() {
+            return Err(Error::InvalidPlan("plan has no exits".to_string()));
+        }
+
+        let mut previous = Decimal::ZERO;
+        for target in &self.targets {
+            if target.profit_pct <= previous {
+                return Err(Error::InvalidPlan(format!(
+                    "profit targets must be positive and ascending, got {}% after {}%",
+                    target.profit_pct, previous
+                )));
+            }
+            if target.fraction <= Decimal::ZERO || target.fraction > Decimal::ONE {
+                return Err(Error::InvalidPlan(format!(
+                    "fraction {} is not between 0 and 1",
+                    target.fraction
+                )));
+            }
+            previous = target.profit_pct;
+        }
+
+        let closed = self.target_fraction();
+        if closed > Decimal::ONE {
+            return Err(Error::InvalidPlan(format!(
+                "targets close {} of the position",
+                closed
+            )));
+        }
+        if let Some(trail) = &self.trail {
+            if trail.callback_pct <= Decimal::ZERO || trail.callback_pct >= Decimal::ONE_HUNDRED {
+                return Err(Error::InvalidPlan(format!(
+                    "callback {}% is not between 0 and 100",
+                    trail.callback_pct
+                )));
+            }
+            if closed == Decimal::ONE {
+                return Err(Error::InvalidPlan(
+                    "targets close the whole position, nothing is left to trail".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Share of the position the targets close
+    fn target_fraction(&self) -> Decimal {
+        self.targets.iter().map(|t| t.fraction).sum()
+    }
+}
+
+/// Where a plan's exits are watched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanManagement {
+    /// Resting algo orders on the exchange
+    Exchange,
+    /// Price checks in [`ScaleOutManager::on_price`]
+    Local,
+}
+
+/// State of one exit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ExitStatus {
+    /// Watched locally, not reached yet
+    Pending,
+    /// Resting on the exchange as an algo order
+    Resting { algo_id: String },
+    /// Reached locally, exit order `order_id` waiting to execute
+    Sending {
+        order_id: Uuid,
+        price: Decimal,
+        at: DateTime<Utc>,
+    },
+    /// Exit order executed, or reported triggered by the exchange
+    Triggered { price: Decimal, at: DateTime<Utc> },
+}
+
+impl ExitStatus {
+    pub fn is_triggered(&self) -> bool {
+        matches!(self, Self::Triggered { .. })
+    }
+
+    fn sending(&self, id: Uuid) -> bool {
+        matches!(self, Self::Sending { order_id, .. } if *order_id == id)
+    }
+
+    fn algo_id(&self) -> Option<&str> {
+        match self {
+            Self::Resting { algo_id } => Some(algo_id),
+            _ => None,
+        }
+    }
+}
+
+/// Take-profit step of an attached plan
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrancheState {
+    pub profit_pct: Decimal,
+    pub trigger_price: Decimal,
+    pub quantity: Decimal,
+    pub status: ExitStatus,
+}
+
+/// Trailing stop of an attached plan
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrailState {
+    pub callback_pct: Decimal,
+    /// Price the stop starts tracking at
+    pub activation_price: Decimal,
+    pub quantity: Decimal,
+    /// Best price since activation (locally managed plans only)
+    pub peak: Option<Decimal>,
+    /// Current stop level (locally managed plans only)
+    pub stop_price: Option<Decimal>,
+    pub status: ExitStatus,
+}
+
+/// Scale-out plan attached to an open position
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PositionPlan {
+    pub strategy_id: Uuid,
+    pub symbol: Symbol,
+    /// `Long` or `Short`
+    pub side: PositionSide,
+    pub entry_price: Decimal,
+    /// Position size when the plan was attached
+    pub quantity: Decimal,
+    pub management: PlanManagement,
+    pub tranches: Vec<TrancheState>,
+    pub trail: Option<TrailState>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl PositionPlan {
+    fn new(
+        strategy_id: Uuid,
+        symbol: Symbol,
+        plan: &ScaleOutPlan,
+        entry_price: Decimal,
+        position: Decimal,
+    ) -> Self {
+        let side = if position > Decimal::ZERO {
+            PositionSide::Long
+        } else {
+            PositionSide::Short
+        };
+        let quantity = position.abs();
+        let price_at = |pct: Decimal| {
+            let move_ = entry_price * pct / Decimal::ONE_HUNDRED;
+            match side {
+                PositionSide::Short => entry_price - move_,
+                _ => entry_price + move_,
+            }
+        };
+
+        let tranches: Vec<TrancheState> = plan
+            .targets
+            .iter()
+            .map(|target| TrancheState {
+                profit_pct: target.profit_pct,
+                trigger_price: price_at(target.profit_pct),
+                quantity: quantity * target.fraction,
+                status: ExitStatus::Pending,
+            })
+            .collect();
+        let trail = plan.trail.as_ref().map(|trail| TrailState {
+            callback_pct: trail.callback_pct,
+            activation_price: tranches
+                .last()
+                .map_or(entry_price, |tranche| tranche.trigger_price),
+            quantity: quantity - tranches.iter().map(|t| t.quantity).sum::<Decimal>(),
+            peak: None,
+            stop_price: None,
+            status: ExitStatus::Pending,
+        });
+
+        Self {
+            strategy_id,
+            symbol,
+            side,
+            entry_price,
+            quantity,
+            management: PlanManagement::Local,
+            tranches,
+            trail,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Size the plan has not closed yet
+    pub fn remaining(&self) -> Decimal {
+        let tranches = self.tranches.iter().filter(|t| !t.status.is_triggered());
+        let trail = self.trail.iter().filter(|t| !t.status.is_triggered());
+        tranches.map(|t| t.quantity).sum::<Decimal>() + trail.map(|t| t.quantity).sum::<Decimal>()
+    }
+
+    /// Whether every exit has triggered
+    pub fn is_complete(&self) -> bool {
+        self.tranches.iter().all(|t| t.status.is_triggered())
+            && self.trail.as_ref().is_none_or(|t| t.status.is_triggered())
+    }
+
+    /// Side of the orders that close the position
+    pub fn exit_side(&self) -> OrderSide {
+        match self.side {
+            PositionSide::Short => OrderSide::Buy,
+            _ => OrderSide::Sell,
+        }
+    }
+
+    /// Whether `price` is at or beyond `level` in the position's favour
+    fn reached(&self, price: Decimal, level: Decimal) -> bool {
+        match self.side {
+            PositionSide::Short => price <= level,
+            _ => price >= level,
+        }
+    }
+
+    fn algo_ids(&self) -> Vec<String> {
+        let tranches = self.tranches.iter().map(|t| &t.status);
+        tranches
+            .chain(self.trail.iter().map(|t| &t.status))
+            .filter_map(|status| status.algo_id().map(str::to_string))
+            .collect()
+    }
+
+    fn exit_order(&self, quantity: Decimal) -> Result<Order> {
+        Ok(Order::new(
+            self.strategy_id,
+            self.symbol.clone(),
+            self.exit_side(),
+            OrderType::Market,
+            Quantity::new(quantity)?,
+            None,
+        )
+        .with_reduce_only())
+    }
+
+    /// Trigger the exits `price` reached, returning their orders
+    ///
+    /// Exits whose order cannot be built stay pending.
+    fn on_price(&mut self, price: Decimal) -> Vec<Order> {
+        let now = Utc::now();
+        let mut orders = Vec::new();
+
+        for i in 0..self.tranches.len() {
+            let tranche = &self.tranches[i];
+            if tranche.status != ExitStatus::Pending || !self.reached(price, tranche.trigger_price)
+            {
+                continue;
+            }
+            match self.exit_order(tranche.quantity) {
+                Ok(order) => {
+                    self.tranches[i].status = ExitStatus::Sending {
+                        order_id: order.id,
+                        price,
+                        at: now,
+                    };
+                    orders.push(order);
+                }
+                Err(e) => warn!("Cannot build {} exit: {}", self.symbol.as_str(), e),
+            }
+        }
+
+        let side = self.side;
+        let Some(trail) = self
+            .trail
+            .as_ref()
+            .filter(|t| t.status == ExitStatus::Pending)
+        else {
+            return orders;
+        };
+        let better = |a: Decimal, b: Decimal| match side {
+            PositionSide::Short => a < b,
+            _ => a > b,
+        };
+        let peak = match trail.peak {
+            Some(peak) if !better(price, peak) => peak,
+            Some(_) => price,
+            None if !better(trail.activation_price, price) => price,
+            None => return orders,
+        };
+        let offset = peak * trail.callback_pct / Decimal::ONE_HUNDRED;
+        let stop = match side {
+            PositionSide::Short => peak + offset,
+            _ => peak - offset,
+        };
+        let stopped = !better(price, stop);
+        let exit = stopped.then(|| self.exit_order(trail.quantity));
+
+        let Some(trail) = self.trail.as_mut() else {
+            return orders;
+        };
+        trail.peak = Some(peak);
+        trail.stop_price = Some(stop);
+        match exit {
+            Some(Ok(order)) => {
+                trail.status = ExitStatus::Sending {
+                    order_id: order.id,
+                    price,
+                    at: now,
+                };
+                orders.push(order);
+            }
+            Some(Err(e)) => warn!("Cannot build {} trailing exit: {}", self.symbol.as_str(), e),
+            None => {}
+        }
+        orders
+    }
+
+    /// Status of the exit waiting on order `order_id`
+    fn sending_exit(&mut self, order_id: Uuid) -> Option<&mut ExitStatus> {
+        let statuses = self.tranches.iter_mut().map(|t| &mut t.status);
+        statuses
+            .chain(self.trail.iter_mut().map(|t| &mut t.status))
+            .find(|status| status.sending(order_id))
+    }
+}
+
+/// Exchange that can hold exits as resting algo orders
+#[async_trait]
+pub trait AlgoOrderVenue: Send + Sync {
+    /// Rest a reduce-only market exit of `quantity` that fires once
+    /// `trigger_price` trades; returns the algo order ID
+    async fn place_take_profit(
+        &self,
+        symbol: &Symbol,
+        side: OrderSide,
+        quantity: Decimal,
+        trigger_price: Decimal,
+    ) -> Result<String>;
+
+    /// Rest a reduce-only trailing stop that starts tracking at
+    /// `activation_price`; returns the algo order ID
+    async fn place_trailing_stop(
+        &self,
+        symbol: &Symbol,
+        side: OrderSide,
+        quantity: Decimal,
+        callback_pct: Decimal,
+        activation_price: Decimal,
+    ) -> Result<String>;
+
+    /// Cancel resting algo orders on `symbol`
+    async fn cancel(&self, symbol: &Symbol, algo_ids: &[String]) -> Result<()>;
+}
+
+/// OKX algo orders (`conditional` take profits and `move_order_stop`)
+pub struct OkxAlgoVenue {
+    client: Arc<OkxRestClient>,
+    trade_mode: TradeMode,
+}
+
+impl OkxAlgoVenue {
+    pub fn new(client: Arc<OkxRestClient>, trade_mode: TradeMode) -> Self {
+        Self { client, trade_mode }
+    }
+
+    fn request(&self, symbol: &Symbol, side: OrderSide, quantity: Decimal) -> AlgoOrderRequest {
+        AlgoOrderRequest {
+            inst_id: symbol.as_str().to_string(),
+            td_mode: self.trade_mode.as_str().to_string(),
+            side: match side {
+                OrderSide::Buy => "buy",
+                OrderSide::Sell => "sell",
+            }
+            .to_string(),
+            sz: quantity.normalize().to_string(),
+            reduce_only: Some(true),
+            ..Default::default()
+        }
+    }
+}
+
+#[async_trait]
+impl AlgoOrderVenue for OkxAlgoVenue {
+    async fn place_take_profit(
+        &self,
+        symbol: &Symbol,
+        side: OrderSide,
+        quantity: Decimal,
+        trigger_price: Decimal,
+    ) -> Result<String> {
+        let request = AlgoOrderRequest {
+            ord_type: "conditional".to_string(),
+            tp_trigger_px: Some(trigger_price.normalize().to_string()),
+            tp_ord_px: Some("-1".to_string()),
+            ..self.request(symbol, side, quantity)
+        };
+        Ok(self.client.place_algo_order(&request).await?.algo_id)
+    }
+
+    async fn place_trailing_stop(
+        &self,
+        symbol: &Symbol,
+        side: OrderSide,
+        quantity: Decimal,
+        callback_pct: Decimal,
+        activation_price: Decimal,
+    ) -> Result<String> {
+        let request = AlgoOrderRequest {
+            ord_type: "move_order_stop".to_string(),
+            callback_ratio: Some(
+                (callback_pct / Decimal::ONE_HUNDRED)
+                    .normalize()
+                    .to_string(),
+            ),
+            active_px: Some(activation_price.normalize().to_string()),
+            ..self.request(symbol, side, quantity)
+        };
+        Ok(self.client.place_algo_order(&request).await?.algo_id)
+    }
+
+    async fn cancel(&self, symbol: &Symbol, algo_ids: &[String]) -> Result<()> {
+        let requests: Vec<CancelAlgoOrderRequest> = algo_ids
+            .iter()
+            .map(|algo_id| CancelAlgoOrderRequest {
+                inst_id: symbol.as_str().to_string(),
+                algo_id: algo_id.clone(),
+                ..Default::default()
+            })
+            .collect();
+        self.client.cancel_algo_orders(&requests).await?;
+        Ok(())
+    }
+}
+
+/// Scale-out plans of open positions, one per strategy and symbol
+#[derive(Default)]
+pub struct ScaleOutManager {
+    venue: Option<Arc<dyn AlgoOrderVenue>>,
+    plans: RwLock<HashMap<(Uuid, Symbol), PositionPlan>>,
+}
+
+impl ScaleOutManager {
+    /// Manager that watches every plan locally
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rest derivative exits on `venue` instead of watching them locally
+    pub fn with_venue(mut self, venue: Arc<dyn AlgoOrderVenue>) -> Self {
+        self.venue = Some(venue);
+        self
+    }
+
+    /// Attach `plan` to the strategy's position of net size `position`
+    /// (positive long, negative short) opened at `entry_price`
+    ///
+    /// Replaces any plan already on the position, cancelling its resting
+    /// orders. If the exits cannot all be placed on the exchange, the ones
+    /// that were are cancelled and the plan is managed locally.
+    pub async fn attach(
+        &self,
+        strategy_id: Uuid,
+        symbol: Symbol,
+        plan: &ScaleOutPlan,
+        entry_price: Decimal,
+        position: Decimal,
+    ) -> Result<PositionPlan> {
+        plan.validate()?;
+        if position.is_zero() {
+            return Err(Error::InvalidPlan(format!(
+                "no open {} position",
+                symbol.as_str()
+            )));
+        }
+        if entry_price <= Decimal::ZERO {
+            return Err(Error::InvalidPlan(format!(
+                "invalid entry price {}",
+                entry_price
+            )));
+        }
+
+        self.detach(strategy_id, &symbol).await?;
+        let mut state = PositionPlan::new(strategy_id, symbol, plan, entry_price, position);
+        if let Some(venue) = self.venue.as_ref().filter(|_| state.symbol.is_derivative()) {
+            match rest_exits(venue.as_ref(), &mut state).await {
+                Ok(()) => state.management = PlanManagement::Exchange,
+                Err(e) => warn!(
+                    "Managing {} scale-out locally, resting orders failed: {}",
+                    state.symbol.as_str(),
+                    e
+                ),
+            }
+        }
+
+        info!(
+            "Attached scale-out plan to {} {:?} ({} tranches, {:?})",
+            state.symbol.as_str(),
+            state.side,
+            state.tranches.len(),
+            state.management
+        );
+        self.plans
+            .write()
+            .insert((strategy_id, state.symbol.clone()), state.clone());
+        Ok(state)
+    }
+
+    /// Remove the position's plan, cancelling its resting orders
+    pub async fn detach(&self, strategy_id: Uuid, symbol: &Symbol) -> Result<Option<PositionPlan>> {
+        let Some(plan) = self.plans.write().remove(&(strategy_id, symbol.clone())) else {
+            return Ok(None);
+        };
+        let algo_ids = plan.algo_ids();
+        if let Some(venue) = &self.venue
+            && !algo_ids.is_empty()
+        {
+            venue.cancel(symbol, &algo_ids).await?;
+        }
+        Ok(Some(plan))
+    }
+
+    /// Check locally managed plans on `symbol` against a new price
+    ///
+    /// Returns reduce-only market orders for the exits the price reached.
+    /// Their exits are not watched again until the outcome of every order
+    /// is reported to [`Self::exit_filled`] or [`Self::exit_failed`].
+    pub fn on_price(&self, symbol: &Symbol, price: Decimal) -> Vec<Order> {
+        let mut plans = self.plans.write();
+        plans
+            .values_mut()
+            .filter(|plan| plan.symbol == *symbol && plan.management == PlanManagement::Local)
+            .flat_map(|plan| plan.on_price(price))
+            .collect()
+    }
+
+    /// Record that exit order `order_id` from [`Self::on_price`] executed
+    ///
+    /// Completed plans are removed. Returns whether the order belonged to a
+    /// plan.
+    pub fn exit_filled(&self, order_id: Uuid) -> bool {
+        let mut plans = self.plans.write();
+        let found = plans
+            .values_mut()
+            .any(|plan| match plan.sending_exit(order_id) {
+                Some(status) => {
+                    if let ExitStatus::Sending { price, at, .. } = *status {
+                        *status = ExitStatus::Triggered { price, at };
+                    }
+                    true
+                }
+                None => false,
+            });
+        plans.retain(|_, plan| !plan.is_complete());
+        found
+    }
+
+    /// Record that exit order `order_id` from [`Self::on_price`] did not
+    /// execute, so its exit fires again on the next price that reaches it
+    pub fn exit_failed(&self, order_id: Uuid) -> bool {
+        self.plans
+            .write()
+            .values_mut()
+            .any(|plan| match plan.sending_exit(order_id) {
+                Some(status) => {
+                    *status = ExitStatus::Pending;
+                    true
+                }
+                None => false,
+            })
+    }
+
+    /// Record that the exchange triggered a resting exit
+    ///
+    /// Returns whether the algo order belonged to a plan.
+    pub fn on_algo_triggered(&self, algo_id: &str, price: Decimal) -> bool {
+        let mut plans = self.plans.write();
+        let triggered = ExitStatus::Triggered {
+            price,
+            at: Utc::now(),
+        };
+        let found = plans.values_mut().any(|plan| {
+            let statuses = plan.tranches.iter_mut().map(|t| &mut t.status);
+            match statuses
+                .chain(plan.trail.iter_mut().map(|t| &mut t.status))
+                .find(|status| status.algo_id() == Some(algo_id))
+            {
+                Some(status) => {
+                    *status = triggered.clone();
+                    true
+                }
+                None => false,
+            }
+        });
+        plans.retain(|_, plan| !plan.is_complete());
+        found
+    }
+
+    /// Plan attached to the strategy's position, if any
+    pub fn plan(&self, strategy_id: Uuid, symbol: &Symbol) -> Option<PositionPlan> {
+        self.plans
+            .read()
+            .get(&(strategy_id, symbol.clone()))
+            .cloned()
+    }
+
+    /// Every attached plan
+    pub fn plans(&self) -> Vec<PositionPlan> {
+        self.plans.read().values().cloned().collect()
+    }
+}
+
+/// Place every exit of `plan` on `venue`, cancelling them all if one fails
+async fn rest_exits(venue: &dyn AlgoOrderVenue, plan: &mut PositionPlan) -> Result<()> {
+    let side = plan.exit_side();
+    let mut placed = Vec::new();
+    let mut result = Ok(());
+
+    for tranche in &plan.tranches {
+        match venue
+            .place_take_profit(&plan.symbol, side, tranche.quantity, tranche.trigger_price)
+            .await
+        {
+            Ok(algo_id) => placed.push(algo_id),
+            Err(e) => {
+                result = Err(e);
+                break;
+            }
+        }
+    }
+    if result.is_ok()
+        && let Some(trail) = &plan.trail
+    {
+        match venue
+            .place_trailing_stop(
+                &plan.symbol,
+                side,
+                trail.quantity,
+                trail.callback_pct,
+                trail.activation_price,
+            )
+            .await
+        {
+            Ok(algo_id) => placed.push(algo_id),
+            Err(e) => result = Err(e),
+        }
+    }
+
+    if let Err(e) = result {
+        if !placed.is_empty()
+            && let Err(cancel) = venue.cancel(&plan.symbol, &placed).await
+        {
+            warn!(
+                "Failed to cancel partially placed scale-out orders: {}",
+                cancel
+            );
+        }
+        return Err(e);
+    }
+
+    let statuses = plan.tranches.iter_mut().map(|t| &mut t.status);
+    for (status, algo_id) in statuses
+        .chain(plan.trail.iter_mut().map(|t| &mut t.status))
+        .zip(placed)
+    {
+        *status = ExitStatus::Resting { algo_id };
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+    use rust_decimal_macros::dec;
+
+    fn half_quarter_trail() -> ScaleOutPlan {
+        ScaleOutPlan {
+            targets: vec![
+                ProfitTarget {
+                    profit_pct: dec!(2),
+                    fraction: dec!(0.5),
+                },
+                ProfitTarget {
+                    profit_pct: dec!(4),
+                    fraction: dec!(0.25),
+                },
+            ],
+            trail: Some(TrailingExit {
+                callback_pct: dec!(1),
+            }),
+        }
+    }
+
+    fn swap() -> Symbol {
+        Symbol::new("BTC-USDT-SWAP").unwrap()
+    }
+
+    /// Records placements, failing the `fail_at`th one
+    #[derive(Default)]
+    struct RecordingVenue {
+        placed: Mutex<Vec<(OrderSide, Decimal, Decimal)>>,
+        cancelled: Mutex<Vec<String>>,
+        fail_at: Option<usize>,
+    }
+
+    impl RecordingVenue {
+        fn place(&self, side: OrderSide, quantity: Decimal, price: Decimal) -> Result<String> {
+            let mut placed = self.placed.lock();
+            if self.fail_at == Some(placed.len()) {
+                return Err(Error::ExecutionError("rejected".to_string()));
+            }
+            placed.push((side, quantity, price));
+            Ok(format!("algo-{}", placed.len()))
+        }
+    }
+
+    #[async_trait]
+    impl AlgoOrderVenue for RecordingVenue {
+        async fn place_take_profit(
+            &self,
+            _symbol: &Symbol,
+            side: OrderSide,
+            quantity: Decimal,
+            trigger_price: Decimal,
+        ) -> Result<String> {
+            self.place(side, quantity, trigger_price)
+        }
+
+        async fn place_trailing_stop(
+            &self,
+            _symbol: &Symbol,
+            side: OrderSide,
+            quantity: Decimal,
+            _callback_pct: Decimal,
+            activation_price: Decimal,
+        ) -> Result<String> {
+            self.place(side, quantity, activation_price)
+        }
+
+        async fn cancel(&self, _symbol: &Symbol, algo_ids: &[String]) -> Result<()> {
+            self.cancelled.lock().extend(algo_ids.iter().cloned());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(half_quarter_trail().validate().is_ok());
+        assert!(ScaleOutPlan::default().validate().is_err());
+
+        let mut descending = half_quarter_trail();
+        descending.targets.reverse();
+        assert!(descending.validate().is_err());
+
+        let mut everything = half_quarter_trail();
+        everything.targets[1].fraction = dec!(0.5);
+        assert!(everything.validate().is_err());
+        everything.trail = None;
+        assert!(everything.validate().is_ok());
+
+        everything.targets[1].fraction = dec!(0.6);
+        assert!(everything.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_local_long_plan_scales_out_then_trails() {
+        let manager = ScaleOutManager::new();
+        let strategy_id = Uuid::new_v4();
+        let symbol = Symbol::new("BTC-USDT").unwrap();
+        let plan = manager
+            .attach(
+                strategy_id,
+                symbol.clone(),
+                &half_quarter_trail(),
+                dec!(100),
+                dec!(2),
+            )
+            .await
+            .unwrap();
+        assert_eq!(plan.management, PlanManagement::Local);
+        assert_eq!(plan.tranches[0].trigger_price, dec!(102));
+        assert_eq!(plan.tranches[1].quantity, dec!(0.5));
+        let trail = plan.trail.as_ref().unwrap();
+        assert_eq!(trail.activation_price, dec!(104));
+        assert_eq!(trail.quantity, dec!(0.5));
+
+        assert!(manager.on_price(&symbol, dec!(101)).is_empty());
+        let first = manager.on_price(&symbol, dec!(102.5));
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].side, OrderSide::Sell);
+        assert!(first[0].reduce_only);
+        assert_eq!(first[0].quantity.as_decimal(), dec!(1));
+        assert!(manager.exit_filled(first[0].id));
+        assert!(manager.on_price(&symbol, dec!(103)).is_empty());
+
+        // Second target fires and starts the trail at the same price
+        let second = manager.on_price(&symbol, dec!(105));
+        assert_eq!(second.len(), 1);
+        assert!(manager.exit_filled(second[0].id));
+        let state = manager.plan(strategy_id, &symbol).unwrap();
+        assert_eq!(state.remaining(), dec!(0.5));
+        assert_eq!(state.trail.as_ref().unwrap().stop_price, Some(dec!(103.95)));
+
+        assert!(manager.on_price(&symbol, dec!(110)).is_empty());
+        assert!(manager.on_price(&symbol, dec!(109)).is_empty());
+        let stopped = manager.on_price(&symbol, dec!(108.8));
+        assert_eq!(stopped.len(), 1);
+        assert_eq!(stopped[0].quantity.as_decimal(), dec!(0.5));
+        assert!(manager.plan(strategy_id, &symbol).is_some());
+        assert!(manager.exit_filled(stopped[0].id));
+
+        // Fully closed plans are dropped
+        assert!(manager.plan(strategy_id, &symbol).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_exits_only_complete_once_executed() {
+        let manager = ScaleOutManager::new();
+        let strategy_id = Uuid::new_v4();
+        let symbol = Symbol::new("BTC-USDT").unwrap();
+        manager
+            .attach(
+                strategy_id,
+                symbol.clone(),
+                &half_quarter_trail(),
+                dec!(100),
+                dec!(2),
+            )
+            .await
+            .unwrap();
+
+        // Both targets reached at once, neither fires twice while in flight
+        let orders = manager.on_price(&symbol, dec!(104));
+        assert_eq!(orders.len(), 2);
+        assert!(manager.on_price(&symbol, dec!(104.5)).is_empty());
+        assert_eq!(
+            manager.plan(strategy_id, &symbol).unwrap().remaining(),
+            dec!(2)
+        );
+
+        // One executes, the other is refused and is watched again
+        assert!(manager.exit_filled(orders[0].id));
+        assert!(manager.exit_failed(orders[1].id));
+        let state = manager.plan(strategy_id, &symbol).unwrap();
+        assert_eq!(state.remaining(), dec!(1));
+        assert_eq!(state.tranches[1].status, ExitStatus::Pending);
+
+        let retry = manager.on_price(&symbol, dec!(104.2));
+        assert_eq!(retry.len(), 1);
+        assert_eq!(retry[0].quantity.as_decimal(), dec!(0.5));
+        assert!(!manager.exit_failed(Uuid::new_v4()));
+    }
+
+    #[tokio::test]
+    async fn test_short_targets_are_below_entry() {
+        let manager = ScaleOutManager::new();
+        let symbol = swap();
+        let plan = manager
+            .attach(
+                Uuid::new_v4(),
+                symbol.clone(),
+                &half_quarter_trail(),
+                dec!(100),
+                dec!(-4),
+            )
+            .await
+            .unwrap();
+        assert_eq!(plan.side, PositionSide::Short);
+        assert_eq!(plan.tranches[0].trigger_price, dec!(98));
+
+        assert!(manager.on_price(&symbol, dec!(99)).is_empty());
+        let orders = manager.on_price(&symbol, dec!(97));
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].side, OrderSide::Buy);
+        assert_eq!(orders[0].quantity.as_decimal(), dec!(2));
+    }
+
+    #[tokio::test]
+    async fn test_derivative_plans_rest_on_the_venue() {
+        let venue = Arc::new(RecordingVenue::default());
+        let manager = ScaleOutManager::new().with_venue(venue.clone());
+        let strategy_id = Uuid::new_v4();
+        let symbol = swap();
+
+        let plan = manager
+            .attach(
+                strategy_id,
+                symbol.clone(),
+                &half_quarter_trail(),
+                dec!(100),
+                dec!(2),
+            )
+            .await
+            .unwrap();
+        assert_eq!(plan.management, PlanManagement::Exchange);
+        assert_eq!(
+            plan.tranches[0].status,
+            ExitStatus::Resting {
+                algo_id: "algo-1".to_string()
+            }
+        );
+        assert_eq!(
+            venue.placed.lock()[2],
+            (OrderSide::Sell, dec!(0.5), dec!(104))
+        );
+
+        // The exchange watches prices, not us
+        assert!(manager.on_price(&symbol, dec!(103)).is_empty());
+        assert!(manager.on_algo_triggered("algo-1", dec!(102)));
+        assert!(!manager.on_algo_triggered("unknown", dec!(102)));
+        assert_eq!(
+            manager.plan(strategy_id, &symbol).unwrap().remaining(),
+            dec!(1)
+        );
+
+        manager.detach(strategy_id, &symbol).await.unwrap();
+        assert_eq!(*venue.cancelled.lock(), vec!["algo-2", "algo-3"]);
+
+        // Spot positions are always managed locally
+        let spot = manager
+            .attach(
+                strategy_id,
+                Symbol::new("BTC-USDT").unwrap(),
+                &half_quarter_trail(),
+                dec!(100),
+                dec!(2),
+            )
+            .await
+            .unwrap();
+        assert_eq!(spot.management, PlanManagement::Local);
+    }
+
+    #[tokio::test]
+    async fn test_failed_placement_falls_back_to_local() {
+        let venue = Arc::new(RecordingVenue {
+            fail_at: Some(1),
+            ..Default::default()
+        });
+        let manager = ScaleOutManager::new().with_venue(venue.clone());
+        let plan = manager
+            .attach(
+                Uuid::new_v4(),
+                swap(),
+                &half_quarter_trail(),
+                dec!(100),
+                dec!(2),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(plan.management, PlanManagement::Local);
+        assert!(
+            plan.tranches
+                .iter()
+                .all(|t| t.status == ExitStatus::Pending)
+        );
+        assert_eq!(*venue.cancelled.lock(), vec!["algo-1"]);
+    }
+}
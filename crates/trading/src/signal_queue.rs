@@ -0,0 +1,318 @@
+//! Bounded priority queue for execution signals
+//!
+//! Signals are served most urgent first: risk management, then stop-loss and
+//! take-profit exits, then closes, then opens, in arrival order within a
+//! priority. The queue is bounded; when it is full an incoming signal evicts
+//! the newest queued signal of a lower priority, and is refused if there is
+//! none, so producers see backpressure instead of an ever-growing backlog.
+//! Open signals that waited longer than the configured age are discarded
+//! when dequeued rather than traded on a stale view of the market.
+
+use crate::error::{Error, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BinaryHeap};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+use tracing::warn;
+
+/// Urgency of a queued signal, least urgent first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignalPriority {
+    /// Opening or adding to a position
+    Open,
+    /// Closing a position
+    Close,
+    /// Stop-loss or take-profit exit
+    Exit,
+    /// Emergency risk-management close
+    RiskManagement,
+}
+
+/// Queue configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignalQueueConfig {
+    /// Maximum number of queued signals
+    pub capacity: usize,
+
+    /// Open signals older than this are discarded instead of executed
+    pub max_open_age_secs: u64,
+}
+
+impl Default for SignalQueueConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 1000,
+            max_open_age_secs: 5,
+        }
+    }
+}
+
+/// Queue depth and throughput counters
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignalQueueMetrics {
+    pub depth: usize,
+    pub depth_by_priority: BTreeMap<SignalPriority, usize>,
+
+    /// Deepest the queue has been
+    pub high_water_mark: usize,
+
+    /// Age of the oldest queued signal
+    pub oldest_age_ms: u64,
+
+    pub enqueued: u64,
+    pub dequeued: u64,
+
+    /// Open signals discarded for waiting too long
+    pub dropped_stale: u64,
+
+    /// Signals pushed out by a more urgent one while full
+    pub evicted: u64,
+
+    /// Signals refused while full
+    pub rejected_full: u64,
+}
+
+struct Entry<T> {
+    priority: SignalPriority,
+    seq: u64,
+    enqueued_at: Instant,
+    item: T,
+}
+
+impl<T> PartialEq for Entry<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for Entry<T> {}
+
+impl<T> PartialOrd for Entry<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Entry<T> {
+    /// Higher priority first, then earlier arrival
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+struct Inner<T> {
+    heap: BinaryHeap<Entry<T>>,
+    next_seq: u64,
+    metrics: SignalQueueMetrics,
+}
+
+/// Bounded, prioritized signal queue shared by producers and one consumer
+pub struct SignalQueue<T> {
+    config: SignalQueueConfig,
+    inner: Mutex<Inner<T>>,
+    notify: Notify,
+}
+
+impl<T> SignalQueue<T> {
+    pub fn new(config: SignalQueueConfig) -> Self {
+        Self {
+            config,
+            inner: Mutex::new(Inner {
+                heap: BinaryHeap::new(),
+                next_seq: 0,
+                metrics: SignalQueueMetrics::default(),
+            }),
+            notify: Notify::new(),
+        }
+    }
+
+    pub fn config(&self) -> &SignalQueueConfig {
+        &self.config
+    }
+
+    /// Queue `item`, evicting the newest less urgent signal if full
+    pub fn push(&self, priority: SignalPriority, item: T) -> Result<()> {
+        {
+            let mut inner = self.inner.lock();
+            if inner.heap.len() >= self.config.capacity.max(1) {
+                let victim = inner
+                    .heap
+                    .iter()
+                    .filter(|e| e.priority < priority)
+                    .min_by(|a, b| a.priority.cmp(&b.priority).then(b.seq.cmp(&a.seq)))
+                    .map(|e| (e.seq, e.priority));
+                let Some((victim, evicted)) = victim else {
+                    inner.metrics.rejected_full += 1;
+                    return Err(Error::QueueFull(format!(
+                        "{} signals queued, none less urgent than {:?}",
+                        inner.heap.len(),
+                        priority
+                    )));
+                };
+                inner.heap.retain(|e| e.seq != victim);
+                inner.metrics.evicted += 1;
+                warn!(
+                    "Signal queue full: evicted {:?} signal for {:?}",
+                    evicted, priority
+                );
+            }
+
+            let seq = inner.next_seq;
+            inner.next_seq += 1;
+            inner.heap.push(Entry {
+                priority,
+                seq,
+                enqueued_at: Instant::now(),
+                item,
+            });
+            inner.metrics.enqueued += 1;
+            inner.metrics.high_water_mark = inner.metrics.high_water_mark.max(inner.heap.len());
+        }
+        self.notify.notify_one();
+        Ok(())
+    }
+
+    /// Most urgent signal, skipping stale opens; `None` if empty
+    pub fn try_pop(&self) -> Option<T> {
+        let max_open_age = Duration::from_secs(self.config.max_open_age_secs);
+        let mut inner = self.inner.lock();
+        while let Some(entry) = inner.heap.pop() {
+            if entry.priority == SignalPriority::Open && entry.enqueued_at.elapsed() > max_open_age
+            {
+                inner.metrics.dropped_stale += 1;
+                continue;
+            }
+            inner.metrics.dequeued += 1;
+            return Some(entry.item);
+        }
+        None
+    }
+
+    /// Wait for the next signal
+    pub async fn pop(&self) -> T {
+        loop {
+            let notified = self.notify.notified();
+            if let Some(item) = self.try_pop() {
+                return item;
+            }
+            notified.await;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.lock().heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn metrics(&self) -> SignalQueueMetrics {
+        let inner = self.inner.lock();
+        let mut metrics = inner.metrics.clone();
+        metrics.depth = inner.heap.len();
+        for entry in &inner.heap {
+            *metrics.depth_by_priority.entry(entry.priority).or_default() += 1;
+        }
+        metrics.oldest_age_ms = inner
+            .heap
+            .iter()
+            .map(|e| e.enqueued_at.elapsed().as_millis() as u64)
+            .max()
+            .unwrap_or(0);
+        metrics
+    }
+}
+
+impl<T> Default for SignalQueue<T> {
+    fn default() -> Self {
+        Self::new(SignalQueueConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_most_urgent_first_then_fifo() {
+        let queue = SignalQueue::default();
+        queue.push(SignalPriority::Open, "open-1").unwrap();
+        queue.push(SignalPriority::Close, "close").unwrap();
+        queue.push(SignalPriority::Open, "open-2").unwrap();
+        queue.push(SignalPriority::RiskManagement, "risk").unwrap();
+        queue.push(SignalPriority::Exit, "stop-loss").unwrap();
+
+        let order: Vec<_> = std::iter::from_fn(|| queue.try_pop()).collect();
+        assert_eq!(
+            order,
+            vec!["risk", "stop-loss", "close", "open-1", "open-2"]
+        );
+    }
+
+    #[test]
+    fn test_full_queue_evicts_less_urgent_or_refuses() {
+        let queue = SignalQueue::new(SignalQueueConfig {
+            capacity: 2,
+            ..Default::default()
+        });
+        queue.push(SignalPriority::Open, "open-1").unwrap();
+        queue.push(SignalPriority::Open, "open-2").unwrap();
+
+        assert!(matches!(
+            queue.push(SignalPriority::Open, "open-3"),
+            Err(Error::QueueFull(_))
+        ));
+        queue.push(SignalPriority::RiskManagement, "risk").unwrap();
+
+        let metrics = queue.metrics();
+        assert_eq!(metrics.depth, 2);
+        assert_eq!(metrics.rejected_full, 1);
+        assert_eq!(metrics.evicted, 1);
+        assert_eq!(metrics.high_water_mark, 2);
+        assert_eq!(
+            metrics
+                .depth_by_priority
+                .get(&SignalPriority::RiskManagement),
+            Some(&1)
+        );
+
+        // The newest open signal made room
+        assert_eq!(queue.try_pop(), Some("risk"));
+        assert_eq!(queue.try_pop(), Some("open-1"));
+    }
+
+    #[test]
+    fn test_stale_open_signals_are_discarded() {
+        let queue = SignalQueue::new(SignalQueueConfig {
+            max_open_age_secs: 0,
+            ..Default::default()
+        });
+        queue.push(SignalPriority::Open, "open").unwrap();
+        queue.push(SignalPriority::Close, "close").unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+
+        // Only opens expire
+        assert_eq!(queue.try_pop(), Some("close"));
+        assert_eq!(queue.try_pop(), None);
+        assert_eq!(queue.metrics().dropped_stale, 1);
+    }
+
+    #[tokio::test]
+    async fn test_pop_waits_for_producer() {
+        let queue = Arc::new(SignalQueue::default());
+        let consumer = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.pop().await }
+        });
+        tokio::task::yield_now().await;
+        queue.push(SignalPriority::Close, 7).unwrap();
+        assert_eq!(consumer.await.unwrap(), 7);
+    }
+}
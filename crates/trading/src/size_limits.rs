@@ -0,0 +1,359 @@
+//! Pre-trade size limits from the exchange
+//!
+//! OKX rejects orders larger than the account can currently open
+//! (`max-avail-size`) or than the instrument allows at the current price
+//! (`max-size`). [`SizeLimitGuard`] queries both, caches them briefly per
+//! instrument and trade mode, and clamps or rejects oversized orders before
+//! they are sent so the caller learns the cap instead of getting a bounce.
+
+use crate::error::{Error, Result};
+use crate::retry_advisor::round_down;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use ea_okx_client::OkxRestClient;
+use ea_okx_client::models::{MaxAvailSizeData, MaxSizeData, MaxSizeRequest};
+use ea_okx_core::Symbol;
+use ea_okx_core::models::{Order, OrderSide};
+use ea_okx_core::types::Quantity;
+use parking_lot::RwLock;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::warn;
+
+/// OKX trade mode (`tdMode`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TradeMode {
+    Cash,
+    Cross,
+    Isolated,
+}
+
+impl TradeMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TradeMode::Cash => "cash",
+            TradeMode::Cross => "cross",
+            TradeMode::Isolated => "isolated",
+        }
+    }
+}
+
+/// Size limits for one instrument and trade mode
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SizeLimits {
+    pub max_buy: Option<Decimal>,
+    pub max_sell: Option<Decimal>,
+    pub avail_buy: Option<Decimal>,
+    pub avail_sell: Option<Decimal>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+impl SizeLimits {
+    /// Combine the `max-size` and `max-avail-size` responses
+    pub fn from_okx(max: &MaxSizeData, avail: &MaxAvailSizeData) -> Result<Self> {
+        Ok(Self {
+            max_buy: parse_optional("maxBuy", &max.max_buy)?,
+            max_sell: parse_optional("maxSell", &max.max_sell)?,
+            avail_buy: parse_optional("availBuy", &avail.avail_buy)?,
+            avail_sell: parse_optional("availSell", &avail.avail_sell)?,
+            fetched_at: Utc::now(),
+        })
+    }
+
+    /// Largest order size allowed on `side`, if known
+    pub fn cap(&self, side: OrderSide) -> Option<Decimal> {
+        let (max, avail) = match side {
+            OrderSide::Buy => (self.max_buy, self.avail_buy),
+            OrderSide::Sell => (self.max_sell, self.avail_sell),
+        };
+        match (max, avail) {
+            (Some(max), Some(avail)) => Some(max.min(avail)),
+            (cap, None) | (None, cap) => cap,
+        }
+    }
+}
+
+/// Source of exchange size limits (the REST account endpoints)
+#[async_trait]
+pub trait SizeLimitSource: Send + Sync {
+    async fn fetch_size_limits(&self, symbol: &Symbol, mode: TradeMode) -> Result<SizeLimits>;
+}
+
+#[async_trait]
+impl SizeLimitSource for OkxRestClient {
+    async fn fetch_size_limits(&self, symbol: &Symbol, mode: TradeMode) -> Result<SizeLimits> {
+        let request = MaxSizeRequest {
+            inst_id: symbol.as_str().to_string(),
+            td_mode: mode.as_str().to_string(),
+            ccy: None,
+            px: None,
+        };
+        let (max, avail) =
+            tokio::try_join!(self.max_size(&request), self.max_avail_size(&request))?;
+        SizeLimits::from_okx(&max, &avail)
+    }
+}
+
+/// What to do with an order above the cap
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OversizeAction {
+    /// Reduce the order to the cap
+    Clamp,
+    /// Refuse the order
+    Reject,
+}
+
+/// Size guard configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SizeLimitConfig {
+    /// Trade mode orders are placed with
+    pub trade_mode: TradeMode,
+
+    /// How long fetched limits are reused
+    pub cache_ttl_secs: i64,
+
+    pub oversize_action: OversizeAction,
+}
+
+impl Default for SizeLimitConfig {
+    fn default() -> Self {
+        Self {
+            trade_mode: TradeMode::Cash,
+            cache_ttl_secs: 5,
+            oversize_action: OversizeAction::Clamp,
+        }
+    }
+}
+
+/// Outcome of checking an order against its size cap
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum SizeDecision {
+    /// Order fits (or no cap is known)
+    Within { cap: Option<Decimal> },
+    /// Order was reduced to `allowed`
+    Clamped {
+        requested: Decimal,
+        allowed: Decimal,
+    },
+    /// Order exceeds the cap and was not sent
+    Rejected {
+        requested: Decimal,
+        allowed: Decimal,
+    },
+}
+
+/// Caches exchange size limits and enforces them before submission
+pub struct SizeLimitGuard {
+    config: SizeLimitConfig,
+    source: Arc<dyn SizeLimitSource>,
+    cache: RwLock<HashMap<(Symbol, TradeMode), SizeLimits>>,
+}
+
+impl SizeLimitGuard {
+    pub fn new(config: SizeLimitConfig, source: Arc<dyn SizeLimitSource>) -> Self {
+        Self {
+            config,
+            source,
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Limits for `symbol`, fetched if the cached entry is missing or stale
+    pub async fn limits(&self, symbol: &Symbol) -> Result<SizeLimits> {
+        let key = (symbol.clone(), self.config.trade_mode);
+        let ttl = Duration::seconds(self.config.cache_ttl_secs);
+        if let Some(limits) = self.cache.read().get(&key)
+            && Utc::now() - limits.fetched_at < ttl
+        {
+            return Ok(limits.clone());
+        }
+
+        let limits = self
+            .source
+            .fetch_size_limits(symbol, self.config.trade_mode)
+            .await?;
+        self.cache.write().insert(key, limits.clone());
+        Ok(limits)
+    }
+
+    /// Drop cached limits for `symbol` (e.g., after a fill or a balance rejection)
+    pub fn invalidate(&self, symbol: &Symbol) {
+        self.cache.write().retain(|(cached, _), _| cached != symbol);
+    }
+
+    /// Check `order` against its cap, clamping its quantity if configured to
+    ///
+    /// A clamped size is rounded down to `lot_size`. If the limits cannot be
+    /// fetched the order is let through and the exchange remains the judge.
+    pub async fn apply(
+        &self,
+        order: &mut Order,
+        lot_size: Option<Decimal>,
+    ) -> Result<SizeDecision> {
+        let limits = match self.limits(&order.symbol).await {
+            Ok(limits) => limits,
+            Err(e) => {
+                warn!(
+                    "Size limits unavailable for {}, not capping order {}: {}",
+                    order.symbol.as_str(),
+                    order.id,
+                    e
+                );
+                return Ok(SizeDecision::Within { cap: None });
+            }
+        };
+
+        let requested = order.quantity.as_decimal();
+        let cap = match limits.cap(order.side) {
+            Some(cap) if requested > cap => cap,
+            cap => return Ok(SizeDecision::Within { cap }),
+        };
+
+        let allowed = round_down(cap, lot_size);
+        if self.config.oversize_action == OversizeAction::Reject || allowed <= Decimal::ZERO {
+            return Ok(SizeDecision::Rejected { requested, allowed });
+        }
+
+        order.quantity = Quantity::new(allowed)?;
+        Ok(SizeDecision::Clamped { requested, allowed })
+    }
+}
+
+fn parse_optional(field: &str, value: &str) -> Result<Option<Decimal>> {
+    if value.is_empty() {
+        return Ok(None);
+    }
+    value.parse().map(Some).map_err(|e| {
+        Error::ClientError(ea_okx_client::Error::ParseError(format!(
+            "Invalid {} '{}': {}",
+            field, value, e
+        )))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ea_okx_core::models::OrderType;
+    use rust_decimal_macros::dec;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use uuid::Uuid;
+
+    struct FixedLimits {
+        limits: SizeLimits,
+        fetches: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl SizeLimitSource for FixedLimits {
+        async fn fetch_size_limits(
+            &self,
+            _symbol: &Symbol,
+            _mode: TradeMode,
+        ) -> Result<SizeLimits> {
+            self.fetches.fetch_add(1, Ordering::SeqCst);
+            Ok(SizeLimits {
+                fetched_at: Utc::now(),
+                ..self.limits.clone()
+            })
+        }
+    }
+
+    fn source() -> Arc<FixedLimits> {
+        Arc::new(FixedLimits {
+            limits: SizeLimits {
+                max_buy: Some(dec!(2)),
+                max_sell: Some(dec!(5)),
+                avail_buy: Some(dec!(0.4273)),
+                avail_sell: None,
+                fetched_at: Utc::now(),
+            },
+            fetches: AtomicUsize::new(0),
+        })
+    }
+
+    fn order(side: OrderSide, qty: Decimal) -> Order {
+        Order::new(
+            Uuid::new_v4(),
+            Symbol::new("BTC-USDT").unwrap(),
+            side,
+            OrderType::Market,
+            Quantity::new(qty).unwrap(),
+            None,
+        )
+    }
+
+    #[test]
+    fn test_limits_from_okx_and_cap() {
+        let max: MaxSizeData = serde_json::from_value(serde_json::json!({
+            "instId": "BTC-USDT", "ccy": "USDT", "maxBuy": "1.5", "maxSell": "0.8"
+        }))
+        .unwrap();
+        let avail: MaxAvailSizeData = serde_json::from_value(serde_json::json!({
+            "instId": "BTC-USDT", "availBuy": "0.9", "availSell": ""
+        }))
+        .unwrap();
+
+        let limits = SizeLimits::from_okx(&max, &avail).unwrap();
+        assert_eq!(limits.cap(OrderSide::Buy), Some(dec!(0.9)));
+        assert_eq!(limits.cap(OrderSide::Sell), Some(dec!(0.8)));
+    }
+
+    #[tokio::test]
+    async fn test_clamps_to_cap_rounded_to_lot() {
+        let guard = SizeLimitGuard::new(SizeLimitConfig::default(), source());
+        let mut buy = order(OrderSide::Buy, dec!(1));
+
+        let decision = guard.apply(&mut buy, Some(dec!(0.01))).await.unwrap();
+        assert_eq!(
+            decision,
+            SizeDecision::Clamped {
+                requested: dec!(1),
+                allowed: dec!(0.42)
+            }
+        );
+        assert_eq!(buy.quantity.as_decimal(), dec!(0.42));
+
+        let mut sell = order(OrderSide::Sell, dec!(1));
+        assert_eq!(
+            guard.apply(&mut sell, None).await.unwrap(),
+            SizeDecision::Within { cap: Some(dec!(5)) }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reject_leaves_order_untouched() {
+        let config = SizeLimitConfig {
+            oversize_action: OversizeAction::Reject,
+            ..Default::default()
+        };
+        let guard = SizeLimitGuard::new(config, source());
+        let mut buy = order(OrderSide::Buy, dec!(1));
+
+        let decision = guard.apply(&mut buy, None).await.unwrap();
+        assert!(
+            matches!(decision, SizeDecision::Rejected { allowed, .. } if allowed == dec!(0.4273))
+        );
+        assert_eq!(buy.quantity.as_decimal(), dec!(1));
+    }
+
+    #[tokio::test]
+    async fn test_limits_are_cached_until_invalidated() {
+        let source = source();
+        let guard = SizeLimitGuard::new(SizeLimitConfig::default(), source.clone());
+        let symbol = Symbol::new("BTC-USDT").unwrap();
+
+        guard.limits(&symbol).await.unwrap();
+        guard.limits(&symbol).await.unwrap();
+        assert_eq!(source.fetches.load(Ordering::SeqCst), 1);
+
+        guard.invalidate(&symbol);
+        guard.limits(&symbol).await.unwrap();
+        assert_eq!(source.fetches.load(Ordering::SeqCst), 2);
+    }
+}
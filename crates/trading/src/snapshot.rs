@@ -0,0 +1,724 @@
+//! Engine state snapshots for disaster recovery
+//!
+//! A [`SnapshotScheduler`] periodically captures the full engine state
+//! (orders, positions, trades, strategy states and allocations, algorithm
+//! executions) from a [`SnapshotDataSource`] and writes it to a
+//! [`SnapshotStore`]. Every snapshot carries a schema version; loading a
+//! snapshot written by a newer build fails instead of silently dropping
+//! fields. On startup [`SnapshotStore::latest_valid`] returns the newest
+//! snapshot that still parses, so a corrupted runtime state can be rolled
+//! back to the last good point. Orders in a snapshot are only a record of
+//! what the engine believed; [`reconcile_orders`] brings the ones still
+//! open in line with the exchange before they are restored.
+
+use crate::error::{Error, Result};
+use crate::execution_store::AlgoExecution;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use ea_okx_core::models::{Order, OrderStatus, Position, StrategyStatus, Trade};
+use ea_okx_core::{ExchangeAdapter, Price, Quantity};
+use parking_lot::RwLock;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+/// Schema version written into new snapshots
+pub const SNAPSHOT_SCHEMA_VERSION: u32 = 1;
+
+/// State of one strategy at snapshot time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategySnapshot {
+    pub strategy_id: Uuid,
+    pub name: String,
+    pub status: StrategyStatus,
+    pub allocated_capital: Decimal,
+
+    /// Output of the implementation's `serialize_state`, if it was running
+    pub state: JsonValue,
+}
+
+/// Full engine state at a point in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EngineSnapshot {
+    pub schema_version: u32,
+    pub id: Uuid,
+    pub taken_at: DateTime<Utc>,
+    pub label: Option<String>,
+    pub orders: Vec<Order>,
+    pub positions: Vec<Position>,
+    pub trades: Vec<Trade>,
+    pub strategies: Vec<StrategySnapshot>,
+    pub algo_executions: Vec<AlgoExecution>,
+}
+
+impl EngineSnapshot {
+    /// Empty snapshot stamped with the current schema version and time
+    pub fn new() -> Self {
+        Self {
+            schema_version: SNAPSHOT_SCHEMA_VERSION,
+            id: Uuid::new_v4(),
+            taken_at: Utc::now(),
+            label: None,
+            orders: Vec::new(),
+            positions: Vec::new(),
+            trades: Vec::new(),
+            strategies: Vec::new(),
+            algo_executions: Vec::new(),
+        }
+    }
+
+    pub fn info(&self, size_bytes: u64) -> SnapshotInfo {
+        SnapshotInfo {
+            id: self.id,
+            taken_at: self.taken_at,
+            schema_version: self.schema_version,
+            label: self.label.clone(),
+            orders: self.orders.len(),
+            positions: self.positions.len(),
+            strategies: self.strategies.len(),
+            size_bytes,
+        }
+    }
+
+    /// Parse a stored snapshot, upgrading older schema versions
+    pub fn from_json(bytes: &[u8]) -> Result<Self> {
+        let value: JsonValue = serde_json::from_slice(bytes)?;
+        let version = value
+            .get("schema_version")
+            .and_then(JsonValue::as_u64)
+            .ok_or_else(|| Error::PersistenceError("snapshot has no schema version".to_string()))?
+            as u32;
+        Ok(serde_json::from_value(upgrade(value, version)?)?)
+    }
+}
+
+impl Default for EngineSnapshot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Bring a snapshot document up to [`SNAPSHOT_SCHEMA_VERSION`]
+///
+/// Migrations from older versions go here when the schema changes.
+fn upgrade(value: JsonValue, version: u32) -> Result<JsonValue> {
+    match version {
+        SNAPSHOT_SCHEMA_VERSION => Ok(value),
+        v if v > SNAPSHOT_SCHEMA_VERSION => Err(Error::PersistenceError(format!(
+            "snapshot schema v{} is newer than supported v{}",
+            v, SNAPSHOT_SCHEMA_VERSION
+        ))),
+        v => Err(Error::PersistenceError(format!(
+            "snapshot schema v{} is no longer supported",
+            v
+        ))),
+    }
+}
+
+/// Summary of a stored snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    pub id: Uuid,
+    pub taken_at: DateTime<Utc>,
+    pub schema_version: u32,
+    pub label: Option<String>,
+    pub orders: usize,
+    pub positions: usize,
+    pub strategies: usize,
+    pub size_bytes: u64,
+}
+
+/// Storage backend for snapshots
+pub trait SnapshotStore: Send + Sync {
+    /// Persist a snapshot
+    fn save(&self, snapshot: &EngineSnapshot) -> Result<SnapshotInfo>;
+
+    /// Stored snapshots, newest first; unreadable ones are skipped
+    fn list(&self) -> Result<Vec<SnapshotInfo>>;
+
+    /// Load a single snapshot
+    fn load(&self, id: Uuid) -> Result<Option<EngineSnapshot>>;
+
+    /// Delete all but the newest `keep` snapshots, returning how many were removed
+    fn prune(&self, keep: usize) -> Result<usize>;
+
+    /// Newest snapshot that still loads
+    fn latest_valid(&self) -> Result<Option<EngineSnapshot>> {
+        for info in self.list()? {
+            match self.load(info.id) {
+                Ok(Some(snapshot)) => return Ok(Some(snapshot)),
+                Ok(None) => {}
+                Err(e) => warn!("Skipping unusable snapshot {}: {}", info.id, e),
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// In-memory store, mainly for tests and when no snapshot directory is writable
+#[derive(Debug, Default)]
+pub struct InMemorySnapshotStore {
+    snapshots: RwLock<HashMap<Uuid, EngineSnapshot>>,
+}
+
+impl InMemorySnapshotStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl SnapshotStore for InMemorySnapshotStore {
+    fn save(&self, snapshot: &EngineSnapshot) -> Result<SnapshotInfo> {
+        let size = serde_json::to_vec(snapshot)?.len() as u64;
+        self.snapshots.write().insert(snapshot.id, snapshot.clone());
+        Ok(snapshot.info(size))
+    }
+
+    fn list(&self) -> Result<Vec<SnapshotInfo>> {
+        let mut snapshots: Vec<_> = self.snapshots.read().values().map(|s| s.info(0)).collect();
+        snapshots.sort_by_key(|s| Reverse(s.taken_at));
+        Ok(snapshots)
+    }
+
+    fn load(&self, id: Uuid) -> Result<Option<EngineSnapshot>> {
+        Ok(self.snapshots.read().get(&id).cloned())
+    }
+
+    fn prune(&self, keep: usize) -> Result<usize> {
+        let stale: Vec<Uuid> = self.list()?.into_iter().skip(keep).map(|s| s.id).collect();
+        let mut snapshots = self.snapshots.write();
+        for id in &stale {
+            snapshots.remove(id);
+        }
+        Ok(stale.len())
+    }
+}
+
+/// File-backed store keeping one JSON document per snapshot
+///
+/// Next to every snapshot a small [`SnapshotInfo`] header is written under
+/// `index/`, so listing never has to parse the full documents. Snapshots
+/// without a header (written by older builds) are read once and indexed.
+#[derive(Debug)]
+pub struct FileSnapshotStore {
+    dir: PathBuf,
+    lock: RwLock<()>,
+}
+
+impl FileSnapshotStore {
+    /// Creates the store, creating the directory if needed
+    pub fn new(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).map_err(|e| {
+            Error::PersistenceError(format!("Failed to create {}: {}", dir.display(), e))
+        })?;
+        Ok(Self {
+            dir,
+            lock: RwLock::new(()),
+        })
+    }
+
+    fn path_for(&self, id: Uuid) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+
+    fn index_path_for(&self, id: Uuid) -> PathBuf {
+        self.dir.join("index").join(format!("{}.json", id))
+    }
+
+    fn write_atomic(path: &Path, bytes: &[u8]) -> Result<()> {
+        let tmp = path.with_extension("json.tmp");
+
+        // Write to a temp file and rename so a crash never leaves a torn file
+        fs::write(&tmp, bytes)
+            .and_then(|_| fs::rename(&tmp, path))
+            .map_err(|e| {
+                Error::PersistenceError(format!("Failed to write {}: {}", path.display(), e))
+            })
+    }
+
+    fn write_index(&self, info: &SnapshotInfo) -> Result<()> {
+        let path = self.index_path_for(info.id);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| {
+                Error::PersistenceError(format!("Failed to create {}: {}", parent.display(), e))
+            })?;
+        }
+        Self::write_atomic(&path, &serde_json::to_vec(info)?)
+    }
+
+    fn read_index(&self, id: Uuid) -> Option<SnapshotInfo> {
+        let bytes = fs::read(self.index_path_for(id)).ok()?;
+        serde_json::from_slice(&bytes).ok()
+    }
+
+    fn read(&self, path: &Path) -> Result<(EngineSnapshot, u64)> {
+        let bytes = fs::read(path).map_err(|e| {
+            Error::PersistenceError(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+        Ok((EngineSnapshot::from_json(&bytes)?, bytes.len() as u64))
+    }
+
+    fn list_unlocked(&self) -> Result<Vec<SnapshotInfo>> {
+        let entries = fs::read_dir(&self.dir).map_err(|e| {
+            Error::PersistenceError(format!("Failed to list {}: {}", self.dir.display(), e))
+        })?;
+
+        let mut snapshots = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let id = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| Uuid::parse_str(stem).ok());
+            if let Some(info) = id.and_then(|id| self.read_index(id)) {
+                snapshots.push(info);
+                continue;
+            }
+            match self.read(&path) {
+                Ok((snapshot, size)) => {
+                    let info = snapshot.info(size);
+                    if let Err(e) = self.write_index(&info) {
+                        warn!("Failed to index snapshot {}: {}", info.id, e);
+                    }
+                    snapshots.push(info);
+                }
+                Err(e) => warn!("Skipping unreadable snapshot {}: {}", path.display(), e),
+            }
+        }
+
+        snapshots.sort_by_key(|s| Reverse(s.taken_at));
+        Ok(snapshots)
+    }
+}
+
+impl SnapshotStore for FileSnapshotStore {
+    fn save(&self, snapshot: &EngineSnapshot) -> Result<SnapshotInfo> {
+        let _guard = self.lock.write();
+        let json = serde_json::to_vec_pretty(snapshot)?;
+        Self::write_atomic(&self.path_for(snapshot.id), &json)?;
+
+        // Written after the snapshot, so a header never points at a missing document
+        let info = snapshot.info(json.len() as u64);
+        self.write_index(&info)?;
+        Ok(info)
+    }
+
+    fn list(&self) -> Result<Vec<SnapshotInfo>> {
+        let _guard = self.lock.read();
+        self.list_unlocked()
+    }
+
+    fn load(&self, id: Uuid) -> Result<Option<EngineSnapshot>> {
+        let _guard = self.lock.read();
+        let path = self.path_for(id);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(self.read(&path)?.0))
+    }
+
+    fn prune(&self, keep: usize) -> Result<usize> {
+        let _guard = self.lock.write();
+        let mut removed = 0;
+        for info in self.list_unlocked()?.into_iter().skip(keep) {
+            let path = self.path_for(info.id);
+            fs::remove_file(&path).map_err(|e| {
+                Error::PersistenceError(format!("Failed to remove {}: {}", path.display(), e))
+            })?;
+            let _ = fs::remove_file(self.index_path_for(info.id));
+            removed += 1;
+        }
+        Ok(removed)
+    }
+}
+
+/// Bring the open orders of a snapshot in line with the exchange
+///
+/// Every order that was still working when the snapshot was taken is looked
+/// up by its client order ID and takes over the exchange's status and fills.
+/// Orders the exchange does not know, or all open orders when no exchange is
+/// connected, are marked cancelled so they are never resumed blindly.
+/// Returns how many orders changed.
+pub async fn reconcile_orders(
+    orders: &mut [Order],
+    exchange: Option<&dyn ExchangeAdapter>,
+) -> Result<usize> {
+    let mut changed = 0;
+    for order in orders.iter_mut().filter(|o| !o.is_terminal()) {
+        let remote = match exchange {
+            Some(exchange) => {
+                exchange
+                    .order(&order.symbol, &order.client_order_id)
+                    .await?
+            }
+            None => None,
+        };
+
+        let Some(remote) = remote else {
+            warn!(
+                "Order {} is unknown to the exchange, marking it cancelled",
+                order.client_order_id
+            );
+            order.set_status(OrderStatus::Cancelled);
+            changed += 1;
+            continue;
+        };
+
+        let before = (order.status, order.filled_quantity, order.avg_fill_price);
+        if order.okx_order_id.is_none() {
+            order.okx_order_id = Some(remote.exchange_order_id.clone());
+        }
+        if remote.filled_quantity > Decimal::ZERO {
+            order.filled_quantity = Quantity::new(remote.filled_quantity)?;
+        }
+        if let Some(price) = remote.avg_fill_price {
+            order.avg_fill_price = Some(Price::new(price)?);
+        }
+        order.set_status(remote.status);
+        if before != (order.status, order.filled_quantity, order.avg_fill_price) {
+            info!(
+                "Order {} reconciled to {:?} with {} filled",
+                order.client_order_id, order.status, order.filled_quantity
+            );
+            changed += 1;
+        }
+    }
+    Ok(changed)
+}
+
+/// Where the scheduler reads the engine state from
+#[async_trait]
+pub trait SnapshotDataSource: Send + Sync {
+    /// Capture the current engine state
+    async fn capture(&self) -> Result<EngineSnapshot>;
+}
+
+/// Snapshot schedule and retention
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotConfig {
+    pub interval_secs: u64,
+
+    /// Snapshots kept after each periodic snapshot
+    pub retain: usize,
+}
+
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: 300,
+            retain: 48,
+        }
+    }
+}
+
+/// Takes snapshots on demand and on a fixed interval
+pub struct SnapshotScheduler {
+    config: SnapshotConfig,
+    source: Arc<dyn SnapshotDataSource>,
+    store: Arc<dyn SnapshotStore>,
+}
+
+impl SnapshotScheduler {
+    pub fn new(
+        config: SnapshotConfig,
+        source: Arc<dyn SnapshotDataSource>,
+        store: Arc<dyn SnapshotStore>,
+    ) -> Self {
+        Self {
+            config,
+            source,
+            store,
+        }
+    }
+
+    pub fn store(&self) -> &Arc<dyn SnapshotStore> {
+        &self.store
+    }
+
+    /// Capture and persist a snapshot now
+    pub async fn take(&self, label: Option<String>) -> Result<SnapshotInfo> {
+        let mut snapshot = self.source.capture().await?;
+        snapshot.label = label;
+        let info = self.store.save(&snapshot)?;
+        info!(
+            "Saved snapshot {} ({} orders, {} positions, {} strategies)",
+            info.id, info.orders, info.positions, info.strategies
+        );
+        Ok(info)
+    }
+
+    /// Take a snapshot every `interval_secs` until the task is aborted
+    pub fn spawn(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let period = std::time::Duration::from_secs(self.config.interval_secs.max(1));
+            let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.take(None).await {
+                    error!("Failed to take snapshot: {}", e);
+                    continue;
+                }
+                if let Err(e) = self.store.prune(self.config.retain.max(1)) {
+                    warn!("Failed to prune snapshots: {}", e);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ea_okx_core::exchange::{ExchangeOrder, InstrumentInfo, MarketEvent, MarketSubscription};
+    use ea_okx_core::models::{OrderSide, OrderType};
+    use ea_okx_core::types::{InstrumentKind, Symbol};
+    use rust_decimal_macros::dec;
+
+    /// Exchange that only answers order lookups
+    #[derive(Default)]
+    struct KnownOrders(HashMap<String, ExchangeOrder>);
+
+    #[async_trait]
+    impl ExchangeAdapter for KnownOrders {
+        fn name(&self) -> &'static str {
+            "known"
+        }
+
+        async fn place_order(&self, _order: &Order) -> ea_okx_core::Result<String> {
+            unimplemented!()
+        }
+
+        async fn cancel_order(&self, _symbol: &Symbol, _id: &str) -> ea_okx_core::Result<()> {
+            unimplemented!()
+        }
+
+        async fn amend_order(
+            &self,
+            _symbol: &Symbol,
+            _id: &str,
+            _quantity: Option<Decimal>,
+            _price: Option<Decimal>,
+        ) -> ea_okx_core::Result<()> {
+            unimplemented!()
+        }
+
+        async fn order(
+            &self,
+            _symbol: &Symbol,
+            client_order_id: &str,
+        ) -> ea_okx_core::Result<Option<ExchangeOrder>> {
+            Ok(self.0.get(client_order_id).cloned())
+        }
+
+        async fn instruments(
+            &self,
+            _kind: InstrumentKind,
+        ) -> ea_okx_core::Result<Vec<InstrumentInfo>> {
+            unimplemented!()
+        }
+
+        async fn subscribe(&self, _subs: &[MarketSubscription]) -> ea_okx_core::Result<()> {
+            unimplemented!()
+        }
+
+        async fn next_event(&self) -> ea_okx_core::Result<Option<MarketEvent>> {
+            unimplemented!()
+        }
+
+        async fn disconnect(&self) -> ea_okx_core::Result<()> {
+            unimplemented!()
+        }
+    }
+
+    fn open_order() -> Order {
+        let mut order = Order::new(
+            Uuid::new_v4(),
+            Symbol::new("BTC-USDT").unwrap(),
+            OrderSide::Buy,
+            OrderType::Limit,
+            Quantity::new(dec!(1)).unwrap(),
+            Some(Price::new(dec!(50000)).unwrap()),
+        );
+        order.mark_submitted(format!("EX-{}", order.client_order_id));
+        order
+    }
+
+    struct FixedSource;
+
+    #[async_trait]
+    impl SnapshotDataSource for FixedSource {
+        async fn capture(&self) -> Result<EngineSnapshot> {
+            let mut snapshot = EngineSnapshot::new();
+            snapshot.orders.push(Order::new(
+                Uuid::new_v4(),
+                Symbol::new("BTC-USDT").unwrap(),
+                OrderSide::Buy,
+                OrderType::Limit,
+                Quantity::new(dec!(0.1)).unwrap(),
+                Some(Price::new(dec!(50000)).unwrap()),
+            ));
+            snapshot.strategies.push(StrategySnapshot {
+                strategy_id: Uuid::new_v4(),
+                name: "grid".to_string(),
+                status: StrategyStatus::Active,
+                allocated_capital: dec!(10000),
+                state: serde_json::json!({ "level": 3 }),
+            });
+            Ok(snapshot)
+        }
+    }
+
+    fn store() -> (FileSnapshotStore, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("snapshots-{}", Uuid::new_v4()));
+        (FileSnapshotStore::new(&dir).unwrap(), dir)
+    }
+
+    #[tokio::test]
+    async fn test_take_list_load_and_prune() {
+        let (store, dir) = store();
+        let store: Arc<dyn SnapshotStore> = Arc::new(store);
+        let scheduler = SnapshotScheduler::new(
+            SnapshotConfig::default(),
+            Arc::new(FixedSource),
+            store.clone(),
+        );
+
+        let first = scheduler
+            .take(Some("before upgrade".to_string()))
+            .await
+            .unwrap();
+        let second = scheduler.take(None).await.unwrap();
+        assert_eq!(first.orders, 1);
+
+        let listed = store.list().unwrap();
+        assert_eq!(
+            listed.iter().map(|s| s.id).collect::<Vec<_>>(),
+            vec![second.id, first.id]
+        );
+
+        let loaded = store.load(first.id).unwrap().unwrap();
+        assert_eq!(loaded.label.as_deref(), Some("before upgrade"));
+        assert_eq!(loaded.strategies[0].allocated_capital, dec!(10000));
+        assert_eq!(loaded.strategies[0].state["level"], 3);
+
+        assert_eq!(store.prune(1).unwrap(), 1);
+        assert!(store.load(first.id).unwrap().is_none());
+        assert!(
+            !dir.join("index")
+                .join(format!("{}.json", first.id))
+                .exists()
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_list_reads_headers_and_indexes_old_snapshots() {
+        let (store, dir) = store();
+        let indexed = store.save(&FixedSource.capture().await.unwrap()).unwrap();
+
+        // A snapshot written before headers existed
+        let mut legacy = FixedSource.capture().await.unwrap();
+        legacy.taken_at = indexed.taken_at - chrono::Duration::minutes(1);
+        fs::write(
+            dir.join(format!("{}.json", legacy.id)),
+            serde_json::to_vec(&legacy).unwrap(),
+        )
+        .unwrap();
+
+        // The header is what gets listed, not the document
+        let mut header = indexed.clone();
+        header.label = Some("from header".to_string());
+        store.write_index(&header).unwrap();
+
+        let listed = store.list().unwrap();
+        assert_eq!(listed[0], header);
+        assert_eq!(listed[1].id, legacy.id);
+        assert!(
+            dir.join("index")
+                .join(format!("{}.json", legacy.id))
+                .exists()
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_orders_adopts_exchange_state() {
+        let filled = open_order();
+        let unknown = open_order();
+        let mut done = open_order();
+        done.set_status(OrderStatus::Cancelled);
+
+        let mut exchange = KnownOrders::default();
+        exchange.0.insert(
+            filled.client_order_id.clone(),
+            ExchangeOrder {
+                exchange_order_id: "EX-1".to_string(),
+                client_order_id: filled.client_order_id.clone(),
+                symbol: filled.symbol.clone(),
+                side: OrderSide::Buy,
+                order_type: OrderType::Limit,
+                quantity: dec!(1),
+                price: Some(dec!(50000)),
+                filled_quantity: dec!(1),
+                avg_fill_price: Some(dec!(49990)),
+                status: OrderStatus::Filled,
+                updated_at: Utc::now(),
+            },
+        );
+
+        let mut orders = vec![filled, unknown, done];
+        let changed = reconcile_orders(&mut orders, Some(&exchange))
+            .await
+            .unwrap();
+        assert_eq!(changed, 2);
+        assert_eq!(orders[0].status, OrderStatus::Filled);
+        assert_eq!(orders[0].filled_quantity.as_decimal(), dec!(1));
+        assert_eq!(
+            orders[0].avg_fill_price.map(|p| p.as_decimal()),
+            Some(dec!(49990))
+        );
+        assert_eq!(orders[1].status, OrderStatus::Cancelled);
+        assert!(orders[1].completed_at.is_some());
+
+        // Without an exchange nothing open survives
+        let mut orders = vec![open_order()];
+        assert_eq!(reconcile_orders(&mut orders, None).await.unwrap(), 1);
+        assert_eq!(orders[0].status, OrderStatus::Cancelled);
+    }
+
+    #[tokio::test]
+    async fn test_latest_valid_skips_corrupt_and_newer_snapshots() {
+        let (store, dir) = store();
+        let good = FixedSource.capture().await.unwrap();
+        store.save(&good).unwrap();
+
+        let mut newer = FixedSource.capture().await.unwrap();
+        newer.schema_version = SNAPSHOT_SCHEMA_VERSION + 1;
+        newer.taken_at = good.taken_at + chrono::Duration::minutes(1);
+        store.save(&newer).unwrap();
+        fs::write(
+            dir.join(format!("{}.json", Uuid::new_v4())),
+            b"{\"schema_version\":1,",
+        )
+        .unwrap();
+
+        assert!(matches!(
+            store.load(newer.id),
+            Err(Error::PersistenceError(msg)) if msg.contains("newer")
+        ));
+        assert_eq!(store.latest_valid().unwrap().unwrap().id, good.id);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -26,7 +26,7 @@ pub enum OrderState {
     Rejected,
     /// Failed to submit
     Failed,
-    /// Expired (timeout)
+    /// Expired (timeout or good-till-date passed)
     Expired,
 }
 
@@ -145,8 +145,10 @@ impl OrderStateMachine {
                 | (Acknowledged, Filled)
                 | (Acknowledged, Cancelled)
                 | (Acknowledged, Rejected)
+                | (Acknowledged, Expired)
                 | (PartiallyFilled, Filled)
                 | (PartiallyFilled, Cancelled)
+                | (PartiallyFilled, Expired)
         )
     }
 
@@ -0,0 +1,419 @@
+//! Catalog of the symbols the exchange lists
+//!
+//! [`SymbolCatalog`] keeps the full instrument listing of the configured
+//! kinds (spot, perpetual swaps and dated futures by default), refreshed from
+//! the exchange's instruments endpoint, so symbol pickers and validation work
+//! from what can actually be traded instead of a hard-coded list. A sync
+//! replaces the listing only once every kind has been fetched, so a failed
+//! request never leaves the catalog half empty. With a path, the listing is
+//! saved after each sync and reloaded on startup, so it is usable before the
+//! first sync completes.
+
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use ea_okx_core::exchange::{ExchangeAdapter, InstrumentInfo};
+use ea_okx_core::{InstrumentKind, Symbol};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// Source of full instrument listings
+#[async_trait]
+pub trait InstrumentListing: Send + Sync {
+    async fn list_instruments(&self, kind: InstrumentKind) -> Result<Vec<InstrumentInfo>>;
+}
+
+#[async_trait]
+impl<T: ExchangeAdapter + ?Sized> InstrumentListing for T {
+    async fn list_instruments(&self, kind: InstrumentKind) -> Result<Vec<InstrumentInfo>> {
+        Ok(self.instruments(kind).await?)
+    }
+}
+
+/// Filter for [`SymbolCatalog::search`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SymbolQuery {
+    /// Case-insensitive text matched against the symbol, e.g. `sol`
+    #[serde(default)]
+    pub text: String,
+    pub kind: Option<InstrumentKind>,
+    /// Quote currency, e.g. `USDT`
+    pub quote: Option<String>,
+    /// Leave out suspended and pre-open instruments
+    #[serde(default)]
+    pub tradable_only: bool,
+    pub limit: Option<usize>,
+}
+
+/// Outcome of a catalog sync
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogSync {
+    pub synced_at: DateTime<Utc>,
+    /// Instruments now in the catalog
+    pub instruments: usize,
+    /// Symbols newly listed since the previous sync
+    pub added: Vec<Symbol>,
+    /// Symbols no longer listed
+    pub removed: Vec<Symbol>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Listing {
+    synced_at: Option<DateTime<Utc>>,
+    instruments: BTreeMap<String, InstrumentInfo>,
+}
+
+/// Instrument listing of the configured kinds, searchable by symbol
+pub struct SymbolCatalog {
+    kinds: Vec<InstrumentKind>,
+    path: Option<PathBuf>,
+    listing: RwLock<Listing>,
+}
+
+impl SymbolCatalog {
+    /// Kinds listed unless configured otherwise
+    pub const DEFAULT_KINDS: [InstrumentKind; 3] = [
+        InstrumentKind::Spot,
+        InstrumentKind::Swap,
+        InstrumentKind::Futures,
+    ];
+
+    /// Empty in-memory catalog of `kinds`
+    pub fn new(kinds: Vec<InstrumentKind>) -> Self {
+        Self {
+            kinds,
+            path: None,
+            listing: RwLock::new(Listing::default()),
+        }
+    }
+
+    /// Catalog saved to `path`, starting from the listing saved there
+    pub fn open(kinds: Vec<InstrumentKind>, path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(|e| {
+                Error::PersistenceError(format!("Failed to create {}: {}", dir.display(), e))
+            })?;
+        }
+        let listing = match std::fs::read(&path) {
+            Ok(contents) => serde_json::from_slice(&contents)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Listing::default(),
+            Err(e) => {
+                return Err(Error::PersistenceError(format!(
+                    "Failed to read {}: {}",
+                    path.display(),
+                    e
+                )));
+            }
+        };
+        Ok(Self {
+            kinds,
+            path: Some(path),
+            listing: RwLock::new(listing),
+        })
+    }
+
+    pub fn kinds(&self) -> &[InstrumentKind] {
+        &self.kinds
+    }
+
+    /// When the listing was last synced; `None` if it never was
+    pub fn synced_at(&self) -> Option<DateTime<Utc>> {
+        self.listing.read().synced_at
+    }
+
+    pub fn len(&self) -> usize {
+        self.listing.read().instruments.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.listing.read().instruments.is_empty()
+    }
+
+    /// Metadata of `symbol`, if listed
+    pub fn get(&self, symbol: &Symbol) -> Option<InstrumentInfo> {
+        self.listing
+            .read()
+            .instruments
+            .get(symbol.as_str())
+            .cloned()
+    }
+
+    /// Listed instruments matching `query`
+    ///
+    /// Exact matches come first, then symbols whose base currency is the
+    /// text, then ones starting with it, then any others containing it;
+    /// alphabetical within each.
+    pub fn search(&self, query: &SymbolQuery) -> Vec<InstrumentInfo> {
+        let text = query.text.trim().to_uppercase();
+        let quote = query.quote.as_ref().map(|q| q.to_uppercase());
+        let listing = self.listing.read();
+
+        let mut matches: Vec<(u8, &InstrumentInfo)> = listing
+            .instruments
+            .values()
+            .filter(|i| query.kind.is_none_or(|kind| i.kind == kind))
+            .filter(|i| quote.as_ref().is_none_or(|q| i.symbol.quote() == q))
+            .filter(|i| !query.tradable_only || i.tradable)
+            .filter_map(|i| {
+                let symbol = i.symbol.as_str();
+                let rank = if text.is_empty() || symbol == text {
+                    0
+                } else if i.symbol.base() == text {
+                    1
+                } else if symbol.starts_with(&text) {
+                    2
+                } else if symbol.contains(&text) {
+                    3
+                } else {
+                    return None;
+                };
+                Some((rank, i))
+            })
+            .collect();
+        // Listing order is already alphabetical, and the sort is stable
+        matches.sort_by_key(|(rank, _)| *rank);
+
+        matches
+            .into_iter()
+            .take(query.limit.unwrap_or(usize::MAX))
+            .map(|(_, i)| i.clone())
+            .collect()
+    }
+
+    /// Parse `symbols`, refusing ones the exchange does not list or that
+    /// cannot be traded
+    ///
+    /// Symbols of kinds the catalog does not cover, and every symbol while
+    /// the catalog has never been synced, are only checked for format.
+    pub fn validate_symbols(&self, symbols: &[String]) -> Result<Vec<Symbol>> {
+        let symbols = symbols
+            .iter()
+            .map(Symbol::new)
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let listing = self.listing.read();
+        if listing.instruments.is_empty() {
+            return Ok(symbols);
+        }
+        for symbol in &symbols {
+            if !self.kinds.contains(&symbol.kind()) {
+                continue;
+            }
+            match listing.instruments.get(symbol.as_str()) {
+                None => {
+                    return Err(Error::UnknownSymbol(format!(
+                        "{} is not listed on the exchange",
+                        symbol.as_str()
+                    )));
+                }
+                Some(info) if !info.tradable => {
+                    return Err(Error::UnknownSymbol(format!(
+                        "{} is listed but not tradable",
+                        symbol.as_str()
+                    )));
+                }
+                Some(_) => {}
+            }
+        }
+        Ok(symbols)
+    }
+
+    /// Replace the listing with a fresh one from `source`
+    pub async fn sync(&self, source: &dyn InstrumentListing) -> Result<CatalogSync> {
+        let mut instruments = BTreeMap::new();
+        for &kind in &self.kinds {
+            for info in source.list_instruments(kind).await? {
+                instruments.insert(info.symbol.as_str().to_string(), info);
+            }
+        }
+
+        let synced_at = Utc::now();
+        let sync = {
+            let mut listing = self.listing.write();
+            let added = instruments
+                .iter()
+                .filter(|(key, _)| !listing.instruments.contains_key(*key))
+                .map(|(_, i)| i.symbol.clone())
+                .collect();
+            let removed = listing
+                .instruments
+                .iter()
+                .filter(|(key, _)| !instruments.contains_key(*key))
+                .map(|(_, i)| i.symbol.clone())
+                .collect();
+            *listing = Listing {
+                synced_at: Some(synced_at),
+                instruments,
+            };
+            self.save(&listing)?;
+            CatalogSync {
+                synced_at,
+                instruments: listing.instruments.len(),
+                added,
+                removed,
+            }
+        };
+
+        info!(
+            "Symbol catalog synced: {} instruments, {} added, {} removed",
+            sync.instruments,
+            sync.added.len(),
+            sync.removed.len()
+        );
+        Ok(sync)
+    }
+
+    /// Sync every `interval` until the task is aborted
+    pub fn start_syncing(
+        self: Arc<Self>,
+        source: Arc<dyn InstrumentListing>,
+        interval: Duration,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.sync(source.as_ref()).await {
+                    warn!("Symbol catalog sync failed: {}", e);
+                }
+            }
+        })
+    }
+
+    fn save(&self, listing: &Listing) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let json = serde_json::to_vec(listing)?;
+        // Write then rename so a crash never leaves a truncated listing
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, json)
+            .and_then(|_| std::fs::rename(&tmp, path))
+            .map_err(|e| {
+                Error::PersistenceError(format!("Failed to save {}: {}", path.display(), e))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+    use uuid::Uuid;
+
+    struct FakeListing {
+        symbols: Vec<(&'static str, bool)>,
+    }
+
+    #[async_trait]
+    impl InstrumentListing for FakeListing {
+        async fn list_instruments(&self, kind: InstrumentKind) -> Result<Vec<InstrumentInfo>> {
+            Ok(self
+                .symbols
+                .iter()
+                .map(|(s, tradable)| Symbol::new(*s).map(|symbol| (symbol, *tradable)))
+                .collect::<std::result::Result<Vec<_>, _>>()?
+                .into_iter()
+                .filter(|(symbol, _)| symbol.kind() == kind)
+                .map(|(symbol, tradable)| InstrumentInfo {
+                    symbol,
+                    kind,
+                    tick_size: dec!(0.01),
+                    lot_size: dec!(0.001),
+                    min_size: dec!(0.001),
+                    tradable,
+                })
+                .collect())
+        }
+    }
+
+    fn listing() -> FakeListing {
+        FakeListing {
+            symbols: vec![
+                ("BTC-USDT", true),
+                ("SOL-USDT", true),
+                ("SOL-USDC", true),
+                ("SOL-USDT-SWAP", true),
+                ("SOL-USD-SWAP", true),
+                ("MSOL-USDT", true),
+                ("LUNA-USDT", false),
+                ("BTC-USD-250328", true),
+                ("BTC-USD-250328-60000-C", true),
+            ],
+        }
+    }
+
+    fn symbols(found: &[InstrumentInfo]) -> Vec<&str> {
+        found.iter().map(|i| i.symbol.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_search_and_validate() {
+        let catalog = SymbolCatalog::new(SymbolCatalog::DEFAULT_KINDS.to_vec());
+        // Never synced: only the format is checked
+        assert!(catalog.validate_symbols(&["DOGE-USDT".to_string()]).is_ok());
+
+        let sync = catalog.sync(&listing()).await.unwrap();
+        assert_eq!(sync.instruments, 8);
+        assert_eq!(sync.added.len(), 8);
+
+        let query = SymbolQuery {
+            text: "sol".to_string(),
+            kind: Some(InstrumentKind::Swap),
+            ..Default::default()
+        };
+        assert_eq!(
+            symbols(&catalog.search(&query)),
+            ["SOL-USD-SWAP", "SOL-USDT-SWAP"]
+        );
+
+        let query = SymbolQuery {
+            text: "sol".to_string(),
+            kind: Some(InstrumentKind::Spot),
+            quote: Some("usdt".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(symbols(&catalog.search(&query)), ["SOL-USDT", "MSOL-USDT"]);
+
+        let query = SymbolQuery {
+            tradable_only: true,
+            limit: Some(100),
+            ..Default::default()
+        };
+        assert_eq!(catalog.search(&query).len(), 7);
+
+        let valid = ["SOL-USDT".to_string(), "BTC-USD-250328-60000-C".to_string()];
+        assert_eq!(catalog.validate_symbols(&valid).unwrap().len(), 2);
+        for invalid in ["DOGE-USDT", "LUNA-USDT", "not a symbol"] {
+            assert!(catalog.validate_symbols(&[invalid.to_string()]).is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sync_persists_and_reports_changes() {
+        let path = std::env::temp_dir().join(format!("symbol-catalog-{}.json", Uuid::new_v4()));
+        let catalog = SymbolCatalog::open(vec![InstrumentKind::Spot], &path).unwrap();
+        catalog.sync(&listing()).await.unwrap();
+
+        let mut delisted = listing();
+        delisted.symbols.retain(|(s, _)| *s != "LUNA-USDT");
+        delisted.symbols.push(("ETH-USDT", true));
+        let sync = catalog.sync(&delisted).await.unwrap();
+        assert_eq!(sync.added, [Symbol::new("ETH-USDT").unwrap()]);
+        assert_eq!(sync.removed, [Symbol::new("LUNA-USDT").unwrap()]);
+
+        let reopened = SymbolCatalog::open(vec![InstrumentKind::Spot], &path).unwrap();
+        assert_eq!(reopened.len(), 5);
+        assert_eq!(reopened.synced_at(), catalog.synced_at());
+        assert!(reopened.get(&Symbol::new("ETH-USDT").unwrap()).is_some());
+
+        let _ = std::fs::remove_file(path);
+    }
+}
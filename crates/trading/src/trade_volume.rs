@@ -0,0 +1,119 @@
+//! Live traded volume from the trades channel
+//!
+//! [`TradeVolumes`] keeps a running tally of every symbol's public trades so
+//! executors can size child orders to what the market is actually trading
+//! rather than to a static profile. Volume only ever grows: readers keep the
+//! tally they last acted on and take the difference.
+
+use chrono::{DateTime, Utc};
+use ea_okx_core::Symbol;
+use ea_okx_core::exchange::TradeTick;
+use parking_lot::RwLock;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+/// Trades seen for one symbol since tracking started
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TradeTally {
+    /// Base quantity traded
+    pub volume: Decimal,
+
+    pub trades: u64,
+
+    pub last_price: Decimal,
+
+    pub last_trade_at: DateTime<Utc>,
+}
+
+/// Traded volume of every symbol; shared between the feed and executors
+#[derive(Debug, Default)]
+pub struct TradeVolumes {
+    tallies: RwLock<HashMap<Symbol, TradeTally>>,
+}
+
+impl TradeVolumes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a trade pushed on the trades channel
+    pub fn record(&self, trade: &TradeTick) {
+        let mut tallies = self.tallies.write();
+        match tallies.get_mut(&trade.symbol) {
+            Some(tally) => {
+                tally.volume += trade.quantity;
+                tally.trades += 1;
+                // Pushes can arrive slightly out of order
+                if trade.timestamp >= tally.last_trade_at {
+                    tally.last_price = trade.price;
+                    tally.last_trade_at = trade.timestamp;
+                }
+            }
+            None => {
+                tallies.insert(
+                    trade.symbol.clone(),
+                    TradeTally {
+                        volume: trade.quantity,
+                        trades: 1,
+                        last_price: trade.price,
+                        last_trade_at: trade.timestamp,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Tally of `symbol`; `None` until its first trade
+    pub fn tally(&self, symbol: &Symbol) -> Option<TradeTally> {
+        self.tallies.read().get(symbol).copied()
+    }
+
+    /// Volume traded in `symbol` since tracking started
+    pub fn volume(&self, symbol: &Symbol) -> Decimal {
+        self.tally(symbol).map_or(Decimal::ZERO, |t| t.volume)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use ea_okx_core::models::OrderSide;
+    use rust_decimal_macros::dec;
+
+    fn trade(symbol: &str, price: Decimal, quantity: Decimal, at: DateTime<Utc>) -> TradeTick {
+        TradeTick {
+            symbol: Symbol::new(symbol).unwrap(),
+            trade_id: "1".to_string(),
+            price,
+            quantity,
+            side: OrderSide::Buy,
+            timestamp: at,
+        }
+    }
+
+    #[test]
+    fn test_tallies_trades_per_symbol() {
+        let volumes = TradeVolumes::new();
+        let btc = Symbol::new("BTC-USDT").unwrap();
+        let now = Utc::now();
+        assert!(volumes.tally(&btc).is_none());
+
+        volumes.record(&trade("BTC-USDT", dec!(50000), dec!(0.5), now));
+        volumes.record(&trade("ETH-USDT", dec!(3000), dec!(4), now));
+        // A late push adds volume without rewinding the last price
+        volumes.record(&trade(
+            "BTC-USDT",
+            dec!(49900),
+            dec!(0.25),
+            now - Duration::seconds(1),
+        ));
+
+        let tally = volumes.tally(&btc).unwrap();
+        assert_eq!(tally.volume, dec!(0.75));
+        assert_eq!(tally.trades, 2);
+        assert_eq!(tally.last_price, dec!(50000));
+        assert_eq!(tally.last_trade_at, now);
+        assert_eq!(volumes.volume(&Symbol::new("ETH-USDT").unwrap()), dec!(4));
+    }
+}
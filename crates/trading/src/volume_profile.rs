@@ -0,0 +1,435 @@
+//! Per-symbol intraday volume profiles for VWAP scheduling
+//!
+//! [`VolumeProfileEstimator`] learns, from hourly volume history, what share
+//! of a symbol's volume trades in each hour of the day and, once enough weeks
+//! are covered, in each hour of each weekday. Hours in which a symbol never
+//! traded get no weight, which keeps VWAP slices out of hours the instrument
+//! is closed or dead. Profiles are persisted through a [`VolumeProfileStore`]
+//! and count as stale once they have not been refit for `max_age_hours`, in
+//! which case executors fall back to their configured profile.
+
+use crate::error::{Error, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Timelike, Utc};
+use ea_okx_core::Symbol;
+use parking_lot::RwLock;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+const HOURS_PER_WEEK: usize = 7 * 24;
+
+/// Volume profile learning configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VolumeProfileConfig {
+    /// History considered when fitting
+    pub lookback_days: i64,
+
+    /// Fewer distinct days of history than this is an error
+    pub min_days: usize,
+
+    /// Weeks of history needed before weekday-specific shares are used
+    pub min_weeks_for_weekday: usize,
+
+    /// Profiles older than this are stale
+    pub max_age_hours: i64,
+}
+
+impl Default for VolumeProfileConfig {
+    fn default() -> Self {
+        Self {
+            lookback_days: 28,
+            min_days: 3,
+            min_weeks_for_weekday: 2,
+            max_age_hours: 48,
+        }
+    }
+}
+
+/// Learned share of volume per hour for one symbol
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VolumeProfile {
+    pub symbol: Symbol,
+
+    /// Share of a day's volume per UTC hour, summing to 1
+    pub hourly: Vec<Decimal>,
+
+    /// Share of a week's volume per weekday (Monday first) and UTC hour,
+    /// summing to 1; only with `min_weeks_for_weekday` weeks of history
+    pub weekly: Option<Vec<Decimal>>,
+
+    /// Distinct days of history the profile was fit on
+    pub days_observed: usize,
+    pub fitted_at: DateTime<Utc>,
+}
+
+impl VolumeProfile {
+    /// Relative weight of the hour starting at `at`
+    ///
+    /// Weekday shares are scaled to a day so both kinds of weight compare.
+    pub fn weight_at(&self, at: DateTime<Utc>) -> Decimal {
+        let hour = at.hour() as usize;
+        match &self.weekly {
+            Some(weekly) => {
+                let weekday = at.weekday().num_days_from_monday() as usize;
+                weekly[weekday * 24 + hour] * Decimal::from(7)
+            }
+            None => self.hourly[hour],
+        }
+    }
+
+    /// Whether the symbol has traded in this hour
+    pub fn is_trading_hour(&self, at: DateTime<Utc>) -> bool {
+        !self.weight_at(at).is_zero()
+    }
+
+    pub fn age(&self, now: DateTime<Utc>) -> Duration {
+        now - self.fitted_at
+    }
+
+    pub fn is_stale(&self, now: DateTime<Utc>, max_age: Duration) -> bool {
+        self.age(now) > max_age
+    }
+}
+
+/// Storage backend for learned volume profiles
+pub trait VolumeProfileStore: Send + Sync {
+    /// Insert or replace a symbol's profile
+    fn save(&self, profile: &VolumeProfile) -> Result<()>;
+
+    /// Load every stored profile
+    fn load_all(&self) -> Result<Vec<VolumeProfile>>;
+}
+
+/// In-memory store, mainly for tests and paper trading
+#[derive(Debug, Default)]
+pub struct InMemoryVolumeProfileStore {
+    profiles: RwLock<HashMap<Symbol, VolumeProfile>>,
+}
+
+impl InMemoryVolumeProfileStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl VolumeProfileStore for InMemoryVolumeProfileStore {
+    fn save(&self, profile: &VolumeProfile) -> Result<()> {
+        self.profiles
+            .write()
+            .insert(profile.symbol.clone(), profile.clone());
+        Ok(())
+    }
+
+    fn load_all(&self) -> Result<Vec<VolumeProfile>> {
+        Ok(self.profiles.read().values().cloned().collect())
+    }
+}
+
+/// File-backed store keeping one JSON document per symbol
+#[derive(Debug)]
+pub struct FileVolumeProfileStore {
+    dir: PathBuf,
+    lock: RwLock<()>,
+}
+
+impl FileVolumeProfileStore {
+    /// Creates the store, creating the directory if needed
+    pub fn new(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).map_err(|e| {
+            Error::PersistenceError(format!("Failed to create {}: {}", dir.display(), e))
+        })?;
+        Ok(Self {
+            dir,
+            lock: RwLock::new(()),
+        })
+    }
+}
+
+impl VolumeProfileStore for FileVolumeProfileStore {
+    fn save(&self, profile: &VolumeProfile) -> Result<()> {
+        let _guard = self.lock.write();
+        let path = self.dir.join(format!("{}.json", profile.symbol.as_str()));
+        let tmp = path.with_extension("json.tmp");
+        let json = serde_json::to_vec_pretty(profile)?;
+
+        fs::write(&tmp, json)
+            .and_then(|_| fs::rename(&tmp, &path))
+            .map_err(|e| {
+                Error::PersistenceError(format!("Failed to write {}: {}", path.display(), e))
+            })
+    }
+
+    fn load_all(&self) -> Result<Vec<VolumeProfile>> {
+        let _guard = self.lock.read();
+        let entries = fs::read_dir(&self.dir).map_err(|e| {
+            Error::PersistenceError(format!("Failed to list {}: {}", self.dir.display(), e))
+        })?;
+
+        let mut profiles = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            match fs::read(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|b| serde_json::from_slice(&b).map_err(|e| e.to_string()))
+            {
+                Ok(profile) => profiles.push(profile),
+                Err(e) => warn!(
+                    "Skipping unreadable volume profile {}: {}",
+                    path.display(),
+                    e
+                ),
+            }
+        }
+        Ok(profiles)
+    }
+}
+
+/// Learns and serves per-symbol volume profiles
+pub struct VolumeProfileEstimator {
+    config: VolumeProfileConfig,
+    store: Arc<dyn VolumeProfileStore>,
+    profiles: RwLock<HashMap<Symbol, VolumeProfile>>,
+}
+
+impl VolumeProfileEstimator {
+    /// Creates the estimator with the profiles already in `store`
+    pub fn new(config: VolumeProfileConfig, store: Arc<dyn VolumeProfileStore>) -> Result<Self> {
+        let profiles = store
+            .load_all()?
+            .into_iter()
+            .map(|p| (p.symbol.clone(), p))
+            .collect();
+        Ok(Self {
+            config,
+            store,
+            profiles: RwLock::new(profiles),
+        })
+    }
+
+    pub fn config(&self) -> &VolumeProfileConfig {
+        &self.config
+    }
+
+    /// Start of the history window to load for a fit at `now`
+    pub fn history_start(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        now - Duration::days(self.config.lookback_days)
+    }
+
+    /// Fit and store a profile from `(hour start, volume)` samples
+    ///
+    /// Samples outside the lookback window are ignored. Hours missing from a
+    /// day that has other samples count as zero volume.
+    pub fn learn(
+        &self,
+        symbol: &Symbol,
+        samples: &[(DateTime<Utc>, Decimal)],
+        now: DateTime<Utc>,
+    ) -> Result<VolumeProfile> {
+        let start = self.history_start(now);
+        let mut hourly = vec![Decimal::ZERO; 24];
+        let mut weekly = vec![Decimal::ZERO; HOURS_PER_WEEK];
+        let mut days: HashSet<NaiveDate> = HashSet::new();
+
+        for (at, volume) in samples.iter().filter(|(at, _)| *at >= start && *at < now) {
+            let hour = at.hour() as usize;
+            let weekday = at.weekday().num_days_from_monday() as usize;
+            hourly[hour] += *volume;
+            weekly[weekday * 24 + hour] += *volume;
+            days.insert(at.date_naive());
+        }
+
+        if days.len() < self.config.min_days {
+            return Err(Error::ExecutionError(format!(
+                "Volume profile for {} needs {} days of history, found {}",
+                symbol.as_str(),
+                self.config.min_days,
+                days.len()
+            )));
+        }
+
+        // Average over the days each bucket was observed on, so a weekday
+        // seen three times does not outweigh one seen twice
+        let mut days_per_weekday = [0usize; 7];
+        for day in &days {
+            days_per_weekday[day.weekday().num_days_from_monday() as usize] += 1;
+        }
+        let weeks_covered = days_per_weekday.iter().copied().min().unwrap_or(0);
+        let weekly = (weeks_covered >= self.config.min_weeks_for_weekday).then(|| {
+            let means: Vec<Decimal> = weekly
+                .iter()
+                .enumerate()
+                .map(|(i, v)| *v / Decimal::from(days_per_weekday[i / 24]))
+                .collect();
+            normalize(means)
+        });
+
+        let profile = VolumeProfile {
+            symbol: symbol.clone(),
+            hourly: normalize(hourly),
+            weekly,
+            days_observed: days.len(),
+            fitted_at: now,
+        };
+        self.store.save(&profile)?;
+        self.profiles
+            .write()
+            .insert(symbol.clone(), profile.clone());
+
+        info!(
+            "Learned volume profile for {} from {} days (weekday shares: {})",
+            symbol.as_str(),
+            profile.days_observed,
+            profile.weekly.is_some()
+        );
+        Ok(profile)
+    }
+
+    /// Latest profile for `symbol`, however old
+    pub fn profile(&self, symbol: &Symbol) -> Option<VolumeProfile> {
+        self.profiles.read().get(symbol).cloned()
+    }
+
+    /// Profile for `symbol` unless it is missing or stale
+    pub fn fresh_profile(&self, symbol: &Symbol, now: DateTime<Utc>) -> Option<VolumeProfile> {
+        let profile = self.profile(symbol)?;
+        if profile.is_stale(now, self.max_age()) {
+            warn!(
+                "Volume profile for {} is stale (fitted {})",
+                symbol.as_str(),
+                profile.fitted_at
+            );
+            return None;
+        }
+        Some(profile)
+    }
+
+    /// Symbols among `symbols` whose profile is missing or stale
+    pub fn stale_symbols(&self, symbols: &[Symbol], now: DateTime<Utc>) -> Vec<Symbol> {
+        let profiles = self.profiles.read();
+        symbols
+            .iter()
+            .filter(|s| {
+                profiles
+                    .get(*s)
+                    .is_none_or(|p| p.is_stale(now, self.max_age()))
+            })
+            .cloned()
+            .collect()
+    }
+
+    fn max_age(&self) -> Duration {
+        Duration::hours(self.config.max_age_hours)
+    }
+}
+
+fn normalize(values: Vec<Decimal>) -> Vec<Decimal> {
+    let total: Decimal = values.iter().sum();
+    if total.is_zero() {
+        return values;
+    }
+    values.into_iter().map(|v| v / total).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn symbol() -> Symbol {
+        Symbol::new("BTC-USDT").unwrap()
+    }
+
+    /// Monday 2024-01-01 00:00 UTC plus `hours`
+    fn at(hours: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_704_067_200, 0).unwrap() + Duration::hours(hours)
+    }
+
+    /// `days` of hourly candles: 3 units at 14:00, 1 at 02:00, none otherwise
+    fn history(days: i64) -> Vec<(DateTime<Utc>, Decimal)> {
+        (0..days * 24)
+            .map(|h| {
+                let volume = match h % 24 {
+                    14 => dec!(3),
+                    2 => dec!(1),
+                    _ => Decimal::ZERO,
+                };
+                (at(h), volume)
+            })
+            .collect()
+    }
+
+    fn estimator() -> VolumeProfileEstimator {
+        VolumeProfileEstimator::new(
+            VolumeProfileConfig::default(),
+            Arc::new(InMemoryVolumeProfileStore::new()),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_learns_hourly_shares_and_closed_hours() {
+        let estimator = estimator();
+        let profile = estimator.learn(&symbol(), &history(5), at(5 * 24)).unwrap();
+
+        assert_eq!(profile.days_observed, 5);
+        assert!(profile.weekly.is_none());
+        assert_eq!(profile.weight_at(at(14)), dec!(0.75));
+        assert_eq!(profile.weight_at(at(2)), dec!(0.25));
+        assert!(!profile.is_trading_hour(at(8)));
+
+        assert!(estimator.learn(&symbol(), &history(2), at(48)).is_err());
+    }
+
+    #[test]
+    fn test_weekday_shares_after_enough_weeks() {
+        let estimator = estimator();
+        let mut samples = history(14);
+        // Saturdays trade three times the usual afternoon volume
+        for (ts, volume) in samples.iter_mut() {
+            if ts.weekday() == chrono::Weekday::Sat && ts.hour() == 14 {
+                *volume = dec!(9);
+            }
+        }
+        let profile = estimator.learn(&symbol(), &samples, at(14 * 24)).unwrap();
+
+        assert!(profile.weekly.is_some());
+        let saturday = at(5 * 24 + 14);
+        let monday = at(14);
+        let ratio = profile.weight_at(saturday) / profile.weight_at(monday);
+        assert_eq!(ratio.round_dp(12), dec!(3));
+    }
+
+    #[test]
+    fn test_profiles_go_stale_and_persist() {
+        let dir = std::env::temp_dir().join(format!("volume-profiles-{}", uuid::Uuid::new_v4()));
+        let store = Arc::new(FileVolumeProfileStore::new(&dir).unwrap());
+        let estimator =
+            VolumeProfileEstimator::new(VolumeProfileConfig::default(), store.clone()).unwrap();
+        let fitted_at = at(5 * 24);
+        estimator.learn(&symbol(), &history(5), fitted_at).unwrap();
+
+        let reloaded = VolumeProfileEstimator::new(VolumeProfileConfig::default(), store).unwrap();
+        assert!(reloaded.fresh_profile(&symbol(), fitted_at).is_some());
+        assert!(
+            reloaded
+                .fresh_profile(&symbol(), fitted_at + Duration::hours(49))
+                .is_none()
+        );
+
+        let eth = Symbol::new("ETH-USDT").unwrap();
+        assert_eq!(
+            reloaded.stale_symbols(&[symbol(), eth.clone()], fitted_at),
+            vec![eth]
+        );
+
+        fs::remove_dir_all(dir).ok();
+    }
+}
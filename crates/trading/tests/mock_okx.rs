@@ -0,0 +1,194 @@
+//! Order manager against the in-repo mock OKX server
+
+use ea_okx_client::models::{Channel, OrderData, SubscriptionRequest, WebSocketEvent};
+use ea_okx_client::{Credentials, OkxAdapter, OkxRestClient, OkxWebSocketClient};
+use ea_okx_core::exchange::ExchangeAdapter;
+use ea_okx_core::models::{Order, OrderSide, OrderType};
+use ea_okx_core::{Price, Quantity, Symbol};
+use ea_okx_mock::{Fault, MockOkx, OrderScript};
+use ea_okx_trading::{
+    InMemoryIntentLog, IntentLog, IntentRecoveryPolicy, OkxIntentVenue, OrderEvent, OrderIntent,
+    OrderManager, OrderManagerConfig, OrderState,
+};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+fn credentials() -> Credentials {
+    Credentials::new("key", "secret", "pass")
+}
+
+async fn mock() -> MockOkx {
+    let mock = MockOkx::start().await.unwrap();
+    mock.add_instrument("BTC-USDT", "0.1", "0.0001", "0.0001");
+    mock
+}
+
+fn rest(mock: &MockOkx) -> Arc<OkxRestClient> {
+    Arc::new(
+        OkxRestClient::new(credentials(), true)
+            .unwrap()
+            .with_base_url(&mock.rest_url())
+            .unwrap(),
+    )
+}
+
+fn limit_buy(quantity: Decimal) -> Order {
+    Order::new(
+        Uuid::new_v4(),
+        Symbol::new("BTC-USDT").unwrap(),
+        OrderSide::Buy,
+        OrderType::Limit,
+        Quantity::new(quantity).unwrap(),
+        Some(Price::new(dec!(50000)).unwrap()),
+    )
+}
+
+#[tokio::test]
+async fn test_intent_recovery_settles_orders_on_exchange() {
+    let mock = mock().await;
+    let rest = rest(&mock);
+    let adapter = Arc::new(OkxAdapter::new(rest.clone()));
+    let log = Arc::new(InMemoryIntentLog::new());
+
+    // The previous run logged two intents but only one order reached OKX,
+    // where it was partly filled before the restart
+    let placed = limit_buy(dec!(1));
+    let lost = limit_buy(dec!(1));
+    log.record(&OrderIntent::new(&placed)).unwrap();
+    log.record(&OrderIntent::new(&lost)).unwrap();
+    mock.script_order(OrderScript::accept().fill(dec!(0.25)));
+    adapter.place_order(&placed).await.unwrap();
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while mock.order(&placed.client_order_id).unwrap()["state"] != "partially_filled" {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+
+    let manager = OrderManager::new(OrderManagerConfig::default(), adapter.clone())
+        .with_intent_log(log.clone());
+    let venue = OkxIntentVenue::new(rest);
+    let policy = IntentRecoveryPolicy::default();
+
+    // With OKX unreachable, every intent waits for the next recovery
+    mock.fail_next("/api/v5/trade/order", Fault::Status(502));
+    mock.fail_next("/api/v5/trade/order", Fault::Status(502));
+    let report = manager.recover_intents(&venue, &policy).await.unwrap();
+    assert_eq!(report.unresolved.len(), 2);
+    assert_eq!(log.unresolved().unwrap().len(), 2);
+
+    let report = manager.recover_intents(&venue, &policy).await.unwrap();
+    assert_eq!(report.recovered.len(), 1);
+    assert_eq!(report.not_placed[0].client_order_id, lost.client_order_id);
+    assert!(log.unresolved().unwrap().is_empty());
+
+    let order_id = manager
+        .resolve_exchange_order(&report.recovered[0])
+        .unwrap()
+        .expect("recovered order tracked");
+    let (order, state) = manager.get_order(order_id).unwrap();
+    assert_eq!(state, OrderState::PartiallyFilled);
+    assert_eq!(order.filled_quantity.as_decimal(), dec!(0.25));
+}
+
+#[tokio::test]
+async fn test_orders_channel_drives_fills_before_delayed_ack() {
+    let mock = mock().await;
+    let rest = rest(&mock);
+    let adapter = Arc::new(OkxAdapter::new(rest));
+    let manager = OrderManager::new(OrderManagerConfig::default(), adapter.clone());
+    let mut events = manager.subscribe_events().unwrap();
+
+    let mut ws = OkxWebSocketClient::new(credentials(), true)
+        .with_urls(&mock.ws_public_url(), &mock.ws_private_url());
+    ws.connect().await.unwrap();
+    ws.subscribe(vec![SubscriptionRequest {
+        channel: Channel::Orders,
+        instrument_id: Some("BTC-USDT".to_string()),
+        instrument_type: Some("ANY".to_string()),
+    }])
+    .await
+    .unwrap();
+    assert!(
+        mock.wait_for_subscription("orders", "BTC-USDT", Duration::from_secs(5))
+            .await
+    );
+
+    // The ack lags behind the fills pushed on the orders channel
+    mock.script_order(
+        OrderScript::accept()
+            .ack_after(Duration::from_millis(300))
+            .fill(dec!(0.4))
+            .fill_rest(),
+    );
+    let order = limit_buy(dec!(1));
+    let placing = {
+        let adapter = adapter.clone();
+        let order = order.clone();
+        tokio::spawn(async move { adapter.place_order(&order).await })
+    };
+
+    let mut order_id = None;
+    let mut states = Vec::new();
+    while states.last() != Some(&OrderState::Filled) {
+        let update: OrderData = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let Some(WebSocketEvent::Order(update)) = ws.next_message().await.unwrap() {
+                    return *update;
+                }
+            }
+        })
+        .await
+        .expect("order update");
+
+        let id = manager
+            .resolve_exchange_order(&update)
+            .unwrap()
+            .expect("order attributed to its strategy");
+        order_id = Some(id);
+        if update.state != "live" {
+            manager
+                .record_fill(
+                    id,
+                    Quantity::new(Decimal::from_str(&update.acc_fill_sz).unwrap()).unwrap(),
+                    Price::new(Decimal::from_str(&update.avg_px).unwrap()).unwrap(),
+                )
+                .unwrap();
+        }
+        states.push(manager.get_order(id).unwrap().1);
+    }
+    assert!(!placing.is_finished(), "fills should beat the ack");
+    assert_eq!(
+        states,
+        [
+            OrderState::Acknowledged,
+            OrderState::PartiallyFilled,
+            OrderState::Filled
+        ]
+    );
+
+    // The late ack names the order the fills were booked against
+    let exchange_id = placing.await.unwrap().unwrap();
+    let latest: OrderData =
+        serde_json::from_value(mock.order(&order.client_order_id).unwrap()).unwrap();
+    assert_eq!(latest.ord_id, exchange_id);
+    assert_eq!(manager.resolve_exchange_order(&latest).unwrap(), order_id);
+    let order_id = order_id.unwrap();
+
+    let mut adopted = false;
+    let mut filled = false;
+    while let Ok(event) = events.try_recv() {
+        match event {
+            OrderEvent::OrderAdopted { order_id: id, .. } => adopted = id == order_id,
+            OrderEvent::OrderFilled { order_id: id, .. } => filled = id == order_id,
+            _ => {}
+        }
+    }
+    assert!(adopted && filled);
+    ws.disconnect().await.unwrap();
+}
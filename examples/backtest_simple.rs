@@ -1,6 +1,6 @@
 use chrono::Utc;
 use ea_okx_backtest::{BacktestConfig, BacktestEngine, CostModel, PositionSizing, MockDataSource};
-use ea_okx_core::{Symbol, Candle, Price, Quantity};
+use ea_okx_core::{Symbol, Candle, Interval, Price, Quantity};
 use ea_okx_strategy::traits::{Strategy, StrategyConfig, MarketDataEvent};
 use ea_okx_strategy::signal::{Signal, SignalType};
 use ea_okx_strategy::metrics::PerformanceMetrics;
@@ -160,7 +160,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         start_time: Utc::now() - chrono::Duration::days(90),
         end_time: Utc::now(),
         symbols: vec![Symbol::new("BTC-USDT").unwrap()],
-        interval: "1H".to_string(),
+        interval: Interval::OneHour,
         cost_model: CostModel::okx_spot_conservative(),
         verbose: true,
         max_positions: 1,
@@ -196,7 +196,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         let candle = Candle {
             symbol: Symbol::new("BTC-USDT").unwrap(),
             timestamp,
-            interval: "1H".to_string(),
+            interval: Interval::OneHour,
             open: Price::new(close - dec!(100.0)).unwrap(),
             high: Price::new(close + dec!(200.0)).unwrap(),
             low: Price::new(close - dec!(200.0)).unwrap(),
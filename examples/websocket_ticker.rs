@@ -18,6 +18,7 @@
 
 use ea_okx_client::auth::Credentials;
 use ea_okx_client::models::{Channel, SubscriptionRequest, WebSocketEvent};
+use ea_okx_core::Interval;
 use ea_okx_client::websocket::{OkxWebSocketClient, WebSocketConfig};
 use std::env;
 use tracing::{error, info};
@@ -75,7 +76,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     client.subscribe(subscriptions).await?;
 
     // Also subscribe to 1-minute candles for BTC
-    let candle_sub = SubscriptionRequest::new(Channel::Candle1m, "BTC-USDT");
+    let candle_sub = SubscriptionRequest::new(Channel::Candle(Interval::OneMinute), "BTC-USDT");
     info!("Subscribing to BTC-USDT 1m candles");
     client.subscribe(vec![candle_sub]).await?;
 
@@ -101,7 +102,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     ticker.vol_24h
                 );
             }
-            WebSocketEvent::Candle(candle) => {
+            WebSocketEvent::Candle(_, candle) => {
                 match candle.parse() {
                     Ok(parsed) => {
                         info!(
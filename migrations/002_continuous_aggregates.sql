@@ -1,4 +1,5 @@
 -- Continuous aggregates and additional optimizations
+-- migrate:no-transaction (continuous aggregates cannot be created inside one)
 
 -- Continuous aggregate for 5-minute OHLCV from ticks
 CREATE MATERIALIZED VIEW market_ohlcv_5m
@@ -0,0 +1,17 @@
+-- Perpetual swap funding rate history
+
+CREATE TABLE funding_rates (
+    inst_id VARCHAR(40) NOT NULL,
+    symbol VARCHAR(20) NOT NULL,
+    funding_time TIMESTAMPTZ NOT NULL,
+    funding_rate DECIMAL(20,10) NOT NULL,
+    next_funding_rate DECIMAL(20,10),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (inst_id, funding_time)
+);
+
+SELECT create_hypertable('funding_rates', 'funding_time', chunk_time_interval => INTERVAL '30 days');
+CREATE INDEX idx_funding_rates_symbol_time ON funding_rates(symbol, funding_time DESC);
+
+-- Compression (funding history is small but kept for backtests)
+SELECT add_compression_policy('funding_rates', INTERVAL '90 days');
@@ -0,0 +1,22 @@
+-- Compressed order book storage
+--
+-- Each row holds a zstd-compressed, delta-encoded run of snapshots for one
+-- symbol. Retention is tiered per symbol and applied by the application's
+-- vacuum job (full depth, then reduced depth, then deleted), so no
+-- TimescaleDB retention policy is attached.
+
+CREATE TABLE order_book_blocks (
+    id BIGSERIAL PRIMARY KEY,
+    symbol VARCHAR(20) NOT NULL,
+    depth_level VARCHAR(20) NOT NULL,
+    start_time TIMESTAMPTZ NOT NULL,
+    end_time TIMESTAMPTZ NOT NULL,
+    snapshot_count INTEGER NOT NULL CHECK (snapshot_count > 0),
+    max_depth INTEGER CHECK (max_depth > 0),
+    payload BYTEA NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (end_time >= start_time)
+);
+
+CREATE INDEX idx_order_book_blocks_symbol_time ON order_book_blocks(symbol, start_time, end_time);
+CREATE INDEX idx_order_book_blocks_end_time ON order_book_blocks(end_time);
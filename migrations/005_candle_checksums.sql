@@ -0,0 +1,15 @@
+-- Per-day candle checksums
+--
+-- One SHA-256 per (symbol, interval, UTC day), recorded after each backfill.
+-- Integrity verification recomputes these from market_ohlcv and re-downloads
+-- days that no longer match.
+
+CREATE TABLE candle_checksums (
+    symbol VARCHAR(20) NOT NULL,
+    interval VARCHAR(10) NOT NULL,
+    day DATE NOT NULL,
+    candle_count INTEGER NOT NULL CHECK (candle_count >= 0),
+    checksum CHAR(64) NOT NULL,
+    computed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (symbol, interval, day)
+);
@@ -0,0 +1,44 @@
+-- Live account equity curve
+--
+-- One row per minute with total equity, per-currency equity and per-symbol
+-- signed position notional. Raw samples are kept for 90 days; the hourly
+-- aggregate keeps the long-run curve for 5 years.
+
+CREATE TABLE account_equity (
+    timestamp TIMESTAMPTZ NOT NULL,
+    total_equity DECIMAL(30,10) NOT NULL,
+    margin_ratio DECIMAL(20,10),
+    gross_exposure DECIMAL(30,10) NOT NULL,
+    net_exposure DECIMAL(30,10) NOT NULL,
+    balances JSONB NOT NULL DEFAULT '{}',
+    exposures JSONB NOT NULL DEFAULT '{}',
+    PRIMARY KEY (timestamp)
+);
+
+SELECT create_hypertable('account_equity', 'timestamp', chunk_time_interval => INTERVAL '7 days');
+
+-- Compression and retention
+SELECT add_compression_policy('account_equity', INTERVAL '7 days');
+SELECT add_retention_policy('account_equity', INTERVAL '90 days');
+
+-- Hourly equity (last sample in each hour); real-time so the current hour
+-- is included before it is materialized
+CREATE MATERIALIZED VIEW account_equity_1h
+WITH (timescaledb.continuous, timescaledb.materialized_only = false) AS
+SELECT
+    time_bucket('1 hour', timestamp) AS timestamp,
+    last(total_equity, timestamp) AS total_equity,
+    last(margin_ratio, timestamp) AS margin_ratio,
+    last(gross_exposure, timestamp) AS gross_exposure,
+    last(net_exposure, timestamp) AS net_exposure,
+    last(balances, timestamp) AS balances,
+    last(exposures, timestamp) AS exposures
+FROM account_equity
+GROUP BY time_bucket('1 hour', timestamp);
+
+SELECT add_continuous_aggregate_policy('account_equity_1h',
+    start_offset => INTERVAL '1 day',
+    end_offset => INTERVAL '1 hour',
+    schedule_interval => INTERVAL '1 hour');
+
+SELECT add_retention_policy('account_equity_1h', INTERVAL '5 years');
@@ -0,0 +1,46 @@
+-- Positioning statistics from the OKX trading statistics (rubik) endpoints
+--
+-- Per-currency aggregates across all contracts, one row per reporting
+-- period ("5m", "1H" or "1D") starting at `timestamp`.
+
+CREATE TABLE open_interest (
+    currency VARCHAR(20) NOT NULL,
+    period VARCHAR(10) NOT NULL,
+    timestamp TIMESTAMPTZ NOT NULL,
+    open_interest DECIMAL(30,10) NOT NULL,
+    volume DECIMAL(30,10) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (currency, period, timestamp)
+);
+
+SELECT create_hypertable('open_interest', 'timestamp', chunk_time_interval => INTERVAL '30 days');
+
+-- Taker volume is reported separately for spot and contracts
+CREATE TABLE taker_volume (
+    currency VARCHAR(20) NOT NULL,
+    inst_type VARCHAR(20) NOT NULL,
+    period VARCHAR(10) NOT NULL,
+    timestamp TIMESTAMPTZ NOT NULL,
+    buy_volume DECIMAL(30,10) NOT NULL,
+    sell_volume DECIMAL(30,10) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (currency, inst_type, period, timestamp)
+);
+
+SELECT create_hypertable('taker_volume', 'timestamp', chunk_time_interval => INTERVAL '30 days');
+
+CREATE TABLE long_short_ratio (
+    currency VARCHAR(20) NOT NULL,
+    period VARCHAR(10) NOT NULL,
+    timestamp TIMESTAMPTZ NOT NULL,
+    ratio DECIMAL(20,10) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (currency, period, timestamp)
+);
+
+SELECT create_hypertable('long_short_ratio', 'timestamp', chunk_time_interval => INTERVAL '30 days');
+
+-- Compression (kept for backtests)
+SELECT add_compression_policy('open_interest', INTERVAL '90 days');
+SELECT add_compression_policy('taker_volume', INTERVAL '90 days');
+SELECT add_compression_policy('long_short_ratio', INTERVAL '90 days');
@@ -0,0 +1,54 @@
+-- Order state and raised alerts
+--
+-- `trades` records each order's execution; `orders` holds the latest state
+-- of every order the engine created, including ones that never reached
+-- the exchange.
+
+CREATE TABLE orders (
+    id UUID PRIMARY KEY,
+    okx_order_id VARCHAR(50),
+    client_order_id VARCHAR(50) NOT NULL UNIQUE,
+    tag VARCHAR(16),
+    strategy_id UUID NOT NULL REFERENCES strategies(id),
+    symbol VARCHAR(40) NOT NULL,
+    side VARCHAR(4) NOT NULL CHECK (side IN ('buy', 'sell')),
+    order_type VARCHAR(20) NOT NULL CHECK (order_type IN ('market', 'limit', 'post_only', 'ioc', 'fok', 'stop_loss', 'take_profit', 'trailing_stop', 'iceberg')),
+    quantity DECIMAL(20,8) NOT NULL CHECK (quantity > 0),
+    price DECIMAL(20,8) CHECK (price > 0),
+    reduce_only BOOLEAN NOT NULL DEFAULT FALSE,
+    avg_fill_price DECIMAL(20,8),
+    filled_quantity DECIMAL(20,8) NOT NULL DEFAULT 0 CHECK (filled_quantity >= 0),
+    status VARCHAR(20) NOT NULL CHECK (status IN ('created', 'submitted', 'partial', 'filled', 'cancelled', 'rejected', 'failed')),
+    reject_reason TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    submitted_at TIMESTAMPTZ,
+    first_fill_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ,
+    latency_ms BIGINT,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_orders_strategy ON orders(strategy_id, created_at DESC);
+CREATE INDEX idx_orders_symbol ON orders(symbol, created_at DESC);
+CREATE INDEX idx_orders_open ON orders(status) WHERE status IN ('created', 'submitted', 'partial');
+CREATE UNIQUE INDEX idx_orders_okx_order_id ON orders(okx_order_id) WHERE okx_order_id IS NOT NULL;
+
+-- Alerts raised by the monitoring rules
+CREATE TABLE alerts (
+    id UUID PRIMARY KEY,
+    rule_id UUID NOT NULL,
+    rule_name VARCHAR(100) NOT NULL,
+    severity VARCHAR(20) NOT NULL CHECK (severity IN ('info', 'warning', 'critical', 'emergency')),
+    message TEXT NOT NULL,
+    metric_name VARCHAR(100) NOT NULL,
+    metric_value DOUBLE PRECISION NOT NULL,
+    threshold DOUBLE PRECISION NOT NULL,
+    triggered_at TIMESTAMPTZ NOT NULL,
+    acknowledged BOOLEAN NOT NULL DEFAULT FALSE,
+    acknowledged_at TIMESTAMPTZ,
+    acknowledged_by VARCHAR(100),
+    metadata JSONB NOT NULL DEFAULT '{}'
+);
+
+CREATE INDEX idx_alerts_triggered ON alerts(triggered_at DESC);
+CREATE INDEX idx_alerts_unacknowledged ON alerts(severity, triggered_at DESC) WHERE NOT acknowledged;
@@ -0,0 +1,20 @@
+-- Tick storage maintenance
+--
+-- Chunk sizing, compression and retention for `market_ticks` are configured
+-- per symbol by the application and applied by its tick maintenance job, so
+-- the fixed policies from the initial schema are removed. Compressed chunks
+-- are segmented by symbol, so reading or deleting one symbol's ticks leaves
+-- the other symbols' segments compressed.
+
+SELECT remove_retention_policy('market_ticks', if_exists => TRUE);
+SELECT remove_compression_policy('market_ticks', if_exists => TRUE);
+
+-- Compression settings cannot change while chunks are compressed
+SELECT decompress_chunk(chunk, if_compressed => TRUE)
+FROM show_chunks('market_ticks') AS chunk;
+
+ALTER TABLE market_ticks SET (
+    timescaledb.compress,
+    timescaledb.compress_segmentby = 'symbol',
+    timescaledb.compress_orderby = 'timestamp DESC, id DESC'
+);
@@ -0,0 +1,23 @@
+-- Candle intervals
+--
+-- `market_ohlcv` accepted six intervals; it now takes the storage label of
+-- every `ea_okx_core::Interval`: all OKX bars from 1s to 3M, their UTC
+-- anchored variants and the 5s/15s bars built from trades. Hour, day and
+-- week units are lowercase, months stay uppercase.
+
+-- The new constraint is checked against every row, compressed chunks
+-- included; the compression policy compresses them again
+SELECT decompress_chunk(chunk, if_compressed => TRUE)
+FROM show_chunks('market_ohlcv') AS chunk;
+
+ALTER TABLE market_ohlcv DROP CONSTRAINT IF EXISTS market_ohlcv_interval_check;
+ALTER TABLE market_ohlcv ADD CONSTRAINT market_ohlcv_interval_check CHECK (interval IN (
+    '1s', '5s', '15s', '1m', '3m', '5m', '15m', '30m', '1h', '2h', '4h', '6h',
+    '12h', '1d', '2d', '3d', '1w', '1M', '3M', '6hutc', '12hutc', '1dutc',
+    '2dutc', '3dutc', '1wutc', '1Mutc', '3Mutc'
+));
+
+-- Checksums were recorded under whatever label the caller passed
+UPDATE candle_checksums
+SET interval = translate(interval, 'HDW', 'hdw')
+WHERE interval <> translate(interval, 'HDW', 'hdw');
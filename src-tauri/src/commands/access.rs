@@ -0,0 +1,37 @@
+use crate::error::CommandResult;
+use crate::services::access::{AccessAuditEntry, Caller};
+use crate::state::AppState;
+use chrono::{DateTime, Utc};
+
+/// Sign in as a user from `users.json`
+#[tauri::command]
+pub async fn sign_in(
+    name: String,
+    passcode: String,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<Caller> {
+    state.access.sign_in(&name, &passcode)
+}
+
+/// End the session; commands then run as an anonymous viewer
+#[tauri::command]
+pub async fn sign_out(state: tauri::State<'_, AppState>) -> CommandResult<Caller> {
+    Ok(state.access.sign_out())
+}
+
+/// Get the identity and role commands currently run as
+#[tauri::command]
+pub async fn get_session(state: tauri::State<'_, AppState>) -> CommandResult<Caller> {
+    Ok(state.access.caller())
+}
+
+/// Query the audit log of admin commands, refused commands and sign-ins,
+/// newest entries first
+#[tauri::command]
+pub async fn get_access_audit_log(
+    since: Option<DateTime<Utc>>,
+    limit: Option<usize>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<Vec<AccessAuditEntry>> {
+    Ok(state.access.audit_log(since, limit))
+}
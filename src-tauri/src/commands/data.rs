@@ -1,4 +1,22 @@
+use crate::error::{CommandError, CommandResult, ErrorCode};
+use crate::state::AppState;
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
+use data::storage::TimescaleStorage;
+use data::{
+    CandleDownloader, IntegrityReport, LongShortRatio, OpenInterest, OrderBookColumns, OrderBookQuery, PositioningConfig, PriceCache,
+    PriceCacheStats, ReferencePrice, SymbolQuality, TakerVolume,
+};
+use ea_okx_core::exchange::InstrumentInfo;
+use ea_okx_core::types::Price;
+use ea_okx_core::types::Symbol;
+use ea_okx_core::{InstrumentKind, Interval};
+use ea_okx_trading::{CatalogSync, SymbolQuery};
+use rust_decimal::prelude::ToPrimitive;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Most stored candles one resampling request reads
+const MAX_RESAMPLE_SOURCE_CANDLES: i64 = 200_000;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(dead_code)]
@@ -22,24 +40,282 @@ pub struct Candle {
 
 /// Subscribe to market data
 #[tauri::command]
-pub async fn subscribe_market_data(symbols: Vec<String>) -> Result<(), String> {
+pub async fn subscribe_market_data(symbols: Vec<String>) -> CommandResult<()> {
     log::info!("Subscribing to market data: {:?}", symbols);
     // TODO: Integrate with WebSocket service
     Ok(())
 }
 
-/// Get latest price
+/// Latest price of `symbol` from the price cache, falling back to the last
+/// blended reference price
 #[tauri::command]
-pub async fn get_latest_price(symbol: String) -> Result<f64, String> {
-    log::info!("Fetching latest price for: {}", symbol);
-    // TODO: Integrate with data service
-    Ok(45000.0) // Mock price
+pub async fn get_latest_price(symbol: String, state: tauri::State<'_, AppState>) -> CommandResult<f64> {
+    let symbol = Symbol::new(&symbol)?;
+    let price = match state.price_cache.get_price(&symbol).await? {
+        Some(price) => price,
+        None => {
+            let reference = state.reference_prices.latest(&symbol).ok_or_else(|| {
+                CommandError::not_found(format!("No price known for {}", symbol.as_str()))
+            })?;
+            let price = Price::new(reference.price)?;
+            state.price_cache.put_price(&symbol, price).await?;
+            price
+        }
+    };
+    price.as_decimal().to_f64().ok_or_else(|| CommandError::internal("Price out of range"))
+}
+
+/// Hit, miss and latency counters of the latest price cache
+#[tauri::command]
+pub async fn get_price_cache_stats(state: tauri::State<'_, AppState>) -> CommandResult<PriceCacheStats> {
+    Ok(state.price_cache.stats())
 }
 
 /// Get candles
 #[tauri::command]
-pub async fn get_candles(symbol: String, interval: String, limit: Option<usize>) -> Result<Vec<Candle>, String> {
+pub async fn get_candles(symbol: String, interval: String, limit: Option<usize>) -> CommandResult<Vec<Candle>> {
     log::info!("Fetching candles for: {} (interval: {}, limit: {:?})", symbol, interval, limit);
     // TODO: Integrate with data service
     Ok(vec![])
 }
+
+/// `symbol` candles of `target_interval` opening within `[start, end)`,
+/// merged on the fly from stored `source_interval` candles
+///
+/// Lets charts show any timeframe the stored interval fits into, e.g. `15m`,
+/// `4H` or `1W` from `1m` candles, without waiting for batch aggregates.
+#[tauri::command]
+pub async fn get_candles_resampled(
+    symbol: String,
+    source_interval: String,
+    target_interval: String,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<Vec<data::storage::Candle>> {
+    log::info!("Resampling {} {} candles into {}: {} to {}", symbol, source_interval, target_interval, start, end);
+
+    if start >= end {
+        return Err(CommandError::validation("start must be before end"));
+    }
+    let source: Interval = source_interval.parse()?;
+    let target: Interval = target_interval.parse()?;
+    if !data::can_resample(source, target) {
+        return Err(CommandError::validation(format!("{} candles cannot be resampled into {}", source, target)));
+    }
+    // Resampling needs fixed-length source bars, so the duration is known
+    let length = source.duration().map_or(1, |d| d.num_milliseconds().max(1));
+    if (end - start).num_milliseconds() / length > MAX_RESAMPLE_SOURCE_CANDLES {
+        return Err(CommandError::validation(format!(
+            "Range covers more than {} {} candles; narrow it or resample from a longer interval",
+            MAX_RESAMPLE_SOURCE_CANDLES, source
+        )));
+    }
+    let storage = state.market_storage.as_ref().ok_or_else(|| {
+        CommandError::new(ErrorCode::Unavailable, "Market data store is not configured")
+    })?;
+
+    let symbol = Symbol::new(&symbol)?;
+    storage.query_candles_resampled(&symbol, source, target, start, end).await
+        .map_err(|e| CommandError::from(e).context("Failed to resample candles"))
+}
+
+/// Verify stored candles for `symbol` against their per-day checksums over
+/// `[start_date, end_date]` (YYYY-MM-DD, inclusive)
+///
+/// With `redownload`, mismatched days are fetched again from the exchange.
+#[tauri::command]
+pub async fn verify_data_integrity(
+    symbol: String,
+    interval: String,
+    start_date: String,
+    end_date: String,
+    redownload: Option<bool>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<IntegrityReport> {
+    log::info!("Verifying {} {} candles: {} to {}", symbol, interval, start_date, end_date);
+
+    let storage = state.market_storage.as_ref().ok_or_else(|| {
+        CommandError::new(ErrorCode::Unavailable, "Market data store is not configured")
+    })?;
+    let downloader: Option<&dyn CandleDownloader> = if redownload.unwrap_or(false) {
+        let client = state.okx_client.as_ref().ok_or_else(|| {
+            CommandError::new(ErrorCode::Unavailable, "Re-downloading candles requires an exchange connection")
+        })?;
+        Some(client.as_ref())
+    } else {
+        None
+    };
+
+    let symbol = Symbol::new(&symbol)?;
+    let interval: Interval = interval.parse()?;
+    let parse = |value: &str| {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map_err(|e| CommandError::validation(format!("Invalid date '{}': {}", value, e)))
+    };
+    let start = parse(&start_date)?;
+    let end = parse(&end_date)?;
+    if start > end {
+        return Err(CommandError::validation("start_date must not be after end_date"));
+    }
+
+    let start = start.and_time(NaiveTime::MIN).and_utc();
+    let end = end.and_time(NaiveTime::MIN).and_utc() + Duration::days(1);
+    storage.verify_data_integrity(&symbol, interval, start, end, downloader).await
+        .map_err(|e| CommandError::from(e).context("Failed to verify candle data"))
+}
+
+/// Stored order books for `symbol` within `[start, end)`, column by column
+///
+/// `sample_secs` keeps the first snapshot of every bucket of that many
+/// seconds and `depth` the best levels per side; both default to everything.
+#[tauri::command]
+pub async fn get_orderbook_history(
+    symbol: String,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    sample_secs: Option<u32>,
+    depth: Option<usize>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<OrderBookColumns> {
+    log::info!("Fetching {} order books: {} to {} (every {:?}s, depth {:?})", symbol, start, end, sample_secs, depth);
+
+    if start >= end {
+        return Err(CommandError::validation("start must be before end"));
+    }
+    let storage = state.market_storage.as_ref().ok_or_else(|| {
+        CommandError::new(ErrorCode::Unavailable, "Market data store is not configured")
+    })?;
+
+    let symbol = Symbol::new(&symbol)?;
+    let query = OrderBookQuery { sample_secs, depth };
+    storage.query_orderbook_columns(&symbol, start, end, &query).await
+        .map_err(|e| CommandError::from(e).context("Failed to load order book history"))
+}
+
+/// Stored open interest and volume for `currency` (e.g. "BTC") within
+/// `[start, end)`; `period` defaults to `5m`
+#[tauri::command]
+pub async fn get_open_interest(
+    currency: String,
+    period: Option<String>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<Vec<OpenInterest>> {
+    let period = period.unwrap_or_else(|| PositioningConfig::default().period);
+    let storage = positioning_storage(&state, start, end)?;
+    storage.query_open_interest(&currency, &period, start, end).await
+        .map_err(|e| CommandError::from(e).context("Failed to load open interest"))
+}
+
+/// Stored taker buy and sell volume for `currency` within `[start, end)`
+///
+/// `inst_type` is `SPOT` or `CONTRACTS` and `period` defaults to `5m`.
+#[tauri::command]
+pub async fn get_taker_volume(
+    currency: String,
+    inst_type: Option<String>,
+    period: Option<String>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<Vec<TakerVolume>> {
+    let defaults = PositioningConfig::default();
+    let inst_type = inst_type.unwrap_or(defaults.taker_inst_type);
+    let period = period.unwrap_or(defaults.period);
+    let storage = positioning_storage(&state, start, end)?;
+    storage.query_taker_volume(&currency, &inst_type, &period, start, end).await
+        .map_err(|e| CommandError::from(e).context("Failed to load taker volume"))
+}
+
+/// Stored long/short account ratios for `currency` within `[start, end)`;
+/// `period` defaults to `5m`
+#[tauri::command]
+pub async fn get_long_short_ratio(
+    currency: String,
+    period: Option<String>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<Vec<LongShortRatio>> {
+    let period = period.unwrap_or_else(|| PositioningConfig::default().period);
+    let storage = positioning_storage(&state, start, end)?;
+    storage.query_long_short_ratio(&currency, &period, start, end).await
+        .map_err(|e| CommandError::from(e).context("Failed to load long/short ratio"))
+}
+
+fn positioning_storage<'a>(
+    state: &'a AppState,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> CommandResult<&'a Arc<TimescaleStorage>> {
+    if start >= end {
+        return Err(CommandError::validation("start must be before end"));
+    }
+    state.market_storage.as_ref().ok_or_else(|| {
+        CommandError::new(ErrorCode::Unavailable, "Market data store is not configured")
+    })
+}
+
+/// Blended reference price for `symbol`, refreshed from every configured source
+#[tauri::command]
+pub async fn get_reference_price(
+    symbol: String,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<ReferencePrice> {
+    if state.reference_prices.source_names().is_empty() {
+        return Err(CommandError::new(
+            ErrorCode::Unavailable,
+            "No reference price sources are configured",
+        ));
+    }
+
+    let symbol = Symbol::new(&symbol)?;
+    state.reference_prices.refresh(&symbol).await
+        .map_err(|e| CommandError::from(e).context("Failed to compute reference price"))
+}
+
+/// Rolling feed quality for `symbol` over the last hour and day
+#[tauri::command]
+pub async fn get_data_quality(
+    symbol: String,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<SymbolQuality> {
+    let symbol = Symbol::new(&symbol)?;
+    state.data_quality.symbol_quality(&symbol).ok_or_else(|| {
+        CommandError::not_found(format!("No market data seen for {} in the last day", symbol.as_str()))
+    })
+}
+
+/// Search the symbols OKX lists, e.g. `search_symbols("SOL", kind = "swap")`
+#[tauri::command]
+pub async fn search_symbols(
+    query: String,
+    kind: Option<String>,
+    quote: Option<String>,
+    tradable_only: Option<bool>,
+    limit: Option<usize>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<Vec<InstrumentInfo>> {
+    let kind = kind.map(|k| k.parse::<InstrumentKind>()).transpose()?;
+    let query = SymbolQuery {
+        text: query,
+        kind,
+        quote,
+        tradable_only: tradable_only.unwrap_or(true),
+        limit: Some(limit.unwrap_or(50)),
+    };
+    Ok(state.symbol_catalog.search(&query))
+}
+
+/// Refresh the symbol catalog from the OKX instruments endpoint now
+#[tauri::command]
+pub async fn sync_symbol_catalog(state: tauri::State<'_, AppState>) -> CommandResult<CatalogSync> {
+    let client = state.okx_client.as_ref().ok_or_else(|| {
+        CommandError::new(ErrorCode::Unavailable, "OKX client is not configured")
+    })?;
+    let adapter = ea_okx_client::OkxAdapter::new(client.clone());
+    state.symbol_catalog.sync(&adapter).await
+        .map_err(|e| CommandError::from(e).context("Failed to sync symbol catalog"))
+}
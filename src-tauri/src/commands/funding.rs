@@ -0,0 +1,125 @@
+use crate::error::{CommandError, CommandResult, ErrorCode};
+use crate::state::AppState;
+use ea_okx_client::models::{
+    AccountType, AssetBalanceData, DepositAddressData, FundsTransferRequest, TransferData,
+    WithdrawalHistoryRequest, WithdrawalRecord,
+};
+use ea_okx_client::OkxRestClient;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+fn okx_client(state: &AppState) -> CommandResult<Arc<OkxRestClient>> {
+    state.okx_client.clone().ok_or_else(|| {
+        CommandError::new(ErrorCode::Unavailable, "OKX API credentials are not configured")
+    })
+}
+
+fn parse_account(value: &str) -> CommandResult<AccountType> {
+    match value.to_lowercase().as_str() {
+        "funding" => Ok(AccountType::Funding),
+        "trading" => Ok(AccountType::Trading),
+        _ => Err(CommandError::validation(format!(
+            "Invalid account '{}': expected funding or trading",
+            value
+        ))),
+    }
+}
+
+/// Allow or refuse funds transfers for this session
+#[tauri::command]
+pub async fn set_transfers_enabled(
+    enabled: bool,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<()> {
+    log::warn!("Setting transfers enabled: {}", enabled);
+
+    state.transfers_enabled.store(enabled, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Transfer funds between the funding and trading accounts
+///
+/// Refused unless transfers have been enabled with `set_transfers_enabled`.
+#[tauri::command]
+pub async fn transfer_funds(
+    ccy: String,
+    amount: String,
+    from: String,
+    to: String,
+    client_id: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<TransferData> {
+    log::info!("Transferring {} {} from {} to {}", amount, ccy, from, to);
+
+    if !state.transfers_enabled.load(Ordering::SeqCst) {
+        return Err(CommandError::new(
+            ErrorCode::InvalidState,
+            "Transfers are disabled; enable them before moving funds",
+        ));
+    }
+
+    let from = parse_account(&from)?;
+    let to = parse_account(&to)?;
+    if from == to {
+        return Err(CommandError::validation("Source and destination accounts must differ"));
+    }
+    let amt = Decimal::from_str(&amount)
+        .ok()
+        .filter(|a| *a > Decimal::ZERO)
+        .ok_or_else(|| CommandError::validation(format!("Invalid amount: {}", amount)))?;
+
+    let request = FundsTransferRequest {
+        ccy: ccy.to_uppercase(),
+        amt: amt.to_string(),
+        from,
+        to,
+        client_id,
+    };
+    okx_client(&state)?.transfer_funds(&request).await
+        .map_err(|e| CommandError::from(e).context("Transfer failed"))
+}
+
+/// Get deposit addresses for a currency
+#[tauri::command]
+pub async fn get_deposit_addresses(
+    ccy: String,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<Vec<DepositAddressData>> {
+    log::info!("Fetching deposit addresses for {}", ccy);
+
+    okx_client(&state)?.deposit_addresses(&ccy.to_uppercase()).await
+        .map_err(|e| CommandError::from(e).context("Failed to fetch deposit addresses"))
+}
+
+/// Get withdrawal history, newest first
+#[tauri::command]
+pub async fn get_withdrawal_history(
+    ccy: Option<String>,
+    limit: Option<u32>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<Vec<WithdrawalRecord>> {
+    log::info!("Fetching withdrawal history: {:?}", ccy);
+
+    let request = WithdrawalHistoryRequest {
+        ccy: ccy.map(|c| c.to_uppercase()),
+        limit: limit.map(|l| l.min(100)),
+        ..Default::default()
+    };
+    okx_client(&state)?.withdrawal_history(&request).await
+        .map_err(|e| CommandError::from(e).context("Failed to fetch withdrawal history"))
+}
+
+/// Get funding account balances
+#[tauri::command]
+pub async fn get_asset_balances(
+    ccy: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<Vec<AssetBalanceData>> {
+    log::info!("Fetching asset balances: {:?}", ccy);
+
+    let ccy = ccy.map(|c| c.to_uppercase());
+    okx_client(&state)?.asset_balances(ccy.as_deref()).await
+        .map_err(|e| CommandError::from(e).context("Failed to fetch asset balances"))
+}
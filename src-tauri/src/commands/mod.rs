@@ -1,8 +1,11 @@
 // Tauri command modules
 
+pub mod access;
 pub mod strategy;
 pub mod trading;
 pub mod data;
 pub mod risk;
 pub mod system;
 pub mod websocket;
+pub mod funding;
+pub mod notifications;
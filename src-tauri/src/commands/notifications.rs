@@ -0,0 +1,79 @@
+use crate::error::{CommandError, CommandResult};
+use crate::services::notifications::{
+    DeliveryPreference, Notification, NotificationCategory, NotificationQuery, QuietHours,
+};
+use crate::state::AppState;
+use std::collections::HashMap;
+
+/// Get the notification history, newest first
+#[tauri::command]
+pub async fn get_notifications(
+    category: Option<NotificationCategory>,
+    unread_only: Option<bool>,
+    limit: Option<usize>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<Vec<Notification>> {
+    log::info!("Fetching notifications (category: {:?}, limit: {:?})", category, limit);
+
+    let query = NotificationQuery {
+        category,
+        unread_only: unread_only.unwrap_or(false),
+        limit,
+    };
+    Ok(state.notifications.list(&query).await)
+}
+
+/// Mark a notification read
+#[tauri::command]
+pub async fn mark_notification_read(
+    id: String,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<Notification> {
+    let id = uuid::Uuid::parse_str(&id)
+        .map_err(|e| CommandError::validation(format!("Invalid notification ID: {}", e)))?;
+    state.notifications.mark_read(id).await
+        .map_err(|e| CommandError::internal(format!("Failed to save notification history: {}", e)))?
+        .ok_or_else(|| CommandError::not_found(format!("Notification not found: {}", id)))
+}
+
+/// Get the delivery preference of every notification category
+#[tauri::command]
+pub async fn get_notification_preferences(
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<HashMap<NotificationCategory, DeliveryPreference>> {
+    Ok(state.notifications.preferences().await)
+}
+
+/// Set how notifications of one category are delivered
+#[tauri::command]
+pub async fn set_notification_preference(
+    category: NotificationCategory,
+    preference: DeliveryPreference,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<()> {
+    log::info!("Setting {:?} notification preference: {:?}", category, preference);
+    state.notifications.set_preference(category, preference).await
+        .map_err(|e| CommandError::internal(format!("Failed to save notification preferences: {}", e)))
+}
+
+/// Get the quiet hours schedule, if one is set
+#[tauri::command]
+pub async fn get_quiet_hours(
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<Option<QuietHours>> {
+    Ok(state.notifications.quiet_hours().await)
+}
+
+/// Set the quiet hours schedule; `None` turns quiet hours off
+#[tauri::command]
+pub async fn set_quiet_hours(
+    quiet_hours: Option<QuietHours>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<()> {
+    if let Some(schedule) = &quiet_hours {
+        schedule.validate().map_err(CommandError::validation)?;
+    }
+    log::info!("Setting quiet hours: {:?}", quiet_hours);
+    state.notifications.set_quiet_hours(quiet_hours).await
+        .map_err(|e| CommandError::internal(format!("Failed to save quiet hours: {}", e)))
+}
@@ -1,13 +1,18 @@
+use crate::error::{CommandError, CommandResult, ErrorCode};
+use crate::state::AppState;
+use chrono::{DateTime, Utc};
+use ea_okx_core::models::{Position, PositionSide};
+use ea_okx_core::types::{Price, Quantity, Symbol};
+use ea_okx_risk::{
+    AuditEntry, AuditQuery, DrawdownSchedule, DrawdownThrottleConfig, DrawdownThrottleSnapshot, ExposureBreakdown, LimitChange, OptimizationResult, OptimizerConfig, PortfolioOptimizer,
+    PortfolioState, RiskLimits, StrategyReturns, StressConfig, StressResult, StressScenario,
+    StressTester,
+};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use ea_okx_trading::{DailyLossEvent, DailyPnl};
 use serde::{Deserialize, Serialize};
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RiskLimits {
-    pub max_position_size: f64,
-    pub max_leverage: f64,
-    pub daily_loss_limit: f64,
-    pub max_concentration: f64,
-    pub min_margin_ratio: f64,
-}
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VaRResult {
@@ -17,30 +22,263 @@ pub struct VaRResult {
     pub method: String, // Historical, Parametric, MonteCarlo
 }
 
-/// Get current risk limits
+/// Drawdown schedules and where the portfolio stands against them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DrawdownThrottleStatus {
+    #[serde(flatten)]
+    pub snapshot: DrawdownThrottleSnapshot,
+    pub config: DrawdownThrottleConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StressPosition {
+    pub symbol: String,
+    pub side: String, // long, short, net
+    pub quantity: f64,
+    pub price: f64,
+    pub leverage: Option<f64>,
+    pub margin: Option<f64>,
+}
+
+/// A strategy to weigh and the backtest its returns are taken from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AllocationCandidate {
+    pub strategy_id: String,
+    pub backtest_id: String,
+}
+
+/// Optimized weights and the capital they assign to each strategy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AllocationPlan {
+    #[serde(flatten)]
+    pub result: OptimizationResult,
+    pub total_capital: Decimal,
+    pub capital: HashMap<uuid::Uuid, Decimal>,
+    /// Whether the capital was written to the strategies
+    pub applied: bool,
+}
+
+fn parse_change_id(change_id: &str) -> CommandResult<uuid::Uuid> {
+    uuid::Uuid::parse_str(change_id)
+        .map_err(|e| CommandError::validation(format!("Invalid change ID: {}", e)))
+}
+
+fn parse_strategy_id(strategy_id: &str) -> CommandResult<uuid::Uuid> {
+    uuid::Uuid::parse_str(strategy_id)
+        .map_err(|e| CommandError::validation(format!("Invalid strategy ID: {}", e)))
+}
+
+fn to_decimal(value: f64, field: &str) -> CommandResult<Decimal> {
+    Decimal::from_f64_retain(value)
+        .ok_or_else(|| CommandError::validation(format!("Invalid {}", field)))
+}
+
+fn to_position(input: &StressPosition) -> CommandResult<Position> {
+    let symbol = Symbol::new(&input.symbol)
+        .map_err(|e| CommandError::validation(format!("Invalid symbol: {}", e)))?;
+    let side = input.side.parse::<PositionSide>()
+        .map_err(|e| CommandError::validation(format!("Invalid side: {}", e)))?;
+    let quantity = Quantity::new(to_decimal(input.quantity, "quantity")?)
+        .map_err(|e| CommandError::validation(format!("Invalid quantity: {}", e)))?;
+    let price = Price::new(to_decimal(input.price, "price")?)
+        .map_err(|e| CommandError::validation(format!("Invalid price: {}", e)))?;
+
+    let mut position = Position::new(uuid::Uuid::nil(), symbol, side, quantity, price);
+    position.leverage = input.leverage.map(|l| to_decimal(l, "leverage")).transpose()?;
+    position.margin = input.margin.map(|m| to_decimal(m, "margin")).transpose()?;
+    Ok(position)
+}
+
+/// Get the risk limits currently in force
+#[tauri::command]
+pub async fn get_risk_limits(state: tauri::State<'_, AppState>) -> CommandResult<RiskLimits> {
+    let mut limits = state.risk_limits.write().await;
+    limits.apply_due(Utc::now())?;
+    Ok(limits.active_limits().clone())
+}
+
+/// Propose new risk limits
+///
+/// The change is held pending until confirmed with
+/// `confirm_risk_limit_change` and takes effect at `effective_at` (as soon as
+/// the approval policy allows when not given).
 #[tauri::command]
-pub async fn get_risk_limits() -> Result<RiskLimits, String> {
-    log::info!("Fetching risk limits");
-    Ok(RiskLimits {
-        max_position_size: 100000.0,
-        max_leverage: 3.0,
-        daily_loss_limit: 5000.0,
-        max_concentration: 0.25,
-        min_margin_ratio: 0.15,
+pub async fn update_risk_limits(
+    limits: RiskLimits,
+    reason: Option<String>,
+    effective_at: Option<DateTime<Utc>>,
+    proposed_by: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<LimitChange> {
+    let actor = limit_change_actor(&state, proposed_by).await?;
+    log::info!("Risk limit change proposed by {}: {:?}", actor, limits);
+
+    let change = state
+        .risk_limits
+        .write()
+        .await
+        .propose(limits, &actor, reason, effective_at, Utc::now())?;
+    Ok(change)
+}
+
+/// Get the risk limit change awaiting confirmation or its effective time
+#[tauri::command]
+pub async fn get_pending_risk_limit_change(
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<Option<LimitChange>> {
+    let mut limits = state.risk_limits.write().await;
+    limits.apply_due(Utc::now())?;
+    Ok(limits.open_change().cloned())
+}
+
+/// Confirm a proposed risk limit change
+#[tauri::command]
+pub async fn confirm_risk_limit_change(
+    change_id: String,
+    confirmed_by: Option<String>,
+    passcode: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<LimitChange> {
+    let id = parse_change_id(&change_id)?;
+    let actor = limit_change_actor(&state, confirmed_by).await?;
+
+    let change = state
+        .risk_limits
+        .write()
+        .await
+        .confirm(id, &actor, passcode.as_deref(), Utc::now())
+        .map_err(|e| CommandError::from(e).context("Risk limit change not confirmed"))?;
+    log::warn!("Risk limit change {} confirmed by {} ({:?})", id, actor, change.status);
+    Ok(change)
+}
+
+/// Withdraw a risk limit change before it takes effect
+#[tauri::command]
+pub async fn cancel_risk_limit_change(
+    change_id: String,
+    reason: Option<String>,
+    cancelled_by: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<LimitChange> {
+    let id = parse_change_id(&change_id)?;
+    let actor = state.access.actor(cancelled_by);
+
+    let change = state
+        .risk_limits
+        .write()
+        .await
+        .cancel(id, &actor, reason, Utc::now())?;
+    Ok(change)
+}
+
+/// Name to record for a step of a risk limit change
+///
+/// With a second approver required the proposer and confirmer must be
+/// different signed-in users; names claimed by the caller would let one
+/// person approve their own change.
+async fn limit_change_actor(state: &AppState, claimed: Option<String>) -> CommandResult<String> {
+    let second_approver = state.risk_limits.read().await.policy().require_second_approver;
+    if second_approver && !state.access.has_users() {
+        return Err(CommandError::new(
+            ErrorCode::Forbidden,
+            "Second approval of risk limit changes requires users configured in users.json",
+        ));
+    }
+    Ok(state.access.actor(claimed))
+}
+
+/// Query the risk limit audit log, newest entries first
+#[tauri::command]
+pub async fn get_risk_limit_audit_log(
+    change_id: Option<String>,
+    since: Option<DateTime<Utc>>,
+    limit: Option<usize>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<Vec<AuditEntry>> {
+    let query = AuditQuery {
+        change_id: change_id.as_deref().map(parse_change_id).transpose()?,
+        since,
+        limit,
+    };
+    Ok(state.risk_limits.read().await.audit_log(&query))
+}
+
+/// Get today's P&L and daily loss lock state of every account
+#[tauri::command]
+pub async fn get_daily_loss_status(state: tauri::State<'_, AppState>) -> CommandResult<Vec<DailyPnl>> {
+    let today = Utc::now().date_naive();
+    Ok(state
+        .execution_gate
+        .daily_loss()
+        .statuses()
+        .into_iter()
+        .filter(|day| day.trading_day == today)
+        .collect())
+}
+
+/// Lift an account's daily loss lock for the rest of the trading day
+///
+/// `confirmation` must read `UNLOCK <account>`.
+#[tauri::command]
+pub async fn override_daily_loss_lock(
+    account: String,
+    confirmation: String,
+    overridden_by: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<DailyLossEvent> {
+    let actor = state.access.actor(overridden_by);
+
+    let event = state
+        .execution_gate
+        .daily_loss()
+        .override_lock(&account, &confirmation, &actor, Utc::now())?;
+    Ok(event)
+}
+
+/// Get the portfolio drawdown, the factor new positions are scaled by and
+/// the schedules behind it
+#[tauri::command]
+pub async fn get_drawdown_throttle(state: tauri::State<'_, AppState>) -> CommandResult<DrawdownThrottleStatus> {
+    let throttle = state.drawdown_throttle.read().await;
+    Ok(DrawdownThrottleStatus {
+        snapshot: throttle.snapshot(),
+        config: throttle.config().clone(),
     })
 }
 
-/// Update risk limits
+/// Set the drawdown schedule of a strategy, or the global one without a
+/// strategy ID
 #[tauri::command]
-pub async fn update_risk_limits(limits: RiskLimits) -> Result<(), String> {
-    log::info!("Updating risk limits: {:?}", limits);
-    // TODO: Integrate with risk service
+pub async fn set_drawdown_schedule(
+    strategy_id: Option<String>,
+    schedule: DrawdownSchedule,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<()> {
+    log::info!("Setting drawdown schedule for {:?}: {:?}", strategy_id, schedule);
+
+    let mut throttle = state.drawdown_throttle.write().await;
+    match strategy_id {
+        Some(strategy_id) => throttle.set_strategy_schedule(parse_strategy_id(&strategy_id)?, schedule)?,
+        None => throttle.set_global_schedule(schedule)?,
+    }
+    Ok(())
+}
+
+/// Drop a strategy's drawdown schedule so the global one applies again
+#[tauri::command]
+pub async fn clear_drawdown_schedule(
+    strategy_id: String,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<()> {
+    let strategy_id = parse_strategy_id(&strategy_id)?;
+
+    state.drawdown_throttle.write().await.clear_strategy_schedule(strategy_id);
     Ok(())
 }
 
 /// Calculate VaR
 #[tauri::command]
-pub async fn calculate_var(confidence: f64, method: String) -> Result<VaRResult, String> {
+pub async fn calculate_var(confidence: f64, method: String) -> CommandResult<VaRResult> {
     log::info!("Calculating VaR (confidence: {}, method: {})", confidence, method);
     // TODO: Integrate with risk service
     Ok(VaRResult {
@@ -50,3 +288,127 @@ pub async fn calculate_var(confidence: f64, method: String) -> Result<VaRResult,
         method,
     })
 }
+
+/// List the built-in stress scenarios
+#[tauri::command]
+pub async fn get_stress_scenarios() -> CommandResult<Vec<StressScenario>> {
+    Ok(StressScenario::library())
+}
+
+/// Net and gross exposure of the open positions, grouped by symbol,
+/// strategy, instrument kind and settlement currency, with percentages of
+/// the account's total equity
+#[tauri::command]
+pub async fn get_exposure_breakdown(state: tauri::State<'_, AppState>) -> CommandResult<ExposureBreakdown> {
+    let positions = state.execution_engine.get_positions().await;
+    Ok(ExposureBreakdown::from_positions(&positions, state.account_tracker.state().total_equity))
+}
+
+/// Revalue a portfolio under stress scenarios
+///
+/// Runs the named built-in scenario, a custom one, or the whole library when
+/// neither is given.
+#[tauri::command]
+pub async fn run_stress_test(
+    equity: f64,
+    positions: Vec<StressPosition>,
+    scenario: Option<String>,
+    custom: Option<StressScenario>,
+    cross_margin: Option<bool>,
+) -> CommandResult<Vec<StressResult>> {
+    log::info!("Running stress test on {} positions (scenario: {:?})", positions.len(), scenario);
+
+    let portfolio = PortfolioState {
+        total_equity: to_decimal(equity, "equity")?,
+        available_margin: Decimal::ZERO,
+        positions: positions.iter().map(to_position).collect::<CommandResult<_>>()?,
+        daily_pnl: Decimal::ZERO,
+    };
+    let tester = StressTester::new(StressConfig {
+        cross_margin: cross_margin.unwrap_or(true),
+        ..Default::default()
+    });
+
+    let scenarios = match (scenario, custom) {
+        (_, Some(custom)) => vec![custom],
+        (Some(name), None) => vec![StressScenario::by_name(&name)
+            .ok_or_else(|| CommandError::not_found(format!("Unknown stress scenario '{}'", name)))?],
+        (None, None) => StressScenario::library(),
+    };
+
+    scenarios
+        .iter()
+        .map(|s| tester.run(&portfolio, s).map_err(|e| CommandError::validation(e.to_string())))
+        .collect()
+}
+
+/// Choose capital weights across strategies from their backtests
+///
+/// Splits `total_capital`, or the capital the strategies hold now, by the
+/// optimized weights; turnover is measured against the current allocations.
+/// With `apply` the new amounts become the strategies' allocated capital; as
+/// with any capital change, running strategies go back to draft.
+#[tauri::command]
+pub async fn optimize_strategy_allocation(
+    candidates: Vec<AllocationCandidate>,
+    config: Option<OptimizerConfig>,
+    total_capital: Option<f64>,
+    apply: Option<bool>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<AllocationPlan> {
+    log::info!("Optimizing allocation across {} strategies", candidates.len());
+
+    let mut series = Vec::with_capacity(candidates.len());
+    let mut current = HashMap::new();
+    let mut current_total = Decimal::ZERO;
+    for candidate in &candidates {
+        let strategy_id = uuid::Uuid::parse_str(&candidate.strategy_id)
+            .map_err(|e| CommandError::validation(format!("Invalid strategy ID: {}", e)))?;
+        let strategy = state.strategy_service.get_strategy(&candidate.strategy_id).await
+            .map_err(|e| CommandError::from(e).context("Failed to load strategy"))?;
+        let backtest = state.backtest_registry.result(&candidate.backtest_id)
+            .map_err(|e| CommandError::from(e).context("Failed to load backtest"))?;
+
+        let allocated = strategy.config.allocated_capital;
+        current.insert(strategy_id, allocated.to_f64().unwrap_or(0.0));
+        current_total += allocated;
+        series.push(StrategyReturns::from_equity_curve(strategy_id, &backtest.equity_curve));
+    }
+
+    let total_capital = match total_capital {
+        Some(total) => to_decimal(total, "total capital")?,
+        None => current_total,
+    };
+    if total_capital <= Decimal::ZERO {
+        return Err(CommandError::validation("Total capital must be positive"));
+    }
+
+    let optimizer = PortfolioOptimizer::new(config.unwrap_or_default());
+    let result = optimizer
+        .optimize(&series, &current)
+        .map_err(|e| CommandError::from(e).context("Failed to optimize allocation"))?;
+    let capital = result.capital(total_capital);
+
+    let apply = apply.unwrap_or(false);
+    if apply {
+        for (strategy_id, amount) in &capital {
+            state.strategy_service.update_strategy(
+                &strategy_id.to_string(),
+                None,
+                None,
+                None,
+                None,
+                None,
+                amount.to_f64(),
+            ).await
+                .map_err(|e| CommandError::from(e).context("Failed to apply allocation"))?;
+        }
+    }
+
+    Ok(AllocationPlan {
+        result,
+        total_capital,
+        capital,
+        applied: apply,
+    })
+}
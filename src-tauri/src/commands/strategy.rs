@@ -1,7 +1,11 @@
+use crate::error::{CommandError, CommandResult};
 use crate::state::AppState;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use ea_okx_core::models::strategy as strategy_models;
+use ea_okx_monitoring::{DecayAssessment, PerformanceBaseline};
+use ea_okx_strategy::{BacktestEvidence, DcaLedger, MetricPoint, MetricSample, StrategyBundle};
+use ea_okx_trading::{ExecutionPolicy, OrderStyle};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateStrategyRequest {
@@ -10,6 +14,9 @@ pub struct CreateStrategyRequest {
     pub parameters: HashMap<String, serde_json::Value>,
     pub symbols: Vec<String>,
     pub allocated_capital: f64,
+    /// `custom` when not given; `script` and `dca` are run by built-in strategies
+    #[serde(default)]
+    pub strategy_type: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +24,8 @@ pub struct UpdateStrategyRequest {
     pub name: Option<String>,
     pub description: Option<String>,
     pub parameters: Option<HashMap<String, serde_json::Value>>,
+    /// Parameter schema; properties flagged `x-hot-tunable` can change while running
+    pub parameter_schema: Option<serde_json::Value>,
     pub symbols: Option<Vec<String>>,
     pub allocated_capital: Option<f64>,
 }
@@ -26,7 +35,7 @@ pub struct UpdateStrategyRequest {
 pub async fn get_strategies(
     state: tauri::State<'_, AppState>,
     _filter: Option<strategy_models::StrategyFilter>,
-) -> Result<strategy_models::StrategyResponse<strategy_models::StrategyListResponse>, String> {
+) -> CommandResult<strategy_models::StrategyResponse<strategy_models::StrategyListResponse>> {
     log::info!("Fetching all strategies");
 
     match state.strategy_service.get_strategies().await {
@@ -55,7 +64,7 @@ pub async fn get_strategies(
 pub async fn get_strategy(
     id: String,
     state: tauri::State<'_, AppState>,
-) -> Result<strategy_models::StrategyResponse<strategy_models::Strategy>, String> {
+) -> CommandResult<strategy_models::StrategyResponse<strategy_models::Strategy>> {
     log::info!("Fetching strategy: {}", id);
 
     match state.strategy_service.get_strategy(&id).await {
@@ -77,23 +86,34 @@ pub async fn get_strategy(
 pub async fn create_strategy(
     request: CreateStrategyRequest,
     state: tauri::State<'_, AppState>,
-) -> Result<strategy_models::StrategyResponse<strategy_models::Strategy>, String> {
+) -> CommandResult<strategy_models::StrategyResponse<strategy_models::Strategy>> {
     log::info!("Creating strategy: {}", request.name);
 
+    state.symbol_catalog.validate_symbols(&request.symbols)?;
+
     match state.strategy_service.create_strategy(
         request.name,
         request.description,
-        "custom".to_string(), // Default strategy type
+        request.strategy_type.unwrap_or_else(|| "custom".to_string()),
         serde_json::to_value(request.parameters).unwrap_or_default(),
         request.symbols,
         request.allocated_capital,
         "default-user".to_string(), // Default user ID
     ).await {
-        Ok(strategy) => Ok(strategy_models::StrategyResponse {
-            success: true,
-            data: Some(strategy),
-            error: None,
-        }),
+        Ok(strategy) => {
+            // DCA buys are not urgent: rest them post-only rather than cross the spread
+            if strategy.strategy_type == ea_okx_strategy::DCA_STRATEGY_TYPE {
+                let policy = ExecutionPolicy { open: OrderStyle::PassiveLimit, ..Default::default() };
+                if let Err(e) = state.execution_engine.execution_policies().set_strategy_policy(strategy.id, policy) {
+                    log::error!("Cannot route DCA strategy {} passively: {}", strategy.id, e);
+                }
+            }
+            Ok(strategy_models::StrategyResponse {
+                success: true,
+                data: Some(strategy),
+                error: None,
+            })
+        }
         Err(e) => Ok(strategy_models::StrategyResponse {
             success: false,
             data: None,
@@ -108,9 +128,12 @@ pub async fn update_strategy(
     id: String,
     request: UpdateStrategyRequest,
     state: tauri::State<'_, AppState>,
-) -> Result<strategy_models::StrategyResponse<strategy_models::Strategy>, String> {
+) -> CommandResult<strategy_models::StrategyResponse<strategy_models::Strategy>> {
     log::info!("Updating strategy: {}", id);
 
+    if let Some(symbols) = &request.symbols {
+        state.symbol_catalog.validate_symbols(symbols)?;
+    }
     let parameters = request.parameters.map(|p| serde_json::to_value(p).unwrap_or_default());
 
     match state.strategy_service.update_strategy(
@@ -118,14 +141,21 @@ pub async fn update_strategy(
         request.name,
         request.description,
         parameters,
+        request.parameter_schema,
         request.symbols,
         request.allocated_capital,
     ).await {
-        Ok(strategy) => Ok(strategy_models::StrategyResponse {
-            success: true,
-            data: Some(strategy),
-            error: None,
-        }),
+        Ok(strategy) => {
+            // Changes that need a restart leave the strategy in draft
+            if strategy.status == strategy_models::StrategyStatus::Draft {
+                state.halt_strategy(&id).await;
+            }
+            Ok(strategy_models::StrategyResponse {
+                success: true,
+                data: Some(strategy),
+                error: None,
+            })
+        }
         Err(e) => Ok(strategy_models::StrategyResponse {
             success: false,
             data: None,
@@ -139,15 +169,21 @@ pub async fn update_strategy(
 pub async fn delete_strategy(
     id: String,
     state: tauri::State<'_, AppState>,
-) -> Result<strategy_models::StrategyResponse<()>, String> {
+) -> CommandResult<strategy_models::StrategyResponse<()>> {
     log::info!("Deleting strategy: {}", id);
 
     match state.strategy_service.delete_strategy(&id).await {
-        Ok(_) => Ok(strategy_models::StrategyResponse {
-            success: true,
-            data: Some(()),
-            error: None,
-        }),
+        Ok(_) => {
+            state.halt_strategy(&id).await;
+            if let Ok(strategy_id) = uuid::Uuid::parse_str(&id) {
+                state.strategy_metrics.remove_strategy(strategy_id);
+            }
+            Ok(strategy_models::StrategyResponse {
+                success: true,
+                data: Some(()),
+                error: None,
+            })
+        }
         Err(e) => Ok(strategy_models::StrategyResponse {
             success: false,
             data: None,
@@ -161,15 +197,32 @@ pub async fn delete_strategy(
 pub async fn start_strategy(
     id: String,
     state: tauri::State<'_, AppState>,
-) -> Result<strategy_models::StrategyResponse<()>, String> {
+) -> CommandResult<strategy_models::StrategyResponse<()>> {
     log::info!("Starting strategy: {}", id);
 
     match state.strategy_service.start_strategy(&id).await {
-        Ok(_) => Ok(strategy_models::StrategyResponse {
-            success: true,
-            data: Some(()),
-            error: None,
-        }),
+        Ok(_) => {
+            // Restarting lifts a pause for repeated quota breaches
+            if let Ok(strategy_id) = uuid::Uuid::parse_str(&id) {
+                state.execution_gate.quotas().resume(strategy_id);
+                // Judge live performance against the newest backtest from now on
+                match state.decay.record_baseline(strategy_id, None) {
+                    Ok(Some(_)) => {}
+                    Ok(None) => log::warn!("Strategy {} has no recorded backtest; performance decay is not monitored", id),
+                    Err(e) => log::error!("Failed to record performance baseline of {}: {}", id, e),
+                }
+            }
+            match state.run_strategy(&id).await {
+                Ok(true) => {}
+                Ok(false) => log::info!("Strategy {} has no built-in implementation to run", id),
+                Err(e) => log::error!("Cannot run strategy {}: {}", id, e),
+            }
+            Ok(strategy_models::StrategyResponse {
+                success: true,
+                data: Some(()),
+                error: None,
+            })
+        }
         Err(e) => Ok(strategy_models::StrategyResponse {
             success: false,
             data: None,
@@ -184,15 +237,38 @@ pub async fn stop_strategy(
     id: String,
     force: Option<bool>,
     state: tauri::State<'_, AppState>,
-) -> Result<strategy_models::StrategyResponse<()>, String> {
+) -> CommandResult<strategy_models::StrategyResponse<()>> {
     log::info!("Stopping strategy: {}", id);
 
     match state.strategy_service.stop_strategy(&id, force.unwrap_or(false)).await {
-        Ok(_) => Ok(strategy_models::StrategyResponse {
-            success: true,
-            data: Some(()),
-            error: None,
-        }),
+        Ok(_) => {
+            state.halt_strategy(&id).await;
+            // Don't leave the stopped strategy's limit orders on the book
+            if let Ok(strategy_id) = uuid::Uuid::parse_str(&id) {
+                if state.execution_engine.execution_policies().policy(strategy_id).cancel_on_stop {
+                    match state.execution_engine.cancel_orders_for_strategy(strategy_id).await {
+                        Ok(cancelled) if !cancelled.is_empty() => {
+                            log::info!("Cancelled {} resting orders of stopped strategy {}", cancelled.len(), id);
+                        }
+                        Ok(_) => {}
+                        // Stopped, but orders are still on the book
+                        Err(e) => {
+                            log::error!("Failed to cancel orders of stopped strategy {}: {}", id, e);
+                            return Ok(strategy_models::StrategyResponse {
+                                success: false,
+                                data: None,
+                                error: Some(format!("Strategy stopped, but {}", e)),
+                            });
+                        }
+                    }
+                }
+            }
+            Ok(strategy_models::StrategyResponse {
+                success: true,
+                data: Some(()),
+                error: None,
+            })
+        }
         Err(e) => Ok(strategy_models::StrategyResponse {
             success: false,
             data: None,
@@ -206,7 +282,7 @@ pub async fn stop_strategy(
 pub async fn pause_strategy(
     id: String,
     state: tauri::State<'_, AppState>,
-) -> Result<strategy_models::StrategyResponse<()>, String> {
+) -> CommandResult<strategy_models::StrategyResponse<()>> {
     log::info!("Pausing strategy: {}", id);
 
     match state.strategy_service.pause_strategy(&id).await {
@@ -228,7 +304,7 @@ pub async fn pause_strategy(
 pub async fn get_strategy_metrics(
     id: String,
     state: tauri::State<'_, AppState>,
-) -> Result<strategy_models::StrategyResponse<serde_json::Value>, String> {
+) -> CommandResult<strategy_models::StrategyResponse<serde_json::Value>> {
     log::info!("Fetching metrics for strategy: {}", id);
 
     match state.strategy_service.get_strategy_metrics(&id).await {
@@ -251,7 +327,7 @@ pub async fn duplicate_strategy(
     id: String,
     name: String,
     state: tauri::State<'_, AppState>,
-) -> Result<strategy_models::StrategyResponse<strategy_models::Strategy>, String> {
+) -> CommandResult<strategy_models::StrategyResponse<strategy_models::Strategy>> {
     log::info!("Duplicating strategy: {} as {}", id, name);
 
     match state.strategy_service.duplicate_strategy(&id, name).await {
@@ -267,3 +343,184 @@ pub async fn duplicate_strategy(
         }),
     }
 }
+
+/// Get the status history of a strategy
+#[tauri::command]
+pub async fn get_strategy_status_history(
+    id: String,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<strategy_models::StrategyResponse<Vec<data::StrategyStatusChange>>> {
+    log::info!("Fetching status history for strategy: {}", id);
+
+    match state.strategy_service.get_status_history(&id).await {
+        Ok(history) => Ok(strategy_models::StrategyResponse {
+            success: true,
+            data: Some(history),
+            error: None,
+        }),
+        Err(e) => Ok(strategy_models::StrategyResponse {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+        }),
+    }
+}
+
+/// Get the custom metrics a strategy reported, with their current values
+#[tauri::command]
+pub async fn get_strategy_custom_metrics(
+    id: String,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<strategy_models::StrategyResponse<Vec<MetricSample>>> {
+    log::info!("Fetching custom metrics for strategy: {}", id);
+
+    match uuid::Uuid::parse_str(&id) {
+        Ok(strategy_id) => Ok(strategy_models::StrategyResponse {
+            success: true,
+            data: Some(state.strategy_metrics.strategy_snapshot(strategy_id)),
+            error: None,
+        }),
+        Err(e) => Ok(strategy_models::StrategyResponse {
+            success: false,
+            data: None,
+            error: Some(format!("Invalid strategy ID: {}", e)),
+        }),
+    }
+}
+
+/// Get the sampled values of one custom metric for charting, oldest first
+#[tauri::command]
+pub async fn get_strategy_custom_metric_history(
+    id: String,
+    name: String,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<strategy_models::StrategyResponse<Vec<MetricPoint>>> {
+    log::info!("Fetching history of custom metric {} for strategy: {}", name, id);
+
+    match uuid::Uuid::parse_str(&id) {
+        Ok(strategy_id) => Ok(strategy_models::StrategyResponse {
+            success: true,
+            data: Some(state.strategy_metrics.history(strategy_id, &name)),
+            error: None,
+        }),
+        Err(e) => Ok(strategy_models::StrategyResponse {
+            success: false,
+            data: None,
+            error: Some(format!("Invalid strategy ID: {}", e)),
+        }),
+    }
+}
+
+/// Key for signing and verifying strategy bundles, shared between trusted
+/// installations through `EA_OKX_BUNDLE_KEY`; bundles are digest-only without it
+fn bundle_signing_key() -> Option<Vec<u8>> {
+    std::env::var("EA_OKX_BUNDLE_KEY").ok().filter(|k| !k.is_empty()).map(String::into_bytes)
+}
+
+/// Export a strategy as a signed bundle file, optionally with backtest evidence
+#[tauri::command]
+pub async fn export_strategy_bundle(
+    id: String,
+    path: String,
+    backtest: Option<BacktestEvidence>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<strategy_models::StrategyResponse<StrategyBundle>> {
+    log::info!("Exporting strategy {} to bundle: {}", id, path);
+
+    let bundle = match state.strategy_service.export_bundle(&id, backtest).await {
+        Ok(bundle) => bundle,
+        Err(e) => {
+            return Ok(strategy_models::StrategyResponse {
+                success: false,
+                data: None,
+                error: Some(e.to_string()),
+            })
+        }
+    };
+
+    match bundle.export(&path, bundle_signing_key().as_deref()) {
+        Ok(()) => Ok(strategy_models::StrategyResponse {
+            success: true,
+            data: Some(bundle),
+            error: None,
+        }),
+        Err(e) => Ok(strategy_models::StrategyResponse {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+        }),
+    }
+}
+
+/// Import a strategy bundle file as a new draft strategy
+#[tauri::command]
+pub async fn import_strategy_bundle(
+    path: String,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<strategy_models::StrategyResponse<strategy_models::Strategy>> {
+    log::info!("Importing strategy bundle: {}", path);
+
+    let bundle = match StrategyBundle::import(&path, bundle_signing_key().as_deref()) {
+        Ok(bundle) => bundle,
+        Err(e) => {
+            return Ok(strategy_models::StrategyResponse {
+                success: false,
+                data: None,
+                error: Some(e.to_string()),
+            })
+        }
+    };
+
+    match state.strategy_service.import_bundle(bundle).await {
+        Ok(strategy) => Ok(strategy_models::StrategyResponse {
+            success: true,
+            data: Some(strategy),
+            error: None,
+        }),
+        Err(e) => Ok(strategy_models::StrategyResponse {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+        }),
+    }
+}
+
+/// Get the positions, cost basis and buy history of a DCA strategy, rebuilt
+/// from its filled orders
+#[tauri::command]
+pub async fn get_dca_history(
+    strategy_id: String,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<DcaLedger> {
+    let strategy_id = uuid::Uuid::parse_str(&strategy_id)
+        .map_err(|e| CommandError::validation(format!("Invalid strategy ID: {}", e)))?;
+
+    let orders = state.execution_engine.get_orders().await;
+    Ok(DcaLedger::from_orders(orders.iter().filter(|o| o.strategy_id == strategy_id)))
+}
+
+/// Judge a strategy's live hit rate, expectancy and Sharpe ratio against its
+/// deployment backtest; `None` when it has no baseline
+#[tauri::command]
+pub async fn get_strategy_decay(
+    strategy_id: String,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<Option<DecayAssessment>> {
+    let strategy = state.strategy_service.get_strategy(&strategy_id).await?;
+    Ok(state.decay.assess(&strategy).await)
+}
+
+/// Judge a strategy against backtest `backtest_id` instead, or against its
+/// newest recorded backtest when not given
+#[tauri::command]
+pub async fn set_strategy_baseline(
+    strategy_id: String,
+    backtest_id: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<PerformanceBaseline> {
+    let strategy = state.strategy_service.get_strategy(&strategy_id).await?;
+    state
+        .decay
+        .record_baseline(strategy.id, backtest_id.as_deref())?
+        .ok_or_else(|| CommandError::not_found(format!("Strategy {} has no recorded backtest", strategy.name)))
+}
@@ -1,3 +1,16 @@
+use crate::error::{CommandError, CommandResult, ErrorCode};
+use crate::state::AppState;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use data::{EquitySnapshot, SchemaVersion};
+use ea_okx_core::contract::format_timestamp;
+use ea_okx_backtest::{
+    BacktestComparison, BacktestConfig, BacktestEngine, BacktestFilter, BacktestRun, CurvePage, CurveQuery,
+    CurveResolution, RunProvenance, TimescaleDataSource,
+};
+use ea_okx_strategy::builtin_factory;
+use ea_okx_monitoring::{DailyReport, HealthReport, TaskHealth};
+use ea_okx_trading::{DropCopyFile, DropCopyFormat, SnapshotInfo};
+use rust_decimal::prelude::ToPrimitive;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,45 +48,351 @@ pub struct BacktestResult {
     pub total_trades: usize,
 }
 
-/// Get system metrics
-#[tauri::command]
-pub async fn get_system_metrics() -> Result<SystemMetrics, String> {
-    log::info!("Fetching system metrics");
-    Ok(SystemMetrics {
+/// Current system metrics; also pushed on `metrics:update`
+pub(crate) fn sample_system_metrics() -> SystemMetrics {
+    SystemMetrics {
         cpu_usage: 35.5,
         memory_usage: 62.3,
         network_latency: 45.2,
         active_strategies: 3,
         total_orders: 150,
-    })
+    }
+}
+
+/// Get system metrics
+#[tauri::command]
+pub async fn get_system_metrics() -> CommandResult<SystemMetrics> {
+    log::info!("Fetching system metrics");
+    Ok(sample_system_metrics())
+}
+
+/// Run every registered health check
+#[tauri::command]
+pub async fn get_health_report(state: tauri::State<'_, AppState>) -> CommandResult<HealthReport> {
+    Ok(state.monitoring.perform_health_check().await)
+}
+
+/// Liveness of the background tasks the watchdog supervises
+#[tauri::command]
+pub async fn get_task_health(state: tauri::State<'_, AppState>) -> CommandResult<Vec<TaskHealth>> {
+    Ok(state.watchdog.status())
+}
+
+/// Applied and pending migrations of the market data store's schema
+#[tauri::command]
+pub async fn get_schema_version(state: tauri::State<'_, AppState>) -> CommandResult<SchemaVersion> {
+    let storage = state.market_storage.as_ref().ok_or_else(|| {
+        CommandError::new(ErrorCode::Unavailable, "Market data store is not configured")
+    })?;
+
+    storage.schema_version().await
+        .map_err(|e| CommandError::from(e).context("Failed to read schema version"))
 }
 
 /// Get alerts
 #[tauri::command]
-pub async fn get_alerts(limit: Option<usize>) -> Result<Vec<Alert>, String> {
+pub async fn get_alerts(
+    limit: Option<usize>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<Vec<Alert>> {
     log::info!("Fetching alerts (limit: {:?})", limit);
-    Ok(vec![])
+
+    let mut alerts = state.monitoring.get_active_alerts().await;
+    alerts.sort_by(|a, b| b.triggered_at.cmp(&a.triggered_at));
+
+    Ok(alerts.into_iter()
+        .take(limit.unwrap_or(usize::MAX))
+        .map(|a| Alert {
+            id: a.id.to_string(),
+            level: format!("{:?}", a.severity).to_uppercase(),
+            message: a.message,
+            timestamp: format_timestamp(a.triggered_at),
+        })
+        .collect())
 }
 
-/// Run backtest
+/// Run a strategy's built-in implementation over the market data store and
+/// record the run, returning its ID
+///
+/// `start_date` and `end_date` are RFC 3339 times or `YYYY-MM-DD` dates
+/// (midnight UTC). The strategy is initialized with its current parameters.
 #[tauri::command]
-pub async fn run_backtest(request: BacktestRequest) -> Result<String, String> {
+pub async fn run_backtest(
+    request: BacktestRequest,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<String> {
     log::info!("Starting backtest: {:?}", request);
-    // TODO: Integrate with backtest engine
-    // Return backtest job ID
-    Ok(format!("backtest_{}", uuid::Uuid::new_v4()))
+
+    let strategy = state.strategy_service.get_strategy(&request.strategy_id).await
+        .map_err(|e| CommandError::from(e).context("Failed to load strategy"))?;
+    let factory = builtin_factory(&strategy.strategy_type).ok_or_else(|| {
+        CommandError::validation(format!("Strategy type '{}' has no implementation to backtest", strategy.strategy_type))
+    })?;
+    let url = std::env::var("EA_OKX_MARKET_DB_URL").map_err(|_| {
+        CommandError::new(ErrorCode::Unavailable, "Market data store is not configured")
+    })?;
+
+    let start_time = parse_backtest_time(&request.start_date)?;
+    let end_time = parse_backtest_time(&request.end_date)?;
+    if end_time <= start_time {
+        return Err(CommandError::validation("Backtest end must be after its start"));
+    }
+    let initial_capital = rust_decimal::Decimal::try_from(request.initial_capital)
+        .ok()
+        .filter(|c| c.is_sign_positive() && !c.is_zero())
+        .ok_or_else(|| CommandError::validation(format!("Invalid initial capital: {}", request.initial_capital)))?;
+    let config = BacktestConfig {
+        initial_capital,
+        start_time,
+        end_time,
+        symbols: vec![ea_okx_core::Symbol::new(&request.symbol)?],
+        ..Default::default()
+    };
+
+    let source = TimescaleDataSource::connect(&url, 4).await
+        .map_err(|e| CommandError::from(e).context("Failed to open market data store"))?;
+    let parameters = strategy.config.parameters.as_object()
+        .map(|parameters| parameters.clone().into_iter().collect())
+        .unwrap_or_default();
+    let mut engine = BacktestEngine::new(config.clone(), factory(), Box::new(source)).await?
+        .with_strategy_parameters(parameters);
+    let result = engine.run().await
+        .map_err(|e| CommandError::from(e).context("Backtest failed"))?;
+
+    let provenance = RunProvenance {
+        strategy_id: Some(strategy.id),
+        strategy_name: strategy.name.clone(),
+        strategy_version: Some(strategy.version.clone()),
+        git_revision: option_env!("EA_OKX_GIT_REVISION").map(str::to_string),
+    };
+    let run = state.backtest_registry.record(provenance, &config, &result)
+        .map_err(|e| CommandError::from(e).context("Failed to record backtest"))?;
+    Ok(run.id)
+}
+
+fn parse_backtest_time(value: &str) -> CommandResult<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|date| date.and_time(chrono::NaiveTime::MIN).and_utc())
+        .map_err(|_| CommandError::validation(format!("Invalid backtest date: {}", value)))
 }
 
-/// Get backtest results
+/// Headline metrics of a recorded backtest
 #[tauri::command]
-pub async fn get_backtest_results(backtest_id: String) -> Result<BacktestResult, String> {
+pub async fn get_backtest_results(
+    backtest_id: String,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<BacktestResult> {
     log::info!("Fetching backtest results: {}", backtest_id);
-    // TODO: Integrate with backtest engine
+
+    let run = state.backtest_registry.run(&backtest_id)
+        .map_err(|e| CommandError::from(e).context("Failed to load backtest"))?;
+    let metrics = run.metrics;
     Ok(BacktestResult {
-        total_return: 0.45,
-        sharpe_ratio: 1.85,
-        max_drawdown: 0.12,
-        win_rate: 0.65,
-        total_trades: 150,
+        total_return: metrics.total_return_pct.to_f64().unwrap_or(0.0),
+        sharpe_ratio: metrics.sharpe_ratio.to_f64().unwrap_or(0.0),
+        max_drawdown: metrics.max_drawdown_pct.abs().to_f64().unwrap_or(0.0),
+        win_rate: metrics.win_rate.to_f64().unwrap_or(0.0),
+        total_trades: metrics.total_trades,
     })
 }
+
+/// Get a backtest's equity curve reduced for charting
+///
+/// `resolution` buckets the curve to one point per minute, hour or day;
+/// `simplify_tolerance` (in quote currency) drops points that barely move
+/// the line; `offset`/`limit` page through what remains.
+#[tauri::command]
+pub async fn get_backtest_equity_curve(
+    backtest_id: String,
+    resolution: Option<CurveResolution>,
+    simplify_tolerance: Option<f64>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<CurvePage> {
+    log::info!("Fetching equity curve for backtest {} ({:?})", backtest_id, resolution);
+
+    let simplify_tolerance = simplify_tolerance
+        .map(|t| {
+            rust_decimal::Decimal::try_from(t)
+                .ok()
+                .filter(|t| !t.is_sign_negative())
+                .ok_or_else(|| CommandError::validation(format!("Invalid simplify tolerance: {}", t)))
+        })
+        .transpose()?;
+    let query = CurveQuery {
+        resolution: resolution.unwrap_or_default(),
+        simplify_tolerance,
+        offset: offset.unwrap_or(0),
+        limit,
+    };
+
+    let result = state.backtest_registry.result(&backtest_id)
+        .map_err(|e| CommandError::from(e).context("Failed to load backtest"))?;
+    Ok(result.equity_curve_page(&query))
+}
+
+/// Recorded backtest runs matching `filter`, newest first
+///
+/// Includes the runs of `run_backtest` and those recorded into the data
+/// directory by other tools.
+#[tauri::command]
+pub async fn list_backtests(
+    filter: Option<BacktestFilter>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<Vec<BacktestRun>> {
+    log::info!("Listing backtests: {:?}", filter);
+
+    state.backtest_registry.list(&filter.unwrap_or_default())
+        .map_err(|e| CommandError::from(e).context("Failed to list backtests"))
+}
+
+/// Compare recorded backtest runs: one metric table row per metric with a
+/// value per run, and the equity curves on one time axis at `resolution`
+#[tauri::command]
+pub async fn compare_backtests(
+    ids: Vec<String>,
+    resolution: Option<CurveResolution>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<BacktestComparison> {
+    log::info!("Comparing backtests {:?} ({:?})", ids, resolution);
+
+    state.backtest_registry.compare(&ids, resolution.unwrap_or(CurveResolution::Hour))
+        .map_err(|e| CommandError::from(e).context("Failed to compare backtests"))
+}
+
+/// Get the recorded live account equity curve within `[start, end)`
+///
+/// `resolution` keeps the last sample per minute, hour or day, matching the
+/// buckets of [`get_backtest_equity_curve`]. Samples are recorded once a
+/// minute; ranges older than the raw retention window come back hourly.
+#[tauri::command]
+pub async fn get_live_equity_curve(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    resolution: Option<CurveResolution>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<Vec<EquitySnapshot>> {
+    log::info!("Fetching live equity curve: {} to {} ({:?})", start, end, resolution);
+
+    if start >= end {
+        return Err(CommandError::validation("start must be before end"));
+    }
+    let storage = state.market_storage.as_ref().ok_or_else(|| {
+        CommandError::new(ErrorCode::Unavailable, "Market data store is not configured")
+    })?;
+
+    let bucket = match resolution.unwrap_or_default() {
+        CurveResolution::Raw => None,
+        CurveResolution::Minute => Some(Duration::minutes(1)),
+        CurveResolution::Hour => Some(Duration::hours(1)),
+        CurveResolution::Day => Some(Duration::days(1)),
+    };
+    storage.query_equity_curve(start, end, bucket).await
+        .map_err(|e| CommandError::from(e).context("Failed to load live equity curve"))
+}
+
+fn parse_report_date(value: &str) -> CommandResult<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|e| CommandError::validation(format!("Invalid date '{}': {}", value, e)))
+}
+
+/// Get stored daily performance reports dated within `[start_date, end_date]` (YYYY-MM-DD)
+#[tauri::command]
+pub async fn get_daily_reports(
+    start_date: String,
+    end_date: String,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<Vec<DailyReport>> {
+    log::info!("Fetching daily reports: {} to {}", start_date, end_date);
+
+    let start = parse_report_date(&start_date)?;
+    let end = parse_report_date(&end_date)?;
+    if start > end {
+        return Err(CommandError::validation("start_date must not be after end_date"));
+    }
+
+    state.reporter.get_daily_reports(start, end)
+        .map_err(|e| CommandError::from(e).context("Failed to load daily reports"))
+}
+
+/// Get the rendered HTML of one daily report
+#[tauri::command]
+pub async fn get_daily_report_html(
+    date: String,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<String> {
+    log::info!("Fetching daily report HTML: {}", date);
+
+    let date = parse_report_date(&date)?;
+    state.reporter.get_report_html(date)
+        .map_err(|e| CommandError::from(e).context("Failed to load daily report"))?
+        .ok_or_else(|| CommandError::not_found(format!("No daily report for {}", date)))
+}
+
+/// Export the order lifecycle events of a finished UTC day (YYYY-MM-DD) as a
+/// drop copy for compliance archiving, `csv` (default) or `fix`
+///
+/// The file and its `.sha256` are written once; exporting the day again
+/// returns the existing file after checking it against its hash.
+#[tauri::command]
+pub async fn export_drop_copy(
+    date: String,
+    format: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<DropCopyFile> {
+    log::info!("Exporting drop copy for {} ({:?})", date, format);
+
+    let day = parse_report_date(&date)?;
+    if day >= Utc::now().date_naive() {
+        return Err(CommandError::validation(format!("{} has not ended yet; only past days can be exported", day)));
+    }
+    let format = match format {
+        Some(format) => format.parse::<DropCopyFormat>().map_err(|e| CommandError::validation(e.to_string()))?,
+        None => DropCopyFormat::Csv,
+    };
+    let journal = state.order_journal.as_ref()
+        .ok_or_else(|| CommandError::new(ErrorCode::Unavailable, "Order journal is not available"))?;
+
+    let file = journal.export(day, format, crate::state::drop_copy_dir())
+        .map_err(|e| CommandError::from(e).context("Failed to export drop copy"))?;
+    log::info!("Drop copy {} written: {} records, sha256 {}", file.path.display(), file.records, file.sha256);
+    Ok(file)
+}
+
+/// Take an engine state snapshot now
+#[tauri::command]
+pub async fn take_snapshot(
+    label: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<SnapshotInfo> {
+    log::info!("Taking snapshot (label: {:?})", label);
+    state.snapshots.take(label).await
+        .map_err(|e| CommandError::from(e).context("Failed to take snapshot"))
+}
+
+/// List stored snapshots, newest first
+#[tauri::command]
+pub async fn list_snapshots(state: tauri::State<'_, AppState>) -> CommandResult<Vec<SnapshotInfo>> {
+    state.snapshots.store().list()
+        .map_err(|e| CommandError::from(e).context("Failed to list snapshots"))
+}
+
+/// Roll the runtime state back to a snapshot, the newest usable one if no ID is given
+#[tauri::command]
+pub async fn restore_snapshot(
+    id: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<SnapshotInfo> {
+    log::warn!("Restoring snapshot {:?}", id);
+
+    let id = id
+        .map(|id| uuid::Uuid::parse_str(&id))
+        .transpose()
+        .map_err(|e| CommandError::validation(format!("Invalid snapshot ID: {}", e)))?;
+    state.restore_from_snapshot(id).await
+        .map_err(|e| CommandError::new(ErrorCode::Internal, format!("Failed to restore snapshot: {}", e)))
+}
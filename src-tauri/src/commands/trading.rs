@@ -1,10 +1,33 @@
+use crate::error::{CommandError, CommandResult};
 use crate::state::AppState;
 use crate::services::strategy_execution::{
-    ExecutionRequest, ExecutionSignal, SignalType,
-    TimeInForce,
+    ExecutionRequest, ExecutionSignal, PipelineSimulation, SignalType,
+    TimeInForce, TradeOrigin,
 };
+use data::storage::{Candle, OrderBookSnapshot};
+use data::PriceCache;
+use ea_okx_core::contract::format_timestamp;
+use ea_okx_core::Interval;
+use ea_okx_monitoring::ExchangeHealthStatus;
 use serde::{Deserialize, Serialize};
 use rust_decimal::prelude::ToPrimitive;
+use ea_okx_risk::{returns_from_closes, ExposureBreakdown, PortfolioState, PreTradeValidator, VarConfig};
+use ea_okx_strategy::SignalSourceConfig;
+use ea_okx_trading::{
+    DegradedModePolicy, DegradedState, ExecutionPolicy, FatFingerConfig, FatFingerLimits,
+    LatencyBreakdown, LiquidityConfig, LatencyBudget, PositionPlan, ProtectedPosition, QuotaUsage, ReconciliationReport, ScaleOutPlan,
+    SignalQueueMetrics, StrategyQuota,
+};
+
+/// Exchange health as judged by the outage detector, and what the gate does about it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExchangeHealth {
+    /// Missing when no OKX client is configured
+    pub status: Option<ExchangeHealthStatus>,
+    /// Set while only reduce-only orders are sent
+    pub degraded: Option<DegradedState>,
+    pub policy: DegradedModePolicy,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlaceOrderRequest {
@@ -17,6 +40,10 @@ pub struct PlaceOrderRequest {
     pub time_in_force: Option<String>,
     pub reduce_only: Option<bool>,
     pub post_only: Option<bool>,
+    /// Send despite a fat-finger warning the user has acknowledged
+    pub confirmed: Option<bool>,
+    /// Cancel a GTC order still resting at this time (good-till-date)
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,44 +60,64 @@ pub struct SignalRequest {
     pub metadata: Option<serde_json::Value>,
 }
 
+/// Everything behind one fill, for replaying why it happened
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeContext {
+    #[serde(flatten)]
+    pub origin: TradeOrigin,
+    pub interval: Interval,
+    /// Candles around the fill, oldest first; empty without a market data store
+    pub candles: Vec<Candle>,
+    /// Book at the fill, if a snapshot was stored in the minute before it
+    pub order_book: Option<OrderBookSnapshot>,
+}
+
 /// Place a new order
 #[tauri::command]
 pub async fn place_order(
     request: PlaceOrderRequest,
     state: tauri::State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
+) -> CommandResult<serde_json::Value> {
     log::info!("Placing order: {:?}", request);
 
     let strategy_id = uuid::Uuid::parse_str(&request.strategy_id)
-        .map_err(|e| format!("Invalid strategy ID: {}", e))?;
+        .map_err(|e| CommandError::validation(format!("Invalid strategy ID: {}", e)))?;
 
     let symbol = ea_okx_core::types::Symbol::new(&request.symbol)
-        .map_err(|e| format!("Invalid symbol: {}", e))?;
+        .map_err(|e| CommandError::validation(format!("Invalid symbol: {}", e)))?;
 
     let side = request.side.parse::<ea_okx_core::models::order::OrderSide>()
-        .map_err(|e| format!("Invalid side: {}", e))?;
+        .map_err(|e| CommandError::validation(format!("Invalid side: {}", e)))?;
 
     let order_type = request.order_type.parse::<ea_okx_core::models::order::OrderType>()
-        .map_err(|e| format!("Invalid order type: {}", e))?;
+        .map_err(|e| CommandError::validation(format!("Invalid order type: {}", e)))?;
 
     let quantity = ea_okx_core::types::Quantity::new(
         rust_decimal::Decimal::from_f64_retain(request.quantity)
-            .ok_or_else(|| "Invalid quantity".to_string())?
-    ).map_err(|e| format!("Invalid quantity: {}", e))?;
+            .ok_or_else(|| CommandError::validation("Invalid quantity"))?
+    ).map_err(|e| CommandError::validation(format!("Invalid quantity: {}", e)))?;
 
     let price = request.price.map(|p| {
         ea_okx_core::types::Price::new(
             rust_decimal::Decimal::from_f64_retain(p)
-                .ok_or_else(|| "Invalid price".to_string())?
-        ).map_err(|e| format!("Invalid price: {}", e))
+                .ok_or_else(|| CommandError::validation("Invalid price"))?
+        ).map_err(|e| CommandError::validation(format!("Invalid price: {}", e)))
     }).transpose()?;
 
     let time_in_force = match request.time_in_force.as_deref().unwrap_or("GTC") {
         "GTC" => TimeInForce::GoodTillCancel,
         "IOC" => TimeInForce::ImmediateOrCancel,
         "FOK" => TimeInForce::FillOrKill,
-        _ => return Err("Invalid time in force".to_string()),
+        _ => return Err(CommandError::validation("Invalid time in force")),
     };
+    if let Some(expires_at) = request.expires_at {
+        if time_in_force != TimeInForce::GoodTillCancel {
+            return Err(CommandError::validation("Only GTC orders can have an expiry"));
+        }
+        if expires_at <= chrono::Utc::now() {
+            return Err(CommandError::validation("Order expiry is in the past"));
+        }
+    }
 
     let execution_request = ExecutionRequest {
         id: uuid::Uuid::new_v4(),
@@ -83,6 +130,9 @@ pub async fn place_order(
         time_in_force,
         reduce_only: request.reduce_only.unwrap_or(false),
         post_only: request.post_only.unwrap_or(false),
+        confirmed: request.confirmed.unwrap_or(false),
+        signal_id: None,
+        expires_at: request.expires_at,
     };
 
     match state.execution_engine.execute_order(execution_request).await {
@@ -93,11 +143,13 @@ pub async fn place_order(
                 "order": result.order,
                 "trade": result.trade,
                 "error": result.error,
+                "fat_finger": result.fat_finger,
+                "liquidity": result.liquidity_decision,
                 "latency_ms": result.latency_ms
             });
             Ok(response)
         }
-        Err(e) => Err(format!("Order execution failed: {}", e))
+        Err(e) => Err(CommandError::from(e).context("Order execution failed"))
     }
 }
 
@@ -106,12 +158,12 @@ pub async fn place_order(
 pub async fn cancel_order(
     order_id: String,
     state: tauri::State<'_, AppState>,
-) -> Result<(), String> {
+) -> CommandResult<()> {
     log::info!("Cancelling order: {}", order_id);
 
     match state.execution_engine.cancel_order(&order_id).await {
         Ok(()) => Ok(()),
-        Err(e) => Err(format!("Failed to cancel order: {}", e))
+        Err(e) => Err(CommandError::from(e).context("Failed to cancel order"))
     }
 }
 
@@ -119,7 +171,7 @@ pub async fn cancel_order(
 #[tauri::command]
 pub async fn get_open_orders(
     state: tauri::State<'_, AppState>,
-) -> Result<Vec<ea_okx_core::models::order::Order>, String> {
+) -> CommandResult<Vec<ea_okx_core::models::order::Order>> {
     log::info!("Fetching open orders");
 
     let orders = state.execution_engine.get_orders().await;
@@ -135,7 +187,7 @@ pub async fn get_open_orders(
 pub async fn get_order_history(
     limit: Option<usize>,
     state: tauri::State<'_, AppState>,
-) -> Result<Vec<ea_okx_core::models::order::Order>, String> {
+) -> CommandResult<Vec<ea_okx_core::models::order::Order>> {
     log::info!("Fetching order history (limit: {:?})", limit);
 
     let mut orders = state.execution_engine.get_orders().await;
@@ -151,7 +203,7 @@ pub async fn get_order_history(
 #[tauri::command]
 pub async fn get_positions(
     state: tauri::State<'_, AppState>,
-) -> Result<Vec<ea_okx_core::models::position::Position>, String> {
+) -> CommandResult<Vec<ea_okx_core::models::position::Position>> {
     log::info!("Fetching positions");
 
     Ok(state.execution_engine.get_positions().await)
@@ -163,7 +215,7 @@ pub async fn get_trades(
     limit: Option<usize>,
     strategy_id: Option<String>,
     state: tauri::State<'_, AppState>,
-) -> Result<Vec<ea_okx_core::models::trade::Trade>, String> {
+) -> CommandResult<Vec<ea_okx_core::models::trade::Trade>> {
     log::info!("Fetching trades (limit: {:?}, strategy_id: {:?})", limit, strategy_id);
 
     let trades = state.execution_engine.get_trades(limit).await;
@@ -179,19 +231,172 @@ pub async fn get_trades(
     }
 }
 
+/// A trade with its order, originating signal, the checks it passed and the
+/// market around the fill: `candles` candles of `interval` (default 50 of
+/// "1m") either side, and the stored order book snapshot
+#[tauri::command]
+pub async fn get_trade_context(
+    trade_id: String,
+    interval: Option<String>,
+    candles: Option<usize>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<TradeContext> {
+    let trade_id = uuid::Uuid::parse_str(&trade_id)
+        .map_err(|e| CommandError::validation(format!("Invalid trade ID: {}", e)))?;
+    let origin = state.execution_engine.trade_origin(trade_id).await
+        .ok_or_else(|| CommandError::not_found(format!("Trade not found: {}", trade_id)))?;
+
+    let interval = match interval {
+        Some(interval) => interval.parse()?,
+        None => Interval::OneMinute,
+    };
+    let count = candles.unwrap_or(50);
+    let (symbol, at) = (&origin.trade.symbol, origin.trade.executed_at);
+    let (candles, order_book) = match &state.market_storage {
+        Some(storage) => {
+            let candles = storage.query_candles_around(symbol, interval, at, count, count).await
+                .map_err(|e| CommandError::from(e).context("Failed to load candles around trade"))?;
+            let order_book = storage.query_orderbook_at(symbol, at, chrono::Duration::minutes(1)).await
+                .map_err(|e| CommandError::from(e).context("Failed to load order book at trade"))?;
+            (candles, order_book)
+        }
+        None => (Vec::new(), None),
+    };
+
+    Ok(TradeContext {
+        origin,
+        interval,
+        candles,
+        order_book,
+    })
+}
+
 /// Submit execution signal from strategy
 #[tauri::command]
 pub async fn submit_execution_signal(
     request: SignalRequest,
     state: tauri::State<'_, AppState>,
-) -> Result<(), String> {
+) -> CommandResult<()> {
     log::info!("Submitting execution signal: {:?}", request);
 
+    let signal = execution_signal(request)?;
+    match state.execution_engine.submit_signal(signal).await {
+        Ok(()) => Ok(()),
+        Err(e) => Err(CommandError::from(e).context("Failed to submit signal"))
+    }
+}
+
+/// Run a signal through the full execution pipeline without trading
+///
+/// Returns each decision in order (strategy state, order construction,
+/// validation, risk limits, size cap, fat-finger guard, execution gate and a
+/// paper fill) so a signal that would not trade shows where it stops.
+/// `equity` stands in for the account equity when no account data is loaded.
+#[tauri::command]
+pub async fn simulate_full_pipeline(
+    signal: SignalRequest,
+    equity: Option<f64>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<PipelineSimulation> {
+    log::info!("Simulating signal through the execution pipeline: {:?}", signal);
+
+    let signal = execution_signal(signal)?;
+
+    let limits = {
+        let mut limits = state.risk_limits.write().await;
+        limits.apply_due(chrono::Utc::now())?;
+        limits.active_limits().clone()
+    };
+    let account = state.account_tracker.state();
+    let total_equity = match equity {
+        Some(equity) => rust_decimal::Decimal::from_f64_retain(equity)
+            .ok_or_else(|| CommandError::validation("Invalid equity"))?,
+        None => account.total_equity,
+    };
+    let available_margin = match equity {
+        Some(_) => total_equity,
+        None => account
+            .balance(state.execution_engine.reporting_currency())
+            .and_then(|b| b.available)
+            .unwrap_or(account.total_equity),
+    };
+    let daily_loss = state.execution_gate.daily_loss();
+    let portfolio = PortfolioState {
+        total_equity,
+        available_margin,
+        positions: state.execution_engine.get_positions().await,
+        // Today's realized and unrealized P&L of the strategy's account
+        daily_pnl: daily_loss
+            .status(&daily_loss.account_for(signal.strategy_id))
+            .map_or(rust_decimal::Decimal::ZERO, |day| day.total()),
+    };
+
+    let market_price = match state.price_cache.get_price(&signal.symbol).await? {
+        Some(price) => Some(price),
+        None => state
+            .reference_prices
+            .latest(&signal.symbol)
+            .and_then(|reference| ea_okx_core::types::Price::new(reference.price).ok()),
+    };
+
+    let mut validator = PreTradeValidator::new(limits.clone());
+    if limits.var_budgets.contains_key(&signal.strategy_id) {
+        let mut symbols: Vec<ea_okx_core::types::Symbol> = portfolio
+            .positions
+            .iter()
+            .filter(|p| p.strategy_id == signal.strategy_id)
+            .map(|p| p.symbol.clone())
+            .collect();
+        symbols.push(signal.symbol.clone());
+        symbols.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        symbols.dedup();
+        let prices = market_price
+            .map(|price| std::collections::HashMap::from([(signal.symbol.clone(), price.as_decimal())]))
+            .unwrap_or_default();
+        validator = validator.with_market_history(daily_returns(&state, &symbols).await, prices);
+    }
+
+    Ok(state
+        .execution_engine
+        .simulate_pipeline(signal, &validator, &portfolio, market_price)
+        .await)
+}
+
+/// Daily returns of `symbols` over the VaR lookback, oldest first, from
+/// stored hourly candles; symbols without candles are left out
+async fn daily_returns(
+    state: &AppState,
+    symbols: &[ea_okx_core::types::Symbol],
+) -> std::collections::HashMap<ea_okx_core::types::Symbol, Vec<rust_decimal::Decimal>> {
+    let mut returns = std::collections::HashMap::new();
+    let Some(storage) = &state.market_storage else {
+        return returns;
+    };
+    // Up to the start of today, leaving out the day still trading
+    let end = Interval::OneDayUtc.bar_start(chrono::Utc::now());
+    let start = end - chrono::Duration::days(i64::from(VarConfig::default().lookback_days) + 1);
+    for symbol in symbols {
+        match storage
+            .query_candles_resampled(symbol, Interval::OneHour, Interval::OneDayUtc, start, end)
+            .await
+        {
+            Ok(days) => {
+                let closes: Vec<_> = days.iter().filter(|c| c.timestamp < end).map(|c| c.close.as_decimal()).collect();
+                returns.insert(symbol.clone(), returns_from_closes(&closes));
+            }
+            Err(e) => log::warn!("Cannot load daily candles of {} for VaR: {}", symbol.as_str(), e),
+        }
+    }
+    returns
+}
+
+/// Execution signal for a signal request from the UI
+fn execution_signal(request: SignalRequest) -> CommandResult<ExecutionSignal> {
     let strategy_id = uuid::Uuid::parse_str(&request.strategy_id)
-        .map_err(|e| format!("Invalid strategy ID: {}", e))?;
+        .map_err(|e| CommandError::validation(format!("Invalid strategy ID: {}", e)))?;
 
     let symbol = ea_okx_core::types::Symbol::new(&request.symbol)
-        .map_err(|e| format!("Invalid symbol: {}", e))?;
+        .map_err(|e| CommandError::validation(format!("Invalid symbol: {}", e)))?;
 
     let signal_type = match request.signal_type.as_str() {
         "open" => SignalType::Open,
@@ -201,41 +406,42 @@ pub async fn submit_execution_signal(
         "stop_loss" => SignalType::StopLoss,
         "take_profit" => SignalType::TakeProfit,
         "risk_management" => SignalType::RiskManagement,
-        _ => return Err("Invalid signal type".to_string()),
+        _ => return Err(CommandError::validation("Invalid signal type")),
     };
 
     let side = request.side.map(|s| {
         s.parse::<ea_okx_core::models::order::OrderSide>()
-            .map_err(|e| format!("Invalid side: {}", e))
+            .map_err(|e| CommandError::validation(format!("Invalid side: {}", e)))
     }).transpose()?;
 
     let quantity = ea_okx_core::types::Quantity::new(
         rust_decimal::Decimal::from_f64_retain(request.quantity)
-            .ok_or_else(|| "Invalid quantity".to_string())?
-    ).map_err(|e| format!("Invalid quantity: {}", e))?;
+            .ok_or_else(|| CommandError::validation("Invalid quantity"))?
+    ).map_err(|e| CommandError::validation(format!("Invalid quantity: {}", e)))?;
 
     let price = request.price.map(|p| {
         ea_okx_core::types::Price::new(
             rust_decimal::Decimal::from_f64_retain(p)
-                .ok_or_else(|| "Invalid price".to_string())?
-        ).map_err(|e| format!("Invalid price: {}", e))
+                .ok_or_else(|| CommandError::validation("Invalid price"))?
+        ).map_err(|e| CommandError::validation(format!("Invalid price: {}", e)))
     }).transpose()?;
 
     let stop_loss = request.stop_loss.map(|p| {
         ea_okx_core::types::Price::new(
             rust_decimal::Decimal::from_f64_retain(p)
-                .ok_or_else(|| "Invalid stop loss".to_string())?
-        ).map_err(|e| format!("Invalid stop loss: {}", e))
+                .ok_or_else(|| CommandError::validation("Invalid stop loss"))?
+        ).map_err(|e| CommandError::validation(format!("Invalid stop loss: {}", e)))
     }).transpose()?;
 
     let take_profit = request.take_profit.map(|p| {
         ea_okx_core::types::Price::new(
             rust_decimal::Decimal::from_f64_retain(p)
-                .ok_or_else(|| "Invalid take profit".to_string())?
-        ).map_err(|e| format!("Invalid take profit: {}", e))
+                .ok_or_else(|| CommandError::validation("Invalid take profit"))?
+        ).map_err(|e| CommandError::validation(format!("Invalid take profit: {}", e)))
     }).transpose()?;
 
     let signal = ExecutionSignal {
+        signal_id: uuid::Uuid::new_v4(),
         strategy_id,
         symbol,
         signal_type,
@@ -248,10 +454,7 @@ pub async fn submit_execution_signal(
         metadata: request.metadata.unwrap_or(serde_json::Value::Null),
     };
 
-    match state.execution_engine.submit_signal(signal).await {
-        Ok(()) => Ok(()),
-        Err(e) => Err(format!("Failed to submit signal: {}", e))
-    }
+    Ok(signal)
 }
 
 /// Close position
@@ -260,23 +463,24 @@ pub async fn close_position(
     symbol: String,
     strategy_id: String,
     state: tauri::State<'_, AppState>,
-) -> Result<(), String> {
+) -> CommandResult<()> {
     log::info!("Closing position: {} for strategy: {}", symbol, strategy_id);
 
     let strategy_uuid = uuid::Uuid::parse_str(&strategy_id)
-        .map_err(|e| format!("Invalid strategy ID: {}", e))?;
+        .map_err(|e| CommandError::validation(format!("Invalid strategy ID: {}", e)))?;
 
     let symbol_type = ea_okx_core::types::Symbol::new(&symbol)
-        .map_err(|e| format!("Invalid symbol: {}", e))?;
+        .map_err(|e| CommandError::validation(format!("Invalid symbol: {}", e)))?;
 
     // Create close signal
     let signal = ExecutionSignal {
+        signal_id: uuid::Uuid::new_v4(),
         strategy_id: strategy_uuid,
         symbol: symbol_type,
         signal_type: SignalType::Close,
         side: None, // Will be determined by position
         quantity: ea_okx_core::types::Quantity::new(rust_decimal::Decimal::MAX)
-            .map_err(|e| format!("Invalid quantity: {}", e))?,
+            .map_err(|e| CommandError::validation(format!("Invalid quantity: {}", e)))?,
         price: None,
         stop_loss: None,
         take_profit: None,
@@ -286,7 +490,7 @@ pub async fn close_position(
 
     match state.execution_engine.submit_signal(signal).await {
         Ok(()) => Ok(()),
-        Err(e) => Err(format!("Failed to close position: {}", e))
+        Err(e) => Err(CommandError::from(e).context("Failed to close position"))
     }
 }
 
@@ -295,22 +499,86 @@ pub async fn close_position(
 pub async fn get_strategy_execution_stats(
     strategy_id: String,
     state: tauri::State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
+) -> CommandResult<serde_json::Value> {
     log::info!("Getting execution stats for strategy: {}", strategy_id);
 
     match state.execution_engine.get_strategy_stats(&strategy_id).await {
         Ok(stats) => Ok(stats),
-        Err(e) => Err(format!("Failed to get stats: {}", e))
+        Err(e) => Err(CommandError::from(e).context("Failed to get stats"))
+    }
+}
+
+/// List external signal sources; secrets are not returned
+#[tauri::command]
+pub async fn get_signal_sources(
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<Vec<SignalSourceConfig>> {
+    Ok(state.signal_ingestor.sources())
+}
+
+/// Add or replace an external signal source for the session
+#[tauri::command]
+pub async fn register_signal_source(
+    config: SignalSourceConfig,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<()> {
+    log::info!("Registering signal source {} for strategy {}", config.name, config.strategy_id);
+    state.signal_ingestor.register_source(config)
+        .map_err(|e| CommandError::validation(e.to_string()))
+}
+
+/// Stop accepting signals from an external source
+#[tauri::command]
+pub async fn remove_signal_source(
+    name: String,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<()> {
+    if state.signal_ingestor.remove_source(&name) {
+        Ok(())
+    } else {
+        Err(CommandError::not_found(format!("Signal source {} not found", name)))
     }
 }
 
+/// Get signal queue depth and drop counters
+#[tauri::command]
+pub async fn get_signal_queue_metrics(
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<SignalQueueMetrics> {
+    Ok(state.execution_engine.signal_queue_metrics())
+}
+
 /// Get account balance information
 #[tauri::command]
 pub async fn get_account_balance(
-    _state: tauri::State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<serde_json::Value> {
     log::info!("Fetching account balance");
 
+    // Live state from the private account channels, once any update has arrived
+    let account = state.account_tracker.state();
+    if let Some(updated_at) = account.updated_at {
+        let usdt = account.balance("USDT");
+        let balances: serde_json::Map<String, serde_json::Value> = account.balances.values()
+            .map(|b| (b.ccy.clone(), serde_json::json!({
+                "equity": b.equity.to_f64().unwrap_or(0.0),
+                "cash_balance": b.cash_balance.to_f64().unwrap_or(0.0),
+                "available": b.available.and_then(|v| v.to_f64()),
+                "frozen": b.frozen.and_then(|v| v.to_f64()),
+            })))
+            .collect();
+
+        return Ok(serde_json::json!({
+            "total_equity": account.total_equity.to_f64().unwrap_or(0.0),
+            "available_balance": usdt.and_then(|b| b.available).and_then(|v| v.to_f64()).unwrap_or(0.0),
+            "used_balance": usdt.and_then(|b| b.frozen).and_then(|v| v.to_f64()).unwrap_or(0.0),
+            "margin_ratio": account.margin_ratio.and_then(|v| v.to_f64()),
+            "currency": "USDT",
+            "balances": balances,
+            "last_updated": format_timestamp(updated_at)
+        }));
+    }
+
     // Mock account balance - in real implementation, this would query OKX API
     let balance = serde_json::json!({
         "total_equity": 50000.0,
@@ -320,7 +588,7 @@ pub async fn get_account_balance(
         "margin_ratio": 0.15,
         "maintenance_margin": 750.0,
         "currency": "USDT",
-        "last_updated": chrono::Utc::now().to_rfc3339()
+        "last_updated": format_timestamp(chrono::Utc::now())
     });
 
     Ok(balance)
@@ -331,7 +599,7 @@ pub async fn get_account_balance(
 pub async fn get_trading_fees(
     symbol: Option<String>,
     _state: tauri::State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
+) -> CommandResult<serde_json::Value> {
     log::info!("Fetching trading fees for symbol: {:?}", symbol);
 
     // Mock trading fees - in real implementation, this would query OKX API
@@ -352,7 +620,7 @@ pub async fn get_order_book(
     symbol: String,
     depth: Option<usize>,
     _state: tauri::State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
+) -> CommandResult<serde_json::Value> {
     log::info!("Fetching order book for: {} (depth: {:?})", symbol, depth);
 
     // Mock order book data - in real implementation, this would query OKX API
@@ -372,7 +640,7 @@ pub async fn get_order_book(
             [45150.0, 0.7],
             [45200.0, 1.8]
         ],
-        "timestamp": chrono::Utc::now().to_rfc3339()
+        "timestamp": format_timestamp(chrono::Utc::now())
     });
 
     Ok(order_book)
@@ -383,7 +651,7 @@ pub async fn get_order_book(
 pub async fn get_24h_stats(
     symbol: String,
     _state: tauri::State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
+) -> CommandResult<serde_json::Value> {
     log::info!("Fetching 24h stats for: {}", symbol);
 
     // Mock 24h statistics - in real implementation, this would query OKX API
@@ -398,8 +666,8 @@ pub async fn get_24h_stats(
         "last_price": 45000.0,
         "volume": 1250.8,
         "quote_volume": 56062500.0,
-        "open_time": chrono::Utc::now().checked_sub_signed(chrono::Duration::hours(24)).unwrap().to_rfc3339(),
-        "close_time": chrono::Utc::now().to_rfc3339(),
+        "open_time": format_timestamp(chrono::Utc::now() - chrono::Duration::hours(24)),
+        "close_time": format_timestamp(chrono::Utc::now()),
         "count": 45820
     });
 
@@ -411,7 +679,7 @@ pub async fn get_24h_stats(
 pub async fn cancel_all_orders(
     symbol: Option<String>,
     state: tauri::State<'_, AppState>,
-) -> Result<usize, String> {
+) -> CommandResult<usize> {
     log::info!("Cancelling all orders for symbol: {:?}", symbol);
 
     let orders = state.execution_engine.get_orders().await;
@@ -437,45 +705,48 @@ pub async fn cancel_all_orders(
 pub async fn get_position_risk(
     symbol: Option<String>,
     state: tauri::State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
+) -> CommandResult<serde_json::Value> {
     log::info!("Fetching position risk for symbol: {:?}", symbol);
 
-    let positions = state.execution_engine.get_positions().await;
-    let mut total_unrealized_pnl = 0.0;
-    let mut total_margin_used = 0.0;
-    let mut position_count = 0;
+    let positions: Vec<_> = state
+        .execution_engine
+        .get_positions()
+        .await
+        .into_iter()
+        .filter(|pos| !pos.is_closed())
+        .filter(|pos| symbol.as_deref().is_none_or(|s| pos.symbol.as_str() == s))
+        .collect();
+    let exposure = ExposureBreakdown::from_positions(&positions, state.account_tracker.state().total_equity);
 
+    let mut total_margin_used = 0.0;
     let positions_data: Vec<serde_json::Value> = positions
-        .into_iter()
-        .filter(|pos| symbol.is_none() || pos.symbol.as_str() == symbol.as_ref().unwrap())
+        .iter()
         .map(|pos| {
-            let unrealized_pnl = pos.unrealized_pnl.to_f64().unwrap_or(0.0);
-            let margin_used = pos.quantity.as_decimal().to_f64().unwrap_or(0.0) *
-                             pos.avg_entry_price.as_decimal().to_f64().unwrap_or(0.0) * 0.1; // 10% margin assumption
-
-            total_unrealized_pnl += unrealized_pnl;
+            let leverage = pos.leverage.filter(|l| *l > rust_decimal::Decimal::ZERO).unwrap_or(rust_decimal::Decimal::ONE);
+            // Exchange-reported margin, else the notional the leverage leaves to fund
+            let margin_used = pos.margin.unwrap_or_else(|| pos.position_value() / leverage).to_f64().unwrap_or(0.0);
             total_margin_used += margin_used;
-            position_count += 1;
 
             serde_json::json!({
                 "symbol": pos.symbol.as_str(),
                 "side": format!("{:?}", pos.side),
                 "size": pos.quantity.as_decimal().to_string(),
                 "entry_price": pos.avg_entry_price.as_decimal().to_string(),
-                "mark_price": (pos.avg_entry_price.as_decimal().to_f64().unwrap_or(0.0) * 1.001).to_string(), // Mock mark price
-                "unrealized_pnl": unrealized_pnl,
+                "mark_price": pos.current_price.as_decimal().to_string(),
+                "unrealized_pnl": pos.unrealized_pnl.to_f64().unwrap_or(0.0),
                 "margin_used": margin_used,
-                "leverage": 1.0,
-                "last_updated": pos.last_updated.to_rfc3339()
+                "leverage": leverage.to_f64().unwrap_or(1.0),
+                "last_updated": format_timestamp(pos.last_updated)
             })
         })
         .collect();
 
     Ok(serde_json::json!({
         "positions": positions_data,
-        "total_unrealized_pnl": total_unrealized_pnl,
+        "total_unrealized_pnl": exposure.total.unrealized_pnl.to_f64().unwrap_or(0.0),
         "total_margin_used": total_margin_used,
-        "position_count": position_count,
+        "position_count": exposure.total.positions,
+        "exposure": exposure.total,
         "risk_metrics": {
             "portfolio_var": 2500.0,  // Mock VaR
             "max_drawdown": 500.0,
@@ -484,3 +755,382 @@ pub async fn get_position_risk(
         }
     }))
 }
+
+/// Take profit on a strategy's open position in tranches, e.g. half at +2%,
+/// a quarter at +4% and a trailing stop on the rest
+#[tauri::command]
+pub async fn set_position_plan(
+    strategy_id: String,
+    symbol: String,
+    plan: ScaleOutPlan,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<PositionPlan> {
+    log::info!("Setting scale-out plan for {} of strategy {}: {:?}", symbol, strategy_id, plan);
+
+    let strategy_id = uuid::Uuid::parse_str(&strategy_id)
+        .map_err(|e| CommandError::validation(format!("Invalid strategy ID: {}", e)))?;
+    let symbol = ea_okx_core::types::Symbol::new(&symbol)
+        .map_err(|e| CommandError::validation(format!("Invalid symbol: {}", e)))?;
+
+    state
+        .execution_engine
+        .set_position_plan(strategy_id, &symbol, &plan)
+        .await
+        .map_err(|e| CommandError::from(e).context("Failed to set position plan"))
+}
+
+/// Scale-out plan of a strategy's position: tranche prices, what has
+/// triggered and whether exits rest on the exchange or are watched locally
+#[tauri::command]
+pub async fn get_position_plan(
+    strategy_id: String,
+    symbol: String,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<Option<PositionPlan>> {
+    let strategy_id = uuid::Uuid::parse_str(&strategy_id)
+        .map_err(|e| CommandError::validation(format!("Invalid strategy ID: {}", e)))?;
+    let symbol = ea_okx_core::types::Symbol::new(&symbol)
+        .map_err(|e| CommandError::validation(format!("Invalid symbol: {}", e)))?;
+
+    Ok(state.execution_engine.get_position_plan(strategy_id, &symbol))
+}
+
+/// Stop loss and take profit resting on OKX for signal-opened positions
+#[tauri::command]
+pub async fn get_position_brackets(
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<Vec<ProtectedPosition>> {
+    Ok(state.execution_engine.get_brackets())
+}
+
+/// Where the time went for orders sent within `[start, end)`: per-stage
+/// distributions from signal to first fill, their budgets, and the share of
+/// the time to acknowledgement spent locally, on the network and at OKX
+#[tauri::command]
+pub async fn get_latency_breakdown(
+    start: chrono::DateTime<chrono::Utc>,
+    end: chrono::DateTime<chrono::Utc>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<LatencyBreakdown> {
+    if start >= end {
+        return Err(CommandError::validation("start must be before end"));
+    }
+    Ok(state.execution_engine.latency_tracker().breakdown(start, end))
+}
+
+/// Set the per-stage latency budget orders are flagged against
+#[tauri::command]
+pub async fn set_latency_budget(
+    budget: LatencyBudget,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<()> {
+    log::info!("Setting latency budget: {:?}", budget);
+
+    state.execution_engine.latency_tracker().set_budget(budget);
+    Ok(())
+}
+
+/// Algorithm execution progress
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlgoExecutionInfo {
+    pub id: String,
+    pub algorithm: String,
+    pub symbol: String,
+    pub side: String,
+    pub status: String,
+    pub status_reason: Option<String>,
+    pub total_quantity: f64,
+    pub executed_quantity: f64,
+    pub average_price: Option<f64>,
+    pub progress_pct: f64,
+    pub slices_total: u32,
+    pub slices_done: u32,
+    pub slices_failed: u32,
+    pub next_slice_at: Option<String>,
+    pub started_at: String,
+    pub updated_at: String,
+}
+
+/// List persisted TWAP/VWAP executions with their progress
+#[tauri::command]
+pub async fn list_algo_executions(
+    include_finished: Option<bool>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<Vec<AlgoExecutionInfo>> {
+    log::info!("Listing algo executions (include_finished: {:?})", include_finished);
+
+    let include_finished = include_finished.unwrap_or(true);
+    let executions = state.algo_store.load_all()
+        .map_err(|e| CommandError::from(e).context("Failed to load algo executions"))?;
+
+    let infos = executions.into_iter()
+        .filter(|e| include_finished || !e.status.is_terminal())
+        .map(|e| AlgoExecutionInfo {
+            id: e.id.to_string(),
+            algorithm: e.params.name().to_string(),
+            symbol: e.symbol.as_str().to_string(),
+            side: format!("{:?}", e.side).to_lowercase(),
+            status: format!("{:?}", e.status).to_lowercase(),
+            status_reason: e.status_reason.clone(),
+            total_quantity: e.total_quantity.as_decimal().to_f64().unwrap_or(0.0),
+            executed_quantity: e.executed_quantity.to_f64().unwrap_or(0.0),
+            average_price: e.average_price().and_then(|p| p.as_decimal().to_f64()),
+            progress_pct: e.progress_pct().to_f64().unwrap_or(0.0),
+            slices_total: e.slices_total,
+            slices_done: e.slices_done,
+            slices_failed: e.slices_failed,
+            next_slice_at: (!e.status.is_terminal()).then(|| format_timestamp(e.next_slice_at)),
+            started_at: format_timestamp(e.started_at),
+            updated_at: format_timestamp(e.updated_at),
+        })
+        .collect();
+
+    Ok(infos)
+}
+
+/// Latest balance reconciliation against the exchange
+#[tauri::command]
+pub async fn get_account_reconciliation(
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<Option<ReconciliationReport>> {
+    Ok(state.account_tracker.last_report())
+}
+
+/// Enable or disable sending orders to the exchange for every strategy
+#[tauri::command]
+pub async fn set_trading_enabled(
+    enabled: bool,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<()> {
+    log::warn!("Setting trading enabled: {}", enabled);
+
+    state.execution_gate.set_trading_enabled(enabled);
+    Ok(())
+}
+
+/// Put a strategy into or out of dry-run; its orders are logged, not sent
+#[tauri::command]
+pub async fn set_strategy_dry_run(
+    strategy_id: String,
+    dry_run: bool,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<()> {
+    log::info!("Setting dry-run for strategy {}: {}", strategy_id, dry_run);
+
+    let strategy_id = uuid::Uuid::parse_str(&strategy_id)
+        .map_err(|e| CommandError::validation(format!("Invalid strategy ID: {}", e)))?;
+
+    state.execution_gate.set_strategy_dry_run(strategy_id, dry_run);
+    Ok(())
+}
+
+/// Default per-strategy quota
+#[tauri::command]
+pub async fn get_default_quota(
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<StrategyQuota> {
+    Ok(state.execution_gate.quotas().default_quota())
+}
+
+/// Order, cancel and signal usage against quota for every active strategy
+#[tauri::command]
+pub async fn get_quota_usage(
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<Vec<QuotaUsage>> {
+    Ok(state.execution_gate.quotas().usage())
+}
+
+/// Set one strategy's quota, or the default when no strategy is given
+#[tauri::command]
+pub async fn set_strategy_quota(
+    strategy_id: Option<String>,
+    quota: StrategyQuota,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<()> {
+    log::info!("Setting quota for {:?}: {:?}", strategy_id, quota);
+
+    let quotas = state.execution_gate.quotas();
+    match strategy_id {
+        Some(strategy_id) => {
+            let strategy_id = uuid::Uuid::parse_str(&strategy_id)
+                .map_err(|e| CommandError::validation(format!("Invalid strategy ID: {}", e)))?;
+            quotas.set_strategy_quota(strategy_id, quota);
+        }
+        None => quotas.set_default_quota(quota),
+    }
+    Ok(())
+}
+
+/// Drop a strategy's quota override so the default applies again
+#[tauri::command]
+pub async fn clear_strategy_quota(
+    strategy_id: String,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<()> {
+    let strategy_id = uuid::Uuid::parse_str(&strategy_id)
+        .map_err(|e| CommandError::validation(format!("Invalid strategy ID: {}", e)))?;
+
+    state.execution_gate.quotas().clear_strategy_quota(strategy_id);
+    Ok(())
+}
+
+/// Execution policy of one strategy, or the default when no strategy is given
+#[tauri::command]
+pub async fn get_execution_policy(
+    strategy_id: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<ExecutionPolicy> {
+    let policies = state.execution_engine.execution_policies();
+    match strategy_id {
+        Some(strategy_id) => {
+            let strategy_id = uuid::Uuid::parse_str(&strategy_id)
+                .map_err(|e| CommandError::validation(format!("Invalid strategy ID: {}", e)))?;
+            Ok(policies.policy(strategy_id))
+        }
+        None => Ok(policies.default_policy()),
+    }
+}
+
+/// Set how one strategy's signals become orders, or the default when no
+/// strategy is given
+#[tauri::command]
+pub async fn set_execution_policy(
+    strategy_id: Option<String>,
+    policy: ExecutionPolicy,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<()> {
+    log::info!("Setting execution policy for {:?}: {:?}", strategy_id, policy);
+
+    let policies = state.execution_engine.execution_policies();
+    match strategy_id {
+        Some(strategy_id) => {
+            let strategy_id = uuid::Uuid::parse_str(&strategy_id)
+                .map_err(|e| CommandError::validation(format!("Invalid strategy ID: {}", e)))?;
+            policies.set_strategy_policy(strategy_id, policy)?;
+        }
+        None => policies.set_default_policy(policy)?,
+    }
+    Ok(())
+}
+
+/// Drop a strategy's execution policy so the default applies again
+#[tauri::command]
+pub async fn clear_execution_policy(
+    strategy_id: String,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<()> {
+    let strategy_id = uuid::Uuid::parse_str(&strategy_id)
+        .map_err(|e| CommandError::validation(format!("Invalid strategy ID: {}", e)))?;
+
+    state.execution_engine.execution_policies().clear_strategy_policy(strategy_id);
+    Ok(())
+}
+
+/// OKX health and the degraded-mode state of the execution gate
+#[tauri::command]
+pub async fn get_exchange_health(
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<ExchangeHealth> {
+    let degraded = state.execution_gate.degraded();
+    Ok(ExchangeHealth {
+        status: state.outage_detector.as_ref().map(|detector| detector.status()),
+        degraded: degraded.state(),
+        policy: degraded.policy(),
+    })
+}
+
+/// Set what changes while the exchange is degraded
+#[tauri::command]
+pub async fn set_degraded_mode_policy(
+    policy: DegradedModePolicy,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<()> {
+    log::info!("Setting degraded mode policy: {:?}", policy);
+
+    state.execution_gate.degraded().set_policy(policy);
+    Ok(())
+}
+
+/// Refuse a strategy's opening orders on symbols whose feed quality score
+/// (0 to 100) is below `min_score`, or clear its minimum when none is given
+#[tauri::command]
+pub async fn set_strategy_min_feed_quality(
+    strategy_id: String,
+    min_score: Option<f64>,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<()> {
+    let strategy_id = uuid::Uuid::parse_str(&strategy_id)
+        .map_err(|e| CommandError::validation(format!("Invalid strategy ID: {}", e)))?;
+    if let Some(min_score) = min_score
+        && !(0.0..=100.0).contains(&min_score)
+    {
+        return Err(CommandError::validation(format!(
+            "Feed quality minimum must be between 0 and 100, got {}",
+            min_score
+        )));
+    }
+
+    state.execution_gate.feed_quality().set_min_score(strategy_id, min_score);
+    Ok(())
+}
+
+/// Current fat-finger limits, default and per symbol
+#[tauri::command]
+pub async fn get_fat_finger_config(
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<FatFingerConfig> {
+    Ok(state.fat_finger.config())
+}
+
+/// Set fat-finger limits for one symbol, or the defaults when no symbol is given
+#[tauri::command]
+pub async fn set_fat_finger_limits(
+    symbol: Option<String>,
+    limits: FatFingerLimits,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<()> {
+    log::info!("Setting fat-finger limits for {:?}: {:?}", symbol, limits);
+
+    match symbol {
+        Some(symbol) => {
+            let symbol = ea_okx_core::types::Symbol::new(&symbol)
+                .map_err(|e| CommandError::validation(format!("Invalid symbol: {}", e)))?;
+            state.fat_finger.set_symbol_limits(symbol, limits);
+        }
+        None => state.fat_finger.set_default_limits(limits),
+    }
+    Ok(())
+}
+
+/// Depth band and share of it one order may take
+#[tauri::command]
+pub async fn get_liquidity_config(
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<LiquidityConfig> {
+    Ok(state.liquidity.config())
+}
+
+/// Change how orders are capped by visible liquidity
+#[tauri::command]
+pub async fn set_liquidity_config(
+    config: LiquidityConfig,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<()> {
+    log::info!("Setting liquidity config: {:?}", config);
+
+    state.liquidity.set_config(config)?;
+    Ok(())
+}
+
+/// Drop a symbol's fat-finger override so the defaults apply again
+#[tauri::command]
+pub async fn clear_fat_finger_limits(
+    symbol: String,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<()> {
+    let symbol = ea_okx_core::types::Symbol::new(&symbol)
+        .map_err(|e| CommandError::validation(format!("Invalid symbol: {}", e)))?;
+
+    state.fat_finger.clear_symbol_limits(&symbol);
+    Ok(())
+}
@@ -1,5 +1,8 @@
+use crate::error::{CommandError, CommandResult};
 use crate::state::AppState;
+use crate::services::push::{PushMessage, PushTopic};
 use crate::services::strategy_monitor::StrategyUpdateEvent;
+use ea_okx_core::contract::format_timestamp;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use tauri::Emitter;
@@ -18,7 +21,7 @@ pub async fn subscribe_strategy_updates(
     subscription: WebSocketSubscription,
     app_handle: tauri::AppHandle,
     state: tauri::State<'_, AppState>,
-) -> Result<String, String> {
+) -> CommandResult<String> {
     log::info!("Client subscribing to strategy updates: {:?}", subscription);
 
     match state.strategy_monitor.subscribe_client(
@@ -52,21 +55,48 @@ pub async fn subscribe_strategy_updates(
 
             Ok(client_id)
         }
-        Err(e) => Err(format!("Failed to subscribe: {}", e))
+        Err(e) => Err(CommandError::from(e).context("Failed to subscribe"))
     }
 }
 
+/// Subscribe to pushed updates for positions, orders or metrics
+///
+/// Returns the current snapshot; changes follow on the topic's event
+/// (`positions:update`, `orders:update`, `metrics:update`).
+#[tauri::command]
+pub async fn subscribe_push(
+    topic: PushTopic,
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<PushMessage> {
+    log::info!("Client subscribing to {}", topic.event_name());
+
+    Ok(state.push.subscribe(&app_handle, topic).await)
+}
+
+/// Stop pushed updates for a topic
+#[tauri::command]
+pub async fn unsubscribe_push(
+    topic: PushTopic,
+    state: tauri::State<'_, AppState>,
+) -> CommandResult<()> {
+    log::info!("Client unsubscribing from {}", topic.event_name());
+
+    state.push.unsubscribe(topic).await;
+    Ok(())
+}
+
 /// Unsubscribe from strategy updates
 #[tauri::command]
 pub async fn unsubscribe_strategy_updates(
     client_id: String,
     state: tauri::State<'_, AppState>,
-) -> Result<(), String> {
+) -> CommandResult<()> {
     log::info!("Client unsubscribing: {}", client_id);
 
     match state.strategy_monitor.unsubscribe_client(&client_id).await {
         Ok(()) => Ok(()),
-        Err(e) => Err(format!("Failed to unsubscribe: {}", e))
+        Err(e) => Err(CommandError::from(e).context("Failed to unsubscribe"))
     }
 }
 
@@ -74,7 +104,7 @@ pub async fn unsubscribe_strategy_updates(
 #[tauri::command]
 pub async fn get_connected_clients_count(
     state: tauri::State<'_, AppState>,
-) -> Result<usize, String> {
+) -> CommandResult<usize> {
     Ok(state.strategy_monitor.get_clients_count().await)
 }
 
@@ -82,7 +112,7 @@ pub async fn get_connected_clients_count(
 #[tauri::command]
 pub async fn get_realtime_strategy_stats(
     state: tauri::State<'_, AppState>,
-) -> Result<HashMap<String, serde_json::Value>, String> {
+) -> CommandResult<HashMap<String, serde_json::Value>> {
     Ok(state.strategy_monitor.get_strategy_stats().await)
 }
 
@@ -95,7 +125,7 @@ pub async fn simulate_strategy_signal(
     price: f64,
     confidence: f64,
     state: tauri::State<'_, AppState>,
-) -> Result<(), String> {
+) -> CommandResult<()> {
     log::info!("Simulating signal for strategy {}: {:?} @ {}", strategy_id, signal_type, price);
 
     match state.strategy_monitor.emit_signal_generated(
@@ -106,7 +136,7 @@ pub async fn simulate_strategy_signal(
         confidence,
     ).await {
         Ok(()) => Ok(()),
-        Err(e) => Err(format!("Failed to emit signal: {}", e))
+        Err(e) => Err(CommandError::from(e).context("Failed to emit signal"))
     }
 }
 
@@ -119,7 +149,7 @@ pub async fn simulate_trade_execution(
     amount: f64,
     price: f64,
     state: tauri::State<'_, AppState>,
-) -> Result<(), String> {
+) -> CommandResult<()> {
     let trade_id = uuid::Uuid::new_v4().to_string();
 
     log::info!("Simulating trade for strategy {}: {} {} @ {}", strategy_id, side, amount, price);
@@ -131,9 +161,10 @@ pub async fn simulate_trade_execution(
         side,
         amount,
         price,
+        false,
     ).await {
         Ok(()) => Ok(()),
-        Err(e) => Err(format!("Failed to emit trade: {}", e))
+        Err(e) => Err(CommandError::from(e).context("Failed to emit trade"))
     }
 }
 
@@ -143,12 +174,12 @@ pub async fn simulate_strategy_error(
     strategy_id: String,
     error_message: String,
     state: tauri::State<'_, AppState>,
-) -> Result<(), String> {
+) -> CommandResult<()> {
     log::warn!("Simulating error for strategy {}: {}", strategy_id, error_message);
 
     match state.strategy_monitor.emit_error(strategy_id, error_message).await {
         Ok(()) => Ok(()),
-        Err(e) => Err(format!("Failed to emit error: {}", e))
+        Err(e) => Err(CommandError::from(e).context("Failed to emit error"))
     }
 }
 
@@ -163,7 +194,7 @@ pub async fn simulate_position_update(
     exit_price: Option<f64>,
     pnl: Option<f64>,
     state: tauri::State<'_, AppState>,
-) -> Result<(), String> {
+) -> CommandResult<()> {
     log::info!("Simulating position update for strategy {}: {} {} {}", strategy_id, symbol, side, size);
 
     match state.strategy_monitor.emit_position_update(
@@ -176,7 +207,7 @@ pub async fn simulate_position_update(
         pnl,
     ).await {
         Ok(()) => Ok(()),
-        Err(e) => Err(format!("Failed to emit position update: {}", e))
+        Err(e) => Err(CommandError::from(e).context("Failed to emit position update"))
     }
 }
 
@@ -186,12 +217,12 @@ pub async fn update_strategy_metrics(
     strategy_id: String,
     metrics: ea_okx_core::models::strategy::StrategyMetrics,
     state: tauri::State<'_, AppState>,
-) -> Result<(), String> {
+) -> CommandResult<()> {
     log::info!("Updating metrics for strategy: {}", strategy_id);
 
     match state.strategy_monitor.emit_metrics_updated(strategy_id, metrics).await {
         Ok(()) => Ok(()),
-        Err(e) => Err(format!("Failed to update metrics: {}", e))
+        Err(e) => Err(CommandError::from(e).context("Failed to update metrics"))
     }
 }
 
@@ -199,7 +230,7 @@ pub async fn update_strategy_metrics(
 #[tauri::command]
 pub async fn get_websocket_status(
     state: tauri::State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
+) -> CommandResult<serde_json::Value> {
     let clients_count = state.strategy_monitor.get_clients_count().await;
     let strategy_stats = state.strategy_monitor.get_strategy_stats().await;
     let active_strategies = strategy_stats.values()
@@ -211,7 +242,7 @@ pub async fn get_websocket_status(
         "active_strategies": active_strategies,
         "total_strategies": strategy_stats.len(),
         "status": "connected",
-        "last_update": chrono::Utc::now().to_rfc3339()
+        "last_update": format_timestamp(chrono::Utc::now())
     }))
 }
 
@@ -219,12 +250,12 @@ pub async fn get_websocket_status(
 #[tauri::command]
 pub async fn get_market_data_status(
     _state: tauri::State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
+) -> CommandResult<serde_json::Value> {
     // This would integrate with the OKX WebSocket client
     Ok(serde_json::json!({
         "status": "connected",
         "subscriptions": ["BTC-USDT", "ETH-USDT", "SOL-USDT"],
-        "last_update": chrono::Utc::now().to_rfc3339(),
+        "last_update": format_timestamp(chrono::Utc::now()),
         "latency_ms": 15
     }))
 }
\ No newline at end of file
@@ -0,0 +1,245 @@
+//! Typed errors returned to the frontend by Tauri commands
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Result type for Tauri commands
+pub type CommandResult<T> = Result<T, CommandError>;
+
+/// Error category the frontend can branch on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// Bad input; retrying the same request will fail again
+    Validation,
+    /// Referenced entity does not exist
+    NotFound,
+    /// Operation not allowed in the entity's current state
+    InvalidState,
+    /// Caller lacks the approval the operation requires
+    Forbidden,
+    /// Exchange rejected the request
+    ExchangeRejected,
+    /// Request rate limit hit; retry later
+    RateLimited,
+    /// Exchange, network or storage temporarily unavailable; retry later
+    Unavailable,
+    /// Unexpected failure
+    Internal,
+}
+
+/// Error payload returned by every command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandError {
+    pub code: ErrorCode,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+impl CommandError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    pub fn validation(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Validation, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::NotFound, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Internal, message)
+    }
+
+    /// Prefix the message, e.g. "Failed to submit signal: <cause>"
+    pub fn context(mut self, context: impl fmt::Display) -> Self {
+        self.message = format!("{}: {}", context, self.message);
+        self
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+impl From<ea_okx_core::error::Error> for CommandError {
+    fn from(e: ea_okx_core::error::Error) -> Self {
+        use ea_okx_core::error::Error;
+
+        let code = match &e {
+            Error::InvalidSymbol(_)
+            | Error::InvalidPrice(_)
+            | Error::InvalidQuantity(_)
+            | Error::InvalidOrderType(_)
+            | Error::InvalidOrderSide(_)
+            | Error::InvalidOrderStatus(_)
+            | Error::InvalidPositionSide(_)
+            | Error::InvalidInterval(_)
+            | Error::DecimalError(_)
+            | Error::ValidationError(_) => ErrorCode::Validation,
+            Error::NotFound(_) => ErrorCode::NotFound,
+            Error::ExchangeRejected { .. } => ErrorCode::ExchangeRejected,
+            Error::RateLimited(_) => ErrorCode::RateLimited,
+            Error::ExchangeUnavailable(_) => ErrorCode::Unavailable,
+            Error::SerializationError(_)
+            | Error::SchemaError(_)
+            | Error::ConfigError(_)
+            | Error::DivisionByZero(_)
+            | Error::ArithmeticOverflow(_)
+            | Error::ExchangeError(_)
+            | Error::Internal(_) => ErrorCode::Internal,
+        };
+        Self::new(code, e.to_string())
+    }
+}
+
+impl From<ea_okx_client::Error> for CommandError {
+    fn from(e: ea_okx_client::Error) -> Self {
+        use ea_okx_client::Error;
+
+        let code = match &e {
+            Error::ApiError { .. } => ErrorCode::ExchangeRejected,
+            Error::RateLimitExceeded(_) => ErrorCode::RateLimited,
+            Error::HttpError(_)
+            | Error::WebSocketError(_)
+            | Error::WebSocketConnection(_)
+            | Error::WebSocketSend(_)
+            | Error::Timeout(_)
+            | Error::ConnectionError(_) => ErrorCode::Unavailable,
+            Error::AuthError(_)
+            | Error::ParseError(_)
+            | Error::InvalidResponse(_)
+            | Error::SerializationError(_)
+            | Error::UrlError(_)
+            | Error::Internal(_) => ErrorCode::Internal,
+        };
+
+        let details = match &e {
+            Error::ApiError { code, .. } => Some(serde_json::json!({
+                "exchange_code": code,
+                "rejection": ea_okx_client::RejectionReason::from_error(&e),
+            })),
+            _ => None,
+        };
+
+        Self {
+            code,
+            message: e.to_string(),
+            details,
+        }
+    }
+}
+
+impl From<data::Error> for CommandError {
+    fn from(e: data::Error) -> Self {
+        use data::Error;
+
+        match e {
+            Error::CoreError(inner) => inner.into(),
+            Error::WebSocketError(inner) => inner.into(),
+            Error::ValidationError(_)
+            | Error::DuplicateData(_)
+            | Error::AnomalyDetected(_)
+            | Error::ParseError(_) => Self::validation(e.to_string()),
+            Error::StaleData(_) | Error::DatabaseError(_) | Error::RedisError(_) => {
+                Self::new(ErrorCode::Unavailable, e.to_string())
+            }
+            Error::SerializationError(_)
+            | Error::CompressionError(_)
+            | Error::ConfigError(_)
+            | Error::Internal(_) => Self::internal(e.to_string()),
+        }
+    }
+}
+
+impl From<ea_okx_trading::Error> for CommandError {
+    fn from(e: ea_okx_trading::Error) -> Self {
+        use ea_okx_trading::Error;
+
+        match e {
+            Error::CoreError(inner) => inner.into(),
+            Error::ClientError(inner) => inner.into(),
+            Error::InvalidStateTransition(_) | Error::TradingDisabled(_) => {
+                Self::new(ErrorCode::InvalidState, e.to_string())
+            }
+            Error::OrderNotFound(_) => Self::not_found(e.to_string()),
+            Error::SizeLimitExceeded { .. }
+            | Error::LiquidityExceeded { .. }
+            | Error::InsufficientBalance { .. }
+            | Error::FatFingerRejected(_)
+            | Error::ReduceOnlyRejected(_)
+            | Error::InvalidPlan(_)
+            | Error::InvalidBracket(_)
+            | Error::InvalidPolicy(_)
+            | Error::UnknownSymbol(_) => Self::validation(e.to_string()),
+            Error::ConfirmationRequired(_) | Error::DailyLossLocked(_) => {
+                Self::new(ErrorCode::Forbidden, e.to_string())
+            }
+            Error::QueueFull(_) | Error::QuotaExceeded(_) => {
+                Self::new(ErrorCode::RateLimited, e.to_string())
+            }
+            Error::TimeoutError(_) => Self::new(ErrorCode::Unavailable, e.to_string()),
+            Error::ReconciliationError(_)
+            | Error::ExecutionError(_)
+            | Error::PersistenceError(_)
+            | Error::SerializationError(_) => Self::internal(e.to_string()),
+        }
+    }
+}
+
+impl From<ea_okx_backtest::Error> for CommandError {
+    fn from(e: ea_okx_backtest::Error) -> Self {
+        use ea_okx_backtest::Error;
+
+        match e {
+            Error::CoreError(inner) => inner.into(),
+            Error::RunNotFound(_) => Self::not_found(e.to_string()),
+            Error::InvalidConfig(_)
+            | Error::InsufficientData(_)
+            | Error::LookAheadBias(_)
+            | Error::DataGaps(_) => Self::validation(e.to_string()),
+            Error::DatabaseError(_) => Self::new(ErrorCode::Unavailable, e.to_string()),
+            Error::StrategyError(_)
+            | Error::ExecutionError(_)
+            | Error::SerializationError(_)
+            | Error::InvalidStateTransition(_)
+            | Error::RegistryError(_) => Self::internal(e.to_string()),
+        }
+    }
+}
+
+impl From<ea_okx_monitoring::Error> for CommandError {
+    fn from(e: ea_okx_monitoring::Error) -> Self {
+        Self::internal(e.to_string())
+    }
+}
+
+impl From<ea_okx_risk::Error> for CommandError {
+    fn from(e: ea_okx_risk::Error) -> Self {
+        use ea_okx_risk::Error;
+
+        match e {
+            Error::CoreError(inner) => inner.into(),
+            Error::LimitChangeNotFound(_) => Self::not_found(e.to_string()),
+            Error::ApprovalDenied(_) => Self::new(ErrorCode::Forbidden, e.to_string()),
+            Error::AuditLogError(_) | Error::CalculationError(_) => Self::internal(e.to_string()),
+            Error::RiskLimitExceeded(_)
+            | Error::ValidationFailed(_)
+            | Error::InsufficientMargin { .. }
+            | Error::PositionLimitExceeded(_)
+            | Error::DailyLossLimitExceeded(_)
+            | Error::LeverageLimitExceeded(_) => Self::validation(e.to_string()),
+        }
+    }
+}
@@ -1,23 +1,30 @@
 mod commands;
+mod error;
 mod services;
 mod state;
 
 use state::AppState;
+use std::time::Duration;
 use tauri::Manager;
 use commands::{
+    access::*,
     strategy::*,
     trading::*,
     data::*,
     risk::*,
     system::*,
     websocket::*,
+    funding::*,
+    notifications::*,
 };
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
   let app_state = AppState::new();
+  let access = app_state.access.clone();
 
   tauri::Builder::default()
+    .plugin(tauri_plugin_notification::init())
     .manage(app_state)
     .setup(|app| {
       if cfg!(debug_assertions) {
@@ -34,65 +41,180 @@ pub fn run() {
       tauri::async_runtime::spawn(async move {
           if let Err(e) = state_clone.initialize().await {
               log::error!("Failed to initialize app state: {}", e);
+              state_clone.notifications
+                  .system_error("Startup failed", format!("Failed to initialize app state: {}", e))
+                  .await;
           }
       });
 
+      // Deliver notifications to the window and the OS
+      state.notifications.attach(app.handle().clone());
+
+      // Push position, order and metric changes to subscribed windows
+      state.push.clone().spawn(app.handle().clone(), Duration::from_millis(500));
+
       Ok(())
     })
-    .invoke_handler(tauri::generate_handler![
-      // Strategy commands
-      get_strategies,
-      get_strategy,
-      create_strategy,
-      update_strategy,
-      delete_strategy,
-      start_strategy,
-      stop_strategy,
-      pause_strategy,
-      get_strategy_metrics,
-      duplicate_strategy,
-      // Trading commands
-      place_order,
-      cancel_order,
-      cancel_all_orders,
-      get_open_orders,
-      get_order_history,
-      get_positions,
-      close_position,
-      get_trades,
-      submit_execution_signal,
-      get_strategy_execution_stats,
-      get_account_balance,
-      get_trading_fees,
-      get_order_book,
-      get_24h_stats,
-      get_position_risk,
-      // Data commands
-      subscribe_market_data,
-      get_latest_price,
-      get_candles,
-      // Risk commands
-      get_risk_limits,
-      update_risk_limits,
-      calculate_var,
-      // System commands
-      get_system_metrics,
-      get_alerts,
-      run_backtest,
-      get_backtest_results,
-      // WebSocket commands
-      subscribe_strategy_updates,
-      unsubscribe_strategy_updates,
-      get_connected_clients_count,
-      get_realtime_strategy_stats,
-      simulate_strategy_signal,
-      simulate_trade_execution,
-      simulate_strategy_error,
-      simulate_position_update,
-      update_strategy_metrics,
-      get_websocket_status,
-      get_market_data_status,
-    ])
+    .invoke_handler({
+      let handler: Box<dyn Fn(tauri::ipc::Invoke) -> bool + Send + Sync> = Box::new(tauri::generate_handler![
+        // Access commands
+        sign_in,
+        sign_out,
+        get_session,
+        get_access_audit_log,
+        // Strategy commands
+        get_strategies,
+        get_strategy,
+        create_strategy,
+        update_strategy,
+        delete_strategy,
+        start_strategy,
+        stop_strategy,
+        pause_strategy,
+        get_strategy_metrics,
+        duplicate_strategy,
+        get_strategy_status_history,
+        get_strategy_custom_metrics,
+        get_strategy_custom_metric_history,
+        get_dca_history,
+        get_strategy_decay,
+        set_strategy_baseline,
+        export_strategy_bundle,
+        import_strategy_bundle,
+        // Trading commands
+        place_order,
+        cancel_order,
+        cancel_all_orders,
+        get_open_orders,
+        get_order_history,
+        get_positions,
+        close_position,
+        get_trades,
+        get_trade_context,
+        submit_execution_signal,
+        simulate_full_pipeline,
+        get_strategy_execution_stats,
+        get_signal_queue_metrics,
+        get_signal_sources,
+        register_signal_source,
+        remove_signal_source,
+        get_account_balance,
+        get_trading_fees,
+        get_order_book,
+        get_24h_stats,
+        get_position_risk,
+        set_position_plan,
+        get_position_plan,
+        get_position_brackets,
+        get_latency_breakdown,
+        set_latency_budget,
+        list_algo_executions,
+        get_account_reconciliation,
+        set_trading_enabled,
+        set_strategy_dry_run,
+        get_fat_finger_config,
+        set_fat_finger_limits,
+        clear_fat_finger_limits,
+        get_liquidity_config,
+        set_liquidity_config,
+        get_default_quota,
+        get_quota_usage,
+        set_strategy_quota,
+        clear_strategy_quota,
+        get_execution_policy,
+        set_execution_policy,
+        clear_execution_policy,
+        get_exchange_health,
+        set_degraded_mode_policy,
+        set_strategy_min_feed_quality,
+        // Data commands
+        subscribe_market_data,
+        get_latest_price,
+        get_price_cache_stats,
+        get_candles,
+        get_candles_resampled,
+        verify_data_integrity,
+        get_orderbook_history,
+        get_open_interest,
+        get_taker_volume,
+        get_long_short_ratio,
+        get_reference_price,
+        get_data_quality,
+        search_symbols,
+        sync_symbol_catalog,
+        // Funding account commands
+        set_transfers_enabled,
+        transfer_funds,
+        get_deposit_addresses,
+        get_withdrawal_history,
+        get_asset_balances,
+        // Risk commands
+        get_risk_limits,
+        update_risk_limits,
+        get_pending_risk_limit_change,
+        confirm_risk_limit_change,
+        cancel_risk_limit_change,
+        get_risk_limit_audit_log,
+        get_daily_loss_status,
+        override_daily_loss_lock,
+        get_drawdown_throttle,
+        set_drawdown_schedule,
+        clear_drawdown_schedule,
+        calculate_var,
+        get_stress_scenarios,
+        get_exposure_breakdown,
+        run_stress_test,
+        optimize_strategy_allocation,
+        // System commands
+        get_system_metrics,
+        get_health_report,
+        get_task_health,
+        get_schema_version,
+        get_alerts,
+        run_backtest,
+        get_backtest_results,
+        get_backtest_equity_curve,
+        list_backtests,
+        compare_backtests,
+        get_live_equity_curve,
+        get_daily_reports,
+        get_daily_report_html,
+        export_drop_copy,
+        take_snapshot,
+        list_snapshots,
+        restore_snapshot,
+        // Notification commands
+        get_notifications,
+        mark_notification_read,
+        get_notification_preferences,
+        set_notification_preference,
+        get_quiet_hours,
+        set_quiet_hours,
+        // WebSocket commands
+        subscribe_strategy_updates,
+        unsubscribe_strategy_updates,
+        subscribe_push,
+        unsubscribe_push,
+        get_connected_clients_count,
+        get_realtime_strategy_stats,
+        simulate_strategy_signal,
+        simulate_trade_execution,
+        simulate_strategy_error,
+        simulate_position_update,
+        update_strategy_metrics,
+        get_websocket_status,
+        get_market_data_status,
+      ]);
+      // Every command is checked against the caller's role before it runs;
+      // register new ones in the access service's role table
+      move |invoke| {
+        if let Err(e) = access.authorize(invoke.message.command()) {
+          invoke.resolver.reject(e);
+          return true;
+        }
+        handler(invoke)
+      }
+    })
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
 }
@@ -0,0 +1,611 @@
+//! Roles and command permissions
+//!
+//! Every Tauri command requires a [`Role`]: viewers can read data, traders
+//! can also place and cancel orders and run strategies, and admins can in
+//! addition run destructive commands such as cancelling every order,
+//! changing risk limits or flipping the trading kill switch. The invoke
+//! handler asks [`AccessControl`] to authorize each invocation against the
+//! signed-in caller before the command runs. Admin commands, refused
+//! invocations and sign-ins are appended to an audit journal with the
+//! caller's identity.
+//!
+//! Users are configured in `users.json` in the data directory, each with a
+//! salted PBKDF2-HMAC-SHA256 [`PasscodeHash`]. Without it the app runs
+//! single-user as `operator`, with the role set by `EA_OKX_DEFAULT_ROLE`
+//! (admin if unset); with it, a session starts as an anonymous viewer until
+//! someone signs in.
+
+use crate::error::{CommandError, CommandResult, ErrorCode};
+use chrono::{DateTime, Utc};
+pub use ea_okx_risk::PasscodeHash;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+
+/// Caller name when no users are configured
+pub const DEFAULT_ACTOR: &str = "operator";
+
+/// Caller name of a multi-user session nobody has signed in to
+const ANONYMOUS: &str = "anonymous";
+
+/// Audit entries kept in memory; the journal keeps all of them
+const AUDIT_LIMIT: usize = 1000;
+
+/// Role needed by every command the invoke handler registers
+///
+/// Admin commands cancel orders wholesale, move funds, or change the limits,
+/// switches and signal routing that keep trading safe.
+const COMMAND_ROLES: &[(&str, Role)] = &[
+    // Access commands
+    ("sign_in", Role::Viewer),
+    ("sign_out", Role::Viewer),
+    ("get_session", Role::Viewer),
+    ("get_access_audit_log", Role::Viewer),
+    // Strategy commands
+    ("get_strategies", Role::Viewer),
+    ("get_strategy", Role::Viewer),
+    ("create_strategy", Role::Trader),
+    ("update_strategy", Role::Trader),
+    ("delete_strategy", Role::Admin),
+    ("start_strategy", Role::Trader),
+    ("stop_strategy", Role::Trader),
+    ("pause_strategy", Role::Trader),
+    ("get_strategy_metrics", Role::Viewer),
+    ("duplicate_strategy", Role::Trader),
+    ("get_strategy_status_history", Role::Viewer),
+    ("get_strategy_custom_metrics", Role::Viewer),
+    ("get_strategy_custom_metric_history", Role::Viewer),
+    ("get_dca_history", Role::Viewer),
+    ("get_strategy_decay", Role::Viewer),
+    ("set_strategy_baseline", Role::Admin),
+    ("export_strategy_bundle", Role::Viewer),
+    ("import_strategy_bundle", Role::Trader),
+    // Trading commands
+    ("place_order", Role::Trader),
+    ("cancel_order", Role::Trader),
+    ("cancel_all_orders", Role::Admin),
+    ("get_open_orders", Role::Viewer),
+    ("get_order_history", Role::Viewer),
+    ("get_positions", Role::Viewer),
+    ("close_position", Role::Trader),
+    ("get_trades", Role::Viewer),
+    ("get_trade_context", Role::Viewer),
+    ("submit_execution_signal", Role::Trader),
+    ("simulate_full_pipeline", Role::Trader),
+    ("get_strategy_execution_stats", Role::Viewer),
+    ("get_signal_queue_metrics", Role::Viewer),
+    ("get_signal_sources", Role::Viewer),
+    ("register_signal_source", Role::Admin),
+    ("remove_signal_source", Role::Admin),
+    ("get_account_balance", Role::Viewer),
+    ("get_trading_fees", Role::Viewer),
+    ("get_order_book", Role::Viewer),
+    ("get_24h_stats", Role::Viewer),
+    ("get_position_risk", Role::Viewer),
+    ("set_position_plan", Role::Trader),
+    ("get_position_plan", Role::Viewer),
+    ("get_position_brackets", Role::Viewer),
+    ("get_latency_breakdown", Role::Viewer),
+    ("set_latency_budget", Role::Trader),
+    ("list_algo_executions", Role::Viewer),
+    ("get_account_reconciliation", Role::Viewer),
+    ("set_trading_enabled", Role::Admin),
+    ("set_strategy_dry_run", Role::Admin),
+    ("get_fat_finger_config", Role::Viewer),
+    ("set_fat_finger_limits", Role::Admin),
+    ("clear_fat_finger_limits", Role::Admin),
+    ("get_liquidity_config", Role::Viewer),
+    ("set_liquidity_config", Role::Admin),
+    ("get_default_quota", Role::Viewer),
+    ("get_quota_usage", Role::Viewer),
+    ("set_strategy_quota", Role::Admin),
+    ("clear_strategy_quota", Role::Admin),
+    ("get_execution_policy", Role::Viewer),
+    ("set_execution_policy", Role::Admin),
+    ("clear_execution_policy", Role::Admin),
+    ("get_exchange_health", Role::Viewer),
+    ("set_degraded_mode_policy", Role::Admin),
+    ("set_strategy_min_feed_quality", Role::Admin),
+    // Data commands
+    ("subscribe_market_data", Role::Viewer),
+    ("get_latest_price", Role::Viewer),
+    ("get_price_cache_stats", Role::Viewer),
+    ("get_candles", Role::Viewer),
+    ("get_candles_resampled", Role::Viewer),
+    ("get_open_interest", Role::Viewer),
+    ("get_taker_volume", Role::Viewer),
+    ("get_long_short_ratio", Role::Viewer),
+    ("verify_data_integrity", Role::Viewer),
+    ("get_orderbook_history", Role::Viewer),
+    ("get_reference_price", Role::Viewer),
+    ("get_data_quality", Role::Viewer),
+    ("search_symbols", Role::Trader),
+    ("sync_symbol_catalog", Role::Trader),
+    // Funding account commands
+    ("set_transfers_enabled", Role::Admin),
+    ("transfer_funds", Role::Admin),
+    ("get_deposit_addresses", Role::Viewer),
+    ("get_withdrawal_history", Role::Viewer),
+    ("get_asset_balances", Role::Viewer),
+    // Risk commands
+    ("get_risk_limits", Role::Viewer),
+    ("update_risk_limits", Role::Admin),
+    ("get_pending_risk_limit_change", Role::Viewer),
+    ("confirm_risk_limit_change", Role::Admin),
+    ("cancel_risk_limit_change", Role::Admin),
+    ("get_risk_limit_audit_log", Role::Viewer),
+    ("get_daily_loss_status", Role::Viewer),
+    ("override_daily_loss_lock", Role::Admin),
+    ("get_drawdown_throttle", Role::Viewer),
+    ("set_drawdown_schedule", Role::Admin),
+    ("clear_drawdown_schedule", Role::Admin),
+    ("calculate_var", Role::Viewer),
+    ("get_stress_scenarios", Role::Viewer),
+    ("get_exposure_breakdown", Role::Viewer),
+    ("run_stress_test", Role::Viewer),
+    ("optimize_strategy_allocation", Role::Trader),
+    // System commands
+    ("get_system_metrics", Role::Viewer),
+    ("get_health_report", Role::Viewer),
+    ("get_task_health", Role::Viewer),
+    ("get_schema_version", Role::Viewer),
+    ("get_alerts", Role::Viewer),
+    ("run_backtest", Role::Trader),
+    ("get_backtest_results", Role::Viewer),
+    ("get_backtest_equity_curve", Role::Viewer),
+    ("list_backtests", Role::Viewer),
+    ("compare_backtests", Role::Viewer),
+    ("get_live_equity_curve", Role::Viewer),
+    ("get_daily_reports", Role::Viewer),
+    ("get_daily_report_html", Role::Viewer),
+    ("export_drop_copy", Role::Viewer),
+    ("take_snapshot", Role::Trader),
+    ("list_snapshots", Role::Viewer),
+    ("restore_snapshot", Role::Admin),
+    // Notification commands
+    ("get_notifications", Role::Viewer),
+    ("mark_notification_read", Role::Viewer),
+    ("get_notification_preferences", Role::Viewer),
+    ("set_notification_preference", Role::Viewer),
+    ("get_quiet_hours", Role::Viewer),
+    ("set_quiet_hours", Role::Viewer),
+    // WebSocket commands
+    ("subscribe_strategy_updates", Role::Viewer),
+    ("unsubscribe_strategy_updates", Role::Viewer),
+    ("subscribe_push", Role::Viewer),
+    ("unsubscribe_push", Role::Viewer),
+    ("get_connected_clients_count", Role::Viewer),
+    ("get_realtime_strategy_stats", Role::Viewer),
+    ("simulate_strategy_signal", Role::Trader),
+    ("simulate_trade_execution", Role::Trader),
+    ("simulate_strategy_error", Role::Trader),
+    ("simulate_position_update", Role::Trader),
+    ("update_strategy_metrics", Role::Trader),
+    ("get_websocket_status", Role::Viewer),
+    ("get_market_data_status", Role::Viewer),
+];
+
+/// What a caller is allowed to do; each role includes the ones below it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// Read data
+    Viewer,
+    /// Also place and cancel orders and run strategies
+    Trader,
+    /// Also run destructive and risk-changing commands
+    Admin,
+}
+
+impl Role {
+    /// Role needed to invoke `command`; commands missing from the table need
+    /// an admin
+    pub fn required_for(command: &str) -> Role {
+        COMMAND_ROLES
+            .iter()
+            .find(|(name, _)| *name == command)
+            .map_or(Role::Admin, |(_, role)| *role)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Viewer => "viewer",
+            Role::Trader => "trader",
+            Role::Admin => "admin",
+        }
+    }
+}
+
+impl std::str::FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "viewer" => Ok(Role::Viewer),
+            "trader" => Ok(Role::Trader),
+            "admin" => Ok(Role::Admin),
+            other => Err(format!("Unknown role '{}'", other)),
+        }
+    }
+}
+
+/// Entry of `users.json`
+#[derive(Debug, Clone, Deserialize)]
+pub struct UserAccount {
+    pub name: String,
+    pub role: Role,
+    pub passcode: PasscodeHash,
+}
+
+/// Identity commands run as
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Caller {
+    pub name: String,
+    pub role: Role,
+}
+
+/// One audited invocation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessAuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub caller: String,
+    pub role: Role,
+    pub command: String,
+    pub allowed: bool,
+}
+
+/// Checks command invocations against the signed-in caller's role
+pub struct AccessControl {
+    users: HashMap<String, UserAccount>,
+    caller: RwLock<Caller>,
+    journal: Option<PathBuf>,
+    entries: Mutex<VecDeque<AccessAuditEntry>>,
+}
+
+impl AccessControl {
+    /// Multi-user access for `users`; single-user as [`DEFAULT_ACTOR`] with
+    /// `default_role` when there are none
+    pub fn new(users: Vec<UserAccount>, default_role: Role) -> Self {
+        let caller = if users.is_empty() {
+            Caller {
+                name: DEFAULT_ACTOR.to_string(),
+                role: default_role,
+            }
+        } else {
+            Caller {
+                name: ANONYMOUS.to_string(),
+                role: Role::Viewer,
+            }
+        };
+        Self {
+            users: users.into_iter().map(|u| (u.name.clone(), u)).collect(),
+            caller: RwLock::new(caller),
+            journal: None,
+            entries: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Append audit entries to the JSONL file at `path`, loading the latest
+    /// of those already there
+    pub fn with_journal(mut self, path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref().to_path_buf();
+        if let Ok(contents) = std::fs::read_to_string(&path) {
+            let mut entries = VecDeque::new();
+            for line in contents.lines().filter(|l| !l.trim().is_empty()) {
+                match serde_json::from_str::<AccessAuditEntry>(line) {
+                    Ok(entry) => {
+                        entries.push_back(entry);
+                        if entries.len() > AUDIT_LIMIT {
+                            entries.pop_front();
+                        }
+                    }
+                    Err(e) => log::warn!("Skipping unreadable access audit entry: {}", e),
+                }
+            }
+            self.entries = Mutex::new(entries);
+        }
+        self.journal = Some(path);
+        self
+    }
+
+    /// Whether callers have to sign in
+    pub fn has_users(&self) -> bool {
+        !self.users.is_empty()
+    }
+
+    pub fn caller(&self) -> Caller {
+        self.caller
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Name to record as the actor of an action; the signed-in user when
+    /// users are configured, whatever the caller claims otherwise
+    pub fn actor(&self, claimed: Option<String>) -> String {
+        if self.has_users() {
+            self.caller().name
+        } else {
+            claimed.unwrap_or_else(|| DEFAULT_ACTOR.to_string())
+        }
+    }
+
+    /// Allow `command` only if the caller's role covers it, auditing admin
+    /// commands and refusals
+    pub fn authorize(&self, command: &str) -> CommandResult<()> {
+        let caller = self.caller();
+        let required = Role::required_for(command);
+        let allowed = caller.role >= required;
+        if !allowed || required == Role::Admin {
+            self.audit(&caller, command, allowed);
+        }
+        if allowed {
+            return Ok(());
+        }
+
+        log::warn!(
+            "Refused {} for {} ({}): requires {}",
+            command,
+            caller.name,
+            caller.role.as_str(),
+            required.as_str()
+        );
+        Err(CommandError::new(
+            ErrorCode::Forbidden,
+            format!(
+                "{} requires the {} role; {} is a {}",
+                command,
+                required.as_str(),
+                caller.name,
+                caller.role.as_str()
+            ),
+        ))
+    }
+
+    /// Start a session as `name`
+    pub fn sign_in(&self, name: &str, passcode: &str) -> CommandResult<Caller> {
+        if !self.has_users() {
+            return Err(CommandError::new(
+                ErrorCode::InvalidState,
+                "No users are configured; the app runs single-user",
+            ));
+        }
+
+        let user = self.users.get(name).filter(|u| u.passcode.verify(passcode));
+        let Some(user) = user else {
+            let attempted = Caller {
+                name: name.to_string(),
+                role: Role::Viewer,
+            };
+            self.audit(&attempted, "sign_in", false);
+            return Err(CommandError::new(
+                ErrorCode::Forbidden,
+                "Unknown user or wrong passcode",
+            ));
+        };
+
+        let caller = Caller {
+            name: user.name.clone(),
+            role: user.role,
+        };
+        *self.caller.write().unwrap_or_else(|e| e.into_inner()) = caller.clone();
+        self.audit(&caller, "sign_in", true);
+        log::info!("{} signed in as {}", caller.name, caller.role.as_str());
+        Ok(caller)
+    }
+
+    /// End the session, falling back to an anonymous viewer
+    pub fn sign_out(&self) -> Caller {
+        if !self.has_users() {
+            return self.caller();
+        }
+        let anonymous = Caller {
+            name: ANONYMOUS.to_string(),
+            role: Role::Viewer,
+        };
+        let previous = std::mem::replace(
+            &mut *self.caller.write().unwrap_or_else(|e| e.into_inner()),
+            anonymous.clone(),
+        );
+        self.audit(&previous, "sign_out", true);
+        anonymous
+    }
+
+    /// Audited invocations, newest first
+    pub fn audit_log(
+        &self,
+        since: Option<DateTime<Utc>>,
+        limit: Option<usize>,
+    ) -> Vec<AccessAuditEntry> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .rev()
+            .filter(|e| since.is_none_or(|since| e.timestamp >= since))
+            .take(limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect()
+    }
+
+    fn audit(&self, caller: &Caller, command: &str, allowed: bool) {
+        let entry = AccessAuditEntry {
+            timestamp: Utc::now(),
+            caller: caller.name.clone(),
+            role: caller.role,
+            command: command.to_string(),
+            allowed,
+        };
+
+        if let Some(path) = &self.journal {
+            let appended = serde_json::to_string(&entry)
+                .map_err(|e| e.to_string())
+                .and_then(|line| {
+                    OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(path)
+                        .and_then(|mut file| writeln!(file, "{}", line))
+                        .map_err(|e| e.to_string())
+                });
+            if let Err(e) = appended {
+                log::error!(
+                    "Failed to append to access audit journal {}: {}",
+                    path.display(),
+                    e
+                );
+            }
+        }
+
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.push_back(entry);
+        if entries.len() > AUDIT_LIMIT {
+            entries.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(name: &str, role: Role, passcode: &str) -> UserAccount {
+        UserAccount {
+            name: name.to_string(),
+            role,
+            passcode: PasscodeHash::derive(passcode, &format!("salt-{}", name), 1000),
+        }
+    }
+
+    fn access() -> AccessControl {
+        AccessControl::new(
+            vec![
+                user("alice", Role::Admin, "open sesame"),
+                user("bob", Role::Trader, "hunter2"),
+            ],
+            Role::Admin,
+        )
+    }
+
+    fn journal_path() -> PathBuf {
+        std::env::temp_dir().join(format!("access_audit_{}.jsonl", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn test_roles_include_the_ones_below() {
+        assert!(Role::Viewer < Role::Trader);
+        assert!(Role::Trader < Role::Admin);
+        assert_eq!("Admin".parse::<Role>(), Ok(Role::Admin));
+        assert!("root".parse::<Role>().is_err());
+    }
+
+    #[test]
+    fn test_required_for() {
+        assert_eq!(Role::required_for("get_strategies"), Role::Viewer);
+        assert_eq!(Role::required_for("sign_in"), Role::Viewer);
+        assert_eq!(Role::required_for("place_order"), Role::Trader);
+        assert_eq!(Role::required_for("cancel_all_orders"), Role::Admin);
+        assert_eq!(Role::required_for("set_strategy_dry_run"), Role::Admin);
+        assert_eq!(Role::required_for("register_signal_source"), Role::Admin);
+        assert_eq!(Role::required_for("set_strategy_baseline"), Role::Admin);
+        // Commands nobody classified are locked down
+        assert_eq!(Role::required_for("get_unlisted_thing"), Role::Admin);
+
+        let mut names: Vec<_> = COMMAND_ROLES.iter().map(|(name, _)| *name).collect();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), COMMAND_ROLES.len(), "command listed twice");
+    }
+
+    #[test]
+    fn test_every_registered_command_has_a_role() {
+        let lib = include_str!("../lib.rs");
+        let start = lib.find("generate_handler![").expect("handler list in lib.rs");
+        let handlers = &lib[start + "generate_handler![".len()..];
+        let handlers = &handlers[..handlers.find(']').unwrap()];
+
+        let commands: Vec<&str> = handlers
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with("//"))
+            .map(|line| line.trim_end_matches(','))
+            .collect();
+        assert!(commands.len() > 100);
+        for command in commands {
+            assert!(
+                COMMAND_ROLES.iter().any(|(name, _)| *name == command),
+                "{} is registered but has no entry in COMMAND_ROLES",
+                command
+            );
+        }
+    }
+
+    #[test]
+    fn test_sign_in_and_out() {
+        let access = access();
+        assert_eq!(
+            access.caller(),
+            Caller {
+                name: ANONYMOUS.to_string(),
+                role: Role::Viewer
+            }
+        );
+        assert!(access.authorize("place_order").is_err());
+
+        let refused = access.sign_in("bob", "wrong").unwrap_err();
+        assert_eq!(refused.code, ErrorCode::Forbidden);
+        assert!(access.sign_in("mallory", "hunter2").is_err());
+
+        let bob = access.sign_in("bob", "hunter2").unwrap();
+        assert_eq!(bob.role, Role::Trader);
+        assert!(access.authorize("place_order").is_ok());
+        assert_eq!(
+            access.authorize("cancel_all_orders").unwrap_err().code,
+            ErrorCode::Forbidden
+        );
+        assert_eq!(access.actor(Some("alice".to_string())), "bob");
+
+        assert_eq!(access.sign_out().role, Role::Viewer);
+        assert_eq!(access.caller().name, ANONYMOUS);
+
+        let single = AccessControl::new(Vec::new(), Role::Trader);
+        assert!(single.sign_in("bob", "hunter2").is_err());
+        assert_eq!(single.sign_out().name, DEFAULT_ACTOR);
+    }
+
+    #[test]
+    fn test_audit_journal() {
+        let path = journal_path();
+        let access = access().with_journal(&path);
+        access.sign_in("bob", "hunter2").unwrap();
+        access.authorize("place_order").unwrap();
+        access.authorize("cancel_all_orders").unwrap_err();
+        access.sign_in("alice", "open sesame").unwrap();
+        access.authorize("cancel_all_orders").unwrap();
+
+        // Routine trader commands are not audited
+        let entries = access.audit_log(None, None);
+        let seen: Vec<_> = entries
+            .iter()
+            .map(|e| (e.caller.as_str(), e.command.as_str(), e.allowed))
+            .collect();
+        assert_eq!(
+            seen,
+            [
+                ("alice", "cancel_all_orders", true),
+                ("alice", "sign_in", true),
+                ("bob", "cancel_all_orders", false),
+                ("bob", "sign_in", true),
+            ]
+        );
+        assert_eq!(access.audit_log(None, Some(1)).len(), 1);
+
+        // A restart picks the journal back up
+        let reopened = self::access().with_journal(&path);
+        assert_eq!(reopened.audit_log(None, None).len(), 4);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
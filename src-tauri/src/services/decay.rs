@@ -0,0 +1,181 @@
+//! Performance decay checks of live strategies
+//!
+//! Feeds the monitoring crate's [`DecayMonitor`]: a strategy's baseline is
+//! taken from its newest recorded backtest when it is started, and its live
+//! performance is rebuilt from the execution engine's closing trades.
+
+use crate::error::{CommandError, CommandResult};
+use chrono::{Duration, NaiveDate, Utc};
+use ea_okx_backtest::{BacktestFilter, BacktestRegistry, BacktestResult};
+use ea_okx_core::models::strategy::{Strategy, StrategyStatus};
+use ea_okx_monitoring::decay::assess;
+use ea_okx_monitoring::{DecayAssessment, DecayMonitor, LivePerformance, PerformanceBaseline};
+use rust_decimal::prelude::ToPrimitive;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use super::{StrategyExecutionEngine, StrategyService};
+
+/// Baseline figures of a finished backtest
+///
+/// The backtest keeps per-trade P&L only as win and loss averages, so the
+/// deviation is that of a two-valued distribution: it leaves out the spread
+/// within wins and within losses and errs towards a narrower band.
+pub fn baseline_from_backtest(strategy_id: Uuid, run_id: &str, result: &BacktestResult) -> PerformanceBaseline {
+    let f = |d: rust_decimal::Decimal| d.to_f64().unwrap_or(0.0);
+    let trades = result.total_trades;
+    let (expectancy, pnl_std) = if trades == 0 {
+        (0.0, 0.0)
+    } else {
+        let n = trades as f64;
+        let (p_win, p_loss) = (result.winning_trades as f64 / n, result.losing_trades as f64 / n);
+        let (avg_win, avg_loss) = (f(result.average_win), f(result.average_loss));
+        let expectancy = (f(result.gross_profit) - f(result.gross_loss)) / n;
+        let second_moment = p_win * avg_win * avg_win + p_loss * avg_loss * avg_loss;
+        (expectancy, (second_moment - expectancy * expectancy).max(0.0).sqrt())
+    };
+
+    PerformanceBaseline {
+        strategy_id,
+        source: run_id.to_string(),
+        recorded_at: Utc::now(),
+        trades,
+        hit_rate: f(result.win_rate),
+        expectancy,
+        pnl_std,
+        // A backtest without returns to measure reports a Sharpe of zero
+        sharpe_ratio: (!result.sharpe_ratio.is_zero()).then(|| f(result.sharpe_ratio)),
+    }
+}
+
+/// Records deployment baselines and checks active strategies against them
+pub struct StrategyDecayWatcher {
+    monitor: Arc<DecayMonitor>,
+    strategies: Arc<StrategyService>,
+    engine: Arc<StrategyExecutionEngine>,
+    backtests: Arc<BacktestRegistry>,
+}
+
+impl StrategyDecayWatcher {
+    pub fn new(
+        monitor: Arc<DecayMonitor>,
+        strategies: Arc<StrategyService>,
+        engine: Arc<StrategyExecutionEngine>,
+        backtests: Arc<BacktestRegistry>,
+    ) -> Self {
+        Self { monitor, strategies, engine, backtests }
+    }
+
+    /// Judge the strategy against backtest `run_id`, or its newest recorded
+    /// backtest; `None` when it has never been backtested
+    pub fn record_baseline(&self, strategy_id: Uuid, run_id: Option<&str>) -> CommandResult<Option<PerformanceBaseline>> {
+        let run_id = match run_id {
+            Some(id) => {
+                let run = self.backtests.run(id)?;
+                if run.provenance.strategy_id != Some(strategy_id) {
+                    return Err(CommandError::validation(format!("Backtest {} is not of strategy {}", id, strategy_id)));
+                }
+                run.id
+            }
+            None => {
+                let filter = BacktestFilter { strategy_id: Some(strategy_id), limit: Some(1), ..Default::default() };
+                match self.backtests.list(&filter)?.into_iter().next() {
+                    Some(run) => run.id,
+                    None => return Ok(None),
+                }
+            }
+        };
+
+        let baseline = baseline_from_backtest(strategy_id, &run_id, &self.backtests.result(&run_id)?);
+        self.monitor.set_baseline(baseline.clone())?;
+        log::info!(
+            "Strategy {} baseline from {}: hit rate {:.3}, expectancy {:.4}, {} trades",
+            strategy_id, run_id, baseline.hit_rate, baseline.expectancy, baseline.trades
+        );
+        Ok(Some(baseline))
+    }
+
+    /// Closing trade P&L and daily returns on allocated capital since the
+    /// strategy was deployed, oldest first
+    pub async fn live_performance(&self, strategy: &Strategy) -> LivePerformance {
+        let mut closes: Vec<_> = self
+            .engine
+            .get_trades(None)
+            .await
+            .into_iter()
+            .filter(|t| t.strategy_id == strategy.id)
+            .filter_map(|t| t.realized_pnl.map(|pnl| (t.executed_at, pnl)))
+            .collect();
+        closes.sort_by_key(|(at, _)| *at);
+
+        let today = Utc::now().date_naive();
+        let first_day = strategy
+            .deployed_at
+            .map(|at| at.date_naive())
+            .or_else(|| closes.first().map(|(at, _)| at.date_naive()))
+            .unwrap_or(today);
+        let mut daily: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+        let mut day = first_day;
+        while day <= today {
+            daily.insert(day, 0.0);
+            day += Duration::days(1);
+        }
+        for (at, pnl) in &closes {
+            *daily.entry(at.date_naive()).or_default() += pnl.to_f64().unwrap_or(0.0);
+        }
+
+        let capital = strategy.config.allocated_capital.to_f64().unwrap_or(0.0);
+        LivePerformance {
+            trade_pnls: closes.iter().map(|(_, pnl)| pnl.to_f64().unwrap_or(0.0)).collect(),
+            daily_returns: if capital > 0.0 { daily.values().map(|pnl| pnl / capital).collect() } else { Vec::new() },
+        }
+    }
+
+    /// Judge one strategy without alerting; `None` without a baseline
+    pub async fn assess(&self, strategy: &Strategy) -> Option<DecayAssessment> {
+        let baseline = self.monitor.baseline(strategy.id)?;
+        let live = self.live_performance(strategy).await;
+        Some(assess(&baseline, &live, self.monitor.config()))
+    }
+
+    /// Judge one strategy, alerting on entering or leaving decay
+    pub async fn check(&self, strategy: &Strategy) -> Option<DecayAssessment> {
+        let live = self.live_performance(strategy).await;
+        self.monitor.check(strategy.id, &strategy.name, &live).await
+    }
+
+    /// Check every active strategy with a baseline
+    pub async fn check_all(&self) -> usize {
+        let strategies = match self.strategies.get_strategies().await {
+            Ok(strategies) => strategies,
+            Err(e) => {
+                log::error!("Performance decay check could not list strategies: {}", e);
+                return 0;
+            }
+        };
+
+        let mut decaying = 0;
+        for strategy in strategies.iter().filter(|s| s.status == StrategyStatus::Active) {
+            if self.check(strategy).await.is_some_and(|a| a.decaying()) {
+                decaying += 1;
+            }
+        }
+        decaying
+    }
+
+    /// Check every `interval` until the task is aborted
+    pub fn start(self: Arc<Self>, interval: std::time::Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let decaying = self.check_all().await;
+                if decaying > 0 {
+                    log::warn!("{} active strategies are underperforming their backtests", decaying);
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,51 @@
+//! Data source for the live equity curve
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use data::{EquitySnapshot, EquitySource, Result};
+use ea_okx_trading::AccountTracker;
+use std::sync::Arc;
+
+use super::StrategyExecutionEngine;
+
+/// Samples equity from the account tracker and exposure from the execution
+/// engine's positions
+pub struct LiveEquitySource {
+    account_tracker: Arc<AccountTracker>,
+    execution_engine: Arc<StrategyExecutionEngine>,
+}
+
+impl LiveEquitySource {
+    pub fn new(
+        account_tracker: Arc<AccountTracker>,
+        execution_engine: Arc<StrategyExecutionEngine>,
+    ) -> Self {
+        Self {
+            account_tracker,
+            execution_engine,
+        }
+    }
+}
+
+#[async_trait]
+impl EquitySource for LiveEquitySource {
+    async fn sample(&self, now: DateTime<Utc>) -> Result<Option<EquitySnapshot>> {
+        // Nothing to chart until the account has been loaded from the exchange
+        let account = self.account_tracker.state();
+        if account.updated_at.is_none() {
+            return Ok(None);
+        }
+
+        let balances = account
+            .balances
+            .values()
+            .map(|b| (b.ccy.clone(), b.equity))
+            .collect();
+        let positions = self.execution_engine.get_positions().await;
+
+        Ok(Some(
+            EquitySnapshot::new(now, account.total_equity, account.margin_ratio, balances)
+                .with_positions(&positions),
+        ))
+    }
+}
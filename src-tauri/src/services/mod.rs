@@ -1,9 +1,23 @@
 //! Services module
 
+pub mod access;
+pub mod decay;
+pub mod equity;
+pub mod notifications;
+pub mod push;
+pub mod reports;
+pub mod snapshots;
 pub mod strategy;
 pub mod strategy_monitor;
 pub mod strategy_execution;
 
+pub use access::AccessControl;
+pub use decay::StrategyDecayWatcher;
+pub use equity::LiveEquitySource;
+pub use notifications::NotificationCenter;
+pub use push::SubscriptionManager;
+pub use reports::TradingReportSource;
+pub use snapshots::EngineSnapshotSource;
 pub use strategy::StrategyService;
 pub use strategy_monitor::StrategyMonitorService;
 pub use strategy_execution::StrategyExecutionEngine;
\ No newline at end of file
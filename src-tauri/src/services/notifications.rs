@@ -0,0 +1,694 @@
+//! Notification center
+//!
+//! Collects alerts, order fills, stop-outs, strategy state changes and system
+//! errors into one persisted history the UI can list and mark read. Each
+//! category is delivered according to the user's preferences: a popup and
+//! sound in the app window, raised by the frontend on the `notification`
+//! event, and an OS notification with its own sound raised here. Muted
+//! categories are still recorded in the history but not pushed.
+//!
+//! During quiet hours only notifications at or above the schedule's level
+//! make a sound or raise an OS notification; the rest still pop up in the
+//! app window, so an overnight stop-out wakes the user but a routine fill
+//! does not.
+
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_notification::NotificationExt;
+use tokio::sync::{RwLock, mpsc};
+use uuid::Uuid;
+
+use super::strategy_monitor::StrategyUpdateEvent;
+use ea_okx_core::models::strategy::StrategyStatus;
+use ea_okx_monitoring::{Alert, AlertSeverity};
+
+/// Tauri event each delivered notification is pushed on
+pub const NOTIFICATION_EVENT: &str = "notification";
+
+/// Notifications kept in the history; older ones are dropped
+const HISTORY_LIMIT: usize = 1000;
+
+/// What a notification is about; preferences are set per category
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationCategory {
+    Alert,
+    OrderFill,
+    /// A position closed by its stop loss
+    StopOut,
+    StrategyState,
+    SystemError,
+}
+
+impl NotificationCategory {
+    pub const ALL: [NotificationCategory; 5] = [
+        NotificationCategory::Alert,
+        NotificationCategory::OrderFill,
+        NotificationCategory::StopOut,
+        NotificationCategory::StrategyState,
+        NotificationCategory::SystemError,
+    ];
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationLevel {
+    Info,
+    Warning,
+    Error,
+    Critical,
+}
+
+impl From<AlertSeverity> for NotificationLevel {
+    fn from(severity: AlertSeverity) -> Self {
+        match severity {
+            AlertSeverity::Info => NotificationLevel::Info,
+            AlertSeverity::Warning => NotificationLevel::Warning,
+            AlertSeverity::Critical | AlertSeverity::Emergency => NotificationLevel::Critical,
+        }
+    }
+}
+
+/// How notifications of one category reach the user
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeliveryPreference {
+    /// Show a popup in the app window
+    pub popup: bool,
+    /// Play a sound in the app window and with the OS notification
+    pub sound: bool,
+    /// Raise a native OS notification
+    pub os_notification: bool,
+    /// Record in the history only, overriding the settings above
+    pub muted: bool,
+    /// Sound played with the OS notification; the system default if unset
+    #[serde(default)]
+    pub sound_name: Option<String>,
+}
+
+impl DeliveryPreference {
+    /// Alerts, stop-outs and system errors interrupt; fills and state
+    /// changes only pop up
+    pub fn default_for(category: NotificationCategory) -> Self {
+        let urgent = matches!(
+            category,
+            NotificationCategory::Alert | NotificationCategory::StopOut | NotificationCategory::SystemError
+        );
+        Self {
+            popup: true,
+            sound: urgent,
+            os_notification: urgent,
+            muted: false,
+            sound_name: None,
+        }
+    }
+
+    /// Channels actually used, with muting and quiet hours applied
+    fn effective(self, quiet: bool) -> Self {
+        if self.muted {
+            Self {
+                popup: false,
+                sound: false,
+                os_notification: false,
+                ..self
+            }
+        } else if quiet {
+            Self {
+                sound: false,
+                os_notification: false,
+                ..self
+            }
+        } else {
+            self
+        }
+    }
+}
+
+/// Daily window in which only urgent notifications make themselves heard
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuietHours {
+    /// Local time the window opens
+    pub start: NaiveTime,
+    /// Local time the window closes; before `start` for a window spanning
+    /// midnight
+    pub end: NaiveTime,
+    /// Offset of the user's local time from UTC
+    #[serde(default)]
+    pub utc_offset_minutes: i32,
+    /// Notifications at or above this level still sound and raise OS
+    /// notifications
+    #[serde(default = "QuietHours::default_level")]
+    pub min_level: NotificationLevel,
+}
+
+impl QuietHours {
+    fn default_level() -> NotificationLevel {
+        NotificationLevel::Error
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.start == self.end {
+            return Err("Quiet hours must start and end at different times".to_string());
+        }
+        if self.utc_offset_minutes.abs() > 14 * 60 {
+            return Err(format!("UTC offset out of range: {} minutes", self.utc_offset_minutes));
+        }
+        Ok(())
+    }
+
+    /// Whether `at` falls in the window
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        let local = (at + Duration::minutes(i64::from(self.utc_offset_minutes))).time();
+        if self.start < self.end {
+            self.start <= local && local < self.end
+        } else {
+            local >= self.start || local < self.end
+        }
+    }
+
+    /// Whether a notification of `level` is quieted at `at`
+    fn silences(&self, level: NotificationLevel, at: DateTime<Utc>) -> bool {
+        level < self.min_level && self.contains(at)
+    }
+}
+
+/// One entry of the notification history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notification {
+    pub id: Uuid,
+    pub category: NotificationCategory,
+    pub level: NotificationLevel,
+    pub title: String,
+    pub message: String,
+    pub created_at: DateTime<Utc>,
+    pub read: bool,
+    pub read_at: Option<DateTime<Utc>>,
+    /// Channels it was delivered through
+    pub delivery: DeliveryPreference,
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+}
+
+impl Notification {
+    pub fn new(
+        category: NotificationCategory,
+        level: NotificationLevel,
+        title: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            category,
+            level,
+            title: title.into(),
+            message: message.into(),
+            created_at: Utc::now(),
+            read: false,
+            read_at: None,
+            delivery: DeliveryPreference::default_for(category),
+            metadata: HashMap::new(),
+        }
+    }
+
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn from_alert(alert: &Alert) -> Self {
+        let mut notification = Self::new(
+            NotificationCategory::Alert,
+            alert.severity.into(),
+            alert.rule_name.clone(),
+            alert.message.clone(),
+        )
+        .with_metadata("alert_id", alert.id.to_string());
+        notification
+            .metadata
+            .extend(alert.metadata.iter().map(|(k, v)| (k.clone(), v.clone())));
+        notification
+    }
+
+    /// Notification for a strategy monitor event; `None` for events too
+    /// frequent to notify about (metrics, signals, position updates)
+    pub fn from_strategy_event(event: &StrategyUpdateEvent) -> Option<Self> {
+        let notification = match event {
+            StrategyUpdateEvent::StatusChanged {
+                strategy_id,
+                old_status,
+                new_status,
+                ..
+            } => {
+                let level = match new_status {
+                    StrategyStatus::Error => NotificationLevel::Error,
+                    _ => NotificationLevel::Info,
+                };
+                Self::new(
+                    NotificationCategory::StrategyState,
+                    level,
+                    format!("Strategy {:?}", new_status),
+                    format!("Strategy {} changed from {:?} to {:?}", strategy_id, old_status, new_status),
+                )
+                .with_metadata("strategy_id", strategy_id.clone())
+            }
+            StrategyUpdateEvent::TradeExecuted {
+                strategy_id,
+                trade_id,
+                symbol,
+                side,
+                amount,
+                price,
+                stop_out: true,
+                ..
+            } => Self::new(
+                NotificationCategory::StopOut,
+                NotificationLevel::Error,
+                format!("Stopped out of {}", symbol),
+                format!("Stop loss {} {} {} at {}", side, amount, symbol, price),
+            )
+            .with_metadata("strategy_id", strategy_id.clone())
+            .with_metadata("trade_id", trade_id.clone()),
+            StrategyUpdateEvent::TradeExecuted {
+                strategy_id,
+                trade_id,
+                symbol,
+                side,
+                amount,
+                price,
+                ..
+            } => Self::new(
+                NotificationCategory::OrderFill,
+                NotificationLevel::Info,
+                format!("{} {} filled", side, symbol),
+                format!("{} {} {} at {}", side, amount, symbol, price),
+            )
+            .with_metadata("strategy_id", strategy_id.clone())
+            .with_metadata("trade_id", trade_id.clone()),
+            StrategyUpdateEvent::Error {
+                strategy_id,
+                error_message,
+                ..
+            } => Self::new(
+                NotificationCategory::SystemError,
+                NotificationLevel::Error,
+                "Strategy error",
+                format!("Strategy {}: {}", strategy_id, error_message),
+            )
+            .with_metadata("strategy_id", strategy_id.clone()),
+            StrategyUpdateEvent::MetricsUpdated { .. }
+            | StrategyUpdateEvent::SignalGenerated { .. }
+            | StrategyUpdateEvent::PositionUpdate { .. } => return None,
+        };
+        Some(notification)
+    }
+}
+
+/// Filter for listing the history
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct NotificationQuery {
+    pub category: Option<NotificationCategory>,
+    #[serde(default)]
+    pub unread_only: bool,
+    pub limit: Option<usize>,
+}
+
+/// Routes notifications by category preference and keeps their history
+///
+/// With a directory, the history, preferences and quiet hours are persisted
+/// there as JSON and reloaded on startup.
+pub struct NotificationCenter {
+    dir: Option<PathBuf>,
+    app: OnceLock<AppHandle>,
+    preferences: RwLock<HashMap<NotificationCategory, DeliveryPreference>>,
+    quiet_hours: RwLock<Option<QuietHours>>,
+    history: RwLock<VecDeque<Notification>>,
+}
+
+impl NotificationCenter {
+    pub fn in_memory() -> Self {
+        Self {
+            dir: None,
+            app: OnceLock::new(),
+            preferences: RwLock::new(HashMap::new()),
+            quiet_hours: RwLock::new(None),
+            history: RwLock::new(VecDeque::new()),
+        }
+    }
+
+    /// Center persisted in `dir`, falling back to in-memory if it cannot be created
+    pub fn open(dir: PathBuf) -> Self {
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            log::error!("Falling back to in-memory notification history: {}", e);
+            return Self::in_memory();
+        }
+
+        let history: VecDeque<Notification> = read_json(&dir.join("history.json")).unwrap_or_default();
+        let preferences = read_json(&dir.join("preferences.json")).unwrap_or_default();
+        let quiet_hours = read_json(&dir.join("quiet_hours.json")).unwrap_or_default();
+        Self {
+            dir: Some(dir),
+            app: OnceLock::new(),
+            preferences: RwLock::new(preferences),
+            quiet_hours: RwLock::new(quiet_hours),
+            history: RwLock::new(history),
+        }
+    }
+
+    /// Deliver to the app window and OS from now on; notifications raised
+    /// before are only recorded
+    pub fn attach(&self, app: AppHandle) {
+        let _ = self.app.set(app);
+    }
+
+    /// Preference for every category, defaults filled in
+    pub async fn preferences(&self) -> HashMap<NotificationCategory, DeliveryPreference> {
+        let preferences = self.preferences.read().await;
+        NotificationCategory::ALL
+            .into_iter()
+            .map(|category| {
+                let preference = preferences
+                    .get(&category)
+                    .cloned()
+                    .unwrap_or_else(|| DeliveryPreference::default_for(category));
+                (category, preference)
+            })
+            .collect()
+    }
+
+    pub async fn set_preference(
+        &self,
+        category: NotificationCategory,
+        preference: DeliveryPreference,
+    ) -> std::io::Result<()> {
+        let mut preferences = self.preferences.write().await;
+        preferences.insert(category, preference);
+        self.save("preferences.json", &*preferences).await
+    }
+
+    pub async fn quiet_hours(&self) -> Option<QuietHours> {
+        *self.quiet_hours.read().await
+    }
+
+    /// Set the quiet hours schedule; `None` turns it off
+    pub async fn set_quiet_hours(&self, schedule: Option<QuietHours>) -> std::io::Result<()> {
+        let mut quiet_hours = self.quiet_hours.write().await;
+        *quiet_hours = schedule;
+        self.save("quiet_hours.json", &*quiet_hours).await
+    }
+
+    /// Record `notification` and deliver it per its category's preference
+    pub async fn notify(&self, mut notification: Notification) -> Notification {
+        let preference = self
+            .preferences
+            .read()
+            .await
+            .get(&notification.category)
+            .cloned()
+            .unwrap_or_else(|| DeliveryPreference::default_for(notification.category));
+        let quiet = self
+            .quiet_hours
+            .read()
+            .await
+            .is_some_and(|q| q.silences(notification.level, notification.created_at));
+        notification.delivery = preference.effective(quiet);
+
+        {
+            let mut history = self.history.write().await;
+            history.push_back(notification.clone());
+            while history.len() > HISTORY_LIMIT {
+                history.pop_front();
+            }
+            if let Err(e) = self.save("history.json", &*history).await {
+                log::error!("Failed to persist notification history: {}", e);
+            }
+        }
+
+        if !notification.delivery.muted && let Some(app) = self.app.get() {
+            self.deliver(app, &notification);
+        }
+        notification
+    }
+
+    /// Shorthand for a system error notification
+    pub async fn system_error(&self, title: impl Into<String>, message: impl Into<String>) {
+        self.notify(Notification::new(
+            NotificationCategory::SystemError,
+            NotificationLevel::Error,
+            title,
+            message,
+        ))
+        .await;
+    }
+
+    /// History matching `query`, newest first
+    pub async fn list(&self, query: &NotificationQuery) -> Vec<Notification> {
+        self.history
+            .read()
+            .await
+            .iter()
+            .rev()
+            .filter(|n| query.category.is_none_or(|category| n.category == category))
+            .filter(|n| !query.unread_only || !n.read)
+            .take(query.limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect()
+    }
+
+    /// Mark one notification read; `None` if it is not in the history
+    pub async fn mark_read(&self, id: Uuid) -> std::io::Result<Option<Notification>> {
+        let mut history = self.history.write().await;
+        let Some(notification) = history.iter_mut().find(|n| n.id == id) else {
+            return Ok(None);
+        };
+        if !notification.read {
+            notification.read = true;
+            notification.read_at = Some(Utc::now());
+        }
+        let notification = notification.clone();
+        self.save("history.json", &*history).await?;
+        Ok(Some(notification))
+    }
+
+    /// Notify about every alert and strategy event received until both
+    /// streams close
+    pub fn spawn_forwarding(
+        self: Arc<Self>,
+        alerts: Option<mpsc::UnboundedReceiver<Alert>>,
+        strategy_events: Option<mpsc::UnboundedReceiver<StrategyUpdateEvent>>,
+    ) {
+        if let Some(mut alerts) = alerts {
+            let center = self.clone();
+            tokio::spawn(async move {
+                while let Some(alert) = alerts.recv().await {
+                    center.notify(Notification::from_alert(&alert)).await;
+                }
+            });
+        }
+        if let Some(mut events) = strategy_events {
+            tokio::spawn(async move {
+                while let Some(event) = events.recv().await {
+                    if let Some(notification) = Notification::from_strategy_event(&event) {
+                        self.notify(notification).await;
+                    }
+                }
+            });
+        }
+    }
+
+    fn deliver(&self, app: &AppHandle, notification: &Notification) {
+        // The frontend shows the popup and plays the sound as configured
+        if let Err(e) = app.emit(NOTIFICATION_EVENT, notification) {
+            log::error!("Failed to push notification: {}", e);
+        }
+
+        if notification.delivery.os_notification {
+            let mut builder = app
+                .notification()
+                .builder()
+                .title(&notification.title)
+                .body(&notification.message);
+            if notification.delivery.sound {
+                builder = builder.sound(notification.delivery.sound_name.as_deref().unwrap_or("default"));
+            }
+            if let Err(e) = builder.show() {
+                log::warn!("Failed to show OS notification: {}", e);
+            }
+        }
+    }
+
+    /// Persist `value` as `file`
+    ///
+    /// Callers hold the lock guarding `value` until the write finishes, so
+    /// writes of one file land in order; the file I/O itself runs on the
+    /// blocking pool.
+    async fn save<T: Serialize>(&self, file: &str, value: &T) -> std::io::Result<()> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        let json = serde_json::to_vec_pretty(value)?;
+        let path = dir.join(file);
+        tokio::task::spawn_blocking(move || {
+            // Write then rename so a crash never leaves a truncated file
+            let tmp = path.with_extension("json.tmp");
+            std::fs::write(&tmp, json)?;
+            std::fs::rename(tmp, path)
+        })
+        .await
+        .map_err(std::io::Error::other)?
+    }
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> Option<T> {
+    let contents = std::fs::read(path).ok()?;
+    match serde_json::from_slice(&contents) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            log::error!("Ignoring unreadable {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 14, hour, minute, 0).unwrap()
+    }
+
+    fn quiet(start: (u32, u32), end: (u32, u32)) -> QuietHours {
+        QuietHours {
+            start: NaiveTime::from_hms_opt(start.0, start.1, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(end.0, end.1, 0).unwrap(),
+            utc_offset_minutes: 0,
+            min_level: NotificationLevel::Error,
+        }
+    }
+
+    #[test]
+    fn test_quiet_hours_within_a_day() {
+        let lunch = quiet((12, 0), (13, 30));
+        assert!(!lunch.contains(at(11, 59)));
+        // Opens at the start and closes at the end
+        assert!(lunch.contains(at(12, 0)));
+        assert!(lunch.contains(at(13, 29)));
+        assert!(!lunch.contains(at(13, 30)));
+    }
+
+    #[test]
+    fn test_quiet_hours_spanning_midnight() {
+        let night = quiet((22, 0), (7, 0));
+        assert!(!night.contains(at(21, 59)));
+        assert!(night.contains(at(22, 0)));
+        assert!(night.contains(at(23, 59)));
+        assert!(night.contains(at(0, 0)));
+        assert!(night.contains(at(6, 59)));
+        assert!(!night.contains(at(7, 0)));
+        assert!(!night.contains(at(12, 0)));
+    }
+
+    #[test]
+    fn test_quiet_hours_in_local_time() {
+        // 22:00-07:00 in UTC+8 is 14:00-23:00 UTC
+        let night = QuietHours {
+            utc_offset_minutes: 8 * 60,
+            ..quiet((22, 0), (7, 0))
+        };
+        assert!(!night.contains(at(13, 59)));
+        assert!(night.contains(at(14, 0)));
+        assert!(night.contains(at(22, 59)));
+        assert!(!night.contains(at(23, 0)));
+
+        assert!(night.silences(NotificationLevel::Warning, at(15, 0)));
+        assert!(!night.silences(NotificationLevel::Error, at(15, 0)));
+        assert!(!night.silences(NotificationLevel::Info, at(12, 0)));
+    }
+
+    #[test]
+    fn test_quiet_hours_validation() {
+        assert!(quiet((22, 0), (7, 0)).validate().is_ok());
+        assert!(quiet((7, 0), (7, 0)).validate().is_err());
+        let far = QuietHours {
+            utc_offset_minutes: 15 * 60,
+            ..quiet((22, 0), (7, 0))
+        };
+        assert!(far.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_muted_categories_are_recorded_but_not_delivered() {
+        let center = NotificationCenter::in_memory();
+        let muted = DeliveryPreference {
+            muted: true,
+            ..DeliveryPreference::default_for(NotificationCategory::OrderFill)
+        };
+        center.set_preference(NotificationCategory::OrderFill, muted).await.unwrap();
+
+        let fill = center
+            .notify(Notification::new(NotificationCategory::OrderFill, NotificationLevel::Info, "Buy filled", ""))
+            .await;
+        assert!(fill.delivery.muted);
+        assert!(!fill.delivery.popup && !fill.delivery.sound && !fill.delivery.os_notification);
+
+        let error = center
+            .notify(Notification::new(NotificationCategory::SystemError, NotificationLevel::Error, "Down", ""))
+            .await;
+        assert!(!error.delivery.muted);
+        assert!(error.delivery.popup && error.delivery.sound && error.delivery.os_notification);
+
+        assert_eq!(center.list(&NotificationQuery::default()).await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_quiet_hours_only_pop_up_routine_notifications() {
+        let center = NotificationCenter::in_memory();
+        // Quiet around the clock but for the last minute of the day
+        center.set_quiet_hours(Some(quiet((0, 0), (23, 59)))).await.unwrap();
+
+        let mut alert = Notification::new(NotificationCategory::Alert, NotificationLevel::Warning, "Spread", "");
+        alert.created_at = at(3, 0);
+        let alert = center.notify(alert).await;
+        assert!(alert.delivery.popup);
+        assert!(!alert.delivery.sound && !alert.delivery.os_notification);
+
+        let mut stop_out = Notification::new(NotificationCategory::StopOut, NotificationLevel::Error, "Stopped out", "");
+        stop_out.created_at = at(3, 0);
+        let stop_out = center.notify(stop_out).await;
+        assert!(stop_out.delivery.sound && stop_out.delivery.os_notification);
+    }
+
+    #[tokio::test]
+    async fn test_history_filters_and_persists() {
+        let dir = std::env::temp_dir().join(format!("notifications_{}", Uuid::new_v4()));
+        let center = NotificationCenter::open(dir.clone());
+        let fill = center
+            .notify(Notification::new(NotificationCategory::OrderFill, NotificationLevel::Info, "Buy filled", ""))
+            .await;
+        center
+            .notify(Notification::new(NotificationCategory::Alert, NotificationLevel::Warning, "Spread", ""))
+            .await;
+        center.mark_read(fill.id).await.unwrap().unwrap();
+
+        let fills = NotificationQuery {
+            category: Some(NotificationCategory::OrderFill),
+            ..Default::default()
+        };
+        let unread = NotificationQuery {
+            unread_only: true,
+            ..Default::default()
+        };
+        assert_eq!(center.list(&fills).await.len(), 1);
+        assert_eq!(center.list(&unread).await[0].title, "Spread");
+
+        let reopened = NotificationCenter::open(dir.clone());
+        let history = reopened.list(&NotificationQuery::default()).await;
+        assert_eq!(history.len(), 2);
+        assert!(history.iter().any(|n| n.id == fill.id && n.read));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
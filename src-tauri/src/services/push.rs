@@ -0,0 +1,226 @@
+//! Push subscriptions for the frontend
+//!
+//! Replaces UI polling of positions, orders and system metrics. A single
+//! background loop samples backend state, diffs it against what was last
+//! pushed and emits only the changes as Tauri events. Every message carries a
+//! per-topic sequence number; a client that sees a gap re-subscribes to get a
+//! fresh snapshot.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Mutex;
+
+use super::StrategyExecutionEngine;
+use crate::commands::system::sample_system_metrics;
+use ea_okx_core::models::order::Order;
+
+/// Data stream the frontend can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PushTopic {
+    Positions,
+    Orders,
+    Metrics,
+}
+
+impl PushTopic {
+    pub const ALL: [PushTopic; 3] = [PushTopic::Positions, PushTopic::Orders, PushTopic::Metrics];
+
+    /// Tauri event the topic is pushed on
+    pub fn event_name(&self) -> &'static str {
+        match self {
+            PushTopic::Positions => "positions:update",
+            PushTopic::Orders => "orders:update",
+            PushTopic::Metrics => "metrics:update",
+        }
+    }
+}
+
+/// Whether a message replaces the client's state or patches it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PushKind {
+    Snapshot,
+    Delta,
+}
+
+/// Message pushed to (or returned on subscribe to) the frontend
+///
+/// A snapshot lists every item in `upserts`; a delta lists only items that
+/// changed plus the keys of items that went away. A delta's `seq` is always
+/// one more than the previous message on the same topic.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PushMessage {
+    pub topic: PushTopic,
+    pub seq: u64,
+    pub kind: PushKind,
+    pub upserts: Vec<JsonValue>,
+    pub removed: Vec<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Default)]
+struct TopicState {
+    seq: u64,
+    subscribers: usize,
+    last: HashMap<String, JsonValue>,
+}
+
+/// Tracks subscribers per topic and pushes changes to them
+pub struct SubscriptionManager {
+    engine: Arc<StrategyExecutionEngine>,
+    topics: Mutex<HashMap<PushTopic, TopicState>>,
+}
+
+impl SubscriptionManager {
+    pub fn new(engine: Arc<StrategyExecutionEngine>) -> Self {
+        Self {
+            engine,
+            topics: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Register a subscriber and return the current snapshot
+    ///
+    /// Changes not yet pushed to existing subscribers are flushed first so
+    /// the snapshot's `seq` is consistent for everyone.
+    pub async fn subscribe(&self, app: &AppHandle, topic: PushTopic) -> PushMessage {
+        let current = self.sample(topic).await;
+        let mut topics = self.topics.lock().await;
+        let state = topics.entry(topic).or_default();
+
+        if state.subscribers > 0 {
+            Self::push_changes(app, topic, state, current);
+        } else {
+            state.last = current;
+        }
+        state.subscribers += 1;
+
+        PushMessage {
+            topic,
+            seq: state.seq,
+            kind: PushKind::Snapshot,
+            upserts: state.last.values().cloned().collect(),
+            removed: Vec::new(),
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// Drop a subscriber; topics without subscribers are not sampled
+    pub async fn unsubscribe(&self, topic: PushTopic) {
+        if let Some(state) = self.topics.lock().await.get_mut(&topic) {
+            state.subscribers = state.subscribers.saturating_sub(1);
+        }
+    }
+
+    /// Sample every subscribed topic and push what changed
+    pub async fn publish(&self, app: &AppHandle) {
+        for topic in PushTopic::ALL {
+            let subscribed = self
+                .topics
+                .lock()
+                .await
+                .get(&topic)
+                .is_some_and(|state| state.subscribers > 0);
+            if !subscribed {
+                continue;
+            }
+
+            let current = self.sample(topic).await;
+            if let Some(state) = self.topics.lock().await.get_mut(&topic) {
+                Self::push_changes(app, topic, state, current);
+            }
+        }
+    }
+
+    /// Publish every `interval` until the task is aborted
+    pub fn spawn(
+        self: Arc<Self>,
+        app: AppHandle,
+        interval: Duration,
+    ) -> tauri::async_runtime::JoinHandle<()> {
+        tauri::async_runtime::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                self.publish(&app).await;
+            }
+        })
+    }
+
+    /// Current state of a topic keyed by item ID
+    async fn sample(&self, topic: PushTopic) -> HashMap<String, JsonValue> {
+        match topic {
+            PushTopic::Positions => keyed(self.engine.get_positions().await, |p| p.id.to_string()),
+            PushTopic::Orders => keyed(
+                self.engine
+                    .get_orders()
+                    .await
+                    .into_iter()
+                    .filter(|o| o.is_active())
+                    .collect::<Vec<_>>(),
+                |o: &Order| o.id.to_string(),
+            ),
+            PushTopic::Metrics => keyed(vec![sample_system_metrics()], |_| "system".to_string()),
+        }
+    }
+
+    /// Diff `current` against the last pushed state and emit a delta if anything changed
+    fn push_changes(
+        app: &AppHandle,
+        topic: PushTopic,
+        state: &mut TopicState,
+        current: HashMap<String, JsonValue>,
+    ) {
+        let upserts: Vec<JsonValue> = current
+            .iter()
+            .filter(|(key, value)| state.last.get(*key) != Some(*value))
+            .map(|(_, value)| value.clone())
+            .collect();
+        let removed: Vec<String> = state
+            .last
+            .keys()
+            .filter(|key| !current.contains_key(*key))
+            .cloned()
+            .collect();
+
+        state.last = current;
+        if upserts.is_empty() && removed.is_empty() {
+            return;
+        }
+
+        state.seq += 1;
+        let message = PushMessage {
+            topic,
+            seq: state.seq,
+            kind: PushKind::Delta,
+            upserts,
+            removed,
+            timestamp: Utc::now(),
+        };
+        if let Err(e) = app.emit(topic.event_name(), &message) {
+            log::error!("Failed to push {}: {}", topic.event_name(), e);
+        }
+    }
+}
+
+fn keyed<T: Serialize>(items: Vec<T>, key: impl Fn(&T) -> String) -> HashMap<String, JsonValue> {
+    items
+        .into_iter()
+        .filter_map(|item| {
+            let id = key(&item);
+            match serde_json::to_value(&item) {
+                Ok(value) => Some((id, value)),
+                Err(e) => {
+                    log::error!("Failed to serialize pushed item {}: {}", id, e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,57 @@
+//! Data source for the daily performance reports
+
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use ea_okx_monitoring::reports::{ReportDataSource, ReportInput};
+use ea_okx_monitoring::{Error, Result};
+use std::sync::Arc;
+
+use super::{StrategyExecutionEngine, StrategyService};
+
+/// Feeds the daily reporter from the execution engine's trade log
+pub struct TradingReportSource {
+    strategy_service: Arc<StrategyService>,
+    execution_engine: Arc<StrategyExecutionEngine>,
+}
+
+impl TradingReportSource {
+    pub fn new(
+        strategy_service: Arc<StrategyService>,
+        execution_engine: Arc<StrategyExecutionEngine>,
+    ) -> Self {
+        Self {
+            strategy_service,
+            execution_engine,
+        }
+    }
+}
+
+#[async_trait]
+impl ReportDataSource for TradingReportSource {
+    async fn collect(&self, date: NaiveDate) -> Result<ReportInput> {
+        let trades = self
+            .execution_engine
+            .get_trades(None)
+            .await
+            .into_iter()
+            .filter(|t| t.executed_at.date_naive() == date)
+            .collect();
+
+        let strategy_names = self
+            .strategy_service
+            .get_strategies()
+            .await
+            .map_err(|e| Error::ReportError(e.to_string()))?
+            .into_iter()
+            .map(|s| (s.id, s.name))
+            .collect();
+
+        // Risk validators are not hosted in the desktop app yet, so there are
+        // no recorded limit breaches to include.
+        Ok(ReportInput {
+            trades,
+            strategy_names,
+            breaches: Vec::new(),
+        })
+    }
+}
@@ -0,0 +1,43 @@
+//! Data source for engine state snapshots
+
+use async_trait::async_trait;
+use ea_okx_trading::{AlgoExecutionStore, EngineSnapshot, Result, SnapshotDataSource};
+use std::sync::Arc;
+
+use super::{StrategyExecutionEngine, StrategyService};
+
+/// Captures orders, positions, trades, strategies and algo executions
+pub struct EngineSnapshotSource {
+    strategy_service: Arc<StrategyService>,
+    execution_engine: Arc<StrategyExecutionEngine>,
+    algo_store: Arc<dyn AlgoExecutionStore>,
+}
+
+impl EngineSnapshotSource {
+    pub fn new(
+        strategy_service: Arc<StrategyService>,
+        execution_engine: Arc<StrategyExecutionEngine>,
+        algo_store: Arc<dyn AlgoExecutionStore>,
+    ) -> Self {
+        Self {
+            strategy_service,
+            execution_engine,
+            algo_store,
+        }
+    }
+}
+
+#[async_trait]
+impl SnapshotDataSource for EngineSnapshotSource {
+    async fn capture(&self) -> Result<EngineSnapshot> {
+        let mut snapshot = EngineSnapshot::new();
+        snapshot.orders = self.execution_engine.get_orders().await;
+        snapshot.positions = self.execution_engine.get_positions().await;
+        // `get_trades` is newest first; keep the log in execution order
+        snapshot.trades = self.execution_engine.get_trades(None).await;
+        snapshot.trades.reverse();
+        snapshot.strategies = self.strategy_service.snapshot_strategies().await;
+        snapshot.algo_executions = self.algo_store.load_all()?;
+        Ok(snapshot)
+    }
+}
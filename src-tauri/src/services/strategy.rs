@@ -12,12 +12,40 @@ use ea_okx_core::{
     error::{Error, Result},
     models::strategy::{Strategy, StrategyConfig, StrategyStatus},
 };
+use data::{InMemoryStrategyRepository, StrategyRepository, StrategyStatusChange};
+use ea_okx_strategy::{BacktestEvidence, ParameterUpdate, StrategyBundle};
+use ea_okx_trading::StrategySnapshot;
+
+/// A running strategy implementation that can receive live parameter updates
+pub type StrategyInstance = ea_okx_strategy::LiveStrategy;
+
+/// Rejects scripted strategies whose script is missing or does not compile,
+/// and DCA strategies whose schedule or rules do not parse
+fn check_parameters(strategy_type: &str, parameters: &JsonValue) -> Result<()> {
+    if strategy_type == ea_okx_strategy::DCA_STRATEGY_TYPE {
+        let parameters = serde_json::from_value(parameters.clone())
+            .map_err(|e| Error::ValidationError(format!("Invalid DCA parameters: {}", e)))?;
+        return ea_okx_strategy::DcaStrategy::check(&parameters)
+            .map_err(|e| Error::ValidationError(e.to_string()));
+    }
+    if strategy_type != ea_okx_strategy::SCRIPT_STRATEGY_TYPE {
+        return Ok(());
+    }
+    let source = parameters
+        .get(ea_okx_strategy::SCRIPT_PARAMETER)
+        .and_then(JsonValue::as_str)
+        .ok_or_else(|| Error::ValidationError("Scripted strategy has no script".to_string()))?;
+    ea_okx_strategy::ScriptStrategy::check(source)
+        .map_err(|e| Error::ValidationError(e.to_string()))
+}
 
 /// Strategy service for managing trading strategies
 #[derive(Clone)]
 pub struct StrategyService {
     strategies: Arc<RwLock<HashMap<String, Strategy>>>,
     monitor: Option<Arc<super::StrategyMonitorService>>,
+    repository: Arc<dyn StrategyRepository>,
+    instances: Arc<RwLock<HashMap<String, StrategyInstance>>>,
 }
 
 impl StrategyService {
@@ -26,6 +54,8 @@ impl StrategyService {
         Self {
             strategies: Arc::new(RwLock::new(HashMap::new())),
             monitor: None,
+            repository: Arc::new(InMemoryStrategyRepository::new()),
+            instances: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -34,7 +64,146 @@ impl StrategyService {
         Self {
             strategies: Arc::new(RwLock::new(HashMap::new())),
             monitor: Some(monitor),
+            repository: Arc::new(InMemoryStrategyRepository::new()),
+            instances: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Persists strategies and their status history through the given repository
+    pub fn with_repository(mut self, repository: Arc<dyn StrategyRepository>) -> Self {
+        self.repository = repository;
+        self
+    }
+
+    /// The strategies themselves, shared with components that judge signals
+    /// by strategy status
+    pub fn shared_strategies(&self) -> Arc<RwLock<HashMap<String, Strategy>>> {
+        self.strategies.clone()
+    }
+
+    /// Registers the running implementation of a strategy so hot-tunable
+    /// parameter changes reach it without a restart
+    pub async fn attach_instance(&self, id: &str, instance: StrategyInstance) {
+        self.instances.write().await.insert(id.to_string(), instance);
+    }
+
+    /// Forgets the running implementation of a strategy
+    pub async fn detach_instance(&self, id: &str) -> Option<StrategyInstance> {
+        self.instances.write().await.remove(id)
+    }
+
+    /// Allocations and implementation states of every strategy
+    pub async fn snapshot_strategies(&self) -> Vec<StrategySnapshot> {
+        let strategies = self.strategies.read().await.clone();
+        let instances = self.instances.read().await.clone();
+
+        let mut snapshots = Vec::with_capacity(strategies.len());
+        for (id, strategy) in strategies {
+            let state = match instances.get(&id) {
+                Some(instance) => instance.serialize_state().await.unwrap_or_else(|e| {
+                    log::warn!("Failed to serialize state of strategy {}: {}", id, e);
+                    None
+                }),
+                None => None,
+            };
+            snapshots.push(StrategySnapshot {
+                strategy_id: strategy.id,
+                name: strategy.name,
+                status: strategy.status,
+                allocated_capital: strategy.config.allocated_capital,
+                state: state.unwrap_or(JsonValue::Null),
+            });
         }
+        snapshots
+    }
+
+    /// Restores allocations and implementation states from a snapshot
+    ///
+    /// Statuses are not restored: a strategy only runs again once it is
+    /// explicitly started. Strategies missing from the snapshot are left as
+    /// they are.
+    pub async fn restore_strategies(&self, snapshots: &[StrategySnapshot]) -> Result<usize> {
+        let mut restored = 0;
+        for snapshot in snapshots {
+            let id = snapshot.strategy_id.to_string();
+            let updated = {
+                let mut strategies = self.strategies.write().await;
+                let Some(strategy) = strategies.get_mut(&id) else {
+                    log::warn!("Snapshot strategy {} ({}) no longer exists", snapshot.name, id);
+                    continue;
+                };
+                strategy.config.allocated_capital = snapshot.allocated_capital;
+                strategy.updated_at = Utc::now();
+                strategy.clone()
+            };
+            self.persist(&updated).await?;
+
+            let instance = self.instances.read().await.get(&id).cloned();
+            if !snapshot.state.is_null() && let Some(instance) = instance {
+                instance
+                    .deserialize_state(snapshot.state.clone())
+                    .await
+                    .map_err(|e| Error::Internal(format!("Failed to restore state of strategy {}: {}", id, e)))?;
+            }
+            restored += 1;
+        }
+        Ok(restored)
+    }
+
+    /// Loads persisted strategies into memory, returning how many were loaded
+    pub async fn load_strategies(&self) -> Result<usize> {
+        let stored = self.repository.load_all().await.map_err(|e| {
+            Error::Internal(format!("Failed to load strategies: {}", e))
+        })?;
+
+        let count = stored.len();
+        let mut strategies = self.strategies.write().await;
+        for strategy in stored {
+            if let Some(monitor) = &self.monitor {
+                let _ = monitor.update_strategy(strategy.clone()).await;
+            }
+            strategies.insert(strategy.id.to_string(), strategy);
+        }
+
+        log::info!("Loaded {} persisted strategies", count);
+        Ok(count)
+    }
+
+    /// Saves a strategy snapshot to the repository
+    async fn persist(&self, strategy: &Strategy) -> Result<()> {
+        self.repository.save(strategy).await.map_err(|e| {
+            Error::Internal(format!("Failed to persist strategy {}: {}", strategy.id, e))
+        })
+    }
+
+    /// Appends a status transition to the strategy's history
+    async fn record_status_change(
+        &self,
+        strategy: &Strategy,
+        from_status: Option<StrategyStatus>,
+        reason: Option<&str>,
+    ) {
+        if from_status == Some(strategy.status) {
+            return;
+        }
+
+        let change = StrategyStatusChange::new(
+            strategy.id,
+            from_status,
+            strategy.status,
+            reason.map(str::to_string),
+        );
+        if let Err(e) = self.repository.record_status_change(&change).await {
+            log::warn!("Failed to record status change for strategy {}: {}", strategy.id, e);
+        }
+    }
+
+    /// Gets the status history of a strategy, oldest first
+    pub async fn get_status_history(&self, id: &str) -> Result<Vec<StrategyStatusChange>> {
+        let strategy = self.get_strategy(id).await?;
+        self.repository.status_history(strategy.id).await.map_err(|e| {
+            Error::Internal(format!("Failed to load status history: {}", e))
+        })
     }
 
     /// Creates a new strategy
@@ -48,6 +217,7 @@ impl StrategyService {
         allocated_capital: f64,
         created_by: String,
     ) -> Result<Strategy> {
+        check_parameters(&strategy_type, &parameters)?;
         let config = StrategyConfig::new(
             parameters.clone(),
             symbols.into_iter().map(|s| ea_okx_core::types::Symbol::new(&s).unwrap()).collect(),
@@ -66,6 +236,9 @@ impl StrategyService {
         let mut strategy = strategy;
         strategy.description = Some(description);
 
+        self.persist(&strategy).await?;
+        self.record_status_change(&strategy, None, Some("created")).await;
+
         let id = strategy.id.to_string();
         let mut strategies = self.strategies.write().await;
         strategies.insert(id.clone(), strategy.clone());
@@ -79,6 +252,24 @@ impl StrategyService {
         Ok(strategy)
     }
 
+    /// Pauses every active strategy trading `symbol`, returning their IDs
+    pub async fn pause_strategies_for_symbol(&self, symbol: &ea_okx_core::types::Symbol) -> Vec<String> {
+        let affected: Vec<String> = self.strategies.read().await
+            .iter()
+            .filter(|(_, s)| s.status == StrategyStatus::Active && s.config.symbols.contains(symbol))
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        let mut paused = Vec::with_capacity(affected.len());
+        for id in affected {
+            match self.pause_strategy(&id).await {
+                Ok(()) => paused.push(id),
+                Err(e) => log::warn!("Failed to pause strategy {}: {}", id, e),
+            }
+        }
+        paused
+    }
+
     /// Gets all strategies
     pub async fn get_strategies(&self) -> Result<Vec<Strategy>> {
         let strategies = self.strategies.read().await;
@@ -97,19 +288,27 @@ impl StrategyService {
     }
 
     /// Updates a strategy
+    ///
+    /// A running strategy keeps its status when only name, description,
+    /// schema or hot-tunable parameters change and its implementation is
+    /// running; the changed parameters are pushed into that instance. Any
+    /// other change resets it to Draft so it has to be restarted.
+    #[allow(clippy::too_many_arguments)]
     pub async fn update_strategy(
         &self,
         id: &str,
         name: Option<String>,
         description: Option<String>,
         parameters: Option<JsonValue>,
+        parameter_schema: Option<JsonValue>,
         symbols: Option<Vec<String>>,
         allocated_capital: Option<f64>,
     ) -> Result<Strategy> {
         let mut strategies = self.strategies.write().await;
-        let strategy = strategies.get_mut(id).ok_or_else(|| {
+        let mut strategy = strategies.get(id).cloned().ok_or_else(|| {
             Error::NotFound(format!("Strategy not found: {}", id))
         })?;
+        let previous_status = strategy.status;
 
         if let Some(name) = name {
             strategy.name = name;
@@ -119,7 +318,44 @@ impl StrategyService {
             strategy.description = Some(description);
         }
 
+        if let Some(schema) = parameter_schema {
+            strategy.config.parameter_schema = schema;
+        }
+
+        let running = matches!(previous_status, StrategyStatus::Active | StrategyStatus::Paused);
+        let mut restart_required = symbols.is_some() || allocated_capital.is_some();
+        let mut tuned = false;
+
         if let Some(parameters) = parameters {
+            check_parameters(&strategy.strategy_type, &parameters)?;
+            let schema = match &strategy.config.parameter_schema {
+                JsonValue::Null => ea_okx_strategy::bundle::parameter_schema(&strategy.config.parameters),
+                declared => declared.clone(),
+            };
+            let update = ParameterUpdate::plan(&schema, &strategy.config.parameters, &parameters)
+                .map_err(|e| Error::ValidationError(e.to_string()))?;
+
+            if running && !restart_required && update.is_live() {
+                let instance = self.instances.read().await.get(id).cloned();
+                let applied = match instance {
+                    Some(instance) => instance
+                        .apply_update(&schema, &strategy.config.parameters, &parameters)
+                        .await
+                        .map_err(|e| Error::ValidationError(format!("Live update refused: {}", e)))?
+                        .is_some(),
+                    None => false,
+                };
+                if applied {
+                    log::info!("Tuned {} live: {}", id, update.changed.join(", "));
+                    tuned = true;
+                } else {
+                    // Nothing running would see the change until a restart
+                    log::info!("No running instance of {} to tune; restart required", id);
+                    restart_required = true;
+                }
+            } else if !update.is_empty() {
+                restart_required = true;
+            }
             strategy.config.parameters = parameters;
         }
 
@@ -132,19 +368,34 @@ impl StrategyService {
         }
 
         strategy.updated_at = Utc::now();
-        strategy.status = StrategyStatus::Draft; // Reset to draft after update
+        if !running || restart_required {
+            strategy.status = StrategyStatus::Draft; // Reset to draft after update
+        }
+
+        self.persist(&strategy).await?;
+        self.record_status_change(&strategy, Some(previous_status), Some("updated")).await;
+        strategies.insert(id.to_string(), strategy.clone());
+
+        if tuned && let Some(monitor) = &self.monitor {
+            let _ = monitor.update_strategy(strategy.clone()).await;
+        }
 
-        let updated_strategy = strategy.clone();
-        log::info!("Updated strategy: {} ({})", updated_strategy.name, id);
-        Ok(updated_strategy)
+        log::info!("Updated strategy: {} ({})", strategy.name, id);
+        Ok(strategy)
     }
 
     /// Deletes a strategy
     pub async fn delete_strategy(&self, id: &str) -> Result<()> {
         let mut strategies = self.strategies.write().await;
-        if strategies.remove(id).is_none() {
-            return Err(Error::NotFound(format!("Strategy not found: {}", id)));
-        }
+        let strategy = strategies.get(id).ok_or_else(|| {
+            Error::NotFound(format!("Strategy not found: {}", id))
+        })?;
+
+        self.repository.delete(strategy.id).await.map_err(|e| {
+            Error::Internal(format!("Failed to delete strategy {}: {}", id, e))
+        })?;
+        strategies.remove(id);
+        self.instances.write().await.remove(id);
 
         log::info!("Deleted strategy: {}", id);
         Ok(())
@@ -153,9 +404,10 @@ impl StrategyService {
     /// Starts a strategy
     pub async fn start_strategy(&self, id: &str) -> Result<()> {
         let mut strategies = self.strategies.write().await;
-        let strategy = strategies.get_mut(id).ok_or_else(|| {
+        let mut strategy = strategies.get(id).cloned().ok_or_else(|| {
             Error::NotFound(format!("Strategy not found: {}", id))
         })?;
+        let previous_status = strategy.status;
 
         match strategy.status {
             StrategyStatus::Draft | StrategyStatus::Paused | StrategyStatus::Stopped => {
@@ -174,15 +426,21 @@ impl StrategyService {
                     "Cannot start strategy in current state".to_string()
                 ))
             }
-        }
+        }?;
+
+        self.persist(&strategy).await?;
+        self.record_status_change(&strategy, Some(previous_status), Some("started")).await;
+        strategies.insert(id.to_string(), strategy);
+        Ok(())
     }
 
     /// Stops a strategy
     pub async fn stop_strategy(&self, id: &str, force: bool) -> Result<()> {
         let mut strategies = self.strategies.write().await;
-        let strategy = strategies.get_mut(id).ok_or_else(|| {
+        let mut strategy = strategies.get(id).cloned().ok_or_else(|| {
             Error::NotFound(format!("Strategy not found: {}", id))
         })?;
+        let previous_status = strategy.status;
 
         match strategy.status {
             StrategyStatus::Active | StrategyStatus::PaperTrading => {
@@ -207,15 +465,21 @@ impl StrategyService {
                     "Cannot stop strategy in current state".to_string()
                 ))
             }
-        }
+        }?;
+
+        self.persist(&strategy).await?;
+        self.record_status_change(&strategy, Some(previous_status), Some("stopped")).await;
+        strategies.insert(id.to_string(), strategy);
+        Ok(())
     }
 
     /// Pauses a strategy
     pub async fn pause_strategy(&self, id: &str) -> Result<()> {
         let mut strategies = self.strategies.write().await;
-        let strategy = strategies.get_mut(id).ok_or_else(|| {
+        let mut strategy = strategies.get(id).cloned().ok_or_else(|| {
             Error::NotFound(format!("Strategy not found: {}", id))
         })?;
+        let previous_status = strategy.status;
 
         match strategy.status {
             StrategyStatus::Active => {
@@ -233,7 +497,55 @@ impl StrategyService {
                     "Cannot pause strategy in current state".to_string()
                 ))
             }
+        }?;
+
+        self.persist(&strategy).await?;
+        self.record_status_change(&strategy, Some(previous_status), Some("paused")).await;
+        strategies.insert(id.to_string(), strategy);
+        Ok(())
+    }
+
+    /// Moves a running strategy to the error state after its runner failed
+    pub async fn mark_failed(&self, id: &str, reason: &str) -> Result<()> {
+        let mut strategies = self.strategies.write().await;
+        let mut strategy = strategies.get(id).cloned().ok_or_else(|| {
+            Error::NotFound(format!("Strategy not found: {}", id))
+        })?;
+        let previous_status = strategy.status;
+        if previous_status == StrategyStatus::Error {
+            return Ok(());
         }
+
+        strategy.status = StrategyStatus::Error;
+        strategy.updated_at = Utc::now();
+        log::error!("Strategy {} ({}) failed: {}", strategy.name, id, reason);
+
+        self.persist(&strategy).await?;
+        self.record_status_change(&strategy, Some(previous_status), Some(reason)).await;
+        strategies.insert(id.to_string(), strategy);
+        Ok(())
+    }
+
+    /// Returns a failed strategy to active once its runner has restarted it
+    pub async fn mark_recovered(&self, id: &str) -> Result<()> {
+        let mut strategies = self.strategies.write().await;
+        let mut strategy = strategies.get(id).cloned().ok_or_else(|| {
+            Error::NotFound(format!("Strategy not found: {}", id))
+        })?;
+        let previous_status = strategy.status;
+        if previous_status != StrategyStatus::Error {
+            return Ok(());
+        }
+
+        strategy.status = StrategyStatus::Active;
+        strategy.updated_at = Utc::now();
+        strategy.last_active_at = Some(Utc::now());
+        log::info!("Restarted strategy: {} ({})", strategy.name, id);
+
+        self.persist(&strategy).await?;
+        self.record_status_change(&strategy, Some(previous_status), Some("restarted")).await;
+        strategies.insert(id.to_string(), strategy);
+        Ok(())
     }
 
     /// Gets strategy metrics
@@ -282,6 +594,9 @@ impl StrategyService {
 
         drop(strategies); // Release read lock before acquiring write lock
 
+        self.persist(&new_strategy).await?;
+        self.record_status_change(&new_strategy, None, Some("duplicated")).await;
+
         let mut strategies = self.strategies.write().await;
         strategies.insert(new_id.to_string(), new_strategy.clone());
 
@@ -289,6 +604,49 @@ impl StrategyService {
         Ok(new_strategy)
     }
 
+    /// Packages a strategy as a shareable bundle
+    pub async fn export_bundle(
+        &self,
+        id: &str,
+        backtest: Option<BacktestEvidence>,
+    ) -> Result<StrategyBundle> {
+        let strategy = self.get_strategy(id).await?;
+        Ok(StrategyBundle::from_strategy(&strategy, backtest))
+    }
+
+    /// Creates a draft strategy from an imported bundle
+    pub async fn import_bundle(&self, bundle: StrategyBundle) -> Result<Strategy> {
+        let mut config = StrategyConfig::new(
+            bundle.default_parameters,
+            bundle.symbols,
+            bundle.allocated_capital,
+        );
+        config.risk_limits = bundle.risk_limits;
+        config.parameter_schema = bundle.parameter_schema;
+
+        let mut strategy = Strategy::new(
+            bundle.name,
+            bundle.strategy_type,
+            bundle.version,
+            config,
+            Uuid::new_v4(),
+        )?;
+        strategy.description = bundle.description;
+
+        self.persist(&strategy).await?;
+        self.record_status_change(&strategy, None, Some("imported")).await;
+
+        let mut strategies = self.strategies.write().await;
+        strategies.insert(strategy.id.to_string(), strategy.clone());
+
+        if let Some(monitor) = &self.monitor {
+            let _ = monitor.update_strategy(strategy.clone()).await;
+        }
+
+        log::info!("Imported strategy bundle: {} ({})", strategy.name, strategy.id);
+        Ok(strategy)
+    }
+
     /// Initializes default strategies
     pub async fn initialize_default_strategies(&self) -> Result<()> {
         let default_user_id = Uuid::new_v4().to_string();
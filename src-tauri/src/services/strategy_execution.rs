@@ -1,15 +1,19 @@
 //! Strategy execution service for real-time trading
 
-use chrono::Utc;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tokio::sync::{mpsc, RwLock};
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
 use uuid::Uuid;
 use rust_decimal::prelude::ToPrimitive;
 
 use ea_okx_core::{
     error::{Error, Result},
+    exchange::{ExchangeAdapter, ExchangeOrder},
+    heartbeat::Heartbeat,
     models::{
         strategy::{Strategy, StrategyStatus},
         order::{Order, OrderSide, OrderType, OrderStatus},
@@ -18,14 +22,30 @@ use ea_okx_core::{
     },
     types::{Symbol, Price, Quantity, Decimal},
 };
+use ea_okx_client::models::{FillFee, OrderData};
+use data::PriceCache;
+use ea_okx_risk::{DrawdownThrottle, PortfolioState, PreTradeValidator};
+use ea_okx_strategy::{ExternalSignal, OrderCanceller, SignalType as StrategySignalType, StrategyInput};
+use ea_okx_trading::{
+    BalanceReservations, Bracket, BracketManager, ExecutionGate, ExecutionPolicies, ExecutionRoute, FatFingerDecision, FatFingerGuard, GateDecision,
+    IntentLog, IntentStatus, LatencyMark, OrderJournal, LatencyTracker, LiquidityGuard, OrderPlan, OrderPurpose, OrderTimeline, PositionPlan, PreTradeCheck,
+    PreTradeChecks, PreTradeReport, ProtectedPosition, ScaleOutManager, ScaleOutPlan, SignalPriority, SignalQueue, SignalQueueConfig, SignalQueueMetrics,
+    SizeDecision, SizeLimitGuard, TwapConfig,
+};
 
 /// Execution signal from strategy
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionSignal {
+    /// Tagged on the orders this signal produces
+    #[serde(default = "Uuid::new_v4")]
+    pub signal_id: Uuid,
     pub strategy_id: Uuid,
     pub symbol: Symbol,
     pub signal_type: SignalType,
+    /// Order side; on exits, the side of the closing order, which must be
+    /// opposite the position
     pub side: Option<OrderSide>,
+    /// Ignored by full closes and risk exits, which take the position's size
     pub quantity: Quantity,
     pub price: Option<Price>,
     pub stop_loss: Option<Price>,
@@ -34,6 +54,84 @@ pub struct ExecutionSignal {
     pub metadata: serde_json::Value,
 }
 
+impl ExecutionSignal {
+    /// Execution signal for a validated external signal
+    pub fn from_external(signal: ExternalSignal) -> Result<Self> {
+        let (signal_type, side, quantity) = execution_of(signal.signal_type, signal.quantity)?;
+
+        Ok(Self {
+            signal_id: signal.id,
+            strategy_id: signal.strategy_id,
+            symbol: signal.symbol,
+            signal_type,
+            side,
+            quantity,
+            price: signal.price,
+            stop_loss: signal.stop_loss,
+            take_profit: signal.take_profit,
+            confidence: signal.confidence,
+            metadata: serde_json::json!({
+                "source": signal.source,
+                "external_signal_id": signal.id,
+                "payload": signal.payload,
+            }),
+        })
+    }
+
+    /// Execution signal for a signal a running strategy emitted
+    ///
+    /// It trades `symbol` unless its metadata names another symbol.
+    pub fn from_strategy(strategy_id: Uuid, symbol: Symbol, signal: ea_okx_strategy::Signal) -> Result<Self> {
+        let symbol = match signal.metadata.get("symbol").and_then(serde_json::Value::as_str) {
+            Some(named) => Symbol::new(named)?,
+            None => symbol,
+        };
+        let (signal_type, side, quantity) = execution_of(signal.signal_type, signal.suggested_quantity)?;
+
+        Ok(Self {
+            signal_id: Uuid::new_v4(),
+            strategy_id,
+            symbol,
+            signal_type,
+            side,
+            quantity,
+            price: signal.target_price,
+            stop_loss: signal.stop_loss,
+            take_profit: signal.take_profit,
+            confidence: signal.confidence,
+            metadata: signal.metadata,
+        })
+    }
+}
+
+/// Signal type, order side and quantity a strategy-level signal executes as
+///
+/// Closes keep their direction: a close-long only sells out of a long
+/// position and a close-short only buys back a short one. A close with a
+/// quantity closes that much of the position; one without closes all of it.
+fn execution_of(
+    signal_type: StrategySignalType,
+    quantity: Option<Quantity>,
+) -> Result<(SignalType, Option<OrderSide>, Quantity)> {
+    let side = match signal_type {
+        StrategySignalType::Buy | StrategySignalType::CloseShort => OrderSide::Buy,
+        StrategySignalType::Sell | StrategySignalType::CloseLong => OrderSide::Sell,
+        StrategySignalType::Hold => {
+            return Err(Error::ValidationError("Hold signals are not executable".to_string()));
+        }
+    };
+    match (signal_type, quantity) {
+        (StrategySignalType::Buy | StrategySignalType::Sell, Some(quantity)) => {
+            Ok((SignalType::Open, Some(side), quantity))
+        }
+        (StrategySignalType::Buy | StrategySignalType::Sell, None) => {
+            Err(Error::ValidationError("Open signals need a quantity".to_string()))
+        }
+        (_, Some(quantity)) => Ok((SignalType::PartialClose, Some(side), quantity)),
+        (_, None) => Ok((SignalType::Close, Some(side), Quantity::new(Decimal::ZERO)?)),
+    }
+}
+
 /// Types of execution signals
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -54,6 +152,18 @@ pub enum SignalType {
     RiskManagement,
 }
 
+impl SignalType {
+    /// Queue priority: risk management, then exits, then closes, then opens
+    pub fn priority(&self) -> SignalPriority {
+        match self {
+            SignalType::RiskManagement => SignalPriority::RiskManagement,
+            SignalType::StopLoss | SignalType::TakeProfit => SignalPriority::Exit,
+            SignalType::Close | SignalType::PartialClose => SignalPriority::Close,
+            SignalType::Open | SignalType::Modify => SignalPriority::Open,
+        }
+    }
+}
+
 /// Order execution request
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionRequest {
@@ -67,6 +177,15 @@ pub struct ExecutionRequest {
     pub time_in_force: TimeInForce,
     pub reduce_only: bool,
     pub post_only: bool,
+    /// Send even if the fat-finger guard asks for confirmation
+    #[serde(default)]
+    pub confirmed: bool,
+    /// Signal the order executes, if any
+    #[serde(default)]
+    pub signal_id: Option<Uuid>,
+    /// Cancel the order if it is still resting at this time (good-till-date)
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
 }
 
 /// Time in force for orders
@@ -82,37 +201,295 @@ pub enum TimeInForce {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionResult {
     pub request_id: Uuid,
+    /// Accepted by the exchange, or filled on paper in dry-run mode; sent
+    /// orders fill as the exchange reports them
     pub success: bool,
     pub order: Option<Order>,
     pub trade: Option<Trade>,
     pub error: Option<String>,
+    /// Exchange size cap applied to the order, if a size guard is configured
+    pub size_decision: Option<SizeDecision>,
+    /// Book depth cap applied to the order, if a liquidity guard is configured
+    #[serde(default)]
+    pub liquidity_decision: Option<SizeDecision>,
+    /// Fat-finger check outcome, if a guard is configured
+    pub fat_finger: Option<FatFingerDecision>,
     pub latency_ms: i64,
 }
 
+/// Outcome of one step of a simulated signal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineStage {
+    /// `strategy`, `order`, `validation`, `risk`, `drawdown`, `gate`, `degraded`, `fat_finger`, `reduce_only`,
+    /// `sizing`, `liquidity`, `balance` or `fill`
+    pub stage: String,
+    /// Whether the signal got past this step
+    pub passed: bool,
+    pub detail: String,
+    /// Step-specific decision, e.g. the risk violations
+    pub data: serde_json::Value,
+}
+
+impl PipelineStage {
+    fn passed(stage: &str, detail: impl Into<String>, data: serde_json::Value) -> Self {
+        Self {
+            stage: stage.to_string(),
+            passed: true,
+            detail: detail.into(),
+            data,
+        }
+    }
+}
+
+/// Stages for the pre-trade checks in `report`, in the order they ran
+///
+/// A refused order's last stage is the check that refused it. `preview`
+/// words the stages for a simulation, where nothing is sent or reserved.
+fn pre_trade_stages(report: &PreTradeReport, confirmed: bool, preview: bool) -> Vec<PipelineStage> {
+    let mut stages = Vec::new();
+    let refused_at = report.refused.as_ref().map(|(check, _)| *check);
+    if refused_at != Some(PreTradeCheck::Gate) {
+        let detail = match (report.dry_run, preview) {
+            (false, false) => "Sent to OKX",
+            (false, true) => "Would be sent to OKX",
+            (true, false) => "Strategy is in dry-run mode, not sent",
+            (true, true) => "Strategy is in dry-run mode, would not be sent",
+        };
+        stages.push(PipelineStage::passed("gate", detail, serde_json::Value::Null));
+    }
+    if let Some(original) = report.widened_from {
+        stages.push(PipelineStage::passed(
+            "degraded",
+            format!("Exchange degraded, limit moved from {}", original),
+            serde_json::Value::Null,
+        ));
+    }
+    if let Some(decision) = &report.fat_finger {
+        let detail = if confirmed { "Confirmed by the user" } else { "Within fat-finger limits" };
+        let data = serde_json::to_value(decision).unwrap_or_default();
+        stages.push(PipelineStage::passed("fat_finger", detail, data));
+    }
+    if let Some(decision) = &report.reduce_only {
+        let data = serde_json::to_value(decision).unwrap_or_default();
+        stages.push(PipelineStage::passed("reduce_only", decision.describe(), data));
+    }
+    if let Some(decision) = &report.size {
+        let detail = match decision {
+            SizeDecision::Clamped { requested, allowed } => {
+                format!("Clamped from {} to max available {}", requested, allowed)
+            }
+            _ => "Within max available size".to_string(),
+        };
+        let data = serde_json::to_value(decision).unwrap_or_default();
+        stages.push(PipelineStage::passed("sizing", detail, data));
+    }
+    if let Some(decision) = &report.liquidity {
+        let detail = match decision {
+            SizeDecision::Clamped { requested, allowed } => {
+                format!("Clamped from {} to {} by visible liquidity", requested, allowed)
+            }
+            SizeDecision::Within { cap: None } => "No fresh order book, not capped".to_string(),
+            _ => "Within visible liquidity".to_string(),
+        };
+        let data = serde_json::to_value(decision).unwrap_or_default();
+        stages.push(PipelineStage::passed("liquidity", detail, data));
+    }
+    if let Some(reservation) = &report.reservation {
+        let verb = if preview { "Would reserve" } else { "Reserved" };
+        let detail = format!("{} {} {}", verb, reservation.amount(), reservation.ccy);
+        let data = serde_json::to_value(reservation).unwrap_or_default();
+        stages.push(PipelineStage::passed("balance", detail, data));
+    }
+
+    if let Some((check, e)) = &report.refused {
+        let stage = match check {
+            PreTradeCheck::Gate => "gate",
+            PreTradeCheck::FatFinger => "fat_finger",
+            PreTradeCheck::ReduceOnly => "reduce_only",
+            PreTradeCheck::Size => "sizing",
+            PreTradeCheck::Liquidity => "liquidity",
+            PreTradeCheck::Balance => "balance",
+            PreTradeCheck::IntentLog => "intent_log",
+        };
+        // The refusing check recorded its decision last, if it reached one
+        match stages.last_mut().filter(|last| last.stage == stage) {
+            Some(last) => {
+                last.passed = false;
+                last.detail = e.to_string();
+            }
+            None => stages.push(PipelineStage {
+                stage: stage.to_string(),
+                passed: false,
+                detail: e.to_string(),
+                data: serde_json::Value::Null,
+            }),
+        }
+    }
+    stages
+}
+
+/// Why a live order was sent: the signal behind it and the checks it passed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderDecision {
+    pub order_id: Uuid,
+    pub request: ExecutionRequest,
+    /// Signal the order executes, if it came from one
+    pub signal: Option<ExecutionSignal>,
+    /// `drawdown`, `gate`, `degraded`, `fat_finger`, `reduce_only`, `sizing`,
+    /// `liquidity`, `balance` and `bracket`, in the order they ran
+    pub checks: Vec<PipelineStage>,
+    pub decided_at: DateTime<Utc>,
+}
+
+/// A fill with the order and decision behind it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeOrigin {
+    pub trade: Trade,
+    pub order: Option<Order>,
+    /// Missing for orders placed before the last restart
+    pub decision: Option<OrderDecision>,
+}
+
+/// Every decision a signal went through in a sandboxed run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineSimulation {
+    pub signal_id: Uuid,
+    pub stages: Vec<PipelineStage>,
+    /// Step that stopped the signal, if one did
+    pub stopped_at: Option<String>,
+    /// Order the live pipeline would send
+    pub order: Option<Order>,
+    /// Paper fill of that order, if it would fill immediately
+    pub trade: Option<Trade>,
+}
+
+impl PipelineSimulation {
+    fn record(&mut self, stage: &str, passed: bool, detail: impl Into<String>, data: serde_json::Value) -> bool {
+        self.stages.push(PipelineStage {
+            stage: stage.to_string(),
+            passed,
+            detail: detail.into(),
+            data,
+        });
+        if !passed {
+            self.stopped_at = Some(stage.to_string());
+        }
+        passed
+    }
+}
+
+/// Fee rates applied to fills the exchange has not reported a fee for
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct FeeSchedule {
+    /// Negative for a maker rebate
+    pub maker_rate: Decimal,
+    pub taker_rate: Decimal,
+}
+
+impl Default for FeeSchedule {
+    /// OKX spot base tier
+    fn default() -> Self {
+        Self {
+            maker_rate: Decimal::new(8, 4),
+            taker_rate: Decimal::new(10, 4),
+        }
+    }
+}
+
+impl FeeSchedule {
+    /// Estimated fee on a fill, charged in the asset received as OKX spot does
+    fn estimate(&self, order: &Order, quantity: Decimal, price: Decimal) -> FillFee {
+        let maker = matches!(order.order_type, OrderType::Limit | OrderType::PostOnly);
+        let rate = if maker { self.maker_rate } else { self.taker_rate };
+        let (commission, currency) = match order.side {
+            OrderSide::Buy => (quantity * rate, order.symbol.base()),
+            OrderSide::Sell => (quantity * price * rate, order.symbol.quote()),
+        };
+        FillFee {
+            commission,
+            currency: currency.to_string(),
+            maker,
+        }
+    }
+}
+
 /// Strategy execution engine
 #[derive(Clone)]
 pub struct StrategyExecutionEngine {
-    #[allow(dead_code)]
+    /// Strategies by ID; signals of strategies that are not active are dropped
     strategies: Arc<RwLock<HashMap<String, Strategy>>>,
     orders: Arc<RwLock<HashMap<String, Order>>>,
     positions: Arc<RwLock<HashMap<String, Position>>>,
     trades: Arc<RwLock<Vec<Trade>>>,
-    signal_tx: mpsc::UnboundedSender<ExecutionSignal>,
+    /// Signal and checks behind each sent order, by order ID
+    decisions: Arc<RwLock<HashMap<Uuid, OrderDecision>>>,
+    signal_queue: Arc<SignalQueue<ExecutionSignal>>,
     monitor: Option<Arc<super::StrategyMonitorService>>,
+    /// Gate, guards, balance reservations and intent log, shared with every
+    /// other order path
+    checks: PreTradeChecks,
+    /// Shrinks new positions as portfolio drawdown deepens
+    drawdown_throttle: Option<Arc<RwLock<DrawdownThrottle>>>,
+    /// Lifecycle events of every stored order, for compliance drop copies
+    order_journal: Option<Arc<OrderJournal>>,
+    /// Tranche take-profit plans of open positions
+    scale_out: Arc<ScaleOutManager>,
+    /// Exchange-side stop loss and take profit of signal-opened positions
+    brackets: Arc<BracketManager>,
+    /// Per-stage timings of orders sent to the exchange
+    latency: Arc<LatencyTracker>,
+    /// Order type, algo routing and slippage rules of each strategy
+    execution_policies: Arc<ExecutionPolicies>,
+    /// Last price seen for each symbol, the reference for slippage caps
+    last_prices: Arc<RwLock<HashMap<String, Decimal>>>,
+    /// Shared latest prices, asked for symbols with no price seen here yet
+    price_cache: Option<Arc<dyn PriceCache>>,
+    fee_schedule: FeeSchedule,
+    reporting_currency: String,
+    /// Reporting-currency value of one unit of each other fee currency
+    fee_rates: Arc<RwLock<HashMap<String, Decimal>>>,
+    /// Cumulative filled size of each fill whose exchange-reported fee is
+    /// recorded, by OKX order ID, to skip redelivered updates
+    exchange_fees: Arc<RwLock<HashMap<String, HashSet<String>>>>,
+    /// Venue orders are placed on and cancelled at; without one only
+    /// dry-run orders execute
+    exchange: Option<Arc<dyn ExchangeAdapter>>,
+    /// Scale-out exit behind each sent order still waiting to fill, by order ID
+    scale_out_exits: Arc<RwLock<HashMap<Uuid, Uuid>>>,
+    /// Fills and rejections of orders, for the strategies that sent them
+    order_update_tx: tokio::sync::mpsc::UnboundedSender<StrategyInput>,
+    order_update_rx: Arc<std::sync::Mutex<Option<tokio::sync::mpsc::UnboundedReceiver<StrategyInput>>>>,
 }
 
 impl StrategyExecutionEngine {
     /// Creates a new execution engine
     pub fn new() -> Self {
-        let (signal_tx, _) = mpsc::unbounded_channel();
-
+        let (order_update_tx, order_update_rx) = tokio::sync::mpsc::unbounded_channel();
         Self {
             strategies: Arc::new(RwLock::new(HashMap::new())),
             orders: Arc::new(RwLock::new(HashMap::new())),
             positions: Arc::new(RwLock::new(HashMap::new())),
             trades: Arc::new(RwLock::new(Vec::new())),
-            signal_tx,
+            decisions: Arc::new(RwLock::new(HashMap::new())),
+            signal_queue: Arc::new(SignalQueue::default()),
             monitor: None,
+            checks: PreTradeChecks::new(),
+            drawdown_throttle: None,
+            order_journal: None,
+            scale_out: Arc::new(ScaleOutManager::new()),
+            brackets: Arc::new(BracketManager::new()),
+            latency: Arc::new(LatencyTracker::default()),
+            execution_policies: Arc::new(ExecutionPolicies::default()),
+            last_prices: Arc::new(RwLock::new(HashMap::new())),
+            price_cache: None,
+            fee_schedule: FeeSchedule::default(),
+            reporting_currency: "USDT".to_string(),
+            fee_rates: Arc::new(RwLock::new(HashMap::new())),
+            exchange_fees: Arc::new(RwLock::new(HashMap::new())),
+            exchange: None,
+            scale_out_exits: Arc::new(RwLock::new(HashMap::new())),
+            order_update_tx,
+            order_update_rx: Arc::new(std::sync::Mutex::new(Some(order_update_rx))),
         }
     }
 
@@ -123,20 +500,323 @@ impl StrategyExecutionEngine {
         engine
     }
 
+    /// Judges signals against the strategies `strategies` holds, e.g. the
+    /// strategy service's, instead of an empty map of its own
+    pub fn with_strategies(mut self, strategies: Arc<RwLock<HashMap<String, Strategy>>>) -> Self {
+        self.strategies = strategies;
+        self
+    }
+
+    /// Routes every order through a shared execution gate
+    pub fn with_gate(mut self, gate: Arc<ExecutionGate>) -> Self {
+        self.checks = self.checks.with_gate(gate);
+        self
+    }
+
+    /// Scales new positions by the factor of the current portfolio drawdown,
+    /// refusing them where the factor is zero
+    pub fn with_drawdown_throttle(mut self, throttle: Arc<RwLock<DrawdownThrottle>>) -> Self {
+        self.drawdown_throttle = Some(throttle);
+        self
+    }
+
+    /// Clamps or rejects orders above the exchange's max available size
+    pub fn with_size_guard(mut self, guard: Arc<SizeLimitGuard>) -> Self {
+        self.checks = self.checks.with_size_guard(guard);
+        self
+    }
+
+    /// Clamps or rejects orders above their share of the visible book depth
+    pub fn with_liquidity_guard(mut self, guard: Arc<LiquidityGuard>) -> Self {
+        self.checks = self.checks.with_liquidity_guard(guard);
+        self
+    }
+
+    /// Holds or refuses orders failing the fat-finger sanity checks
+    pub fn with_fat_finger_guard(mut self, guard: Arc<FatFingerGuard>) -> Self {
+        self.checks = self.checks.with_fat_finger_guard(guard);
+        self
+    }
+
+    /// Logs each order durably before it is sent to OKX
+    pub fn with_intent_log(mut self, log: Arc<dyn IntentLog>) -> Self {
+        self.checks = self.checks.with_intent_log(log);
+        self
+    }
+
+    /// Journals each step of every order's life, the source of drop copies
+    pub fn with_order_journal(mut self, journal: Arc<OrderJournal>) -> Self {
+        self.order_journal = Some(journal);
+        self
+    }
+
+    /// Reserves balance for each spot order sent, refusing orders the
+    /// balance not yet reserved cannot cover
+    pub fn with_balance_reservations(mut self, reservations: Arc<BalanceReservations>) -> Self {
+        self.checks = self.checks.with_balance_reservations(reservations);
+        self
+    }
+
+    /// Places and cancels orders on `exchange`
+    pub fn with_exchange(mut self, exchange: Arc<dyn ExchangeAdapter>) -> Self {
+        self.exchange = Some(exchange);
+        self
+    }
+
+    /// Manages scale-out plans with `manager`, e.g. one resting exits on OKX
+    pub fn with_scale_out(mut self, manager: Arc<ScaleOutManager>) -> Self {
+        self.scale_out = manager;
+        self
+    }
+
+    /// Sends opening signals that carry both a stop loss and a take profit
+    /// with the pair attached, armed on the exchange once the order fills
+    pub fn with_brackets(mut self, manager: Arc<BracketManager>) -> Self {
+        self.brackets = manager;
+        self
+    }
+
+    /// Falls back to `cache` for the price of symbols no market price was
+    /// seen for here
+    pub fn with_price_cache(mut self, cache: Arc<dyn PriceCache>) -> Self {
+        self.price_cache = Some(cache);
+        self
+    }
+
+    /// Bounds the signal queue and the age at which open signals go stale
+    pub fn with_signal_queue_config(mut self, config: SignalQueueConfig) -> Self {
+        self.signal_queue = Arc::new(SignalQueue::new(config));
+        self
+    }
+
+    /// Currency fee totals are reported in
+    pub fn reporting_currency(&self) -> &str {
+        &self.reporting_currency
+    }
+
+    /// Value of one unit of `currency` in the reporting currency
+    ///
+    /// Needed for fees charged in an asset outside the traded pair, such as
+    /// OKB; fees in the pair's own assets convert at the fill price.
+    pub async fn set_fee_conversion_rate(&self, currency: &str, rate: Decimal) {
+        self.fee_rates.write().await.insert(currency.to_uppercase(), rate);
+    }
+
     /// Submit execution signal from strategy
+    ///
+    /// Fails when the strategy is over its signal quota or paused for
+    /// repeated quota breaches, or when the queue is full of signals at least
+    /// as urgent.
     pub async fn submit_signal(&self, signal: ExecutionSignal) -> Result<()> {
-        if let Err(e) = self.signal_tx.send(signal.clone()) {
-            return Err(Error::Internal(e.to_string()));
+        let (signal_type, strategy_id, signal_id) = (signal.signal_type, signal.strategy_id, signal.signal_id);
+        match self.checks.gate().check_signal(strategy_id) {
+            GateDecision::Blocked(reason) | GateDecision::Throttled(reason) => {
+                return Err(Error::Internal(reason));
+            }
+            GateDecision::Send | GateDecision::DryRun => {}
         }
+        self.signal_queue
+            .push(signal_type.priority(), signal)
+            .map_err(|e| Error::Internal(e.to_string()))?;
+        self.latency.signal_received(signal_id);
 
-        log::info!("Submitted signal: {:?} for strategy {:?}",
-                  signal.signal_type, signal.strategy_id);
+        log::info!("Submitted signal: {:?} for strategy {:?}", signal_type, strategy_id);
         Ok(())
     }
 
+    /// Take the receiver of order fills and rejections; only the first call gets it
+    pub fn subscribe_order_updates(&self) -> Option<tokio::sync::mpsc::UnboundedReceiver<StrategyInput>> {
+        self.order_update_rx.lock().ok()?.take()
+    }
+
+    /// Queue depth and drop counters
+    pub fn signal_queue_metrics(&self) -> SignalQueueMetrics {
+        self.signal_queue.metrics()
+    }
+
+    /// Process queued signals, most urgent first, until the task is aborted
+    ///
+    /// Beats `heartbeat` after every signal and every 10 seconds while the
+    /// queue is empty, so only a processor stuck on a signal goes silent.
+    pub fn spawn_signal_processor(&self, heartbeat: Heartbeat) -> JoinHandle<()> {
+        let engine = self.clone();
+        tokio::spawn(async move {
+            let mut idle = tokio::time::interval(std::time::Duration::from_secs(10));
+            loop {
+                tokio::select! {
+                    signal = engine.signal_queue.pop() => {
+                        if let Err(e) = engine.process_signal(signal).await {
+                            log::error!("Failed to process signal: {}", e);
+                        }
+                    }
+                    _ = idle.tick() => {}
+                }
+                heartbeat.beat();
+            }
+        })
+    }
+
+    /// Take profit on the strategy's open position in tranches
+    ///
+    /// Replaces any plan already attached to the position.
+    pub async fn set_position_plan(
+        &self,
+        strategy_id: Uuid,
+        symbol: &Symbol,
+        plan: &ScaleOutPlan,
+    ) -> Result<PositionPlan> {
+        let key = format!("{}-{}", strategy_id, symbol.as_str());
+        let entry_price = self
+            .positions
+            .read()
+            .await
+            .get(&key)
+            .map(|position| position.avg_entry_price.as_decimal())
+            .ok_or_else(|| Error::NotFound(format!("No open {} position", symbol.as_str())))?;
+        let position = self.net_position(strategy_id, symbol).await;
+
+        self.scale_out
+            .attach(strategy_id, symbol.clone(), plan, entry_price, position)
+            .await
+            .map_err(|e| match e {
+                ea_okx_trading::Error::InvalidPlan(_) => Error::ValidationError(e.to_string()),
+                e => Error::Internal(e.to_string()),
+            })
+    }
+
+    /// Scale-out plan attached to the strategy's position, if any
+    pub fn get_position_plan(&self, strategy_id: Uuid, symbol: &Symbol) -> Option<PositionPlan> {
+        self.scale_out.plan(strategy_id, symbol)
+    }
+
+    /// Exchange-side brackets protecting open positions
+    pub fn get_brackets(&self) -> Vec<ProtectedPosition> {
+        self.brackets.brackets()
+    }
+
+    /// Per-stage timings of sent orders and their budget
+    pub fn latency_tracker(&self) -> Arc<LatencyTracker> {
+        self.latency.clone()
+    }
+
+    /// How each strategy's signals become orders
+    pub fn execution_policies(&self) -> Arc<ExecutionPolicies> {
+        self.execution_policies.clone()
+    }
+
+    async fn last_price(&self, symbol: &Symbol) -> Option<Price> {
+        if let Some(price) = self.last_prices.read().await.get(symbol.as_str()).copied() {
+            return Price::new(price).ok();
+        }
+        match self.price_cache.as_ref()?.get_price(symbol).await {
+            Ok(price) => price,
+            Err(e) => {
+                log::warn!("No cached price for {}: {}", symbol.as_str(), e);
+                None
+            }
+        }
+    }
+
+    /// Mark open positions in `symbol` to `price` and send the exits of
+    /// locally managed scale-out plans it reached
+    ///
+    /// Every exit order is sent even if an earlier one fails; exits whose
+    /// order is refused, or ends without filling, are watched again, and the
+    /// first error is returned.
+    pub async fn on_market_price(&self, symbol: &Symbol, price: Decimal) -> Result<()> {
+        self.last_prices.write().await.insert(symbol.as_str().to_string(), price);
+        self.mark_positions(symbol, price).await;
+
+        let mut first_error = None;
+        for order in self.scale_out.on_price(symbol, price) {
+            let request = ExecutionRequest {
+                id: Uuid::new_v4(),
+                strategy_id: order.strategy_id,
+                symbol: order.symbol,
+                side: order.side,
+                order_type: OrderType::Market,
+                quantity: order.quantity,
+                price: None,
+                time_in_force: TimeInForce::ImmediateOrCancel,
+                reduce_only: true,
+                post_only: false,
+                // Take profits are never held for confirmation
+                confirmed: true,
+                signal_id: None,
+                expires_at: None,
+            };
+            match self.execute_order(request).await {
+                Ok(ExecutionResult { success: true, order: Some(sent), .. }) if !sent.is_filled() => {
+                    // Completed once the exchange reports the order filled,
+                    // which may already have happened
+                    self.scale_out_exits.write().await.insert(sent.id, order.id);
+                    let current = self.orders.read().await.get(&sent.id.to_string()).cloned();
+                    if let Some(current) = current {
+                        self.settle_scale_out_exit(&current).await;
+                    }
+                }
+                Ok(result) if result.success => {
+                    self.scale_out.exit_filled(order.id);
+                }
+                Ok(result) => {
+                    log::warn!(
+                        "Scale-out exit on {} not filled: {}",
+                        symbol.as_str(),
+                        result.error.unwrap_or_default()
+                    );
+                    self.scale_out.exit_failed(order.id);
+                }
+                Err(e) => {
+                    log::error!("Scale-out exit on {} failed: {}", symbol.as_str(), e);
+                    self.scale_out.exit_failed(order.id);
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+
+    /// Revalue positions in `symbol` and report each account's unrealized
+    /// P&L to the daily loss lock
+    async fn mark_positions(&self, symbol: &Symbol, price: Decimal) {
+        let Ok(price) = Price::new(price) else {
+            return;
+        };
+        let mut positions = self.positions.write().await;
+        for position in positions.values_mut().filter(|p| &p.symbol == symbol && !p.is_closed()) {
+            position.update_price(price);
+        }
+
+        let daily_loss = self.checks.gate().daily_loss();
+        // Accounts left flat drop back to zero
+        let mut unrealized: HashMap<String, Decimal> = daily_loss.statuses().into_iter()
+            .map(|day| (day.account, Decimal::ZERO))
+            .collect();
+        for position in positions.values().filter(|p| !p.is_closed()) {
+            *unrealized.entry(daily_loss.account_for(position.strategy_id)).or_default() += position.unrealized_pnl;
+        }
+        drop(positions);
+
+        let now = Utc::now();
+        for (account, pnl) in unrealized {
+            daily_loss.set_unrealized(&account, pnl, now);
+        }
+    }
+
     /// Execute a single order
     pub async fn execute_order(&self, request: ExecutionRequest) -> Result<ExecutionResult> {
+        self.execute(request, None).await
+    }
+
+    /// Execute the order for `request`, recording `signal` and the checks it
+    /// passes against the order if it is sent
+    async fn execute(&self, request: ExecutionRequest, signal: Option<ExecutionSignal>) -> Result<ExecutionResult> {
         let start_time = std::time::Instant::now();
+        // Queued signals are timed from when they were submitted
+        let received_at = signal
+            .as_ref()
+            .and_then(|s| self.latency.take_signal_received(s.signal_id))
+            .unwrap_or_else(Utc::now);
         log::info!("Executing order: {:?}", request);
 
         // Validate request
@@ -151,129 +831,252 @@ impl StrategyExecutionEngine {
             request.quantity,
             request.price,
         );
+        if let Some(signal_id) = request.signal_id {
+            order = order.with_signal(signal_id);
+        }
+        if let Some(expires_at) = request.expires_at {
+            order = order.with_expiry(expires_at);
+        }
+        let mut timeline = OrderTimeline::new(order.id, order.strategy_id, order.symbol.clone(), received_at);
+        let mut checks = Vec::new();
+
+        // Deeper drawdowns leave less room for new risk
+        if let Some(throttle) = &self.drawdown_throttle {
+            let decision = throttle
+                .read()
+                .await
+                .apply(&mut order, None)
+                .map_err(|e| Error::Internal(e.to_string()))?;
+            if decision.is_blocked() {
+                return Ok(ExecutionResult {
+                    request_id: request.id,
+                    success: false,
+                    order: None,
+                    trade: None,
+                    error: Some(decision.describe()),
+                    size_decision: None,
+                    liquidity_decision: None,
+                    fat_finger: None,
+                    latency_ms: start_time.elapsed().as_millis() as i64,
+                });
+            }
+            let data = serde_json::to_value(decision).unwrap_or_default();
+            checks.push(PipelineStage::passed("drawdown", decision.describe(), data));
+        }
 
-        // Submit order to OKX (mock implementation for now)
-        let okx_order_id = self.submit_to_okx(&order).await?;
-
-        // Update order status
-        order.mark_submitted(okx_order_id.clone());
-
-        // Simulate order execution (in real implementation, this would be handled by WebSocket)
-        let (execution_result, trade) = if self.simulate_execution(&mut order).await? {
-            let trade = self.create_trade_record(&order)?;
-            (true, Some(trade))
+        // Reduce-only orders may close the strategy's position but never flip it
+        let position = if request.reduce_only {
+            order = order.with_reduce_only();
+            Some(self.net_position(request.strategy_id, &order.symbol).await)
         } else {
-            (false, None)
+            None
         };
+        if request.confirmed
+            && let Some(guard) = self.checks.fat_finger()
+        {
+            guard.confirm(order.id);
+        }
 
-        // Store order
-        let mut orders = self.orders.write().await;
-        orders.insert(order.id.to_string(), order.clone());
+        // Nothing reaches the exchange without passing the same checks as
+        // every other order path, the execution gate first
+        let report = self.checks.run(&mut order, None, position).await;
+        checks.extend(pre_trade_stages(&report, request.confirmed, false));
+        let size_decision = report.size.clone();
+        let liquidity_decision = report.liquidity.clone();
+        let fat_finger = report.fat_finger.clone();
+        if let Some((_, e)) = report.refused {
+            return Ok(ExecutionResult {
+                request_id: request.id,
+                success: false,
+                order: None,
+                trade: None,
+                error: Some(e.to_string()),
+                size_decision,
+                liquidity_decision,
+                fat_finger,
+                latency_ms: start_time.elapsed().as_millis() as i64,
+            });
+        }
 
-        // Update positions based on execution
-        if execution_result {
-            if let Some(ref trade) = trade {
-                self.update_positions_from_trade(trade).await?;
+        let mut rejection = None;
+        let paper = report.dry_run;
+        let exchange_order_id = if paper {
+            Some(format!("dryrun_{}", Uuid::new_v4()))
+        } else {
+            timeline.mark(LatencyMark::RiskChecked);
+            let sent = match signal.as_ref().and_then(|s| self.signal_bracket(s, &order)) {
+                Some(bracket) => {
+                    timeline.mark(LatencyMark::Sent);
+                    let placed = self.brackets.place(&order, bracket).await;
+                    if placed.is_ok() {
+                        timeline.mark(LatencyMark::Acknowledged);
+                        checks.push(PipelineStage::passed(
+                            "bracket",
+                            format!(
+                                "Stop loss {} and take profit {} attached on OKX",
+                                bracket.stop_loss, bracket.take_profit
+                            ),
+                            serde_json::to_value(bracket).unwrap_or_default(),
+                        ));
+                    }
+                    placed.map_err(|e| match e {
+                        ea_okx_trading::Error::InvalidBracket(_) => Error::ValidationError(e.to_string()),
+                        e => Error::Internal(e.to_string()),
+                    })
+                }
+                None => self.submit_to_exchange(&order, &mut timeline).await,
+            };
+            self.resolve_intent(&order, &sent);
+            if sent.is_err() {
+                self.release_reservation(order.id);
             }
-        }
+            match sent {
+                Ok(exchange_order_id) => Some(exchange_order_id),
+                // Refused outright: nothing rests, the strategy hears why
+                Err(e @ Error::ExchangeRejected { .. }) => {
+                    rejection = Some(e.to_string());
+                    None
+                }
+                Err(e) => return Err(e),
+            }
+        };
 
-        let latency = start_time.elapsed().as_millis() as i64;
+        match exchange_order_id {
+            Some(exchange_order_id) => order.mark_submitted(exchange_order_id),
+            None => order.set_status(OrderStatus::Rejected),
+        }
 
-        // Emit monitoring events
-        if let Some(monitor) = &self.monitor {
-            if execution_result {
-                if let Some(ref trade) = trade {
-                    let _ = monitor.emit_trade_executed(
-                        order.strategy_id.to_string(),
-                        trade.id.to_string(),
-                        trade.symbol.as_str().to_string(),
-                        format!("{:?}", trade.side),
-                        trade.quantity.as_decimal().to_f64().unwrap_or(0.0),
-                        trade.price.as_decimal().to_f64().unwrap_or(0.0),
-                    ).await;
+        // Sent orders fill as the exchange reports them; dry-run orders fill
+        // on paper at once, at their limit or the last market price
+        let mut fill = None;
+        if paper {
+            match order.price.or(self.last_price(&order.symbol).await) {
+                Some(price) => {
+                    order.update_fill(order.quantity, price);
+                    fill = Some((order.quantity, price));
+                }
+                None => {
+                    order.set_status(OrderStatus::Rejected);
+                    rejection = Some("No market price to fill the dry-run order at".to_string());
                 }
-            } else {
-                let _ = monitor.emit_error(
-                    order.strategy_id.to_string(),
-                    "Order execution failed".to_string(),
-                ).await;
             }
         }
+        if timeline.marked(LatencyMark::Acknowledged).is_some() {
+            self.latency.record(&timeline);
+        }
+
+        // Store order
+        self.orders.write().await.insert(order.id.to_string(), order.clone());
+        self.journal_order(&order);
+        self.settle_reservation(&order);
+        self.decisions.write().await.insert(order.id, OrderDecision {
+            order_id: order.id,
+            request: request.clone(),
+            signal,
+            checks,
+            decided_at: Utc::now(),
+        });
+
+        let trade = match fill {
+            Some((quantity, price)) => Some(self.record_fill(&order, quantity, price).await?),
+            None => None,
+        };
+        if let Some(reason) = &rejection {
+            self.report_rejection(&order, reason.clone()).await;
+        }
 
         Ok(ExecutionResult {
             request_id: request.id,
-            success: execution_result,
+            success: rejection.is_none(),
             order: Some(order),
             trade,
-            error: None,
-            latency_ms: latency,
+            error: rejection,
+            size_decision,
+            liquidity_decision,
+            fat_finger,
+            latency_ms: start_time.elapsed().as_millis() as i64,
+        })
+    }
+
+    /// Bracket an opening signal's order is sent with, if it carries both
+    /// exits and brackets can be placed
+    fn signal_bracket(&self, signal: &ExecutionSignal, order: &Order) -> Option<Bracket> {
+        if signal.signal_type != SignalType::Open || order.reduce_only || !self.brackets.is_enabled() {
+            return None;
+        }
+        Some(Bracket {
+            stop_loss: signal.stop_loss?.as_decimal(),
+            take_profit: signal.take_profit?.as_decimal(),
         })
     }
 
+    /// Resize or cancel the position's bracket after a fill changed its size
+    async fn reconcile_bracket(&self, strategy_id: Uuid, symbol: &Symbol) {
+        let position = self.net_position(strategy_id, symbol).await;
+        match self.brackets.reconcile(strategy_id, symbol, position).await {
+            Ok(Some(change)) => log::info!("Bracket on {} adjusted: {:?}", symbol.as_str(), change),
+            Ok(None) => {}
+            Err(e) => log::warn!("Failed to adjust bracket on {}: {}", symbol.as_str(), e),
+        }
+    }
+
     /// Process execution signal
-    #[allow(dead_code)]
     async fn process_signal(&self, signal: ExecutionSignal) -> Result<()> {
         log::info!("Processing signal: {:?} for strategy {:?}",
                   signal.signal_type, signal.strategy_id);
 
-        // Check if strategy is active
-        let strategies = self.strategies.read().await;
-        if let Some(strategy) = strategies.get(&signal.strategy_id.to_string()) {
-            if strategy.status != StrategyStatus::Active {
-                log::warn!("Strategy {} is not active (status: {:?})",
-                          signal.strategy_id, strategy.status);
-                return Ok(());
-            }
-        } else {
-            log::warn!("Strategy {} not found", signal.strategy_id);
+        if let Some(reason) = self.inactive_reason(signal.strategy_id).await {
+            log::warn!("{}", reason);
             return Ok(());
         }
 
-        match signal.signal_type {
-            SignalType::Open | SignalType::Modify => {
-                self.execute_open_signal(signal).await?;
+        match self.order_request(&signal).await {
+            Ok((request, None)) => {
+                self.execute(request, Some(signal)).await?;
             }
-            SignalType::Close | SignalType::PartialClose => {
-                self.execute_close_signal(signal).await?;
+            Ok((request, Some(twap))) => {
+                self.spawn_twap(request, twap, signal);
             }
-            SignalType::StopLoss | SignalType::TakeProfit | SignalType::RiskManagement => {
-                self.execute_risk_signal(signal).await?;
+            Err(reason) => {
+                log::warn!("Signal {} not executed: {}", signal.signal_id, reason);
             }
         }
 
         Ok(())
     }
 
-    /// Execute open position signal
-    #[allow(dead_code)]
-    async fn execute_open_signal(&self, signal: ExecutionSignal) -> Result<()> {
-        if let (Some(side), Some(price)) = (signal.side, signal.price) {
-            let request = ExecutionRequest {
-                id: Uuid::new_v4(),
-                strategy_id: signal.strategy_id,
-                symbol: signal.symbol,
-                side,
-                order_type: OrderType::Limit,
-                quantity: signal.quantity,
-                price: Some(price),
-                time_in_force: TimeInForce::GoodTillCancel,
-                reduce_only: false,
-                post_only: false,
-            };
-
-            let _result = self.execute_order(request).await?;
+    /// Why the engine would ignore signals from a strategy, if it would
+    async fn inactive_reason(&self, strategy_id: Uuid) -> Option<String> {
+        let strategies = self.strategies.read().await;
+        match strategies.get(&strategy_id.to_string()) {
+            Some(strategy) if strategy.status == StrategyStatus::Active => None,
+            Some(strategy) => Some(format!(
+                "Strategy {} is not active (status: {:?})",
+                strategy_id, strategy.status
+            )),
+            None => Some(format!("Strategy {} not found", strategy_id)),
         }
-        Ok(())
     }
 
-    /// Execute close position signal
-    #[allow(dead_code)]
-    async fn execute_close_signal(&self, signal: ExecutionSignal) -> Result<()> {
-        let positions = self.positions.read().await;
-
-        if let Some(position) = positions.get(&format!("{}-{}",
-                                                   signal.strategy_id, signal.symbol.as_str())) {
-            let close_side = match position.side {
+    /// Order request a signal turns into, or why it produces none
+    ///
+    /// The strategy's execution policy picks the order type; by default opens
+    /// are limit orders at the signal price and closes are market orders.
+    /// Closes and risk exits are reduce-only against the strategy's open
+    /// position; risk exits always go out at market and are never held for
+    /// fat-finger confirmation. Orders the policy sends to a TWAP come with
+    /// its config, and the request describes their child orders.
+    async fn order_request(&self, signal: &ExecutionSignal) -> std::result::Result<(ExecutionRequest, Option<TwapConfig>), String> {
+        let (purpose, side, quantity) = if matches!(signal.signal_type, SignalType::Open | SignalType::Modify) {
+            let side = signal.side.ok_or("Open signal has no side")?;
+            (OrderPurpose::Open, side, signal.quantity)
+        } else {
+            let positions = self.positions.read().await;
+            let key = format!("{}-{}", signal.strategy_id, signal.symbol.as_str());
+            let position = positions
+                .get(&key)
+                .ok_or_else(|| format!("No open {} position to close", signal.symbol.as_str()))?;
+            let side = match position.side {
                 PositionSide::Long => OrderSide::Sell,
                 PositionSide::Short => OrderSide::Buy,
                 PositionSide::Net => {
@@ -285,69 +1088,309 @@ impl StrategyExecutionEngine {
                     }
                 }
             };
-
-            let close_quantity = if signal.signal_type == SignalType::PartialClose {
+            if signal.side.is_some_and(|wanted| wanted != side) {
+                let direction = if side == OrderSide::Sell { "short" } else { "long" };
+                return Err(format!("No open {} {} position to close", direction, signal.symbol.as_str()));
+            }
+            let quantity = if signal.signal_type == SignalType::PartialClose {
                 signal.quantity
             } else {
                 position.quantity
             };
-
-            let request = ExecutionRequest {
-                id: Uuid::new_v4(),
-                strategy_id: signal.strategy_id,
-                symbol: signal.symbol,
-                side: close_side,
-                order_type: OrderType::Market,
-                quantity: close_quantity,
-                price: None,
-                time_in_force: TimeInForce::ImmediateOrCancel,
-                reduce_only: true,
-                post_only: false,
+            let purpose = match signal.signal_type {
+                SignalType::StopLoss | SignalType::TakeProfit | SignalType::RiskManagement => OrderPurpose::Exit,
+                _ => OrderPurpose::Close,
             };
+            (purpose, side, quantity)
+        };
 
-            let _result = self.execute_order(request).await?;
-        }
+        let last_price = self.last_price(&signal.symbol).await;
+        let policy = self.execution_policies.policy(signal.strategy_id);
+        let route = policy
+            .route(purpose, side, quantity, signal.price, last_price)
+            .map_err(|e| e.to_string())?;
+        let (plan, twap) = match route {
+            ExecutionRoute::Direct(plan) => (plan, None),
+            ExecutionRoute::Twap(config) => {
+                let price = match config.order_type {
+                    OrderType::Market => None,
+                    _ => signal.price.or(last_price),
+                };
+                (OrderPlan { order_type: config.order_type, price }, Some(config))
+            }
+        };
 
-        Ok(())
+        let request = ExecutionRequest {
+            id: Uuid::new_v4(),
+            strategy_id: signal.strategy_id,
+            symbol: signal.symbol.clone(),
+            side,
+            order_type: plan.order_type,
+            quantity,
+            price: plan.price,
+            time_in_force: match plan.order_type {
+                OrderType::Market | OrderType::Ioc => TimeInForce::ImmediateOrCancel,
+                OrderType::Fok => TimeInForce::FillOrKill,
+                _ => TimeInForce::GoodTillCancel,
+            },
+            reduce_only: purpose != OrderPurpose::Open,
+            post_only: plan.order_type == OrderType::PostOnly,
+            // Emergency exits are never held for confirmation
+            confirmed: purpose == OrderPurpose::Exit,
+            signal_id: Some(signal.signal_id),
+            // Only orders that rest on the book can outlive their welcome
+            expires_at: match plan.order_type {
+                OrderType::Limit | OrderType::PostOnly => policy.expiry(Utc::now()),
+                _ => None,
+            },
+        };
+        Ok((request, twap))
     }
 
-    /// Execute risk management signal
-    #[allow(dead_code)]
-    async fn execute_risk_signal(&self, signal: ExecutionSignal) -> Result<()> {
-        // High-priority execution - use market orders
-        let positions = self.positions.read().await;
+    /// Work `request` in equal slices over the TWAP's duration, sending the
+    /// last one at market when the config asks for it
+    ///
+    /// Slices that fail are logged and their size carried into the next one.
+    /// The TWAP stops early once the strategy is no longer active.
+    fn spawn_twap(&self, request: ExecutionRequest, config: TwapConfig, signal: ExecutionSignal) {
+        let engine = self.clone();
+        let interval = std::time::Duration::from_secs(config.slice_interval_seconds.max(1) as u64);
+        let slices = (config.duration_minutes as u64 * 60 / interval.as_secs()).max(1);
+        log::info!(
+            "Working {} {} over {} TWAP slices for signal {}",
+            request.quantity, request.symbol.as_str(), slices, signal.signal_id
+        );
 
-        if let Some(position) = positions.get(&format!("{}-{}",
-                                                   signal.strategy_id, signal.symbol.as_str())) {
-            let risk_side = match position.side {
-                PositionSide::Long => OrderSide::Sell,
-                PositionSide::Short => OrderSide::Buy,
-                PositionSide::Net => {
-                    if position.quantity.as_decimal() > Decimal::ZERO {
-                        OrderSide::Sell
-                    } else {
-                        OrderSide::Buy
+        tokio::spawn(async move {
+            let mut remaining = request.quantity.as_decimal();
+            for slice in 0..slices {
+                if slice > 0 {
+                    tokio::time::sleep(interval).await;
+                }
+                if let Some(reason) = engine.inactive_reason(request.strategy_id).await {
+                    log::warn!("TWAP for signal {} stopped: {}", signal.signal_id, reason);
+                    return;
+                }
+
+                let last = slice + 1 == slices;
+                let size = if last { remaining } else { remaining / Decimal::from(slices - slice) };
+                let Ok(quantity) = Quantity::new(size) else {
+                    continue;
+                };
+                let mut child = ExecutionRequest { id: Uuid::new_v4(), quantity, ..request.clone() };
+                if child.expires_at.is_some() {
+                    // Each slice rests for its own time to live
+                    child.expires_at = engine.execution_policies.policy(child.strategy_id).expiry(Utc::now());
+                }
+                if last && config.aggressive_on_final {
+                    child.order_type = OrderType::Market;
+                    child.price = None;
+                    child.time_in_force = TimeInForce::ImmediateOrCancel;
+                    child.post_only = false;
+                }
+
+                match engine.execute(child, Some(signal.clone())).await {
+                    Ok(result) if result.success => remaining -= size,
+                    Ok(result) => log::warn!(
+                        "TWAP slice {}/{} for signal {} not sent: {}",
+                        slice + 1, slices, signal.signal_id, result.error.unwrap_or_default()
+                    ),
+                    Err(e) => log::warn!(
+                        "TWAP slice {}/{} for signal {} failed: {}",
+                        slice + 1, slices, signal.signal_id, e
+                    ),
+                }
+            }
+        });
+    }
+
+    /// Run a signal through every step of the live pipeline without trading
+    ///
+    /// Nothing is sent, stored or emitted: the order is filled on paper at its
+    /// limit price, or at `market_price` for market orders and marketable
+    /// limits. Unlike the live path, the order is also checked against
+    /// `risk` with `portfolio` so rejections show up before limits do.
+    pub async fn simulate_pipeline(
+        &self,
+        signal: ExecutionSignal,
+        risk: &PreTradeValidator,
+        portfolio: &PortfolioState,
+        market_price: Option<Price>,
+    ) -> PipelineSimulation {
+        let mut sim = PipelineSimulation {
+            signal_id: signal.signal_id,
+            stages: Vec::new(),
+            stopped_at: None,
+            order: None,
+            trade: None,
+        };
+
+        let inactive = self.inactive_reason(signal.strategy_id).await;
+        let strategy_ok = inactive.is_none();
+        if !sim.record(
+            "strategy",
+            strategy_ok,
+            inactive.unwrap_or_else(|| "Strategy is active".to_string()),
+            serde_json::json!({ "strategy_id": signal.strategy_id }),
+        ) {
+            return sim;
+        }
+
+        let (request, twap) = match self.order_request(&signal).await {
+            Ok(routed) => routed,
+            Err(reason) => {
+                sim.record("order", false, reason, serde_json::Value::Null);
+                return sim;
+            }
+        };
+        let mut detail = format!(
+            "{:?} {:?} {} {}",
+            request.order_type,
+            request.side,
+            request.quantity,
+            request.symbol.as_str()
+        );
+        if let Some(twap) = &twap {
+            detail.push_str(&format!(" via TWAP over {} minutes", twap.duration_minutes));
+        }
+        sim.record("order", true, detail, serde_json::to_value(&request).unwrap_or_default());
+
+        if let Err(e) = self.validate_order_request(&request) {
+            sim.record("validation", false, e.to_string(), serde_json::Value::Null);
+            return sim;
+        }
+        sim.record("validation", true, "Quantity and price are valid", serde_json::Value::Null);
+
+        let mut order = Order::new(
+            request.strategy_id,
+            request.symbol.clone(),
+            request.side,
+            request.order_type,
+            request.quantity,
+            request.price,
+        )
+        .with_signal(signal.signal_id);
+        if let Some(expires_at) = request.expires_at {
+            order = order.with_expiry(expires_at);
+        }
+
+        let position = if request.reduce_only {
+            order = order.with_reduce_only();
+            Some(self.net_position(request.strategy_id, &order.symbol).await)
+        } else {
+            None
+        };
+
+        match risk.validate_order(&order, portfolio) {
+            Ok(result) => {
+                let detail = match result.violations.len() {
+                    0 => "No risk limits breached".to_string(),
+                    n => format!("{} risk rule(s) flagged", n),
+                };
+                // Violations, and the strategy's marginal VaR under a VaR budget
+                let data = serde_json::to_value(&result).unwrap_or_default();
+                if !sim.record("risk", result.is_valid(), detail, data) {
+                    return sim;
+                }
+            }
+            Err(e) => {
+                sim.record("risk", false, e.to_string(), serde_json::Value::Null);
+                return sim;
+            }
+        }
+
+        if let Some(throttle) = &self.drawdown_throttle {
+            match throttle.read().await.apply(&mut order, None) {
+                Ok(decision) => {
+                    let data = serde_json::to_value(decision).unwrap_or_default();
+                    if !sim.record("drawdown", !decision.is_blocked(), decision.describe(), data) {
+                        return sim;
                     }
                 }
-            };
+                Err(e) => {
+                    sim.record("drawdown", false, e.to_string(), serde_json::Value::Null);
+                    return sim;
+                }
+            }
+        }
 
-            let request = ExecutionRequest {
-                id: Uuid::new_v4(),
-                strategy_id: signal.strategy_id,
-                symbol: signal.symbol,
-                side: risk_side,
-                order_type: OrderType::Market,
-                quantity: position.quantity,
-                price: None,
-                time_in_force: TimeInForce::ImmediateOrCancel,
-                reduce_only: true,
-                post_only: false,
-            };
+        // The live checks, without using up quota or reserving balance
+        if request.confirmed
+            && let Some(guard) = self.checks.fat_finger()
+        {
+            guard.confirm(order.id);
+        }
+        let report = self.checks.preview(&mut order, None, position).await;
+        sim.stages.extend(pre_trade_stages(&report, request.confirmed, true));
+        if report.refused.is_some() {
+            sim.stopped_at = sim.stages.last().map(|stage| stage.stage.clone());
+            return sim;
+        }
 
-            let _result = self.execute_order(request).await?;
+        let limit = order.price.map(|p| p.as_decimal());
+        let market = market_price.map(|p| p.as_decimal());
+        let fill_price = match (order.side, limit, market) {
+            (_, None, Some(market)) => Some(market),
+            (OrderSide::Buy, Some(limit), Some(market)) if market <= limit => Some(market),
+            (OrderSide::Sell, Some(limit), Some(market)) if market >= limit => Some(market),
+            (_, Some(_), Some(_)) => None,
+            // Without a market price a limit order is assumed to fill at its limit
+            (_, limit, None) => limit,
+        };
+
+        order.mark_submitted(format!("paper_{}", Uuid::new_v4()));
+        match fill_price.map(Price::new) {
+            Some(Ok(price)) => {
+                order.update_fill(order.quantity, price);
+                let trade = self.create_trade_record(&order, order.quantity, price, None);
+                sim.record(
+                    "fill",
+                    true,
+                    format!("Filled {} at {}", order.quantity, price),
+                    serde_json::to_value(&trade).unwrap_or_default(),
+                );
+                sim.trade = Some(trade);
+            }
+            Some(Err(e)) => {
+                sim.record("fill", false, e.to_string(), serde_json::Value::Null);
+            }
+            None if limit.is_some() => {
+                sim.record(
+                    "fill",
+                    true,
+                    format!(
+                        "Would rest on the book at {}, market is {}",
+                        limit.unwrap_or_default(),
+                        market.unwrap_or_default()
+                    ),
+                    serde_json::Value::Null,
+                );
+            }
+            None => {
+                sim.record(
+                    "fill",
+                    false,
+                    "No market price to fill a market order at",
+                    serde_json::Value::Null,
+                );
+            }
         }
+        sim.order = Some(order);
+        sim
+    }
 
-        Ok(())
+    /// Signed size of the strategy's open position in `symbol`, negative when short
+    async fn net_position(&self, strategy_id: Uuid, symbol: &Symbol) -> Decimal {
+        let key = format!("{}-{}", strategy_id, symbol.as_str());
+        self.positions
+            .read()
+            .await
+            .get(&key)
+            .map(|position| match position.side {
+                PositionSide::Short => -position.quantity.as_decimal(),
+                PositionSide::Long | PositionSide::Net => position.quantity.as_decimal(),
+            })
+            .unwrap_or(Decimal::ZERO)
     }
 
     /// Validate order request
@@ -365,46 +1408,247 @@ impl StrategyExecutionEngine {
         Ok(())
     }
 
-    /// Submit order to OKX (mock implementation)
-    async fn submit_to_okx(&self, _order: &Order) -> Result<String> {
-        // In real implementation, this would call OKX API
-        Ok(format!("okx_{}", Uuid::new_v4()))
+    /// Record the outcome of sending `order` in the intent log
+    ///
+    /// Validation failures never reached OKX and orders it refused never
+    /// rest there; anything else may have, so the intent stays pending for
+    /// startup recovery to settle.
+    fn resolve_intent(&self, order: &Order, sent: &Result<String>) {
+        let Some(log) = self.checks.intent_log() else {
+            return;
+        };
+        let (status, exchange_id, reason) = match sent {
+            Ok(exchange_id) => (IntentStatus::Acknowledged, Some(exchange_id.clone()), None),
+            Err(e @ (Error::ValidationError(_) | Error::ExchangeRejected { .. })) => {
+                (IntentStatus::Failed, None, Some(e.to_string()))
+            }
+            Err(_) => return,
+        };
+        if let Err(e) = log.resolve(&order.client_order_id, status, exchange_id, reason) {
+            log::error!("Failed to resolve order intent {}: {}", order.client_order_id, e);
+        }
     }
 
-    /// Simulate order execution
-    async fn simulate_execution(&self, order: &mut Order) -> Result<bool> {
-        // Simulate random execution for demo
-        let success = rand::random::<f64>() > 0.1; // 90% success rate
+    /// Place `order` on the exchange, returning the exchange order ID
+    async fn submit_to_exchange(&self, order: &Order, timeline: &mut OrderTimeline) -> Result<String> {
+        let exchange = self.exchange()?;
+        timeline.mark(LatencyMark::Serialized);
+        timeline.mark(LatencyMark::Sent);
+        let exchange_order_id = exchange.place_order(order).await?;
+        timeline.mark(LatencyMark::Acknowledged);
+        Ok(exchange_order_id)
+    }
 
-        if success {
-            let fill_qty = order.quantity;
-            let fill_price = order.price.unwrap_or_else(|| {
-                // Simulate market price for market orders
-                Price::new(Decimal::from_f64_retain(45000.0).unwrap()).unwrap()
-            });
+    fn exchange(&self) -> Result<&Arc<dyn ExchangeAdapter>> {
+        self.exchange
+            .as_ref()
+            .ok_or_else(|| Error::ConfigError("No exchange configured to send orders to".to_string()))
+    }
+
+    /// Apply the exchange's report on one of the engine's orders
+    ///
+    /// New fills become trades and move the position; an order the exchange
+    /// ended without filling is reported to its strategy as rejected.
+    /// Returns whether the order was one of the engine's.
+    pub async fn on_exchange_order(&self, update: &ExchangeOrder) -> Result<bool> {
+        let (order, fill) = {
+            let mut orders = self.orders.write().await;
+            let Some(order) = orders.values_mut().find(|o| o.client_order_id == update.client_order_id) else {
+                return Ok(false);
+            };
+            if order.is_terminal() {
+                return Ok(true);
+            }
+
+            // Reports carry cumulative fills; the new fill is the difference
+            let filled_before = order.filled_quantity.as_decimal();
+            let fill = match update.avg_fill_price {
+                Some(avg_price) if update.filled_quantity > filled_before => {
+                    let quantity = update.filled_quantity - filled_before;
+                    let value_before = order.avg_fill_price.map_or(Decimal::ZERO, |p| p.as_decimal()) * filled_before;
+                    let price = (avg_price * update.filled_quantity - value_before) / quantity;
+                    order.update_fill(Quantity::new(update.filled_quantity)?, Price::new(avg_price)?);
+                    Some((Quantity::new(quantity)?, Price::new(price)?))
+                }
+                _ => None,
+            };
+            if matches!(update.status, OrderStatus::Cancelled | OrderStatus::Rejected | OrderStatus::Failed)
+                && !order.is_filled()
+            {
+                order.set_status(update.status);
+            }
+            self.journal_order(order);
+            self.settle_reservation(order);
+            (order.clone(), fill)
+        };
+
+        let booked = match fill {
+            Some((quantity, price)) => self.record_fill(&order, quantity, price).await.map(|_| ()),
+            None => Ok(()),
+        };
+        if order.is_terminal() && order.filled_quantity.is_zero() {
+            let reason = format!("Ended {:?} on the exchange without filling", order.status);
+            self.report_rejection(&order, reason).await;
+        }
+        // The exit executed whether or not its fill could be booked
+        self.settle_scale_out_exit(&order).await;
+        booked.map(|_| true)
+    }
+
+    /// Book a fill of `order`: record the trade, move the position, resize its
+    /// bracket and tell the strategy and the monitor
+    async fn record_fill(&self, order: &Order, quantity: Quantity, price: Price) -> Result<Trade> {
+        let trade = self.create_trade_record(order, quantity, price, None);
+        self.trades.write().await.push(trade.clone());
+        self.update_positions_from_trade(&trade).await?;
+        self.reconcile_bracket(order.strategy_id, &order.symbol).await;
+        let _ = self.order_update_tx.send(StrategyInput::OrderFill(order.clone()));
+
+        if let Some(monitor) = &self.monitor {
+            let stop_out = self.decisions.read().await
+                .get(&order.id)
+                .and_then(|decision| decision.signal.as_ref())
+                .is_some_and(|signal| signal.signal_type == SignalType::StopLoss);
+            let _ = monitor.emit_trade_executed(
+                order.strategy_id.to_string(),
+                trade.id.to_string(),
+                trade.symbol.as_str().to_string(),
+                format!("{:?}", trade.side),
+                trade.quantity.as_decimal().to_f64().unwrap_or(0.0),
+                trade.price.as_decimal().to_f64().unwrap_or(0.0),
+                stop_out,
+            ).await;
+        }
+        Ok(trade)
+    }
+
+    /// Tell the strategy and the monitor that `order` will not trade
+    async fn report_rejection(&self, order: &Order, reason: String) {
+        if let Some(monitor) = &self.monitor {
+            let _ = monitor.emit_error(
+                order.strategy_id.to_string(),
+                format!("Order {} not executed: {}", order.id, reason),
+            ).await;
+        }
+        let _ = self.order_update_tx.send(StrategyInput::OrderReject {
+            order: order.clone(),
+            reason,
+        });
+    }
 
-            order.update_fill(fill_qty, fill_price);
-            Ok(true)
+    /// Complete or re-arm the scale-out exit behind `order` once it has ended
+    async fn settle_scale_out_exit(&self, order: &Order) {
+        if !order.is_terminal() {
+            return;
+        }
+        let Some(exit_id) = self.scale_out_exits.write().await.remove(&order.id) else {
+            return;
+        };
+        if order.is_filled() {
+            self.scale_out.exit_filled(exit_id);
         } else {
-            order.set_status(OrderStatus::Rejected);
-            Ok(false)
+            log::warn!("Scale-out exit order {} ended {:?}, watching the exit again", order.id, order.status);
+            self.scale_out.exit_failed(exit_id);
         }
     }
 
-    /// Create trade record from order
-    fn create_trade_record(&self, order: &Order) -> Result<Trade> {
-        let trade = Trade::new(
+    /// Trade record of a fill of `quantity` at `price` of `order`
+    ///
+    /// Uses the exchange-reported fee when there is one and estimates it from
+    /// the fee schedule otherwise.
+    fn create_trade_record(&self, order: &Order, quantity: Quantity, price: Price, fee: Option<FillFee>) -> Trade {
+        let fee = fee.unwrap_or_else(|| {
+            self.fee_schedule.estimate(order, quantity.as_decimal(), price.as_decimal())
+        });
+
+        let mut trade = Trade::new(
             order.strategy_id,
             order.client_order_id.clone(),
             order.symbol.clone(),
             order.side,
             order.order_type,
-            order.filled_quantity,
-            order.avg_fill_price.unwrap(),
-            Decimal::from_f64_retain(0.001).unwrap(), // 0.1% commission
-        );
+            quantity,
+            price,
+            Decimal::ZERO,
+        )
+        .with_commission(fee.commission, fee.currency);
+        trade.okx_order_id = order.okx_order_id.clone();
+        trade
+    }
 
-        Ok(trade)
+    /// Replace the estimated fee of an order's latest fill with what OKX
+    /// charged for it
+    ///
+    /// Call once [`Self::on_exchange_order`] has booked the fill the update
+    /// reports; an update delivered twice is counted once. Returns whether a
+    /// trade for the update's order was found.
+    pub async fn record_exchange_fee(&self, update: &OrderData) -> Result<bool> {
+        let Some(fee) = update.fill_fee().map_err(|e| Error::DecimalError(e.to_string()))? else {
+            return Ok(false);
+        };
+
+        {
+            let mut fees = self.exchange_fees.write().await;
+            let fills = fees.entry(update.ord_id.clone()).or_default();
+            let counted = !fills.insert(update.acc_fill_sz.clone());
+            if matches!(update.state.as_str(), "filled" | "canceled" | "mmp_canceled") {
+                fees.remove(&update.ord_id);
+            }
+            if counted {
+                return Ok(true);
+            }
+        }
+
+        let mut trades = self.trades.write().await;
+        match trades.iter_mut().rev().find(|t| t.okx_order_id.as_deref() == Some(update.ord_id.as_str())) {
+            Some(trade) => {
+                trade.commission = fee.commission;
+                trade.commission_asset = fee.currency;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Fee totals for `trades` in the reporting currency
+    ///
+    /// Fees that cannot be converted are left out of the totals and listed
+    /// under `unconverted`, still counted in `by_currency`.
+    async fn fee_summary(&self, trades: &[&Trade]) -> serde_json::Value {
+        let rates = self.fee_rates.read().await;
+        let mut by_currency: HashMap<String, Decimal> = HashMap::new();
+        let mut unconverted: Vec<String> = Vec::new();
+        let (mut paid, mut rebates) = (Decimal::ZERO, Decimal::ZERO);
+
+        for trade in trades {
+            *by_currency.entry(trade.commission_asset.clone()).or_default() += trade.commission;
+
+            let converted = trade.commission_in(&self.reporting_currency).or_else(|| {
+                rates
+                    .get(&trade.commission_asset.to_uppercase())
+                    .map(|rate| trade.commission * *rate)
+            });
+            match converted {
+                Some(fee) if fee < Decimal::ZERO => rebates -= fee,
+                Some(fee) => paid += fee,
+                None if !unconverted.contains(&trade.commission_asset) => {
+                    unconverted.push(trade.commission_asset.clone());
+                }
+                None => {}
+            }
+        }
+
+        serde_json::json!({
+            "currency": self.reporting_currency,
+            "paid": paid.to_string(),
+            "rebates": rebates.to_string(),
+            "net": (paid - rebates).to_string(),
+            "by_currency": by_currency.iter()
+                .map(|(ccy, total)| (ccy.clone(), serde_json::Value::String(total.to_string())))
+                .collect::<serde_json::Map<_, _>>(),
+            "unconverted": unconverted,
+            "rebated_fills": trades.iter().filter(|t| t.is_rebate()).count(),
+        })
     }
 
     /// Update positions from trade execution
@@ -414,7 +1658,22 @@ impl StrategyExecutionEngine {
 
         if let Some(position) = positions.get_mut(&position_key) {
             // Update existing position
+            let side = position.side;
             self.update_existing_position(position, trade)?;
+
+            // A closed position is dropped; neither it nor a flipped one has
+            // anything left to scale out of
+            let closed = position.is_closed();
+            if closed || position.side != side {
+                if closed {
+                    positions.remove(&position_key);
+                }
+                drop(positions);
+                self.scale_out
+                    .detach(trade.strategy_id, &trade.symbol)
+                    .await
+                    .map_err(|e| Error::Internal(e.to_string()))?;
+            }
         } else {
             // Create new position
             let new_position = self.create_position_from_trade(trade)?;
@@ -425,6 +1684,10 @@ impl StrategyExecutionEngine {
     }
 
     /// Update existing position from trade
+    ///
+    /// A closing trade realizes P&L on the part it closes before the position
+    /// changes; whatever exceeds the position opens the opposite side at the
+    /// trade price.
     fn update_existing_position(&self, position: &mut Position, trade: &Trade) -> Result<()> {
         let trade_qty = trade.quantity.as_decimal();
         let trade_price = trade.price.as_decimal();
@@ -439,41 +1702,41 @@ impl StrategyExecutionEngine {
             _ => false,
         };
 
-        let (new_qty, new_entry_price) = if is_same_side {
+        if is_same_side {
             // Adding to position
             let total_value = (current_qty * current_entry_price) + (trade_qty * trade_price);
             let new_total_qty = current_qty + trade_qty;
-            let new_entry_price = if new_total_qty != Decimal::ZERO {
-                total_value / new_total_qty
-            } else {
-                Decimal::ZERO
-            };
 
-            (new_total_qty, new_entry_price)
+            position.quantity = Quantity::new(new_total_qty)
+                .map_err(|e| Error::ValidationError(e.to_string()))?;
+            position.avg_entry_price = Price::new(total_value / new_total_qty)
+                .map_err(|e| Error::ValidationError(e.to_string()))?;
         } else {
-            // Reducing position
-            let new_total_qty = current_qty - trade_qty;
-            let new_entry_price = if new_total_qty != Decimal::ZERO {
-                current_entry_price // Keep original entry price for remaining position
-            } else {
-                Decimal::ZERO
-            };
-
-            (new_total_qty, new_entry_price)
-        };
-
-        position.quantity = Quantity::new(new_qty)
-            .map_err(|e| Error::ValidationError(e.to_string()))?;
-        position.avg_entry_price = Price::new(new_entry_price)
-            .map_err(|e| Error::ValidationError(e.to_string()))?;
-        position.last_updated = Utc::now();
-
-        // Calculate realized PnL for closing trades
-        if (trade.side == OrderSide::Buy && position.side == PositionSide::Short) ||
-           (trade.side == OrderSide::Sell && position.side == PositionSide::Long) {
-            let realized_pnl = self.calculate_realized_pnl(position, trade)?;
+            // Realized against the entry price the closed part was opened at
+            let closed_qty = trade_qty.min(current_qty);
+            let realized_pnl = self.calculate_realized_pnl(position, trade_price, closed_qty)?;
             position.realized_pnl += realized_pnl;
+
+            let daily_loss = self.checks.gate().daily_loss();
+            daily_loss.record_realized(&daily_loss.account_for(trade.strategy_id), realized_pnl, trade.executed_at);
+
+            let excess = trade_qty - closed_qty;
+            if excess > Decimal::ZERO {
+                position.side = match position.side {
+                    PositionSide::Long => PositionSide::Short,
+                    PositionSide::Short => PositionSide::Long,
+                    PositionSide::Net => PositionSide::Net,
+                };
+                position.quantity = Quantity::new(excess)
+                    .map_err(|e| Error::ValidationError(e.to_string()))?;
+                position.avg_entry_price = trade.price;
+            } else {
+                // Reducing position keeps the entry price, even once closed
+                position.quantity = Quantity::new(current_qty - closed_qty)
+                    .map_err(|e| Error::ValidationError(e.to_string()))?;
+            }
         }
+        position.last_updated = Utc::now();
 
         Ok(())
     }
@@ -496,18 +1759,16 @@ impl StrategyExecutionEngine {
         Ok(position)
     }
 
-    /// Calculate realized PnL for trade
-    fn calculate_realized_pnl(&self, position: &Position, trade: &Trade) -> Result<Decimal> {
+    /// Calculate realized PnL of closing `quantity` of the position at `exit_price`
+    fn calculate_realized_pnl(&self, position: &Position, exit_price: Decimal, quantity: Decimal) -> Result<Decimal> {
         let entry_price = position.avg_entry_price.as_decimal();
-        let exit_price = trade.price.as_decimal();
-        let trade_qty = trade.quantity.as_decimal();
 
         let pnl = match position.side {
             PositionSide::Long => {
-                (exit_price - entry_price) * trade_qty
+                (exit_price - entry_price) * quantity
             }
             PositionSide::Short => {
-                (entry_price - exit_price) * trade_qty
+                (entry_price - exit_price) * quantity
             }
             PositionSide::Net => {
                 // Complex calculation for net positions
@@ -537,23 +1798,188 @@ impl StrategyExecutionEngine {
         }
     }
 
+    /// A trade with the order it filled and the signal and checks behind it
+    pub async fn trade_origin(&self, trade_id: Uuid) -> Option<TradeOrigin> {
+        let trade = self.trades.read().await.iter().find(|t| t.id == trade_id).cloned()?;
+        let order = self.orders.read().await
+            .values()
+            .find(|o| o.client_order_id == trade.client_order_id)
+            .cloned();
+        let decision = match &order {
+            Some(order) => self.decisions.read().await.get(&order.id).cloned(),
+            None => None,
+        };
+
+        Some(TradeOrigin { trade, order, decision })
+    }
+
+    /// Replace orders, positions and trades (oldest first) with a snapshot's
+    pub async fn restore_state(&self, orders: Vec<Order>, positions: Vec<Position>, trades: Vec<Trade>) {
+        *self.orders.write().await = orders
+            .into_iter()
+            .map(|o| (o.id.to_string(), o))
+            .collect();
+        *self.positions.write().await = positions
+            .into_iter()
+            .map(|p| (format!("{}-{}", p.strategy_id, p.symbol.as_str()), p))
+            .collect();
+        *self.trades.write().await = trades;
+    }
+
     /// Cancel an order
+    ///
+    /// Fails when the order's strategy is over its cancel quota or the
+    /// exchange refuses the cancel.
     pub async fn cancel_order(&self, order_id: &str) -> Result<()> {
+        let order = self.orders.read().await.get(order_id).filter(|o| o.is_active()).cloned();
+        let Some(order) = order else {
+            return Ok(());
+        };
+        if let GateDecision::Throttled(reason) = self.checks.gate().check_cancel(order.strategy_id) {
+            return Err(Error::Internal(reason));
+        }
+        self.cancel_at_exchange(&order).await?;
+
+        // Notify monitor
+        if let Some(monitor) = &self.monitor {
+            let _ = monitor.emit_error(
+                order.strategy_id.to_string(),
+                format!("Order {} cancelled", order_id),
+            ).await;
+        }
+        Ok(())
+    }
+
+    /// Cancel `order` on the exchange, ending it here once the exchange has
+    /// acknowledged the cancel
+    async fn cancel_at_exchange(&self, order: &Order) -> Result<()> {
+        self.exchange()?.cancel_order(&order.symbol, &order.client_order_id).await?;
+
         let mut orders = self.orders.write().await;
-        if let Some(order) = orders.get_mut(order_id) {
-            if order.is_active() {
-                order.set_status(OrderStatus::Cancelled);
-
-                // Notify monitor
-                if let Some(monitor) = &self.monitor {
-                    let _ = monitor.emit_error(
-                        order.strategy_id.to_string(),
-                        format!("Order {} cancelled", order_id),
-                    ).await;
-                }
+        // A fill reported meanwhile may have ended it already
+        let Some(order) = orders.get_mut(&order.id.to_string()).filter(|o| o.is_active()) else {
+            return Ok(());
+        };
+        order.set_status(OrderStatus::Cancelled);
+        self.journal_order(order);
+        self.settle_reservation(order);
+        let order = order.clone();
+        drop(orders);
+        self.settle_scale_out_exit(&order).await;
+        Ok(())
+    }
+
+    /// Cancel every active order on `symbol` on the exchange, returning the
+    /// cancelled order IDs
+    ///
+    /// Used when trading in the symbol halts, so cancels are not subject to
+    /// cancel quotas. Every order is tried; if any cancel fails, the error
+    /// names the orders still resting.
+    pub async fn cancel_orders_for_symbol(&self, symbol: &ea_okx_core::types::Symbol) -> Result<Vec<String>> {
+        let active: Vec<Order> = self.orders.read().await
+            .values()
+            .filter(|o| o.is_active() && &o.symbol == symbol)
+            .cloned()
+            .collect();
+        self.cancel_all_at_exchange(active, symbol.as_str()).await
+    }
+
+    /// Cancel every active order past its good-till-date, returning their IDs
+    ///
+    /// Like cleanup after a stop, expiries are not subject to cancel quotas.
+    /// An order ends here only once the exchange has acknowledged its cancel;
+    /// one whose cancel failed is retried on the next call.
+    pub async fn expire_due_orders(&self, now: DateTime<Utc>) -> Vec<String> {
+        let due: Vec<Order> = self.orders.read().await
+            .values()
+            .filter(|o| o.is_active() && o.is_expired(now))
+            .cloned()
+            .collect();
+
+        let mut expired = Vec::with_capacity(due.len());
+        for order in due {
+            if let Err(e) = self.cancel_at_exchange(&order).await {
+                log::warn!("Failed to cancel expired order {}: {}", order.id, e);
+                continue;
+            }
+            log::info!("Order {} reached its expiry and was cancelled", order.id);
+            if let Some(monitor) = &self.monitor {
+                let _ = monitor.emit_error(
+                    order.strategy_id.to_string(),
+                    format!("Order {} expired", order.id),
+                ).await;
             }
+            expired.push(order.id.to_string());
+        }
+        expired
+    }
+
+    /// Cancel every active order of `strategy_id` on the exchange, returning
+    /// the cancelled order IDs
+    ///
+    /// Cancels are not subject to the strategy's cancel quota: this is cleanup
+    /// after the strategy stopped running, not the strategy churning orders.
+    /// Every order is tried; if any cancel fails, the error names the orders
+    /// still resting.
+    pub async fn cancel_orders_for_strategy(&self, strategy_id: Uuid) -> Result<Vec<String>> {
+        let active: Vec<Order> = self.orders.read().await
+            .values()
+            .filter(|o| o.is_active() && o.strategy_id == strategy_id)
+            .cloned()
+            .collect();
+        self.cancel_all_at_exchange(active, &format!("strategy {}", strategy_id)).await
+    }
+
+    /// Cancel each of `orders` on the exchange, returning the cancelled order
+    /// IDs, or an error naming the orders of `scope` whose cancel failed
+    async fn cancel_all_at_exchange(&self, orders: Vec<Order>, scope: &str) -> Result<Vec<String>> {
+        let mut cancelled = Vec::with_capacity(orders.len());
+        let mut failed = Vec::new();
+        for order in orders {
+            match self.cancel_at_exchange(&order).await {
+                Ok(()) => cancelled.push(order.id.to_string()),
+                Err(e) => failed.push(format!("{} ({})", order.id, e)),
+            }
+        }
+        if !failed.is_empty() {
+            return Err(Error::ExchangeError(format!(
+                "Cancelled {} orders of {}, failed to cancel {}",
+                cancelled.len(),
+                scope,
+                failed.join(", ")
+            )));
+        }
+        Ok(cancelled)
+    }
+
+    /// Journal the order's latest step; the order itself is already stored,
+    /// so a journal failure is logged rather than failing the caller
+    fn journal_order(&self, order: &Order) {
+        if let Some(journal) = &self.order_journal
+            && let Err(e) = journal.observe(order)
+        {
+            log::error!("Failed to journal order {}: {}", order.id, e);
+        }
+    }
+
+    /// Shrink the order's balance reservation to its unfilled quantity,
+    /// releasing it once the order is filled or ended
+    fn settle_reservation(&self, order: &Order) {
+        let Some(reservations) = self.checks.reservations() else {
+            return;
+        };
+        if order.is_terminal() {
+            reservations.release(order.id);
+        } else {
+            reservations.fill(order.id, order.quantity.as_decimal(), order.filled_quantity.as_decimal());
+        }
+    }
+
+    /// Release the order's balance reservation after it failed to send
+    fn release_reservation(&self, order_id: Uuid) {
+        if let Some(reservations) = self.checks.reservations() {
+            reservations.release(order_id);
         }
-        Ok(())
     }
 
     /// Get strategy statistics
@@ -581,6 +2007,8 @@ impl StrategyExecutionEngine {
         let unrealized_pnl = strategy_positions.iter()
             .fold(Decimal::ZERO, |acc, p| acc + p.unrealized_pnl);
 
+        let fees = self.fee_summary(&strategy_trades).await;
+
         Ok(serde_json::json!({
             "strategy_id": strategy_id,
             "total_orders": strategy_orders.len(),
@@ -590,6 +2018,7 @@ impl StrategyExecutionEngine {
             "realized_pnl": total_pnl.to_string(),
             "unrealized_pnl": unrealized_pnl.to_string(),
             "total_pnl": (total_pnl + unrealized_pnl).to_string(),
+            "fees": fees,
             "win_rate": if strategy_trades.is_empty() { 0.0 } else {
                 strategy_trades.iter().filter(|t| {
                     t.realized_pnl.map_or(false, |pnl| pnl > Decimal::ZERO)
@@ -599,9 +2028,429 @@ impl StrategyExecutionEngine {
     }
 }
 
+#[async_trait]
+impl OrderCanceller for StrategyExecutionEngine {
+    async fn cancel_strategy_orders(&self, strategy_id: Uuid) -> ea_okx_strategy::Result<usize> {
+        Ok(self.cancel_orders_for_strategy(strategy_id).await?.len())
+    }
+}
+
 impl Default for StrategyExecutionEngine {
     fn default() -> Self {
         Self::new()
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ea_okx_core::exchange::{InstrumentInfo, MarketEvent, MarketSubscription};
+    use ea_okx_core::types::InstrumentKind;
+    use ea_okx_trading::{BreachAction, FatFingerConfig, FatFingerLimits};
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingExchange {
+        calls: Mutex<Vec<String>>,
+
+        /// Code the next placement is refused with
+        reject_next: Mutex<Option<&'static str>>,
+
+        /// Refuse every cancel
+        refuse_cancels: Mutex<bool>,
+    }
+
+    impl RecordingExchange {
+        fn calls(&self) -> Vec<String> {
+            self.calls.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl ExchangeAdapter for RecordingExchange {
+        fn name(&self) -> &'static str {
+            "recording"
+        }
+
+        async fn place_order(&self, order: &Order) -> Result<String> {
+            self.calls.lock().unwrap().push(format!("place {}", order.client_order_id));
+            match self.reject_next.lock().unwrap().take() {
+                Some(code) => Err(Error::ExchangeRejected {
+                    code: code.to_string(),
+                    message: "Order refused".to_string(),
+                }),
+                None => Ok(format!("EX-{}", order.client_order_id)),
+            }
+        }
+
+        async fn cancel_order(&self, _symbol: &Symbol, client_order_id: &str) -> Result<()> {
+            self.calls.lock().unwrap().push(format!("cancel {}", client_order_id));
+            if *self.refuse_cancels.lock().unwrap() {
+                return Err(Error::ExchangeUnavailable("Cancel timed out".to_string()));
+            }
+            Ok(())
+        }
+
+        async fn amend_order(
+            &self,
+            _symbol: &Symbol,
+            _client_order_id: &str,
+            _quantity: Option<Decimal>,
+            _price: Option<Decimal>,
+        ) -> Result<()> {
+            Ok(())
+        }
+
+        async fn order(&self, _symbol: &Symbol, _client_order_id: &str) -> Result<Option<ExchangeOrder>> {
+            Ok(None)
+        }
+
+        async fn instruments(&self, _kind: InstrumentKind) -> Result<Vec<InstrumentInfo>> {
+            Ok(Vec::new())
+        }
+
+        async fn subscribe(&self, _subscriptions: &[MarketSubscription]) -> Result<()> {
+            Ok(())
+        }
+
+        async fn next_event(&self) -> Result<Option<MarketEvent>> {
+            Ok(None)
+        }
+
+        async fn disconnect(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    fn engine_on(exchange: Arc<RecordingExchange>) -> StrategyExecutionEngine {
+        StrategyExecutionEngine::new().with_exchange(exchange)
+    }
+
+    fn request(strategy_id: Uuid, side: OrderSide, quantity: Decimal, price: Option<Decimal>) -> ExecutionRequest {
+        ExecutionRequest {
+            id: Uuid::new_v4(),
+            strategy_id,
+            symbol: Symbol::new("BTC-USDT").unwrap(),
+            side,
+            order_type: if price.is_some() { OrderType::Limit } else { OrderType::Market },
+            quantity: Quantity::new(quantity).unwrap(),
+            price: price.map(|p| Price::new(p).unwrap()),
+            time_in_force: TimeInForce::GoodTillCancel,
+            reduce_only: false,
+            post_only: false,
+            confirmed: true,
+            signal_id: None,
+            expires_at: None,
+        }
+    }
+
+    /// Report of `order` having filled `filled` in total at an average `price`
+    fn report(order: &Order, filled: Decimal, price: Decimal, status: OrderStatus) -> ExchangeOrder {
+        ExchangeOrder {
+            exchange_order_id: format!("EX-{}", order.client_order_id),
+            client_order_id: order.client_order_id.clone(),
+            symbol: order.symbol.clone(),
+            side: order.side,
+            order_type: order.order_type,
+            quantity: order.quantity.as_decimal(),
+            price: order.price.map(|p| p.as_decimal()),
+            filled_quantity: filled,
+            avg_fill_price: Some(price),
+            status,
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_orders_are_placed_on_the_exchange_and_filled_by_its_reports() {
+        let exchange = Arc::new(RecordingExchange::default());
+        let engine = engine_on(exchange.clone());
+        let strategy_id = Uuid::new_v4();
+
+        let result = engine
+            .execute_order(request(strategy_id, OrderSide::Buy, Decimal::ONE, Some(Decimal::from(50_000))))
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert!(result.trade.is_none());
+        let order = result.order.unwrap();
+        assert_eq!(order.okx_order_id, Some(format!("EX-{}", order.client_order_id)));
+        assert_eq!(exchange.calls(), vec![format!("place {}", order.client_order_id)]);
+        // Nothing trades until the exchange reports a fill
+        assert!(engine.get_trades(None).await.is_empty());
+        assert!(engine.get_positions().await.is_empty());
+
+        let half = Decimal::new(5, 1);
+        let partial = report(&order, half, Decimal::from(50_000), OrderStatus::Partial);
+        assert!(engine.on_exchange_order(&partial).await.unwrap());
+        let filled = report(&order, Decimal::ONE, Decimal::from(50_100), OrderStatus::Filled);
+        assert!(engine.on_exchange_order(&filled).await.unwrap());
+
+        let trades = engine.get_trades(None).await;
+        assert_eq!(trades.len(), 2);
+        assert!(trades.iter().all(|t| t.quantity.as_decimal() == half));
+        assert!(trades.iter().any(|t| t.price.as_decimal() == Decimal::from(50_200)));
+        let positions = engine.get_positions().await;
+        assert_eq!(positions.len(), 1);
+        assert_eq!(positions[0].quantity.as_decimal(), Decimal::ONE);
+        assert_eq!(engine.get_orders().await[0].status, OrderStatus::Filled);
+
+        // Orders placed elsewhere are not the engine's
+        let mut foreign = filled.clone();
+        foreign.client_order_id = "someone-else".to_string();
+        assert!(!engine.on_exchange_order(&foreign).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_orders_the_exchange_refuses_are_rejected() {
+        let exchange = Arc::new(RecordingExchange::default());
+        *exchange.reject_next.lock().unwrap() = Some("51008");
+        let engine = engine_on(exchange.clone());
+
+        let result = engine
+            .execute_order(request(Uuid::new_v4(), OrderSide::Buy, Decimal::ONE, Some(Decimal::from(50_000))))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("51008"));
+        assert_eq!(result.order.unwrap().status, OrderStatus::Rejected);
+        assert_eq!(exchange.calls().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_orders_pass_the_gate_before_the_guards() {
+        let exchange = Arc::new(RecordingExchange::default());
+        let guard = FatFingerGuard::new(FatFingerConfig {
+            default_limits: FatFingerLimits {
+                max_price_deviation: None,
+                max_notional: Some(Decimal::from(100)),
+                max_size_multiple: None,
+                action: BreachAction::Reject,
+            },
+            ..FatFingerConfig::default()
+        });
+        let engine = engine_on(exchange.clone()).with_fat_finger_guard(Arc::new(guard));
+        let strategy_id = Uuid::new_v4();
+        let price = Some(Decimal::from(50_000));
+
+        engine.checks.gate().set_trading_enabled(false);
+        let result = engine.execute_order(request(strategy_id, OrderSide::Buy, Decimal::ONE, price)).await.unwrap();
+        assert!(result.error.unwrap().starts_with("Trading disabled"));
+        assert!(result.fat_finger.is_none());
+
+        engine.checks.gate().set_trading_enabled(true);
+        let result = engine.execute_order(request(strategy_id, OrderSide::Buy, Decimal::ONE, price)).await.unwrap();
+        assert!(result.error.unwrap().contains("fat-finger"));
+        assert!(exchange.calls().is_empty());
+
+        let small = Decimal::new(1, 3);
+        let order = engine
+            .execute_order(request(strategy_id, OrderSide::Buy, small, price))
+            .await
+            .unwrap()
+            .order
+            .unwrap();
+        let stages: Vec<String> = engine.decisions.read().await[&order.id]
+            .checks
+            .iter()
+            .map(|check| check.stage.clone())
+            .collect();
+        assert_eq!(stages, vec!["gate", "fat_finger"]);
+        assert_eq!(exchange.calls().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_cancels_go_to_the_exchange() {
+        let exchange = Arc::new(RecordingExchange::default());
+        let engine = engine_on(exchange.clone());
+
+        let order = engine
+            .execute_order(request(Uuid::new_v4(), OrderSide::Buy, Decimal::ONE, Some(Decimal::from(40_000))))
+            .await
+            .unwrap()
+            .order
+            .unwrap();
+        engine.cancel_order(&order.id.to_string()).await.unwrap();
+
+        assert_eq!(exchange.calls()[1], format!("cancel {}", order.client_order_id));
+        assert_eq!(engine.get_orders().await[0].status, OrderStatus::Cancelled);
+    }
+
+    #[tokio::test]
+    async fn test_expired_orders_are_cancelled_on_the_exchange() {
+        let exchange = Arc::new(RecordingExchange::default());
+        let engine = engine_on(exchange.clone());
+
+        let mut expiring = request(Uuid::new_v4(), OrderSide::Buy, Decimal::ONE, Some(Decimal::from(40_000)));
+        expiring.expires_at = Some(Utc::now() + chrono::Duration::minutes(1));
+        let order = engine.execute_order(expiring).await.unwrap().order.unwrap();
+
+        assert!(engine.expire_due_orders(Utc::now()).await.is_empty());
+        assert_eq!(engine.get_orders().await[0].status, OrderStatus::Submitted);
+
+        // Still resting until the exchange confirms the cancel
+        let later = Utc::now() + chrono::Duration::minutes(2);
+        *exchange.refuse_cancels.lock().unwrap() = true;
+        assert!(engine.expire_due_orders(later).await.is_empty());
+        assert_eq!(engine.get_orders().await[0].status, OrderStatus::Submitted);
+
+        *exchange.refuse_cancels.lock().unwrap() = false;
+        assert_eq!(engine.expire_due_orders(later).await, vec![order.id.to_string()]);
+        assert_eq!(exchange.calls()[2], format!("cancel {}", order.client_order_id));
+        assert_eq!(engine.get_orders().await[0].status, OrderStatus::Cancelled);
+    }
+
+    #[tokio::test]
+    async fn test_strategy_orders_are_cancelled_on_the_exchange() {
+        let exchange = Arc::new(RecordingExchange::default());
+        let engine = engine_on(exchange.clone());
+        let strategy_id = Uuid::new_v4();
+        let resting = engine
+            .execute_order(request(strategy_id, OrderSide::Buy, Decimal::ONE, Some(Decimal::from(40_000))))
+            .await
+            .unwrap()
+            .order
+            .unwrap();
+        let other = engine
+            .execute_order(request(Uuid::new_v4(), OrderSide::Buy, Decimal::ONE, Some(Decimal::from(40_000))))
+            .await
+            .unwrap()
+            .order
+            .unwrap();
+
+        *exchange.refuse_cancels.lock().unwrap() = true;
+        let refused = engine.cancel_orders_for_strategy(strategy_id).await.unwrap_err();
+        assert!(refused.to_string().contains(&resting.id.to_string()));
+        assert!(engine.get_orders().await.iter().all(|o| o.is_active()));
+
+        *exchange.refuse_cancels.lock().unwrap() = false;
+        let cancelled = engine.cancel_strategy_orders(strategy_id).await.unwrap();
+        assert_eq!(cancelled, 1);
+        assert_eq!(exchange.calls().last().unwrap(), &format!("cancel {}", resting.client_order_id));
+        let orders = engine.get_orders().await;
+        assert!(orders.iter().any(|o| o.id == resting.id && o.status == OrderStatus::Cancelled));
+        assert!(orders.iter().any(|o| o.id == other.id && o.is_active()));
+    }
+
+    #[tokio::test]
+    async fn test_halted_symbol_orders_are_cancelled_on_the_exchange() {
+        let exchange = Arc::new(RecordingExchange::default());
+        let engine = engine_on(exchange.clone());
+        let mut orders = Vec::new();
+        for _ in 0..2 {
+            let result = engine
+                .execute_order(request(Uuid::new_v4(), OrderSide::Buy, Decimal::ONE, Some(Decimal::from(40_000))))
+                .await
+                .unwrap();
+            orders.push(result.order.unwrap());
+        }
+
+        let symbol = Symbol::new("BTC-USDT").unwrap();
+        let mut cancelled = engine.cancel_orders_for_symbol(&symbol).await.unwrap();
+        cancelled.sort();
+        let mut expected: Vec<String> = orders.iter().map(|o| o.id.to_string()).collect();
+        expected.sort();
+        assert_eq!(cancelled, expected);
+
+        let calls = exchange.calls();
+        for order in &orders {
+            assert!(calls.contains(&format!("cancel {}", order.client_order_id)));
+        }
+        assert!(engine.get_orders().await.iter().all(|o| o.status == OrderStatus::Cancelled));
+        assert!(engine.cancel_orders_for_symbol(&symbol).await.unwrap().is_empty());
+    }
+
+    /// Send `request` and report it filled in full at `price`
+    async fn fill(engine: &StrategyExecutionEngine, request: ExecutionRequest, price: Decimal) -> Order {
+        let order = engine.execute_order(request).await.unwrap().order.unwrap();
+        let filled = report(&order, order.quantity.as_decimal(), price, OrderStatus::Filled);
+        assert!(engine.on_exchange_order(&filled).await.unwrap());
+        order
+    }
+
+    /// Report the exit order the last price sent filled in full at `price`
+    async fn fill_exit(engine: &StrategyExecutionEngine, exchange: &RecordingExchange, price: Decimal) {
+        let client_order_id = exchange.calls().last().unwrap().strip_prefix("place ").unwrap().to_string();
+        let exit = engine.get_orders().await.into_iter().find(|o| o.client_order_id == client_order_id).unwrap();
+        let filled = report(&exit, exit.quantity.as_decimal(), price, OrderStatus::Filled);
+        assert!(engine.on_exchange_order(&filled).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_scale_out_plan_runs_through_a_full_close() {
+        let exchange = Arc::new(RecordingExchange::default());
+        let engine = engine_on(exchange.clone());
+        let strategy_id = Uuid::new_v4();
+        let symbol = Symbol::new("BTC-USDT").unwrap();
+        fill(&engine, request(strategy_id, OrderSide::Buy, Decimal::ONE, Some(Decimal::from(100))), Decimal::from(100)).await;
+
+        let half = Decimal::new(5, 1);
+        let plan = ScaleOutPlan {
+            targets: vec![
+                ea_okx_trading::ProfitTarget { profit_pct: Decimal::from(2), fraction: half },
+                ea_okx_trading::ProfitTarget { profit_pct: Decimal::from(4), fraction: half },
+            ],
+            trail: None,
+        };
+        engine.set_position_plan(strategy_id, &symbol, &plan).await.unwrap();
+
+        engine.on_market_price(&symbol, Decimal::from(103)).await.unwrap();
+        assert_eq!(exchange.calls().len(), 2);
+        fill_exit(&engine, &exchange, Decimal::from(103)).await;
+        assert_eq!(engine.get_positions().await[0].quantity.as_decimal(), half);
+
+        engine.on_market_price(&symbol, Decimal::from(105)).await.unwrap();
+        assert_eq!(exchange.calls().len(), 3);
+        fill_exit(&engine, &exchange, Decimal::from(105)).await;
+
+        // Flat: the position and its plan are gone and nothing fires again
+        assert!(engine.get_positions().await.is_empty());
+        assert!(engine.get_position_plan(strategy_id, &symbol).is_none());
+        engine.on_market_price(&symbol, Decimal::from(110)).await.unwrap();
+        assert_eq!(exchange.calls().len(), 3);
+
+        // The next entry opens a fresh position
+        fill(&engine, request(strategy_id, OrderSide::Sell, Decimal::ONE, Some(Decimal::from(110))), Decimal::from(110)).await;
+        let positions = engine.get_positions().await;
+        assert_eq!(positions.len(), 1);
+        assert_eq!(positions[0].side, PositionSide::Short);
+    }
+
+    #[tokio::test]
+    async fn test_closing_trades_record_realized_pnl() {
+        let exchange = Arc::new(RecordingExchange::default());
+        let engine = engine_on(exchange.clone());
+        let strategy_id = Uuid::new_v4();
+        let daily_loss = engine.checks.gate().daily_loss();
+        let realized = || daily_loss.status(&daily_loss.account_for(strategy_id)).map_or(Decimal::ZERO, |day| day.realized);
+
+        fill(&engine, request(strategy_id, OrderSide::Buy, Decimal::from(2), Some(Decimal::from(100))), Decimal::from(100)).await;
+        fill(&engine, request(strategy_id, OrderSide::Sell, Decimal::ONE, Some(Decimal::from(110))), Decimal::from(110)).await;
+        assert_eq!(realized(), Decimal::from(10));
+
+        // A full close realizes against the entry price, not zero
+        fill(&engine, request(strategy_id, OrderSide::Sell, Decimal::ONE, Some(Decimal::from(120))), Decimal::from(120)).await;
+        assert_eq!(realized(), Decimal::from(30));
+        assert!(engine.get_positions().await.is_empty());
+
+        // Selling past a long closes it and opens a short with the rest
+        fill(&engine, request(strategy_id, OrderSide::Buy, Decimal::ONE, Some(Decimal::from(100))), Decimal::from(100)).await;
+        fill(&engine, request(strategy_id, OrderSide::Sell, Decimal::from(3), Some(Decimal::from(90))), Decimal::from(90)).await;
+        assert_eq!(realized(), Decimal::from(20));
+        let positions = engine.get_positions().await;
+        assert_eq!(positions.len(), 1);
+        assert_eq!(positions[0].side, PositionSide::Short);
+        assert_eq!(positions[0].quantity.as_decimal(), Decimal::from(2));
+        assert_eq!(positions[0].avg_entry_price.as_decimal(), Decimal::from(90));
+    }
+
+    #[tokio::test]
+    async fn test_orders_without_an_exchange_are_refused() {
+        let engine = StrategyExecutionEngine::new();
+        let result = engine
+            .execute_order(request(Uuid::new_v4(), OrderSide::Buy, Decimal::ONE, Some(Decimal::from(50_000))))
+            .await;
+        assert!(matches!(result, Err(Error::ConfigError(_))));
+    }
+}
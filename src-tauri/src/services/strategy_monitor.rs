@@ -32,6 +32,9 @@ pub enum StrategyUpdateEvent {
         side: String,
         amount: f64,
         price: f64,
+        /// Closed a position at its stop loss
+        #[serde(default)]
+        stop_out: bool,
         timestamp: chrono::DateTime<Utc>,
     },
     /// Performance metrics updated
@@ -93,20 +96,27 @@ pub struct StrategyMonitorService {
     strategies: Arc<RwLock<HashMap<String, Strategy>>>,
     clients: Arc<RwLock<HashMap<String, ClientSubscription>>>,
     event_tx: mpsc::UnboundedSender<StrategyUpdateEvent>,
+    event_rx: Arc<std::sync::Mutex<Option<mpsc::UnboundedReceiver<StrategyUpdateEvent>>>>,
 }
 
 impl StrategyMonitorService {
     /// Creates a new strategy monitoring service
     pub fn new() -> Self {
-        let (event_tx, _) = mpsc::unbounded_channel();
+        let (event_tx, event_rx) = mpsc::unbounded_channel();
 
         Self {
             strategies: Arc::new(RwLock::new(HashMap::new())),
             clients: Arc::new(RwLock::new(HashMap::new())),
             event_tx,
+            event_rx: Arc::new(std::sync::Mutex::new(Some(event_rx))),
         }
     }
 
+    /// Get event receiver (can only be called once)
+    pub fn subscribe_events(&self) -> Option<mpsc::UnboundedReceiver<StrategyUpdateEvent>> {
+        self.event_rx.lock().ok()?.take()
+    }
+
     /// Register a new client for real-time updates
     pub async fn subscribe_client(
         &self,
@@ -181,6 +191,7 @@ impl StrategyMonitorService {
         side: String,
         amount: f64,
         price: f64,
+        stop_out: bool,
     ) -> Result<()> {
         self.emit_event(StrategyUpdateEvent::TradeExecuted {
             strategy_id,
@@ -189,6 +200,7 @@ impl StrategyMonitorService {
             side,
             amount,
             price,
+            stop_out,
             timestamp: Utc::now(),
         }).await
     }
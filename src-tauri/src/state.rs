@@ -1,7 +1,481 @@
 //! Application state
 
-use crate::services::{StrategyService, StrategyMonitorService, StrategyExecutionEngine};
+use crate::services::strategy_execution::ExecutionSignal;
+use crate::services::access::{Role, UserAccount};
+use crate::services::{
+    AccessControl, EngineSnapshotSource, StrategyDecayWatcher, LiveEquitySource, NotificationCenter, StrategyService,
+    StrategyMonitorService, StrategyExecutionEngine, SubscriptionManager, TradingReportSource,
+};
+use data::storage::{RedisStorage, TimescaleStorage};
+use data::{
+    EquityRecorder, HttpTickerSource, OrderBookStorageConfig, InMemoryStrategyRepository, OkxPositioningSource, OkxPriceKind, OkxPriceSource,
+    PositioningCollector, PositioningConfig, PriceCache, PriceCacheConfig, PriceSource, QualityControl, ReferenceConfig, ReferencePriceService, SchemaMigrator, SqlStrategyRepository, StrategyRepository,
+    TickMaintenanceStatus, TickStorageConfig, TieredPriceCache,
+};
+use ea_okx_core::models::strategy::StrategyStatus;
+use ea_okx_core::types::Symbol;
+use ea_okx_core::Interval;
+use ea_okx_trading::{
+    cancel_child_orders, reconcile_orders, recover_executions, recover_intents, AccountEvent, AccountTracker, AlgoExecutionStore, BalanceReservations, BracketManager, DailyLossEvent, ExecutionGate,
+    FatFingerGuard, FileAlgoExecutionStore, FileIntentLog, InMemoryIntentLog, IntentLog, IntentRecoveryPolicy, LiquidityConfig, LiquidityGuard, OrderBooks, OrderJournal, FileSnapshotStore, InMemoryAlgoExecutionStore, InMemorySnapshotStore,
+    InstrumentEvent, InstrumentStatusTracker, OkxAlgoVenue, OkxBracketVenue, OkxIntentVenue, ReconciliationConfig, RecoveryPolicy, ScaleOutManager,
+    SizeLimitConfig, SizeLimitGuard, SnapshotConfig, SnapshotInfo, SnapshotScheduler, SnapshotStore, FileVolumeProfileStore, SymbolCatalog, TradeMode, UnlockReason,
+    InMemoryVolumeProfileStore, VolumeProfileConfig, VolumeProfileEstimator, VolumeProfileStore,
+};
+use ea_okx_monitoring::{
+    Alert, AlertSeverity, ClockDriftMonitor, DailyReporter, DecayConfig, DecayMonitor, DiskSpaceHealthChecker, ExchangeHealthEvent,
+    FileReportStore, InMemoryReportStore, MonitoringService, OutageDetector, OutageThresholds,
+    PoolHealthChecker, RedisHealthChecker, ReportConfig, ReportStore, SchemaHealthChecker,
+    TickMaintenanceHealthChecker, Watchdog, WatchdogConfig, WebSocketHealthChecker, market_data_silence_rule,
+};
+use ea_okx_backtest::{
+    BacktestRegistry, BacktestRunStore, FileBacktestRunStore, InMemoryBacktestRunStore,
+};
+use ea_okx_client::adapter::exchange_order;
+use ea_okx_client::models::{Channel, SubscriptionRequest, WebSocketEvent};
+use ea_okx_client::{ConnectionTelemetry, Credentials, OkxAdapter, OkxRestClient, OkxWebSocketClient};
+use ea_okx_risk::{ApprovalPolicy, DrawdownThrottle, LimitChangeManager, RiskLimits};
+use ea_okx_strategy::{
+    builtin_factory, ExternalSignalSource, FileSignalSource, MarketDataEvent, MetricsRegistry, OrderCleanup, SignalIngestor, SignalSourceConfig,
+    StrategyInput, StrategySupervisor, SupervisorEvent, SupervisorExit, UrlSignalSource,
+};
+use rust_decimal::prelude::ToPrimitive;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use uuid::Uuid;
+use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Root directory for locally persisted application data
+fn data_dir() -> PathBuf {
+    std::env::var("EA_OKX_DATA_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("data"))
+}
+
+/// Directory holding persisted TWAP/VWAP executions
+fn algo_executions_dir() -> PathBuf {
+    data_dir().join("algo_executions")
+}
+
+/// Directory holding the write-ahead log of order intents
+fn order_intents_dir() -> PathBuf {
+    data_dir().join("order_intents")
+}
+
+/// Directory holding the daily journals of order lifecycle events
+fn order_journal_dir() -> PathBuf {
+    data_dir().join("order_journal")
+}
+
+/// Directory compliance drop copies are exported to
+pub fn drop_copy_dir() -> PathBuf {
+    data_dir().join("drop_copies")
+}
+
+/// Directory holding generated daily reports
+fn reports_dir() -> PathBuf {
+    data_dir().join("reports")
+}
+
+/// Directory holding recorded backtest runs and their artifacts
+fn backtests_dir() -> PathBuf {
+    data_dir().join("backtests")
+}
+
+/// Directory holding engine state snapshots
+fn snapshots_dir() -> PathBuf {
+    data_dir().join("snapshots")
+}
+
+/// Directory holding the notification history and preferences
+fn notifications_dir() -> PathBuf {
+    data_dir().join("notifications")
+}
+
+/// Directory holding learned VWAP volume profiles
+fn volume_profiles_dir() -> PathBuf {
+    data_dir().join("volume_profiles")
+}
+
+/// File holding the last synced OKX instrument listing
+fn symbol_catalog_path() -> PathBuf {
+    data_dir().join("symbols.json")
+}
+
+/// Opens the symbol catalog over its last saved listing
+fn open_symbol_catalog() -> Arc<SymbolCatalog> {
+    let kinds = SymbolCatalog::DEFAULT_KINDS.to_vec();
+    match SymbolCatalog::open(kinds.clone(), symbol_catalog_path()) {
+        Ok(catalog) => Arc::new(catalog),
+        Err(e) => {
+            log::error!("Falling back to in-memory symbol catalog: {}", e);
+            Arc::new(SymbolCatalog::new(kinds))
+        }
+    }
+}
+
+/// Opens the backtest run registry over its persisted runs
+fn open_backtest_registry() -> Arc<BacktestRegistry> {
+    let store: Arc<dyn BacktestRunStore> = match FileBacktestRunStore::new(backtests_dir()) {
+        Ok(store) => Arc::new(store),
+        Err(e) => {
+            log::error!("Falling back to in-memory backtest run store: {}", e);
+            Arc::new(InMemoryBacktestRunStore::new())
+        }
+    };
+    Arc::new(BacktestRegistry::new(store))
+}
+
+/// Opens the volume profile estimator over its persisted profiles
+fn open_volume_profiles() -> Arc<VolumeProfileEstimator> {
+    let store: Arc<dyn VolumeProfileStore> = match FileVolumeProfileStore::new(volume_profiles_dir()) {
+        Ok(store) => Arc::new(store),
+        Err(e) => {
+            log::error!("Falling back to in-memory volume profile store: {}", e);
+            Arc::new(InMemoryVolumeProfileStore::new())
+        }
+    };
+    match VolumeProfileEstimator::new(VolumeProfileConfig::default(), store) {
+        Ok(estimator) => Arc::new(estimator),
+        Err(e) => {
+            log::error!("Stored volume profiles unreadable, starting without them: {}", e);
+            Arc::new(
+                VolumeProfileEstimator::new(
+                    VolumeProfileConfig::default(),
+                    Arc::new(InMemoryVolumeProfileStore::new()),
+                )
+                .expect("in-memory store loads"),
+            )
+        }
+    }
+}
+
+/// Append-only audit journal of risk limit changes
+fn risk_limit_journal() -> PathBuf {
+    data_dir().join("risk_limit_audit.jsonl")
+}
+
+/// Users allowed to sign in
+fn users_file() -> PathBuf {
+    data_dir().join("users.json")
+}
+
+/// Append-only audit journal of admin commands, refused commands and sign-ins
+fn access_audit_journal() -> PathBuf {
+    data_dir().join("access_audit.jsonl")
+}
+
+/// Backtest baselines live strategies are judged against
+fn strategy_baselines_file() -> PathBuf {
+    data_dir().join("strategy_baselines.json")
+}
+
+/// Opens the performance decay monitor over the saved baselines
+fn open_decay_monitor(monitoring: Arc<MonitoringService>) -> Arc<DecayMonitor> {
+    match DecayMonitor::new(DecayConfig::default(), monitoring.clone()).with_file(strategy_baselines_file()) {
+        Ok(monitor) => Arc::new(monitor),
+        Err(e) => {
+            log::error!("Strategy baselines will not be saved: {}", e);
+            Arc::new(DecayMonitor::new(DecayConfig::default(), monitoring))
+        }
+    }
+}
+
+/// Opens command access control over the users in `users.json`; without
+/// them the app runs single-user with the role from `EA_OKX_DEFAULT_ROLE`
+fn open_access_control() -> Arc<AccessControl> {
+    let default_role = match std::env::var("EA_OKX_DEFAULT_ROLE") {
+        Ok(role) => role.parse().unwrap_or_else(|e| {
+            log::error!("Running as viewer, invalid EA_OKX_DEFAULT_ROLE: {}", e);
+            Role::Viewer
+        }),
+        Err(_) => Role::Admin,
+    };
+
+    let path = users_file();
+    let access = match std::fs::read_to_string(&path) {
+        Ok(contents) => match serde_json::from_str::<Vec<UserAccount>>(&contents) {
+            Ok(users) => AccessControl::new(users, default_role),
+            Err(e) => {
+                // Users were meant to be configured: run locked down rather than as admin
+                log::error!("Running as viewer, invalid {}: {}", path.display(), e);
+                AccessControl::new(Vec::new(), Role::Viewer)
+            }
+        },
+        Err(_) => AccessControl::new(Vec::new(), default_role),
+    };
+    Arc::new(access.with_journal(access_audit_journal()))
+}
+
+/// Approval policy for risk limit changes: `EA_OKX_RISK_PASSCODE` requires a
+/// passcode to confirm, `EA_OKX_RISK_SECOND_APPROVER=1` a confirmer other than
+/// the proposer, and `EA_OKX_RISK_CHANGE_DELAY_SECS` a minimum lead time
+fn risk_approval_policy() -> ApprovalPolicy {
+    let mut policy = ApprovalPolicy {
+        require_second_approver: std::env::var("EA_OKX_RISK_SECOND_APPROVER")
+            .is_ok_and(|v| v == "1" || v == "true"),
+        min_delay_secs: std::env::var("EA_OKX_RISK_CHANGE_DELAY_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0),
+        ..Default::default()
+    };
+    if let Ok(passcode) = std::env::var("EA_OKX_RISK_PASSCODE") {
+        policy = policy.with_passcode(&passcode);
+    }
+    policy
+}
+
+/// Opens the risk limit workflow, replaying its audit journal
+fn open_limit_changes() -> LimitChangeManager {
+    let policy = risk_approval_policy();
+    match LimitChangeManager::open_journal(risk_limit_journal(), RiskLimits::default(), policy.clone()) {
+        Ok(manager) => manager,
+        Err(e) => {
+            log::error!("Falling back to default risk limits without an audit journal: {}", e);
+            LimitChangeManager::new(RiskLimits::default(), policy)
+        }
+    }
+}
+
+/// Opens the strategy store: `EA_OKX_STRATEGY_DB_URL` (SQLite or Postgres URL)
+/// if set, otherwise a SQLite file in the data directory
+fn open_strategy_repository() -> Arc<dyn StrategyRepository> {
+    let opened = tauri::async_runtime::block_on(async {
+        match std::env::var("EA_OKX_STRATEGY_DB_URL") {
+            Ok(url) => SqlStrategyRepository::connect(&url).await,
+            Err(_) => SqlStrategyRepository::open_sqlite(data_dir().join("strategies.db")).await,
+        }
+    });
+
+    match opened {
+        Ok(repository) => Arc::new(repository),
+        Err(e) => {
+            log::error!("Falling back to in-memory strategy store: {}", e);
+            Arc::new(InMemoryStrategyRepository::new())
+        }
+    }
+}
+
+/// Connects to the TimescaleDB market data store named by `EA_OKX_MARKET_DB_URL`,
+/// if set, and brings its schema up to date; market data commands report it
+/// as unavailable otherwise
+///
+/// A database created before migrations were tracked is adopted by setting
+/// `EA_OKX_SCHEMA_BASELINE` to the last migration it was created with.
+fn open_market_storage() -> Option<Arc<TimescaleStorage>> {
+    let url = std::env::var("EA_OKX_MARKET_DB_URL").ok()?;
+    let storage = match tauri::async_runtime::block_on(TimescaleStorage::new(&url)) {
+        Ok(storage) => storage.with_orderbook_config(&orderbook_storage_config()),
+        Err(e) => {
+            log::error!("Market data store unavailable: {}", e);
+            return None;
+        }
+    };
+
+    let mut migrator = SchemaMigrator::new();
+    if let Some(baseline) = std::env::var("EA_OKX_SCHEMA_BASELINE").ok().and_then(|v| v.parse().ok()) {
+        migrator = migrator.with_baseline(baseline);
+    }
+    // The schema health check keeps reporting a failed migration
+    match tauri::async_runtime::block_on(storage.migrate(&migrator)) {
+        Ok(report) if !report.applied.is_empty() => {
+            log::info!("Applied schema migrations {:?}", report.applied);
+        }
+        Ok(_) => {}
+        Err(e) => log::error!("Schema migration failed: {}", e),
+    }
+    Some(Arc::new(storage))
+}
+
+/// Redis cache named by `EA_OKX_REDIS_URL`, if set
+fn open_redis() -> Option<Arc<RedisStorage>> {
+    let url = std::env::var("EA_OKX_REDIS_URL").ok()?;
+    match RedisStorage::new(&url) {
+        Ok(storage) => Some(Arc::new(storage)),
+        Err(e) => {
+            log::error!("Redis unavailable: {}", e);
+            None
+        }
+    }
+}
+
+/// Latest prices cached in process for `EA_OKX_PRICE_CACHE_TTL_MS`
+/// (default 500) in front of Redis, when there is one
+fn open_price_cache(redis: Option<&Arc<RedisStorage>>) -> Arc<TieredPriceCache> {
+    let mut config = PriceCacheConfig::default();
+    if let Some(ttl_ms) = std::env::var("EA_OKX_PRICE_CACHE_TTL_MS").ok().and_then(|v| v.parse().ok()) {
+        config.ttl_ms = ttl_ms;
+    }
+    let cache = TieredPriceCache::new(&config);
+    Arc::new(match redis {
+        Some(redis) => cache.with_remote(redis.clone()),
+        None => cache,
+    })
+}
+
+/// OKX credentials from `OKX_API_KEY`, `OKX_SECRET_KEY` and
+/// `OKX_PASSPHRASE`, and whether `OKX_TESTNET=1` selects demo trading
+fn okx_credentials() -> Option<(Credentials, bool)> {
+    let api_key = std::env::var("OKX_API_KEY").ok()?;
+    let secret_key = std::env::var("OKX_SECRET_KEY").ok()?;
+    let passphrase = std::env::var("OKX_PASSPHRASE").ok()?;
+    let testnet = std::env::var("OKX_TESTNET").is_ok_and(|v| v == "1" || v == "true");
+    Some((Credentials::new(api_key, secret_key, passphrase), testnet))
+}
+
+/// Authenticated OKX REST client from the [`okx_credentials`]
+fn open_okx_client() -> Option<Arc<OkxRestClient>> {
+    let (credentials, testnet) = okx_credentials()?;
+    match OkxRestClient::new(credentials, testnet) {
+        Ok(client) => Some(Arc::new(client)),
+        Err(e) => {
+            log::error!("OKX REST client unavailable: {}", e);
+            None
+        }
+    }
+}
+
+/// Reference price sources: OKX index and mark prices when a client is
+/// configured, plus an external ticker from `EA_OKX_REFERENCE_URL` with the
+/// price at the JSON pointer `EA_OKX_REFERENCE_POINTER`
+fn reference_sources(okx_client: Option<&Arc<OkxRestClient>>) -> Vec<Arc<dyn PriceSource>> {
+    let mut sources: Vec<Arc<dyn PriceSource>> = Vec::new();
+    if let Some(client) = okx_client {
+        sources.push(Arc::new(OkxPriceSource::new(client.clone(), OkxPriceKind::Index)));
+        sources.push(Arc::new(OkxPriceSource::new(client.clone(), OkxPriceKind::Mark)));
+    }
+    if let Ok(url) = std::env::var("EA_OKX_REFERENCE_URL") {
+        let pointer = std::env::var("EA_OKX_REFERENCE_POINTER").unwrap_or_else(|_| "/price".to_string());
+        match HttpTickerSource::new("external", url, pointer) {
+            Ok(source) => sources.push(Arc::new(source)),
+            Err(e) => log::error!("External reference source unavailable: {}", e),
+        }
+    }
+    sources
+}
+
+/// Symbols kept refreshed in the background, from the comma-separated
+/// `EA_OKX_REFERENCE_SYMBOLS`
+fn reference_symbols() -> Vec<Symbol> {
+    std::env::var("EA_OKX_REFERENCE_SYMBOLS")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .filter_map(|s| match Symbol::new(s) {
+            Ok(symbol) => Some(symbol),
+            Err(e) => {
+                log::error!("Ignoring reference symbol '{}': {}", s, e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Decimal setting `name` of a strategy's risk limits, if set
+fn risk_limit(limits: &serde_json::Value, name: &str) -> Option<rust_decimal::Decimal> {
+    limits.get(name).and_then(|value| serde_json::from_value(value.clone()).ok())
+}
+
+/// Tick storage settings from `tick_storage.json` in the data directory,
+/// defaults for anything it leaves out
+fn tick_storage_config() -> TickStorageConfig {
+    let path = data_dir().join("tick_storage.json");
+    let Ok(contents) = std::fs::read_to_string(&path) else {
+        return TickStorageConfig::default();
+    };
+    match serde_json::from_str::<TickStorageConfig>(&contents) {
+        Ok(config) if config.validate().is_ok() => config,
+        Ok(config) => {
+            log::error!("Ignoring invalid {}: {}", path.display(), config.validate().unwrap_err());
+            TickStorageConfig::default()
+        }
+        Err(e) => {
+            log::error!("Ignoring invalid {}: {}", path.display(), e);
+            TickStorageConfig::default()
+        }
+    }
+}
+
+/// Order book block size and retention from `orderbook_storage.json`,
+/// defaulting when the file is absent
+fn orderbook_storage_config() -> OrderBookStorageConfig {
+    let path = data_dir().join("orderbook_storage.json");
+    let Ok(contents) = std::fs::read_to_string(&path) else {
+        return OrderBookStorageConfig::default();
+    };
+    serde_json::from_str(&contents).unwrap_or_else(|e| {
+        log::error!("Ignoring invalid {}: {}", path.display(), e);
+        OrderBookStorageConfig::default()
+    })
+}
+
+/// Entry of `signal_sources.json`: an external signal source and, for
+/// polled sources, where to poll it. Webhook sources set neither.
+#[derive(Deserialize)]
+struct SignalSourceEntry {
+    #[serde(flatten)]
+    config: SignalSourceConfig,
+    poll_url: Option<String>,
+    poll_file: Option<PathBuf>,
+    #[serde(default = "default_poll_interval_secs")]
+    poll_interval_secs: u64,
+}
+
+fn default_poll_interval_secs() -> u64 {
+    5
+}
+
+/// Registers the sources in `signal_sources.json` in the data directory and
+/// returns the ones to poll
+fn load_signal_sources(
+    ingestor: &SignalIngestor,
+) -> Vec<(Arc<dyn ExternalSignalSource>, std::time::Duration)> {
+    let path = data_dir().join("signal_sources.json");
+    let entries: Vec<SignalSourceEntry> = match std::fs::read_to_string(&path) {
+        Ok(contents) => match serde_json::from_str(&contents) {
+            Ok(entries) => entries,
+            Err(e) => {
+                log::error!("Ignoring invalid {}: {}", path.display(), e);
+                return Vec::new();
+            }
+        },
+        Err(_) => return Vec::new(),
+    };
+
+    let mut pollers = Vec::new();
+    for entry in entries {
+        let name = entry.config.name.clone();
+        if let Err(e) = ingestor.register_source(entry.config) {
+            log::error!("Ignoring signal source {}: {}", name, e);
+            continue;
+        }
+        let interval = std::time::Duration::from_secs(entry.poll_interval_secs.max(1));
+        if let Some(url) = entry.poll_url {
+            match UrlSignalSource::new(name.clone(), url) {
+                Ok(source) => pollers.push((Arc::new(source) as Arc<dyn ExternalSignalSource>, interval)),
+                Err(e) => log::error!("Cannot poll signal source {}: {}", name, e),
+            }
+        } else if let Some(file) = entry.poll_file {
+            pollers.push((Arc::new(FileSignalSource::new(name, file)) as Arc<dyn ExternalSignalSource>, interval));
+        }
+    }
+    pollers
+}
+
+/// A strategy implementation running under a supervisor
+struct StrategyRun {
+    symbols: Vec<Symbol>,
+    /// Market data and order updates; dropping it shuts the strategy down
+    inputs: tokio::sync::mpsc::Sender<StrategyInput>,
+}
+
+/// Inputs queued per running strategy before new ones are dropped
+const STRATEGY_INPUT_CAPACITY: usize = 1024;
 
 /// Application state shared across all commands
 #[derive(Clone)]
@@ -9,30 +483,1170 @@ pub struct AppState {
     pub strategy_service: Arc<StrategyService>,
     pub strategy_monitor: Arc<StrategyMonitorService>,
     pub execution_engine: Arc<StrategyExecutionEngine>,
+    pub execution_gate: Arc<ExecutionGate>,
+    pub fat_finger: Arc<FatFingerGuard>,
+    /// Caps orders to a share of the depth in `order_books`
+    pub liquidity: Arc<LiquidityGuard>,
+    pub push: Arc<SubscriptionManager>,
+    /// Alerts, fills, strategy state changes and system errors for the user
+    pub notifications: Arc<NotificationCenter>,
+    pub algo_store: Arc<dyn AlgoExecutionStore>,
+    /// Orders logged before being sent, settled against OKX on startup
+    pub intent_log: Arc<dyn IntentLog>,
+    /// Lifecycle events of every order, exported as drop copies
+    pub order_journal: Option<Arc<OrderJournal>>,
+    pub account_tracker: Arc<AccountTracker>,
+    pub reporter: Arc<DailyReporter>,
+    pub snapshots: Arc<SnapshotScheduler>,
+    pub monitoring: Arc<MonitoringService>,
+    /// Heartbeats of long-running background tasks, restarting the ones it supervises
+    pub watchdog: Arc<Watchdog>,
+    pub instrument_tracker: Arc<InstrumentStatusTracker>,
+    /// Every instrument OKX lists, synced periodically with an OKX client
+    pub symbol_catalog: Arc<SymbolCatalog>,
+    pub market_storage: Option<Arc<TimescaleStorage>>,
+    pub redis: Option<Arc<RedisStorage>>,
+    /// Latest prices: a short-lived local LRU in front of Redis
+    pub price_cache: Arc<TieredPriceCache>,
+    /// Learned per-symbol volume profiles for VWAP executions
+    pub volume_profiles: Arc<VolumeProfileEstimator>,
+    /// Market data WebSocket health, fed by the account stream's connection
+    pub market_feed: ConnectionTelemetry,
+    pub okx_client: Option<Arc<OkxRestClient>>,
+    /// Judges OKX health from REST telemetry; present with an OKX client
+    pub outage_detector: Option<Arc<OutageDetector>>,
+    pub reference_prices: Arc<ReferencePriceService>,
+    /// Rolling per-symbol market data quality
+    pub data_quality: Arc<QualityControl>,
+    pub signal_ingestor: Arc<SignalIngestor>,
+    /// Custom metrics reported by supervised strategies
+    pub strategy_metrics: MetricsRegistry,
+    /// Every recorded backtest run, with metadata and artifacts
+    pub backtest_registry: Arc<BacktestRegistry>,
+    /// Active risk limits and their change approval workflow
+    pub risk_limits: Arc<RwLock<LimitChangeManager>>,
+    /// Drawdown schedules new positions are scaled by, and the equity peak
+    pub drawdown_throttle: Arc<RwLock<DrawdownThrottle>>,
+    /// Funds transfers stay refused until explicitly enabled for the session
+    pub transfers_enabled: Arc<AtomicBool>,
+    /// Role checks and audit journal applied to every command
+    pub access: Arc<AccessControl>,
+    /// Live strategy performance judged against deployment backtests
+    pub decay: Arc<StrategyDecayWatcher>,
+    /// Supervised implementations of started strategies by strategy ID
+    strategy_runs: Arc<RwLock<HashMap<Uuid, StrategyRun>>>,
 }
 
 impl AppState {
     /// Creates a new application state
     pub fn new() -> Self {
         let strategy_monitor = Arc::new(StrategyMonitorService::new());
-        let strategy_service = Arc::new(StrategyService::with_monitor(strategy_monitor.clone()));
-        let execution_engine = Arc::new(StrategyExecutionEngine::with_monitor(strategy_monitor.clone()));
+        let strategy_service = Arc::new(
+            StrategyService::with_monitor(strategy_monitor.clone())
+                .with_repository(open_strategy_repository()),
+        );
+        let execution_gate = Arc::new(ExecutionGate::new());
+        // Fed reference prices, public trades and books5 quotes in
+        // `initialize`; without an OKX connection only the notional cap on
+        // limit orders applies
+        let fat_finger = Arc::new(FatFingerGuard::default());
+        // Books come from the same books5 stream; a symbol without a book is
+        // never capped by visible liquidity
+        let liquidity = Arc::new(LiquidityGuard::new(LiquidityConfig::default(), Arc::new(OrderBooks::new())));
+        let intent_log: Arc<dyn IntentLog> = match FileIntentLog::new(order_intents_dir()) {
+            Ok(log) => Arc::new(log),
+            Err(e) => {
+                log::error!("Falling back to in-memory order intent log: {}", e);
+                Arc::new(InMemoryIntentLog::new())
+            }
+        };
+        let order_journal = match OrderJournal::open(order_journal_dir()) {
+            Ok(journal) => Some(Arc::new(journal)),
+            Err(e) => {
+                log::error!("Order lifecycle events will not be journaled: {}", e);
+                None
+            }
+        };
+        let redis = open_redis();
+        let price_cache = open_price_cache(redis.as_ref());
+        let okx_client = open_okx_client();
+        let account_tracker = Arc::new(AccountTracker::new(ReconciliationConfig::default()));
+        // With an OKX account, balances are seeded from it on startup and
+        // orders wait for them rather than pass unchecked
+        let mut reservations = BalanceReservations::new(account_tracker.clone());
+        if okx_client.is_some() {
+            reservations = reservations.requiring_balances();
+        }
+        let drawdown_throttle = Arc::new(RwLock::new(DrawdownThrottle::default()));
+        let mut engine = StrategyExecutionEngine::with_monitor(strategy_monitor.clone())
+            .with_strategies(strategy_service.shared_strategies())
+            .with_gate(execution_gate.clone())
+            .with_drawdown_throttle(drawdown_throttle.clone())
+            .with_fat_finger_guard(fat_finger.clone())
+            .with_liquidity_guard(liquidity.clone())
+            .with_intent_log(intent_log.clone())
+            .with_price_cache(price_cache.clone())
+            .with_balance_reservations(Arc::new(reservations));
+        if let Some(journal) = &order_journal {
+            engine = engine.with_order_journal(journal.clone());
+        }
+        // Orders go to OKX, capped at what it lets the account open; scale-out
+        // exits of derivatives and signal brackets rest there as algo orders.
+        // Without an account only dry-run strategies trade.
+        if let Some(client) = &okx_client {
+            engine = engine
+                .with_exchange(Arc::new(OkxAdapter::new(client.clone())))
+                .with_size_guard(Arc::new(SizeLimitGuard::new(SizeLimitConfig::default(), client.clone())))
+                .with_scale_out(Arc::new(
+                    ScaleOutManager::new().with_venue(Arc::new(OkxAlgoVenue::new(client.clone(), TradeMode::Cash))),
+                ))
+                .with_brackets(Arc::new(
+                    BracketManager::new().with_venue(Arc::new(OkxBracketVenue::new(client.clone(), TradeMode::Cash))),
+                ));
+        }
+        let execution_engine = Arc::new(engine);
+
+        let push = Arc::new(SubscriptionManager::new(execution_engine.clone()));
+
+        let algo_store: Arc<dyn AlgoExecutionStore> =
+            match FileAlgoExecutionStore::new(algo_executions_dir()) {
+                Ok(store) => Arc::new(store),
+                Err(e) => {
+                    log::error!("Falling back to in-memory algo execution store: {}", e);
+                    Arc::new(InMemoryAlgoExecutionStore::new())
+                }
+            };
+
+        let report_store: Arc<dyn ReportStore> = match FileReportStore::new(reports_dir()) {
+            Ok(store) => Arc::new(store),
+            Err(e) => {
+                log::error!("Falling back to in-memory report store: {}", e);
+                Arc::new(InMemoryReportStore::new())
+            }
+        };
+        let reporter = Arc::new(DailyReporter::new(
+            ReportConfig::default(),
+            Arc::new(TradingReportSource::new(
+                strategy_service.clone(),
+                execution_engine.clone(),
+            )),
+            report_store,
+        ));
+
+        let snapshot_store: Arc<dyn SnapshotStore> = match FileSnapshotStore::new(snapshots_dir()) {
+            Ok(store) => Arc::new(store),
+            Err(e) => {
+                log::error!("Falling back to in-memory snapshot store: {}", e);
+                Arc::new(InMemorySnapshotStore::new())
+            }
+        };
+        let snapshots = Arc::new(SnapshotScheduler::new(
+            SnapshotConfig::default(),
+            Arc::new(EngineSnapshotSource::new(
+                strategy_service.clone(),
+                execution_engine.clone(),
+                algo_store.clone(),
+            )),
+            snapshot_store,
+        ));
+
+        // Polled from OKX's instrument listings when a client is configured
+        let instrument_tracker = Arc::new(
+            InstrumentStatusTracker::new(vec!["SPOT".to_string(), "SWAP".to_string()])
+                .with_gate(execution_gate.clone()),
+        );
+
+        let monitoring = Arc::new(MonitoringService::new());
+        let watchdog = Arc::new(Watchdog::new(WatchdogConfig::default()).with_monitoring(monitoring.clone()));
+        // Only REST health is judged: `market_feed` is only fed while the
+        // account stream runs and carries no market data without symbols
+        let outage_detector = okx_client.as_ref().map(|client| {
+            Arc::new(
+                OutageDetector::new(OutageThresholds::default())
+                    .with_rest(client.telemetry())
+                    .with_monitoring(monitoring.clone()),
+            )
+        });
+        // Only reference prices are fed: the desktop app runs no market data
+        // collector, so no symbol has a score and feed quality minimums
+        // never block
+        let data_quality = Arc::new(QualityControl::default());
+        let reference_prices = Arc::new(
+            ReferencePriceService::new(
+                ReferenceConfig::default(),
+                reference_sources(okx_client.as_ref()),
+            )
+            .with_quality_control(data_quality.clone()),
+        );
+        let backtest_registry = open_backtest_registry();
+        let decay = Arc::new(StrategyDecayWatcher::new(
+            open_decay_monitor(monitoring.clone()),
+            strategy_service.clone(),
+            execution_engine.clone(),
+            backtest_registry.clone(),
+        ));
 
         Self {
             strategy_service,
             strategy_monitor,
             execution_engine,
+            execution_gate,
+            fat_finger,
+            liquidity,
+            push,
+            notifications: Arc::new(NotificationCenter::open(notifications_dir())),
+            algo_store,
+            intent_log,
+            order_journal,
+            account_tracker,
+            reporter,
+            snapshots,
+            monitoring,
+            watchdog,
+            instrument_tracker,
+            symbol_catalog: open_symbol_catalog(),
+            market_storage: open_market_storage(),
+            redis,
+            price_cache,
+            volume_profiles: open_volume_profiles(),
+            market_feed: ConnectionTelemetry::new(),
+            okx_client,
+            outage_detector,
+            reference_prices,
+            data_quality,
+            signal_ingestor: Arc::new(SignalIngestor::new()),
+            strategy_metrics: MetricsRegistry::new(),
+            backtest_registry,
+            risk_limits: Arc::new(RwLock::new(open_limit_changes())),
+            drawdown_throttle,
+            transfers_enabled: Arc::new(AtomicBool::new(false)),
+            access: open_access_control(),
+            decay,
+            strategy_runs: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
     /// Initializes the application state (should be called from within a Tokio context)
     pub async fn initialize(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // Initialize default strategies
-        self.strategy_service.initialize_default_strategies().await?;
+        // Restore persisted strategies, seeding the demo strategies on first run only
+        if self.strategy_service.load_strategies().await? == 0 {
+            self.strategy_service.initialize_default_strategies().await?;
+        }
+
+        // Roll back to a snapshot when asked to: `EA_OKX_RESTORE_SNAPSHOT` is
+        // a snapshot ID or `latest` for the newest one that still loads
+        if let Ok(target) = std::env::var("EA_OKX_RESTORE_SNAPSHOT") {
+            let id = match target.as_str() {
+                "latest" => None,
+                id => Some(Uuid::parse_str(id)?),
+            };
+            let info = self.restore_from_snapshot(id).await?;
+            log::warn!("Restored engine state from snapshot {} taken at {}", info.id, info.taken_at);
+        }
+
+        // Seed balances before any strategy can place an order against them
+        if let Some(client) = &self.okx_client {
+            match self.account_tracker.reconcile(client.as_ref()).await {
+                Ok(_) => log::info!("Seeded account balances from OKX"),
+                Err(e) => log::error!("Failed to load account balances, orders wait for them: {}", e),
+            }
+        }
+
+        // Recover TWAP/VWAP executions left over from the previous run. The desktop
+        // app does not host live algo executors yet, so in-flight executions are
+        // cancelled rather than left dangling, child orders resting at OKX included.
+        let policy = RecoveryPolicy {
+            resume_in_flight: false,
+            ..Default::default()
+        };
+        let report = recover_executions(self.algo_store.as_ref(), &policy)?;
+        if !report.cancelled.is_empty() {
+            log::warn!(
+                "Cancelled {} in-flight algo executions on startup",
+                report.cancelled.len()
+            );
+            match &self.okx_client {
+                Some(client) => {
+                    let adapter = OkxAdapter::new(client.clone());
+                    for execution in &report.cancelled {
+                        cancel_child_orders(execution, &adapter).await;
+                    }
+                }
+                None => log::warn!("Child orders of cancelled algo executions may still rest at OKX: no OKX client to cancel them with"),
+            }
+        }
+
+        // Settle orders that were sent without an answer before the last exit.
+        // Orders found resting are cancelled: the engine only tracks the
+        // orders it placed this run.
+        let unresolved = self.intent_log.unresolved()?;
+        match &self.okx_client {
+            Some(client) if !unresolved.is_empty() => {
+                let policy = IntentRecoveryPolicy { cancel_open: true };
+                let venue = OkxIntentVenue::new(client.clone());
+                let report = recover_intents(self.intent_log.as_ref(), &venue, &policy).await?;
+                log::warn!(
+                    "Settled order intents on startup: {} recovered, {} cancelled, {} never placed, {} still unknown",
+                    report.recovered.len(),
+                    report.cancelled.len(),
+                    report.not_placed.len(),
+                    report.unresolved.len()
+                );
+            }
+            None if !unresolved.is_empty() => {
+                log::warn!("{} order intents left unresolved: no OKX client to settle them with", unresolved.len());
+            }
+            _ => {}
+        }
+        let pruned = self.intent_log.prune(chrono::Utc::now() - chrono::Duration::days(7))?;
+        if pruned > 0 {
+            log::info!("Pruned {} settled order intents", pruned);
+        }
+
+        // Judge order prices against the blended reference rather than a
+        // single venue's print, value fees charged in other assets by it and
+        // trigger locally managed scale-out exits on it
+        if let Some(mut prices) = self.reference_prices.subscribe_events() {
+            let fat_finger = self.fat_finger.clone();
+            let engine = self.execution_engine.clone();
+            let price_cache = self.price_cache.clone();
+            let runs = self.strategy_runs.clone();
+            tokio::spawn(async move {
+                while let Some(reference) = prices.recv().await {
+                    fat_finger.update_reference_price(&reference.symbol, reference.price);
+                    for (id, run) in runs.read().await.iter() {
+                        if !run.symbols.contains(&reference.symbol) {
+                            continue;
+                        }
+                        let tick = MarketDataEvent::Ticker {
+                            symbol: reference.symbol.clone(),
+                            price: reference.price,
+                            volume: rust_decimal::Decimal::ZERO,
+                            timestamp: reference.computed_at,
+                        };
+                        if run.inputs.try_send(StrategyInput::Market(tick)).is_err() {
+                            log::warn!("Strategy {} is not keeping up; dropped a {} price", id, reference.symbol.as_str());
+                        }
+                    }
+                    if let Ok(price) = ea_okx_core::types::Price::new(reference.price)
+                        && let Err(e) = price_cache.put_price(&reference.symbol, price).await
+                    {
+                        log::warn!("Failed to cache price of {}: {}", reference.symbol.as_str(), e);
+                    }
+                    if reference.symbol.quote() == engine.reporting_currency() {
+                        engine
+                            .set_fee_conversion_rate(reference.symbol.base(), reference.price)
+                            .await;
+                    }
+                    if let Err(e) = engine.on_market_price(&reference.symbol, reference.price).await {
+                        log::error!("Scale-out exit on {} failed: {}", reference.symbol.as_str(), e);
+                    }
+                }
+            });
+        }
+        // Running strategies are fed these prices: reference every symbol a
+        // strategy trades, not only the configured ones
+        let mut symbols = reference_symbols();
+        for strategy in self.strategy_service.get_strategies().await? {
+            for symbol in strategy.config.symbols {
+                if !symbols.contains(&symbol) {
+                    symbols.push(symbol);
+                }
+            }
+        }
+        if !symbols.is_empty() && !self.reference_prices.source_names().is_empty() {
+            self.reference_prices.clone().start(symbols.clone());
+        }
+
+        // Tell running strategies how their orders went, then run the
+        // strategies left active by the previous session
+        if let Some(mut updates) = self.execution_engine.subscribe_order_updates() {
+            let runs = self.strategy_runs.clone();
+            tokio::spawn(async move {
+                while let Some(update) = updates.recv().await {
+                    let strategy_id = match &update {
+                        StrategyInput::OrderFill(order) | StrategyInput::OrderReject { order, .. } => order.strategy_id,
+                        StrategyInput::Market(_) => continue,
+                    };
+                    if let Some(run) = runs.read().await.get(&strategy_id)
+                        && run.inputs.try_send(update).is_err()
+                    {
+                        log::warn!("Strategy {} is not keeping up; dropped an order update", strategy_id);
+                    }
+                }
+            });
+        }
+        for strategy in self.strategy_service.get_strategies().await? {
+            if strategy.status == StrategyStatus::Active
+                && let Err(e) = self.run_strategy(&strategy.id.to_string()).await
+            {
+                log::error!("Cannot run strategy {} ({}): {}", strategy.name, strategy.id, e);
+            }
+        }
+
+        // Drain queued strategy signals, most urgent first, respawning the
+        // processor if it dies or hangs on a signal
+        let engine = self.execution_engine.clone();
+        self.watchdog.supervise("signal_processor", std::time::Duration::from_secs(60), move |heartbeat| {
+            engine.spawn_signal_processor(heartbeat)
+        });
+
+        // External signals (webhooks, polled URLs and files) enter the same
+        // queue as strategy signals and pass the same checks
+        for (source, interval) in load_signal_sources(&self.signal_ingestor) {
+            self.signal_ingestor.clone().spawn_poller(source, interval);
+        }
+        if let Some(mut signals) = self.signal_ingestor.subscribe_signals() {
+            let engine = self.execution_engine.clone();
+            tokio::spawn(async move {
+                while let Some(signal) = signals.recv().await {
+                    let source = signal.source.clone();
+                    let submitted = match ExecutionSignal::from_external(signal) {
+                        Ok(signal) => engine.submit_signal(signal).await,
+                        Err(e) => Err(e),
+                    };
+                    if let Err(e) = submitted {
+                        log::warn!("Dropped external signal from {}: {}", source, e);
+                    }
+                }
+            });
+        }
+        if let Ok(addr) = std::env::var("EA_OKX_WEBHOOK_ADDR") {
+            match addr.parse() {
+                Ok(addr) => {
+                    let ingestor = self.signal_ingestor.clone();
+                    let notifications = self.notifications.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = ea_okx_strategy::serve_webhooks(ingestor, addr).await {
+                            log::error!("Signal webhook server stopped: {}", e);
+                            notifications
+                                .system_error("Signal webhooks stopped", e.to_string())
+                                .await;
+                        }
+                    });
+                }
+                Err(e) => log::error!("Invalid EA_OKX_WEBHOOK_ADDR '{}': {}", addr, e),
+            }
+        }
+
+        // Cancel resting orders once their good-till-date or session end comes
+        let engine = self.execution_engine.clone();
+        let expiry = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(std::time::Duration::from_secs(1));
+            loop {
+                ticker.tick().await;
+                engine.expire_due_orders(chrono::Utc::now()).await;
+            }
+        });
+        self.watchdog.watch_handle("order_expiry", expiry);
+
+        // Chart custom strategy metrics and export them to Prometheus
+        let registry = self.strategy_metrics.clone();
+        let sampler = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(std::time::Duration::from_secs(60));
+            loop {
+                ticker.tick().await;
+                registry.sample(chrono::Utc::now());
+            }
+        });
+        self.watchdog.watch_handle("strategy_metrics_sampler", sampler);
+        if let Ok(addr) = std::env::var("EA_OKX_METRICS_ADDR") {
+            match addr.parse() {
+                Ok(addr) => {
+                    let registry = self.strategy_metrics.clone();
+                    let notifications = self.notifications.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = ea_okx_strategy::serve_metrics(registry, addr).await {
+                            log::error!("Strategy metrics server stopped: {}", e);
+                            notifications
+                                .system_error("Strategy metrics export stopped", e.to_string())
+                                .await;
+                        }
+                    });
+                }
+                Err(e) => log::error!("Invalid EA_OKX_METRICS_ADDR '{}': {}", addr, e),
+            }
+        }
+
+        // Notify the user of alerts, fills and strategy state changes
+        self.notifications.clone().spawn_forwarding(
+            self.monitoring.subscribe_alerts(),
+            self.strategy_monitor.subscribe_events(),
+        );
+
+        // Snapshot the engine state periodically for disaster recovery
+        self.snapshots.clone().spawn();
+
+        // Generate the previous day's performance report each day
+        self.reporter.clone().spawn();
+
+        // Health checks for every dependency the app is configured with
+        self.monitoring
+            .register_health_checker(Box::new(DiskSpaceHealthChecker::new(data_dir())))
+            .await?;
+        if let Some(storage) = &self.market_storage {
+            self.monitoring
+                .register_health_checker(Box::new(PoolHealthChecker::new(storage.clone())))
+                .await?;
+            self.monitoring
+                .register_health_checker(Box::new(SchemaHealthChecker::new(storage.clone())))
+                .await?;
+        }
+        if let Some(redis) = &self.redis {
+            self.monitoring
+                .register_health_checker(Box::new(RedisHealthChecker::new(redis.clone())))
+                .await?;
+        }
+
+        // Drop, delete and compress stored ticks per their retention settings
+        if let Some(storage) = self.market_storage.clone() {
+            let interval = std::time::Duration::from_secs(6 * 3600);
+            let status = TickMaintenanceStatus::new();
+            self.monitoring
+                .register_health_checker(Box::new(TickMaintenanceHealthChecker::new(status.clone(), interval)))
+                .await?;
+            let maintenance = storage.clone().spawn_tick_maintenance(tick_storage_config(), interval, status);
+            self.watchdog.watch_handle("tick_maintenance", maintenance);
+
+            // Thin and expire stored order book blocks per their retention
+            let vacuum = storage.spawn_orderbook_vacuum(orderbook_storage_config(), std::time::Duration::from_secs(24 * 3600));
+            self.watchdog.watch_handle("orderbook_vacuum", vacuum);
+        }
+
+        // Record the live equity curve once a minute
+        if let Some(storage) = self.market_storage.clone() {
+            let source = LiveEquitySource::new(self.account_tracker.clone(), self.execution_engine.clone());
+            Arc::new(EquityRecorder::new(Arc::new(source), storage)).spawn();
+        }
+
+        // Refit stale volume profiles from stored hourly candles
+        if let Some(storage) = self.market_storage.clone() {
+            let estimator = self.volume_profiles.clone();
+            let symbols = reference_symbols();
+            tokio::spawn(async move {
+                let mut ticker = tokio::time::interval(std::time::Duration::from_secs(3600));
+                loop {
+                    ticker.tick().await;
+                    let now = chrono::Utc::now();
+                    for symbol in estimator.stale_symbols(&symbols, now) {
+                        let candles = match storage
+                            .query_candles(&symbol, Interval::OneHour, estimator.history_start(now), now)
+                            .await
+                        {
+                            Ok(candles) => candles,
+                            Err(e) => {
+                                log::warn!("Cannot load candles for {} volume profile: {}", symbol.as_str(), e);
+                                continue;
+                            }
+                        };
+                        let samples: Vec<_> =
+                            candles.iter().map(|c| (c.timestamp, c.volume.as_decimal())).collect();
+                        if let Err(e) = estimator.learn(&symbol, &samples, now) {
+                            log::warn!("Volume profile for {} not refreshed: {}", symbol.as_str(), e);
+                        }
+                    }
+                }
+            });
+        }
+
+        // Store OKX open interest, taker volume and long/short ratios so the
+        // positioning commands have history to serve
+        if let (Some(client), Some(storage)) = (self.okx_client.clone(), self.market_storage.clone()) {
+            let collector = PositioningCollector::new(
+                PositioningConfig::default(),
+                Arc::new(OkxPositioningSource::new(client)),
+            )
+            .with_storage(storage);
+            self.watchdog.watch_handle("positioning_collector", Arc::new(collector).start());
+        }
+
+        // Put confirmed risk limit changes into force at their effective time
+        let risk_limits = self.risk_limits.clone();
+        let daily_loss_limit = risk_limits.read().await.active_limits().daily_loss_limit;
+        self.execution_gate.daily_loss().set_limit(daily_loss_limit);
+        let gate = self.execution_gate.clone();
+        let monitoring = self.monitoring.clone();
+        let notifications = self.notifications.clone();
+        let heartbeat = self.watchdog.register("risk_limit_scheduler", std::time::Duration::from_secs(60));
+        let scheduler = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(std::time::Duration::from_secs(15));
+            loop {
+                ticker.tick().await;
+                heartbeat.beat();
+                let applied = risk_limits.write().await.apply_due(chrono::Utc::now());
+                match applied {
+                    Ok(Some(change)) => {
+                        gate.daily_loss().set_limit(change.proposed.daily_loss_limit);
+                        let mut alert = Alert::event(
+                            "risk_limits",
+                            AlertSeverity::Warning,
+                            format!("Risk limit change {} is now in force", change.id),
+                        );
+                        alert.metadata.insert("proposed_by".to_string(), change.proposed_by.clone());
+                        alert.metadata.insert(
+                            "confirmed_by".to_string(),
+                            change.confirmed_by.clone().unwrap_or_default(),
+                        );
+                        monitoring.raise_alert(alert).await;
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        log::error!("Failed to apply risk limit change: {}", e);
+                        notifications
+                            .system_error("Risk limit change failed", e.to_string())
+                            .await;
+                    }
+                }
+            }
+        });
+        self.watchdog.watch_handle("risk_limit_scheduler", scheduler);
+
+        // Surface balance divergences found by account reconciliation
+        if let Some(mut events) = self.account_tracker.subscribe_events() {
+            let notifications = self.notifications.clone();
+            tokio::spawn(async move {
+                while let Some(AccountEvent::Diverged(report)) = events.recv().await {
+                    for d in &report.divergences {
+                        log::error!(
+                            "Account balance diverged for {}: local {} vs exchange {}",
+                            d.ccy, d.local, d.exchange
+                        );
+                        notifications
+                            .system_error(
+                                "Account balance diverged",
+                                format!("{}: local {} vs exchange {}", d.ccy, d.local, d.exchange),
+                            )
+                            .await;
+                    }
+                }
+            });
+        }
+
+        // Pause strategies and cancel resting orders on suspended or delisted
+        // instruments; the tracker has already halted the symbol in the gate
+        if let Some(mut events) = self.instrument_tracker.subscribe_events() {
+            let state = self.clone();
+            tokio::spawn(async move {
+                while let Some(InstrumentEvent::StatusChanged(change)) = events.recv().await {
+                    if !change.halts_trading() {
+                        continue;
+                    }
+
+                    let symbol = &change.symbol;
+                    let paused = state.strategy_service.pause_strategies_for_symbol(symbol).await;
+                    let cancelled = match state.execution_engine.cancel_orders_for_symbol(symbol).await {
+                        Ok(cancelled) => format!("cancelled {} orders", cancelled.len()),
+                        Err(e) => e.to_string(),
+                    };
+
+                    let mut alert = Alert::event(
+                        "instrument_status",
+                        AlertSeverity::Critical,
+                        format!(
+                            "{} is {}: paused {} strategies, {}",
+                            symbol.as_str(),
+                            change.current.as_str(),
+                            paused.len(),
+                            cancelled
+                        ),
+                    );
+                    alert.metadata.insert("symbol".to_string(), symbol.as_str().to_string());
+                    alert.metadata.insert("paused_strategies".to_string(), paused.join(","));
+                    state.monitoring.raise_alert(alert).await;
+                }
+            });
+        }
+
+        // Stop opening positions while OKX is degraded and resume once it has
+        // been healthy for a few checks in a row
+        if let Some(detector) = &self.outage_detector {
+            if let Some(mut events) = detector.subscribe_events() {
+                let state = self.clone();
+                tokio::spawn(async move {
+                    while let Some(event) = events.recv().await {
+                        let alert = match event {
+                            ExchangeHealthEvent::Degraded { reasons, .. } => {
+                                let message = format!(
+                                    "OKX degraded, only reduce-only orders are sent: {}",
+                                    reasons.join("; ")
+                                );
+                                state.execution_gate.degraded().enter(reasons);
+                                Alert::event("exchange_degraded", AlertSeverity::Critical, message)
+                            }
+                            ExchangeHealthEvent::Recovered { degraded_secs, .. } => {
+                                state.execution_gate.degraded().exit();
+                                Alert::event(
+                                    "exchange_recovered",
+                                    AlertSeverity::Info,
+                                    format!("OKX recovered after {}s, normal trading resumed", degraded_secs),
+                                )
+                            }
+                        };
+                        state.monitoring.raise_alert(alert).await;
+                    }
+                });
+            }
+            detector.clone().start(std::time::Duration::from_secs(10));
+        }
+
+        // Sign OKX requests on the exchange clock and alert when the local
+        // clock drifts far from it or syncing stops working
+        if let Some(client) = &self.okx_client {
+            let sync = client.clone().spawn_clock_sync(std::time::Duration::from_secs(300));
+            self.watchdog.watch_handle("okx_clock_sync", sync);
+            Arc::new(ClockDriftMonitor::new(client.clock(), self.monitoring.clone()))
+                .start(std::time::Duration::from_secs(60));
+
+            // Keep the symbol catalog in step with OKX listings
+            let sync = self.symbol_catalog.clone().start_syncing(
+                Arc::new(OkxAdapter::new(client.clone())),
+                std::time::Duration::from_secs(6 * 3600),
+            );
+            self.watchdog.watch_handle("symbol_catalog_sync", sync);
+
+            // Halt symbols OKX suspends or delists and resume them once live
+            let polling = self
+                .instrument_tracker
+                .clone()
+                .start_polling(client.clone(), std::time::Duration::from_secs(60));
+            self.watchdog.watch_handle("instrument_status_poll", polling);
+
+            // Follow balances on the private channels and reconcile them with
+            // the REST balance snapshot, adopting the exchange's figures
+            let reconciliation = self.account_tracker.clone().start_reconciliation(client.clone());
+            self.watchdog.watch_handle("account_reconciliation", reconciliation);
+            // On the same connection, order updates fill the engine's orders
+            // and carry the fees OKX charged, and public trades and top-of-book of the traded symbols feed the
+            // fat-finger and liquidity guards
+            if let Some((credentials, testnet)) = okx_credentials() {
+                let (order_tx, mut order_rx) = tokio::sync::mpsc::unbounded_channel();
+                let engine = self.execution_engine.clone();
+                let fat_finger = self.fat_finger.clone();
+                let books = self.liquidity.books().clone();
+                tokio::spawn(async move {
+                    while let Some(event) = order_rx.recv().await {
+                        match event {
+                            WebSocketEvent::Order(update) => {
+                                let applied = match exchange_order(&update) {
+                                    Ok(report) => engine.on_exchange_order(&report).await,
+                                    Err(e) => Err(e),
+                                };
+                                if let Err(e) = applied {
+                                    log::warn!("Failed to apply update of order {}: {}", update.ord_id, e);
+                                }
+                                if let Err(e) = engine.record_exchange_fee(&update).await {
+                                    log::warn!("Failed to record fee of order {}: {}", update.ord_id, e);
+                                }
+                            }
+                            WebSocketEvent::Trade(trade) => {
+                                if let (Ok(symbol), Ok(size)) = (Symbol::new(&trade.inst_id), trade.sz.parse()) {
+                                    fat_finger.record_trade(&symbol, size);
+                                }
+                            }
+                            WebSocketEvent::OrderBook(book) => {
+                                let Some(symbol) = book.inst_id.as_deref().and_then(|id| Symbol::new(id).ok()) else {
+                                    continue;
+                                };
+                                // Every books5 push is a whole snapshot
+                                if let Err(e) = books.apply(&symbol, &book, true) {
+                                    log::warn!("Dropped {} book update: {}", symbol.as_str(), e);
+                                    continue;
+                                }
+                                if let Some(local) = books.book(&symbol)
+                                    && let (Some(bid), Some(ask)) = (local.best_bid(), local.best_ask())
+                                {
+                                    fat_finger.update_quote(&symbol, bid, ask);
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                });
+
+                let mut subscriptions = vec![SubscriptionRequest::new_instrument_type(Channel::Orders, "ANY")];
+                for symbol in &symbols {
+                    subscriptions.push(SubscriptionRequest::new(Channel::Trades, symbol.as_str()));
+                    subscriptions.push(SubscriptionRequest::new(Channel::Books5, symbol.as_str()));
+                }
+                // The feed is judged only when it carries market data; order
+                // updates alone can be quiet for hours. Its metrics go
+                // through the alert rules, which page once data stops.
+                if !symbols.is_empty() {
+                    self.monitoring
+                        .register_health_checker(Box::new(WebSocketHealthChecker::new(self.market_feed.clone(), 30)))
+                        .await?;
+                    self.monitoring.register_alert_rule(market_data_silence_rule(60)).await?;
+                    let reporter = self
+                        .monitoring
+                        .clone()
+                        .spawn_connection_reporter(self.market_feed.clone(), std::time::Duration::from_secs(10));
+                    self.watchdog.watch_handle("market_feed_reporter", reporter);
+                }
+                let tracker = self.account_tracker.clone();
+                let market_feed = self.market_feed.clone();
+                let stream = tokio::spawn(async move {
+                    let mut ws = OkxWebSocketClient::new(credentials, testnet).with_telemetry(market_feed);
+                    let result = match ws.connect().await {
+                        Ok(()) => match ws.subscribe(subscriptions).await {
+                            Ok(()) => tracker.run(&ws, Some(&order_tx)).await,
+                            Err(e) => Err(e.into()),
+                        },
+                        Err(e) => Err(e.into()),
+                    };
+                    if let Err(e) = result {
+                        log::error!("Account stream stopped: {}", e);
+                    }
+                });
+                self.watchdog.watch_handle("account_stream", stream);
+            }
+        }
+
+        // Alert when a live strategy's hit rate, expectancy or Sharpe falls
+        // below what its deployment backtest makes plausible
+        self.decay.clone().start(std::time::Duration::from_secs(3600));
+
+        // Report every sent order's stage timings as metrics, so alert rules
+        // can fire on a slow stage
+        if let Some(mut latencies) = self.execution_engine.latency_tracker().subscribe_events() {
+            let monitoring = self.monitoring.clone();
+            tokio::spawn(async move {
+                while let Some(latency) = latencies.recv().await {
+                    let mut values: Vec<(String, f64)> = latency
+                        .stages
+                        .iter()
+                        .map(|stage| (format!("order_latency_ms.{}", stage.stage), stage.ms))
+                        .collect();
+                    if let Some(ms) = latency.time_to_ack_ms {
+                        values.push(("order_time_to_ack_ms".to_string(), ms));
+                    }
+                    for (name, value) in values {
+                        if let Err(e) = monitoring.evaluate_metric(&name, value).await {
+                            log::warn!("Failed to report {}: {}", name, e);
+                        }
+                    }
+                }
+            });
+        }
+
+        // Track portfolio drawdown from the account equity and report the
+        // factor new positions are throttled by
+        let account_tracker = self.account_tracker.clone();
+        let throttle = self.drawdown_throttle.clone();
+        let monitoring = self.monitoring.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(std::time::Duration::from_secs(30));
+            loop {
+                ticker.tick().await;
+                let account = account_tracker.state();
+                let Some(updated_at) = account.updated_at else {
+                    continue;
+                };
+                let snapshot = {
+                    let mut throttle = throttle.write().await;
+                    throttle.record_equity(account.total_equity, updated_at);
+                    throttle.snapshot()
+                };
+                let drawdown = snapshot.drawdown.to_f64().unwrap_or(0.0);
+                let factor = snapshot.factor.to_f64().unwrap_or(1.0);
+                monitoring.metrics().set_drawdown_throttle(drawdown, factor);
+                for (name, value) in [("portfolio_drawdown", drawdown), ("drawdown_throttle_factor", factor)] {
+                    if let Err(e) = monitoring.evaluate_metric(name, value).await {
+                        log::warn!("Failed to report {}: {}", name, e);
+                    }
+                }
+            }
+        });
+
+        // Report feed quality scores and hand them to the gate, which holds
+        // strategies to their configured minimums
+        let data_quality = self.data_quality.clone();
+        let gate = self.execution_gate.clone();
+        let monitoring = self.monitoring.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(std::time::Duration::from_secs(30));
+            loop {
+                ticker.tick().await;
+                for quality in data_quality.symbol_qualities() {
+                    gate.feed_quality().update_score(&quality.symbol, quality.score);
+                    if let Err(e) = monitoring.report_data_quality(&quality).await {
+                        log::warn!("Failed to report data quality for {}: {}", quality.symbol.as_str(), e);
+                    }
+                }
+            }
+        });
+
+        // Start each account's trading day at UTC midnight, lifting daily
+        // loss locks, and alert whenever an account locks or unlocks
+        let gate = self.execution_gate.clone();
+        let rollover = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(std::time::Duration::from_secs(60));
+            loop {
+                ticker.tick().await;
+                gate.daily_loss().roll_over(chrono::Utc::now());
+            }
+        });
+        self.watchdog.watch_handle("daily_loss_rollover", rollover);
+
+        if let Some(mut events) = self.execution_gate.daily_loss().subscribe_events() {
+            let monitoring = self.monitoring.clone();
+            tokio::spawn(async move {
+                while let Some(event) = events.recv().await {
+                    let (mut alert, account) = match event {
+                        DailyLossEvent::Locked { account, pnl, limit, .. } => {
+                            let mut alert = Alert::event(
+                                "daily_loss_locked",
+                                AlertSeverity::Critical,
+                                format!(
+                                    "Account {} lost {} today, past its daily limit of {}; only exits are sent until the next UTC day",
+                                    account, -pnl, limit
+                                ),
+                            );
+                            alert.metadata.insert("pnl".to_string(), pnl.to_string());
+                            (alert, account)
+                        }
+                        DailyLossEvent::Unlocked { account, reason, .. } => {
+                            let why = match reason {
+                                UnlockReason::Rollover => "a new trading day started".to_string(),
+                                UnlockReason::Override { by } => format!("overridden by {}", by),
+                            };
+                            let alert = Alert::event(
+                                "daily_loss_unlocked",
+                                AlertSeverity::Info,
+                                format!("Daily loss lock on account {} lifted: {}", account, why),
+                            );
+                            (alert, account)
+                        }
+                    };
+                    alert.metadata.insert("account".to_string(), account);
+                    monitoring.raise_alert(alert).await;
+                }
+            });
+        }
+
+        // Pause strategies the gate stopped for repeatedly exceeding their
+        // order, cancel or signal quotas
+        if let Some(mut breaches) = self.execution_gate.quotas().subscribe_breaches() {
+            let state = self.clone();
+            tokio::spawn(async move {
+                while let Some(breach) = breaches.recv().await {
+                    if !breach.paused {
+                        continue;
+                    }
+
+                    let strategy_id = breach.strategy_id.to_string();
+                    if let Err(e) = state.strategy_service.pause_strategy(&strategy_id).await {
+                        log::warn!("Failed to pause strategy {}: {}", strategy_id, e);
+                    }
+
+                    let mut alert = Alert::event("strategy_quota", AlertSeverity::Critical, breach.describe());
+                    alert.metadata.insert("strategy_id".to_string(), strategy_id);
+                    state.monitoring.raise_alert(alert).await;
+                }
+            });
+        }
+
+        // Alert on, and restart, background tasks that stop beating. Account
+        // reconciliation and the OKX WebSocket processors expose heartbeats
+        // too, but the desktop app does not run them yet
+        self.watchdog.clone().start(std::time::Duration::from_secs(10));
+
         Ok(())
     }
 }
 
+impl AppState {
+    /// Replaces the runtime state with a stored snapshot, or the newest one
+    /// that still loads when `id` is `None`
+    ///
+    /// Orders, positions and trades replace the execution engine's, after the
+    /// orders still open in the snapshot are reconciled with OKX (without a
+    /// connection they are marked cancelled); strategy allocations are
+    /// restored and implementation states are loaded into the strategies
+    /// that are running; algo executions are written back to their store,
+    /// where startup recovery decides whether they resume.
+    pub async fn restore_from_snapshot(
+        &self,
+        id: Option<Uuid>,
+    ) -> Result<SnapshotInfo, Box<dyn std::error::Error + Send + Sync>> {
+        let store = self.snapshots.store();
+        let mut snapshot = match id {
+            Some(id) => store.load(id)?.ok_or_else(|| format!("Snapshot {} not found", id))?,
+            None => store.latest_valid()?.ok_or("No usable snapshot found")?,
+        };
+
+        let exchange = self.okx_client.as_ref().map(|client| OkxAdapter::new(client.clone()));
+        let reconciled = reconcile_orders(
+            &mut snapshot.orders,
+            exchange.as_ref().map(|adapter| adapter as &dyn ea_okx_core::ExchangeAdapter),
+        )
+        .await?;
+        if reconciled > 0 {
+            log::warn!("{} snapshot orders differed from the exchange and were reconciled", reconciled);
+        }
+
+        self.execution_engine
+            .restore_state(snapshot.orders.clone(), snapshot.positions.clone(), snapshot.trades.clone())
+            .await;
+        let strategies = self.strategy_service.restore_strategies(&snapshot.strategies).await?;
+        for execution in &snapshot.algo_executions {
+            self.algo_store.save(execution)?;
+        }
+
+        log::info!(
+            "Restored {} orders, {} positions, {} strategies from snapshot {}",
+            snapshot.orders.len(),
+            snapshot.positions.len(),
+            strategies,
+            snapshot.id
+        );
+        Ok(store
+            .list()?
+            .into_iter()
+            .find(|info| info.id == snapshot.id)
+            .unwrap_or_else(|| snapshot.info(0)))
+    }
+}
+
+impl AppState {
+    /// Runs the implementation of strategy `id`, feeding it reference prices
+    /// of its symbols and updates of its orders and queueing its signals for
+    /// execution
+    ///
+    /// Returns false for strategy types without a built-in implementation:
+    /// only their status is managed. A strategy already running is left
+    /// alone.
+    pub async fn run_strategy(&self, id: &str) -> ea_okx_core::Result<bool> {
+        let strategy = self.strategy_service.get_strategy(id).await?;
+        let Some(factory) = builtin_factory(&strategy.strategy_type) else {
+            return Ok(false);
+        };
+        let Some(default_symbol) = strategy.config.symbols.first().cloned() else {
+            return Err(ea_okx_core::Error::ValidationError(format!("Strategy {} has no symbols", id)));
+        };
+
+        let mut runs = self.strategy_runs.write().await;
+        if runs.get(&strategy.id).is_some_and(|run| !run.inputs.is_closed()) {
+            return Ok(true);
+        }
+
+        let config = ea_okx_strategy::StrategyConfig {
+            strategy_id: strategy.id,
+            name: strategy.name.clone(),
+            version: strategy.version.clone(),
+            symbols: strategy.config.symbols.iter().map(|s| s.as_str().to_string()).collect(),
+            parameters: strategy
+                .config
+                .parameters
+                .as_object()
+                .map(|parameters| parameters.clone().into_iter().collect())
+                .unwrap_or_default(),
+            risk_limits: ea_okx_strategy::traits::RiskLimits {
+                max_position_size: strategy.config.max_position_size,
+                max_leverage: strategy.config.max_leverage,
+                stop_loss_pct: risk_limit(&strategy.config.risk_limits, "stop_loss_pct")
+                    .unwrap_or(rust_decimal::Decimal::new(2, 2)),
+                take_profit_pct: risk_limit(&strategy.config.risk_limits, "take_profit_pct"),
+            },
+        };
+        let (inputs, input_rx) = tokio::sync::mpsc::channel(STRATEGY_INPUT_CAPACITY);
+        let (signal_tx, mut signals) = tokio::sync::mpsc::unbounded_channel();
+        let supervisor = StrategySupervisor::new(config, factory);
+        // Hot-tunable parameter changes reach the instance it runs
+        self.strategy_service.attach_instance(id, supervisor.live()).await;
+        self.supervise_strategy(supervisor, OrderCleanup::CancelOpenOrders, input_rx, signal_tx);
+
+        let engine = self.execution_engine.clone();
+        let strategy_id = strategy.id;
+        tokio::spawn(async move {
+            while let Some(signal) = signals.recv().await {
+                let submitted = match ExecutionSignal::from_strategy(strategy_id, default_symbol.clone(), signal) {
+                    Ok(signal) => engine.submit_signal(signal).await,
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = submitted {
+                    log::warn!("Dropped signal of strategy {}: {}", strategy_id, e);
+                }
+            }
+        });
+
+        runs.insert(strategy.id, StrategyRun { symbols: strategy.config.symbols, inputs });
+        log::info!("Running strategy {} ({})", strategy.name, id);
+        Ok(true)
+    }
+
+    /// Shuts down the running implementation of strategy `id`, if any
+    pub async fn halt_strategy(&self, id: &str) -> bool {
+        let Ok(strategy_id) = Uuid::parse_str(id) else {
+            return false;
+        };
+        self.strategy_service.detach_instance(id).await;
+        // Closing its inputs makes the supervisor shut the strategy down
+        self.strategy_runs.write().await.remove(&strategy_id).is_some()
+    }
+
+    /// Runs a strategy under a supervisor that cancels its orders, marks it
+    /// failed and restarts it with backoff when it panics
+    ///
+    /// Signals go to `signals` for the caller to turn into execution signals.
+    pub fn supervise_strategy(
+        &self,
+        supervisor: StrategySupervisor,
+        cleanup: OrderCleanup,
+        inputs: tokio::sync::mpsc::Receiver<StrategyInput>,
+        signals: tokio::sync::mpsc::UnboundedSender<ea_okx_strategy::Signal>,
+    ) -> tokio::task::JoinHandle<SupervisorExit> {
+        let supervisor = supervisor
+            .with_order_cleanup(cleanup, self.execution_engine.clone())
+            .with_metrics(self.strategy_metrics.clone());
+        if let Some(mut events) = supervisor.subscribe_events() {
+            let strategies = self.strategy_service.clone();
+            let monitoring = self.monitoring.clone();
+            let notifications = self.notifications.clone();
+            tokio::spawn(async move {
+                while let Some(event) = events.recv().await {
+                    match event {
+                        SupervisorEvent::Failed { failure, orders_cancelled, restarts } => {
+                            let id = failure.strategy_id.to_string();
+                            if let Err(e) = strategies.mark_failed(&id, &failure.message).await {
+                                log::warn!("Cannot mark strategy {} failed: {}", id, e);
+                            }
+                            let mut alert = Alert::event(
+                                "strategy_failed",
+                                AlertSeverity::Critical,
+                                format!(
+                                    "Strategy {} {}; cancelled {} orders",
+                                    id, failure.message, orders_cancelled
+                                ),
+                            );
+                            alert.metadata.insert("strategy_id".to_string(), id);
+                            alert.metadata.insert("restarts".to_string(), restarts.to_string());
+                            if let Some(backtrace) = failure.backtrace {
+                                alert.metadata.insert("backtrace".to_string(), backtrace);
+                            }
+                            monitoring.raise_alert(alert).await;
+                        }
+                        SupervisorEvent::Restarted { strategy_id, .. } => {
+                            let id = strategy_id.to_string();
+                            if let Err(e) = strategies.mark_recovered(&id).await {
+                                log::warn!("Cannot mark strategy {} recovered: {}", id, e);
+                            }
+                        }
+                        SupervisorEvent::GaveUp { strategy_id, restarts } => {
+                            notifications
+                                .system_error(
+                                    "Strategy stopped restarting",
+                                    format!("Strategy {} failed again after {} restarts", strategy_id, restarts),
+                                )
+                                .await;
+                        }
+                    }
+                }
+            });
+        }
+        supervisor.spawn(inputs, signals)
+    }
+}
+
 impl Default for AppState {
     fn default() -> Self {
         Self::new()
@@ -543,6 +543,23 @@ export class StrategyService {
     }
   }
 
+  /**
+   * Search the OKX symbol catalog, e.g. searchSymbols('SOL', 'swap')
+   */
+  async searchSymbols(query: string, kind?: string, limit = 50): Promise<string[]> {
+    try {
+      const instruments = await invoke<Array<{ symbol: string }>>('search_symbols', {
+        query,
+        kind,
+        limit
+      })
+      return instruments.map(i => i.symbol)
+    } catch (error) {
+      console.error('Failed to search symbols:', error)
+      return []
+    }
+  }
+
   // Private helper methods
 
   private async validateStrategyData(formData: StrategyFormData): Promise<StrategyApiResponse> {
@@ -0,0 +1,98 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { OrderSide } from "./OrderSide";
+import type { OrderStatus } from "./OrderStatus";
+import type { OrderType } from "./OrderType";
+import type { Price } from "./Price";
+import type { Quantity } from "./Quantity";
+import type { Symbol } from "./Symbol";
+
+/**
+ * Order entity
+ */
+export type Order = { 
+/**
+ * Internal order ID
+ */
+id: string, 
+/**
+ * OKX order ID (after submission)
+ */
+okx_order_id: string | null, 
+/**
+ * Client-assigned order ID, encoding the strategy (see [`attribution`])
+ *
+ * [`attribution`]: crate::models::attribution
+ */
+client_order_id: string, 
+/**
+ * OKX order tag identifying the originating signal
+ */
+tag: string | null, 
+/**
+ * Strategy ID that created this order
+ */
+strategy_id: string, 
+/**
+ * Trading symbol
+ */
+symbol: Symbol, 
+/**
+ * Order side
+ */
+side: OrderSide, 
+/**
+ * Order type
+ */
+order_type: OrderType, 
+/**
+ * Order quantity
+ */
+quantity: Quantity, 
+/**
+ * Limit price (None for market orders)
+ */
+price: Price | null, 
+/**
+ * Only reduce an open position, never open or flip one
+ */
+reduce_only: boolean, 
+/**
+ * Cancel the order if it is still resting at this time (good-till-date)
+ */
+expires_at: string | null, 
+/**
+ * Average fill price
+ */
+avg_fill_price: Price | null, 
+/**
+ * Filled quantity
+ */
+filled_quantity: Quantity, 
+/**
+ * Order status
+ */
+status: OrderStatus, 
+/**
+ * Rejection reason (if rejected)
+ */
+reject_reason: string | null, 
+/**
+ * Order creation time
+ */
+created_at: string, 
+/**
+ * Submission time
+ */
+submitted_at: string | null, 
+/**
+ * First fill time
+ */
+first_fill_at: string | null, 
+/**
+ * Completion time
+ */
+completed_at: string | null, 
+/**
+ * Latency from submission to first fill (milliseconds)
+ */
+latency_ms: number | null, };
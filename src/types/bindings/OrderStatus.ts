@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Order status in lifecycle
+ */
+export type OrderStatus = "created" | "submitted" | "partial" | "filled" | "cancelled" | "rejected" | "failed";
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Order type
+ */
+export type OrderType = "market" | "limit" | "post_only" | "ioc" | "fok" | "stop_loss" | "take_profit" | "trailing_stop" | "iceberg";
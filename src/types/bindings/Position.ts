@@ -0,0 +1,66 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PositionSide } from "./PositionSide";
+import type { Price } from "./Price";
+import type { Quantity } from "./Quantity";
+import type { Symbol } from "./Symbol";
+
+/**
+ * Position entity
+ */
+export type Position = { 
+/**
+ * Position ID
+ */
+id: string, 
+/**
+ * Strategy ID
+ */
+strategy_id: string, 
+/**
+ * Trading symbol
+ */
+symbol: Symbol, 
+/**
+ * Position side
+ */
+side: PositionSide, 
+/**
+ * Position quantity
+ */
+quantity: Quantity, 
+/**
+ * Average entry price
+ */
+avg_entry_price: Price, 
+/**
+ * Current market price
+ */
+current_price: Price, 
+/**
+ * Unrealized profit/loss
+ */
+unrealized_pnl: string, 
+/**
+ * Realized profit/loss
+ */
+realized_pnl: string, 
+/**
+ * Margin requirement
+ */
+margin: string | null, 
+/**
+ * Leverage ratio
+ */
+leverage: string | null, 
+/**
+ * Liquidation price
+ */
+liquidation_price: Price | null, 
+/**
+ * Position open time
+ */
+opened_at: string, 
+/**
+ * Last update time
+ */
+last_updated: string, };
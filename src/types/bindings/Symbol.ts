@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * OKX instrument ID: a spot pair (`BTC-USDT`), perpetual swap
+ * (`BTC-USDT-SWAP`), dated future (`BTC-USD-240628`) or option
+ * (`BTC-USD-240628-60000-C`)
+ */
+export type Symbol = string;
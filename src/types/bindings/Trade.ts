@@ -0,0 +1,71 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { OrderSide } from "./OrderSide";
+import type { OrderType } from "./OrderType";
+import type { Price } from "./Price";
+import type { Quantity } from "./Quantity";
+import type { Symbol } from "./Symbol";
+
+/**
+ * Trade record - represents a completed trade execution
+ */
+export type Trade = { 
+/**
+ * Trade ID
+ */
+id: string, 
+/**
+ * OKX order ID
+ */
+okx_order_id: string | null, 
+/**
+ * Client order ID
+ */
+client_order_id: string, 
+/**
+ * Strategy ID
+ */
+strategy_id: string, 
+/**
+ * Trading symbol
+ */
+symbol: Symbol, 
+/**
+ * Order side
+ */
+side: OrderSide, 
+/**
+ * Order type
+ */
+order_type: OrderType, 
+/**
+ * Executed quantity
+ */
+quantity: Quantity, 
+/**
+ * Execution price
+ */
+price: Price, 
+/**
+ * Trading commission; negative for a maker rebate
+ */
+commission: string, 
+/**
+ * Commission asset
+ */
+commission_asset: string, 
+/**
+ * Realized profit/loss
+ */
+realized_pnl: string | null, 
+/**
+ * Slippage in basis points
+ */
+slippage_bps: number | null, 
+/**
+ * Execution timestamp
+ */
+executed_at: string, 
+/**
+ * Latency from signal to execution (milliseconds)
+ */
+latency_ms: number | null, };